@@ -72,6 +72,9 @@ pub struct ClientState {
 	pub latest_relay_hash: Hash,
 	/// para_id of associated parachain
 	pub para_id: u32,
+	/// Whether the client follows a standalone chain, whose own headers are finalized, rather
+	/// than a parachain of the relay chain. The revision number of its heights is `para_id`.
+	pub standalone: bool,
 }
 
 /// Holds relavant parachain proofs for both header and timestamp extrinsic.
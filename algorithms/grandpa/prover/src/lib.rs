@@ -66,6 +66,9 @@ pub struct GrandpaProver<T: Config> {
 	pub para_id: u32,
 	/// Delay between rpc calls to the RPC
 	pub rpc_call_delay: Duration,
+	/// Whether the chain is a standalone chain finalized by its own GRANDPA authorities, in which
+	/// case the relay chain and parachain clients are both clients of that chain.
+	pub standalone: bool,
 }
 
 // We redefine these here because we want the header to be bounded by subxt::config::Header in the
@@ -98,6 +101,7 @@ impl<T: Config> Clone for GrandpaProver<T> {
 			para_ws_client: self.para_ws_client.clone(),
 			para_id: self.para_id,
 			rpc_call_delay: self.rpc_call_delay,
+			standalone: self.standalone,
 		}
 	}
 }
@@ -128,6 +132,28 @@ where
 			para_client,
 			para_id,
 			rpc_call_delay,
+			standalone: false,
+		})
+	}
+
+	/// Initializes the clients of a standalone chain given its ws url. `revision_number` is the
+	/// revision of the chain heights, which the client state keeps as its `para_id`.
+	pub async fn new_standalone(
+		ws_url: &str,
+		revision_number: u32,
+		rpc_call_delay: Duration,
+	) -> Result<Self, anyhow::Error> {
+		let ws_client = Arc::new(WsClientBuilder::default().build(ws_url).await?);
+		let client = OnlineClient::<T>::from_rpc_client(ws_client.clone()).await?;
+
+		Ok(Self {
+			relay_ws_client: ws_client.clone(),
+			relay_client: client.clone(),
+			para_ws_client: ws_client,
+			para_client: client,
+			para_id: revision_number,
+			rpc_call_delay,
+			standalone: true,
 		})
 	}
 
@@ -136,9 +162,21 @@ where
 	where
 		<T as subxt::Config>::Header: Decode,
 	{
-		use sp_consensus_grandpa::AuthorityList;
 		let latest_relay_hash = self.relay_client.rpc().finalized_head().await.unwrap();
 		log::debug!(target: "hyperspace", "Latest relay hash: {:?}", latest_relay_hash);
+		self.initialize_client_state_at(latest_relay_hash).await
+	}
+
+	/// Construct the client state whose latest relay chain block is the finalized block
+	/// `latest_relay_hash`, with the authority set that was active at that block.
+	pub async fn initialize_client_state_at(
+		&self,
+		latest_relay_hash: T::Hash,
+	) -> Result<ClientState, anyhow::Error>
+	where
+		<T as subxt::Config>::Header: Decode,
+	{
+		use sp_consensus_grandpa::AuthorityList;
 		let header = self
 			.relay_client
 			.rpc()
@@ -199,10 +237,74 @@ where
 			para_id: self.para_id,
 			// we'll set this below
 			latest_para_height: u32::from(finalized_para_header.number()),
+			standalone: self.standalone,
 		})
 	}
 
+	/// Returns the hash of the first finalized relay chain block in which the parachain header
+	/// `para_height` was included. The state of the relay chain blocks is searched, so they must
+	/// not have been pruned by the node.
+	pub async fn query_relay_hash_for_para_height(
+		&self,
+		para_height: u32,
+	) -> Result<T::Hash, anyhow::Error>
+	where
+		<T as subxt::Config>::Header: Decode,
+	{
+		let finalized_hash = self.relay_client.rpc().finalized_head().await?;
+		let finalized_header = self
+			.relay_client
+			.rpc()
+			.header(Some(finalized_hash))
+			.await?
+			.ok_or_else(|| anyhow!("Header not found for hash: {finalized_hash:?}"))?;
+		if self.standalone {
+			// standalone chains finalize their own headers
+			if u32::from(finalized_header.number()) < para_height {
+				Err(anyhow!("Header {para_height} isn't finalized yet"))?
+			}
+			return self
+				.relay_client
+				.rpc()
+				.block_hash(Some(para_height.into()))
+				.await?
+				.ok_or_else(|| anyhow!("Block hash not found for number: {para_height}"))
+		}
+		// the included parachain height never decreases, so the first relay chain block that
+		// includes a header at or above `para_height` is found by bisection.
+		let (mut low, mut high) = (1u32, u32::from(finalized_header.number()));
+		if u32::from(self.query_latest_finalized_parachain_header(high).await?.number()) <
+			para_height
+		{
+			Err(anyhow!("Parachain header {para_height} isn't finalized yet"))?
+		}
+		while low < high {
+			let mid = low + (high - low) / 2;
+			let header = self.query_latest_finalized_parachain_header(mid).await?;
+			if u32::from(header.number()) < para_height {
+				low = mid + 1;
+			} else {
+				high = mid;
+			}
+		}
+
+		let header = self.query_latest_finalized_parachain_header(low).await?;
+		if u32::from(header.number()) != para_height {
+			Err(anyhow!(
+				"Parachain header {para_height} was never included in the relay chain, the next \
+				 included header is {}",
+				u32::from(header.number())
+			))?
+		}
+		self.relay_client
+			.rpc()
+			.block_hash(Some(low.into()))
+			.await?
+			.ok_or_else(|| anyhow!("Block hash not found for number: {low}"))
+	}
+
 	/// Returns the latest finalized parachain header at the given finalized relay chain height.
+	/// For standalone chains, this is the finalized header at that height.
 	pub async fn query_latest_finalized_parachain_header(
 		&self,
 		latest_finalized_height: u32,
@@ -216,6 +318,14 @@ where
 			.block_hash(Some(latest_finalized_height.into()))
 			.await?
 			.ok_or_else(|| anyhow!("Block hash not found for number: {latest_finalized_height}"))?;
+		if self.standalone {
+			return self
+				.relay_client
+				.rpc()
+				.header(Some(latest_finalized_hash))
+				.await?
+				.ok_or_else(|| anyhow!("Header not found for hash: {latest_finalized_hash:?}"))
+		}
 		let key = T::Storage::paras_heads(self.para_id);
 		let header = <T::Storage as RuntimeStorage>::HeadData::from_inner(
 			self.relay_client
@@ -332,6 +442,37 @@ where
 			}
 		}
 
+		if self.standalone {
+			// the finalized headers are the ones of the chain itself, so only the timestamp
+			// extrinsics of the requested headers are proven.
+			let mut latest_para_height = 0u32;
+			let mut parachain_headers_with_proof =
+				BTreeMap::<H256, ParachainHeaderProofs>::default();
+			for header in &unknown_headers {
+				let number = u32::from(header.number());
+				if number == 0 || !header_numbers.iter().any(|n| u32::from(n.clone()) == number) {
+					continue
+				}
+				let hash = T::Hash::from(header.hash());
+				let TimeStampExtWithProof { ext: extrinsic, proof: extrinsic_proof } =
+					fetch_timestamp_extrinsic_with_proof(&self.para_client, Some(hash))
+						.await
+						.map_err(|err| anyhow!("Error fetching timestamp with proof: {err:?}"))?;
+				let proofs =
+					ParachainHeaderProofs { state_proof: vec![], extrinsic, extrinsic_proof };
+				parachain_headers_with_proof.insert(H256::from(hash), proofs);
+				latest_para_height = latest_para_height.max(number);
+			}
+			unknown_headers.sort_by_key(|header| header.number());
+			finality_proof.unknown_headers = unknown_headers;
+
+			return Ok(ParachainHeadersWithFinalityProof {
+				finality_proof,
+				parachain_headers: parachain_headers_with_proof,
+				latest_para_height,
+			})
+		}
+
 		// we are interested only in the blocks where our parachain header changes.
 		let para_storage_key = parachain_header_storage_key(self.para_id);
 		let keys = vec![para_storage_key.as_ref()];
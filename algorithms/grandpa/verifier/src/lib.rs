@@ -112,19 +112,24 @@ where
 			headers.header(&hash).expect("Headers have been checked by AncestryChain; qed");
 
 		let ParachainHeaderProofs { extrinsic_proof, extrinsic, state_proof } = proofs;
-		let proof = StorageProof::new(state_proof);
-		let key = parachain_header_storage_key(client_state.para_id);
-		// verify patricia-merkle state proofs
-		let header = state_machine::read_proof_check::<Host::BlakeTwo256, _>(
-			relay_chain_header.state_root(),
-			proof,
-			&[key.as_ref()],
-		)
-		.map_err(|err| anyhow!("error verifying parachain header state proof: {err}"))?
-		.remove(key.as_ref())
-		.flatten()
-		.ok_or_else(|| anyhow!("Invalid proof, parachain header not found"))?;
-		let parachain_header = H::decode(&mut &header[..])?;
+		let parachain_header = if client_state.standalone {
+			// the headers of standalone chains are finalized by the justification itself
+			relay_chain_header.clone()
+		} else {
+			let proof = StorageProof::new(state_proof);
+			let key = parachain_header_storage_key(client_state.para_id);
+			// verify patricia-merkle state proofs
+			let header = state_machine::read_proof_check::<Host::BlakeTwo256, _>(
+				relay_chain_header.state_root(),
+				proof,
+				&[key.as_ref()],
+			)
+			.map_err(|err| anyhow!("error verifying parachain header state proof: {err}"))?
+			.remove(key.as_ref())
+			.flatten()
+			.ok_or_else(|| anyhow!("Invalid proof, parachain header not found"))?;
+			H::decode(&mut &header[..])?
+		};
 		para_heights.push(parachain_header.number().clone().into());
 		// Timestamp extrinsic should be the first inherent and hence the first extrinsic
 		// https://github.com/paritytech/substrate/blob/d602397a0bbb24b5d627795b797259a44a5e29e9/primitives/trie/src/lib.rs#L99-L101
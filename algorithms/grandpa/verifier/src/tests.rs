@@ -14,7 +14,8 @@
 // limitations under the License.
 
 use crate::verify_parachain_headers_with_grandpa_finality_proof;
-use codec::{Decode, Encode};
+use codec::{Compact, Decode, Encode};
+use finality_grandpa::{Precommit, SignedPrecommit};
 use futures::StreamExt;
 use grandpa_prover::{
 	beefy_prover::helpers::unsafe_arc_cast, host_functions::HostFunctionsProvider, GrandpaProver,
@@ -22,11 +23,14 @@ use grandpa_prover::{
 use hyperspace_core::substrate::DefaultConfig as PolkadotConfig;
 use polkadot_core_primitives::Header;
 use primitives::{
-	justification::GrandpaJustification, FinalityProof, ParachainHeadersWithFinalityProof,
+	justification::GrandpaJustification, ClientState, Commit, FinalityProof, ParachainHeaderProofs,
+	ParachainHeadersWithFinalityProof,
 };
 use serde::{Deserialize, Serialize};
-use sp_core::H256;
-use std::time::Duration;
+use sp_core::{ed25519, Pair, H256};
+use sp_runtime::traits::Header as _;
+use sp_trie::{generate_trie_proof, LayoutV0, MemoryDB, TrieDBMutBuilder, TrieMut};
+use std::{collections::BTreeMap, time::Duration};
 use subxt::{
 	config::substrate::{BlakeTwo256, SubstrateHeader},
 	rpc_params,
@@ -151,3 +155,163 @@ async fn follow_grandpa_justifications() {
 		println!("========= Successfully verified grandpa justification =========");
 	}
 }
+
+const ROUND: u64 = 1;
+const SET_ID: u64 = 1;
+const REVISION_NUMBER: u32 = 1;
+
+/// Builds the client state of a standalone chain finalized by `authorities`, and a proof of the
+/// finality of the two standalone headers that follow its latest header, which include the
+/// timestamp extrinsic.
+fn standalone_finality_proof(
+	authorities: &[ed25519::Pair],
+) -> (ClientState, ParachainHeadersWithFinalityProof<Header>) {
+	let mut db = MemoryDB::<sp_runtime::traits::BlakeTwo256>::default();
+	let extrinsic = [vec![0, 0], (1u8, 0u8, Compact(1_650_894_363_000u64)).encode()].concat();
+	let key = Compact(0u64).encode();
+	let extrinsics_root = {
+		let mut root = Default::default();
+		let mut trie =
+			TrieDBMutBuilder::<LayoutV0<sp_runtime::traits::BlakeTwo256>>::new(&mut db, &mut root)
+				.build();
+		trie.insert(&key, &extrinsic).unwrap();
+		*trie.root()
+	};
+	let extrinsic_proof =
+		generate_trie_proof::<LayoutV0<sp_runtime::traits::BlakeTwo256>, _, _, _>(
+			&db,
+			extrinsics_root,
+			vec![&key],
+		)
+		.unwrap();
+
+	let mut headers: Vec<Header> = vec![];
+	for number in 2..=3 {
+		headers.push(Header {
+			parent_hash: headers.last().map(|header| header.hash()).unwrap_or_default(),
+			number,
+			state_root: H256::repeat_byte(number as u8),
+			extrinsics_root,
+			digest: Default::default(),
+		});
+	}
+	let target = headers.last().unwrap().clone();
+
+	let precommit = Precommit { target_hash: target.hash(), target_number: target.number };
+	let message = finality_grandpa::Message::Precommit(precommit.clone());
+	let payload = sp_consensus_grandpa::localized_payload(ROUND, SET_ID, &message);
+	let precommits = authorities
+		.iter()
+		.map(|pair| SignedPrecommit {
+			precommit: precommit.clone(),
+			signature: pair.sign(&payload).into(),
+			id: pair.public().into(),
+		})
+		.collect();
+	let justification = GrandpaJustification::<Header> {
+		round: ROUND,
+		commit: Commit::<Header> {
+			target_hash: target.hash(),
+			target_number: target.number,
+			precommits,
+		},
+		votes_ancestries: vec![],
+	};
+
+	let parachain_headers = headers
+		.iter()
+		.map(|header| {
+			let proofs = ParachainHeaderProofs {
+				state_proof: vec![],
+				extrinsic: extrinsic.clone(),
+				extrinsic_proof: extrinsic_proof.clone(),
+			};
+			(header.hash(), proofs)
+		})
+		.collect::<BTreeMap<_, _>>();
+	let client_state = ClientState {
+		current_authorities: authorities.iter().map(|pair| (pair.public().into(), 1)).collect(),
+		current_set_id: SET_ID,
+		latest_relay_height: 1,
+		latest_para_height: 1,
+		latest_relay_hash: H256::default(),
+		para_id: REVISION_NUMBER,
+		standalone: true,
+	};
+	let proof = ParachainHeadersWithFinalityProof {
+		finality_proof: FinalityProof {
+			block: target.hash(),
+			justification: justification.encode(),
+			unknown_headers: headers,
+		},
+		parachain_headers,
+		latest_para_height: target.number,
+	};
+	(client_state, proof)
+}
+
+fn authorities() -> Vec<ed25519::Pair> {
+	["//Alice", "//Bob", "//Charlie"]
+		.iter()
+		.map(|seed| ed25519::Pair::from_string(seed, None).unwrap())
+		.collect()
+}
+
+#[test]
+fn standalone_headers_are_finalized_by_their_justification() {
+	let (client_state, proof) = standalone_finality_proof(&authorities());
+	let target = proof.finality_proof.block;
+
+	let client_state = verify_parachain_headers_with_grandpa_finality_proof::<
+		Header,
+		HostFunctionsProvider,
+	>(client_state, proof)
+	.unwrap();
+
+	assert_eq!(client_state.latest_relay_hash, target);
+	assert_eq!(client_state.latest_relay_height, 3);
+	assert_eq!(client_state.latest_para_height, 3);
+	assert_eq!(client_state.current_set_id, SET_ID);
+}
+
+#[test]
+fn standalone_headers_with_a_tampered_extrinsic_are_rejected() {
+	let (client_state, mut proof) = standalone_finality_proof(&authorities());
+	let proofs = proof.parachain_headers.values_mut().next().unwrap();
+	*proofs.extrinsic.last_mut().unwrap() ^= 1;
+
+	let result = verify_parachain_headers_with_grandpa_finality_proof::<
+		Header,
+		HostFunctionsProvider,
+	>(client_state, proof);
+
+	assert!(result.is_err());
+}
+
+#[test]
+fn standalone_headers_with_a_tampered_header_are_rejected() {
+	let (client_state, mut proof) = standalone_finality_proof(&authorities());
+	// the parent of the finalized header no longer is the header the authorities voted on
+	proof.finality_proof.unknown_headers[0].state_root = H256::repeat_byte(0xff);
+
+	let result = verify_parachain_headers_with_grandpa_finality_proof::<
+		Header,
+		HostFunctionsProvider,
+	>(client_state, proof);
+
+	assert!(result.is_err());
+}
+
+#[test]
+fn standalone_headers_signed_by_unknown_authorities_are_rejected() {
+	let (client_state, _) = standalone_finality_proof(&authorities());
+	let (_, proof) =
+		standalone_finality_proof(&[ed25519::Pair::from_string("//Eve", None).unwrap()]);
+
+	let result = verify_parachain_headers_with_grandpa_finality_proof::<
+		Header,
+		HostFunctionsProvider,
+	>(client_state, proof);
+
+	assert!(result.is_err());
+}
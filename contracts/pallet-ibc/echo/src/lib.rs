@@ -0,0 +1,327 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Example of an IBC application implemented by a pallet of the runtime.
+//!
+//! The ports of the application are bound at runtime by the admin origin of `pallet-ibc`, with
+//! `pallet_ibc::Pallet::bind_port` and the module id [`MODULE_ID`], and it echoes the data of the
+//! packets it receives as their acknowledgement. Its [`IbcModule`] and [`WeightHandler`] have to be
+//! routed to by the `ModuleRouter` of the runtime under [`MODULE_ID`].
+
+extern crate alloc;
+
+use alloc::{format, string::ToString};
+use core::{fmt::Formatter, write};
+use frame_support::{
+	dispatch::{DispatchResult, Weight},
+	traits::Get,
+};
+use ibc::{
+	core::{
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty, Order, State},
+			error::Error as Ics04Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+use ibc_primitives::{
+	channel_id_from_bytes, connection_id_from_bytes, port_id_from_bytes, CallbackWeight,
+	HandlerMessage, IbcHandler, Timeout,
+};
+use sp_std::{marker::PhantomData, prelude::*};
+// Re-export pallet items so that they can be accessed from the crate namespace.
+pub use pallet::*;
+
+pub const MODULE_ID: &str = "PalletIbcEcho";
+pub const VERSION: &str = "echo-1";
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// ibc subsystem
+		type IbcHandler: ibc_primitives::IbcHandler<<Self as frame_system::Config>::AccountId>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Opens an unordered channel on a bound port with the `counterparty_port_id` of the echo
+		/// application of the chain at the other end of the connection.
+		// the port binding, connection, client and channel counter are read, and the channel end,
+		// its sequences, the connection channels and the counter written
+		#[pallet::call_index(1)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(5, 6))]
+		pub fn open_channel(
+			origin: OriginFor<T>,
+			port_id: Vec<u8>,
+			connection_id: Vec<u8>,
+			counterparty_port_id: Vec<u8>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T>::InvalidParams)?;
+			let connection_id =
+				connection_id_from_bytes(connection_id).map_err(|_| Error::<T>::InvalidParams)?;
+			let counterparty_port_id =
+				port_id_from_bytes(counterparty_port_id).map_err(|_| Error::<T>::InvalidParams)?;
+			let channel_end = ChannelEnd::new(
+				State::Init,
+				Order::Unordered,
+				Counterparty::new(counterparty_port_id, None),
+				vec![connection_id],
+				Version::new(VERSION.to_string()),
+			);
+			T::IbcHandler::handle_message(HandlerMessage::OpenChannel { port_id, channel_end })
+				.map_err(|e| {
+					log::trace!(target: "pallet_ibc_echo", "[open_channel] error: {:?}", e);
+					Error::<T>::ChannelInitError
+				})?;
+			Ok(())
+		}
+
+		/// Sends `data` to the echo application at the other end of the channel.
+		// the channel, its connection, client and consensus state and next send sequence are read,
+		// and the next send sequence, packet commitment and packet written
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(7, 3))]
+		pub fn send_echo(
+			origin: OriginFor<T>,
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			data: Vec<u8>,
+			timeout: Timeout,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T>::InvalidParams)?;
+			let channel_id =
+				channel_id_from_bytes(channel_id).map_err(|_| Error::<T>::InvalidParams)?;
+			T::IbcHandler::handle_message(HandlerMessage::SendPacket {
+				data,
+				timeout,
+				port_id,
+				channel_id,
+			})
+			.map_err(|e| {
+				log::trace!(target: "pallet_ibc_echo", "[send_echo] error: {:?}", e);
+				Error::<T>::PacketSendError
+			})?;
+			Ok(())
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A channel has been opened
+		ChannelOpened { channel_id: Vec<u8>, port_id: Vec<u8> },
+		/// A packet has been received and echoed back
+		PacketEchoed { channel_id: Vec<u8>, port_id: Vec<u8>, sequence: u64 },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Invalid params passed
+		InvalidParams,
+		/// Error opening channel
+		ChannelInitError,
+		/// Error registering packet
+		PacketSendError,
+	}
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct IbcModule<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for IbcModule<T> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config> core::fmt::Debug for IbcModule<T> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		write!(f, "pallet-ibc-echo")
+	}
+}
+
+impl<T: Config> IbcModule<T> {
+	fn check_channel(order: Order, version: &Version) -> Result<(), Ics04Error> {
+		if version.to_string() != *VERSION {
+			return Err(Ics04Error::no_common_version())
+		}
+		if order != Order::Unordered {
+			return Err(Ics04Error::unknown_order_type(order.to_string()))
+		}
+		Ok(())
+	}
+
+	fn channel_opened(port_id: &PortId, channel_id: &ChannelId) {
+		Pallet::<T>::deposit_event(Event::<T>::ChannelOpened {
+			channel_id: channel_id.to_string().into_bytes(),
+			port_id: port_id.as_bytes().to_vec(),
+		});
+	}
+}
+
+impl<T: Config + Send + Sync> Module for IbcModule<T> {
+	fn on_chan_open_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		order: Order,
+		_connection_hops: &[ConnectionId],
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty: &Counterparty,
+		version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		Self::check_channel(order, version)
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		order: Order,
+		_connection_hops: &[ConnectionId],
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		Self::check_channel(order, counterparty_version)?;
+		Self::check_channel(order, version)?;
+		Ok(version.clone())
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		if counterparty_version.to_string() != *VERSION {
+			return Err(Ics04Error::no_common_version())
+		}
+		Self::channel_opened(port_id, channel_id);
+		Ok(())
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		Self::channel_opened(port_id, channel_id);
+		Ok(())
+	}
+
+	fn on_recv_packet(
+		&self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		_relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		let echo = packet.data.clone();
+		T::IbcHandler::handle_message(HandlerMessage::WriteAck {
+			packet: packet.clone(),
+			ack: echo.clone(),
+		})
+		.map_err(|e| Ics04Error::implementation_specific(format!("{e:?}")))?;
+		Pallet::<T>::deposit_event(Event::<T>::PacketEchoed {
+			channel_id: packet.destination_channel.to_string().into_bytes(),
+			port_id: packet.destination_port.as_bytes().to_vec(),
+			sequence: packet.sequence.into(),
+		});
+		Ok(echo.into())
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &Acknowledgement,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		if acknowledgement.as_ref() != packet.data.as_slice() {
+			log::warn!(target: "pallet_ibc_echo", "Packet {:?} was not echoed back", packet.sequence);
+		}
+		Ok(())
+	}
+}
+
+pub struct WeightHandler<T: Config>(PhantomData<T>);
+impl<T: Config> Default for WeightHandler<T> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config> CallbackWeight for WeightHandler<T> {
+	fn on_chan_open_init(&self) -> Weight {
+		Weight::default()
+	}
+
+	fn on_chan_open_try(&self) -> Weight {
+		Weight::default()
+	}
+
+	fn on_chan_open_ack(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::default()
+	}
+
+	fn on_chan_open_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::default()
+	}
+
+	fn on_chan_close_init(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::default()
+	}
+
+	fn on_chan_close_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::default()
+	}
+
+	fn on_recv_packet(&self, _packet: &Packet) -> Weight {
+		// the channel is read, and the acknowledgement and its commitment written
+		T::DbWeight::get().reads_writes(1, 2)
+	}
+
+	fn on_acknowledgement_packet(
+		&self,
+		_packet: &Packet,
+		_acknowledgement: &Acknowledgement,
+	) -> Weight {
+		Weight::default()
+	}
+
+	fn on_timeout_packet(&self, _packet: &Packet) -> Weight {
+		Weight::default()
+	}
+}
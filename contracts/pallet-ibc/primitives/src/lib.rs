@@ -282,13 +282,35 @@ pub struct QueryDenomTraceResponse {
 	pub denom: Vec<u8>,
 }
 
-#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo)]
+#[derive(
+	Clone, Default, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo,
+)]
 pub struct QueryDenomTracesResponse {
 	pub denoms: Vec<Vec<u8>>,
 	pub next_key: Option<Vec<u8>>,
 	pub total: Option<u64>,
 }
 
+/// Page of the items of a paginated query: the `limit` items following the first `offset` ones.
+#[derive(
+	Clone, Copy, Default, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo,
+)]
+pub struct PageRequest {
+	pub offset: u32,
+	pub limit: u32,
+	/// Whether to count the total number of items
+	pub count_total: bool,
+}
+
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo)]
+pub struct Page<Item> {
+	pub items: Vec<Item>,
+	/// Offset of the next page, if there are more items
+	pub next_offset: Option<u32>,
+	pub total: Option<u64>,
+	pub height: u64,
+}
+
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd, TypeInfo)]
 pub struct ConnectionHandshake {
 	pub client_state: Vec<u8>,
@@ -313,6 +335,8 @@ pub enum Error {
 	TimestampOrHeightNotFound { msg: Option<String> },
 	/// Failed to register a token transfer packet
 	SendTransferError { msg: Option<String> },
+	/// The token transfer exceeds the rate limits of its channel
+	RateLimited { msg: Option<String> },
 	/// Ics20 receive packet processing error
 	ReceivePacketError { msg: Option<String> },
 	/// Write acknowledgement error
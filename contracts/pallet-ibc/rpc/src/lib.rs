@@ -4,6 +4,7 @@
 
 use codec::Encode;
 use ibc::{
+	applications::transfer::{receiver_chain_denom, PrefixedDenom},
 	core::{
 		ics03_connection::connection::ConnectionEnd,
 		ics04_channel::channel::{ChannelEnd, IdentifiedChannelEnd, Order},
@@ -100,6 +101,18 @@ impl<Hash: std::fmt::Debug> Display for BlockNumberOrHash<Hash> {
 	}
 }
 
+/// Maximum number of blocks whose events are returned by a single `ibc_queryEventsPage` request
+pub const MAX_EVENTS_PAGE_SIZE: u32 = 500;
+
+/// A page of the ibc events deposited in a series of blocks
+#[derive(Serialize, Deserialize)]
+pub struct EventsPage {
+	/// Events of the blocks of the page, keyed by block number or hash
+	pub events: HashMap<String, Vec<RawIbcEvent>>,
+	/// Offset in the queried blocks of the first block of the next page, if there are more
+	pub next_offset: Option<u32>,
+}
+
 /// Proof for a set of keys
 #[derive(Serialize, Deserialize)]
 pub struct Proof {
@@ -160,7 +173,43 @@ impl TryFrom<RawPacketInfo> for PacketInfo {
 	}
 }
 
+/// Denomination that a token is credited as on the receiving chain of an ICS-20 transfer
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedVoucherDenom {
+	/// Full denomination on the receiving chain, i.e. `{port}/{channel}/.../{base_denom}`
+	pub denom: String,
+	/// The `ibc/{hash}` form of the denomination, as displayed by chains that store hashed
+	/// denominations. Equal to `denom` for native tokens.
+	pub ibc_denom: String,
+	/// True if the token is sent back to the chain it originates from, which receives it as its
+	/// native denomination
+	pub is_native: bool,
+}
+
+impl ExpectedVoucherDenom {
+	/// Computes the denomination that the chain at the other end of the channel
+	/// `source_port/source_channel` credits when receiving `denom` from it.
+	pub fn new(
+		source_port: PortId,
+		source_channel: ChannelId,
+		dest_port: PortId,
+		dest_channel: ChannelId,
+		denom: &PrefixedDenom,
+	) -> Self {
+		let denom =
+			receiver_chain_denom(source_port, source_channel, dest_port, dest_channel, denom);
+		Self {
+			ibc_denom: denom.hashed_denom(|data| sp_core::hashing::sha2_256(&data).to_vec()),
+			is_native: denom.trace_path().is_empty(),
+			denom: denom.to_string(),
+		}
+	}
+}
+
 /// IBC RPC methods.
+///
+/// Methods taking a `pallet_prefix` query the `pallet_ibc` instance stored under that prefix,
+/// or the runtime's default instance when it is omitted.
 #[rpc(client, server)]
 pub trait IbcApi<BlockNumber, Hash, AssetId>
 where
@@ -174,6 +223,7 @@ where
 		channel_id: String,
 		port_id: String,
 		seqs: Vec<u64>,
+		pallet_prefix: Option<String>,
 	) -> Result<Vec<PacketInfo>>;
 	/// Query Recv Packet
 	#[method(name = "ibc_queryRecvPackets")]
@@ -182,6 +232,7 @@ where
 		channel_id: String,
 		port_id: String,
 		seqs: Vec<u64>,
+		pallet_prefix: Option<String>,
 	) -> Result<Vec<PacketInfo>>;
 
 	/// Query local time and height that a client was updated
@@ -191,11 +242,37 @@ where
 		client_id: String,
 		revision_number: u64,
 		revision_height: u64,
+		pallet_prefix: Option<String>,
 	) -> Result<HeightAndTimestamp>;
 
 	/// Generate proof for given key
 	#[method(name = "ibc_queryProof")]
-	fn query_proof(&self, height: u32, keys: Vec<Vec<u8>>) -> Result<Proof>;
+	fn query_proof(
+		&self,
+		height: u32,
+		keys: Vec<Vec<u8>>,
+		pallet_prefix: Option<String>,
+	) -> Result<Proof>;
+
+	/// Generate a proof for each of the given sets of keys at the same height, in one request
+	#[method(name = "ibc_queryProofs")]
+	fn query_proofs(
+		&self,
+		height: u32,
+		keys: Vec<Vec<Vec<u8>>>,
+		pallet_prefix: Option<String>,
+	) -> Result<Vec<Proof>>;
+
+	/// Generate a single proof of all the given commitment paths, e.g.
+	/// `commitments/ports/transfer/channels/channel-0/sequences/1`, which are prefixed with the
+	/// commitment prefix of the pallet instance
+	#[method(name = "ibc_queryBatchProof")]
+	fn query_batch_proof(
+		&self,
+		height: u32,
+		paths: Vec<String>,
+		pallet_prefix: Option<String>,
+	) -> Result<Proof>;
 
 	/// Query latest height
 	#[method(name = "ibc_queryLatestHeight")]
@@ -204,7 +281,12 @@ where
 	/// Query balance of an address on chain, addr should be a valid hexadecimal or SS58 string,
 	/// representing the account id.
 	#[method(name = "ibc_queryBalanceWithAddress")]
-	fn query_balance_with_address(&self, addr: String, asset_id: AssetId) -> Result<Coin>;
+	fn query_balance_with_address(
+		&self,
+		addr: String,
+		asset_id: AssetId,
+		pallet_prefix: Option<String>,
+	) -> Result<Coin>;
 
 	/// Query a client state
 	#[method(name = "ibc_queryClientState")]
@@ -212,6 +294,7 @@ where
 		&self,
 		height: u32,
 		src_client_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryClientStateResponse>;
 
 	/// Query client consensus state
@@ -228,6 +311,7 @@ where
 		revision_height: u64,
 		revision_number: u64,
 		latest_consensus_state: bool,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryConsensusStateResponse>;
 
 	/// Query upgraded client state
@@ -240,7 +324,7 @@ where
 
 	/// Query all client states
 	#[method(name = "ibc_queryClients")]
-	fn query_clients(&self) -> Result<Vec<IdentifiedClientState>>;
+	fn query_clients(&self, pallet_prefix: Option<String>) -> Result<Vec<IdentifiedClientState>>;
 
 	/// Query a connection state
 	#[method(name = "ibc_queryConnection")]
@@ -248,11 +332,12 @@ where
 		&self,
 		height: u32,
 		connection_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryConnectionResponse>;
 
 	/// Query all connection states
 	#[method(name = "ibc_queryConnections")]
-	fn query_connections(&self) -> Result<QueryConnectionsResponse>;
+	fn query_connections(&self, pallet_prefix: Option<String>) -> Result<QueryConnectionsResponse>;
 
 	/// Query all connection states for associated client
 	#[method(name = "ibc_queryConnectionUsingClient")]
@@ -260,6 +345,7 @@ where
 		&self,
 		height: u32,
 		client_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<Vec<IdentifiedConnection>>;
 
 	/// Generate proof for connection handshake
@@ -269,6 +355,7 @@ where
 		height: u32,
 		client_id: String,
 		conn_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<ConnHandshakeProof>;
 
 	/// Query a channel state
@@ -278,6 +365,7 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryChannelResponse>;
 
 	/// Query client state for channel and port id
@@ -287,6 +375,7 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<IdentifiedClientState>;
 
 	/// Query all channel states for associated connection
@@ -295,11 +384,12 @@ where
 		&self,
 		height: u32,
 		connection_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryChannelsResponse>;
 
 	/// Query all channel states
 	#[method(name = "ibc_queryChannels")]
-	fn query_channels(&self) -> Result<QueryChannelsResponse>;
+	fn query_channels(&self, pallet_prefix: Option<String>) -> Result<QueryChannelsResponse>;
 
 	/// Query packet commitments
 	#[method(name = "ibc_queryPacketCommitments")]
@@ -308,6 +398,7 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryPacketCommitmentsResponse>;
 
 	/// Query packet acknowledgements
@@ -317,6 +408,7 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryPacketAcknowledgementsResponse>;
 
 	/// Given a list of counterparty packet commitments, the querier checks if the packet
@@ -337,6 +429,7 @@ where
 		channel_id: String,
 		port_id: String,
 		seqs: Vec<u64>,
+		pallet_prefix: Option<String>,
 	) -> Result<Vec<u64>>;
 
 	/// Given a list of counterparty packet acknowledgements, the querier checks if the ack
@@ -357,6 +450,7 @@ where
 		channel_id: String,
 		port_id: String,
 		seqs: Vec<u64>,
+		pallet_prefix: Option<String>,
 	) -> Result<Vec<u64>>;
 
 	/// Query next sequence to be received on channel
@@ -366,6 +460,7 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryNextSequenceReceiveResponse>;
 
 	/// Query packet commitment
@@ -376,6 +471,7 @@ where
 		channel_id: String,
 		port_id: String,
 		seq: u64,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryPacketCommitmentResponse>;
 
 	/// Query packet acknowledgement
@@ -386,6 +482,7 @@ where
 		channel_id: String,
 		port_id: String,
 		seq: u64,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryPacketAcknowledgementResponse>;
 
 	/// Query packet receipt
@@ -396,13 +493,18 @@ where
 		channel_id: String,
 		port_id: String,
 		seq: u64,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryPacketReceiptResponse>;
 
 	/// Query the denom trace for an ibc denom from the asset Id
 	// In ibc-go this method accepts a string which is the hash of the ibc denom
 	// that is because ibc denoms are stored as hashes in ibc-go, but in our implementation here
 	#[method(name = "ibc_queryDenomTrace")]
-	fn query_denom_trace(&self, asset_id: AssetId) -> Result<QueryDenomTraceResponse>;
+	fn query_denom_trace(
+		&self,
+		asset_id: AssetId,
+		pallet_prefix: Option<String>,
+	) -> Result<QueryDenomTraceResponse>;
 
 	/// Query the denom traces for ibc denoms
 	/// key is the asset id from which to start paginating results
@@ -416,14 +518,44 @@ where
 		offset: Option<u32>,
 		limit: Option<u64>,
 		count_total: bool,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryDenomTracesResponse>;
 
+	/// Query the local asset id of an ibc denom (its full trace path), which is either the asset
+	/// id it was registered with or the one derived from its hash. The reverse lookup is
+	/// `ibc_queryDenomTrace`
+	#[method(name = "ibc_queryDenomAssetId")]
+	fn query_denom_asset_id(&self, denom: String, pallet_prefix: Option<String>)
+		-> Result<AssetId>;
+
+	/// Query the denom trace of a received ibc denom from the hash it's known by on cosmos
+	/// chains, either as `ibc/{hash}` or as the hex encoded hash
+	#[method(name = "ibc_queryDenomTraceByHash")]
+	fn query_denom_trace_by_hash(
+		&self,
+		hash: String,
+		pallet_prefix: Option<String>,
+	) -> Result<QueryDenomTraceResponse>;
+
+	/// Query the denomination that the counterparty chain credits for `denom` when it's sent over
+	/// the channel `port_id/channel_id` at the best block, so that it can be displayed before
+	/// the transfer is made
+	#[method(name = "ibc_queryExpectedVoucherDenom")]
+	fn query_expected_voucher_denom(
+		&self,
+		denom: String,
+		channel_id: String,
+		port_id: String,
+		pallet_prefix: Option<String>,
+	) -> Result<ExpectedVoucherDenom>;
+
 	/// Query newly created client in block and extrinsic
 	#[method(name = "ibc_queryNewlyCreatedClient")]
 	fn query_newly_created_client(
 		&self,
 		block_hash: Hash,
 		ext_hash: Hash,
+		pallet_prefix: Option<String>,
 	) -> Result<IdentifiedClientState>;
 
 	/// Query newly created connection in block and extrinsic
@@ -432,6 +564,7 @@ where
 		&self,
 		block_hash: Hash,
 		ext_hash: Hash,
+		pallet_prefix: Option<String>,
 	) -> Result<IdentifiedConnection>;
 
 	/// Query newly created channel in block and extrinsic
@@ -440,6 +573,7 @@ where
 		&self,
 		block_hash: Hash,
 		ext_hash: Hash,
+		pallet_prefix: Option<String>,
 	) -> Result<IdentifiedChannel>;
 
 	/// Query Ibc Events that were deposited in a series of blocks
@@ -448,7 +582,20 @@ where
 	fn query_events(
 		&self,
 		block_numbers: Vec<BlockNumberOrHash<Hash>>,
+		pallet_prefix: Option<String>,
 	) -> Result<HashMap<String, Vec<RawIbcEvent>>>;
+
+	/// Query the Ibc Events that were deposited in the blocks `block_numbers[offset..]`, at most
+	/// `limit` blocks at a time, which is capped at [`MAX_EVENTS_PAGE_SIZE`]. The offset of the
+	/// next page is returned until the last block was read.
+	#[method(name = "ibc_queryEventsPage")]
+	fn query_events_page(
+		&self,
+		block_numbers: Vec<BlockNumberOrHash<Hash>>,
+		offset: u32,
+		limit: u32,
+		pallet_prefix: Option<String>,
+	) -> Result<EventsPage>;
 }
 
 /// Converts a runtime trap into an RPC error.
@@ -495,11 +642,14 @@ where
 		channel_id: String,
 		port_id: String,
 		seqs: Vec<u64>,
+		pallet_prefix: Option<String>,
 	) -> Result<Vec<PacketInfo>> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 		let packets: Vec<ibc_primitives::PacketInfo> = api
 			.query_send_packet_info(
 				self.client.info().best_hash,
+				pallet_prefix.clone(),
 				channel_id.as_bytes().to_vec(),
 				port_id.as_bytes().to_vec(),
 				seqs,
@@ -552,12 +702,15 @@ where
 		channel_id: String,
 		port_id: String,
 		seqs: Vec<u64>,
+		pallet_prefix: Option<String>,
 	) -> Result<Vec<PacketInfo>> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 		let at = self.client.info().best_hash;
 		let packets: Vec<ibc_primitives::PacketInfo> = api
 			.query_recv_packet_info(
 				at,
+				pallet_prefix.clone(),
 				channel_id.as_bytes().to_vec(),
 				port_id.as_bytes().to_vec(),
 				seqs,
@@ -610,7 +763,9 @@ where
 		client_id: String,
 		revision_number: u64,
 		revision_height: u64,
+		pallet_prefix: Option<String>,
 	) -> Result<HeightAndTimestamp> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 		let at = self.client.info().best_hash;
 		let para_id = api
@@ -619,6 +774,7 @@ where
 		let (update_height, update_time) = api
 			.client_update_time_and_height(
 				at,
+				pallet_prefix.clone(),
 				client_id.as_bytes().to_vec(),
 				revision_number,
 				revision_height,
@@ -634,7 +790,32 @@ where
 		})
 	}
 
-	fn query_proof(&self, height: u32, mut keys: Vec<Vec<u8>>) -> Result<Proof> {
+	fn query_proof(
+		&self,
+		height: u32,
+		keys: Vec<Vec<u8>>,
+		pallet_prefix: Option<String>,
+	) -> Result<Proof> {
+		self.read_proofs(height, vec![keys], pallet_prefix)?
+			.pop()
+			.ok_or_else(|| runtime_error_into_rpc_error("Error generating proof"))
+	}
+
+	fn query_proofs(
+		&self,
+		height: u32,
+		keys: Vec<Vec<Vec<u8>>>,
+		pallet_prefix: Option<String>,
+	) -> Result<Vec<Proof>> {
+		self.read_proofs(height, keys, pallet_prefix)
+	}
+
+	fn query_batch_proof(
+		&self,
+		height: u32,
+		paths: Vec<String>,
+		pallet_prefix: Option<String>,
+	) -> Result<Proof> {
 		let api = self.client.runtime_api();
 		let at = BlockId::Number(height.into());
 		let hash_at = self
@@ -642,24 +823,18 @@ where
 			.block_hash_from_id(&at)
 			.map_err(|_| RpcError::Custom("Unknown block".into()))?
 			.ok_or_else(|| RpcError::Custom("Unknown block".into()))?;
-		let para_id = api
-			.para_id(hash_at)
-			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
-		let child_trie_key = api
-			.child_trie_key(hash_at)
-			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
-		let child_info = ChildInfo::new_default(&child_trie_key);
-		let proof = self
-			.client
-			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
-			.map_err(runtime_error_into_rpc_error)?
-			.iter_nodes()
-			.collect::<Vec<_>>()
-			.encode();
-		Ok(Proof {
-			proof,
-			height: Height { revision_number: para_id.into(), revision_height: height as u64 },
-		})
+		let keys = api
+			.commitment_trie_keys(
+				hash_at,
+				self.pallet_prefix(pallet_prefix.clone())?,
+				paths.into_iter().map(String::into_bytes).collect(),
+			)
+			.ok()
+			.flatten()
+			.ok_or_else(|| runtime_error_into_rpc_error("Invalid commitment paths"))?;
+		self.read_proofs(height, vec![keys], pallet_prefix)?
+			.pop()
+			.ok_or_else(|| runtime_error_into_rpc_error("Error generating proof"))
 	}
 
 	fn query_latest_height(&self) -> Result<<<Block as BlockT>::Header as HeaderT>::Number> {
@@ -670,11 +845,17 @@ where
 		}
 	}
 
-	fn query_balance_with_address(&self, addr: String, asset_id: AssetId) -> Result<Coin> {
+	fn query_balance_with_address(
+		&self,
+		addr: String,
+		asset_id: AssetId,
+		pallet_prefix: Option<String>,
+	) -> Result<Coin> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 		let at = self.client.info().best_hash;
 		let denom = String::from_utf8(
-			api.denom_trace(at, asset_id)
+			api.denom_trace(at, pallet_prefix.clone(), asset_id)
 				.map_err(|e| {
 					runtime_error_into_rpc_error(format!("failed to get denom trace: {e}"))
 				})?
@@ -697,7 +878,9 @@ where
 		&self,
 		height: u32,
 		client_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryClientStateResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -710,15 +893,12 @@ where
 			.para_id(hash_at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
 		let result: ibc_primitives::QueryClientStateResponse = api
-			.client_state(hash_at, client_id.as_bytes().to_vec())
+			.client_state(hash_at, pallet_prefix.clone(), client_id.as_bytes().to_vec())
 			.ok()
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("[API] Error querying client state"))?;
 		let mut keys = vec![result.trie_key];
-		let child_trie_key = api
-			.child_trie_key(hash_at)
-			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
-		let child_info = ChildInfo::new_default(&child_trie_key);
+		let child_info = ChildInfo::new_default(&pallet_prefix);
 		let proof = self
 			.client
 			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
@@ -746,7 +926,9 @@ where
 		revision_height: u64,
 		revision_number: u64,
 		latest_cs: bool,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryConsensusStateResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 		let at = if let Some(height) = height {
 			BlockId::Number(height.into())
@@ -764,6 +946,7 @@ where
 		let result: ibc_primitives::QueryConsensusStateResponse = api
 			.client_consensus_state(
 				hash_at,
+				pallet_prefix.clone(),
 				client_id.as_bytes().to_vec(),
 				revision_number,
 				revision_height,
@@ -775,10 +958,7 @@ where
 		let consensus_state = AnyConsensusState::decode_vec(&result.consensus_state)
 			.map_err(|_| runtime_error_into_rpc_error("Error querying client consensus state"))?;
 		let mut keys = vec![result.trie_key];
-		let child_trie_key = api
-			.child_trie_key(hash_at)
-			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
-		let child_info = ChildInfo::new_default(&child_trie_key);
+		let child_info = ChildInfo::new_default(&pallet_prefix);
 		let proof = self
 			.client
 			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
@@ -804,11 +984,12 @@ where
 		Err(runtime_error_into_rpc_error("Unimplemented"))
 	}
 
-	fn query_clients(&self) -> Result<Vec<IdentifiedClientState>> {
+	fn query_clients(&self, pallet_prefix: Option<String>) -> Result<Vec<IdentifiedClientState>> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let client_states: Option<Vec<(Vec<u8>, Vec<u8>)>> =
-			api.clients(self.client.info().best_hash).ok().flatten();
+			api.clients(self.client.info().best_hash, pallet_prefix.clone()).ok().flatten();
 		match client_states {
 			Some(client_states) => client_states
 				.into_iter()
@@ -832,7 +1013,9 @@ where
 		&self,
 		height: u32,
 		connection_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryConnectionResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -845,7 +1028,7 @@ where
 			.para_id(hash_at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
 		let result: ibc_primitives::QueryConnectionResponse = api
-			.connection(hash_at, connection_id.as_bytes().to_vec())
+			.connection(hash_at, pallet_prefix.clone(), connection_id.as_bytes().to_vec())
 			.ok()
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch connection state"))?;
@@ -853,10 +1036,7 @@ where
 			ibc::core::ics03_connection::connection::ConnectionEnd::decode_vec(&result.connection)
 				.map_err(|_| runtime_error_into_rpc_error("Failed to decode connection end"))?;
 		let mut keys = vec![result.trie_key];
-		let child_trie_key = api
-			.child_trie_key(hash_at)
-			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
-		let child_info = ChildInfo::new_default(&child_trie_key);
+		let child_info = ChildInfo::new_default(&pallet_prefix);
 		let proof = self
 			.client
 			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
@@ -874,7 +1054,8 @@ where
 		})
 	}
 
-	fn query_connections(&self) -> Result<QueryConnectionsResponse> {
+	fn query_connections(&self, pallet_prefix: Option<String>) -> Result<QueryConnectionsResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Hash(self.client.info().best_hash);
@@ -884,7 +1065,7 @@ where
 			.map_err(|_| RpcError::Custom("Unknown block".into()))?
 			.ok_or_else(|| RpcError::Custom("Unknown block".into()))?;
 		let result: ibc_primitives::QueryConnectionsResponse = api
-			.connections(hash_at)
+			.connections(hash_at, pallet_prefix.clone())
 			.ok()
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch connections"))?;
@@ -926,7 +1107,9 @@ where
 		&self,
 		height: u32,
 		client_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<Vec<IdentifiedConnection>> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -936,7 +1119,7 @@ where
 			.map_err(|_| RpcError::Custom("Unknown block".into()))?
 			.ok_or_else(|| RpcError::Custom("Unknown block".into()))?;
 		let result: Vec<ibc_primitives::IdentifiedConnection> = api
-			.connection_using_client(hash_at, client_id.as_bytes().to_vec())
+			.connection_using_client(hash_at, pallet_prefix.clone(), client_id.as_bytes().to_vec())
 			.ok()
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch connections"))?;
@@ -965,7 +1148,9 @@ where
 		height: u32,
 		client_id: String,
 		conn_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<ConnHandshakeProof> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -980,16 +1165,14 @@ where
 		let mut result: ibc_primitives::ConnectionHandshake = api
 			.connection_handshake(
 				hash_at,
+				pallet_prefix.clone(),
 				client_id.as_bytes().to_vec(),
 				conn_id.as_bytes().to_vec(),
 			)
 			.ok()
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("Error getting trie inputs"))?;
-		let child_trie_key = api
-			.child_trie_key(hash_at)
-			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
-		let child_info = ChildInfo::new_default(&child_trie_key);
+		let child_info = ChildInfo::new_default(&pallet_prefix);
 		let proof = self
 			.client
 			.read_child_proof(
@@ -1022,7 +1205,9 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryChannelResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -1035,17 +1220,19 @@ where
 			.para_id(hash_at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
 		let result: ibc_primitives::QueryChannelResponse = api
-			.channel(hash_at, channel_id.as_bytes().to_vec(), port_id.as_bytes().to_vec())
+			.channel(
+				hash_at,
+				pallet_prefix.clone(),
+				channel_id.as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+			)
 			.ok()
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch channel state"))?;
 		let channel = ibc::core::ics04_channel::channel::ChannelEnd::decode_vec(&result.channel)
 			.map_err(|_| runtime_error_into_rpc_error("Failed to decode channel state"))?;
 		let mut keys = vec![result.trie_key];
-		let child_trie_key = api
-			.child_trie_key(hash_at)
-			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
-		let child_info = ChildInfo::new_default(&child_trie_key);
+		let child_info = ChildInfo::new_default(&pallet_prefix);
 		let proof = self
 			.client
 			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
@@ -1068,7 +1255,9 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<IdentifiedClientState> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -1078,7 +1267,12 @@ where
 			.map_err(|_| RpcError::Custom("Unknown block".into()))?
 			.ok_or_else(|| RpcError::Custom("Unknown block".into()))?;
 		let result: ibc_primitives::IdentifiedClientState = api
-			.channel_client(hash_at, channel_id.as_bytes().to_vec(), port_id.as_bytes().to_vec())
+			.channel_client(
+				hash_at,
+				pallet_prefix.clone(),
+				channel_id.as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+			)
 			.ok()
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("Failed to Client state for channel"))?;
@@ -1096,7 +1290,9 @@ where
 		&self,
 		height: u32,
 		connection_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryChannelsResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -1109,7 +1305,7 @@ where
 			.para_id(hash_at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
 		let result: ibc_primitives::QueryChannelsResponse = api
-			.connection_channels(hash_at, connection_id.as_bytes().to_vec())
+			.connection_channels(hash_at, pallet_prefix.clone(), connection_id.as_bytes().to_vec())
 			.ok()
 			.flatten()
 			.ok_or_else(|| {
@@ -1149,7 +1345,8 @@ where
 		})
 	}
 
-	fn query_channels(&self) -> Result<QueryChannelsResponse> {
+	fn query_channels(&self, pallet_prefix: Option<String>) -> Result<QueryChannelsResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 		let at = BlockId::Hash(self.client.info().best_hash);
 		let hash_at = self
@@ -1161,7 +1358,7 @@ where
 			.para_id(hash_at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
 		let result: ibc_primitives::QueryChannelsResponse = api
-			.channels(hash_at)
+			.channels(hash_at, pallet_prefix.clone())
 			.ok()
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch channels"))?;
@@ -1204,7 +1401,9 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryPacketCommitmentsResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -1219,6 +1418,7 @@ where
 		let result: ibc_primitives::QueryPacketCommitmentsResponse = api
 			.packet_commitments(
 				hash_at,
+				pallet_prefix.clone(),
 				channel_id.as_bytes().to_vec(),
 				port_id.as_bytes().to_vec(),
 			)
@@ -1256,7 +1456,9 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryPacketAcknowledgementsResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -1272,6 +1474,7 @@ where
 		let result: ibc_primitives::QueryPacketAcknowledgementsResponse = api
 			.packet_acknowledgements(
 				hash_at,
+				pallet_prefix.clone(),
 				channel_id.as_bytes().to_vec(),
 				port_id.as_bytes().to_vec(),
 			)
@@ -1310,7 +1513,9 @@ where
 		channel_id: String,
 		port_id: String,
 		seqs: Vec<u64>,
+		pallet_prefix: Option<String>,
 	) -> Result<Vec<u64>> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 		let at = BlockId::Number(height.into());
 		let hash_at = self
@@ -1321,6 +1526,7 @@ where
 
 		api.unreceived_packets(
 			hash_at,
+			pallet_prefix.clone(),
 			channel_id.as_bytes().to_vec(),
 			port_id.as_bytes().to_vec(),
 			seqs,
@@ -1336,7 +1542,9 @@ where
 		channel_id: String,
 		port_id: String,
 		seqs: Vec<u64>,
+		pallet_prefix: Option<String>,
 	) -> Result<Vec<u64>> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 		let at = BlockId::Number(height.into());
 		let hash_at = self
@@ -1347,6 +1555,7 @@ where
 
 		api.unreceived_acknowledgements(
 			hash_at,
+			pallet_prefix.clone(),
 			channel_id.as_bytes().to_vec(),
 			port_id.as_bytes().to_vec(),
 			seqs,
@@ -1361,7 +1570,9 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryNextSequenceReceiveResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -1374,15 +1585,17 @@ where
 			.para_id(hash_at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
 		let result: ibc_primitives::QueryNextSequenceReceiveResponse = api
-			.next_seq_recv(hash_at, channel_id.as_bytes().to_vec(), port_id.as_bytes().to_vec())
+			.next_seq_recv(
+				hash_at,
+				pallet_prefix.clone(),
+				channel_id.as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+			)
 			.ok()
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("Error fetching next sequence recv"))?;
 		let mut keys = vec![result.trie_key];
-		let child_trie_key = api
-			.child_trie_key(hash_at)
-			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
-		let child_info = ChildInfo::new_default(&child_trie_key);
+		let child_info = ChildInfo::new_default(&pallet_prefix);
 		let proof = self
 			.client
 			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
@@ -1406,7 +1619,9 @@ where
 		channel_id: String,
 		port_id: String,
 		seq: u64,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryPacketCommitmentResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -1421,6 +1636,7 @@ where
 		let result: ibc_primitives::QueryPacketCommitmentResponse = api
 			.packet_commitment(
 				hash_at,
+				pallet_prefix.clone(),
 				channel_id.as_bytes().to_vec(),
 				port_id.as_bytes().to_vec(),
 				seq,
@@ -1429,10 +1645,7 @@ where
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("Error fetching packet commitment"))?;
 		let mut keys = vec![result.trie_key];
-		let child_trie_key = api
-			.child_trie_key(hash_at)
-			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
-		let child_info = ChildInfo::new_default(&child_trie_key);
+		let child_info = ChildInfo::new_default(&pallet_prefix);
 		let proof = self
 			.client
 			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
@@ -1456,7 +1669,9 @@ where
 		channel_id: String,
 		port_id: String,
 		seq: u64,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryPacketAcknowledgementResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -1471,6 +1686,7 @@ where
 		let result: ibc_primitives::QueryPacketAcknowledgementResponse = api
 			.packet_acknowledgement(
 				hash_at,
+				pallet_prefix.clone(),
 				channel_id.as_bytes().to_vec(),
 				port_id.as_bytes().to_vec(),
 				seq,
@@ -1479,10 +1695,7 @@ where
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("Error fetching packet acknowledgement"))?;
 		let mut keys = vec![result.trie_key];
-		let child_trie_key = api
-			.child_trie_key(hash_at)
-			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
-		let child_info = ChildInfo::new_default(&child_trie_key);
+		let child_info = ChildInfo::new_default(&pallet_prefix);
 		let proof = self
 			.client
 			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
@@ -1506,7 +1719,9 @@ where
 		channel_id: String,
 		port_id: String,
 		seq: u64,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryPacketReceiptResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 
 		let at = BlockId::Number(height.into());
@@ -1521,6 +1736,7 @@ where
 		let result: ibc_primitives::QueryPacketReceiptResponse = api
 			.packet_receipt(
 				hash_at,
+				pallet_prefix.clone(),
 				channel_id.as_bytes().to_vec(),
 				port_id.as_bytes().to_vec(),
 				seq,
@@ -1529,10 +1745,7 @@ where
 			.flatten()
 			.ok_or_else(|| runtime_error_into_rpc_error("Error fetching packet receipt"))?;
 		let mut keys = vec![result.trie_key];
-		let child_trie_key = api
-			.child_trie_key(hash_at)
-			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
-		let child_info = ChildInfo::new_default(&child_trie_key);
+		let child_info = ChildInfo::new_default(&pallet_prefix);
 		let proof = self
 			.client
 			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
@@ -1550,16 +1763,23 @@ where
 		})
 	}
 
-	fn query_denom_trace(&self, asset_id: AssetId) -> Result<QueryDenomTraceResponse> {
+	fn query_denom_trace(
+		&self,
+		asset_id: AssetId,
+		pallet_prefix: Option<String>,
+	) -> Result<QueryDenomTraceResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 		let block_hash = self.client.info().best_hash;
 
 		let denom_trace =
-			api.denom_trace(block_hash, asset_id).ok().flatten().ok_or_else(|| {
-				runtime_error_into_rpc_error(
-					"[ibc_rpc]: Could not find a denom trace for asset id provided",
-				)
-			})?;
+			api.denom_trace(block_hash, pallet_prefix, asset_id).ok().flatten().ok_or_else(
+				|| {
+					runtime_error_into_rpc_error(
+						"[ibc_rpc]: Could not find a denom trace for asset id provided",
+					)
+				},
+			)?;
 
 		let denom_str = String::from_utf8(denom_trace.denom).map_err(|_| {
 			runtime_error_into_rpc_error(
@@ -1588,15 +1808,19 @@ where
 		offset: Option<u32>,
 		limit: Option<u64>,
 		count_total: bool,
+		pallet_prefix: Option<String>,
 	) -> Result<QueryDenomTracesResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 		let block_hash = self.client.info().best_hash;
 
 		let at = block_hash;
 		// Set default limit to 20 items
 		let limit = limit.unwrap_or(20);
-		let result =
-			api.denom_traces(at, key, offset, limit, count_total).ok().ok_or_else(|| {
+		let result = api
+			.denom_traces(at, pallet_prefix, key, offset, limit, count_total)
+			.ok()
+			.ok_or_else(|| {
 				runtime_error_into_rpc_error(
 					"[ibc_rpc]: Could not find a denom trace for asset id provided",
 				)
@@ -1636,18 +1860,130 @@ where
 		})
 	}
 
+	fn query_denom_asset_id(
+		&self,
+		denom: String,
+		pallet_prefix: Option<String>,
+	) -> Result<AssetId> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
+		let api = self.client.runtime_api();
+		let block_hash = self.client.info().best_hash;
+
+		let denom = PrefixedDenom::from_str(&denom).map_err(|_| {
+			runtime_error_into_rpc_error(
+				"[ibc_rpc]: Could not derive a valid ibc denom from string",
+			)
+		})?;
+		api.denom_asset_id(block_hash, pallet_prefix, denom.to_string().into_bytes())
+			.ok()
+			.flatten()
+			.ok_or_else(|| {
+				runtime_error_into_rpc_error(
+					"[ibc_rpc]: Could not resolve the asset id of the denom",
+				)
+			})
+	}
+
+	fn query_denom_trace_by_hash(
+		&self,
+		hash: String,
+		pallet_prefix: Option<String>,
+	) -> Result<QueryDenomTraceResponse> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
+		let api = self.client.runtime_api();
+		let block_hash = self.client.info().best_hash;
+
+		let hash = sp_core::bytes::from_hex(hash.strip_prefix("ibc/").unwrap_or(&hash))
+			.map_err(|_| runtime_error_into_rpc_error("[ibc_rpc]: Invalid denom hash"))?;
+		let denom_trace = api
+			.denom_trace_by_hash(block_hash, pallet_prefix, hash)
+			.ok()
+			.flatten()
+			.ok_or_else(|| {
+			runtime_error_into_rpc_error("[ibc_rpc]: Could not find a denom trace for the hash")
+		})?;
+
+		let denom_str = String::from_utf8(denom_trace.denom).map_err(|_| {
+			runtime_error_into_rpc_error(
+				"[ibc_rpc]: Could not decode ibc denom into a valid string",
+			)
+		})?;
+		let denom_trace = ibc::applications::transfer::PrefixedDenom::from_str(&denom_str)
+			.map_err(|_| {
+				runtime_error_into_rpc_error(
+					"[ibc_rpc]: Could not derive a valid ibc denom from string",
+				)
+			})?;
+		let denom_trace: ibc_proto::ibc::applications::transfer::v1::DenomTrace =
+			denom_trace.try_into().map_err(|_| {
+				runtime_error_into_rpc_error(
+					"[ibc_rpc]: Could not derive a valid ibc denom from string",
+				)
+			})?;
+
+		Ok(QueryDenomTraceResponse { denom_trace: Some(denom_trace) })
+	}
+
+	fn query_expected_voucher_denom(
+		&self,
+		denom: String,
+		channel_id: String,
+		port_id: String,
+		pallet_prefix: Option<String>,
+	) -> Result<ExpectedVoucherDenom> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
+		let api = self.client.runtime_api();
+		let block_hash = self.client.info().best_hash;
+
+		let denom = PrefixedDenom::from_str(&denom).map_err(|_| {
+			runtime_error_into_rpc_error(
+				"[ibc_rpc]: Could not derive a valid ibc denom from string",
+			)
+		})?;
+		let source_channel = ChannelId::from_str(&channel_id)
+			.map_err(|_| runtime_error_into_rpc_error("[ibc_rpc]: Invalid channel id"))?;
+		let source_port = PortId::from_str(&port_id)
+			.map_err(|_| runtime_error_into_rpc_error("[ibc_rpc]: Invalid port id"))?;
+		let result: ibc_primitives::QueryChannelResponse = api
+			.channel(
+				block_hash,
+				pallet_prefix.clone(),
+				channel_id.as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+			)
+			.ok()
+			.flatten()
+			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch channel state"))?;
+		let channel = ChannelEnd::decode_vec(&result.channel)
+			.map_err(|_| runtime_error_into_rpc_error("Failed to decode channel state"))?;
+		let dest_channel = *channel.counterparty().channel_id().ok_or_else(|| {
+			runtime_error_into_rpc_error("[ibc_rpc]: Channel has no counterparty channel id")
+		})?;
+
+		Ok(ExpectedVoucherDenom::new(
+			source_port,
+			source_channel,
+			channel.counterparty().port_id().clone(),
+			dest_channel,
+			&denom,
+		))
+	}
+
 	fn query_newly_created_client(
 		&self,
 		block_hash: Block::Hash,
 		ext_hash: Block::Hash,
+		pallet_prefix: Option<String>,
 	) -> Result<IdentifiedClientState> {
-		let (block, event) = self.ibc_event_by_tx_id(block_hash, ext_hash)?;
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
+		let (block, event) =
+			self.ibc_event_by_tx_id(block_hash, ext_hash, pallet_prefix.clone())?;
 		let api = self.client.runtime_api();
 
 		match event {
 			Ok(IbcEvent::CreateClient { client_id, .. }) => {
 				let result: ibc_primitives::QueryClientStateResponse = api
-					.client_state(block.block.header().hash(), client_id.clone())
+					.client_state(block.block.header().hash(), pallet_prefix, client_id.clone())
 					.ok()
 					.flatten()
 					.ok_or_else(|| runtime_error_into_rpc_error("client state to exist"))?;
@@ -1670,8 +2006,13 @@ where
 		&self,
 		block_hash: Block::Hash,
 		ext_hash: Block::Hash,
+		pallet_prefix: Option<String>,
 	) -> Result<IdentifiedConnection> {
-		let (block, event) = self.ibc_event_by_tx_id(block_hash, ext_hash)?;
+		let (block, event) = self.ibc_event_by_tx_id(
+			block_hash,
+			ext_hash,
+			self.pallet_prefix(pallet_prefix.clone())?,
+		)?;
 
 		match event {
 			Ok(IbcEvent::OpenInitConnection { connection_id, client_id, .. }) => {
@@ -1686,6 +2027,7 @@ where
 					String::from_utf8(client_id).map_err(|_| {
 						runtime_error_into_rpc_error("client id should be valid utf8")
 					})?,
+					pallet_prefix,
 				)?;
 				let connection = connections
 					.into_iter()
@@ -1704,8 +2046,13 @@ where
 		&self,
 		block_hash: Block::Hash,
 		ext_hash: Block::Hash,
+		pallet_prefix: Option<String>,
 	) -> Result<IdentifiedChannel> {
-		let (block, event) = self.ibc_event_by_tx_id(block_hash, ext_hash)?;
+		let (block, event) = self.ibc_event_by_tx_id(
+			block_hash,
+			ext_hash,
+			self.pallet_prefix(pallet_prefix.clone())?,
+		)?;
 
 		match event {
 			Ok(IbcEvent::OpenInitChannel { channel_id, port_id, connection_id, .. }) => {
@@ -1719,6 +2066,7 @@ where
 					String::from_utf8(connection_id).map_err(|_| {
 						runtime_error_into_rpc_error("connection id should be valid utf8")
 					})?,
+					pallet_prefix,
 				)?;
 				let channel = channels
 					.channels
@@ -1739,7 +2087,48 @@ where
 	fn query_events(
 		&self,
 		block_numbers: Vec<BlockNumberOrHash<Block::Hash>>,
+		pallet_prefix: Option<String>,
+	) -> Result<HashMap<String, Vec<RawIbcEvent>>> {
+		self.read_events(block_numbers, pallet_prefix)
+	}
+
+	fn query_events_page(
+		&self,
+		block_numbers: Vec<BlockNumberOrHash<Block::Hash>>,
+		offset: u32,
+		limit: u32,
+		pallet_prefix: Option<String>,
+	) -> Result<EventsPage> {
+		let start = (offset as usize).min(block_numbers.len());
+		let end = start.saturating_add(limit.clamp(1, MAX_EVENTS_PAGE_SIZE) as usize);
+		let end = end.min(block_numbers.len());
+		let events = self.read_events(block_numbers[start..end].to_vec(), pallet_prefix)?;
+		let next_offset = (end < block_numbers.len()).then_some(end as u32);
+		Ok(EventsPage { events, next_offset })
+	}
+}
+
+impl<C, Block, AssetId> IbcRpcHandler<C, Block, AssetId>
+where
+	Block: BlockT,
+	C: 'static
+		+ BlockBackend<Block>
+		+ HeaderBackend<Block>
+		+ ProofProvider<Block>
+		+ ProvideRuntimeApi<Block>
+		+ Send
+		+ Sync,
+	C::Api: IbcRuntimeApi<Block, AssetId>,
+	AssetId: codec::Codec + Copy,
+{
+	/// Reads the ibc events deposited in each of the blocks `block_numbers`, keyed by block number
+	/// or hash.
+	fn read_events(
+		&self,
+		block_numbers: Vec<BlockNumberOrHash<Block::Hash>>,
+		pallet_prefix: Option<String>,
 	) -> Result<HashMap<String, Vec<RawIbcEvent>>> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
 		let api = self.client.runtime_api();
 		let mut events = HashMap::new();
 		for block_number_or_hash in block_numbers {
@@ -1753,7 +2142,7 @@ where
 				.map_err(|_| RpcError::Custom("Unknown block".into()))?
 				.ok_or_else(|| RpcError::Custom("Unknown block".into()))?;
 
-			let temp = api.block_events(hash_at, None).map_err(|_| {
+			let temp = api.block_events(hash_at, pallet_prefix.clone(), None).map_err(|_| {
 				runtime_error_into_rpc_error("[ibc_rpc]: failed to read block events")
 			})?;
 			let temp = temp
@@ -1766,25 +2155,69 @@ where
 		}
 		Ok(events)
 	}
-}
 
-impl<C, Block, AssetId> IbcRpcHandler<C, Block, AssetId>
-where
-	Block: BlockT,
-	C: 'static
-		+ BlockBackend<Block>
-		+ HeaderBackend<Block>
-		+ ProofProvider<Block>
-		+ ProvideRuntimeApi<Block>
-		+ Send
-		+ Sync,
-	C::Api: IbcRuntimeApi<Block, AssetId>,
-	AssetId: codec::Codec + Copy,
-{
+	/// Reads a child trie proof of each of the sets of `keys` at `height`, resolving the block and
+	/// the para id once for all of them.
+	fn read_proofs(
+		&self,
+		height: u32,
+		keys: Vec<Vec<Vec<u8>>>,
+		pallet_prefix: Option<String>,
+	) -> Result<Vec<Proof>> {
+		let pallet_prefix = self.pallet_prefix(pallet_prefix)?;
+		let api = self.client.runtime_api();
+		let at = BlockId::Number(height.into());
+		let hash_at = self
+			.client
+			.block_hash_from_id(&at)
+			.map_err(|_| RpcError::Custom("Unknown block".into()))?
+			.ok_or_else(|| RpcError::Custom("Unknown block".into()))?;
+		let para_id = api
+			.para_id(hash_at)
+			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
+		let child_info = ChildInfo::new_default(&pallet_prefix);
+		keys.into_iter()
+			.map(|mut keys| {
+				let proof = self
+					.client
+					.read_child_proof(
+						hash_at,
+						&child_info,
+						&mut keys.iter_mut().map(|nodes| &nodes[..]),
+					)
+					.map_err(runtime_error_into_rpc_error)?
+					.iter_nodes()
+					.collect::<Vec<_>>()
+					.encode();
+				Ok(Proof {
+					proof,
+					height: Height {
+						revision_number: para_id.into(),
+						revision_height: height as u64,
+					},
+				})
+			})
+			.collect()
+	}
+
+	/// Returns the pallet prefix of the `pallet_ibc` instance a request is for: the one it
+	/// specifies, or the prefix of the runtime's default instance.
+	fn pallet_prefix(&self, pallet_prefix: Option<String>) -> Result<Vec<u8>> {
+		match pallet_prefix {
+			Some(pallet_prefix) => Ok(pallet_prefix.into_bytes()),
+			None => self
+				.client
+				.runtime_api()
+				.child_trie_key(self.client.info().best_hash)
+				.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key")),
+		}
+	}
+
 	fn ibc_event_by_tx_id(
 		&self,
 		block_hash: <Block as BlockT>::Hash,
 		ext_hash: <Block as BlockT>::Hash,
+		pallet_prefix: Vec<u8>,
 	) -> Result<(SignedBlock<Block>, core::result::Result<IbcEvent, IbcError>)> {
 		let api = self.client.runtime_api();
 		let block = self.client.block(block_hash).ok().flatten().ok_or_else(|| {
@@ -1802,7 +2235,7 @@ where
 			})?;
 
 		let events = api
-			.block_events(block.block.header().hash(), Some(ext_index as _))
+			.block_events(block.block.header().hash(), pallet_prefix, Some(ext_index as _))
 			.map_err(|_| runtime_error_into_rpc_error("[ibc_rpc]: failed to read block events"))?;
 
 		// There should be only one ibc event in this list in this case
@@ -8,7 +8,10 @@ use sp_std::vec::Vec;
 sp_api::decl_runtime_apis! {
 	/// IBC Runtime Apis
 	pub trait IbcRuntimeApi<AssetId: codec::Codec> {
-		/// Get Ibc child trie prefix
+		/// Get Ibc child trie prefix of the default pallet instance
+		///
+		/// The remaining queries take the `pallet_prefix` of the pallet instance to query and
+		/// return `None` (or an empty result) when no instance uses that prefix.
 		fn child_trie_key() -> Vec<u8>;
 		/// Get parachain id
 		fn para_id() -> u32;
@@ -17,66 +20,90 @@ sp_api::decl_runtime_apis! {
 		fn query_balance_with_address(addr: Vec<u8>, asset_id: AssetId) -> Option<u128>;
 
 		/// Query receive packet info
-		fn query_send_packet_info(channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<PacketInfo>>;
+		fn query_send_packet_info(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<PacketInfo>>;
 
 		/// Query receive packet info
-		fn query_recv_packet_info(channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<PacketInfo>>;
+		fn query_recv_packet_info(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<PacketInfo>>;
 
 		/// Get the host time and height at which a client was updated for given consensus height
-		fn client_update_time_and_height(client_id: Vec<u8>, revision_number: u64, revision_height: u64) -> Option<(u64, u64)>;
+		fn client_update_time_and_height(pallet_prefix: Vec<u8>, client_id: Vec<u8>, revision_number: u64, revision_height: u64) -> Option<(u64, u64)>;
 
 		/// Returns client state at height
-		fn client_state(client_id: Vec<u8>) -> Option<QueryClientStateResponse>;
+		fn client_state(pallet_prefix: Vec<u8>, client_id: Vec<u8>) -> Option<QueryClientStateResponse>;
 
 		/// Return the consensus state for the given client at a height
-		fn client_consensus_state(client_id: Vec<u8>, revision_number: u64, revision_height: u64, latest_cs: bool) -> Option<QueryConsensusStateResponse>;
+		fn client_consensus_state(pallet_prefix: Vec<u8>, client_id: Vec<u8>, revision_number: u64, revision_height: u64, latest_cs: bool) -> Option<QueryConsensusStateResponse>;
 
 		/// Returns client states for all clients on chain
-		fn clients() -> Option<Vec<(Vec<u8>, Vec<u8>)>>;
+		fn clients(pallet_prefix: Vec<u8>) -> Option<Vec<(Vec<u8>, Vec<u8>)>>;
 
 		/// Query the given connection state with proof
-		fn connection(connection_id: Vec<u8>) -> Option<QueryConnectionResponse>;
+		fn connection(pallet_prefix: Vec<u8>, connection_id: Vec<u8>) -> Option<QueryConnectionResponse>;
 
 		/// Returns all connections registered on chain
-		fn connections() -> Option<QueryConnectionsResponse>;
+		fn connections(pallet_prefix: Vec<u8>) -> Option<QueryConnectionsResponse>;
 
 		/// Returns all connections associated with the given client
-		fn connection_using_client(client_id: Vec<u8>) -> Option<Vec<IdentifiedConnection>>;
+		fn connection_using_client(pallet_prefix: Vec<u8>, client_id: Vec<u8>) -> Option<Vec<IdentifiedConnection>>;
 
-		fn channel(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryChannelResponse>;
+		fn channel(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryChannelResponse>;
 
 		/// Should return the client state for the client supporting this channel
-		fn channel_client(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<IdentifiedClientState>;
+		fn channel_client(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<IdentifiedClientState>;
 
 		/// Returns all channels associated with this connection
-		fn connection_channels(connection_id: Vec<u8>) -> Option<QueryChannelsResponse>;
+		fn connection_channels(pallet_prefix: Vec<u8>, connection_id: Vec<u8>) -> Option<QueryChannelsResponse>;
 
 		/// Returns all channels registered on chain
-		fn channels() -> Option<QueryChannelsResponse>;
+		fn channels(pallet_prefix: Vec<u8>) -> Option<QueryChannelsResponse>;
 
-		fn connection_handshake(client_id: Vec<u8>, connection_id: Vec<u8>) -> Option<ConnectionHandshake>;
+		fn connection_handshake(pallet_prefix: Vec<u8>, client_id: Vec<u8>, connection_id: Vec<u8>) -> Option<ConnectionHandshake>;
 
-		fn packet_commitments(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryPacketCommitmentsResponse>;
+		/// Returns the child trie keys of the given commitment paths, to prove them all at once
+		fn commitment_trie_keys(pallet_prefix: Vec<u8>, paths: Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>>;
 
-		fn packet_acknowledgements(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryPacketAcknowledgementsResponse>;
+		fn packet_commitments(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryPacketCommitmentsResponse>;
 
-		fn unreceived_packets(channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<u64>>;
+		fn packet_acknowledgements(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryPacketAcknowledgementsResponse>;
 
-		fn unreceived_acknowledgements(channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<u64>>;
+		/// Returns a page of the client states
+		fn clients_page(pallet_prefix: Vec<u8>, page: PageRequest) -> Option<Page<IdentifiedClientState>>;
 
-		fn next_seq_recv(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryNextSequenceReceiveResponse>;
+		/// Returns a page of the connection ends
+		fn connections_page(pallet_prefix: Vec<u8>, page: PageRequest) -> Option<Page<IdentifiedConnection>>;
 
-		fn packet_commitment(channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<QueryPacketCommitmentResponse>;
+		/// Returns a page of the channel ends
+		fn channels_page(pallet_prefix: Vec<u8>, page: PageRequest) -> Option<Page<IdentifiedChannel>>;
 
-		fn packet_acknowledgement(channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<QueryPacketAcknowledgementResponse>;
+		/// Returns a page of the packet commitments of a channel
+		fn packet_commitments_page(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, page: PageRequest) -> Option<Page<PacketState>>;
 
-		fn packet_receipt(channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<QueryPacketReceiptResponse>;
+		/// Returns a page of the packet acknowledgements of a channel
+		fn packet_acknowledgements_page(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, page: PageRequest) -> Option<Page<PacketState>>;
 
-		fn denom_trace(asset_id: AssetId) -> Option<QueryDenomTraceResponse>;
+		fn unreceived_packets(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<u64>>;
+
+		fn unreceived_acknowledgements(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<u64>>;
+
+		fn next_seq_recv(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryNextSequenceReceiveResponse>;
+
+		fn packet_commitment(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<QueryPacketCommitmentResponse>;
+
+		fn packet_acknowledgement(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<QueryPacketAcknowledgementResponse>;
+
+		fn packet_receipt(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<QueryPacketReceiptResponse>;
+
+		fn denom_trace(pallet_prefix: Vec<u8>, asset_id: AssetId) -> Option<QueryDenomTraceResponse>;
+
+		/// Returns the local asset id of an ibc denom, registered or derived from its hash
+		fn denom_asset_id(pallet_prefix: Vec<u8>, denom: Vec<u8>) -> Option<AssetId>;
+
+		/// Returns the trace of a received ibc denom from the hash it's known by as `ibc/{hash}`
+		fn denom_trace_by_hash(pallet_prefix: Vec<u8>, hash: Vec<u8>) -> Option<QueryDenomTraceResponse>;
 
 		/// Key is the asset id from which to start looking up results
-		fn denom_traces(key: Option<AssetId>, offset: Option<u32>, limit: u64, count_total: bool) -> QueryDenomTracesResponse;
+		fn denom_traces(pallet_prefix: Vec<u8>, key: Option<AssetId>, offset: Option<u32>, limit: u64, count_total: bool) -> QueryDenomTracesResponse;
 
-		fn block_events(extrinsic_index: Option<u32>) -> Vec<Result<pallet_ibc::events::IbcEvent, pallet_ibc::errors::IbcError>>;
+		fn block_events(pallet_prefix: Vec<u8>, extrinsic_index: Option<u32>) -> Vec<Result<pallet_ibc::events::IbcEvent, pallet_ibc::errors::IbcError>>;
 	}
 }
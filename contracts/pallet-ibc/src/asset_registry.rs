@@ -0,0 +1,163 @@
+//! Pluggable registry of the assets of ICS-20 denominations.
+//!
+//! The transfer module resolves denominations to local assets through
+//! `Config::IbcDenomToAssetIdConversion`. [`IbcDenomRegistry`] implements it on top of the
+//! denomination mappings of the pallet (see [`crate::denom`]) and of an [`AssetRegistry`], which
+//! creates the asset of a voucher the first time it's received and holds its metadata. Runtimes
+//! plug their assets or tokens pallet in by implementing [`AssetRegistry`], or with
+//! [`FungiblesRegistry`] for the pallets implementing the `fungibles` traits:
+//! ```rust
+//! # #[cfg(any())] // This is just to make the doc test compile
+//! type IbcDenomToAssetIdConversion = pallet_ibc::asset_registry::IbcDenomRegistry<
+//!     Runtime,
+//!     pallet_ibc::asset_registry::FungiblesRegistry<Assets, AccountId, IbcPalletAccount, ConstU128<1>>,
+//! >;
+//! ```
+
+use crate::{
+	denom::derive_asset_id, Config, DenomToAssetId, IbcAssetIds, IbcAssets, IbcDenoms, Pallet,
+};
+use alloc::string::String;
+use core::marker::PhantomData;
+use frame_support::traits::{
+	fungibles::{metadata, Create, Inspect},
+	Get,
+};
+use sp_runtime::{DispatchError, Either};
+use sp_std::{boxed::Box, vec::Vec};
+
+/// Decimals of the vouchers, which the ICS-20 denominations don't carry.
+pub const VOUCHER_DECIMALS: u8 = 12;
+
+/// Metadata of an asset.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AssetMetadata {
+	pub name: Vec<u8>,
+	pub symbol: Vec<u8>,
+	pub decimals: u8,
+}
+
+impl AssetMetadata {
+	/// Metadata of the voucher of `denom`, named after its full trace path and with its base
+	/// denomination as symbol.
+	pub fn voucher(denom: &str) -> Self {
+		let symbol = denom.rsplit('/').next().unwrap_or(denom);
+		Self {
+			name: denom.as_bytes().to_vec(),
+			symbol: symbol.as_bytes().to_vec(),
+			decimals: VOUCHER_DECIMALS,
+		}
+	}
+}
+
+/// Assets of the runtime that the ICS-20 vouchers are minted in.
+pub trait AssetRegistry<AssetId> {
+	type Error: Into<DispatchError>;
+
+	/// Returns true if the asset exists.
+	fn exists(asset_id: &AssetId) -> bool;
+
+	/// Creates the asset with its metadata.
+	fn create(asset_id: AssetId, metadata: AssetMetadata) -> Result<(), Self::Error>;
+
+	/// Returns the metadata of the asset, if it exists.
+	fn metadata(asset_id: &AssetId) -> Option<AssetMetadata>;
+}
+
+/// [`AssetRegistry`] of a pallet implementing the `fungibles` traits, e.g. `pallet-assets`. The
+/// assets are created as sufficient, owned by `Owner` and with a minimum balance of `MinBalance`.
+pub struct FungiblesRegistry<F, AccountId, Owner, MinBalance>(
+	PhantomData<(F, AccountId, Owner, MinBalance)>,
+);
+
+impl<F, AccountId, Owner, MinBalance> AssetRegistry<F::AssetId>
+	for FungiblesRegistry<F, AccountId, Owner, MinBalance>
+where
+	F: Create<AccountId> + metadata::Mutate<AccountId>,
+	Owner: Get<AccountId>,
+	MinBalance: Get<F::Balance>,
+{
+	type Error = DispatchError;
+
+	fn exists(asset_id: &F::AssetId) -> bool {
+		F::asset_exists(asset_id.clone())
+	}
+
+	fn create(asset_id: F::AssetId, metadata: AssetMetadata) -> Result<(), Self::Error> {
+		let owner = Owner::get();
+		F::create(asset_id.clone(), owner.clone(), true, MinBalance::get())?;
+		F::set(asset_id, &owner, metadata.name, metadata.symbol, metadata.decimals)
+	}
+
+	fn metadata(asset_id: &F::AssetId) -> Option<AssetMetadata> {
+		Self::exists(asset_id).then(|| AssetMetadata {
+			name: <F as metadata::Inspect<AccountId>>::name(asset_id.clone()),
+			symbol: <F as metadata::Inspect<AccountId>>::symbol(asset_id.clone()),
+			decimals: <F as metadata::Inspect<AccountId>>::decimals(asset_id.clone()),
+		})
+	}
+}
+
+/// Resolves the ICS-20 denominations to the assets of the [`AssetRegistry`]. A denomination
+/// without a registered asset id gets the id derived from its hash, and its asset is created when
+/// it's first received.
+pub struct IbcDenomRegistry<T, R, I = ()>(PhantomData<(T, R, I)>);
+
+impl<T, R, I> DenomToAssetId<T, I> for IbcDenomRegistry<T, R, I>
+where
+	T: Config<I>,
+	T::AssetId: From<u128>,
+	R: AssetRegistry<T::AssetId>,
+	I: 'static,
+{
+	type Error = DispatchError;
+
+	fn from_denom_to_asset_id(denom: &str) -> Result<T::AssetId, Self::Error> {
+		if let Some(asset_id) = Self::lookup_asset_id(denom) {
+			return Ok(asset_id)
+		}
+		let asset_id: T::AssetId = derive_asset_id(denom).into();
+		Pallet::<T, I>::register_denom(denom.as_bytes().to_vec(), asset_id.clone())
+			.map_err(Into::<DispatchError>::into)?;
+		if !R::exists(&asset_id) {
+			R::create(asset_id.clone(), AssetMetadata::voucher(denom)).map_err(Into::into)?;
+		}
+		Ok(asset_id)
+	}
+
+	fn lookup_asset_id(denom: &str) -> Option<T::AssetId> {
+		IbcDenoms::<T, I>::get(denom.as_bytes())
+	}
+
+	fn from_asset_id_to_denom(id: T::AssetId) -> Option<String> {
+		Pallet::<T, I>::asset_id_to_denom(id)
+	}
+
+	fn ibc_assets(start_key: Option<Either<T::AssetId, u32>>, limit: u64) -> IbcAssets<T::AssetId> {
+		let mut iterator: Box<dyn Iterator<Item = (T::AssetId, Vec<u8>)>> = match start_key {
+			None => Box::new(IbcAssetIds::<T, I>::iter()),
+			Some(Either::Left(asset_id)) => {
+				// the iteration from a key starts after it
+				let first = IbcAssetIds::<T, I>::get(asset_id.clone())
+					.map(|denom| (asset_id.clone(), denom));
+				let key = IbcAssetIds::<T, I>::hashed_key_for(asset_id);
+				Box::new(first.into_iter().chain(IbcAssetIds::<T, I>::iter_from(key)))
+			},
+			Some(Either::Right(offset)) =>
+				Box::new(IbcAssetIds::<T, I>::iter().skip(offset as usize)),
+		};
+		let denoms = iterator.by_ref().take(limit as usize).map(|(_, denom)| denom).collect();
+		IbcAssets {
+			denoms,
+			total_count: IbcAssetIds::<T, I>::count() as u64,
+			next_id: iterator.next().map(|(id, _)| id),
+		}
+	}
+}
+
+impl<T: Config<I>, R: AssetRegistry<T::AssetId>, I: 'static> IbcDenomRegistry<T, R, I> {
+	/// Returns the metadata of the asset of `denom`, if it exists.
+	pub fn metadata(denom: &str) -> Option<AssetMetadata> {
+		IbcDenoms::<T, I>::get(denom.as_bytes()).and_then(|asset_id| R::metadata(&asset_id))
+	}
+}
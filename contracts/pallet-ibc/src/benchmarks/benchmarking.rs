@@ -4,11 +4,16 @@
 use super::super::*;
 use crate::{
 	benchmarks::{
-		grandpa_benchmark_utils::{generate_finality_proof, GRANDPA_UPDATE_TIMESTAMP},
+		grandpa_benchmark_utils::{
+			generate_finality_proof, generate_upgrade_proof, GRANDPA_UPDATE_TIMESTAMP,
+		},
 		tendermint_benchmark_utils::*,
 	},
-	ics20::IbcModule,
-	ics23::client_states::ClientStates,
+	ics20::{
+		rate_limit::{Quota, TransferLimit},
+		IbcModule,
+	},
+	ics23::{client_states::ClientStates, consensus_states::ConsensusStates},
 	light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState},
 	routing::Context,
 	Any, Config,
@@ -26,12 +31,14 @@ use ibc::{
 	},
 	core::{
 		ics02_client::{
+			client_consensus::ConsensusState,
 			client_state::ClientState,
 			context::ClientKeeper,
 			height::Height,
 			msgs::{
 				create_client::{MsgCreateAnyClient, TYPE_URL},
 				update_client::{MsgUpdateAnyClient, TYPE_URL as UPDATE_CLIENT_TYPE_URL},
+				upgrade_client::{MsgUpgradeAnyClient, TYPE_URL as UPGRADE_CLIENT_TYPE_URL},
 			},
 		},
 		ics03_connection::{
@@ -75,7 +82,7 @@ use ibc_primitives::{get_channel_escrow_address, IbcHandler};
 use pallet_membership::Instance2;
 use scale_info::prelude::string::ToString;
 use sp_core::{crypto::AccountId32, Get};
-use sp_runtime::{traits::IdentifyAccount, DigestItem};
+use sp_runtime::{traits::IdentifyAccount, DigestItem, Perbill};
 use sp_std::vec;
 use tendermint_proto::Protobuf;
 
@@ -1284,7 +1291,42 @@ benchmarks! {
 		assert_eq!(client_state.latest_height(), Height::new(2000, 2));
 	}
 
-	cleanup_packets {
+	upgrade_grandpa_client {
+		let mut ctx = routing::Context::<T>::new();
+		let (client_state, mut consensus_state, _) = generate_finality_proof(1, 1);
+		let mut upgraded_client_state = client_state.clone();
+		upgraded_client_state.latest_para_height += 1;
+		let upgraded_client_state = AnyClientState::Grandpa(upgraded_client_state);
+		let upgraded_consensus_state = AnyConsensusState::Grandpa(consensus_state.clone());
+		let (root, proof) = generate_upgrade_proof(
+			upgraded_client_state.encode_to_vec().unwrap(),
+			upgraded_consensus_state.encode_to_vec().unwrap(),
+		);
+		consensus_state.root = root.as_bytes().to_vec().into();
+		let client_state = AnyClientState::Grandpa(client_state);
+		let client_id = ClientId::new(&client_state.client_type(), 0).unwrap();
+		let height = client_state.latest_height();
+		ctx.store_client_type(client_id.clone(), client_state.client_type()).unwrap();
+		ctx.store_client_state(client_id.clone(), client_state).unwrap();
+		ctx.store_consensus_state(client_id.clone(), height, AnyConsensusState::Grandpa(consensus_state)).unwrap();
+		let msg = MsgUpgradeAnyClient::<routing::Context<T>> {
+			client_id: client_id.clone(),
+			client_state: upgraded_client_state,
+			consensus_state: upgraded_consensus_state,
+			proof_upgrade_client: proof.clone(),
+			proof_upgrade_consensus_state: proof,
+			signer: Signer::from_str("relayer").unwrap(),
+		};
+		let msg = Any { type_url: UPGRADE_CLIENT_TYPE_URL.to_string(), value: msg.encode_vec().unwrap() };
+		let caller: <T as frame_system::Config>::AccountId = relayer_origin::<T>();
+	}: deliver(RawOrigin::Signed(caller), vec![msg])
+	verify {
+		let client_state = ClientStates::<T>::get(&client_id).unwrap();
+		let client_state = AnyClientState::decode_vec(&*client_state).unwrap();
+		assert_eq!(client_state.latest_height(), Height::new(2000, 2));
+	}
+
+	packet_cleanup {
 		let i in 1..100u32;
 		let i = i as u64;
 		let data = vec![0u8;i.try_into().unwrap()];
@@ -1377,6 +1419,197 @@ benchmarks! {
 			assert_eq!(last_removed_ack, i - 1);
 		}
 	}
+
+	upgrade_client {
+		let params = UpgradeParams { client_state: vec![1u8; 1024], consensus_state: vec![1u8; 1024] };
+	}: _(RawOrigin::Root, params)
+	verify {
+		assert_eq!(sp_io::storage::get(b"client-state-upgrade-path").map(|state| state.len()), Some(1024));
+	}
+
+	freeze_client {
+		let client_id = store_tendermint_client::<T>(0, 2, 1);
+	}: _(RawOrigin::Root, client_id.as_bytes().to_vec(), 2)
+	verify {
+		let client_state = ClientStates::<T>::get(&client_id).unwrap();
+		let client_state = AnyClientState::decode_vec(&*client_state).unwrap();
+		assert!(client_state.frozen_height().is_some());
+	}
+
+	unfreeze_client {
+		let client_id = store_tendermint_client::<T>(0, 2, 1);
+		Pallet::<T>::freeze_client(RawOrigin::Root.into(), client_id.as_bytes().to_vec(), 2).unwrap();
+	}: _(RawOrigin::Root, client_id.as_bytes().to_vec())
+	verify {
+		let client_state = ClientStates::<T>::get(&client_id).unwrap();
+		let client_state = AnyClientState::decode_vec(&*client_state).unwrap();
+		assert!(client_state.frozen_height().is_none());
+	}
+
+	// i is the number of consensus states of the recovered client, which are all removed
+	recover_client {
+		let i in 1..250u32;
+		let subject = store_tendermint_client::<T>(0, i as u64, i as u64);
+		let substitute = store_tendermint_client::<T>(1, i as u64 + 1, 1);
+		Pallet::<T>::freeze_client(RawOrigin::Root.into(), subject.as_bytes().to_vec(), 1).unwrap();
+	}: _(RawOrigin::Root, subject.as_bytes().to_vec(), substitute.as_bytes().to_vec())
+	verify {
+		assert_eq!(ConsensusStateHeights::<T>::get(subject.as_bytes().to_vec()).len(), 1);
+	}
+
+	add_channels_to_feeless_channel_list {
+	}: _(RawOrigin::Root, 0, 1)
+	verify {
+		assert!(FeeLessChannelIds::<T>::contains_key((0, 1)));
+	}
+
+	remove_channels_from_feeless_channel_list {
+		Pallet::<T>::add_channels_to_feeless_channel_list(RawOrigin::Root.into(), 0, 1).unwrap();
+	}: _(RawOrigin::Root, 0, 1)
+	verify {
+		assert!(!FeeLessChannelIds::<T>::contains_key((0, 1)));
+	}
+
+	set_child_storage {
+		let key = b"clients/07-tendermint-0/clientState".to_vec();
+	}: _(RawOrigin::Root, key, vec![1u8; 1024])
+
+	substitute_client_state {
+		let client_id = store_tendermint_client::<T>(0, 2, 1);
+		let (client_state, cs_state) = create_mock_state();
+		let client_state = AnyClientState::Tendermint(client_state).encode_vec().unwrap();
+		let cs_state = AnyConsensusState::Tendermint(cs_state).encode_vec().unwrap();
+	}: _(RawOrigin::Root, client_id.to_string(), Height::new(0, 3), client_state, cs_state)
+	verify {
+		assert!(ConsensusStates::<T>::get(client_id, Height::new(0, 3)).is_some());
+	}
+
+	set_unsigned_client_update_interval {
+	}: _(RawOrigin::Root, Some(10u32.into()))
+	verify {
+		assert_eq!(UnsignedClientUpdateInterval::<T>::get(), Some(10u32.into()));
+	}
+
+	set_denom_asset_id {
+		let denom = b"transfer/channel-0/uatom".to_vec();
+		let asset_id: <T as Config>::AssetId = 1_000u128.into();
+	}: _(RawOrigin::Root, denom.clone(), Some(asset_id.clone()))
+	verify {
+		assert_eq!(IbcDenoms::<T>::get(&denom), Some(asset_id));
+	}
+
+	// i is the number of consensus states of the client, all but one are removed
+	set_consensus_states_limit {
+		let i in 1..250u32;
+		let client_id = store_tendermint_client::<T>(0, i as u64, i as u64);
+	}: _(RawOrigin::Root, client_id.as_bytes().to_vec(), Some(1))
+	verify {
+		assert_eq!(ConsensusStateHeights::<T>::get(client_id.as_bytes().to_vec()).len(), 1);
+	}
+
+	set_transfer_limit {
+		let asset_id: <T as Config>::AssetId = 1_000u128.into();
+		let limit = TransferLimit {
+			send: Some(Quota::Supply(Perbill::from_percent(10))),
+			receive: Some(Quota::Amount(1_000_000u128.into())),
+			period: 100u32.into(),
+			escrow_cap: Some(10_000_000u128.into()),
+		};
+	}: _(RawOrigin::Root, 0, asset_id.clone(), Some(limit.clone()))
+	verify {
+		assert_eq!(TransferLimits::<T>::get(0, asset_id), Some(limit));
+	}
+
+	set_packet_retention_period {
+	}: _(RawOrigin::Root, Some(100u32.into()))
+	verify {
+		assert_eq!(PacketRetentionPeriod::<T>::get(), Some(100u32.into()));
+	}
+
+	// i is the number of timed out packets received at the pruned block, whose receipts are all
+	// removed
+	prune_retained_packets {
+		let i in 1..1000u32;
+		let i = i as u64;
+		frame_system::Pallet::<T>::set_block_number(1u32.into());
+		Pallet::<T>::set_packet_retention_period(RawOrigin::Root.into(), Some(1u32.into())).unwrap();
+		let mut ctx = routing::Context::<T>::new();
+		let channel_id = ChannelId::new(0);
+		let port_id = PortId::transfer();
+		let channel_end = ChannelEnd::default();
+		ctx.store_channel((port_id.clone(), channel_id), &channel_end).unwrap();
+
+		for i in 1..=i {
+			let packet = Packet {
+				sequence: i.into(),
+				source_port: port_id.clone(),
+				source_channel: channel_id,
+				destination_port: port_id.clone(),
+				destination_channel: channel_id,
+				data: "hello".as_bytes().to_vec(),
+				timeout_height: Height::new(0, 1),
+				timeout_timestamp: Default::default(),
+			};
+			ctx.store_packet_receipt((port_id.clone(), channel_id, i.into()), Receipt::Ok)
+				.unwrap();
+			ctx.store_recv_packet((port_id.clone(), channel_id, i.into()), packet).unwrap();
+			ctx.store_packet_acknowledgement(
+				(port_id.clone(), channel_id, i.into()),
+				"commitment".as_bytes().to_vec().into(),
+			)
+			.unwrap();
+		}
+		let block: <T as frame_system::Config>::BlockNumber = 1u32.into();
+		assert_eq!(RetainedPackets::<T>::decode_len(block), Some(i as usize));
+	}: { Pallet::<T>::prune_retained_packets(2u32.into(), Weight::MAX) }
+	verify {
+		assert_eq!(RetentionCursor::<T>::get(), 2u32.into());
+		assert_eq!(AcknowledgementCounter::<T>::get(), i as u32);
+		assert_eq!(PacketReceiptCounter::<T>::get(), 0);
+	}
+
+	bind_port {
+		let port_id = b"bound".to_vec();
+		let module_id = ibc::applications::transfer::MODULE_ID_STR.as_bytes().to_vec();
+	}: _(RawOrigin::Root, port_id.clone(), module_id.clone())
+	verify {
+		assert_eq!(PortBindings::<T>::get(port_id), Some(module_id));
+	}
+}
+
+/// Stores the tendermint client `07-tendermint-{index}` at `latest_height`, with the consensus
+/// states of its `consensus_states` highest heights.
+fn store_tendermint_client<T: Config + Send + Sync>(
+	index: u64,
+	latest_height: u64,
+	consensus_states: u64,
+) -> ClientId
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	use ibc::core::ics02_client::context::ClientReader;
+
+	let mut ctx = routing::Context::<T>::new();
+	let (mut client_state, cs_state) = create_mock_state();
+	client_state.latest_height = Height::new(0, latest_height);
+	let client_state = AnyClientState::Tendermint(client_state);
+	let client_id = ClientId::new(&client_state.client_type(), index).unwrap();
+	ctx.store_client_type(client_id.clone(), client_state.client_type()).unwrap();
+	ctx.store_client_state(client_id.clone(), client_state).unwrap();
+	for height in latest_height + 1 - consensus_states..=latest_height {
+		let height = Height::new(0, height);
+		ctx.store_consensus_state(
+			client_id.clone(),
+			height,
+			AnyConsensusState::Tendermint(cs_state.clone()),
+		)
+		.unwrap();
+		let host_timestamp = ClientReader::host_timestamp(&ctx);
+		let host_height = ClientReader::host_height(&ctx);
+		ctx.store_update_time(client_id.clone(), height, host_timestamp).unwrap();
+		ctx.store_update_height(client_id.clone(), height, host_height).unwrap();
+	}
+	client_id
 }
 
 fn set_timestamp<T: pallet_timestamp::Config + pallet_aura::Config>(
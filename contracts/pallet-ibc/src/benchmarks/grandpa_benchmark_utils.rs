@@ -8,6 +8,7 @@ use grandpa_client_primitives::{
 };
 use ibc::{timestamp::Timestamp, Height};
 use ics10_grandpa::{
+	client_def::{CLIENT_STATE_UPGRADE_PATH, CONSENSUS_STATE_UPGRADE_PATH},
 	client_message::{ClientMessage, Header as GrandpaHeader, RelayChainHeader},
 	client_state::ClientState,
 	consensus_state::ConsensusState,
@@ -158,6 +159,7 @@ pub fn generate_finality_proof(
 		para_id,
 		current_set_id: set_id,
 		current_authorities: authorities.into_iter().map(|authority| (authority, 100)).collect(),
+		unbonding_period: None,
 		_phantom: Default::default(),
 	};
 
@@ -172,3 +174,21 @@ pub fn generate_finality_proof(
 
 	(client_state, consensus_state, client_message)
 }
+
+/// Builds the proof of the upgraded client and consensus states stored in the state of the
+/// parachain, returning the state root they are proved against and the encoded proof nodes.
+pub fn generate_upgrade_proof(client_state: Vec<u8>, consensus_state: Vec<u8>) -> (H256, Vec<u8>) {
+	let mut db = MemoryDB::<BlakeTwo256>::default();
+	let mut root = Default::default();
+	let state_root = {
+		let mut trie = TrieDBMutBuilder::<LayoutV0<BlakeTwo256>>::new(&mut db, &mut root).build();
+		trie.insert(CLIENT_STATE_UPGRADE_PATH, &client_state).unwrap();
+		trie.insert(CONSENSUS_STATE_UPGRADE_PATH, &consensus_state).unwrap();
+		*trie.root()
+	};
+	let proof = StorageProof::new(db.drain().into_iter().map(|(_, (val, ..))| val.to_vec()))
+		.into_nodes()
+		.into_iter()
+		.collect::<Vec<_>>();
+	(state_root, proof.encode())
+}
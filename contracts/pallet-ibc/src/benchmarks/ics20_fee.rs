@@ -0,0 +1,34 @@
+//! Benchmarks of the ICS20 service charge extrinsics
+
+use crate::ics20_fee::{Config, FeeLessChannelIds, Pallet, ServiceChargeIn};
+use frame_benchmarking::benchmarks;
+use frame_system::RawOrigin;
+use sp_runtime::Perbill;
+
+benchmarks! {
+	// Run these benchmarks via
+	// ```bash
+	// cargo +nightly test -p pallet-ibc  --features=runtime-benchmarks
+	// ```
+	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+
+	set_charge {
+		let charge = Perbill::from_rational(4u32, 1000u32);
+	}: _(RawOrigin::Root, charge)
+	verify {
+		assert_eq!(ServiceChargeIn::<T>::get(), Some(charge));
+	}
+
+	add_channels_to_feeless_channel_list {
+	}: _(RawOrigin::Root, 0, 1)
+	verify {
+		assert!(FeeLessChannelIds::<T>::contains_key((0, 1)));
+	}
+
+	remove_channels_from_feeless_channel_list {
+		Pallet::<T>::add_channels_to_feeless_channel_list(RawOrigin::Root.into(), 0, 1).unwrap();
+	}: _(RawOrigin::Root, 0, 1)
+	verify {
+		assert!(!FeeLessChannelIds::<T>::contains_key((0, 1)));
+	}
+}
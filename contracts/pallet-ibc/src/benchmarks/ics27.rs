@@ -0,0 +1,175 @@
+//! Benchmarks of the interchain accounts extrinsics and of the transactions executed by the host
+
+use crate::{
+	ics27::{
+		controller_port_id, host_port_id, Config, ControllerChannels, ControllerEnabled, Event,
+		HostEnabled, IcaHost, InterchainAccountPacketData, InterchainAccounts, Metadata, Pallet,
+	},
+	routing::Context,
+	Any, Timeout,
+};
+use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+use ibc::{
+	core::{
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty, Order, State},
+			context::{ChannelKeeper, ChannelReader},
+			packet::Packet,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module, ModuleOutputBuilder},
+	},
+	signer::Signer,
+	timestamp::Timestamp,
+	Height,
+};
+use ibc_primitives::IbcHandler;
+use ibc_proto::ibc::applications::interchain_accounts::v1::{CosmosTx, Type};
+use prost::Message;
+use scale_info::prelude::string::{String, ToString};
+use sp_core::crypto::AccountId32;
+use sp_std::{str::FromStr, vec, vec::Vec};
+
+fn assert_last_event<T: Config>(event: <T as Config>::RuntimeEvent) {
+	frame_system::Pallet::<T>::assert_last_event(event.into());
+}
+
+/// Messages of the runtime calls of a transaction, `size` bytes in total.
+fn remarks<T: Config>(calls: u32, size: u32) -> Vec<Any>
+where
+	<T as Config>::RuntimeCall: From<frame_system::Call<T>>,
+{
+	(0..calls)
+		.map(|_| {
+			let call: <T as Config>::RuntimeCall =
+				frame_system::Call::<T>::remark { remark: vec![1; (size / calls) as usize] }.into();
+			Pallet::<T>::runtime_call_message(&call)
+		})
+		.collect()
+}
+
+benchmarks! {
+	where_clause {
+		where u32: From<<T as frame_system::Config>::BlockNumber>,
+				<T as frame_system::Config>::BlockNumber: From<u32>,
+				T: Send + Sync + Config,
+				<T as Config>::RuntimeCall: From<frame_system::Call<T>>,
+		AccountId32: From<<T as frame_system::Config>::AccountId>,
+	}
+
+	// Run these benchmarks via
+	// ```bash
+	// cargo +nightly test -p pallet-ibc  --features=runtime-benchmarks
+	// ```
+	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+
+	set_params {
+	}: _(RawOrigin::Root, true, true)
+	verify {
+		assert!(HostEnabled::<T>::get() && ControllerEnabled::<T>::get());
+	}
+
+	register_interchain_account {
+		let caller: T::AccountId = whitelisted_caller();
+		let client_id = crate::Pallet::<T>::create_client().unwrap();
+		let connection_id = ConnectionId::new(0);
+		crate::Pallet::<T>::create_connection(client_id, connection_id.clone()).unwrap();
+		ControllerEnabled::<T>::put(true);
+	}: _(RawOrigin::Signed(caller.clone()), connection_id.as_bytes().to_vec())
+	verify {
+		let port_id = controller_port_id::<T>(&caller).unwrap();
+		let channel_end = Context::<T>::new().channel_end(&(port_id, ChannelId::new(0))).unwrap();
+		assert_eq!(channel_end.state, State::Init);
+	}
+
+	// i is the size in bytes of the messages of the transaction
+	send_tx {
+		let i in 1..100_000u32;
+		let caller: T::AccountId = whitelisted_caller();
+		let client_id = crate::Pallet::<T>::create_client().unwrap();
+		let connection_id = ConnectionId::new(0);
+		crate::Pallet::<T>::create_connection(client_id, connection_id.clone()).unwrap();
+		ControllerEnabled::<T>::put(true);
+		let port_id = controller_port_id::<T>(&caller).unwrap();
+		let channel_id = ChannelId::new(0);
+		let mut metadata = Metadata::new(&connection_id, &ConnectionId::new(1));
+		metadata.address = "cosmos1ica".to_string();
+		let channel_end = ChannelEnd::new(
+			State::Open,
+			Order::Ordered,
+			Counterparty::new(host_port_id(), Some(ChannelId::new(1))),
+			vec![connection_id.clone()],
+			metadata.to_version().unwrap(),
+		);
+		let mut ctx = Context::<T>::new();
+		ctx.store_channel((port_id.clone(), channel_id), &channel_end).unwrap();
+		ctx.store_next_sequence_send((port_id.clone(), channel_id), 1.into()).unwrap();
+		ControllerChannels::<T>::insert(
+			connection_id.as_bytes(),
+			port_id.as_bytes(),
+			channel_id.to_string().as_bytes().to_vec(),
+		);
+		let messages = remarks::<T>(1, i);
+		let timeout = Timeout::Offset { timestamp: Some(1690894363), height: Some(2000) };
+	}: _(RawOrigin::Signed(caller), connection_id.as_bytes().to_vec(), messages, vec![], timeout)
+	verify {
+		assert!(ctx.get_packet_commitment(&(port_id, channel_id, 1.into())).is_ok());
+	}
+
+	// i is the number of calls of the transaction
+	execute_tx {
+		let i in 1..<T as Config>::MaxCalls::get();
+		frame_system::Pallet::<T>::set_block_number(1u32.into());
+		HostEnabled::<T>::put(true);
+		let connection_id = ConnectionId::new(0);
+		let channel_id = ChannelId::new(0);
+		let controller_port_id = PortId::from_str("icacontroller-0x0101").unwrap();
+		let channel_end = ChannelEnd::new(
+			State::Open,
+			Order::Ordered,
+			Counterparty::new(controller_port_id.clone(), Some(channel_id)),
+			vec![connection_id.clone()],
+			Metadata::new(&ConnectionId::new(1), &connection_id).to_version().unwrap(),
+		);
+		let mut ctx = Context::<T>::new();
+		ctx.store_channel((host_port_id(), channel_id), &channel_end).unwrap();
+		let account: T::AccountId = whitelisted_caller();
+		InterchainAccounts::<T>::insert(
+			connection_id.as_bytes().to_vec(),
+			controller_port_id.as_bytes().to_vec(),
+			account.clone(),
+		);
+		let tx = CosmosTx {
+			messages: remarks::<T>(i, i).into_iter().map(Into::into).collect(),
+		};
+		let data = InterchainAccountPacketData {
+			ty: Type::ExecuteTx.as_str_name().to_string(),
+			data: tx.encode_to_vec(),
+			memo: String::new(),
+		};
+		let mut packet = Packet {
+			sequence: 1u64.into(),
+			source_port: controller_port_id,
+			source_channel: channel_id,
+			destination_port: host_port_id(),
+			destination_channel: channel_id,
+			data: serde_json::to_vec(&data).unwrap(),
+			timeout_height: Height::new(0, 100),
+			timeout_timestamp: Timestamp::none(),
+		};
+		let host = IcaHost::<T>::default();
+		let mut output = ModuleOutputBuilder::new();
+		let signer = Signer::from_str("relayer").unwrap();
+	}: {
+		host.on_recv_packet(&ctx, &mut output, &mut packet, &signer).unwrap();
+	}
+	verify {
+		assert_last_event::<T>(Event::<T>::TxExecuted {
+			account,
+			channel_id: channel_id.to_string().as_bytes().to_vec(),
+			sequence: 1,
+			success: true,
+		}.into());
+	}
+}
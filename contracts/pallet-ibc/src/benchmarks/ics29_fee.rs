@@ -0,0 +1,108 @@
+//! Benchmarks of the fee middleware extrinsics
+
+use crate::{
+	ics29_fee::{
+		Config, CounterpartyPayees, Fee, FeeEnabledChannels, FeesInEscrow, PacketFee, Pallet,
+		Payees,
+	},
+	routing::Context,
+};
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::traits::{Currency, Get};
+use frame_system::RawOrigin;
+use ibc::core::{
+	ics04_channel::{commitment::PacketCommitment, context::ChannelKeeper},
+	ics24_host::identifier::{ChannelId, PortId},
+};
+use sp_core::crypto::AccountId32;
+use sp_std::{vec, vec::Vec};
+
+const MILLIS: u128 = 1_000_000;
+
+/// Key of a fee enabled channel.
+fn fee_enabled_channel<T: Config>() -> (Vec<u8>, Vec<u8>) {
+	let key = (PortId::transfer().as_bytes().to_vec(), ChannelId::new(0).to_string().into_bytes());
+	FeeEnabledChannels::<T>::insert(key.clone(), ());
+	key
+}
+
+fn packet_fee<T: Config>() -> Fee<T::AssetId, T::Balance> {
+	Fee {
+		asset_id: <T as crate::Config>::NativeAssetId::get(),
+		recv_fee: (300 * MILLIS).into(),
+		ack_fee: (200 * MILLIS).into(),
+		timeout_fee: (100 * MILLIS).into(),
+	}
+}
+
+/// Funds the payer, and escrows all but one of the fees of the packet, so that the fees appended
+/// to the storage are the most expensive to decode and encode.
+fn escrow_fees<T: Config>(payer: &T::AccountId, key: (Vec<u8>, Vec<u8>), sequence: u64) {
+	<<T as crate::Config>::NativeCurrency as Currency<T::AccountId>>::make_free_balance_be(
+		payer,
+		(1_000_000 * MILLIS).into(),
+	);
+	let fees = (1..<T as Config>::MaxFeesPerPacket::get())
+		.map(|_| PacketFee { fee: packet_fee::<T>(), refund_account: payer.clone() })
+		.collect::<Vec<_>>();
+	FeesInEscrow::<T>::insert(key, sequence, sp_runtime::BoundedVec::truncate_from(fees));
+}
+
+benchmarks! {
+	where_clause {
+		where u32: From<<T as frame_system::Config>::BlockNumber>,
+				T: Send + Sync + Config,
+			AccountId32: From<<T as frame_system::Config>::AccountId>,
+	}
+
+	// Run these benchmarks via
+	// ```bash
+	// cargo +nightly test -p pallet-ibc  --features=runtime-benchmarks
+	// ```
+	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+
+	register_payee {
+		let caller: T::AccountId = whitelisted_caller();
+		let payee: T::AccountId = account("payee", 0, 0);
+		let (port_id, channel_id) = fee_enabled_channel::<T>();
+	}: _(RawOrigin::Signed(caller.clone()), port_id, channel_id.clone(), payee.clone())
+	verify {
+		assert_eq!(Payees::<T>::get(channel_id, caller), Some(payee));
+	}
+
+	register_counterparty_payee {
+		let caller: T::AccountId = whitelisted_caller();
+		let counterparty_payee = b"cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu".to_vec();
+		let (port_id, channel_id) = fee_enabled_channel::<T>();
+	}: _(RawOrigin::Signed(caller.clone()), port_id, channel_id.clone(), counterparty_payee.clone())
+	verify {
+		assert_eq!(CounterpartyPayees::<T>::get(channel_id, caller), Some(counterparty_payee));
+	}
+
+	pay_packet_fee {
+		let caller: T::AccountId = whitelisted_caller();
+		let key = fee_enabled_channel::<T>();
+		Context::<T>::new()
+			.store_next_sequence_send((PortId::transfer(), ChannelId::new(0)), 1.into())
+			.unwrap();
+		escrow_fees::<T>(&caller, key.clone(), 1);
+	}: _(RawOrigin::Signed(caller), key.0.clone(), key.1.clone(), packet_fee::<T>())
+	verify {
+		assert_eq!(FeesInEscrow::<T>::get(key, 1).len() as u32, <T as Config>::MaxFeesPerPacket::get());
+	}
+
+	pay_packet_fee_async {
+		let caller: T::AccountId = whitelisted_caller();
+		let key = fee_enabled_channel::<T>();
+		Context::<T>::new()
+			.store_packet_commitment(
+				(PortId::transfer(), ChannelId::new(0), 1.into()),
+				PacketCommitment::from(vec![1; 32]),
+			)
+			.unwrap();
+		escrow_fees::<T>(&caller, key.clone(), 1);
+	}: _(RawOrigin::Signed(caller), key.0.clone(), key.1.clone(), 1, packet_fee::<T>())
+	verify {
+		assert_eq!(FeesInEscrow::<T>::get(key, 1).len() as u32, <T as Config>::MaxFeesPerPacket::get());
+	}
+}
@@ -0,0 +1,180 @@
+//! Benchmarks of the non-fungible token transfers and of the callbacks of the non-fungible token
+//! transfer module
+
+use crate::{
+	ics721::{
+		class_prefix, port_id, Config, Event, NftMetadata, NftTransfer, NonFungibleTokenPacketData,
+		NonFungibles, Pallet, VERSION,
+	},
+	routing::Context,
+	MultiAddress, Timeout, TransferParams,
+};
+use alloc::{
+	format,
+	string::{String, ToString},
+};
+use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+use ibc::{
+	core::{
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty, Order, State},
+			context::{ChannelKeeper, ChannelReader},
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId},
+		ics26_routing::context::{Module, ModuleOutputBuilder},
+	},
+	signer::Signer,
+	timestamp::Timestamp,
+	Height,
+};
+use ibc_primitives::IbcHandler;
+use sp_core::crypto::AccountId32;
+use sp_std::{str::FromStr, vec, vec::Vec};
+
+fn assert_last_event<T: Config>(event: <T as Config>::RuntimeEvent) {
+	frame_system::Pallet::<T>::assert_last_event(event.into());
+}
+
+fn token_ids(n: u32) -> Vec<String> {
+	(0..n).map(|i| i.to_string()).collect()
+}
+
+/// Opens a non-fungible token transfer channel, `channel-0`, to `channel-1` of the counterparty.
+fn open_channel<T: Config>() -> ChannelId {
+	let client_id = crate::Pallet::<T>::create_client().unwrap();
+	let connection_id = ConnectionId::new(0);
+	crate::Pallet::<T>::create_connection(client_id, connection_id.clone()).unwrap();
+	let channel_id = ChannelId::new(0);
+	let channel_end = ChannelEnd::new(
+		State::Open,
+		Order::Unordered,
+		Counterparty::new(port_id(), Some(ChannelId::new(1))),
+		vec![connection_id],
+		Version::new(VERSION.to_string()),
+	);
+	let mut ctx = Context::<T>::new();
+	ctx.store_channel((port_id(), channel_id), &channel_end).unwrap();
+	ctx.store_next_sequence_send((port_id(), channel_id), 1.into()).unwrap();
+	channel_id
+}
+
+/// Packet of the transfer of `n` tokens of the class, received on `channel-0`.
+fn packet(sender: String, receiver: String, class_id: String, n: u32) -> Packet {
+	let data = NonFungibleTokenPacketData {
+		class_id,
+		class_uri: "ipfs://class".to_string(),
+		token_ids: token_ids(n),
+		token_uris: (0..n).map(|i| format!("ipfs://class/{i}")).collect(),
+		sender,
+		receiver,
+		..Default::default()
+	};
+	Packet {
+		sequence: 1u64.into(),
+		source_port: port_id(),
+		source_channel: ChannelId::new(1),
+		destination_port: port_id(),
+		destination_channel: ChannelId::new(0),
+		data: serde_json::to_vec(&data).unwrap(),
+		timeout_height: Height::new(0, 100),
+		timeout_timestamp: Timestamp::none(),
+	}
+}
+
+benchmarks! {
+	where_clause {
+		where u32: From<<T as frame_system::Config>::BlockNumber>,
+				<T as frame_system::Config>::BlockNumber: From<u32>,
+				T: Send + Sync + Config,
+			AccountId32: From<<T as frame_system::Config>::AccountId>,
+	}
+
+	// Run these benchmarks via
+	// ```bash
+	// cargo +nightly test -p pallet-ibc  --features=runtime-benchmarks
+	// ```
+	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+
+	// n is the number of tokens transferred
+	nft_transfer {
+		let n in 1..<T as Config>::MaxTokensPerTransfer::get();
+		let caller: T::AccountId = whitelisted_caller();
+		let channel_id = open_channel::<T>();
+		let class_id = "punks";
+		T::NonFungibles::create_class(class_id, NftMetadata::default()).unwrap();
+		for token_id in token_ids(n) {
+			T::NonFungibles::mint(class_id, &token_id, &caller, NftMetadata::default()).unwrap();
+		}
+		let params = TransferParams {
+			to: MultiAddress::Raw(b"cosmos1receiver".to_vec()),
+			source_channel: channel_id.sequence(),
+			timeout: Timeout::Offset { timestamp: Some(1690894363), height: Some(2000) },
+		};
+		let tokens = token_ids(n).into_iter().map(String::into_bytes).collect::<Vec<_>>();
+	}: transfer(RawOrigin::Signed(caller), class_id.as_bytes().to_vec(), tokens, params, vec![])
+	verify {
+		assert!(Context::<T>::new().get_packet_commitment(&(port_id(), channel_id, 1.into())).is_ok());
+	}
+
+	// n is the number of vouchers minted
+	on_recv_packet {
+		let n in 1..<T as Config>::MaxTokensPerTransfer::get();
+		frame_system::Pallet::<T>::set_block_number(1u32.into());
+		let receiver: T::AccountId = whitelisted_caller();
+		let mut packet = packet(
+			"cosmos1sender".to_string(),
+			Pallet::<T>::address(&receiver),
+			"wizards".to_string(),
+			n,
+		);
+		let ctx = Context::<T>::new();
+		let mut output = ModuleOutputBuilder::new();
+		let signer = Signer::from_str("relayer").unwrap();
+	}: {
+		NftTransfer::<T>::default().on_recv_packet(&ctx, &mut output, &mut packet, &signer).unwrap();
+	}
+	verify {
+		assert_last_event::<T>(Event::<T>::NftReceived {
+			from: b"cosmos1sender".to_vec(),
+			to: receiver,
+			class_id: b"nft-transfer/channel-0/wizards".to_vec(),
+			token_ids: token_ids(n).into_iter().map(String::into_bytes).collect(),
+			channel_id: b"channel-0".to_vec(),
+		}.into());
+	}
+
+	// n is the number of vouchers minted back to the sender
+	on_timeout_packet {
+		let n in 1..<T as Config>::MaxTokensPerTransfer::get();
+		frame_system::Pallet::<T>::set_block_number(1u32.into());
+		let sender: T::AccountId = whitelisted_caller();
+		let class_id = format!("{}wizards", class_prefix(&port_id(), &ChannelId::new(0)));
+		T::NonFungibles::create_class(&class_id, NftMetadata::default()).unwrap();
+		// the packet is sent from channel-0, the vouchers having been burnt
+		let mut packet = packet(
+			Pallet::<T>::address(&sender),
+			"cosmos1receiver".to_string(),
+			class_id.clone(),
+			n,
+		);
+		packet.source_channel = ChannelId::new(0);
+		packet.destination_channel = ChannelId::new(1);
+		let ctx = Context::<T>::new();
+		let mut output = ModuleOutputBuilder::new();
+		let signer = Signer::from_str("relayer").unwrap();
+	}: {
+		NftTransfer::<T>::default().on_timeout_packet(&ctx, &mut output, &mut packet, &signer).unwrap();
+	}
+	verify {
+		assert_last_event::<T>(Event::<T>::NftTransferRefunded {
+			to: sender,
+			class_id: class_id.into_bytes(),
+			token_ids: token_ids(n).into_iter().map(String::into_bytes).collect(),
+			channel_id: b"channel-0".to_vec(),
+			sequence: 1,
+		}.into());
+	}
+}
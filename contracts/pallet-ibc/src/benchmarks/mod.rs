@@ -1,6 +1,18 @@
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod ics20_fee;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod ics27;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod ics29_fee;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod ics721;
+
 #[cfg(feature = "runtime-benchmarks")]
 pub mod tendermint_benchmark_utils;
 
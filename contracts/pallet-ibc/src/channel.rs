@@ -32,7 +32,7 @@ use ibc::{
 };
 use tendermint_proto::Protobuf;
 
-impl<T: Config + Sync + Send> ChannelReader for Context<T>
+impl<T: Config<I> + Sync + Send, I: 'static> ChannelReader for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 {
@@ -43,7 +43,7 @@ where
 			port_channel_id.0,
 			port_channel_id.1
 		);
-		let data = <Channels<T>>::get(port_channel_id.0.clone(), port_channel_id.1)
+		let data = <Channels<T, I>>::get(port_channel_id.0.clone(), port_channel_id.1)
 			.ok_or_else(ICS04Error::missing_channel)?;
 		let channel_end = ChannelEnd::decode_vec(&data).map_err(|_| {
 			ICS04Error::channel_not_found(port_channel_id.clone().0, port_channel_id.clone().1)
@@ -56,8 +56,8 @@ where
 		&self,
 		conn_id: &ConnectionId,
 	) -> Result<Vec<(PortId, ChannelId)>, ICS04Error> {
-		if <ChannelsConnection<T>>::contains_key(conn_id.as_bytes()) {
-			let port_and_channel_id = <ChannelsConnection<T>>::get(conn_id.as_bytes());
+		if <ChannelsConnection<T, I>>::contains_key(conn_id.as_bytes()) {
+			let port_and_channel_id = <ChannelsConnection<T, I>>::get(conn_id.as_bytes());
 
 			let mut result = vec![];
 
@@ -101,7 +101,7 @@ where
 		&self,
 		port_channel_id: &(PortId, ChannelId),
 	) -> Result<Sequence, ICS04Error> {
-		let seq = <NextSequenceSend<T>>::get(port_channel_id.0.clone(), port_channel_id.1)
+		let seq = <NextSequenceSend<T, I>>::get(port_channel_id.0.clone(), port_channel_id.1)
 			.ok_or_else(|| ICS04Error::missing_next_send_seq(port_channel_id.clone()))?;
 		log::trace!(target: "pallet_ibc", "in channel : [get_next_sequence] >> sequence  = {:?}", seq);
 		Ok(Sequence::from(seq))
@@ -111,7 +111,7 @@ where
 		&self,
 		port_channel_id: &(PortId, ChannelId),
 	) -> Result<Sequence, ICS04Error> {
-		let seq = <NextSequenceRecv<T>>::get(port_channel_id.0.clone(), port_channel_id.1)
+		let seq = <NextSequenceRecv<T, I>>::get(port_channel_id.0.clone(), port_channel_id.1)
 			.ok_or_else(|| ICS04Error::missing_next_recv_seq(port_channel_id.clone()))?;
 
 		log::trace!(target: "pallet_ibc", "in channel : [get_next_sequence_recv] >> sequence = {:?}", seq);
@@ -122,7 +122,7 @@ where
 		&self,
 		port_channel_id: &(PortId, ChannelId),
 	) -> Result<Sequence, ICS04Error> {
-		let seq = <NextSequenceAck<T>>::get(port_channel_id.0.clone(), port_channel_id.1)
+		let seq = <NextSequenceAck<T, I>>::get(port_channel_id.0.clone(), port_channel_id.1)
 			.ok_or_else(|| ICS04Error::missing_next_ack_seq(port_channel_id.clone()))?;
 		log::trace!(target: "pallet_ibc", "in channel : [get_next_sequence_ack] >> sequence = {:?}", seq);
 		Ok(Sequence::from(seq))
@@ -132,8 +132,8 @@ where
 		&self,
 		key: &(PortId, ChannelId, Sequence),
 	) -> Result<PacketCommitmentType, ICS04Error> {
-		if <PacketCommitment<T>>::contains_key((key.0.clone(), key.1, key.2)) {
-			let data = <PacketCommitment<T>>::get((key.0.clone(), key.1, key.2))
+		if <PacketCommitment<T, I>>::contains_key((key.0.clone(), key.1, key.2)) {
+			let data = <PacketCommitment<T, I>>::get((key.0.clone(), key.1, key.2))
 				.ok_or_else(ICS04Error::missing_packet)?;
 			log::trace!(target: "pallet_ibc", "in channel : [get_packet_commitment] >> packet_commitment = {:?}", data);
 			Ok(data.into())
@@ -151,8 +151,8 @@ where
 	) -> Result<Receipt, ICS04Error> {
 		let seq = u64::from(key.2);
 
-		if <PacketReceipt<T>>::contains_key((key.0.clone(), key.1, key.2)) {
-			let data = <PacketReceipt<T>>::get((key.0.clone(), key.1, key.2))
+		if <PacketReceipt<T, I>>::contains_key((key.0.clone(), key.1, key.2)) {
+			let data = <PacketReceipt<T, I>>::get((key.0.clone(), key.1, key.2))
 				.ok_or_else(|| ICS04Error::packet_receipt_not_found(key.2))?;
 			let data = String::from_utf8(data).map_err(|e| {
 				ICS04Error::implementation_specific(format!(
@@ -175,8 +175,8 @@ where
 		&self,
 		key: &(PortId, ChannelId, Sequence),
 	) -> Result<AcknowledgementCommitment, ICS04Error> {
-		if <Acknowledgements<T>>::contains_key((key.0.clone(), key.1, key.2)) {
-			let ack = <Acknowledgements<T>>::get((key.0.clone(), key.1, key.2))
+		if <Acknowledgements<T, I>>::contains_key((key.0.clone(), key.1, key.2)) {
+			let ack = <Acknowledgements<T, I>>::get((key.0.clone(), key.1, key.2))
 				.ok_or_else(|| ICS04Error::packet_acknowledgement_not_found(key.2))?;
 			log::trace!(target: "pallet_ibc",
 				"in channel : [get_packet_acknowledgement] >> packet_acknowledgement = {:?}",
@@ -206,7 +206,7 @@ where
 			.map_err(|e| ICS04Error::ics02_client(ICS02Error::encode(e)))?;
 		let client_id_bytes = client_id.as_bytes().to_vec();
 		let timestamp =
-			ClientUpdateTime::<T>::get(client_id_bytes, encoded_height).ok_or_else(|| {
+			ClientUpdateTime::<T, I>::get(client_id_bytes, encoded_height).ok_or_else(|| {
 				ICS04Error::implementation_specific(format!(
 					"[client_update_time]:  client update timestamp not found for {client_id} at height: {height}"
 				))
@@ -229,7 +229,7 @@ where
 			.encode_vec()
 			.map_err(|e| ICS04Error::ics02_client(ICS02Error::encode(e)))?;
 		let client_id_bytes = client_id.as_bytes().to_vec();
-		let host_height = ClientUpdateHeight::<T>::get(client_id_bytes, encoded_height)
+		let host_height = ClientUpdateHeight::<T, I>::get(client_id_bytes, encoded_height)
 			.ok_or_else(|| {
 				ICS04Error::implementation_specific(format!(
 					"[client_update_time]:  client update height not found for {client_id} at height: {height}"
@@ -248,7 +248,7 @@ where
 	/// The value of this counter should increase only via method
 	/// `ChannelKeeper::increase_channel_counter`.
 	fn channel_counter(&self) -> Result<u64, ICS04Error> {
-		let count = ChannelCounter::<T>::get();
+		let count = ChannelCounter::<T, I>::get();
 		log::trace!(target: "pallet_ibc", "in channel: [channel_counter] >> channel_counter = {:?}", count);
 		Ok(count.into())
 	}
@@ -259,7 +259,7 @@ where
 	}
 }
 
-impl<T: Config + Sync + Send> ChannelKeeper for Context<T>
+impl<T: Config<I> + Sync + Send, I: 'static> ChannelKeeper for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
@@ -271,9 +271,9 @@ where
 		commitment: PacketCommitmentType,
 	) -> Result<(), ICS04Error> {
 		log::trace!(target: "pallet_ibc", "in channel : [store_packet_commitment] >> packet_commitment {key:?} = {:#?}", commitment);
-		<PacketCommitment<T>>::insert((key.0.clone(), key.1, key.2), commitment);
-		if let Some(val) = PacketCounter::<T>::get().checked_add(1) {
-			PacketCounter::<T>::put(val);
+		<PacketCommitment<T, I>>::insert((key.0.clone(), key.1, key.2), commitment);
+		if let Some(val) = PacketCounter::<T, I>::get().checked_add(1) {
+			PacketCounter::<T, I>::put(val);
 		}
 
 		Ok(())
@@ -289,13 +289,13 @@ where
 		let port_id = key.0.as_bytes().to_vec();
 		let seq = u64::from(key.2);
 		let channel_end = ChannelReader::channel_end(self, &(key.0, key.1))?;
-		let key = Pallet::<T>::send_packet_key(channel_id, port_id, seq);
+		let key = Pallet::<T, I>::send_packet_key(channel_id, port_id, seq);
 
 		let mut packet_info: PacketInfo = packet.into();
 		packet_info.height = Some(host_height::<T>());
 		packet_info.channel_order = channel_end.ordering as u8;
 
-		SendPackets::<T>::insert(&key, packet_info.encode());
+		SendPackets::<T, I>::insert(&key, packet_info.encode());
 		log::trace!(target: "pallet_ibc", "in channel: [store_send_packet] >> writing packet {:?} {:?}", key, packet_info);
 		Ok(())
 	}
@@ -310,11 +310,12 @@ where
 		let port_id = key.0.as_bytes().to_vec();
 		let seq = u64::from(key.2);
 		let channel_end = ChannelReader::channel_end(self, &(key.0, key.1))?;
-		let key = Pallet::<T>::recv_packet_key(channel_id, port_id, seq);
+		Pallet::<T, I>::retain_received_packet(&packet);
+		let key = Pallet::<T, I>::recv_packet_key(channel_id, port_id, seq);
 		let mut packet_info: PacketInfo = packet.into();
 		packet_info.height = Some(host_height::<T>());
 		packet_info.channel_order = channel_end.ordering as u8;
-		RecvPackets::<T>::insert(&key, packet_info.encode());
+		RecvPackets::<T, I>::insert(&key, packet_info.encode());
 		log::trace!(target: "pallet_ibc", "in channel: [store_recv_packet] >> writing packet {:?} {:?}", key, packet_info);
 		Ok(())
 	}
@@ -324,10 +325,10 @@ where
 		key: (PortId, ChannelId, Sequence),
 	) -> Result<(), ICS04Error> {
 		// delete packet commitment
-		<PacketCommitment<T>>::remove((key.0.clone(), key.1, key.2));
+		<PacketCommitment<T, I>>::remove((key.0.clone(), key.1, key.2));
 
-		if let Some(val) = PacketCounter::<T>::get().checked_sub(1) {
-			PacketCounter::<T>::put(val);
+		if let Some(val) = PacketCounter::<T, I>::get().checked_sub(1) {
+			PacketCounter::<T, I>::put(val);
 		}
 
 		Ok(())
@@ -342,10 +343,10 @@ where
 			Receipt::Ok => b"Ok".to_vec(),
 		};
 
-		<PacketReceipt<T>>::insert((key.0.clone(), key.1, key.2), receipt);
+		<PacketReceipt<T, I>>::insert((key.0.clone(), key.1, key.2), receipt);
 
-		if let Some(val) = <PacketReceiptCounter<T>>::get().checked_add(1) {
-			<PacketReceiptCounter<T>>::put(val)
+		if let Some(val) = <PacketReceiptCounter<T, I>>::get().checked_add(1) {
+			<PacketReceiptCounter<T, I>>::put(val)
 		}
 
 		Ok(())
@@ -356,7 +357,7 @@ where
 		key: (PortId, ChannelId, Sequence),
 		ack: Acknowledgement,
 	) -> Result<(), ICS04Error> {
-		Pallet::<T>::store_raw_acknowledgement(key, ack.into_bytes()).map_err(|_| {
+		Pallet::<T, I>::store_raw_acknowledgement(key, ack.into_bytes()).map_err(|_| {
 			ICS04Error::implementation_specific("Error storing acknowledgement".to_string())
 		})
 	}
@@ -367,10 +368,10 @@ where
 		ack_commitment: AcknowledgementCommitment,
 	) -> Result<(), ICS04Error> {
 		// store packet acknowledgement key-value
-		<Acknowledgements<T>>::insert((key.0.clone(), key.1, key.2), ack_commitment);
+		<Acknowledgements<T, I>>::insert((key.0.clone(), key.1, key.2), ack_commitment);
 
-		if let Some(val) = AcknowledgementCounter::<T>::get().checked_add(1) {
-			AcknowledgementCounter::<T>::put(val)
+		if let Some(val) = AcknowledgementCounter::<T, I>::get().checked_add(1) {
+			AcknowledgementCounter::<T, I>::put(val)
 		}
 
 		Ok(())
@@ -381,10 +382,10 @@ where
 		key: (PortId, ChannelId, Sequence),
 	) -> Result<(), ICS04Error> {
 		// remove acknowledgements
-		<Acknowledgements<T>>::remove((key.0.clone(), key.1, key.2));
+		<Acknowledgements<T, I>>::remove((key.0.clone(), key.1, key.2));
 
-		if let Some(val) = AcknowledgementCounter::<T>::get().checked_sub(1) {
-			AcknowledgementCounter::<T>::put(val)
+		if let Some(val) = AcknowledgementCounter::<T, I>::get().checked_sub(1) {
+			AcknowledgementCounter::<T, I>::put(val)
 		}
 		Ok(())
 	}
@@ -401,9 +402,9 @@ where
 			port_channel_id.1.to_string().as_bytes().to_vec(),
 		);
 
-		if <ChannelsConnection<T>>::contains_key(conn_id.clone()) {
+		if <ChannelsConnection<T, I>>::contains_key(conn_id.clone()) {
 			log::trace!(target: "pallet_ibc", "in channel: [store_connection_channels] >> insert port_channel_id");
-			<ChannelsConnection<T>>::try_mutate(conn_id, |val| -> Result<(), &'static str> {
+			<ChannelsConnection<T, I>>::try_mutate(conn_id, |val| -> Result<(), &'static str> {
 				val.push(port_channel_id);
 				Ok(())
 			})
@@ -411,7 +412,7 @@ where
 		} else {
 			log::trace!(target: "pallet_ibc", "in channel: [store_connection_channels] >> init ChannelsConnection");
 			let temp_connection_channels = vec![port_channel_id];
-			<ChannelsConnection<T>>::insert(conn_id, temp_connection_channels);
+			<ChannelsConnection<T, I>>::insert(conn_id, temp_connection_channels);
 		}
 
 		Ok(())
@@ -424,7 +425,7 @@ where
 		channel_end: &ChannelEnd,
 	) -> Result<(), ICS04Error> {
 		// store channels key-value
-		<Channels<T>>::insert(port_channel_id.0.clone(), port_channel_id.1, channel_end);
+		<Channels<T, I>>::insert(port_channel_id.0.clone(), port_channel_id.1, channel_end);
 
 		Ok(())
 	}
@@ -436,7 +437,7 @@ where
 	) -> Result<(), ICS04Error> {
 		let seq = u64::from(seq);
 
-		<NextSequenceSend<T>>::insert(port_channel_id.0.clone(), port_channel_id.1, seq);
+		<NextSequenceSend<T, I>>::insert(port_channel_id.0.clone(), port_channel_id.1, seq);
 
 		Ok(())
 	}
@@ -448,7 +449,7 @@ where
 	) -> Result<(), ICS04Error> {
 		let seq = u64::from(seq);
 
-		<NextSequenceRecv<T>>::insert(port_channel_id.0.clone(), port_channel_id.1, seq);
+		<NextSequenceRecv<T, I>>::insert(port_channel_id.0.clone(), port_channel_id.1, seq);
 
 		Ok(())
 	}
@@ -460,7 +461,7 @@ where
 	) -> Result<(), ICS04Error> {
 		let seq = u64::from(seq);
 
-		<NextSequenceAck<T>>::insert(port_channel_id.0.clone(), port_channel_id.1, seq);
+		<NextSequenceAck<T, I>>::insert(port_channel_id.0.clone(), port_channel_id.1, seq);
 
 		Ok(())
 	}
@@ -470,7 +471,7 @@ where
 	/// Should never fail.
 	fn increase_channel_counter(&mut self) {
 		log::trace!(target: "pallet_ibc", "in channel: [increase_channel_counter]");
-		let _ = ChannelCounter::<T>::try_mutate::<_, (), _>(|val| {
+		let _ = ChannelCounter::<T, I>::try_mutate::<_, (), _>(|val| {
 			*val = val.saturating_add(1);
 			Ok(())
 		});
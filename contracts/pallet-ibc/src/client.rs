@@ -33,7 +33,7 @@ pub struct HostConsensusProof {
 	pub code_id: Option<Vec<u8>>,
 }
 
-impl<T: Config + Send + Sync> ClientReader for Context<T>
+impl<T: Config<I> + Send + Sync, I: 'static> ClientReader for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	<T as frame_system::Config>::BlockNumber: From<u32>,
@@ -41,8 +41,8 @@ where
 	fn client_type(&self, client_id: &ClientId) -> Result<ClientType, ICS02Error> {
 		log::trace!(target: "pallet_ibc", "in client : [client_type] >> client_id = {:?}", client_id);
 
-		if <Clients<T>>::contains_key(client_id) {
-			let data = <Clients<T>>::get(client_id)
+		if <Clients<T, I>>::contains_key(client_id) {
+			let data = <Clients<T, I>>::get(client_id)
 				.ok_or_else(|| ICS02Error::client_not_found(client_id.clone()))?;
 			let data = String::from_utf8(data).map_err(|e| {
 				ICS02Error::implementation_specific(format!(
@@ -58,7 +58,7 @@ where
 
 	fn client_state(&self, client_id: &ClientId) -> Result<AnyClientState, ICS02Error> {
 		log::trace!(target: "pallet_ibc", "in client : [client_state] >> client_id = {:?}", client_id);
-		let data = <ClientStates<T>>::get(client_id)
+		let data = <ClientStates<T, I>>::get(client_id)
 			.ok_or_else(|| ICS02Error::client_not_found(client_id.clone()))?;
 		let state = AnyClientState::decode_vec(&data)
 			.map_err(|_| ICS02Error::client_not_found(client_id.clone()))?;
@@ -78,7 +78,7 @@ where
 		);
 
 		let native_height = height;
-		let value = <ConsensusStates<T>>::get(client_id.clone(), height)
+		let value = <ConsensusStates<T, I>>::get(client_id.clone(), height)
 			.ok_or_else(|| ICS02Error::consensus_state_not_found(client_id.clone(), height))?;
 
 		let any_consensus_state = AnyConsensusState::decode_vec(&value)
@@ -95,7 +95,7 @@ where
 		if cfg!(any(test, feature = "runtime-benchmarks")) {
 			"tendermint".to_string()
 		} else {
-			match <T as Config>::LightClientProtocol::get() {
+			match <T as Config<I>>::LightClientProtocol::get() {
 				LightClientProtocol::Beefy => "beefy".to_string(),
 				LightClientProtocol::Grandpa => "grandpa".to_string(),
 			}
@@ -107,7 +107,7 @@ where
 		client_id: &ClientId,
 		height: Height,
 	) -> Result<Option<AnyConsensusState>, ICS02Error> {
-		let consensus_heights = ConsensusHeights::<T>::get(client_id.as_bytes().to_vec());
+		let consensus_heights = ConsensusHeights::<T, I>::get(client_id.as_bytes().to_vec());
 		let cs_state = consensus_heights
 			.into_iter()
 			.find(|next_height| next_height > &height)
@@ -121,7 +121,7 @@ where
 		client_id: &ClientId,
 		height: Height,
 	) -> Result<Option<AnyConsensusState>, ICS02Error> {
-		let consensus_heights = ConsensusHeights::<T>::get(client_id.as_bytes().to_vec());
+		let consensus_heights = ConsensusHeights::<T, I>::get(client_id.as_bytes().to_vec());
 		let cs_state = consensus_heights
 			.into_iter()
 			.filter(|prev_height| prev_height < &height)
@@ -158,7 +158,7 @@ where
 		let timestamp = Timestamp::from_nanoseconds(1).unwrap();
 		let timestamp = timestamp.into_tm_time().unwrap();
 
-		let consensus_state = match <T as Config>::LightClientProtocol::get() {
+		let consensus_state = match <T as Config<I>>::LightClientProtocol::get() {
 			crate::LightClientProtocol::Beefy =>
 				AnyConsensusState::Beefy(ics11_beefy::consensus_state::ConsensusState {
 					timestamp,
@@ -263,7 +263,7 @@ where
 		};
 
 		// now this header can be trusted
-		let consensus_state = match <T as Config>::LightClientProtocol::get() {
+		let consensus_state = match <T as Config<I>>::LightClientProtocol::get() {
 			crate::LightClientProtocol::Beefy => {
 				let cs_state = ics11_beefy::consensus_state::ConsensusState {
 					timestamp,
@@ -297,21 +297,21 @@ where
 	}
 
 	fn client_counter(&self) -> Result<u64, ICS02Error> {
-		let count = ClientCounter::<T>::get();
+		let count = ClientCounter::<T, I>::get();
 		log::trace!(target: "pallet_ibc", "in client : [client_counter] >> client_counter: {:?}", count);
 
 		Ok(count as u64)
 	}
 }
 
-impl<T: Config> ClientTypes for Context<T> {
+impl<T: Config<I>, I: 'static> ClientTypes for Context<T, I> {
 	type AnyClientMessage = AnyClientMessage;
 	type AnyClientState = AnyClientState;
 	type AnyConsensusState = AnyConsensusState;
 	type ClientDef = AnyClient;
 }
 
-impl<T: Config + Send + Sync> ClientKeeper for Context<T>
+impl<T: Config<I> + Send + Sync, I: 'static> ClientKeeper for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 {
@@ -327,7 +327,7 @@ where
 		);
 
 		let client_type = client_type.as_bytes().to_vec();
-		<Clients<T>>::insert(&client_id, client_type);
+		<Clients<T, I>>::insert(&client_id, client_type);
 		Ok(())
 	}
 
@@ -344,7 +344,7 @@ where
 
 		let data = client_state.encode_to_vec().map_err(ICS02Error::encode)?;
 		// store client states key-value
-		<ClientStates<T>>::insert(&client_id, data);
+		<ClientStates<T, I>>::insert(&client_id, data);
 
 		Ok(())
 	}
@@ -359,13 +359,12 @@ where
 			client_id, height, consensus_state);
 
 		let data = consensus_state.encode_to_vec().map_err(ICS02Error::encode)?;
-		// todo: pruning
-		ConsensusStates::<T>::insert(client_id.clone(), height, data);
+		ConsensusStates::<T, I>::insert(client_id.clone(), height, data);
 		// We do not need this hack for neither beefy nor grandpa clients
 		if !client_id.as_str().starts_with("10-grandpa") &&
 			!client_id.as_str().starts_with("11-beefy")
 		{
-			let mut stored_heights = ConsensusHeights::<T>::get(client_id.as_bytes().to_vec());
+			let mut stored_heights = ConsensusHeights::<T, I>::get(client_id.as_bytes().to_vec());
 			if let Err(val) = stored_heights.try_insert(height) {
 				let first =
 					*stored_heights.iter().next().expect("Cannot fail as a value always exists");
@@ -374,7 +373,7 @@ where
 					.try_insert(val)
 					.expect("Cannot panic, since bounds cannot be exceeded at this point");
 			}
-			ConsensusHeights::<T>::insert(client_id.as_bytes().to_vec(), stored_heights);
+			ConsensusHeights::<T, I>::insert(client_id.as_bytes().to_vec(), stored_heights);
 		}
 
 		Ok(())
@@ -383,8 +382,8 @@ where
 	fn increase_client_counter(&mut self) {
 		log::trace!(target: "pallet_ibc", "in client : [increase_client_counter]");
 		// increment counter
-		if let Some(val) = <ClientCounter<T>>::get().checked_add(1) {
-			<ClientCounter<T>>::put(val);
+		if let Some(val) = <ClientCounter<T, I>>::get().checked_add(1) {
+			<ClientCounter<T, I>>::put(val);
 		}
 	}
 
@@ -398,7 +397,7 @@ where
 		let height = height.encode_vec().map_err(ICS02Error::encode)?;
 		let timestamp = timestamp.nanoseconds();
 		let client_id = client_id.as_bytes().to_vec();
-		ClientUpdateTime::<T>::insert(client_id, height, timestamp);
+		ClientUpdateTime::<T, I>::insert(client_id, height, timestamp);
 		Ok(())
 	}
 
@@ -412,7 +411,7 @@ where
 		let height = height.encode_vec().map_err(ICS02Error::encode)?;
 		let host_height = host_height.encode_vec().map_err(ICS02Error::encode)?;
 		let client_id = client_id.as_bytes().to_vec();
-		ClientUpdateHeight::<T>::insert(client_id, height, host_height);
+		ClientUpdateHeight::<T, I>::insert(client_id, height, host_height);
 		Ok(())
 	}
 
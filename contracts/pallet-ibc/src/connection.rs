@@ -17,7 +17,7 @@ use ibc::{
 };
 use tendermint_proto::Protobuf;
 
-impl<T: Config + Sync + Send> ConnectionReader for Context<T>
+impl<T: Config<I> + Sync + Send, I: 'static> ConnectionReader for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 {
@@ -28,7 +28,7 @@ where
 	fn connection_end(&self, conn_id: &ConnectionId) -> Result<ConnectionEnd, ICS03Error> {
 		log::trace!(target: "pallet_ibc", "in connection : [connection_end] >> connection_id = {:?}", conn_id);
 
-		let data = <Connections<T>>::get(conn_id)
+		let data = <Connections<T, I>>::get(conn_id)
 			.ok_or_else(|| ICS03Error::connection_not_found(conn_id.clone()))?;
 		let ret = ConnectionEnd::decode_vec(&data)
 			.map_err(|_| ICS03Error::connection_mismatch(conn_id.clone()))?;
@@ -62,14 +62,14 @@ where
 	}
 
 	fn connection_counter(&self) -> Result<u64, ICS03Error> {
-		let count = ConnectionCounter::<T>::get();
+		let count = ConnectionCounter::<T, I>::get();
 		log::trace!(target: "pallet_ibc", "in connection : [connection_counter] >> Connection_counter = {:?}", count);
 
 		Ok(count as u64)
 	}
 }
 
-impl<T: Config + Sync + Send> ConnectionKeeper for Context<T>
+impl<T: Config<I> + Sync + Send, I: 'static> ConnectionKeeper for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 {
@@ -84,7 +84,7 @@ where
 			connection_end
 		);
 
-		<Connections<T>>::insert(&connection_id, connection_end);
+		<Connections<T, I>>::insert(&connection_id, connection_end);
 
 		let temp = ConnectionReader::connection_end(self, &connection_id);
 		log::trace!(target: "pallet_ibc", "in connection : [store_connection] >> read store after: {:?}", temp);
@@ -103,7 +103,7 @@ where
 			client_id
 		);
 
-		ConnectionClient::<T>::try_mutate::<_, _, ICS03Error, _>(
+		ConnectionClient::<T, I>::try_mutate::<_, _, ICS03Error, _>(
 			client_id.as_bytes().to_vec(),
 			|val| {
 				val.push(connection_id.as_bytes().to_vec());
@@ -115,8 +115,8 @@ where
 	fn increase_connection_counter(&mut self) {
 		log::trace!(target: "pallet_ibc", "in connection : [increase_connection_counter]");
 		// connections uses a counted storage map
-		if let Some(val) = <ConnectionCounter<T>>::get().checked_add(1) {
-			<ConnectionCounter<T>>::put(val);
+		if let Some(val) = <ConnectionCounter<T, I>>::get().checked_add(1) {
+			<ConnectionCounter<T, I>>::put(val);
 		}
 	}
 }
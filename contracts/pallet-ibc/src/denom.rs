@@ -0,0 +1,100 @@
+//! Deterministic mapping between ICS-20 denominations and local asset ids.
+//!
+//! Unless it was registered explicitly, the asset id of a denomination is derived from the hash of
+//! its full trace path (`{port}/{channel}/.../{base_denom}`). Vouchers can thus be minted for the
+//! first transfer of a token without registering its asset beforehand, and the id can be computed
+//! off-chain (e.g. by a relayer) with [`derive_asset_id`].
+//!
+//! Explicit mappings live in [`IbcDenoms`] and [`IbcAssetIds`]. They take precedence over the
+//! derived ids and can be set by governance with [`Pallet::set_denom_asset_id`], e.g. to map a
+//! denomination to an asset that already exists.
+//!
+//! The traces of the received vouchers are kept in [`DenomTraces`] under the hash their
+//! denomination is known by on cosmos chains (`ibc/{hash}`), see [`denom_trace_hash`].
+
+use crate::{Config, DenomTraces, Error, IbcAssetIds, IbcDenoms, Pallet};
+use alloc::string::String;
+use sp_std::vec::Vec;
+
+/// Domain separator of the hash the asset ids are derived from.
+pub const DERIVED_ASSET_ID_DOMAIN: &[u8] = b"pallet-ibc/asset-id/";
+
+/// Bit set in every derived asset id, so that derived ids don't collide with the (sequential) ids
+/// of assets created by other means.
+pub const DERIVED_ASSET_ID_FLAG: u128 = 1 << 127;
+
+/// Returns the asset id derived from `denom`, its full trace path.
+pub fn derive_asset_id(denom: &str) -> u128 {
+	let hash = sp_io::hashing::blake2_256(&[DERIVED_ASSET_ID_DOMAIN, denom.as_bytes()].concat());
+	let mut id = [0u8; 16];
+	id.copy_from_slice(&hash[..16]);
+	u128::from_le_bytes(id) | DERIVED_ASSET_ID_FLAG
+}
+
+/// Returns true if `id` is in the range of the derived asset ids.
+pub fn is_derived_asset_id(id: u128) -> bool {
+	id & DERIVED_ASSET_ID_FLAG != 0
+}
+
+/// Returns the ICS-20 hash of `denom`, its full trace path.
+pub fn denom_trace_hash(denom: &str) -> [u8; 32] {
+	sp_io::hashing::sha2_256(denom.as_bytes())
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I>
+where
+	T::AssetId: From<u128>,
+{
+	/// Returns the asset id of `denom`: the one it was registered with, if any, else the derived
+	/// one. The asset itself may not exist yet.
+	pub fn denom_to_asset_id(denom: &str) -> T::AssetId {
+		IbcDenoms::<T, I>::get(denom.as_bytes()).unwrap_or_else(|| derive_asset_id(denom).into())
+	}
+
+	pub fn get_denom_asset_id(denom: Vec<u8>) -> Option<T::AssetId> {
+		String::from_utf8(denom).ok().map(|denom| Self::denom_to_asset_id(&denom))
+	}
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Returns the denomination registered for `asset_id`, if any.
+	pub fn asset_id_to_denom(asset_id: T::AssetId) -> Option<String> {
+		IbcAssetIds::<T, I>::get(asset_id).and_then(|denom| String::from_utf8(denom).ok())
+	}
+
+	/// Registers `asset_id` as the asset of `denom`, replacing its previous mapping. Fails if the
+	/// asset id is already mapped to another denomination.
+	pub fn register_denom(denom: Vec<u8>, asset_id: T::AssetId) -> Result<(), Error<T, I>> {
+		if IbcAssetIds::<T, I>::get(&asset_id).map_or(false, |existing| existing != denom) {
+			return Err(Error::<T, I>::AssetIdAlreadyMapped)
+		}
+		Self::unregister_denom(&denom);
+		IbcDenoms::<T, I>::insert(denom.clone(), asset_id.clone());
+		IbcAssetIds::<T, I>::insert(asset_id, denom);
+		Ok(())
+	}
+
+	/// Removes the registered mapping of `denom`, if any, so that it resolves to its derived id.
+	pub fn unregister_denom(denom: &[u8]) {
+		if let Some(asset_id) = IbcDenoms::<T, I>::take(denom) {
+			IbcAssetIds::<T, I>::remove(asset_id);
+		}
+	}
+
+	/// Records the trace of a received voucher, so that it can be looked up by its hash.
+	pub fn record_denom_trace(denom: &str) {
+		let hash = denom_trace_hash(denom);
+		if !DenomTraces::<T, I>::contains_key(hash) {
+			DenomTraces::<T, I>::insert(hash, denom.as_bytes().to_vec());
+		}
+	}
+
+	/// Returns the trace of a received voucher from its hash.
+	pub fn get_denom_trace_by_hash(
+		hash: Vec<u8>,
+	) -> Option<ibc_primitives::QueryDenomTraceResponse> {
+		let hash: [u8; 32] = hash.try_into().ok()?;
+		DenomTraces::<T, I>::get(hash)
+			.map(|denom| ibc_primitives::QueryDenomTraceResponse { denom })
+	}
+}
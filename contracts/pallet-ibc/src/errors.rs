@@ -39,10 +39,10 @@ impl From<RoutingError> for IbcError {
 	}
 }
 
-impl<T: Config> From<Vec<RoutingError>> for Event<T> {
+impl<T: Config<I>, I: 'static> From<Vec<RoutingError>> for Event<T, I> {
 	fn from(errors: Vec<RoutingError>) -> Self {
 		let errors: Vec<Result<_, IbcError>> =
 			errors.into_iter().map(|err| Err(err.into())).collect();
-		Event::<T>::Events { events: errors }
+		Event::<T, I>::Events { events: errors }
 	}
 }
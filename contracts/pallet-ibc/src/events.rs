@@ -446,14 +446,14 @@ impl From<RawIbcEvent> for IbcEvent {
 	}
 }
 
-impl<T: Config> From<Vec<RawIbcEvent>> for Event<T> {
+impl<T: Config<I>, I: 'static> From<Vec<RawIbcEvent>> for Event<T, I> {
 	fn from(events: Vec<RawIbcEvent>) -> Self {
 		let events: Vec<Result<IbcEvent, _>> = events.into_iter().map(|ev| Ok(ev.into())).collect();
 		Event::Events { events }
 	}
 }
 
-impl<T: Config> From<Vec<Result<RawIbcEvent, RoutingError>>> for Event<T> {
+impl<T: Config<I>, I: 'static> From<Vec<Result<RawIbcEvent, RoutingError>>> for Event<T, I> {
 	fn from(events: Vec<Result<RawIbcEvent, RoutingError>>) -> Self {
 		let events: Vec<Result<IbcEvent, IbcError>> = events
 			.into_iter()
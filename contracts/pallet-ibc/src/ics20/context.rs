@@ -17,7 +17,7 @@ use ibc_primitives::get_channel_escrow_address;
 use sp_core::crypto::AccountId32;
 use sp_runtime::traits::IdentifyAccount;
 
-impl<T: Config + Send + Sync> Ics20Reader for Context<T>
+impl<T: Config<I> + Send + Sync, I: 'static> Ics20Reader for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
@@ -49,7 +49,7 @@ where
 	}
 }
 
-impl<T: Config + Send + Sync> Ics20Keeper for Context<T>
+impl<T: Config<I> + Send + Sync, I: 'static> Ics20Keeper for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
@@ -57,7 +57,7 @@ where
 	type AccountId = T::AccountIdConversion;
 }
 
-impl<T: Config + Send + Sync> Ics20Context for Context<T>
+impl<T: Config<I> + Send + Sync, I: 'static> Ics20Context for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
@@ -65,9 +65,9 @@ where
 	type AccountId = T::AccountIdConversion;
 }
 
-impl<T> BankKeeper for Context<T>
+impl<T, I: 'static> BankKeeper for Context<T, I>
 where
-	T: Config + Send + Sync,
+	T: Config<I> + Send + Sync,
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
 {
@@ -82,8 +82,8 @@ where
 		let amount: T::Balance = amt.amount.as_u256().as_u128().into();
 		let denom = amt.denom.to_string();
 		// Token should be registered already if sending an ibc asset
-		let asset_id = T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(&denom)
-			.map_err(|_| Ics20Error::invalid_token())?;
+		let asset_id = T::IbcDenomToAssetIdConversion::lookup_asset_id(&denom)
+			.ok_or_else(Ics20Error::invalid_token)?;
 
 		if asset_id == T::NativeAssetId::get() {
 			<T::NativeCurrency as Currency<<T as frame_system::Config>::AccountId>>::transfer(
@@ -97,7 +97,7 @@ where
 				Ics20Error::invalid_token()
 			})?;
 		} else {
-			<<T as Config>::Fungibles as Mutate<<T as frame_system::Config>::AccountId>>::transfer(
+			<<T as Config<I>>::Fungibles as Mutate<<T as frame_system::Config>::AccountId>>::transfer(
 				asset_id.clone(),
 				&from.clone().into_account(),
 				&to.clone().into_account(),
@@ -123,8 +123,9 @@ where
 		// Find existing asset or create a new one
 		let asset_id = T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(&denom)
 			.map_err(|_err| Ics20Error::invalid_token())?;
+		Pallet::<T, I>::record_denom_trace(&denom);
 
-		<<T as Config>::Fungibles as Mutate<<T as frame_system::Config>::AccountId>>::mint_into(
+		<<T as Config<I>>::Fungibles as Mutate<<T as frame_system::Config>::AccountId>>::mint_into(
 			asset_id,
 			&account.clone().into_account(),
 			amount,
@@ -144,9 +145,9 @@ where
 		let amount: T::Balance = amt.amount.as_u256().as_u128().into();
 		let denom = amt.denom.to_string();
 		// Token should be registered already if burning a voucher
-		let asset_id = T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(&denom)
-			.map_err(|_| Ics20Error::invalid_token())?;
-		<<T as Config>::Fungibles as Mutate<<T as frame_system::Config>::AccountId>>::burn_from(
+		let asset_id = T::IbcDenomToAssetIdConversion::lookup_asset_id(&denom)
+			.ok_or_else(Ics20Error::invalid_token)?;
+		<<T as Config<I>>::Fungibles as Mutate<<T as frame_system::Config>::AccountId>>::burn_from(
 			asset_id,
 			&account.clone().into_account(),
 			amount,
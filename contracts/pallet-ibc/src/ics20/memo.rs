@@ -1,12 +1,20 @@
-use crate::{ics20::HandleMemo, Config};
+use crate::{
+	ics20::{full_ibc_denom, HandleMemo},
+	Config, DenomToAssetId, Event, Pallet,
+};
 use alloc::{
 	format,
 	string::{String, ToString},
 };
-use core::{fmt::Debug, str::FromStr};
+use core::{fmt::Debug, marker::PhantomData, str::FromStr};
+use frame_support::{
+	storage::{with_transaction, TransactionOutcome},
+	CloneNoBound, DebugNoBound, EqNoBound, PartialEqNoBound,
+};
 use ibc::{
 	applications::transfer::{
 		acknowledgement::Acknowledgement as Ics20Acknowledgement, error::Error as Ics20Error,
+		packet::PacketData,
 	},
 	core::{
 		ics04_channel::{
@@ -21,6 +29,8 @@ use ibc::{
 	},
 	signer::Signer,
 };
+use sp_core::crypto::AccountId32;
+use sp_runtime::{traits::IdentifyAccount, DispatchError};
 
 /// This middleware should be used to wrap ics20 to execute memo
 /// We chose to use this as a middleware so that we can easily choose
@@ -37,22 +47,32 @@ use ibc::{
 ///     >,
 /// }
 /// ```
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Memo<T: Config, S: Module + Clone + Default + PartialEq + Eq + Debug> {
+#[derive(CloneNoBound, DebugNoBound, PartialEqNoBound, EqNoBound)]
+pub struct Memo<T: Config<I>, S: Module + Clone + Default + PartialEq + Eq + Debug, I: 'static = ()>
+{
 	inner: S,
-	_phantom: core::marker::PhantomData<T>,
+	_phantom: core::marker::PhantomData<(T, I)>,
 }
 
-impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Debug> Default
-	for Memo<T, S>
+impl<
+		T: Config<I> + Send + Sync,
+		S: Module + Clone + Default + PartialEq + Eq + Debug,
+		I: 'static,
+	> Default for Memo<T, S, I>
 {
 	fn default() -> Self {
 		Self { inner: S::default(), _phantom: Default::default() }
 	}
 }
 
-impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Debug> Module
-	for Memo<T, S>
+impl<
+		T: Config<I> + Send + Sync,
+		S: Module + Clone + Default + PartialEq + Eq + Debug,
+		I: 'static,
+	> Module for Memo<T, S, I>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
 {
 	fn on_chan_open_init(
 		&mut self,
@@ -159,32 +179,53 @@ impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Deb
 		packet: &mut Packet,
 		relayer: &Signer,
 	) -> Result<Acknowledgement, Error> {
-		let ack = self.inner.on_recv_packet(ctx, output, packet, relayer)?;
-		let ics20_ack = Ics20Acknowledgement::from_str(&String::from_utf8_lossy(ack.as_ref()))
-			.map_err(|_| Error::invalid_acknowledgement())?;
+		// The tokens are credited to the receiver by the inner modules before the memo is
+		// executed, so the whole receipt is reverted if the memo fails, and an error
+		// acknowledgement is written for the sender to be refunded
+		let mut inner_output = ModuleOutputBuilder::new();
+		let mut memo_error = None;
+		// the inner modules may deduct their fees from the amount of the packet data
+		let received_packet = packet.clone();
+		let ack = with_transaction(|| {
+			let result = self
+				.inner
+				.on_recv_packet(ctx, &mut inner_output, packet, relayer)
+				.and_then(|ack| {
+					let ics20_ack =
+						Ics20Acknowledgement::from_str(&String::from_utf8_lossy(ack.as_ref()))
+							.map_err(|_| Error::invalid_acknowledgement())?;
+					// the memo is only executed if the transfer succeeded
+					if ics20_ack.is_successful() {
+						memo_error = Self::process_memo(packet).err();
+					}
+					Ok(ack)
+				});
+			if result.is_err() || memo_error.is_some() {
+				TransactionOutcome::Rollback(Ok(result))
+			} else {
+				TransactionOutcome::Commit(Ok(result))
+			}
+		})
+		.map_err(|e: DispatchError| Error::implementation_specific(format!("{e:?}")))??;
 
-		// we need to ensure that the previous hasn't failed. We do this, by ensuring that the ACK
-		// does not contain an error
-
-		if !ics20_ack.is_successful() {
+		let Some(err) = memo_error else {
+			output.merge(inner_output);
 			return Ok(ack)
-		}
-
-		// We want the whole chain of calls to fail only if the ics20 transfer fails, because
-		// the other modules are not part of ics-20 standard
-		match Self::process_memo(packet) {
-			Ok(_) => Ok(ack),
-			Err(err) => {
-				log::error!(target: "pallet_ibc", "Error while handling memo: {:?}", err);
-				Ok(Acknowledgement::from_bytes(
-					Ics20Acknowledgement::from_error(Ics20Error::implementation_specific(
-						err.to_string(),
-					))
-					.to_string()
-					.into_bytes(),
-				))
-			},
-		}
+		};
+		log::error!(target: "pallet_ibc", "Error while handling memo: {:?}", err);
+		*packet = received_packet;
+		let ack =
+			Ics20Acknowledgement::from_error(Ics20Error::implementation_specific(err.to_string()))
+				.to_string()
+				.into_bytes();
+		let memo = serde_json::from_slice::<PacketData>(packet.data.as_slice())
+			.map(|packet_data| packet_data.memo)
+			.unwrap_or_default();
+		Pallet::<T, I>::deposit_event(Event::<T, I>::ExecuteMemoFailed {
+			memo,
+			error: err.to_string(),
+		});
+		Ok(Acknowledgement::from_bytes(ack))
 	}
 
 	fn on_acknowledgement_packet(
@@ -210,11 +251,117 @@ impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Deb
 	}
 }
 
-impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Debug> Memo<T, S> {
+impl<
+		T: Config<I> + Send + Sync,
+		S: Module + Clone + Default + PartialEq + Eq + Debug,
+		I: 'static,
+	> Memo<T, S, I>
+{
 	fn process_memo(packet: &mut Packet) -> Result<(), Error> {
-		<T as Config>::HandleMemo::default().execute_memo(packet).map_err(|e| {
+		<T as Config<I>>::HandleMemo::default().execute_memo(packet).map_err(|e| {
 			Error::implementation_specific(format!("Failed to execute memo {:?}", e))
 		})?;
 		Ok(())
 	}
 }
+
+/// Transfer received with a JSON object memo, passed to the memo handlers once its tokens are
+/// credited to the receiver.
+pub struct ReceivedTransfer<'a, T: Config<I>, I: 'static = ()> {
+	pub packet: &'a Packet,
+	pub sender: String,
+	pub receiver: T::AccountId,
+	pub asset_id: T::AssetId,
+	pub amount: T::Balance,
+}
+
+/// Hook executed on the receipt of a transfer whose memo is a JSON object, e.g. to forward the
+/// tokens over XCM, call a pallet or invoke a smart contract. Each handler acts on the key of the
+/// memo it's meant for and ignores the others. If a handler fails, the receipt of the transfer is
+/// reverted and an error acknowledgement is written.
+pub trait MemoHandler<T: Config<I>, I: 'static = ()> {
+	fn on_recv(
+		transfer: &ReceivedTransfer<T, I>,
+		memo: &serde_json::Map<String, serde_json::Value>,
+	) -> Result<(), Ics20Error>;
+}
+
+impl<T: Config<I>, I: 'static> MemoHandler<T, I> for () {
+	fn on_recv(
+		_transfer: &ReceivedTransfer<T, I>,
+		_memo: &serde_json::Map<String, serde_json::Value>,
+	) -> Result<(), Ics20Error> {
+		Ok(())
+	}
+}
+
+macro_rules! impl_memo_handler_for_tuples {
+	($($handler:ident),+) => {
+		impl<T: Config<I>, I: 'static, $($handler: MemoHandler<T, I>),+> MemoHandler<T, I>
+			for ($($handler,)+)
+		{
+			fn on_recv(
+				transfer: &ReceivedTransfer<T, I>,
+				memo: &serde_json::Map<String, serde_json::Value>,
+			) -> Result<(), Ics20Error> {
+				$($handler::on_recv(transfer, memo)?;)+
+				Ok(())
+			}
+		}
+	};
+}
+
+impl_memo_handler_for_tuples!(A);
+impl_memo_handler_for_tuples!(A, B);
+impl_memo_handler_for_tuples!(A, B, C);
+impl_memo_handler_for_tuples!(A, B, C, D);
+impl_memo_handler_for_tuples!(A, B, C, D, E);
+
+/// Dispatches the JSON object memos of the received transfers to the memo handlers, in order.
+/// It's meant to be the inner handler of the `IbcMemoHandler`, which forwards the transfers whose
+/// memo has a `forward` key.
+/// USAGE:
+/// ```rust
+/// # #[cfg(any())] // This is just to make the doc test compile
+/// type HandleMemo = IbcMemoHandler<MemoHooks<(WasmHook, CallHook), Runtime>, Runtime>;
+/// ```
+pub struct MemoHooks<H, T, I = ()>(PhantomData<(H, T, I)>);
+
+impl<H, T, I> Default for MemoHooks<H, T, I> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<H: MemoHandler<T, I>, T: Config<I>, I: 'static> HandleMemo<T, I> for MemoHooks<H, T, I> {
+	fn execute_memo(&self, packet: &Packet) -> Result<(), Ics20Error> {
+		let packet_data: PacketData =
+			serde_json::from_slice(packet.data.as_slice()).map_err(|e| {
+				Ics20Error::implementation_specific(format!("Failed to decode packet data {e:?}"))
+			})?;
+		// the plain text memos aren't meant for the hooks
+		let Ok(serde_json::Value::Object(memo)) = serde_json::from_str(&packet_data.memo) else {
+			return Ok(())
+		};
+		let receiver = <T as Config<I>>::AccountIdConversion::try_from(packet_data.receiver)
+			.map_err(|_| {
+				Ics20Error::implementation_specific("Failed to parse receiver account".to_string())
+			})?
+			.into_account();
+		let denom = full_ibc_denom(packet, packet_data.token.clone());
+		let asset_id =
+			T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(&denom).map_err(|_| {
+				Ics20Error::implementation_specific(format!(
+					"Asset does not exist for denom {denom}"
+				))
+			})?;
+		let transfer = ReceivedTransfer {
+			packet,
+			sender: packet_data.sender.to_string(),
+			receiver,
+			asset_id,
+			amount: packet_data.token.amount.as_u256().low_u128().into(),
+		};
+		H::on_recv(&transfer, &memo)
+	}
+}
@@ -1,8 +1,10 @@
 pub mod context;
 pub mod memo;
+pub mod rate_limit;
 
 use crate::{
-	routing::Context, ChannelIds, Config, DenomToAssetId, Event, Pallet, SequenceFee, WeightInfo,
+	ics20::rate_limit::Direction, routing::Context, ChannelIds, Config, DenomToAssetId, Event,
+	ForwardedTransfers, MultiAddress, Pallet, SequenceFee, TransferParams, WeightInfo,
 };
 use alloc::{
 	format,
@@ -10,7 +12,7 @@ use alloc::{
 	string::{String, ToString},
 };
 
-use frame_support::weights::Weight;
+use frame_support::{weights::Weight, CloneNoBound, DebugNoBound, EqNoBound, PartialEqNoBound};
 pub use ibc::applications::transfer::{MODULE_ID_STR, PORT_ID_STR};
 use ibc::{
 	applications::transfer::{
@@ -30,6 +32,7 @@ use ibc::{
 	core::{
 		ics04_channel::{
 			channel::{Counterparty, Order},
+			context::ChannelReader,
 			error::Error as Ics04Error,
 			msgs::acknowledgement::Acknowledgement,
 			packet::Packet,
@@ -40,25 +43,25 @@ use ibc::{
 	},
 	signer::Signer,
 };
-use ibc_primitives::{CallbackWeight, HandlerMessage, IbcHandler};
+use ibc_primitives::{CallbackWeight, Timeout};
 use sp_core::crypto::AccountId32;
-use sp_runtime::traits::IdentifyAccount;
+use sp_runtime::{traits::IdentifyAccount, DispatchError, RuntimeDebug};
 use sp_std::marker::PhantomData;
 
 pub type Ics20TransferMsg = ibc::applications::transfer::msgs::transfer::MsgTransfer<
 	ibc::applications::transfer::Coin<ibc::applications::transfer::PrefixedDenom>,
 >;
 
-#[derive(Clone, Eq, Debug, PartialEq)]
-pub struct IbcModule<T: Config>(PhantomData<T>);
+#[derive(CloneNoBound, EqNoBound, DebugNoBound, PartialEqNoBound)]
+pub struct IbcModule<T: Config<I>, I: 'static = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> Default for IbcModule<T> {
+impl<T: Config<I>, I: 'static> Default for IbcModule<T, I> {
 	fn default() -> Self {
 		Self(PhantomData)
 	}
 }
 
-impl<T: Config + Send + Sync> Module for IbcModule<T>
+impl<T: Config<I> + Send + Sync, I: 'static> Module for IbcModule<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
@@ -75,7 +78,7 @@ where
 		version: &Version,
 		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
-		let mut ctx = Context::<T>::default();
+		let mut ctx = Context::<T, I>::default();
 		on_chan_open_init(
 			&mut ctx,
 			output,
@@ -102,7 +105,7 @@ where
 		counterparty_version: &Version,
 		_relayer: &Signer,
 	) -> Result<Version, Ics04Error> {
-		let mut ctx = Context::<T>::default();
+		let mut ctx = Context::<T, I>::default();
 		on_chan_open_try(
 			&mut ctx,
 			output,
@@ -126,11 +129,11 @@ where
 		counterparty_version: &Version,
 		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
-		let _ = ChannelIds::<T>::try_mutate::<_, (), _>(|channels| {
+		let _ = ChannelIds::<T, I>::try_mutate::<_, (), _>(|channels| {
 			channels.push(channel_id.to_string().as_bytes().to_vec());
 			Ok(())
 		});
-		let mut ctx = Context::<T>::default();
+		let mut ctx = Context::<T, I>::default();
 		on_chan_open_ack(&mut ctx, output, port_id, channel_id, counterparty_version)
 			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
 	}
@@ -143,11 +146,11 @@ where
 		channel_id: &ChannelId,
 		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
-		let _ = ChannelIds::<T>::try_mutate::<_, (), _>(|channels| {
+		let _ = ChannelIds::<T, I>::try_mutate::<_, (), _>(|channels| {
 			channels.push(channel_id.to_string().as_bytes().to_vec());
 			Ok(())
 		});
-		let mut ctx = Context::<T>::default();
+		let mut ctx = Context::<T, I>::default();
 		on_chan_open_confirm(&mut ctx, output, port_id, channel_id)
 			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
 	}
@@ -160,7 +163,7 @@ where
 		channel_id: &ChannelId,
 		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
-		let _ = ChannelIds::<T>::try_mutate::<_, (), _>(|channels| {
+		let _ = ChannelIds::<T, I>::try_mutate::<_, (), _>(|channels| {
 			let rem = channels
 				.iter()
 				.filter(|chan| chan.as_slice() != channel_id.to_string().as_bytes())
@@ -170,8 +173,8 @@ where
 			Ok(())
 		});
 		// Remove escrow address for closed channel if it exists
-		let _ = Pallet::<T>::remove_channel_escrow_address(port_id, *channel_id);
-		let mut ctx = Context::<T>::default();
+		let _ = Pallet::<T, I>::remove_channel_escrow_address(port_id, *channel_id);
+		let mut ctx = Context::<T, I>::default();
 		on_chan_close_init(&mut ctx, output, port_id, channel_id)
 			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
 	}
@@ -184,7 +187,7 @@ where
 		channel_id: &ChannelId,
 		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
-		let _ = ChannelIds::<T>::try_mutate::<_, (), _>(|channels| {
+		let _ = ChannelIds::<T, I>::try_mutate::<_, (), _>(|channels| {
 			let rem = channels
 				.iter()
 				.filter(|chan| chan.as_slice() != channel_id.to_string().as_bytes())
@@ -194,8 +197,8 @@ where
 			Ok(())
 		});
 		// Remove escrow address for closed channel if it exists
-		let _ = Pallet::<T>::remove_channel_escrow_address(port_id, *channel_id);
-		let mut ctx = Context::<T>::default();
+		let _ = Pallet::<T, I>::remove_channel_escrow_address(port_id, *channel_id);
+		let mut ctx = Context::<T, I>::default();
 		on_chan_close_confirm(&mut ctx, output, port_id, channel_id)
 			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
 	}
@@ -207,7 +210,7 @@ where
 		packet: &mut Packet,
 		_relayer: &Signer,
 	) -> Result<Acknowledgement, Ics04Error> {
-		let mut ctx = Context::<T>::default();
+		let mut ctx = Context::<T, I>::default();
 
 		let result = serde_json::from_slice(packet.data.as_slice())
 			.map_err(|e| {
@@ -230,8 +233,19 @@ where
 				let amount = packet_data.token.amount.as_u256();
 				u128::try_from(amount)
 					.map_err(|e| Ics04Error::implementation_specific(format!("{e:?}")))?;
+				let flow = Pallet::<T, I>::limit_transfer(
+					packet.destination_channel,
+					&token,
+					Direction::Receive,
+				)
+				.map_err(|e| Ics04Error::implementation_specific(format!("{e:?}")))?;
 				process_recv_packet(&mut ctx, output, packet, packet_data.clone())
-					.map(|_| packet_data)
+					.map(|_| {
+						if let Some(flow) = flow {
+							flow.record();
+						}
+						packet_data
+					})
 					.map_err(|e| {
 						log::trace!(target: "pallet_ibc", "[on_recv_packet]: token: {}, error: {:?}", denom, e);
 						Ics04Error::implementation_specific(e.to_string())
@@ -241,29 +255,20 @@ where
 		let ack = match result {
 			Err(err) => {
 				log::trace!(target: "pallet_ibc", "Acknowledgement error: {:?}", err);
-				let ack = Ics20Acknowledgement::Error(format!("{ACK_ERR_STR}: {err:?}"))
+				Ics20Acknowledgement::Error(format!("{ACK_ERR_STR}: {err:?}"))
 					.to_string()
-					.into_bytes();
-				Pallet::<T>::handle_message(HandlerMessage::WriteAck {
-					packet: packet.clone(),
-					ack: ack.clone(),
-				})
-				.map_err(|e| {
-					Ics04Error::implementation_specific(format!("[on_recv_packet] {e:#?}"))
-				})?;
-				ack
+					.into_bytes()
 			},
 			Ok(packet_data) => {
 				let denom = full_ibc_denom(packet, packet_data.token.clone());
 				let prefixed_denom = PrefixedDenom::from_str(&denom).map_err(|_| {
 					Ics04Error::implementation_specific("Failed to parse token denom".to_string())
 				})?;
-				Pallet::<T>::deposit_event(Event::<T>::TokenReceived {
+				Pallet::<T, I>::deposit_event(Event::<T, I>::TokenReceived {
 					from: packet_data.sender,
 					to: packet_data.receiver,
 					ibc_denom: denom.as_bytes().to_vec(),
-					local_asset_id: T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(&denom)
-						.ok(),
+					local_asset_id: T::IbcDenomToAssetIdConversion::lookup_asset_id(&denom),
 					amount: packet_data.token.amount.as_u256().as_u128().into(),
 					is_receiver_source: is_receiver_chain_source(
 						packet.source_port.clone(),
@@ -273,14 +278,6 @@ where
 					source_channel: packet.source_channel.to_string().as_bytes().to_vec(),
 					destination_channel: packet.destination_channel.to_string().as_bytes().to_vec(),
 				});
-				let packet = packet.clone();
-				Pallet::<T>::handle_message(HandlerMessage::WriteAck {
-					packet,
-					ack: Ics20Acknowledgement::success().to_string().into_bytes(),
-				})
-				.map_err(|e| {
-					Ics04Error::implementation_specific(format!("[on_recv_packet] {e:#?}"))
-				})?;
 				Ics20Acknowledgement::success().to_string().into_bytes()
 			},
 		};
@@ -295,7 +292,7 @@ where
 		acknowledgement: &Acknowledgement,
 		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
-		let mut ctx = Context::<T>::default();
+		let mut ctx = Context::<T, I>::default();
 		let packet_data: PacketData =
 			serde_json::from_slice(packet.data.as_slice()).map_err(|e| {
 				Ics04Error::implementation_specific(format!("Failed to decode packet data {e:?}"))
@@ -311,18 +308,18 @@ where
 			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))?;
 		match ack.into_result() {
 			Ok(_) => {
-				if SequenceFee::<T>::contains_key(sequence) {
-					SequenceFee::<T>::remove(sequence);
-					Pallet::<T>::deposit_event(Event::<T>::ChargingFeeConfirmed { sequence });
+				ForwardedTransfers::<T, I>::remove(packet.source_channel.sequence(), sequence);
+				if SequenceFee::<T, I>::contains_key(sequence) {
+					SequenceFee::<T, I>::remove(sequence);
+					Pallet::<T, I>::deposit_event(Event::<T, I>::ChargingFeeConfirmed { sequence });
 				}
-				Pallet::<T>::deposit_event(Event::<T>::TokenTransferCompleted {
+				Pallet::<T, I>::deposit_event(Event::<T, I>::TokenTransferCompleted {
 					from: packet_data.sender,
 					to: packet_data.receiver,
 					ibc_denom: packet_data.token.denom.to_string().as_bytes().to_vec(),
-					local_asset_id: T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
+					local_asset_id: T::IbcDenomToAssetIdConversion::lookup_asset_id(
 						&packet_data.token.denom.to_string(),
-					)
-					.ok(),
+					),
 					amount: packet_data.token.amount.as_u256().as_u128().into(),
 					is_sender_source: is_sender_chain_source(
 						packet.source_port.clone(),
@@ -339,18 +336,23 @@ where
 					"error: acknowledgement error: {e}",
 				);
 				Self::refund_fee(packet, &packet_data)?;
-				Pallet::<T>::deposit_event(Event::<T>::ChargingFeeFailedAcknowledgement {
+				Pallet::<T, I>::revert_sent_flow(packet.source_channel, &packet_data.token);
+				Pallet::<T, I>::deposit_event(Event::<T, I>::ChargingFeeFailedAcknowledgement {
 					sequence,
 				});
+				if let Some(forward) =
+					ForwardedTransfers::<T, I>::take(packet.source_channel.sequence(), sequence)
+				{
+					Self::refund_forwarded_transfer(forward);
+				}
 
-				Pallet::<T>::deposit_event(Event::<T>::TokenTransferFailed {
+				Pallet::<T, I>::deposit_event(Event::<T, I>::TokenTransferFailed {
 					from: packet_data.sender,
 					to: packet_data.receiver,
 					ibc_denom: packet_data.token.denom.to_string().as_bytes().to_vec(),
-					local_asset_id: T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
+					local_asset_id: T::IbcDenomToAssetIdConversion::lookup_asset_id(
 						&packet_data.token.denom.to_string(),
-					)
-					.ok(),
+					),
 					amount: packet_data.token.amount.as_u256().as_u128().into(),
 					is_sender_source: is_sender_chain_source(
 						packet.source_port.clone(),
@@ -373,23 +375,24 @@ where
 		packet: &mut Packet,
 		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
-		let mut ctx = Context::<T>::default();
+		let mut ctx = Context::<T, I>::default();
 		let packet_data: PacketData = serde_json::from_slice(packet.data.as_slice())
 			.map_err(|e| Ics04Error::app_module(format!("Failed to decode packet data {e:?}")))?;
 		process_timeout_packet(&mut ctx, packet, &packet_data)
 			.map_err(|e| Ics04Error::app_module(e.to_string()))?;
 		let sequence: u64 = packet.sequence.into();
 		Self::refund_fee(packet, &packet_data)?;
-		Pallet::<T>::deposit_event(Event::<T>::ChargingFeeTimeout { sequence });
+		Pallet::<T, I>::revert_sent_flow(packet.source_channel, &packet_data.token);
+		Pallet::<T, I>::deposit_event(Event::<T, I>::ChargingFeeTimeout { sequence });
+		Self::retry_forwarded_transfer(packet);
 
-		Pallet::<T>::deposit_event(Event::<T>::TokenTransferTimeout {
+		Pallet::<T, I>::deposit_event(Event::<T, I>::TokenTransferTimeout {
 			from: packet_data.sender,
 			to: packet_data.receiver,
 			ibc_denom: packet_data.token.denom.to_string().as_bytes().to_vec(),
-			local_asset_id: T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
+			local_asset_id: T::IbcDenomToAssetIdConversion::lookup_asset_id(
 				&packet_data.token.denom.to_string(),
-			)
-			.ok(),
+			),
 			amount: packet_data.token.amount.as_u256().as_u128().into(),
 			is_sender_source: is_sender_chain_source(
 				packet.source_port.clone(),
@@ -403,9 +406,9 @@ where
 	}
 }
 
-impl<T> IbcModule<T>
+impl<T, I: 'static> IbcModule<T, I>
 where
-	T: Config + Send + Sync,
+	T: Config<I> + Send + Sync,
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
 {
@@ -434,21 +437,21 @@ where
 		use ibc::bigint::U256;
 		use sp_core::Get;
 		let sequence: u64 = packet.sequence.into();
-		if !SequenceFee::<T>::contains_key(sequence) {
+		if !SequenceFee::<T, I>::contains_key(sequence) {
 			return Ok(()) //there is nothing to refund.
 		}
-		let fee = SequenceFee::<T>::take(sequence);
+		let fee = SequenceFee::<T, I>::take(sequence);
 
 		let fee_account = T::FeeAccount::get();
 
-		let mut ctx = Context::<T>::default();
+		let mut ctx = Context::<T, I>::default();
 		let mut fee_coin = packet_data.token.clone();
 
 		fee_coin.amount = U256::from(fee).into();
 
 		let signer_from = packet_data.sender.clone();
 		let refund_to_account_id =
-			<T as Config>::AccountIdConversion::try_from(signer_from.clone()).map_err(|_| {
+			<T as Config<I>>::AccountIdConversion::try_from(signer_from.clone()).map_err(|_| {
 				Ics04Error::implementation_specific(format!(
 					"Failed to parse receiver account {signer_from:?}"
 				))
@@ -462,43 +465,129 @@ where
 			})?;
 		Ok(())
 	}
+
+	/// Sends a transfer forwarded by a memo, and records it under the sequence of its packet to
+	/// retry or refund it once the packet times out or fails. Returns the sequence of the packet.
+	fn send_forwarded_transfer(forward: ForwardedTransferOf<T, I>) -> Result<u64, DispatchError> {
+		let sequence: u64 = Context::<T, I>::default()
+			.get_next_sequence_send(&(PortId::transfer(), ChannelId::new(forward.channel)))
+			.map_err(|_| crate::Error::<T, I>::ChannelNotFound)?
+			.into();
+		let params = TransferParams {
+			to: MultiAddress::Raw(forward.to.clone()),
+			source_channel: forward.channel,
+			timeout: Timeout::Offset { timestamp: Some(forward.timeout), height: Some(1000) },
+		};
+		Pallet::<T, I>::transfer(
+			RawOrigin::Signed(forward.from.clone()).into(),
+			params,
+			forward.asset_id.clone(),
+			forward.amount,
+			forward.next_memo.clone(),
+		)?;
+		ForwardedTransfers::<T, I>::insert(forward.channel, sequence, forward);
+		Ok(sequence)
+	}
+
+	/// Sends the transfer forwarded in the timed out packet again if it has retries left, and
+	/// refunds it otherwise.
+	fn retry_forwarded_transfer(packet: &Packet) {
+		let Some(forward) = ForwardedTransfers::<T, I>::take(
+			packet.source_channel.sequence(),
+			u64::from(packet.sequence),
+		) else {
+			return
+		};
+		if forward.retries == 0 {
+			return Self::refund_forwarded_transfer(forward)
+		}
+		let retry = ForwardedTransfer { retries: forward.retries - 1, ..forward.clone() };
+		match Self::send_forwarded_transfer(retry) {
+			Ok(sequence) =>
+				Pallet::<T, I>::deposit_event(Event::<T, I>::ForwardedTransferRetried {
+					from: forward.from,
+					channel: forward.channel,
+					sequence,
+					retries: forward.retries - 1,
+				}),
+			Err(e) => {
+				log::debug!(target: "pallet_ibc", "[retry_forwarded_transfer]: error: {:?}", e);
+				Self::refund_forwarded_transfer(forward)
+			},
+		}
+	}
+
+	/// Sends the tokens of a failed forwarded transfer, refunded to the receiver of the packet
+	/// they were received in, back to the sender of the packet. The tokens are left to the
+	/// receiver if the refund can't be sent.
+	fn refund_forwarded_transfer(forward: ForwardedTransferOf<T, I>) {
+		let params = TransferParams {
+			to: MultiAddress::Raw(forward.refund_to.clone()),
+			source_channel: forward.refund_channel,
+			timeout: Timeout::Offset { timestamp: Some(forward.timeout), height: Some(1000) },
+		};
+		let result = Pallet::<T, I>::transfer(
+			RawOrigin::Signed(forward.from.clone()).into(),
+			params,
+			forward.asset_id.clone(),
+			forward.amount,
+			None,
+		);
+		match result {
+			Ok(()) => Pallet::<T, I>::deposit_event(Event::<T, I>::ForwardedTransferRefunded {
+				from: forward.from,
+				to: forward.refund_to,
+				asset_id: forward.asset_id,
+				amount: forward.amount,
+				channel: forward.refund_channel,
+			}),
+			Err(e) => {
+				log::debug!(target: "pallet_ibc", "[refund_forwarded_transfer]: error: {:?}", e);
+				Pallet::<T, I>::deposit_event(Event::<T, I>::ForwardedTransferRefundFailed {
+					from: forward.from,
+					asset_id: forward.asset_id,
+					amount: forward.amount,
+				})
+			},
+		}
+	}
 }
 
-pub struct WeightHandler<T: Config>(PhantomData<T>);
+pub struct WeightHandler<T: Config<I>, I: 'static = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> Default for WeightHandler<T> {
+impl<T: Config<I>, I: 'static> Default for WeightHandler<T, I> {
 	fn default() -> Self {
 		Self(PhantomData)
 	}
 }
 
-impl<T: Config> CallbackWeight for WeightHandler<T> {
+impl<T: Config<I>, I: 'static> CallbackWeight for WeightHandler<T, I> {
 	fn on_chan_open_init(&self) -> Weight {
-		<T as Config>::WeightInfo::on_chan_open_init()
+		<T as Config<I>>::WeightInfo::on_chan_open_init()
 	}
 
 	fn on_chan_open_try(&self) -> Weight {
-		<T as Config>::WeightInfo::on_chan_open_try()
+		<T as Config<I>>::WeightInfo::on_chan_open_try()
 	}
 
 	fn on_chan_open_ack(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
-		<T as Config>::WeightInfo::on_chan_open_ack()
+		<T as Config<I>>::WeightInfo::on_chan_open_ack()
 	}
 
 	fn on_chan_open_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
-		<T as Config>::WeightInfo::on_chan_open_confirm()
+		<T as Config<I>>::WeightInfo::on_chan_open_confirm()
 	}
 
 	fn on_chan_close_init(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
-		<T as Config>::WeightInfo::on_chan_close_init()
+		<T as Config<I>>::WeightInfo::on_chan_close_init()
 	}
 
 	fn on_chan_close_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
-		<T as Config>::WeightInfo::on_chan_close_confirm()
+		<T as Config<I>>::WeightInfo::on_chan_close_confirm()
 	}
 
 	fn on_recv_packet(&self, _packet: &Packet) -> Weight {
-		<T as Config>::WeightInfo::on_recv_packet()
+		<T as Config<I>>::WeightInfo::on_recv_packet()
 	}
 
 	fn on_acknowledgement_packet(
@@ -506,11 +595,11 @@ impl<T: Config> CallbackWeight for WeightHandler<T> {
 		_packet: &Packet,
 		_acknowledgement: &Acknowledgement,
 	) -> Weight {
-		<T as Config>::WeightInfo::on_acknowledgement_packet()
+		<T as Config<I>>::WeightInfo::on_acknowledgement_packet()
 	}
 
 	fn on_timeout_packet(&self, _packet: &Packet) -> Weight {
-		<T as Config>::WeightInfo::on_timeout_packet()
+		<T as Config<I>>::WeightInfo::on_timeout_packet()
 	}
 }
 
@@ -530,11 +619,11 @@ pub fn full_ibc_denom(packet: &Packet, mut token: PrefixedCoin) -> String {
 
 use ibc::applications::transfer::error::Error as Ics20Error;
 
-pub trait HandleMemo<T: Config> {
+pub trait HandleMemo<T: Config<I>, I: 'static = ()> {
 	fn execute_memo(&self, packet: &Packet) -> Result<(), Ics20Error>;
 }
 
-impl<T: Config> HandleMemo<T> for () {
+impl<T: Config<I>, I: 'static> HandleMemo<T, I> for () {
 	fn execute_memo(&self, _packet: &Packet) -> Result<(), Ics20Error> {
 		Ok(())
 	}
@@ -554,7 +643,7 @@ pub trait SubstrateMultihopXcmHandler {
 
 pub struct SubstrateMultihopXcmHandlerNone<T>(PhantomData<T>);
 
-impl<T: Config> SubstrateMultihopXcmHandler for SubstrateMultihopXcmHandlerNone<T> {
+impl<T: frame_system::Config> SubstrateMultihopXcmHandler for SubstrateMultihopXcmHandlerNone<T> {
 	type AccountId = T::AccountId;
 	fn transfer_xcm(
 		_: Self::AccountId,
@@ -568,7 +657,7 @@ impl<T: Config> SubstrateMultihopXcmHandler for SubstrateMultihopXcmHandlerNone<
 }
 
 use frame_system::RawOrigin;
-use scale_info::prelude::boxed::Box;
+use scale_info::{prelude::boxed::Box, TypeInfo};
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct Forward {
 	pub receiver: String,
@@ -637,6 +726,36 @@ pub enum MemoType {
 	XCM(MemoXcm),
 }
 
+/// Transfer forwarded over IBC on the receipt of a packet with a `forward` memo. It's sent again
+/// when its packet times out, until it has no retries left, and its tokens are refunded to the
+/// sender of the received packet when it fails.
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Clone)]
+pub struct ForwardedTransfer<AccountId, AssetId, Balance, Memo> {
+	/// Receiver of the packet, which the tokens are forwarded from
+	pub from: AccountId,
+	/// Receiver of the tokens on the next chain
+	pub to: Vec<u8>,
+	pub channel: u64,
+	pub asset_id: AssetId,
+	pub amount: Balance,
+	/// Timeout of the forwarded packet, in seconds
+	pub timeout: u64,
+	/// Number of times the transfer is sent again once timed out
+	pub retries: u64,
+	pub next_memo: Option<Memo>,
+	/// Channel the packet was received on
+	pub refund_channel: u64,
+	/// Sender of the packet on the counterparty chain of the refund channel
+	pub refund_to: Vec<u8>,
+}
+
+pub type ForwardedTransferOf<T, I> = ForwardedTransfer<
+	<T as frame_system::Config>::AccountId,
+	<T as Config<I>>::AssetId,
+	<T as Config<I>>::Balance,
+	<T as Config<I>>::MemoMessage,
+>;
+
 impl Forward {
 	pub fn get_memo(&self) -> Result<MemoType, Ics20Error> {
 		if self.substrate.unwrap_or_default() {
@@ -663,21 +782,21 @@ impl Forward {
 	}
 }
 
-impl<H: Default, T> Default for IbcMemoHandler<H, T> {
+impl<H: Default, T, I> Default for IbcMemoHandler<H, T, I> {
 	fn default() -> Self {
 		Self { inner: H::default(), _phantom: PhantomData }
 	}
 }
 
-pub struct IbcMemoHandler<H, T> {
+pub struct IbcMemoHandler<H, T, I = ()> {
 	pub inner: H,
-	pub _phantom: PhantomData<T>,
+	pub _phantom: PhantomData<(T, I)>,
 }
 
-use codec::Decode;
-impl<T, H: HandleMemo<T>> HandleMemo<T> for IbcMemoHandler<H, T>
+use codec::{Decode, Encode};
+impl<T, H: HandleMemo<T, I>, I: 'static> HandleMemo<T, I> for IbcMemoHandler<H, T, I>
 where
-	T: Config + Send + Sync + pallet_timestamp::Config,
+	T: Config<I> + Send + Sync + pallet_timestamp::Config,
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
 	u128: From<T::AssetId>,
@@ -689,24 +808,30 @@ where
 			serde_json::from_slice(packet.data.as_slice()).map_err(|e| {
 				Ics20Error::implementation_specific(format!("Failed to decode packet data {:?}", e))
 			})?;
-		let receiver = <T as Config>::AccountIdConversion::try_from(packet_data.receiver.clone())
-			.map_err(|_| {
-				Ics20Error::implementation_specific(format!("Failed to parse receiver account"))
-			})?
-			.into_account();
+		let receiver =
+			<T as Config<I>>::AccountIdConversion::try_from(packet_data.receiver.clone())
+				.map_err(|_| {
+					Ics20Error::implementation_specific(format!("Failed to parse receiver account"))
+				})?
+				.into_account();
 
 		//Handle only memo with IBC forward.
 		if packet_data.memo.is_empty() {
 			return Ok(())
 		}
+		// the plain text memos, and the memos of the other hooks, are left to the inner handler
+		match serde_json::from_str::<serde_json::Value>(&packet_data.memo) {
+			Ok(serde_json::Value::Object(memo)) if memo.contains_key("forward") => {},
+			_ => return Ok(()),
+		}
 
-		crate::Pallet::<T>::deposit_event(Event::<T>::ExecuteMemoStarted {
+		crate::Pallet::<T, I>::deposit_event(Event::<T, I>::ExecuteMemoStarted {
 			account_id: receiver.clone(),
 			memo: Some(packet_data.memo.clone()),
 		});
 
 		let memo: MemoData = serde_json::from_str(&packet_data.memo).map_err(|_| {
-			IbcModule::<T>::emit_memo_execution_failed_event(
+			IbcModule::<T, I>::emit_memo_execution_failed_event(
 				receiver.clone(),
 				packet_data.memo.clone(),
 				0,
@@ -736,19 +861,23 @@ where
 
 		// At this point the asset SHOULD exist
 		let asset_id =
-			<T as crate::Config>::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
+			<T as crate::Config<I>>::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
 				&prefixed_coin.denom.to_string(),
 			)
 			.map_err(|_| {
 				log::warn!(target: "pallet_ibc", "Asset does not exist for denom: {}", prefixed_coin.denom.to_string());
-				IbcModule::<T>::emit_memo_execution_failed_event(receiver.clone(), packet_data.memo.clone(), 1);
+				IbcModule::<T, I>::emit_memo_execution_failed_event(
+					receiver.clone(),
+					packet_data.memo.clone(),
+					1,
+				);
 				Ics20Error::implementation_specific("asset does not exist".to_string())
 			})?;
 
 		let amount = packet_data.token.amount.as_u256().low_u128();
 
 		let memo_forward = memo.forward.get_memo().map_err(|_| {
-			IbcModule::<T>::emit_memo_execution_failed_event(
+			IbcModule::<T, I>::emit_memo_execution_failed_event(
 				receiver.clone(),
 				packet_data.memo.clone(),
 				2,
@@ -760,7 +889,7 @@ where
 			MemoType::IBC(memo_forward) => memo_forward,
 			MemoType::XCM(memo_forward) => {
 				let s = memo_forward.receiver.strip_prefix("0x").ok_or_else(|| {
-					IbcModule::<T>::emit_memo_execution_failed_event(
+					IbcModule::<T, I>::emit_memo_execution_failed_event(
 						receiver.clone(),
 						packet_data.memo.clone(),
 						11,
@@ -769,7 +898,7 @@ where
 				})?;
 
 				let decoded_accout = hex::decode(s).map_err(|_| {
-					IbcModule::<T>::emit_memo_execution_failed_event(
+					IbcModule::<T, I>::emit_memo_execution_failed_event(
 						receiver.clone(),
 						packet_data.memo.clone(),
 						12,
@@ -778,7 +907,7 @@ where
 				})?;
 
 				let account_to = T::AccountId::decode(&mut &*decoded_accout).map_err(|_| {
-					IbcModule::<T>::emit_memo_execution_failed_event(
+					IbcModule::<T, I>::emit_memo_execution_failed_event(
 						receiver.clone(),
 						packet_data.memo.clone(),
 						13,
@@ -794,7 +923,7 @@ where
 					asset_id.clone().into(),
 				)
 				.ok_or_else(|| {
-					crate::Pallet::<T>::deposit_event(Event::<T>::ExecuteMemoXcmFailed {
+					crate::Pallet::<T, I>::deposit_event(Event::<T, I>::ExecuteMemoXcmFailed {
 						from: receiver.clone(),
 						to: account_to.clone(),
 						para_id: memo_forward.para_id,
@@ -806,7 +935,7 @@ where
 					)
 				})?;
 
-				crate::Pallet::<T>::deposit_event(Event::<T>::ExecuteMemoXcmSuccess {
+				crate::Pallet::<T, I>::deposit_event(Event::<T, I>::ExecuteMemoXcmSuccess {
 					from: receiver.clone(),
 					to: account_to.clone(),
 					para_id: memo_forward.para_id,
@@ -820,16 +949,12 @@ where
 
 		let raw_bytes = memo_forward.receiver.into_bytes();
 
-		let transfer_ibc_account_to =
-			crate::MultiAddress::<<T as frame_system::Config>::AccountId>::Raw(raw_bytes.clone());
-
-		let origin = RawOrigin::Signed(receiver.clone());
 		let channel_id = memo_forward
 			.channel
 			.split('-')
 			.last()
 			.ok_or_else(|| {
-				IbcModule::<T>::emit_memo_execution_failed_event(
+				IbcModule::<T, I>::emit_memo_execution_failed_event(
 					receiver.clone(),
 					packet_data.memo.clone(),
 					3,
@@ -841,7 +966,7 @@ where
 			})?
 			.parse()
 			.map_err(|_| {
-				IbcModule::<T>::emit_memo_execution_failed_event(
+				IbcModule::<T, I>::emit_memo_execution_failed_event(
 					receiver.clone(),
 					packet_data.memo.clone(),
 					4,
@@ -849,51 +974,50 @@ where
 				Ics20Error::implementation_specific("Failed to parse channel ID".to_string())
 			})?;
 
-		let params = crate::TransferParams::<<T as frame_system::Config>::AccountId> {
-			to: transfer_ibc_account_to,
-			source_channel: channel_id,
-			timeout: ibc_primitives::Timeout::Offset {
-				timestamp: Some(memo_forward.timeout),
-				height: Some(1000),
-			},
-		};
-
 		let mut next_memo: Option<T::MemoMessage> = None;
 		if let Some(memo) = memo.forward.next {
-			let memo_result = <T as crate::Config>::MemoMessage::try_from(*memo).map_err(|_| {
-				IbcModule::<T>::emit_memo_execution_failed_event(
-					receiver.clone(),
-					packet_data.memo.clone(),
-					5,
-				);
-				Ics20Error::implementation_specific("failed to try_from memo".to_string())
-			})?;
+			let memo_result =
+				<T as crate::Config<I>>::MemoMessage::try_from(*memo).map_err(|_| {
+					IbcModule::<T, I>::emit_memo_execution_failed_event(
+						receiver.clone(),
+						packet_data.memo.clone(),
+						5,
+					);
+					Ics20Error::implementation_specific("failed to try_from memo".to_string())
+				})?;
 
 			next_memo = Some(memo_result);
 		}
 
-		crate::Pallet::<T>::transfer(
-			origin.into(),
-			params,
-			asset_id.clone(),
-			amount.into(),
-			next_memo.clone(),
-		)
-		.map_err(|_| {
-			crate::Pallet::<T>::deposit_event(Event::<T>::ExecuteMemoIbcTokenTransferFailed {
-				from: receiver.clone(),
-				to: raw_bytes.clone(),
-				asset_id: asset_id.clone(),
-				amount: amount.into(),
-				channel: channel_id,
-				next_memo: next_memo.clone(),
-			});
+		let forward = ForwardedTransfer {
+			from: receiver.clone(),
+			to: raw_bytes.clone(),
+			channel: channel_id,
+			asset_id: asset_id.clone(),
+			amount: amount.into(),
+			timeout: memo_forward.timeout,
+			retries: memo_forward.retries,
+			next_memo: next_memo.clone(),
+			refund_channel: packet.destination_channel.sequence(),
+			refund_to: packet_data.sender.to_string().into_bytes(),
+		};
+		IbcModule::<T, I>::send_forwarded_transfer(forward).map_err(|_| {
+			crate::Pallet::<T, I>::deposit_event(
+				Event::<T, I>::ExecuteMemoIbcTokenTransferFailed {
+					from: receiver.clone(),
+					to: raw_bytes.clone(),
+					asset_id: asset_id.clone(),
+					amount: amount.into(),
+					channel: channel_id,
+					next_memo: next_memo.clone(),
+				},
+			);
 			Ics20Error::implementation_specific(
 				"Pallet ibc transfer failed to send message".to_string(),
 			)
 		})?;
 
-		crate::Pallet::<T>::deposit_event(Event::<T>::ExecuteMemoIbcTokenTransferSuccess {
+		crate::Pallet::<T, I>::deposit_event(Event::<T, I>::ExecuteMemoIbcTokenTransferSuccess {
 			from: receiver,
 			to: raw_bytes,
 			asset_id,
@@ -905,16 +1029,20 @@ where
 	}
 }
 
-impl<T> IbcModule<T>
+impl<T, I: 'static> IbcModule<T, I>
 where
-	T: Config + Send + Sync,
+	T: Config<I> + Send + Sync,
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
 {
 	//function that takes account and memo and emit event that memo execution failed
 	fn emit_memo_execution_failed_event(account: T::AccountId, memo: String, reason: u8) {
-		crate::Pallet::<T>::deposit_event(
-			Event::<T>::ExecuteMemoIbcTokenTransferFailedWithReason { from: account, memo, reason },
+		crate::Pallet::<T, I>::deposit_event(
+			Event::<T, I>::ExecuteMemoIbcTokenTransferFailedWithReason {
+				from: account,
+				memo,
+				reason,
+			},
 		);
 	}
 }
@@ -0,0 +1,200 @@
+//! Rate limits of the ICS-20 transfers.
+//!
+//! Governance sets the [`TransferLimit`] of an asset on a channel with
+//! [`Pallet::set_transfer_limit`]. The net amount of the asset sent, or received, over the channel
+//! during a window of `period` blocks is capped by a [`Quota`]: an absolute amount, or a share of
+//! the total issuance of the asset at the start of the window. The window starts with the first
+//! transfer after the previous one ended. The balance of the asset held by the escrow account of
+//! the channel can be capped too.
+//!
+//! The transfers exceeding the limits are rejected: the sends fail with the `RateLimiter` error,
+//! and the received packets are acknowledged with an error, so that their tokens are refunded on
+//! the counterparty chain, and a `TransferRateLimited` event. The tokens of the sent packets that
+//! fail or time out are deducted from the amount sent during the current window.
+
+use crate::{Config, DenomToAssetId, Error, Event, Pallet, TransferFlows, TransferLimits};
+use codec::{Decode, Encode};
+use frame_support::traits::{fungibles::Inspect, Currency, Get};
+use ibc::{
+	applications::transfer::{is_sender_chain_source, PrefixedCoin},
+	core::ics24_host::identifier::{ChannelId, PortId},
+};
+use ibc_primitives::get_channel_escrow_address;
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{IdentifyAccount, Saturating, Zero},
+	Perbill, RuntimeDebug,
+};
+
+/// Maximum net amount of an asset transferred over a channel in a direction during a window.
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Clone)]
+pub enum Quota<Balance> {
+	/// An absolute amount of the asset
+	Amount(Balance),
+	/// A share of the total issuance of the asset at the start of the window. No transfer is
+	/// allowed while the asset has no supply, e.g. before its voucher is first received.
+	Supply(Perbill),
+}
+
+impl<Balance: sp_runtime::traits::AtLeast32BitUnsigned + Copy> Quota<Balance> {
+	/// Returns the amount of the quota for the supply of the asset.
+	pub fn amount(&self, supply: Balance) -> Balance {
+		match self {
+			Quota::Amount(amount) => *amount,
+			Quota::Supply(share) => *share * supply,
+		}
+	}
+}
+
+/// Limits of the transfers of an asset over a channel.
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Clone)]
+pub struct TransferLimit<Balance, BlockNumber> {
+	/// Quota of the tokens sent per window, unlimited if `None`
+	pub send: Option<Quota<Balance>>,
+	/// Quota of the tokens received per window, unlimited if `None`
+	pub receive: Option<Quota<Balance>>,
+	/// Length of the window, in blocks
+	pub period: BlockNumber,
+	/// Maximum balance of the escrow account of the channel, unlimited if `None`
+	pub escrow_cap: Option<Balance>,
+}
+
+pub type TransferLimitOf<T, I> =
+	TransferLimit<<T as Config<I>>::Balance, <T as frame_system::Config>::BlockNumber>;
+
+/// Amounts of an asset transferred over a channel during the current window.
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Clone)]
+pub struct TransferFlow<Balance, BlockNumber> {
+	/// Block the window started at
+	pub window_start: BlockNumber,
+	/// Total issuance of the asset at the start of the window
+	pub supply: Balance,
+	pub sent: Balance,
+	pub received: Balance,
+}
+
+pub type TransferFlowOf<T, I> =
+	TransferFlow<<T as Config<I>>::Balance, <T as frame_system::Config>::BlockNumber>;
+
+/// Direction of a transfer over a channel
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Clone, Copy)]
+pub enum Direction {
+	Send,
+	Receive,
+}
+
+/// Flow of a transfer within the limits of its channel, to be recorded once the transfer succeeds.
+#[must_use]
+pub(crate) struct FlowUpdate<T: Config<I>, I: 'static> {
+	channel: u64,
+	asset_id: T::AssetId,
+	flow: TransferFlowOf<T, I>,
+}
+
+impl<T: Config<I>, I: 'static> FlowUpdate<T, I> {
+	pub(crate) fn record(self) {
+		TransferFlows::<T, I>::insert(self.channel, self.asset_id, self.flow);
+	}
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Checks the transfer of `token` over the transfer channel `channel_id` in `direction`
+	/// against the limits of the channel for its asset, returning the flow to record once the
+	/// transfer succeeds. Emits `TransferRateLimited` and returns an error if a limit is exceeded.
+	pub(crate) fn limit_transfer(
+		channel_id: ChannelId,
+		token: &PrefixedCoin,
+		direction: Direction,
+	) -> Result<Option<FlowUpdate<T, I>>, Error<T, I>> {
+		// the limits are set on the assets, so unknown denominations have none
+		let Some(asset_id) =
+			T::IbcDenomToAssetIdConversion::lookup_asset_id(&token.denom.to_string())
+		else {
+			return Ok(None)
+		};
+		let channel = channel_id.sequence();
+		let Some(limit) = TransferLimits::<T, I>::get(channel, &asset_id) else { return Ok(None) };
+		let amount: T::Balance = token.amount.as_u256().low_u128().into();
+
+		let now = frame_system::Pallet::<T>::block_number();
+		let mut flow = TransferFlows::<T, I>::get(channel, &asset_id)
+			.filter(|flow| now < flow.window_start.saturating_add(limit.period))
+			.unwrap_or_else(|| TransferFlow {
+				window_start: now,
+				supply: Self::total_issuance(asset_id.clone()),
+				sent: Zero::zero(),
+				received: Zero::zero(),
+			});
+		let (net_amount, quota) = match direction {
+			Direction::Send => {
+				flow.sent = flow.sent.saturating_add(amount);
+				(flow.sent.saturating_sub(flow.received), &limit.send)
+			},
+			Direction::Receive => {
+				flow.received = flow.received.saturating_add(amount);
+				(flow.received.saturating_sub(flow.sent), &limit.receive)
+			},
+		};
+		let mut exceeded =
+			quota.as_ref().map_or(false, |quota| net_amount > quota.amount(flow.supply));
+
+		// the tokens of the assets originating from this chain are sent to the escrow account
+		if let (Direction::Send, Some(cap)) = (direction, limit.escrow_cap) {
+			if is_sender_chain_source(PortId::transfer(), channel_id, &token.denom) {
+				let escrow_balance = Self::escrow_balance(channel_id, asset_id.clone())?;
+				exceeded |= escrow_balance.saturating_add(amount) > cap;
+			}
+		}
+
+		if exceeded {
+			Self::deposit_event(Event::<T, I>::TransferRateLimited {
+				channel,
+				asset_id,
+				amount,
+				direction,
+			});
+			return Err(Error::<T, I>::RateLimiter)
+		}
+		Ok(Some(FlowUpdate { channel, asset_id, flow }))
+	}
+
+	/// Removes the tokens of a failed or timed out transfer from the tokens sent over the channel
+	/// during the current window.
+	pub(crate) fn revert_sent_flow(channel_id: ChannelId, token: &PrefixedCoin) {
+		let Some(asset_id) =
+			T::IbcDenomToAssetIdConversion::lookup_asset_id(&token.denom.to_string())
+		else {
+			return
+		};
+		let amount: T::Balance = token.amount.as_u256().low_u128().into();
+		TransferFlows::<T, I>::mutate(channel_id.sequence(), asset_id, |flow| {
+			if let Some(flow) = flow {
+				flow.sent = flow.sent.saturating_sub(amount);
+			}
+		});
+	}
+
+	fn total_issuance(asset_id: T::AssetId) -> T::Balance {
+		if asset_id == T::NativeAssetId::get() {
+			<T::NativeCurrency as Currency<T::AccountId>>::total_issuance()
+		} else {
+			<T::Fungibles as Inspect<T::AccountId>>::total_issuance(asset_id)
+		}
+	}
+
+	fn escrow_balance(
+		channel_id: ChannelId,
+		asset_id: T::AssetId,
+	) -> Result<T::Balance, Error<T, I>> {
+		let escrow_account = get_channel_escrow_address(&PortId::transfer(), channel_id)
+			.ok()
+			.and_then(|address| T::AccountIdConversion::try_from(address).ok())
+			.ok_or(Error::<T, I>::ChannelEscrowAddress)?
+			.into_account();
+		Ok(if asset_id == T::NativeAssetId::get() {
+			<T::NativeCurrency as Currency<T::AccountId>>::total_balance(&escrow_account)
+		} else {
+			<T::Fungibles as Inspect<T::AccountId>>::balance(asset_id, &escrow_account)
+		})
+	}
+}
@@ -28,9 +28,13 @@ use sp_core::crypto::AccountId32;
 use sp_runtime::traits::Get;
 
 pub use pallet::*;
+pub use weight::WeightInfo;
+
+mod weight;
 
 #[frame_support::pallet]
 pub mod pallet {
+	use super::WeightInfo;
 	use frame_support::{pallet_prelude::*, PalletId};
 	use frame_system::{ensure_root, pallet_prelude::OriginFor};
 	use ibc_primitives::IbcAccount;
@@ -58,6 +62,8 @@ pub mod pallet {
 		type ServiceChargeIn: Get<Perbill>;
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
+		/// Weight information for the extrinsics of the pallet
+		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::pallet]
@@ -86,7 +92,7 @@ pub mod pallet {
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		#[pallet::call_index(0)]
-		#[pallet::weight(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_charge())]
 		pub fn set_charge(origin: OriginFor<T>, charge: Perbill) -> DispatchResult {
 			<T as crate::Config>::AdminOrigin::ensure_origin(origin)?;
 			ServiceChargeIn::<T>::put(charge);
@@ -94,7 +100,7 @@ pub mod pallet {
 		}
 
 		#[pallet::call_index(1)]
-		#[pallet::weight(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::add_channels_to_feeless_channel_list())]
 		#[frame_support::transactional]
 		pub fn add_channels_to_feeless_channel_list(
 			origin: OriginFor<T>,
@@ -113,7 +119,7 @@ pub mod pallet {
 		}
 
 		#[pallet::call_index(2)]
-		#[pallet::weight(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::remove_channels_from_feeless_channel_list())]
 		#[frame_support::transactional]
 		pub fn remove_channels_from_feeless_channel_list(
 			origin: OriginFor<T>,
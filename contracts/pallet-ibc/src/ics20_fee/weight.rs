@@ -0,0 +1,23 @@
+//! Weights of the ICS20 service charge extrinsics.
+
+use frame_support::pallet_prelude::Weight;
+
+pub trait WeightInfo {
+	fn set_charge() -> Weight;
+	fn add_channels_to_feeless_channel_list() -> Weight;
+	fn remove_channels_from_feeless_channel_list() -> Weight;
+}
+
+impl WeightInfo for () {
+	fn set_charge() -> Weight {
+		Weight::default()
+	}
+
+	fn add_channels_to_feeless_channel_list() -> Weight {
+		Weight::default()
+	}
+
+	fn remove_channels_from_feeless_channel_list() -> Weight {
+		Weight::default()
+	}
+}
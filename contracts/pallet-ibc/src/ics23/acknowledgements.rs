@@ -15,9 +15,9 @@ use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
 
 /// (port_id, channel_id, sequence) => hash
 /// trie key path: "acks/ports/{port_id}/channels/{channel_id}/sequences/{sequence}"
-pub struct Acknowledgements<T>(PhantomData<T>);
+pub struct Acknowledgements<T, I = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> Acknowledgements<T> {
+impl<T: Config<I>, I: 'static> Acknowledgements<T, I> {
 	pub fn insert(
 		(port_id, channel_id, sequence): (PortId, ChannelId, Sequence),
 		ack: AcknowledgementCommitment,
@@ -17,9 +17,9 @@ use tendermint_proto::Protobuf;
 // todo: pruning
 /// (port_id, channel_id) => ChannelEnd
 /// trie key path: "channelEnds/ports/{port_id}/channels/{channel_id}"
-pub struct Channels<T>(PhantomData<T>);
+pub struct Channels<T, I = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> Channels<T> {
+impl<T: Config<I>, I: 'static> Channels<T, I> {
 	pub fn get(port_id: PortId, channel_id: ChannelId) -> Option<Vec<u8>> {
 		let channel_path = format!("{}", ChannelEndsPath(port_id, channel_id));
 		let channel_key = apply_prefix(T::PalletPrefix::get(), vec![channel_path]);
@@ -8,9 +8,9 @@ use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
 
 /// client_id => client_states
 /// trie key path: "clients/{client_id}/clientState"
-pub struct ClientStates<T>(PhantomData<T>);
+pub struct ClientStates<T, I = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> ClientStates<T> {
+impl<T: Config<I>, I: 'static> ClientStates<T, I> {
 	pub fn get(client_id: &ClientId) -> Option<Vec<u8>> {
 		let client_state_path = format!("{}", ClientStatePath(client_id.clone()));
 		let client_state_key = apply_prefix(T::PalletPrefix::get(), vec![client_state_path]);
@@ -7,9 +7,9 @@ use sp_std::{marker::PhantomData, prelude::*};
 
 /// client_id => client_type
 /// trie key path: "clients/{}/clientType"
-pub struct Clients<T>(PhantomData<T>);
+pub struct Clients<T, I = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> Clients<T> {
+impl<T: Config<I>, I: 'static> Clients<T, I> {
 	pub fn get(client_id: &ClientId) -> Option<Vec<u8>> {
 		let client_type_path = format!("{}", ClientTypePath(client_id.clone()));
 		let client_type_key = apply_prefix(T::PalletPrefix::get(), vec![client_type_path]);
@@ -13,9 +13,9 @@ use tendermint_proto::Protobuf;
 // todo: pruning
 /// connection_id => ConnectionEnd
 /// trie key path: "connections/{}"
-pub struct Connections<T>(PhantomData<T>);
+pub struct Connections<T, I = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> Connections<T> {
+impl<T: Config<I>, I: 'static> Connections<T, I> {
 	pub fn get(connection_id: &ConnectionId) -> Option<Vec<u8>> {
 		let connection_path = format!("{}", ConnectionsPath(connection_id.clone()));
 		let connection_key = apply_prefix(T::PalletPrefix::get(), vec![connection_path]);
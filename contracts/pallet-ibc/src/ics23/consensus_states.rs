@@ -1,4 +1,7 @@
-use crate::{format, Config};
+use crate::{
+	format, ClientUpdateHeight, ClientUpdateTime, Config, ConsensusHeights, ConsensusStateHeights,
+	ConsensusStatesLimit,
+};
 use frame_support::storage::{child, child::ChildInfo};
 use ibc::{
 	core::ics24_host::{identifier::ClientId, path::ClientConsensusStatePath},
@@ -7,32 +10,95 @@ use ibc::{
 use ibc_primitives::apply_prefix;
 use sp_core::Get;
 use sp_std::{marker::PhantomData, prelude::*};
+use tendermint_proto::Protobuf;
+
+/// Number of consensus states kept per client, unless governance configured another limit for
+/// the client.
+pub const DEFAULT_CONSENSUS_STATES_LIMIT: u32 = 250;
 
 /// client_id, height => consensus_state
 /// trie key path: "clients/{client_id}/consensusStates/{height}"
-/// todo: only store up to 250 (height => consensus_state) per client_id
-pub struct ConsensusStates<T>(PhantomData<T>);
+/// Only the consensus states of the `ConsensusStatesLimit` highest heights of a client are kept,
+/// the ones of lower heights are removed along with their processed time and height.
+pub struct ConsensusStates<T, I = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> ConsensusStates<T> {
+impl<T: Config<I>, I: 'static> ConsensusStates<T, I> {
 	pub fn get(client_id: ClientId, height: Height) -> Option<Vec<u8>> {
-		let consensus_path = ClientConsensusStatePath {
-			client_id,
-			epoch: height.revision_number,
-			height: height.revision_height,
-		};
-		let path = format!("{consensus_path}");
-		let key = apply_prefix(T::PalletPrefix::get(), vec![path]);
+		let key = Self::key(client_id, height);
 		child::get(&ChildInfo::new_default(T::PalletPrefix::get()), &key)
 	}
 
 	pub fn insert(client_id: ClientId, height: Height, consensus_state: Vec<u8>) {
+		let key = Self::key(client_id.clone(), height);
+		child::put(&ChildInfo::new_default(T::PalletPrefix::get()), &key, &consensus_state);
+		ConsensusStateHeights::<T, I>::mutate(client_id.as_bytes().to_vec(), |heights| {
+			heights.insert(height);
+		});
+		Self::prune(&client_id);
+	}
+
+	/// Maximum number of consensus states kept for the client.
+	pub fn limit(client_id: &ClientId) -> u32 {
+		ConsensusStatesLimit::<T, I>::get(client_id.as_bytes().to_vec())
+			.unwrap_or(DEFAULT_CONSENSUS_STATES_LIMIT)
+	}
+
+	/// Removes the consensus states of the lowest heights of the client exceeding its limit,
+	/// returning the number of removed consensus states.
+	pub fn prune(client_id: &ClientId) -> u32 {
+		Self::prune_up_to(client_id, u32::MAX)
+	}
+
+	/// Removes at most `max` of the consensus states of the lowest heights of the client
+	/// exceeding its limit, returning the number of removed consensus states.
+	pub fn prune_up_to(client_id: &ClientId, max: u32) -> u32 {
+		let limit = Self::limit(client_id) as usize;
+		let client_id_bytes = client_id.as_bytes().to_vec();
+		let mut heights = ConsensusStateHeights::<T, I>::get(&client_id_bytes);
+		let mut removed = 0;
+		while heights.len() > limit && removed < max {
+			let Some(height) = heights.pop_first() else { break };
+			Self::remove(client_id, height);
+			removed += 1;
+		}
+		if removed > 0 {
+			ConsensusStateHeights::<T, I>::insert(&client_id_bytes, heights);
+		}
+		removed
+	}
+
+	/// Removes all the consensus states of the client, returning their number.
+	pub fn clear(client_id: &ClientId) -> u32 {
+		let heights = ConsensusStateHeights::<T, I>::take(client_id.as_bytes().to_vec());
+		for height in &heights {
+			Self::remove(client_id, *height);
+		}
+		heights.len() as u32
+	}
+
+	/// Removes the consensus state of the client at `height`, and the time and height it was
+	/// processed at.
+	fn remove(client_id: &ClientId, height: Height) {
+		let key = Self::key(client_id.clone(), height);
+		child::kill(&ChildInfo::new_default(T::PalletPrefix::get()), &key);
+		let client_id_bytes = client_id.as_bytes().to_vec();
+		// the encoding of a height can't fail
+		if let Ok(encoded_height) = height.encode_vec() {
+			ClientUpdateTime::<T, I>::remove(&client_id_bytes, &encoded_height);
+			ClientUpdateHeight::<T, I>::remove(&client_id_bytes, &encoded_height);
+		}
+		ConsensusHeights::<T, I>::mutate(&client_id_bytes, |heights| {
+			heights.remove(&height);
+		});
+	}
+
+	fn key(client_id: ClientId, height: Height) -> Vec<u8> {
 		let consensus_path = ClientConsensusStatePath {
 			client_id,
 			epoch: height.revision_number,
 			height: height.revision_height,
 		};
 		let path = format!("{consensus_path}");
-		let key = apply_prefix(T::PalletPrefix::get(), vec![path]);
-		child::put(&ChildInfo::new_default(T::PalletPrefix::get()), &key, &consensus_state)
+		apply_prefix(T::PalletPrefix::get(), vec![path])
 	}
 }
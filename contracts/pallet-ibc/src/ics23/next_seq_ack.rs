@@ -12,9 +12,9 @@ use sp_std::marker::PhantomData;
 // todo: pruning
 /// (port_id, channel_id) => Sequence
 /// trie key path: "nextSequenceAck/ports/{port_id}/channels/{channel_id}"
-pub struct NextSequenceAck<T>(PhantomData<T>);
+pub struct NextSequenceAck<T, I = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> NextSequenceAck<T> {
+impl<T: Config<I>, I: 'static> NextSequenceAck<T, I> {
 	pub fn get(port_id: PortId, channel_id: ChannelId) -> Option<u64> {
 		let next_seq_ack_path = format!("{}", SeqAcksPath(port_id, channel_id));
 		let next_seq_ack_key = apply_prefix(T::PalletPrefix::get(), vec![next_seq_ack_path]);
@@ -12,9 +12,9 @@ use sp_std::marker::PhantomData;
 // todo: pruning
 /// (port_id, channel_id) => Sequence
 /// trie key path: "nextSequenceRecv/ports/{port_id}/channels/{channel_id}"
-pub struct NextSequenceRecv<T>(PhantomData<T>);
+pub struct NextSequenceRecv<T, I = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> NextSequenceRecv<T> {
+impl<T: Config<I>, I: 'static> NextSequenceRecv<T, I> {
 	pub fn get(port_id: PortId, channel_id: ChannelId) -> Option<u64> {
 		let next_seq_recv_path = format!("{}", SeqRecvsPath(port_id, channel_id));
 		let next_seq_recv_key = apply_prefix(T::PalletPrefix::get(), vec![next_seq_recv_path]);
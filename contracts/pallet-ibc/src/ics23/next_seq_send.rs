@@ -12,9 +12,9 @@ use sp_std::marker::PhantomData;
 // todo: pruning
 /// (port_id, channel_id) => Sequence
 /// trie key path: "nextSequenceSend/ports/{port_id}/channels/{channel_id}"
-pub struct NextSequenceSend<T>(PhantomData<T>);
+pub struct NextSequenceSend<T, I = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> NextSequenceSend<T> {
+impl<T: Config<I>, I: 'static> NextSequenceSend<T, I> {
 	pub fn get(port_id: PortId, channel_id: ChannelId) -> Option<u64> {
 		let next_seq_send_path = format!("{}", SeqSendsPath(port_id, channel_id));
 		let next_seq_send_key = apply_prefix(T::PalletPrefix::get(), vec![next_seq_send_path]);
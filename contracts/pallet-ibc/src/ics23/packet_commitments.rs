@@ -15,9 +15,9 @@ use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
 
 /// (port_id, channel_id, sequence) => hash
 /// trie key path: "commitments/ports/{port_id}/channels/{channel_id}/sequences/{sequence}"
-pub struct PacketCommitment<T>(PhantomData<T>);
+pub struct PacketCommitment<T, I = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> PacketCommitment<T> {
+impl<T: Config<I>, I: 'static> PacketCommitment<T, I> {
 	pub fn insert(
 		(port_id, channel_id, sequence): (PortId, ChannelId, Sequence),
 		commitment: PacketCommitmentType,
@@ -11,12 +11,11 @@ use ibc_primitives::apply_prefix;
 use sp_core::Get;
 use sp_std::{marker::PhantomData, prelude::*};
 
-// todo: pruning
 /// (port_id, channel_id, sequence) => hash
 /// trie key path: "receipts/ports/{port_id}/channels/{channel_id}/sequences/{sequence}"
-pub struct PacketReceipt<T>(PhantomData<T>);
+pub struct PacketReceipt<T, I = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> PacketReceipt<T> {
+impl<T: Config<I>, I: 'static> PacketReceipt<T, I> {
 	pub fn insert(
 		(port_id, channel_id, sequence): (PortId, ChannelId, Sequence),
 		receipt: Vec<u8>,
@@ -34,12 +33,12 @@ impl<T: Config> PacketReceipt<T> {
 		child::get(&ChildInfo::new_default(T::PalletPrefix::get()), &receipt_key)
 	}
 
-	// pub fn remove((port_id, channel_id, sequence): (PortId, ChannelId, Sequence)) {
-	// 	let receipt_path = ReceiptsPath { port_id, channel_id, sequence };
-	// 	let receipt_path = format!("{}", receipt_path);
-	// 	let receipt_key = apply_prefix_and_encode(T::PalletPrefix::get(), vec![receipt_path]);
-	// 	child::kill(&ChildInfo::new_default(T::PalletPrefix::get()), &receipt_key)
-	// }
+	pub fn remove((port_id, channel_id, sequence): (PortId, ChannelId, Sequence)) {
+		let receipt_path = ReceiptsPath { port_id, channel_id, sequence };
+		let receipt_path = format!("{receipt_path}");
+		let receipt_key = apply_prefix(T::PalletPrefix::get(), vec![receipt_path]);
+		child::kill(&ChildInfo::new_default(T::PalletPrefix::get()), &receipt_key)
+	}
 
 	pub fn contains_key((port_id, channel_id, sequence): (PortId, ChannelId, Sequence)) -> bool {
 		let receipt_path = ReceiptsPath { port_id, channel_id, sequence };
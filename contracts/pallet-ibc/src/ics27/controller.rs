@@ -0,0 +1,284 @@
+//! Controller module, bound to the `icacontroller-{owner}` ports of the accounts registering
+//! interchain accounts. The ports are bound on registration, and each of them has at most one open
+//! channel per connection, to the `icahost` port of the host chain.
+
+use super::{
+	Config, ControllerChannels, ControllerEnabled, ControllerPorts, Event, IcaAcknowledgement,
+	Metadata, Pallet, RegisteredAccounts, CONTROLLER_PORT_PREFIX, HOST_PORT_ID,
+};
+use alloc::{format, string::ToString};
+use core::marker::PhantomData;
+use ibc::{
+	core::{
+		ics03_connection::context::ConnectionReader,
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			context::ChannelReader,
+			error::Error as Ics04Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module as IbcModule, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+use sp_core::crypto::AccountId32;
+use sp_std::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IcaController<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for IcaController<T> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+/// Connection of an interchain accounts channel of the controller.
+fn channel_connection(
+	ctx: &dyn ModuleCallbackContext,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+) -> Result<Vec<u8>, Ics04Error> {
+	let channel_end = ctx.channel_end(&(port_id.clone(), *channel_id))?;
+	let connection_id =
+		channel_end.connection_hops().first().ok_or_else(Ics04Error::missing_channel)?;
+	Ok(connection_id.as_bytes().to_vec())
+}
+
+fn disallowed(action: &str) -> Ics04Error {
+	Ics04Error::implementation_specific(format!(
+		"The interchain accounts controller doesn't {action}"
+	))
+}
+
+impl<T: Config + Send + Sync> IbcModule for IcaController<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		_channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		if !ControllerEnabled::<T>::get() {
+			return Err(Ics04Error::implementation_specific(
+				"The interchain accounts controller is disabled".to_string(),
+			))
+		}
+		if order != Order::Ordered {
+			return Err(Ics04Error::unknown_order_type(order.to_string()))
+		}
+		if !port_id.as_str().starts_with(CONTROLLER_PORT_PREFIX) ||
+			!ControllerPorts::<T>::contains_key(port_id.as_bytes())
+		{
+			return Err(Ics04Error::implementation_specific(format!(
+				"The port {port_id} isn't bound to the interchain accounts controller"
+			)))
+		}
+		if counterparty.port_id().as_str() != HOST_PORT_ID {
+			return Err(Ics04Error::implementation_specific(format!(
+				"Invalid counterparty port {}, expected {HOST_PORT_ID}",
+				counterparty.port_id()
+			)))
+		}
+		let metadata = Metadata::parse(version)?;
+		let connection_id = connection_hops.first().ok_or_else(Ics04Error::missing_channel)?;
+		let connection_end =
+			ctx.connection_end(connection_id).map_err(Ics04Error::ics03_connection)?;
+		let host_connection_id = connection_end.counterparty().connection_id();
+		if metadata.controller_connection_id != connection_id.as_str() ||
+			host_connection_id.map(|id| id.as_str()) !=
+				Some(metadata.host_connection_id.as_str())
+		{
+			return Err(Ics04Error::implementation_specific(format!(
+				"Invalid connections {} and {} of the version",
+				metadata.controller_connection_id, metadata.host_connection_id
+			)))
+		}
+		if ControllerChannels::<T>::contains_key(connection_id.as_bytes(), port_id.as_bytes()) {
+			return Err(Ics04Error::implementation_specific(format!(
+				"The interchain account of {port_id} already has an open channel"
+			)))
+		}
+		Ok(())
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_order: Order,
+		_connection_hops: &[ConnectionId],
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty: &Counterparty,
+		_version: &Version,
+		_counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		Err(disallowed("accept channels"))
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let metadata = Metadata::parse(counterparty_version)?;
+		if metadata.address.is_empty() {
+			return Err(Ics04Error::implementation_specific(
+				"The host didn't set the address of the interchain account".to_string(),
+			))
+		}
+		let owner = ControllerPorts::<T>::get(port_id.as_bytes()).ok_or_else(|| {
+			Ics04Error::implementation_specific(format!(
+				"The port {port_id} isn't bound to the interchain accounts controller"
+			))
+		})?;
+		let connection_id = channel_connection(ctx, port_id, channel_id)?;
+		let port_id = port_id.as_bytes().to_vec();
+		if ControllerChannels::<T>::contains_key(&connection_id, &port_id) {
+			return Err(Ics04Error::implementation_specific(
+				"The interchain account already has an open channel".to_string(),
+			))
+		}
+		let channel_id = channel_id.to_string().as_bytes().to_vec();
+		let address = metadata.address.into_bytes();
+		ControllerChannels::<T>::insert(&connection_id, &port_id, &channel_id);
+		RegisteredAccounts::<T>::insert(&connection_id, &port_id, &address);
+		Pallet::<T>::deposit_event(Event::<T>::InterchainAccountRegistered {
+			owner,
+			connection_id,
+			channel_id,
+			address,
+		});
+		Ok(())
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		Err(disallowed("accept channels"))
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		Err(Ics04Error::implementation_specific(
+			"Interchain accounts channels can't be closed".to_string(),
+		))
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let connection_id = channel_connection(ctx, port_id, channel_id)?;
+		Pallet::<T>::close_channel(connection_id, port_id, channel_id);
+		Ok(())
+	}
+
+	fn on_recv_packet(
+		&self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_packet: &mut Packet,
+		_relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		let ack = IcaAcknowledgement::Error(
+			"The interchain accounts controller doesn't receive packets".to_string(),
+		);
+		serde_json::to_vec(&ack).map(Acknowledgement::from_bytes).map_err(|e| {
+			Ics04Error::implementation_specific(format!(
+				"Failed to encode the acknowledgement: {e}"
+			))
+		})
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &Acknowledgement,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let ack = serde_json::from_slice::<IcaAcknowledgement>(acknowledgement.as_ref()).map_err(
+			|e| {
+				Ics04Error::implementation_specific(format!(
+					"Failed to decode the acknowledgement: {e}"
+				))
+			},
+		)?;
+		if let Some(owner) = ControllerPorts::<T>::get(packet.source_port.as_bytes()) {
+			Pallet::<T>::deposit_event(Event::<T>::TxAcknowledged {
+				owner,
+				channel_id: packet.source_channel.to_string().as_bytes().to_vec(),
+				sequence: packet.sequence.into(),
+				success: ack.is_success(),
+			});
+		}
+		Ok(())
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		// the ordered channel is closed on timeout, the owner has to register the interchain
+		// account again to open a new channel to it
+		let connection_id = channel_connection(ctx, &packet.source_port, &packet.source_channel)?;
+		Pallet::<T>::close_channel(connection_id, &packet.source_port, &packet.source_channel);
+		if let Some(owner) = ControllerPorts::<T>::get(packet.source_port.as_bytes()) {
+			Pallet::<T>::deposit_event(Event::<T>::TxTimedOut {
+				owner,
+				channel_id: packet.source_channel.to_string().as_bytes().to_vec(),
+				sequence: packet.sequence.into(),
+			});
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Removes the channel of the interchain account of the controller port, if it's the open one.
+	fn close_channel(connection_id: Vec<u8>, port_id: &PortId, channel_id: &ChannelId) {
+		let port_id = port_id.as_bytes().to_vec();
+		let channel_id = channel_id.to_string().as_bytes().to_vec();
+		if ControllerChannels::<T>::get(&connection_id, &port_id) == Some(channel_id) {
+			ControllerChannels::<T>::remove(&connection_id, &port_id);
+		}
+	}
+}
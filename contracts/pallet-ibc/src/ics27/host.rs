@@ -0,0 +1,335 @@
+//! Host module, bound to the `icahost` port. It creates an interchain account for each controller
+//! port opening a channel to it, and executes the runtime calls of the transactions received on
+//! the channel with a signed origin of the account.
+
+use super::{
+	Config, Event, HostChannels, HostEnabled, IcaAcknowledgement, InterchainAccountPacketData,
+	InterchainAccounts, Metadata, Pallet, CONTROLLER_PORT_PREFIX, HOST_PORT_ID,
+	RUNTIME_CALL_TYPE_URL,
+};
+use alloc::{
+	format,
+	string::{String, ToString},
+};
+use codec::{Decode, DecodeLimit, Encode};
+use core::marker::PhantomData;
+use frame_support::{
+	dispatch::GetDispatchInfo,
+	storage::{with_transaction, TransactionOutcome},
+	traits::{Contains, Get},
+	weights::Weight,
+};
+use frame_system::RawOrigin;
+use ibc::{
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			context::ChannelReader,
+			error::Error as Ics04Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module as IbcModule, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+use ibc_proto::{
+	cosmos::base::abci::v1beta1::TxMsgData,
+	google::protobuf::Any,
+	ibc::applications::interchain_accounts::v1::{CosmosTx, Type},
+};
+use prost::Message;
+use sp_core::{crypto::AccountId32, hashing::blake2_256};
+use sp_runtime::{
+	traits::{Dispatchable, TrailingZeroInput},
+	DispatchError,
+};
+use sp_std::prelude::*;
+
+/// Maximum depth of the runtime calls decoded from the transactions.
+const CALL_DECODE_DEPTH_LIMIT: u32 = 256;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IcaHost<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for IcaHost<T> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+/// Account created by the host for the controller port on the connection.
+fn derive_account<T: Config>(connection_id: &ConnectionId, port_id: &PortId) -> T::AccountId {
+	let entropy =
+		(b"ics27/account", connection_id.as_bytes(), port_id.as_bytes()).using_encoded(blake2_256);
+	T::AccountId::decode(&mut TrailingZeroInput::new(entropy.as_ref()))
+		.expect("infinite length input; no invalid inputs for type; qed")
+}
+
+/// Connection and controller port of an interchain accounts channel of the host.
+fn channel_key(
+	ctx: &dyn ModuleCallbackContext,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+) -> Result<(Vec<u8>, Vec<u8>), Ics04Error> {
+	let channel_end = ctx.channel_end(&(port_id.clone(), *channel_id))?;
+	let connection_id =
+		channel_end.connection_hops().first().ok_or_else(Ics04Error::missing_channel)?;
+	Ok((
+		connection_id.as_bytes().to_vec(),
+		channel_end.counterparty().port_id().as_bytes().to_vec(),
+	))
+}
+
+/// Decodes the runtime call of a message of a transaction, if the interchain accounts are allowed
+/// to execute it.
+fn decode_call<T: Config>(message: Any) -> Result<<T as Config>::RuntimeCall, String> {
+	if message.type_url != RUNTIME_CALL_TYPE_URL {
+		return Err(format!("Unsupported message type {}", message.type_url))
+	}
+	let call = <T as Config>::RuntimeCall::decode_with_depth_limit(
+		CALL_DECODE_DEPTH_LIMIT,
+		&mut message.value.as_slice(),
+	)
+	.map_err(|e| format!("Invalid runtime call: {e}"))?;
+	if !T::AllowedCalls::contains(&call) {
+		return Err("The runtime call isn't allowed for interchain accounts".to_string())
+	}
+	Ok(call)
+}
+
+impl<T: Config + Send + Sync> IcaHost<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	/// Executes the transaction of the packet, all its calls being reverted if one of them fails.
+	fn execute_tx(
+		ctx: &dyn ModuleCallbackContext,
+		packet: &Packet,
+	) -> Result<T::AccountId, (Option<T::AccountId>, String)> {
+		if !HostEnabled::<T>::get() {
+			return Err((None, "The interchain accounts host is disabled".to_string()))
+		}
+		let data = serde_json::from_slice::<InterchainAccountPacketData>(&packet.data)
+			.map_err(|e| (None, format!("Invalid packet data: {e}")))?;
+		if data.ty != Type::ExecuteTx.as_str_name() {
+			return Err((None, format!("Unsupported packet type {}", data.ty)))
+		}
+		let (connection_id, port_id) =
+			channel_key(ctx, &packet.destination_port, &packet.destination_channel)
+				.map_err(|e| (None, e.to_string()))?;
+		let account = InterchainAccounts::<T>::get(connection_id, port_id)
+			.ok_or_else(|| (None, "The interchain account doesn't exist".to_string()))?;
+		let error = |e: String| (Some(account.clone()), e);
+		let tx = CosmosTx::decode(data.data.as_slice())
+			.map_err(|e| error(format!("Invalid transaction: {e}")))?;
+		if tx.messages.is_empty() || tx.messages.len() > T::MaxCalls::get() as usize {
+			return Err(error(format!(
+				"The transaction must have between 1 and {} messages",
+				T::MaxCalls::get()
+			)))
+		}
+		let calls = tx
+			.messages
+			.into_iter()
+			.map(decode_call::<T>)
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(error)?;
+		let weight = calls.iter().fold(Weight::zero(), |weight, call| {
+			weight.saturating_add(call.get_dispatch_info().weight)
+		});
+		if weight.any_gt(T::MaxTxWeight::get()) {
+			return Err(error(format!("The weight {weight:?} of the transaction is too high")))
+		}
+		with_transaction(|| {
+			for call in calls {
+				if let Err(e) = call.dispatch(RawOrigin::Signed(account.clone()).into()) {
+					return TransactionOutcome::Rollback(Err(e.error))
+				}
+			}
+			TransactionOutcome::Commit(Ok::<_, DispatchError>(()))
+		})
+		.map_err(|e| error(format!("Failed to execute the transaction: {e:?}")))?;
+		Ok(account)
+	}
+}
+
+impl<T: Config + Send + Sync> IbcModule for IcaHost<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_order: Order,
+		_connection_hops: &[ConnectionId],
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty: &Counterparty,
+		_version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		Err(Ics04Error::implementation_specific(
+			"Channels can't be opened from the interchain accounts host".to_string(),
+		))
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		_channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		_version: &Version,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		if !HostEnabled::<T>::get() {
+			return Err(Ics04Error::implementation_specific(
+				"The interchain accounts host is disabled".to_string(),
+			))
+		}
+		if order != Order::Ordered {
+			return Err(Ics04Error::unknown_order_type(order.to_string()))
+		}
+		let controller_port_id = counterparty.port_id();
+		if port_id.as_str() != HOST_PORT_ID ||
+			!controller_port_id.as_str().starts_with(CONTROLLER_PORT_PREFIX)
+		{
+			return Err(Ics04Error::implementation_specific(format!(
+				"Invalid ports {port_id} and {controller_port_id}"
+			)))
+		}
+		let mut metadata = Metadata::parse(counterparty_version)?;
+		let connection_id = connection_hops.first().ok_or_else(Ics04Error::missing_channel)?;
+		if metadata.host_connection_id != connection_id.as_str() {
+			return Err(Ics04Error::implementation_specific(format!(
+				"Invalid host connection {}, expected {connection_id}",
+				metadata.host_connection_id
+			)))
+		}
+		let key = (connection_id.as_bytes().to_vec(), controller_port_id.as_bytes().to_vec());
+		if HostChannels::<T>::contains_key(&key.0, &key.1) {
+			return Err(Ics04Error::implementation_specific(format!(
+				"The interchain account of {controller_port_id} already has an open channel"
+			)))
+		}
+		let account = InterchainAccounts::<T>::get(&key.0, &key.1).unwrap_or_else(|| {
+			let account = derive_account::<T>(connection_id, controller_port_id);
+			InterchainAccounts::<T>::insert(&key.0, &key.1, &account);
+			Pallet::<T>::deposit_event(Event::<T>::InterchainAccountCreated {
+				connection_id: key.0.clone(),
+				port_id: key.1.clone(),
+				account: account.clone(),
+			});
+			account
+		});
+		metadata.address = format!("0x{}", hex::encode(account.encode()));
+		metadata.to_version()
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		Err(Ics04Error::implementation_specific(
+			"Channels can't be opened from the interchain accounts host".to_string(),
+		))
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let (connection_id, controller_port_id) = channel_key(ctx, port_id, channel_id)?;
+		HostChannels::<T>::insert(
+			connection_id,
+			controller_port_id,
+			channel_id.to_string().as_bytes().to_vec(),
+		);
+		Ok(())
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		Err(Ics04Error::implementation_specific(
+			"Interchain accounts channels can't be closed".to_string(),
+		))
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let (connection_id, controller_port_id) = channel_key(ctx, port_id, channel_id)?;
+		HostChannels::<T>::remove(connection_id, controller_port_id);
+		Ok(())
+	}
+
+	fn on_recv_packet(
+		&self,
+		ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		_relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		let (account, ack) = match Self::execute_tx(ctx, packet) {
+			Ok(account) =>
+				(Some(account), IcaAcknowledgement::Result(TxMsgData::default().encode_to_vec())),
+			Err((account, error)) => (account, IcaAcknowledgement::Error(error)),
+		};
+		if let Some(account) = account {
+			Pallet::<T>::deposit_event(Event::<T>::TxExecuted {
+				account,
+				channel_id: packet.destination_channel.to_string().as_bytes().to_vec(),
+				sequence: packet.sequence.into(),
+				success: ack.is_success(),
+			});
+		}
+		serde_json::to_vec(&ack).map(Acknowledgement::from_bytes).map_err(|e| {
+			Ics04Error::implementation_specific(format!(
+				"Failed to encode the acknowledgement: {e}"
+			))
+		})
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_packet: &mut Packet,
+		_acknowledgement: &Acknowledgement,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		Err(Ics04Error::implementation_specific(
+			"The interchain accounts host doesn't send packets".to_string(),
+		))
+	}
+}
@@ -0,0 +1,469 @@
+//! ICS-27 interchain accounts. The host module executes the runtime calls sent by the controller
+//! chains on behalf of the interchain accounts it creates for them, and the controller module lets
+//! the accounts of this chain register interchain accounts on host chains and send them
+//! transactions.
+//!
+//! The transactions executed by the host are `CosmosTx`s of messages with the
+//! [`RUNTIME_CALL_TYPE_URL`] type url, whose values are SCALE encoded runtime calls.
+
+use alloc::{
+	boxed::Box,
+	format,
+	string::{String, ToString},
+};
+use codec::Encode;
+use core::str::FromStr;
+use ibc::core::{
+	ics04_channel::{error::Error as Ics04Error, Version},
+	ics24_host::identifier::{ConnectionId, PortId},
+	ics26_routing::context::ModuleId,
+};
+use ibc_primitives::CallbackWeight;
+use serde::{Deserialize, Serialize};
+use sp_std::prelude::*;
+
+pub use controller::IcaController;
+pub use host::IcaHost;
+pub use pallet::*;
+pub use weight::{ControllerWeightHandler, HostWeightHandler, WeightInfo};
+
+mod controller;
+mod host;
+mod weight;
+
+/// Version of the interchain accounts protocol negotiated in the channel handshakes.
+pub const VERSION: &str = "ics27-1";
+/// Port the host module is bound to.
+pub const HOST_PORT_ID: &str = "icahost";
+/// Prefix of the ports of the controller module, followed by the hex encoded owner account.
+pub const CONTROLLER_PORT_PREFIX: &str = "icacontroller-";
+pub const HOST_MODULE_ID: &str = "icahost";
+pub const CONTROLLER_MODULE_ID: &str = "icacontroller";
+/// Encoding of the transactions.
+pub const ENCODING: &str = "proto3";
+/// Type of the transactions.
+pub const TX_TYPE: &str = "sdk_multi_msg";
+/// Type url of the messages of the transactions holding a SCALE encoded runtime call.
+pub const RUNTIME_CALL_TYPE_URL: &str = "/pallet_ibc.ics27.RuntimeCall";
+
+/// Version of the interchain accounts channels.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+	pub version: String,
+	pub controller_connection_id: String,
+	pub host_connection_id: String,
+	/// Address of the interchain account, set by the host on channel open try
+	#[serde(default)]
+	pub address: String,
+	pub encoding: String,
+	pub tx_type: String,
+}
+
+impl Metadata {
+	pub fn new(controller_connection_id: &ConnectionId, host_connection_id: &ConnectionId) -> Self {
+		Self {
+			version: VERSION.to_string(),
+			controller_connection_id: controller_connection_id.to_string(),
+			host_connection_id: host_connection_id.to_string(),
+			address: String::new(),
+			encoding: ENCODING.to_string(),
+			tx_type: TX_TYPE.to_string(),
+		}
+	}
+
+	/// Parses and validates the metadata of a channel version.
+	pub fn parse(version: &Version) -> Result<Self, Ics04Error> {
+		let metadata = serde_json::from_str::<Self>(&version.to_string()).map_err(|e| {
+			Ics04Error::implementation_specific(format!(
+				"Invalid interchain accounts version {version}: {e}"
+			))
+		})?;
+		if metadata.version != VERSION {
+			return Err(Ics04Error::no_common_version())
+		}
+		if metadata.encoding != ENCODING || metadata.tx_type != TX_TYPE {
+			return Err(Ics04Error::implementation_specific(format!(
+				"Unsupported encoding {} or tx type {}",
+				metadata.encoding, metadata.tx_type
+			)))
+		}
+		Ok(metadata)
+	}
+
+	pub fn to_version(&self) -> Result<Version, Ics04Error> {
+		serde_json::to_string(self).map(Version::new).map_err(|e| {
+			Ics04Error::implementation_specific(format!(
+				"Failed to encode the channel version: {e}"
+			))
+		})
+	}
+}
+
+/// Data of the packets sent by the controllers to the hosts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterchainAccountPacketData {
+	/// `TYPE_EXECUTE_TX`
+	#[serde(rename = "type")]
+	pub ty: String,
+	/// Protobuf encoded `CosmosTx`
+	#[serde(with = "crate::serde_base64")]
+	pub data: Vec<u8>,
+	#[serde(default)]
+	pub memo: String,
+}
+
+/// Acknowledgement of the packets executed by the hosts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IcaAcknowledgement {
+	Result(#[serde(with = "crate::serde_base64")] Vec<u8>),
+	Error(String),
+}
+
+impl IcaAcknowledgement {
+	pub fn is_success(&self) -> bool {
+		matches!(self, Self::Result(_))
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::{
+		controller_port_id, host_port_id, InterchainAccountPacketData, Metadata, WeightInfo,
+	};
+	use crate::{routing::Context, Any};
+	use frame_support::{
+		dispatch::{GetDispatchInfo, PostDispatchInfo},
+		pallet_prelude::*,
+		traits::{Contains, EnsureOrigin},
+	};
+	use frame_system::pallet_prelude::*;
+	use ibc::core::{
+		ics03_connection::context::ConnectionReader,
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty, Order, State},
+			context::ChannelReader,
+		},
+	};
+	use ibc_primitives::{
+		channel_id_from_bytes, connection_id_from_bytes, HandlerMessage, IbcHandler, Timeout,
+	};
+	use ibc_proto::ibc::applications::interchain_accounts::v1::{CosmosTx, Type};
+	use prost::Message;
+	use sp_core::crypto::AccountId32;
+	use sp_runtime::traits::Dispatchable;
+	use sp_std::prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + crate::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		/// Calls executed by the host on behalf of the interchain accounts
+		type RuntimeCall: Parameter
+			+ Dispatchable<
+				RuntimeOrigin = <Self as frame_system::Config>::RuntimeOrigin,
+				PostInfo = PostDispatchInfo,
+			> + GetDispatchInfo;
+		/// Calls the interchain accounts are allowed to execute
+		type AllowedCalls: Contains<<Self as Config>::RuntimeCall>;
+		/// Maximum number of calls in a transaction executed by the host
+		#[pallet::constant]
+		type MaxCalls: Get<u32>;
+		/// Maximum weight of the calls of a transaction executed by the host
+		#[pallet::constant]
+		type MaxTxWeight: Get<Weight>;
+		/// Weights of the extrinsics and of the transactions executed by the host
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub (super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	/// Whether the host module accepts channels and executes transactions
+	pub type HostEnabled<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::storage]
+	/// Whether the controller module registers interchain accounts and sends transactions
+	pub type ControllerEnabled<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::storage]
+	/// connection_id, controller port_id => interchain account created by the host
+	pub type InterchainAccounts<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		T::AccountId,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// connection_id, controller port_id => open channel of the interchain account on the host
+	pub type HostChannels<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// controller port_id => account owning the port
+	pub type ControllerPorts<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, T::AccountId, OptionQuery>;
+
+	#[pallet::storage]
+	/// connection_id, controller port_id => open channel of the interchain account on the
+	/// controller
+	pub type ControllerChannels<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// connection_id, controller port_id => address of the interchain account on the host chain
+	pub type RegisteredAccounts<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub (super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The host and controller modules have been enabled or disabled
+		ParamsUpdated { host_enabled: bool, controller_enabled: bool },
+		/// The host created an interchain account for a controller port
+		InterchainAccountCreated { connection_id: Vec<u8>, port_id: Vec<u8>, account: T::AccountId },
+		/// The host executed a transaction of an interchain account
+		TxExecuted { account: T::AccountId, channel_id: Vec<u8>, sequence: u64, success: bool },
+		/// The channel of an interchain account of the owner has been opened on the host chain
+		InterchainAccountRegistered {
+			owner: T::AccountId,
+			connection_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			address: Vec<u8>,
+		},
+		/// A transaction has been sent to the interchain account of the owner
+		TxSent { owner: T::AccountId, connection_id: Vec<u8>, channel_id: Vec<u8>, sequence: u64 },
+		/// A transaction sent to the interchain account of the owner has been acknowledged
+		TxAcknowledged { owner: T::AccountId, channel_id: Vec<u8>, sequence: u64, success: bool },
+		/// A transaction sent to the interchain account of the owner timed out, which closed
+		/// the channel of the account
+		TxTimedOut { owner: T::AccountId, channel_id: Vec<u8>, sequence: u64 },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The controller module is disabled
+		ControllerDisabled,
+		/// The connection doesn't exist
+		ConnectionNotFound,
+		/// The interchain account of the owner already has an open channel on the connection
+		ActiveChannelExists,
+		/// The interchain account of the owner has no open channel on the connection
+		NoActiveChannel,
+		/// The transaction has no messages
+		EmptyTx,
+		/// The memo isn't valid utf8
+		InvalidMemo,
+		/// Failed to encode the port, version or packet data
+		EncodingFailed,
+		/// Failed to open the channel
+		ChannelInitError,
+		/// Failed to send the packet
+		PacketSendError,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T>
+	where
+		T: Send + Sync,
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+		AccountId32: From<<T as frame_system::Config>::AccountId>,
+	{
+		/// Enable or disable the host and controller modules.
+		#[pallet::call_index(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_params())]
+		pub fn set_params(
+			origin: OriginFor<T>,
+			host_enabled: bool,
+			controller_enabled: bool,
+		) -> DispatchResult {
+			<T as crate::Config>::AdminOrigin::ensure_origin(origin)?;
+			HostEnabled::<T>::put(host_enabled);
+			ControllerEnabled::<T>::put(controller_enabled);
+			Self::deposit_event(Event::<T>::ParamsUpdated { host_enabled, controller_enabled });
+			Ok(())
+		}
+
+		/// Register an interchain account of the origin on the host chain of the connection, by
+		/// opening an ordered channel from the controller port of the origin to the host port.
+		/// The address of the account is known once the channel handshake completes.
+		#[pallet::call_index(1)]
+		#[pallet::weight(<T as Config>::WeightInfo::register_interchain_account())]
+		#[frame_support::transactional]
+		pub fn register_interchain_account(
+			origin: OriginFor<T>,
+			connection_id: Vec<u8>,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			ensure!(ControllerEnabled::<T>::get(), Error::<T>::ControllerDisabled);
+			let port_id = controller_port_id::<T>(&owner).ok_or(Error::<T>::EncodingFailed)?;
+			ensure!(
+				!ControllerChannels::<T>::contains_key(&connection_id, port_id.as_bytes()),
+				Error::<T>::ActiveChannelExists
+			);
+			let connection_id = connection_id_from_bytes(connection_id)
+				.map_err(|_| Error::<T>::ConnectionNotFound)?;
+			let connection_end = Context::<T>::new()
+				.connection_end(&connection_id)
+				.map_err(|_| Error::<T>::ConnectionNotFound)?;
+			let host_connection_id = connection_end
+				.counterparty()
+				.connection_id()
+				.ok_or(Error::<T>::ConnectionNotFound)?;
+			let version = Metadata::new(&connection_id, host_connection_id)
+				.to_version()
+				.map_err(|_| Error::<T>::EncodingFailed)?;
+			let channel_end = ChannelEnd::new(
+				State::Init,
+				Order::Ordered,
+				Counterparty::new(host_port_id(), None),
+				vec![connection_id],
+				version,
+			);
+			ControllerPorts::<T>::insert(port_id.as_bytes().to_vec(), owner);
+			crate::Pallet::<T>::handle_message(HandlerMessage::OpenChannel {
+				port_id,
+				channel_end,
+			})
+			.map_err(|e| {
+				log::trace!(target: "pallet_ibc", "[register_interchain_account] error: {e:?}");
+				Error::<T>::ChannelInitError
+			})?;
+			Ok(())
+		}
+
+		/// Send a transaction of messages to the interchain account of the origin on the host
+		/// chain of the connection. The messages of the transactions executed by substrate hosts
+		/// hold SCALE encoded runtime calls under the `RUNTIME_CALL_TYPE_URL` type url.
+		#[pallet::call_index(2)]
+		#[pallet::weight(<T as Config>::WeightInfo::send_tx(messages.encoded_size() as u32))]
+		#[frame_support::transactional]
+		pub fn send_tx(
+			origin: OriginFor<T>,
+			connection_id: Vec<u8>,
+			messages: Vec<Any>,
+			memo: Vec<u8>,
+			timeout: Timeout,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			ensure!(ControllerEnabled::<T>::get(), Error::<T>::ControllerDisabled);
+			ensure!(!messages.is_empty(), Error::<T>::EmptyTx);
+			let port_id = controller_port_id::<T>(&owner).ok_or(Error::<T>::EncodingFailed)?;
+			let channel_id = ControllerChannels::<T>::get(&connection_id, port_id.as_bytes())
+				.and_then(|channel_id| channel_id_from_bytes(channel_id).ok())
+				.ok_or(Error::<T>::NoActiveChannel)?;
+			let tx = CosmosTx { messages: messages.into_iter().map(Into::into).collect() };
+			let data = InterchainAccountPacketData {
+				ty: Type::ExecuteTx.as_str_name().to_string(),
+				data: tx.encode_to_vec(),
+				memo: String::from_utf8(memo).map_err(|_| Error::<T>::InvalidMemo)?,
+			};
+			let data = serde_json::to_vec(&data).map_err(|_| Error::<T>::EncodingFailed)?;
+			let sequence = Context::<T>::new()
+				.get_next_sequence_send(&(port_id.clone(), channel_id))
+				.map_err(|_| Error::<T>::NoActiveChannel)?;
+			crate::Pallet::<T>::handle_message(HandlerMessage::SendPacket {
+				data,
+				timeout,
+				port_id,
+				channel_id,
+			})
+			.map_err(|e| {
+				log::trace!(target: "pallet_ibc", "[send_tx] error: {e:?}");
+				Error::<T>::PacketSendError
+			})?;
+			Self::deposit_event(Event::<T>::TxSent {
+				owner,
+				connection_id,
+				channel_id: channel_id.to_string().as_bytes().to_vec(),
+				sequence: sequence.into(),
+			});
+			Ok(())
+		}
+	}
+}
+
+/// Module of the interchain accounts ports, for the `ModuleRouter::lookup_module_by_port` of the
+/// runtimes routing the interchain accounts modules.
+pub fn lookup_module_by_port(port_id: &PortId) -> Option<ModuleId> {
+	if port_id.as_str() == HOST_PORT_ID {
+		ModuleId::from_str(HOST_MODULE_ID).ok()
+	} else if port_id.as_str().starts_with(CONTROLLER_PORT_PREFIX) {
+		ModuleId::from_str(CONTROLLER_MODULE_ID).ok()
+	} else {
+		None
+	}
+}
+
+/// Weight handler of the callbacks of the interchain accounts modules, for the
+/// `ModuleRouter::weight_handler` of the runtimes routing the interchain accounts modules.
+pub fn weight_handler<T: Config>(module_id: &ModuleId) -> Option<Box<dyn CallbackWeight>> {
+	match module_id.as_ref() {
+		HOST_MODULE_ID => Some(Box::new(HostWeightHandler::<T>::default())),
+		CONTROLLER_MODULE_ID => Some(Box::new(ControllerWeightHandler::<T>::default())),
+		_ => None,
+	}
+}
+
+pub fn host_port_id() -> PortId {
+	PortId::from_str(HOST_PORT_ID).expect("HOST_PORT_ID is static and valid; qed")
+}
+
+/// Port of the controller module owned by the account.
+pub fn controller_port_id<T: Config>(owner: &T::AccountId) -> Option<PortId> {
+	PortId::from_str(&format!("{CONTROLLER_PORT_PREFIX}0x{}", hex::encode(owner.encode()))).ok()
+}
+
+impl<T: Config> Pallet<T> {
+	/// Interchain account created by the host for the controller port on the connection.
+	pub fn interchain_account(
+		connection_id: &ConnectionId,
+		port_id: &PortId,
+	) -> Option<T::AccountId> {
+		InterchainAccounts::<T>::get(connection_id.as_bytes().to_vec(), port_id.as_bytes().to_vec())
+	}
+
+	/// Address of the interchain account of the owner on the host chain of the connection, once
+	/// the channel of the account is open.
+	pub fn registered_account(
+		connection_id: &ConnectionId,
+		owner: &T::AccountId,
+	) -> Option<String> {
+		let port_id = controller_port_id::<T>(owner)?;
+		RegisteredAccounts::<T>::get(connection_id.as_bytes().to_vec(), port_id.as_bytes().to_vec())
+			.and_then(|address| String::from_utf8(address).ok())
+	}
+
+	/// Message of a transaction executing the runtime call on the host.
+	pub fn runtime_call_message(call: &<T as Config>::RuntimeCall) -> crate::Any {
+		crate::Any { type_url: RUNTIME_CALL_TYPE_URL.to_string(), value: call.encode() }
+	}
+}
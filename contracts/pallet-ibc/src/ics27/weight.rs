@@ -0,0 +1,142 @@
+//! Weights of the interchain accounts extrinsics, and the weight handlers of the callbacks of the
+//! host and controller modules. The callbacks only reading and writing a few storage items are
+//! weighed with the `DbWeight` of the runtime, the transactions executed by the host are charged
+//! the maximum weight of a transaction on top of the benchmarked overhead of executing them.
+
+use super::Config;
+use core::marker::PhantomData;
+use frame_support::{pallet_prelude::Weight, traits::Get};
+use ibc::core::{
+	ics04_channel::{msgs::acknowledgement::Acknowledgement, packet::Packet},
+	ics24_host::identifier::{ChannelId, PortId},
+};
+use ibc_primitives::CallbackWeight;
+
+pub trait WeightInfo {
+	fn set_params() -> Weight;
+	fn register_interchain_account() -> Weight;
+	fn send_tx(i: u32) -> Weight;
+	fn execute_tx(i: u32) -> Weight;
+}
+
+impl WeightInfo for () {
+	fn set_params() -> Weight {
+		Weight::default()
+	}
+
+	fn register_interchain_account() -> Weight {
+		Weight::default()
+	}
+
+	fn send_tx(_i: u32) -> Weight {
+		Weight::default()
+	}
+
+	fn execute_tx(_i: u32) -> Weight {
+		Weight::default()
+	}
+}
+
+pub struct HostWeightHandler<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for HostWeightHandler<T> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config> CallbackWeight for HostWeightHandler<T> {
+	fn on_chan_open_init(&self) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_chan_open_try(&self) -> Weight {
+		// the host channel and the interchain account are read, and the account created
+		T::DbWeight::get().reads_writes(3, 2)
+	}
+
+	fn on_chan_open_ack(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_chan_open_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		T::DbWeight::get().reads_writes(1, 1)
+	}
+
+	fn on_chan_close_init(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_chan_close_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		T::DbWeight::get().reads_writes(1, 1)
+	}
+
+	fn on_recv_packet(&self, _packet: &Packet) -> Weight {
+		// the weight of the calls isn't known before they are decoded, the host executes
+		// transactions of up to `MaxTxWeight`
+		T::MaxTxWeight::get()
+			.saturating_add(<T as Config>::WeightInfo::execute_tx(T::MaxCalls::get()))
+	}
+
+	fn on_acknowledgement_packet(
+		&self,
+		_packet: &Packet,
+		_acknowledgement: &Acknowledgement,
+	) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_timeout_packet(&self, _packet: &Packet) -> Weight {
+		Weight::zero()
+	}
+}
+
+pub struct ControllerWeightHandler<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for ControllerWeightHandler<T> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config> CallbackWeight for ControllerWeightHandler<T> {
+	fn on_chan_open_init(&self) -> Weight {
+		T::DbWeight::get().reads(4)
+	}
+
+	fn on_chan_open_try(&self) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_chan_open_ack(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		T::DbWeight::get().reads_writes(3, 2)
+	}
+
+	fn on_chan_open_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_chan_close_init(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_chan_close_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		T::DbWeight::get().reads_writes(2, 1)
+	}
+
+	fn on_recv_packet(&self, _packet: &Packet) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_acknowledgement_packet(
+		&self,
+		_packet: &Packet,
+		_acknowledgement: &Acknowledgement,
+	) -> Weight {
+		T::DbWeight::get().reads(1)
+	}
+
+	fn on_timeout_packet(&self, _packet: &Packet) -> Weight {
+		T::DbWeight::get().reads_writes(3, 1)
+	}
+}
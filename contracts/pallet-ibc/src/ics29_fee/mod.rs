@@ -0,0 +1,789 @@
+//! ICS-29 fee middleware. Fees are escrowed for the packets sent on the channels that negotiated
+//! the middleware, and paid on acknowledgement or timeout to the payees of the relayers that
+//! delivered the packet, its acknowledgement or its timeout.
+
+use crate::routing::Context;
+use alloc::{
+	format,
+	string::{String, ToString},
+};
+use codec::{Decode, Encode};
+use core::{fmt::Debug, marker::PhantomData, str::FromStr};
+use frame_support::{
+	dispatch::DispatchResult,
+	ensure,
+	traits::{fungibles::Mutate, tokens::Preservation, Currency, ExistenceRequirement, Get},
+};
+use ibc::{
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			error::Error as Ics04Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module as IbcModule, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_core::crypto::AccountId32;
+use sp_runtime::{
+	traits::{AccountIdConversion, IdentifyAccount, Saturating, Zero},
+	RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+pub use pallet::*;
+pub use weight::WeightInfo;
+
+mod weight;
+
+/// Version of the fee middleware negotiated in the channel handshakes.
+pub const FEE_VERSION: &str = "ics29-1";
+
+/// Fees paid to the relayers of a packet.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct Fee<AssetId, Balance> {
+	/// Asset the fees are paid in
+	pub asset_id: AssetId,
+	/// Paid to the relayer that delivered the packet to the counterparty chain
+	pub recv_fee: Balance,
+	/// Paid to the relayer that delivered the acknowledgement of the packet
+	pub ack_fee: Balance,
+	/// Paid to the relayer that delivered the timeout of the packet
+	pub timeout_fee: Balance,
+}
+
+impl<AssetId, Balance: Saturating + Copy> Fee<AssetId, Balance> {
+	/// Amount escrowed for the fees.
+	pub fn total(&self) -> Balance {
+		self.recv_fee.saturating_add(self.ack_fee).saturating_add(self.timeout_fee)
+	}
+}
+
+/// Fees escrowed for a packet, and the account the fees that aren't paid are refunded to.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct PacketFee<AccountId, AssetId, Balance> {
+	pub fee: Fee<AssetId, Balance>,
+	pub refund_account: AccountId,
+}
+
+/// Version of the fee enabled channels, wrapping the version of the application.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+	pub fee_version: String,
+	pub app_version: String,
+}
+
+/// Acknowledgement of the packets received on fee enabled channels, wrapping the
+/// acknowledgement of the application.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncentivizedAcknowledgement {
+	#[serde(with = "crate::serde_base64")]
+	pub app_acknowledgement: Vec<u8>,
+	/// Address of the payee of the receiving relayer on the sending chain
+	pub forward_relayer_address: String,
+	pub underlying_app_success: bool,
+}
+
+/// Error acknowledgement of the applications.
+#[derive(Deserialize)]
+struct ErrorAcknowledgement {
+	#[allow(dead_code)]
+	error: String,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::{Context, Fee, PacketFee, WeightInfo};
+	use frame_support::{pallet_prelude::*, PalletId};
+	use frame_system::pallet_prelude::*;
+	use ibc::core::ics04_channel::context::ChannelReader;
+	use sp_core::crypto::AccountId32;
+	use sp_std::prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + crate::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		/// Id of the account escrowing the fees
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// Maximum number of fees escrowed for a packet
+		#[pallet::constant]
+		type MaxFeesPerPacket: Get<u32>;
+		/// Weight information for the extrinsics of the pallet
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub (super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	/// (port_id, channel_id) => () for the channels that negotiated the fee middleware
+	pub type FeeEnabledChannels<T: Config> =
+		StorageMap<_, Blake2_128Concat, (Vec<u8>, Vec<u8>), (), OptionQuery>;
+
+	#[pallet::storage]
+	/// channel_id, relayer => account the fees of the relayer are paid to
+	pub type Payees<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		T::AccountId,
+		T::AccountId,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// channel_id, relayer => address on the counterparty chain the receive fees of the packets
+	/// delivered by the relayer are paid to
+	pub type CounterpartyPayees<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		T::AccountId,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// (port_id, channel_id), sequence => fees escrowed for the packet
+	pub type FeesInEscrow<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(Vec<u8>, Vec<u8>),
+		Blake2_128Concat,
+		u64,
+		BoundedVec<PacketFee<T::AccountId, T::AssetId, T::Balance>, T::MaxFeesPerPacket>,
+		ValueQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub (super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Fees have been escrowed for a packet
+		IncentivizedPacket {
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			sequence: u64,
+			fee: Fee<T::AssetId, T::Balance>,
+			refund_account: T::AccountId,
+		},
+		/// A relayer registered the account its fees are paid to
+		PayeeRegistered { channel_id: Vec<u8>, relayer: T::AccountId, payee: T::AccountId },
+		/// A relayer registered its address on the counterparty chain
+		CounterpartyPayeeRegistered {
+			channel_id: Vec<u8>,
+			relayer: T::AccountId,
+			counterparty_payee: Vec<u8>,
+		},
+		/// The fees escrowed for a packet have been paid to the relayers and refunded
+		FeesDistributed { port_id: Vec<u8>, channel_id: Vec<u8>, sequence: u64 },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The channel didn't negotiate the fee middleware
+		FeeNotEnabled,
+		/// The channel doesn't exist
+		ChannelNotFound,
+		/// The packet hasn't been sent, or it has already been acknowledged or timed out
+		PacketNotInFlight,
+		/// The fees are zero
+		InvalidFee,
+		/// The counterparty payee isn't a valid address
+		InvalidCounterpartyPayee,
+		/// Failed to escrow the fees
+		EscrowFailed,
+		/// The maximum number of fees escrowed for the packet has been reached
+		TooManyFees,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T>
+	where
+		T: Send + Sync,
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+		AccountId32: From<<T as frame_system::Config>::AccountId>,
+	{
+		/// Register the account the fees of the packets relayed by the origin on the channel are
+		/// paid to, instead of the relayer account.
+		#[pallet::call_index(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::register_payee())]
+		pub fn register_payee(
+			origin: OriginFor<T>,
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			payee: T::AccountId,
+		) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+			Self::ensure_fee_enabled(&port_id, &channel_id)?;
+			Payees::<T>::insert(&channel_id, &relayer, &payee);
+			Self::deposit_event(Event::<T>::PayeeRegistered { channel_id, relayer, payee });
+			Ok(())
+		}
+
+		/// Register the address of the origin on the counterparty chain, which the receive fees
+		/// of the packets it delivers on the channel are paid to.
+		#[pallet::call_index(1)]
+		#[pallet::weight(<T as Config>::WeightInfo::register_counterparty_payee())]
+		pub fn register_counterparty_payee(
+			origin: OriginFor<T>,
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			counterparty_payee: Vec<u8>,
+		) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+			Self::ensure_fee_enabled(&port_id, &channel_id)?;
+			ensure!(
+				!counterparty_payee.is_empty() && core::str::from_utf8(&counterparty_payee).is_ok(),
+				Error::<T>::InvalidCounterpartyPayee
+			);
+			CounterpartyPayees::<T>::insert(&channel_id, &relayer, &counterparty_payee);
+			Self::deposit_event(Event::<T>::CounterpartyPayeeRegistered {
+				channel_id,
+				relayer,
+				counterparty_payee,
+			});
+			Ok(())
+		}
+
+		/// Escrow the fees of the next packet sent on the channel. This is meant to be batched
+		/// with the extrinsic sending the packet.
+		#[pallet::call_index(2)]
+		#[pallet::weight(<T as Config>::WeightInfo::pay_packet_fee())]
+		#[frame_support::transactional]
+		pub fn pay_packet_fee(
+			origin: OriginFor<T>,
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			fee: Fee<T::AssetId, T::Balance>,
+		) -> DispatchResult {
+			let refund_account = ensure_signed(origin)?;
+			let port_channel_id = Self::ensure_fee_enabled(&port_id, &channel_id)?;
+			let sequence = Context::<T>::new()
+				.get_next_sequence_send(&port_channel_id)
+				.map_err(|_| Error::<T>::ChannelNotFound)?;
+			Self::escrow_packet_fee(port_id, channel_id, sequence.into(), fee, refund_account)
+		}
+
+		/// Escrow fees for a packet sent on the channel that hasn't been acknowledged or timed
+		/// out yet.
+		#[pallet::call_index(3)]
+		#[pallet::weight(<T as Config>::WeightInfo::pay_packet_fee_async())]
+		#[frame_support::transactional]
+		pub fn pay_packet_fee_async(
+			origin: OriginFor<T>,
+			port_id: Vec<u8>,
+			channel_id: Vec<u8>,
+			sequence: u64,
+			fee: Fee<T::AssetId, T::Balance>,
+		) -> DispatchResult {
+			let refund_account = ensure_signed(origin)?;
+			let (port, channel) = Self::ensure_fee_enabled(&port_id, &channel_id)?;
+			Context::<T>::new()
+				.get_packet_commitment(&(port, channel, sequence.into()))
+				.map_err(|_| Error::<T>::PacketNotInFlight)?;
+			Self::escrow_packet_fee(port_id, channel_id, sequence, fee, refund_account)
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Account escrowing the fees.
+	pub fn account_id() -> T::AccountId {
+		T::PalletId::get().into_account_truncating()
+	}
+
+	pub fn is_fee_enabled(port_id: &PortId, channel_id: &ChannelId) -> bool {
+		FeeEnabledChannels::<T>::contains_key((
+			port_id.as_bytes().to_vec(),
+			channel_id.to_string().as_bytes().to_vec(),
+		))
+	}
+
+	fn ensure_fee_enabled(
+		port_id: &[u8],
+		channel_id: &[u8],
+	) -> Result<(PortId, ChannelId), Error<T>> {
+		if !FeeEnabledChannels::<T>::contains_key((port_id.to_vec(), channel_id.to_vec())) {
+			return Err(Error::<T>::FeeNotEnabled)
+		}
+		let port_id = core::str::from_utf8(port_id)
+			.ok()
+			.and_then(|port_id| PortId::from_str(port_id).ok())
+			.ok_or(Error::<T>::ChannelNotFound)?;
+		let channel_id = core::str::from_utf8(channel_id)
+			.ok()
+			.and_then(|channel_id| ChannelId::from_str(channel_id).ok())
+			.ok_or(Error::<T>::ChannelNotFound)?;
+		Ok((port_id, channel_id))
+	}
+
+	fn escrow_packet_fee(
+		port_id: Vec<u8>,
+		channel_id: Vec<u8>,
+		sequence: u64,
+		fee: Fee<T::AssetId, T::Balance>,
+		refund_account: T::AccountId,
+	) -> DispatchResult {
+		let total = fee.total();
+		ensure!(!total.is_zero(), Error::<T>::InvalidFee);
+		FeesInEscrow::<T>::try_mutate((port_id.clone(), channel_id.clone()), sequence, |fees| {
+			fees.try_push(PacketFee { fee: fee.clone(), refund_account: refund_account.clone() })
+				.map_err(|_| Error::<T>::TooManyFees)
+		})?;
+		Self::transfer(fee.asset_id.clone(), &refund_account, &Self::account_id(), total)
+			.map_err(|_| Error::<T>::EscrowFailed)?;
+		Self::deposit_event(Event::<T>::IncentivizedPacket {
+			port_id,
+			channel_id,
+			sequence,
+			fee,
+			refund_account,
+		});
+		Ok(())
+	}
+
+	/// Pays the fees of an acknowledged packet: the receive fee to the forward relayer, the
+	/// acknowledgement fee to the payee of the relayer, and refunds the timeout fee. The receive
+	/// fee is refunded as well if the counterparty chain didn't forward a valid relayer address.
+	pub fn distribute_fees_on_ack(
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: u64,
+		forward_relayer: Option<T::AccountId>,
+		relayer: Option<T::AccountId>,
+	) {
+		Self::distribute_fees(port_id, channel_id, sequence, |fee, refund_account| {
+			let payee =
+				Self::payee(channel_id, relayer.clone()).unwrap_or_else(|| refund_account.clone());
+			let forward_relayer = forward_relayer.clone().unwrap_or_else(|| refund_account.clone());
+			vec![
+				(forward_relayer, fee.recv_fee),
+				(payee, fee.ack_fee),
+				(refund_account.clone(), fee.timeout_fee),
+			]
+		})
+	}
+
+	/// Pays the timeout fee of a timed out packet to the payee of the relayer, and refunds the
+	/// receive and acknowledgement fees.
+	pub fn distribute_fees_on_timeout(
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: u64,
+		relayer: Option<T::AccountId>,
+	) {
+		Self::distribute_fees(port_id, channel_id, sequence, |fee, refund_account| {
+			let payee =
+				Self::payee(channel_id, relayer.clone()).unwrap_or_else(|| refund_account.clone());
+			vec![
+				(payee, fee.timeout_fee),
+				(refund_account.clone(), fee.recv_fee.saturating_add(fee.ack_fee)),
+			]
+		})
+	}
+
+	/// Refunds the fees of all the packets of a closed channel.
+	pub fn refund_fees(port_id: &PortId, channel_id: &ChannelId) {
+		let key = (port_id.as_bytes().to_vec(), channel_id.to_string().as_bytes().to_vec());
+		for (_, packet_fees) in FeesInEscrow::<T>::drain_prefix(key) {
+			for PacketFee { fee, refund_account } in packet_fees {
+				Self::pay(fee.asset_id.clone(), &refund_account, fee.total());
+			}
+		}
+	}
+
+	fn distribute_fees(
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: u64,
+		payments: impl Fn(
+			&Fee<T::AssetId, T::Balance>,
+			&T::AccountId,
+		) -> Vec<(T::AccountId, T::Balance)>,
+	) {
+		let port_id = port_id.as_bytes().to_vec();
+		let channel_id_bytes = channel_id.to_string().as_bytes().to_vec();
+		let packet_fees =
+			FeesInEscrow::<T>::take((port_id.clone(), channel_id_bytes.clone()), sequence);
+		if packet_fees.is_empty() {
+			return
+		}
+		for PacketFee { fee, refund_account } in packet_fees {
+			for (account, amount) in payments(&fee, &refund_account) {
+				Self::pay(fee.asset_id.clone(), &account, amount);
+			}
+		}
+		Self::deposit_event(Event::<T>::FeesDistributed {
+			port_id,
+			channel_id: channel_id_bytes,
+			sequence,
+		});
+	}
+
+	/// Account the fees of the relayer are paid to on the channel.
+	fn payee(channel_id: &ChannelId, relayer: Option<T::AccountId>) -> Option<T::AccountId> {
+		let relayer = relayer?;
+		let payee = Payees::<T>::get(channel_id.to_string().as_bytes().to_vec(), &relayer);
+		Some(payee.unwrap_or(relayer))
+	}
+
+	/// Pays `amount` out of the escrow. The failed payments are logged, so that they don't fail
+	/// the delivery of the packet.
+	fn pay(asset_id: T::AssetId, to: &T::AccountId, amount: T::Balance) {
+		if amount.is_zero() {
+			return
+		}
+		if let Err(e) = Self::transfer(asset_id.clone(), &Self::account_id(), to, amount) {
+			log::error!(target: "pallet_ibc", "Failed to pay the relayer fee of {amount:?} {asset_id:?} to {to:?}: {e:?}");
+		}
+	}
+
+	fn transfer(
+		asset_id: T::AssetId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		if asset_id == <T as crate::Config>::NativeAssetId::get() {
+			<<T as crate::Config>::NativeCurrency as Currency<T::AccountId>>::transfer(
+				from,
+				to,
+				amount,
+				ExistenceRequirement::AllowDeath,
+			)
+		} else {
+			<<T as crate::Config>::Fungibles as Mutate<T::AccountId>>::transfer(
+				asset_id,
+				from,
+				to,
+				amount,
+				Preservation::Expendable,
+			)
+			.map(|_| ())
+		}
+	}
+}
+
+/// Account of the relayer signing the messages.
+fn relayer_account<T: Config>(signer: &Signer) -> Option<T::AccountId> {
+	<T as crate::Config>::AccountIdConversion::try_from(signer.clone())
+		.ok()
+		.map(|account| account.into_account())
+}
+
+/// Metadata of a fee enabled channel version, `None` if the channel didn't negotiate the fee
+/// middleware.
+fn parse_metadata(version: &Version) -> Result<Option<Metadata>, Ics04Error> {
+	let Ok(metadata) = serde_json::from_str::<Metadata>(&version.to_string()) else {
+		return Ok(None)
+	};
+	if metadata.fee_version != FEE_VERSION {
+		return Err(Ics04Error::implementation_specific(format!(
+			"Unsupported fee version {}, expected {FEE_VERSION}",
+			metadata.fee_version
+		)))
+	}
+	Ok(Some(metadata))
+}
+
+fn fee_enabled_version(app_version: &Version) -> Result<Version, Ics04Error> {
+	let metadata =
+		Metadata { fee_version: FEE_VERSION.to_string(), app_version: app_version.to_string() };
+	serde_json::to_string(&metadata).map(Version::new).map_err(|e| {
+		Ics04Error::implementation_specific(format!("Failed to encode the channel version: {e}"))
+	})
+}
+
+fn channel_key(port_id: &PortId, channel_id: &ChannelId) -> (Vec<u8>, Vec<u8>) {
+	(port_id.as_bytes().to_vec(), channel_id.to_string().as_bytes().to_vec())
+}
+
+/// Fee middleware, to be the outermost module of the applications stacks, so that the inner
+/// modules handle the acknowledgements of the applications.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ics29Fee<T: Config, S: IbcModule + Clone + Default + PartialEq + Eq + Debug> {
+	inner: S,
+	_phantom: PhantomData<T>,
+}
+
+impl<T: Config + Send + Sync, S: IbcModule + Clone + Default + PartialEq + Eq + Debug> Default
+	for Ics29Fee<T, S>
+{
+	fn default() -> Self {
+		Self { inner: S::default(), _phantom: Default::default() }
+	}
+}
+
+impl<T: Config + Send + Sync, S: IbcModule + Clone + Default + PartialEq + Eq + Debug> IbcModule
+	for Ics29Fee<T, S>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let Some(metadata) = parse_metadata(version)? else {
+			return self.inner.on_chan_open_init(
+				ctx,
+				output,
+				order,
+				connection_hops,
+				port_id,
+				channel_id,
+				counterparty,
+				version,
+				relayer,
+			)
+		};
+		self.inner.on_chan_open_init(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			&Version::new(metadata.app_version),
+			relayer,
+		)?;
+		FeeEnabledChannels::<T>::insert(channel_key(port_id, channel_id), ());
+		Ok(())
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		let Some(counterparty_metadata) = parse_metadata(counterparty_version)? else {
+			return self.inner.on_chan_open_try(
+				ctx,
+				output,
+				order,
+				connection_hops,
+				port_id,
+				channel_id,
+				counterparty,
+				version,
+				counterparty_version,
+				relayer,
+			)
+		};
+		let app_version = parse_metadata(version)?
+			.map(|metadata| Version::new(metadata.app_version))
+			.unwrap_or_else(|| version.clone());
+		let app_version = self.inner.on_chan_open_try(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			&app_version,
+			&Version::new(counterparty_metadata.app_version),
+			relayer,
+		)?;
+		FeeEnabledChannels::<T>::insert(channel_key(port_id, channel_id), ());
+		fee_enabled_version(&app_version)
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		if !Pallet::<T>::is_fee_enabled(port_id, channel_id) {
+			return self.inner.on_chan_open_ack(
+				ctx,
+				output,
+				port_id,
+				channel_id,
+				counterparty_version,
+				relayer,
+			)
+		}
+		let metadata = parse_metadata(counterparty_version)?.ok_or_else(|| {
+			Ics04Error::implementation_specific(format!(
+				"The counterparty version {counterparty_version} doesn't enable the fee middleware"
+			))
+		})?;
+		self.inner.on_chan_open_ack(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			&Version::new(metadata.app_version),
+			relayer,
+		)
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_open_confirm(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_close_init(ctx, output, port_id, channel_id, relayer)?;
+		Pallet::<T>::refund_fees(port_id, channel_id);
+		Ok(())
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_close_confirm(ctx, output, port_id, channel_id, relayer)?;
+		Pallet::<T>::refund_fees(port_id, channel_id);
+		Ok(())
+	}
+
+	fn on_recv_packet(
+		&self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		let ack = self.inner.on_recv_packet(ctx, output, packet, relayer)?;
+		if !Pallet::<T>::is_fee_enabled(&packet.destination_port, &packet.destination_channel) {
+			return Ok(ack)
+		}
+		let forward_relayer_address = relayer_account::<T>(relayer)
+			.and_then(|relayer| {
+				CounterpartyPayees::<T>::get(
+					packet.destination_channel.to_string().as_bytes().to_vec(),
+					relayer,
+				)
+			})
+			.and_then(|address| String::from_utf8(address).ok())
+			.unwrap_or_default();
+		let app_acknowledgement = ack.into_bytes();
+		let ack = IncentivizedAcknowledgement {
+			underlying_app_success: serde_json::from_slice::<ErrorAcknowledgement>(
+				&app_acknowledgement,
+			)
+			.is_err(),
+			app_acknowledgement,
+			forward_relayer_address,
+		};
+		// the applications return their acknowledgement for the core handler to write it, so that
+		// the incentivized acknowledgement is the only one written for the packet
+		serde_json::to_vec(&ack).map(Acknowledgement::from_bytes).map_err(|e| {
+			Ics04Error::implementation_specific(format!(
+				"Failed to encode the acknowledgement: {e}"
+			))
+		})
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &Acknowledgement,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		if !Pallet::<T>::is_fee_enabled(&packet.source_port, &packet.source_channel) {
+			return self.inner.on_acknowledgement_packet(
+				ctx,
+				output,
+				packet,
+				acknowledgement,
+				relayer,
+			)
+		}
+		let ack = serde_json::from_slice::<IncentivizedAcknowledgement>(acknowledgement.as_ref())
+			.map_err(|e| {
+			Ics04Error::implementation_specific(format!(
+				"Failed to decode the incentivized acknowledgement: {e}"
+			))
+		})?;
+		let forward_relayer = Signer::from_str(&ack.forward_relayer_address)
+			.ok()
+			.and_then(|signer| relayer_account::<T>(&signer));
+		Pallet::<T>::distribute_fees_on_ack(
+			&packet.source_port,
+			&packet.source_channel,
+			packet.sequence.into(),
+			forward_relayer,
+			relayer_account::<T>(relayer),
+		);
+		self.inner.on_acknowledgement_packet(
+			ctx,
+			output,
+			packet,
+			&Acknowledgement::from_bytes(ack.app_acknowledgement),
+			relayer,
+		)
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		if Pallet::<T>::is_fee_enabled(&packet.source_port, &packet.source_channel) {
+			Pallet::<T>::distribute_fees_on_timeout(
+				&packet.source_port,
+				&packet.source_channel,
+				packet.sequence.into(),
+				relayer_account::<T>(relayer),
+			);
+		}
+		self.inner.on_timeout_packet(ctx, output, packet, relayer)
+	}
+}
@@ -0,0 +1,28 @@
+//! Weights of the fee middleware extrinsics.
+
+use frame_support::pallet_prelude::Weight;
+
+pub trait WeightInfo {
+	fn register_payee() -> Weight;
+	fn register_counterparty_payee() -> Weight;
+	fn pay_packet_fee() -> Weight;
+	fn pay_packet_fee_async() -> Weight;
+}
+
+impl WeightInfo for () {
+	fn register_payee() -> Weight {
+		Weight::default()
+	}
+
+	fn register_counterparty_payee() -> Weight {
+		Weight::default()
+	}
+
+	fn pay_packet_fee() -> Weight {
+		Weight::default()
+	}
+
+	fn pay_packet_fee_async() -> Weight {
+		Weight::default()
+	}
+}
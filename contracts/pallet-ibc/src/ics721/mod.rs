@@ -0,0 +1,367 @@
+//! ICS-721 non-fungible token transfers. The [`NftTransfer`] module, bound to the `nft-transfer`
+//! port, moves the tokens of the [`NonFungibles`] of the runtime between chains.
+//!
+//! The tokens of the classes originating from this chain are escrowed in the escrow account of the
+//! channel they are sent over, and released when they are sent back. The tokens received from
+//! other chains are minted in voucher classes whose ids are the class ids of the packets prefixed
+//! by the port and channel they are received on, `{port}/{channel}/{class_id}`, and burnt when
+//! they are sent back. The trace paths of the voucher classes are kept under their ICS-721 hash,
+//! the `ibc/{hash}` class ids the cosmos chains know them by.
+
+use alloc::{
+	boxed::Box,
+	format,
+	string::{String, ToString},
+};
+use codec::Encode;
+use core::str::FromStr;
+use frame_support::dispatch::DispatchResult;
+use ibc::core::{
+	ics24_host::identifier::{ChannelId, PortId},
+	ics26_routing::context::ModuleId,
+};
+use ibc_primitives::{get_channel_escrow_address, CallbackWeight};
+use serde::{Deserialize, Serialize};
+use sp_runtime::traits::IdentifyAccount;
+use sp_std::prelude::*;
+
+pub use module::NftTransfer;
+pub use pallet::*;
+pub use weight::{WeightHandler, WeightInfo};
+
+mod module;
+mod weight;
+
+/// Version of the non-fungible token transfer protocol negotiated in the channel handshakes.
+pub const VERSION: &str = "ics721-1";
+/// Port the non-fungible token transfer module is bound to.
+pub const PORT_ID: &str = "nft-transfer";
+pub const MODULE_ID: &str = "nfttransfer";
+
+/// Uri and data of a class or a token.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NftMetadata {
+	pub uri: String,
+	pub data: String,
+}
+
+/// Non-fungible tokens of the runtime transferred over IBC. The classes and tokens are identified
+/// by strings, the ids of the voucher classes being their full trace path.
+pub trait NonFungibles<AccountId> {
+	/// Owner of the token, `None` if it doesn't exist
+	fn owner(class_id: &str, token_id: &str) -> Option<AccountId>;
+	/// Metadata of the class, `None` if it doesn't exist
+	fn class(class_id: &str) -> Option<NftMetadata>;
+	/// Metadata of the token, `None` if it doesn't exist
+	fn token(class_id: &str, token_id: &str) -> Option<NftMetadata>;
+	/// Creates a voucher class for the tokens received from another chain
+	fn create_class(class_id: &str, metadata: NftMetadata) -> DispatchResult;
+	fn mint(
+		class_id: &str,
+		token_id: &str,
+		owner: &AccountId,
+		metadata: NftMetadata,
+	) -> DispatchResult;
+	fn burn(class_id: &str, token_id: &str) -> DispatchResult;
+	fn transfer(class_id: &str, token_id: &str, to: &AccountId) -> DispatchResult;
+}
+
+/// Data of the non-fungible token transfer packets.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NonFungibleTokenPacketData {
+	pub class_id: String,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub class_uri: String,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub class_data: String,
+	pub token_ids: Vec<String>,
+	/// Uris of the tokens, empty or one per token
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub token_uris: Vec<String>,
+	/// Data of the tokens, empty or one per token
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub token_data: Vec<String>,
+	pub sender: String,
+	pub receiver: String,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub memo: String,
+}
+
+impl NonFungibleTokenPacketData {
+	/// Checks that the packet transfers at least one token, with uris and data for all its tokens
+	/// or none.
+	pub fn validate(&self) -> Result<(), String> {
+		if self.class_id.trim().is_empty() {
+			return Err("The class id is empty".to_string())
+		}
+		if self.token_ids.is_empty() || self.token_ids.iter().any(|id| id.trim().is_empty()) {
+			return Err("The token ids are empty".to_string())
+		}
+		let tokens = self.token_ids.len();
+		if !self.token_uris.is_empty() && self.token_uris.len() != tokens ||
+			!self.token_data.is_empty() && self.token_data.len() != tokens
+		{
+			return Err(format!("The uris and data of the tokens don't match the {tokens} tokens"))
+		}
+		Ok(())
+	}
+
+	/// Metadata of the token at `index` in the packet.
+	pub fn token_metadata(&self, index: usize) -> NftMetadata {
+		NftMetadata {
+			uri: self.token_uris.get(index).cloned().unwrap_or_default(),
+			data: self.token_data.get(index).cloned().unwrap_or_default(),
+		}
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::{
+		class_prefix, escrow_account, port_id, NftMetadata, NonFungibleTokenPacketData,
+		NonFungibles, WeightInfo,
+	};
+	use crate::{routing::Context, MultiAddress, TransferParams};
+	use alloc::string::{String, ToString};
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use ibc::core::{ics04_channel::context::ChannelReader, ics24_host::identifier::ChannelId};
+	use ibc_primitives::{HandlerMessage, IbcHandler};
+	use sp_core::crypto::AccountId32;
+	use sp_std::prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + crate::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		/// Non-fungible tokens transferred over IBC
+		type NonFungibles: NonFungibles<Self::AccountId>;
+		/// Maximum number of tokens transferred by a packet
+		#[pallet::constant]
+		type MaxTokensPerTransfer: Get<u32>;
+		/// Weight information for the extrinsics and callbacks of the pallet
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub (super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	/// ICS-721 hash of the id of a voucher class => id of the voucher class, its full trace path
+	pub type ClassTraces<T: Config> =
+		StorageMap<_, Blake2_128Concat, [u8; 32], Vec<u8>, OptionQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub (super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Non-fungible tokens have been sent to another chain
+		NftTransferInitiated {
+			from: T::AccountId,
+			to: Vec<u8>,
+			class_id: Vec<u8>,
+			token_ids: Vec<Vec<u8>>,
+			channel_id: Vec<u8>,
+			sequence: u64,
+		},
+		/// Non-fungible tokens have been received from another chain
+		NftReceived {
+			from: Vec<u8>,
+			to: T::AccountId,
+			class_id: Vec<u8>,
+			token_ids: Vec<Vec<u8>>,
+			channel_id: Vec<u8>,
+		},
+		/// A transfer of non-fungible tokens has been acknowledged by the receiving chain
+		NftTransferCompleted { channel_id: Vec<u8>, sequence: u64 },
+		/// The tokens of a transfer that failed or timed out have been refunded to its sender
+		NftTransferRefunded {
+			to: T::AccountId,
+			class_id: Vec<u8>,
+			token_ids: Vec<Vec<u8>>,
+			channel_id: Vec<u8>,
+			sequence: u64,
+		},
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Invalid class id, token ids, receiver or memo
+		InvalidParams,
+		/// The transfer has no tokens or more than `MaxTokensPerTransfer`
+		TooManyTokens,
+		/// The class doesn't exist
+		ClassNotFound,
+		/// A token doesn't exist or isn't owned by the sender
+		TokenNotOwned,
+		/// The channel doesn't exist
+		ChannelNotFound,
+		/// Failed to derive the escrow account of the channel
+		ChannelEscrowAddress,
+		/// Failed to encode the packet data
+		EncodingFailed,
+		/// Failed to send the packet
+		PacketSendError,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T>
+	where
+		T: Send + Sync,
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+		AccountId32: From<<T as frame_system::Config>::AccountId>,
+	{
+		/// Send the tokens of the class owned by the origin over a non-fungible token transfer
+		/// channel. The tokens of the classes originating from this chain are escrowed, and the
+		/// vouchers of the tokens received from other chains burnt.
+		#[pallet::call_index(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::nft_transfer(token_ids.len() as u32))]
+		#[frame_support::transactional]
+		pub fn transfer(
+			origin: OriginFor<T>,
+			class_id: Vec<u8>,
+			token_ids: Vec<Vec<u8>>,
+			params: TransferParams<T::AccountId>,
+			memo: Vec<u8>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(
+				!token_ids.is_empty() && token_ids.len() <= T::MaxTokensPerTransfer::get() as usize,
+				Error::<T>::TooManyTokens
+			);
+			let class_id = String::from_utf8(class_id).map_err(|_| Error::<T>::InvalidParams)?;
+			let token_ids = token_ids
+				.into_iter()
+				.map(String::from_utf8)
+				.collect::<Result<Vec<_>, _>>()
+				.map_err(|_| Error::<T>::InvalidParams)?;
+			let receiver = match params.to {
+				MultiAddress::Id(id) => Self::address(&id),
+				MultiAddress::Raw(bytes) =>
+					String::from_utf8(bytes).map_err(|_| Error::<T>::InvalidParams)?,
+			};
+			let memo = String::from_utf8(memo).map_err(|_| Error::<T>::InvalidParams)?;
+			let class = T::NonFungibles::class(&class_id).ok_or(Error::<T>::ClassNotFound)?;
+
+			let port_id = port_id();
+			let channel_id = ChannelId::new(params.source_channel);
+			let sequence = Context::<T>::new()
+				.get_next_sequence_send(&(port_id.clone(), channel_id))
+				.map_err(|_| Error::<T>::ChannelNotFound)?;
+			// the vouchers of the tokens received over the channel are burnt when sent back
+			let is_source = !class_id.starts_with(&class_prefix(&port_id, &channel_id));
+			let escrow =
+				escrow_account::<T>(&channel_id).ok_or(Error::<T>::ChannelEscrowAddress)?;
+			let (mut token_uris, mut token_data) = (Vec::new(), Vec::new());
+			for token_id in &token_ids {
+				ensure!(
+					T::NonFungibles::owner(&class_id, token_id).as_ref() == Some(&sender),
+					Error::<T>::TokenNotOwned
+				);
+				let NftMetadata { uri, data } =
+					T::NonFungibles::token(&class_id, token_id).unwrap_or_default();
+				token_uris.push(uri);
+				token_data.push(data);
+				if is_source {
+					T::NonFungibles::transfer(&class_id, token_id, &escrow)?;
+				} else {
+					T::NonFungibles::burn(&class_id, token_id)?;
+				}
+			}
+			if token_uris.iter().all(String::is_empty) {
+				token_uris.clear();
+			}
+			if token_data.iter().all(String::is_empty) {
+				token_data.clear();
+			}
+
+			let data = NonFungibleTokenPacketData {
+				class_id: class_id.clone(),
+				class_uri: class.uri,
+				class_data: class.data,
+				token_ids: token_ids.clone(),
+				token_uris,
+				token_data,
+				sender: Self::address(&sender),
+				receiver: receiver.clone(),
+				memo,
+			};
+			let data = serde_json::to_vec(&data).map_err(|_| Error::<T>::EncodingFailed)?;
+			crate::Pallet::<T>::handle_message(HandlerMessage::SendPacket {
+				data,
+				timeout: params.timeout,
+				port_id,
+				channel_id,
+			})
+			.map_err(|e| {
+				log::trace!(target: "pallet_ibc", "[ics721::transfer] error: {e:?}");
+				Error::<T>::PacketSendError
+			})?;
+			Self::deposit_event(Event::<T>::NftTransferInitiated {
+				from: sender,
+				to: receiver.into_bytes(),
+				class_id: class_id.into_bytes(),
+				token_ids: token_ids.into_iter().map(String::into_bytes).collect(),
+				channel_id: channel_id.to_string().into_bytes(),
+				sequence: sequence.into(),
+			});
+			Ok(())
+		}
+	}
+}
+
+/// Module of the non-fungible token transfer port, for the `ModuleRouter::lookup_module_by_port`
+/// of the runtimes routing the non-fungible token transfer module.
+pub fn lookup_module_by_port(port_id: &PortId) -> Option<ModuleId> {
+	if port_id.as_str() == PORT_ID {
+		ModuleId::from_str(MODULE_ID).ok()
+	} else {
+		None
+	}
+}
+
+/// Weight handler of the callbacks of the non-fungible token transfer module, for the
+/// `ModuleRouter::weight_handler` of the runtimes routing the non-fungible token transfer module.
+pub fn weight_handler<T: Config>(module_id: &ModuleId) -> Option<Box<dyn CallbackWeight>> {
+	if module_id.as_ref() == MODULE_ID {
+		Some(Box::new(WeightHandler::<T>::default()))
+	} else {
+		None
+	}
+}
+
+pub fn port_id() -> PortId {
+	PortId::from_str(PORT_ID).expect("PORT_ID is static and valid; qed")
+}
+
+/// Prefix of the ids of the voucher classes of the tokens received over the channel.
+pub fn class_prefix(port_id: &PortId, channel_id: &ChannelId) -> String {
+	format!("{port_id}/{channel_id}/")
+}
+
+/// ICS-721 hash of a class id, the `ibc/{hash}` class ids being its upper case hex encoding.
+pub fn class_hash(class_id: &str) -> [u8; 32] {
+	sp_io::hashing::sha2_256(class_id.as_bytes())
+}
+
+/// Account the tokens sent over the channel are escrowed in.
+pub fn escrow_account<T: Config>(channel_id: &ChannelId) -> Option<T::AccountId> {
+	get_channel_escrow_address(&port_id(), *channel_id)
+		.ok()
+		.and_then(|address| <T as crate::Config>::AccountIdConversion::try_from(address).ok())
+		.map(IdentifyAccount::into_account)
+}
+
+impl<T: Config> Pallet<T> {
+	/// Id of the voucher class with the ICS-721 hash, e.g. of an `ibc/{hash}` class id.
+	pub fn class_trace(hash: [u8; 32]) -> Option<String> {
+		ClassTraces::<T>::get(hash).and_then(|class_id| String::from_utf8(class_id).ok())
+	}
+
+	/// Address of the account on the chains the tokens are sent to.
+	pub(crate) fn address(account: &T::AccountId) -> String {
+		format!("0x{}", hex::encode(account.encode()))
+	}
+}
@@ -0,0 +1,287 @@
+//! Non-fungible token transfer module, bound to the `nft-transfer` port. It mints or releases the
+//! tokens of the packets it receives, and refunds the tokens of the packets it sent that failed or
+//! timed out.
+
+use super::{
+	class_hash, class_prefix, escrow_account, ClassTraces, Config, Event, NftMetadata,
+	NonFungibleTokenPacketData, NonFungibles, Pallet, VERSION,
+};
+use alloc::{
+	format,
+	string::{String, ToString},
+};
+use core::{marker::PhantomData, str::FromStr};
+use frame_support::{
+	storage::{with_transaction, TransactionOutcome},
+	traits::Get,
+};
+use ibc::{
+	applications::transfer::acknowledgement::{
+		Acknowledgement as Ics20Acknowledgement, ACK_ERR_STR,
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			error::Error as Ics04Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module as IbcModule, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+use sp_core::crypto::AccountId32;
+use sp_runtime::{traits::IdentifyAccount, DispatchError};
+use sp_std::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NftTransfer<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for NftTransfer<T> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+fn check_channel(order: Order, version: &Version) -> Result<(), Ics04Error> {
+	if order != Order::Unordered {
+		return Err(Ics04Error::unknown_order_type(order.to_string()))
+	}
+	if version.to_string() != VERSION {
+		return Err(Ics04Error::no_common_version())
+	}
+	Ok(())
+}
+
+fn decode_packet_data(packet: &Packet) -> Result<NonFungibleTokenPacketData, String> {
+	let data = serde_json::from_slice::<NonFungibleTokenPacketData>(&packet.data)
+		.map_err(|e| format!("Invalid packet data: {e}"))?;
+	data.validate()?;
+	Ok(data)
+}
+
+fn account<T: Config>(address: &str) -> Result<T::AccountId, String> {
+	Signer::from_str(address)
+		.ok()
+		.and_then(|signer| <T as crate::Config>::AccountIdConversion::try_from(signer).ok())
+		.map(IdentifyAccount::into_account)
+		.ok_or_else(|| format!("Invalid account {address}"))
+}
+
+fn dispatch_error(e: DispatchError) -> String {
+	format!("{e:?}")
+}
+
+impl<T: Config + Send + Sync> NftTransfer<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	/// Releases the tokens of the packet from escrow if they return to this chain, or mints their
+	/// vouchers otherwise, returning the receiver and the local class of the tokens.
+	fn receive_tokens(
+		packet: &Packet,
+		data: &NonFungibleTokenPacketData,
+	) -> Result<(T::AccountId, String), String> {
+		if data.token_ids.len() > T::MaxTokensPerTransfer::get() as usize {
+			return Err(format!("More than {} tokens", T::MaxTokensPerTransfer::get()))
+		}
+		let receiver = account::<T>(&data.receiver)?;
+		let source_prefix = class_prefix(&packet.source_port, &packet.source_channel);
+		if let Some(class_id) = data.class_id.strip_prefix(&source_prefix) {
+			let escrow = escrow_account::<T>(&packet.destination_channel)
+				.ok_or_else(|| "Failed to derive the escrow account".to_string())?;
+			for token_id in &data.token_ids {
+				if T::NonFungibles::owner(class_id, token_id).as_ref() != Some(&escrow) {
+					return Err(format!("The token {class_id}/{token_id} isn't escrowed"))
+				}
+				T::NonFungibles::transfer(class_id, token_id, &receiver).map_err(dispatch_error)?;
+			}
+			return Ok((receiver, class_id.to_string()))
+		}
+
+		let class_id = format!(
+			"{}{}",
+			class_prefix(&packet.destination_port, &packet.destination_channel),
+			data.class_id
+		);
+		if T::NonFungibles::class(&class_id).is_none() {
+			let metadata =
+				NftMetadata { uri: data.class_uri.clone(), data: data.class_data.clone() };
+			T::NonFungibles::create_class(&class_id, metadata).map_err(dispatch_error)?;
+			ClassTraces::<T>::insert(class_hash(&class_id), class_id.as_bytes().to_vec());
+		}
+		for (index, token_id) in data.token_ids.iter().enumerate() {
+			T::NonFungibles::mint(&class_id, token_id, &receiver, data.token_metadata(index))
+				.map_err(dispatch_error)?;
+		}
+		Ok((receiver, class_id))
+	}
+
+	/// Refunds the tokens of a packet sent by this chain to their sender, by releasing them from
+	/// escrow or minting back their vouchers.
+	fn refund_tokens(packet: &Packet) -> Result<(), Ics04Error> {
+		let data = decode_packet_data(packet).map_err(Ics04Error::implementation_specific)?;
+		let sender = account::<T>(&data.sender).map_err(Ics04Error::implementation_specific)?;
+		let is_source = !data
+			.class_id
+			.starts_with(&class_prefix(&packet.source_port, &packet.source_channel));
+		for (index, token_id) in data.token_ids.iter().enumerate() {
+			if is_source {
+				T::NonFungibles::transfer(&data.class_id, token_id, &sender)
+			} else {
+				T::NonFungibles::mint(&data.class_id, token_id, &sender, data.token_metadata(index))
+			}
+			.map_err(|e| Ics04Error::implementation_specific(dispatch_error(e)))?;
+		}
+		Pallet::<T>::deposit_event(Event::<T>::NftTransferRefunded {
+			to: sender,
+			class_id: data.class_id.into_bytes(),
+			token_ids: data.token_ids.into_iter().map(String::into_bytes).collect(),
+			channel_id: packet.source_channel.to_string().into_bytes(),
+			sequence: packet.sequence.into(),
+		});
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> IbcModule for NftTransfer<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		order: Order,
+		_connection_hops: &[ConnectionId],
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty: &Counterparty,
+		version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		check_channel(order, version)
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		order: Order,
+		_connection_hops: &[ConnectionId],
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty: &Counterparty,
+		_version: &Version,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		check_channel(order, counterparty_version)?;
+		Ok(counterparty_version.clone())
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		if counterparty_version.to_string() != VERSION {
+			return Err(Ics04Error::no_common_version())
+		}
+		Ok(())
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		// the escrowed tokens would be locked if the channel was closed
+		Err(Ics04Error::implementation_specific(
+			"Non-fungible token transfer channels can't be closed".to_string(),
+		))
+	}
+
+	fn on_recv_packet(
+		&self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		_relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		// the tokens received before an error are reverted
+		let result = decode_packet_data(packet).and_then(|data| {
+			with_transaction(|| {
+				let result = Self::receive_tokens(packet, &data);
+				if result.is_err() {
+					TransactionOutcome::Rollback(Ok(result))
+				} else {
+					TransactionOutcome::Commit(Ok(result))
+				}
+			})
+			.map_err(dispatch_error)?
+			.map(|receipt| (receipt, data))
+		});
+		let ack = match result {
+			Ok(((receiver, class_id), data)) => {
+				Pallet::<T>::deposit_event(Event::<T>::NftReceived {
+					from: data.sender.into_bytes(),
+					to: receiver,
+					class_id: class_id.into_bytes(),
+					token_ids: data.token_ids.into_iter().map(String::into_bytes).collect(),
+					channel_id: packet.destination_channel.to_string().into_bytes(),
+				});
+				Ics20Acknowledgement::success()
+			},
+			Err(e) => {
+				log::trace!(target: "pallet_ibc", "[ics721::on_recv_packet] error: {e}");
+				Ics20Acknowledgement::Error(format!("{ACK_ERR_STR}: {e}"))
+			},
+		};
+		let ack = ack.to_string().into_bytes();
+		Ok(Acknowledgement::from_bytes(ack))
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &Acknowledgement,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let ack = serde_json::from_slice::<Ics20Acknowledgement>(acknowledgement.as_ref())
+			.map_err(|e| {
+				Ics04Error::implementation_specific(format!("Invalid acknowledgement: {e}"))
+			})?;
+		if !ack.is_successful() {
+			return Self::refund_tokens(packet)
+		}
+		Pallet::<T>::deposit_event(Event::<T>::NftTransferCompleted {
+			channel_id: packet.source_channel.to_string().into_bytes(),
+			sequence: packet.sequence.into(),
+		});
+		Ok(())
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		Self::refund_tokens(packet)
+	}
+}
@@ -0,0 +1,91 @@
+//! Weights of the non-fungible token transfers, and the weight handler of the callbacks of the
+//! non-fungible token transfer module. The packets are charged for the number of tokens they
+//! transfer, up to `MaxTokensPerTransfer`.
+
+use super::{Config, NonFungibleTokenPacketData};
+use core::marker::PhantomData;
+use frame_support::{pallet_prelude::Weight, traits::Get};
+use ibc::core::{
+	ics04_channel::{msgs::acknowledgement::Acknowledgement, packet::Packet},
+	ics24_host::identifier::{ChannelId, PortId},
+};
+use ibc_primitives::CallbackWeight;
+
+pub trait WeightInfo {
+	fn nft_transfer(n: u32) -> Weight;
+	fn on_recv_packet(n: u32) -> Weight;
+	fn on_timeout_packet(n: u32) -> Weight;
+}
+
+impl WeightInfo for () {
+	fn nft_transfer(_n: u32) -> Weight {
+		Weight::default()
+	}
+
+	fn on_recv_packet(_n: u32) -> Weight {
+		Weight::default()
+	}
+
+	fn on_timeout_packet(_n: u32) -> Weight {
+		Weight::default()
+	}
+}
+
+/// Number of tokens transferred by the packet, the maximum if its data can't be decoded.
+fn tokens<T: Config>(packet: &Packet) -> u32 {
+	let max = T::MaxTokensPerTransfer::get();
+	serde_json::from_slice::<NonFungibleTokenPacketData>(&packet.data)
+		.map(|data| (data.token_ids.len() as u32).min(max))
+		.unwrap_or(max)
+}
+
+pub struct WeightHandler<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for WeightHandler<T> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config> CallbackWeight for WeightHandler<T> {
+	fn on_chan_open_init(&self) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_chan_open_try(&self) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_chan_open_ack(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_chan_open_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_chan_close_init(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_chan_close_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_recv_packet(&self, packet: &Packet) -> Weight {
+		<T as Config>::WeightInfo::on_recv_packet(tokens::<T>(packet))
+	}
+
+	fn on_acknowledgement_packet(
+		&self,
+		packet: &Packet,
+		_acknowledgement: &Acknowledgement,
+	) -> Weight {
+		// the tokens of a failed transfer are refunded as on timeout
+		<T as Config>::WeightInfo::on_timeout_packet(tokens::<T>(packet))
+	}
+
+	fn on_timeout_packet(&self, packet: &Packet) -> Weight {
+		<T as Config>::WeightInfo::on_timeout_packet(tokens::<T>(packet))
+	}
+}
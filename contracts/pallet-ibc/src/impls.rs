@@ -2,6 +2,7 @@ use alloc::{format, str::FromStr, string::String};
 use core::time::Duration;
 
 use crate::{
+	ics20::rate_limit::Direction,
 	ics23::{
 		acknowledgements::Acknowledgements, channels::Channels, client_states::ClientStates,
 		connections::Connections, consensus_states::ConsensusStates,
@@ -10,9 +11,9 @@ use crate::{
 	},
 	light_clients::AnyClientState,
 	routing::Context,
-	Acks, ChannelsConnection, Config, ConnectionClient, DenomToAssetId, Error, EscrowAddresses,
-	IbcAssets, Pallet, PendingRecvPacketSeqs, PendingSendPacketSeqs, RecvPackets, SendPackets,
-	MODULE_ID,
+	Acks, Any, ChannelsConnection, Config, ConnectionClient, DenomToAssetId, Error,
+	EscrowAddresses, IbcAssets, LastUnsignedClientUpdate, Pallet, PendingRecvPacketSeqs,
+	PendingSendPacketSeqs, RecvPackets, SendPackets, UnsignedClientUpdateInterval, MODULE_ID,
 };
 use codec::{Decode, Encode};
 use frame_support::traits::{fungibles::Inspect, Currency};
@@ -22,7 +23,11 @@ use ibc::{
 	},
 	core::{
 		ics02_client::{
-			client_consensus::ConsensusState, client_state::ClientState, context::ClientReader,
+			client_consensus::ConsensusState,
+			client_state::ClientState,
+			context::ClientReader,
+			handler::update_client,
+			msgs::update_client::{MsgUpdateAnyClient, TYPE_URL as UPDATE_CLIENT_TYPE_URL},
 		},
 		ics03_connection::context::ConnectionReader,
 		ics04_channel::{
@@ -36,7 +41,7 @@ use ibc::{
 			identifier::*,
 			path::{
 				AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath,
-				CommitmentsPath, ConnectionsPath, ReceiptsPath, SeqRecvsPath,
+				CommitmentsPath, ConnectionsPath, Path, ReceiptsPath, SeqRecvsPath,
 			},
 		},
 		ics26_routing::handler::MsgReceipt,
@@ -51,9 +56,9 @@ use ibc_primitives::{
 	apply_prefix, channel_id_from_bytes, client_id_from_bytes, connection_id_from_bytes,
 	get_channel_escrow_address, port_id_from_bytes, runtime_interface, ConnectionHandshake,
 	Error as IbcHandlerError, HandlerMessage, IbcHandler, IdentifiedChannel, IdentifiedClientState,
-	IdentifiedConnection, PacketInfo, PacketState, QueryChannelResponse, QueryChannelsResponse,
-	QueryClientStateResponse, QueryConnectionResponse, QueryConnectionsResponse,
-	QueryConsensusStateResponse, QueryNextSequenceReceiveResponse,
+	IdentifiedConnection, PacketInfo, PacketState, Page, PageRequest, QueryChannelResponse,
+	QueryChannelsResponse, QueryClientStateResponse, QueryConnectionResponse,
+	QueryConnectionsResponse, QueryConsensusStateResponse, QueryNextSequenceReceiveResponse,
 	QueryPacketAcknowledgementResponse, QueryPacketAcknowledgementsResponse,
 	QueryPacketCommitmentResponse, QueryPacketCommitmentsResponse, QueryPacketReceiptResponse,
 	Timeout,
@@ -61,7 +66,7 @@ use ibc_primitives::{
 use scale_info::prelude::string::ToString;
 use sp_core::crypto::AccountId32;
 use sp_runtime::{
-	traits::{Get, IdentifyAccount},
+	traits::{Get, IdentifyAccount, Saturating},
 	Either,
 };
 use sp_std::prelude::*;
@@ -69,14 +74,14 @@ use tendermint_proto::Protobuf;
 
 const PACKET_CLEANUP_PER_CYCLE: u64 = 1001;
 
-impl<T: Config> Pallet<T>
+impl<T: Config<I>, I: 'static> Pallet<T, I>
 where
 	T: Send + Sync,
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
 {
 	pub(crate) fn execute_ibc_messages(
-		ctx: &mut Context<T>,
+		ctx: &mut Context<T, I>,
 		messages: Vec<ibc_proto::google::protobuf::Any>,
 	) {
 		let (events, logs) =
@@ -99,9 +104,43 @@ where
 			Self::deposit_event(events.into())
 		};
 	}
+
+	/// Checks that `message` is a `MsgUpdateClient` that may be submitted as an unsigned
+	/// extrinsic at the current block, and returns the id of the client it updates.
+	pub(crate) fn check_unsigned_client_update(message: &Any) -> Result<ClientId, Error<T, I>> {
+		let interval = UnsignedClientUpdateInterval::<T, I>::get()
+			.ok_or(Error::<T, I>::UnsignedClientUpdatesDisabled)?;
+		if message.type_url != UPDATE_CLIENT_TYPE_URL {
+			return Err(Error::<T, I>::InvalidMessageType)
+		}
+		let msg = MsgUpdateAnyClient::<Context<T, I>>::decode_vec(&message.value)
+			.map_err(|_| Error::<T, I>::DecodingError)?;
+
+		let now = frame_system::Pallet::<T>::block_number();
+		if let Some(last) = LastUnsignedClientUpdate::<T, I>::get(msg.client_id.as_bytes()) {
+			if now < last.saturating_add(interval) {
+				return Err(Error::<T, I>::UnsignedClientUpdateTooEarly)
+			}
+		}
+
+		Ok(msg.client_id)
+	}
+
+	/// Verifies the client message of a `MsgUpdateClient` against the current client state,
+	/// without writing anything to storage.
+	pub(crate) fn verify_client_update(message: &Any) -> Result<(), Error<T, I>> {
+		let msg = MsgUpdateAnyClient::<Context<T, I>>::decode_vec(&message.value)
+			.map_err(|_| Error::<T, I>::DecodingError)?;
+		let ctx = Context::<T, I>::new();
+		update_client::process(&ctx, msg).map_err(|e| {
+			log::trace!(target: "pallet_ibc", "unsigned client update verification failed: {}", e);
+			Error::<T, I>::ProcessingError
+		})?;
+		Ok(())
+	}
 }
 
-impl<T: Config> Pallet<T>
+impl<T: Config<I>, I: 'static> Pallet<T, I>
 where
 	T: Send + Sync,
 	u32: From<<T as frame_system::Config>::BlockNumber>,
@@ -112,12 +151,12 @@ where
 	pub fn channel(
 		channel_id: Vec<u8>,
 		port_id: Vec<u8>,
-	) -> Result<QueryChannelResponse, Error<T>> {
-		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T>::DecodingError)?;
+	) -> Result<QueryChannelResponse, Error<T, I>> {
+		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let channel_id =
-			channel_id_from_bytes(channel_id).map_err(|_| Error::<T>::DecodingError)?;
-		let channel =
-			Channels::<T>::get(port_id.clone(), channel_id).ok_or(Error::<T>::ChannelNotFound)?;
+			channel_id_from_bytes(channel_id).map_err(|_| Error::<T, I>::DecodingError)?;
+		let channel = Channels::<T, I>::get(port_id.clone(), channel_id)
+			.ok_or(Error::<T, I>::ChannelNotFound)?;
 		let channel_path = format!("{}", ChannelEndsPath(port_id, channel_id));
 		let key = apply_prefix(T::PalletPrefix::get(), vec![channel_path]);
 
@@ -125,11 +164,11 @@ where
 	}
 
 	/// Get a connection state
-	pub fn connection(connection_id: Vec<u8>) -> Result<QueryConnectionResponse, Error<T>> {
+	pub fn connection(connection_id: Vec<u8>) -> Result<QueryConnectionResponse, Error<T, I>> {
 		let connection_id =
-			connection_id_from_bytes(connection_id).map_err(|_| Error::<T>::DecodingError)?;
+			connection_id_from_bytes(connection_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let connection =
-			Connections::<T>::get(&connection_id).ok_or(Error::<T>::ConnectionNotFound)?;
+			Connections::<T, I>::get(&connection_id).ok_or(Error::<T, I>::ConnectionNotFound)?;
 
 		let connection_path = format!("{}", ConnectionsPath(connection_id));
 		let key = apply_prefix(T::PalletPrefix::get(), vec![connection_path]);
@@ -138,10 +177,11 @@ where
 	}
 
 	/// Get a client state
-	pub fn client(client_id: Vec<u8>) -> Result<QueryClientStateResponse, Error<T>> {
-		let client_id = client_id_from_bytes(client_id).map_err(|_| Error::<T>::DecodingError)?;
+	pub fn client(client_id: Vec<u8>) -> Result<QueryClientStateResponse, Error<T, I>> {
+		let client_id =
+			client_id_from_bytes(client_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let client_state =
-			ClientStates::<T>::get(&client_id).ok_or(Error::<T>::ClientStateNotFound)?;
+			ClientStates::<T, I>::get(&client_id).ok_or(Error::<T, I>::ClientStateNotFound)?;
 		let client_state_path = format!("{}", ClientStatePath(client_id));
 
 		let key = apply_prefix(T::PalletPrefix::get(), vec![client_state_path]);
@@ -152,7 +192,7 @@ where
 	/// Get all client states
 	/// Returns a Vec of (client_id, client_state)
 	pub fn clients() -> Vec<(Vec<u8>, Vec<u8>)> {
-		ClientStates::<T>::iter()
+		ClientStates::<T, I>::iter()
 			.map(|(client_id, client_state)| (client_id.as_bytes().to_vec(), client_state))
 			.collect::<Vec<_>>()
 	}
@@ -163,19 +203,20 @@ where
 		revision_number: u64,
 		revision_height: u64,
 		latest_cs: bool,
-	) -> Result<QueryConsensusStateResponse, Error<T>> {
-		let client_id = client_id_from_bytes(client_id).map_err(|_| Error::<T>::DecodingError)?;
+	) -> Result<QueryConsensusStateResponse, Error<T, I>> {
+		let client_id =
+			client_id_from_bytes(client_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let height = if latest_cs {
 			let client_state =
-				ClientStates::<T>::get(&client_id).ok_or(Error::<T>::ClientStateNotFound)?;
-			let client_state =
-				AnyClientState::decode_vec(&client_state).map_err(|_| Error::<T>::DecodingError)?;
+				ClientStates::<T, I>::get(&client_id).ok_or(Error::<T, I>::ClientStateNotFound)?;
+			let client_state = AnyClientState::decode_vec(&client_state)
+				.map_err(|_| Error::<T, I>::DecodingError)?;
 			client_state.latest_height()
 		} else {
 			Height::new(revision_number, revision_height)
 		};
-		let consensus_state = ConsensusStates::<T>::get(client_id.clone(), height)
-			.ok_or(Error::<T>::ConsensusStateNotFound)?;
+		let consensus_state = ConsensusStates::<T, I>::get(client_id.clone(), height)
+			.ok_or(Error::<T, I>::ConsensusStateNotFound)?;
 
 		let consensus_path = ClientConsensusStatePath {
 			client_id,
@@ -196,15 +237,15 @@ where
 	/// Get all connection states for a client
 	pub fn connection_using_client(
 		client_id: Vec<u8>,
-	) -> Result<Vec<IdentifiedConnection>, Error<T>> {
-		let connection_ids = ConnectionClient::<T>::get(client_id);
+	) -> Result<Vec<IdentifiedConnection>, Error<T, I>> {
+		let connection_ids = ConnectionClient::<T, I>::get(client_id);
 		let connections = connection_ids
 			.into_iter()
 			.filter_map(|connection_id| {
 				let conn_id = connection_id_from_bytes(connection_id.clone()).ok()?;
 
 				Some(IdentifiedConnection {
-					connection_end: Connections::<T>::get(&conn_id)?,
+					connection_end: Connections::<T, I>::get(&conn_id)?,
 					connection_id,
 				})
 			})
@@ -213,12 +254,13 @@ where
 		Ok(connections)
 	}
 
-	fn channel_client_id(channel_end: &ChannelEnd) -> Result<ClientId, Error<T>> {
-		let ctx = Context::<T>::default();
+	fn channel_client_id(channel_end: &ChannelEnd) -> Result<ClientId, Error<T, I>> {
+		let ctx = Context::<T, I>::default();
 		let connection_id =
-			channel_end.connection_hops.get(0).ok_or(Error::<T>::ConnectionNotFound)?;
-		let connection_end =
-			ctx.connection_end(connection_id).map_err(|_| Error::<T>::ConnectionNotFound)?;
+			channel_end.connection_hops.get(0).ok_or(Error::<T, I>::ConnectionNotFound)?;
+		let connection_end = ctx
+			.connection_end(connection_id)
+			.map_err(|_| Error::<T, I>::ConnectionNotFound)?;
 		Ok(connection_end.client_id().clone())
 	}
 
@@ -226,75 +268,77 @@ where
 	pub fn channel_client(
 		channel_id: Vec<u8>,
 		port_id: Vec<u8>,
-	) -> Result<IdentifiedClientState, Error<T>> {
+	) -> Result<IdentifiedClientState, Error<T, I>> {
 		let channel_id =
-			channel_id_from_bytes(channel_id).map_err(|_| Error::<T>::InvalidChannelId)?;
-		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T>::InvalidPortId)?;
-		let ctx = Context::<T>::new();
+			channel_id_from_bytes(channel_id).map_err(|_| Error::<T, I>::InvalidChannelId)?;
+		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T, I>::InvalidPortId)?;
+		let ctx = Context::<T, I>::new();
 		let channel_end = ctx
 			.channel_end(&(port_id, channel_id))
-			.map_err(|_| Error::<T>::ChannelNotFound)?;
+			.map_err(|_| Error::<T, I>::ChannelNotFound)?;
 		let client_id = Self::channel_client_id(&channel_end)?;
 		let client_state =
-			ClientStates::<T>::get(&client_id).ok_or(Error::<T>::ClientStateNotFound)?;
+			ClientStates::<T, I>::get(&client_id).ok_or(Error::<T, I>::ClientStateNotFound)?;
 		Ok(IdentifiedClientState { client_id: client_id.as_bytes().to_vec(), client_state })
 	}
 
 	/// Get all channel states
-	pub fn channels() -> Result<QueryChannelsResponse, Error<T>> {
-		let channels = Channels::<T>::iter()
+	pub fn channels() -> Result<QueryChannelsResponse, Error<T, I>> {
+		let channels = Channels::<T, I>::iter()
 			.map(|(port_id, channel_id, channel_end)| {
 				Ok(IdentifiedChannel { channel_id, port_id, channel_end })
 			})
-			.collect::<Result<Vec<_>, Error<T>>>()?;
+			.collect::<Result<Vec<_>, Error<T, I>>>()?;
 
 		Ok(QueryChannelsResponse { channels, height: host_height::<T>() })
 	}
 
 	/// Get all connection states
-	pub fn connections() -> Result<QueryConnectionsResponse, Error<T>> {
-		let connections = Connections::<T>::iter()
+	pub fn connections() -> Result<QueryConnectionsResponse, Error<T, I>> {
+		let connections = Connections::<T, I>::iter()
 			.map(|(connection_id, connection_end)| {
 				Ok(IdentifiedConnection { connection_id, connection_end })
 			})
-			.collect::<Result<Vec<_>, Error<T>>>()?;
+			.collect::<Result<Vec<_>, Error<T, I>>>()?;
 
 		Ok(QueryConnectionsResponse { connections, height: host_height::<T>() })
 	}
 
 	/// Get all channels bound to this connection
-	pub fn connection_channels(connection_id: Vec<u8>) -> Result<QueryChannelsResponse, Error<T>> {
-		let identifiers = ChannelsConnection::<T>::get(connection_id);
+	pub fn connection_channels(
+		connection_id: Vec<u8>,
+	) -> Result<QueryChannelsResponse, Error<T, I>> {
+		let identifiers = ChannelsConnection::<T, I>::get(connection_id);
 
 		let channels = identifiers
 			.into_iter()
 			.map(|(port_id_bytes, channel_id_bytes)| {
 				let channel_id = channel_id_from_bytes(channel_id_bytes.clone())
-					.map_err(|_| Error::<T>::DecodingError)?;
+					.map_err(|_| Error::<T, I>::DecodingError)?;
 				let port_id = port_id_from_bytes(port_id_bytes.clone())
-					.map_err(|_| Error::<T>::DecodingError)?;
+					.map_err(|_| Error::<T, I>::DecodingError)?;
 
-				let channel_end =
-					Channels::<T>::get(port_id, channel_id).ok_or(Error::<T>::ChannelNotFound)?;
+				let channel_end = Channels::<T, I>::get(port_id, channel_id)
+					.ok_or(Error::<T, I>::ChannelNotFound)?;
 				Ok(IdentifiedChannel {
 					channel_id: channel_id_bytes,
 					port_id: port_id_bytes,
 					channel_end,
 				})
 			})
-			.collect::<Result<Vec<_>, Error<T>>>()?;
+			.collect::<Result<Vec<_>, Error<T, I>>>()?;
 		Ok(QueryChannelsResponse { channels, height: host_height::<T>() })
 	}
 
 	pub fn packet_commitments(
 		channel_id_bytes: Vec<u8>,
 		port_id_bytes: Vec<u8>,
-	) -> Result<QueryPacketCommitmentsResponse, Error<T>> {
+	) -> Result<QueryPacketCommitmentsResponse, Error<T, I>> {
 		let channel_id = channel_id_from_bytes(channel_id_bytes.clone())
-			.map_err(|_| Error::<T>::DecodingError)?;
+			.map_err(|_| Error::<T, I>::DecodingError)?;
 		let port_id =
-			port_id_from_bytes(port_id_bytes.clone()).map_err(|_| Error::<T>::DecodingError)?;
-		let commitments = PacketCommitment::<T>::iter()
+			port_id_from_bytes(port_id_bytes.clone()).map_err(|_| Error::<T, I>::DecodingError)?;
+		let commitments = PacketCommitment::<T, I>::iter()
 			.filter_map(|((p, c, s), commitment)| {
 				if p == port_id && c == channel_id {
 					let packet_state = PacketState {
@@ -316,12 +360,12 @@ where
 	pub fn packet_acknowledgements(
 		channel_id_bytes: Vec<u8>,
 		port_id_bytes: Vec<u8>,
-	) -> Result<QueryPacketAcknowledgementsResponse, Error<T>> {
+	) -> Result<QueryPacketAcknowledgementsResponse, Error<T, I>> {
 		let channel_id = channel_id_from_bytes(channel_id_bytes.clone())
-			.map_err(|_| Error::<T>::DecodingError)?;
+			.map_err(|_| Error::<T, I>::DecodingError)?;
 		let port_id =
-			port_id_from_bytes(port_id_bytes.clone()).map_err(|_| Error::<T>::DecodingError)?;
-		let acks = Acknowledgements::<T>::iter()
+			port_id_from_bytes(port_id_bytes.clone()).map_err(|_| Error::<T, I>::DecodingError)?;
+		let acks = Acknowledgements::<T, I>::iter()
 			.filter_map(|((p, c, s), ack)| {
 				if p == port_id && c == channel_id {
 					let packet_state = PacketState {
@@ -339,18 +383,103 @@ where
 		Ok(QueryPacketAcknowledgementsResponse { acks, height: host_height::<T>() })
 	}
 
+	/// Returns the requested page of `items`.
+	fn page<Item>(mut items: impl Iterator<Item = Item>, page: PageRequest) -> Page<Item> {
+		let skipped = items.by_ref().take(page.offset as usize).count();
+		let page_items = items.by_ref().take(page.limit as usize).collect::<Vec<_>>();
+		let next_offset = (skipped + page_items.len()) as u32;
+		let (more, total) = if page.count_total {
+			let remaining = items.count();
+			(remaining > 0, Some((skipped + page_items.len() + remaining) as u64))
+		} else {
+			(items.next().is_some(), None)
+		};
+		Page {
+			items: page_items,
+			next_offset: more.then_some(next_offset),
+			total,
+			height: host_height::<T>(),
+		}
+	}
+
+	/// Get a page of the client states
+	pub fn clients_page(page: PageRequest) -> Page<IdentifiedClientState> {
+		let clients = ClientStates::<T, I>::iter().map(|(client_id, client_state)| {
+			IdentifiedClientState { client_id: client_id.as_bytes().to_vec(), client_state }
+		});
+		Self::page(clients, page)
+	}
+
+	/// Get a page of the connection states
+	pub fn connections_page(page: PageRequest) -> Page<IdentifiedConnection> {
+		let connections = Connections::<T, I>::iter().map(|(connection_id, connection_end)| {
+			IdentifiedConnection { connection_id, connection_end }
+		});
+		Self::page(connections, page)
+	}
+
+	/// Get a page of the channel states
+	pub fn channels_page(page: PageRequest) -> Page<IdentifiedChannel> {
+		let channels = Channels::<T, I>::iter().map(|(port_id, channel_id, channel_end)| {
+			IdentifiedChannel { channel_id, port_id, channel_end }
+		});
+		Self::page(channels, page)
+	}
+
+	/// Get a page of the packet commitments of a channel
+	pub fn packet_commitments_page(
+		channel_id: Vec<u8>,
+		port_id: Vec<u8>,
+		page: PageRequest,
+	) -> Result<Page<PacketState>, Error<T, I>> {
+		let packets =
+			Self::channel_packet_states(PacketCommitment::<T, I>::iter(), channel_id, port_id)?;
+		Ok(Self::page(packets, page))
+	}
+
+	/// Get a page of the packet acknowledgements of a channel
+	pub fn packet_acknowledgements_page(
+		channel_id: Vec<u8>,
+		port_id: Vec<u8>,
+		page: PageRequest,
+	) -> Result<Page<PacketState>, Error<T, I>> {
+		let packets =
+			Self::channel_packet_states(Acknowledgements::<T, I>::iter(), channel_id, port_id)?;
+		Ok(Self::page(packets, page))
+	}
+
+	/// Filters the packet states of a channel out of the packet states of all the channels.
+	fn channel_packet_states(
+		packets: impl Iterator<Item = ((PortId, ChannelId, Sequence), Vec<u8>)>,
+		channel_id_bytes: Vec<u8>,
+		port_id_bytes: Vec<u8>,
+	) -> Result<impl Iterator<Item = PacketState>, Error<T, I>> {
+		let channel_id = channel_id_from_bytes(channel_id_bytes.clone())
+			.map_err(|_| Error::<T, I>::DecodingError)?;
+		let port_id =
+			port_id_from_bytes(port_id_bytes.clone()).map_err(|_| Error::<T, I>::DecodingError)?;
+		Ok(packets.filter_map(move |((p, c, s), data)| {
+			(p == port_id && c == channel_id).then(|| PacketState {
+				port_id: port_id_bytes.clone(),
+				channel_id: channel_id_bytes.clone(),
+				sequence: s.into(),
+				data,
+			})
+		}))
+	}
+
 	pub fn unreceived_packets(
 		channel_id: Vec<u8>,
 		port_id: Vec<u8>,
 		seqs: Vec<u64>,
-	) -> Result<Vec<u64>, Error<T>> {
+	) -> Result<Vec<u64>, Error<T, I>> {
 		let channel_id =
-			channel_id_from_bytes(channel_id).map_err(|_| Error::<T>::DecodingError)?;
-		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T>::DecodingError)?;
+			channel_id_from_bytes(channel_id).map_err(|_| Error::<T, I>::DecodingError)?;
+		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		Ok(seqs
 			.into_iter()
 			.filter(|s| {
-				!PacketReceipt::<T>::contains_key((port_id.clone(), channel_id, (*s).into()))
+				!PacketReceipt::<T, I>::contains_key((port_id.clone(), channel_id, (*s).into()))
 			})
 			.collect())
 	}
@@ -359,14 +488,15 @@ where
 		channel_id_bytes: Vec<u8>,
 		port_id_bytes: Vec<u8>,
 		seqs: Vec<u64>,
-	) -> Result<Vec<u64>, Error<T>> {
+	) -> Result<Vec<u64>, Error<T, I>> {
 		let channel_id =
-			channel_id_from_bytes(channel_id_bytes).map_err(|_| Error::<T>::DecodingError)?;
-		let port_id = port_id_from_bytes(port_id_bytes).map_err(|_| Error::<T>::DecodingError)?;
+			channel_id_from_bytes(channel_id_bytes).map_err(|_| Error::<T, I>::DecodingError)?;
+		let port_id =
+			port_id_from_bytes(port_id_bytes).map_err(|_| Error::<T, I>::DecodingError)?;
 		Ok(seqs
 			.into_iter()
 			.filter(|s| {
-				PacketCommitment::<T>::contains_key((port_id.clone(), channel_id, (*s).into()))
+				PacketCommitment::<T, I>::contains_key((port_id.clone(), channel_id, (*s).into()))
 			})
 			.collect())
 	}
@@ -374,12 +504,12 @@ where
 	pub fn next_seq_recv(
 		channel_id: Vec<u8>,
 		port_id: Vec<u8>,
-	) -> Result<QueryNextSequenceReceiveResponse, Error<T>> {
-		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T>::DecodingError)?;
+	) -> Result<QueryNextSequenceReceiveResponse, Error<T, I>> {
+		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let channel_id =
-			channel_id_from_bytes(channel_id).map_err(|_| Error::<T>::DecodingError)?;
-		let sequence = NextSequenceRecv::<T>::get(port_id.clone(), channel_id)
-			.ok_or(Error::<T>::SendPacketError)?;
+			channel_id_from_bytes(channel_id).map_err(|_| Error::<T, I>::DecodingError)?;
+		let sequence = NextSequenceRecv::<T, I>::get(port_id.clone(), channel_id)
+			.ok_or(Error::<T, I>::SendPacketError)?;
 		let next_seq_recv_path = format!("{}", SeqRecvsPath(port_id, channel_id));
 		let key = apply_prefix(T::PalletPrefix::get(), vec![next_seq_recv_path]);
 
@@ -390,12 +520,12 @@ where
 		channel_id: Vec<u8>,
 		port_id: Vec<u8>,
 		seq: u64,
-	) -> Result<QueryPacketCommitmentResponse, Error<T>> {
-		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T>::DecodingError)?;
+	) -> Result<QueryPacketCommitmentResponse, Error<T, I>> {
+		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let channel_id =
-			channel_id_from_bytes(channel_id).map_err(|_| Error::<T>::DecodingError)?;
-		let commitment = PacketCommitment::<T>::get((port_id.clone(), channel_id, seq.into()))
-			.ok_or(Error::<T>::PacketCommitmentNotFound)?;
+			channel_id_from_bytes(channel_id).map_err(|_| Error::<T, I>::DecodingError)?;
+		let commitment = PacketCommitment::<T, I>::get((port_id.clone(), channel_id, seq.into()))
+			.ok_or(Error::<T, I>::PacketCommitmentNotFound)?;
 		let sequence = ibc::core::ics04_channel::packet::Sequence::from(seq);
 		let commitment_path = format!("{}", CommitmentsPath { port_id, channel_id, sequence });
 		let key = apply_prefix(T::PalletPrefix::get(), vec![commitment_path]);
@@ -407,13 +537,13 @@ where
 		channel_id: Vec<u8>,
 		port_id: Vec<u8>,
 		seq: u64,
-	) -> Result<QueryPacketAcknowledgementResponse, Error<T>> {
-		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T>::DecodingError)?;
+	) -> Result<QueryPacketAcknowledgementResponse, Error<T, I>> {
+		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let channel_id =
-			channel_id_from_bytes(channel_id).map_err(|_| Error::<T>::DecodingError)?;
+			channel_id_from_bytes(channel_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let sequence = ibc::core::ics04_channel::packet::Sequence::from(seq);
-		let ack = Acknowledgements::<T>::get((port_id.clone(), channel_id, sequence))
-			.ok_or(Error::<T>::PacketCommitmentNotFound)?;
+		let ack = Acknowledgements::<T, I>::get((port_id.clone(), channel_id, sequence))
+			.ok_or(Error::<T, I>::PacketCommitmentNotFound)?;
 		let acks_path = format!("{}", AcksPath { port_id, channel_id, sequence });
 		let key = apply_prefix(T::PalletPrefix::get(), vec![acks_path]);
 
@@ -424,14 +554,14 @@ where
 		channel_id: Vec<u8>,
 		port_id: Vec<u8>,
 		seq: u64,
-	) -> Result<QueryPacketReceiptResponse, Error<T>> {
-		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T>::DecodingError)?;
+	) -> Result<QueryPacketReceiptResponse, Error<T, I>> {
+		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let channel_id =
-			channel_id_from_bytes(channel_id).map_err(|_| Error::<T>::DecodingError)?;
+			channel_id_from_bytes(channel_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let sequence = ibc::core::ics04_channel::packet::Sequence::from(seq);
-		let receipt = PacketReceipt::<T>::get((port_id.clone(), channel_id, sequence))
-			.ok_or(Error::<T>::PacketReceiptNotFound)?;
-		let receipt = String::from_utf8(receipt).map_err(|_| Error::<T>::DecodingError)?;
+		let receipt = PacketReceipt::<T, I>::get((port_id.clone(), channel_id, sequence))
+			.ok_or(Error::<T, I>::PacketReceiptNotFound)?;
+		let receipt = String::from_utf8(receipt).map_err(|_| Error::<T, I>::DecodingError)?;
 		let receipt_path = format!("{}", ReceiptsPath { port_id, channel_id, sequence });
 		let key = apply_prefix(T::PalletPrefix::get(), vec![receipt_path]);
 		let receipt = &receipt == "Ok";
@@ -441,15 +571,16 @@ where
 	pub fn connection_handshake(
 		client_id: Vec<u8>,
 		connection_id: Vec<u8>,
-	) -> Result<ConnectionHandshake, Error<T>> {
-		let client_id = client_id_from_bytes(client_id).map_err(|_| Error::<T>::DecodingError)?;
+	) -> Result<ConnectionHandshake, Error<T, I>> {
+		let client_id =
+			client_id_from_bytes(client_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let client_state =
-			ClientStates::<T>::get(&client_id).ok_or(Error::<T>::ClientStateNotFound)?;
+			ClientStates::<T, I>::get(&client_id).ok_or(Error::<T, I>::ClientStateNotFound)?;
 		let client_state_decoded =
-			AnyClientState::decode_vec(&client_state).map_err(|_| Error::<T>::DecodingError)?;
+			AnyClientState::decode_vec(&client_state).map_err(|_| Error::<T, I>::DecodingError)?;
 		let height = client_state_decoded.latest_height();
 		let connection_id =
-			connection_id_from_bytes(connection_id).map_err(|_| Error::<T>::DecodingError)?;
+			connection_id_from_bytes(connection_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let prefix = T::PalletPrefix::get();
 		let connection_path = format!("{}", ConnectionsPath(connection_id));
 		let consensus_path = ClientConsensusStatePath {
@@ -469,14 +600,28 @@ where
 		})
 	}
 
+	/// Returns the child trie keys of the given commitment paths, e.g.
+	/// `commitments/ports/transfer/channels/channel-0/sequences/1`, so that a single proof of all
+	/// of them can be generated.
+	pub fn commitment_trie_keys(paths: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>, Error<T, I>> {
+		paths
+			.into_iter()
+			.map(|path| {
+				let path = String::from_utf8(path).map_err(|_| Error::<T, I>::DecodingError)?;
+				let path = Path::from_str(&path).map_err(|_| Error::<T, I>::DecodingError)?;
+				Ok(apply_prefix(T::PalletPrefix::get(), vec![format!("{path}")]))
+			})
+			.collect()
+	}
+
 	pub fn query_balance_with_address(
 		addr: Vec<u8>,
 		asset_id: T::AssetId,
-	) -> Result<u128, Error<T>> {
-		let hex_string = String::from_utf8(addr).map_err(|_| Error::<T>::DecodingError)?;
-		let signer = Signer::from_str(&hex_string).map_err(|_| Error::<T>::DecodingError)?;
+	) -> Result<u128, Error<T, I>> {
+		let hex_string = String::from_utf8(addr).map_err(|_| Error::<T, I>::DecodingError)?;
+		let signer = Signer::from_str(&hex_string).map_err(|_| Error::<T, I>::DecodingError)?;
 		let ibc_acc =
-			T::AccountIdConversion::try_from(signer).map_err(|_| Error::<T>::DecodingError)?;
+			T::AccountIdConversion::try_from(signer).map_err(|_| Error::<T, I>::DecodingError)?;
 		let account_id = ibc_acc.into_account();
 
 		if asset_id == T::NativeAssetId::get() {
@@ -506,42 +651,48 @@ where
 	pub fn store_raw_acknowledgement(
 		key: (PortId, ChannelId, Sequence),
 		ack: Vec<u8>,
-	) -> Result<(), Error<T>> {
+	) -> Result<(), Error<T, I>> {
 		let channel_id = key.1.to_string().as_bytes().to_vec();
 		let port_id = key.0.as_bytes().to_vec();
 		let seq = u64::from(key.2);
 
-		let key = Pallet::<T>::ack_key(channel_id, port_id, seq);
-		Acks::<T>::insert(key.clone(), ack.clone());
+		let key = Pallet::<T, I>::ack_key(channel_id, port_id, seq);
+		Acks::<T, I>::insert(key.clone(), ack.clone());
 		let ack_str = String::from_utf8_lossy(&ack);
 		log::trace!(target: "pallet_ibc", "in channel: [store_raw_acknowledgement] >> writing acknowledgement: {}, key: 0x{}", ack_str, hex::encode(&key));
 		Ok(())
 	}
 
-	pub(crate) fn packet_cleanup() -> Result<usize, (Error<T>, usize)> {
-		let ctx = Context::<T>::default();
+	pub(crate) fn packet_cleanup() -> Result<usize, (Error<T, I>, usize)> {
+		let ctx = Context::<T, I>::default();
 
 		let mut removed_count = 0;
-		for (port_id_bytes, channel_id_bytes, _) in Channels::<T>::iter() {
+		for (port_id_bytes, channel_id_bytes, _) in Channels::<T, I>::iter() {
 			let channel_id = channel_id_from_bytes(channel_id_bytes.clone())
-				.map_err(|_| (Error::<T>::DecodingError, removed_count))?;
+				.map_err(|_| (Error::<T, I>::DecodingError, removed_count))?;
 			let port_id = port_id_from_bytes(port_id_bytes.clone())
-				.map_err(|_| (Error::<T>::DecodingError, removed_count))?;
+				.map_err(|_| (Error::<T, I>::DecodingError, removed_count))?;
 
-			let (mut send_seq_set, mut last_removed_send) =
-				PendingSendPacketSeqs::<T>::get(&(port_id_bytes.clone(), channel_id_bytes.clone()));
+			let (mut send_seq_set, mut last_removed_send) = PendingSendPacketSeqs::<T, I>::get(&(
+				port_id_bytes.clone(),
+				channel_id_bytes.clone(),
+			));
 
 			let last_removed_send_copy = last_removed_send;
 
 			// We first try to remove sequences that were skipped in a previous cycle
 			for seq in send_seq_set.clone() {
-				if !PacketCommitment::<T>::contains_key((port_id.clone(), channel_id, seq.into())) {
-					let key = Pallet::<T>::send_packet_key(
+				if !PacketCommitment::<T, I>::contains_key((
+					port_id.clone(),
+					channel_id,
+					seq.into(),
+				)) {
+					let key = Pallet::<T, I>::send_packet_key(
 						channel_id_bytes.clone(),
 						port_id_bytes.clone(),
 						seq,
 					);
-					SendPackets::<T>::remove(key.clone());
+					SendPackets::<T, I>::remove(key.clone());
 					send_seq_set.remove(&seq);
 					last_removed_send = seq;
 					removed_count += 1;
@@ -552,19 +703,23 @@ where
 			let next_seq_send =
 				ctx.get_next_sequence_send(&(port_id.clone(), channel_id)).map_err(|_| {
 					log::trace!(target: "pallet_ibc", "Failed to run packet clean up");
-					(Error::<T>::Other, removed_count)
+					(Error::<T, I>::Other, removed_count)
 				})?;
 			let range = (last_removed_send + 1)..
 				(last_removed_send + PACKET_CLEANUP_PER_CYCLE).min(next_seq_send.into());
 			for seq in range {
-				if !PacketCommitment::<T>::contains_key((port_id.clone(), channel_id, seq.into())) {
-					let key = Pallet::<T>::send_packet_key(
+				if !PacketCommitment::<T, I>::contains_key((
+					port_id.clone(),
+					channel_id,
+					seq.into(),
+				)) {
+					let key = Pallet::<T, I>::send_packet_key(
 						channel_id_bytes.clone(),
 						port_id_bytes.clone(),
 						seq,
 					);
-					if SendPackets::<T>::contains_key(key.clone()) {
-						SendPackets::<T>::remove(key.clone());
+					if SendPackets::<T, I>::contains_key(key.clone()) {
+						SendPackets::<T, I>::remove(key.clone());
 						last_removed_send = seq;
 						removed_count += 1;
 					}
@@ -574,27 +729,36 @@ where
 				}
 			}
 
-			PendingSendPacketSeqs::<T>::insert(
+			PendingSendPacketSeqs::<T, I>::insert(
 				(port_id_bytes.clone(), channel_id_bytes.clone()),
 				(send_seq_set, last_removed_send.max(last_removed_send_copy)),
 			);
 
-			let (mut recv_seq_set, mut last_removed_ack) =
-				PendingRecvPacketSeqs::<T>::get(&(port_id_bytes.clone(), channel_id_bytes.clone()));
+			let (mut recv_seq_set, mut last_removed_ack) = PendingRecvPacketSeqs::<T, I>::get(&(
+				port_id_bytes.clone(),
+				channel_id_bytes.clone(),
+			));
 			let last_removed_ack_copy = last_removed_ack;
 
 			// We first try to remove sequences that were skipped in a previous cycle
 			for seq in recv_seq_set.clone() {
-				if !Acknowledgements::<T>::contains_key((port_id.clone(), channel_id, seq.into())) {
-					let key = Pallet::<T>::recv_packet_key(
+				if !Acknowledgements::<T, I>::contains_key((
+					port_id.clone(),
+					channel_id,
+					seq.into(),
+				)) {
+					let key = Pallet::<T, I>::recv_packet_key(
+						channel_id_bytes.clone(),
+						port_id_bytes.clone(),
+						seq,
+					);
+					let ack_key = Pallet::<T, I>::ack_key(
 						channel_id_bytes.clone(),
 						port_id_bytes.clone(),
 						seq,
 					);
-					let ack_key =
-						Pallet::<T>::ack_key(channel_id_bytes.clone(), port_id_bytes.clone(), seq);
-					RecvPackets::<T>::remove(key.clone());
-					Acks::<T>::remove(ack_key.clone());
+					RecvPackets::<T, I>::remove(key.clone());
+					Acks::<T, I>::remove(ack_key.clone());
 					recv_seq_set.remove(&seq);
 					last_removed_ack = seq;
 					removed_count += 1;
@@ -604,23 +768,30 @@ where
 			let next_seq_recv =
 				ctx.get_next_sequence_recv(&(port_id.clone(), channel_id)).map_err(|_| {
 					log::trace!(target: "pallet_ibc", "Failed to run packet clean up");
-					(Error::<T>::Other, removed_count)
+					(Error::<T, I>::Other, removed_count)
 				})?;
 			let range = (last_removed_ack + 1)..
 				(last_removed_ack + PACKET_CLEANUP_PER_CYCLE).min(next_seq_recv.into());
 			for seq in range {
-				if !Acknowledgements::<T>::contains_key((port_id.clone(), channel_id, seq.into())) {
-					let key = Pallet::<T>::recv_packet_key(
+				if !Acknowledgements::<T, I>::contains_key((
+					port_id.clone(),
+					channel_id,
+					seq.into(),
+				)) {
+					let key = Pallet::<T, I>::recv_packet_key(
 						channel_id_bytes.clone(),
 						port_id_bytes.clone(),
 						seq,
 					);
 
-					let ack_key =
-						Pallet::<T>::ack_key(channel_id_bytes.clone(), port_id_bytes.clone(), seq);
-					if RecvPackets::<T>::contains_key(key.clone()) {
-						RecvPackets::<T>::remove(key.clone());
-						Acks::<T>::remove(ack_key.clone());
+					let ack_key = Pallet::<T, I>::ack_key(
+						channel_id_bytes.clone(),
+						port_id_bytes.clone(),
+						seq,
+					);
+					if RecvPackets::<T, I>::contains_key(key.clone()) {
+						RecvPackets::<T, I>::remove(key.clone());
+						Acks::<T, I>::remove(ack_key.clone());
 						last_removed_ack = seq;
 						removed_count += 1;
 					}
@@ -629,7 +800,7 @@ where
 					recv_seq_set.insert(seq);
 				}
 			}
-			PendingRecvPacketSeqs::<T>::insert(
+			PendingRecvPacketSeqs::<T, I>::insert(
 				(port_id_bytes.clone(), channel_id_bytes.clone()),
 				(recv_seq_set, last_removed_ack.max(last_removed_ack_copy)),
 			);
@@ -641,13 +812,13 @@ where
 		channel_id: Vec<u8>,
 		port_id: Vec<u8>,
 		sequences: Vec<u64>,
-	) -> Result<Vec<PacketInfo>, Error<T>> {
+	) -> Result<Vec<PacketInfo>, Error<T, I>> {
 		let packets = sequences
 			.clone()
 			.into_iter()
 			.filter_map(|seq| {
-				let key = Pallet::<T>::send_packet_key(channel_id.clone(), port_id.clone(), seq);
-				SendPackets::<T>::get(key).and_then(|v| PacketInfo::decode(&mut &*v).ok())
+				let key = Pallet::<T, I>::send_packet_key(channel_id.clone(), port_id.clone(), seq);
+				SendPackets::<T, I>::get(key).and_then(|v| PacketInfo::decode(&mut &*v).ok())
 			})
 			.collect();
 		log::trace!(target: "pallet_ibc", "get_send_packet_info: {:?}, {:?}", sequences, packets);
@@ -658,16 +829,16 @@ where
 		channel_id: Vec<u8>,
 		port_id: Vec<u8>,
 		sequences: Vec<u64>,
-	) -> Result<Vec<PacketInfo>, Error<T>> {
+	) -> Result<Vec<PacketInfo>, Error<T, I>> {
 		let packets = sequences
 			.clone()
 			.into_iter()
 			.filter_map(|seq| {
-				let key = Pallet::<T>::recv_packet_key(channel_id.clone(), port_id.clone(), seq);
-				let ack_key = Pallet::<T>::ack_key(channel_id.clone(), port_id.clone(), seq);
+				let key = Pallet::<T, I>::recv_packet_key(channel_id.clone(), port_id.clone(), seq);
+				let ack_key = Pallet::<T, I>::ack_key(channel_id.clone(), port_id.clone(), seq);
 				let packet_info =
-					RecvPackets::<T>::get(key).and_then(|v| PacketInfo::decode(&mut &*v).ok());
-				let ack = Acks::<T>::get(ack_key);
+					RecvPackets::<T, I>::get(key).and_then(|v| PacketInfo::decode(&mut &*v).ok());
+				let ack = Acks::<T, I>::get(ack_key);
 				packet_info.map(|mut packet_info| {
 					packet_info.ack = ack;
 					packet_info
@@ -682,22 +853,23 @@ where
 		client_id: Vec<u8>,
 		revision_number: u64,
 		revision_height: u64,
-	) -> Result<(u64, u64), Error<T>> {
-		let ctx = Context::<T>::default();
-		let client_id = client_id_from_bytes(client_id).map_err(|_| Error::<T>::DecodingError)?;
+	) -> Result<(u64, u64), Error<T, I>> {
+		let ctx = Context::<T, I>::default();
+		let client_id =
+			client_id_from_bytes(client_id).map_err(|_| Error::<T, I>::DecodingError)?;
 		let height = Height::new(revision_number, revision_height);
 		let update_height = ctx
 			.client_update_height(&client_id, height)
-			.map_err(|_| Error::<T>::ClientUpdateNotFound)?;
+			.map_err(|_| Error::<T, I>::ClientUpdateNotFound)?;
 		let update_time = ctx
 			.client_update_time(&client_id, height)
-			.map_err(|_| Error::<T>::ClientUpdateNotFound)?
+			.map_err(|_| Error::<T, I>::ClientUpdateNotFound)?
 			.nanoseconds();
 		Ok((update_height.revision_height, update_time))
 	}
 }
 
-impl<T: Config> Pallet<T> {
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
 	pub fn remove_channel_escrow_address(
 		port_id: &PortId,
 		channel_id: ChannelId,
@@ -714,7 +886,7 @@ impl<T: Config> Pallet<T> {
 				)
 			})?
 			.into_account();
-		let _ = EscrowAddresses::<T>::try_mutate::<_, &'static str, _>(|addresses| {
+		let _ = EscrowAddresses::<T, I>::try_mutate::<_, &'static str, _>(|addresses| {
 			addresses.remove(&account_id);
 			Ok(())
 		});
@@ -723,12 +895,12 @@ impl<T: Config> Pallet<T> {
 
 	/// Returns true if address provided is an escrow address
 	pub fn is_escrow_address(address: <T as frame_system::Config>::AccountId) -> bool {
-		let set = EscrowAddresses::<T>::get();
+		let set = EscrowAddresses::<T, I>::get();
 		set.contains(&address)
 	}
 }
 
-impl<T: Config> Pallet<T> {
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
 	pub fn get_denom_trace(
 		asset_id: T::AssetId,
 	) -> Option<ibc_primitives::QueryDenomTraceResponse> {
@@ -752,7 +924,8 @@ impl<T: Config> Pallet<T> {
 	}
 }
 
-impl<T: Config + Send + Sync> IbcHandler<<T as frame_system::Config>::AccountId> for Pallet<T>
+impl<T: Config<I> + Send + Sync, I: 'static> IbcHandler<<T as frame_system::Config>::AccountId>
+	for Pallet<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
@@ -761,7 +934,7 @@ where
 		port_id: &PortId,
 		channel_id: &ChannelId,
 	) -> Result<(Height, Timestamp), IbcHandlerError> {
-		Pallet::<T>::latest_height_and_timestamp(port_id, channel_id)
+		Pallet::<T, I>::latest_height_and_timestamp(port_id, channel_id)
 	}
 
 	fn handle_message(
@@ -769,17 +942,18 @@ where
 	) -> Result<(), IbcHandlerError> {
 		match msg {
 			HandlerMessage::OpenChannel { port_id, channel_end } =>
-				Pallet::<T>::open_channel(port_id, channel_end),
+				Pallet::<T, I>::open_channel(port_id, channel_end),
 			HandlerMessage::CloseChannel { channel_id, port_id } =>
-				Pallet::<T>::close_channel(port_id, channel_id),
+				Pallet::<T, I>::close_channel(port_id, channel_id),
 			HandlerMessage::Transfer { timeout, to, from, channel_id, coin, memo } => {
-				let msg = Pallet::<T>::to_msg_transfer(coin, from, to, timeout, channel_id, memo)?;
-				Pallet::<T>::send_transfer(msg)
+				let msg =
+					Pallet::<T, I>::to_msg_transfer(coin, from, to, timeout, channel_id, memo)?;
+				Pallet::<T, I>::send_transfer(msg)
 			},
 			HandlerMessage::SendPacket { data, timeout, port_id, channel_id } =>
-				Pallet::<T>::send_packet(data, timeout, port_id, channel_id),
+				Pallet::<T, I>::send_packet(data, timeout, port_id, channel_id),
 			HandlerMessage::WriteAck { ack, packet } =>
-				Pallet::<T>::write_acknowledgement(packet, ack),
+				Pallet::<T, I>::write_acknowledgement(packet, ack),
 		}
 	}
 
@@ -793,7 +967,7 @@ where
 
 		let (mock_client_state, mock_cs_state) = create_mock_state();
 		let client_id = ClientId::new(&mock_client_state.client_type(), 0).unwrap();
-		let msg = MsgCreateAnyClient::<Context<T>>::new(
+		let msg = MsgCreateAnyClient::<Context<T, I>>::new(
 			AnyClientState::Tendermint(mock_client_state),
 			AnyConsensusState::Tendermint(mock_cs_state),
 			Signer::from_str("pallet_ibc").unwrap(),
@@ -802,7 +976,7 @@ where
 		.encode_vec()
 		.unwrap();
 		let msg = ibc_proto::google::protobuf::Any { type_url: TYPE_URL.to_string(), value: msg };
-		let mut ctx = Context::<T>::new();
+		let mut ctx = Context::<T, I>::new();
 		ibc::core::ics26_routing::handler::deliver(&mut ctx, msg).unwrap();
 		Ok(client_id)
 	}
@@ -821,7 +995,7 @@ where
 		let counter_party = Counterparty::new(
 			client_id.clone(),
 			Some(ConnectionId::new(1)),
-			<T as Config>::PalletPrefix::get().to_vec().try_into().unwrap(),
+			<T as Config<I>>::PalletPrefix::get().to_vec().try_into().unwrap(),
 		);
 		let connection_end = ConnectionEnd::new(
 			State::Open,
@@ -830,14 +1004,14 @@ where
 			vec![Version::default()],
 			delay_period,
 		);
-		let mut ctx = crate::routing::Context::<T>::new();
+		let mut ctx = crate::routing::Context::<T, I>::new();
 		ctx.store_connection(connection_id.clone(), &connection_end).unwrap();
 		ctx.store_connection_to_client(connection_id, &client_id).unwrap();
 		Ok(())
 	}
 }
 
-impl<T: Config + Send + Sync> Pallet<T>
+impl<T: Config<I> + Send + Sync, I: 'static> Pallet<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
@@ -848,7 +1022,7 @@ where
 		source_port: PortId,
 		source_channel: ChannelId,
 	) -> Result<(), IbcHandlerError> {
-		let mut ctx = Context::<T>::new();
+		let mut ctx = Context::<T, I>::new();
 		let source_channel_end =
 			ctx.channel_end(&(source_port.clone(), source_channel)).map_err(|_| {
 				IbcHandlerError::ChannelOrPortError {
@@ -873,7 +1047,7 @@ where
 			},
 		)?;
 		let (latest_height, latest_timestamp) =
-			Pallet::<T>::latest_height_and_timestamp(&source_port, &source_channel).map_err(
+			Pallet::<T, I>::latest_height_and_timestamp(&source_port, &source_channel).map_err(
 				|_| IbcHandlerError::TimestampOrHeightNotFound {
 					msg: Some("Failed to retreive client height and timestamp".to_string()),
 				},
@@ -936,7 +1110,7 @@ where
 	}
 
 	fn open_channel(port_id: PortId, channel_end: ChannelEnd) -> Result<(), IbcHandlerError> {
-		let mut ctx = crate::routing::Context::<T>::new();
+		let mut ctx = crate::routing::Context::<T, I>::new();
 		// Signer does not matter in this case
 		let msg = MsgChannelOpenInit {
 			port_id,
@@ -958,7 +1132,7 @@ where
 	}
 
 	fn write_acknowledgement(packet: Packet, ack: Vec<u8>) -> Result<(), IbcHandlerError> {
-		let mut ctx = Context::<T>::default();
+		let mut ctx = Context::<T, I>::default();
 		let error = |action, err| {
 			let msg = Some(format!("Failed to {action} acknowledgement{err:?}"));
 			IbcHandlerError::AcknowledgementError { msg }
@@ -985,7 +1159,7 @@ where
 			<T as frame_system::Config>::SS58Prefix::get(),
 		);
 		let (latest_height, latest_timestamp) =
-			Pallet::<T>::latest_height_and_timestamp(&PortId::transfer(), &channel_id).map_err(
+			Pallet::<T, I>::latest_height_and_timestamp(&PortId::transfer(), &channel_id).map_err(
 				|_| IbcHandlerError::TimestampOrHeightNotFound {
 					msg: Some("Failed to retreive client height and timestamp".to_string()),
 				},
@@ -1043,17 +1217,22 @@ where
 	}
 
 	pub(crate) fn send_transfer(msg: MsgTransfer<PrefixedCoin>) -> Result<(), IbcHandlerError> {
-		let mut ctx = Context::<T>::default();
+		let flow = Self::limit_transfer(msg.source_channel, &msg.token, Direction::Send)
+			.map_err(|e| IbcHandlerError::RateLimited { msg: Some(format!("{e:?}")) })?;
+		let mut ctx = Context::<T, I>::default();
 		let mut handler_output = HandlerOutputBuilder::default();
 		send_transfer::<_, _>(&mut ctx, &mut handler_output, msg)
 			.map_err(|e| IbcHandlerError::SendTransferError { msg: Some(e.to_string()) })?;
+		if let Some(flow) = flow {
+			flow.record();
+		}
 		let result = handler_output.with_result(());
 		Self::deposit_event(result.events.into());
 		Ok(())
 	}
 
 	fn close_channel(port_id: PortId, channel_id: ChannelId) -> Result<(), IbcHandlerError> {
-		let mut ctx = crate::routing::Context::<T>::new();
+		let mut ctx = crate::routing::Context::<T, I>::new();
 		// Signer does not matter in this case
 		let msg = MsgChannelCloseInit {
 			port_id,
@@ -1077,7 +1256,7 @@ where
 		port_id: &PortId,
 		channel_id: &ChannelId,
 	) -> Result<(Height, Timestamp), IbcHandlerError> {
-		let ctx = Context::<T>::new();
+		let ctx = Context::<T, I>::new();
 		let source_channel_end =
 			ctx.channel_end(&(port_id.clone(), *channel_id)).map_err(|_| {
 				IbcHandlerError::ChannelOrPortError {
@@ -1109,7 +1288,7 @@ where
 	}
 }
 
-pub fn host_height<T: Config>() -> u64
+pub fn host_height<T: frame_system::Config>() -> u64
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 {
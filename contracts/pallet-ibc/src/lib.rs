@@ -46,16 +46,22 @@ use scale_info::{
 use sp_runtime::{Either, RuntimeDebug};
 use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
 
+pub mod asset_registry;
 mod channel;
 mod client;
 mod connection;
+pub mod denom;
 pub mod errors;
 pub mod events;
 pub mod ics20;
 mod ics23;
 pub mod light_clients;
+pub mod migrations;
 mod port;
+mod recovery;
+mod retention;
 pub mod routing;
+mod serde_base64;
 pub use client::HostConsensusProof;
 pub use ibc_primitives::Timeout;
 pub use light_client_common;
@@ -128,6 +134,9 @@ mod mock;
 mod tests;
 
 pub mod ics20_fee;
+pub mod ics27;
+pub mod ics29_fee;
+pub mod ics721;
 mod impls;
 pub mod weight;
 
@@ -163,6 +172,7 @@ pub mod pallet {
 	};
 	use crate::{
 		ics20::{HandleMemo, SubstrateMultihopXcmHandler},
+		ics23::consensus_states::ConsensusStates,
 		light_clients::AnyConsensusState,
 		routing::{Context, ModuleRouter},
 	};
@@ -176,11 +186,14 @@ pub mod pallet {
 			ics02_client::context::{ClientKeeper, ClientReader},
 			ics04_channel::context::ChannelReader,
 			ics24_host::identifier::{ChannelId, ClientId, PortId},
+			ics26_routing::context::ModuleId,
 		},
 		timestamp::Timestamp,
 		Height,
 	};
-	use ibc_primitives::{client_id_from_bytes, get_channel_escrow_address, IbcHandler};
+	use ibc_primitives::{
+		client_id_from_bytes, get_channel_escrow_address, port_id_from_bytes, IbcHandler,
+	};
 	use light_clients::AnyClientState;
 	use sp_runtime::{
 		traits::{IdentifyAccount, Saturating, Zero},
@@ -193,10 +206,13 @@ pub mod pallet {
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
-	pub trait Config: frame_system::Config + parachain_info::Config + core::fmt::Debug {
+	pub trait Config<I: 'static = ()>:
+		frame_system::Config + parachain_info::Config + core::fmt::Debug
+	{
 		type TimeProvider: UnixTime;
 		/// The overarching event type.
-		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		type RuntimeEvent: From<Event<Self, I>>
+			+ IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		/// Currency type of the runtime
 		type NativeCurrency: ReservableCurrency<
 			<Self as frame_system::Config>::AccountId,
@@ -210,9 +226,10 @@ pub mod pallet {
 		#[pallet::constant]
 		type NativeAssetId: Get<Self::AssetId>;
 		/// Convert ibc denom to asset id and vice versa
-		type IbcDenomToAssetIdConversion: DenomToAssetId<Self>;
+		type IbcDenomToAssetIdConversion: DenomToAssetId<Self, I>;
 
 		/// Prefix for events stored in the Off-chain DB via Indexing API, child trie and connection
+		/// prefix. Every instance of the pallet in a runtime must use a distinct prefix.
 		#[pallet::constant]
 		type PalletPrefix: Get<&'static [u8]>;
 
@@ -263,7 +280,7 @@ pub mod pallet {
 		type TransferOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::IbcAccountId>;
 		type RelayerOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
 		/// Handle Ics20 Memo
-		type HandleMemo: HandleMemo<Self> + Default;
+		type HandleMemo: HandleMemo<Self, I> + Default;
 		/// Memo Message types supported by the runtime
 		type MemoMessage: codec::Codec
 			+ FromStr
@@ -300,8 +317,8 @@ pub mod pallet {
 		type ServiceChargeOut: Get<Perbill>;
 
 		type FlatFeeConverter: FlatFeeConverter<
-			AssetId = <Self as crate::Config>::AssetId,
-			Balance = <Self as crate::Config>::Balance,
+			AssetId = <Self as crate::Config<I>>::AssetId,
+			Balance = <Self as crate::Config<I>>::Balance,
 		>;
 
 		//Asset Id for fee that will charged. for example  USDT
@@ -312,12 +329,13 @@ pub mod pallet {
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub (super) trait Store)]
+	#[pallet::storage_version(migrations::STORAGE_VERSION)]
 	#[pallet::without_storage_info]
-	pub struct Pallet<T>(_);
+	pub struct Pallet<T, I = ()>(_);
 
 	#[pallet::storage]
 	/// client_id , Height => Height
-	pub type ClientUpdateHeight<T: Config> = StorageDoubleMap<
+	pub type ClientUpdateHeight<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
 		_,
 		Blake2_128Concat,
 		Vec<u8>,
@@ -328,94 +346,103 @@ pub mod pallet {
 	>;
 
 	#[pallet::storage]
-	pub type ServiceChargeOut<T: Config> = StorageValue<_, Perbill, OptionQuery>;
+	pub type ServiceChargeOut<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, Perbill, OptionQuery>;
 
 	#[pallet::storage]
 	/// client_id , Height => Timestamp
-	pub type ClientUpdateTime<T: Config> =
+	pub type ClientUpdateTime<T: Config<I>, I: 'static = ()> =
 		StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Blake2_128Concat, Vec<u8>, u64, OptionQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
-	pub type ChannelCounter<T: Config> = StorageValue<_, u32, ValueQuery>;
+	pub type ChannelCounter<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
-	pub type PacketCounter<T: Config> = StorageValue<_, u32, ValueQuery>;
+	pub type PacketCounter<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// connection_identifier => Vec<(port_id, channel_id)>
-	pub type ChannelsConnection<T: Config> =
+	pub type ChannelsConnection<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// storage map. key is tuple of (source_channel.sequence(), destination_channel.sequence()) and
 	/// value () that means that this group of channels is feeless
-	pub type FeeLessChannelIds<T: Config> =
+	pub type FeeLessChannelIds<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Blake2_128Concat, (u64, u64), (), ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// storage map where key is transfer sequence number and value calculated fee for that sequence
 	/// number
-	pub type SequenceFee<T: Config> = StorageMap<_, Blake2_128Concat, u64, u128, ValueQuery>;
+	pub type SequenceFee<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, u64, u128, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// counter for clients
-	pub type ClientCounter<T: Config> = StorageValue<_, u32, ValueQuery>;
+	pub type ClientCounter<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// counter for clients
-	pub type ConnectionCounter<T: Config> = StorageValue<_, u32, ValueQuery>;
+	pub type ConnectionCounter<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// counter for acknowledgments
-	pub type AcknowledgementCounter<T: Config> = StorageValue<_, u32, ValueQuery>;
+	pub type AcknowledgementCounter<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, u32, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// counter for packet receipts
-	pub type PacketReceiptCounter<T: Config> = StorageValue<_, u32, ValueQuery>;
+	pub type PacketReceiptCounter<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// client_id => Vec<Connection_id>
-	pub type ConnectionClient<T: Config> =
+	pub type ConnectionClient<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<Vec<u8>>, ValueQuery>;
 
 	#[pallet::storage]
 	/// Map of asset id to ibc denom pairs (T::AssetId, Vec<u8>)
 	/// ibc denoms represented as utf8 string bytes
-	pub type IbcAssetIds<T: Config> =
+	pub type IbcAssetIds<T: Config<I>, I: 'static = ()> =
 		CountedStorageMap<_, Twox64Concat, T::AssetId, Vec<u8>, OptionQuery>;
 
 	#[pallet::storage]
 	/// Map of asset id to ibc denom pairs (Vec<u8>, T::AssetId)
 	/// ibc denoms represented as utf8 string bytes
-	pub type IbcDenoms<T: Config> =
+	pub type IbcDenoms<T: Config<I>, I: 'static = ()> =
 		CountedStorageMap<_, Twox64Concat, Vec<u8>, T::AssetId, OptionQuery>;
 
+	#[pallet::storage]
+	/// Map of the hashes of the received ibc denoms to their full trace path, the denoms are
+	/// known as `ibc/{hash}` on cosmos chains
+	pub type DenomTraces<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, [u8; 32], Vec<u8>, OptionQuery>;
+
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// ChannelIds open from this module
-	pub type ChannelIds<T: Config> = StorageValue<_, Vec<Vec<u8>>, ValueQuery>;
+	pub type ChannelIds<T: Config<I>, I: 'static = ()> = StorageValue<_, Vec<Vec<u8>>, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// Active Escrow addresses
-	pub type EscrowAddresses<T: Config> =
+	pub type EscrowAddresses<T: Config<I>, I: 'static = ()> =
 		StorageValue<_, BTreeSet<<T as frame_system::Config>::AccountId>, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// Consensus heights
 	/// Stored as a tuple of (revision_number, revision_height)
-	pub type ConsensusHeights<T: Config> = StorageMap<
+	pub type ConsensusHeights<T: Config<I>, I: 'static = ()> = StorageMap<
 		_,
 		Blake2_128Concat,
 		Vec<u8>,
@@ -423,35 +450,131 @@ pub mod pallet {
 		ValueQuery,
 	>;
 
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// client_id => heights of the consensus states stored for the client
+	pub type ConsensusStateHeights<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, BTreeSet<Height>, ValueQuery>;
+
+	#[pallet::storage]
+	/// client_id => maximum number of consensus states kept for the client, defaults to
+	/// `DEFAULT_CONSENSUS_STATES_LIMIT`
+	pub type ConsensusStatesLimit<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, u32, OptionQuery>;
+
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// SendPackets info
-	pub type SendPackets<T: Config> =
+	pub type SendPackets<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<u8>, OptionQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// RecvPackets info
-	pub type RecvPackets<T: Config> =
+	pub type RecvPackets<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<u8>, OptionQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// Acks info
-	pub type Acks<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<u8>, OptionQuery>;
+	pub type Acks<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<u8>, OptionQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// Pending send packet sequences. Used in `packet_cleanup` procedure.
-	pub type PendingSendPacketSeqs<T: Config> =
+	pub type PendingSendPacketSeqs<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Blake2_128Concat, (Vec<u8>, Vec<u8>), (BTreeSet<u64>, u64), ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// Pending recv packet sequences. Used in `packet_cleanup` procedure.
-	pub type PendingRecvPacketSeqs<T: Config> =
+	pub type PendingRecvPacketSeqs<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Blake2_128Concat, (Vec<u8>, Vec<u8>), (BTreeSet<u64>, u64), ValueQuery>;
 
+	#[pallet::storage]
+	/// Minimum number of blocks between two unsigned updates of the same client.
+	/// Unsigned client updates are rejected while this isn't set.
+	pub type UnsignedClientUpdateInterval<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, T::BlockNumber, OptionQuery>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// client_id => block number of the last unsigned update of the client
+	pub type LastUnsignedClientUpdate<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, T::BlockNumber, OptionQuery>;
+
+	#[pallet::storage]
+	/// channel, sequence => transfer forwarded by a memo in the packet sent with the sequence on
+	/// the channel, retried or refunded once the packet times out or fails
+	pub type ForwardedTransfers<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		Blake2_128Concat,
+		u64,
+		crate::ics20::ForwardedTransferOf<T, I>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// port_id => module_id of the module of the runtime's router the port is bound to
+	pub type PortBindings<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<u8>, OptionQuery>;
+
+	#[pallet::storage]
+	/// channel, asset_id => limits of the transfers of the asset over the channel
+	pub type TransferLimits<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		Twox64Concat,
+		T::AssetId,
+		crate::ics20::rate_limit::TransferLimitOf<T, I>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// channel, asset_id => amounts of the asset transferred over the channel during the current
+	/// window of its limits
+	pub type TransferFlows<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		Twox64Concat,
+		T::AssetId,
+		crate::ics20::rate_limit::TransferFlowOf<T, I>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// Number of blocks the receipts of the received packets are kept for, before they're pruned
+	/// once their packets have timed out. They're kept forever while this isn't set.
+	/// Acknowledgements are never pruned.
+	pub type PacketRetentionPeriod<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, T::BlockNumber, OptionQuery>;
+
+	#[pallet::storage]
+	/// block number => packets that can time out received at the block, or whose pruning was
+	/// postponed to it
+	pub type RetainedPackets<T: Config<I>, I: 'static = ()> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Vec<crate::retention::RetainedPacket>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	/// Position the pending storage migration resumes from in the next block, encoded by the
+	/// migration
+	pub type MigrationCursor<T: Config<I>, I: 'static = ()> = StorageValue<_, Vec<u8>, OptionQuery>;
+
+	#[pallet::storage]
+	/// Block number of the next packets of `RetainedPackets` to prune
+	pub type RetentionCursor<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, T::BlockNumber, ValueQuery>;
+
 	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 	pub struct AssetConfig<AssetId> {
 		pub id: AssetId,
@@ -459,31 +582,33 @@ pub mod pallet {
 	}
 
 	#[pallet::genesis_config]
-	pub struct GenesisConfig<T: Config> {
+	pub struct GenesisConfig<T: Config<I>, I: 'static = ()> {
 		/// This should contain the native currency's asset_id and denom.
 		pub assets: Vec<AssetConfig<T::AssetId>>,
+		#[serde(skip)]
+		pub _phantom: PhantomData<I>,
 	}
 
 	#[cfg(feature = "std")]
-	impl<T: Config> Default for GenesisConfig<T> {
+	impl<T: Config<I>, I: 'static> Default for GenesisConfig<T, I> {
 		fn default() -> Self {
-			Self { assets: Default::default() }
+			Self { assets: Default::default(), _phantom: Default::default() }
 		}
 	}
 
 	#[pallet::genesis_build]
-	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+	impl<T: Config<I>, I: 'static> GenesisBuild<T, I> for GenesisConfig<T, I> {
 		fn build(&self) {
 			for AssetConfig { id, denom } in &self.assets {
-				IbcDenoms::<T>::insert(denom.clone(), id);
-				IbcAssetIds::<T>::insert(id, denom);
+				IbcDenoms::<T, I>::insert(denom.clone(), id);
+				IbcAssetIds::<T, I>::insert(id, denom);
 			}
 		}
 	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub (super) fn deposit_event)]
-	pub enum Event<T: Config> {
+	pub enum Event<T: Config<I>, I: 'static = ()> {
 		/// Events emitted by the ibc subsystem
 		Events {
 			events: Vec<Result<events::IbcEvent, errors::IbcError>>,
@@ -566,6 +691,22 @@ pub mod pallet {
 			height: u64,
 			revision_number: u64,
 		},
+		/// Client has been unfrozen
+		ClientUnfrozen {
+			client_id: Vec<u8>,
+		},
+		/// The state of a frozen or expired client has been replaced by the one of the substitute
+		/// client, at `height`
+		ClientRecovered {
+			subject_client_id: Vec<u8>,
+			substitute_client_id: Vec<u8>,
+			height: Height,
+		},
+		/// A port has been bound to a module of the runtime
+		PortBound {
+			port_id: Vec<u8>,
+			module_id: Vec<u8>,
+		},
 		/// Asset Admin Account Updated
 		AssetAdminUpdated {
 			admin_account: <T as frame_system::Config>::AccountId,
@@ -604,6 +745,21 @@ pub mod pallet {
 			client_id: String,
 			height: Height,
 		},
+		/// The minimum interval between unsigned client updates has been set, `None` disables them
+		UnsignedClientUpdateIntervalSet {
+			interval: Option<T::BlockNumber>,
+		},
+		/// The asset id of a denomination has been set, `None` restores the derived asset id
+		DenomAssetIdSet {
+			denom: Vec<u8>,
+			asset_id: Option<T::AssetId>,
+		},
+		/// The maximum number of consensus states kept for a client has been set, `None` restores
+		/// the default limit
+		ConsensusStatesLimitSet {
+			client_id: Vec<u8>,
+			limit: Option<u32>,
+		},
 		ExecuteMemoStarted {
 			account_id: T::AccountId,
 			memo: Option<String>,
@@ -643,11 +799,59 @@ pub mod pallet {
 			asset_id: T::AssetId,
 			para_id: Option<u32>,
 		},
+		/// The memo of a received transfer failed, the transfer was reverted and an error
+		/// acknowledgement written
+		ExecuteMemoFailed {
+			memo: String,
+			error: String,
+		},
+		/// A transfer forwarded by a memo timed out and was sent again
+		ForwardedTransferRetried {
+			from: T::AccountId,
+			channel: u64,
+			sequence: u64,
+			retries: u64,
+		},
+		/// A transfer forwarded by a memo failed or timed out, its tokens were sent back to the
+		/// sender of the packet they were received in
+		ForwardedTransferRefunded {
+			from: T::AccountId,
+			to: Vec<u8>,
+			asset_id: T::AssetId,
+			amount: T::Balance,
+			channel: u64,
+		},
+		/// The refund of a forwarded transfer couldn't be sent, its tokens are kept by the
+		/// receiver of the packet they were received in
+		ForwardedTransferRefundFailed {
+			from: T::AccountId,
+			asset_id: T::AssetId,
+			amount: T::Balance,
+		},
+		/// The limits of the transfers of an asset over a channel have been set, `None` removes
+		/// them
+		TransferLimitSet {
+			channel: u64,
+			asset_id: T::AssetId,
+			limit: Option<crate::ics20::rate_limit::TransferLimitOf<T, I>>,
+		},
+		/// A transfer exceeding the limits of its channel for its asset has been rejected
+		TransferRateLimited {
+			channel: u64,
+			asset_id: T::AssetId,
+			amount: T::Balance,
+			direction: crate::ics20::rate_limit::Direction,
+		},
+		/// The number of blocks the receipts of the received packets are kept for has been set,
+		/// `None` keeps them forever
+		PacketRetentionPeriodSet {
+			period: Option<T::BlockNumber>,
+		},
 	}
 
 	/// Errors inform users that something went wrong.
 	#[pallet::error]
-	pub enum Error<T> {
+	pub enum Error<T, I = ()> {
 		/// Error processing ibc messages
 		ProcessingError,
 		/// Error decoding some type
@@ -716,6 +920,7 @@ pub mod pallet {
 		ClientFreezeFailed,
 		/// Access denied
 		AccessDenied,
+		/// The transfer exceeds the limits of its channel for its asset
 		RateLimiter,
 		/// Fee errors
 		FailedSendFeeToAccount,
@@ -726,21 +931,45 @@ pub mod pallet {
 		/// - The memo is in invalid format
 		/// - The memo contains unsupported middlewares
 		InvalidMemo,
+		/// Unsigned client updates are disabled
+		UnsignedClientUpdatesDisabled,
+		/// The client was updated with an unsigned extrinsic too recently
+		UnsignedClientUpdateTooEarly,
+		/// The asset id is already mapped to another denomination
+		AssetIdAlreadyMapped,
+		/// A client must keep at least one consensus state
+		InvalidConsensusStatesLimit,
+		/// The client isn't frozen
+		ClientNotFrozen,
+		/// The client is active, only frozen or expired clients can be recovered
+		ClientIsActive,
+		/// The substitute client is frozen or expired
+		SubstituteClientNotActive,
+		/// The substitute client isn't of the type of the subject client, or isn't ahead of it
+		InvalidSubstituteClient,
+		/// Error recovering client
+		ClientRecoveryFailed,
+		/// The port is already bound to a module
+		PortAlreadyBound,
 	}
 
 	#[pallet::hooks]
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T>
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I>
 	where
 		u32: From<<T as frame_system::Config>::BlockNumber>,
 		T: Send + Sync,
 		AccountId32: From<<T as frame_system::Config>::AccountId>,
 	{
 		fn on_idle(n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			let remaining_weight =
+				remaining_weight.saturating_sub(migrations::migrate::<T, I>(remaining_weight));
+			let remaining_weight = remaining_weight
+				.saturating_sub(Pallet::<T, I>::prune_retained_packets(n, remaining_weight));
 			if n % T::CleanUpPacketsPeriod::get() != T::BlockNumber::zero() {
 				return remaining_weight
 			}
 			log::trace!(target: "pallet_ibc", "Cleaning up packets");
-			let removed_packets_count = Pallet::<T>::packet_cleanup()
+			let removed_packets_count = Pallet::<T, I>::packet_cleanup()
 				.map_err(|(e, n)| {
 					log::warn!(target: "pallet_ibc", "Error cleaning up packets: {:?}", e);
 					n
@@ -750,20 +979,34 @@ pub mod pallet {
 		}
 
 		fn offchain_worker(_n: BlockNumberFor<T>) {}
+
+		fn on_runtime_upgrade() -> Weight {
+			migrations::migrate::<T, I>(migrations::max_upgrade_weight::<T>())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+			migrations::pre_upgrade::<T, I>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+			migrations::post_upgrade::<T, I>(state)
+		}
 	}
 
 	// Dispatch able functions allows users to interact with the pallet and invoke state changes.
 	// These functions materialize as "extrinsic", which are often compared to transactions.
 	// Dispatch able functions must be annotated with a weight and must return a DispatchResult.
 	#[pallet::call]
-	impl<T: Config> Pallet<T>
+	impl<T: Config<I>, I: 'static> Pallet<T, I>
 	where
 		T: Send + Sync,
 		AccountId32: From<<T as frame_system::Config>::AccountId>,
 		u32: From<<T as frame_system::Config>::BlockNumber>,
 	{
 		#[pallet::call_index(0)]
-		#[pallet::weight(crate::weight::deliver::< T > (messages))]
+		#[pallet::weight(crate::weight::deliver::< T, I > (messages))]
 		#[frame_support::transactional]
 		pub fn deliver(origin: OriginFor<T>, messages: Vec<Any>) -> DispatchResult {
 			use ibc::core::{
@@ -773,7 +1016,7 @@ pub mod pallet {
 
 			// reserve a fixed deposit for every client and connection created
 			// so people don't spam our chain with useless clients.
-			let mut ctx = routing::Context::<T>::new();
+			let mut ctx = routing::Context::<T, I>::new();
 			let mut reserve_count = 0u128;
 			let messages = messages
 				.into_iter()
@@ -807,7 +1050,7 @@ pub mod pallet {
 
 		#[pallet::call_index(1)]
 		#[frame_support::transactional]
-		#[pallet::weight(<T as Config>::WeightInfo::transfer())]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::transfer())]
 		pub fn transfer(
 			origin: OriginFor<T>,
 			params: TransferParams<<T as frame_system::Config>::AccountId>,
@@ -817,7 +1060,7 @@ pub mod pallet {
 		) -> DispatchResult {
 			let account_id_32 = T::TransferOrigin::ensure_origin(origin)?.into();
 			let denom = T::IbcDenomToAssetIdConversion::from_asset_id_to_denom(asset_id)
-				.ok_or(Error::<T>::InvalidAssetId)?;
+				.ok_or(Error::<T, I>::InvalidAssetId)?;
 			let from = {
 				let mut hex_string = hex::encode(account_id_32.to_raw_vec());
 				hex_string.insert_str(0, "0x");
@@ -834,18 +1077,18 @@ pub mod pallet {
 					hex_string
 				},
 				MultiAddress::Raw(bytes) =>
-					String::from_utf8(bytes).map_err(|_| Error::<T>::Utf8Error)?,
+					String::from_utf8(bytes).map_err(|_| Error::<T, I>::Utf8Error)?,
 			};
 			let denom =
-				PrefixedDenom::from_str(&denom).map_err(|_| Error::<T>::PrefixedDenomParse)?;
-			let ibc_amount =
-				Amount::from_str(&format!("{amount:?}")).map_err(|_| Error::<T>::InvalidAmount)?;
+				PrefixedDenom::from_str(&denom).map_err(|_| Error::<T, I>::PrefixedDenomParse)?;
+			let ibc_amount = Amount::from_str(&format!("{amount:?}"))
+				.map_err(|_| Error::<T, I>::InvalidAmount)?;
 			let mut coin = PrefixedCoin { denom, amount: ibc_amount };
 			let source_channel = ChannelId::new(params.source_channel);
 			let source_port = PortId::transfer();
 			let (latest_height, _) =
-				Pallet::<T>::latest_height_and_timestamp(&source_port, &source_channel)
-					.map_err(|_| Error::<T>::TimestampAndHeightNotFound)?;
+				Pallet::<T, I>::latest_height_and_timestamp(&source_port, &source_channel)
+					.map_err(|_| Error::<T, I>::TimestampAndHeightNotFound)?;
 
 			let (timeout_height, timeout_timestamp) = match params.timeout {
 				Timeout::Offset { timestamp, height } => {
@@ -857,7 +1100,7 @@ pub mod pallet {
 							)
 						})
 						.transpose()
-						.map_err(|_| Error::<T>::InvalidTimestamp)?
+						.map_err(|_| Error::<T, I>::InvalidTimestamp)?
 						.unwrap_or_default();
 					let height = height.map(|offset| latest_height.add(offset)).unwrap_or_default();
 					(height, timestamp)
@@ -866,7 +1109,7 @@ pub mod pallet {
 					let timestamp = timestamp
 						.map(Timestamp::from_nanoseconds)
 						.transpose()
-						.map_err(|_| Error::<T>::InvalidTimestamp)?
+						.map_err(|_| Error::<T, I>::InvalidTimestamp)?
 						.unwrap_or_default();
 					let height = height
 						.map(|revision_height| {
@@ -878,31 +1121,31 @@ pub mod pallet {
 			};
 
 			if timeout_height.is_zero() && timeout_timestamp.nanoseconds() == 0 {
-				return Err(Error::<T>::InvalidTimestamp.into())
+				return Err(Error::<T, I>::InvalidTimestamp.into())
 			}
 
-			let mut ctx = Context::<T>::default();
+			let mut ctx = Context::<T, I>::default();
 			let channel_end = ctx
 				.channel_end(&(PortId::transfer(), source_channel))
-				.map_err(|_| Error::<T>::ChannelNotFound)?;
+				.map_err(|_| Error::<T, I>::ChannelNotFound)?;
 
 			let destination_channel =
-				channel_end.counterparty().channel_id.ok_or(Error::<T>::ChannelNotFound)?;
+				channel_end.counterparty().channel_id.ok_or(Error::<T, I>::ChannelNotFound)?;
 
-			let is_feeless_channel_ids = FeeLessChannelIds::<T>::contains_key((
+			let is_feeless_channel_ids = FeeLessChannelIds::<T, I>::contains_key((
 				source_channel.sequence(),
 				destination_channel.sequence(),
 			));
 
 			if !is_feeless_channel_ids {
-				let percent = ServiceChargeOut::<T>::get().unwrap_or(T::ServiceChargeOut::get());
+				let percent = ServiceChargeOut::<T, I>::get().unwrap_or(T::ServiceChargeOut::get());
 				// Now we proceed to send the service fee from the receiver's account to the pallet
 				// FeeAccount
 				let fee_account = T::FeeAccount::get();
 
 				let mut fee_coin = coin.clone();
 				let asset_id =
-					<T as crate::Config>::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
+					<T as crate::Config<I>>::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
 						&fee_coin.denom.to_string(),
 					);
 				let amt = coin.amount.as_u256().low_u128();
@@ -929,38 +1172,37 @@ pub mod pallet {
 				fee = fee.min(amt);
 				fee_coin.amount = U256::from(fee).into();
 
-				let signer_from = Signer::from_str(&from).map_err(|_| Error::<T>::Utf8Error)?;
-				let account_id_from = <T as Config>::AccountIdConversion::try_from(signer_from)
-					.map_err(|_| Error::<T>::OriginAddress)?;
+				let signer_from = Signer::from_str(&from).map_err(|_| Error::<T, I>::Utf8Error)?;
+				let account_id_from = <T as Config<I>>::AccountIdConversion::try_from(signer_from)
+					.map_err(|_| Error::<T, I>::OriginAddress)?;
 
 				ctx.send_coins(&account_id_from, &fee_account, &fee_coin).map_err(|e| {
 					log::debug!(target: "pallet_ibc", "[transfer]: error: {:?}", &e);
-					Error::<T>::FailedSendFeeToAccount
+					Error::<T, I>::FailedSendFeeToAccount
 				})?;
 
 				// We modify the packet data to remove the fee so any other middleware has access to
 				// the correct amount deposited in the receiver's account
 				coin.amount = (coin.amount.as_u256() - U256::from(fee)).into();
-				//found sequence that will used in Pallet::<T>::send_transfer function.
+				//found sequence that will used in Pallet::<T, I>::send_transfer function.
 				let sequence = ctx
 					.get_next_sequence_send(&(source_port.clone(), source_channel))
-					.map_err(|_| Error::<T>::ChannelNotFound)?;
+					.map_err(|_| Error::<T, I>::ChannelNotFound)?;
 				//use this sequence as a key in storage map where sequence is key and fee is value
 				let sequence: u64 = sequence.into();
 				//we need this data in storage map because on_timeout_packet and
 				// on_acknowledgement_packet use this data to refund fee in case of falure or clean
 				// un in case of on_acknowledgement_packet success.
-				SequenceFee::<T>::insert(sequence, fee);
-				Self::deposit_event(Event::<T>::ChargingFeeOnTransferInitiated {
+				SequenceFee::<T, I>::insert(sequence, fee);
+				Self::deposit_event(Event::<T, I>::ChargingFeeOnTransferInitiated {
 					sequence,
 					from: from.clone().into(),
 					to: to.clone().into(),
 					amount: fee.into(),
 					is_flat_fee,
-					local_asset_id: T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
+					local_asset_id: T::IbcDenomToAssetIdConversion::lookup_asset_id(
 						&coin.denom.to_string(),
-					)
-					.ok(),
+					),
 					ibc_denom: coin.denom.to_string().as_bytes().to_vec(),
 					source_channel: source_channel.to_string().as_bytes().to_vec(),
 					destination_channel: destination_channel.to_string().as_bytes().to_vec(),
@@ -971,7 +1213,7 @@ pub mod pallet {
 				.map(|memo| {
 					memo.validate().map_err(|e| {
 						log::debug!(target: "pallet_ibc", "[transfer]: memo validation error: {}", e);
-						Error::<T>::InvalidMemo
+						Error::<T, I>::InvalidMemo
 					})
 				})
 				.transpose()?;
@@ -980,8 +1222,8 @@ pub mod pallet {
 				source_port,
 				source_channel,
 				token: coin.clone(),
-				sender: Signer::from_str(&from).map_err(|_| Error::<T>::Utf8Error)?,
-				receiver: Signer::from_str(&to).map_err(|_| Error::<T>::Utf8Error)?,
+				sender: Signer::from_str(&from).map_err(|_| Error::<T, I>::Utf8Error)?,
+				receiver: Signer::from_str(&to).map_err(|_| Error::<T, I>::Utf8Error)?,
 				timeout_height,
 				timeout_timestamp,
 				memo: memo.map(|memo| memo.to_string()).unwrap_or_default(),
@@ -997,11 +1239,11 @@ pub mod pallet {
 				// Store escrow address
 				let escrow_address =
 					get_channel_escrow_address(&msg.source_port, msg.source_channel)
-						.map_err(|_| Error::<T>::ChannelEscrowAddress)?;
+						.map_err(|_| Error::<T, I>::ChannelEscrowAddress)?;
 				let account_id = T::AccountIdConversion::try_from(escrow_address)
-					.map_err(|_| Error::<T>::ChannelEscrowAddress)?
+					.map_err(|_| Error::<T, I>::ChannelEscrowAddress)?
 					.into_account();
-				let _ = EscrowAddresses::<T>::try_mutate::<_, &'static str, _>(|addresses| {
+				let _ = EscrowAddresses::<T, I>::try_mutate::<_, &'static str, _>(|addresses| {
 					if !addresses.contains(&account_id) {
 						addresses.insert(account_id);
 						Ok(())
@@ -1011,42 +1253,43 @@ pub mod pallet {
 				});
 			}
 
-			Pallet::<T>::send_transfer(msg).map_err(|e| {
+			Pallet::<T, I>::send_transfer(msg).map_err(|e| {
 				log::warn!(target: "pallet_ibc", "[transfer]: error: {:?}", &e);
 				use ibc_primitives::Error::*;
 				match e {
-					SendPacketError { .. } => Error::<T>::TransferSend,
-					SendTransferError { .. } => Error::<T>::TransferSend,
-
-					ReceivePacketError { .. } => Error::<T>::TransferProtocol,
-					WriteAcknowledgementError { .. } => Error::<T>::TransferProtocol,
-					AcknowledgementError { .. } => Error::<T>::TransferProtocol,
-					TimeoutError { .. } => Error::<T>::TransferProtocol,
-
-					TimestampOrHeightNotFound { .. } => Error::<T>::TransferInternals,
-					ChannelOrPortError { .. } => Error::<T>::TransferInternals,
-					ClientStateError { .. } => Error::<T>::TransferInternals,
-					ConnectionIdError { .. } => Error::<T>::TransferInternals,
-					ClientIdError { .. } => Error::<T>::TransferInternals,
-					BindPortError { .. } => Error::<T>::TransferInternals,
-					ChannelInitError { .. } => Error::<T>::TransferInternals,
-					ChannelCloseError { .. } => Error::<T>::TransferInternals,
-
-					DecodingError { .. } => Error::<T>::TransferSerde,
-					ErrorDecodingPrefix => Error::<T>::TransferSerde,
-
-					Other { .. } => Error::<T>::TransferOther,
+					SendPacketError { .. } => Error::<T, I>::TransferSend,
+					SendTransferError { .. } => Error::<T, I>::TransferSend,
+
+					ReceivePacketError { .. } => Error::<T, I>::TransferProtocol,
+					WriteAcknowledgementError { .. } => Error::<T, I>::TransferProtocol,
+					AcknowledgementError { .. } => Error::<T, I>::TransferProtocol,
+					TimeoutError { .. } => Error::<T, I>::TransferProtocol,
+
+					TimestampOrHeightNotFound { .. } => Error::<T, I>::TransferInternals,
+					ChannelOrPortError { .. } => Error::<T, I>::TransferInternals,
+					ClientStateError { .. } => Error::<T, I>::TransferInternals,
+					ConnectionIdError { .. } => Error::<T, I>::TransferInternals,
+					ClientIdError { .. } => Error::<T, I>::TransferInternals,
+					BindPortError { .. } => Error::<T, I>::TransferInternals,
+					ChannelInitError { .. } => Error::<T, I>::TransferInternals,
+					ChannelCloseError { .. } => Error::<T, I>::TransferInternals,
+
+					RateLimited { .. } => Error::<T, I>::RateLimiter,
+
+					DecodingError { .. } => Error::<T, I>::TransferSerde,
+					ErrorDecodingPrefix => Error::<T, I>::TransferSerde,
+
+					Other { .. } => Error::<T, I>::TransferOther,
 				}
 			})?;
 
-			Self::deposit_event(Event::<T>::TokenTransferInitiated {
+			Self::deposit_event(Event::<T, I>::TokenTransferInitiated {
 				from: from.as_bytes().to_vec(),
 				to: to.as_bytes().to_vec(),
 				amount,
-				local_asset_id: T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
+				local_asset_id: T::IbcDenomToAssetIdConversion::lookup_asset_id(
 					&coin.denom.to_string(),
-				)
-				.ok(),
+				),
 				ibc_denom: coin.denom.to_string().as_bytes().to_vec(),
 				is_sender_source,
 				source_channel: source_channel.to_string().as_bytes().to_vec(),
@@ -1059,42 +1302,42 @@ pub mod pallet {
 		/// we can produce state proofs of the values to connected chains
 		/// in order to execute client upgrades.
 		#[pallet::call_index(3)]
-		#[pallet::weight(0)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::upgrade_client())]
 		pub fn upgrade_client(origin: OriginFor<T>, params: UpgradeParams) -> DispatchResult {
-			<T as Config>::AdminOrigin::ensure_origin(origin)?;
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
 			const CLIENT_STATE_UPGRADE_PATH: &[u8] = b"client-state-upgrade-path";
 			const CONSENSUS_STATE_UPGRADE_PATH: &[u8] = b"consensus-state-upgrade-path";
 
 			sp_io::storage::set(CLIENT_STATE_UPGRADE_PATH, &params.client_state);
 			sp_io::storage::set(CONSENSUS_STATE_UPGRADE_PATH, &params.consensus_state);
 
-			Self::deposit_event(Event::<T>::ClientUpgradeSet);
+			Self::deposit_event(Event::<T, I>::ClientUpgradeSet);
 
 			Ok(())
 		}
 
 		/// Freeze a client at a specific height
 		#[pallet::call_index(4)]
-		#[pallet::weight(0)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::freeze_client())]
 		pub fn freeze_client(
 			origin: OriginFor<T>,
 			client_id: Vec<u8>,
 			height: u64,
 		) -> DispatchResult {
 			use ibc::core::ics02_client::client_state::ClientState;
-			<T as Config>::FreezeOrigin::ensure_origin(origin)?;
+			<T as Config<I>>::FreezeOrigin::ensure_origin(origin)?;
 			let client_id =
-				client_id_from_bytes(client_id).map_err(|_| Error::<T>::DecodingError)?;
-			let mut ctx = routing::Context::<T>::default();
+				client_id_from_bytes(client_id).map_err(|_| Error::<T, I>::DecodingError)?;
+			let mut ctx = routing::Context::<T, I>::default();
 			let client_state =
-				ctx.client_state(&client_id).map_err(|_| Error::<T>::ClientStateNotFound)?;
+				ctx.client_state(&client_id).map_err(|_| Error::<T, I>::ClientStateNotFound)?;
 			let frozen_state = match client_state {
 				AnyClientState::Grandpa(grandpa) => {
 					let latest_height = grandpa.latest_height();
 					AnyClientState::wrap(
 						&grandpa
 							.with_frozen_height(Height::new(latest_height.revision_number, height))
-							.map_err(|_| Error::<T>::ClientFreezeFailed)?,
+							.map_err(|_| Error::<T, I>::ClientFreezeFailed)?,
 					)
 				},
 				AnyClientState::Beefy(beefy) => {
@@ -1102,17 +1345,17 @@ pub mod pallet {
 					AnyClientState::wrap(
 						&beefy
 							.with_frozen_height(Height::new(latest_height.revision_number, height))
-							.map_err(|_| Error::<T>::ClientFreezeFailed)?,
+							.map_err(|_| Error::<T, I>::ClientFreezeFailed)?,
 					)
 				},
 				AnyClientState::Tendermint(tm) => {
 					let latest_height = tm.latest_height();
 					AnyClientState::wrap(
 						&tm.with_frozen_height(Height::new(latest_height.revision_number, height))
-							.map_err(|_| Error::<T>::ClientFreezeFailed)?,
+							.map_err(|_| Error::<T, I>::ClientFreezeFailed)?,
 					)
 				},
-				AnyClientState::Wasm(_) => return Err(Error::<T>::ClientFreezeFailed.into()),
+				AnyClientState::Wasm(_) => return Err(Error::<T, I>::ClientFreezeFailed.into()),
 				#[cfg(test)]
 				AnyClientState::Mock(mut ms) => {
 					ms.frozen_height =
@@ -1120,12 +1363,12 @@ pub mod pallet {
 					AnyClientState::wrap(&ms)
 				},
 			}
-			.ok_or(Error::<T>::ClientFreezeFailed)?;
+			.ok_or(Error::<T, I>::ClientFreezeFailed)?;
 			let revision_number = frozen_state.latest_height().revision_number;
 			ctx.store_client_state(client_id.clone(), frozen_state)
-				.map_err(|_| Error::<T>::ClientFreezeFailed)?;
+				.map_err(|_| Error::<T, I>::ClientFreezeFailed)?;
 
-			Self::deposit_event(Event::<T>::ClientFrozen {
+			Self::deposit_event(Event::<T, I>::ClientFrozen {
 				client_id: client_id.as_bytes().to_vec(),
 				height,
 				revision_number,
@@ -1135,7 +1378,7 @@ pub mod pallet {
 		}
 
 		#[pallet::call_index(5)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(9, 9))]
 		#[frame_support::transactional]
 		/// Increase all IBC counters by 1. Used only in testing to ensure that
 		/// relayer uses proper proper values for source/sink chains.
@@ -1143,39 +1386,42 @@ pub mod pallet {
 			ensure_root(origin)?;
 			#[cfg(not(feature = "testing"))]
 			{
-				Err(Error::<T>::AccessDenied.into())
+				Err(Error::<T, I>::AccessDenied.into())
 			}
 			#[cfg(feature = "testing")]
 			{
-				ChannelCounter::<T>::set(ChannelCounter::<T>::get() + 1);
-				PacketCounter::<T>::set(PacketCounter::<T>::get() + 1);
-				ClientCounter::<T>::set(ClientCounter::<T>::get() + 1);
-				ConnectionCounter::<T>::set(ConnectionCounter::<T>::get() + 1);
-				AcknowledgementCounter::<T>::set(AcknowledgementCounter::<T>::get() + 1);
-				PacketReceiptCounter::<T>::set(PacketReceiptCounter::<T>::get() + 1);
+				ChannelCounter::<T, I>::set(ChannelCounter::<T, I>::get() + 1);
+				PacketCounter::<T, I>::set(PacketCounter::<T, I>::get() + 1);
+				ClientCounter::<T, I>::set(ClientCounter::<T, I>::get() + 1);
+				ConnectionCounter::<T, I>::set(ConnectionCounter::<T, I>::get() + 1);
+				AcknowledgementCounter::<T, I>::set(AcknowledgementCounter::<T, I>::get() + 1);
+				PacketReceiptCounter::<T, I>::set(PacketReceiptCounter::<T, I>::get() + 1);
 				let port_id = PortId::transfer();
-				let channel_id = ChannelId::new(ChannelCounter::<T>::get() as _);
-				NextSequenceAck::<T>::insert(
+				let channel_id = ChannelId::new(ChannelCounter::<T, I>::get() as _);
+				NextSequenceAck::<T, I>::insert(
 					port_id.clone(),
 					channel_id,
-					NextSequenceAck::<T>::get(port_id.clone(), channel_id).unwrap_or_default() + 1,
+					NextSequenceAck::<T, I>::get(port_id.clone(), channel_id).unwrap_or_default() +
+						1,
 				);
-				NextSequenceRecv::<T>::insert(
+				NextSequenceRecv::<T, I>::insert(
 					port_id.clone(),
 					channel_id,
-					NextSequenceRecv::<T>::get(port_id.clone(), channel_id).unwrap_or_default() + 1,
+					NextSequenceRecv::<T, I>::get(port_id.clone(), channel_id).unwrap_or_default() +
+						1,
 				);
-				NextSequenceSend::<T>::insert(
+				NextSequenceSend::<T, I>::insert(
 					port_id.clone(),
 					channel_id,
-					NextSequenceSend::<T>::get(port_id.clone(), channel_id).unwrap_or_default() + 1,
+					NextSequenceSend::<T, I>::get(port_id.clone(), channel_id).unwrap_or_default() +
+						1,
 				);
 				Ok(())
 			}
 		}
 
 		#[pallet::call_index(6)]
-		#[pallet::weight(0)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::add_channels_to_feeless_channel_list())]
 		#[frame_support::transactional]
 		pub fn add_channels_to_feeless_channel_list(
 			origin: OriginFor<T>,
@@ -1184,8 +1430,8 @@ pub mod pallet {
 		) -> DispatchResult {
 			ensure_root(origin)?;
 
-			FeeLessChannelIds::<T>::insert((source_channel, destination_channel), ());
-			Self::deposit_event(Event::<T>::FeeLessChannelIdsAdded {
+			FeeLessChannelIds::<T, I>::insert((source_channel, destination_channel), ());
+			Self::deposit_event(Event::<T, I>::FeeLessChannelIdsAdded {
 				source_channel,
 				destination_channel,
 			});
@@ -1194,7 +1440,7 @@ pub mod pallet {
 		}
 
 		#[pallet::call_index(7)]
-		#[pallet::weight(0)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::remove_channels_from_feeless_channel_list())]
 		#[frame_support::transactional]
 		pub fn remove_channels_from_feeless_channel_list(
 			origin: OriginFor<T>,
@@ -1203,8 +1449,8 @@ pub mod pallet {
 		) -> DispatchResult {
 			ensure_root(origin)?;
 
-			FeeLessChannelIds::<T>::remove((source_channel, destination_channel));
-			Self::deposit_event(Event::<T>::FeeLessChannelIdsRemoved {
+			FeeLessChannelIds::<T, I>::remove((source_channel, destination_channel));
+			Self::deposit_event(Event::<T, I>::FeeLessChannelIdsRemoved {
 				source_channel,
 				destination_channel,
 			});
@@ -1213,24 +1459,24 @@ pub mod pallet {
 		}
 
 		#[pallet::call_index(8)]
-		#[pallet::weight(0)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::set_child_storage())]
 		#[frame_support::transactional]
 		pub fn set_child_storage(
 			origin: OriginFor<T>,
 			key: Vec<u8>,
 			value: Vec<u8>,
 		) -> DispatchResult {
-			<T as Config>::AdminOrigin::ensure_origin(origin)?;
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
 
 			let concat_key = [T::PalletPrefix::get(), &key].concat();
 			child::put(&ChildInfo::new_default(T::PalletPrefix::get()), &concat_key, &value);
-			Self::deposit_event(Event::<T>::ChildStateUpdated);
+			Self::deposit_event(Event::<T, I>::ChildStateUpdated);
 
 			Ok(())
 		}
 
 		#[pallet::call_index(9)]
-		#[pallet::weight(0)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::substitute_client_state())]
 		#[frame_support::transactional]
 		pub fn substitute_client_state(
 			origin: OriginFor<T>,
@@ -1239,31 +1485,262 @@ pub mod pallet {
 			client_state_bytes: Vec<u8>,
 			consensus_state_bytes: Vec<u8>,
 		) -> DispatchResult {
-			<T as Config>::AdminOrigin::ensure_origin(origin)?;
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
 
-			let client_id = ClientId::from_str(&client_id).map_err(|_| Error::<T>::Other)?;
+			let client_id = ClientId::from_str(&client_id).map_err(|_| Error::<T, I>::Other)?;
 			let client_state = AnyClientState::decode_vec(&client_state_bytes[..])
-				.map_err(|_| Error::<T>::Other)?;
+				.map_err(|_| Error::<T, I>::Other)?;
 			let consensus_state = AnyConsensusState::decode_vec(&consensus_state_bytes[..])
-				.map_err(|_| Error::<T>::Other)?;
+				.map_err(|_| Error::<T, I>::Other)?;
 
-			let mut ctx = Context::<T>::new();
+			let mut ctx = Context::<T, I>::new();
 			ctx.store_client_state(client_id.clone(), client_state)
-				.map_err(|_| Error::<T>::Other)?;
+				.map_err(|_| Error::<T, I>::Other)?;
 			ctx.store_consensus_state(client_id.clone(), height, consensus_state)
-				.map_err(|_| Error::<T>::Other)?;
+				.map_err(|_| Error::<T, I>::Other)?;
 			ctx.store_update_time(client_id.clone(), height, ctx.host_timestamp())
-				.map_err(|_| Error::<T>::Other)?;
+				.map_err(|_| Error::<T, I>::Other)?;
 			ctx.store_update_height(client_id.clone(), height, ctx.host_height())
-				.map_err(|_| Error::<T>::Other)?;
+				.map_err(|_| Error::<T, I>::Other)?;
 
-			Self::deposit_event(Event::<T>::ClientStateSubstituted {
+			Self::deposit_event(Event::<T, I>::ClientStateSubstituted {
 				client_id: client_id.to_string(),
 				height,
 			});
 
 			Ok(())
 		}
+
+		/// Set the minimum number of blocks between two unsigned updates of the same client.
+		/// `None` disables unsigned client updates.
+		#[pallet::call_index(10)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::set_unsigned_client_update_interval())]
+		pub fn set_unsigned_client_update_interval(
+			origin: OriginFor<T>,
+			interval: Option<T::BlockNumber>,
+		) -> DispatchResult {
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
+			UnsignedClientUpdateInterval::<T, I>::set(interval);
+			Self::deposit_event(Event::<T, I>::UnsignedClientUpdateIntervalSet { interval });
+			Ok(())
+		}
+
+		/// Update a client with a `MsgUpdateClient` submitted as an unsigned extrinsic, so that
+		/// relayers don't pay fees for the updates required to relay packets.
+		/// The client message is verified in `validate_unsigned`, and every client can be updated
+		/// this way at most once per `UnsignedClientUpdateInterval` blocks. Failed updates aren't
+		/// counted towards the interval.
+		#[pallet::call_index(11)]
+		#[pallet::weight(crate::weight::deliver::< T, I > (core::slice::from_ref(message)))]
+		#[frame_support::transactional]
+		pub fn update_client_unsigned(origin: OriginFor<T>, message: Any) -> DispatchResult {
+			ensure_none(origin)?;
+			let client_id = Self::check_unsigned_client_update(&message)?;
+
+			let mut ctx = routing::Context::<T, I>::new();
+			let message = ibc_proto::google::protobuf::Any {
+				type_url: message.type_url,
+				value: message.value,
+			};
+			let receipt =
+				ibc::core::ics26_routing::handler::deliver(&mut ctx, message).map_err(|e| {
+					log::trace!(target: "pallet_ibc", "unsigned client update failed: {}", e);
+					Error::<T, I>::ProcessingError
+				})?;
+			let events = receipt
+				.events
+				.into_iter()
+				.map(Ok::<_, ibc::core::ics26_routing::error::Error>)
+				.collect::<Vec<_>>();
+			Self::deposit_event(events.into());
+			LastUnsignedClientUpdate::<T, I>::insert(
+				client_id.as_bytes().to_vec(),
+				frame_system::Pallet::<T>::block_number(),
+			);
+
+			Ok(())
+		}
+
+		/// Map the ibc denomination `denom` (its full trace path) to `asset_id`, overriding the
+		/// asset id derived from its hash. `None` removes the mapping, restoring the derived id.
+		#[pallet::call_index(12)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::set_denom_asset_id())]
+		pub fn set_denom_asset_id(
+			origin: OriginFor<T>,
+			denom: Vec<u8>,
+			asset_id: Option<T::AssetId>,
+		) -> DispatchResult {
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
+			match asset_id.clone() {
+				Some(asset_id) => Self::register_denom(denom.clone(), asset_id)?,
+				None => Self::unregister_denom(&denom),
+			}
+			Self::deposit_event(Event::<T, I>::DenomAssetIdSet { denom, asset_id });
+			Ok(())
+		}
+
+		/// Set the maximum number of consensus states kept for the client, the consensus states
+		/// of its lowest heights exceeding the limit being removed. `None` restores the default
+		/// limit.
+		#[pallet::call_index(13)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::set_consensus_states_limit(
+			ConsensusStateHeights::<T, I>::get(client_id).len() as u32
+		))]
+		pub fn set_consensus_states_limit(
+			origin: OriginFor<T>,
+			client_id: Vec<u8>,
+			limit: Option<u32>,
+		) -> DispatchResult {
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
+			ensure!(limit != Some(0), Error::<T, I>::InvalidConsensusStatesLimit);
+			let client_id =
+				client_id_from_bytes(client_id).map_err(|_| Error::<T, I>::DecodingError)?;
+			ConsensusStatesLimit::<T, I>::set(client_id.as_bytes().to_vec(), limit);
+			ConsensusStates::<T, I>::prune(&client_id);
+			Self::deposit_event(Event::<T, I>::ConsensusStatesLimitSet {
+				client_id: client_id.as_bytes().to_vec(),
+				limit,
+			});
+			Ok(())
+		}
+
+		/// Unfreeze a client frozen for misbehaviour
+		#[pallet::call_index(14)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::unfreeze_client())]
+		pub fn unfreeze_client(origin: OriginFor<T>, client_id: Vec<u8>) -> DispatchResult {
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
+			let client_id =
+				client_id_from_bytes(client_id).map_err(|_| Error::<T, I>::DecodingError)?;
+			Self::unfreeze_client_state(&client_id)?;
+			Self::deposit_event(Event::<T, I>::ClientUnfrozen {
+				client_id: client_id.as_bytes().to_vec(),
+			});
+			Ok(())
+		}
+
+		/// Recover the frozen or expired `subject_client_id` client with the state of the active
+		/// `substitute_client_id` client of the same chain. The consensus states of the subject
+		/// client are removed and replaced by the latest consensus state of the substitute client.
+		#[pallet::call_index(15)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::recover_client(
+			ConsensusStateHeights::<T, I>::get(subject_client_id).len() as u32
+		))]
+		#[frame_support::transactional]
+		pub fn recover_client(
+			origin: OriginFor<T>,
+			subject_client_id: Vec<u8>,
+			substitute_client_id: Vec<u8>,
+		) -> DispatchResult {
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
+			let subject = client_id_from_bytes(subject_client_id)
+				.map_err(|_| Error::<T, I>::DecodingError)?;
+			let substitute = client_id_from_bytes(substitute_client_id)
+				.map_err(|_| Error::<T, I>::DecodingError)?;
+			let height = Self::recover_client_state(&subject, &substitute)?;
+			Self::deposit_event(Event::<T, I>::ClientRecovered {
+				subject_client_id: subject.as_bytes().to_vec(),
+				substitute_client_id: substitute.as_bytes().to_vec(),
+				height,
+			});
+			Ok(())
+		}
+
+		/// Set the limits of the transfers of `asset_id` over the transfer channel `channel`,
+		/// restarting their window. `None` removes the limits.
+		#[pallet::call_index(16)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::set_transfer_limit())]
+		pub fn set_transfer_limit(
+			origin: OriginFor<T>,
+			channel: u64,
+			asset_id: T::AssetId,
+			limit: Option<crate::ics20::rate_limit::TransferLimitOf<T, I>>,
+		) -> DispatchResult {
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
+			ensure!(
+				limit.as_ref().map_or(true, |limit| !limit.period.is_zero()),
+				Error::<T, I>::InvalidParams
+			);
+			match limit.clone() {
+				Some(limit) => TransferLimits::<T, I>::insert(channel, &asset_id, limit),
+				None => TransferLimits::<T, I>::remove(channel, &asset_id),
+			}
+			TransferFlows::<T, I>::remove(channel, &asset_id);
+			Self::deposit_event(Event::<T, I>::TransferLimitSet { channel, asset_id, limit });
+			Ok(())
+		}
+
+		/// Set the number of blocks the receipts of the received packets are kept for, after which
+		/// the receipts of the timed out packets are removed in `on_idle`. `None` keeps them
+		/// forever. The period must be well above the time it takes to relay an acknowledgement,
+		/// see the `retention` module.
+		#[pallet::call_index(17)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::set_packet_retention_period())]
+		pub fn set_packet_retention_period(
+			origin: OriginFor<T>,
+			period: Option<T::BlockNumber>,
+		) -> DispatchResult {
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
+			ensure!(period.map_or(true, |period| !period.is_zero()), Error::<T, I>::InvalidParams);
+			if PacketRetentionPeriod::<T, I>::get().is_none() &&
+				RetainedPackets::<T, I>::iter_keys().next().is_none()
+			{
+				// no packets were retained before the current block
+				RetentionCursor::<T, I>::put(frame_system::Pallet::<T>::block_number());
+			}
+			PacketRetentionPeriod::<T, I>::set(period);
+			Self::deposit_event(Event::<T, I>::PacketRetentionPeriodSet { period });
+			Ok(())
+		}
+
+		/// Bind `port_id` to the module of the runtime's router with the id `module_id`, so that
+		/// the channels and packets of the port are routed to it. A port can only be bound once,
+		/// and only to a module the router has a route to.
+		#[pallet::call_index(18)]
+		#[pallet::weight(<T as Config<I>>::WeightInfo::bind_port())]
+		pub fn bind_port(
+			origin: OriginFor<T>,
+			port_id: Vec<u8>,
+			module_id: Vec<u8>,
+		) -> DispatchResult {
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
+			let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T, I>::InvalidPortId)?;
+			let module_id = String::from_utf8(module_id)
+				.ok()
+				.and_then(|module_id| ModuleId::from_str(&module_id).ok())
+				.ok_or(Error::<T, I>::InvalidParams)?;
+			Self::bind_port_to_module(port_id, module_id)?;
+			Ok(())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config<I>, I: 'static> ValidateUnsigned for Pallet<T, I>
+	where
+		T: Send + Sync,
+		AccountId32: From<<T as frame_system::Config>::AccountId>,
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+	{
+		type Call = Call<T, I>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			let Call::update_client_unsigned { message } = call else {
+				return InvalidTransaction::Call.into()
+			};
+			let client_id = Self::check_unsigned_client_update(message).map_err(|e| match e {
+				Error::<T, I>::UnsignedClientUpdateTooEarly => InvalidTransaction::Stale,
+				_ => InvalidTransaction::Call,
+			})?;
+			Self::verify_client_update(message).map_err(|_| InvalidTransaction::BadProof)?;
+
+			let longevity = UnsignedClientUpdateInterval::<T, I>::get()
+				.map(|interval| u32::from(interval).max(1) as TransactionLongevity)
+				.unwrap_or(1);
+			ValidTransaction::with_tag_prefix("IbcUnsignedClientUpdate")
+				.and_provides(client_id.as_bytes().to_vec())
+				.longevity(longevity)
+				.propagate(true)
+				.build()
+		}
 	}
 }
 
@@ -1277,7 +1754,7 @@ pub struct IbcAssets<AssetId> {
 	pub next_id: Option<AssetId>,
 }
 
-pub trait DenomToAssetId<T: Config> {
+pub trait DenomToAssetId<T: Config<I>, I: 'static = ()> {
 	type Error: Debug;
 
 	/// Get the equivalent asset id for this ibc denom
@@ -1286,6 +1763,12 @@ pub trait DenomToAssetId<T: Config> {
 	/// if an asset does not exist for this denom
 	fn from_denom_to_asset_id(denom: &str) -> Result<T::AssetId, Self::Error>;
 
+	/// Get the asset id of this ibc denom if its asset exists, without creating it.
+	/// Defaults to `from_denom_to_asset_id` for the implementations that don't distinguish them.
+	fn lookup_asset_id(denom: &str) -> Option<T::AssetId> {
+		Self::from_denom_to_asset_id(denom).ok()
+	}
+
 	/// Return full denom for given asset id
 	fn from_asset_id_to_denom(id: T::AssetId) -> Option<String>;
 
@@ -364,6 +364,16 @@ impl AnyClientMessage {
 
 	pub fn wasm(inner: Self) -> Result<Self, tendermint_proto::Error> {
 		let maybe_height = inner.maybe_header_height();
+		Self::wasm_at(inner, maybe_height)
+	}
+
+	/// Wraps `inner` into a `08-wasm` header at `maybe_height`, or into a `08-wasm` misbehaviour
+	/// if it's `None`. Used for the headers whose height can't be read from them, like the `Beefy`
+	/// ones, which don't tell the parachain they carry the headers of.
+	pub fn wasm_at(
+		inner: Self,
+		maybe_height: Option<Height>,
+	) -> Result<Self, tendermint_proto::Error> {
 		Ok(match maybe_height {
 			Some(height) => Self::Wasm(ics08_wasm::client_message::ClientMessage::Header(
 				ics08_wasm::client_message::Header {
@@ -456,6 +466,11 @@ impl TryFrom<Any> for AnyClientMessage {
 					ics08_wasm::client_message::Misbehaviour::decode_vec(&value.value)
 						.map_err(ics02_client::error::Error::decode_raw_header)?,
 				))),
+			#[cfg(test)]
+			MOCK_CLIENT_MESSAGE_TYPE_URL => Ok(Self::Mock(ibc::mock::header::MockClientMessage::Header(
+				ibc::mock::header::MockHeader::decode_vec(&value.value)
+					.map_err(ics02_client::error::Error::decode_raw_header)?,
+			))),
 			_ => Err(ics02_client::error::Error::unknown_consensus_state_type(value.type_url)),
 		}
 	}
@@ -494,7 +509,12 @@ impl From<AnyClientMessage> for Any {
 			},
 
 			#[cfg(test)]
-			AnyClientMessage::Mock(_msg) => panic!("MockHeader can't be serialized"),
+			AnyClientMessage::Mock(ibc::mock::header::MockClientMessage::Header(h)) => Any {
+				type_url: MOCK_CLIENT_MESSAGE_TYPE_URL.to_string(),
+				value: h.encode_vec().expect("encode_vec failed"),
+			},
+			#[cfg(test)]
+			AnyClientMessage::Mock(_msg) => panic!("MockMisbehaviour can't be serialized"),
 		}
 	}
 }
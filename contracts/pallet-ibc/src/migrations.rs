@@ -0,0 +1,303 @@
+//! Migrations of the storage of the pallet and of the layout of its child trie.
+//!
+//! The storage version of the pallet is stored on chain. [`migrate`] runs the
+//! [`VersionedMigration`]s of the versions above the on-chain version in order, bumping the
+//! on-chain version after each of them. The migrations are executed in steps bounded by a weight:
+//! `on_runtime_upgrade` runs them within [`max_upgrade_weight`], and the migrations that didn't
+//! complete resume from their [`MigrationCursor`] in `on_idle`, within the remaining weight of the
+//! next blocks. The chains built at the current [`STORAGE_VERSION`] have nothing to migrate. With
+//! the `try-runtime` feature, the state checked by each migration is collected before the upgrade,
+//! and validated after it once the remaining steps were executed, each of them within
+//! [`max_upgrade_weight`].
+
+use crate::{
+	ics23::consensus_states::ConsensusStates, ClientUpdateHeight, Config, ConsensusStateHeights,
+	MigrationCursor, Pallet,
+};
+#[cfg(feature = "try-runtime")]
+use codec::Encode;
+use frame_support::{
+	traits::{GetStorageVersion, StorageVersion},
+	weights::Weight,
+};
+use sp_core::Get;
+use sp_runtime::Perbill;
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+use sp_std::prelude::*;
+
+/// Current version of the storage of the pallet.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+/// Share of the maximum weight of a block the migrations may consume in `on_runtime_upgrade`.
+const UPGRADE_WEIGHT_RATIO: Perbill = Perbill::from_percent(25);
+
+/// Maximum weight of the migration steps executed by `on_runtime_upgrade`.
+pub fn max_upgrade_weight<T: frame_system::Config>() -> Weight {
+	UPGRADE_WEIGHT_RATIO * T::BlockWeights::get().max_block
+}
+
+/// Migration of the storage from the previous version to `VERSION`, executed in steps.
+pub trait VersionedMigration {
+	const VERSION: u16;
+
+	/// Migrates the storage from `cursor`, the position the previous step stopped at, within
+	/// `max_weight`. Returns the weight consumed and the position to resume from in the next
+	/// step, or `None` once the migration is complete.
+	fn step(cursor: Option<Vec<u8>>, max_weight: Weight) -> (Weight, Option<Vec<u8>>);
+
+	/// Returns the state to check after the migration.
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+		Ok(Vec::new())
+	}
+
+	/// Checks the migrated storage against the state returned before the migration.
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+		Ok(())
+	}
+}
+
+/// Runs the migrations of the versions above the on-chain storage version within `max_weight`,
+/// returning the weight consumed.
+pub fn migrate<T: Config<I>, I: 'static>(max_weight: Weight) -> Weight {
+	let mut consumed = T::DbWeight::get().reads(1);
+	let on_chain = Pallet::<T, I>::on_chain_storage_version();
+	let (weight, _completed) =
+		run::<T, I, v1::MigrateToV1<T, I>>(on_chain, max_weight.saturating_sub(consumed));
+	consumed.saturating_accrue(weight);
+	consumed
+}
+
+/// Runs a step of the migration `M` if it's pending, returning the weight consumed and whether
+/// the storage is at its version.
+fn run<T: Config<I>, I: 'static, M: VersionedMigration>(
+	on_chain: StorageVersion,
+	max_weight: Weight,
+) -> (Weight, bool) {
+	if on_chain >= M::VERSION {
+		return (Weight::zero(), true)
+	}
+	let overhead = T::DbWeight::get().reads_writes(1, 2);
+	if overhead.any_gt(max_weight) {
+		return (Weight::zero(), false)
+	}
+	let cursor = MigrationCursor::<T, I>::get();
+	if cursor.is_none() {
+		log::info!(target: "pallet_ibc", "Migrating the storage from {on_chain:?} to v{}", M::VERSION);
+	}
+	let (weight, cursor) = M::step(cursor, max_weight.saturating_sub(overhead));
+	let completed = cursor.is_none();
+	match cursor {
+		Some(cursor) => MigrationCursor::<T, I>::put(cursor),
+		None => {
+			MigrationCursor::<T, I>::kill();
+			StorageVersion::new(M::VERSION).put::<Pallet<T, I>>();
+			log::info!(target: "pallet_ibc", "Migrated the storage to v{}", M::VERSION);
+		},
+	}
+	(weight.saturating_add(overhead), completed)
+}
+
+/// Collects the states checked by the pending migrations, along with their versions.
+#[cfg(feature = "try-runtime")]
+pub fn pre_upgrade<T: Config<I>, I: 'static>() -> Result<Vec<u8>, TryRuntimeError> {
+	let on_chain = Pallet::<T, I>::on_chain_storage_version();
+	let mut states = Vec::<(u16, Vec<u8>)>::new();
+	if on_chain < v1::MigrateToV1::<T, I>::VERSION {
+		states.push((v1::MigrateToV1::<T, I>::VERSION, v1::MigrateToV1::<T, I>::pre_upgrade()?));
+	}
+	Ok(states.encode())
+}
+
+/// Executes the remaining steps of the migrations as `on_idle` would, checking that each of them
+/// stays within [`max_upgrade_weight`] and makes progress, then checks that the storage was
+/// migrated to the current version, and the states of the migrations.
+#[cfg(feature = "try-runtime")]
+pub fn post_upgrade<T: Config<I>, I: 'static>(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+	use codec::Decode;
+
+	let max_weight = max_upgrade_weight::<T>();
+	let mut steps = 0u32;
+	while Pallet::<T, I>::on_chain_storage_version() != STORAGE_VERSION {
+		let (version, cursor) =
+			(Pallet::<T, I>::on_chain_storage_version(), MigrationCursor::<T, I>::get());
+		if migrate::<T, I>(max_weight).any_gt(max_weight) {
+			return Err("A migration step exceeded its maximum weight".into())
+		}
+		if Pallet::<T, I>::on_chain_storage_version() == version &&
+			MigrationCursor::<T, I>::get() == cursor
+		{
+			return Err("A migration step made no progress".into())
+		}
+		steps += 1;
+	}
+	log::info!(target: "pallet_ibc", "The migrations completed after {steps} more steps");
+
+	let states = Vec::<(u16, Vec<u8>)>::decode(&mut state.as_slice())
+		.map_err(|_| "Invalid pre-upgrade state")?;
+	for (version, state) in states {
+		match version {
+			1 => v1::MigrateToV1::<T, I>::post_upgrade(state)?,
+			_ => return Err("Unknown migration".into()),
+		}
+	}
+	Ok(())
+}
+
+/// Version 1 records the heights of the consensus states of each client, so that they are pruned
+/// above the limit of the client.
+pub mod v1 {
+	use super::*;
+	use alloc::string::String;
+	use codec::{Decode, Encode};
+	use core::{marker::PhantomData, str::FromStr};
+	use ibc::{core::ics24_host::identifier::ClientId, Height};
+	use tendermint_proto::Protobuf;
+
+	/// Position of the migration, as the raw storage key of the last entry processed by its
+	/// current phase.
+	#[derive(Encode, Decode)]
+	enum Cursor {
+		/// Recording the heights of the consensus states from `ClientUpdateHeight`
+		Record(Option<Vec<u8>>),
+		/// Pruning the consensus states of the clients of `ConsensusStateHeights`
+		Prune(Option<Vec<u8>>),
+	}
+
+	/// Records the heights of the consensus states stored before version 1 in
+	/// `ConsensusStateHeights`, from the heights their processing was recorded at, then prunes
+	/// the consensus states of the clients above their limit.
+	pub struct MigrateToV1<T, I = ()>(PhantomData<(T, I)>);
+
+	impl<T: Config<I>, I: 'static> MigrateToV1<T, I> {
+		/// Records the heights of the consensus states from the entry after `last`, returning
+		/// the last entry processed if the weight ran out before the end.
+		fn record(last: Option<Vec<u8>>, max_weight: Weight, consumed: &mut Weight) -> Cursor {
+			let entry_weight = T::DbWeight::get().reads_writes(2, 1);
+			let mut entries = match last.clone() {
+				Some(last) => ClientUpdateHeight::<T, I>::iter_from(last),
+				None => ClientUpdateHeight::<T, I>::iter(),
+			};
+			let mut last = last;
+			loop {
+				if consumed.saturating_add(entry_weight).any_gt(max_weight) {
+					return Cursor::Record(last)
+				}
+				let Some((client_id, encoded_height, _)) = entries.next() else {
+					return Cursor::Prune(None)
+				};
+				consumed.saturating_accrue(entry_weight);
+				last =
+					Some(ClientUpdateHeight::<T, I>::hashed_key_for(&client_id, &encoded_height));
+				let client = String::from_utf8(client_id.clone())
+					.ok()
+					.and_then(|client_id| ClientId::from_str(&client_id).ok());
+				let (Some(client), Ok(height)) = (client, Height::decode_vec(&encoded_height))
+				else {
+					continue
+				};
+				if ConsensusStates::<T, I>::get(client, height).is_some() {
+					ConsensusStateHeights::<T, I>::mutate(client_id, |heights| {
+						heights.insert(height);
+					});
+				}
+			}
+		}
+
+		/// Prunes the consensus states of the clients from the one after `last`, returning the
+		/// last client pruned entirely if the weight ran out before the end.
+		fn prune(
+			last: Option<Vec<u8>>,
+			max_weight: Weight,
+			consumed: &mut Weight,
+		) -> Option<Cursor> {
+			let client_weight = T::DbWeight::get().reads_writes(2, 1);
+			// the consensus state, its processed time and height, and its height are removed
+			let state_weight = T::DbWeight::get().writes(4);
+			let mut clients = match last.clone() {
+				Some(last) => ConsensusStateHeights::<T, I>::iter_keys_from(last),
+				None => ConsensusStateHeights::<T, I>::iter_keys(),
+			};
+			let mut last = last;
+			loop {
+				let remaining = max_weight.saturating_sub(*consumed);
+				if client_weight.any_gt(remaining) {
+					return Some(Cursor::Prune(last))
+				}
+				let Some(client_id) = clients.next() else { return None };
+				consumed.saturating_accrue(client_weight);
+				let Ok(client) = ClientId::from_str(&String::from_utf8_lossy(&client_id)) else {
+					last = Some(ConsensusStateHeights::<T, I>::hashed_key_for(&client_id));
+					continue
+				};
+				let max_states = remaining
+					.saturating_sub(client_weight)
+					.ref_time()
+					.checked_div(state_weight.ref_time())
+					.unwrap_or(u64::MAX)
+					.min(u32::MAX as u64) as u32;
+				let removed = ConsensusStates::<T, I>::prune_up_to(&client, max_states);
+				consumed.saturating_accrue(state_weight.saturating_mul(removed as u64));
+				if ConsensusStateHeights::<T, I>::get(&client_id).len() >
+					ConsensusStates::<T, I>::limit(&client) as usize
+				{
+					// the client is pruned further in the next step
+					return Some(Cursor::Prune(last))
+				}
+				last = Some(ConsensusStateHeights::<T, I>::hashed_key_for(&client_id));
+			}
+		}
+	}
+
+	impl<T: Config<I>, I: 'static> VersionedMigration for MigrateToV1<T, I> {
+		const VERSION: u16 = 1;
+
+		fn step(cursor: Option<Vec<u8>>, max_weight: Weight) -> (Weight, Option<Vec<u8>>) {
+			let mut consumed = Weight::zero();
+			let mut cursor = cursor
+				.and_then(|cursor| Cursor::decode(&mut cursor.as_slice()).ok())
+				.unwrap_or(Cursor::Record(None));
+			if let Cursor::Record(last) = cursor {
+				cursor = Self::record(last, max_weight, &mut consumed);
+				if matches!(cursor, Cursor::Record(_)) {
+					return (consumed, Some(cursor.encode()))
+				}
+			}
+			let Cursor::Prune(last) = cursor else { unreachable!("recording is complete") };
+			let cursor = Self::prune(last, max_weight, &mut consumed);
+			(consumed, cursor.map(|cursor| cursor.encode()))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let clients = ClientUpdateHeight::<T, I>::iter_keys()
+				.map(|(client_id, _)| client_id)
+				.collect::<sp_std::collections::btree_set::BTreeSet<_>>();
+			Ok(clients.into_iter().collect::<Vec<_>>().encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let clients = Vec::<Vec<u8>>::decode(&mut state.as_slice())
+				.map_err(|_| "Invalid pre-upgrade state")?;
+			for client_id in clients {
+				let Ok(client) = ClientId::from_str(&String::from_utf8_lossy(&client_id)) else {
+					continue
+				};
+				let heights = ConsensusStateHeights::<T, I>::get(&client_id);
+				if heights.len() > ConsensusStates::<T, I>::limit(&client) as usize {
+					return Err("The consensus states of a client weren't pruned".into())
+				}
+				if heights
+					.iter()
+					.any(|height| ConsensusStates::<T, I>::get(client.clone(), *height).is_none())
+				{
+					return Err("A recorded consensus state height has no consensus state".into())
+				}
+			}
+			Ok(())
+		}
+	}
+}
@@ -1,10 +1,11 @@
 use crate::{
 	self as pallet_ibc, ics20::SubstrateMultihopXcmHandlerNone, ics20_fee::FlatFeeConverter,
-	routing::ModuleRouter,
+	ics721::NftMetadata, routing::ModuleRouter,
 };
 use cumulus_primitives_core::ParaId;
 use derive_more::Display;
 use frame_support::{
+	dispatch::DispatchResult,
 	pallet_prelude::ConstU32,
 	parameter_types,
 	traits::{
@@ -14,6 +15,7 @@ use frame_support::{
 		},
 		AsEnsureOriginWithArg, ConstU64, Everything,
 	},
+	Blake2_128Concat,
 };
 use frame_system as system;
 use frame_system::EnsureSigned;
@@ -30,7 +32,7 @@ use sp_keystore::{testing::MemoryKeystore, KeystoreExt};
 use sp_runtime::{
 	generic,
 	traits::{BlakeTwo256, IdentityLookup},
-	MultiSignature, Perbill,
+	DispatchError, MultiSignature, Perbill,
 };
 use std::{
 	convert::Infallible,
@@ -50,7 +52,10 @@ pub type Balance = u128;
 pub type AccountId = <<MultiSignature as Verify>::Signer as IdentifyAccount>::AccountId;
 use super::*;
 use crate::{
-	ics20::{IbcMemoHandler, MemoData},
+	ics20::{
+		memo::{MemoHandler, MemoHooks, ReceivedTransfer},
+		IbcMemoHandler, MemoData,
+	},
 	light_clients::{AnyClientMessage, AnyConsensusState},
 };
 use ibc::mock::{client_state::MockConsensusState, header::MockClientMessage, host::MockHostBlock};
@@ -116,7 +121,7 @@ impl system::Config for Test {
 	type BaseCallFilter = Everything;
 	type BlockWeights = ();
 	type BlockLength = ();
-	type DbWeight = ();
+	type DbWeight = frame_support::weights::constants::RocksDbWeight;
 	type RuntimeOrigin = RuntimeOrigin;
 	type RuntimeCall = RuntimeCall;
 	type Index = u64;
@@ -147,6 +152,12 @@ impl pallet_ibc_ping::Config for Test {
 	type IbcHandler = Ibc;
 }
 
+impl pallet_ibc_echo::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+
+	type IbcHandler = Ibc;
+}
+
 parameter_types! {
 	pub const NativeAssetId: u128 = 1;
 	pub const StringLimit: u32 = 32;
@@ -212,6 +223,9 @@ parameter_types! {
 	pub const IbcTriePrefix : &'static [u8] = b"ibc/";
 	pub const ServiceCharge: Perbill = Perbill::from_percent(1);
 	pub const PalletId: frame_support::PalletId = frame_support::PalletId(*b"ics20fee");
+	pub const FeeEscrowPalletId: frame_support::PalletId = frame_support::PalletId(*b"ics29fee");
+	pub const IcaMaxTxWeight: frame_support::weights::Weight =
+		frame_support::weights::Weight::from_parts(500_000_000_000, 5 * 1024 * 1024);
 	pub const FlatFeeAssetId: AssetId = 130;
 	pub const FlatFeeAmount: AssetId = 10_000_000;
 	pub FeeAccount: <Test as Config>::AccountIdConversion = create_alice_key();
@@ -285,7 +299,7 @@ impl Config for Test {
 	type IbcAccountId = Self::AccountId;
 	type TransferOrigin = EnsureSigned<Self::IbcAccountId>;
 	type RelayerOrigin = EnsureSigned<Self::AccountId>;
-	type HandleMemo = IbcMemoHandler<(), Test>;
+	type HandleMemo = IbcMemoHandler<MemoHooks<(TestMemoHook,), Test>, Test>;
 	type MemoMessage = RawMemo;
 	type IsReceiveEnabled = sp_core::ConstBool<true>;
 	type IsSendEnabled = sp_core::ConstBool<true>;
@@ -314,10 +328,122 @@ impl<T: Config> FlatFeeConverter for FlatFeeConverterDummy<T> {
 		None
 	}
 }
+/// Memo hook acting on the `test` key of the memos, it fails on `fail` and remarks the receiver
+/// otherwise.
+pub struct TestMemoHook;
+impl MemoHandler<Test> for TestMemoHook {
+	fn on_recv(
+		transfer: &ReceivedTransfer<Test>,
+		memo: &serde_json::Map<String, serde_json::Value>,
+	) -> Result<(), ibc::applications::transfer::error::Error> {
+		let Some(value) = memo.get("test") else { return Ok(()) };
+		if value.as_str() == Some("fail") {
+			return Err(ibc::applications::transfer::error::Error::implementation_specific(
+				"test memo hook failed".to_string(),
+			))
+		}
+		System::deposit_event(frame_system::Event::Remarked {
+			sender: transfer.receiver.clone(),
+			hash: H256::from(sp_core::blake2_256(value.to_string().as_bytes())),
+		});
+		Ok(())
+	}
+}
+
 impl crate::ics20_fee::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type ServiceChargeIn = ServiceCharge;
 	type PalletId = PalletId;
+	type WeightInfo = ();
+}
+
+impl crate::ics29_fee::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type PalletId = FeeEscrowPalletId;
+	type MaxFeesPerPacket = ConstU32<8>;
+	type WeightInfo = ();
+}
+
+impl crate::ics27::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type AllowedCalls = Everything;
+	type MaxCalls = ConstU32<8>;
+	type MaxTxWeight = IcaMaxTxWeight;
+	type WeightInfo = ();
+}
+
+#[frame_support::storage_alias]
+pub type NftClasses = StorageMap<Nfts, Blake2_128Concat, String, (String, String)>;
+
+#[frame_support::storage_alias]
+pub type NftTokens = StorageDoubleMap<
+	Nfts,
+	Blake2_128Concat,
+	String,
+	Blake2_128Concat,
+	String,
+	(AccountId, String, String),
+>;
+
+/// Non-fungible tokens of the mock runtime, the classes mapped to their uri and data, and the
+/// tokens to their owner, uri and data.
+pub struct MockNonFungibles;
+
+impl crate::ics721::NonFungibles<AccountId> for MockNonFungibles {
+	fn owner(class_id: &str, token_id: &str) -> Option<AccountId> {
+		NftTokens::get(class_id, token_id).map(|(owner, ..)| owner)
+	}
+
+	fn class(class_id: &str) -> Option<NftMetadata> {
+		NftClasses::get(class_id).map(|(uri, data)| NftMetadata { uri, data })
+	}
+
+	fn token(class_id: &str, token_id: &str) -> Option<NftMetadata> {
+		NftTokens::get(class_id, token_id).map(|(_, uri, data)| NftMetadata { uri, data })
+	}
+
+	fn create_class(class_id: &str, metadata: NftMetadata) -> DispatchResult {
+		if NftClasses::contains_key(class_id) {
+			return Err(DispatchError::Other("The class already exists"))
+		}
+		NftClasses::insert(class_id, (metadata.uri, metadata.data));
+		Ok(())
+	}
+
+	fn mint(
+		class_id: &str,
+		token_id: &str,
+		owner: &AccountId,
+		metadata: NftMetadata,
+	) -> DispatchResult {
+		if !NftClasses::contains_key(class_id) || NftTokens::contains_key(class_id, token_id) {
+			return Err(DispatchError::Other("Unknown class or existing token"))
+		}
+		NftTokens::insert(class_id, token_id, (owner.clone(), metadata.uri, metadata.data));
+		Ok(())
+	}
+
+	fn burn(class_id: &str, token_id: &str) -> DispatchResult {
+		NftTokens::take(class_id, token_id)
+			.map(|_| ())
+			.ok_or(DispatchError::Other("Unknown token"))
+	}
+
+	fn transfer(class_id: &str, token_id: &str, to: &AccountId) -> DispatchResult {
+		NftTokens::try_mutate(class_id, token_id, |token| {
+			let (owner, ..) = token.as_mut().ok_or(DispatchError::Other("Unknown token"))?;
+			*owner = to.clone();
+			Ok(())
+		})
+	}
+}
+
+impl crate::ics721::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type NonFungibles = MockNonFungibles;
+	type MaxTokensPerTransfer = ConstU32<8>;
+	type WeightInfo = ();
 }
 
 #[derive(
@@ -423,10 +549,17 @@ where
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub struct Router {
 	ibc_ping: pallet_ibc_ping::IbcModule<Test>,
-	ics20: crate::ics20::memo::Memo<
+	ibc_echo: pallet_ibc_echo::IbcModule<Test>,
+	ics20: crate::ics29_fee::Ics29Fee<
 		Test,
-		crate::ics20_fee::Ics20ServiceCharge<Test, crate::ics20::IbcModule<Test>>,
+		crate::ics20::memo::Memo<
+			Test,
+			crate::ics20_fee::Ics20ServiceCharge<Test, crate::ics20::IbcModule<Test>>,
+		>,
 	>,
+	ica_host: crate::ics27::IcaHost<Test>,
+	ica_controller: crate::ics27::IcaController<Test>,
+	nft_transfer: crate::ics721::NftTransfer<Test>,
 }
 
 impl ModuleRouter for Router {
@@ -436,7 +569,11 @@ impl ModuleRouter for Router {
 	) -> Option<&mut dyn ibc::core::ics26_routing::context::Module> {
 		match module_id.as_ref() {
 			pallet_ibc_ping::MODULE_ID => Some(&mut self.ibc_ping),
+			pallet_ibc_echo::MODULE_ID => Some(&mut self.ibc_echo),
 			ibc::applications::transfer::MODULE_ID_STR => Some(&mut self.ics20),
+			crate::ics27::HOST_MODULE_ID => Some(&mut self.ica_host),
+			crate::ics27::CONTROLLER_MODULE_ID => Some(&mut self.ica_controller),
+			crate::ics721::MODULE_ID => Some(&mut self.nft_transfer),
 			&_ => None,
 		}
 	}
@@ -444,7 +581,12 @@ impl ModuleRouter for Router {
 	fn has_route(module_id: &ibc::core::ics26_routing::context::ModuleId) -> bool {
 		matches!(
 			module_id.as_ref(),
-			pallet_ibc_ping::MODULE_ID | ibc::applications::transfer::MODULE_ID_STR
+			pallet_ibc_ping::MODULE_ID |
+				pallet_ibc_echo::MODULE_ID |
+				ibc::applications::transfer::MODULE_ID_STR |
+				crate::ics27::HOST_MODULE_ID |
+				crate::ics27::CONTROLLER_MODULE_ID |
+				crate::ics721::MODULE_ID
 		)
 	}
 
@@ -460,7 +602,21 @@ impl ModuleRouter for Router {
 					ibc::applications::transfer::MODULE_ID_STR,
 				)
 				.ok(),
-			_ => None,
+			crate::ics721::PORT_ID => crate::ics721::lookup_module_by_port(port_id),
+			_ => crate::ics27::lookup_module_by_port(port_id),
+		}
+	}
+
+	fn weight_handler(
+		module_id: &ibc::core::ics26_routing::context::ModuleId,
+	) -> Option<Box<dyn ibc_primitives::CallbackWeight>> {
+		match module_id.as_ref() {
+			pallet_ibc_ping::MODULE_ID =>
+				Some(Box::new(pallet_ibc_ping::WeightHandler::<Test>::default())),
+			pallet_ibc_echo::MODULE_ID =>
+				Some(Box::new(pallet_ibc_echo::WeightHandler::<Test>::default())),
+			crate::ics721::MODULE_ID => crate::ics721::weight_handler::<Test>(module_id),
+			_ => crate::ics27::weight_handler::<Test>(module_id),
 		}
 	}
 }
@@ -479,7 +635,11 @@ frame_support::construct_runtime!(
 		Assets: pallet_assets,
 		PalletBalances: balances,
 		IbcPing: pallet_ibc_ping,
+		IbcEcho: pallet_ibc_echo,
 		Ics20Fee: crate::ics20_fee,
+		Ics29Fee: crate::ics29_fee,
+		Ics27: crate::ics27,
+		Ics721: crate::ics721,
 		Ibc: pallet_ibc,
 		Aura: pallet_aura,
 		Membership: pallet_membership::<Instance2>,
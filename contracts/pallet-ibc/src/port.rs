@@ -8,17 +8,26 @@ use ibc::{
 	core::{
 		ics05_port::{context::PortReader, error::Error as ICS05Error},
 		ics24_host::identifier::PortId,
-		ics26_routing::context::ModuleId,
+		ics26_routing::context::{Ics26Context, ModuleId, Router},
 	},
 };
+use sp_core::crypto::AccountId32;
 
-impl<T: Config + Sync + Send> PortReader for Context<T> {
+impl<T: Config<I> + Sync + Send, I: 'static> PortReader for Context<T, I> {
 	fn lookup_module_by_port(&self, port_id: &PortId) -> Result<ModuleId, ICS05Error> {
 		// check if the user has defined any custom modules
 		if let Some(module_id) = T::Router::lookup_module_by_port(port_id) {
 			return Ok(module_id)
 		}
 
+		// then the ports bound at runtime by the modules
+		if let Some(module_id) = PortBindings::<T, I>::get(port_id.as_bytes()) {
+			return String::from_utf8(module_id)
+				.ok()
+				.and_then(|module_id| ModuleId::from_str(&module_id).ok())
+				.ok_or_else(|| ICS05Error::module_not_found(port_id.clone()))
+		}
+
 		match port_id.as_str() {
 			TRANSFER_PORT_ID => Ok(ModuleId::from_str(TRANSFER_MODULE_ID)
 				.map_err(|_| ICS05Error::module_not_found(port_id.clone()))?),
@@ -26,3 +35,42 @@ impl<T: Config + Sync + Send> PortReader for Context<T> {
 		}
 	}
 }
+
+impl<T: Config<I> + Sync + Send, I: 'static> Pallet<T, I>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	/// Binds `port_id` to the module of the runtime's router with the id `module_id`, so that the
+	/// channels and packets of the port are routed to it. A port can only be bound once.
+	pub(crate) fn bind_port_to_module(
+		port_id: PortId,
+		module_id: ModuleId,
+	) -> Result<(), Error<T, I>> {
+		let ctx = Context::<T, I>::new();
+		if ctx.lookup_module_by_port(&port_id).is_ok() {
+			return Err(Error::<T, I>::PortAlreadyBound)
+		}
+		if !ctx.router().has_route(&module_id) {
+			return Err(Error::<T, I>::InvalidRoute)
+		}
+		let port_id = port_id.as_bytes().to_vec();
+		let module_id = module_id.to_string().into_bytes();
+		PortBindings::<T, I>::insert(&port_id, &module_id);
+		Self::deposit_event(Event::<T, I>::PortBound { port_id, module_id });
+		Ok(())
+	}
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Returns the id of the module `port_id` is routed to by the runtime's router or bound to at
+	/// runtime.
+	pub(crate) fn module_of_port(port_id: &str) -> Option<ModuleId> {
+		let port_id = PortId::from_str(port_id).ok()?;
+		T::Router::lookup_module_by_port(&port_id).or_else(|| {
+			PortBindings::<T, I>::get(port_id.as_bytes())
+				.and_then(|module_id| String::from_utf8(module_id).ok())
+				.and_then(|module_id| ModuleId::from_str(&module_id).ok())
+		})
+	}
+}
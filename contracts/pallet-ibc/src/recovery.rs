@@ -0,0 +1,137 @@
+//! Recovery of light clients by governance.
+//!
+//! A client frozen for misbehaviour can be unfrozen with [`Pallet::unfreeze_client`] once the
+//! misbehaviour has been dealt with. A client whose trusting period has elapsed can't be updated
+//! anymore, so like in ibc-go's client recovery proposals it's recovered from a substitute client
+//! of the same chain with [`Pallet::recover_client`]: the state of the subject client is replaced
+//! by the one of the substitute, and the consensus states of the subject client are replaced by the
+//! latest consensus state of the substitute. The connections and channels built on the subject
+//! client are thus kept.
+
+use crate::{
+	ics23::consensus_states::ConsensusStates, light_clients::AnyClientState, routing::Context,
+	ClientUpdateHeight, ClientUpdateTime, Config, Error, Pallet,
+};
+use ibc::{
+	core::{
+		ics02_client::{
+			client_consensus::ConsensusState,
+			client_state::{ClientState, Status},
+			context::{ClientKeeper, ClientReader},
+		},
+		ics24_host::identifier::ClientId,
+	},
+	Height,
+};
+use tendermint_proto::Protobuf;
+
+impl<T: Config<I> + Send + Sync, I: 'static> Pallet<T, I>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	/// Returns the status of the client: frozen, expired if the trusting period has elapsed since
+	/// the timestamp of its latest consensus state, or active.
+	pub fn client_status(
+		ctx: &Context<T, I>,
+		client_id: &ClientId,
+		client_state: &AnyClientState,
+	) -> Status {
+		if client_state.frozen_height().is_some() {
+			return Status::Frozen
+		}
+		let Ok(consensus_state) = ctx.consensus_state(client_id, client_state.latest_height())
+		else {
+			return Status::Expired
+		};
+		let elapsed = ctx
+			.host_timestamp()
+			.duration_since(&consensus_state.timestamp())
+			.unwrap_or_default();
+		if client_state.expired(elapsed) {
+			Status::Expired
+		} else {
+			Status::Active
+		}
+	}
+
+	/// Clears the frozen height of a frozen client.
+	pub(crate) fn unfreeze_client_state(client_id: &ClientId) -> Result<(), Error<T, I>> {
+		let mut ctx = Context::<T, I>::new();
+		let client_state =
+			ctx.client_state(client_id).map_err(|_| Error::<T, I>::ClientStateNotFound)?;
+		if client_state.frozen_height().is_none() {
+			return Err(Error::<T, I>::ClientNotFrozen)
+		}
+		let client_state = match client_state {
+			AnyClientState::Grandpa(mut grandpa) => {
+				grandpa.frozen_height = None;
+				AnyClientState::Grandpa(grandpa)
+			},
+			AnyClientState::Beefy(mut beefy) => {
+				beefy.frozen_height = None;
+				AnyClientState::Beefy(beefy)
+			},
+			AnyClientState::Tendermint(mut tm) => {
+				tm.frozen_height = None;
+				AnyClientState::Tendermint(tm)
+			},
+			AnyClientState::Wasm(_) => return Err(Error::<T, I>::ClientFreezeFailed),
+			#[cfg(test)]
+			AnyClientState::Mock(mut ms) => {
+				ms.frozen_height = None;
+				AnyClientState::Mock(ms)
+			},
+		};
+		ctx.store_client_state(client_id.clone(), client_state)
+			.map_err(|_| Error::<T, I>::ClientFreezeFailed)
+	}
+
+	/// Replaces the state of the frozen or expired `subject` client with the one of the active
+	/// `substitute` client, returning the height the subject client was recovered at.
+	pub(crate) fn recover_client_state(
+		subject: &ClientId,
+		substitute: &ClientId,
+	) -> Result<Height, Error<T, I>> {
+		if subject == substitute {
+			return Err(Error::<T, I>::InvalidSubstituteClient)
+		}
+		let mut ctx = Context::<T, I>::new();
+		let subject_state =
+			ctx.client_state(subject).map_err(|_| Error::<T, I>::ClientStateNotFound)?;
+		let substitute_state =
+			ctx.client_state(substitute).map_err(|_| Error::<T, I>::ClientStateNotFound)?;
+		if Self::client_status(&ctx, subject, &subject_state) == Status::Active {
+			return Err(Error::<T, I>::ClientIsActive)
+		}
+		if Self::client_status(&ctx, substitute, &substitute_state) != Status::Active {
+			return Err(Error::<T, I>::SubstituteClientNotActive)
+		}
+		let height = substitute_state.latest_height();
+		// the chain id may differ, e.g. if the chain was restarted with a new one
+		if subject_state.client_type() != substitute_state.client_type() ||
+			height <= subject_state.latest_height()
+		{
+			return Err(Error::<T, I>::InvalidSubstituteClient)
+		}
+		let consensus_state = ctx
+			.consensus_state(substitute, height)
+			.map_err(|_| Error::<T, I>::InvalidSubstituteClient)?;
+		let encoded_height = height.encode_vec().map_err(|_| Error::<T, I>::EncodingError)?;
+		let substitute_bytes = substitute.as_bytes().to_vec();
+		let update_time = ClientUpdateTime::<T, I>::get(&substitute_bytes, &encoded_height)
+			.ok_or(Error::<T, I>::ClientUpdateNotFound)?;
+		let update_height = ClientUpdateHeight::<T, I>::get(&substitute_bytes, &encoded_height)
+			.ok_or(Error::<T, I>::ClientUpdateNotFound)?;
+
+		// the consensus states of the subject client can't be trusted anymore
+		ConsensusStates::<T, I>::clear(subject);
+		ctx.store_client_state(subject.clone(), substitute_state)
+			.map_err(|_| Error::<T, I>::ClientRecoveryFailed)?;
+		ctx.store_consensus_state(subject.clone(), height, consensus_state)
+			.map_err(|_| Error::<T, I>::ClientRecoveryFailed)?;
+		let subject_bytes = subject.as_bytes().to_vec();
+		ClientUpdateTime::<T, I>::insert(&subject_bytes, &encoded_height, update_time);
+		ClientUpdateHeight::<T, I>::insert(&subject_bytes, &encoded_height, update_height);
+		Ok(height)
+	}
+}
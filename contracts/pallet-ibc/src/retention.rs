@@ -0,0 +1,152 @@
+//! Retention of the receipts of the received packets.
+//!
+//! The receipts of the received packets are stored in the child trie for the relayers to prove
+//! them, and accumulate over the life of the channels. Once a retention period is set with
+//! [`Pallet::set_packet_retention_period`], the received packets that can time out are recorded in
+//! [`RetainedPackets`] at the block they were received at, and their receipts are pruned in
+//! `on_idle` once the period has elapsed and the packet has timed out on this chain, since it can't
+//! be received again then. The receipts of the packets without a timeout are kept, as they're the
+//! only protection against their replay, and the pruning of the packets that haven't timed out yet
+//! is postponed by another period.
+//!
+//! The acknowledgements are never pruned: this chain can't tell whether the acknowledgement of a
+//! packet was relayed to its sender, and removing it before then would leave the packet pending on
+//! the sender forever, e.g. with the tokens of a transfer locked in escrow. Pruning a receipt has a
+//! similar trade-off: once it's removed, its absence can be proven to the sender, which would time
+//! out a packet that was received if its acknowledgement wasn't relayed yet. The retention period
+//! must therefore be well above the time it takes the relayers to deliver the acknowledgements.
+//!
+//! The commitments of the sent packets are removed on their acknowledgement or timeout, and the
+//! offchain records of the packets by the packet cleanup, so they're left as they are.
+
+use crate::{
+	ics23::receipts::PacketReceipt, routing::Context, weight::WeightInfo, Config,
+	PacketReceiptCounter, PacketRetentionPeriod, Pallet, RetainedPackets, RetentionCursor,
+};
+use alloc::string::ToString;
+use codec::{Decode, Encode};
+use frame_support::weights::Weight;
+use ibc::{
+	core::{
+		ics02_client::context::ClientReader,
+		ics04_channel::packet::{Packet, Sequence},
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	timestamp::{Expiry, Timestamp},
+	Height,
+};
+use ibc_primitives::{channel_id_from_bytes, port_id_from_bytes};
+use scale_info::TypeInfo;
+use sp_core::Get;
+use sp_runtime::{
+	traits::{One, Saturating},
+	RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+/// Received packet whose receipt is pruned after the retention period, once it has timed out.
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Clone)]
+pub struct RetainedPacket {
+	pub port_id: Vec<u8>,
+	pub channel_id: Vec<u8>,
+	pub sequence: u64,
+	pub timeout_height: Height,
+	/// Timeout timestamp of the packet in nanoseconds, zero if it has none
+	pub timeout_timestamp: u64,
+}
+
+impl RetainedPacket {
+	fn timed_out<T: Config<I> + Send + Sync, I: 'static>(&self, ctx: &Context<T, I>) -> bool
+	where
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+	{
+		(!self.timeout_height.is_zero() && self.timeout_height <= ctx.host_height()) ||
+			(self.timeout_timestamp != 0 &&
+				Timestamp::from_nanoseconds(self.timeout_timestamp)
+					.map_or(false, |timestamp| {
+						ctx.host_timestamp().check_expiry(&timestamp) == Expiry::Expired
+					}))
+	}
+
+	fn can_time_out(&self) -> bool {
+		!self.timeout_height.is_zero() || self.timeout_timestamp != 0
+	}
+
+	/// Prunes the receipt of the packet, returning the packet if it hasn't timed out yet.
+	fn prune<T: Config<I> + Send + Sync, I: 'static>(self, ctx: &Context<T, I>) -> Option<Self>
+	where
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+	{
+		let (Ok(port_id), Ok(channel_id)) = (
+			port_id_from_bytes(self.port_id.clone()),
+			channel_id_from_bytes(self.channel_id.clone()),
+		) else {
+			return None
+		};
+		let key: (PortId, ChannelId, Sequence) = (port_id, channel_id, self.sequence.into());
+		if !PacketReceipt::<T, I>::contains_key(key.clone()) || !self.can_time_out() {
+			return None
+		}
+		if !self.timed_out(ctx) {
+			return Some(self)
+		}
+		PacketReceipt::<T, I>::remove(key);
+		if let Some(val) = PacketReceiptCounter::<T, I>::get().checked_sub(1) {
+			PacketReceiptCounter::<T, I>::put(val)
+		}
+		None
+	}
+}
+
+impl<T: Config<I> + Send + Sync, I: 'static> Pallet<T, I>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	/// Records a received packet to prune its receipt after the retention period, if it's set and
+	/// the packet can time out.
+	pub(crate) fn retain_received_packet(packet: &Packet) {
+		if PacketRetentionPeriod::<T, I>::get().is_none() {
+			return
+		}
+		let packet = RetainedPacket {
+			port_id: packet.destination_port.as_bytes().to_vec(),
+			channel_id: packet.destination_channel.to_string().as_bytes().to_vec(),
+			sequence: packet.sequence.into(),
+			timeout_height: packet.timeout_height,
+			timeout_timestamp: packet.timeout_timestamp.nanoseconds(),
+		};
+		if packet.can_time_out() {
+			RetainedPackets::<T, I>::append(frame_system::Pallet::<T>::block_number(), packet);
+		}
+	}
+
+	/// Prunes the packets received at the blocks for which the retention period has elapsed at
+	/// `now`, within `max_weight`, returning the weight consumed.
+	pub fn prune_retained_packets(now: T::BlockNumber, max_weight: Weight) -> Weight {
+		let mut consumed = T::DbWeight::get().reads(1);
+		let Some(period) = PacketRetentionPeriod::<T, I>::get() else { return consumed };
+		let ctx = Context::<T, I>::default();
+		let mut block = RetentionCursor::<T, I>::get();
+		let mut postponed = Vec::new();
+		while block.saturating_add(period) <= now {
+			let count = RetainedPackets::<T, I>::decode_len(block).unwrap_or_default() as u32;
+			let weight = <T as Config<I>>::WeightInfo::prune_retained_packets(count)
+				.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+			if consumed.saturating_add(weight).any_gt(max_weight) {
+				break
+			}
+			consumed.saturating_accrue(weight);
+			postponed.extend(
+				RetainedPackets::<T, I>::take(block)
+					.into_iter()
+					.filter_map(|packet| packet.prune(&ctx)),
+			);
+			block = block.saturating_add(One::one());
+		}
+		RetentionCursor::<T, I>::put(block);
+		if !postponed.is_empty() {
+			RetainedPackets::<T, I>::mutate(now, |packets| packets.extend(postponed));
+		}
+		consumed.saturating_add(T::DbWeight::get().writes(2))
+	}
+}
@@ -1,5 +1,7 @@
 use super::*;
+use alloc::boxed::Box;
 use core::fmt::Debug;
+use frame_support::{CloneNoBound, DebugNoBound, EqNoBound, PartialEqNoBound};
 use ibc::{
 	applications::transfer::MODULE_ID_STR as IBC_TRANSFER_MODULE_ID,
 	core::{
@@ -9,36 +11,37 @@ use ibc::{
 		},
 	},
 };
+use ibc_primitives::CallbackWeight;
 use scale_info::prelude::string::ToString;
 use sp_core::crypto::AccountId32;
 
-#[derive(Clone, Eq, PartialEq, Debug)]
-pub(crate) struct Context<T: Config> {
-	pub _pd: PhantomData<T>,
-	router: IbcRouter<T>,
+#[derive(CloneNoBound, EqNoBound, PartialEqNoBound, DebugNoBound)]
+pub(crate) struct Context<T: Config<I>, I: 'static = ()> {
+	pub _pd: PhantomData<(T, I)>,
+	router: IbcRouter<T, I>,
 }
 
-impl<T: Config + Send + Sync> Default for Context<T> {
+impl<T: Config<I> + Send + Sync, I: 'static> Default for Context<T, I> {
 	fn default() -> Self {
 		Self { _pd: PhantomData, router: IbcRouter::default() }
 	}
 }
 
-impl<T: Config + Send + Sync> Context<T> {
+impl<T: Config<I> + Send + Sync, I: 'static> Context<T, I> {
 	pub fn new() -> Self {
 		Self::default()
 	}
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct IbcRouter<T: Config> {
-	ibc_transfer: ics20::IbcModule<T>,
+#[derive(CloneNoBound, DebugNoBound, EqNoBound, PartialEqNoBound)]
+pub struct IbcRouter<T: Config<I>, I: 'static = ()> {
+	ibc_transfer: ics20::IbcModule<T, I>,
 	sub_router: T::Router,
 }
 
-impl<T: Config> Default for IbcRouter<T> {
+impl<T: Config<I>, I: 'static> Default for IbcRouter<T, I> {
 	fn default() -> Self {
-		Self { ibc_transfer: ics20::IbcModule::<T>::default(), sub_router: Default::default() }
+		Self { ibc_transfer: ics20::IbcModule::<T, I>::default(), sub_router: Default::default() }
 	}
 }
 
@@ -50,9 +53,15 @@ pub trait ModuleRouter: Default + Clone + Eq + PartialEq + Debug {
 	fn has_route(module_id: &ModuleId) -> bool;
 	/// Should return the module_id associated with a given port_id
 	fn lookup_module_by_port(port_id: &PortId) -> Option<ModuleId>;
+	/// Returns the handler estimating the weight of the callbacks of the `Module` registered
+	/// against the specified `ModuleId`. The messages routed to modules without one are weighed
+	/// at `Weight::MAX`, so every routed module should provide it.
+	fn weight_handler(_module_id: &ModuleId) -> Option<Box<dyn CallbackWeight>> {
+		None
+	}
 }
 
-impl<T: Config + Send + Sync> Router for IbcRouter<T>
+impl<T: Config<I> + Send + Sync, I: 'static> Router for IbcRouter<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
@@ -79,12 +88,12 @@ where
 	}
 }
 
-impl<T: Config + Send + Sync> Ics26Context for Context<T>
+impl<T: Config<I> + Send + Sync, I: 'static> Ics26Context for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
 {
-	type Router = IbcRouter<T>;
+	type Router = IbcRouter<T, I>;
 
 	fn router(&self) -> &Self::Router {
 		&self.router
@@ -95,14 +104,14 @@ where
 	}
 }
 
-impl<T: Config + Send + Sync> ReaderContext for Context<T>
+impl<T: Config<I> + Send + Sync, I: 'static> ReaderContext for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
 {
 }
 
-impl<T: Config + Send + Sync> ModuleCallbackContext for Context<T>
+impl<T: Config<I> + Send + Sync, I: 'static> ModuleCallbackContext for Context<T, I>
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<<T as frame_system::Config>::AccountId>,
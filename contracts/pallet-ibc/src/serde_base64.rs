@@ -0,0 +1,15 @@
+//! Serde helper (de)serializing bytes as base64 strings, as the JSON encoding of protobuf bytes
+//! fields used by the ibc-go applications.
+
+use alloc::{format, string::String, vec::Vec};
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+	let encoded = subtle_encoding::base64::encode(bytes);
+	serializer.serialize_str(&String::from_utf8_lossy(&encoded))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+	let encoded = String::deserialize(deserializer)?;
+	subtle_encoding::base64::decode(encoded).map_err(|e| D::Error::custom(format!("{e:?}")))
+}
@@ -503,7 +503,7 @@ fn on_deliver_ics20_recv_packet() {
 fn on_deliver_ics20_recv_packet_incorrect_memo() {
 	let mut ext = new_test_ext();
 	ext.execute_with(|| {
-		let incorrect_memo = "Incorrect memo".to_string();
+		let incorrect_memo = r#"{"forward":{"port":"transfer"}}"#.to_string();
 		// Create  a new account
 		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
 		let reciever = AccountId32::new(pair.public().0);
@@ -590,23 +590,30 @@ fn on_deliver_ics20_recv_packet_incorrect_memo() {
 		let balance = <<Test as Config>::NativeCurrency as Currency<
 			<Test as frame_system::Config>::AccountId,
 		>>::free_balance(&reciever);
-
 		let pallet_balance =
 			<<Test as Config>::NativeCurrency as Currency<
 				<Test as frame_system::Config>::AccountId,
 			>>::free_balance(&<Test as crate::Config>::FeeAccount::get().into_account());
-		let fee = <Test as crate::ics20_fee::Config>::ServiceChargeIn::get() * amt;
-		assert_eq!(balance, amt - fee);
-		assert_eq!(pallet_balance, fee);
+		// the receipt of the transfer is reverted when its memo fails
+		assert_eq!(balance, 0);
+		assert_eq!(pallet_balance, 0);
+
+		let ctx = Context::<Test>::default();
+		let ack = ctx
+			.get_packet_acknowledgement(&(PortId::transfer(), channel_id, 1u64.into()))
+			.unwrap();
+		let success_ack = ctx.ack_commitment(Acknowledgement::from_bytes(
+			Ics20Acknowledgement::success().to_string().into_bytes(),
+		));
+		assert_ne!(ack, success_ack);
 
-		// let test_event = TestEvent::PalletIbc($event);
 		assert_eq!(
 			System::events()
 				.iter()
 				.filter(|a| {
 					if let RuntimeEvent::Ibc(ibc_event) = &a.event {
-						if let Event::<Test>::ExecuteMemoStarted { memo, .. } = ibc_event {
-							return memo.as_ref().map_or(false, |m| m == &incorrect_memo)
+						if let Event::<Test>::ExecuteMemoFailed { memo, .. } = ibc_event {
+							return memo == &incorrect_memo
 						}
 					}
 					false
@@ -614,26 +621,230 @@ fn on_deliver_ics20_recv_packet_incorrect_memo() {
 				.count(),
 			1
 		);
+	})
+}
 
-		assert_eq!(
+#[test]
+fn on_deliver_ics20_recv_packet_dispatches_memo_to_hooks() {
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let reciever = AccountId32::new(pair.public().0);
+		let ss58_address =
+			ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		setup_client_and_consensus_state(PortId::transfer());
+
+		let channel_id = ChannelId::new(0);
+		let channel_escrow_address =
+			get_channel_escrow_address(&PortId::transfer(), channel_id).unwrap();
+		let channel_escrow_address =
+			<Test as Config>::AccountIdConversion::try_from(channel_escrow_address)
+				.map_err(|_| ())
+				.unwrap();
+		let _ = <<Test as Config>::NativeCurrency as Currency<
+			<Test as frame_system::Config>::AccountId,
+		>>::deposit_creating(&channel_escrow_address.into_account(), 100000 * MILLIS);
+
+		let amt = 1000 * MILLIS;
+		let fee = <Test as crate::ics20_fee::Config>::ServiceChargeIn::get() * amt;
+		let time_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+		let recv = |sequence: u64, memo: &str| {
+			let packet_data = PacketData {
+				token: Coin {
+					denom: PrefixedDenom::from_str("transfer/channel-1/PICA").unwrap(),
+					amount: ibc::applications::transfer::Amount::from_str(&format!("{:?}", amt))
+						.unwrap(),
+				},
+				sender: Signer::from_str("alice").unwrap(),
+				receiver: Signer::from_str(&ss58_address).unwrap(),
+				memo: memo.to_string(),
+			};
+			let packet = Packet {
+				sequence: sequence.into(),
+				source_port: PortId::transfer(),
+				source_channel: ChannelId::new(1),
+				destination_port: PortId::transfer(),
+				destination_channel: channel_id,
+				data: serde_json::to_vec(&packet_data).unwrap(),
+				timeout_height: Height::new(2000, 5),
+				timeout_timestamp: ibc::timestamp::Timestamp::from_nanoseconds(
+					time_now as u64 + 10000000,
+				)
+				.unwrap(),
+			};
+			let msg = MsgRecvPacket {
+				packet,
+				proofs: Proofs::new(
+					vec![0u8; 32].try_into().unwrap(),
+					None,
+					None,
+					None,
+					Height::new(0, 1),
+				)
+				.unwrap(),
+				signer: Signer::from_str(MODULE_ID).unwrap(),
+			};
+			let msg = Any { type_url: msg.type_url(), value: msg.encode_vec().unwrap() };
+			Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]).unwrap();
+			<<Test as Config>::NativeCurrency as Currency<
+				<Test as frame_system::Config>::AccountId,
+			>>::free_balance(&reciever)
+		};
+		let remarks = || {
 			System::events()
 				.iter()
 				.filter(|a| {
-					if let RuntimeEvent::Ibc(ibc_event) = &a.event {
-						if let Event::<Test>::ExecuteMemoIbcTokenTransferFailedWithReason {
-							memo,
-							reason,
-							from,
-						} = ibc_event
-						{
-							return memo == &incorrect_memo && *reason == 0 && from == &reciever
-						}
-					}
-					false
+					matches!(
+						&a.event,
+						RuntimeEvent::System(frame_system::Event::Remarked { sender, .. })
+							if sender == &reciever
+					)
 				})
-				.count(),
-			1
+				.count()
+		};
+
+		// plain text memos are left alone
+		assert_eq!(recv(1, "Plain text memo"), amt - fee);
+		assert_eq!(remarks(), 0);
+
+		// the hook is called with the memo once the tokens are received
+		assert_eq!(recv(2, r#"{"test":"ok"}"#), 2 * (amt - fee));
+		assert_eq!(remarks(), 1);
+
+		// a failing hook reverts the receipt of the transfer
+		assert_eq!(recv(3, r#"{"test":"fail"}"#), 2 * (amt - fee));
+		assert_eq!(remarks(), 1);
+		assert!(System::events().iter().any(|a| matches!(
+			&a.event,
+			RuntimeEvent::Ibc(Event::<Test>::ExecuteMemoFailed { memo, .. })
+				if memo == r#"{"test":"fail"}"#
+		)));
+	})
+}
+
+#[test]
+fn forwarded_transfers_are_retried_on_timeout_and_refunded_on_failure() {
+	use crate::{ics20::IbcModule, ForwardedTransfers};
+	use ibc::core::ics26_routing::context::{Module, ModuleOutputBuilder};
+
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let reciever = AccountId32::new(pair.public().0);
+		let ss58_address =
+			ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		setup_client_and_consensus_state(PortId::transfer());
+
+		let channel_id = ChannelId::new(0);
+		let channel_escrow_address =
+			get_channel_escrow_address(&PortId::transfer(), channel_id).unwrap();
+		let channel_escrow_address =
+			<Test as Config>::AccountIdConversion::try_from(channel_escrow_address)
+				.map_err(|_| ())
+				.unwrap();
+		let _ = <<Test as Config>::NativeCurrency as Currency<
+			<Test as frame_system::Config>::AccountId,
+		>>::deposit_creating(&channel_escrow_address.into_account(), 100000 * MILLIS);
+
+		let amt = 1000 * MILLIS;
+		let memo = r#"{"forward":{"receiver":"cosmos1receiver","port":"transfer","channel":"channel-0","timeout":600,"retries":1}}"#;
+		let packet_data = PacketData {
+			token: Coin {
+				denom: PrefixedDenom::from_str("transfer/channel-1/PICA").unwrap(),
+				amount: ibc::applications::transfer::Amount::from_str(&format!("{:?}", amt))
+					.unwrap(),
+			},
+			sender: Signer::from_str("alice").unwrap(),
+			receiver: Signer::from_str(&ss58_address).unwrap(),
+			memo: memo.to_string(),
+		};
+		let time_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+		let msg = MsgRecvPacket {
+			packet: Packet {
+				sequence: 1u64.into(),
+				source_port: PortId::transfer(),
+				source_channel: ChannelId::new(1),
+				destination_port: PortId::transfer(),
+				destination_channel: channel_id,
+				data: serde_json::to_vec(&packet_data).unwrap(),
+				timeout_height: Height::new(2000, 5),
+				timeout_timestamp: ibc::timestamp::Timestamp::from_nanoseconds(
+					time_now as u64 + 10000000,
+				)
+				.unwrap(),
+			},
+			proofs: Proofs::new(
+				vec![0u8; 32].try_into().unwrap(),
+				None,
+				None,
+				None,
+				Height::new(0, 1),
+			)
+			.unwrap(),
+			signer: Signer::from_str(MODULE_ID).unwrap(),
+		};
+		let msg = Any { type_url: msg.type_url(), value: msg.encode_vec().unwrap() };
+		Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]).unwrap();
+
+		// the received tokens are forwarded on the next channel
+		let forward = ForwardedTransfers::<Test>::get(0, 1).unwrap();
+		assert_eq!(forward.from, reciever);
+		assert_eq!(forward.to, b"cosmos1receiver".to_vec());
+		assert_eq!(forward.retries, 1);
+		assert_eq!(forward.refund_channel, 0);
+		assert_eq!(forward.refund_to, b"alice".to_vec());
+
+		let ctx = Context::<Test>::default();
+		let relayer = Signer::from_str(MODULE_ID).unwrap();
+		let sent_packet = |sequence: u64| {
+			Packet::from(
+				Ibc::get_send_packet_info(
+					channel_id.to_string().as_bytes().to_vec(),
+					PortId::transfer().as_bytes().to_vec(),
+					vec![sequence],
+				)
+				.unwrap()
+				.get(0)
+				.unwrap()
+				.clone(),
+			)
+		};
+
+		// the timed out transfer is sent again while it has retries left
+		IbcModule::<Test>::default()
+			.on_timeout_packet(&ctx, &mut ModuleOutputBuilder::new(), &mut sent_packet(1), &relayer)
+			.unwrap();
+		assert!(!ForwardedTransfers::<Test>::contains_key(0, 1));
+		assert_eq!(ForwardedTransfers::<Test>::get(0, 2).unwrap().retries, 0);
+		assert!(System::events().iter().any(|a| matches!(
+			&a.event,
+			RuntimeEvent::Ibc(Event::<Test>::ForwardedTransferRetried { sequence: 2, retries: 0, .. })
+		)));
+
+		// the failed transfer is refunded to the sender on the channel it was received on
+		let error_ack = Acknowledgement::from_bytes(
+			Ics20Acknowledgement::Error("forward failed".to_string()).to_string().into_bytes(),
 		);
+		IbcModule::<Test>::default()
+			.on_acknowledgement_packet(
+				&ctx,
+				&mut ModuleOutputBuilder::new(),
+				&mut sent_packet(2),
+				&error_ack,
+				&relayer,
+			)
+			.unwrap();
+		assert!(!ForwardedTransfers::<Test>::contains_key(0, 2));
+		assert!(System::events().iter().any(|a| matches!(
+			&a.event,
+			RuntimeEvent::Ibc(Event::<Test>::ForwardedTransferRefunded { to, channel: 0, .. })
+				if to == b"alice"
+		)));
+		let refund = serde_json::from_slice::<PacketData>(&sent_packet(3).data).unwrap();
+		assert_eq!(refund.receiver.to_string(), "alice");
+		assert!(refund.memo.is_empty());
 	})
 }
 
@@ -1310,3 +1521,1376 @@ fn test_next_and_previous_consensus_state_for_other_client_types() {
 		assert!(ctx.next_consensus_state(&client_id, Height::new(0, 400)).unwrap().is_some());
 	})
 }
+
+#[test]
+fn consensus_states_are_pruned_above_the_client_limit() {
+	use crate::{ClientUpdateTime, ConsensusStateHeights};
+
+	new_test_ext().execute_with(|| {
+		let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+		let mut ctx = Context::<Test>::default();
+		let mock_cs_state = MockConsensusState::new(MockHeader::default());
+		for i in 1..=300u64 {
+			let height = Height::new(0, i);
+			ctx.store_consensus_state(
+				client_id.clone(),
+				height,
+				AnyConsensusState::Mock(mock_cs_state.clone()),
+			)
+			.unwrap();
+			ctx.store_update_time(client_id.clone(), height, ctx.host_timestamp()).unwrap();
+			ctx.store_update_height(client_id.clone(), height, ctx.host_height()).unwrap();
+		}
+
+		// only the 250 highest consensus states are kept by default
+		let stored_heights = ConsensusStateHeights::<Test>::get(client_id.as_bytes().to_vec());
+		assert_eq!(stored_heights.len(), 250);
+		assert_eq!(stored_heights.iter().next(), Some(&Height::new(0, 51)));
+		assert!(ctx.consensus_state(&client_id, Height::new(0, 50)).is_err());
+		assert!(ctx.consensus_state(&client_id, Height::new(0, 51)).is_ok());
+		assert!(ctx.client_update_time(&client_id, Height::new(0, 50)).is_err());
+		assert!(ctx.client_update_height(&client_id, Height::new(0, 50)).is_err());
+		assert!(ctx.client_update_time(&client_id, Height::new(0, 51)).is_ok());
+
+		assert_noop!(
+			Ibc::set_consensus_states_limit(
+				RuntimeOrigin::root(),
+				client_id.as_bytes().to_vec(),
+				Some(0)
+			),
+			crate::Error::<Test>::InvalidConsensusStatesLimit
+		);
+		assert_ok!(Ibc::set_consensus_states_limit(
+			RuntimeOrigin::root(),
+			client_id.as_bytes().to_vec(),
+			Some(10)
+		));
+		let stored_heights = ConsensusStateHeights::<Test>::get(client_id.as_bytes().to_vec());
+		assert_eq!(stored_heights.len(), 10);
+		assert!(ctx.consensus_state(&client_id, Height::new(0, 290)).is_err());
+		assert!(ctx.consensus_state(&client_id, Height::new(0, 291)).is_ok());
+		assert!(!ConsensusHeights::<Test>::get(client_id.as_bytes().to_vec())
+			.contains(&Height::new(0, 290)));
+		assert_eq!(
+			ClientUpdateTime::<Test>::iter_prefix(client_id.as_bytes().to_vec()).count(),
+			10
+		);
+	})
+}
+
+#[test]
+fn packet_fees_are_escrowed_and_paid_to_the_relayers() {
+	use crate::ics29_fee::{Fee, FeeEnabledChannels, FeesInEscrow, Payees};
+	use frame_support::traits::Get;
+	use ibc::core::ics04_channel::commitment::PacketCommitment;
+
+	new_test_ext().execute_with(|| {
+		let port_id = PortId::transfer();
+		let channel_id = ChannelId::new(0);
+		let channel_key = (port_id.as_bytes().to_vec(), channel_id.to_string().as_bytes().to_vec());
+		let payer = AccountId32::new([1; 32]);
+		let relayer = AccountId32::new([2; 32]);
+		let payee = AccountId32::new([3; 32]);
+		let forward_relayer = AccountId32::new([4; 32]);
+		let balance_of = |account: &AccountId32| {
+			<<Test as Config>::NativeCurrency as Currency<AccountId32>>::free_balance(account)
+		};
+		let _ = <<Test as Config>::NativeCurrency as Currency<AccountId32>>::deposit_creating(
+			&payer, 1_000_000,
+		);
+		let fee =
+			Fee { asset_id: NativeAssetId::get(), recv_fee: 300, ack_fee: 200, timeout_fee: 100 };
+
+		// fees can only be paid on the channels that negotiated the fee middleware
+		assert_noop!(
+			Ics29Fee::pay_packet_fee_async(
+				RuntimeOrigin::signed(payer.clone()),
+				channel_key.0.clone(),
+				channel_key.1.clone(),
+				1,
+				fee.clone()
+			),
+			crate::ics29_fee::Error::<Test>::FeeNotEnabled
+		);
+		FeeEnabledChannels::<Test>::insert(channel_key.clone(), ());
+		assert_noop!(
+			Ics29Fee::pay_packet_fee_async(
+				RuntimeOrigin::signed(payer.clone()),
+				channel_key.0.clone(),
+				channel_key.1.clone(),
+				1,
+				fee.clone()
+			),
+			crate::ics29_fee::Error::<Test>::PacketNotInFlight
+		);
+
+		let mut ctx = Context::<Test>::default();
+		ctx.store_packet_commitment(
+			(port_id.clone(), channel_id, 1.into()),
+			PacketCommitment::from(vec![1; 32]),
+		)
+		.unwrap();
+		assert_ok!(Ics29Fee::pay_packet_fee_async(
+			RuntimeOrigin::signed(payer.clone()),
+			channel_key.0.clone(),
+			channel_key.1.clone(),
+			1,
+			fee.clone()
+		));
+		assert_eq!(balance_of(&payer), 1_000_000 - 600);
+		assert_eq!(balance_of(&crate::ics29_fee::Pallet::<Test>::account_id()), 600);
+		assert_eq!(FeesInEscrow::<Test>::get(channel_key.clone(), 1).len(), 1);
+
+		assert_ok!(Ics29Fee::register_payee(
+			RuntimeOrigin::signed(relayer.clone()),
+			channel_key.0.clone(),
+			channel_key.1.clone(),
+			payee.clone()
+		));
+		assert_eq!(Payees::<Test>::get(channel_key.1.clone(), &relayer), Some(payee.clone()));
+
+		crate::ics29_fee::Pallet::<Test>::distribute_fees_on_ack(
+			&port_id,
+			&channel_id,
+			1,
+			Some(forward_relayer.clone()),
+			Some(relayer.clone()),
+		);
+		assert_eq!(balance_of(&forward_relayer), 300);
+		assert_eq!(balance_of(&payee), 200);
+		assert_eq!(balance_of(&relayer), 0);
+		assert_eq!(balance_of(&payer), 1_000_000 - 500);
+		assert_eq!(balance_of(&crate::ics29_fee::Pallet::<Test>::account_id()), 0);
+		assert!(FeesInEscrow::<Test>::get(channel_key.clone(), 1).is_empty());
+
+		// the number of fees escrowed for a packet is bounded
+		let pay_fee = || {
+			Ics29Fee::pay_packet_fee_async(
+				RuntimeOrigin::signed(payer.clone()),
+				channel_key.0.clone(),
+				channel_key.1.clone(),
+				1,
+				fee.clone(),
+			)
+		};
+		let max_fees: u32 = <Test as crate::ics29_fee::Config>::MaxFeesPerPacket::get();
+		for _ in 0..max_fees {
+			assert_ok!(pay_fee());
+		}
+		assert_noop!(pay_fee(), crate::ics29_fee::Error::<Test>::TooManyFees);
+	})
+}
+
+#[test]
+fn acknowledgements_of_fee_enabled_channels_are_written_once() {
+	use crate::ics29_fee::{FeeEnabledChannels, IncentivizedAcknowledgement};
+
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		setup_client_and_consensus_state(PortId::transfer());
+		let channel_id = ChannelId::new(0);
+		FeeEnabledChannels::<Test>::insert(
+			(PortId::transfer().as_bytes().to_vec(), channel_id.to_string().as_bytes().to_vec()),
+			(),
+		);
+
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let ss58_address =
+			ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		let channel_escrow_address =
+			get_channel_escrow_address(&PortId::transfer(), channel_id).unwrap();
+		let channel_escrow_address =
+			<Test as Config>::AccountIdConversion::try_from(channel_escrow_address)
+				.map_err(|_| ())
+				.unwrap()
+				.into_account();
+		let _ = <<Test as Config>::NativeCurrency as Currency<
+			<Test as frame_system::Config>::AccountId,
+		>>::deposit_creating(&channel_escrow_address, 100000 * MILLIS);
+
+		let amount = 1000 * MILLIS;
+		let packet_data = PacketData {
+			token: Coin {
+				denom: PrefixedDenom::from_str("transfer/channel-1/PICA").unwrap(),
+				amount: ibc::applications::transfer::Amount::from_str(&format!("{amount}"))
+					.unwrap(),
+			},
+			sender: Signer::from_str("alice").unwrap(),
+			receiver: Signer::from_str(&ss58_address).unwrap(),
+			memo: "".to_string(),
+		};
+		let time_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+		let packet = Packet {
+			sequence: 1u64.into(),
+			source_port: PortId::transfer(),
+			source_channel: ChannelId::new(1),
+			destination_port: PortId::transfer(),
+			destination_channel: channel_id,
+			data: serde_json::to_vec(&packet_data).unwrap(),
+			timeout_height: Height::new(2000, 5),
+			timeout_timestamp: ibc::timestamp::Timestamp::from_nanoseconds(
+				time_now as u64 + 10000000,
+			)
+			.unwrap(),
+		};
+		let msg = MsgRecvPacket {
+			packet,
+			proofs: Proofs::new(
+				vec![0u8; 32].try_into().unwrap(),
+				None,
+				None,
+				None,
+				Height::new(0, 1),
+			)
+			.unwrap(),
+			signer: Signer::from_str(MODULE_ID).unwrap(),
+		};
+		let msg = Any { type_url: msg.type_url(), value: msg.encode_vec().unwrap() };
+		Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]).unwrap();
+
+		// the acknowledgement of the application is wrapped before it is written
+		let ctx = Context::<Test>::default();
+		let key = (PortId::transfer(), channel_id, 1u64.into());
+		let ack = Acknowledgement::from_bytes(
+			serde_json::to_vec(&IncentivizedAcknowledgement {
+				app_acknowledgement: Ics20Acknowledgement::success().to_string().into_bytes(),
+				forward_relayer_address: String::new(),
+				underlying_app_success: true,
+			})
+			.unwrap(),
+		);
+		assert_eq!(ctx.get_packet_acknowledgement(&key).unwrap(), ctx.ack_commitment(ack));
+		assert_eq!(
+			System::events()
+				.iter()
+				.filter_map(|record| match &record.event {
+					RuntimeEvent::Ibc(Event::<Test>::Events { events }) => Some(events),
+					_ => None,
+				})
+				.flatten()
+				.filter(|event| {
+					matches!(event, Ok(crate::events::IbcEvent::WriteAcknowledgement { .. }))
+				})
+				.count(),
+			1
+		);
+	})
+}
+
+#[test]
+fn incentivized_acknowledgements_wrap_the_application_acknowledgements() {
+	use crate::ics29_fee::IncentivizedAcknowledgement;
+
+	let ack = IncentivizedAcknowledgement {
+		app_acknowledgement: br#"{"result":"AQ=="}"#.to_vec(),
+		forward_relayer_address: "5yNZjX24n2eg7W6EVamaTXNQbWCwchhThEaSWB7V3GRjtHeL".to_string(),
+		underlying_app_success: true,
+	};
+	let encoded = serde_json::to_string(&ack).unwrap();
+	assert_eq!(
+		encoded,
+		r#"{"app_acknowledgement":"eyJyZXN1bHQiOiJBUT09In0=","forward_relayer_address":"5yNZjX24n2eg7W6EVamaTXNQbWCwchhThEaSWB7V3GRjtHeL","underlying_app_success":true}"#
+	);
+	assert_eq!(serde_json::from_str::<IncentivizedAcknowledgement>(&encoded).unwrap(), ack);
+}
+
+#[test]
+fn ica_host_executes_the_transactions_of_the_interchain_accounts() {
+	use crate::{
+		ics27::{
+			host_port_id, IcaAcknowledgement, IcaHost, InterchainAccountPacketData, Metadata,
+			HOST_PORT_ID,
+		},
+		weight::WeightRouter,
+	};
+	use codec::Encode;
+	use ibc::{
+		core::ics26_routing::context::{Module, ModuleOutputBuilder},
+		timestamp::Timestamp,
+	};
+	use ibc_proto::ibc::applications::interchain_accounts::v1::{CosmosTx, Type};
+	use prost::Message;
+
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Ics27::set_params(RuntimeOrigin::root(), true, false));
+		let mut ctx = Context::<Test>::default();
+		let mut host = IcaHost::<Test>::default();
+		let mut output = ModuleOutputBuilder::new();
+		let signer = Signer::from_str(MODULE_ID).unwrap();
+		let connection_id = ConnectionId::new(0);
+		let channel_id = ChannelId::new(0);
+		let controller_port_id = PortId::from_str("icacontroller-0x0101").unwrap();
+		let counterparty = ChanCounterParty::new(controller_port_id.clone(), Some(channel_id));
+		let version = Metadata::new(&ConnectionId::new(1), &connection_id).to_version().unwrap();
+
+		// the account is created on open try and its address returned in the version
+		let version = host
+			.on_chan_open_try(
+				&ctx,
+				&mut output,
+				Order::Ordered,
+				&[connection_id.clone()],
+				&host_port_id(),
+				&channel_id,
+				&counterparty,
+				&version,
+				&version,
+				&signer,
+			)
+			.unwrap();
+		let account = Ics27::interchain_account(&connection_id, &controller_port_id).unwrap();
+		assert_eq!(
+			Metadata::parse(&version).unwrap().address,
+			format!("0x{}", hex::encode(account.encode()))
+		);
+
+		let channel_end = ChannelEnd::new(
+			State::Open,
+			Order::Ordered,
+			counterparty,
+			vec![connection_id.clone()],
+			version,
+		);
+		ctx.store_channel((host_port_id(), channel_id), &channel_end).unwrap();
+		host.on_chan_open_confirm(&ctx, &mut output, &host_port_id(), &channel_id, &signer)
+			.unwrap();
+
+		let remarks = || {
+			System::events()
+				.iter()
+				.filter(|record| {
+					matches!(
+						&record.event,
+						RuntimeEvent::System(frame_system::Event::Remarked { sender, .. })
+							if sender == &account
+					)
+				})
+				.count()
+		};
+		let mut packet = |sequence: u64, calls: Vec<RuntimeCall>| {
+			let tx = CosmosTx {
+				messages: calls
+					.iter()
+					.map(|call| Ics27::runtime_call_message(call).into())
+					.collect(),
+			};
+			let data = InterchainAccountPacketData {
+				ty: Type::ExecuteTx.as_str_name().to_string(),
+				data: tx.encode_to_vec(),
+				memo: String::new(),
+			};
+			let mut packet = Packet {
+				sequence: sequence.into(),
+				source_port: controller_port_id.clone(),
+				source_channel: channel_id,
+				destination_port: host_port_id(),
+				destination_channel: channel_id,
+				data: serde_json::to_vec(&data).unwrap(),
+				timeout_height: Height::new(0, 100),
+				timeout_timestamp: Timestamp::none(),
+			};
+			let ack = host.on_recv_packet(&ctx, &mut output, &mut packet, &signer).unwrap();
+			serde_json::from_slice::<IcaAcknowledgement>(ack.as_ref()).unwrap()
+		};
+		let remark =
+			RuntimeCall::System(frame_system::Call::remark_with_event { remark: b"ica".to_vec() });
+
+		assert!(packet(1, vec![remark.clone()]).is_success());
+		assert_eq!(remarks(), 1);
+
+		// the calls of a failed transaction are reverted
+		let root_call = RuntimeCall::System(frame_system::Call::set_heap_pages { pages: 1 });
+		assert!(!packet(2, vec![remark, root_call]).is_success());
+		assert_eq!(remarks(), 1);
+
+		// the packets are charged the maximum weight of the transactions executed by the host
+		let weight = WeightRouter::<Test>::get_weight(HOST_PORT_ID).unwrap();
+		assert!(weight.on_recv_packet(&Packet::default()).all_gte(IcaMaxTxWeight::get()));
+	})
+}
+
+#[test]
+fn ica_controller_registers_interchain_accounts_and_sends_transactions() {
+	use crate::ics27::{
+		controller_port_id, host_port_id, ControllerChannels, IcaController, Metadata,
+	};
+	use ibc::core::ics26_routing::context::{Module, ModuleOutputBuilder};
+
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_client_and_consensus_state(PortId::transfer());
+		let owner = AccountId32::new([1; 32]);
+		let connection_id = ConnectionId::new(0);
+		let port_id = controller_port_id::<Test>(&owner).unwrap();
+
+		assert_noop!(
+			Ics27::register_interchain_account(
+				RuntimeOrigin::signed(owner.clone()),
+				connection_id.as_bytes().to_vec()
+			),
+			crate::ics27::Error::<Test>::ControllerDisabled
+		);
+		assert_ok!(Ics27::set_params(RuntimeOrigin::root(), false, true));
+		assert_ok!(Ics27::register_interchain_account(
+			RuntimeOrigin::signed(owner.clone()),
+			connection_id.as_bytes().to_vec()
+		));
+
+		let mut ctx = Context::<Test>::default();
+		let channel_id = ChannelId::new(ctx.channel_counter().unwrap() - 1);
+		let channel_end = ctx.channel_end(&(port_id.clone(), channel_id)).unwrap();
+		assert_eq!(channel_end.state, State::Init);
+		assert_eq!(channel_end.ordering, Order::Ordered);
+		let mut metadata = Metadata::parse(&channel_end.version).unwrap();
+		assert_eq!(metadata.host_connection_id, ConnectionId::new(1).to_string());
+
+		// the address of the interchain account is registered on open ack
+		metadata.address = "cosmos1ica".to_string();
+		IcaController::<Test>::default()
+			.on_chan_open_ack(
+				&ctx,
+				&mut ModuleOutputBuilder::new(),
+				&port_id,
+				&channel_id,
+				&metadata.to_version().unwrap(),
+				&Signer::from_str(MODULE_ID).unwrap(),
+			)
+			.unwrap();
+		assert_eq!(
+			Ics27::registered_account(&connection_id, &owner),
+			Some("cosmos1ica".to_string())
+		);
+		assert!(ControllerChannels::<Test>::contains_key(
+			connection_id.as_bytes(),
+			port_id.as_bytes()
+		));
+		assert_noop!(
+			Ics27::register_interchain_account(
+				RuntimeOrigin::signed(owner.clone()),
+				connection_id.as_bytes().to_vec()
+			),
+			crate::ics27::Error::<Test>::ActiveChannelExists
+		);
+
+		let channel_end = ChannelEnd::new(
+			State::Open,
+			Order::Ordered,
+			ChanCounterParty::new(host_port_id(), Some(ChannelId::new(1))),
+			vec![connection_id.clone()],
+			metadata.to_version().unwrap(),
+		);
+		ctx.store_channel((port_id.clone(), channel_id), &channel_end).unwrap();
+		let remark =
+			RuntimeCall::System(frame_system::Call::remark_with_event { remark: b"ica".to_vec() });
+		assert_ok!(Ics27::send_tx(
+			RuntimeOrigin::signed(owner.clone()),
+			connection_id.as_bytes().to_vec(),
+			vec![Ics27::runtime_call_message(&remark)],
+			vec![],
+			Timeout::Offset { timestamp: Some(1000), height: Some(5) },
+		));
+		assert!(ctx.get_packet_commitment(&(port_id, channel_id, 1.into())).is_ok());
+	})
+}
+
+#[test]
+fn unsigned_client_updates_are_gated_by_governance() {
+	use frame_support::{pallet_prelude::*, traits::ValidateUnsigned};
+	use ibc::core::ics02_client::msgs::update_client::TYPE_URL as UPDATE_CLIENT_TYPE_URL;
+
+	new_test_ext().execute_with(|| {
+		let message = Any { type_url: UPDATE_CLIENT_TYPE_URL.to_string(), value: vec![] };
+		let call = crate::Call::<Test>::update_client_unsigned { message: message.clone() };
+
+		// disabled by default
+		assert_eq!(
+			Ibc::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Call.into()
+		);
+		assert_noop!(
+			Ibc::update_client_unsigned(RuntimeOrigin::none(), message.clone()),
+			crate::Error::<Test>::UnsignedClientUpdatesDisabled
+		);
+
+		assert_noop!(
+			Ibc::set_unsigned_client_update_interval(
+				RuntimeOrigin::signed(AccountId32::new([0; 32])),
+				Some(10)
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Ibc::set_unsigned_client_update_interval(RuntimeOrigin::root(), Some(10)));
+		assert_eq!(crate::UnsignedClientUpdateInterval::<Test>::get(), Some(10));
+
+		// only signed extrinsics may deliver other messages
+		let message = Any { type_url: TYPE_URL.to_string(), value: vec![] };
+		assert_noop!(
+			Ibc::update_client_unsigned(RuntimeOrigin::none(), message),
+			crate::Error::<Test>::InvalidMessageType
+		);
+	})
+}
+
+#[test]
+fn unsigned_client_updates_are_rate_limited() {
+	use crate::{light_clients::AnyClientMessage, LastUnsignedClientUpdate};
+	use frame_support::{pallet_prelude::*, traits::ValidateUnsigned};
+	use ibc::core::ics02_client::msgs::update_client::{
+		MsgUpdateAnyClient, TYPE_URL as UPDATE_CLIENT_TYPE_URL,
+	};
+
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let msg = MsgCreateAnyClient::<Context<Test>>::new(
+			AnyClientState::Mock(MockClientState::new(MockClientMessage::from(MockHeader::new(
+				Height::new(0, 1),
+			)))),
+			AnyConsensusState::Mock(MockConsensusState::new(MockHeader::new(Height::new(0, 1)))),
+			Signer::from_str(MODULE_ID).unwrap(),
+		)
+		.unwrap()
+		.encode_vec()
+		.unwrap();
+		let msg = Any { type_url: TYPE_URL.to_string(), value: msg };
+		assert_ok!(Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]));
+		assert_ok!(Ibc::set_unsigned_client_update_interval(RuntimeOrigin::root(), Some(10)));
+
+		let client_id = ClientId::new("9999-mock", 0).unwrap();
+		let update = |client_id: &ClientId, height: u64| {
+			let msg = MsgUpdateAnyClient::<Context<Test>> {
+				client_id: client_id.clone(),
+				client_message: AnyClientMessage::Mock(MockClientMessage::from(MockHeader::new(
+					Height::new(0, height),
+				))),
+				signer: Signer::from_str(MODULE_ID).unwrap(),
+			};
+			Any { type_url: UPDATE_CLIENT_TYPE_URL.to_string(), value: msg.encode_vec().unwrap() }
+		};
+		let validate = |message: &Any| {
+			let call = crate::Call::<Test>::update_client_unsigned { message: message.clone() };
+			Ibc::validate_unsigned(TransactionSource::External, &call)
+		};
+
+		// updates of unknown clients fail without counting towards the interval
+		let unknown = ClientId::new("9999-mock", 1).unwrap();
+		assert_noop!(
+			Ibc::update_client_unsigned(RuntimeOrigin::none(), update(&unknown, 2)),
+			crate::Error::<Test>::ProcessingError
+		);
+		assert_eq!(LastUnsignedClientUpdate::<Test>::get(unknown.as_bytes()), None);
+
+		let message = update(&client_id, 2);
+		assert!(validate(&message).is_ok());
+		assert_ok!(Ibc::update_client_unsigned(RuntimeOrigin::none(), message));
+		assert_eq!(LastUnsignedClientUpdate::<Test>::get(client_id.as_bytes()), Some(1));
+		let ctx = Context::<Test>::default();
+		assert_eq!(ctx.client_state(&client_id).unwrap().latest_height(), Height::new(0, 2));
+
+		// the client can't be updated again before the interval has elapsed
+		let message = update(&client_id, 3);
+		frame_system::Pallet::<Test>::set_block_number(10u32);
+		assert_eq!(validate(&message), InvalidTransaction::Stale.into());
+		assert_noop!(
+			Ibc::update_client_unsigned(RuntimeOrigin::none(), message.clone()),
+			crate::Error::<Test>::UnsignedClientUpdateTooEarly
+		);
+
+		frame_system::Pallet::<Test>::set_block_number(11u32);
+		assert!(validate(&message).is_ok());
+		assert_ok!(Ibc::update_client_unsigned(RuntimeOrigin::none(), message));
+		assert_eq!(LastUnsignedClientUpdate::<Test>::get(client_id.as_bytes()), Some(11));
+		assert_eq!(ctx.client_state(&client_id).unwrap().latest_height(), Height::new(0, 3));
+	})
+}
+
+#[test]
+fn denoms_resolve_to_derived_or_overridden_asset_ids() {
+	use crate::denom::{derive_asset_id, is_derived_asset_id};
+
+	new_test_ext().execute_with(|| {
+		let denom = "transfer/channel-0/uatom";
+		let derived = derive_asset_id(denom);
+		assert!(is_derived_asset_id(derived));
+		assert_ne!(derived, derive_asset_id("transfer/channel-1/uatom"));
+		assert_eq!(Ibc::denom_to_asset_id(denom), derived);
+		assert_eq!(Ibc::asset_id_to_denom(derived), None);
+
+		assert_noop!(
+			Ibc::set_denom_asset_id(
+				RuntimeOrigin::signed(AccountId32::new([0; 32])),
+				denom.as_bytes().to_vec(),
+				Some(5)
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Ibc::set_denom_asset_id(
+			RuntimeOrigin::root(),
+			denom.as_bytes().to_vec(),
+			Some(5)
+		));
+		assert_eq!(Ibc::denom_to_asset_id(denom), 5);
+		assert_eq!(Ibc::asset_id_to_denom(5), Some(denom.to_string()));
+
+		// an asset id can't be shared by two denoms
+		assert_noop!(
+			Ibc::set_denom_asset_id(
+				RuntimeOrigin::root(),
+				b"transfer/channel-1/uatom".to_vec(),
+				Some(5)
+			),
+			crate::Error::<Test>::AssetIdAlreadyMapped
+		);
+
+		assert_ok!(Ibc::set_denom_asset_id(RuntimeOrigin::root(), denom.as_bytes().to_vec(), None));
+		assert_eq!(Ibc::denom_to_asset_id(denom), derived);
+		assert_eq!(Ibc::asset_id_to_denom(5), None);
+	})
+}
+
+#[test]
+fn received_denoms_create_their_assets_and_record_their_traces() {
+	use crate::{
+		asset_registry::{AssetMetadata, FungiblesRegistry, IbcDenomRegistry},
+		denom::{denom_trace_hash, derive_asset_id},
+	};
+
+	frame_support::parameter_types! {
+		pub IbcPalletAccount: AccountId = AccountId32::new([1; 32]);
+	}
+	type Registry = IbcDenomRegistry<
+		Test,
+		FungiblesRegistry<Assets, AccountId, IbcPalletAccount, frame_support::traits::ConstU128<1>>,
+	>;
+
+	new_test_ext().execute_with(|| {
+		let denom = "transfer/channel-0/uatom";
+		let asset_id = derive_asset_id(denom);
+		assert_eq!(Registry::lookup_asset_id(denom), None);
+		assert!(!Assets::asset_exists(asset_id));
+
+		// the asset of the voucher is created on the first lookup of a received denom
+		assert_eq!(Registry::from_denom_to_asset_id(denom), Ok(asset_id));
+		assert!(Assets::asset_exists(asset_id));
+		assert_eq!(Registry::lookup_asset_id(denom), Some(asset_id));
+		assert_eq!(Registry::from_asset_id_to_denom(asset_id), Some(denom.to_string()));
+		let metadata = Registry::metadata(denom).unwrap();
+		assert_eq!(metadata, AssetMetadata::voucher(denom));
+		assert_eq!(metadata.symbol, b"uatom".to_vec());
+		assert_eq!(Registry::from_denom_to_asset_id(denom), Ok(asset_id));
+		assert_eq!(Registry::ibc_assets(None, 10).denoms, vec![denom.as_bytes().to_vec()]);
+
+		let hash = denom_trace_hash(denom).to_vec();
+		assert_eq!(Ibc::get_denom_trace_by_hash(hash.clone()), None);
+		Ibc::record_denom_trace(denom);
+		assert_eq!(
+			Ibc::get_denom_trace_by_hash(hash).map(|trace| trace.denom),
+			Some(denom.as_bytes().to_vec())
+		);
+		assert_eq!(Ibc::get_denom_trace_by_hash(vec![0; 31]), None);
+	})
+}
+
+#[test]
+fn frozen_clients_are_unfrozen_or_recovered_by_governance() {
+	use crate::ConsensusStateHeights;
+
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let create_client = |height: u64| {
+			let msg = MsgCreateAnyClient::<Context<Test>>::new(
+				AnyClientState::Mock(MockClientState::new(MockClientMessage::from(
+					MockHeader::new(Height::new(0, height)),
+				))),
+				AnyConsensusState::Mock(MockConsensusState::new(MockHeader::new(Height::new(
+					0, height,
+				)))),
+				Signer::from_str(MODULE_ID).unwrap(),
+			)
+			.unwrap()
+			.encode_vec()
+			.unwrap();
+			let msg = Any { type_url: TYPE_URL.to_string(), value: msg };
+			assert_ok!(Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]));
+		};
+		create_client(1);
+		create_client(5);
+		let ctx = Context::<Test>::default();
+		let subject = ClientId::new("9999-mock", 0).unwrap();
+		let substitute = ClientId::new("9999-mock", 1).unwrap();
+		let subject_bytes = subject.as_bytes().to_vec();
+		let substitute_bytes = substitute.as_bytes().to_vec();
+
+		assert_noop!(
+			Ibc::unfreeze_client(RuntimeOrigin::root(), subject_bytes.clone()),
+			crate::Error::<Test>::ClientNotFrozen
+		);
+		assert_noop!(
+			Ibc::recover_client(
+				RuntimeOrigin::root(),
+				subject_bytes.clone(),
+				substitute_bytes.clone()
+			),
+			crate::Error::<Test>::ClientIsActive
+		);
+
+		assert_ok!(Ibc::freeze_client(RuntimeOrigin::root(), subject_bytes.clone(), 1));
+		assert!(ctx.client_state(&subject).unwrap().frozen_height().is_some());
+		assert_noop!(
+			Ibc::unfreeze_client(
+				RuntimeOrigin::signed(AccountId32::new([0; 32])),
+				subject_bytes.clone()
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Ibc::unfreeze_client(RuntimeOrigin::root(), subject_bytes.clone()));
+		assert!(ctx.client_state(&subject).unwrap().frozen_height().is_none());
+
+		// a frozen client can't be the substitute
+		assert_ok!(Ibc::freeze_client(RuntimeOrigin::root(), substitute_bytes.clone(), 5));
+		assert_ok!(Ibc::freeze_client(RuntimeOrigin::root(), subject_bytes.clone(), 1));
+		assert_noop!(
+			Ibc::recover_client(
+				RuntimeOrigin::root(),
+				subject_bytes.clone(),
+				substitute_bytes.clone()
+			),
+			crate::Error::<Test>::SubstituteClientNotActive
+		);
+		assert_ok!(Ibc::unfreeze_client(RuntimeOrigin::root(), substitute_bytes.clone()));
+
+		assert_ok!(Ibc::recover_client(
+			RuntimeOrigin::root(),
+			subject_bytes.clone(),
+			substitute_bytes
+		));
+		let client_state = ctx.client_state(&subject).unwrap();
+		assert_eq!(client_state, ctx.client_state(&substitute).unwrap());
+		assert!(client_state.frozen_height().is_none());
+		let stored_heights = ConsensusStateHeights::<Test>::get(&subject_bytes);
+		assert_eq!(stored_heights.into_iter().collect::<Vec<_>>(), vec![Height::new(0, 5)]);
+		assert!(ctx.consensus_state(&subject, Height::new(0, 1)).is_err());
+		assert!(ctx.client_update_time(&subject, Height::new(0, 1)).is_err());
+		assert!(ctx.client_update_time(&subject, Height::new(0, 5)).is_ok());
+		assert!(ctx.client_update_height(&subject, Height::new(0, 5)).is_ok());
+		assert!(System::events().iter().any(|a| matches!(
+			&a.event,
+			RuntimeEvent::Ibc(Event::<Test>::ClientRecovered { subject_client_id, height, .. })
+				if subject_client_id == &subject_bytes && height == &Height::new(0, 5)
+		)));
+	})
+}
+
+#[test]
+fn ibc_state_is_enumerated_by_pages() {
+	use ibc_primitives::PageRequest;
+
+	new_test_ext().execute_with(|| {
+		let port_id = PortId::transfer();
+		setup_client_and_consensus_state(port_id.clone());
+		let mut ctx = Context::<Test>::default();
+		for seq in 1..=5u64 {
+			ctx.store_packet_commitment(
+				(port_id.clone(), ChannelId::new(0), seq.into()),
+				vec![seq as u8; 32].into(),
+			)
+			.unwrap();
+		}
+		// commitments of another channel
+		ctx.store_packet_commitment(
+			(port_id.clone(), ChannelId::new(1), 1.into()),
+			vec![0; 32].into(),
+		)
+		.unwrap();
+		let channel_id = ChannelId::new(0).to_string().into_bytes();
+		let port_id = port_id.as_bytes().to_vec();
+
+		let page = |offset, count_total| PageRequest { offset, limit: 2, count_total };
+		let mut sequences = vec![];
+		let mut offset = Some(0);
+		while let Some(next) = offset {
+			let commitments =
+				Ibc::packet_commitments_page(channel_id.clone(), port_id.clone(), page(next, true))
+					.unwrap();
+			assert_eq!(commitments.total, Some(5));
+			assert!(commitments.items.len() <= 2);
+			sequences.extend(commitments.items.into_iter().map(|packet| packet.sequence));
+			offset = commitments.next_offset;
+		}
+		sequences.sort();
+		assert_eq!(sequences, vec![1, 2, 3, 4, 5]);
+
+		let commitments =
+			Ibc::packet_commitments_page(channel_id.clone(), port_id, page(4, false)).unwrap();
+		assert_eq!((commitments.items.len(), commitments.next_offset), (1, None));
+		assert_eq!(commitments.total, None);
+		assert!(Ibc::packet_acknowledgements_page(
+			channel_id,
+			b"invalid port".to_vec(),
+			page(0, true)
+		)
+		.is_err());
+
+		let clients = Ibc::clients_page(page(0, true));
+		assert_eq!(clients.items.len(), 1);
+		assert_eq!((clients.next_offset, clients.total), (None, Some(1)));
+		assert_eq!(Ibc::connections_page(page(0, true)).items.len(), 1);
+		let channels = Ibc::channels_page(page(1, true));
+		assert!(channels.items.is_empty());
+		assert_eq!((channels.next_offset, channels.total), (None, Some(1)));
+	})
+}
+
+#[test]
+fn ports_bound_by_runtime_modules_are_routed_to_them() {
+	use crate::{ics23::channels::Channels, weight::WeightRouter};
+	use ibc::core::{ics05_port::context::PortReader, ics26_routing::context::ModuleId};
+
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		setup_client_and_consensus_state(PortId::transfer());
+		let connection_id = ConnectionId::new(0).to_string().into_bytes();
+		let open_channel = || {
+			IbcEcho::open_channel(
+				RuntimeOrigin::root(),
+				b"echo".to_vec(),
+				connection_id.clone(),
+				b"echo".to_vec(),
+			)
+		};
+
+		// the channels of unbound ports can't be opened
+		assert_noop!(open_channel(), pallet_ibc_echo::Error::<Test>::ChannelInitError);
+		let bind_port = |origin, port: &str, module_id: &str| {
+			Ibc::bind_port(origin, port.as_bytes().to_vec(), module_id.as_bytes().to_vec())
+		};
+		// only the admin origin can bind ports
+		assert_noop!(
+			bind_port(
+				RuntimeOrigin::signed(AccountId32::new([0; 32])),
+				"echo",
+				pallet_ibc_echo::MODULE_ID
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert!(WeightRouter::<Test>::get_weight("echo").is_none());
+		assert_ok!(bind_port(RuntimeOrigin::root(), "echo", pallet_ibc_echo::MODULE_ID));
+		assert!(System::events().iter().any(|a| matches!(
+			&a.event,
+			RuntimeEvent::Ibc(Event::<Test>::PortBound { port_id, module_id })
+				if port_id == b"echo" && module_id == pallet_ibc_echo::MODULE_ID.as_bytes()
+		)));
+		let ctx = Context::<Test>::default();
+		assert_eq!(
+			ctx.lookup_module_by_port(&PortId::from_str("echo").unwrap()).unwrap(),
+			ModuleId::from_str(pallet_ibc_echo::MODULE_ID).unwrap()
+		);
+		// the callbacks of the bound ports are weighed by the weight handler of their module
+		assert!(WeightRouter::<Test>::get_weight("echo").is_some());
+
+		// the ports already bound, statically or at runtime, can't be bound again
+		for port in ["echo", "transfer", "ping"] {
+			assert_noop!(
+				bind_port(RuntimeOrigin::root(), port, pallet_ibc_echo::MODULE_ID),
+				crate::Error::<Test>::PortAlreadyBound
+			);
+		}
+		// nor can ports be bound to modules the runtime doesn't route to
+		assert_noop!(
+			bind_port(RuntimeOrigin::root(), "unrouted", "Unrouted"),
+			crate::Error::<Test>::InvalidRoute
+		);
+
+		assert_ok!(open_channel());
+		assert!(Channels::<Test>::iter().any(|(port_id, ..)| port_id == b"echo"));
+	})
+}
+
+#[test]
+fn transfers_exceeding_the_rate_limits_of_their_channel_are_rejected() {
+	use crate::{
+		ics20::rate_limit::{Direction, Quota, TransferLimit},
+		TransferLimits,
+	};
+
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let ss58_address =
+			ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		setup_client_and_consensus_state(PortId::transfer());
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				"PICA",
+			)
+			.unwrap();
+		let sender = AccountId32::new([0; 32]);
+		let _ = <<Test as Config>::NativeCurrency as Currency<
+			<Test as frame_system::Config>::AccountId,
+		>>::deposit_creating(&sender, 100000 * MILLIS);
+		let ctx = Context::<Test>::default();
+		let destination_channel = ctx
+			.channel_end(&(PortId::transfer(), ChannelId::new(0)))
+			.unwrap()
+			.counterparty()
+			.channel_id
+			.unwrap();
+		// without the service fee the amounts sent are the amounts transferred
+		assert_ok!(Ibc::add_channels_to_feeless_channel_list(
+			RuntimeOrigin::root(),
+			0,
+			destination_channel.sequence()
+		));
+
+		let limit = TransferLimit {
+			send: Some(Quota::Amount(1000 * MILLIS)),
+			receive: Some(Quota::Amount(1000 * MILLIS)),
+			period: 10,
+			escrow_cap: None,
+		};
+		assert_noop!(
+			Ibc::set_transfer_limit(
+				RuntimeOrigin::signed(sender.clone()),
+				0,
+				asset_id,
+				Some(limit.clone())
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Ibc::set_transfer_limit(
+				RuntimeOrigin::root(),
+				0,
+				asset_id,
+				Some(TransferLimit { period: 0, ..limit.clone() })
+			),
+			crate::Error::<Test>::InvalidParams
+		);
+		assert_ok!(Ibc::set_transfer_limit(RuntimeOrigin::root(), 0, asset_id, Some(limit)));
+
+		let transfer = |amount: u128| {
+			Ibc::transfer(
+				RuntimeOrigin::signed(sender.clone()),
+				TransferParams {
+					to: MultiAddress::Raw(ss58_address.as_bytes().to_vec()),
+					source_channel: 0,
+					timeout: Timeout::Offset { timestamp: Some(1000), height: Some(5) },
+				},
+				asset_id,
+				amount,
+				None,
+			)
+		};
+		assert_ok!(transfer(600 * MILLIS));
+		assert_noop!(transfer(600 * MILLIS), crate::Error::<Test>::RateLimiter);
+		// the window of the limits ended
+		frame_system::Pallet::<Test>::set_block_number(11u32);
+		assert_ok!(transfer(600 * MILLIS));
+
+		// the tokens received are rejected once their net amount exceeds the quota
+		let amt = 2000 * MILLIS;
+		let packet_data = PacketData {
+			token: Coin {
+				denom: PrefixedDenom::from_str("transfer/channel-1/PICA").unwrap(),
+				amount: ibc::applications::transfer::Amount::from_str(&format!("{amt:?}")).unwrap(),
+			},
+			sender: Signer::from_str("alice").unwrap(),
+			receiver: Signer::from_str(&ss58_address).unwrap(),
+			memo: "".to_string(),
+		};
+		let time_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+		let packet = Packet {
+			sequence: 1u64.into(),
+			source_port: PortId::transfer(),
+			source_channel: ChannelId::new(1),
+			destination_port: PortId::transfer(),
+			destination_channel: ChannelId::new(0),
+			data: serde_json::to_vec(&packet_data).unwrap(),
+			timeout_height: Height::new(2000, 5),
+			timeout_timestamp: ibc::timestamp::Timestamp::from_nanoseconds(
+				time_now as u64 + 10000000,
+			)
+			.unwrap(),
+		};
+		let msg = MsgRecvPacket {
+			packet,
+			proofs: Proofs::new(
+				vec![0u8; 32].try_into().unwrap(),
+				None,
+				None,
+				None,
+				Height::new(0, 1),
+			)
+			.unwrap(),
+			signer: Signer::from_str(MODULE_ID).unwrap(),
+		};
+		let msg = Any { type_url: msg.type_url(), value: msg.encode_vec().unwrap() };
+		assert_ok!(Ibc::deliver(RuntimeOrigin::signed(sender.clone()), vec![msg]));
+
+		let receiver_balance = <<Test as Config>::NativeCurrency as Currency<
+			<Test as frame_system::Config>::AccountId,
+		>>::free_balance(&AccountId32::new(pair.public().0));
+		assert_eq!(receiver_balance, 0);
+		assert!(System::events().iter().any(|a| matches!(
+			&a.event,
+			RuntimeEvent::Ibc(Event::<Test>::TransferRateLimited {
+				channel: 0,
+				direction: Direction::Receive,
+				amount,
+				..
+			}) if *amount == amt
+		)));
+
+		assert_ok!(Ibc::set_transfer_limit(RuntimeOrigin::root(), 0, asset_id, None));
+		assert!(TransferLimits::<Test>::get(0, asset_id).is_none());
+	})
+}
+
+#[test]
+fn nft_transfers_escrow_native_tokens_and_mint_vouchers_of_received_ones() {
+	use crate::{
+		ics721::{class_hash, escrow_account, port_id, NftTransfer, NonFungibleTokenPacketData},
+		weight::WeightRouter,
+	};
+	use ibc::core::ics26_routing::context::{Module, ModuleOutputBuilder};
+
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		setup_client_and_consensus_state(port_id());
+		let alice = AccountId32::new([1; 32]);
+		let bob = AccountId32::new([2; 32]);
+		NftClasses::insert("punks", ("ipfs://punks".to_string(), String::new()));
+		NftTokens::insert(
+			"punks",
+			"1",
+			(alice.clone(), "ipfs://punks/1".to_string(), String::new()),
+		);
+		let transfer = |owner: &AccountId32, class_id: &str, token_id: &str| {
+			Ics721::transfer(
+				RuntimeOrigin::signed(owner.clone()),
+				class_id.as_bytes().to_vec(),
+				vec![token_id.as_bytes().to_vec()],
+				TransferParams {
+					to: MultiAddress::Raw(b"cosmos1receiver".to_vec()),
+					source_channel: 0,
+					timeout: Timeout::Offset { timestamp: Some(1000), height: Some(5) },
+				},
+				vec![],
+			)
+		};
+		let receive = |sequence: u64, class_id: &str, token_id: &str| {
+			let data = NonFungibleTokenPacketData {
+				class_id: class_id.to_string(),
+				token_ids: vec![token_id.to_string()],
+				sender: "cosmos1sender".to_string(),
+				receiver: format!("0x{}", hex::encode(bob.clone())),
+				..Default::default()
+			};
+			let time_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+			let msg = MsgRecvPacket {
+				packet: Packet {
+					sequence: sequence.into(),
+					source_port: port_id(),
+					source_channel: ChannelId::new(1),
+					destination_port: port_id(),
+					destination_channel: ChannelId::new(0),
+					data: serde_json::to_vec(&data).unwrap(),
+					timeout_height: Height::new(2000, 5),
+					timeout_timestamp: ibc::timestamp::Timestamp::from_nanoseconds(
+						time_now as u64 + 10000000,
+					)
+					.unwrap(),
+				},
+				proofs: Proofs::new(
+					vec![0u8; 32].try_into().unwrap(),
+					None,
+					None,
+					None,
+					Height::new(0, 1),
+				)
+				.unwrap(),
+				signer: Signer::from_str(MODULE_ID).unwrap(),
+			};
+			let msg = Any { type_url: msg.type_url(), value: msg.encode_vec().unwrap() };
+			assert_ok!(Ibc::deliver(RuntimeOrigin::signed(bob.clone()), vec![msg]));
+		};
+
+		// the tokens of the classes of this chain are escrowed
+		assert_noop!(transfer(&bob, "punks", "1"), crate::ics721::Error::<Test>::TokenNotOwned);
+		assert_ok!(transfer(&alice, "punks", "1"));
+		let escrow = escrow_account::<Test>(&ChannelId::new(0)).unwrap();
+		assert_eq!(NftTokens::get("punks", "1").unwrap().0, escrow);
+		let sent = serde_json::from_slice::<NonFungibleTokenPacketData>(
+			&Packet::from(
+				Ibc::get_send_packet_info(
+					b"channel-0".to_vec(),
+					port_id().as_bytes().to_vec(),
+					vec![1],
+				)
+				.unwrap()[0]
+					.clone(),
+			)
+			.data,
+		)
+		.unwrap();
+		assert_eq!(sent.class_uri, "ipfs://punks");
+		assert_eq!(sent.token_uris, vec!["ipfs://punks/1".to_string()]);
+
+		// and released when they are sent back
+		receive(1, "nft-transfer/channel-1/punks", "1");
+		assert_eq!(NftTokens::get("punks", "1").unwrap().0, bob);
+
+		// the tokens of other chains are minted in voucher classes
+		receive(2, "wizards", "7");
+		let voucher_class = "nft-transfer/channel-0/wizards";
+		assert_eq!(NftTokens::get(voucher_class, "7").unwrap().0, bob);
+		assert_eq!(Ics721::class_trace(class_hash(voucher_class)).unwrap(), voucher_class);
+		assert!(System::events().iter().any(|a| matches!(
+			&a.event,
+			RuntimeEvent::Ics721(crate::ics721::Event::NftReceived { to, class_id, .. })
+				if to == &bob && class_id == voucher_class.as_bytes()
+		)));
+
+		// the vouchers sent back are burnt, and minted back if the transfer times out
+		assert_ok!(transfer(&bob, voucher_class, "7"));
+		assert!(!NftTokens::contains_key(voucher_class, "7"));
+		let mut sent_packet = Packet::from(
+			Ibc::get_send_packet_info(
+				b"channel-0".to_vec(),
+				port_id().as_bytes().to_vec(),
+				vec![2],
+			)
+			.unwrap()[0]
+				.clone(),
+		);
+		NftTransfer::<Test>::default()
+			.on_timeout_packet(
+				&Context::<Test>::default(),
+				&mut ModuleOutputBuilder::new(),
+				&mut sent_packet,
+				&Signer::from_str(MODULE_ID).unwrap(),
+			)
+			.unwrap();
+		assert_eq!(NftTokens::get(voucher_class, "7").unwrap().0, bob);
+		assert!(System::events().iter().any(|a| matches!(
+			&a.event,
+			RuntimeEvent::Ics721(crate::ics721::Event::NftTransferRefunded { to, sequence: 2, .. })
+				if to == &bob
+		)));
+
+		// the callbacks of the module are weighed by its weight handler
+		assert!(WeightRouter::<Test>::get_weight(crate::ics721::PORT_ID).is_some());
+	})
+}
+
+#[test]
+fn storage_is_migrated_to_the_current_version_on_runtime_upgrade() {
+	use crate::{migrations::STORAGE_VERSION, ConsensusStateHeights, ConsensusStatesLimit};
+	use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+		let client_key = client_id.as_bytes().to_vec();
+		let mut ctx = Context::<Test>::default();
+		let mock_cs_state = MockConsensusState::new(MockHeader::default());
+		for i in 1..=20u64 {
+			let height = Height::new(0, i);
+			ctx.store_consensus_state(
+				client_id.clone(),
+				height,
+				AnyConsensusState::Mock(mock_cs_state.clone()),
+			)
+			.unwrap();
+			ctx.store_update_time(client_id.clone(), height, ctx.host_timestamp()).unwrap();
+			ctx.store_update_height(client_id.clone(), height, ctx.host_height()).unwrap();
+		}
+		// the heights of the consensus states weren't recorded before version 1
+		ConsensusStateHeights::<Test>::remove(&client_key);
+		ConsensusStatesLimit::<Test>::insert(&client_key, 10);
+		StorageVersion::new(0).put::<Ibc>();
+
+		<Ibc as OnRuntimeUpgrade>::on_runtime_upgrade();
+		assert_eq!(Ibc::on_chain_storage_version(), STORAGE_VERSION);
+		let heights = ConsensusStateHeights::<Test>::get(&client_key);
+		assert_eq!(heights.len(), 10);
+		assert_eq!(heights.iter().next(), Some(&Height::new(0, 11)));
+		assert!(ctx.consensus_state(&client_id, Height::new(0, 10)).is_err());
+		assert!(ctx.consensus_state(&client_id, Height::new(0, 11)).is_ok());
+
+		// the migrations up to the on-chain version aren't executed again
+		ConsensusStateHeights::<Test>::remove(&client_key);
+		<Ibc as OnRuntimeUpgrade>::on_runtime_upgrade();
+		assert!(ConsensusStateHeights::<Test>::get(&client_key).is_empty());
+	})
+}
+
+#[test]
+fn storage_migrations_are_executed_over_several_blocks_within_their_weight() {
+	use crate::{
+		migrations::{self, STORAGE_VERSION},
+		ConsensusStateHeights, ConsensusStatesLimit, MigrationCursor,
+	};
+	use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+		let client_key = client_id.as_bytes().to_vec();
+		let mut ctx = Context::<Test>::default();
+		let mock_cs_state = MockConsensusState::new(MockHeader::default());
+		for i in 1..=20u64 {
+			let height = Height::new(0, i);
+			ctx.store_consensus_state(
+				client_id.clone(),
+				height,
+				AnyConsensusState::Mock(mock_cs_state.clone()),
+			)
+			.unwrap();
+			ctx.store_update_time(client_id.clone(), height, ctx.host_timestamp()).unwrap();
+			ctx.store_update_height(client_id.clone(), height, ctx.host_height()).unwrap();
+		}
+		ConsensusStateHeights::<Test>::remove(&client_key);
+		ConsensusStatesLimit::<Test>::insert(&client_key, 10);
+		StorageVersion::new(0).put::<Ibc>();
+
+		// enough for a few consensus states per block
+		let max_weight = Weight::from_parts(1_000_000_000, u64::MAX);
+		let mut steps = 0;
+		while Ibc::on_chain_storage_version() != STORAGE_VERSION {
+			assert!(!migrations::migrate::<Test, ()>(max_weight).any_gt(max_weight));
+			steps += 1;
+			assert!(steps < 100, "the migration makes no progress");
+			if Ibc::on_chain_storage_version() != STORAGE_VERSION {
+				assert!(MigrationCursor::<Test>::get().is_some());
+			}
+		}
+		assert!(steps > 1);
+		assert!(MigrationCursor::<Test>::get().is_none());
+		let heights = ConsensusStateHeights::<Test>::get(&client_key);
+		assert_eq!(heights.len(), 10);
+		assert_eq!(heights.iter().next(), Some(&Height::new(0, 11)));
+		assert!(ctx.consensus_state(&client_id, Height::new(0, 10)).is_err());
+		assert!(ctx.consensus_state(&client_id, Height::new(0, 11)).is_ok());
+	})
+}
+
+#[test]
+fn receipts_of_timed_out_packets_are_pruned_after_the_retention_period() {
+	use crate::{
+		ics23::{acknowledgements::Acknowledgements, receipts::PacketReceipt},
+		AcknowledgementCounter, PacketReceiptCounter, PacketRetentionPeriod, RetainedPackets,
+	};
+	use ibc::core::ics04_channel::packet::Receipt;
+
+	new_test_ext().execute_with(|| {
+		let channel_id = ChannelId::new(0);
+		let port_id = PortId::transfer();
+		let mut ctx = Context::<Test>::default();
+		let revision = ctx.host_height().revision_number;
+		ctx.store_channel((port_id.clone(), channel_id), &ChannelEnd::default())
+			.unwrap();
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		assert_noop!(
+			Ibc::set_packet_retention_period(RuntimeOrigin::root(), Some(0)),
+			crate::Error::<Test>::InvalidParams
+		);
+		assert_ok!(Ibc::set_packet_retention_period(RuntimeOrigin::root(), Some(2)));
+		assert_eq!(PacketRetentionPeriod::<Test>::get(), Some(2));
+
+		// the packets time out at the blocks 3, 5 and 4, the 4th has no timeout
+		let timeouts =
+			[Height::new(revision, 3), Height::new(revision, 5), Height::new(revision, 4)];
+		for sequence in 1..=4u64 {
+			let key = (port_id.clone(), channel_id, sequence.into());
+			let packet = Packet {
+				sequence: sequence.into(),
+				source_port: port_id.clone(),
+				source_channel: ChannelId::new(1),
+				destination_port: port_id.clone(),
+				destination_channel: channel_id,
+				data: "hello".as_bytes().to_vec(),
+				timeout_height: timeouts.get(sequence as usize - 1).copied().unwrap_or_default(),
+				timeout_timestamp: Default::default(),
+			};
+			ctx.store_packet_receipt(key.clone(), Receipt::Ok).unwrap();
+			ctx.store_recv_packet(key.clone(), packet).unwrap();
+			ctx.store_packet_acknowledgement(key, "commitment".as_bytes().to_vec().into())
+				.unwrap();
+		}
+		let key = |sequence: u64| (port_id.clone(), channel_id, sequence.into());
+		// the packet without a timeout is never pruned
+		assert_eq!(RetainedPackets::<Test>::get(1).len(), 3);
+
+		// nothing is pruned within the period
+		Ibc::on_idle(2, Weight::MAX);
+		assert_eq!(PacketReceiptCounter::<Test>::get(), 4);
+
+		// the receipts of the timed out packets are pruned
+		frame_system::Pallet::<Test>::set_block_number(3u32);
+		Ibc::on_idle(3, Weight::MAX);
+		assert!(!PacketReceipt::<Test>::contains_key(key(1)));
+		assert!((2..=4).all(|sequence| PacketReceipt::<Test>::contains_key(key(sequence))));
+		assert_eq!(PacketReceiptCounter::<Test>::get(), 3);
+		// the pruning of the packets pending a timeout is postponed
+		assert_eq!(RetainedPackets::<Test>::get(3).len(), 2);
+
+		frame_system::Pallet::<Test>::set_block_number(5u32);
+		Ibc::on_idle(5, Weight::MAX);
+		assert!((1..=3).all(|sequence| !PacketReceipt::<Test>::contains_key(key(sequence))));
+		// the receipts of the packets without a timeout guard against their replay
+		assert!(PacketReceipt::<Test>::contains_key(key(4)));
+		assert_eq!(PacketReceiptCounter::<Test>::get(), 1);
+		assert!(RetainedPackets::<Test>::iter().next().is_none());
+
+		// the acknowledgements are kept, as they may not have been relayed yet
+		assert!((1..=4).all(|sequence| Acknowledgements::<Test>::contains_key(key(sequence))));
+		assert_eq!(AcknowledgementCounter::<Test>::get(), 4);
+	})
+}
+
+#[test]
+fn late_acknowledgements_can_be_relayed_after_the_retention_period() {
+	use crate::{ics23::receipts::PacketReceipt, RetainedPackets};
+	use ibc::core::ics04_channel::packet::Receipt;
+
+	new_test_ext().execute_with(|| {
+		let channel_id = ChannelId::new(0);
+		let port_id = PortId::transfer();
+		let mut ctx = Context::<Test>::default();
+		let revision = ctx.host_height().revision_number;
+		ctx.store_channel((port_id.clone(), channel_id), &ChannelEnd::default())
+			.unwrap();
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		assert_ok!(Ibc::set_packet_retention_period(RuntimeOrigin::root(), Some(2)));
+
+		let key = (port_id.clone(), channel_id, 1.into());
+		let packet = Packet {
+			sequence: 1.into(),
+			source_port: port_id.clone(),
+			source_channel: ChannelId::new(1),
+			destination_port: port_id.clone(),
+			destination_channel: channel_id,
+			data: "hello".as_bytes().to_vec(),
+			timeout_height: Height::new(revision, 2),
+			timeout_timestamp: Default::default(),
+		};
+		ctx.store_packet_receipt(key.clone(), Receipt::Ok).unwrap();
+		ctx.store_recv_packet(key.clone(), packet).unwrap();
+
+		// the packet is acknowledged asynchronously, after it has timed out and its receipt was
+		// pruned
+		frame_system::Pallet::<Test>::set_block_number(3u32);
+		Ibc::on_idle(3, Weight::MAX);
+		assert!(!PacketReceipt::<Test>::contains_key(key.clone()));
+		assert!(RetainedPackets::<Test>::iter().next().is_none());
+		ctx.store_packet_acknowledgement(key.clone(), "commitment".as_bytes().to_vec().into())
+			.unwrap();
+
+		// the acknowledgement is still there for the relayers to prove long after the period
+		frame_system::Pallet::<Test>::set_block_number(100u32);
+		Ibc::on_idle(100, Weight::MAX);
+		assert_eq!(
+			ctx.get_packet_acknowledgement(&key).unwrap(),
+			"commitment".as_bytes().to_vec().into()
+		);
+	})
+}
@@ -1,11 +1,14 @@
 use super::*;
-use crate::{light_clients::AnyClientMessage, routing::Context};
+use crate::{
+	light_clients::AnyClientMessage,
+	routing::{Context, ModuleRouter},
+};
 use core::marker::PhantomData;
 use frame_support::{pallet_prelude::Weight, weights::constants::WEIGHT_REF_TIME_PER_MILLIS};
 use grandpa_client_primitives::justification::GrandpaJustification;
 use ibc::core::{
 	ics02_client::msgs::ClientMsg,
-	ics03_connection::{context::ConnectionReader, msgs::ConnectionMsg},
+	ics03_connection::msgs::ConnectionMsg,
 	ics04_channel::msgs::{ChannelMsg, PacketMsg},
 	ics24_host::identifier::ClientId,
 	ics26_routing::msgs::Ics26Envelope,
@@ -41,7 +44,23 @@ pub trait WeightInfo {
 	fn on_acknowledgement_packet() -> Weight;
 	fn on_timeout_packet() -> Weight;
 	fn update_grandpa_client(i: u32, j: u32) -> Weight;
+	fn upgrade_grandpa_client() -> Weight;
 	fn packet_cleanup(i: u32) -> Weight;
+	fn upgrade_client() -> Weight;
+	fn freeze_client() -> Weight;
+	fn unfreeze_client() -> Weight;
+	fn recover_client(i: u32) -> Weight;
+	fn add_channels_to_feeless_channel_list() -> Weight;
+	fn remove_channels_from_feeless_channel_list() -> Weight;
+	fn set_child_storage() -> Weight;
+	fn substitute_client_state() -> Weight;
+	fn set_unsigned_client_update_interval() -> Weight;
+	fn set_denom_asset_id() -> Weight;
+	fn set_consensus_states_limit(i: u32) -> Weight;
+	fn set_transfer_limit() -> Weight;
+	fn set_packet_retention_period() -> Weight;
+	fn prune_retained_packets(i: u32) -> Weight;
+	fn bind_port() -> Weight;
 }
 
 impl WeightInfo for () {
@@ -149,38 +168,108 @@ impl WeightInfo for () {
 		Weight::default()
 	}
 
+	fn upgrade_grandpa_client() -> Weight {
+		Weight::default()
+	}
+
 	fn packet_cleanup(_i: u32) -> Weight {
 		Weight::default()
 	}
+
+	fn upgrade_client() -> Weight {
+		Weight::default()
+	}
+
+	fn freeze_client() -> Weight {
+		Weight::default()
+	}
+
+	fn unfreeze_client() -> Weight {
+		Weight::default()
+	}
+
+	fn recover_client(_i: u32) -> Weight {
+		Weight::default()
+	}
+
+	fn add_channels_to_feeless_channel_list() -> Weight {
+		Weight::default()
+	}
+
+	fn remove_channels_from_feeless_channel_list() -> Weight {
+		Weight::default()
+	}
+
+	fn set_child_storage() -> Weight {
+		Weight::default()
+	}
+
+	fn substitute_client_state() -> Weight {
+		Weight::default()
+	}
+
+	fn set_unsigned_client_update_interval() -> Weight {
+		Weight::default()
+	}
+
+	fn set_denom_asset_id() -> Weight {
+		Weight::default()
+	}
+
+	fn set_consensus_states_limit(_i: u32) -> Weight {
+		Weight::default()
+	}
+
+	fn set_transfer_limit() -> Weight {
+		Weight::default()
+	}
+
+	fn set_packet_retention_period() -> Weight {
+		Weight::default()
+	}
+
+	fn prune_retained_packets(_i: u32) -> Weight {
+		Weight::default()
+	}
+
+	fn bind_port() -> Weight {
+		Weight::default()
+	}
 }
 
-pub struct WeightRouter<T: Config>(PhantomData<T>);
+pub struct WeightRouter<T: Config<I>, I: 'static = ()>(PhantomData<(T, I)>);
 
-impl<T: Config> WeightRouter<T> {
+impl<T: Config<I>, I: 'static> WeightRouter<T, I> {
 	pub fn get_weight(port_id: &str) -> Option<Box<dyn CallbackWeight>> {
 		match port_id {
 			#[allow(clippy::box_default)]
-			ibc::applications::transfer::PORT_ID_STR => Some(Box::new(ics20::WeightHandler::<T>::default())),
-			_ => None,
+			ibc::applications::transfer::PORT_ID_STR =>
+				Some(Box::new(ics20::WeightHandler::<T, I>::default())),
+			// the custom routes and the ports bound at runtime are weighed by their module
+			_ => Pallet::<T, I>::module_of_port(port_id)
+				.and_then(|module_id| T::Router::weight_handler(&module_id)),
 		}
 	}
 }
 
 /// Get client id for a port and channel combination
-pub fn channel_client<T: Config>(channel_id: &[u8], port_id: &[u8]) -> Result<ClientId, Error<T>> {
-	for (connection_id, channels) in ChannelsConnection::<T>::iter() {
+pub fn channel_client<T: Config<I>, I: 'static>(
+	channel_id: &[u8],
+	port_id: &[u8],
+) -> Result<ClientId, Error<T, I>> {
+	for (connection_id, channels) in ChannelsConnection::<T, I>::iter() {
 		if channels.contains(&(port_id.to_vec(), channel_id.to_vec())) {
-			if let Some((client_id, ..)) = ConnectionClient::<T>::iter()
+			if let Some((client_id, ..)) = ConnectionClient::<T, I>::iter()
 				.find(|(.., connection_ids)| connection_ids.contains(&connection_id))
 			{
-				return client_id_from_bytes(client_id).map_err(|_| Error::<T>::Other)
+				return client_id_from_bytes(client_id).map_err(|_| Error::<T, I>::Other)
 			}
 		}
 	}
-	Err(Error::<T>::Other)
+	Err(Error::<T, I>::Other)
 }
 
-pub(crate) fn deliver<T: Config + Send + Sync>(msgs: &[Any]) -> Weight
+pub(crate) fn deliver<T: Config<I> + Send + Sync, I: 'static>(msgs: &[Any]) -> Weight
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 {
@@ -188,7 +277,7 @@ where
 		.filter_map(|msg| {
 			let type_url = msg.type_url.clone();
 			let msg = ibc_proto::google::protobuf::Any { type_url, value: msg.value.clone() };
-			let msg: Option<Ics26Envelope<Context<T>>> = msg.try_into().ok();
+			let msg: Option<Ics26Envelope<Context<T, I>>> = msg.try_into().ok();
 			msg
 		})
 		.fold(Weight::default(), |acc, msg| {
@@ -196,7 +285,7 @@ where
 			// Add benchmarked weight for module callback
 			let temp = match msg {
 				Ics26Envelope::Ics2Msg(msgs) => match msgs {
-					ClientMsg::CreateClient(_) => <T as Config>::WeightInfo::create_client(),
+					ClientMsg::CreateClient(_) => <T as Config<I>>::WeightInfo::create_client(),
 					ClientMsg::UpdateClient(msg) => {
 						let client_type = msg
 							.client_id
@@ -207,11 +296,11 @@ where
 							Some(ty) if ty.contains("tendermint") => match msg.client_message {
 								AnyClientMessage::Tendermint(client_message) => match client_message {
 									ics07_tendermint::client_message::ClientMessage::Header(header) => {
-										<T as Config>::WeightInfo::update_tendermint_client(header.signed_header.commit.signatures.len() as u32)
+										<T as Config<I>>::WeightInfo::update_tendermint_client(header.signed_header.commit.signatures.len() as u32)
 									}
 									ics07_tendermint::client_message::ClientMessage::Misbehaviour(misbehaviour) => {
-										<T as Config>::WeightInfo::update_tendermint_client(misbehaviour.header1.signed_header.commit.signatures.len() as u32).
-											saturating_add(<T as Config>::WeightInfo::update_tendermint_client(misbehaviour.header2.signed_header.commit.signatures.len() as u32))
+										<T as Config<I>>::WeightInfo::update_tendermint_client(misbehaviour.header1.signed_header.commit.signatures.len() as u32).
+											saturating_add(<T as Config<I>>::WeightInfo::update_tendermint_client(misbehaviour.header2.signed_header.commit.signatures.len() as u32))
 									}
 
 								}
@@ -225,7 +314,7 @@ where
 												&mut &*header.finality_proof.justification,
 											)
 											.expect("Justification should be valid");
-										<T as Config>::WeightInfo::update_grandpa_client(
+										<T as Config<I>>::WeightInfo::update_grandpa_client(
 											justification.commit.precommits.len() as u32,
 											header.finality_proof.unknown_headers.len() as u32,
 										)
@@ -243,10 +332,10 @@ where
 											)
 												.expect("Justification should be valid");
 
-										<T as Config>::WeightInfo::update_grandpa_client(
+										<T as Config<I>>::WeightInfo::update_grandpa_client(
 											justification_a.commit.precommits.len() as u32,
 											misbehaviour.first_finality_proof.unknown_headers.len() as u32,
-										).saturating_add(<T as Config>::WeightInfo::update_grandpa_client(
+										).saturating_add(<T as Config<I>>::WeightInfo::update_grandpa_client(
 											justification_b.commit.precommits.len() as u32,
 											misbehaviour.second_finality_proof.unknown_headers.len() as u32,
 										))
@@ -257,315 +346,115 @@ where
 							_ => Weight::default(),
 						}
 					},
-					ClientMsg::UpgradeClient(_) => Weight::default(),
+					// Only the grandpa client verifies upgrades, the other clients reject them
+					// before verifying any proof
+					ClientMsg::UpgradeClient(_) =>
+						<T as Config<I>>::WeightInfo::upgrade_grandpa_client(),
 				},
+				// The proofs of the connection, channel and packet messages are verified with the
+				// weights benchmarked for the tendermint client, which the other clients are charged
+				// too until they have their own benchmarks
 				Ics26Envelope::Ics3Msg(msgs) => match msgs {
 					ConnectionMsg::ConnectionOpenInit(_) =>
-						<T as Config>::WeightInfo::conn_open_init(),
-					ConnectionMsg::ConnectionOpenTry(msg) => {
-						let client_type = msg
-							.client_id
-							.as_str()
-							.rsplit_once('-')
-							.map(|(client_type_str, ..)| client_type_str);
-						match client_type {
-							Some(ty) if ty.contains("tendermint") =>
-								<T as Config>::WeightInfo::conn_try_open_tendermint(),
-							_ => Weight::default(),
-						}
-					},
-					ConnectionMsg::ConnectionOpenAck(msg) => {
-						let connection_id = msg.connection_id;
-						let ctx = routing::Context::<T>::new();
-						let connection_end = ctx.connection_end(&connection_id).unwrap_or_default();
-						let client_type = connection_end
-							.client_id()
-							.as_str()
-							.rsplit_once('-')
-							.map(|(client_type_str, ..)| client_type_str);
-						match client_type {
-							Some(ty) if ty.contains("tendermint") =>
-								<T as Config>::WeightInfo::conn_open_ack_tendermint(),
-							_ => Weight::default(),
-						}
-					},
-					ConnectionMsg::ConnectionOpenConfirm(msg) => {
-						let connection_id = msg.connection_id;
-						let ctx = routing::Context::<T>::new();
-						let connection_end = ctx.connection_end(&connection_id).unwrap_or_default();
-						let client_type = connection_end
-							.client_id()
-							.as_str()
-							.rsplit_once('-')
-							.map(|(client_type_str, ..)| client_type_str);
-						match client_type {
-							Some(ty) if ty.contains("tendermint") =>
-								<T as Config>::WeightInfo::conn_open_confirm_tendermint(),
-							_ => Weight::default(),
-						}
-					},
+						<T as Config<I>>::WeightInfo::conn_open_init(),
+					ConnectionMsg::ConnectionOpenTry(_) =>
+						<T as Config<I>>::WeightInfo::conn_try_open_tendermint(),
+					ConnectionMsg::ConnectionOpenAck(_) =>
+						<T as Config<I>>::WeightInfo::conn_open_ack_tendermint(),
+					ConnectionMsg::ConnectionOpenConfirm(_) =>
+						<T as Config<I>>::WeightInfo::conn_open_confirm_tendermint(),
 				},
 				Ics26Envelope::Ics4ChannelMsg(msgs) => match msgs {
 					ChannelMsg::ChannelOpenInit(channel_msg) => {
-						let cb = WeightRouter::<T>::get_weight(channel_msg.port_id.as_str())
+						let cb = WeightRouter::<T, I>::get_weight(channel_msg.port_id.as_str())
 							.unwrap_or_else(|| Box::new(()));
-						let cb_weight = cb.on_chan_open_init();
-						let lc_verification_weight =
-							match channel_msg.channel.connection_hops.get(0) {
-								Some(connection_id) => {
-									let ctx = routing::Context::<T>::new();
-									let connection_end =
-										ctx.connection_end(connection_id).unwrap_or_default();
-									let client_type = connection_end
-										.client_id()
-										.as_str()
-										.rsplit_once('-')
-										.map(|(client_type_str, ..)| client_type_str);
-									match client_type {
-										Some(ty) if ty.contains("tendermint") =>
-											<T as Config>::WeightInfo::channel_open_init(),
-										_ => Weight::default(),
-									}
-								},
-								None => Weight::default(),
-							};
-						cb_weight.saturating_add(lc_verification_weight)
+						cb.on_chan_open_init()
+							.saturating_add(<T as Config<I>>::WeightInfo::channel_open_init())
 					},
 					ChannelMsg::ChannelOpenTry(channel_msg) => {
-						let cb = WeightRouter::<T>::get_weight(channel_msg.port_id.as_str())
+						let cb = WeightRouter::<T, I>::get_weight(channel_msg.port_id.as_str())
 							.unwrap_or_else(|| Box::new(()));
-						let cb_weight = cb.on_chan_open_try();
-						let lc_verification_weight =
-							match channel_msg.channel.connection_hops.get(0) {
-								Some(connection_id) => {
-									let ctx = routing::Context::<T>::new();
-									let connection_end =
-										ctx.connection_end(connection_id).unwrap_or_default();
-									let client_type = connection_end
-										.client_id()
-										.as_str()
-										.rsplit_once('-')
-										.map(|(client_type_str, ..)| client_type_str);
-									match client_type {
-										Some(ty) if ty.contains("tendermint") =>
-											<T as Config>::WeightInfo::channel_open_try_tendermint(),
-										_ => Weight::default(),
-									}
-								},
-								None => Weight::default(),
-							};
-						cb_weight.saturating_add(lc_verification_weight)
+						cb.on_chan_open_try().saturating_add(
+							<T as Config<I>>::WeightInfo::channel_open_try_tendermint(),
+						)
 					},
 					ChannelMsg::ChannelOpenAck(channel_msg) => {
-						let cb = WeightRouter::<T>::get_weight(channel_msg.port_id.as_str())
+						let cb = WeightRouter::<T, I>::get_weight(channel_msg.port_id.as_str())
 							.unwrap_or_else(|| Box::new(()));
-						let cb_weight =
-							cb.on_chan_open_ack(&channel_msg.port_id, &channel_msg.channel_id);
-						let lc_verification_weight = match channel_client::<T>(
-							channel_msg.port_id.as_bytes(),
-							channel_msg.channel_id.to_string().as_bytes(),
-						) {
-							Ok(client_id) => {
-								let client_type = client_id
-									.as_str()
-									.rsplit_once('-')
-									.map(|(client_type_str, ..)| client_type_str);
-								match client_type {
-									Some(ty) if ty.contains("tendermint") =>
-										<T as Config>::WeightInfo::channel_open_ack_tendermint(),
-									_ => Weight::default(),
-								}
-							},
-							Err(_) => Weight::default(),
-						};
-						cb_weight.saturating_add(lc_verification_weight)
+						cb.on_chan_open_ack(&channel_msg.port_id, &channel_msg.channel_id)
+							.saturating_add(
+								<T as Config<I>>::WeightInfo::channel_open_ack_tendermint(),
+							)
 					},
 					ChannelMsg::ChannelOpenConfirm(channel_msg) => {
-						let cb = WeightRouter::<T>::get_weight(channel_msg.port_id.as_str())
+						let cb = WeightRouter::<T, I>::get_weight(channel_msg.port_id.as_str())
 							.unwrap_or_else(|| Box::new(()));
-						let cb_weight =
-							cb.on_chan_open_confirm(&channel_msg.port_id, &channel_msg.channel_id);
-						let lc_verification_weight = match channel_client::<T>(
-							channel_msg.port_id.as_bytes(),
-							channel_msg.channel_id.to_string().as_bytes(),
-						) {
-							Ok(client_id) => {
-								let client_type = client_id
-									.as_str()
-									.rsplit_once('-')
-									.map(|(client_type_str, ..)| client_type_str);
-								match client_type {
-									Some(ty) if ty.contains("tendermint") =>
-										<T as Config>::WeightInfo::channel_open_confirm_tendermint(),
-									_ => Weight::default(),
-								}
-							},
-							Err(_) => Weight::default(),
-						};
-						cb_weight.saturating_add(lc_verification_weight)
+						cb.on_chan_open_confirm(&channel_msg.port_id, &channel_msg.channel_id)
+							.saturating_add(
+								<T as Config<I>>::WeightInfo::channel_open_confirm_tendermint(),
+							)
 					},
 					ChannelMsg::ChannelCloseInit(channel_msg) => {
-						let cb = WeightRouter::<T>::get_weight(channel_msg.port_id.as_str())
+						let cb = WeightRouter::<T, I>::get_weight(channel_msg.port_id.as_str())
 							.unwrap_or_else(|| Box::new(()));
-						let cb_weight =
-							cb.on_chan_close_init(&channel_msg.port_id, &channel_msg.channel_id);
-						let lc_verification_weight = match channel_client::<T>(
-							channel_msg.port_id.as_bytes(),
-							channel_msg.channel_id.to_string().as_bytes(),
-						) {
-							Ok(client_id) => {
-								let client_type = client_id
-									.as_str()
-									.rsplit_once('-')
-									.map(|(client_type_str, ..)| client_type_str);
-								match client_type {
-									Some(ty) if ty.contains("tendermint") =>
-										<T as Config>::WeightInfo::channel_close_init(),
-									_ => Weight::default(),
-								}
-							},
-							Err(_) => Weight::default(),
-						};
-						cb_weight.saturating_add(lc_verification_weight)
+						cb.on_chan_close_init(&channel_msg.port_id, &channel_msg.channel_id)
+							.saturating_add(<T as Config<I>>::WeightInfo::channel_close_init())
 					},
 					ChannelMsg::ChannelCloseConfirm(channel_msg) => {
-						let cb = WeightRouter::<T>::get_weight(channel_msg.port_id.as_str())
+						let cb = WeightRouter::<T, I>::get_weight(channel_msg.port_id.as_str())
 							.unwrap_or_else(|| Box::new(()));
-						let cb_weight =
-							cb.on_chan_close_confirm(&channel_msg.port_id, &channel_msg.channel_id);
-						let lc_verification_weight = match channel_client::<T>(
-							channel_msg.port_id.as_bytes(),
-							channel_msg.channel_id.to_string().as_bytes(),
-						) {
-							Ok(client_id) => {
-								let client_type = client_id
-									.as_str()
-									.rsplit_once('-')
-									.map(|(client_type_str, ..)| client_type_str);
-								match client_type {
-									Some(ty) if ty.contains("tendermint") =>
-										<T as Config>::WeightInfo::channel_close_confirm_tendermint(
-										),
-									_ => Weight::default(),
-								}
-							},
-							Err(_) => Weight::default(),
-						};
-						cb_weight.saturating_add(lc_verification_weight)
+						cb.on_chan_close_confirm(&channel_msg.port_id, &channel_msg.channel_id)
+							.saturating_add(
+								<T as Config<I>>::WeightInfo::channel_close_confirm_tendermint(),
+							)
 					},
 				},
 				Ics26Envelope::Ics4PacketMsg(msgs) => match msgs {
 					PacketMsg::RecvPacket(packet_msg) => {
-						let cb = WeightRouter::<T>::get_weight(
+						let cb = WeightRouter::<T, I>::get_weight(
 							packet_msg.packet.destination_port.as_str(),
 						)
 						.unwrap_or_else(|| Box::new(()));
-						let cb_weight = cb.on_recv_packet(&packet_msg.packet);
-						let lc_verification_weight = match channel_client::<T>(
-							packet_msg.packet.destination_port.as_bytes(),
-							packet_msg.packet.destination_channel.to_string().as_bytes(),
-						) {
-							Ok(client_id) => {
-								let client_type = client_id
-									.as_str()
-									.rsplit_once('-')
-									.map(|(client_type_str, ..)| client_type_str);
-								match client_type {
-									Some(ty) if ty.contains("tendermint") =>
-										<T as Config>::WeightInfo::recv_packet_tendermint(
-											packet_msg.packet.data.len() as u32,
-										),
-									_ => Weight::default(),
-								}
-							},
-							Err(_) => Weight::default(),
-						};
-						cb_weight.saturating_add(lc_verification_weight)
+						cb.on_recv_packet(&packet_msg.packet).saturating_add(
+							<T as Config<I>>::WeightInfo::recv_packet_tendermint(
+								packet_msg.packet.data.len() as u32,
+							),
+						)
 					},
+					// the acknowledged and timed out packets were sent by the module of their
+					// source port
 					PacketMsg::AckPacket(packet_msg) => {
-						let cb = WeightRouter::<T>::get_weight(
-							packet_msg.packet.destination_port.as_str(),
-						)
-						.unwrap_or_else(|| Box::new(()));
-						let cb_weight = cb.on_acknowledgement_packet(
+						let cb =
+							WeightRouter::<T, I>::get_weight(packet_msg.packet.source_port.as_str())
+								.unwrap_or_else(|| Box::new(()));
+						cb.on_acknowledgement_packet(
 							&packet_msg.packet,
 							&packet_msg.acknowledgement,
-						);
-						let lc_verification_weight = match channel_client::<T>(
-							packet_msg.packet.destination_port.as_bytes(),
-							packet_msg.packet.destination_channel.to_string().as_bytes(),
-						) {
-							Ok(client_id) => {
-								let client_type = client_id
-									.as_str()
-									.rsplit_once('-')
-									.map(|(client_type_str, ..)| client_type_str);
-								match client_type {
-									Some(ty) if ty.contains("tendermint") =>
-										<T as Config>::WeightInfo::ack_packet_tendermint(
-											packet_msg.packet.data.len() as u32,
-											packet_msg.acknowledgement.into_bytes().len() as u32,
-										),
-									_ => Weight::default(),
-								}
-							},
-							Err(_) => Weight::default(),
-						};
-						cb_weight.saturating_add(lc_verification_weight)
+						)
+						.saturating_add(<T as Config<I>>::WeightInfo::ack_packet_tendermint(
+							packet_msg.packet.data.len() as u32,
+							packet_msg.acknowledgement.into_bytes().len() as u32,
+						))
 					},
 					PacketMsg::ToPacket(packet_msg) => {
-						let cb = WeightRouter::<T>::get_weight(
-							packet_msg.packet.destination_port.as_str(),
+						let cb =
+							WeightRouter::<T, I>::get_weight(packet_msg.packet.source_port.as_str())
+								.unwrap_or_else(|| Box::new(()));
+						cb.on_timeout_packet(&packet_msg.packet).saturating_add(
+							<T as Config<I>>::WeightInfo::timeout_packet_tendermint(
+								packet_msg.packet.data.len() as u32,
+							),
 						)
-						.unwrap_or_else(|| Box::new(()));
-						let cb_weight = cb.on_timeout_packet(&packet_msg.packet);
-						let lc_verification_weight = match channel_client::<T>(
-							packet_msg.packet.destination_port.as_bytes(),
-							packet_msg.packet.destination_channel.to_string().as_bytes(),
-						) {
-							Ok(client_id) => {
-								let client_type = client_id
-									.as_str()
-									.rsplit_once('-')
-									.map(|(client_type_str, ..)| client_type_str);
-								match client_type {
-									Some(ty) if ty.contains("tendermint") =>
-										<T as Config>::WeightInfo::timeout_packet_tendermint(
-											packet_msg.packet.data.len() as u32,
-										),
-									_ => Weight::default(),
-								}
-							},
-							Err(_) => Weight::default(),
-						};
-						cb_weight.saturating_add(lc_verification_weight)
 					},
 					PacketMsg::ToClosePacket(packet_msg) => {
-						let cb = WeightRouter::<T>::get_weight(
-							packet_msg.packet.destination_port.as_str(),
+						let cb =
+							WeightRouter::<T, I>::get_weight(packet_msg.packet.source_port.as_str())
+								.unwrap_or_else(|| Box::new(()));
+						cb.on_timeout_packet(&packet_msg.packet).saturating_add(
+							<T as Config<I>>::WeightInfo::timeout_packet_tendermint(
+								packet_msg.packet.data.len() as u32,
+							),
 						)
-						.unwrap_or_else(|| Box::new(()));
-						let cb_weight = cb.on_timeout_packet(&packet_msg.packet);
-						let lc_verification_weight = match channel_client::<T>(
-							packet_msg.packet.destination_port.as_bytes(),
-							packet_msg.packet.destination_channel.to_string().as_bytes(),
-						) {
-							Ok(client_id) => {
-								let client_type = client_id
-									.as_str()
-									.rsplit_once('-')
-									.map(|(client_type_str, ..)| client_type_str);
-								match client_type {
-									Some(ty) if ty.contains("tendermint") =>
-										<T as Config>::WeightInfo::timeout_packet_tendermint(
-											packet_msg.packet.data.len() as u32,
-										),
-									_ => Weight::default(),
-								}
-							},
-							Err(_) => Weight::default(),
-						};
-						cb_weight.saturating_add(lc_verification_weight)
 					},
 				},
 				_ => Weight::default(),
@@ -0,0 +1,474 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JSON-RPC 2.0 admin interface of a running relayer, served over HTTP `POST`.
+//!
+//! Supported methods (`chain` is the name of one of the relayed chains):
+//! - `pause_channel { chain, channel_id, port_id }`: stops relaying packets, acknowledgements and
+//!   timeouts of the channel end on `chain`.
+//! - `resume_channel { chain, channel_id, port_id }`: resumes a paused channel.
+//! - `update_client { chain }`: sends an update of `chain`'s light client to its counterparty with
+//!   the next finality event, even if the update is optional.
+//! - `flush_queue { chain }`: marks every kind of packet as undelivered on `chain`, so that the
+//!   next finality event sends the client updates needed to relay all of the pending ones.
+//! - `reload_keys { chain }`: reloads the relayer keys of `chain` from its key backend, e.g. after
+//!   keys were added to or removed from its keystore directory, and returns their accounts.
+//! - `dump_state`: returns the internal state of both chains.
+//! - `snapshot`: returns a [`RelayerSnapshot`] of both chains, as written by `hyperspace
+//!   dump-state` and read back with `--restore-state`.
+//!
+//! Requests are applied by the relay loop between finality events, see [`RelayCommand`].
+//!
+//! Every request must carry the configured `admin_token` in an `Authorization: Bearer <token>`
+//! header, and the server binds to localhost when its endpoint is only a port.
+
+use crate::RelayCommand;
+use anyhow::anyhow;
+use hyper::{
+	header::AUTHORIZATION,
+	http::StatusCode,
+	server::Server,
+	service::{make_service_fn, service_fn},
+	Body, Method, Request, Response,
+};
+use ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use primitives::{Chain, RelayerSnapshot, UndeliveredType};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+	net::{Ipv4Addr, SocketAddr},
+	str::FromStr,
+};
+use tokio::sync::{mpsc::UnboundedSender, oneshot};
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+#[derive(Deserialize)]
+struct RpcRequest {
+	#[serde(default)]
+	id: Value,
+	method: String,
+	#[serde(default)]
+	params: Value,
+}
+
+#[derive(Deserialize)]
+struct ChainParams {
+	chain: String,
+}
+
+#[derive(Deserialize)]
+struct ChannelParams {
+	chain: String,
+	channel_id: String,
+	port_id: String,
+}
+
+struct RpcError {
+	code: i64,
+	message: String,
+}
+
+impl RpcError {
+	fn new(code: i64, message: impl ToString) -> Self {
+		Self { code, message: message.to_string() }
+	}
+}
+
+type Reply = oneshot::Sender<anyhow::Result<Value>>;
+
+/// Parses the `admin_endpoint` of the config: a socket address, or a port to bind on localhost.
+pub fn admin_addr(endpoint: &str) -> anyhow::Result<SocketAddr> {
+	if let Ok(port) = endpoint.parse::<u16>() {
+		return Ok(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+	}
+	endpoint
+		.parse()
+		.map_err(|e| anyhow!("{endpoint} is neither a socket address nor a port: {e}"))
+}
+
+/// Whether the `Authorization` header of `req` carries `token`, compared in constant time.
+fn is_authorized(req: &Request<Body>, token: &str) -> bool {
+	let Some(provided) = req
+		.headers()
+		.get(AUTHORIZATION)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.strip_prefix("Bearer "))
+	else {
+		return false
+	};
+	provided.len() == token.len() &&
+		provided.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+	serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e))
+}
+
+fn channel_params(params: ChannelParams) -> Result<(String, ChannelId, PortId), RpcError> {
+	let channel_id =
+		ChannelId::from_str(&params.channel_id).map_err(|e| RpcError::new(INVALID_PARAMS, e))?;
+	let port_id =
+		PortId::from_str(&params.port_id).map_err(|e| RpcError::new(INVALID_PARAMS, e))?;
+	Ok((params.chain, channel_id, port_id))
+}
+
+/// Returns the chain named `name`.
+fn select<'a, C: Chain>(a: &'a mut C, b: &'a mut C, name: &str) -> anyhow::Result<&'a mut C> {
+	if a.name() == name {
+		Ok(a)
+	} else if b.name() == name {
+		Ok(b)
+	} else {
+		Err(anyhow!("Unknown chain {name}, expected one of {} or {}", a.name(), b.name()))
+	}
+}
+
+pub(crate) fn dump_chain_state(chain: &impl Chain) -> Value {
+	let state = chain.common_state();
+	let channels = |channels: Vec<(ChannelId, PortId)>| {
+		channels
+			.into_iter()
+			.map(|(channel_id, port_id)| format!("{port_id}/{channel_id}"))
+			.collect::<Vec<_>>()
+	};
+	json!({
+		"name": chain.name(),
+		"client_id": chain.client_id().to_string(),
+		"connection_id": chain.connection_id().map(|id| id.to_string()),
+		"channel_whitelist": channels(chain.channel_whitelist().into_iter().collect()),
+		"paused_channels": channels(state.paused_channels().into_iter().collect()),
+		"halted_channels": state
+			.halted_channels()
+			.into_iter()
+			.map(|((channel_id, port_id), reason)| (format!("{port_id}/{channel_id}"), reason))
+			.collect::<std::collections::BTreeMap<_, _>>(),
+		"relay_status": state.relay_status(),
+		"low_balance": state.has_low_balance(),
+		"signing_keys": state.keys.key_count(),
+		"low_balance_keys": (0..state.keys.key_count())
+			.filter(|&key| state.keys.has_low_balance(key))
+			.collect::<Vec<_>>(),
+		"stall_reason": state.stall_reason(),
+		"undelivered_acks": state.has_undelivered_sequences(UndeliveredType::Acks),
+		"undelivered_recvs": state.has_undelivered_sequences(UndeliveredType::Recvs),
+		"undelivered_timeouts": state.has_undelivered_sequences(UndeliveredType::Timeouts),
+		"skip_optional_client_updates": state.skip_optional_client_updates,
+		"adaptive_client_updates": state
+			.adaptive_updates
+			.lock()
+			.unwrap()
+			.as_ref()
+			.map(|scheduler| scheduler.params().clone()),
+		"max_packets_to_process": state.max_packets_to_process,
+		"skipped_stream_items": state.skipped_stream_items(),
+		"incentivized_packets": state.incentivized_packets.lock().unwrap().len(),
+		"pending_messages": state.pending_messages.lock().unwrap().len(),
+		"submission_queue_depth": state.keys.queue_depth(),
+		"pipeline_counters": state.pipeline_counters.lock().unwrap().summary(),
+		"pipeline_errors": state.pipeline_counters.lock().unwrap().errors(),
+		"dry_run": state.dry_run,
+	})
+}
+
+/// Translates an RPC call into a [`RelayCommand`] that sends its outcome to `reply`.
+fn command<C: Chain>(
+	method: &str,
+	raw_params: Value,
+	reply: Reply,
+) -> Result<RelayCommand<C, C>, RpcError> {
+	let command: Box<dyn FnOnce(&mut C, &mut C) -> anyhow::Result<Value> + Send> = match method {
+		"pause_channel" => {
+			let (chain, channel_id, port_id) = channel_params(params(raw_params)?)?;
+			Box::new(move |a, b| {
+				let chain = select(a, b, &chain)?;
+				let paused = chain.common_state().pause_channel(channel_id, port_id.clone());
+				log::info!("Paused channel {port_id}/{channel_id} on {}", chain.name());
+				Ok(json!(paused))
+			})
+		},
+		"resume_channel" => {
+			let (chain, channel_id, port_id) = channel_params(params(raw_params)?)?;
+			Box::new(move |a, b| {
+				let chain = select(a, b, &chain)?;
+				let resumed = chain.common_state().resume_channel(&channel_id, &port_id);
+				log::info!("Resumed channel {port_id}/{channel_id} on {}", chain.name());
+				Ok(json!(resumed))
+			})
+		},
+		"update_client" => {
+			let ChainParams { chain } = params(raw_params)?;
+			Box::new(move |a, b| {
+				select(a, b, &chain)?.common_state().request_client_update();
+				Ok(json!(true))
+			})
+		},
+		"flush_queue" => {
+			let ChainParams { chain } = params(raw_params)?;
+			Box::new(move |a, b| {
+				let mut undelivered = select(a, b, &chain)?
+					.common_state()
+					.maybe_has_undelivered_packets
+					.lock()
+					.unwrap();
+				for kind in
+					[UndeliveredType::Acks, UndeliveredType::Recvs, UndeliveredType::Timeouts]
+				{
+					undelivered.insert(kind, true);
+				}
+				Ok(json!(true))
+			})
+		},
+		"reload_keys" => {
+			let ChainParams { chain } = params(raw_params)?;
+			Box::new(move |a, b| Ok(json!(select(a, b, &chain)?.reload_keys()?)))
+		},
+		"dump_state" => Box::new(|a, b| Ok(json!([dump_chain_state(a), dump_chain_state(b)]))),
+		"snapshot" => Box::new(|a, b| Ok(serde_json::to_value(RelayerSnapshot::capture(a, b))?)),
+		_ => return Err(RpcError::new(METHOD_NOT_FOUND, format!("Method {method} not found"))),
+	};
+	Ok(Box::new(move |a, b| {
+		let _ = reply.send(command(a, b));
+		Ok(())
+	}))
+}
+
+async fn handle_call<C: Chain>(
+	body: &[u8],
+	control: &UnboundedSender<RelayCommand<C, C>>,
+) -> (Value, Result<Value, RpcError>) {
+	let request = match serde_json::from_slice::<RpcRequest>(body) {
+		Ok(request) => request,
+		Err(e) => return (Value::Null, Err(RpcError::new(PARSE_ERROR, e))),
+	};
+	let (reply, response) = oneshot::channel();
+	let result = match command::<C>(&request.method, request.params, reply) {
+		Ok(command) =>
+			if control.send(command).is_err() {
+				Err(RpcError::new(INTERNAL_ERROR, "Relay loop is not running"))
+			} else {
+				match response.await {
+					Ok(Ok(result)) => Ok(result),
+					Ok(Err(e)) => Err(RpcError::new(INVALID_PARAMS, e)),
+					Err(_) => Err(RpcError::new(INTERNAL_ERROR, "Relay loop dropped the request")),
+				}
+			},
+		Err(e) => Err(e),
+	};
+	(request.id, result)
+}
+
+async fn request_admin<C: Chain>(
+	req: Request<Body>,
+	token: &str,
+	control: UnboundedSender<RelayCommand<C, C>>,
+) -> Result<Response<Body>, anyhow::Error> {
+	if !is_authorized(&req, token) {
+		return Response::builder()
+			.status(StatusCode::UNAUTHORIZED)
+			.header("WWW-Authenticate", "Bearer")
+			.body(Body::from("Missing or invalid admin token."))
+			.map_err(Into::into)
+	}
+	if req.method() != Method::POST {
+		return Response::builder()
+			.status(StatusCode::METHOD_NOT_ALLOWED)
+			.body(Body::from("Only POST requests are supported."))
+			.map_err(Into::into)
+	}
+	let body = hyper::body::to_bytes(req.into_body()).await?;
+	let (id, result) = handle_call::<C>(&body, &control).await;
+	let response = match result {
+		Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+		Err(RpcError { code, message }) => json!({
+			"jsonrpc": "2.0",
+			"id": id,
+			"error": { "code": code, "message": message },
+		}),
+	};
+
+	Response::builder()
+		.status(StatusCode::OK)
+		.header("Content-Type", "application/json")
+		.body(Body::from(response.to_string()))
+		.map_err(Into::into)
+}
+
+/// Starts the admin JSON-RPC server, forwarding the requests authorized with `token` to the relay
+/// loop over `control`.
+pub async fn init_admin_server<C: Chain>(
+	addr: SocketAddr,
+	token: String,
+	control: UnboundedSender<RelayCommand<C, C>>,
+) -> anyhow::Result<()> {
+	if !addr.ip().is_loopback() {
+		log::warn!(target: "hyperspace", "The admin server is exposed on {addr}, make sure only operators can reach it");
+	}
+	let token: std::sync::Arc<str> = token.into();
+	let listener = tokio::net::TcpListener::bind(&addr)
+		.await
+		.map_err(|_| anyhow!("Admin server port {addr} already in use."))?;
+	let listener = hyper::server::conn::AddrIncoming::from_listener(listener)?;
+
+	let service = make_service_fn(move |_| {
+		let (token, control) = (token.clone(), control.clone());
+
+		async move {
+			Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+				let (token, control) = (token.clone(), control.clone());
+				async move { request_admin::<C>(req, &token, control).await }
+			}))
+		}
+	});
+
+	Server::builder(listener).serve(service).await.map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hyperspace_mock::{MockChain, MockConfig};
+	use tokio::sync::mpsc;
+
+	const TOKEN: &str = "secret";
+
+	/// Applies the commands sent over the returned channel to clones of two mock chains, as the
+	/// relay loop does.
+	fn relay_loop() -> (UnboundedSender<RelayCommand<MockChain, MockChain>>, MockChain, MockChain) {
+		let chain_a = MockChain::new(MockConfig::new("chain-a"));
+		let chain_b = MockChain::new(MockConfig::new("chain-b"));
+		let (control, mut commands) =
+			mpsc::unbounded_channel::<RelayCommand<MockChain, MockChain>>();
+		let (mut a, mut b) = (chain_a.clone(), chain_b.clone());
+		tokio::spawn(async move {
+			while let Some(command) = commands.recv().await {
+				command(&mut a, &mut b).unwrap();
+			}
+		});
+		(control, chain_a, chain_b)
+	}
+
+	async fn call(
+		control: &UnboundedSender<RelayCommand<MockChain, MockChain>>,
+		method: &str,
+		params: Value,
+	) -> Result<Value, (i64, String)> {
+		let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+		let (id, result) = handle_call::<MockChain>(body.to_string().as_bytes(), control).await;
+		assert_eq!(id, json!(1));
+		result.map_err(|RpcError { code, message }| (code, message))
+	}
+
+	fn request(token: Option<&str>, body: Value) -> Request<Body> {
+		let mut request = Request::builder().method(Method::POST).uri("/");
+		if let Some(token) = token {
+			request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+		}
+		request.body(Body::from(body.to_string())).unwrap()
+	}
+
+	#[tokio::test]
+	async fn pauses_and_resumes_channels() {
+		let (control, chain_a, chain_b) = relay_loop();
+		let params =
+			json!({ "chain": "chain-a", "channel_id": "channel-0", "port_id": "transfer" });
+		let channel = (ChannelId::new(0), PortId::transfer());
+
+		assert_eq!(call(&control, "pause_channel", params.clone()).await, Ok(json!(true)));
+		assert_eq!(call(&control, "pause_channel", params.clone()).await, Ok(json!(false)));
+		assert!(chain_a.common_state().paused_channels().contains(&channel));
+		assert!(chain_b.common_state().paused_channels().is_empty());
+
+		assert_eq!(call(&control, "resume_channel", params.clone()).await, Ok(json!(true)));
+		assert_eq!(call(&control, "resume_channel", params).await, Ok(json!(false)));
+		assert!(chain_a.common_state().paused_channels().is_empty());
+	}
+
+	#[tokio::test]
+	async fn requests_client_updates_and_flushes_queues() {
+		let (control, chain_a, chain_b) = relay_loop();
+		let params = json!({ "chain": "chain-b" });
+
+		assert_eq!(call(&control, "update_client", params.clone()).await, Ok(json!(true)));
+		assert!(chain_b.common_state().take_client_update_request());
+		assert!(!chain_a.common_state().take_client_update_request());
+
+		assert_eq!(call(&control, "flush_queue", params).await, Ok(json!(true)));
+		for kind in [UndeliveredType::Acks, UndeliveredType::Recvs, UndeliveredType::Timeouts] {
+			assert!(chain_b.common_state().has_undelivered_sequences(kind));
+			assert!(!chain_a.common_state().has_undelivered_sequences(kind));
+		}
+	}
+
+	#[tokio::test]
+	async fn rejects_invalid_calls() {
+		let (control, ..) = relay_loop();
+
+		let (code, _) = call(&control, "halt", json!({ "chain": "chain-a" })).await.unwrap_err();
+		assert_eq!(code, METHOD_NOT_FOUND);
+		let (code, _) = call(&control, "update_client", json!({})).await.unwrap_err();
+		assert_eq!(code, INVALID_PARAMS);
+		let params = json!({ "chain": "chain-a", "channel_id": "channel", "port_id": "transfer" });
+		let (code, _) = call(&control, "pause_channel", params).await.unwrap_err();
+		assert_eq!(code, INVALID_PARAMS);
+		let (code, message) = call(&control, "update_client", json!({ "chain": "chain-c" }))
+			.await
+			.unwrap_err();
+		assert_eq!(code, INVALID_PARAMS);
+		assert!(message.starts_with("Unknown chain chain-c"));
+
+		let (id, result) = handle_call::<MockChain>(b"{", &control).await;
+		assert_eq!(id, Value::Null);
+		assert!(matches!(result, Err(RpcError { code: PARSE_ERROR, .. })));
+	}
+
+	#[tokio::test]
+	async fn requires_the_admin_token() {
+		let (control, chain_a, _) = relay_loop();
+		let body = json!({
+			"jsonrpc": "2.0",
+			"id": 1,
+			"method": "update_client",
+			"params": { "chain": "chain-a" },
+		});
+
+		for token in [None, Some("wrong"), Some("secrets")] {
+			let response =
+				request_admin::<MockChain>(request(token, body.clone()), TOKEN, control.clone())
+					.await
+					.unwrap();
+			assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+		}
+		assert!(!chain_a.common_state().take_client_update_request());
+
+		let response = request_admin::<MockChain>(request(Some(TOKEN), body), TOKEN, control)
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::OK);
+		let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+		let response: Value = serde_json::from_slice(&body).unwrap();
+		assert_eq!(response["result"], json!(true));
+		assert!(chain_a.common_state().take_client_update_request());
+	}
+
+	#[test]
+	fn binds_to_localhost_by_default() {
+		assert_eq!(admin_addr("9090").unwrap(), SocketAddr::from(([127, 0, 0, 1], 9090)));
+		assert_eq!(admin_addr("0.0.0.0:9090").unwrap(), SocketAddr::from(([0, 0, 0, 0], 9090)));
+		assert!(admin_addr("localhost").is_err());
+	}
+}
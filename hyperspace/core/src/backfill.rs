@@ -0,0 +1,144 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Relaying of the packets and acknowledgements emitted during a past block range, so that a
+//! relayer that was down for a while doesn't have to wait for the packets it missed to time out.
+//!
+//! The events of the range are replayed with proofs at the latest height known by the
+//! counterparty client, so no client update is needed. Blocks after that height are left to the
+//! relay loop, which scans them when it updates the client.
+
+use crate::{events::parse_events, packets, process_messages};
+use anyhow::anyhow;
+use ibc::{core::ics02_client::client_state::ClientState, events::IbcEvent, Height};
+use metrics::handler::MetricsHandler;
+use pallet_ibc::light_clients::AnyClientState;
+use primitives::{find_suitable_proof_height_for_client, Chain, PacketDirection};
+use std::collections::{HashMap, HashSet};
+
+/// Number of blocks whose events are queried and relayed at once.
+pub const BACKFILL_BATCH_SIZE: u64 = 100;
+
+/// Relays the packets and acknowledgements emitted by `source` since `from_height` that `sink`
+/// hasn't received yet.
+pub async fn backfill<A: Chain, B: Chain>(
+	source: &mut A,
+	sink: &mut B,
+	metrics: &mut Option<MetricsHandler>,
+	from_height: u64,
+) -> anyhow::Result<()> {
+	let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+	let client_state = sink
+		.query_client_state(sink_height, source.client_id())
+		.await?
+		.client_state
+		.ok_or_else(|| anyhow!("Client of {} not found on {}", source.name(), sink.name()))?;
+	let client_height = AnyClientState::try_from(client_state)
+		.map_err(|e| anyhow!("Failed to decode client state: {e:?}"))?
+		.latest_height();
+	let proof_height = find_suitable_proof_height_for_client(
+		&*source,
+		&*sink,
+		sink_height,
+		source.client_id(),
+		Height::new(client_height.revision_number, client_height.revision_height.saturating_sub(1)),
+		None,
+		client_height,
+	)
+	.await
+	.ok_or_else(|| anyhow!("No proof height found for {} on {}", source.name(), sink.name()))?;
+
+	if from_height > proof_height.revision_height {
+		log::info!(target: "hyperspace", "Nothing to backfill on {}: its client on {} is at {client_height}", source.name(), sink.name());
+		return Ok(())
+	}
+	log::info!(
+		target: "hyperspace",
+		"Backfilling {} from {from_height} to {}, with proofs at {proof_height}",
+		source.name(),
+		proof_height.revision_height
+	);
+
+	let mut from = from_height;
+	while from <= proof_height.revision_height {
+		let to = (from + BACKFILL_BATCH_SIZE - 1).min(proof_height.revision_height);
+		let events = source.query_ibc_events_in_range(&*sink, from, to).await?;
+		let events = undelivered_packet_events(&*sink, sink_height, events)
+			.await?
+			.into_iter()
+			.map(|mut event| {
+				event.set_height(proof_height);
+				event
+			})
+			.collect::<Vec<_>>();
+		log::debug!(target: "hyperspace", "Found {} undelivered packet events on {} in {from}..={to}", events.len(), source.name());
+		let msgs = parse_events(source, sink, events, None).await?;
+		let relayed = packets::relayed_sequences(&msgs);
+		process_messages(sink, metrics, msgs).await?;
+		source.common_state().record_relayed(relayed);
+		from = to + 1;
+	}
+	log::info!(target: "hyperspace", "Finished backfilling {}", source.name());
+
+	Ok(())
+}
+
+/// Returns the `SendPacket` and `WriteAcknowledgement` events whose packets or acknowledgements
+/// weren't received on `sink` at height `at` yet.
+async fn undelivered_packet_events(
+	sink: &impl Chain,
+	at: Height,
+	events: Vec<IbcEvent>,
+) -> anyhow::Result<Vec<IbcEvent>> {
+	// keyed by the channel end on `sink`
+	let key = |event: &IbcEvent| match event {
+		IbcEvent::SendPacket(ev) => Some((
+			PacketDirection::Recv,
+			ev.packet.destination_channel,
+			ev.packet.destination_port.clone(),
+			u64::from(ev.packet.sequence),
+		)),
+		IbcEvent::WriteAcknowledgement(ev) => Some((
+			PacketDirection::Ack,
+			ev.packet.source_channel,
+			ev.packet.source_port.clone(),
+			u64::from(ev.packet.sequence),
+		)),
+		_ => None,
+	};
+
+	let mut sequences = HashMap::<_, Vec<u64>>::new();
+	for (direction, channel_id, port_id, sequence) in events.iter().filter_map(key) {
+		sequences.entry((direction, channel_id, port_id)).or_default().push(sequence);
+	}
+	let mut undelivered = HashSet::new();
+	for ((direction, channel_id, port_id), seqs) in sequences {
+		let seqs = match direction {
+			PacketDirection::Recv =>
+				sink.query_unreceived_packets(at, channel_id, port_id.clone(), seqs).await?,
+			_ =>
+				sink.query_unreceived_acknowledgements(at, channel_id, port_id.clone(), seqs)
+					.await?,
+		};
+		undelivered.extend(
+			seqs.into_iter()
+				.map(|sequence| (direction, channel_id, port_id.clone(), sequence)),
+		);
+	}
+
+	Ok(events
+		.into_iter()
+		.filter(|event| key(event).map_or(false, |key| undelivered.contains(&key)))
+		.collect())
+}
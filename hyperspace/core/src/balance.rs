@@ -0,0 +1,115 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Monitors the balance of the relayer accounts.
+//!
+//! The balance of each relayer key in the token used to pay transaction fees is exported as the
+//! `hyperspace_key_balance` metric, and their sum as the `hyperspace_wallet_balance` metric. When a
+//! chain has a `min_balance` configured, keys whose balance falls below it are left out of the
+//! [`KeyRotation`](primitives::KeyRotation), and once all of them are, submissions to that chain
+//! are halted (see [`flush_message_batch`](crate::queue::flush_message_batch)) instead of spending
+//! the remaining funds on transactions that are likely to fail. Submissions resume once a key is
+//! refilled.
+
+use ibc::applications::transfer::Amount;
+use metrics::handler::MetricsHandler;
+use primitives::Chain;
+use std::{collections::HashSet, time::Duration};
+
+/// Default interval between two balance checks.
+pub const DEFAULT_BALANCE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Checks the balance of the relayer accounts on both chains every `interval`.
+/// The chains are expected to share their [`primitives::CommonClientState`] with the relay loop.
+pub async fn watch_balances<A: Chain, B: Chain>(
+	chain_a: A,
+	chain_b: B,
+	metrics_a: Option<MetricsHandler>,
+	metrics_b: Option<MetricsHandler>,
+	interval: Duration,
+) {
+	let mut interval = tokio::time::interval(interval);
+	loop {
+		interval.tick().await;
+		if let Err(e) = check_balance(&chain_a, metrics_a.as_ref()).await {
+			log::warn!(target: "hyperspace", "Failed to query the balance on {}: {:?}", chain_a.name(), e);
+		}
+		if let Err(e) = check_balance(&chain_b, metrics_b.as_ref()).await {
+			log::warn!(target: "hyperspace", "Failed to query the balance on {}: {:?}", chain_b.name(), e);
+		}
+	}
+}
+
+async fn check_balance(chain: &impl Chain, metrics: Option<&MetricsHandler>) -> anyhow::Result<()> {
+	let balances = chain.query_key_balances().await?;
+	let state = chain.common_state();
+	let min_balance = state.min_balance();
+	let rotating = state.keys.key_count() > 1;
+	let mut denom = String::new();
+	let mut total = 0f64;
+	let mut counted = HashSet::new();
+	for (key, (address, balance)) in balances.into_iter().enumerate() {
+		let amount = balance.amount;
+		denom = balance.denom.to_string();
+		let value: f64 = amount.to_string().parse()?;
+		// keys sharing a fee payer are only counted once
+		if counted.insert(address.clone()) {
+			total += value;
+		}
+		if let Some(metrics) = metrics {
+			metrics.update_key_balance(&address, &denom, value);
+		}
+
+		let low = min_balance.map_or(false, |min| amount < Amount::from(min));
+		let was_low = state.keys.set_low_balance(key, low);
+		if !rotating {
+			continue
+		}
+		if low && !was_low {
+			log::warn!(
+				target: "hyperspace",
+				"Balance of the relayer key {address} on {} is {amount}{denom}, below the minimum of {}{denom}. Excluding it from the rotation until it's refilled",
+				chain.name(),
+				min_balance.unwrap_or_default(),
+			);
+		} else if !low && was_low {
+			log::info!(
+				target: "hyperspace",
+				"Balance of the relayer key {address} on {} is {amount}{denom}, adding it back to the rotation",
+				chain.name()
+			);
+		}
+	}
+	if let Some(metrics) = metrics {
+		metrics.update_wallet_balance(&denom, total);
+	}
+
+	let low = state.keys.all_low_balance();
+	let was_low = state.set_low_balance(low);
+	if low && !was_low {
+		log::error!(
+			target: "hyperspace",
+			"ALERT: balances of all the relayer keys on {} are below the minimum of {}{denom}. Halting submissions until they're refilled",
+			chain.name(),
+			min_balance.unwrap_or_default(),
+		);
+	} else if !low && was_low {
+		log::info!(
+			target: "hyperspace",
+			"Balance of a relayer key on {} is above the minimum, resuming submissions",
+			chain.name()
+		);
+	}
+	Ok(())
+}
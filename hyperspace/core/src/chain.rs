@@ -16,6 +16,7 @@
 
 use crate::{
 	chains,
+	logging::LoggingConfig,
 	substrate::{
 		default::DefaultConfig, ComposableConfig, PicassoKusamaConfig, PicassoRococoConfig,
 	},
@@ -23,16 +24,19 @@ use crate::{
 use async_trait::async_trait;
 #[cfg(feature = "cosmos")]
 use cosmos::client::{CosmosClient, CosmosClientConfig};
-use futures::Stream;
+use futures::{Stream, StreamExt};
 #[cfg(any(test, feature = "testing"))]
 use ibc::applications::transfer::msgs::transfer::MsgTransfer;
 use ibc::{
 	applications::transfer::PrefixedCoin,
 	core::{
 		ics02_client::{
-			client_state::ClientType,
+			client_state::{ClientState as _, ClientType},
 			events::{CodeId, UpdateClient},
-			msgs::{create_client::MsgCreateAnyClient, update_client::MsgUpdateAnyClient},
+			msgs::{
+				create_client::MsgCreateAnyClient, update_client::MsgUpdateAnyClient,
+				upgrade_client::MsgUpgradeAnyClient,
+			},
 		},
 		ics03_connection::msgs::{
 			conn_open_ack::MsgConnectionOpenAck, conn_open_try::MsgConnectionOpenTry,
@@ -60,34 +64,142 @@ use ibc_proto::{
 	},
 };
 use ics08_wasm::Bytes;
+use ics11_beefy::client_message::{BeefyHeader, ClientMessage as BeefyClientMessage};
 use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState};
 #[cfg(any(test, feature = "testing"))]
 use pallet_ibc::Timeout;
 use parachain::{ParachainClient, ParachainClientConfig};
 use primitives::{
-	mock::LocalClientTypes, Chain, CommonClientState, IbcProvider, KeyProvider, LightClientSync,
-	MisbehaviourHandler, UpdateType,
+	mock::LocalClientTypes, Chain, CommonClientState, FinalitySource, IbcQuerier, KeyProvider,
+	LightClientSync, MisbehaviourHandler, TxSubmitter, UpdateType, UpgradedClient,
 };
 use serde::{Deserialize, Serialize};
-use std::{pin::Pin, time::Duration};
+use std::{net::SocketAddr, pin::Pin, time::Duration};
 use tendermint_proto::Protobuf;
 use thiserror::Error;
 
 #[derive(Serialize, Deserialize)]
-pub struct Config {
-	pub chain_a: AnyConfig,
-	pub chain_b: AnyConfig,
+pub struct Config<C = AnyConfig> {
+	pub chain_a: C,
+	pub chain_b: C,
 	pub core: CoreConfig,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct CoreConfig {
 	pub prometheus_endpoint: Option<String>,
+	/// Address of the HTTP server serving the `/health` and `/ready` endpoints.
+	/// The server is disabled if not set.
+	pub health_endpoint: Option<String>,
+	/// Maximum time in seconds since the last finality event of a chain before `/ready` reports
+	/// the relayer as not ready.
+	pub max_finality_event_age: Option<u64>,
+	/// Interval in seconds between two health probes of the chains. Relaying is paused while
+	/// either chain is stalled.
+	pub health_check_interval: Option<u64>,
+	/// Maximum number of blocks between the best and the finalized block of a chain before it's
+	/// considered stalled.
+	pub max_finality_gap: Option<u64>,
+	/// Maximum time in milliseconds the RPC node of a chain may take to answer a health probe
+	/// before the chain is considered stalled.
+	pub max_rpc_latency: Option<u64>,
+	/// Address of the admin JSON-RPC server used to pause/resume channels and inspect the
+	/// relayer, or only its port to bind it to localhost. The server is disabled if not set.
+	pub admin_endpoint: Option<String>,
+	/// Token the requests to the admin server must carry in an `Authorization: Bearer` header.
+	/// Required when `admin_endpoint` is set.
+	pub admin_token: Option<String>,
+	/// Address of the OTLP collector the spans of the relay pipeline are exported to (e.g.
+	/// `"http://localhost:4317"`). Requires the `otlp` feature.
+	pub otlp_endpoint: Option<String>,
+	/// Interval in seconds between two checks of the channel and client states, used to halt
+	/// relaying of closed channels and channels whose clients are frozen or expired.
+	pub channel_watch_interval: Option<u64>,
+	/// Interval in seconds between two checks of the age of the clients' latest consensus
+	/// states, used to update idle clients before they expire. Defaults to ten minutes.
+	pub client_expiry_check_interval: Option<u64>,
+	/// Interval in seconds between two checks of the upgraded clients stored by the chains, used
+	/// to upgrade their clients on the counterparty. Defaults to five minutes.
+	pub client_upgrade_check_interval: Option<u64>,
+	/// Interval in seconds between two checks of the relayer key balances, which are exported as
+	/// metrics and used to rotate away from the keys below a chain's `min_balance`.
+	pub balance_check_interval: Option<u64>,
+	/// Length in seconds of the sliding window over which the p50/p95/p99 packet latencies are
+	/// computed. Defaults to one hour.
+	pub latency_window: Option<u64>,
+	/// Interval in seconds between two log lines reporting the packet latency percentiles of
+	/// every channel. Defaults to five minutes.
+	pub latency_report_interval: Option<u64>,
+	/// Whether `relay` also watches both chains for misbehaviour of their counterparty, like
+	/// `fish` does.
+	pub watch_misbehaviour: Option<bool>,
+	/// Directory the misbehaviour evidence is stored in until its submission is accepted.
+	/// Defaults to `misbehaviour-evidence`.
+	pub evidence_dir: Option<String>,
+	/// File the signed receipts of the successful submissions are appended to. No receipts are
+	/// issued if not set.
+	pub receipts_path: Option<String>,
+	/// Address of the HTTP server serving the relay receipts at `/receipts`. Requires
+	/// `receipts_path`. The server is disabled if not set.
+	pub receipts_endpoint: Option<String>,
+	/// File the packets sent from the chains, and the receipts, acknowledgements and timeouts
+	/// delivered by the relayer, are recorded in. Checked against the chains by `reconcile`. No
+	/// packets are recorded if not set.
+	pub ledger_path: Option<String>,
+	/// Length in seconds of the windows over which the watchdog compares the packets sent and
+	/// relayed on every channel. Defaults to five minutes.
+	pub watchdog_window: Option<u64>,
+	/// Format, filter and files of the logs. Logs are written to stdout as text if not set.
+	pub logging: Option<LoggingConfig>,
 }
 
-impl From<String> for AnyError {
-	fn from(s: String) -> Self {
-		Self::Other(s)
+impl CoreConfig {
+	/// Checks that the endpoints are socket addresses, that the intervals aren't zero and that the
+	/// options depending on each other are set together. Returns all the problems found.
+	pub fn validate(&self) -> anyhow::Result<()> {
+		let mut problems = vec![];
+		let endpoints = [
+			("prometheus_endpoint", &self.prometheus_endpoint),
+			("health_endpoint", &self.health_endpoint),
+			("receipts_endpoint", &self.receipts_endpoint),
+		];
+		for (key, endpoint) in endpoints {
+			if let Some(Err(e)) = endpoint.as_ref().map(|endpoint| endpoint.parse::<SocketAddr>()) {
+				problems.push(format!("{key} is not a socket address: {e}"));
+			}
+		}
+		if let Some(endpoint) = &self.admin_endpoint {
+			if let Err(e) = crate::admin::admin_addr(endpoint) {
+				problems.push(format!("admin_endpoint is invalid: {e}"));
+			}
+			if self.admin_token.as_ref().map_or(true, |token| token.is_empty()) {
+				problems.push("admin_endpoint requires a non-empty admin_token".to_string());
+			}
+		}
+		let intervals = [
+			("health_check_interval", self.health_check_interval),
+			("channel_watch_interval", self.channel_watch_interval),
+			("client_expiry_check_interval", self.client_expiry_check_interval),
+			("client_upgrade_check_interval", self.client_upgrade_check_interval),
+			("balance_check_interval", self.balance_check_interval),
+			("latency_window", self.latency_window),
+			("latency_report_interval", self.latency_report_interval),
+			("watchdog_window", self.watchdog_window),
+		];
+		for (key, _) in intervals.iter().filter(|(_, interval)| *interval == Some(0)) {
+			problems.push(format!("{key} must not be zero"));
+		}
+		if self.receipts_endpoint.is_some() && self.receipts_path.is_none() {
+			problems.push("receipts_endpoint requires receipts_path to be set".to_string());
+		}
+		if self.otlp_endpoint.is_some() && !cfg!(feature = "otlp") {
+			problems.push("otlp_endpoint requires hyperspace to be built with `otlp`".to_string());
+		}
+		if problems.is_empty() {
+			Ok(())
+		} else {
+			Err(anyhow::anyhow!("{}", problems.join(", ")))
+		}
 	}
 }
 
@@ -101,12 +213,19 @@ chains! {
 	Cosmos(CosmosClientConfig, CosmosClient<DefaultConfig>),
 }
 
-fn wrap_any_msg_into_wasm(msg: Any, code_id: Bytes) -> Result<Any, anyhow::Error> {
+/// Wraps the light client states and messages of `msg`, submitted to `host`, into their `08-wasm`
+/// counterparts.
+pub async fn wrap_any_msg_into_wasm(
+	host: &AnyChain,
+	msg: Any,
+	code_id: Bytes,
+) -> Result<Any, anyhow::Error> {
 	// TODO: consider rewriting with Ics26Envelope
 	use ibc::core::{
 		ics02_client::msgs::{
 			create_client::TYPE_URL as CREATE_CLIENT_TYPE_URL,
 			update_client::TYPE_URL as UPDATE_CLIENT_TYPE_URL,
+			upgrade_client::TYPE_URL as UPGRADE_CLIENT_TYPE_URL,
 		},
 		ics03_connection::msgs::{
 			conn_open_ack::TYPE_URL as CONN_OPEN_ACK_TYPE_URL,
@@ -135,8 +254,21 @@ fn wrap_any_msg_into_wasm(msg: Any, code_id: Bytes) -> Result<Any, anyhow::Error
 		UPDATE_CLIENT_TYPE_URL => {
 			let mut msg_decoded =
 				MsgUpdateAnyClient::<LocalClientTypes>::decode_vec(&msg.value).unwrap();
-			msg_decoded.client_message = AnyClientMessage::wasm(msg_decoded.client_message)?;
-
+			msg_decoded.client_message = match msg_decoded.client_message {
+				AnyClientMessage::Beefy(BeefyClientMessage::Header(header)) => {
+					let height = beefy_header_height(host, &msg_decoded.client_id, &header).await?;
+					let message = AnyClientMessage::Beefy(BeefyClientMessage::Header(header));
+					AnyClientMessage::wasm_at(message, Some(height))?
+				},
+				message => AnyClientMessage::wasm(message)?,
+			};
+			msg_decoded.to_any()
+		},
+		UPGRADE_CLIENT_TYPE_URL => {
+			let mut msg_decoded =
+				MsgUpgradeAnyClient::<LocalClientTypes>::decode_vec(&msg.value).unwrap();
+			msg_decoded.consensus_state = AnyConsensusState::wasm(msg_decoded.consensus_state)?;
+			msg_decoded.client_state = AnyClientState::wasm(msg_decoded.client_state, code_id)?;
 			msg_decoded.to_any()
 		},
 		_ => msg,
@@ -144,8 +276,25 @@ fn wrap_any_msg_into_wasm(msg: Any, code_id: Bytes) -> Result<Any, anyhow::Error
 	Ok(msg)
 }
 
-#[derive(Clone)]
-pub struct WasmChain {
-	pub inner: Box<AnyChain>,
-	pub code_id: Bytes,
+/// Height of the beefy `header` updating the client `client_id` of `host`: the number of its
+/// latest parachain header, in the revision of the client, which is the id of the parachain.
+async fn beefy_header_height(
+	host: &AnyChain,
+	client_id: &ClientId,
+	header: &BeefyHeader,
+) -> Result<Height, anyhow::Error> {
+	let (latest_height, _) = host.latest_height_and_timestamp().await?;
+	let client_state = host
+		.query_client_state(latest_height, client_id.clone())
+		.await?
+		.client_state
+		.and_then(|any| AnyClientState::decode_recursive(any, |_| true))
+		.ok_or_else(|| anyhow::anyhow!("Client state of {client_id} not found"))?;
+	let client_height = client_state.latest_height();
+	let number = header
+		.headers_with_proof
+		.as_ref()
+		.and_then(|proof| proof.headers.iter().map(|h| h.parachain_header.number).max())
+		.map_or(client_height.revision_height, u64::from);
+	Ok(Height::new(client_height.revision_number, number))
 }
@@ -13,26 +13,126 @@
 // limitations under the License.
 
 use crate::{
-	chain::{AnyConfig, Config, CoreConfig},
-	fish, relay, Mode,
+	admin::{admin_addr, init_admin_server},
+	backfill::backfill,
+	balance::{watch_balances, DEFAULT_BALANCE_CHECK_INTERVAL},
+	chain::{Config, CoreConfig},
+	config::{read_config, write_config, CHAIN_A_ENV_PREFIX, CHAIN_B_ENV_PREFIX, CORE_ENV_PREFIX},
+	expiry::{watch_client_expiry, DEFAULT_CLIENT_EXPIRY_CHECK_INTERVAL},
+	fish,
+	health::{
+		init_health_server, watch_health, HealthState, DEFAULT_HEALTH_CHECK_INTERVAL,
+		DEFAULT_MAX_FINALITY_EVENT_AGE,
+	},
+	ledger::reconcile,
+	logging::{init_logging, setup_logging},
+	misbehaviour::{scan_misbehaviour, watch_misbehaviour, EvidenceStore, DEFAULT_EVIDENCE_DIR},
+	plugin::ChainConfig,
+	queue::resubmit_pending_messages,
+	receipts::init_receipts_server,
+	relay, relay_with_control,
+	reload::{reload_on_sighup, PrometheusServer},
+	telemetry::otlp_layer,
+	upgrade::{watch_client_upgrades, DEFAULT_CLIENT_UPGRADE_CHECK_INTERVAL},
+	watchdog::{watch_pipeline, DEFAULT_WATCHDOG_WINDOW},
+	watcher::{watch_channels, DEFAULT_CHANNEL_WATCH_INTERVAL},
+	Mode,
 };
 use anyhow::{anyhow, Result};
 use clap::Parser;
 use ibc::core::{ics04_channel::channel::Order, ics24_host::identifier::PortId};
-use metrics::{data::Metrics, handler::MetricsHandler, init_prometheus};
+use metrics::{
+	data::Metrics,
+	handler::{report_packet_latency, MetricsHandler},
+};
+#[cfg(feature = "testing")]
+use primitives::pfm::Hop;
 use primitives::{
-	utils::{create_channel, create_clients, create_connection},
-	Chain, IbcProvider,
+	fee::register_counterparty_payee,
+	signing::{EncryptedKey, DEFAULT_PASSPHRASE_ENV},
+	upgrade::upgrade_client,
+	utils::{create_channel, create_client_at, create_clients_at, create_connection},
+	HealthThresholds, IbcQuerier, PacketLedger, ReceiptStore, RelayerSnapshot, TxSubmitter,
 };
 use prometheus::Registry;
 use std::{num::NonZeroU64, path::PathBuf, str::FromStr, time::Duration};
 
+/// Default interval between two reports of the packet latency percentiles.
+const DEFAULT_LATENCY_REPORT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+fn evidence_store(config: &CoreConfig) -> Result<EvidenceStore> {
+	EvidenceStore::new(config.evidence_dir.as_deref().unwrap_or(DEFAULT_EVIDENCE_DIR))
+}
+
+/// Reads the chain config at `path` and checks it, see [`ChainConfig::validate`].
+fn read_chain_config<C: ChainConfig>(path: &str, env_prefix: Option<&str>) -> Result<C> {
+	let config: C = read_config(path, env_prefix)?;
+	config.validate().map_err(|e| anyhow!("Invalid {path}: {e}"))?;
+	Ok(config)
+}
+
+/// Reads the core config at `path` and checks it, see [`CoreConfig::validate`].
+fn read_core_config(path: &str) -> Result<CoreConfig> {
+	let config: CoreConfig = read_config(path, Some(CORE_ENV_PREFIX))?;
+	config.validate().map_err(|e| anyhow!("Invalid {path}: {e}"))?;
+	Ok(config)
+}
+
 #[derive(Debug, Parser)]
 pub struct Cli {
 	#[structopt(subcommand)]
 	pub subcommand: Subcommand,
 }
 
+impl Cli {
+	/// Runs the subcommand, building chain clients from configs of type `C`. Logging is set up
+	/// from the core config of the subcommand, unless a logger is already installed.
+	pub async fn run<C: ChainConfig>(&self) -> Result<()> {
+		match &self.subcommand {
+			Subcommand::Relay(cmd) |
+			Subcommand::Fish(cmd) |
+			Subcommand::CreateClients(cmd) |
+			Subcommand::CreateConnection(cmd) |
+			Subcommand::CreateChannel(cmd) |
+			Subcommand::UpgradeClients(cmd) |
+			Subcommand::Reconcile(cmd) =>
+				if let Err(e) = cmd.init_logging() {
+					setup_logging();
+					log::warn!(target: "hyperspace", "Logging is not configured by the core config: {e:?}");
+				},
+			_ => setup_logging(),
+		}
+		match &self.subcommand {
+			Subcommand::Relay(cmd) => cmd.run::<C>().await,
+			Subcommand::UploadWasm(cmd) => {
+				let new_config = cmd.run::<C>().await?;
+				cmd.save_config(&new_config).await
+			},
+			Subcommand::CreateClients(cmd) => {
+				let new_config = cmd.create_clients::<C>().await?;
+				cmd.save_config(&new_config).await
+			},
+			Subcommand::CreateConnection(cmd) => {
+				let new_config = cmd.create_connection::<C>().await?;
+				cmd.save_config(&new_config).await
+			},
+			Subcommand::CreateChannel(cmd) => {
+				let new_config = cmd.create_channel::<C>().await?;
+				cmd.save_config(&new_config).await
+			},
+			Subcommand::Fish(cmd) => cmd.fish::<C>().await,
+			Subcommand::UpgradeClients(cmd) => cmd.upgrade_clients::<C>().await,
+			Subcommand::Reconcile(cmd) => cmd.reconcile::<C>().await,
+			Subcommand::EncryptKey(cmd) => cmd.run(),
+			Subcommand::DumpState(cmd) => cmd.run().await,
+			#[cfg(feature = "testing")]
+			Subcommand::Transfer(cmd) => cmd.run::<C>().await,
+			#[cfg(feature = "testing")]
+			Subcommand::StressTest(cmd) => cmd.run::<C>().await,
+		}
+	}
+}
+
 /// Possible subcommands of the main binary.
 #[derive(Debug, Parser)]
 pub enum Subcommand {
@@ -51,6 +151,38 @@ pub enum Subcommand {
 	CreateConnection(Cmd),
 	#[clap(name = "create-channel", about = "Creates a channel on the specified port")]
 	CreateChannel(Cmd),
+	#[clap(
+		name = "upgrade-clients",
+		about = "Upgrades the light clients of the chains that were upgraded on their counterparty"
+	)]
+	UpgradeClients(Cmd),
+	#[clap(
+		name = "reconcile",
+		about = "Checks the packet ledger against the chains and reports the discrepancies"
+	)]
+	Reconcile(Cmd),
+	#[clap(
+		name = "encrypt-key",
+		about = "Encrypts a relayer key read from stdin into a keystore file"
+	)]
+	EncryptKey(EncryptKeyCmd),
+	#[clap(
+		name = "dump-state",
+		about = "Writes a snapshot of the relaying state of a running relayer to a JSON file"
+	)]
+	DumpState(DumpStateCmd),
+	#[cfg(feature = "testing")]
+	#[clap(
+		name = "transfer",
+		about = "Sends an ICS-20 transfer, optionally forwarded over more hops"
+	)]
+	Transfer(TransferCmd),
+	#[cfg(feature = "testing")]
+	#[clap(
+		name = "stress-test",
+		about = "Sends packets at a fixed rate and reports their delivery latency and throughput"
+	)]
+	StressTest(StressTestCmd),
 }
 
 #[derive(Debug, Clone, Parser)]
@@ -82,6 +214,45 @@ pub struct Cmd {
 	/// New config path for B to avoid overriding existing configuration
 	#[clap(long)]
 	pub out_config_b: Option<String>,
+	/// Height of chain A from which to relay the packets and acknowledgements that weren't
+	/// delivered yet, before relaying new finality events. When fishing, height of chain A from
+	/// which to scan the past client updates for misbehaviour instead of watching new ones
+	#[clap(long)]
+	from_height_a: Option<u64>,
+	/// Height of chain B from which to relay the packets and acknowledgements that weren't
+	/// delivered yet, before relaying new finality events. When fishing, height of chain B from
+	/// which to scan the past client updates for misbehaviour instead of watching new ones
+	#[clap(long)]
+	from_height_b: Option<u64>,
+	/// Last height of chain A scanned for misbehaviour, defaults to the latest height
+	#[clap(long)]
+	to_height_a: Option<u64>,
+	/// Last height of chain B scanned for misbehaviour, defaults to the latest height
+	#[clap(long)]
+	to_height_b: Option<u64>,
+	/// Run the whole pipeline, but log the messages and their estimated cost instead of
+	/// submitting them
+	#[clap(long)]
+	dry_run: bool,
+	/// Past finalized height of chain A the client of chain A is created at, defaults to the
+	/// latest height
+	#[clap(long)]
+	client_height_a: Option<u64>,
+	/// Past finalized height of chain B the client of chain B is created at, defaults to the
+	/// latest height
+	#[clap(long)]
+	client_height_b: Option<u64>,
+	/// Create the clients missing from the chain configs before relaying, and save their ids to
+	/// the configs
+	#[clap(long)]
+	create_missing_clients: bool,
+	/// Snapshot written by `dump-state` the relaying state is restored from. Unless set, the
+	/// heights to relay from default to the last heights processed in the snapshot
+	#[clap(long)]
+	restore_state: Option<PathBuf>,
+	/// When reconciling, append the entries the chains show are missing to the packet ledger
+	#[clap(long)]
+	repair: bool,
 }
 
 #[derive(Debug, Clone, Parser)]
@@ -97,12 +268,197 @@ pub struct UploadWasmCmd {
 	wasm_path: PathBuf,
 }
 
+#[derive(Debug, Clone, Parser)]
+pub struct EncryptKeyCmd {
+	/// Path the encrypted key is written to.
+	#[clap(long)]
+	output: PathBuf,
+	/// Environment variable the passphrase is read from.
+	#[clap(long, default_value = DEFAULT_PASSPHRASE_ENV)]
+	passphrase_env: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct DumpStateCmd {
+	/// Relayer core config path, the snapshot is requested from its admin endpoint.
+	#[clap(long)]
+	config_core: String,
+	/// Path the snapshot is written to.
+	#[clap(long)]
+	output: PathBuf,
+}
+
+#[cfg(feature = "testing")]
+#[derive(Debug, Clone, Parser)]
+pub struct TransferCmd {
+	/// Sending chain config path.
+	#[clap(long)]
+	config: String,
+	/// Port the transfer is sent over.
+	#[clap(long, default_value = "transfer")]
+	port_id: String,
+	/// Channel the transfer is sent over.
+	#[clap(long)]
+	channel_id: String,
+	/// Denomination of the tokens, including its trace path.
+	#[clap(long)]
+	denom: String,
+	/// Amount of tokens, in the smallest unit of the denomination.
+	#[clap(long)]
+	amount: String,
+	/// Receiver on the counterparty chain.
+	#[clap(long)]
+	receiver: String,
+	/// Comma separated hops the tokens are forwarded over by the packet forward middleware,
+	/// starting from the counterparty chain, each written as `{port}/{channel}/{receiver}`
+	#[clap(long, value_delimiter = ',')]
+	hops: Vec<Hop>,
+	/// Timeout of the transfer, in seconds after the latest block of the sending chain.
+	#[clap(long, default_value = "3600")]
+	timeout: u64,
+}
+
+#[cfg(feature = "testing")]
+impl TransferCmd {
+	pub async fn run<C: ChainConfig>(&self) -> Result<()> {
+		use ibc::{
+			applications::transfer::{msgs::transfer::MsgTransfer, Amount, PrefixedCoin},
+			Height,
+		};
+		use primitives::{pfm::forward_memo, KeyProvider, TestProvider};
+
+		let config: C = read_chain_config(&self.config, None)?;
+		let chain = config.into_client().await?;
+		let (_, timestamp) = chain.latest_height_and_timestamp().await?;
+		let msg = MsgTransfer {
+			source_port: PortId::from_str(&self.port_id)?,
+			source_channel: self.channel_id.parse()?,
+			token: PrefixedCoin {
+				denom: self.denom.parse().map_err(|e| anyhow!("Invalid denom: {e:?}"))?,
+				amount: Amount::from_str(&self.amount)
+					.map_err(|e| anyhow!("Invalid amount: {e:?}"))?,
+			},
+			sender: chain.account_id(),
+			receiver: self.receiver.parse().map_err(|e| anyhow!("Invalid receiver: {e:?}"))?,
+			timeout_height: Height::zero(),
+			timeout_timestamp: (timestamp + Duration::from_secs(self.timeout))?,
+			memo: forward_memo(&self.hops),
+		};
+		log::info!(target: "hyperspace", "Sending {:?} from {} with memo {:?}", msg.token, chain.name(), msg.memo);
+		chain.send_transfer(msg).await?;
+		Ok(())
+	}
+}
+
+#[cfg(feature = "testing")]
+#[derive(Debug, Clone, Parser)]
+pub struct StressTestCmd {
+	/// Sending chain config path, packets are sent over its whitelisted transfer and ping
+	/// channels.
+	#[clap(long)]
+	config_a: String,
+	/// Receiving chain config path.
+	#[clap(long)]
+	config_b: String,
+	/// Packets sent per second, over all the channels.
+	#[clap(long, default_value = "1")]
+	rate: std::num::NonZeroU32,
+	/// How long packets are sent for, in seconds.
+	#[clap(long, default_value = "60")]
+	duration: u64,
+	/// How long the acknowledgements are waited for after the last packet was sent, in seconds.
+	#[clap(long, default_value = "600")]
+	drain_timeout: u64,
+	/// Denomination of the tokens sent over transfer channels, including its trace path.
+	#[clap(long)]
+	denom: String,
+	/// Amount of tokens sent by each transfer, in the smallest unit of the denomination.
+	#[clap(long, default_value = "1")]
+	amount: String,
+	/// Timeout of the packets, in seconds after the start of the test.
+	#[clap(long, default_value = "3600")]
+	timeout: u64,
+}
+
+#[cfg(feature = "testing")]
+impl StressTestCmd {
+	pub async fn run<C: ChainConfig>(&self) -> Result<()> {
+		use crate::stress::{stress_test, StressParams};
+		use ibc::applications::transfer::{Amount, PrefixedCoin};
+
+		let config_a: C = read_chain_config(&self.config_a, Some(CHAIN_A_ENV_PREFIX))?;
+		let config_b: C = read_chain_config(&self.config_b, Some(CHAIN_B_ENV_PREFIX))?;
+		let chain_a = config_a.into_client().await?;
+		let chain_b = config_b.into_client().await?;
+		let params = StressParams {
+			rate: self.rate,
+			duration: Duration::from_secs(self.duration),
+			drain_timeout: Duration::from_secs(self.drain_timeout),
+			coin: PrefixedCoin {
+				denom: self.denom.parse().map_err(|e| anyhow!("Invalid denom: {e:?}"))?,
+				amount: Amount::from_str(&self.amount)
+					.map_err(|e| anyhow!("Invalid amount: {e:?}"))?,
+			},
+			packet_timeout: Duration::from_secs(self.timeout),
+		};
+		log::info!(target: "hyperspace", "Sending {} packets per second from {} for {}s", self.rate, chain_a.name(), self.duration);
+		let report = stress_test(&chain_a, &chain_b, params).await?;
+		println!("{report}");
+		Ok(())
+	}
+}
+
+impl EncryptKeyCmd {
+	/// Encrypts the private key or mnemonic read from stdin, so that it can be used in the
+	/// `keystore` signer of a chain config.
+	pub fn run(&self) -> Result<()> {
+		let passphrase = std::env::var(&self.passphrase_env)
+			.map_err(|_| anyhow!("The passphrase must be set in ${}", self.passphrase_env))?;
+		let mut secret = String::new();
+		std::io::stdin().read_line(&mut secret)?;
+		let secret = secret.trim();
+		if secret.is_empty() {
+			return Err(anyhow!("No key was read from stdin"))
+		}
+		EncryptedKey::encrypt(secret, &passphrase)?.write(&self.output)
+	}
+}
+
+impl DumpStateCmd {
+	/// Requests a [`RelayerSnapshot`] from the admin server of the relayer and writes it to the
+	/// output file, so that it can be restored with `relay --restore-state`.
+	pub async fn run(&self) -> Result<()> {
+		use hyper::{Body, Client, Method, Request};
+
+		let config = read_core_config(&self.config_core)?;
+		let endpoint = config
+			.admin_endpoint
+			.ok_or_else(|| anyhow!("admin_endpoint is not set in {}", self.config_core))?;
+		let token = config
+			.admin_token
+			.ok_or_else(|| anyhow!("admin_token is not set in {}", self.config_core))?;
+		let request = Request::builder()
+			.method(Method::POST)
+			.uri(format!("http://{}", admin_addr(&endpoint)?))
+			.header("Content-Type", "application/json")
+			.header("Authorization", format!("Bearer {token}"))
+			.body(Body::from(r#"{"jsonrpc":"2.0","id":1,"method":"snapshot"}"#))?;
+		let response = Client::new().request(request).await?;
+		let body = hyper::body::to_bytes(response.into_body()).await?;
+		let mut response: serde_json::Value = serde_json::from_slice(&body)?;
+		if let Some(error) = response.get("error") {
+			return Err(anyhow!("Admin server returned an error: {error}"))
+		}
+		let snapshot: RelayerSnapshot = serde_json::from_value(response["result"].take())?;
+		snapshot.write(&self.output)?;
+		log::info!(target: "hyperspace", "Wrote the snapshot of {} chains to {}", snapshot.chains.len(), self.output.display());
+		Ok(())
+	}
+}
+
 impl UploadWasmCmd {
-	pub async fn run(&self) -> Result<AnyConfig> {
-		use tokio::fs::read_to_string;
-		let path: PathBuf = self.config.parse()?;
-		let file_content = read_to_string(path).await?;
-		let mut config: AnyConfig = toml::from_str(&file_content)?;
+	pub async fn run<C: ChainConfig>(&self) -> Result<C> {
+		let mut config: C = read_chain_config(&self.config, None)?;
 		let client = config.clone().into_client().await?;
 		let wasm = tokio::fs::read(&self.wasm_path).await?;
 		let code_id = client.upload_wasm(wasm).await?;
@@ -112,34 +468,90 @@ impl UploadWasmCmd {
 		Ok(config)
 	}
 
-	pub async fn save_config(&self, new_config: &AnyConfig) -> Result<()> {
-		let path = self.out_config.as_ref().cloned().unwrap_or_else(|| self.config.clone());
-		write_config(path, new_config).await
+	pub async fn save_config<C: ChainConfig>(&self, new_config: &C) -> Result<()> {
+		let path = self.out_config.as_ref().unwrap_or(&self.config);
+		write_config(path, new_config, &self.config, None).await
 	}
 }
 
 impl Cmd {
-	async fn parse_config(&self) -> Result<Config> {
-		use tokio::fs::read_to_string;
-		let path_a: PathBuf = self.config_a.parse()?;
-		let path_b: PathBuf = self.config_b.parse()?;
-		let path_core: PathBuf = self.config_core.parse()?;
-		let file_content = read_to_string(path_a).await?;
-		let config_a: AnyConfig = toml::from_str(&file_content)?;
-		let file_content = read_to_string(path_b).await?;
-		let config_b: AnyConfig = toml::from_str(&file_content)?;
-		let file_content = read_to_string(path_core).await?;
-		let config_core: CoreConfig = toml::from_str(&file_content)?;
-
-		Ok(Config { chain_a: config_a, chain_b: config_b, core: config_core })
+	pub(crate) async fn parse_config<C: ChainConfig>(&self) -> Result<Config<C>> {
+		// all the configs are checked, so that every mistake is reported at once
+		let chain_a = read_chain_config::<C>(&self.config_a, Some(CHAIN_A_ENV_PREFIX));
+		let chain_b = read_chain_config::<C>(&self.config_b, Some(CHAIN_B_ENV_PREFIX));
+		let core = read_core_config(&self.config_core);
+		match (chain_a, chain_b, core) {
+			(Ok(chain_a), Ok(chain_b), Ok(core)) => Ok(Config { chain_a, chain_b, core }),
+			(chain_a, chain_b, core) => {
+				let errors = [chain_a.err(), chain_b.err(), core.err()]
+					.into_iter()
+					.flatten()
+					.map(|e| e.to_string())
+					.collect::<Vec<_>>();
+				Err(anyhow!("Invalid configuration:\n{}", errors.join("\n")))
+			},
+		}
+	}
+
+	/// Sets up logging as configured by the `[logging]` section of the core config, exporting the
+	/// spans over OTLP when `otlp_endpoint` is set.
+	pub fn init_logging(&self) -> Result<()> {
+		let config_core = read_core_config(&self.config_core)?;
+		let extra = config_core.otlp_endpoint.as_deref().map(otlp_layer).transpose()?;
+		init_logging(&config_core.logging.unwrap_or_default(), extra.into_iter().collect())?;
+		if let Some(endpoint) = config_core.otlp_endpoint.as_ref() {
+			log::info!(target: "hyperspace", "Exporting traces to {endpoint}");
+		}
+		Ok(())
 	}
 
 	// todo: IntoClient, since clients are generic, users must configure clients themselves.
 	/// Run the command
-	pub async fn run(&self) -> Result<()> {
-		let config = self.parse_config().await?;
-		let chain_a = config.chain_a.into_client().await?;
-		let chain_b = config.chain_b.into_client().await?;
+	pub async fn run<C: ChainConfig>(&self) -> Result<()> {
+		let mut config = self.parse_config::<C>().await?;
+		let missing_clients =
+			[(&config.chain_a, &self.config_a), (&config.chain_b, &self.config_b)]
+				.into_iter()
+				.filter(|(chain, _)| chain.client_id().is_none())
+				.map(|(_, path)| path.as_str())
+				.collect::<Vec<_>>();
+		if !missing_clients.is_empty() && (!self.create_missing_clients || self.dry_run) {
+			return Err(anyhow!(
+				"client_id is not set in {}, create the clients with `create-clients` or pass \
+				 --create-missing-clients",
+				missing_clients.join(" and ")
+			))
+		}
+		let mut chain_a = config.chain_a.clone().into_client().await?;
+		let mut chain_b = config.chain_b.clone().into_client().await?;
+		if !missing_clients.is_empty() {
+			self.create_missing_clients(&mut config, &mut chain_a, &mut chain_b).await?;
+		}
+		if self.dry_run {
+			log::info!(target: "hyperspace", "Dry run: messages are logged instead of being submitted");
+			chain_a.common_state_mut().dry_run = true;
+			chain_b.common_state_mut().dry_run = true;
+		}
+		if let Some(path) = config.core.receipts_path.as_ref() {
+			let store = ReceiptStore::new(path);
+			chain_a.common_state_mut().receipts = Some(store.clone());
+			chain_b.common_state_mut().receipts = Some(store.clone());
+			if let Some(addr) = config.core.receipts_endpoint.as_ref().and_then(|s| s.parse().ok())
+			{
+				tokio::spawn(init_receipts_server(addr, store));
+			}
+		}
+		if let Some(path) = config.core.ledger_path.as_ref() {
+			let ledger = PacketLedger::new(path);
+			chain_a.common_state_mut().ledger = Some(ledger.clone());
+			chain_b.common_state_mut().ledger = Some(ledger);
+		}
+		if let Err(e) = register_counterparty_payee(&chain_a).await {
+			log::error!(target: "hyperspace", "Failed to register the counterparty payee on {}: {:?}", chain_a.name(), e);
+		}
+		if let Err(e) = register_counterparty_payee(&chain_b).await {
+			log::error!(target: "hyperspace", "Failed to register the counterparty payee on {}: {:?}", chain_b.name(), e);
+		}
 
 		let registry =
 			Registry::new_custom(None, None).expect("this can only fail if the prefix is empty");
@@ -148,30 +560,241 @@ impl Cmd {
 		let mut metrics_handler_a = MetricsHandler::new(registry.clone(), metrics_a);
 		let mut metrics_handler_b = MetricsHandler::new(registry.clone(), metrics_b);
 		metrics_handler_a.link_with_counterparty(&mut metrics_handler_b);
+		if let Some(window) = config.core.latency_window.map(Duration::from_secs) {
+			metrics_handler_a.set_latency_window(window);
+			metrics_handler_b.set_latency_window(window);
+		}
+		let latency_report_interval = config
+			.core
+			.latency_report_interval
+			.map(Duration::from_secs)
+			.unwrap_or(DEFAULT_LATENCY_REPORT_INTERVAL);
+		tokio::spawn(report_packet_latency(
+			vec![
+				(chain_a.name().to_string(), metrics_handler_a.clone()),
+				(chain_b.name().to_string(), metrics_handler_b.clone()),
+			],
+			latency_report_interval,
+		));
 
-		if let Some(addr) = config.core.prometheus_endpoint.and_then(|s| s.parse().ok()) {
-			tokio::spawn(init_prometheus(addr, registry.clone()));
+		let prometheus = PrometheusServer::start(config.core.prometheus_endpoint, &registry);
+		let max_finality_event_age = config
+			.core
+			.max_finality_event_age
+			.map(Duration::from_secs)
+			.unwrap_or(DEFAULT_MAX_FINALITY_EVENT_AGE);
+		if let Some(addr) = config.core.health_endpoint.as_ref().and_then(|s| s.parse().ok()) {
+			let state = HealthState::new(max_finality_event_age)
+				.with_chain(&chain_a)
+				.with_chain(&chain_b);
+			tokio::spawn(init_health_server(addr, state));
+		}
+		let default_thresholds = HealthThresholds::default();
+		let thresholds = HealthThresholds {
+			max_finality_event_age,
+			max_finality_gap: config
+				.core
+				.max_finality_gap
+				.unwrap_or(default_thresholds.max_finality_gap),
+			max_rpc_latency: config
+				.core
+				.max_rpc_latency
+				.map(Duration::from_millis)
+				.unwrap_or(default_thresholds.max_rpc_latency),
+		};
+		let health_check_interval = config
+			.core
+			.health_check_interval
+			.map(Duration::from_secs)
+			.unwrap_or(DEFAULT_HEALTH_CHECK_INTERVAL);
+		tokio::spawn(watch_health(
+			chain_a.clone(),
+			chain_b.clone(),
+			thresholds,
+			health_check_interval,
+		));
+		let watch_interval = config
+			.core
+			.channel_watch_interval
+			.map(Duration::from_secs)
+			.unwrap_or(DEFAULT_CHANNEL_WATCH_INTERVAL);
+		tokio::spawn(watch_channels(chain_a.clone(), chain_b.clone(), watch_interval));
+		let expiry_check_interval = config
+			.core
+			.client_expiry_check_interval
+			.map(Duration::from_secs)
+			.unwrap_or(DEFAULT_CLIENT_EXPIRY_CHECK_INTERVAL);
+		tokio::spawn(watch_client_expiry(chain_a.clone(), chain_b.clone(), expiry_check_interval));
+		let upgrade_check_interval = config
+			.core
+			.client_upgrade_check_interval
+			.map(Duration::from_secs)
+			.unwrap_or(DEFAULT_CLIENT_UPGRADE_CHECK_INTERVAL);
+		tokio::spawn(watch_client_upgrades(
+			chain_a.clone(),
+			chain_b.clone(),
+			upgrade_check_interval,
+		));
+		let balance_check_interval = config
+			.core
+			.balance_check_interval
+			.map(Duration::from_secs)
+			.unwrap_or(DEFAULT_BALANCE_CHECK_INTERVAL);
+		tokio::spawn(watch_balances(
+			chain_a.clone(),
+			chain_b.clone(),
+			Some(metrics_handler_a.clone()),
+			Some(metrics_handler_b.clone()),
+			balance_check_interval,
+		));
+		let watchdog_window = config
+			.core
+			.watchdog_window
+			.map(Duration::from_secs)
+			.unwrap_or(DEFAULT_WATCHDOG_WINDOW);
+		tokio::spawn(watch_pipeline(chain_a.clone(), chain_b.clone(), watchdog_window));
+		if config.core.watch_misbehaviour.unwrap_or(false) {
+			let store = evidence_store(&config.core)?;
+			let (chain_a, chain_b) = (chain_a.clone(), chain_b.clone());
+			tokio::spawn(async move {
+				if let Err(e) = watch_misbehaviour(chain_a, chain_b, store).await {
+					log::error!(target: "hyperspace", "Misbehaviour watcher stopped: {:?}", e);
+				}
+			});
+		}
+		let (control, control_rx) = tokio::sync::mpsc::unbounded_channel();
+		if let (Some(endpoint), Some(token)) =
+			(&config.core.admin_endpoint, &config.core.admin_token)
+		{
+			tokio::spawn(init_admin_server(admin_addr(endpoint)?, token.clone(), control.clone()));
+		}
+		tokio::spawn(reload_on_sighup::<C>(self.clone(), registry.clone(), prometheus, control));
+
+		let (mut from_height_a, mut from_height_b) = (self.from_height_a, self.from_height_b);
+		if let Some(path) = self.restore_state.as_ref() {
+			let snapshot = RelayerSnapshot::read(path)?;
+			from_height_a = from_height_a.or(snapshot.restore(&mut chain_a));
+			from_height_b = from_height_b.or(snapshot.restore(&mut chain_b));
+		}
+		// messages whose submission was interrupted by a crash
+		if let Err(e) = resubmit_pending_messages(&chain_b, &chain_a, None).await {
+			log::error!(target: "hyperspace", "Failed to resubmit pending messages to {}: {:?}", chain_a.name(), e);
+		}
+		if let Err(e) = resubmit_pending_messages(&chain_a, &chain_b, None).await {
+			log::error!(target: "hyperspace", "Failed to resubmit pending messages to {}: {:?}", chain_b.name(), e);
+		}
+
+		let mut metrics_handler_a = Some(metrics_handler_a);
+		let mut metrics_handler_b = Some(metrics_handler_b);
+		if let Some(height) = from_height_a {
+			backfill(&mut chain_a, &mut chain_b, &mut metrics_handler_a, height).await?;
+		}
+		if let Some(height) = from_height_b {
+			backfill(&mut chain_b, &mut chain_a, &mut metrics_handler_b, height).await?;
 		}
 
-		relay(chain_a, chain_b, Some(metrics_handler_a), Some(metrics_handler_b), None).await
+		relay_with_control(
+			chain_a,
+			chain_b,
+			metrics_handler_a,
+			metrics_handler_b,
+			None,
+			Some(control_rx),
+		)
+		.await
 	}
 
 	/// Run fisherman
-	pub async fn fish(&self) -> Result<()> {
-		let config = self.parse_config().await?;
+	pub async fn fish<C: ChainConfig>(&self) -> Result<()> {
+		let config = self.parse_config::<C>().await?;
+		let store = evidence_store(&config.core)?;
+		let mut chain_a = config.chain_a.into_client().await?;
+		let mut chain_b = config.chain_b.into_client().await?;
+		chain_a.common_state_mut().dry_run = self.dry_run;
+		chain_b.common_state_mut().dry_run = self.dry_run;
+
+		if self.from_height_a.is_some() || self.from_height_b.is_some() {
+			if let Some(from) = self.from_height_a {
+				scan_misbehaviour(&chain_a, &chain_b, from, self.to_height_a, &store).await?;
+			}
+			if let Some(from) = self.from_height_b {
+				scan_misbehaviour(&chain_b, &chain_a, from, self.to_height_b, &store).await?;
+			}
+			return Ok(())
+		}
+
+		fish(chain_a, chain_b, store).await
+	}
+
+	/// Upgrades the client of each chain on its counterparty if the chain stores an upgraded client
+	/// more recent than it.
+	pub async fn upgrade_clients<C: ChainConfig>(&self) -> Result<()> {
+		let config = self.parse_config::<C>().await?;
+		let mut chain_a = config.chain_a.into_client().await?;
+		let mut chain_b = config.chain_b.into_client().await?;
+		chain_a.common_state_mut().dry_run = self.dry_run;
+		chain_b.common_state_mut().dry_run = self.dry_run;
+
+		for (source, sink) in [(&chain_a, &chain_b), (&chain_b, &chain_a)] {
+			if !upgrade_client(source, sink).await? && !self.dry_run {
+				log::info!(target: "hyperspace", "No upgrade of the client of {} on {}", source.name(), sink.name());
+			}
+		}
+		Ok(())
+	}
+
+	/// Checks the packet ledger against the commitments and receipts of both chains, printing
+	/// the discrepancies found and repairing them with `--repair`.
+	pub async fn reconcile<C: ChainConfig>(&self) -> Result<()> {
+		let config = self.parse_config::<C>().await?;
+		let path = config
+			.core
+			.ledger_path
+			.as_ref()
+			.ok_or_else(|| anyhow!("ledger_path is not set in {}", self.config_core))?;
 		let chain_a = config.chain_a.into_client().await?;
 		let chain_b = config.chain_b.into_client().await?;
+		let report = reconcile(&chain_a, &chain_b, &PacketLedger::new(path), self.repair).await?;
+		print!("{report}");
+		Ok(())
+	}
 
-		fish(chain_a, chain_b).await
+	/// Creates the clients of the chains whose config has no client id on their counterparty, at
+	/// `--client-height-a/b` or at the latest finalized height, and saves their ids to the configs.
+	async fn create_missing_clients<C: ChainConfig>(
+		&self,
+		config: &mut Config<C>,
+		chain_a: &mut C::Chain,
+		chain_b: &mut C::Chain,
+	) -> Result<()> {
+		// the client id of a chain is the one of its client on the counterparty
+		if config.chain_a.client_id().is_none() {
+			let client_id = create_client_at(&*chain_b, &*chain_a, self.client_height_a).await?;
+			log::info!(target: "hyperspace", "Created client {client_id} of {} on {}", chain_a.name(), chain_b.name());
+			chain_a.set_client_id(client_id.clone());
+			config.chain_a.set_client_id(client_id);
+		}
+		if config.chain_b.client_id().is_none() {
+			let client_id = create_client_at(&*chain_a, &*chain_b, self.client_height_b).await?;
+			log::info!(target: "hyperspace", "Created client {client_id} of {} on {}", chain_b.name(), chain_a.name());
+			chain_b.set_client_id(client_id.clone());
+			config.chain_b.set_client_id(client_id);
+		}
+		self.save_config(config).await
 	}
 
-	pub async fn create_clients(&self) -> Result<Config> {
-		let mut config = self.parse_config().await?;
+	pub async fn create_clients<C: ChainConfig>(&self) -> Result<Config<C>> {
+		let mut config = self.parse_config::<C>().await?;
 		let mut chain_a = config.chain_a.clone().into_client().await?;
 		let mut chain_b = config.chain_b.clone().into_client().await?;
 
-		let (client_id_a_on_b, client_id_b_on_a) =
-			create_clients(&mut chain_a, &mut chain_b).await?;
+		let (client_id_a_on_b, client_id_b_on_a) = create_clients_at(
+			&mut chain_a,
+			&mut chain_b,
+			self.client_height_a,
+			self.client_height_b,
+		)
+		.await?;
 		log::info!(
 			"ClientId for Chain {} on Chain {}: {}",
 			chain_b.name(),
@@ -190,13 +813,13 @@ impl Cmd {
 		Ok(config)
 	}
 
-	pub async fn create_connection(&self) -> Result<Config> {
+	pub async fn create_connection<C: ChainConfig>(&self) -> Result<Config<C>> {
 		let delay_period_seconds: NonZeroU64 = self
 			.delay_period
 			.expect("delay_period should be provided when creating a connection")
 			.into();
 		let delay = Duration::from_secs(delay_period_seconds.into());
-		let mut config = self.parse_config().await?;
+		let mut config = self.parse_config::<C>().await?;
 		let mut chain_a = config.chain_a.clone().into_client().await?;
 		let mut chain_b = config.chain_b.clone().into_client().await?;
 
@@ -220,7 +843,7 @@ impl Cmd {
 		Ok(config)
 	}
 
-	pub async fn create_channel(&self) -> Result<Config> {
+	pub async fn create_channel<C: ChainConfig>(&self) -> Result<Config<C>> {
 		let port_id = PortId::from_str(
 			self.port_id
 				.as_ref()
@@ -234,7 +857,7 @@ impl Cmd {
 			.expect("version must be specified when creating a channel")
 			.clone();
 		let order = self.order.as_ref().expect("order must be specified when creating a channel, expected one of 'ordered' or 'unordered'").as_str();
-		let mut config = self.parse_config().await?;
+		let mut config = self.parse_config::<C>().await?;
 		let mut chain_a = config.chain_a.clone().into_client().await?;
 		let mut chain_b = config.chain_b.clone().into_client().await?;
 
@@ -267,16 +890,10 @@ impl Cmd {
 		Ok(config)
 	}
 
-	pub async fn save_config(&self, new_config: &Config) -> Result<()> {
-		let path_a = self.out_config_a.as_ref().cloned().unwrap_or_else(|| self.config_a.clone());
-		let path_b = self.out_config_b.as_ref().cloned().unwrap_or_else(|| self.config_b.clone());
-		write_config(path_a, &new_config.chain_a).await?;
-		write_config(path_b, &new_config.chain_b).await
+	pub async fn save_config<C: ChainConfig>(&self, new_config: &Config<C>) -> Result<()> {
+		let path_a = self.out_config_a.as_ref().unwrap_or(&self.config_a);
+		let path_b = self.out_config_b.as_ref().unwrap_or(&self.config_b);
+		write_config(path_a, &new_config.chain_a, &self.config_a, Some(CHAIN_A_ENV_PREFIX)).await?;
+		write_config(path_b, &new_config.chain_b, &self.config_b, Some(CHAIN_B_ENV_PREFIX)).await
 	}
 }
-
-async fn write_config(path: String, config: &AnyConfig) -> Result<()> {
-	tokio::fs::write(path.parse::<PathBuf>()?, toml::to_string(config)?)
-		.await
-		.map_err(|e| anyhow!(e))
-}
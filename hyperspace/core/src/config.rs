@@ -0,0 +1,226 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading and writing of the config files.
+//!
+//! Configs are TOML, YAML or JSON files, depending on their extension (`.yaml`/`.yml`, `.json`,
+//! TOML otherwise). Any key of a config can be overridden by an environment variable named after
+//! the prefix of the config ([`CORE_ENV_PREFIX`], [`CHAIN_A_ENV_PREFIX`] or
+//! [`CHAIN_B_ENV_PREFIX`]) and the path of the key, upper-cased and separated by double
+//! underscores, e.g. `HYPERSPACE_CORE__LOGGING__FILTER=debug` or
+//! `HYPERSPACE_CHAIN_A__SIGNER__PATHS__0=key.json`. Values are parsed as JSON, and taken as
+//! strings if they aren't valid JSON or the overridden value is a string.
+//!
+//! Keys that aren't part of the config are rejected, instead of being silently ignored.
+
+use anyhow::anyhow;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Prefix of the environment variables overriding the core config.
+pub const CORE_ENV_PREFIX: &str = "HYPERSPACE_CORE";
+/// Prefix of the environment variables overriding the config of chain A.
+pub const CHAIN_A_ENV_PREFIX: &str = "HYPERSPACE_CHAIN_A";
+/// Prefix of the environment variables overriding the config of chain B.
+pub const CHAIN_B_ENV_PREFIX: &str = "HYPERSPACE_CHAIN_B";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+	Toml,
+	Yaml,
+	Json,
+}
+
+impl ConfigFormat {
+	/// Format of the config at `path`, according to its extension.
+	pub fn from_path(path: &Path) -> Self {
+		match path.extension().and_then(|extension| extension.to_str()) {
+			Some("yaml" | "yml") => Self::Yaml,
+			Some("json") => Self::Json,
+			_ => Self::Toml,
+		}
+	}
+
+	fn parse(self, content: &str) -> anyhow::Result<Value> {
+		Ok(match self {
+			Self::Toml => serde_json::to_value(toml::from_str::<toml::Value>(content)?)?,
+			Self::Yaml => serde_yaml::from_str(content)?,
+			Self::Json => serde_json::from_str(content)?,
+		})
+	}
+
+	fn serialize<T: Serialize>(self, config: &T) -> anyhow::Result<String> {
+		Ok(match self {
+			Self::Toml => toml::to_string(config)?,
+			Self::Yaml => serde_yaml::to_string(config)?,
+			Self::Json => serde_json::to_string_pretty(config)?,
+		})
+	}
+}
+
+/// Reads the config at `path`, overridden by the environment variables starting with
+/// `env_prefix`, if any. Fails if the config has keys that aren't part of `T`.
+pub fn read_config<T>(path: impl AsRef<Path>, env_prefix: Option<&str>) -> anyhow::Result<T>
+where
+	T: Serialize + DeserializeOwned,
+{
+	let path = path.as_ref();
+	let content = std::fs::read_to_string(path)
+		.map_err(|e| anyhow!("Failed to read {}: {e}", path.display()))?;
+	let mut value = ConfigFormat::from_path(path)
+		.parse(&content)
+		.map_err(|e| anyhow!("Failed to parse {}: {e}", path.display()))?;
+	if let Some(prefix) = env_prefix {
+		apply_env_overrides(&mut value, prefix, std::env::vars())?;
+	}
+	let config = T::deserialize(&value).map_err(|e| anyhow!("Invalid {}: {e}", path.display()))?;
+
+	// keys ignored by the deserialization are missing from the serialized config
+	let mut unknown = vec![];
+	unknown_keys(&value, &serde_json::to_value(&config)?, "", &mut unknown);
+	if !unknown.is_empty() {
+		return Err(anyhow!("Unknown keys in {}: {}", path.display(), unknown.join(", ")))
+	}
+	Ok(config)
+}
+
+/// Writes `config` to `path`, in the format given by its extension. The keys overridden by the
+/// environment variables starting with `env_prefix` keep the value they have in the `source` the
+/// config was read from, so that the secrets passed through the environment aren't written.
+pub async fn write_config<T: Serialize>(
+	path: impl AsRef<Path>,
+	config: &T,
+	source: impl AsRef<Path>,
+	env_prefix: Option<&str>,
+) -> anyhow::Result<()> {
+	let (path, source) = (path.as_ref(), source.as_ref());
+	let mut value = serde_json::to_value(config)?;
+	if let Some(prefix) = env_prefix {
+		let source = match std::fs::read_to_string(source) {
+			Ok(content) => ConfigFormat::from_path(source).parse(&content)?,
+			Err(_) => Value::Null,
+		};
+		restore_env_overrides(&mut value, &source, prefix, std::env::vars());
+	}
+	strip_nulls(&mut value);
+	let content = ConfigFormat::from_path(path).serialize(&value)?;
+	tokio::fs::write(path, content)
+		.await
+		.map_err(|e| anyhow!("Failed to write {}: {e}", path.display()))
+}
+
+/// Keys of the `{prefix}__{PATH}` variables of `vars`, with the variable names.
+fn env_overrides(
+	prefix: &str,
+	vars: impl IntoIterator<Item = (String, String)>,
+) -> impl Iterator<Item = (String, Vec<String>, String)> {
+	let prefix = format!("{prefix}__");
+	vars.into_iter().filter_map(move |(name, raw)| {
+		let keys = name.strip_prefix(&prefix)?.split("__").map(str::to_lowercase).collect();
+		Some((name, keys, raw))
+	})
+}
+
+/// Sets the keys of `value` overridden by the `{prefix}__{PATH}` variables of `vars`.
+fn apply_env_overrides(
+	value: &mut Value,
+	prefix: &str,
+	vars: impl IntoIterator<Item = (String, String)>,
+) -> anyhow::Result<()> {
+	for (name, keys, raw) in env_overrides(prefix, vars) {
+		let mut target = &mut *value;
+		for key in keys {
+			if target.is_null() {
+				*target = Value::Object(Default::default());
+			}
+			target = match target {
+				Value::Array(items) => key
+					.parse::<usize>()
+					.ok()
+					.and_then(|index| items.get_mut(index))
+					.ok_or_else(|| anyhow!("{name} overrides a missing item of an array"))?,
+				Value::Object(keys) => keys.entry(key).or_insert(Value::Null),
+				_ => return Err(anyhow!("{name} overrides a key of a value that has none")),
+			};
+		}
+		*target = if target.is_string() {
+			Value::String(raw)
+		} else {
+			serde_json::from_str(&raw).unwrap_or(Value::String(raw))
+		};
+	}
+	Ok(())
+}
+
+/// Sets the keys of `value` overridden by the `{prefix}__{PATH}` variables of `vars` back to
+/// their value in `source`, removing the ones it doesn't have.
+fn restore_env_overrides(
+	value: &mut Value,
+	source: &Value,
+	prefix: &str,
+	vars: impl IntoIterator<Item = (String, String)>,
+) {
+	let pointer = |keys: &[String]| keys.iter().map(|key| format!("/{key}")).collect::<String>();
+	for (_, keys, _) in env_overrides(prefix, vars) {
+		let Some((last, parents)) = keys.split_last() else { continue };
+		let original = source.pointer(&pointer(&keys)).cloned();
+		match (value.pointer_mut(&pointer(parents)), original) {
+			(Some(Value::Object(keys)), Some(original)) => {
+				keys.insert(last.clone(), original);
+			},
+			(Some(Value::Object(keys)), None) => {
+				keys.remove(last);
+			},
+			(Some(Value::Array(items)), Some(original)) => {
+				let item = last.parse::<usize>().ok().and_then(|index| items.get_mut(index));
+				if let Some(item) = item {
+					*item = original;
+				}
+			},
+			_ => {},
+		}
+	}
+}
+
+/// Removes the null values of the objects of `value`, which TOML can't represent.
+fn strip_nulls(value: &mut Value) {
+	match value {
+		Value::Object(keys) => {
+			keys.retain(|_, value| !value.is_null());
+			keys.values_mut().for_each(strip_nulls);
+		},
+		Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+		_ => {},
+	}
+}
+
+/// Pushes the paths of the keys of `input` that are missing from `parsed` to `unknown`.
+fn unknown_keys(input: &Value, parsed: &Value, path: &str, unknown: &mut Vec<String>) {
+	match (input, parsed) {
+		(Value::Object(input), Value::Object(parsed)) =>
+			for (key, input) in input {
+				let path = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+				match parsed.get(key) {
+					Some(parsed) => unknown_keys(input, parsed, &path, unknown),
+					None => unknown.push(path),
+				}
+			},
+		(Value::Array(input), Value::Array(parsed)) =>
+			for (index, (input, parsed)) in input.iter().zip(parsed).enumerate() {
+				unknown_keys(input, parsed, &format!("{path}[{index}]"), unknown);
+			},
+		_ => {},
+	}
+}
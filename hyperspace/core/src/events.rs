@@ -14,7 +14,7 @@
 
 #[cfg(feature = "testing")]
 use crate::send_packet_relay::packet_relay_status;
-use crate::Mode;
+use crate::{packets::utils::construct_timeout_message, telemetry::packet_id, Mode};
 use codec::Encode;
 use ibc::{
 	core::{
@@ -27,7 +27,7 @@ use ibc::{
 			},
 		},
 		ics04_channel::{
-			channel::{ChannelEnd, Counterparty as ChannelCounterparty, State},
+			channel::{ChannelEnd, Counterparty as ChannelCounterparty, Order, State},
 			msgs::{
 				acknowledgement::MsgAcknowledgement, chan_close_confirm::MsgChannelCloseConfirm,
 				chan_open_ack::MsgChannelOpenAck, chan_open_confirm::MsgChannelOpenConfirm,
@@ -35,7 +35,7 @@ use ibc::{
 			},
 		},
 		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes},
-		ics24_host::identifier::ConnectionId,
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
 	},
 	events::{IbcEvent, IbcEventType},
 	proofs::{ConsensusProof, Proofs},
@@ -44,7 +44,9 @@ use ibc::{
 };
 use ibc_proto::google::protobuf::Any;
 use pallet_ibc::light_clients::AnyClientState;
-use primitives::{error::Error, mock::LocalClientTypes, Chain};
+use primitives::{
+	error::Error, mock::LocalClientTypes, packet_info_to_packet, query_undelivered_sequences, Chain,
+};
 use std::str::FromStr;
 use tendermint_proto::Protobuf;
 
@@ -58,6 +60,7 @@ pub struct ConnectionProof {
 /// This parses events coming from a source chain
 /// Returns a tuple of messages, with the first item being packets that are ready to be sent to the
 /// sink chain. And the second item being packet timeouts that should be sent to the source.
+#[tracing::instrument(skip_all, err, fields(source = source.name(), sink = sink.name()))]
 pub async fn parse_events(
 	source: &mut impl Chain,
 	sink: &mut impl Chain,
@@ -67,6 +70,10 @@ pub async fn parse_events(
 	let mut messages = vec![];
 	// 1. translate events to messages
 	for event in events {
+		if !source.common_state().event_filter.allows(&event) {
+			log::trace!(target: "hyperspace", "Skipping {} event filtered out on {}", event.event_type().as_str(), source.name());
+			continue
+		}
 		match event {
 			IbcEvent::OpenInitConnection(open_init) => {
 				if let Some(connection_id) = open_init.connection_id() {
@@ -437,7 +444,46 @@ pub async fn parse_events(
 
 				let value = msg.encode_vec()?;
 				let msg = Any { value, type_url: msg.type_url() };
-				messages.push(msg)
+				messages.push(msg);
+				messages.extend(
+					timeout_on_close_messages(
+						&*source,
+						&*sink,
+						&channel_end,
+						channel_id,
+						close_init.port_id.clone(),
+						close_init.height(),
+					)
+					.await?,
+				);
+			},
+			IbcEvent::CloseConfirmChannel(close_confirm) => {
+				let Some(channel_id) = close_confirm.channel_id else { continue };
+				let channel_response = source
+					.query_channel_end(
+						close_confirm.height(),
+						channel_id,
+						close_confirm.port_id.clone(),
+					)
+					.await?;
+				let channel_end =
+					ChannelEnd::try_from(channel_response.channel.ok_or_else(|| {
+						Error::Custom(format!(
+							"[get_messages_for_events - close_chan_confirm] ChannelEnd not found for {:?}/{:?}",
+							channel_id, close_confirm.port_id
+						))
+					})?)?;
+				messages.extend(
+					timeout_on_close_messages(
+						&*source,
+						&*sink,
+						&channel_end,
+						channel_id,
+						close_confirm.port_id.clone(),
+						close_confirm.height(),
+					)
+					.await?,
+				);
 			},
 			IbcEvent::SendPacket(send_packet) => {
 				#[cfg(feature = "testing")]
@@ -450,6 +496,21 @@ pub async fn parse_events(
 				// 3. otherwise skip.
 				let port_id = send_packet.packet.source_port.clone();
 				let channel_id = send_packet.packet.source_channel;
+				tracing::info!(
+					packet_id = %packet_id(&send_packet.packet),
+					height = %send_packet.height,
+					"SendPacket detected"
+				);
+				if source.common_state().is_channel_paused(&channel_id, &port_id) {
+					log::debug!(target: "hyperspace", "Skipping packet relay because channel {channel_id}/{port_id} is paused");
+					continue
+				}
+				if let Some(reason) =
+					source.common_state().channel_halt_reason(&channel_id, &port_id)
+				{
+					log::debug!(target: "hyperspace", "Skipping packet relay because channel {channel_id}/{port_id} is halted: {reason}");
+					continue
+				}
 				let channel_response = source
 					.query_channel_end(send_packet.height, channel_id, port_id.clone())
 					.await?;
@@ -471,6 +532,12 @@ pub async fn parse_events(
 					ConnectionEnd::try_from(connection_response.connection.ok_or_else(|| {
 						Error::Custom(format!("ConnectionEnd not found for {connection_id:?}"))
 					})?)?;
+				if channel_end.ordering == Order::Ordered {
+					// the packets of ordered channels are relayed in sequence by
+					// `query_ready_and_timed_out_packets`
+					log::debug!(target: "hyperspace", "Skipping packet relay because channel {channel_id}/{port_id} is ordered");
+					continue
+				}
 				if !connection_end.delay_period().is_zero() {
 					// We can't send this packet immediately because of connection delays
 					log::debug!(
@@ -512,11 +579,26 @@ pub async fn parse_events(
 				let value = msg.encode_vec()?;
 				let msg = Any { value, type_url: msg.type_url() };
 				messages.push(msg);
+				tracing::info!(packet_id = %packet_id(&packet), %proof_height, "MsgRecvPacket constructed");
 				log::debug!(target: "hyperspace", "Sending packet {:?}", packet);
 			},
 			IbcEvent::WriteAcknowledgement(write_ack) => {
 				let port_id = &write_ack.packet.destination_port.clone();
 				let channel_id = &write_ack.packet.destination_channel.clone();
+				tracing::info!(
+					packet_id = %packet_id(&write_ack.packet),
+					height = %write_ack.height,
+					"WriteAcknowledgement detected"
+				);
+				if source.common_state().is_channel_paused(channel_id, port_id) {
+					log::debug!(target: "hyperspace", "Skipping write acknowledgement because channel {channel_id}/{port_id} is paused");
+					continue
+				}
+				if let Some(reason) = source.common_state().channel_halt_reason(channel_id, port_id)
+				{
+					log::debug!(target: "hyperspace", "Skipping write acknowledgement because channel {channel_id}/{port_id} is halted: {reason}");
+					continue
+				}
 				let channel_response = source
 					.query_channel_end(write_ack.height, *channel_id, port_id.clone())
 					.await?;
@@ -538,6 +620,10 @@ pub async fn parse_events(
 					ConnectionEnd::try_from(connection_response.connection.ok_or_else(|| {
 						Error::Custom(format!("ConnectionEnd not found for {connection_id:?}"))
 					})?)?;
+				if channel_end.ordering == Order::Ordered {
+					log::debug!(target: "hyperspace", "Skipping write acknowledgement because channel {channel_id}/{port_id} is ordered");
+					continue
+				}
 				if !connection_end.delay_period().is_zero() {
 					log::debug!(target: "hyperspace", "Skipping write acknowledgement because of connection delay {:?}",
 						connection_end.delay_period());
@@ -558,6 +644,7 @@ pub async fn parse_events(
 					.expect("Proof height should be present");
 				let proof_height =
 					Height::new(proof_height.revision_number, proof_height.revision_height);
+				tracing::info!(packet_id = %packet_id(&packet), %proof_height, "MsgAcknowledgement constructed");
 				let msg = MsgAcknowledgement {
 					packet,
 					acknowledgement: acknowledgement.into(),
@@ -582,6 +669,81 @@ pub async fn parse_events(
 	Ok(messages)
 }
 
+/// Returns the `MsgTimeoutOnClose` of the packets sent from `sink` on the counterparty of the
+/// channel end `port_id/channel_id` that `source` closed at `height`, and which it didn't receive.
+/// They're proven at `height`, so if the connection has a delay period they're left to
+/// [`crate::packets::query_ready_and_timed_out_packets`] instead.
+async fn timeout_on_close_messages(
+	source: &impl Chain,
+	sink: &impl Chain,
+	channel_end: &ChannelEnd,
+	channel_id: ChannelId,
+	port_id: PortId,
+	height: Height,
+) -> Result<Vec<Any>, anyhow::Error> {
+	if !source.channel_whitelist().contains(&(channel_id, port_id.clone())) {
+		return Ok(vec![])
+	}
+	let connection_id = channel_end
+		.connection_hops
+		.get(0)
+		.ok_or_else(|| Error::Custom("Channel end missing connection id".to_string()))?
+		.clone();
+	let connection_response = source.query_connection_end(height, connection_id.clone()).await?;
+	let connection_end =
+		ConnectionEnd::try_from(connection_response.connection.ok_or_else(|| {
+			Error::Custom(format!("ConnectionEnd not found for {connection_id:?}"))
+		})?)?;
+	if !connection_end.delay_period().is_zero() {
+		log::debug!(target: "hyperspace", "Leaving the timeouts on close of channel {channel_id}/{port_id} to the packet scanner because of connection delays");
+		return Ok(vec![])
+	}
+
+	let counterparty = channel_end.counterparty();
+	let counterparty_channel_id = counterparty
+		.channel_id
+		.ok_or_else(|| Error::Custom(format!("Counterparty of channel {channel_id} has no id")))?;
+	let counterparty_port_id = counterparty.port_id.clone();
+	let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+	let mut seqs = query_undelivered_sequences(
+		sink_height,
+		height,
+		counterparty_channel_id,
+		counterparty_port_id.clone(),
+		sink,
+		source,
+	)
+	.await?;
+	seqs.truncate(sink.common_state().max_packets_to_process);
+	if seqs.is_empty() {
+		return Ok(vec![])
+	}
+	log::info!(target: "hyperspace", "Timing out {} packets of {counterparty_port_id}/{counterparty_channel_id} on close", seqs.len());
+
+	let next_sequence_recv = source
+		.query_next_sequence_recv(height, &port_id, &channel_id)
+		.await?
+		.next_sequence_receive;
+	let mut messages = vec![];
+	let send_packets = sink
+		.query_send_packets(counterparty_channel_id, counterparty_port_id, seqs)
+		.await?;
+	for send_packet in send_packets {
+		let packet = packet_info_to_packet(&send_packet);
+		let msg = construct_timeout_message(
+			sink,
+			source,
+			channel_end,
+			packet,
+			next_sequence_recv,
+			height,
+		)
+		.await?;
+		messages.push(msg);
+	}
+	Ok(messages)
+}
+
 /// Fetch the consensus state proof for the sink chain.
 async fn query_host_consensus_state_proof(
 	sink: &impl Chain,
@@ -0,0 +1,79 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Keeps the light clients from expiring while no packets flow.
+//!
+//! A client whose latest consensus state is older than its trusting period can't be updated
+//! anymore and has to be recovered by governance. The trusting period of `07-tendermint` clients
+//! is part of their state; for the other clients, the `trusting_period` of the chain's
+//! [`primitives::SecurityParams`] is used as the equivalent window.
+//!
+//! [`watch_client_expiry`] periodically checks the age of the latest consensus state of the client
+//! of each chain on its counterparty, and requests an update of the client once a third of the
+//! window has elapsed, leaving plenty of time for the update to land. The update is sent with the
+//! next finality event of the chain, even if it's optional.
+
+use ibc::core::ics02_client::client_state::ClientState;
+use primitives::{health::query_client_age, Chain};
+use std::time::Duration;
+
+/// Default interval between two checks of the client ages.
+pub const DEFAULT_CLIENT_EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Checks the clients of both chains every `interval`, requesting updates of the ones that are
+/// getting close to expiry. The chains are expected to share their
+/// [`primitives::CommonClientState`] with the relay loop.
+pub async fn watch_client_expiry<A: Chain, B: Chain>(chain_a: A, chain_b: B, interval: Duration) {
+	let mut interval = tokio::time::interval(interval);
+	loop {
+		interval.tick().await;
+		if let Err(e) = check_client_expiry(&chain_a, &chain_b).await {
+			log::warn!(target: "hyperspace", "Failed to check the client of {} on {}: {:?}", chain_a.name(), chain_b.name(), e);
+		}
+		if let Err(e) = check_client_expiry(&chain_b, &chain_a).await {
+			log::warn!(target: "hyperspace", "Failed to check the client of {} on {}: {:?}", chain_b.name(), chain_a.name(), e);
+		}
+	}
+}
+
+/// Requests an update of the client of `source` hosted on `sink` if it's getting close to expiry.
+async fn check_client_expiry(source: &impl Chain, sink: &impl Chain) -> anyhow::Result<()> {
+	let (client_state, elapsed) = query_client_age(sink, source.client_id()).await?;
+	if client_state.frozen_height().is_some() {
+		return Ok(())
+	}
+	let window = source.security_params().expiry_window(&client_state);
+
+	if elapsed >= window * 2 / 3 {
+		log::error!(
+			target: "hyperspace",
+			"ALERT: client of {} on {} was last updated {:?} ago and expires in {:?}",
+			source.name(),
+			sink.name(),
+			elapsed,
+			window.saturating_sub(elapsed)
+		);
+	}
+	if elapsed >= window / 3 {
+		log::info!(
+			target: "hyperspace",
+			"Client of {} on {} was last updated {:?} ago, requesting an update",
+			source.name(),
+			sink.name(),
+			elapsed
+		);
+		source.common_state().request_client_update();
+	}
+	Ok(())
+}
@@ -0,0 +1,94 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracking of the transfers routed over several chains by the packet forward middleware.
+//!
+//! A transfer whose memo asks for a forward is tracked from its "send packet" event until its
+//! acknowledgement, which the forwarding chain only writes once the forwarded packet is
+//! acknowledged, so the observed latency covers the whole route. When a forwarding chain receives
+//! such a transfer, the packet it sends in the same block to the next hop is linked to it and
+//! tracked as well.
+
+use ibc::{
+	applications::transfer::packet::PacketData, core::ics04_channel::packet::Packet,
+	events::IbcEvent,
+};
+use metrics::handler::MetricsHandler;
+use primitives::pfm::ForwardMemo;
+
+fn transfer_data(packet: &Packet) -> Option<PacketData> {
+	serde_json::from_slice(&packet.data).ok()
+}
+
+fn forward_memo(packet: &Packet) -> Option<ForwardMemo> {
+	transfer_data(packet).and_then(|data| ForwardMemo::parse(&data.memo))
+}
+
+/// Starts tracking the forwarded transfers among the `events` of `chain`.
+pub fn track_forwarded_packets(chain: &str, events: &[IbcEvent], metrics: &MetricsHandler) {
+	for event in events {
+		match event {
+			IbcEvent::SendPacket(send) => {
+				let Some(memo) = forward_memo(&send.packet) else { continue };
+				let packet = &send.packet;
+				log::debug!(
+					target: "hyperspace",
+					"Packet {}/{}/{} on {chain} is routed over {} more hops",
+					packet.source_port,
+					packet.source_channel,
+					packet.sequence,
+					memo.forward.hops()
+				);
+				metrics.track_forwarded_packet(packet, 1 + memo.forward.hops());
+			},
+			IbcEvent::ReceivePacket(recv) => {
+				let Some(ForwardMemo { forward }) = forward_memo(&recv.packet) else { continue };
+				let forwarded = events.iter().find_map(|event| match event {
+					IbcEvent::SendPacket(send)
+						if send.height == recv.height &&
+							send.packet.source_port.as_str() == forward.port &&
+							send.packet.source_channel.to_string() == forward.channel &&
+							transfer_data(&send.packet).map_or(false, |data| {
+								data.receiver.as_ref() == forward.receiver
+							}) =>
+						Some(&send.packet),
+					_ => None,
+				});
+				let received = &recv.packet;
+				let Some(forwarded) = forwarded else {
+					log::debug!(
+						target: "hyperspace",
+						"Packet {}/{}/{} received on {chain} wasn't forwarded in the same block",
+						received.destination_port,
+						received.destination_channel,
+						received.sequence
+					);
+					continue
+				};
+				log::info!(
+					target: "hyperspace",
+					"Packet {}/{}/{} received on {chain} was forwarded as {}/{}/{}",
+					received.destination_port,
+					received.destination_channel,
+					received.sequence,
+					forwarded.source_port,
+					forwarded.source_channel,
+					forwarded.sequence
+				);
+				metrics.track_forwarded_packet(forwarded, forward.hops());
+			},
+			_ => {},
+		}
+	}
+}
@@ -0,0 +1,205 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HTTP endpoints that let orchestrators (e.g. Kubernetes probes) detect a stalled relayer.
+//!
+//! - `/health` always answers `200 OK` while the process is running (liveness).
+//! - `/ready` answers `200 OK` only if every chain produced a finality event within the configured
+//!   threshold, and `503 Service Unavailable` otherwise (readiness).
+//!
+//! Both endpoints return the [`RelayStatus`] of each chain as a JSON body.
+//!
+//! Independently of the endpoints, [`watch_health`] periodically probes both chains (see
+//! [`ChainHealth`]) and records in their common state why they're stalled, if they are. The relay
+//! loop doesn't process finality events while either chain is stalled, and resumes once both
+//! are healthy again. Stalled chains are also reported as not ready.
+//!
+//! The relay loop additionally runs the [`ChainHealth::health_check`] of both chains on startup
+//! and every [`HEALTH_REPORT_INTERVAL`] (see [`report_health`]), which also covers the balances
+//! of the relayer keys and the expiry of the clients, and exports the result as metrics.
+
+use anyhow::anyhow;
+use hyper::{
+	http::StatusCode,
+	server::Server,
+	service::{make_service_fn, service_fn},
+	Body, Request, Response,
+};
+use metrics::handler::MetricsHandler;
+use primitives::{Chain, ChainHealth, HealthThresholds, RelayStatus, StallReason};
+use std::{
+	collections::BTreeMap,
+	net::SocketAddr,
+	sync::{Arc, Mutex},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Default maximum age of the last finality event before a chain is reported as not ready.
+pub const DEFAULT_MAX_FINALITY_EVENT_AGE: Duration = Duration::from_secs(600);
+
+/// Default interval between two health probes of the chains.
+pub const DEFAULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Interval between two health checks of the chains by the relay loop.
+pub const HEALTH_REPORT_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Relay status of the chains served by the health endpoints.
+#[derive(Clone)]
+pub struct HealthState {
+	chains: Vec<(String, Arc<Mutex<RelayStatus>>, Arc<Mutex<Option<StallReason>>>)>,
+	max_finality_event_age: Duration,
+}
+
+impl HealthState {
+	pub fn new(max_finality_event_age: Duration) -> Self {
+		Self { chains: vec![], max_finality_event_age }
+	}
+
+	/// Tracks the relay status of `chain`.
+	pub fn with_chain(mut self, chain: &impl Chain) -> Self {
+		let state = chain.common_state();
+		self.chains.push((
+			chain.name().to_string(),
+			state.relay_status.clone(),
+			state.stall_reason.clone(),
+		));
+		self
+	}
+
+	fn statuses(&self) -> BTreeMap<String, (RelayStatus, Option<StallReason>)> {
+		self.chains
+			.iter()
+			.map(|(name, status, stall_reason)| {
+				(name.clone(), (status.lock().unwrap().clone(), *stall_reason.lock().unwrap()))
+			})
+			.collect()
+	}
+
+	fn is_ready(&self, statuses: &BTreeMap<String, (RelayStatus, Option<StallReason>)>) -> bool {
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+		statuses.values().all(|(status, stall_reason)| {
+			stall_reason.is_none() &&
+				status.last_finality_event.map_or(false, |last| {
+					now.saturating_sub(last) <= self.max_finality_event_age.as_secs()
+				})
+		})
+	}
+}
+
+/// Probes both chains every `interval`, recording why they're stalled according to `thresholds`
+/// in their common state. The chains are expected to share their
+/// [`primitives::CommonClientState`] with the relay loop.
+pub async fn watch_health<A: Chain, B: Chain>(
+	chain_a: A,
+	chain_b: B,
+	thresholds: HealthThresholds,
+	interval: Duration,
+) {
+	let mut interval = tokio::time::interval(interval);
+	loop {
+		interval.tick().await;
+		update_stall_reason(&chain_a, &thresholds).await;
+		update_stall_reason(&chain_b, &thresholds).await;
+	}
+}
+
+async fn update_stall_reason(chain: &impl Chain, thresholds: &HealthThresholds) {
+	let reason = chain.check_stalled(thresholds).await;
+	match (chain.common_state().set_stall_reason(reason), reason) {
+		(None, Some(reason)) => log::error!(
+			target: "hyperspace",
+			"ALERT: {} is stalled: {reason}. Pausing relaying until it recovers",
+			chain.name()
+		),
+		(Some(_), None) =>
+			log::info!(target: "hyperspace", "{} recovered, resuming relaying", chain.name()),
+		(Some(previous), Some(reason)) if previous != reason =>
+			log::warn!(target: "hyperspace", "{} is still stalled: {reason}", chain.name()),
+		_ => (),
+	}
+}
+
+/// Runs the health check of `chain`, raising an alert if anything prevents it from being relayed
+/// and recording the result in `metrics`.
+pub async fn report_health(
+	chain: &impl Chain,
+	counterparty: &impl Chain,
+	metrics: Option<&MetricsHandler>,
+) {
+	let report = match chain.health_check(counterparty, &HealthThresholds::default()).await {
+		Ok(report) => report,
+		Err(e) => {
+			log::warn!(target: "hyperspace", "Failed to check the health of {}: {:?}", chain.name(), e);
+			return
+		},
+	};
+	if let Some(metrics) = metrics {
+		metrics.update_health(report.is_healthy(), report.client_expires_in);
+	}
+	let problems = report.problems();
+	if problems.is_empty() {
+		log::debug!(target: "hyperspace", "{} is healthy: {:?}", chain.name(), report);
+	} else {
+		log::error!(target: "hyperspace", "ALERT: {} is unhealthy: {}", chain.name(), problems.join(", "));
+	}
+}
+
+async fn request_health(
+	req: Request<Body>,
+	state: HealthState,
+) -> Result<Response<Body>, hyper::http::Error> {
+	let statuses = state.statuses();
+	let ready = state.is_ready(&statuses);
+	let status = match req.uri().path() {
+		"/health" => StatusCode::OK,
+		"/ready" if ready => StatusCode::OK,
+		"/ready" => StatusCode::SERVICE_UNAVAILABLE,
+		_ =>
+			return Response::builder().status(StatusCode::NOT_FOUND).body(Body::from("Not found.")),
+	};
+	let chains = statuses
+		.into_iter()
+		.map(|(name, (status, stall_reason))| {
+			let mut status = serde_json::to_value(status).unwrap_or_default();
+			status["stall_reason"] = serde_json::json!(stall_reason);
+			(name, status)
+		})
+		.collect::<BTreeMap<_, _>>();
+	let body = serde_json::json!({ "ready": ready, "chains": chains });
+
+	Response::builder()
+		.status(status)
+		.header("Content-Type", "application/json")
+		.body(Body::from(body.to_string()))
+}
+
+/// Starts an HTTP server that serves the health endpoints.
+pub async fn init_health_server(addr: SocketAddr, state: HealthState) -> anyhow::Result<()> {
+	let listener = tokio::net::TcpListener::bind(&addr)
+		.await
+		.map_err(|_| anyhow!("Health server port {addr} already in use."))?;
+	let listener = hyper::server::conn::AddrIncoming::from_listener(listener)?;
+
+	let service = make_service_fn(move |_| {
+		let state = state.clone();
+
+		async move {
+			Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+				request_health(req, state.clone())
+			}))
+		}
+	});
+
+	Server::builder(listener).serve(service).await.map_err(Into::into)
+}
@@ -0,0 +1,305 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording of the relayed packets in the [`PacketLedger`], and reconciliation of the ledger
+//! with the chains.
+//!
+//! [`reconcile`] checks the recorded lifecycle of every packet of the relayed channels against
+//! the packet commitments left on the chain it was sent from and the receipts of the chain it was
+//! sent to, reporting the packets whose ledger entries are missing or contradict the chains.
+
+use crate::packets::relayed_packets;
+use anyhow::anyhow;
+use ibc::{
+	core::{
+		ics04_channel::packet::Packet,
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	events::IbcEvent,
+};
+use ibc_proto::google::protobuf::Any;
+use primitives::{Chain, LedgerEntry, PacketDirection, PacketLedger, PacketStatus};
+use std::{
+	collections::{BTreeMap, BTreeSet, HashSet},
+	fmt,
+};
+
+/// Records the packets sent in `events` of `chain`, if it keeps a ledger. Failures are only
+/// logged.
+pub fn record_sent_packets(chain: &impl Chain, events: &[IbcEvent]) {
+	let Some(ledger) = chain.common_state().ledger.as_ref() else { return };
+	let entries = events
+		.iter()
+		.filter_map(|event| match event {
+			IbcEvent::SendPacket(send) =>
+				Some(LedgerEntry::new(chain.name(), PacketStatus::Observed, &send.packet, None)),
+			_ => None,
+		})
+		.collect::<Vec<_>>();
+	if let Err(e) = ledger.append(&entries) {
+		log::warn!(target: "hyperspace", "Failed to record the packets sent from {}: {e:?}", chain.name());
+	}
+}
+
+/// Records the packets delivered by the successful submission of `msgs` to `sink`, if it keeps a
+/// ledger. Failures are only logged, the messages were submitted anyway.
+pub fn record_delivered_packets<C: Chain>(sink: &C, msgs: &[Any], tx_id: &C::TransactionId) {
+	let Some(ledger) = sink.common_state().ledger.as_ref() else { return };
+	let tx_hash = format!("{tx_id:?}");
+	let entries = relayed_packets(msgs)
+		.into_iter()
+		.filter_map(|(direction, packet)| {
+			let status = match direction {
+				PacketDirection::Recv => PacketStatus::Relayed,
+				PacketDirection::Ack => PacketStatus::Acknowledged,
+				PacketDirection::Timeout => PacketStatus::TimedOut,
+			};
+			let packet = Packet::try_from(packet).ok()?;
+			Some(LedgerEntry::new(sink.name(), status, &packet, Some(tx_hash.clone())))
+		})
+		.collect::<Vec<_>>();
+	if let Err(e) = ledger.append(&entries) {
+		log::warn!(target: "hyperspace", "Failed to record the packets delivered to {}: {e:?}", sink.name());
+	}
+}
+
+/// Way the ledger disagrees with the chains about a packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscrepancyKind {
+	/// The packet was never recorded as sent.
+	MissingObservation,
+	/// The packet was received by its destination, but its delivery wasn't recorded.
+	MissingRelay,
+	/// The packet was recorded as received or acknowledged, but its destination has no receipt.
+	NotReceived,
+	/// The commitment of the packet was cleared, but no acknowledgement or timeout was recorded.
+	MissingClearance,
+	/// The packet was recorded as acknowledged or timed out, but its commitment is still stored.
+	NotCleared,
+}
+
+impl DiscrepancyKind {
+	/// Status of the entry repairing the discrepancy, if the chains tell which one it is.
+	fn repair(&self, received: bool) -> Option<PacketStatus> {
+		match self {
+			DiscrepancyKind::MissingObservation => Some(PacketStatus::Observed),
+			DiscrepancyKind::MissingRelay => Some(PacketStatus::Relayed),
+			DiscrepancyKind::MissingClearance if received => Some(PacketStatus::Acknowledged),
+			DiscrepancyKind::MissingClearance => Some(PacketStatus::TimedOut),
+			// recorded deliveries that didn't happen can't be taken back
+			DiscrepancyKind::NotReceived | DiscrepancyKind::NotCleared => None,
+		}
+	}
+}
+
+impl fmt::Display for DiscrepancyKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let description = match self {
+			DiscrepancyKind::MissingObservation => "sent but not recorded",
+			DiscrepancyKind::MissingRelay => "received but its delivery is not recorded",
+			DiscrepancyKind::NotReceived => "recorded as delivered but not received",
+			DiscrepancyKind::MissingClearance => "cleared but its acknowledgement is not recorded",
+			DiscrepancyKind::NotCleared => "recorded as acknowledged but still committed",
+		};
+		f.write_str(description)
+	}
+}
+
+/// A packet the ledger disagrees with the chains about.
+#[derive(Debug, Clone)]
+pub struct Discrepancy {
+	/// Chain the packet was sent from.
+	pub chain: String,
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub sequence: u64,
+	pub kind: DiscrepancyKind,
+}
+
+/// Result of a reconciliation of the ledger with the chains.
+#[derive(Debug, Default)]
+pub struct ReconciliationReport {
+	/// Number of packets checked.
+	pub checked: usize,
+	pub discrepancies: Vec<Discrepancy>,
+	/// Number of entries added to the ledger to repair the discrepancies.
+	pub repaired: usize,
+}
+
+impl fmt::Display for ReconciliationReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(
+			f,
+			"Checked {} packets, found {} discrepancies, added {} ledger entries",
+			self.checked,
+			self.discrepancies.len(),
+			self.repaired
+		)?;
+		for discrepancy in &self.discrepancies {
+			let Discrepancy { chain, port_id, channel_id, sequence, kind } = discrepancy;
+			writeln!(f, "{chain} {port_id}/{channel_id} #{sequence}: {kind}")?;
+		}
+		Ok(())
+	}
+}
+
+/// Statuses recorded for the packets sent over a channel, by sequence.
+type ChannelRecords = BTreeMap<u64, BTreeSet<PacketStatus>>;
+
+/// Groups the `entries` of the packets sent from `origin` to `destination` by the channel they
+/// were sent over.
+fn channel_records(
+	entries: &[LedgerEntry],
+	origin: &str,
+	destination: &str,
+) -> BTreeMap<(PortId, ChannelId), ChannelRecords> {
+	let mut channels = BTreeMap::<_, ChannelRecords>::new();
+	for entry in entries {
+		let from_origin = if entry.status.is_on_source() {
+			entry.chain == origin
+		} else {
+			entry.chain == destination
+		};
+		if !from_origin {
+			continue
+		}
+		channels
+			.entry((entry.source_port.clone(), entry.source_channel))
+			.or_default()
+			.entry(entry.sequence)
+			.or_default()
+			.insert(entry.status);
+	}
+	channels
+}
+
+/// Checks the ledger entries of the packets sent from `origin` to `destination` against the
+/// commitments on `origin` and the receipts on `destination`, over the channels found in the
+/// ledger and the channel whitelist of `origin`. The discrepancies that the chains tell how to
+/// resolve are repaired by appending entries to the ledger if `repair` is set.
+async fn reconcile_packets(
+	origin: &impl Chain,
+	destination: &impl Chain,
+	ledger: &PacketLedger,
+	entries: &[LedgerEntry],
+	repair: bool,
+	report: &mut ReconciliationReport,
+) -> anyhow::Result<()> {
+	let mut channels = channel_records(entries, origin.name(), destination.name());
+	for (channel_id, port_id) in origin.channel_whitelist() {
+		channels.entry((port_id, channel_id)).or_default();
+	}
+	let (origin_height, _) = origin.latest_height_and_timestamp().await?;
+	let (destination_height, _) = destination.latest_height_and_timestamp().await?;
+
+	for ((port_id, channel_id), records) in channels {
+		let channel = origin
+			.query_channel_end(origin_height, channel_id, port_id.clone())
+			.await?
+			.channel
+			.and_then(|channel| channel.counterparty)
+			.ok_or_else(|| {
+				anyhow!("Channel {port_id}/{channel_id} not found on {}", origin.name())
+			})?;
+		let destination_port = channel.port_id.parse::<PortId>()?;
+		let destination_channel = channel.channel_id.parse::<ChannelId>()?;
+
+		let committed = origin
+			.query_packet_commitments(origin_height, channel_id, port_id.clone())
+			.await?
+			.into_iter()
+			.collect::<HashSet<_>>();
+		let sequences = records
+			.keys()
+			.copied()
+			.chain(committed.iter().copied())
+			.collect::<BTreeSet<_>>();
+		let unreceived = destination
+			.query_unreceived_packets(
+				destination_height,
+				destination_channel,
+				destination_port.clone(),
+				sequences.iter().copied().collect(),
+			)
+			.await?
+			.into_iter()
+			.collect::<HashSet<_>>();
+
+		let mut repairs = vec![];
+		for sequence in sequences {
+			report.checked += 1;
+			let recorded = records.get(&sequence).cloned().unwrap_or_default();
+			let committed = committed.contains(&sequence);
+			let received = !unreceived.contains(&sequence);
+			let cleared = recorded.contains(&PacketStatus::Acknowledged) ||
+				recorded.contains(&PacketStatus::TimedOut);
+			let kinds = [
+				(!recorded.contains(&PacketStatus::Observed), DiscrepancyKind::MissingObservation),
+				(
+					received && !recorded.contains(&PacketStatus::Relayed),
+					DiscrepancyKind::MissingRelay,
+				),
+				(
+					!received &&
+						(recorded.contains(&PacketStatus::Relayed) ||
+							recorded.contains(&PacketStatus::Acknowledged)),
+					DiscrepancyKind::NotReceived,
+				),
+				(!committed && !cleared, DiscrepancyKind::MissingClearance),
+				(committed && cleared, DiscrepancyKind::NotCleared),
+			];
+			for kind in kinds.into_iter().filter(|(found, _)| *found).map(|(_, kind)| kind) {
+				report.discrepancies.push(Discrepancy {
+					chain: origin.name().to_string(),
+					port_id: port_id.clone(),
+					channel_id,
+					sequence,
+					kind,
+				});
+				let Some(status) = kind.repair(received).filter(|_| repair) else { continue };
+				let packet = Packet {
+					sequence: sequence.into(),
+					source_port: port_id.clone(),
+					source_channel: channel_id,
+					destination_port: destination_port.clone(),
+					destination_channel,
+					..Default::default()
+				};
+				let chain = if status.is_on_source() { origin.name() } else { destination.name() };
+				repairs.push(LedgerEntry {
+					reconciled: true,
+					..LedgerEntry::new(chain, status, &packet, None)
+				});
+			}
+		}
+		ledger.append(&repairs)?;
+		report.repaired += repairs.len();
+	}
+	Ok(())
+}
+
+/// Reconciles the ledger with the packets sent between `chain_a` and `chain_b`, see
+/// [`reconcile_packets`].
+pub async fn reconcile(
+	chain_a: &impl Chain,
+	chain_b: &impl Chain,
+	ledger: &PacketLedger,
+	repair: bool,
+) -> anyhow::Result<ReconciliationReport> {
+	let entries = ledger.entries()?;
+	let mut report = ReconciliationReport::default();
+	reconcile_packets(chain_a, chain_b, ledger, &entries, repair, &mut report).await?;
+	reconcile_packets(chain_b, chain_a, ledger, &entries, repair, &mut report).await?;
+	Ok(report)
+}
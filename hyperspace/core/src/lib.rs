@@ -14,25 +14,44 @@
 
 #![warn(unused_variables)]
 
+pub mod admin;
+pub mod backfill;
+pub mod balance;
 pub mod chain;
 pub mod command;
+pub mod config;
 pub mod events;
+pub mod expiry;
+pub mod forward;
+pub mod health;
+pub mod ledger;
 pub mod logging;
 mod macros;
+pub mod misbehaviour;
 pub mod packets;
+pub mod plugin;
 pub mod queue;
+pub mod receipts;
+pub mod reload;
+#[cfg(feature = "testing")]
+pub mod stress;
 pub mod substrate;
+pub mod telemetry;
+pub mod upgrade;
 mod utils;
+pub mod watchdog;
+pub mod watcher;
 
 use crate::utils::RecentStream;
 use anyhow::anyhow;
 use events::{has_packet_events, parse_events};
-use futures::{future::ready, StreamExt, TryFutureExt};
+use futures::{StreamExt, TryFutureExt};
 use ibc::{events::IbcEvent, Height};
 use ibc_proto::google::protobuf::Any;
 use metrics::handler::MetricsHandler;
-use primitives::{Chain, IbcProvider, UndeliveredType, UpdateType};
+use primitives::{Chain, FinalitySource, StallReason, UndeliveredType, UpdateType};
 use std::collections::HashSet;
+use tokio::{sync::mpsc::UnboundedReceiver, time::Instant};
 
 #[derive(Copy, Debug, Clone)]
 pub enum Mode {
@@ -40,21 +59,49 @@ pub enum Mode {
 	Light,
 }
 
+/// A command sent to a running relay loop through its control channel. Commands are applied
+/// between finality events, so they never observe a half-processed batch of messages.
+pub type RelayCommand<A, B> = Box<dyn FnOnce(&mut A, &mut B) -> anyhow::Result<()> + Send>;
+
 /// Core relayer loop, waits for new finality events and forwards any new [`ibc::IbcEvents`]
-/// to the counter party chain.
+/// to the counter party chain. The health of both chains is checked on startup and periodically,
+/// see [`health::report_health`].
 pub async fn relay<A, B>(
+	chain_a: A,
+	chain_b: B,
+	chain_a_metrics: Option<MetricsHandler>,
+	chain_b_metrics: Option<MetricsHandler>,
+	mode: Option<Mode>,
+) -> Result<(), anyhow::Error>
+where
+	A: Chain,
+	B: Chain,
+{
+	relay_with_control(chain_a, chain_b, chain_a_metrics, chain_b_metrics, mode, None).await
+}
+
+/// Same as [`relay`], but additionally applies [`RelayCommand`]s received on `control`.
+pub async fn relay_with_control<A, B>(
 	mut chain_a: A,
 	mut chain_b: B,
 	mut chain_a_metrics: Option<MetricsHandler>,
 	mut chain_b_metrics: Option<MetricsHandler>,
 	mode: Option<Mode>,
+	mut control: Option<UnboundedReceiver<RelayCommand<A, B>>>,
 ) -> Result<(), anyhow::Error>
 where
 	A: Chain,
 	B: Chain,
 {
-	let stream_a = RecentStream::new(chain_a.finality_notifications().await?);
-	let stream_b = RecentStream::new(chain_b.finality_notifications().await?);
+	health::report_health(&chain_a, &chain_b, chain_a_metrics.as_ref()).await;
+	health::report_health(&chain_b, &chain_a, chain_b_metrics.as_ref()).await;
+	let period = health::HEALTH_REPORT_INTERVAL;
+	let mut health_interval = tokio::time::interval_at(Instant::now() + period, period);
+
+	let stream_a =
+		RecentStream::new(chain_a.finality_notifications().await?, A::is_mandatory_finality_event);
+	let stream_b =
+		RecentStream::new(chain_b.finality_notifications().await?, B::is_mandatory_finality_event);
 	let (mut chain_a_finality, mut chain_b_finality) = (stream_a, stream_b);
 
 	// Introduce altering between branches so that each branch gets a chance to execute first after
@@ -74,6 +121,23 @@ where
 				first_executed = false;
 				process_finality_event(&mut chain_b, &mut chain_a, &mut chain_b_metrics, mode, result, &mut chain_b_finality, &mut chain_a_finality).await?;
 			}
+			// periodic health check of both chains
+			_ = health_interval.tick() => {
+				health::report_health(&chain_a, &chain_b, chain_a_metrics.as_ref()).await;
+				health::report_health(&chain_b, &chain_a, chain_b_metrics.as_ref()).await;
+			}
+			// command from the control channel
+			command = next_command(&mut control), if control.is_some() => {
+				match command {
+					Some(command) => if let Err(e) = command(&mut chain_a, &mut chain_b) {
+						tracing::error!(target: "hyperspace", error = ?e, "Failed to apply relay command");
+					},
+					None => {
+						log::debug!(target: "hyperspace", "Relay control channel closed");
+						control = None;
+					},
+				}
+			}
 			else => {
 				first_executed = false;
 			}
@@ -81,66 +145,29 @@ where
 	}
 }
 
-pub async fn fish<A, B>(chain_a: A, chain_b: B) -> Result<(), anyhow::Error>
+async fn next_command<C>(control: &mut Option<UnboundedReceiver<C>>) -> Option<C> {
+	match control {
+		Some(receiver) => receiver.recv().await,
+		None => futures::future::pending().await,
+	}
+}
+
+/// Watches both chains for misbehaviour of their counterparty, see [`misbehaviour`].
+pub async fn fish<A, B>(
+	chain_a: A,
+	chain_b: B,
+	store: misbehaviour::EvidenceStore,
+) -> Result<(), anyhow::Error>
 where
 	A: Chain,
-	A::Error: From<B::Error>,
 	B: Chain,
-	B::Error: From<A::Error>,
 {
-	// we only care about events where the counterparty light client is updated.
-	let (mut chain_a_client_updates, mut chain_b_client_updates) = (
-		chain_a.ibc_events().await.filter_map(|ev| {
-			ready(match ev {
-				IbcEvent::UpdateClient(update) if chain_b.client_id() == *update.client_id() =>
-					Some(update),
-				_ => None,
-			})
-		}),
-		chain_b.ibc_events().await.filter_map(|ev| {
-			ready(match ev {
-				IbcEvent::UpdateClient(update) if chain_a.client_id() == *update.client_id() =>
-					Some(update),
-				_ => None,
-			})
-		}),
-	);
-
-	// loop forever
-	loop {
-		tokio::select! {
-			// new finality event from chain A
-			update = chain_a_client_updates.next() => {
-				let update = match update {
-					Some(update) => update,
-					None => break,
-				};
-				// The corresponding transaction on tendermint may not be indexed yet, so we wait for a bit
-				if chain_a.client_type() == "07-tendermint" {
-					tokio::time::sleep(chain_a.expected_block_time()).await;
-				}
-				let message = chain_a.query_client_message(update).await.map_err(|e| { log::info!("error: {}", e); e })?;
-				chain_b.check_for_misbehaviour(&chain_a, message).await.map_err(|e| { log::info!("error: {}", e); e })?;
-			}
-			// new finality event from chain B
-			update = chain_b_client_updates.next() => {
-				let update = match update {
-					Some(update) => update,
-					None => break,
-				};
-				// The corresponding transaction on tendermint may not be indexed yet, so we wait for a bit
-				if chain_a.client_type() == "07-tendermint" {
-					tokio::time::sleep(chain_a.expected_block_time()).await;
-				}
-				let message = chain_b.query_client_message(update).await.map_err(|e| { log::info!("error: {}", e); e })?;
-				chain_a.check_for_misbehaviour(&chain_b, message).await.map_err(|e| { log::info!("error: {}", e); e })?;
-			}
-		}
-	}
-
-	Ok(())
+	misbehaviour::watch_misbehaviour(chain_a, chain_b, store).await
 }
 
+/// Handles the finality stream of `source` yielding `result`: relays the events of a new finality
+/// event from `source` to `sink`, or reconnects both finality streams if the one of `source` was
+/// closed.
 async fn process_finality_event<A: Chain, B: Chain>(
 	source: &mut A,
 	sink: &mut B,
@@ -153,69 +180,100 @@ async fn process_finality_event<A: Chain, B: Chain>(
 	match result {
 		// stream closed
 		None => {
-			log::warn!("Stream closed for {}", source.name());
-			*stream_source = loop {
-				match source.finality_notifications().await {
-					Ok(stream) => break RecentStream::new(stream),
-					Err(e) => {
-						log::error!("Failed to get finality notifications for {} {:?}. Trying again in 30 seconds...", source.name(), e);
-						tokio::time::sleep(std::time::Duration::from_secs(30)).await;
-						let _ = source.reconnect().await;
-					},
-				};
-			};
-			*stream_sink = loop {
-				match sink.finality_notifications().await {
-					Ok(stream) => break RecentStream::new(stream),
-					Err(e) => {
-						log::error!("Failed to get finality notifications for {} {:?}. Trying again in 30 seconds...", sink.name(), e);
-						tokio::time::sleep(std::time::Duration::from_secs(30)).await;
-						let _ = sink.reconnect().await;
-					},
-				};
-			};
+			tracing::warn!(target: "hyperspace", chain = source.name(), "Finality stream closed");
+			*stream_source = reconnect_finality_stream(source).await;
+			*stream_sink = reconnect_finality_stream(sink).await;
 		},
-		Some(finality_event) => {
-			log::info!("=======================================================");
-			log::info!("Received finality notification from {}", source.name(),);
-
-			let result =
-				process_some_finality_event(source, sink, metrics, mode, finality_event).await;
-
-			match result {
-				Ok(()) => {
-					let sink_initial_rpc_call_delay = sink.initial_rpc_call_delay();
-					let source_initial_rpc_call_delay = source.initial_rpc_call_delay();
-					sink.set_rpc_call_delay(sink_initial_rpc_call_delay);
-					source.set_rpc_call_delay(source_initial_rpc_call_delay);
-				},
+		Some(finality_event) =>
+			handle_finality_event(source, sink, metrics, mode, finality_event).await,
+	}
+	Ok(())
+}
+
+/// Subscribes to the finality notifications of `chain` again, retrying every 30 seconds until it
+/// succeeds.
+async fn reconnect_finality_stream<C: Chain>(chain: &mut C) -> RecentStream<C::FinalityEvent> {
+	loop {
+		match chain.finality_notifications().await {
+			Ok(stream) => break RecentStream::new(stream, C::is_mandatory_finality_event),
+			Err(e) => {
+				tracing::error!(target: "hyperspace", chain = chain.name(), error = ?e, "Failed to get finality notifications. Trying again in 30 seconds...");
+				tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+				let _ = chain.reconnect().await;
+			},
+		}
+	}
+}
+
+/// Relays the events of `finality_event` from `source` to `sink`, along with the packets of the
+/// whitelisted channels that became ready and the timeouts that became provable. The event is
+/// skipped while either chain is stalled. Errors are reported to both chains, which may adjust
+/// their RPC call delays, rather than returned, so that the relay loop carries on with the next
+/// finality event.
+///
+/// The chains and the metrics handler are the only dependencies, so the relay loop of any pair of
+/// chains, including in-memory ones, can be driven one finality event at a time.
+pub async fn handle_finality_event<S: Chain, D: Chain>(
+	source: &mut S,
+	sink: &mut D,
+	metrics: &mut Option<MetricsHandler>,
+	mode: Option<Mode>,
+	finality_event: S::FinalityEvent,
+) {
+	tracing::info!(target: "hyperspace", chain = source.name(), "Received finality notification");
+	source.common_state().on_finality_event();
+	if let Some((name, reason)) = stalled_chain(source).or_else(|| stalled_chain(sink)) {
+		tracing::warn!(target: "hyperspace", chain = source.name(), stalled = name, %reason, "Skipping finality event: a chain is stalled");
+		return
+	}
+
+	match process_some_finality_event(source, sink, metrics, mode, finality_event).await {
+		Ok(()) => {
+			let sink_initial_rpc_call_delay = sink.initial_rpc_call_delay();
+			let source_initial_rpc_call_delay = source.initial_rpc_call_delay();
+			sink.set_rpc_call_delay(sink_initial_rpc_call_delay);
+			source.set_rpc_call_delay(source_initial_rpc_call_delay);
+		},
+		Err(e) => {
+			tracing::error!(target: "hyperspace", source = source.name(), sink = sink.name(), "{e}");
+			source.common_state().record_error();
+			match sink.handle_error(&e).and_then(|_| source.handle_error(&e)).await {
+				Ok(_) => (),
 				Err(e) => {
-					log::error!("{}", e);
-					match sink.handle_error(&e).and_then(|_| source.handle_error(&e)).await {
-						Ok(_) => (),
-						Err(e) => {
-							log::error!("Failed to handle error {:?}", e)
-						},
-					}
+					tracing::error!(target: "hyperspace", error = ?e, "Failed to handle error")
 				},
 			}
 		},
 	}
-	Ok(())
 }
 
+/// Returns the name of `chain` and why it's stalled, if the last health probe found it stalled.
+fn stalled_chain(chain: &impl Chain) -> Option<(&str, StallReason)> {
+	chain.common_state().stall_reason().map(|reason| (chain.name(), reason))
+}
+
+#[tracing::instrument(skip_all, err, fields(source = source.name(), sink = sink.name()))]
 async fn process_some_finality_event<A: Chain, B: Chain>(
 	source: &mut A,
 	sink: &mut B,
 	metrics: &mut Option<MetricsHandler>,
 	mode: Option<Mode>,
-	finality_event: <A as IbcProvider>::FinalityEvent,
+	finality_event: <A as FinalitySource>::FinalityEvent,
 ) -> anyhow::Result<()> {
 	let updates = source
 		.query_latest_ibc_events(finality_event, &*sink)
 		.await
 		.map_err(|e| anyhow!("Failed to fetch IBC events for finality event {e}"))?;
+	let updates = packets::confirmation::confirmed_updates(&*source, updates).await?;
 	log::trace!(target: "hyperspace", "Received updates count: {}", updates.len());
+	if let Some(metrics) = metrics.as_ref() {
+		metrics.update_skipped_stream_items(source.common_state().skipped_stream_items());
+		metrics.update_disconnects(source.common_state().disconnects());
+		metrics.update_submission_queue_depth(source.common_state().keys.queue_depth());
+		for usage in source.common_state().take_gas_usage() {
+			metrics.handle_gas_usage(usage.simulated, usage.used);
+		}
+	}
 	// query packets that can now be sent, at this sink height because of connection
 	// delay.
 	let (ready_packets, timeout_msgs) =
@@ -250,9 +308,15 @@ async fn process_some_finality_event<A: Chain, B: Chain>(
 	process_updates(source, sink, metrics, mode, updates, &mut msgs).await?;
 
 	msgs.extend(ready_packets);
+	flush_held_update(&*source, &mut msgs);
 
+	// remember the relayed sequences, so that overlapping finality events don't relay them again
+	let relayed = packets::relayed_sequences(&msgs);
 	process_messages(sink, metrics, msgs).await?;
+	source.common_state().record_relayed(relayed);
+	let relayed = packets::relayed_sequences(&timeout_msgs);
 	process_timeouts(source, metrics, timeout_msgs).await?;
+	source.common_state().record_relayed(relayed);
 	Ok(())
 }
 
@@ -277,14 +341,27 @@ async fn process_updates<A: Chain, B: Chain>(
 			HashSet::new()
 		};
 
+	// a client update requested through the admin API is sent with the latest update
+	let forced_update_height =
+		if !updates.is_empty() && source.common_state().take_client_update_request() {
+			updates.last().map(|(_, height, ..)| *height)
+		} else {
+			None
+		};
+
 	for (msg_update_client, height, events, update_type) in updates {
 		if let Some(metrics) = metrics.as_mut() {
 			if let Err(e) = metrics.handle_events(events.as_slice()).await {
-				log::error!("Failed to handle metrics for {} {:?}", source.name(), e);
+				tracing::error!(target: "hyperspace", chain = source.name(), error = ?e, "Failed to handle metrics");
 			}
+			forward::track_forwarded_packets(source.name(), &events, metrics);
 		}
 
 		let event_types = events.iter().map(|ev| ev.event_type()).collect::<Vec<_>>();
+		let sent_packets =
+			events.iter().filter(|ev| matches!(ev, IbcEvent::SendPacket(_))).count() as u64;
+		source.common_state().observe_events(&events);
+		ledger::record_sent_packets(source, &events);
 		let mut messages = parse_events(source, sink, events, mode)
 			.await
 			.map_err(|e| anyhow!("Failed to parse events: {:?}", e))?;
@@ -299,7 +376,28 @@ async fn process_updates<A: Chain, B: Chain>(
 			source_has_undelivered_acks) &&
 			mandatory_heights_for_undelivered_seqs.contains(&height.revision_height);
 		let common_state = source.common_state();
-		let skip_optional_updates = common_state.skip_optional_client_updates;
+		common_state.record_send_packets(height.revision_height, sent_packets);
+		let adaptive_update_due = common_state.is_adaptive_update_due(height.revision_height);
+		let skip_optional_updates = common_state.skip_optional_client_updates &&
+			forced_update_height != Some(height) &&
+			!adaptive_update_due;
+
+		// optional updates that prove no packet event are held, and only the latest one is sent
+		let coalesce = common_state.optional_update_window.is_some() &&
+			update_type.is_optional() &&
+			!skip_optional_updates &&
+			!need_to_send_proofs_for_sequences &&
+			!has_packet_events(&event_types) &&
+			messages.is_empty() &&
+			forced_update_height != Some(height);
+		if coalesce {
+			let superseded =
+				common_state.update_coalescer.lock().unwrap().hold(msg_update_client, height);
+			if let Some(superseded) = superseded {
+				tracing::debug!(target: "hyperspace", chain = sink.name(), %height, %superseded, "Dropping an optional update superseded by a newer one");
+			}
+			continue
+		}
 
 		// We want to send client update if packet messages exist but where not sent due
 		// to a connection delay even if client update message is optional
@@ -316,24 +414,55 @@ async fn process_updates<A: Chain, B: Chain>(
 		) {
 			(true, false, true) => {
 				// skip sending ibc messages if no new events
-				log::info!("Skipping finality notification for {}", sink.name());
+				tracing::info!(target: "hyperspace", chain = sink.name(), %height, "Skipping finality notification");
 				continue
 			},
 			(false, _, true) =>
 				if update_type.is_optional() && need_to_send_proofs_for_sequences {
-					log::info!("Sending an optional update because source ({}) chain has undelivered sequences", sink.name());
+					tracing::info!(target: "hyperspace", chain = sink.name(), %height, "Sending an optional update because the source chain has undelivered sequences");
+				} else if update_type.is_optional() && adaptive_update_due {
+					tracing::info!(
+						target: "hyperspace",
+						chain = sink.name(),
+						%height,
+						period = common_state
+							.adaptive_update_period(height.revision_height)
+							.unwrap_or_default(),
+						"Sending a scheduled optional update"
+					);
 				} else {
-					log::info!("Sending mandatory client update message for {}", sink.name())
+					tracing::info!(target: "hyperspace", chain = sink.name(), %height, "Sending mandatory client update message")
 				},
-			_ => log::info!("Received finalized events from: {} {event_types:#?}", source.name()),
+			_ =>
+				tracing::info!(target: "hyperspace", chain = source.name(), %height, events = ?event_types, "Received finalized events"),
 		};
+		// the held update, if any, is superseded by this one
+		common_state.update_coalescer.lock().unwrap().take();
+		common_state.record_client_update(height.revision_height);
 		msgs.push(msg_update_client);
 		msgs.append(&mut messages);
 	}
 	Ok(())
 }
 
-async fn process_messages<B: Chain>(
+/// Appends the optional client update of `source` held by its coalescer to `msgs` if there are
+/// other messages to submit, or if updates have been held for the whole `optional_update_window`.
+fn flush_held_update<A: Chain>(source: &A, msgs: &mut Vec<Any>) {
+	let common_state = source.common_state();
+	let mut coalescer = common_state.update_coalescer.lock().unwrap();
+	let held = match common_state.optional_update_window {
+		Some(window) if msgs.is_empty() => coalescer.take_expired(window),
+		_ => coalescer.take(),
+	};
+	if let Some((msg, height)) = held {
+		tracing::info!(target: "hyperspace", chain = source.name(), %height, "Sending the latest held optional client update");
+		common_state.record_client_update(height.revision_height);
+		msgs.push(msg);
+	}
+}
+
+#[tracing::instrument(skip_all, err, fields(sink = sink.name()))]
+pub(crate) async fn process_messages<B: Chain>(
 	sink: &mut B,
 	metrics: &mut Option<MetricsHandler>,
 	msgs: Vec<Any>,
@@ -343,16 +472,17 @@ async fn process_messages<B: Chain>(
 			metrics.handle_messages(msgs.as_slice()).await;
 		}
 		let type_urls = msgs.iter().map(|msg| msg.type_url.as_str()).collect::<Vec<_>>();
-		log::info!("Submitting messages to {}: {type_urls:#?}", sink.name());
+		tracing::info!(target: "hyperspace", chain = sink.name(), messages = ?type_urls, "Submitting messages");
 
 		queue::flush_message_batch(msgs, metrics.as_ref(), &*sink)
 			.await
 			.map_err(|e| anyhow!("Failed to submit messages: {:?}", e))?;
-		log::debug!(target: "hyperspace", "Successfully submitted messages to {}", sink.name());
+		tracing::debug!(target: "hyperspace", chain = sink.name(), "Successfully submitted messages");
 	}
 	Ok(())
 }
 
+#[tracing::instrument(skip_all, err, fields(source = source.name()))]
 async fn process_timeouts<A: Chain>(
 	source: &mut A,
 	metrics: &mut Option<MetricsHandler>,
@@ -363,11 +493,11 @@ async fn process_timeouts<A: Chain>(
 			metrics.handle_timeouts(timeout_msgs.as_slice()).await;
 		}
 		let type_urls = timeout_msgs.iter().map(|msg| msg.type_url.as_str()).collect::<Vec<_>>();
-		log::info!("Submitting timeout messages to {}: {type_urls:#?}", source.name());
+		tracing::info!(target: "hyperspace", chain = source.name(), messages = ?type_urls, "Submitting timeout messages");
 		queue::flush_message_batch(timeout_msgs, metrics.as_ref(), &*source)
 			.await
 			.map_err(|e| anyhow!("Failed to submit timeout messages: {:?}", e))?;
-		log::debug!(target: "hyperspace", "Successfully submitted timeout messages to {}", source.name());
+		tracing::debug!(target: "hyperspace", chain = source.name(), "Successfully submitted timeout messages");
 	}
 	Ok(())
 }
@@ -418,3 +548,106 @@ pub mod send_packet_relay {
 		RELAY_PACKETS.store(status, Ordering::SeqCst);
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hyperspace_mock::{open_transfer_channel, MockChain, MockConfig, MockFinalityEvent};
+	use ibc::{
+		core::{
+			ics04_channel::channel::Order,
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		timestamp::Timestamp,
+	};
+	use ibc_proto_registry::type_url;
+	use primitives::IbcQuerier;
+
+	async fn connected_chains() -> (MockChain, MockChain) {
+		let mut chain_a = MockChain::new(MockConfig::new("chain-a"));
+		let mut chain_b = MockChain::new(MockConfig::new("chain-b"));
+		open_transfer_channel(&mut chain_a, &mut chain_b, Order::Unordered)
+			.await
+			.unwrap();
+		(chain_a, chain_b)
+	}
+
+	/// Sends a packet on `channel-0` of `chain`, and returns the finality event of its block.
+	fn send_packet(chain: &MockChain) -> MockFinalityEvent {
+		chain
+			.send_packet(
+				PortId::transfer(),
+				ChannelId::new(0),
+				b"data".to_vec(),
+				Height::new(1, 100),
+				Timestamp::none(),
+			)
+			.unwrap();
+		MockFinalityEvent { height: chain.produce_block().revision_height }
+	}
+
+	fn next_finality_event(chain: &MockChain) -> MockFinalityEvent {
+		MockFinalityEvent { height: chain.produce_block().revision_height }
+	}
+
+	fn is_received(chain: &MockChain, sequence: u64) -> bool {
+		chain.with_ledger(|ledger| {
+			ledger.has_receipt(&PortId::transfer(), &ChannelId::new(0), sequence)
+		})
+	}
+
+	#[tokio::test]
+	async fn send_packet_is_relayed_to_the_sink() {
+		let (mut chain_a, mut chain_b) = connected_chains().await;
+		let event = send_packet(&chain_a);
+		handle_finality_event(&mut chain_a, &mut chain_b, &mut None, None, event).await;
+
+		assert!(is_received(&chain_b, 1));
+		let tx = chain_b.submitted().pop().unwrap();
+		let type_urls = tx.messages.iter().map(|msg| msg.type_url.as_str()).collect::<Vec<_>>();
+		assert_eq!(type_urls, [type_url::MSG_UPDATE_CLIENT, type_url::MSG_RECV_PACKET]);
+
+		// the packet isn't relayed again, and the optional update is skipped
+		let submitted = chain_b.submitted().len();
+		let event = next_finality_event(&chain_a);
+		handle_finality_event(&mut chain_a, &mut chain_b, &mut None, None, event).await;
+		assert_eq!(chain_b.submitted().len(), submitted);
+	}
+
+	#[tokio::test]
+	async fn finality_events_are_skipped_while_a_chain_is_stalled() {
+		let (mut chain_a, mut chain_b) = connected_chains().await;
+		let submitted = chain_b.submitted().len();
+		chain_b.common_state().set_stall_reason(Some(StallReason::FinalityStalled));
+		let event = send_packet(&chain_a);
+		handle_finality_event(&mut chain_a, &mut chain_b, &mut None, None, event).await;
+		assert_eq!(chain_b.submitted().len(), submitted);
+		assert!(!is_received(&chain_b, 1));
+
+		// the packet is relayed with the next finality event once the chain recovers
+		chain_b.common_state().set_stall_reason(None);
+		let event = next_finality_event(&chain_a);
+		handle_finality_event(&mut chain_a, &mut chain_b, &mut None, None, event).await;
+		assert!(is_received(&chain_b, 1));
+	}
+
+	#[tokio::test]
+	async fn errors_adjust_the_rpc_call_delays() {
+		let (mut chain_a, mut chain_b) = connected_chains().await;
+		let initial_delay = chain_a.initial_rpc_call_delay();
+		let event = send_packet(&chain_a);
+		chain_a.fail_next_queries(1);
+		handle_finality_event(&mut chain_a, &mut chain_b, &mut None, None, event).await;
+		// the mock chains handle the injected failures as the errors of an overloaded node
+		assert_eq!(chain_a.rpc_call_delay(), initial_delay * 2);
+		assert_eq!(chain_b.rpc_call_delay(), initial_delay * 2);
+		assert!(!is_received(&chain_b, 1));
+
+		// the delays are reset once a finality event is handled successfully
+		let event = next_finality_event(&chain_a);
+		handle_finality_event(&mut chain_a, &mut chain_b, &mut None, None, event).await;
+		assert_eq!(chain_a.rpc_call_delay(), initial_delay);
+		assert_eq!(chain_b.rpc_call_delay(), initial_delay);
+		assert!(is_received(&chain_b, 1));
+	}
+}
@@ -12,11 +12,136 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use log::LevelFilter;
+//! Logging of the relayer.
+//!
+//! Logs are emitted as `tracing` events, and the records of the `log` macros are converted into
+//! such events, so every line carries the fields of the spans it was emitted in: the `source` and
+//! `sink` chains of the relay loop, and the `chain`, `channel`, `sequence` and `height` of the
+//! message being handled. Lines are written to stdout, and optionally to files rotated in a
+//! directory, either as text or as JSON objects whose fields are at the top level, so that they
+//! can be ingested by log aggregators like Loki or Elasticsearch.
 
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{
+	fmt::MakeWriter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry,
+};
+
+/// Filter directives used when neither `RUST_LOG` nor the `filter` of the config is set.
+pub const DEFAULT_LOG_FILTER: &str = "hyper=info";
+
+/// Default prefix of the names of the log files.
+pub const DEFAULT_LOG_FILE_PREFIX: &str = "hyperspace.log";
+
+/// A log layer of the relayer's subscriber.
+pub type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+	/// Human readable lines, with the fields of the enclosing spans as a prefix.
+	#[default]
+	Text,
+	/// One JSON object per line, with the fields of the event at the top level and the enclosing
+	/// spans under `spans`.
+	Json,
+}
+
+/// How often a new log file is started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+	Minutely,
+	Hourly,
+	#[default]
+	Daily,
+	Never,
+}
+
+impl From<LogRotation> for Rotation {
+	fn from(rotation: LogRotation) -> Self {
+		match rotation {
+			LogRotation::Minutely => Rotation::MINUTELY,
+			LogRotation::Hourly => Rotation::HOURLY,
+			LogRotation::Daily => Rotation::DAILY,
+			LogRotation::Never => Rotation::NEVER,
+		}
+	}
+}
+
+/// The `[logging]` section of the core config.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoggingConfig {
+	/// Format of the log lines, on stdout and in the log files.
+	#[serde(default)]
+	pub format: LogFormat,
+	/// Filter directives, in the `RUST_LOG` syntax. `RUST_LOG` takes precedence when it's set.
+	/// Defaults to [`DEFAULT_LOG_FILTER`].
+	pub filter: Option<String>,
+	/// Directory the logs are also written to. Logs are only written to stdout if not set.
+	pub directory: Option<PathBuf>,
+	/// Prefix of the names of the log files, which are suffixed with the date of their period.
+	/// Defaults to [`DEFAULT_LOG_FILE_PREFIX`].
+	pub file_prefix: Option<String>,
+	/// How often a new log file is started. Defaults to daily.
+	#[serde(default)]
+	pub rotation: LogRotation,
+}
+
+impl LoggingConfig {
+	fn env_filter(&self) -> anyhow::Result<EnvFilter> {
+		let directives = std::env::var(EnvFilter::DEFAULT_ENV)
+			.ok()
+			.or_else(|| self.filter.clone())
+			.unwrap_or_else(|| DEFAULT_LOG_FILTER.to_string());
+		Ok(EnvFilter::try_new(directives)?)
+	}
+}
+
+/// Logs text lines to stdout, filtered by `RUST_LOG`, unless a global subscriber is already
+/// installed. Used by the tests and the commands that don't read a core config.
 pub fn setup_logging() {
-	env_logger::builder()
-		.filter_module("hyper", LevelFilter::Info)
-		.format_module_path(false)
-		.init();
+	if tracing::dispatcher::has_been_set() {
+		return
+	}
+	if let Err(e) = init_logging(&LoggingConfig::default(), vec![]) {
+		eprintln!("Failed to set up logging: {e}");
+	}
+}
+
+/// Installs the global subscriber logging as configured by `config`, alongside the `extra`
+/// layers, e.g. the OTLP exporter. Fails if a global subscriber or logger is already installed.
+pub fn init_logging(config: &LoggingConfig, extra: Vec<BoxedLayer>) -> anyhow::Result<()> {
+	let mut layers = vec![fmt_layer(config.format, std::io::stdout, true, config.env_filter()?)];
+	if let Some(directory) = config.directory.as_ref() {
+		let appender = RollingFileAppender::new(
+			config.rotation.into(),
+			directory,
+			config.file_prefix.as_deref().unwrap_or(DEFAULT_LOG_FILE_PREFIX),
+		);
+		layers.push(fmt_layer(config.format, appender, false, config.env_filter()?));
+	}
+	layers.extend(extra);
+	tracing_subscriber::registry().with(layers).try_init()?;
+	if let Some(directory) = config.directory.as_ref() {
+		log::info!(target: "hyperspace", "Writing logs to {}", directory.display());
+	}
+	Ok(())
+}
+
+fn fmt_layer<W>(format: LogFormat, writer: W, ansi: bool, filter: EnvFilter) -> BoxedLayer
+where
+	W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+	let layer = tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(ansi);
+	match format {
+		LogFormat::Text => layer.with_filter(filter).boxed(),
+		LogFormat::Json => layer
+			.json()
+			.flatten_event(true)
+			.with_span_list(true)
+			.with_filter(filter)
+			.boxed(),
+	}
 }
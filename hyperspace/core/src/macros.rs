@@ -37,11 +37,17 @@ macro_rules! chains {
 			Wasm(WasmChain),
 		}
 
+		#[derive(Clone)]
+		pub struct WasmChain {
+			pub inner: Box<AnyChain>,
+			pub code_id: Bytes,
+		}
+
 		#[derive(Debug)]
 		pub enum AnyFinalityEvent {
 			$(
 				$(#[$($meta)*])*
-				$name(<$client as IbcProvider>::FinalityEvent),
+				$name(<$client as FinalitySource>::FinalityEvent),
 			)*
 		}
 
@@ -49,7 +55,7 @@ macro_rules! chains {
 		pub enum AnyAssetId {
 			$(
 				$(#[$($meta)*])*
-				$name(<$client as IbcProvider>::AssetId),
+				$name(<$client as IbcQuerier>::AssetId),
 			)*
 		}
 
@@ -57,7 +63,7 @@ macro_rules! chains {
 		pub enum AnyTransactionId {
 			$(
 				$(#[$($meta)*])*
-				$name(<$client as IbcProvider>::TransactionId),
+				$name(<$client as TxSubmitter>::TransactionId),
 			)*
 		}
 
@@ -66,7 +72,7 @@ macro_rules! chains {
 			$(
 				$(#[$($meta)*])*
 				#[error("{0}")]
-				$name(<$client as IbcProvider>::Error),
+				$name(<$client as IbcQuerier>::Error),
 			)*
 			#[error("{0}")]
 			Other(String),
@@ -78,40 +84,44 @@ macro_rules! chains {
 			}
 		}
 
+		impl From<String> for AnyError {
+			fn from(s: String) -> Self {
+				Self::Other(s)
+			}
+		}
+
 		#[async_trait]
-		impl IbcProvider for AnyChain {
-			type FinalityEvent = AnyFinalityEvent;
-			type TransactionId = AnyTransactionId;
+		impl IbcQuerier for AnyChain {
 			type Error = AnyError;
 			type AssetId = AnyAssetId;
 
-			async fn query_latest_ibc_events<T>(
-				&mut self,
-				finality_event: Self::FinalityEvent,
+			async fn query_ibc_events_in_range<T>(
+				&self,
 				counterparty: &T,
-			) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+				from: u64,
+				to: u64,
+			) -> Result<Vec<IbcEvent>, anyhow::Error>
 			where
 				T: Chain,
 			{
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => {
-							let finality_event = downcast!(finality_event => AnyFinalityEvent::$name)
-								.ok_or_else(|| AnyError::Other("Invalid finality event type".to_owned()))?;
-							chain.query_latest_ibc_events(finality_event, counterparty).await
-						}
+						Self::$name(chain) =>
+							chain.query_ibc_events_in_range(counterparty, from, to).await,
 					)*
-					AnyChain::Wasm(c) =>
-						c.inner.query_latest_ibc_events(finality_event, counterparty).await,
+					Self::Wasm(c) => c.inner.query_ibc_events_in_range(counterparty, from, to).await,
 				}
 			}
 
-			async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
+			async fn ibc_events(
+				&self,
+			) -> Pin<Box<dyn Stream<Item = Result<IbcEvent, Self::Error>> + Send + 'static>> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.ibc_events().await,
+						Self::$name(chain) =>
+							Box::pin(chain.ibc_events().await.map(|ev| ev.map_err(AnyError::$name))),
 					)*
 					Self::Wasm(c) => c.inner.ibc_events().await,
 				}
@@ -201,6 +211,40 @@ macro_rules! chains {
 				}
 			}
 
+			async fn query_proofs(
+				&self,
+				at: Height,
+				keys: Vec<Vec<Vec<u8>>>,
+			) -> Result<Vec<Vec<u8>>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_proofs(at, keys)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					AnyChain::Wasm(c) => c.inner.query_proofs(at, keys).await,
+				}
+			}
+
+			async fn query_batch_proof(
+				&self,
+				at: Height,
+				paths: Vec<String>,
+			) -> Result<Option<Vec<u8>>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_batch_proof(at, paths)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					AnyChain::Wasm(c) => c.inner.query_batch_proof(at, paths).await,
+				}
+			}
+
 			async fn query_packet_commitment(
 				&self,
 				at: Height,
@@ -473,6 +517,20 @@ macro_rules! chains {
 				}
 			}
 
+			async fn query_upgraded_client(
+				&self,
+				at: Height,
+			) -> Result<Option<UpgradedClient>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) =>
+							chain.query_upgraded_client(at).await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.query_upgraded_client(at).await,
+				}
+			}
+
 			async fn query_ibc_balance(
 				&self,
 				asset_id: AnyAssetId,
@@ -488,6 +546,28 @@ macro_rules! chains {
 				}
 			}
 
+			async fn query_native_balance(&self) -> Result<PrefixedCoin, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) =>
+							chain.query_native_balance().await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.query_native_balance().await,
+				}
+			}
+
+			async fn query_key_balances(&self) -> Result<Vec<(String, PrefixedCoin)>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) =>
+							chain.query_key_balances().await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.query_key_balances().await,
+				}
+			}
+
 			fn connection_prefix(&self) -> CommitmentPrefix {
 				match self {
 					$(
@@ -583,151 +663,132 @@ macro_rules! chains {
 				}
 			}
 
-			async fn is_update_required(
-				&self,
-				latest_height: u64,
-				latest_client_height_on_counterparty: u64,
-			) -> Result<bool, Self::Error> {
+			fn set_channel_whitelist(&mut self, channel_whitelist: std::collections::HashSet<(ChannelId, PortId)>) {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain
-							.is_update_required(latest_height, latest_client_height_on_counterparty)
-							.await
-							.map_err(AnyError::$name),
+						Self::$name(chain) => chain.set_channel_whitelist(channel_whitelist),
 					)*
-					Self::Wasm(c) => c
-						.inner
-						.is_update_required(latest_height, latest_client_height_on_counterparty)
-						.await
-						.map_err(Into::into),
+					Self::Wasm(c) => c.inner.set_channel_whitelist(channel_whitelist),
 				}
 			}
 
-			async fn initialize_client_state(
-				&self,
-			) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+			fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.initialize_client_state().await.map_err(AnyError::$name),
+						Self::$name(chain) => chain.add_channel_to_whitelist(channel),
 					)*
-					Self::Wasm(c) => c.inner.initialize_client_state().await,
+					Self::Wasm(c) => c.inner.add_channel_to_whitelist(channel),
 				}
 			}
 
-			async fn query_client_id_from_tx_hash(
-				&self,
-				tx_id: Self::TransactionId,
-			) -> Result<ClientId, Self::Error> {
+			fn set_connection_id(&mut self, connection_id: ConnectionId) {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain
-							.query_client_id_from_tx_hash(
-								downcast!(tx_id => AnyTransactionId::$name)
-									.expect("Should be $name transaction id"),
-							)
-							.await
-							.map_err(AnyError::$name),
+						Self::$name(chain) => chain.set_connection_id(connection_id),
 					)*
-					Self::Wasm(c) => c.inner.query_client_id_from_tx_hash(tx_id).await,
+					Self::Wasm(c) => c.inner.set_connection_id(connection_id),
 				}
 			}
 
-			async fn upload_wasm(&self, wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
+			fn name(&self) -> &str {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.upload_wasm(wasm).await.map_err(AnyError::$name),
+						Self::$name(chain) => chain.name(),
 					)*
-					Self::Wasm(c) => c.inner.upload_wasm(wasm).await,
+					Self::Wasm(c) => c.inner.name(),
 				}
 			}
 
-			async fn query_connection_id_from_tx_hash(
-				&self,
-				tx_id: Self::TransactionId,
-			) -> Result<ConnectionId, Self::Error> {
+			async fn query_best_and_finalized_heights(&self) -> Result<(u64, u64), Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain
-							.query_connection_id_from_tx_hash(
-								downcast!(tx_id => AnyTransactionId::$name)
-									.expect("Should be $name transaction id"),
-							)
-							.await
-							.map_err(AnyError::$name),
+						Self::$name(chain) =>
+							chain.query_best_and_finalized_heights().await.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.query_connection_id_from_tx_hash(tx_id).await,
+					Self::Wasm(c) => c.inner.query_best_and_finalized_heights().await,
 				}
 			}
 
-			async fn query_channel_id_from_tx_hash(
-				&self,
-				tx_id: Self::TransactionId,
-			) -> Result<(ChannelId, PortId), Self::Error> {
+			async fn handle_error(&mut self, e: &anyhow::Error) -> std::result::Result<(), anyhow::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain
-							.query_channel_id_from_tx_hash(
-								downcast!(tx_id => AnyTransactionId::$name)
-									.expect("Should be $name transaction id"),
-							)
-							.await
-							.map_err(AnyError::$name),
+						Self::$name(chain) => chain.handle_error(e).await,
 					)*
-					Self::Wasm(c) => c.inner.query_channel_id_from_tx_hash(tx_id).await,
+					Self::Wasm(c) => c.inner.handle_error(e).await,
 				}
 			}
 
-			fn set_channel_whitelist(&mut self, channel_whitelist: std::collections::HashSet<(ChannelId, PortId)>) {
+			fn rpc_call_delay(&self) -> std::time::Duration {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.set_channel_whitelist(channel_whitelist),
+						Self::$name(chain) => chain.rpc_call_delay(),
 					)*
-					Self::Wasm(c) => c.inner.set_channel_whitelist(channel_whitelist),
+					Self::Wasm(c) => c.inner.rpc_call_delay(),
 				}
 			}
 
-			fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
+			fn set_rpc_call_delay(&mut self, d: std::time::Duration) {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.add_channel_to_whitelist(channel),
+						Self::$name(chain) => chain.set_rpc_call_delay(d),
 					)*
-					Self::Wasm(c) => c.inner.add_channel_to_whitelist(channel),
+					Self::Wasm(c) => c.inner.set_rpc_call_delay(d),
 				}
 			}
 
-			fn set_connection_id(&mut self, connection_id: ConnectionId) {
+			fn common_state(&self) -> &CommonClientState {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.set_connection_id(connection_id),
+						Self::$name(chain) => chain.common_state(),
 					)*
-					Self::Wasm(c) => c.inner.set_connection_id(connection_id),
+					Self::Wasm(c) => c.inner.common_state(),
+				}
+			}
+
+			fn common_state_mut(&mut self) -> &mut CommonClientState {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.common_state_mut(),
+					)*
+					Self::Wasm(c) => c.inner.common_state_mut(),
+				}
+			}
+
+			async fn reconnect(&mut self) -> anyhow::Result<()> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.reconnect().await,
+					)*
+					Self::Wasm(c) => c.inner.reconnect().await,
 				}
 			}
 		}
 
 		#[async_trait]
 		impl MisbehaviourHandler for AnyChain {
-			async fn check_for_misbehaviour<C: Chain>(
+			async fn detect_misbehaviour<C: Chain>(
 				&self,
 				counterparty: &C,
 				client_message: AnyClientMessage,
-			) -> Result<(), anyhow::Error> {
+			) -> Result<Option<Any>, anyhow::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
 						Self::$name(chain) =>
-							chain.check_for_misbehaviour(counterparty, client_message).await,
+							chain.detect_misbehaviour(counterparty, client_message).await,
 					)*
-					AnyChain::Wasm(c) => c.inner.check_for_misbehaviour(counterparty, client_message).await,
+					AnyChain::Wasm(c) => c.inner.detect_misbehaviour(counterparty, client_message).await,
 				}
 			}
 		}
@@ -742,17 +803,99 @@ macro_rules! chains {
 					AnyChain::Wasm(c) => c.inner.account_id(),
 				}
 			}
+
+			fn sign_payload(
+				&self,
+				payload: &[u8],
+			) -> Result<primitives::PayloadSignature, anyhow::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.sign_payload(payload),
+					)*
+					AnyChain::Wasm(c) => c.inner.sign_payload(payload),
+				}
+			}
+
+			fn reload_keys(&mut self) -> Result<Vec<String>, anyhow::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.reload_keys(),
+					)*
+					AnyChain::Wasm(c) => c.inner.reload_keys(),
+				}
+			}
 		}
 
 		#[async_trait]
-		impl Chain for AnyChain {
-			fn name(&self) -> &str {
+		impl TxSubmitter for AnyChain {
+			type TransactionId = AnyTransactionId;
+
+			async fn query_client_id_from_tx_hash(
+				&self,
+				tx_id: Self::TransactionId,
+			) -> Result<ClientId, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.name(),
+						Self::$name(chain) => chain
+							.query_client_id_from_tx_hash(
+								downcast!(tx_id => AnyTransactionId::$name)
+									.expect("Should be $name transaction id"),
+							)
+							.await
+							.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.name(),
+					Self::Wasm(c) => c.inner.query_client_id_from_tx_hash(tx_id).await,
+				}
+			}
+
+			async fn upload_wasm(&self, wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.upload_wasm(wasm).await.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.upload_wasm(wasm).await,
+				}
+			}
+
+			async fn query_connection_id_from_tx_hash(
+				&self,
+				tx_id: Self::TransactionId,
+			) -> Result<ConnectionId, Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_connection_id_from_tx_hash(
+								downcast!(tx_id => AnyTransactionId::$name)
+									.expect("Should be $name transaction id"),
+							)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.query_connection_id_from_tx_hash(tx_id).await,
+				}
+			}
+
+			async fn query_channel_id_from_tx_hash(
+				&self,
+				tx_id: Self::TransactionId,
+			) -> Result<(ChannelId, PortId), Self::Error> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain
+							.query_channel_id_from_tx_hash(
+								downcast!(tx_id => AnyTransactionId::$name)
+									.expect("Should be $name transaction id"),
+							)
+							.await
+							.map_err(AnyError::$name),
+					)*
+					Self::Wasm(c) => c.inner.query_channel_id_from_tx_hash(tx_id).await,
 				}
 			}
 
@@ -776,22 +919,13 @@ macro_rules! chains {
 				}
 			}
 
-			async fn finality_notifications(
-				&self,
-			) -> Result<Pin<Box<dyn Stream<Item = Self::FinalityEvent> + Send + Sync>>, Self::Error> {
+			async fn estimate_fee(&self, msgs: &[Any]) -> Result<primitives::Fee, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => {
-							use futures::StreamExt;
-							Ok(
-								Box::pin(chain.finality_notifications().await
-									.map_err(AnyError::$name)?
-									.map(AnyFinalityEvent::$name))
-							)
-						},
+						Self::$name(chain) => chain.estimate_fee(msgs).await.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.finality_notifications().await,
+					Self::Wasm(c) => c.inner.estimate_fee(msgs).await,
 				}
 			}
 
@@ -806,95 +940,141 @@ macro_rules! chains {
 							.map(|id| AnyTransactionId::$name(id)),
 					)*
 					Self::Wasm(chain) => {
-						let messages = messages
-							.into_iter()
-							.map(|msg| wrap_any_msg_into_wasm(msg, chain.code_id.clone()))
-							.collect::<Result<Vec<_>, _>>()?;
-						chain.inner.submit(messages).await.map_err(AnyError::into)
+						let mut wrapped = Vec::with_capacity(messages.len());
+						for msg in messages {
+							let code_id = chain.code_id.clone();
+							wrapped.push(wrap_any_msg_into_wasm(&chain.inner, msg, code_id).await?);
+						}
+						chain.inner.submit(wrapped).await.map_err(AnyError::into)
 					},
 				}
 			}
+		}
 
-			async fn query_client_message(
-				&self,
-				update: UpdateClient,
-			) -> Result<AnyClientMessage, Self::Error> {
-				match self {
+		#[async_trait]
+		impl FinalitySource for AnyChain {
+			type FinalityEvent = AnyFinalityEvent;
+
+			fn is_mandatory_finality_event(finality_event: &Self::FinalityEvent) -> bool {
+				match finality_event {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.query_client_message(update).await.map_err(AnyError::$name),
+						AnyFinalityEvent::$name(finality_event) =>
+							<$client as FinalitySource>::is_mandatory_finality_event(finality_event),
 					)*
-					Self::Wasm(c) => c.inner.query_client_message(update).await,
 				}
 			}
 
-			async fn get_proof_height(&self, block_height: Height) -> Height {
+			async fn query_latest_ibc_events<T>(
+				&mut self,
+				finality_event: Self::FinalityEvent,
+				counterparty: &T,
+			) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+			where
+				T: Chain,
+			{
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.get_proof_height(block_height).await,
+						Self::$name(chain) => {
+							let finality_event = downcast!(finality_event => AnyFinalityEvent::$name)
+								.ok_or_else(|| AnyError::Other("Invalid finality event type".to_owned()))?;
+							chain.query_latest_ibc_events(finality_event, counterparty).await
+						}
 					)*
-					Self::Wasm(c) => c.inner.get_proof_height(block_height).await,
+					AnyChain::Wasm(c) =>
+						c.inner.query_latest_ibc_events(finality_event, counterparty).await,
 				}
 			}
 
-			async fn handle_error(&mut self, e: &anyhow::Error) -> std::result::Result<(), anyhow::Error> {
+			async fn is_update_required(
+				&self,
+				latest_height: u64,
+				latest_client_height_on_counterparty: u64,
+			) -> Result<bool, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.handle_error(e).await,
+						Self::$name(chain) => chain
+							.is_update_required(latest_height, latest_client_height_on_counterparty)
+							.await
+							.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.handle_error(e).await,
+					Self::Wasm(c) => c
+						.inner
+						.is_update_required(latest_height, latest_client_height_on_counterparty)
+						.await
+						.map_err(Into::into),
 				}
 			}
 
-			fn rpc_call_delay(&self) -> std::time::Duration {
+			async fn initialize_client_state(
+				&self,
+			) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.rpc_call_delay(),
+						Self::$name(chain) => chain.initialize_client_state().await.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.rpc_call_delay(),
+					Self::Wasm(c) => c.inner.initialize_client_state().await,
 				}
 			}
 
-			fn set_rpc_call_delay(&mut self, d: std::time::Duration) {
+			async fn initialize_client_state_at(
+				&self,
+				height: u64,
+			) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.set_rpc_call_delay(d),
+						Self::$name(chain) => chain
+							.initialize_client_state_at(height)
+							.await
+							.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.set_rpc_call_delay(d),
+					Self::Wasm(c) => c.inner.initialize_client_state_at(height).await,
 				}
 			}
 
-			fn common_state(&self) -> &CommonClientState {
+			async fn finality_notifications(
+				&self,
+			) -> Result<Pin<Box<dyn Stream<Item = Self::FinalityEvent> + Send + Sync>>, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.common_state(),
+						Self::$name(chain) => {
+							use futures::StreamExt;
+							Ok(
+								Box::pin(chain.finality_notifications().await
+									.map_err(AnyError::$name)?
+									.map(AnyFinalityEvent::$name))
+							)
+						},
 					)*
-					Self::Wasm(c) => c.inner.common_state(),
+					Self::Wasm(c) => c.inner.finality_notifications().await,
 				}
 			}
 
-			fn common_state_mut(&mut self) -> &mut CommonClientState {
+			async fn query_client_message(
+				&self,
+				update: UpdateClient,
+			) -> Result<AnyClientMessage, Self::Error> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.common_state_mut(),
+						Self::$name(chain) => chain.query_client_message(update).await.map_err(AnyError::$name),
 					)*
-					Self::Wasm(c) => c.inner.common_state_mut(),
+					Self::Wasm(c) => c.inner.query_client_message(update).await,
 				}
 			}
 
-			async fn reconnect(&mut self) -> anyhow::Result<()> {
+			async fn get_proof_height(&self, block_height: Height) -> Height {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.reconnect().await,
+						Self::$name(chain) => chain.get_proof_height(block_height).await,
 					)*
-					Self::Wasm(c) => c.inner.reconnect().await,
+					Self::Wasm(c) => c.inner.get_proof_height(block_height).await,
 				}
 			}
 		}
@@ -926,6 +1106,25 @@ macro_rules! chains {
 			}
 		}
 
+		impl AnyChain {
+			/// Applies a reloaded configuration to the running client. The chain type itself
+			/// cannot change at runtime.
+			pub fn reload_config(&mut self, config: AnyConfig) -> anyhow::Result<()> {
+				match (self, config) {
+					$(
+						$(#[$($meta)*])*
+						(Self::$name(chain), AnyConfig::$name(config)) =>
+							Ok(chain.reload_config(config)?),
+					)*
+					(Self::Wasm(c), config) => c.inner.reload_config(config),
+					(chain, _) => Err(anyhow::anyhow!(
+						"Chain type of {} cannot be changed without a restart",
+						chain.name()
+					)),
+				}
+			}
+		}
+
 		#[cfg(any(test, feature = "testing"))]
 		impl AnyChain {
 			pub fn set_client_id(&mut self, client_id: ClientId) {
@@ -966,11 +1165,15 @@ macro_rules! chains {
 				}
 			}
 
-			async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>> {
+			async fn subscribe_blocks(
+				&self,
+			) -> Pin<Box<dyn Stream<Item = Result<u64, Self::Error>> + Send + Sync>> {
 				match self {
 					$(
 						$(#[$($meta)*])*
-						Self::$name(chain) => chain.subscribe_blocks().await,
+						Self::$name(chain) => Box::pin(
+							chain.subscribe_blocks().await.map(|block| block.map_err(AnyError::$name)),
+						),
 					)*
 					Self::Wasm(c) => c.inner.subscribe_blocks().await,
 				}
@@ -1003,6 +1206,33 @@ macro_rules! chains {
 				}
 			}
 
+			pub fn client_id(&self) -> Option<ClientId> {
+				match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => chain.client_id.clone(),
+					)*
+				}
+			}
+
+			/// Checks the config without connecting to the chain.
+			pub fn validate(&self) -> anyhow::Result<()> {
+				let code_id = match self {
+					$(
+						$(#[$($meta)*])*
+						Self::$name(chain) => {
+							chain.validate()?;
+							chain.wasm_code_id.as_ref()
+						},
+					)*
+				};
+				if let Some(code_id) = code_id {
+					hex::decode(code_id)
+						.map_err(|e| anyhow::anyhow!("Invalid wasm code id {code_id}: {e}"))?;
+				}
+				Ok(())
+			}
+
 			pub fn set_client_id(&mut self, client_id: ClientId) {
 				match self {
 					$(
@@ -1060,5 +1290,42 @@ macro_rules! chains {
 				}
 			}
 		}
+
+		#[async_trait]
+		impl $crate::plugin::ChainConfig for AnyConfig {
+			type Chain = AnyChain;
+
+			async fn into_client(self) -> anyhow::Result<AnyChain> {
+				AnyConfig::into_client(self).await
+			}
+
+			fn reload(self, chain: &mut AnyChain) -> anyhow::Result<()> {
+				chain.reload_config(self)
+			}
+
+			fn validate(&self) -> anyhow::Result<()> {
+				AnyConfig::validate(self)
+			}
+
+			fn client_id(&self) -> Option<ClientId> {
+				AnyConfig::client_id(self)
+			}
+
+			fn set_client_id(&mut self, client_id: ClientId) {
+				AnyConfig::set_client_id(self, client_id)
+			}
+
+			fn set_connection_id(&mut self, connection_id: ConnectionId) {
+				AnyConfig::set_connection_id(self, connection_id)
+			}
+
+			fn set_channel_whitelist(&mut self, channel_id: ChannelId, port_id: PortId) {
+				AnyConfig::set_channel_whitelist(self, channel_id, port_id)
+			}
+
+			fn set_wasm_code_id(&mut self, code_id: String) {
+				AnyConfig::set_wasm_code_id(self, code_id)
+			}
+		}
 	};
 }
@@ -0,0 +1,391 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watching of the client updates on both chains for misbehaviour (fishing).
+//!
+//! The evidence of a detected misbehaviour is written to an [`EvidenceStore`] before it's
+//! submitted, and only removed from it once the submission was accepted. Failed submissions are
+//! retried until they succeed, and evidence left in the store by a previous run is submitted
+//! again on startup, so no evidence is lost if a transaction fails or the process restarts.
+//!
+//! What happens on misbehaviour of a chain is controlled by the [`MisbehaviourPolicy`] of its
+//! security params: the evidence is either submitted, only saved and reported, or the client
+//! updates aren't checked at all.
+//!
+//! Past client updates can be checked as well with [`scan_misbehaviour`].
+
+use anyhow::anyhow;
+use futures::{future::ready, Stream, StreamExt};
+use ibc::{core::ics02_client::events::UpdateClient, events::IbcEvent};
+use ibc_proto::google::protobuf::Any;
+use primitives::{Chain, MisbehaviourPolicy};
+use serde::{Deserialize, Serialize};
+use std::{
+	path::{Path, PathBuf},
+	pin::Pin,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Default directory of the misbehaviour evidence.
+pub const DEFAULT_EVIDENCE_DIR: &str = "misbehaviour-evidence";
+
+/// Delay between two submission attempts of the same evidence.
+const EVIDENCE_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Number of blocks whose client updates are queried at once by [`scan_misbehaviour`].
+const SCAN_BATCH_SIZE: u64 = 100;
+
+/// Delay before resubscribing to a failed event stream, doubled on each consecutive failure up to
+/// [`MAX_RESUBSCRIBE_DELAY`].
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum delay before resubscribing to a failed event stream.
+const MAX_RESUBSCRIBE_DELAY: Duration = Duration::from_secs(60);
+
+/// A misbehaviour report, as stored on disk.
+#[derive(Serialize, Deserialize)]
+struct Evidence {
+	/// Name of the chain the report is submitted to.
+	chain: String,
+	type_url: String,
+	/// Hex encoded message.
+	value: String,
+}
+
+/// Directory holding the misbehaviour reports that weren't accepted yet, one JSON file each.
+#[derive(Debug, Clone)]
+pub struct EvidenceStore {
+	dir: PathBuf,
+}
+
+impl EvidenceStore {
+	pub fn new(dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+		let dir = dir.into();
+		std::fs::create_dir_all(&dir)
+			.map_err(|e| anyhow!("Failed to create evidence directory {}: {e}", dir.display()))?;
+		Ok(Self { dir })
+	}
+
+	/// Writes the report `msg` to be submitted to `chain`, returning the path of its file.
+	pub fn save(&self, chain: &str, msg: &Any) -> anyhow::Result<PathBuf> {
+		let evidence = Evidence {
+			chain: chain.to_string(),
+			type_url: msg.type_url.clone(),
+			value: hex::encode(&msg.value),
+		};
+		let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+		let path = self.dir.join(format!("{chain}-{nanos}.json"));
+		// write to a temporary file first, so that a crash never leaves a truncated report
+		let tmp = path.with_extension("tmp");
+		std::fs::write(&tmp, serde_json::to_vec(&evidence)?)?;
+		std::fs::rename(&tmp, &path)?;
+		Ok(path)
+	}
+
+	/// Returns the stored reports to be submitted to `chain`.
+	pub fn pending(&self, chain: &str) -> anyhow::Result<Vec<(PathBuf, Any)>> {
+		let mut pending = vec![];
+		for entry in std::fs::read_dir(&self.dir)? {
+			let path = entry?.path();
+			if path.extension().map_or(true, |ext| ext != "json") {
+				continue
+			}
+			let evidence = serde_json::from_slice::<Evidence>(&std::fs::read(&path)?)
+				.map_err(|e| anyhow!("Invalid evidence file {}: {e}", path.display()))?;
+			if evidence.chain == chain {
+				let msg = Any { type_url: evidence.type_url, value: hex::decode(evidence.value)? };
+				pending.push((path, msg));
+			}
+		}
+		pending.sort_by(|(a, _), (b, _)| a.cmp(b));
+		Ok(pending)
+	}
+
+	pub fn remove(&self, path: &Path) -> anyhow::Result<()> {
+		std::fs::remove_file(path).map_err(Into::into)
+	}
+}
+
+/// Watches the client updates on both chains for misbehaviour of their counterparty, reporting
+/// it through `store`. Only returns if an event stream ends.
+pub async fn watch_misbehaviour<A: Chain, B: Chain>(
+	mut chain_a: A,
+	mut chain_b: B,
+	store: EvidenceStore,
+) -> anyhow::Result<()> {
+	resubmit_pending_evidence(&chain_a, &chain_b, &store)?;
+	resubmit_pending_evidence(&chain_b, &chain_a, &store)?;
+
+	// we only care about events where the counterparty light client is updated.
+	let (mut chain_a_client_updates, mut chain_b_client_updates) =
+		(client_updates(&chain_a, &chain_b).await, client_updates(&chain_b, &chain_a).await);
+	// consecutive failures of the event streams, to back off while a node is down
+	let (mut chain_a_failures, mut chain_b_failures) = (0u32, 0u32);
+
+	loop {
+		tokio::select! {
+			update = chain_a_client_updates.next() => {
+				let update = match update {
+					Some(Ok(update)) => {
+						chain_a_failures = 0;
+						update
+					},
+					Some(Err(e)) => {
+						log::error!("Event stream failed for {}: {:?}", chain_a.name(), e);
+						chain_a_client_updates =
+							resubscribe(&mut chain_a, &chain_b, &mut chain_a_failures).await;
+						continue
+					},
+					None => break,
+				};
+				if let Err(e) = check_client_update(&chain_a, &chain_b, update, &store).await {
+					log::error!("Failed to check client update on {} for misbehaviour: {:?}", chain_a.name(), e);
+				}
+			}
+			update = chain_b_client_updates.next() => {
+				let update = match update {
+					Some(Ok(update)) => {
+						chain_b_failures = 0;
+						update
+					},
+					Some(Err(e)) => {
+						log::error!("Event stream failed for {}: {:?}", chain_b.name(), e);
+						chain_b_client_updates =
+							resubscribe(&mut chain_b, &chain_a, &mut chain_b_failures).await;
+						continue
+					},
+					None => break,
+				};
+				if let Err(e) = check_client_update(&chain_b, &chain_a, update, &store).await {
+					log::error!("Failed to check client update on {} for misbehaviour: {:?}", chain_b.name(), e);
+				}
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Checks an update of the client of `counterparty` hosted on `host` for misbehaviour, storing
+/// and submitting the evidence to `host` if there is any.
+async fn check_client_update<H: Chain, C: Chain>(
+	host: &H,
+	counterparty: &C,
+	update: UpdateClient,
+	store: &EvidenceStore,
+) -> anyhow::Result<()> {
+	let policy = counterparty.security_params().misbehaviour_policy;
+	if policy == MisbehaviourPolicy::Ignore {
+		return Ok(())
+	}
+	// The corresponding transaction on tendermint may not be indexed yet, so we wait for a bit
+	if host.client_type() == "07-tendermint" {
+		tokio::time::sleep(host.expected_block_time()).await;
+	}
+	let Some((path, evidence)) = detect_misbehaviour(host, counterparty, update, store).await?
+	else {
+		return Ok(())
+	};
+	if policy == MisbehaviourPolicy::Submit && !host.common_state().dry_run {
+		tokio::spawn(submit_evidence(host.clone(), store.clone(), path, evidence));
+	}
+	Ok(())
+}
+
+/// Checks the client message of `update` for misbehaviour, saving the evidence to `store` if
+/// there is any.
+async fn detect_misbehaviour<H: Chain, C: Chain>(
+	host: &H,
+	counterparty: &C,
+	update: UpdateClient,
+	store: &EvidenceStore,
+) -> anyhow::Result<Option<(PathBuf, Any)>> {
+	let message = host.query_client_message(update).await?;
+	let Some(evidence) = counterparty.detect_misbehaviour(host, message).await? else {
+		return Ok(None)
+	};
+	let path = store.save(host.name(), &evidence)?;
+	log::error!(
+		target: "hyperspace",
+		"ALERT: misbehaviour of {} detected, evidence saved to {}",
+		counterparty.name(),
+		path.display()
+	);
+	Ok(Some((path, evidence)))
+}
+
+/// Checks the updates of the client of `counterparty` submitted to `host` in the blocks
+/// `from..=to` (up to the latest block if `to` is `None`) for misbehaviour, e.g. to find out
+/// after an incident whether the client was fed a conflicting header. The blocks must still be
+/// available on `host`'s nodes, which usually requires archive nodes.
+///
+/// The evidence is saved even if the [`MisbehaviourPolicy`] is `Ignore`, and submitted once if
+/// it's `Submit`. Evidence whose submission failed stays in `store` and is retried by the next
+/// [`watch_misbehaviour`]. Returns the number of misbehaviours found.
+pub async fn scan_misbehaviour<H: Chain, C: Chain>(
+	host: &H,
+	counterparty: &C,
+	from: u64,
+	to: Option<u64>,
+	store: &EvidenceStore,
+) -> anyhow::Result<usize> {
+	let to = match to {
+		Some(to) => to,
+		None => host.latest_height_and_timestamp().await?.0.revision_height,
+	};
+	let client_id = counterparty.client_id();
+	let submit = counterparty.security_params().misbehaviour_policy == MisbehaviourPolicy::Submit &&
+		!host.common_state().dry_run;
+	log::info!(target: "hyperspace", "Scanning blocks {from}..={to} of {} for misbehaviour of {}", host.name(), counterparty.name());
+
+	let mut found = 0;
+	let mut batch_from = from;
+	while batch_from <= to {
+		let batch_to = (batch_from + SCAN_BATCH_SIZE - 1).min(to);
+		let updates = host
+			.query_ibc_events_in_range(counterparty, batch_from, batch_to)
+			.await?
+			.into_iter()
+			.filter_map(|event| match event {
+				IbcEvent::UpdateClient(update) if *update.client_id() == client_id => Some(update),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		log::debug!(target: "hyperspace", "Found {} updates of {client_id} on {} in {batch_from}..={batch_to}", updates.len(), host.name());
+		for update in updates {
+			let height = update.height();
+			let (path, evidence) = match detect_misbehaviour(host, counterparty, update, store)
+				.await
+			{
+				Ok(Some(evidence)) => evidence,
+				Ok(None) => continue,
+				Err(e) => {
+					log::warn!(target: "hyperspace", "Failed to check the update of {client_id} at {height} on {}: {:?}", host.name(), e);
+					continue
+				},
+			};
+			found += 1;
+			if !submit {
+				continue
+			}
+			match host.submit(vec![evidence]).await {
+				Ok(_) => {
+					log::info!(target: "hyperspace", "Submitted misbehaviour evidence {} to {}", path.display(), host.name());
+					if let Err(e) = store.remove(&path) {
+						log::warn!(target: "hyperspace", "Failed to remove submitted evidence {}: {:?}", path.display(), e);
+					}
+				},
+				Err(e) => log::error!(
+					target: "hyperspace",
+					"Failed to submit misbehaviour evidence {} to {}: {:?}",
+					path.display(),
+					host.name(),
+					e
+				),
+			}
+		}
+		batch_from = batch_to + 1;
+	}
+	log::info!(target: "hyperspace", "Found {found} misbehaviours of {} in blocks {from}..={to} of {}", counterparty.name(), host.name());
+
+	Ok(found)
+}
+
+/// Submits the evidence of `counterparty`'s misbehaviour left in `store` to `chain`, unless its
+/// [`MisbehaviourPolicy`] leaves the submission to an operator.
+fn resubmit_pending_evidence(
+	chain: &impl Chain,
+	counterparty: &impl Chain,
+	store: &EvidenceStore,
+) -> anyhow::Result<()> {
+	if counterparty.security_params().misbehaviour_policy != MisbehaviourPolicy::Submit ||
+		chain.common_state().dry_run
+	{
+		return Ok(())
+	}
+	for (path, evidence) in store.pending(chain.name())? {
+		log::info!(target: "hyperspace", "Resubmitting misbehaviour evidence {} to {}", path.display(), chain.name());
+		tokio::spawn(submit_evidence(chain.clone(), store.clone(), path, evidence));
+	}
+	Ok(())
+}
+
+/// Submits `evidence` to `chain` until it's accepted, then removes it from `store`.
+async fn submit_evidence<C: Chain>(chain: C, store: EvidenceStore, path: PathBuf, evidence: Any) {
+	loop {
+		match chain.submit(vec![evidence.clone()]).await {
+			Ok(_) => break,
+			Err(e) => {
+				log::error!(
+					target: "hyperspace",
+					"Failed to submit misbehaviour evidence {} to {}: {:?}. Retrying in {:?}",
+					path.display(),
+					chain.name(),
+					e,
+					EVIDENCE_RETRY_DELAY
+				);
+				tokio::time::sleep(EVIDENCE_RETRY_DELAY).await;
+			},
+		}
+	}
+	log::info!(target: "hyperspace", "Submitted misbehaviour evidence {} to {}", path.display(), chain.name());
+	if let Err(e) = store.remove(&path) {
+		log::warn!(target: "hyperspace", "Failed to remove submitted evidence {}: {:?}", path.display(), e);
+	}
+}
+
+/// Returns a stream of `UpdateClient` events on `chain` that target the light client of
+/// `counterparty`. Stream failures are passed through so the caller can resubscribe.
+/// Reconnects `chain` and subscribes to its client updates again after the `failures`-th
+/// consecutive failure of the subscription, waiting longer after each one.
+async fn resubscribe<A: Chain, B: Chain>(
+	chain: &mut A,
+	counterparty: &B,
+	failures: &mut u32,
+) -> Pin<Box<dyn Stream<Item = Result<UpdateClient, A::Error>> + Send + 'static>> {
+	let delay = resubscribe_delay(*failures);
+	*failures = failures.saturating_add(1);
+	log::info!("Resubscribing to the events of {} in {delay:?}", chain.name());
+	tokio::time::sleep(delay).await;
+	if let Err(e) = chain.reconnect().await {
+		log::error!("Failed to reconnect to {}: {e:?}", chain.name());
+	}
+	client_updates(chain, counterparty).await
+}
+
+/// Delay before resubscribing to an event stream after `failures` consecutive failures.
+fn resubscribe_delay(failures: u32) -> Duration {
+	RESUBSCRIBE_DELAY
+		.saturating_mul(2u32.saturating_pow(failures))
+		.min(MAX_RESUBSCRIBE_DELAY)
+}
+
+async fn client_updates<A: Chain, B: Chain>(
+	chain: &A,
+	counterparty: &B,
+) -> Pin<Box<dyn Stream<Item = Result<UpdateClient, A::Error>> + Send + 'static>> {
+	let client_id = counterparty.client_id();
+	chain
+		.ibc_events()
+		.await
+		.filter_map(move |ev| {
+			ready(match ev {
+				Ok(IbcEvent::UpdateClient(update)) if client_id == *update.client_id() =>
+					Some(Ok(update)),
+				Ok(_) => None,
+				Err(e) => Some(Err(e)),
+			})
+		})
+		.boxed()
+}
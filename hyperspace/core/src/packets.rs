@@ -14,6 +14,7 @@
 
 #[cfg(feature = "testing")]
 use crate::send_packet_relay::packet_relay_status;
+use prost::Message;
 use rand::Rng;
 use sp_runtime::Either::{Left, Right};
 use std::{
@@ -24,28 +25,42 @@ use std::{
 	time::Duration,
 };
 use tokio::{task::JoinSet, time::sleep};
+use tracing::Instrument;
 
-use crate::packets::utils::{
-	construct_ack_message, construct_recv_message, construct_timeout_message,
-	get_timeout_proof_height, verify_delay_passed, VerifyDelayOn,
+use crate::packets::{
+	packet_data::AppPacketData,
+	utils::{
+		construct_close_confirm_message, construct_timeout_message, get_timeout_proof_height,
+		prove_packet_messages, verify_delay_passed, UnprovenPacketMessage, VerifyDelayOn,
+	},
 };
 use ibc::{
-	applications::transfer::packet::PacketData,
 	core::{
 		ics02_client::client_state::ClientState as ClientStateT,
 		ics03_connection::connection::ConnectionEnd,
-		ics04_channel::channel::{ChannelEnd, State},
+		ics04_channel::channel::{ChannelEnd, Order, State},
 	},
+	events::IbcEventType,
 	Height,
 };
-use ibc_proto::google::protobuf::Any;
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::core::channel::v1::{
+		MsgAcknowledgement, MsgRecvPacket, MsgTimeout, MsgTimeoutOnClose, Packet as RawPacket,
+	},
+};
+use ibc_proto_registry::type_url;
 use pallet_ibc::light_clients::AnyClientState;
 use primitives::{
 	error::Error, find_suitable_proof_height_for_client, packet_info_to_packet,
-	query_undelivered_acks, query_undelivered_sequences, Chain, UndeliveredType,
+	query_undelivered_acks, query_undelivered_sequences, Chain, HaltReason, PacketDirection,
+	RelayedSequence, UndeliveredType,
 };
 
+pub mod confirmation;
 pub mod connection_delay;
+pub mod ordered;
+pub mod packet_data;
 pub mod utils;
 
 pub const PROCESS_PACKETS_BATCH_SIZE: usize = 100;
@@ -59,18 +74,48 @@ pub const PROCESS_PACKETS_BATCH_SIZE: usize = 100;
 /// source -> ack_packet     -> sink   => sink has undelivered acks
 /// source -> timeout_packet -> source => source & sink has undelivered timeouts (since timeouts
 /// need both clients to be up to date)
+#[tracing::instrument(skip_all, err, fields(source = source.name(), sink = sink.name()))]
 pub async fn query_ready_and_timed_out_packets(
 	source: &impl Chain,
 	sink: &impl Chain,
 ) -> Result<(Vec<Any>, Vec<Any>), anyhow::Error> {
 	let mut messages = vec![];
+	// receive and acknowledgement messages of all the channels, proven together at the end
+	let mut unproven_messages = vec![];
 	let mut timeout_messages = vec![];
 	let (source_height, source_timestamp) = source.latest_height_and_timestamp().await?;
 	let (sink_height, sink_timestamp) = sink.latest_height_and_timestamp().await?;
+	source
+		.common_state()
+		.relayed_sequences
+		.lock()
+		.unwrap()
+		.advance(source_height.revision_height);
 	let channel_whitelist = source.channel_whitelist();
 
 	// TODO: parallelize this
 	for (channel_id, port_id) in channel_whitelist {
+		if source.common_state().is_channel_paused(&channel_id, &port_id) {
+			log::trace!(target: "hyperspace", "Skipping paused channel {}/{} on {}", channel_id, port_id, source.name());
+			continue
+		}
+		match source.common_state().channel_halt_reason(&channel_id, &port_id) {
+			// timeouts of closed channels can still be delivered, closed channel ends are
+			// handled below
+			None | Some(HaltReason::ChannelClosed) => (),
+			Some(reason) => {
+				log::trace!(target: "hyperspace", "Skipping halted channel {}/{} on {}: {}", channel_id, port_id, source.name(), reason);
+				continue
+			},
+		}
+		let event_filter = &source.common_state().event_filter;
+		let relay_packets =
+			event_filter.allows_packets(IbcEventType::SendPacket, &port_id, &channel_id);
+		let relay_acks = event_filter.allows_packets(IbcEventType::WriteAck, &port_id, &channel_id);
+		if !relay_packets && !relay_acks {
+			log::trace!(target: "hyperspace", "Skipping channel {}/{} on {}: its packets are filtered out", channel_id, port_id, source.name());
+			continue
+		}
 		let source_channel_response = match source
 			.query_channel_end(source_height, channel_id, port_id.clone())
 			.await
@@ -107,6 +152,10 @@ pub async fn query_ready_and_timed_out_packets(
 					"[query_ready_and_timed_out_packets] ConnectionEnd not found for {connection_id:?}"
 				))
 			})?)?;
+		let sink_connection_id =
+			source_connection_end.counterparty().connection_id().cloned().ok_or_else(|| {
+				Error::Custom(format!("Counterparty of connection {connection_id} has no id"))
+			})?;
 
 		let sink_channel_id = source_channel_end.counterparty().channel_id.ok_or_else(|| {
 			Error::Custom(
@@ -178,10 +227,51 @@ pub async fn query_ready_and_timed_out_packets(
 		let latest_sink_height_on_source = sink_client_state_on_source.latest_height();
 		let latest_source_height_on_sink = source_client_state_on_sink.latest_height();
 
+		let is_ordered = source_channel_end.ordering == Order::Ordered;
+		if is_ordered && source_channel_end.state == State::Closed {
+			// the channel was closed by the timeout of one of its packets, the remaining packets
+			// can only be timed out on close once the counterparty's end is closed as well
+			if sink_channel_end.state != State::Closed {
+				match construct_close_confirm_message(
+					source,
+					sink,
+					channel_id,
+					port_id.clone(),
+					latest_source_height_on_sink,
+				)
+				.await?
+				{
+					Some(msg) => messages.push(msg),
+					None => {
+						log::debug!(target: "hyperspace", "Waiting for a client update of {} on {} to close channel {}/{}", source.name(), sink.name(), sink_channel_id, sink_port_id);
+					},
+				}
+				continue
+			}
+		}
+		// only consecutive sequences are relayed on an open ordered channel
+		let sequenced = is_ordered && sink_channel_end.state == State::Open;
+		// the packets of a channel closed on the sink are timed out on close, proving the closed
+		// channel end at the latest height of the sink's client on the source
+		let close_proof_height = if sink_channel_end.state == State::Closed {
+			sink.query_channel_end(
+				latest_sink_height_on_source,
+				sink_channel_id,
+				sink_port_id.clone(),
+			)
+			.await?
+			.channel
+			.and_then(|channel| ChannelEnd::try_from(channel).ok())
+			.filter(|channel| channel.state == State::Closed)
+			.map(|_| latest_sink_height_on_source)
+		} else {
+			None
+		};
+
 		let max_packets_to_process = source.common_state().max_packets_to_process;
 
 		// query packets that are waiting for connection delay.
-		let seqs = query_undelivered_sequences(
+		let undelivered_seqs = query_undelivered_sequences(
 			source_height,
 			sink_height,
 			channel_id,
@@ -189,10 +279,40 @@ pub async fn query_ready_and_timed_out_packets(
 			source,
 			sink,
 		)
-		.await?
-		.into_iter()
-		.take(max_packets_to_process)
-		.collect::<Vec<_>>();
+		.await?;
+		let is_relayed = |sequence| {
+			[PacketDirection::Recv, PacketDirection::Timeout].into_iter().any(|direction| {
+				source.common_state().is_relayed(&RelayedSequence {
+					channel_id,
+					port_id: port_id.clone(),
+					sequence,
+					direction,
+				})
+			})
+		};
+		let mut seqs = if sequenced {
+			ordered::consecutive_sequences(
+				undelivered_seqs,
+				next_sequence_recv.next_sequence_receive,
+				is_relayed,
+			)
+		} else {
+			let mut seqs = undelivered_seqs
+				.into_iter()
+				.filter(|&sequence| !is_relayed(sequence))
+				.collect::<Vec<_>>();
+			// relay the incentivized packets first
+			source.common_state().incentivized_packets.lock().unwrap().prioritize(
+				&port_id,
+				&channel_id,
+				&mut seqs,
+			);
+			seqs
+		};
+		if !relay_packets {
+			seqs.clear();
+		}
+		seqs.truncate(max_packets_to_process);
 
 		log::debug!(target: "hyperspace", "Found {} undelivered packets for {:?}/{:?} for {seqs:?}", seqs.len(), channel_id, port_id.clone());
 
@@ -210,7 +330,8 @@ pub async fn query_ready_and_timed_out_packets(
 			for send_packet in send_packets.iter().cloned() {
 				let source_connection_end = source_connection_end.clone();
 				let sink_channel_end = sink_channel_end.clone();
-				let source_connection_end = source_connection_end.clone();
+				let connection_id = connection_id.clone();
+				let sink_connection_id = sink_connection_id.clone();
 				let source = source.clone();
 				let sink = sink.clone();
 				let duration = Duration::from_millis(
@@ -223,6 +344,7 @@ pub async fn query_ready_and_timed_out_packets(
 					let source = &source;
 					let sink = &sink;
 					let packet = packet_info_to_packet(&send_packet);
+					let sequence = u64::from(packet.sequence);
 					// Check if packet has timed out
 					let packet_height = send_packet.height.ok_or_else(|| {
 						Error::Custom(format!("Packet height not found for packet {packet:?}"))
@@ -261,6 +383,7 @@ pub async fn query_ready_and_timed_out_packets(
 							sink_timestamp,
 							sink_height,
 							source_connection_end.delay_period(),
+							&connection_id,
 							proof_height,
 							VerifyDelayOn::Source,
 						)
@@ -280,17 +403,46 @@ pub async fn query_ready_and_timed_out_packets(
 							proof_height,
 						)
 							.await?;
-						return Ok(Some(Left(msg)))
+						return Ok(Some((sequence, Left(msg))))
 					} else {
 						log::trace!(target: "hyperspace", "The packet has not timed out yet: {:?}", packet);
 					}
 
-					// If packet has not timed out but channel is closed on sink we skip
-					// Since we have no reference point for when this channel was closed so we can't
-					// calculate connection delays yet
+					// If packet has not timed out but channel is closed on sink, it's timed out on
+					// close once the sink's client on the source has a height at which the channel
+					// is closed
 					if sink_channel_end.state == State::Closed {
-						log::debug!(target: "hyperspace", "Skipping packet as channel is closed on sink: {:?}", packet);
-						return Ok(None)
+						let Some(proof_height) = close_proof_height else {
+							log::debug!(target: "hyperspace", "Skipping packet as channel closure on sink is not proven yet: {:?}", packet);
+							return Ok(None)
+						};
+						if !verify_delay_passed(
+							&**source,
+							&**sink,
+							source_timestamp,
+							source_height,
+							sink_timestamp,
+							sink_height,
+							source_connection_end.delay_period(),
+							&connection_id,
+							proof_height,
+							VerifyDelayOn::Source,
+						)
+							.await?
+						{
+							log::trace!(target: "hyperspace", "Skipping packet as connection delay has not passed {:?}", packet);
+							return Ok(None)
+						}
+						let msg = construct_timeout_message(
+							&**source,
+							&**sink,
+							&sink_channel_end,
+							packet,
+							next_sequence_recv.next_sequence_receive,
+							proof_height,
+						)
+							.await?;
+						return Ok(Some((sequence, Left(msg))))
 					}
 
 					#[cfg(feature = "testing")]
@@ -335,6 +487,7 @@ pub async fn query_ready_and_timed_out_packets(
 						sink_timestamp,
 						sink_height,
 						source_connection_end.delay_period(),
+						&sink_connection_id,
 						proof_height,
 						VerifyDelayOn::Sink,
 					)
@@ -351,29 +504,34 @@ pub async fn query_ready_and_timed_out_packets(
 
 					let list = &source.common_state().skip_tokens_list;
 
-					let decoded_dara: PacketData = serde_json::from_str(&String::from_utf8_lossy(packet.data.as_ref())).map_err(|e| {
-						Error::Custom(format!(
-						"Failed to decode packet data for packet {:?}: {:?}",
-						packet, e
-						))
-					})?;
-
-					if list.iter().any(|skiped_denom| decoded_dara.token.denom.base_denom.as_str() == skiped_denom) {
+					if AppPacketData::decode(packet.data.as_ref()).is_skipped(list) {
 						log::info!(target: "hyperspace", "Skipping packet with ignored token: {:?}", packet);
 						return Ok(None)
 					}
 
-					let msg = construct_recv_message(&**source, &**sink, packet, proof_height).await?;
-					Ok(Some(Right(msg)))
-				});
+					Ok(Some((sequence, Right(UnprovenPacketMessage::Recv { packet, proof_height }))))
+				}.in_current_span());
 			}
 		}
 
+		let mut packet_messages = vec![];
 		while let Some(result) = recv_packets_join_set.join_next().await {
-			let Some(either) = result?? else { continue };
-			match either {
-				Left(msg) => timeout_messages.push(msg),
-				Right(msg) => messages.push(msg),
+			let Some(msg) = result?? else { continue };
+			packet_messages.push(msg)
+		}
+		if sequenced {
+			let (recvs, timeout) = ordered::sequence_packet_messages(
+				packet_messages,
+				next_sequence_recv.next_sequence_receive,
+			);
+			unproven_messages.extend(recvs);
+			timeout_messages.extend(timeout);
+		} else {
+			for (_, either) in packet_messages {
+				match either {
+					Left(msg) => timeout_messages.push(msg),
+					Right(msg) => unproven_messages.push(msg),
+				}
 			}
 		}
 
@@ -392,9 +550,12 @@ pub async fn query_ready_and_timed_out_packets(
 			log::trace!(target: "hyperspace", "Skipping acknowledgements for channel {:?} as channel is closed on source", channel_id);
 			continue
 		}
+		if !relay_acks {
+			continue
+		}
 
 		// query acknowledgements that are waiting for connection delay.
-		let acks = query_undelivered_acks(
+		let undelivered_acks = query_undelivered_acks(
 			source_height,
 			sink_height,
 			channel_id,
@@ -402,10 +563,34 @@ pub async fn query_ready_and_timed_out_packets(
 			&*source,
 			&*sink,
 		)
-		.await?
-		.into_iter()
-		.take(max_packets_to_process)
-		.collect::<Vec<_>>();
+		.await?;
+		let is_relayed = |sequence| {
+			source.common_state().is_relayed(&RelayedSequence {
+				channel_id,
+				port_id: port_id.clone(),
+				sequence,
+				direction: PacketDirection::Ack,
+			})
+		};
+		// the commitments of the packets of an ordered channel are deleted in sequence as they're
+		// acknowledged, so the lowest undelivered acknowledgement is the next one the sink expects
+		let next_sequence_ack = undelivered_acks.iter().min().copied().unwrap_or_default();
+		let mut acks = if sequenced {
+			ordered::consecutive_sequences(undelivered_acks, next_sequence_ack, is_relayed)
+		} else {
+			let mut acks = undelivered_acks
+				.into_iter()
+				.filter(|&sequence| !is_relayed(sequence))
+				.collect::<Vec<_>>();
+			// the acknowledged packets were sent from the sink
+			sink.common_state().incentivized_packets.lock().unwrap().prioritize(
+				&sink_port_id,
+				&sink_channel_id,
+				&mut acks,
+			);
+			acks
+		};
+		acks.truncate(max_packets_to_process);
 
 		let acknowledgements =
 			source.query_received_packets(channel_id, port_id.clone(), acks).await?;
@@ -416,6 +601,7 @@ pub async fn query_ready_and_timed_out_packets(
 		for acknowledgements in acknowledgements.chunks(PROCESS_PACKETS_BATCH_SIZE) {
 			for acknowledgement in acknowledgements.iter().cloned() {
 				let source_connection_end = source_connection_end.clone();
+				let sink_connection_id = sink_connection_id.clone();
 				let source = source.clone();
 				let sink = sink.clone();
 				let duration1 = Duration::from_millis(
@@ -426,6 +612,7 @@ pub async fn query_ready_and_timed_out_packets(
 					let source = &source;
 					let sink = &sink;
 					let packet = packet_info_to_packet(&acknowledgement);
+					let sequence = u64::from(packet.sequence);
 					let ack = if let Some(ack) = acknowledgement.ack {
 						ack
 					} else {
@@ -475,6 +662,7 @@ pub async fn query_ready_and_timed_out_packets(
 						sink_timestamp,
 						sink_height,
 						source_connection_end.delay_period(),
+						&sink_connection_id,
 						proof_height,
 						VerifyDelayOn::Sink,
 					)
@@ -484,17 +672,174 @@ pub async fn query_ready_and_timed_out_packets(
 						return Ok(None)
 					}
 
-					let msg = construct_ack_message(&**source, &**sink, packet, ack, proof_height).await?;
-					Ok(Some(msg))
-				});
+					Ok(Some((sequence, UnprovenPacketMessage::Ack { packet, ack, proof_height })))
+				}.in_current_span());
 			}
 		}
 
+		let mut ack_messages = vec![];
 		while let Some(result) = acknowledgements_join_set.join_next().await {
 			let Some(msg) = result?? else { continue };
-			messages.push(msg)
+			ack_messages.push(msg)
+		}
+		if sequenced {
+			unproven_messages
+				.extend(ordered::consecutive_messages(ack_messages, next_sequence_ack));
+		} else {
+			unproven_messages.extend(ack_messages.into_iter().map(|(_, msg)| msg));
 		}
 	}
 
+	messages.extend(prove_packet_messages(source, sink, unproven_messages).await?);
 	Ok((messages, timeout_messages))
 }
+
+/// Returns the packets relayed by `msgs`, with the direction they were relayed in.
+pub fn relayed_packets(msgs: &[Any]) -> Vec<(PacketDirection, RawPacket)> {
+	msgs.iter()
+		.filter_map(|msg| {
+			let value = msg.value.as_slice();
+			let (packet, direction) = match msg.type_url.as_str() {
+				type_url::MSG_RECV_PACKET =>
+					(MsgRecvPacket::decode(value).ok()?.packet?, PacketDirection::Recv),
+				type_url::MSG_ACKNOWLEDGEMENT =>
+					(MsgAcknowledgement::decode(value).ok()?.packet?, PacketDirection::Ack),
+				type_url::MSG_TIMEOUT =>
+					(MsgTimeout::decode(value).ok()?.packet?, PacketDirection::Timeout),
+				type_url::MSG_TIMEOUT_ON_CLOSE =>
+					(MsgTimeoutOnClose::decode(value).ok()?.packet?, PacketDirection::Timeout),
+				_ => return None,
+			};
+			Some((direction, packet))
+		})
+		.collect()
+}
+
+/// Returns the sequences relayed by `msgs`, keyed by the channel end on the chain whose packets
+/// are queried (see [`RelayedSequence`]).
+pub fn relayed_sequences(msgs: &[Any]) -> Vec<RelayedSequence> {
+	relayed_packets(msgs)
+		.into_iter()
+		.filter_map(|(direction, packet)| {
+			let (channel_id, port_id) = match direction {
+				PacketDirection::Ack => (packet.destination_channel, packet.destination_port),
+				_ => (packet.source_channel, packet.source_port),
+			};
+			Some(RelayedSequence {
+				channel_id: channel_id.parse().ok()?,
+				port_id: port_id.parse().ok()?,
+				sequence: packet.sequence,
+				direction,
+			})
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::queue::flush_message_batch;
+	use hyperspace_mock::{open_transfer_channel, MockChain, MockConfig, MockFinalityEvent};
+	use ibc::{
+		core::ics24_host::identifier::{ChannelId, PortId},
+		timestamp::Timestamp,
+	};
+	use primitives::{FinalitySource, TxSubmitter};
+
+	async fn connected_chains() -> (MockChain, MockChain) {
+		let mut chain_a = MockChain::new(MockConfig::new("chain-a"));
+		let mut chain_b = MockChain::new(MockConfig::new("chain-b"));
+		open_transfer_channel(&mut chain_a, &mut chain_b, Order::Unordered)
+			.await
+			.unwrap();
+		(chain_a, chain_b)
+	}
+
+	/// Sends a packet on `channel-0` of `chain` timing out at `timeout_height`, and returns the
+	/// height of its block.
+	fn send_packet(chain: &MockChain, timeout_height: Height) -> Height {
+		chain
+			.send_packet(
+				PortId::transfer(),
+				ChannelId::new(0),
+				b"data".to_vec(),
+				timeout_height,
+				Timestamp::none(),
+			)
+			.unwrap();
+		chain.produce_block()
+	}
+
+	/// Updates the client of `chain` on `counterparty` to `height`.
+	async fn update_client(chain: &mut MockChain, counterparty: &MockChain, height: Height) {
+		let event = MockFinalityEvent { height: height.revision_height };
+		let (update, ..) =
+			chain.query_latest_ibc_events(event, counterparty).await.unwrap().pop().unwrap();
+		counterparty.submit(vec![update]).await.unwrap();
+		counterparty.produce_block();
+	}
+
+	fn type_urls(msgs: &[Any]) -> Vec<&str> {
+		msgs.iter().map(|msg| msg.type_url.as_str()).collect()
+	}
+
+	#[tokio::test]
+	async fn packets_are_ready_once_the_sink_client_is_updated() {
+		let (mut chain_a, chain_b) = connected_chains().await;
+		let height = send_packet(&chain_a, Height::new(1, 100));
+
+		// the client of the source on the sink can't verify the packet commitment yet
+		let (ready, timeouts) =
+			query_ready_and_timed_out_packets(&chain_a, &chain_b).await.unwrap();
+		assert!(ready.is_empty() && timeouts.is_empty());
+
+		update_client(&mut chain_a, &chain_b, height).await;
+		let (ready, timeouts) =
+			query_ready_and_timed_out_packets(&chain_a, &chain_b).await.unwrap();
+		assert_eq!(type_urls(&ready), [type_url::MSG_RECV_PACKET]);
+		assert!(timeouts.is_empty());
+		let msg = MsgRecvPacket::decode(ready[0].value.as_slice()).unwrap();
+		assert_eq!(msg.packet.unwrap().sequence, 1);
+		assert_eq!(msg.proof_height, Some(height.into()));
+
+		// received packets aren't relayed again
+		flush_message_batch(ready, None, &chain_b).await.unwrap();
+		let (ready, timeouts) =
+			query_ready_and_timed_out_packets(&chain_a, &chain_b).await.unwrap();
+		assert!(ready.is_empty() && timeouts.is_empty());
+	}
+
+	#[tokio::test]
+	async fn timed_out_packets_are_timed_out_on_the_source() {
+		let (chain_a, mut chain_b) = connected_chains().await;
+		let timeout_height = Height::new(1, 4);
+		send_packet(&chain_a, timeout_height);
+		let (ready, timeouts) =
+			query_ready_and_timed_out_packets(&chain_a, &chain_b).await.unwrap();
+		assert!(ready.is_empty() && timeouts.is_empty());
+
+		// the packet timed out on the sink, but the timeout can't be proven on the source until
+		// the client of the sink reaches the timeout height
+		assert_eq!(chain_b.produce_blocks(2), timeout_height);
+		let (ready, timeouts) =
+			query_ready_and_timed_out_packets(&chain_a, &chain_b).await.unwrap();
+		assert!(ready.is_empty() && timeouts.is_empty());
+
+		update_client(&mut chain_b, &chain_a, timeout_height).await;
+		let (ready, timeouts) =
+			query_ready_and_timed_out_packets(&chain_a, &chain_b).await.unwrap();
+		assert!(ready.is_empty());
+		assert_eq!(type_urls(&timeouts), [type_url::MSG_TIMEOUT]);
+		let msg = MsgTimeout::decode(timeouts[0].value.as_slice()).unwrap();
+		assert_eq!(msg.proof_height, Some(timeout_height.into()));
+
+		flush_message_batch(timeouts, None, &chain_a).await.unwrap();
+		let is_committed = chain_a.with_ledger(|ledger| {
+			ledger.has_commitment(&PortId::transfer(), &ChannelId::new(0), 1)
+		});
+		assert!(!is_committed);
+		let (ready, timeouts) =
+			query_ready_and_timed_out_packets(&chain_a, &chain_b).await.unwrap();
+		assert!(ready.is_empty() && timeouts.is_empty());
+	}
+}
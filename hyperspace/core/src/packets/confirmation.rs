@@ -0,0 +1,49 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use primitives::{confirmation::IbcUpdate, Chain};
+
+/// Buffers the `updates` of `source` and returns the buffered ones whose block has at least
+/// `confirmation_depth` finalized blocks on top of it, so that their messages and proofs are
+/// only constructed once the block is confirmed. The updates are returned as they are if the
+/// depth is `0`.
+pub async fn confirmed_updates(
+	source: &impl Chain,
+	updates: Vec<IbcUpdate>,
+) -> anyhow::Result<Vec<IbcUpdate>> {
+	let depth = source.common_state().confirmation_depth;
+	if depth == 0 && source.common_state().confirmations.lock().unwrap().is_empty() {
+		return Ok(updates)
+	}
+	// the updates left in the buffer when the depth is reset are all confirmed
+	let confirmed_height = if depth == 0 {
+		u64::MAX
+	} else {
+		let (_, finalized_height) = source.query_best_and_finalized_heights().await?;
+		finalized_height.saturating_sub(depth)
+	};
+
+	let mut buffer = source.common_state().confirmations.lock().unwrap();
+	buffer.push(updates);
+	let confirmed = buffer.take_confirmed(confirmed_height);
+	if !buffer.is_empty() {
+		log::debug!(
+			target: "hyperspace",
+			"{} updates of {} are waiting for {depth} confirmations",
+			buffer.len(),
+			source.name()
+		);
+	}
+	Ok(confirmed)
+}
@@ -12,33 +12,39 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use ibc::{timestamp::Timestamp, Height};
-use primitives::error::Error;
+use ibc::{
+	core::{ics04_channel::context::calculate_block_delay, ics24_host::identifier::ConnectionId},
+	Height,
+};
+use primitives::{Chain, DelayDeadline};
 use std::time::Duration;
 
-/// Verify the time and height delays
-pub fn has_delay_elapsed(
-	current_time: Timestamp,
-	current_height: Height,
-	client_update_time: Timestamp,
-	client_update_height: Height,
-	delay_period_time: Duration,
-	delay_period_blocks: u64,
-) -> Result<bool, anyhow::Error> {
-	log::trace!(target: "hyperspace",
-		"Checking if delay has elapsed: current_time: {}, current_height: {}, client_update_time: {}, client_update_height: {}, delay_period_time: {:?}, delay_period_blocks: {}",
-		current_time, current_height, client_update_time, client_update_height, delay_period_time, delay_period_blocks
-	);
-	let earliest_time = (client_update_time + delay_period_time)
-		.map_err(|_| Error::Custom("Timestamp overflow".to_string()))?;
-	if !(current_time == earliest_time || current_time.after(&earliest_time)) {
-		return Ok(false)
+/// Returns the time and height of `chain` from which the messages proven at `proof_height` of
+/// `counterparty` are accepted on the connection `connection_id` of `chain`, whose delay period
+/// is `delay_period`. The deadline is cached in the chain's [`primitives::ConnectionDelays`].
+/// Returns `None` if the client of `counterparty` on `chain` wasn't updated to that height.
+pub async fn delay_deadline(
+	chain: &impl Chain,
+	counterparty: &impl Chain,
+	connection_id: &ConnectionId,
+	delay_period: Duration,
+	proof_height: Height,
+) -> Result<Option<DelayDeadline>, anyhow::Error> {
+	let delays = &chain.common_state().connection_delays;
+	if let Some(deadline) = delays.lock().unwrap().get(connection_id, proof_height) {
+		return Ok(Some(deadline))
 	}
-
-	let earliest_height = client_update_height.add(delay_period_blocks);
-	if current_height < earliest_height {
-		return Ok(false)
-	}
-
-	Ok(true)
+	let actual_proof_height = counterparty.get_proof_height(proof_height).await;
+	let Ok((update_height, update_time)) = chain
+		.query_client_update_time_and_height(counterparty.client_id(), actual_proof_height)
+		.await
+	else {
+		log::trace!(target: "hyperspace", "Failed to get client update time and height for {} client on {} for height {}", counterparty.name(), chain.name(), actual_proof_height);
+		return Ok(None)
+	};
+	let block_delay = calculate_block_delay(delay_period, chain.expected_block_time());
+	let deadline = DelayDeadline::new(update_time, update_height, delay_period, block_delay)?;
+	log::trace!(target: "hyperspace", "Messages proven at {proof_height} are accepted on {connection_id} of {} from {} and height {}", chain.name(), deadline.earliest_time, deadline.earliest_height);
+	delays.lock().unwrap().insert(connection_id.clone(), proof_height, deadline);
+	Ok(Some(deadline))
 }
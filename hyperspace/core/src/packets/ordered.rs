@@ -0,0 +1,136 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sequencing of the packets of ordered channels.
+//!
+//! An ordered channel end only accepts the packet with the next expected sequence, and the
+//! acknowledgement of its oldest unacknowledged packet. Packets are thus relayed strictly in
+//! sequence: a packet is stalled until all the earlier ones have been delivered, including the
+//! ones that are still in flight. The timeout of a packet closes the channel, so the packets after
+//! the first one that timed out are never received.
+
+use sp_runtime::Either::{self, Left, Right};
+
+/// Returns the consecutive sequences starting at `next_sequence` among `sequences`. The run stops
+/// at the first missing or relayed sequence, the later sequences are stalled until it's
+/// delivered.
+pub fn consecutive_sequences(
+	mut sequences: Vec<u64>,
+	next_sequence: u64,
+	is_relayed: impl Fn(u64) -> bool,
+) -> Vec<u64> {
+	sequences.sort_unstable();
+	sequences.dedup();
+	sequences
+		.into_iter()
+		.skip_while(|&sequence| sequence < next_sequence)
+		.zip(next_sequence..)
+		.take_while(|&(sequence, expected)| sequence == expected && !is_relayed(sequence))
+		.map(|(sequence, _)| sequence)
+		.collect()
+}
+
+/// Returns the messages of the consecutive sequences starting at `next_sequence`, in order. Only
+/// the first message of a sequence is kept, and the ones below `next_sequence` are dropped.
+pub fn consecutive_messages<M>(mut msgs: Vec<(u64, M)>, next_sequence: u64) -> Vec<M> {
+	msgs.sort_by_key(|(sequence, _)| *sequence);
+	msgs.dedup_by_key(|(sequence, _)| *sequence);
+	msgs.into_iter()
+		.skip_while(|(sequence, _)| *sequence < next_sequence)
+		.zip(next_sequence..)
+		.take_while(|((sequence, _), expected)| sequence == expected)
+		.map(|((_, msg), _)| msg)
+		.collect()
+}
+
+/// Splits the timeout (`Left`) and receive (`Right`) messages of the consecutive sequences
+/// starting at `next_sequence_recv`. Only the first timeout is returned, since it closes the
+/// channel, and the packets after it are left out.
+pub fn sequence_packet_messages<T, R>(
+	mut msgs: Vec<(u64, Either<T, R>)>,
+	next_sequence_recv: u64,
+) -> (Vec<R>, Option<T>) {
+	msgs.sort_by_key(|(sequence, _)| *sequence);
+	msgs.dedup_by_key(|(sequence, _)| *sequence);
+	let msgs = msgs.into_iter().skip_while(|(sequence, _)| *sequence < next_sequence_recv);
+	let mut recvs = vec![];
+	for ((sequence, msg), expected) in msgs.zip(next_sequence_recv..) {
+		if sequence != expected {
+			break
+		}
+		match msg {
+			Left(timeout) => return (recvs, Some(timeout)),
+			Right(recv) => recvs.push(recv),
+		}
+	}
+	(recvs, None)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sequences_stop_at_the_first_gap() {
+		assert_eq!(consecutive_sequences(vec![1, 2, 4, 5], 1, |_| false), vec![1, 2]);
+		// nothing is relayed until the next expected sequence is seen
+		assert_eq!(consecutive_sequences(vec![2, 3], 1, |_| false), Vec::<u64>::new());
+	}
+
+	#[test]
+	fn sequences_stop_at_the_first_in_flight_sequence() {
+		assert_eq!(consecutive_sequences(vec![1, 2, 3], 1, |sequence| sequence == 2), vec![1]);
+		assert_eq!(consecutive_sequences(vec![1, 2, 3], 1, |sequence| sequence == 1), Vec::<u64>::new());
+	}
+
+	#[test]
+	fn sequences_are_sorted_deduplicated_and_start_at_the_next_sequence() {
+		assert_eq!(consecutive_sequences(vec![3, 1, 2, 2, 3], 1, |_| false), vec![1, 2, 3]);
+		assert_eq!(consecutive_sequences(vec![1, 2, 3, 4, 6], 3, |_| false), vec![3, 4]);
+	}
+
+	#[test]
+	fn messages_are_sequenced() {
+		let msgs = vec![(4, "d"), (2, "b"), (3, "c"), (3, "c'"), (1, "a"), (6, "f")];
+		assert_eq!(consecutive_messages(msgs.clone(), 2), vec!["b", "c", "d"]);
+		assert_eq!(consecutive_messages(msgs.clone(), 1), vec!["a", "b", "c", "d"]);
+		assert_eq!(consecutive_messages(msgs, 5), Vec::<&str>::new());
+	}
+
+	#[test]
+	fn receives_are_relayed_up_to_the_first_timeout() {
+		let msgs = vec![
+			(3, Left("timeout 3")),
+			(1, Right("recv 1")),
+			(2, Right("recv 2")),
+			(4, Right("recv 4")),
+			(5, Left("timeout 5")),
+		];
+		assert_eq!(
+			sequence_packet_messages(msgs.clone(), 1),
+			(vec!["recv 1", "recv 2"], Some("timeout 3"))
+		);
+		// the delivered sequences are skipped
+		assert_eq!(sequence_packet_messages(msgs.clone(), 3), (vec![], Some("timeout 3")));
+		assert_eq!(sequence_packet_messages(msgs, 4), (vec!["recv 4"], Some("timeout 5")));
+	}
+
+	#[test]
+	fn timeouts_after_a_gap_are_not_relayed() {
+		let msgs = vec![(1, Right("recv 1")), (2, Right("recv 2")), (4, Left("timeout 4"))];
+		assert_eq!(sequence_packet_messages(msgs, 1), (vec!["recv 1", "recv 2"], None));
+		let msgs = vec![(1, Right("recv 1")), (1, Left("timeout 1")), (2, Right("recv 2"))];
+		assert_eq!(sequence_packet_messages(msgs, 1), (vec!["recv 1", "recv 2"], None));
+	}
+}
@@ -0,0 +1,53 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decoding of the data of the relayed packets.
+//!
+//! The packets of the fungible (ICS-20) and non-fungible (ICS-721) token transfer applications are
+//! decoded to filter out the tokens of the skip list. The packets of the other applications are
+//! relayed as they are.
+
+use ibc::applications::transfer::packet::PacketData;
+use pallet_ibc::ics721::NonFungibleTokenPacketData;
+
+/// Data of a packet, by application.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppPacketData {
+	Transfer(PacketData),
+	NftTransfer(NonFungibleTokenPacketData),
+	Other,
+}
+
+impl AppPacketData {
+	pub fn decode(data: &[u8]) -> Self {
+		if let Ok(data) = serde_json::from_slice::<PacketData>(data) {
+			return Self::Transfer(data)
+		}
+		if let Ok(data) = serde_json::from_slice::<NonFungibleTokenPacketData>(data) {
+			return Self::NftTransfer(data)
+		}
+		Self::Other
+	}
+
+	/// Whether the packet transfers a token of the skip list, matched against the base denom of
+	/// the fungible tokens and the base class id of the non-fungible ones.
+	pub fn is_skipped(&self, skip_list: &[String]) -> bool {
+		let base = match self {
+			Self::Transfer(data) => data.token.denom.base_denom.as_str(),
+			Self::NftTransfer(data) => data.class_id.rsplit('/').next().unwrap_or_default(),
+			Self::Other => return false,
+		};
+		skip_list.iter().any(|skipped| skipped == base)
+	}
+}
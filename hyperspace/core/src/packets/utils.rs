@@ -12,7 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::packets::connection_delay::has_delay_elapsed;
+use crate::{packets::connection_delay::delay_deadline, telemetry::packet_id};
+use anyhow::anyhow;
 use ibc::{
 	core::{
 		ics02_client::client_state::ClientState as ClientStateT,
@@ -20,14 +21,16 @@ use ibc::{
 			channel::{ChannelEnd, Order, State},
 			context::calculate_block_delay,
 			msgs::{
-				acknowledgement::MsgAcknowledgement, recv_packet::MsgRecvPacket,
-				timeout::MsgTimeout, timeout_on_close::MsgTimeoutOnClose,
+				acknowledgement::MsgAcknowledgement, chan_close_confirm::MsgChannelCloseConfirm,
+				recv_packet::MsgRecvPacket, timeout::MsgTimeout,
+				timeout_on_close::MsgTimeoutOnClose,
 			},
 			packet::{Packet, TimeoutVariant},
 		},
 		ics23_commitment::commitment::CommitmentProofBytes,
-		ics24_host::path::{
-			AcksPath, ChannelEndsPath, CommitmentsPath, ReceiptsPath, SeqRecvsPath,
+		ics24_host::{
+			identifier::{ChannelId, ConnectionId, PortId},
+			path::{AcksPath, ChannelEndsPath, CommitmentsPath, ReceiptsPath, SeqRecvsPath},
 		},
 	},
 	proofs::Proofs,
@@ -38,7 +41,7 @@ use ibc::{
 use ibc_proto::google::protobuf::Any;
 use pallet_ibc::light_clients::AnyClientState;
 use primitives::{find_suitable_proof_height_for_client, Chain};
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
 use tendermint_proto::Protobuf;
 
 #[allow(clippy::too_many_arguments)]
@@ -151,6 +154,9 @@ pub enum VerifyDelayOn {
 	Sink,
 }
 
+/// Returns whether the delay period of the connection `connection_id`, on the chain the messages
+/// proven at `proof_height` are submitted to, has elapsed. The messages are withheld until then,
+/// since they'd be rejected.
 #[allow(clippy::too_many_arguments)]
 pub async fn verify_delay_passed(
 	source: &impl Chain,
@@ -160,74 +166,28 @@ pub async fn verify_delay_passed(
 	sink_timestamp: Timestamp,
 	sink_height: Height,
 	connection_delay: Duration,
+	connection_id: &ConnectionId,
 	proof_height: Height,
 	verify_delay_on: VerifyDelayOn,
 ) -> Result<bool, anyhow::Error> {
 	log::trace!(target: "hyperspace", "Verifying delay passed for source: {source_height}, {source_timestamp}, sink: {sink_height}, {sink_timestamp}, connection delay: {}, proof height: {proof_height}, verify delay on: {verify_delay_on:?}", connection_delay.as_secs());
-	match verify_delay_on {
-		VerifyDelayOn::Source => {
-			let actual_proof_height = sink.get_proof_height(proof_height).await;
-			if let Ok((source_client_update_height, source_client_update_time)) = source
-				.query_client_update_time_and_height(sink.client_id(), actual_proof_height)
-				.await
-			{
-				let block_delay =
-					calculate_block_delay(connection_delay, source.expected_block_time());
-				has_delay_elapsed(
-					source_timestamp,
-					source_height,
-					source_client_update_time,
-					source_client_update_height, // shouldn't be the latest.
-					connection_delay,
-					block_delay,
-				)
-			} else {
-				log::trace!(target: "hyperspace", "Failed to get client update time and height for source client for height {}", actual_proof_height);
-				Ok(false)
-			}
-		},
-		VerifyDelayOn::Sink => {
-			let actual_proof_height = source.get_proof_height(proof_height).await;
-			log::info!(
-				"Checking proof height on {} as {}:{}",
-				sink.name(),
-				proof_height,
-				actual_proof_height
-			);
-			let _cs = sink
-				.query_client_consensus(sink_height, source.client_id(), actual_proof_height)
-				.await
-				.unwrap()
-				.consensus_state
-				.unwrap_or_else(|| {
-					panic!(
-						"query_client_consensus for {} at height {} is not found",
-						source.client_id(),
-						actual_proof_height
-					)
-				});
-			if let Ok((sink_client_update_height, sink_client_update_time)) = sink
-				.query_client_update_time_and_height(source.client_id(), actual_proof_height)
-				.await
-			{
-				let block_delay =
-					calculate_block_delay(connection_delay, sink.expected_block_time());
-				has_delay_elapsed(
-					sink_timestamp,
-					sink_height,
-					sink_client_update_time,
-					sink_client_update_height,
-					connection_delay,
-					block_delay,
-				)
-			} else {
-				log::trace!(target: "hyperspace", "Failed to get client update time and height for sink client for height {}", actual_proof_height);
-				Ok(false)
-			}
-		},
+	if connection_delay.is_zero() {
+		return Ok(true)
 	}
+	let elapsed = match verify_delay_on {
+		VerifyDelayOn::Source =>
+			delay_deadline(source, sink, connection_id, connection_delay, proof_height)
+				.await?
+				.map(|deadline| deadline.has_elapsed(source_timestamp, source_height)),
+		VerifyDelayOn::Sink =>
+			delay_deadline(sink, source, connection_id, connection_delay, proof_height)
+				.await?
+				.map(|deadline| deadline.has_elapsed(sink_timestamp, sink_height)),
+	};
+	Ok(elapsed.unwrap_or_default())
 }
 
+#[tracing::instrument(skip_all, err, fields(chain = source.name(), packet_id = %packet_id(&packet), channel = %packet.source_channel, sequence = packet.sequence, %proof_height))]
 pub async fn construct_timeout_message(
 	source: &impl Chain,
 	sink: &impl Chain,
@@ -242,6 +202,19 @@ pub async fn construct_timeout_message(
 		KeyPathType::ReceiptPath
 	};
 	let key = get_key_path(path_type, &packet).into_bytes();
+	// the next sequence to receive on an ordered channel is proven, so it must be the one at the
+	// proof height
+	let next_sequence_recv = if sink_channel_end.ordering == Order::Ordered {
+		sink.query_next_sequence_recv(
+			proof_height,
+			&packet.destination_port,
+			&packet.destination_channel,
+		)
+		.await?
+		.next_sequence_receive
+	} else {
+		next_sequence_recv
+	};
 
 	let proof_unreceived = sink.query_proof(proof_height, vec![key]).await?;
 	let proof_unreceived = CommitmentProofBytes::try_from(proof_unreceived)?;
@@ -279,47 +252,117 @@ pub async fn construct_timeout_message(
 	Ok(msg)
 }
 
-pub async fn construct_recv_message(
+/// Returns the `MsgChannelCloseConfirm` closing the counterparty of the channel end
+/// `port_id/channel_id` of `source`, proven at `proof_height`, or `None` if the channel end
+/// wasn't closed yet at that height.
+#[tracing::instrument(skip_all, err, fields(chain = source.name(), channel = %channel_id, %port_id, %proof_height))]
+pub async fn construct_close_confirm_message(
 	source: &impl Chain,
 	sink: &impl Chain,
-	packet: Packet,
+	channel_id: ChannelId,
+	port_id: PortId,
 	proof_height: Height,
-) -> Result<Any, anyhow::Error> {
-	let key = get_key_path(KeyPathType::CommitmentPath, &packet).into_bytes();
-	let proof = source.query_proof(proof_height, vec![key]).await?;
-	let commitment_proof = CommitmentProofBytes::try_from(proof)?;
+) -> Result<Option<Any>, anyhow::Error> {
+	let channel_response = source.query_channel_end(proof_height, channel_id, port_id).await?;
+	let channel_end = ChannelEnd::try_from(
+		channel_response
+			.channel
+			.ok_or_else(|| anyhow!("ChannelEnd not found for {channel_id} at {proof_height}"))?,
+	)?;
+	if channel_end.state != State::Closed {
+		return Ok(None)
+	}
+	let counterparty = channel_end.counterparty();
+	let channel_proof = CommitmentProofBytes::try_from(channel_response.proof)?;
 	let actual_proof_height = source.get_proof_height(proof_height).await;
-	let msg = MsgRecvPacket {
-		packet,
-		proofs: Proofs::new(commitment_proof, None, None, None, actual_proof_height)?,
+	let msg = MsgChannelCloseConfirm {
+		port_id: counterparty.port_id.clone(),
+		channel_id: counterparty
+			.channel_id
+			.ok_or_else(|| anyhow!("Counterparty of channel {channel_id} has no id"))?,
+		proofs: Proofs::new(channel_proof, None, None, None, actual_proof_height)?,
 		signer: sink.account_id(),
 	};
 	let value = msg.encode_vec()?;
-	let msg = Any { value, type_url: msg.type_url() };
-	Ok(msg)
+	Ok(Some(Any { value, type_url: msg.type_url() }))
+}
+
+/// A receive or acknowledgement message of a packet whose proof is yet to be queried, so that the
+/// proofs of all the packets relayed together are fetched at once by [`prove_packet_messages`].
+#[derive(Debug, Clone)]
+pub enum UnprovenPacketMessage {
+	Recv { packet: Packet, proof_height: Height },
+	Ack { packet: Packet, ack: Vec<u8>, proof_height: Height },
 }
 
-pub async fn construct_ack_message(
+impl UnprovenPacketMessage {
+	fn proof_height(&self) -> Height {
+		match self {
+			Self::Recv { proof_height, .. } | Self::Ack { proof_height, .. } => *proof_height,
+		}
+	}
+
+	fn key_path(&self) -> String {
+		match self {
+			Self::Recv { packet, .. } => get_key_path(KeyPathType::CommitmentPath, packet),
+			Self::Ack { packet, .. } => get_key_path(KeyPathType::AcksPath, packet),
+		}
+	}
+}
+
+/// Builds the `MsgRecvPacket`s and `MsgAcknowledgement`s of `msgs`, in order, with proof queries
+/// to `source` per proof height instead of per packet. Every message carries the proof of its own
+/// packet only: a batch proof covering several packets would be embedded whole in each of their
+/// messages, making them larger than with per-packet proofs.
+#[tracing::instrument(skip_all, err, fields(chain = source.name(), msgs = msgs.len()))]
+pub async fn prove_packet_messages(
 	source: &impl Chain,
 	sink: &impl Chain,
-	packet: Packet,
-	ack: Vec<u8>,
-	proof_height: Height,
-) -> Result<Any, anyhow::Error> {
-	let key = get_key_path(KeyPathType::AcksPath, &packet);
-	log::debug!(target: "hyperspace", "query proof for acks path: {:?}", key);
-	let proof = source.query_proof(proof_height, vec![key.into_bytes()]).await?;
-	let commitment_proof = CommitmentProofBytes::try_from(proof)?;
-	let actual_proof_height = source.get_proof_height(proof_height).await;
-	let msg = MsgAcknowledgement {
-		packet,
-		proofs: Proofs::new(commitment_proof, None, None, None, actual_proof_height)?,
-		acknowledgement: ack.into(),
-		signer: sink.account_id(),
-	};
-	let value = msg.encode_vec()?;
-	let msg = Any { value, type_url: msg.type_url() };
-	Ok(msg)
+	msgs: Vec<UnprovenPacketMessage>,
+) -> Result<Vec<Any>, anyhow::Error> {
+	let mut by_proof_height = BTreeMap::<Height, Vec<usize>>::new();
+	for (index, msg) in msgs.iter().enumerate() {
+		by_proof_height.entry(msg.proof_height()).or_default().push(index);
+	}
+
+	let mut proven = vec![None; msgs.len()];
+	for (proof_height, indices) in by_proof_height {
+		let keys = indices
+			.iter()
+			.map(|&index| vec![msgs[index].key_path().into_bytes()])
+			.collect::<Vec<_>>();
+		log::debug!(target: "hyperspace", "query {} proofs at {proof_height}", keys.len());
+		let proofs = source.query_proofs(proof_height, keys).await?;
+		if proofs.len() != indices.len() {
+			return Err(anyhow!(
+				"Expected {} proofs at {proof_height}, got {}",
+				indices.len(),
+				proofs.len()
+			))
+		}
+		let actual_proof_height = source.get_proof_height(proof_height).await;
+		for (index, proof) in indices.into_iter().zip(proofs) {
+			let commitment_proof = CommitmentProofBytes::try_from(proof)?;
+			let proofs = Proofs::new(commitment_proof, None, None, None, actual_proof_height)?;
+			let msg = match msgs[index].clone() {
+				UnprovenPacketMessage::Recv { packet, .. } => {
+					let msg = MsgRecvPacket { packet, proofs, signer: sink.account_id() };
+					Any { value: msg.encode_vec()?, type_url: msg.type_url() }
+				},
+				UnprovenPacketMessage::Ack { packet, ack, .. } => {
+					let msg = MsgAcknowledgement {
+						packet,
+						proofs,
+						acknowledgement: ack.into(),
+						signer: sink.account_id(),
+					};
+					Any { value: msg.encode_vec()?, type_url: msg.type_url() }
+				},
+			};
+			proven[index] = Some(msg);
+		}
+	}
+	Ok(proven.into_iter().flatten().collect())
 }
 
 pub enum KeyPathType {
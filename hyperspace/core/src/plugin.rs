@@ -0,0 +1,145 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Registration of chain providers that live outside of this crate.
+//!
+//! The `type` field of a chain config selects a variant of the `AnyConfig` enum generated by
+//! [`chains!`](crate::chains). A crate that wants hyperspace to relay for its own [`Chain`]
+//! implementation invokes the macro with the built-in chains plus its own ones, and runs the
+//! regular CLI with the generated config type:
+//!
+//! ```ignore
+//! use hyperspace_core::plugin::prelude::*;
+//!
+//! hyperspace_core::chains! {
+//! 	Parachain(ParachainClientConfig, ParachainClient<DefaultConfig>),
+//! 	MyChain(MyChainConfig, MyChainClient),
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//! 	Cli::parse().run::<AnyConfig>().await
+//! }
+//! ```
+//!
+//! With the above, configs with `type = "my_chain"` are built with `MyChainClient::new`.
+//! A registered chain needs:
+//! - a config with `client_id`, `connection_id`, `channel_whitelist` and `wasm_code_id` fields, and
+//!   a `fn validate(&self) -> Result<(), E>` inherent method checking it without connecting to the
+//!   chain,
+//! - a client implementing [`Chain`], i.e. [`primitives::IbcQuerier`], [`primitives::TxSubmitter`],
+//!   [`primitives::FinalitySource`] and the other supertraits (and [`primitives::TestProvider`]
+//!   with the `testing` feature), with `async fn new(config) -> Result<Self, E>` and `fn
+//!   reload_config(&mut self, config) -> Result<(), E>` inherent methods.
+//!
+//! The invoking crate must also depend on `serde` and `thiserror`, whose derive macros are used
+//! by the generated code.
+
+use async_trait::async_trait;
+use ibc::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+use primitives::Chain;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A chain config that the hyperspace CLI can build clients from. Implemented by the `AnyConfig`
+/// generated by [`chains!`](crate::chains).
+#[async_trait]
+pub trait ChainConfig: Serialize + DeserializeOwned + Clone + Send + Sync + 'static {
+	/// The client built from this config.
+	#[cfg(not(feature = "testing"))]
+	type Chain: Chain + Clone + 'static;
+	/// The client built from this config.
+	#[cfg(feature = "testing")]
+	type Chain: Chain + primitives::TestProvider + Clone + 'static;
+
+	/// Builds the chain client.
+	async fn into_client(self) -> anyhow::Result<Self::Chain>;
+
+	/// Applies a reloaded config to an already running client.
+	fn reload(self, chain: &mut Self::Chain) -> anyhow::Result<()>;
+
+	/// Checks the config before any client is built, e.g. that its relayer keys exist.
+	fn validate(&self) -> anyhow::Result<()>;
+
+	/// Client of this chain on its counterparty, unless it wasn't created yet.
+	fn client_id(&self) -> Option<ClientId>;
+
+	fn set_client_id(&mut self, client_id: ClientId);
+
+	fn set_connection_id(&mut self, connection_id: ConnectionId);
+
+	fn set_channel_whitelist(&mut self, channel_id: ChannelId, port_id: PortId);
+
+	fn set_wasm_code_id(&mut self, code_id: String);
+}
+
+/// Everything [`chains!`](crate::chains) expects to be in scope where it's invoked.
+pub mod prelude {
+	pub use crate::{
+		chain::{wrap_any_msg_into_wasm, CoreConfig},
+		command::Cli,
+		plugin::ChainConfig,
+		substrate::{
+			default::DefaultConfig, ComposableConfig, PicassoKusamaConfig, PicassoRococoConfig,
+		},
+	};
+	pub use anyhow;
+	pub use async_trait::async_trait;
+	pub use clap::Parser;
+	#[cfg(feature = "cosmos")]
+	pub use cosmos::client::{CosmosClient, CosmosClientConfig};
+	pub use futures::{Stream, StreamExt};
+	pub use hex;
+	pub use ibc::{
+		applications::transfer::{msgs::transfer::MsgTransfer, PrefixedCoin},
+		core::{
+			ics02_client::{
+				client_state::ClientType,
+				events::{CodeId, UpdateClient},
+			},
+			ics23_commitment::commitment::CommitmentPrefix,
+			ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		},
+		downcast,
+		events::IbcEvent,
+		signer::Signer,
+		timestamp::Timestamp,
+		Height,
+	};
+	pub use ibc_proto::{
+		google::protobuf::Any,
+		ibc::core::{
+			channel::v1::{
+				QueryChannelResponse, QueryChannelsResponse, QueryNextSequenceReceiveResponse,
+				QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
+				QueryPacketReceiptResponse,
+			},
+			client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
+			connection::v1::{IdentifiedConnection, QueryConnectionResponse},
+		},
+	};
+	pub use ibc_rpc;
+	pub use ics08_wasm::Bytes;
+	pub use pallet_ibc::{
+		light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState},
+		Timeout,
+	};
+	pub use parachain::{ParachainClient, ParachainClientConfig};
+	pub use primitives::{
+		self, Chain, CommonClientState, FinalitySource, IbcQuerier, KeyProvider, LightClientSync,
+		MisbehaviourHandler, TxSubmitter, UpdateType, UpgradedClient,
+	};
+	pub use serde::{Deserialize, Serialize};
+	pub use std::{pin::Pin, time::Duration};
+	pub use thiserror::Error;
+}
@@ -12,27 +12,218 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use ibc_proto::google::protobuf::Any;
+use crate::{ledger::record_delivered_packets, packets::relayed_sequences, telemetry::packet_ids};
+use anyhow::anyhow;
+use ibc::{
+	core::ics02_client::{
+		client_state::ClientState,
+		msgs::update_client::{MsgUpdateAnyClient, TYPE_URL as UPDATE_CLIENT_TYPE_URL},
+	},
+	protobuf::Protobuf,
+	timestamp::Timestamp,
+	Height,
+};
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::core::channel::v1::{
+		MsgAcknowledgement, MsgRecvPacket, MsgTimeout, MsgTimeoutOnClose, Packet as RawPacket,
+	},
+};
+use ibc_proto_registry::type_url;
 use metrics::handler::MetricsHandler;
-use primitives::Chain;
+use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState};
+use primitives::{mock::LocalClientTypes, Chain, Fee, RelayReceipt};
+use prost::Message;
+use std::{collections::HashMap, time::Duration};
+use tracing::Instrument;
+
+/// Submission priority of a message within a batch, the lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Priority {
+	/// Client updates and handshake messages, which the packet messages may depend on.
+	Prerequisite,
+	/// Timeouts, which are only built once they're provable and refund the sender.
+	Timeout,
+	/// Receives, by the time left until the packet times out and then by sequence.
+	Recv(Duration, u64),
+	/// Acknowledgements, which don't expire.
+	Ack,
+}
+
+/// Time left until `packet` times out on the chain at `height` and `timestamp`, estimating the
+/// time of the timeout height with `block_time`. `Duration::MAX` if it never times out.
+fn time_left(
+	packet: &RawPacket,
+	height: Height,
+	timestamp: Timestamp,
+	block_time: Duration,
+) -> Duration {
+	let by_timestamp = (packet.timeout_timestamp != 0).then(|| {
+		Duration::from_nanos(packet.timeout_timestamp.saturating_sub(timestamp.nanoseconds()))
+	});
+	let by_height = packet
+		.timeout_height
+		.as_ref()
+		.filter(|timeout| {
+			timeout.revision_number == height.revision_number && timeout.revision_height != 0
+		})
+		.map(|timeout| {
+			let blocks = timeout.revision_height.saturating_sub(height.revision_height);
+			block_time.saturating_mul(u32::try_from(blocks).unwrap_or(u32::MAX))
+		});
+	by_timestamp.into_iter().chain(by_height).min().unwrap_or(Duration::MAX)
+}
+
+/// Orders `msgs` for their submission to `sink`, so that the packets closest to their timeout
+/// are relayed first when the batch has to be split. Packet messages come after the client
+/// updates and handshake messages, the timeouts first. A receive never overtakes an earlier
+/// packet of its channel, as ordered channels require.
+async fn prioritize(sink: &impl Chain, msgs: Vec<Any>) -> anyhow::Result<Vec<Any>> {
+	let recvs = msgs
+		.iter()
+		.filter_map(|msg| {
+			(msg.type_url == type_url::MSG_RECV_PACKET)
+				.then(|| MsgRecvPacket::decode(msg.value.as_slice()).ok()?.packet)
+				.flatten()
+		})
+		.collect::<Vec<_>>();
+	let mut recv_priorities = HashMap::new();
+	if recvs.len() > 1 {
+		let (height, timestamp) = sink.latest_height_and_timestamp().await?;
+		let block_time = sink.expected_block_time();
+		let mut channels = HashMap::<_, Vec<_>>::new();
+		for packet in &recvs {
+			let key = (packet.destination_port.clone(), packet.destination_channel.clone());
+			let time_left = time_left(packet, height, timestamp, block_time);
+			channels.entry(key).or_default().push((packet.sequence, time_left));
+		}
+		for (key, mut packets) in channels {
+			packets.sort_unstable();
+			let mut latest = Duration::ZERO;
+			for (sequence, time_left) in packets {
+				latest = latest.max(time_left);
+				recv_priorities.insert((key.clone(), sequence), Priority::Recv(latest, sequence));
+			}
+		}
+	}
+
+	let priority = |msg: &Any| match msg.type_url.as_str() {
+		type_url::MSG_TIMEOUT | type_url::MSG_TIMEOUT_ON_CLOSE => Priority::Timeout,
+		type_url::MSG_ACKNOWLEDGEMENT => Priority::Ack,
+		type_url::MSG_RECV_PACKET => MsgRecvPacket::decode(msg.value.as_slice())
+			.ok()
+			.and_then(|msg| msg.packet)
+			.and_then(|packet| {
+				let key = (packet.destination_port, packet.destination_channel);
+				recv_priorities.get(&(key, packet.sequence)).copied()
+			})
+			.unwrap_or(Priority::Recv(Duration::MAX, 0)),
+		_ => Priority::Prerequisite,
+	};
+	let mut msgs = msgs.into_iter().map(|msg| (priority(&msg), msg)).collect::<Vec<_>>();
+	msgs.sort_by_key(|(priority, _)| *priority);
+	Ok(msgs.into_iter().map(|(_, msg)| msg).collect())
+}
+
+/// Submits `msgs` to `sink`, keeping them in its journal of pending messages until the
+/// submission returns.
+async fn submit<C: Chain>(sink: &C, msgs: Vec<Any>) -> Result<C::TransactionId, C::Error> {
+	let pending = &sink.common_state().pending_messages;
+	let id = pending.lock().unwrap().insert(msgs.clone());
+	let result = sink.submit(msgs).await;
+	pending.lock().unwrap().remove(id);
+	result
+}
+
+/// Stores a signed receipt of the successful submission of `msgs` to `sink`, if it issues
+/// receipts. Failures are only logged, the messages were submitted anyway.
+fn issue_receipt<C: Chain>(sink: &C, msgs: &[Any], tx_id: &C::TransactionId, weight: u64) {
+	let Some(store) = sink.common_state().receipts.as_ref() else { return };
+	let result =
+		RelayReceipt::new(sink, sink.name(), relayed_sequences(msgs), format!("{tx_id:?}"), weight)
+			.and_then(|receipt| store.append(&receipt));
+	if let Err(e) = result {
+		log::warn!(target: "hyperspace", "Failed to issue a relay receipt for {}: {e:?}", sink.name());
+	}
+}
+
+/// Records the share of `count` messages in the estimated `fee` of a batch of `total` messages,
+/// once they were successfully submitted.
+fn record_projected_fee(
+	metrics: Option<&MetricsHandler>,
+	fee: Option<&Fee>,
+	count: usize,
+	total: usize,
+) {
+	if let (Some(metrics), Some(fee)) = (metrics, fee) {
+		metrics.handle_projected_fee(&fee.denom, fee.amount as f64 * count as f64 / total as f64);
+	}
+}
 
 /// This sends messages to the sink chain in a gas-aware manner.
+#[tracing::instrument(skip_all, err, fields(sink = sink.name(), msgs = msgs.len(), packet_ids = ?packet_ids(&msgs)))]
 pub async fn flush_message_batch(
 	msgs: Vec<Any>,
 	metrics: Option<&MetricsHandler>,
 	sink: &impl Chain,
 ) -> Result<(), anyhow::Error> {
+	sink.common_state().set_queue_depth(msgs.len());
+	let msgs = prioritize(sink, msgs).await?;
+	let dry_run = sink.common_state().dry_run;
+	if !dry_run && sink.common_state().has_low_balance() {
+		return Err(anyhow::anyhow!(
+			"Not submitting {} messages to {}: the relayer account balance is below the configured minimum",
+			msgs.len(),
+			sink.name()
+		))
+	}
 	let block_max_weight = sink.block_max_weight();
 	let batch_weight = sink.estimate_weight(msgs.clone()).await?;
+	// the fee estimate is only reported, the messages are submitted without it
+	let batch_fee = match sink.estimate_fee(&msgs).await {
+		Ok(fee) => Some(fee),
+		Err(e) => {
+			log::warn!(target: "hyperspace", "Failed to estimate the fee of {} messages to {}: {e:?}", msgs.len(), sink.name());
+			None
+		},
+	};
 
 	if let Some(metrics) = metrics {
 		metrics.handle_transaction_costs(batch_weight, &msgs).await;
+		if let Some(fee) = batch_fee.as_ref() {
+			metrics.handle_fee_estimate(&fee.denom, fee.amount as f64);
+		}
 	}
 
 	log::debug!(target: "hyperspace", "Outgoing messages weight: {} block max weight: {}", batch_weight, block_max_weight);
+	if dry_run {
+		for msg in &msgs {
+			log::info!(target: "hyperspace", "Dry run: {} for {}: {}", msg.type_url, sink.name(), hex::encode(&msg.value));
+		}
+		let fee = batch_fee
+			.map_or_else(|| "unknown".to_string(), |fee| format!("{}{}", fee.amount, fee.denom));
+		log::info!(
+			target: "hyperspace",
+			"Dry run: not submitting {} messages to {}, estimated weight {batch_weight} (block max weight {block_max_weight}), estimated fee {fee}",
+			msgs.len(),
+			sink.name()
+		);
+		return Ok(())
+	}
 	let ratio = (batch_weight / block_max_weight) as usize;
 	if ratio == 0 {
-		sink.submit(msgs).await?;
+		let count = msgs.len();
+		let result = submit(sink, msgs.clone())
+			.instrument(tracing::info_span!("submit", count))
+			.await;
+		if let Some(metrics) = metrics {
+			metrics.handle_submission(&msgs, batch_weight, result.is_ok());
+		}
+		let tx_id = result?;
+		record_projected_fee(metrics, batch_fee.as_ref(), count, count);
+		issue_receipt(sink, &msgs, &tx_id, batch_weight);
+		record_delivered_packets(sink, &msgs, &tx_id);
+		sink.common_state().on_successful_submission(count);
 		return Ok(())
 	}
 
@@ -53,8 +244,434 @@ pub async fn flush_message_batch(
 	// TODO: return number of failed messages and record it to metrics
 	for batch in msgs.chunks(chunk_size) {
 		// send out batches.
-		sink.submit(batch.to_vec()).await?;
+		let result = submit(sink, batch.to_vec())
+			.instrument(tracing::info_span!("submit", count = batch.len()))
+			.await;
+		// the weight of a chunk is approximated from the weight of the whole batch
+		let weight = batch_weight * batch.len() as u64 / msgs.len() as u64;
+		if let Some(metrics) = metrics {
+			metrics.handle_submission(batch, weight, result.is_ok());
+		}
+		let tx_id = result?;
+		record_projected_fee(metrics, batch_fee.as_ref(), batch.len(), msgs.len());
+		issue_receipt(sink, batch, &tx_id, weight);
+		record_delivered_packets(sink, batch, &tx_id);
+		sink.common_state().on_successful_submission(batch.len());
 	}
 
 	Ok(())
 }
+
+/// Re-validates the message batches left in the journal of `sink` by an interrupted run and
+/// submits the messages that are still applicable. `source` is the chain the messages were
+/// relayed from. Batches that couldn't be handled are kept in the journal.
+pub async fn resubmit_pending_messages<A: Chain, B: Chain>(
+	source: &A,
+	sink: &B,
+	metrics: Option<&MetricsHandler>,
+) -> anyhow::Result<()> {
+	// keep the journal for a real run
+	if sink.common_state().dry_run {
+		return Ok(())
+	}
+	let mut batches = sink.common_state().pending_messages.lock().unwrap().take().into_iter();
+	while let Some(batch) = batches.next() {
+		let count = batch.len();
+		let result = async {
+			let msgs = applicable_messages(source, sink, batch.clone()).await?;
+			log::info!(target: "hyperspace", "Resubmitting {} of {count} pending messages to {}", msgs.len(), sink.name());
+			if !msgs.is_empty() {
+				flush_message_batch(msgs, metrics, sink).await?;
+			}
+			Ok::<_, anyhow::Error>(())
+		}
+		.await;
+		if let Err(e) = result {
+			let mut pending = sink.common_state().pending_messages.lock().unwrap();
+			pending.insert(batch);
+			batches.for_each(|batch| {
+				pending.insert(batch);
+			});
+			return Err(e)
+		}
+	}
+	Ok(())
+}
+
+/// Returns the messages of a pending `batch` that still have to be submitted to `sink`: the
+/// packet messages that weren't delivered yet and whose proofs can be verified, along with the
+/// client updates they may need.
+async fn applicable_messages<A: Chain, B: Chain>(
+	source: &A,
+	sink: &B,
+	batch: Vec<Any>,
+) -> anyhow::Result<Vec<Any>> {
+	let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+	let client_state = sink
+		.query_client_state(sink_height, source.client_id())
+		.await?
+		.client_state
+		.ok_or_else(|| anyhow!("Client of {} not found on {}", source.name(), sink.name()))?;
+	let client_height = AnyClientState::try_from(client_state)
+		.map_err(|e| anyhow!("Failed to decode client state: {e:?}"))?
+		.latest_height();
+
+	// the updates that still advance the client
+	let mut updates = vec![];
+	let mut update_heights = vec![];
+	let mut packet_msgs = vec![];
+	for msg in batch {
+		if msg.type_url != UPDATE_CLIENT_TYPE_URL {
+			packet_msgs.push(msg);
+			continue
+		}
+		let update = MsgUpdateAnyClient::<LocalClientTypes>::decode_vec(&msg.value)
+			.map_err(|e| anyhow!("Failed to decode pending client update: {e:?}"))?;
+		let height = match update.client_message.unpack_recursive() {
+			AnyClientMessage::Beefy(_) => None,
+			message => message.maybe_header_height(),
+		};
+		match height {
+			Some(height) if height <= client_height => continue,
+			Some(height) => update_heights.push(height),
+			None => {},
+		}
+		updates.push(msg);
+	}
+
+	let mut msgs = vec![];
+	for msg in packet_msgs {
+		let value = msg.value.as_slice();
+		let (packet, proof_height, is_recv) = match msg.type_url.as_str() {
+			type_url::MSG_RECV_PACKET => {
+				let msg = MsgRecvPacket::decode(value)?;
+				(msg.packet, msg.proof_height, true)
+			},
+			type_url::MSG_ACKNOWLEDGEMENT => {
+				let msg = MsgAcknowledgement::decode(value)?;
+				(msg.packet, msg.proof_height, false)
+			},
+			type_url::MSG_TIMEOUT => {
+				let msg = MsgTimeout::decode(value)?;
+				(msg.packet, msg.proof_height, false)
+			},
+			type_url::MSG_TIMEOUT_ON_CLOSE => {
+				let msg = MsgTimeoutOnClose::decode(value)?;
+				(msg.packet, msg.proof_height, false)
+			},
+			_ => {
+				log::warn!(target: "hyperspace", "Dropping pending message {} for {}", msg.type_url, sink.name());
+				continue
+			},
+		};
+		let packet = packet.ok_or_else(|| anyhow!("Pending {} has no packet", msg.type_url))?;
+		let undelivered = if is_recv {
+			// the packet must not have been received on `sink` yet
+			sink.query_unreceived_packets(
+				sink_height,
+				packet.destination_channel.parse()?,
+				packet.destination_port.parse()?,
+				vec![packet.sequence],
+			)
+			.await?
+		} else {
+			// the commitment of the packet sent from `sink` must still exist
+			sink.query_unreceived_acknowledgements(
+				sink_height,
+				packet.source_channel.parse()?,
+				packet.source_port.parse()?,
+				vec![packet.sequence],
+			)
+			.await?
+		};
+		if !undelivered.contains(&packet.sequence) {
+			log::debug!(target: "hyperspace", "Pending {} of packet {} was already delivered to {}", msg.type_url, packet.sequence, sink.name());
+			continue
+		}
+		let proof_height = proof_height
+			.map(|height| Height::new(height.revision_number, height.revision_height))
+			.ok_or_else(|| anyhow!("Pending {} has no proof height", msg.type_url))?;
+		let provable = update_heights.contains(&proof_height) ||
+			sink.query_client_consensus(sink_height, source.client_id(), proof_height)
+				.await
+				.is_ok();
+		if !provable {
+			log::debug!(target: "hyperspace", "Pending {} of packet {} can't be proven at {proof_height} anymore", msg.type_url, packet.sequence);
+			continue
+		}
+		msgs.push(msg);
+	}
+
+	if msgs.is_empty() {
+		return Ok(vec![])
+	}
+	updates.extend(msgs);
+	Ok(updates)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hyperspace_mock::{ledger::MOCK_PROOF, open_transfer_channel, MockChain, MockConfig};
+	use ibc::core::{
+		ics04_channel::{channel::Order, packet::Packet},
+		ics24_host::identifier::{ChannelId, PortId},
+	};
+	use ibc_proto::ibc::core::client::v1::Height as RawHeight;
+	use primitives::IbcQuerier;
+
+	fn recv_packet(packet: RawPacket, proof_height: Height) -> Any {
+		let msg = MsgRecvPacket {
+			packet: Some(packet),
+			proof_commitment: MOCK_PROOF.to_vec(),
+			proof_height: Some(proof_height.into()),
+			signer: "relayer".to_string(),
+		};
+		Any { type_url: type_url::MSG_RECV_PACKET.to_string(), value: msg.encode_to_vec() }
+	}
+
+	fn raw_packet(channel: u64, sequence: u64, timeout_height: u64) -> RawPacket {
+		RawPacket {
+			sequence,
+			source_port: PortId::transfer().to_string(),
+			source_channel: ChannelId::new(channel).to_string(),
+			destination_port: PortId::transfer().to_string(),
+			destination_channel: ChannelId::new(channel).to_string(),
+			data: b"data".to_vec(),
+			timeout_height: Some(RawHeight { revision_number: 1, revision_height: timeout_height }),
+			timeout_timestamp: 0,
+		}
+	}
+
+	fn timeout_packet(packet: RawPacket, proof_height: Height) -> Any {
+		let msg = MsgTimeout {
+			next_sequence_recv: packet.sequence,
+			packet: Some(packet),
+			proof_unreceived: MOCK_PROOF.to_vec(),
+			proof_height: Some(proof_height.into()),
+			signer: "relayer".to_string(),
+		};
+		Any { type_url: type_url::MSG_TIMEOUT.to_string(), value: msg.encode_to_vec() }
+	}
+
+	fn ack_packet(packet: RawPacket, proof_height: Height) -> Any {
+		let msg = MsgAcknowledgement {
+			packet: Some(packet),
+			acknowledgement: br#"{"result":"AQ=="}"#.to_vec(),
+			proof_acked: MOCK_PROOF.to_vec(),
+			proof_height: Some(proof_height.into()),
+			signer: "relayer".to_string(),
+		};
+		Any { type_url: type_url::MSG_ACKNOWLEDGEMENT.to_string(), value: msg.encode_to_vec() }
+	}
+
+	fn sequence(msg: &Any) -> u64 {
+		MsgRecvPacket::decode(msg.value.as_slice()).unwrap().packet.unwrap().sequence
+	}
+
+	/// Channel and sequence of the packets of the receive messages among `msgs`, in order.
+	fn recv_packets(msgs: &[Any]) -> Vec<(String, u64)> {
+		msgs.iter()
+			.filter(|msg| msg.type_url == type_url::MSG_RECV_PACKET)
+			.map(|msg| {
+				let packet = MsgRecvPacket::decode(msg.value.as_slice()).unwrap().packet.unwrap();
+				(packet.destination_channel, packet.sequence)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn time_left_is_the_time_until_the_earliest_timeout() {
+		let height = Height::new(1, 10);
+		let timestamp = Timestamp::from_nanoseconds(1_000_000_000_000).unwrap();
+		let block_time = Duration::from_secs(6);
+		let left = |timeout_height: Option<(u64, u64)>, timeout_in: Option<Duration>| {
+			let packet = RawPacket {
+				timeout_height: timeout_height.map(|(revision_number, revision_height)| {
+					RawHeight { revision_number, revision_height }
+				}),
+				timeout_timestamp: timeout_in
+					.map_or(0, |timeout_in| timestamp.nanoseconds() + timeout_in.as_nanos() as u64),
+				..raw_packet(0, 1, 0)
+			};
+			time_left(&packet, height, timestamp, block_time)
+		};
+
+		// 10 blocks of 6 seconds
+		assert_eq!(left(Some((1, 20)), None), Duration::from_secs(60));
+		assert_eq!(left(None, Some(Duration::from_secs(30))), Duration::from_secs(30));
+		// the earliest of the height and timestamp timeouts
+		assert_eq!(left(Some((1, 20)), Some(Duration::from_secs(90))), Duration::from_secs(60));
+		assert_eq!(left(Some((1, 20)), Some(Duration::from_secs(30))), Duration::from_secs(30));
+		// timeouts that are already reached
+		assert_eq!(left(Some((1, 5)), None), Duration::ZERO);
+		assert_eq!(left(None, Some(Duration::ZERO)), Duration::ZERO);
+	}
+
+	#[test]
+	fn packets_without_timeout_never_time_out() {
+		let height = Height::new(1, 10);
+		let timestamp = Timestamp::from_nanoseconds(1_000_000_000_000).unwrap();
+		let block_time = Duration::from_secs(6);
+		let mut packet = raw_packet(0, 1, 0);
+		assert_eq!(time_left(&packet, height, timestamp, block_time), Duration::MAX);
+		packet.timeout_height = None;
+		assert_eq!(time_left(&packet, height, timestamp, block_time), Duration::MAX);
+		// the timeout heights of other revisions are never reached
+		packet.timeout_height = Some(RawHeight { revision_number: 2, revision_height: 20 });
+		assert_eq!(time_left(&packet, height, timestamp, block_time), Duration::MAX);
+	}
+
+	#[tokio::test]
+	async fn receives_are_prioritized_by_their_earliest_timeout() {
+		let sink = MockChain::new(MockConfig::new("chain-b"));
+		let (height, timestamp) = sink.latest_height_and_timestamp().await.unwrap();
+		let proof_height = Height::new(1, 1);
+		// times out after 10 seconds
+		let by_timestamp = RawPacket {
+			timeout_timestamp: timestamp.nanoseconds() + 10_000_000_000,
+			..raw_packet(0, 1, 0)
+		};
+		// times out after 5 blocks of 6 seconds
+		let by_height = raw_packet(1, 1, height.revision_height + 5);
+		let never = raw_packet(2, 1, 0);
+		let msgs = vec![
+			ack_packet(raw_packet(3, 1, 0), proof_height),
+			recv_packet(never, proof_height),
+			recv_packet(by_height, proof_height),
+			timeout_packet(raw_packet(4, 1, 1), proof_height),
+			recv_packet(by_timestamp, proof_height),
+		];
+
+		let msgs = prioritize(&sink, msgs).await.unwrap();
+		assert_eq!(msgs[0].type_url, type_url::MSG_TIMEOUT);
+		assert_eq!(
+			recv_packets(&msgs[1..4]),
+			[
+				(ChannelId::new(0).to_string(), 1),
+				(ChannelId::new(1).to_string(), 1),
+				(ChannelId::new(2).to_string(), 1),
+			]
+		);
+		assert_eq!(msgs[4].type_url, type_url::MSG_ACKNOWLEDGEMENT);
+	}
+
+	#[tokio::test]
+	async fn receives_never_overtake_earlier_packets_of_their_channel() {
+		let sink = MockChain::new(MockConfig::new("chain-b"));
+		let (height, _) = sink.latest_height_and_timestamp().await.unwrap();
+		let proof_height = Height::new(1, 1);
+		let msgs = vec![
+			// the first packet of `channel-0` never times out, so neither do the later ones
+			recv_packet(raw_packet(0, 3, height.revision_height + 2), proof_height),
+			recv_packet(raw_packet(0, 2, height.revision_height + 1), proof_height),
+			recv_packet(raw_packet(1, 1, height.revision_height + 10), proof_height),
+			recv_packet(raw_packet(0, 1, 0), proof_height),
+			// the second packet of `channel-2` is delayed to the timeout of the first one
+			recv_packet(raw_packet(2, 2, height.revision_height + 1), proof_height),
+			recv_packet(raw_packet(2, 1, height.revision_height + 20), proof_height),
+		];
+
+		let msgs = prioritize(&sink, msgs).await.unwrap();
+		assert_eq!(
+			recv_packets(&msgs),
+			[
+				(ChannelId::new(1).to_string(), 1),
+				(ChannelId::new(2).to_string(), 1),
+				(ChannelId::new(2).to_string(), 2),
+				(ChannelId::new(0).to_string(), 1),
+				(ChannelId::new(0).to_string(), 2),
+				(ChannelId::new(0).to_string(), 3),
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn messages_are_prioritized() {
+		let sink = MockChain::new(MockConfig::new("chain-b"));
+		let message = |type_url: &str| Any { type_url: type_url.to_string(), value: vec![] };
+		let proof_height = Height::new(1, 1);
+		let msgs = vec![
+			message(type_url::MSG_ACKNOWLEDGEMENT),
+			recv_packet(raw_packet(0, 2, 10), proof_height),
+			recv_packet(raw_packet(1, 1, 50), proof_height),
+			recv_packet(raw_packet(0, 1, 100), proof_height),
+			message(type_url::MSG_TIMEOUT),
+			message(type_url::MSG_UPDATE_CLIENT),
+		];
+
+		let msgs = prioritize(&sink, msgs).await.unwrap();
+		let type_urls = msgs.iter().map(|msg| msg.type_url.as_str()).collect::<Vec<_>>();
+		assert_eq!(
+			type_urls,
+			[
+				type_url::MSG_UPDATE_CLIENT,
+				type_url::MSG_TIMEOUT,
+				type_url::MSG_RECV_PACKET,
+				type_url::MSG_RECV_PACKET,
+				type_url::MSG_RECV_PACKET,
+				type_url::MSG_ACKNOWLEDGEMENT,
+			]
+		);
+		// the packet of `channel-1` times out first, and the second packet of `channel-0` doesn't
+		// overtake the first one even though it times out sooner
+		let recvs = msgs[2..5]
+			.iter()
+			.map(|msg| {
+				let packet = MsgRecvPacket::decode(msg.value.as_slice()).unwrap().packet.unwrap();
+				(packet.destination_channel, packet.sequence)
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(
+			recvs,
+			[
+				(ChannelId::new(1).to_string(), 1),
+				(ChannelId::new(0).to_string(), 1),
+				(ChannelId::new(0).to_string(), 2),
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn batches_exceeding_the_block_max_weight_are_split() {
+		let mut chain_a = MockChain::new(MockConfig::new("chain-a"));
+		// three messages fit in a block of the sink
+		let mut chain_b =
+			MockChain::new(MockConfig { block_max_weight: 3_000, ..MockConfig::new("chain-b") });
+		open_transfer_channel(&mut chain_a, &mut chain_b, Order::Unordered)
+			.await
+			.unwrap();
+		let packets = (0..4)
+			.map(|_| {
+				chain_a
+					.send_packet(
+						PortId::transfer(),
+						ChannelId::new(0),
+						b"data".to_vec(),
+						Height::new(1, 100),
+						Timestamp::none(),
+					)
+					.unwrap()
+			})
+			.collect::<Vec<Packet>>();
+		let proof_height = chain_a.produce_block();
+		let msgs = packets
+			.into_iter()
+			.map(|packet| recv_packet(packet.into(), proof_height))
+			.collect::<Vec<_>>();
+
+		let submitted = chain_b.submitted().len();
+		flush_message_batch(msgs, None, &chain_b).await.unwrap();
+
+		// a batch of 4 messages weighing 4000 is split into 2 chunks
+		let txs = chain_b.submitted().split_off(submitted);
+		let sequences = txs
+			.iter()
+			.map(|tx| tx.messages.iter().map(sequence).collect::<Vec<_>>())
+			.collect::<Vec<_>>();
+		assert_eq!(sequences, [vec![1, 2], vec![3, 4]]);
+		assert!((1..=4).all(|sequence| chain_b.with_ledger(|ledger| {
+			ledger.has_receipt(&PortId::transfer(), &ChannelId::new(0), sequence)
+		})));
+	}
+}
@@ -0,0 +1,92 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HTTP endpoint serving the relay receipts issued by the relayer.
+//!
+//! `GET /receipts?since={timestamp}&limit={n}` returns, as a JSON array, up to `limit` (default
+//! and maximum [`MAX_RECEIPTS`]) receipts issued at or after the unix timestamp `since` (default
+//! `0`), oldest first. See [`primitives::RelayReceipt`] for how to verify their signatures.
+
+use anyhow::anyhow;
+use hyper::{
+	http::StatusCode,
+	server::Server,
+	service::{make_service_fn, service_fn},
+	Body, Method, Request, Response,
+};
+use primitives::ReceiptStore;
+use std::net::SocketAddr;
+
+/// Maximum number of receipts returned by a single request.
+pub const MAX_RECEIPTS: usize = 1000;
+
+fn query_param<T: std::str::FromStr>(req: &Request<Body>, name: &str) -> Result<Option<T>, String> {
+	let Some(query) = req.uri().query() else { return Ok(None) };
+	query
+		.split('&')
+		.filter_map(|pair| pair.split_once('='))
+		.find(|(key, _)| *key == name)
+		.map(|(_, value)| value.parse().map_err(|_| format!("Invalid {name}: {value}")))
+		.transpose()
+}
+
+async fn request_receipts(
+	req: Request<Body>,
+	store: ReceiptStore,
+) -> Result<Response<Body>, hyper::http::Error> {
+	if req.method() != Method::GET || req.uri().path() != "/receipts" {
+		return Response::builder().status(StatusCode::NOT_FOUND).body(Body::from("Not found."))
+	}
+	let params = query_param::<u64>(&req, "since")
+		.and_then(|since| Ok((since, query_param::<usize>(&req, "limit")?)));
+	let (since, limit) = match params {
+		Ok((since, limit)) =>
+			(since.unwrap_or_default(), limit.unwrap_or(MAX_RECEIPTS).min(MAX_RECEIPTS)),
+		Err(e) => return Response::builder().status(StatusCode::BAD_REQUEST).body(Body::from(e)),
+	};
+	let receipts = match store.receipts(since, limit) {
+		Ok(receipts) => receipts,
+		Err(e) => {
+			log::error!(target: "hyperspace", "Failed to read relay receipts: {e:?}");
+			return Response::builder()
+				.status(StatusCode::INTERNAL_SERVER_ERROR)
+				.body(Body::from("Failed to read receipts."))
+		},
+	};
+
+	Response::builder()
+		.status(StatusCode::OK)
+		.header("Content-Type", "application/json")
+		.body(Body::from(serde_json::to_string(&receipts).unwrap_or_default()))
+}
+
+/// Starts an HTTP server that serves the receipts of `store`.
+pub async fn init_receipts_server(addr: SocketAddr, store: ReceiptStore) -> anyhow::Result<()> {
+	let listener = tokio::net::TcpListener::bind(&addr)
+		.await
+		.map_err(|_| anyhow!("Receipts server port {addr} already in use."))?;
+	let listener = hyper::server::conn::AddrIncoming::from_listener(listener)?;
+
+	let service = make_service_fn(move |_| {
+		let store = store.clone();
+
+		async move {
+			Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+				request_receipts(req, store.clone())
+			}))
+		}
+	});
+
+	Server::builder(listener).serve(service).await.map_err(Into::into)
+}
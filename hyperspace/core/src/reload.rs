@@ -0,0 +1,87 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{chain::Config, command::Cmd, plugin::ChainConfig, RelayCommand};
+use metrics::init_prometheus;
+use primitives::Chain;
+use prometheus::Registry;
+use tokio::{
+	signal::unix::{signal, SignalKind},
+	sync::mpsc::UnboundedSender,
+	task::JoinHandle,
+};
+
+/// Handle to the running prometheus server, restarted whenever its endpoint changes.
+#[derive(Default)]
+pub struct PrometheusServer {
+	endpoint: Option<String>,
+	handle: Option<JoinHandle<Result<(), metrics::Error>>>,
+}
+
+impl PrometheusServer {
+	/// Spawns the prometheus server if an endpoint is configured.
+	pub fn start(endpoint: Option<String>, registry: &Registry) -> Self {
+		let handle = endpoint
+			.as_ref()
+			.and_then(|s| s.parse().ok())
+			.map(|addr| tokio::spawn(init_prometheus(addr, registry.clone())));
+		Self { endpoint, handle }
+	}
+
+	fn restart_if_changed(&mut self, endpoint: Option<String>, registry: &Registry) {
+		if self.endpoint == endpoint {
+			return
+		}
+		log::info!("Prometheus endpoint changed from {:?} to {:?}", self.endpoint, endpoint);
+		if let Some(handle) = self.handle.take() {
+			handle.abort();
+		}
+		*self = Self::start(endpoint, registry);
+	}
+}
+
+/// Re-reads the relayer configuration every time the process receives `SIGHUP` and forwards it
+/// to the relay loop over `control`. The chain clients are updated in place, so in-memory packet
+/// state survives the reload.
+pub async fn reload_on_sighup<C: ChainConfig>(
+	cmd: Cmd,
+	registry: Registry,
+	mut prometheus: PrometheusServer,
+	control: UnboundedSender<RelayCommand<C::Chain, C::Chain>>,
+) -> anyhow::Result<()> {
+	let mut hangup = signal(SignalKind::hangup())?;
+	while hangup.recv().await.is_some() {
+		log::info!("Received SIGHUP, reloading configuration");
+		let Config { chain_a, chain_b, core } = match cmd.parse_config::<C>().await {
+			Ok(config) => config,
+			Err(e) => {
+				log::error!("Failed to reload configuration, keeping the current one: {:?}", e);
+				continue
+			},
+		};
+		prometheus.restart_if_changed(core.prometheus_endpoint, &registry);
+
+		let command: RelayCommand<C::Chain, C::Chain> = Box::new(move |a, b| {
+			chain_a.reload(a)?;
+			chain_b.reload(b)?;
+			log::info!("Applied reloaded configuration to {} and {}", a.name(), b.name());
+			Ok(())
+		});
+		if control.send(command).is_err() {
+			// the relay loop has exited
+			break
+		}
+	}
+	Ok(())
+}
@@ -0,0 +1,257 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Load generation against a pair of live chains, used to benchmark a running relayer.
+//!
+//! Packets are sent from the first chain over its whitelisted channels at a fixed rate, and the
+//! latency of each one is measured from the submission of the transaction that sent it to the
+//! observed acknowledgement.
+
+use anyhow::anyhow;
+use futures::StreamExt;
+use ibc::{
+	applications::transfer::{msgs::transfer::MsgTransfer, PrefixedCoin},
+	core::ics24_host::identifier::{ChannelId, PortId},
+	events::IbcEvent,
+	Height,
+};
+use metrics::latency::{LatencyPercentiles, PacketLatencyWindows};
+use pallet_ibc::Timeout;
+use primitives::{IbcQuerier, KeyProvider, TestProvider};
+use std::{
+	collections::{HashMap, VecDeque},
+	fmt,
+	num::NonZeroU32,
+	str::FromStr,
+	time::Duration,
+};
+use tokio::{task::JoinSet, time::Instant};
+
+/// Load generated by [`stress_test`].
+#[derive(Debug, Clone)]
+pub struct StressParams {
+	/// Packets sent per second, over all the channels.
+	pub rate: NonZeroU32,
+	/// How long packets are sent for.
+	pub duration: Duration,
+	/// How long the acknowledgements of the packets are waited for after the last one was sent.
+	pub drain_timeout: Duration,
+	/// Tokens sent by each packet over a transfer channel.
+	pub coin: PrefixedCoin,
+	/// Timeout of the packets, relative to the start of the test.
+	pub packet_timeout: Duration,
+}
+
+/// Outcome of a [`stress_test`].
+#[derive(Debug, Clone, Default)]
+pub struct StressReport {
+	/// Packets whose transaction was submitted successfully.
+	pub sent: usize,
+	/// Transactions that failed to be submitted.
+	pub failed: usize,
+	pub acknowledged: usize,
+	pub timed_out: usize,
+	/// Time from the first submission to the last acknowledgement.
+	pub elapsed: Duration,
+	/// Latency percentiles per channel, from the submission of a packet to its acknowledgement.
+	pub latencies: Vec<LatencyPercentiles>,
+}
+
+impl StressReport {
+	/// Acknowledged packets per second.
+	pub fn throughput(&self) -> f64 {
+		if self.elapsed.is_zero() {
+			return 0.0
+		}
+		self.acknowledged as f64 / self.elapsed.as_secs_f64()
+	}
+
+	/// Sent packets that were neither acknowledged nor timed out.
+	pub fn undelivered(&self) -> usize {
+		self.sent.saturating_sub(self.acknowledged + self.timed_out)
+	}
+}
+
+impl fmt::Display for StressReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "sent:         {}", self.sent)?;
+		writeln!(f, "failed:       {}", self.failed)?;
+		writeln!(f, "acknowledged: {}", self.acknowledged)?;
+		writeln!(f, "timed out:    {}", self.timed_out)?;
+		writeln!(f, "undelivered:  {}", self.undelivered())?;
+		writeln!(f, "elapsed:      {:.1}s", self.elapsed.as_secs_f64())?;
+		writeln!(f, "throughput:   {:.2} packets/s", self.throughput())?;
+		for LatencyPercentiles { channel_id, port_id, count, p50, p95, p99 } in &self.latencies {
+			writeln!(
+				f,
+				"{port_id}/{channel_id}: {count} packets, latency p50 {p50:.0}ms, p95 {p95:.0}ms, \
+				 p99 {p99:.0}ms"
+			)?;
+		}
+		Ok(())
+	}
+}
+
+/// A packet submitted by [`stress_test`] whose "send packet" event wasn't observed yet.
+struct Submission {
+	id: u64,
+	submitted_at: Instant,
+}
+
+/// Sends packets from `chain_a` to `chain_b` over the whitelisted channels of `chain_a`, round
+/// robin, and waits for their acknowledgements. Transfer channels send `params.coin`, ping
+/// channels send ping packets, other channels are skipped. The packets are relayed by a relayer
+/// running separately, and the packets sent over the same channels by other accounts must be
+/// avoided, since they are mistaken for the ones sent by the test.
+pub async fn stress_test<A, B>(
+	chain_a: &A,
+	chain_b: &B,
+	params: StressParams,
+) -> anyhow::Result<StressReport>
+where
+	A: TestProvider,
+	B: TestProvider,
+{
+	let ping_port = PortId::from_str(pallet_ibc_ping::PORT_ID)?;
+	let mut channels = chain_a
+		.channel_whitelist()
+		.into_iter()
+		.filter(|(_, port_id)| *port_id == PortId::transfer() || *port_id == ping_port)
+		.collect::<Vec<_>>();
+	channels.sort();
+	if channels.is_empty() {
+		return Err(anyhow!("{} has no whitelisted transfer or ping channel", chain_a.name()))
+	}
+
+	let (_, timestamp) = chain_b.latest_height_and_timestamp().await?;
+	let timeout_timestamp = (timestamp + params.packet_timeout)?;
+	// a block takes at least a second, so the timestamp timeout is always reached first
+	let ping_timeout = Timeout::Offset {
+		timestamp: Some(params.packet_timeout.as_secs()),
+		height: Some(params.packet_timeout.as_secs()),
+	};
+
+	let latencies = PacketLatencyWindows::new("stress_test", Duration::MAX)?;
+	let mut report = StressReport::default();
+	let mut submissions: HashMap<(ChannelId, PortId), VecDeque<Submission>> = HashMap::new();
+	let mut in_flight: HashMap<(ChannelId, PortId, u64), Instant> = HashMap::new();
+	let mut sends = JoinSet::new();
+	let mut events = chain_a.ibc_events().await;
+
+	let period = Duration::from_secs(1) / params.rate.get();
+	let mut interval = tokio::time::interval(period);
+	let started = Instant::now();
+	let (send_deadline, drain_deadline) =
+		(started + params.duration, started + params.duration + params.drain_timeout);
+	let mut next_id = 0u64;
+	let mut last_ack = started;
+
+	loop {
+		let sending = Instant::now() < send_deadline;
+		let pending = submissions.values().any(|queue| !queue.is_empty());
+		if !sending && sends.is_empty() && !pending && in_flight.is_empty() {
+			break
+		}
+		tokio::select! {
+			_ = interval.tick(), if sending => {
+				let (channel_id, port_id) = channels[next_id as usize % channels.len()].clone();
+				let submission = Submission { id: next_id, submitted_at: Instant::now() };
+				submissions.entry((channel_id, port_id.clone())).or_default().push_back(submission);
+				let chain = chain_a.clone();
+				let id = next_id;
+				if port_id == PortId::transfer() {
+					let msg = MsgTransfer {
+						source_port: port_id.clone(),
+						source_channel: channel_id,
+						token: params.coin.clone(),
+						sender: chain_a.account_id(),
+						receiver: chain_b.account_id(),
+						timeout_height: Height::zero(),
+						timeout_timestamp,
+						memo: String::new(),
+					};
+					sends.spawn(async move {
+						let result = chain.send_transfer(msg).await;
+						(id, channel_id, port_id, result.map_err(|e| e.to_string()))
+					});
+				} else {
+					let timeout = ping_timeout.clone();
+					sends.spawn(async move {
+						let result = chain.send_ordered_packet(channel_id, timeout).await;
+						(id, channel_id, port_id, result.map_err(|e| e.to_string()))
+					});
+				}
+				next_id += 1;
+			},
+			Some(result) = sends.join_next() => {
+				let (id, channel_id, port_id, result) = result?;
+				match result {
+					Ok(()) => report.sent += 1,
+					Err(e) => {
+						log::warn!(target: "hyperspace", "Failed to send packet over {port_id}/{channel_id}: {e}");
+						report.failed += 1;
+						if let Some(queue) = submissions.get_mut(&(channel_id, port_id)) {
+							queue.retain(|submission| submission.id != id);
+						}
+					},
+				}
+			},
+			event = events.next() => match event {
+				Some(Ok(IbcEvent::SendPacket(e))) => {
+					let packet = e.packet;
+					let channel = (packet.source_channel, packet.source_port);
+					if let Some(submission) =
+						submissions.get_mut(&channel).and_then(|queue| queue.pop_front())
+					{
+						let key = (channel.0, channel.1, u64::from(packet.sequence));
+						in_flight.insert(key, submission.submitted_at);
+					}
+				},
+				Some(Ok(IbcEvent::AcknowledgePacket(e))) => {
+					let packet = e.packet;
+					let sequence = u64::from(packet.sequence);
+					let key = (packet.source_channel, packet.source_port, sequence);
+					if let Some(submitted_at) = in_flight.remove(&key) {
+						last_ack = Instant::now();
+						let latency = last_ack.duration_since(submitted_at).as_millis() as f64;
+						latencies.observe(&key.0.to_string(), key.1.as_str(), latency);
+						report.acknowledged += 1;
+					}
+				},
+				Some(Ok(IbcEvent::TimeoutPacket(e))) => {
+					let packet = e.packet;
+					let sequence = u64::from(packet.sequence);
+					let key = (packet.source_channel, packet.source_port, sequence);
+					if in_flight.remove(&key).is_some() {
+						report.timed_out += 1;
+					}
+				},
+				Some(Ok(_)) => {},
+				Some(Err(e)) => {
+					log::warn!(target: "hyperspace", "Event stream of {} failed, resubscribing: {e:?}", chain_a.name());
+					events = chain_a.ibc_events().await;
+				},
+				None => return Err(anyhow!("Event stream of {} ended", chain_a.name())),
+			},
+			_ = tokio::time::sleep_until(drain_deadline) => {
+				log::warn!(target: "hyperspace", "Stopped waiting for {} acknowledgements", in_flight.len());
+				break
+			},
+		}
+	}
+
+	report.elapsed = last_ack.duration_since(started);
+	report.latencies = latencies.percentiles();
+	Ok(report)
+}
@@ -882,5 +882,14 @@ macro_rules! define_asset_id {
 				serializer.serialize_u128(self.0 .0)
 			}
 		}
+
+		impl<'de> serde::Deserialize<'de> for $name {
+			fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+			where
+				D: serde::Deserializer<'de>,
+			{
+				<u128 as serde::Deserialize>::deserialize(deserializer).map(Self::from)
+			}
+		}
 	};
 }
@@ -0,0 +1,89 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracing of the relay pipeline.
+//!
+//! Finality handling, event parsing, proof queries and transaction submission are wrapped in
+//! `tracing` spans. Every packet is identified by a correlation id,
+//! `{source_port}/{source_channel}/ {sequence}`, which is recorded from the detection of its
+//! `SendPacket` event through the submission of its `MsgRecvPacket`, `MsgAcknowledgement` or
+//! `MsgTimeout`, so that all spans of a packet can be found by searching for its id.
+//!
+//! Spans are exported over OTLP when `otlp_endpoint` is set in the core config and the relayer
+//! was built with the `otlp` feature.
+
+use crate::logging::BoxedLayer;
+use ibc::core::ics04_channel::packet::Packet;
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::core::channel::v1::{
+		MsgAcknowledgement, MsgRecvPacket, MsgTimeout, MsgTimeoutOnClose, Packet as RawPacket,
+	},
+};
+use ibc_proto_registry::type_url;
+use prost::Message;
+
+/// Returns the correlation id of `packet`.
+pub fn packet_id(packet: &Packet) -> String {
+	format!("{}/{}/{}", packet.source_port, packet.source_channel, packet.sequence)
+}
+
+fn raw_packet_id(packet: &RawPacket) -> String {
+	format!("{}/{}/{}", packet.source_port, packet.source_channel, packet.sequence)
+}
+
+/// Returns the correlation ids of the packets relayed by `msgs`.
+pub fn packet_ids(msgs: &[Any]) -> Vec<String> {
+	msgs.iter()
+		.filter_map(|msg| {
+			let value = msg.value.as_slice();
+			match msg.type_url.as_str() {
+				type_url::MSG_RECV_PACKET => MsgRecvPacket::decode(value).ok()?.packet,
+				type_url::MSG_ACKNOWLEDGEMENT => MsgAcknowledgement::decode(value).ok()?.packet,
+				type_url::MSG_TIMEOUT => MsgTimeout::decode(value).ok()?.packet,
+				type_url::MSG_TIMEOUT_ON_CLOSE => MsgTimeoutOnClose::decode(value).ok()?.packet,
+				_ => None,
+			}
+		})
+		.map(|packet| raw_packet_id(&packet))
+		.collect()
+}
+
+/// Returns the log layer exporting the spans of the relayer to the OTLP collector at `endpoint`
+/// (e.g. `http://localhost:4317`). Must be called from within a tokio runtime.
+#[cfg(feature = "otlp")]
+pub fn otlp_layer(endpoint: &str) -> anyhow::Result<BoxedLayer> {
+	use opentelemetry::{sdk, KeyValue};
+	use opentelemetry_otlp::WithExportConfig;
+	use tracing_subscriber::Layer;
+
+	let tracer = opentelemetry_otlp::new_pipeline()
+		.tracing()
+		.with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+		.with_trace_config(
+			sdk::trace::config()
+				.with_resource(sdk::Resource::new([KeyValue::new("service.name", "hyperspace")])),
+		)
+		.install_batch(opentelemetry::runtime::Tokio)?;
+	Ok(tracing_opentelemetry::layer().with_tracer(tracer).boxed())
+}
+
+/// Returns the log layer exporting the spans of the relayer to the OTLP collector at `endpoint`
+/// (e.g. `http://localhost:4317`). Must be called from within a tokio runtime.
+#[cfg(not(feature = "otlp"))]
+pub fn otlp_layer(endpoint: &str) -> anyhow::Result<BoxedLayer> {
+	Err(anyhow::anyhow!(
+		"Cannot export traces to {endpoint}: hyperspace was built without the `otlp` feature"
+	))
+}
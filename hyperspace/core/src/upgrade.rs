@@ -0,0 +1,41 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Relays the upgrades of the light clients after an upgrade of their chain.
+//!
+//! [`watch_client_upgrades`] periodically checks whether either chain stores an upgraded client
+//! that is more recent than its client on the counterparty, and submits the `MsgUpgradeClient`
+//! to the counterparty if so. The upgraded client can be proven once the client was updated to
+//! the height at which it's stored, which the relay loop does with the next client update.
+
+use primitives::{upgrade::upgrade_client, Chain};
+use std::time::Duration;
+
+/// Default interval between two checks of the upgraded clients.
+pub const DEFAULT_CLIENT_UPGRADE_CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Checks both chains for upgraded clients every `interval`, upgrading their clients on the
+/// counterparty.
+pub async fn watch_client_upgrades<A: Chain, B: Chain>(chain_a: A, chain_b: B, interval: Duration) {
+	let mut interval = tokio::time::interval(interval);
+	loop {
+		interval.tick().await;
+		if let Err(e) = upgrade_client(&chain_a, &chain_b).await {
+			log::warn!(target: "hyperspace", "Failed to upgrade the client of {} on {}: {:?}", chain_a.name(), chain_b.name(), e);
+		}
+		if let Err(e) = upgrade_client(&chain_b, &chain_a).await {
+			log::warn!(target: "hyperspace", "Failed to upgrade the client of {} on {}: {:?}", chain_b.name(), chain_a.name(), e);
+		}
+	}
+}
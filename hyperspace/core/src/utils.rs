@@ -1,22 +1,33 @@
 use futures::{Stream, StreamExt};
 use std::{
+	collections::VecDeque,
 	pin::Pin,
 	sync::{Arc, Mutex},
 	task::Poll,
 };
 
-/// Keeps the most recent value of a stream and acts as stream itself.
+/// Keeps the most recent value of a stream and acts as stream itself. Values that are mandatory
+/// are kept until they're consumed, even when more recent values arrive in the meantime.
 pub struct RecentStream<T: Send + 'static> {
-	value: Arc<Mutex<Option<Option<T>>>>,
+	value: Arc<Mutex<Option<VecDeque<T>>>>,
 }
 
 impl<T: Send + 'static> RecentStream<T> {
-	pub fn new(mut stream: impl Stream<Item = T> + Send + Unpin + 'static) -> Self {
-		let value = Arc::new(Mutex::new(Some(None)));
+	pub fn new(
+		mut stream: impl Stream<Item = T> + Send + Unpin + 'static,
+		is_mandatory: fn(&T) -> bool,
+	) -> Self {
+		let value = Arc::new(Mutex::new(Some(VecDeque::new())));
 		let value_cloned = value.clone();
 		tokio::spawn(async move {
 			while let Some(v) = stream.next().await {
-				*value_cloned.lock().unwrap() = Some(Some(v));
+				if let Some(pending) = value_cloned.lock().unwrap().as_mut() {
+					// the latest pending value is superseded, unless it's mandatory
+					if pending.back().map_or(false, |last| !is_mandatory(last)) {
+						pending.pop_back();
+					}
+					pending.push_back(v);
+				}
 			}
 			*value_cloned.lock().unwrap() = None;
 		});
@@ -34,7 +45,7 @@ impl<T: Send> Stream for RecentStream<T> {
 		let this = self.get_mut();
 		let mut value = this.value.lock().unwrap();
 		match value.as_mut() {
-			Some(v) => match v.take() {
+			Some(pending) => match pending.pop_front() {
 				Some(v) => Poll::Ready(Some(v)),
 				None => {
 					cx.waker().wake_by_ref();
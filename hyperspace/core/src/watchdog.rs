@@ -0,0 +1,138 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Detects the relay pipeline silently deadlocking: packets being sent on a channel but none of
+//! them being relayed, while the relay loop doesn't report any error.
+//!
+//! [`watch_pipeline`] compares the [`primitives::PipelineCounters`] of both chains at the end of
+//! every window. A channel strikes when packets were sent from it during the window but none was
+//! relayed, and no error was reported. After [`MAX_STRIKES`] consecutive strikes the watchdog
+//! raises an alert, logs the internal state of both chains and forces a rescan of the backlog:
+//! the relayed sequences and the cached packet timeouts of the channel are forgotten, and every
+//! kind of packet is marked as undelivered on both chains, so that the next finality events send
+//! the client updates needed to relay all of the pending packets.
+
+use crate::admin::dump_chain_state;
+use ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use primitives::{Chain, ChannelCounters, UndeliveredType};
+use serde_json::json;
+use std::{collections::HashMap, time::Duration};
+
+/// Default length of the windows over which the pipeline counters are compared.
+pub const DEFAULT_WATCHDOG_WINDOW: Duration = Duration::from_secs(300);
+
+/// Number of consecutive windows without any packet relayed before a rescan is forced.
+pub const MAX_STRIKES: u32 = 3;
+
+#[derive(Default)]
+struct ChannelWatch {
+	/// Counters at the end of the previous window.
+	counters: ChannelCounters,
+	strikes: u32,
+}
+
+/// Counters of a chain at the end of the previous window.
+#[derive(Default)]
+struct PipelineWatch {
+	channels: HashMap<(ChannelId, PortId), ChannelWatch>,
+	errors: u64,
+}
+
+/// Compares the pipeline counters of both chains every `window`. The chains are expected to
+/// share their [`primitives::CommonClientState`] with the relay loop.
+pub async fn watch_pipeline<A: Chain, B: Chain>(chain_a: A, chain_b: B, window: Duration) {
+	let mut interval = tokio::time::interval(window);
+	let mut watch_a = PipelineWatch::default();
+	let mut watch_b = PipelineWatch::default();
+	loop {
+		interval.tick().await;
+		check_pipeline(&chain_a, &chain_b, &mut watch_a);
+		check_pipeline(&chain_b, &chain_a, &mut watch_b);
+	}
+}
+
+/// Checks the whitelisted channels of `source` for packets that were sent but not relayed during
+/// the last window.
+fn check_pipeline(source: &impl Chain, sink: &impl Chain, watch: &mut PipelineWatch) {
+	let state = source.common_state();
+	let (errors, channels) = {
+		let counters = state.pipeline_counters.lock().unwrap();
+		let channels = source
+			.channel_whitelist()
+			.into_iter()
+			.map(|(channel_id, port_id)| {
+				let channel = counters.channel(channel_id, port_id.clone());
+				((channel_id, port_id), channel)
+			})
+			.collect::<Vec<_>>();
+		(counters.errors(), channels)
+	};
+	let quiet = errors == watch.errors;
+	watch.errors = errors;
+
+	for ((channel_id, port_id), counters) in channels {
+		let channel = watch.channels.entry((channel_id, port_id.clone())).or_default();
+		let observed = counters.observed.saturating_sub(channel.counters.observed);
+		let submitted = counters.submitted.saturating_sub(channel.counters.submitted);
+		channel.counters = counters;
+		let stopped = state.is_channel_paused(&channel_id, &port_id) ||
+			state.channel_halt_reason(&channel_id, &port_id).is_some();
+		if observed == 0 || submitted != 0 || !quiet || stopped {
+			channel.strikes = 0;
+			continue
+		}
+
+		channel.strikes += 1;
+		log::warn!(target: "hyperspace", "{observed} packets were sent from {port_id}/{channel_id} on {} during the last window, but none was relayed", source.name());
+		if channel.strikes < MAX_STRIKES {
+			continue
+		}
+		channel.strikes = 0;
+		log::error!(
+			target: "hyperspace",
+			"ALERT: no packet sent from {port_id}/{channel_id} on {} was relayed during the last {MAX_STRIKES} windows, although no error was reported. Forcing a rescan of the backlog",
+			source.name()
+		);
+		log::error!(target: "hyperspace", "Relayer state: {}", json!([dump_chain_state(source), dump_chain_state(sink)]));
+		rescan_backlog(source, sink, &channel_id, &port_id);
+	}
+}
+
+/// Makes the next finality events query all the pending packets of the channel again.
+fn rescan_backlog(
+	source: &impl Chain,
+	sink: &impl Chain,
+	channel_id: &ChannelId,
+	port_id: &PortId,
+) {
+	source
+		.common_state()
+		.relayed_sequences
+		.lock()
+		.unwrap()
+		.forget_channel(channel_id, port_id);
+	source
+		.common_state()
+		.pending_timeouts
+		.lock()
+		.unwrap()
+		.forget_channel(channel_id, port_id);
+	for chain_state in [source.common_state(), sink.common_state()] {
+		let mut undelivered = chain_state.maybe_has_undelivered_packets.lock().unwrap();
+		for kind in [UndeliveredType::Acks, UndeliveredType::Recvs, UndeliveredType::Timeouts] {
+			undelivered.insert(kind, true);
+		}
+	}
+	source.common_state().request_client_update();
+}
@@ -0,0 +1,143 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watches the channels and clients of both chains and halts relaying of the channels that can't
+//! be relayed anymore, so that the relayer doesn't pay fees for submissions that are guaranteed
+//! to fail.
+//!
+//! A whitelisted channel is halted when:
+//! - it's closed on either side ([`HaltReason::ChannelClosed`]). Packets and acknowledgements are
+//!   no longer relayed, but timeouts still are, so that the senders get refunded.
+//! - one of the two clients is frozen or expired ([`HaltReason::ClientFrozen`],
+//!   [`HaltReason::ClientExpired`]). Nothing is relayed until the client is recovered.
+//!
+//! Halted channels are resumed automatically once the condition is gone (e.g. after a client
+//! recovery).
+
+use anyhow::anyhow;
+use ibc::core::{
+	ics02_client::client_state::ClientState,
+	ics04_channel::channel::{ChannelEnd, State},
+	ics24_host::identifier::{ChannelId, ClientId, PortId},
+};
+use primitives::{health::query_client_age, Chain, HaltReason};
+use std::time::Duration;
+
+/// Default interval between two checks of the channels.
+pub const DEFAULT_CHANNEL_WATCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Checks the channels of both chains every `interval`, halting and resuming them as needed.
+/// The chains are expected to share their [`primitives::CommonClientState`] with the relay loop.
+pub async fn watch_channels<A: Chain, B: Chain>(chain_a: A, chain_b: B, interval: Duration) {
+	let mut interval = tokio::time::interval(interval);
+	loop {
+		interval.tick().await;
+		if let Err(e) = check_channels(&chain_a, &chain_b).await {
+			log::warn!(target: "hyperspace", "Failed to check channels of {}: {:?}", chain_a.name(), e);
+		}
+		if let Err(e) = check_channels(&chain_b, &chain_a).await {
+			log::warn!(target: "hyperspace", "Failed to check channels of {}: {:?}", chain_b.name(), e);
+		}
+	}
+}
+
+/// Updates the halt status of the whitelisted channels of `source`.
+async fn check_channels(source: &impl Chain, sink: &impl Chain) -> anyhow::Result<()> {
+	// packets and acknowledgements are verified by the client on the sink, timeouts by the one
+	// on the source
+	let client_reason = match client_halt_reason(sink, source.client_id()).await? {
+		Some(reason) => Some(reason),
+		None => client_halt_reason(source, sink.client_id()).await?,
+	};
+
+	for (channel_id, port_id) in source.channel_whitelist() {
+		let reason = match client_reason {
+			Some(reason) => Some(reason),
+			None => is_channel_closed(source, sink, channel_id, port_id.clone())
+				.await?
+				.then_some(HaltReason::ChannelClosed),
+		};
+		let state = source.common_state();
+		match reason {
+			Some(reason) =>
+				if state.halt_channel(channel_id, port_id.clone(), reason) != Some(reason) {
+					log::error!(
+						target: "hyperspace",
+						"ALERT: halting relay of channel {port_id}/{channel_id} on {}: {reason}",
+						source.name()
+					);
+				},
+			None =>
+				if let Some(reason) = state.unhalt_channel(&channel_id, &port_id) {
+					log::info!(
+						target: "hyperspace",
+						"Resuming relay of channel {port_id}/{channel_id} on {}, previously halted because {reason}",
+						source.name()
+					);
+				},
+		}
+	}
+
+	Ok(())
+}
+
+/// Returns why the client `client_id` hosted on `host` can't verify proofs anymore, if it can't.
+async fn client_halt_reason(
+	host: &impl Chain,
+	client_id: ClientId,
+) -> anyhow::Result<Option<HaltReason>> {
+	let (client_state, elapsed) = query_client_age(host, client_id).await?;
+	if client_state.frozen_height().is_some() {
+		return Ok(Some(HaltReason::ClientFrozen))
+	}
+	if client_state.expired(elapsed) {
+		return Ok(Some(HaltReason::ClientExpired))
+	}
+
+	Ok(None)
+}
+
+/// Returns whether the channel is closed on `source` or on `sink`.
+async fn is_channel_closed(
+	source: &impl Chain,
+	sink: &impl Chain,
+	channel_id: ChannelId,
+	port_id: PortId,
+) -> anyhow::Result<bool> {
+	let (source_height, _) = source.latest_height_and_timestamp().await?;
+	let channel = query_channel_end(source, source_height, channel_id, port_id).await?;
+	if channel.state == State::Closed {
+		return Ok(true)
+	}
+
+	let Some(counterparty_channel_id) = channel.counterparty().channel_id else { return Ok(false) };
+	let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+	let counterparty_port_id = channel.counterparty().port_id.clone();
+	let counterparty =
+		query_channel_end(sink, sink_height, counterparty_channel_id, counterparty_port_id).await?;
+	Ok(counterparty.state == State::Closed)
+}
+
+async fn query_channel_end(
+	chain: &impl Chain,
+	at: ibc::Height,
+	channel_id: ChannelId,
+	port_id: PortId,
+) -> anyhow::Result<ChannelEnd> {
+	let response = chain.query_channel_end(at, channel_id, port_id.clone()).await?;
+	let channel = response
+		.channel
+		.ok_or_else(|| anyhow!("Channel {port_id}/{channel_id} not found on {}", chain.name()))?;
+	Ok(ChannelEnd::try_from(channel)?)
+}
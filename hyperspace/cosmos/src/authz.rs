@@ -0,0 +1,192 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Relaying on behalf of a granter account with the authz module. The relayed messages name the
+//! granter as their signer and are wrapped in a `MsgExec` of the relayer key, which only holds
+//! grants for the IBC message types. The grants of the relayer keys are checked periodically, so
+//! that they're renewed before they expire.
+
+use crate::{client::CosmosClient, error::Error};
+use ibc_proto::{
+	cosmos::base::query::v1beta1::{PageRequest, PageResponse},
+	google::protobuf::{Any, Timestamp},
+};
+use primitives::KeyProvider;
+use prost::Message;
+use std::{
+	collections::HashMap,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Type url of `MsgExec`.
+pub const MSG_EXEC_TYPE_URL: &str = "/cosmos.authz.v1beta1.MsgExec";
+
+/// Type url of `GenericAuthorization`.
+pub const GENERIC_AUTHORIZATION_TYPE_URL: &str = "/cosmos.authz.v1beta1.GenericAuthorization";
+
+/// Path of the query of the grants of a granter to a grantee.
+const GRANTS_QUERY_PATH: &str = "/cosmos.authz.v1beta1.Query/Grants";
+
+/// Messages the relayer keys need a grant for to relay packets.
+pub const RELAYED_MESSAGE_TYPES: &[&str] = &[
+	"/ibc.core.client.v1.MsgUpdateClient",
+	"/ibc.core.channel.v1.MsgRecvPacket",
+	"/ibc.core.channel.v1.MsgAcknowledgement",
+	"/ibc.core.channel.v1.MsgTimeout",
+	"/ibc.core.channel.v1.MsgTimeoutOnClose",
+];
+
+/// Interval between two checks of the grants.
+pub const GRANT_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Grants expiring within this duration are reported.
+pub const GRANT_EXPIRY_WARNING: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgExec {
+	#[prost(string, tag = "1")]
+	pub grantee: String,
+	#[prost(message, repeated, tag = "2")]
+	pub msgs: Vec<Any>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct GenericAuthorization {
+	/// Type url of the message the grant authorizes
+	#[prost(string, tag = "1")]
+	pub msg: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Grant {
+	#[prost(message, optional, tag = "1")]
+	pub authorization: Option<Any>,
+	/// The grant never expires if it's not set
+	#[prost(message, optional, tag = "2")]
+	pub expiration: Option<Timestamp>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct QueryGrantsRequest {
+	#[prost(string, tag = "1")]
+	granter: String,
+	#[prost(string, tag = "2")]
+	grantee: String,
+	#[prost(string, tag = "3")]
+	msg_type_url: String,
+	#[prost(message, optional, tag = "4")]
+	pagination: Option<PageRequest>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct QueryGrantsResponse {
+	#[prost(message, repeated, tag = "1")]
+	grants: Vec<Grant>,
+	#[prost(message, optional, tag = "2")]
+	pagination: Option<PageResponse>,
+}
+
+/// `MsgExec` of `messages` by `grantee`.
+pub fn msg_exec(grantee: &str, messages: Vec<Any>) -> Any {
+	let msg = MsgExec { grantee: grantee.to_string(), msgs: messages };
+	Any { type_url: MSG_EXEC_TYPE_URL.to_string(), value: msg.encode_to_vec() }
+}
+
+impl<H> CosmosClient<H>
+where
+	Self: KeyProvider,
+	H: Clone + Send + Sync + 'static,
+{
+	/// Queries the grants of `granter` to `grantee`.
+	pub async fn query_grants(&self, granter: &str, grantee: &str) -> Result<Vec<Grant>, Error> {
+		let mut grants = vec![];
+		let mut next_key = vec![];
+		loop {
+			let request = QueryGrantsRequest {
+				granter: granter.to_string(),
+				grantee: grantee.to_string(),
+				msg_type_url: String::new(),
+				pagination: Some(PageRequest { key: next_key, ..Default::default() }),
+			};
+			let response =
+				self.abci_query(GRANTS_QUERY_PATH, request.encode_to_vec(), None, false).await?;
+			if !response.code.is_ok() {
+				return Err(Error::from(format!(
+					"Failed to query the grants of {granter} to {grantee}: {}",
+					response.log
+				)))
+			}
+			let response = QueryGrantsResponse::decode(response.value.as_slice())?;
+			grants.extend(response.grants);
+			match response.pagination {
+				Some(page) if !page.next_key.is_empty() => next_key = page.next_key,
+				_ => return Ok(grants),
+			}
+		}
+	}
+
+	/// Checks that every relayer key has a grant of the `authz_granter` for the
+	/// [`RELAYED_MESSAGE_TYPES`], logging the missing grants and the ones expiring within
+	/// [`GRANT_EXPIRY_WARNING`].
+	pub async fn check_grants(&self) -> Result<(), Error> {
+		let Some(granter) = &self.authz_granter else { return Ok(()) };
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+		for key in &self.signing_keys {
+			// expiration of the grant of each message type, `None` for grants without expiration
+			let expirations = self
+				.query_grants(granter, &key.account)
+				.await?
+				.into_iter()
+				.filter_map(|grant| {
+					let authorization = grant.authorization?;
+					if authorization.type_url != GENERIC_AUTHORIZATION_TYPE_URL {
+						return None
+					}
+					let msg =
+						GenericAuthorization::decode(authorization.value.as_slice()).ok()?.msg;
+					let expiration = grant
+						.expiration
+						.map(|expiration| Duration::from_secs(expiration.seconds.max(0) as u64));
+					Some((msg, expiration))
+				})
+				.collect::<HashMap<_, _>>();
+			for message_type in RELAYED_MESSAGE_TYPES {
+				match expirations.get(*message_type) {
+					None => {
+						log::error!(target: "hyperspace_cosmos", "{} has no grant of {granter} for {message_type} on {}", key.account, self.name);
+					},
+					Some(Some(expiration)) if *expiration <= now => {
+						log::error!(target: "hyperspace_cosmos", "The grant of {granter} to {} for {message_type} on {} expired", key.account, self.name);
+					},
+					Some(Some(expiration)) if *expiration <= now + GRANT_EXPIRY_WARNING => {
+						log::warn!(target: "hyperspace_cosmos", "The grant of {granter} to {} for {message_type} on {} expires in {:?}", key.account, self.name, *expiration - now);
+					},
+					Some(_) => {},
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Checks the grants of the relayer keys every [`GRANT_CHECK_INTERVAL`].
+	pub async fn watch_grants(self) {
+		let mut interval = tokio::time::interval(GRANT_CHECK_INTERVAL);
+		loop {
+			interval.tick().await;
+			if let Err(e) = self.check_grants().await {
+				log::warn!(target: "hyperspace_cosmos", "Failed to check the grants on {}: {e}", self.name);
+			}
+		}
+	}
+}
@@ -1,13 +1,26 @@
-use super::{client::CosmosClient, tx::sign_tx};
+use super::{
+	authz::msg_exec,
+	client::{CosmosClient, VerificationMode},
+	events::ibc_event_try_from_abci_event,
+	provider::{TransactionId, NUMBER_OF_BLOCKS_TO_PROCESS_PER_ITER},
+	subscription::{self, SubscriptionItem},
+	tx::{sign_tx, simulate_tx},
+};
 use crate::{error::Error, events::client_extract_attributes_from_tx, provider::FinalityEvent};
 use futures::{Stream, StreamExt};
 use ibc::{
 	core::{
-		ics02_client::{events::UpdateClient, msgs::ClientMsg},
-		ics24_host::identifier::ChainId,
+		ics02_client::{
+			events::UpdateClient,
+			msgs::{update_client::TYPE_URL as UPDATE_CLIENT_TYPE_URL, ClientMsg},
+		},
+		ics23_commitment::specs::ProofSpecs,
+		ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
 		ics26_routing::msgs::Ics26Envelope,
 	},
 	events::IbcEvent,
+	protobuf::Protobuf,
+	signer::Signer,
 	Height,
 };
 use ibc_proto::{
@@ -16,19 +29,32 @@ use ibc_proto::{
 		tx::v1beta1::{service_client::ServiceClient, Fee, GetTxsEventRequest, OrderBy},
 	},
 	google::protobuf::Any,
+	ibc::core::client::v1::MsgUpdateClient as RawMsgUpdateClient,
+};
+use ics07_tendermint::{
+	client_message::{ClientMessage, Header},
+	client_state::ClientState,
+	consensus_state::ConsensusState,
+};
+use ics08_wasm::msg::MsgPushNewWasmCode;
+use pallet_ibc::light_clients::{
+	AnyClientMessage, AnyClientState, AnyConsensusState, HostFunctionsManager,
 };
-use pallet_ibc::light_clients::AnyClientMessage;
 use primitives::{
-	mock::LocalClientTypes, Chain, CommonClientState, IbcProvider, LightClientSync,
-	MisbehaviourHandler,
+	mock::LocalClientTypes, Chain, FinalitySource, IbcQuerier, KeyProvider, LightClientSync,
+	MisbehaviourHandler, TxSubmitter, UpdateType,
 };
 use prost::Message;
+use rand::Rng;
 use std::{pin::Pin, time::Duration};
+use tendermint::{block::Height as TmHeight, Hash};
 use tendermint_rpc::{
+	endpoint::tx::Response,
 	event::{Event, EventData},
 	query::{EventType, Query},
-	SubscriptionClient, WebSocketClient,
+	Client, Order,
 };
+use tokio::{task::JoinSet, time::sleep};
 
 #[async_trait::async_trait]
 impl<H> LightClientSync for CosmosClient<H>
@@ -48,12 +74,254 @@ where
 }
 
 #[async_trait::async_trait]
-impl<H> Chain for CosmosClient<H>
+impl<H> TxSubmitter for CosmosClient<H>
 where
 	H: Clone + Send + Sync + 'static,
 {
-	fn name(&self) -> &str {
-		&self.name
+	type TransactionId = TransactionId<Hash>;
+
+	async fn query_client_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<ClientId, Self::Error> {
+		const WAIT_BACKOFF: Duration = Duration::from_millis(300);
+		const TIME_OUT: Duration = Duration::from_millis(30000);
+		let start_time = std::time::Instant::now();
+
+		let response: Response = loop {
+			let response = self
+				.rpc_ws_client()
+				.tx_search(
+					Query::eq("tx.hash", tx_id.hash.to_string()),
+					false,
+					1,
+					1, // get only the first Tx matching the query
+					Order::Ascending,
+				)
+				.await
+				.map_err(|e| Error::from(format!("Failed to query tx hash: {e}")))?;
+			match response.txs.into_iter().next() {
+				None => {
+					let elapsed = start_time.elapsed();
+					if elapsed > TIME_OUT {
+						return Err(Error::from(format!(
+							"Timeout waiting for tx {:?} to be included in a block",
+							tx_id.hash
+						)))
+					} else {
+						std::thread::sleep(WAIT_BACKOFF);
+					}
+				},
+				Some(resp) => break resp,
+			}
+		};
+
+		let height = Height::new(
+			ChainId::chain_version(self.chain_id.to_string().as_str()),
+			response.height.value(),
+		);
+		let deliver_tx_result = response.tx_result;
+		if deliver_tx_result.code.is_err() {
+			Err(Error::from(format!(
+				"Transaction failed with code {:?} and log {:?}",
+				deliver_tx_result.code, deliver_tx_result.log
+			)))
+		} else {
+			let result = deliver_tx_result
+				.events
+				.iter()
+				.flat_map(|e| ibc_event_try_from_abci_event(e, height).ok().into_iter())
+				.filter(|e| matches!(e, IbcEvent::CreateClient(_)))
+				.collect::<Vec<_>>();
+			if result.len() != 1 {
+				Err(Error::from(format!(
+					"Expected exactly one CreateClient event, found {}",
+					result.len()
+				)))
+			} else {
+				Ok(match result[0] {
+					IbcEvent::CreateClient(ref e) => e.client_id().clone(),
+					_ => unreachable!(),
+				})
+			}
+		}
+	}
+
+	async fn query_connection_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<ConnectionId, Self::Error> {
+		const WAIT_BACKOFF: Duration = Duration::from_millis(300);
+		const TIME_OUT: Duration = Duration::from_millis(30000);
+		let start_time = std::time::Instant::now();
+
+		let response: Response = loop {
+			let response = self
+				.rpc_ws_client()
+				.tx_search(
+					Query::eq("tx.hash", tx_id.hash.to_string()),
+					false,
+					1,
+					1, // get only the first Tx matching the query
+					Order::Ascending,
+				)
+				.await
+				.map_err(|e| Error::from(format!("Failed to query tx hash: {e}")))?;
+			match response.txs.into_iter().next() {
+				None => {
+					let elapsed = start_time.elapsed();
+					if elapsed > TIME_OUT {
+						return Err(Error::from(format!(
+							"Timeout waiting for tx {:?} to be included in a block",
+							tx_id.hash
+						)))
+					} else {
+						std::thread::sleep(WAIT_BACKOFF);
+					}
+				},
+				Some(resp) => break resp,
+			}
+		};
+
+		let height = Height::new(
+			ChainId::chain_version(self.chain_id.to_string().as_str()),
+			response.height.value(),
+		);
+		let deliver_tx_result = response.tx_result;
+		if deliver_tx_result.code.is_err() {
+			Err(Error::from(format!(
+				"Transaction failed with code {:?} and log {:?}",
+				deliver_tx_result.code, deliver_tx_result.log
+			)))
+		} else {
+			let result = deliver_tx_result
+				.events
+				.iter()
+				.flat_map(|e| ibc_event_try_from_abci_event(e, height).ok().into_iter())
+				.filter(|e| matches!(e, IbcEvent::OpenInitConnection(_)))
+				.collect::<Vec<_>>();
+			if result.len() != 1 {
+				Err(Error::from(format!(
+					"Expected exactly one CreateClient event, found {}",
+					result.len()
+				)))
+			} else {
+				Ok(match result[0] {
+					IbcEvent::OpenInitConnection(ref e) =>
+						e.connection_id().expect("Connection id wasn't found").clone(),
+					_ => unreachable!(),
+				})
+			}
+		}
+	}
+
+	async fn query_channel_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<(ChannelId, PortId), Self::Error> {
+		const WAIT_BACKOFF: Duration = Duration::from_millis(300);
+		const TIME_OUT: Duration = Duration::from_millis(30000);
+		let start_time = std::time::Instant::now();
+
+		let response: Response = loop {
+			let response = self
+				.rpc_ws_client()
+				.tx_search(
+					Query::eq("tx.hash", tx_id.hash.to_string()),
+					false,
+					1,
+					1, // get only the first Tx matching the query
+					Order::Ascending,
+				)
+				.await
+				.map_err(|e| Error::from(format!("Failed to query tx hash: {e}")))?;
+			match response.txs.into_iter().next() {
+				None => {
+					let elapsed = start_time.elapsed();
+					if elapsed > TIME_OUT {
+						return Err(Error::from(format!(
+							"Timeout waiting for tx {:?} to be included in a block",
+							tx_id.hash
+						)))
+					} else {
+						std::thread::sleep(WAIT_BACKOFF);
+					}
+				},
+				Some(resp) => break resp,
+			}
+		};
+
+		let height = Height::new(
+			ChainId::chain_version(self.chain_id.to_string().as_str()),
+			response.height.value(),
+		);
+		let deliver_tx_result = response.tx_result;
+		if deliver_tx_result.code.is_err() {
+			Err(Error::from(format!(
+				"Transaction failed with code {:?} and log {:?}",
+				deliver_tx_result.code, deliver_tx_result.log
+			)))
+		} else {
+			let result = deliver_tx_result
+				.events
+				.iter()
+				.flat_map(|e| ibc_event_try_from_abci_event(e, height).ok().into_iter())
+				.filter(|e| matches!(e, IbcEvent::OpenInitChannel(_)))
+				.collect::<Vec<_>>();
+			if result.len() != 1 {
+				Err(Error::from(format!(
+					"Expected exactly one CreateClient event, found {}",
+					result.len()
+				)))
+			} else {
+				Ok(match result[0] {
+					IbcEvent::OpenInitChannel(ref e) =>
+						(*e.channel_id().expect("Channel id wasn't found"), e.port_id().clone()),
+					_ => unreachable!(),
+				})
+			}
+		}
+	}
+
+	async fn upload_wasm(&self, wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
+		let msg = MsgPushNewWasmCode { signer: self.account_id(), code: wasm };
+		let hash = self.submit(vec![msg.into()]).await?;
+		let resp = self.wait_for_tx_result(hash).await?;
+		let height = Height::new(
+			ChainId::chain_version(self.chain_id.to_string().as_str()),
+			resp.height.value(),
+		);
+		let deliver_tx_result = resp.tx_result;
+		let mut result = deliver_tx_result
+			.events
+			.iter()
+			.flat_map(|e| ibc_event_try_from_abci_event(e, height).ok().into_iter())
+			.filter(|e| matches!(e, IbcEvent::PushWasmCode(_)))
+			.collect::<Vec<_>>();
+		let code_id = if result.len() != 1 {
+			return Err(Error::from(format!(
+				"Expected exactly one PushWasmCode event, found {}",
+				result.len()
+			)))
+		} else {
+			match result.pop().unwrap() {
+				IbcEvent::PushWasmCode(ev) => ev.0,
+				_ => unreachable!(),
+			}
+		};
+		// let resp = MsgClient::connect(
+		// 	Endpoint::try_from(self.grpc_url().to_string())
+		// 		.map_err(|e| Error::from(format!("Failed to parse grpc url: {:?}", e)))?,
+		// )
+		// .await
+		// .map_err(|e| Error::from(format!("Failed to connect to grpc endpoint: {:?}", e)))?
+		// .push_new_wasm_code(msg)
+		// .await
+		// .map_err(|e| {
+		// 	Error::from(format!("Failed to upload wasm code to grpc endpoint: {:?}", e))
+		// })?;
+
+		Ok(code_id)
 	}
 
 	fn block_max_weight(&self) -> u64 {
@@ -63,8 +331,22 @@ where
 	async fn estimate_weight(&self, messages: Vec<Any>) -> Result<u64, Self::Error> {
 		let account_info = self.query_account().await?;
 		let fee = self.get_fee();
-		let (_, tx_raw, _) =
-			sign_tx(self.keybase.clone(), self.chain_id.clone(), &account_info, vec![], fee)?;
+		// the timeout height is encoded with its maximum length, as it's only known on submission
+		let timeout_height = self.timeout_height_offset.map_or(0, |_| u64::MAX);
+		// with authz, the messages are added to an empty `MsgExec`, whose length delimiters may
+		// take a few more bytes
+		let envelope_messages = match self.authz_granter {
+			Some(_) => vec![msg_exec(&self.keybase.account, vec![])],
+			None => vec![],
+		};
+		let (_, tx_raw, _) = sign_tx(
+			self.keybase.clone(),
+			self.chain_id.clone(),
+			&account_info,
+			envelope_messages,
+			fee,
+			self.tx_body_options(timeout_height),
+		)?;
 
 		let body_bytes_len = tx_raw.body_bytes.len();
 		// Full length of the transaction can then be derived from the length of the invariable
@@ -90,42 +372,32 @@ where
 		Ok(current_len as u64)
 	}
 
-	async fn finality_notifications(
-		&self,
-	) -> Result<Pin<Box<dyn Stream<Item = <Self as IbcProvider>::FinalityEvent> + Send + Sync>>, Error> {
-		let ws_client = self.rpc_ws_client().clone();
-		let subscription = ws_client
-			.subscribe(Query::from(EventType::NewBlock))
-			.await
-			.map_err(|e| Error::from(format!("failed to subscribe to new blocks {e:?}")))?
-			.chunks(6);
-		log::info!(target: "hyperspace_cosmos", "🛰️ Subscribed to {} listening to finality notifications", self.name);
-		let stream = subscription.filter_map(|events| {
-			let events = events
-				.into_iter()
-				.collect::<Result<Vec<_>, _>>()
-				.map_err(|e| Error::from(format!("failed to get event {e:?}")))
-				.unwrap();
-			let get_height = |event: &Event| {
-				let Event { data, events: _, query: _ } = &event;
-				let height = match &data {
-					EventData::NewBlock { block, .. } =>
-						block
-							.as_ref()
-							.expect("NewBlock event should always have a block; qed")
-							.header
-							.height,
-					_ => unreachable!(),
-				};
-				height
-			};
-			futures::future::ready(Some(FinalityEvent::Tendermint {
-				from: get_height(events.first().unwrap()),
-				to: get_height(events.last().unwrap()),
-			}))
-		});
-
-		Ok(Box::pin(stream))
+	async fn estimate_fee(&self, messages: &[Any]) -> Result<primitives::Fee, Self::Error> {
+		let account_info = self.query_account().await?;
+		let tx_messages = match self.authz_granter {
+			Some(_) => vec![msg_exec(&self.keybase.account, messages.to_vec())],
+			None => messages.to_vec(),
+		};
+		let (tx, _, tx_bytes) = sign_tx(
+			self.keybase.clone(),
+			self.chain_id.clone(),
+			&account_info,
+			tx_messages,
+			self.get_fee(),
+			self.tx_body_options(0),
+		)?;
+		let gas_used = simulate_tx(self.grpc_url(), tx, tx_bytes)
+			.await?
+			.gas_info
+			.ok_or_else(|| Error::from("Simulation returned no gas info".to_string()))?
+			.gas_used;
+		self.adjusted_gas(messages, gas_used)?;
+		// the whole fee of the transaction is deducted, whatever the gas it uses
+		let amount = self
+			.fee_amount
+			.parse()
+			.map_err(|e| Error::from(format!("Invalid fee amount {}: {e}", self.fee_amount)))?;
+		Ok(primitives::Fee { amount, denom: self.fee_denom.clone() })
 	}
 
 	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Error> {
@@ -133,6 +405,215 @@ where
 		log::debug!(target: "hyperspace_cosmos", "Submitted. Tx hash: {}", hash);
 		Ok(Self::TransactionId { hash })
 	}
+}
+
+#[async_trait::async_trait]
+impl<H> FinalitySource for CosmosClient<H>
+where
+	H: Clone + Send + Sync + 'static,
+{
+	type FinalityEvent = FinalityEvent;
+
+	async fn query_latest_ibc_events<C>(
+		&mut self,
+		finality_event: Self::FinalityEvent,
+		counterparty: &C,
+	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+	where
+		C: Chain,
+	{
+		let finality_event_height = match finality_event {
+			FinalityEvent::Tendermint { from: _, to } => to,
+		};
+		// only relay the blocks that are buried under enough blocks
+		let finality_event_height = TmHeight::try_from(
+			finality_event_height
+				.value()
+				.saturating_sub(self.security_params().finality_depth),
+		)
+		.expect("should not overflow");
+		let client_id = self.client_id();
+		let latest_cp_height = counterparty.latest_height_and_timestamp().await?.0;
+		let latest_cp_client_state =
+			counterparty.query_client_state(latest_cp_height, client_id.clone()).await?;
+		let client_state_response = latest_cp_client_state
+			.client_state
+			.ok_or_else(|| Error::Custom("counterparty returned empty client state".to_string()))?;
+		let client_state =
+			ClientState::<HostFunctionsManager>::decode_vec(&client_state_response.value)
+				.map_err(|_| Error::Custom("failed to decode client state response".to_string()))?;
+		let latest_cp_client_height = client_state.latest_height().revision_height;
+		let latest_height = self.latest_height_and_timestamp().await?.0;
+		let latest_revision = latest_height.revision_number;
+
+		let from = TmHeight::try_from(latest_cp_client_height).unwrap();
+		let to = finality_event_height.min(
+			TmHeight::try_from(latest_cp_client_height + NUMBER_OF_BLOCKS_TO_PROCESS_PER_ITER)
+				.expect("should not overflow"),
+		);
+		if to < from {
+			return Ok(vec![])
+		}
+		log::info!(target: "hyperspace_cosmos", "Getting blocks {}..{}", from, to);
+
+		// query (exclusively) up to `to`, because the proof for the event at `to - 1` will be
+		// contained at `to` and will be fetched below by `msg_update_client_header`
+		let update_headers =
+			self.msg_update_client_header(from, to, client_state.latest_height).await?;
+		let mut block_events = Vec::new();
+		let mut join_set: JoinSet<Result<_, anyhow::Error>> = JoinSet::new();
+		let range = (from.value()..to.value()).collect::<Vec<_>>();
+		let to = self.rpc_call_delay().as_millis();
+		for heights in range.chunks(100) {
+			for height in heights.iter().copied() {
+				log::trace!(target: "hyperspace_cosmos", "Parsing events at height {:?}", height);
+				let client = self.clone();
+				let duration = Duration::from_millis(rand::thread_rng().gen_range(0..to) as u64);
+				let counterparty = counterparty.clone();
+				join_set.spawn(async move {
+					sleep(duration).await;
+					let xs = tokio::time::timeout(
+						Duration::from_secs(30),
+						client.parse_ibc_events_at(&counterparty, latest_revision, height),
+					)
+					.await??;
+					Ok((height, xs))
+				});
+			}
+			while let Some(res) = join_set.join_next().await {
+				let out = res??;
+				block_events.push(out);
+			}
+		}
+
+		if block_events.len() != update_headers.len() {
+			return Err(anyhow::anyhow!(
+				"block events and updates must match, got {} and {}",
+				block_events.len(),
+				update_headers.len()
+			))
+		}
+		block_events.sort_by_key(|(height, _)| *height);
+
+		let mut updates = Vec::new();
+		let mut last_mandatory_height = latest_cp_client_height;
+		for (i, (events, (update_header, mut update_type))) in block_events
+			.into_iter()
+			.map(|(_, events)| events)
+			.zip(update_headers)
+			.enumerate()
+		{
+			let height = update_header.height();
+			// the headers of the sequential mode are verified against the previous ones
+			if i == NUMBER_OF_BLOCKS_TO_PROCESS_PER_ITER as usize - 1 ||
+				self.verification_mode == VerificationMode::Sequential ||
+				self.is_update_required(height.revision_height, last_mandatory_height).await?
+			{
+				update_type = UpdateType::Mandatory;
+			}
+			if !update_type.is_optional() {
+				last_mandatory_height = height.revision_height;
+			}
+			let update_client_header =
+				msg_update_tendermint_client(&client_id, update_header, &counterparty.account_id());
+			updates.push((update_client_header, height, events, update_type));
+		}
+		Ok(updates)
+	}
+
+	async fn is_update_required(
+		&self,
+		latest_height: u64,
+		latest_client_height_on_counterparty: u64,
+	) -> Result<bool, Self::Error> {
+		// unless a refresh period or a maximum header lag is configured, we never need to use
+		// LightClientSync trait in this case, because all the events will be eventually submitted
+		// via `finality_notifications`
+		let lag = latest_height.saturating_sub(latest_client_height_on_counterparty);
+		Ok([self.client_update_period(), self.security_params().max_header_lag]
+			.into_iter()
+			.flatten()
+			.any(|period| lag >= period))
+	}
+
+	async fn initialize_client_state(
+		&self,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		let (latest_height, _) = self.latest_height_and_timestamp().await?;
+		self.initialize_client_state_at(latest_height.revision_height).await
+	}
+
+	async fn initialize_client_state_at(
+		&self,
+		height: u64,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		let height = Height::new(self.chain_id.version(), height);
+		let client_state = ClientState::new(
+			self.chain_id.clone(),
+			self.trust_threshold,
+			self.security_params().trusting_period(),
+			self.security_params().unbonding_period(),
+			self.security_params().max_clock_drift(),
+			height,
+			ProofSpecs::default(),
+			vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
+		)
+		.map_err(|e| Error::from(format!("Invalid client state {e}")))?;
+		let light_block = self
+			.light_client
+			.verify(height, height, &client_state)
+			.await
+			.map_err(|e| Error::from(format!("Invalid light block {e}")))?;
+		let consensus_state = ConsensusState::from(light_block.signed_header.header);
+		Ok((
+			AnyClientState::Tendermint(client_state),
+			AnyConsensusState::Tendermint(consensus_state),
+		))
+	}
+
+	async fn finality_notifications(
+		&self,
+	) -> Result<
+		Pin<Box<dyn Stream<Item = <Self as FinalitySource>::FinalityEvent> + Send + Sync>>,
+		Error,
+	> {
+		let subscription = subscription::subscribe(
+			self.endpoints.clone(),
+			vec![Query::from(EventType::NewBlock)],
+			self.common_state.clone(),
+		)
+		.await
+		.map_err(|e| Error::from(format!("failed to subscribe to new blocks {e:?}")))?;
+		log::info!(target: "hyperspace_cosmos", "🛰️ Subscribed to {} listening to finality notifications", self.name);
+		let common_state = self.common_state.clone();
+		let stream = subscription
+			.filter_map(move |item| {
+				let height = match item {
+					Ok(SubscriptionItem::Event(Event {
+						data: EventData::NewBlock { block: Some(block), .. },
+						..
+					})) => Some(block.header.height),
+					Ok(SubscriptionItem::Event(_)) => {
+						common_state.on_skipped_stream_item("NewBlock event without a block");
+						None
+					},
+					// blocks committed while the subscription was disconnected
+					Ok(SubscriptionItem::MissedBlock(height)) => TmHeight::try_from(height).ok(),
+					Err(e) => {
+						common_state.on_skipped_stream_item(&format!("failed to get event {e:?}"));
+						None
+					},
+				};
+				futures::future::ready(height)
+			})
+			.chunks(6)
+			.map(|heights| FinalityEvent::Tendermint {
+				from: heights[0],
+				to: heights[heights.len() - 1],
+			});
+
+		Ok(Box::pin(stream))
+	}
 
 	async fn query_client_message(
 		&self,
@@ -220,68 +701,45 @@ where
 	async fn get_proof_height(&self, block_height: Height) -> Height {
 		block_height.increment()
 	}
-
-	async fn handle_error(&mut self, error: &anyhow::Error) -> Result<(), anyhow::Error> {
-		let err_str = if let Some(rpc_err) = error.downcast_ref::<Error>() {
-			match rpc_err {
-				Error::RpcError(s) => s.clone(),
-				_ => "".to_string(),
-			}
-		} else {
-			error.to_string()
-		};
-		log::debug!(target: "hyperspace_cosmos", "Handling error: {err_str}");
-		if err_str.contains("dispatch task is gone") ||
-			err_str.contains("failed to send message to internal channel")
-		{
-			self.reconnect().await?;
-			self.common_state.rpc_call_delay *= 2;
-		}
-
-		Ok(())
-	}
-
-	fn rpc_call_delay(&self) -> Duration {
-		self.common_state.rpc_call_delay
-	}
-
-	fn set_rpc_call_delay(&mut self, delay: Duration) {
-		self.common_state.rpc_call_delay = delay;
-	}
-
-	fn common_state(&self) -> &CommonClientState {
-		&self.common_state
-	}
-
-	fn common_state_mut(&mut self) -> &mut CommonClientState {
-		&mut self.common_state
-	}
-
-	async fn reconnect(&mut self) -> anyhow::Result<()> {
-		// TODO: don't reconnect if the url is not presented
-		let (rpc_client, ws_driver) = WebSocketClient::new(self.websocket_url().clone())
-			.await
-			.map_err(|e| Error::RpcError(format!("{e:?}")))?;
-		self.join_handles.lock().await.push(tokio::spawn(ws_driver.run()));
-		self.rpc_ws_client = Some(rpc_client);
-		log::info!(target: "hyperspace_cosmos", "Reconnected to cosmos chain");
-		Ok(())
-	}
 }
 
 impl<H> CosmosClient<H>
 where
 	H: 'static + Clone + Send + Sync,
 {
+	/// Fee of a transaction with the maximum gas limit.
 	pub fn get_fee(&self) -> Fee {
+		self.fee(self.gas_limit)
+	}
+
+	/// Fee of a transaction with the gas limit `gas_limit`.
+	pub fn fee(&self, gas_limit: u64) -> Fee {
 		Fee {
 			amount: vec![Coin { denom: self.fee_denom.clone(), amount: self.fee_amount.clone() }],
-			gas_limit: self.gas_limit,
+			gas_limit,
 			payer: "".to_string(),
-			granter: "".to_string(),
+			granter: self.fee_granter.clone().unwrap_or_default(),
 		}
 	}
 
+	/// Gas limit of a transaction of `messages` whose simulation used `simulated` gas: the
+	/// simulated gas times the `gas_adjustment`, and at least the sum of the gas floors of the
+	/// messages, capped at the `gas_limit`. Fails if the simulated gas is above the cap.
+	pub fn adjusted_gas(&self, messages: &[Any], simulated: u64) -> Result<u64, Error> {
+		if simulated > self.gas_limit {
+			return Err(Error::from(format!(
+				"Simulated transaction uses {simulated} gas, above the gas limit of {}",
+				self.gas_limit
+			)))
+		}
+		let floor = messages
+			.iter()
+			.filter_map(|message| self.message_gas_floors.get(&message.type_url))
+			.sum::<u64>();
+		let adjusted = (simulated as f64 * self.gas_adjustment).ceil() as u64;
+		Ok(adjusted.max(floor).min(self.gas_limit))
+	}
+
 	pub fn id(&self) -> &ChainId {
 		&self.chain_id
 	}
@@ -292,11 +750,27 @@ impl<H> MisbehaviourHandler for CosmosClient<H>
 where
 	H: Clone + Send + Sync + 'static,
 {
-	async fn check_for_misbehaviour<C: Chain>(
+	async fn detect_misbehaviour<C: Chain>(
 		&self,
 		_counterparty: &C,
 		_client_message: AnyClientMessage,
-	) -> Result<(), anyhow::Error> {
-		Ok(())
+	) -> Result<Option<Any>, anyhow::Error> {
+		Ok(None)
 	}
 }
+
+/// Builds the `MsgUpdateClient` updating the tendermint client `client_id` with `header`.
+///
+/// The header is encoded as a `/ibc.lightclients.tendermint.v1.Header`, which the 07-tendermint
+/// clients of ibc-go expect, instead of the `ClientMessage` envelope of
+/// [`AnyClientMessage::Tendermint`] that only pallet-ibc understands. pallet-ibc accepts both, so
+/// the same message updates the client of a Cosmos chain on a parachain or on another Cosmos
+/// chain.
+fn msg_update_tendermint_client(client_id: &ClientId, header: Header, signer: &Signer) -> Any {
+	let msg = RawMsgUpdateClient {
+		client_id: client_id.to_string(),
+		client_message: Some(ClientMessage::Header(header).into()),
+		signer: signer.to_string(),
+	};
+	Any { type_url: UPDATE_CLIENT_TYPE_URL.to_string(), value: msg.encode_to_vec() }
+}
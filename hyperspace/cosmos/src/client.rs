@@ -1,24 +1,32 @@
 #![allow(clippy::all)]
 use super::{
-	key_provider::KeyEntry,
+	authz::msg_exec,
+	encode::TxBodyOptions,
+	endpoints::{Endpoint, EndpointConfig, Endpoints},
+	key_provider::{KeyEntry, KeyType, LocalSigner},
 	light_client::LightClient,
-	tx::{broadcast_tx, confirm_tx, sign_tx, simulate_tx},
+	tx::{broadcast_tx, broadcast_tx_grpc, confirm_tx, sign_tx, simulate_tx, with_signer},
 };
 use crate::error::Error;
-use bech32::ToBase32;
-use bip32::{DerivationPath, ExtendedPrivateKey, XPrv, XPub as ExtendedPublicKey};
+use bip32::XPub as ExtendedPublicKey;
 use core::convert::{From, Into, TryFrom};
-use digest::Digest;
-use ibc::core::{
-	ics02_client::height::Height,
-	ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes},
-	ics24_host::{
-		identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
-		IBC_QUERY_PATH,
+use ibc::{
+	applications::transfer::{Amount, BaseDenom, PrefixedCoin, PrefixedDenom, TracePath},
+	core::{
+		ics02_client::{height::Height, trust_threshold::TrustThreshold},
+		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes},
+		ics24_host::{
+			identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
+			IBC_QUERY_PATH,
+		},
 	},
 };
 use ibc_proto::{
-	cosmos::auth::v1beta1::{query_client::QueryClient, BaseAccount, QueryAccountRequest},
+	cosmos::{
+		auth::v1beta1::{query_client::QueryClient, BaseAccount, QueryAccountRequest},
+		bank::v1beta1::QueryBalanceRequest,
+		base::tendermint::v1beta1::{service_client::ServiceClient, AbciQueryRequest},
+	},
 	google::protobuf::Any,
 };
 use ics07_tendermint::{
@@ -27,20 +35,25 @@ use ics07_tendermint::{
 };
 use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState, HostFunctionsManager};
 use primitives::{
-	Chain, CommonClientConfig, CommonClientState, IbcProvider, KeyProvider, UpdateType,
+	signing::{check_signer_config, load_signing_keys, SignerConfig, SigningKey},
+	AdaptiveUpdateScheduler, CommonClientConfig, CommonClientState, FinalitySource, GasUsage,
+	IbcQuerier, KeyProvider, KeyRotation, PendingMessages, RelayedSequences, UpdateType,
 };
 use prost::Message;
 use quick_cache::sync::Cache;
 use rand::Rng;
-use ripemd::Ripemd160;
 use serde::{Deserialize, Serialize};
 use std::{
-	collections::HashSet,
+	collections::{HashMap, HashSet},
 	str::FromStr,
 	sync::{Arc, Mutex},
-	time::Duration,
+	time::{Duration, Instant},
+};
+use tendermint::{
+	block::Height as TmHeight,
+	merkle::proof::{ProofOp, ProofOps},
+	Hash,
 };
-use tendermint::{block::Height as TmHeight, Hash};
 use tendermint_light_client::components::io::{AtHeight, Io};
 use tendermint_light_client_verifier::types::{LightBlock, ValidatorSet};
 use tendermint_rpc::{endpoint::abci_query::AbciQuery, Client, HttpClient, Url, WebSocketClient};
@@ -53,11 +66,25 @@ use tokio::{
 const DEFAULT_FEE_DENOM: &str = "stake";
 const DEFAULT_FEE_AMOUNT: &str = "4000";
 const DEFAULT_GAS_LIMIT: u64 = (i64::MAX - 1) as u64;
+const DEFAULT_GAS_ADJUSTMENT: f64 = 1.1;
+const DEFAULT_MEMO: &str = "ibc";
+/// Number of packets relayed per batch when `max_packets_to_process` isn't configured
+pub const DEFAULT_MAX_PACKETS_TO_PROCESS: usize = 50;
+/// Path of the store the upgraded client and consensus states are stored in.
+pub const UPGRADE_QUERY_PATH: &str = "store/upgrade/key";
 
 fn default_gas_limit() -> u64 {
 	DEFAULT_GAS_LIMIT
 }
 
+fn default_gas_adjustment() -> f64 {
+	DEFAULT_GAS_ADJUSTMENT
+}
+
+fn default_memo() -> String {
+	DEFAULT_MEMO.to_string()
+}
+
 fn default_fee_denom() -> String {
 	DEFAULT_FEE_DENOM.to_string()
 }
@@ -66,6 +93,52 @@ fn default_fee_amount() -> String {
 	DEFAULT_FEE_AMOUNT.to_string()
 }
 
+/// Endpoint of the node that transactions are broadcast to or ABCI queries are sent to.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Transport {
+	/// The Tendermint RPC
+	#[default]
+	Rpc,
+	/// The grpc endpoint of the Cosmos SDK, falling back to the Tendermint RPC when a request
+	/// can't be served
+	Grpc,
+}
+
+/// How the headers of the client updates are verified by the `07-tendermint` client on the
+/// counterparty.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationMode {
+	/// Every header is verified against the latest consensus state of the client, skipping the
+	/// blocks in between as long as enough of the trusted validators signed the header
+	#[default]
+	Skipping,
+	/// Every header is verified against the header of the previous block, which is always
+	/// submitted. Needed by chains whose validator sets change too fast for skipping.
+	Sequential,
+}
+
+/// Extension option of the relayer transactions.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ExtensionOptionConfig {
+	/// Type url of the option, e.g. `/ethermint.types.v1.ExtensionOptionsWeb3Tx`
+	pub type_url: String,
+	/// Hex encoded protobuf value of the option
+	pub value: String,
+}
+
+impl TryFrom<&ExtensionOptionConfig> for Any {
+	type Error = Error;
+
+	fn try_from(option: &ExtensionOptionConfig) -> Result<Self, Self::Error> {
+		let value = hex::decode(option.value.trim_start_matches("0x")).map_err(|e| {
+			Error::Custom(format!("Invalid value of extension option {}: {e}", option.type_url))
+		})?;
+		Ok(Any { type_url: option.type_url.clone(), value })
+	}
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ConfigKeyEntry {
 	pub public_key: String,
@@ -79,8 +152,9 @@ impl TryFrom<ConfigKeyEntry> for KeyEntry {
 
 	fn try_from(value: ConfigKeyEntry) -> Result<Self, Self::Error> {
 		Ok(KeyEntry {
-			public_key: ExtendedPublicKey::from_str(&value.public_key)?,
-			private_key: ExtendedPrivateKey::from_str(&value.private_key)?,
+			public_key: ExtendedPublicKey::from_str(&value.public_key)?.to_bytes().to_vec(),
+			public_key_type_url: KeyType::Secp256k1.public_key_type_url().to_string(),
+			signer: Arc::new(LocalSigner::from_str(&value.private_key)?),
 			account: value.account,
 			address: value.address,
 		})
@@ -88,37 +162,32 @@ impl TryFrom<ConfigKeyEntry> for KeyEntry {
 }
 
 impl TryFrom<MnemonicEntry> for KeyEntry {
-	type Error = bip32::Error;
+	type Error = Error;
 
 	fn try_from(mnemonic_entry: MnemonicEntry) -> Result<Self, Self::Error> {
-		// From mnemonic to pubkey
-		let mnemonic =
-			bip39::Mnemonic::from_phrase(&mnemonic_entry.mnemonic, bip39::Language::English)
-				.unwrap();
-		let seed = bip39::Seed::new(&mnemonic, "");
-		let key_m = XPrv::derive_from_path(seed, &DerivationPath::from_str("m/44'/118'/0'/0/0")?)?;
-
-		// From pubkey to address
-		let sha256 = sha2::Sha256::digest(key_m.public_key().to_bytes());
-		let public_key_hash: [u8; 20] = Ripemd160::digest(sha256).into();
-		let account = bech32::encode(
+		let signer = LocalSigner::from_mnemonic_with_key_type(
+			&mnemonic_entry.mnemonic,
+			mnemonic_entry.key_type,
+		)
+		.map_err(|e| Error::from(format!("Invalid mnemonic: {e}")))?;
+		KeyEntry::new(
+			Arc::new(signer),
 			&mnemonic_entry.prefix,
-			public_key_hash.to_base32(),
-			bech32::Variant::Bech32,
+			mnemonic_entry.key_type,
+			mnemonic_entry.public_key_type_url.as_deref(),
 		)
-		.unwrap();
-		Ok(KeyEntry {
-			public_key: key_m.public_key(),
-			private_key: key_m,
-			account,
-			address: public_key_hash.into(),
-		})
+		.map_err(|e| Error::from(format!("Invalid account prefix: {e}")))
 	}
 }
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MnemonicEntry {
 	pub mnemonic: String,
 	pub prefix: String,
+	#[serde(default)]
+	pub key_type: KeyType,
+	#[serde(default)]
+	pub public_key_type_url: Option<String>,
 }
 // Implements the [`crate::Chain`] trait for cosmos.
 /// This is responsible for:
@@ -131,16 +200,18 @@ pub struct CosmosClient<H> {
 	pub name: String,
 	/// Chain websocket rpc client
 	pub rpc_ws_client: Option<WebSocketClient>,
-	/// Chain http rpc client
-	pub rpc_http_client: HttpClient,
-	/// Reusable GRPC client
-	pub grpc_client: Option<tonic::transport::Channel>,
+	/// Nodes of the chain, with their http rpc and grpc clients
+	pub endpoints: Endpoints,
+	/// Archive node http rpc client, used for queries at pruned heights
+	pub archive_rpc_http_client: Option<HttpClient>,
 	/// Chain rpc address
 	pub rpc_url: Url,
-	/// Chain grpc address
-	pub grpc_url: Option<Url>,
-	/// Websocket chain ws client
-	pub websocket_url: Option<Url>,
+	/// Archive node rpc address
+	pub archive_rpc_url: Option<Url>,
+	/// Endpoint transactions are broadcast to
+	pub broadcast_transport: Transport,
+	/// Endpoint ABCI queries are sent to
+	pub query_transport: Transport,
 	/// Chain Id
 	pub chain_id: ChainId,
 	/// Light client id on counterparty chain
@@ -153,6 +224,9 @@ pub struct CosmosClient<H> {
 	pub light_client: LightClient,
 	/// The key that signs transactions
 	pub keybase: KeyEntry,
+	/// All the keys that sign transactions, in the order of the [`primitives::KeyRotation`]. The
+	/// first one is `keybase`.
+	pub signing_keys: Vec<KeyEntry>,
 	/// Account prefix
 	pub account_prefix: String,
 	/// Reference to commitment
@@ -161,15 +235,32 @@ pub struct CosmosClient<H> {
 	pub fee_denom: String,
 	/// Fee amount
 	pub fee_amount: String,
-	/// Fee amount
+	/// Maximum gas of a transaction
 	pub gas_limit: u64,
+	/// Multiplier of the simulated gas of a transaction
+	pub gas_adjustment: f64,
+	/// Minimum gas of the messages, by type url
+	pub message_gas_floors: HashMap<String, u64>,
+	/// Account paying the transaction fees through the feegrant module
+	pub fee_granter: Option<String>,
+	/// Account the relayed messages are executed on behalf of through authz grants
+	pub authz_granter: Option<String>,
+	/// Verification of the headers of the client updates
+	pub verification_mode: VerificationMode,
+	/// Trust threshold of the clients created for the chain
+	pub trust_threshold: TrustThreshold,
+	/// Memo of the transactions
+	pub memo: String,
+	/// Number of blocks after the latest one a transaction expires at
+	pub timeout_height_offset: Option<u64>,
+	/// Extension options of the transactions
+	pub extension_options: Vec<Any>,
+	/// Non-critical extension options of the transactions
+	pub non_critical_extension_options: Vec<Any>,
 	/// Maximun transaction size
 	pub max_tx_size: usize,
 	/// Finality protocol to use, eg Tenderminet
 	pub _phantom: std::marker::PhantomData<H>,
-	/// Mutex used to sequentially send transactions. This is necessary because
-	/// account sequence numbers are not updated until the transaction is processed.
-	pub tx_mutex: Arc<tokio::sync::Mutex<()>>,
 	/// Light-client blocks cache
 	pub light_block_cache: Arc<Cache<TmHeight, LightBlock>>,
 	/// Relayer data
@@ -185,10 +276,30 @@ pub struct CosmosClientConfig {
 	pub name: String,
 	/// rpc url for cosmos
 	pub rpc_url: Url,
+	/// rpc url for a cosmos archive node. Proofs at heights that were pruned on the `rpc_url`
+	/// node are queried from it.
+	#[serde(default)]
+	pub archive_rpc_url: Option<Url>,
 	/// grpc url for cosmos
 	pub grpc_url: Option<Url>,
+	/// Endpoint transactions are broadcast to. Transactions that can't be broadcast over grpc are
+	/// sent to the Tendermint RPC instead.
+	#[serde(default)]
+	pub broadcast_transport: Transport,
+	/// Endpoint ABCI queries, and their proofs, are sent to. Queries that can't be served over
+	/// grpc are sent to the Tendermint RPC instead.
+	#[serde(default)]
+	pub query_transport: Transport,
 	/// websocket url for cosmos
 	pub websocket_url: Option<Url>,
+	/// Further nodes of the chain, used when the main endpoint fails or is slower than
+	/// `max_endpoint_latency`
+	#[serde(default)]
+	pub fallback_endpoints: Vec<EndpointConfig>,
+	/// Average latency, in milliseconds, above which an endpoint is only used if all the others
+	/// are slower or failing
+	#[serde(default)]
+	pub max_endpoint_latency: Option<u64>,
 	/// Cosmos chain Id
 	pub chain_id: String,
 	/// Light client id on counterparty chain
@@ -197,15 +308,61 @@ pub struct CosmosClientConfig {
 	pub connection_id: Option<ConnectionId>,
 	/// Account prefix
 	pub account_prefix: String,
+	/// Kind of the relayer keys, `eth_secp256k1` for Ethermint chains such as Evmos
+	#[serde(default)]
+	pub key_type: KeyType,
+	/// Type url of the public keys of the relayer in the transactions, if the chain doesn't use
+	/// the default one of the `key_type`, e.g. `/injective.crypto.v1beta1.ethsecp256k1.PubKey`
+	#[serde(default)]
+	pub public_key_type_url: Option<String>,
 	/// Fee denom
 	#[serde(default = "default_fee_denom")]
 	pub fee_denom: String,
 	/// Fee amount
 	#[serde(default = "default_fee_amount")]
 	pub fee_amount: String,
-	/// Fee amount
+	/// Hard cap on the gas of a transaction. Transactions whose simulation uses more gas are not
+	/// submitted.
 	#[serde(default = "default_gas_limit")]
 	pub gas_limit: u64,
+	/// Multiplier applied to the simulated gas of a transaction to set its gas limit, as a margin
+	/// for state changes between the simulation and the execution. Defaults to 1.1.
+	#[serde(default = "default_gas_adjustment")]
+	pub gas_adjustment: f64,
+	/// Minimum gas of the messages, by type url, e.g. for messages whose simulation is known to
+	/// underestimate their execution. The gas limit of a transaction is at least the sum of the
+	/// floors of its messages.
+	#[serde(default)]
+	pub message_gas_floors: HashMap<String, u64>,
+	/// Account whose feegrant allowance pays the fees of the relayer transactions, so that the
+	/// relayer keys don't need to be funded
+	#[serde(default)]
+	pub fee_granter: Option<String>,
+	/// Account the relayer acts on behalf of through the authz module. The relayed messages name
+	/// it as their signer and are wrapped in a `MsgExec` of the relayer keys, which only need
+	/// grants for the IBC message types.
+	#[serde(default)]
+	pub authz_granter: Option<String>,
+	/// Verification of the headers of the client updates, `skipping` by default
+	#[serde(default)]
+	pub verification_mode: VerificationMode,
+	/// Fraction of the voting power of the trusted validators that must sign a header for the
+	/// clients created for the chain to accept it, 1/3 by default
+	#[serde(default)]
+	pub trust_threshold: Option<TrustThreshold>,
+	/// Memo of the relayer transactions, e.g. to identify them in explorers. Defaults to `ibc`.
+	#[serde(default = "default_memo")]
+	pub memo: String,
+	/// Number of blocks after the latest one at submission time that a transaction can be
+	/// included in, so that transactions stuck in the mempool expire instead of landing late
+	#[serde(default)]
+	pub timeout_height_offset: Option<u64>,
+	/// Extension options of the relayer transactions, required by some chains
+	#[serde(default)]
+	pub extension_options: Vec<ExtensionOptionConfig>,
+	/// Extension options of the relayer transactions that the chain may ignore
+	#[serde(default)]
+	pub non_critical_extension_options: Vec<ExtensionOptionConfig>,
 	/// Store prefix
 	pub store_prefix: String,
 	/// Maximun transaction size
@@ -222,26 +379,79 @@ pub struct CosmosClientConfig {
 	pub default_gas: Option<u64>,	  			// TODO: Could be set to `0` by default
 	pub max_gas: Option<u64>,                   // TODO: DEFAULT_MAX_GAS: u64 = 400_000
 	pub gas_multiplier: Option<GasMultiplier>,  // TODO: Could be set to `1.1` by default
-	pub fee_granter: Option<String>,            // TODO: DEFAULT_FEE_GRANTER: &str = ""
 	pub max_msg_num: MaxMsgNum,                 // TODO: Default is 30, Could be set usize = 1 for test
 												// TODO: Could be set to const MAX_LEN: usize = 50;
 	pub proof_specs: Option<ProofSpecs>,        // TODO: Could be set to None
 	pub sequential_batch_tx: bool,			    // TODO: sequential_send_batched_messages_and_wait_commit() or send_batched_messages_and_wait_commit() ?
-	pub trust_threshold: TrustThreshold,
 	pub gas_price: GasPrice,   				    // TODO: Could be set to `0`
 	pub packet_filter: PacketFilter,            // TODO: AllowAll
 	pub address_type: AddressType,			    // TODO: Type = cosmos
-	pub extension_options: Vec<ExtensionOption>,// TODO: Could be set to None
 	*/
 	/// Whitelisted channels
 	pub channel_whitelist: Vec<(ChannelId, PortId)>,
-	/// The key that signs transactions
+	/// The key that signs transactions, unless `signer` is set
+	#[serde(default)]
 	pub mnemonic: String,
+	/// Mnemonics of further funded accounts. Transactions are signed with `mnemonic` and these
+	/// keys in turn.
+	#[serde(default)]
+	pub additional_mnemonics: Vec<String>,
+	/// Backend holding the relayer keys, instead of the raw `mnemonic` and
+	/// `additional_mnemonics`
+	#[serde(default)]
+	pub signer: Option<SignerConfig>,
 	/// Common client config
 	#[serde(flatten)]
 	pub common: CommonClientConfig,
-	/// Skip transfer packets with the following tokens base denoms
-	pub skip_tokens_list: Option<Vec<String>>,
+}
+
+impl CosmosClientConfig {
+	/// The main endpoint of the chain, followed by the `fallback_endpoints`.
+	pub fn endpoint_configs(&self) -> impl Iterator<Item = EndpointConfig> + '_ {
+		let main = EndpointConfig {
+			rpc_url: self.rpc_url.clone(),
+			grpc_url: self.grpc_url.clone(),
+			websocket_url: self.websocket_url.clone(),
+		};
+		std::iter::once(main).chain(self.fallback_endpoints.iter().cloned())
+	}
+
+	/// Checks the parameters, store prefix and relayer keys of the config without connecting to
+	/// the chain.
+	pub fn validate(&self) -> Result<(), Error> {
+		self.common
+			.validate()
+			.map_err(|e| Error::Custom(format!("Invalid params of {}: {e}", self.name)))?;
+		if self.store_prefix.is_empty() {
+			return Err(Error::Custom(format!("The store prefix of {} is empty", self.name)))
+		}
+		if !self.gas_adjustment.is_finite() || self.gas_adjustment <= 0.0 {
+			return Err(Error::Custom(format!(
+				"The gas adjustment of {} must be positive, got {}",
+				self.name, self.gas_adjustment
+			)))
+		}
+		if self.endpoint_configs().any(|endpoint| endpoint.grpc_url.is_none()) &&
+			(self.broadcast_transport == Transport::Grpc ||
+				self.query_transport == Transport::Grpc)
+		{
+			return Err(Error::Custom(format!(
+				"The grpc transport of {} needs a grpc_url on every endpoint",
+				self.name
+			)))
+		}
+		if let Some(trust_threshold) = self.trust_threshold {
+			check_trust_threshold(trust_threshold).map_err(|e| {
+				Error::Custom(format!("Invalid trust threshold of {}: {e}", self.name))
+			})?;
+		}
+		self.extension_options
+			.iter()
+			.chain(&self.non_critical_extension_options)
+			.try_for_each(|option| Any::try_from(option).map(drop))?;
+		check_signer_config(self.signer.as_ref(), &self.mnemonic, &self.additional_mnemonics)
+			.map_err(|e| Error::Custom(format!("Invalid keys of {}: {e}", self.name)))
+	}
 }
 
 impl<H> CosmosClient<H>
@@ -251,6 +461,7 @@ where
 {
 	/// Initializes a [`CosmosClient`] given a [`CosmosClientConfig`]
 	pub async fn new(config: CosmosClientConfig) -> Result<Self, Error> {
+		config.validate()?;
 		let mut rpc_client = None;
 
 		let mut join_handles = vec![];
@@ -264,8 +475,10 @@ where
 		} else {
 			log::warn!(target: "hyperspace_cosmos", "No websocket url provided for cosmos chain");
 		}
-		let rpc_http_client = HttpClient::new(config.rpc_url.clone())
-			.map_err(|e| Error::RpcError(format!("failed to connect to RPC {:?}", e)))?;
+		let archive_rpc_http_client =
+			config.archive_rpc_url.clone().map(HttpClient::new).transpose().map_err(|e| {
+				Error::RpcError(format!("failed to connect to archive RPC {:?}", e))
+			})?;
 		let mut grpc_client = None;
 		if let Some(grpc_url) = &config.grpc_url {
 			grpc_client = tonic::transport::Endpoint::new(grpc_url.to_string())
@@ -277,6 +490,19 @@ where
 		} else {
 			log::warn!(target: "hyperspace_cosmos", "No grpc url provided for cosmos chain");
 		}
+		let mut endpoints = config
+			.endpoint_configs()
+			.enumerate()
+			.map(|(index, endpoint)| Endpoint::new(index, endpoint))
+			.collect::<Result<Vec<_>, _>>()?;
+		// the main grpc endpoint is connected eagerly, so that a wrong url is reported
+		endpoints[0].grpc_client = grpc_client;
+		let endpoints =
+			Endpoints::new(endpoints, config.max_endpoint_latency.map(Duration::from_millis));
+		if endpoints.len() > 1 {
+			let health_checks = endpoints.clone().run_health_checks();
+			join_handles.push(tokio::spawn(async move { Ok(health_checks.await) }));
+		}
 
 		let chain_id = ChainId::from(config.chain_id);
 		let light_client =
@@ -284,22 +510,50 @@ where
 		let commitment_prefix = CommitmentPrefix::try_from(config.store_prefix.as_bytes().to_vec())
 			.map_err(|e| Error::from(format!("Invalid store prefix {:?}", e)))?;
 
-		let keybase: KeyEntry = KeyEntry::try_from(MnemonicEntry {
-			mnemonic: config.mnemonic,
-			prefix: config.account_prefix.clone(),
+		let prefix = &config.account_prefix;
+		let signing_keys = load_signing_keys(
+			config.signer.as_ref(),
+			&config.mnemonic,
+			&config.additional_mnemonics,
+			"secp256k1",
+		)
+		.await
+		.map_err(|e| Error::Custom(format!("Failed to load the keys of {}: {e}", config.name)))?
+		.into_iter()
+		.map(|key| match key {
+			SigningKey::Secret(mnemonic) => KeyEntry::try_from(MnemonicEntry {
+				mnemonic,
+				prefix: prefix.clone(),
+				key_type: config.key_type,
+				public_key_type_url: config.public_key_type_url.clone(),
+			}),
+			// remote signers sign the sha-256 hash of the payloads
+			SigningKey::Remote(_) if config.key_type == KeyType::EthSecp256k1 => Err(Error::from(
+				format!("Remote signers don't support the keys of {}", config.name),
+			)),
+			SigningKey::Remote(signer) => KeyEntry::new(
+				Arc::new(signer),
+				prefix,
+				config.key_type,
+				config.public_key_type_url.as_deref(),
+			)
+			.map_err(|e| Error::from(format!("Invalid account prefix: {e}"))),
 		})
-		.map_err(|e| e.to_string())?;
+		.collect::<Result<Vec<_>, _>>()?;
+		let keybase = signing_keys[0].clone();
+		let keys = KeyRotation::new(signing_keys.len(), config.common.max_concurrent_submissions);
 
 		let rpc_call_delay = Duration::from_millis(1000);
-		Ok(Self {
+		let client = Self {
 			name: config.name,
 			chain_id,
 			rpc_ws_client: rpc_client,
-			rpc_http_client,
-			grpc_client,
+			endpoints,
+			archive_rpc_http_client,
 			rpc_url: config.rpc_url,
-			grpc_url: config.grpc_url,
-			websocket_url: config.websocket_url,
+			archive_rpc_url: config.archive_rpc_url,
+			broadcast_transport: config.broadcast_transport,
+			query_transport: config.query_transport,
 			client_id: Arc::new(Mutex::new(config.client_id)),
 			connection_id: Arc::new(Mutex::new(config.connection_id)),
 			channel_whitelist: Arc::new(Mutex::new(config.channel_whitelist.into_iter().collect())),
@@ -309,10 +563,28 @@ where
 			fee_denom: config.fee_denom,
 			fee_amount: config.fee_amount,
 			gas_limit: config.gas_limit,
+			gas_adjustment: config.gas_adjustment,
+			message_gas_floors: config.message_gas_floors,
+			fee_granter: config.fee_granter,
+			authz_granter: config.authz_granter,
+			verification_mode: config.verification_mode,
+			trust_threshold: config.trust_threshold.unwrap_or_default(),
+			memo: config.memo,
+			timeout_height_offset: config.timeout_height_offset,
+			extension_options: config
+				.extension_options
+				.iter()
+				.map(Any::try_from)
+				.collect::<Result<_, _>>()?,
+			non_critical_extension_options: config
+				.non_critical_extension_options
+				.iter()
+				.map(Any::try_from)
+				.collect::<Result<_, _>>()?,
 			max_tx_size: config.max_tx_size,
 			keybase,
+			signing_keys,
 			_phantom: std::marker::PhantomData,
-			tx_mutex: Default::default(),
 			light_block_cache: Arc::new(Cache::new(100000)),
 			common_state: CommonClientState {
 				skip_optional_client_updates: config.common.skip_optional_client_updates,
@@ -320,23 +592,78 @@ where
 				rpc_call_delay,
 				initial_rpc_call_delay: rpc_call_delay,
 				misbehaviour_client_msg_queue: Arc::new(AsyncMutex::new(vec![])),
-				max_packets_to_process: config.common.max_packets_to_process as usize,
-				skip_tokens_list: config.skip_tokens_list.unwrap_or_default(),
+				max_packets_to_process: config
+					.common
+					.max_packets_to_process_or(DEFAULT_MAX_PACKETS_TO_PROCESS),
+				skip_tokens_list: config.common.skip_tokens_list.unwrap_or_default(),
+				client_update_period: config.common.client_update_period,
+				confirmation_depth: config.common.confirmation_depth,
+				confirmations: Default::default(),
+				optional_update_window: config
+					.common
+					.optional_update_window
+					.map(Duration::from_secs),
+				update_coalescer: Default::default(),
+				adaptive_updates: Arc::new(Mutex::new(
+					config.common.adaptive_client_updates.clone().map(AdaptiveUpdateScheduler::new),
+				)),
+				security: config.common.security.clone(),
+				counterparty_payee: config.common.counterparty_payee.clone(),
+				event_filter: config.common.event_filter.clone(),
+				incentivized_packets: Default::default(),
+				skipped_stream_items: Default::default(),
+				disconnects: Default::default(),
+				dry_run: false,
+				relay_status: Default::default(),
+				paused_channels: Default::default(),
+				force_client_update: Default::default(),
+				halted_channels: Default::default(),
+				min_balance: Arc::new(Mutex::new(config.common.min_balance)),
+				low_balance: Default::default(),
+				stall_reason: Default::default(),
+				relayed_sequences: Arc::new(Mutex::new(RelayedSequences::new(
+					config.common.relayed_sequences_retention,
+					config.common.relayed_sequences_path.clone(),
+				))),
+				pending_messages: Arc::new(Mutex::new(PendingMessages::new(
+					config.common.pending_messages_path.clone(),
+				))),
+				receipts: None,
+				ledger: None,
+				keys,
+				connection_delays: Default::default(),
+				pipeline_counters: Default::default(),
+				pending_timeouts: Default::default(),
+				gas_usage: Default::default(),
 			},
 			join_handles: Arc::new(TokioMutex::new(join_handles)),
-		})
+		};
+		if client.authz_granter.is_some() {
+			let grants = client.clone().watch_grants();
+			client
+				.join_handles
+				.lock()
+				.await
+				.push(tokio::spawn(async move { Ok(grants.await) }));
+		}
+		Ok(client)
+	}
+
+	/// Http rpc client of the active endpoint
+	pub fn rpc_http_client(&self) -> HttpClient {
+		self.endpoints.active().rpc_client
 	}
 
 	pub fn grpc_url(&self) -> Url {
-		self.grpc_url.clone().expect("grpc url is not set")
+		self.endpoints.active().grpc_url.expect("grpc url is not set")
 	}
 
 	pub fn websocket_url(&self) -> Url {
-		self.websocket_url.clone().expect("rpc url is not set")
+		self.endpoints.active().websocket_url.expect("rpc url is not set")
 	}
 
-	pub fn grpc_client(&self) -> &tonic::transport::Channel {
-		self.grpc_client.as_ref().expect("grpc client is not set")
+	pub fn grpc_client(&self) -> tonic::transport::Channel {
+		self.endpoints.active().grpc_client.expect("grpc client is not set")
 	}
 
 	pub fn rpc_ws_client(&self) -> WebSocketClient {
@@ -356,12 +683,56 @@ where
 		*self.client_id.lock().unwrap() = Some(client_id);
 	}
 
+	/// Applies the parts of a reloaded [`CosmosClientConfig`] that can be changed without
+	/// reconnecting: channel whitelist, fee settings and common relayer parameters.
+	pub fn reload_config(&mut self, config: CosmosClientConfig) -> Result<(), Error> {
+		if config.chain_id != self.chain_id.to_string() ||
+			config.rpc_url != self.rpc_url ||
+			config.archive_rpc_url != self.archive_rpc_url
+		{
+			return Err(Error::Custom(format!(
+				"Chain id and rpc urls of {} cannot be changed without a restart",
+				self.name
+			)))
+		}
+		config
+			.common
+			.validate()
+			.map_err(|e| Error::Custom(format!("Invalid params of {}: {e}", config.name)))?;
+		if let Some(trust_threshold) = config.trust_threshold {
+			check_trust_threshold(trust_threshold).map_err(|e| {
+				Error::Custom(format!("Invalid trust threshold of {}: {e}", config.name))
+			})?;
+		}
+		let extension_options =
+			config.extension_options.iter().map(Any::try_from).collect::<Result<_, _>>()?;
+		let non_critical_extension_options = config
+			.non_critical_extension_options
+			.iter()
+			.map(Any::try_from)
+			.collect::<Result<_, _>>()?;
+		*self.channel_whitelist.lock().unwrap() = config.channel_whitelist.into_iter().collect();
+		self.fee_denom = config.fee_denom;
+		self.fee_amount = config.fee_amount;
+		self.gas_limit = config.gas_limit;
+		self.fee_granter = config.fee_granter;
+		self.verification_mode = config.verification_mode;
+		self.trust_threshold = config.trust_threshold.unwrap_or_default();
+		self.memo = config.memo;
+		self.timeout_height_offset = config.timeout_height_offset;
+		self.extension_options = extension_options;
+		self.non_critical_extension_options = non_critical_extension_options;
+		self.max_tx_size = config.max_tx_size;
+		self.common_state.apply_config(&config.common, DEFAULT_MAX_PACKETS_TO_PROCESS);
+		Ok(())
+	}
+
 	/// Construct a tendermint client state to be submitted to the counterparty chain
 	pub async fn construct_tendermint_client_state(
 		&self,
 	) -> Result<(ClientState<HostFunctionsManager>, ConsensusState), Error>
 	where
-		Self: KeyProvider + IbcProvider,
+		Self: KeyProvider + FinalitySource,
 		H: Clone + Send + Sync + 'static,
 	{
 		let (client_state, consensus_state) =
@@ -383,31 +754,117 @@ where
 		}
 	}
 
+	/// Memo, extension options and timeout of the relayer transactions, which expire after
+	/// `timeout_height` if it's not 0.
+	pub fn tx_body_options(&self, timeout_height: u64) -> TxBodyOptions {
+		TxBodyOptions {
+			memo: self.memo.clone(),
+			timeout_height,
+			extension_options: self.extension_options.clone(),
+			non_critical_extension_options: self.non_critical_extension_options.clone(),
+		}
+	}
+
+	/// Height after which a transaction submitted now expires, or 0 if transactions don't expire.
+	async fn tx_timeout_height(&self) -> Result<u64, Error> {
+		let Some(offset) = self.timeout_height_offset else { return Ok(0) };
+		let abci_info = self
+			.rpc_http_client()
+			.abci_info()
+			.await
+			.map_err(|e| Error::RpcError(format!("{e:?}")))?;
+		Ok(u64::from(abci_info.last_block_height) + offset)
+	}
+
+	/// Signs the messages with the next key of the chain's [`KeyRotation`], broadcasts them and
+	/// waits for the transaction to be committed.
 	pub async fn submit_call(&self, messages: Vec<Any>) -> Result<Hash, Error> {
-		let _lock = self.tx_mutex.lock().await;
-		let account_info = self.query_account().await?;
+		// the messages name the primary key as their signer, they're only signed with another key
+		// if all their signers can be replaced, unless they're executed on behalf of the authz
+		// granter
+		let key = self.common_state.keys.next_key();
+		let resigned = (key != 0 && self.authz_granter.is_none())
+			.then(|| {
+				messages
+					.iter()
+					.map(|msg| with_signer(msg, &self.signing_keys[key].account))
+					.collect::<Option<Vec<_>>>()
+			})
+			.flatten();
+		let (key, messages) = match resigned {
+			Some(messages) => (key, messages),
+			None if self.authz_granter.is_some() => (key, messages),
+			None => (0, messages),
+		};
+		let signing_key = &self.signing_keys[key];
+		let tx_messages = match self.authz_granter {
+			Some(_) => vec![msg_exec(&signing_key.account, messages.clone())],
+			None => messages.clone(),
+		};
+
+		let mut slot = self.common_state.keys.submissions(key).begin().await;
+		let mut account_info = self.query_account_of(&signing_key.account).await?;
+		// the account sequence is only updated once the transactions are committed, so the
+		// sequences of the transactions in flight are tracked by the coordinator
+		account_info.sequence = slot.reserve_nonce(account_info.sequence);
+		let options = self.tx_body_options(self.tx_timeout_height().await?);
 
-		// Sign transaction
+		// Simulate transaction, signed with the maximum gas
 		let (tx, _, tx_bytes) = sign_tx(
-			self.keybase.clone(),
+			signing_key.clone(),
 			self.chain_id.clone(),
 			&account_info,
-			messages,
+			tx_messages.clone(),
 			self.get_fee(),
+			options.clone(),
 		)?;
-
-		// Simulate transaction
-		let res = simulate_tx(self.grpc_url(), tx, tx_bytes.clone()).await?;
+		let res = simulate_tx(self.grpc_url(), tx, tx_bytes).await?;
 		res.result
 			.map(|r| log::debug!(target: "hyperspace_cosmos", "Simulated transaction: events: {:?}\nlogs: {}", r.events, r.log));
+		let simulated = res
+			.gas_info
+			.ok_or_else(|| Error::from("Simulation returned no gas info".to_string()))?
+			.gas_used;
+		let gas = self.adjusted_gas(&messages, simulated)?;
+
+		// Sign transaction with the adjusted gas
+		let (_, _, tx_bytes) = sign_tx(
+			signing_key.clone(),
+			self.chain_id.clone(),
+			&account_info,
+			tx_messages,
+			self.fee(gas),
+			options,
+		)?;
 
 		// Broadcast transaction
-		let client = &self.rpc_ws_client();
-		let hash = broadcast_tx(client, tx_bytes).await?;
-		log::info!(target: "hyperspace_cosmos", "🤝 Transaction sent with hash: {:?}", hash);
+		let hash = self.broadcast(tx_bytes).await?;
+		log::info!(target: "hyperspace_cosmos", "🤝 Transaction sent with hash: {:?}, gas limit: {gas}", hash);
 
 		// wait for confirmation
-		confirm_tx(client, hash).await
+		let response = confirm_tx(&self.rpc_ws_client(), hash).await?;
+		slot.confirm();
+		let used = u64::try_from(response.tx_result.gas_used).unwrap_or_default();
+		self.common_state.record_gas_usage(GasUsage { simulated, used });
+		Ok(response.hash)
+	}
+
+	/// Broadcasts `tx_bytes` over the `broadcast_transport`, falling back to the Tendermint RPC if
+	/// the grpc endpoint can't serve the request.
+	async fn broadcast(&self, tx_bytes: Vec<u8>) -> Result<Hash, Error> {
+		let endpoint = self.endpoints.active();
+		if let (Transport::Grpc, Some(grpc_client)) =
+			(self.broadcast_transport, endpoint.grpc_client)
+		{
+			match broadcast_tx_grpc(grpc_client, tx_bytes.clone()).await {
+				Err(Error::GrpcError(e)) => {
+					log::warn!(target: "hyperspace_cosmos", "Failed to broadcast over grpc on {}, falling back to the rpc: {e}", self.name);
+					self.endpoints.on_failure(endpoint.index, &e);
+				},
+				result => return result,
+			}
+		}
+		broadcast_tx(&self.rpc_ws_client(), tx_bytes).await
 	}
 
 	pub async fn fetch_light_block_with_cache(
@@ -444,6 +901,12 @@ where
 				let client = client.clone();
 				let duration =
 					Duration::from_millis(rand::thread_rng().gen_range(0..delay_to) as u64);
+				// in sequential mode, every header is verified against the previous one
+				let trusted_height = match client.verification_mode {
+					VerificationMode::Skipping => trusted_height,
+					VerificationMode::Sequential =>
+						Height::new(trusted_height.revision_number, height - 1).max(trusted_height),
+				};
 				let fut = async move {
 					log::trace!(target: "hyperspace_cosmos", "Fetching header at height {:?}", height);
 					let latest_light_block =
@@ -492,12 +955,15 @@ where
 
 	/// Uses the GRPC client to retrieve the account sequence
 	pub async fn query_account(&self) -> Result<BaseAccount, Error> {
+		self.query_account_of(&self.keybase.account).await
+	}
+
+	pub async fn query_account_of(&self, address: &str) -> Result<BaseAccount, Error> {
 		let mut client = QueryClient::connect(self.grpc_url().to_string())
 			.await
 			.map_err(|e| Error::from(format!("GRPC client error: {:?}", e)))?;
 
-		let request =
-			tonic::Request::new(QueryAccountRequest { address: self.keybase.account.to_string() });
+		let request = tonic::Request::new(QueryAccountRequest { address: address.to_string() });
 
 		let response = client.account(request).await;
 
@@ -508,8 +974,44 @@ where
 				None => return Err(Error::from(format!("Account not found"))),
 			};
 
-		Ok(BaseAccount::decode(resp_account.value.as_slice())
-			.map_err(|e| Error::from(format!("Failed to decode account {}", e)))?)
+		base_account(resp_account)
+	}
+
+	/// Queries the balance of `address` in `denom`.
+	pub async fn query_balance_of(
+		&self,
+		address: &str,
+		denom: &str,
+	) -> Result<PrefixedCoin, Error> {
+		let mut grpc_client = ibc_proto::cosmos::bank::v1beta1::query_client::QueryClient::connect(
+			self.grpc_url().to_string(),
+		)
+		.await
+		.map_err(|e| Error::from(format!("{e:?}")))?;
+
+		let request = tonic::Request::new(QueryBalanceRequest {
+			address: address.to_string(),
+			denom: denom.to_string(),
+		});
+
+		let response = grpc_client
+			.balance(request)
+			.await
+			.map(|r| r.into_inner())
+			.map_err(|e| Error::from(format!("{e:?}")))?;
+
+		// Querying for a balance might fail, i.e. if the account doesn't actually exist
+		let balance = response
+			.balance
+			.ok_or_else(|| Error::from(format!("No balance for denom {denom}")))?;
+
+		Ok(PrefixedCoin {
+			denom: PrefixedDenom {
+				trace_path: TracePath::default(),
+				base_denom: BaseDenom::from_str(denom)?,
+			},
+			amount: Amount::from_str(balance.amount.as_str())?,
+		})
 	}
 
 	pub async fn query_path(
@@ -518,7 +1020,18 @@ where
 		height_query: Height,
 		prove: bool,
 	) -> Result<(AbciQuery, Vec<u8>), Error> {
-		let path = IBC_QUERY_PATH;
+		self.query_store_path(IBC_QUERY_PATH, data, height_query, prove).await
+	}
+
+	/// Queries the key `data` of the store at `path`, e.g. `store/ibc/key`, with its proof if
+	/// `prove` is set.
+	pub async fn query_store_path(
+		&self,
+		path: &str,
+		data: Vec<u8>,
+		height_query: Height,
+		prove: bool,
+	) -> Result<(AbciQuery, Vec<u8>), Error> {
 		let height = TmHeight::try_from(height_query.revision_height)
 			.map_err(|e| Error::from(format!("Invalid height {}", e)))?;
 
@@ -527,14 +1040,23 @@ where
 			_ => Some(height),
 		};
 
-		// Use the Tendermint-rs RPC client to do the query.
-		let response = self
-			.rpc_http_client
-			.abci_query(Some(path.to_owned()), data.clone(), height, prove)
-			.await
-			.map_err(|e| {
-				Error::from(format!("Failed to query chain {} with error {:?}", self.name, e))
-			})?;
+		let mut response = self.abci_query(path, data.clone(), height, prove).await?;
+
+		// The node may have pruned the state at this height, try the archive node instead
+		if let Some(archive_rpc_http_client) = &self.archive_rpc_http_client {
+			if !response.code.is_ok() && is_pruned_state_error(&response.log) {
+				log::debug!(target: "hyperspace_cosmos", "State is pruned on {}, querying the archive node: {}", self.name, response.log);
+				response = archive_rpc_http_client
+					.abci_query(Some(path.to_owned()), data.clone(), height, prove)
+					.await
+					.map_err(|e| {
+						Error::from(format!(
+							"Failed to query archive node of chain {} with error {:?}",
+							self.name, e
+						))
+					})?;
+			}
+		}
 
 		if !response.code.is_ok() {
 			// Fail with response log.
@@ -563,6 +1085,122 @@ where
 			.map_err(|err| Error::Custom(format!("bad client state proof: {}", err)))?;
 		Ok((response, proof.into()))
 	}
+
+	/// Sends the ABCI query of the key `data` of the store at `path` over the `query_transport`,
+	/// falling back to the Tendermint RPC if the grpc endpoint can't serve the request. Queries
+	/// at a height are sent to the endpoint pinned for that height.
+	pub(crate) async fn abci_query(
+		&self,
+		path: &str,
+		data: Vec<u8>,
+		height: Option<TmHeight>,
+		prove: bool,
+	) -> Result<AbciQuery, Error> {
+		let endpoint = match height {
+			Some(height) => self.endpoints.pinned(height.value()),
+			None => self.endpoints.active(),
+		};
+		let start = Instant::now();
+		if let (Transport::Grpc, Some(grpc_client)) = (self.query_transport, &endpoint.grpc_client)
+		{
+			match abci_query_grpc(grpc_client.clone(), path, data.clone(), height, prove).await {
+				Err(Error::GrpcError(e)) => {
+					log::warn!(target: "hyperspace_cosmos", "Failed to query {} over grpc, falling back to the rpc: {e}", self.name);
+					self.endpoints.on_failure(endpoint.index, &e);
+				},
+				Ok(response) => {
+					self.endpoints.on_success(endpoint.index, start.elapsed());
+					return Ok(response)
+				},
+				result => return result,
+			}
+		}
+		match endpoint.rpc_client.abci_query(Some(path.to_owned()), data, height, prove).await {
+			Ok(response) => {
+				self.endpoints.on_success(endpoint.index, start.elapsed());
+				Ok(response)
+			},
+			Err(e) => {
+				self.endpoints.on_failure(endpoint.index, &format!("ABCI query failed {e:?}"));
+				Err(Error::from(format!("Failed to query chain {} with error {:?}", self.name, e)))
+			},
+		}
+	}
+}
+
+/// Ethermint account, e.g. `/ethermint.types.v1.EthAccount` or
+/// `/injective.types.v1beta1.EthAccount`.
+#[derive(Clone, PartialEq, Message)]
+struct EthAccount {
+	#[prost(message, optional, tag = "1")]
+	base_account: Option<BaseAccount>,
+	#[prost(string, tag = "2")]
+	code_hash: String,
+}
+
+/// Base account of the `account` returned by the auth module, which is either a `BaseAccount` or
+/// an Ethermint `EthAccount` wrapping one.
+fn base_account(account: Any) -> Result<BaseAccount, Error> {
+	let decode_error = |e| Error::from(format!("Failed to decode account {}", e));
+	if account.type_url.ends_with(".EthAccount") {
+		EthAccount::decode(account.value.as_slice())
+			.map_err(decode_error)?
+			.base_account
+			.ok_or_else(|| Error::from(format!("Account {} has no base account", account.type_url)))
+	} else {
+		BaseAccount::decode(account.value.as_slice()).map_err(decode_error)
+	}
+}
+
+/// Sends an ABCI query with the `ABCIQuery` method of the grpc tendermint service, available from
+/// Cosmos SDK v0.46.
+async fn abci_query_grpc(
+	grpc_client: tonic::transport::Channel,
+	path: &str,
+	data: Vec<u8>,
+	height: Option<TmHeight>,
+	prove: bool,
+) -> Result<AbciQuery, Error> {
+	let request = AbciQueryRequest {
+		data,
+		path: path.to_owned(),
+		height: height.map_or(0, |height| height.value() as i64),
+		prove,
+	};
+	let response = ServiceClient::new(grpc_client)
+		.abci_query(request)
+		.await
+		.map_err(|e| Error::GrpcError(format!("ABCI query failed {e:?}")))?
+		.into_inner();
+	let proof = response.proof_ops.map(|proof| ProofOps {
+		ops: proof
+			.ops
+			.into_iter()
+			.map(|op| ProofOp { field_type: op.r#type, key: op.key, data: op.data })
+			.collect(),
+	});
+	Ok(AbciQuery {
+		code: response.code.into(),
+		log: response.log,
+		info: response.info,
+		index: response.index,
+		key: response.key,
+		value: response.value,
+		proof,
+		height: TmHeight::try_from(response.height)?,
+		codespace: response.codespace,
+	})
+}
+
+/// Checks that `trust_threshold` is at least 1/3 and below 1, the range accepted by `07-tendermint`
+/// clients.
+fn check_trust_threshold(trust_threshold: TrustThreshold) -> Result<(), String> {
+	TrustThreshold::new(trust_threshold.numerator(), trust_threshold.denominator())
+		.map_err(|e| e.to_string())?;
+	if trust_threshold.numerator().saturating_mul(3) < trust_threshold.denominator() {
+		return Err(format!("{trust_threshold} is below 1/3"))
+	}
+	Ok(())
 }
 
 /// Checks that the two validator sets are equal. The default implementation
@@ -571,10 +1209,17 @@ fn is_validators_equal(set_a: &ValidatorSet, set_b: &ValidatorSet) -> bool {
 	set_a.hash() == set_b.hash()
 }
 
+/// Returns true if an ABCI query failed because the node has already pruned the queried state.
+fn is_pruned_state_error(log: &str) -> bool {
+	["version does not exist", "failed to load state at height", "is not available, lowest height"]
+		.iter()
+		.any(|pattern| log.contains(pattern))
+}
+
 #[cfg(test)]
 pub mod tests {
 	use super::MnemonicEntry;
-	use crate::key_provider::KeyEntry;
+	use crate::key_provider::{KeyEntry, KeyType, LocalSigner};
 
 	struct TestVector {
 		mnemonic: &'static str,
@@ -615,14 +1260,32 @@ pub mod tests {
 			match KeyEntry::try_from(MnemonicEntry {
 				mnemonic: vector.mnemonic.to_string(),
 				prefix: "cosmos".to_string(),
+				key_type: KeyType::Secp256k1,
+				public_key_type_url: None,
 			}) {
 				Ok(key_entry) => {
-					assert_eq!(key_entry.private_key.to_bytes(), vector.private_key);
-					assert_eq!(key_entry.public_key.to_bytes(), vector.public_key);
+					assert_eq!(key_entry.public_key, vector.public_key);
 					assert_eq!(key_entry.account, vector.account);
 				},
 				Err(_) => panic!("Try from mnemonic failed"),
 			}
+			let signer = LocalSigner::from_mnemonic(vector.mnemonic).unwrap();
+			assert_eq!(signer.private_key.to_bytes(), vector.private_key);
 		}
 	}
+
+	#[test]
+	fn test_eth_secp256k1_from_mnemonic() {
+		let key_entry = KeyEntry::try_from(MnemonicEntry {
+			mnemonic: "test test test test test test test test test test test junk".to_string(),
+			prefix: "evmos".to_string(),
+			key_type: KeyType::EthSecp256k1,
+			public_key_type_url: None,
+		})
+		.unwrap();
+		assert_eq!(hex::encode(&key_entry.address), "f39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+		assert_eq!(key_entry.account, "evmos17w0adeg64ky0daxwd2ugyuneellmjgnxpu2u3g");
+		assert_eq!(key_entry.public_key_type_url, "/ethermint.crypto.v1.ethsecp256k1.PubKey");
+		assert_eq!(key_entry.signer.sign(b"payload").unwrap().len(), 65);
+	}
 }
@@ -8,18 +8,20 @@ use ibc_proto::{
 	},
 	google::protobuf::Any,
 };
-use k256::ecdsa::{signature::Signer as _, Signature, SigningKey};
 use prost::Message;
 
 pub fn encode_key_bytes(key: &KeyEntry) -> Result<Vec<u8>, Error> {
 	let mut pk_buf = Vec::new();
-	Message::encode(&key.public_key.public_key().to_bytes().as_slice().to_vec(), &mut pk_buf)
-		.map_err(|e| Error::from(e.to_string()))?;
+	Message::encode(&key.public_key, &mut pk_buf).map_err(|e| Error::from(e.to_string()))?;
 	Ok(pk_buf)
 }
 
-pub fn encode_signer_info(sequence: u64, key_bytes: Vec<u8>) -> Result<SignerInfo, Error> {
-	let pk_any = Any { type_url: "/cosmos.crypto.secp256k1.PubKey".to_string(), value: key_bytes };
+pub fn encode_signer_info(
+	sequence: u64,
+	key_type_url: &str,
+	key_bytes: Vec<u8>,
+) -> Result<SignerInfo, Error> {
+	let pk_any = Any { type_url: key_type_url.to_string(), value: key_bytes };
 	let single = Single { mode: 1 };
 	let sum_single = Some(Sum::Single(single));
 	let mode = Some(ModeInfo { sum: sum_single });
@@ -50,22 +52,33 @@ pub fn encode_sign_doc(
 	Message::encode(&sign_doc, &mut signdoc_buf)?;
 
 	// Create signature
-	let private_key_bytes = key.private_key.private_key().to_bytes();
-	let signing_key = SigningKey::from_bytes(private_key_bytes.as_slice())
-		.map_err(|e| Error::from(e.to_string()))?;
-	let signature: Signature = signing_key.sign(&signdoc_buf);
-	let signature_bytes = signature.as_ref().to_vec();
+	let signature_bytes = key.signer.sign(&signdoc_buf).map_err(|e| Error::from(e.to_string()))?;
 
 	Ok(signature_bytes)
 }
 
-pub fn encode_tx_body(messages: Vec<Any>) -> Result<(TxBody, Vec<u8>), Error> {
+/// Fields of a transaction body other than its messages.
+#[derive(Debug, Clone, Default)]
+pub struct TxBodyOptions {
+	pub memo: String,
+	/// Height after which the transaction can't be included in a block, or 0 for none
+	pub timeout_height: u64,
+	/// Extension options the chain must understand to accept the transaction
+	pub extension_options: Vec<Any>,
+	/// Extension options the chain may ignore
+	pub non_critical_extension_options: Vec<Any>,
+}
+
+pub fn encode_tx_body(
+	messages: Vec<Any>,
+	options: TxBodyOptions,
+) -> Result<(TxBody, Vec<u8>), Error> {
 	let body = TxBody {
 		messages,
-		memo: "ibc".to_string(),
-		timeout_height: 0_u64,
-		extension_options: Vec::<Any>::default(),
-		non_critical_extension_options: Vec::<Any>::default(),
+		memo: options.memo,
+		timeout_height: options.timeout_height,
+		extension_options: options.extension_options,
+		non_critical_extension_options: options.non_critical_extension_options,
 	};
 	let mut body_bytes = Vec::new();
 	Message::encode(&body, &mut body_bytes).map_err(|e| Error::from(e.to_string()))?;
@@ -0,0 +1,229 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The nodes a Cosmos client talks to. The requests go to the active endpoint, which is the first
+//! configured endpoint that is healthy and fast enough: an endpoint failing a request is skipped
+//! for [`FAILURE_COOLDOWN`], and one whose latency exceeds the configured maximum is skipped
+//! until it recovers. Proofs at a height are all queried from the same endpoint, as the stores of
+//! different nodes may not be in the same state.
+
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::VecDeque,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+use tendermint_rpc::{Client, HttpClient, Url};
+use tonic::transport::Channel;
+
+/// Time an endpoint is skipped for after failing a request.
+pub const FAILURE_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Interval between two latency probes of the endpoints.
+pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Number of heights whose proof endpoint is remembered.
+const PINNED_HEIGHTS: usize = 256;
+
+/// Weight of the latest latency measurement in the average latency of an endpoint.
+const LATENCY_WEIGHT: f64 = 0.3;
+
+/// Urls of a node of the chain, used in addition to the main `rpc_url`, `grpc_url` and
+/// `websocket_url` of a [`crate::client::CosmosClientConfig`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointConfig {
+	/// rpc url of the node
+	pub rpc_url: Url,
+	/// grpc url of the node
+	#[serde(default)]
+	pub grpc_url: Option<Url>,
+	/// websocket url of the node
+	#[serde(default)]
+	pub websocket_url: Option<Url>,
+}
+
+/// A node of the chain and its clients.
+#[derive(Clone, Debug)]
+pub struct Endpoint {
+	/// Position of the endpoint in the configuration, the main one being 0
+	pub index: usize,
+	pub rpc_url: Url,
+	pub rpc_client: HttpClient,
+	pub grpc_url: Option<Url>,
+	pub grpc_client: Option<Channel>,
+	pub websocket_url: Option<Url>,
+}
+
+impl Endpoint {
+	/// Creates the clients of the node at `config`. The grpc channel connects on first use.
+	pub fn new(index: usize, config: EndpointConfig) -> Result<Self, Error> {
+		let rpc_client = HttpClient::new(config.rpc_url.clone())
+			.map_err(|e| Error::RpcError(format!("failed to connect to RPC {:?}", e)))?;
+		let grpc_client = config
+			.grpc_url
+			.as_ref()
+			.map(|grpc_url| {
+				tonic::transport::Endpoint::new(grpc_url.to_string())
+					.map(|endpoint| endpoint.connect_lazy())
+					.map_err(|e| Error::RpcError(format!("failed to connect to RPC {:?}", e)))
+			})
+			.transpose()?;
+		Ok(Self {
+			index,
+			rpc_url: config.rpc_url,
+			rpc_client,
+			grpc_url: config.grpc_url,
+			grpc_client,
+			websocket_url: config.websocket_url,
+		})
+	}
+}
+
+#[derive(Default)]
+struct Health {
+	/// Moving average of the latency of the endpoint
+	latency: Option<Duration>,
+	/// The endpoint is skipped until then, after a failed request
+	failed_until: Option<Instant>,
+}
+
+struct State {
+	active: usize,
+	health: Vec<Health>,
+	/// Endpoints the proofs were queried from, by height
+	pinned: VecDeque<(u64, usize)>,
+}
+
+/// The endpoints of a chain, shared by the clones of a client.
+#[derive(Clone)]
+pub struct Endpoints {
+	endpoints: Arc<Vec<Endpoint>>,
+	state: Arc<Mutex<State>>,
+	max_latency: Option<Duration>,
+}
+
+impl Endpoints {
+	/// `endpoints` are in order of preference and can't be empty.
+	pub fn new(endpoints: Vec<Endpoint>, max_latency: Option<Duration>) -> Self {
+		assert!(!endpoints.is_empty(), "a chain has at least one endpoint; qed");
+		let health = endpoints.iter().map(|_| Health::default()).collect();
+		Self {
+			endpoints: Arc::new(endpoints),
+			state: Arc::new(Mutex::new(State { active: 0, health, pinned: VecDeque::new() })),
+			max_latency,
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.endpoints.len()
+	}
+
+	/// The endpoint requests are sent to.
+	pub fn active(&self) -> Endpoint {
+		let active = self.state.lock().unwrap().active;
+		self.endpoints[active].clone()
+	}
+
+	/// The endpoint proofs at `height` are queried from: the active endpoint at the time of the
+	/// first query at that height.
+	pub fn pinned(&self, height: u64) -> Endpoint {
+		let mut state = self.state.lock().unwrap();
+		let index = match state.pinned.iter().find(|(pinned, _)| *pinned == height) {
+			Some((_, index)) => *index,
+			None => {
+				if state.pinned.len() >= PINNED_HEIGHTS {
+					state.pinned.pop_front();
+				}
+				let active = state.active;
+				state.pinned.push_back((height, active));
+				active
+			},
+		};
+		self.endpoints[index].clone()
+	}
+
+	/// Records a request to the endpoint `index` that succeeded in `latency`.
+	pub fn on_success(&self, index: usize, latency: Duration) {
+		let mut state = self.state.lock().unwrap();
+		let health = &mut state.health[index];
+		health.failed_until = None;
+		health.latency = Some(match health.latency {
+			Some(average) =>
+				average.mul_f64(1.0 - LATENCY_WEIGHT) + latency.mul_f64(LATENCY_WEIGHT),
+			None => latency,
+		});
+		self.select(&mut state);
+	}
+
+	/// Records a failed request to the endpoint `index`, switching to another endpoint if it's
+	/// the active one. The proofs pinned to it are queried from the next active endpoint.
+	pub fn on_failure(&self, index: usize, reason: &str) {
+		let mut state = self.state.lock().unwrap();
+		if self.endpoints.len() > 1 {
+			log::warn!(target: "hyperspace_cosmos", "Endpoint {} failed: {reason}", self.endpoints[index].rpc_url);
+		}
+		state.health[index].failed_until = Some(Instant::now() + FAILURE_COOLDOWN);
+		state.pinned.retain(|(_, pinned)| *pinned != index);
+		self.select(&mut state);
+	}
+
+	/// Makes the first healthy endpoint whose latency is below the maximum the active one. When
+	/// all the endpoints are slow, the first healthy one is used, and when all of them failed,
+	/// the one that failed first.
+	fn select(&self, state: &mut State) {
+		let now = Instant::now();
+		let healthy = |health: &Health| health.failed_until.map_or(true, |until| until <= now);
+		let fast = |health: &Health| match (health.latency, self.max_latency) {
+			(Some(latency), Some(max_latency)) => latency <= max_latency,
+			_ => true,
+		};
+		let active = state
+			.health
+			.iter()
+			.position(|health| healthy(health) && fast(health))
+			.or_else(|| state.health.iter().position(healthy))
+			.or_else(|| {
+				state
+					.health
+					.iter()
+					.enumerate()
+					.min_by_key(|(_, health)| health.failed_until)
+					.map(|(index, _)| index)
+			})
+			.unwrap_or_default();
+		if active != state.active {
+			log::info!(target: "hyperspace_cosmos", "Switching from endpoint {} to {}", self.endpoints[state.active].rpc_url, self.endpoints[active].rpc_url);
+			state.active = active;
+		}
+	}
+
+	/// Measures the latency of every endpoint every [`HEALTH_CHECK_INTERVAL`], so that a
+	/// degraded endpoint is left and a recovered one is used again. Only needed when there are
+	/// several endpoints.
+	pub async fn run_health_checks(self) {
+		let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+		loop {
+			interval.tick().await;
+			for endpoint in self.endpoints.iter() {
+				let start = Instant::now();
+				match endpoint.rpc_client.health().await {
+					Ok(()) => self.on_success(endpoint.index, start.elapsed()),
+					Err(e) =>
+						self.on_failure(endpoint.index, &format!("health check failed {e:?}")),
+				}
+			}
+		}
+	}
+}
@@ -7,6 +7,9 @@ pub enum Error {
 	/// An error from the rpc interface
 	#[error("Rpc client error: {0}")]
 	RpcError(String),
+	/// A request to the grpc endpoint that couldn't be served
+	#[error("Grpc client error: {0}")]
+	GrpcError(String),
 	/// Custom error
 	#[error("{0}")]
 	Custom(String),
@@ -19,16 +19,28 @@ use ibc::{
 			events::{self as channel_events, Attributes as ChannelAttributes},
 			packet::Packet,
 		},
+		ics24_host::identifier::{ChannelId, PortId},
 	},
 	events::{Error as IbcEventError, IbcEvent, IbcEventType},
 	protobuf::Protobuf,
 };
 use ics07_tendermint::client_message::{decode_header as tm_decode_header, Header};
+use primitives::PacketFees;
 use serde::Serialize;
 use tendermint::abci::Event as AbciEvent;
 
 pub const HEADER_ATTRIBUTE_KEY: &str = "header";
 
+/// Prefix CosmWasm adds to the type of the events emitted by contracts, e.g. `wasm-send_packet`
+/// for a `send_packet` event of a contract implementing IBC.
+pub const WASM_EVENT_PREFIX: &str = "wasm-";
+
+/// Hex encoded data of a packet, which unlike `packet_data` supports data that isn't valid UTF-8.
+pub const PKT_DATA_HEX_ATTRIBUTE_KEY: &str = "packet_data_hex";
+
+/// Hex encoded acknowledgement of a packet.
+pub const PKT_ACK_HEX_ATTRIBUTE_KEY: &str = "packet_ack_hex";
+
 #[derive(Clone, Debug, Serialize)]
 pub struct IbcEventWithHeight {
 	pub event: IbcEvent,
@@ -90,6 +102,11 @@ pub fn event_is_type_channel(ev: &IbcEvent) -> bool {
 	)
 }
 
+/// Type of `abci_event`, without the prefix of the events emitted by CosmWasm contracts.
+pub fn abci_event_type(abci_event: &AbciEvent) -> &str {
+	abci_event.kind.strip_prefix(WASM_EVENT_PREFIX).unwrap_or(&abci_event.kind)
+}
+
 /// Note: This function, as well as other helpers, are needed as a workaround to
 /// Rust's orphan rule. That is, we want the AbciEvent -> IbcEvent to be defined
 /// in the relayer crate, but can't because neither AbciEvent nor IbcEvent are
@@ -99,7 +116,7 @@ pub fn ibc_event_try_from_abci_event(
 	abci_event: &AbciEvent,
 	height: Height,
 ) -> Result<IbcEvent, IbcEventError> {
-	match &abci_event.kind.parse() {
+	match &abci_event_type(abci_event).parse() {
 		Ok(IbcEventType::CreateClient) => Ok(IbcEvent::CreateClient(
 			create_client_try_from_abci_event(abci_event, height).map_err(IbcEventError::client)?,
 		)),
@@ -375,6 +392,39 @@ pub fn timeout_packet_try_from_abci_event(
 		.map_err(|_| ChannelError::abci_conversion_failed(abci_event.kind.to_owned()))
 }
 
+/// Parses an ICS-29 `incentivized_ibc_packet` event into the source port, channel and sequence
+/// of the packet and its total fees.
+pub fn incentivized_packet_try_from_abci_event(
+	abci_event: &AbciEvent,
+) -> Result<(PortId, ChannelId, u64, PacketFees), ChannelError> {
+	let mut port_id = None;
+	let mut channel_id = None;
+	let mut sequence = None;
+	let mut fees = PacketFees::default();
+	for tag in &abci_event.attributes {
+		let key = tag.key.as_str();
+		let value = tag.value.as_str();
+		match key {
+			"port_id" => port_id = Some(value.parse().map_err(ChannelError::identifier)?),
+			"channel_id" => channel_id = Some(value.parse().map_err(ChannelError::identifier)?),
+			"packet_sequence" =>
+				sequence =
+					Some(value.parse::<u64>().map_err(|e| {
+						ChannelError::invalid_string_as_sequence(value.to_string(), e)
+					})?),
+			"recv_fee" => fees.recv_fee = value.to_string(),
+			"ack_fee" => fees.ack_fee = value.to_string(),
+			"timeout_fee" => fees.timeout_fee = value.to_string(),
+			_ => {},
+		}
+	}
+	match (port_id, channel_id, sequence) {
+		(Some(port_id), Some(channel_id), Some(sequence)) =>
+			Ok((port_id, channel_id, sequence, fees)),
+		_ => Err(ChannelError::abci_conversion_failed(abci_event.kind.to_owned())),
+	}
+}
+
 pub fn client_extract_attributes_from_tx(
 	event: &AbciEvent,
 	height: Height,
@@ -494,6 +544,9 @@ fn extract_packet_and_write_ack_from_tx(
 ) -> Result<(Packet, Vec<u8>), ChannelError> {
 	let mut packet = Packet::default();
 	let mut write_ack: Vec<u8> = Vec::new();
+	// the hex encoded data and acknowledgement are used when available
+	let mut data_hex = None;
+	let mut ack_hex = None;
 	for tag in &event.attributes {
 		let key = tag.key.as_str();
 		let value = tag.value.as_str();
@@ -529,9 +582,27 @@ fn extract_packet_and_write_ack_from_tx(
 			channel_events::PKT_ACK_ATTRIBUTE_KEY => {
 				write_ack = Vec::from(value.as_bytes());
 			},
+			PKT_DATA_HEX_ATTRIBUTE_KEY => {
+				data_hex =
+					Some(hex::decode(value).map_err(|_| {
+						ChannelError::abci_conversion_failed(event.kind.to_owned())
+					})?);
+			},
+			PKT_ACK_HEX_ATTRIBUTE_KEY => {
+				ack_hex =
+					Some(hex::decode(value).map_err(|_| {
+						ChannelError::abci_conversion_failed(event.kind.to_owned())
+					})?);
+			},
 			_ => {},
 		}
 	}
+	if let Some(data) = data_hex {
+		packet.data = data;
+	}
+	if let Some(ack) = ack_hex {
+		write_ack = ack;
+	}
 
 	Ok((packet, write_ack))
 }
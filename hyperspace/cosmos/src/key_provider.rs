@@ -1,16 +1,83 @@
 use super::client::CosmosClient;
+use anyhow::anyhow;
 use bech32::{ToBase32, Variant};
-use bip32::{XPrv as ExtendedPrivateKey, XPub as ExtendedPublicKey};
-use primitives::{error::Error, KeyProvider};
-use std::str::FromStr;
+use bip32::{DerivationPath, XPrv as ExtendedPrivateKey};
+use digest::Digest;
+use k256::{
+	ecdsa::{
+		recoverable,
+		signature::{DigestSigner, Signer as _},
+		Signature, SigningKey,
+	},
+	elliptic_curve::sec1::ToEncodedPoint,
+	PublicKey,
+};
+use primitives::{error::Error, signing::Signer, KeyProvider, PayloadSignature};
+use ripemd::Ripemd160;
+use serde::{Deserialize, Serialize};
+use sha3::Keccak256;
+use std::{str::FromStr, sync::Arc};
 use tendermint::account::Id as AccountId;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Kind of the relayer keys of a chain.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyType {
+	/// Cosmos SDK secp256k1 keys
+	#[default]
+	Secp256k1,
+	/// Ethermint keys, used by Evmos, Injective and other EVM chains: secp256k1 keys derived
+	/// with the Ethereum coin type, whose address is their Ethereum address and which sign the
+	/// keccak-256 hash of the payload
+	EthSecp256k1,
+}
+
+impl KeyType {
+	/// BIP-44 path the key of the first account of a mnemonic is derived at.
+	pub fn derivation_path(self) -> &'static str {
+		match self {
+			KeyType::Secp256k1 => "m/44'/118'/0'/0/0",
+			KeyType::EthSecp256k1 => "m/44'/60'/0'/0/0",
+		}
+	}
+
+	/// Type url of the public keys in the signer infos of the transactions, unless overridden in
+	/// the chain config. Some chains, e.g. Injective, define their own.
+	pub fn public_key_type_url(self) -> &'static str {
+		match self {
+			KeyType::Secp256k1 => "/cosmos.crypto.secp256k1.PubKey",
+			KeyType::EthSecp256k1 => "/ethermint.crypto.v1.ethsecp256k1.PubKey",
+		}
+	}
+
+	/// Address of the compressed public key `public_key`.
+	pub fn address(self, public_key: &[u8]) -> Result<[u8; 20], anyhow::Error> {
+		match self {
+			KeyType::Secp256k1 => {
+				let sha256 = sha2::Sha256::digest(public_key);
+				Ok(Ripemd160::digest(sha256).into())
+			},
+			KeyType::EthSecp256k1 => {
+				let public_key = PublicKey::from_sec1_bytes(public_key)
+					.map_err(|e| anyhow!("Invalid secp256k1 public key: {e}"))?;
+				let uncompressed = public_key.to_encoded_point(false);
+				let hash = Keccak256::digest(&uncompressed.as_bytes()[1..]);
+				let mut address = [0u8; 20];
+				address.copy_from_slice(&hash[12..]);
+				Ok(address)
+			},
+		}
+	}
+}
+
+#[derive(Clone)]
 pub struct KeyEntry {
-	/// Public key
-	pub public_key: ExtendedPublicKey,
-	/// Private key
-	pub private_key: ExtendedPrivateKey,
+	/// Compressed secp256k1 public key
+	pub public_key: Vec<u8>,
+	/// Type url of the public key in the transactions
+	pub public_key_type_url: String,
+	/// Signer holding the private key
+	pub signer: Arc<dyn Signer>,
 	/// Account Bech32 format
 	pub account: String,
 	/// Address
@@ -18,18 +85,101 @@ pub struct KeyEntry {
 }
 
 impl KeyEntry {
+	/// Creates the entry of the `key_type` key of `signer`, deriving its account with the bech32
+	/// `prefix`. The public key is encoded with the `public_key_type_url` in the transactions,
+	/// defaulting to the one of the `key_type`.
 	pub fn new(
-		public_key: ExtendedPublicKey,
-		private_key: ExtendedPrivateKey,
-		account: String,
-		address: Vec<u8>,
-	) -> Self {
-		Self { public_key, private_key, account, address }
+		signer: Arc<dyn Signer>,
+		prefix: &str,
+		key_type: KeyType,
+		public_key_type_url: Option<&str>,
+	) -> Result<Self, anyhow::Error> {
+		let public_key = signer.public_key().to_vec();
+		let address = key_type.address(&public_key)?;
+		let account = bech32::encode(prefix, address.to_base32(), Variant::Bech32)?;
+		let public_key_type_url =
+			public_key_type_url.unwrap_or(key_type.public_key_type_url()).to_string();
+		Ok(Self { public_key, public_key_type_url, signer, account, address: address.into() })
+	}
+}
+
+/// Signs with a secp256k1 private key held in memory.
+pub struct LocalSigner {
+	pub private_key: ExtendedPrivateKey,
+	public_key: Vec<u8>,
+	key_type: KeyType,
+}
+
+impl LocalSigner {
+	pub fn new(private_key: ExtendedPrivateKey) -> Self {
+		Self::with_key_type(private_key, KeyType::Secp256k1)
+	}
+
+	pub fn with_key_type(private_key: ExtendedPrivateKey, key_type: KeyType) -> Self {
+		let public_key = private_key.public_key().to_bytes().to_vec();
+		Self { private_key, public_key, key_type }
+	}
+}
+
+impl LocalSigner {
+	/// Derives the key of the first account of `mnemonic`.
+	pub fn from_mnemonic(mnemonic: &str) -> Result<Self, anyhow::Error> {
+		Self::from_mnemonic_with_key_type(mnemonic, KeyType::Secp256k1)
+	}
+
+	/// Derives the `key_type` key of the first account of `mnemonic`.
+	pub fn from_mnemonic_with_key_type(
+		mnemonic: &str,
+		key_type: KeyType,
+	) -> Result<Self, anyhow::Error> {
+		let mnemonic = bip39::Mnemonic::from_phrase(mnemonic, bip39::Language::English)?;
+		let seed = bip39::Seed::new(&mnemonic, "");
+		let path = DerivationPath::from_str(key_type.derivation_path())?;
+		Ok(Self::with_key_type(ExtendedPrivateKey::derive_from_path(seed, &path)?, key_type))
+	}
+}
+
+impl FromStr for LocalSigner {
+	type Err = bip32::Error;
+
+	fn from_str(private_key: &str) -> Result<Self, Self::Err> {
+		ExtendedPrivateKey::from_str(private_key).map(Self::new)
+	}
+}
+
+impl Signer for LocalSigner {
+	fn public_key(&self) -> &[u8] {
+		&self.public_key
+	}
+
+	fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+		let private_key_bytes = self.private_key.private_key().to_bytes();
+		let signing_key = SigningKey::from_bytes(private_key_bytes.as_slice())
+			.map_err(|e| anyhow!("Invalid relayer key: {e}"))?;
+		match self.key_type {
+			KeyType::Secp256k1 => {
+				let signature: Signature = signing_key.sign(payload);
+				Ok(signature.as_ref().to_vec())
+			},
+			// Ethermint expects the 65 bytes `r || s || v` signature of the keccak-256 hash
+			KeyType::EthSecp256k1 => {
+				let signature: recoverable::Signature =
+					signing_key.sign_digest(Keccak256::new_with_prefix(payload));
+				Ok(signature.as_ref().to_vec())
+			},
+		}
 	}
 }
 
 impl<H> KeyProvider for CosmosClient<H> {
 	fn account_id(&self) -> ibc::signer::Signer {
+		// the relayed messages are executed on behalf of the authz granter
+		if let Some(granter) = &self.authz_granter {
+			return granter
+				.parse()
+				.map_err(|e| Error::from(format!("Could not parse account id {e}")))
+				.unwrap()
+		}
 		let key_entry = self.keybase.clone();
 		let address = hex::encode(key_entry.address);
 		let account = AccountId::from_str(address.as_str())
@@ -45,4 +195,9 @@ impl<H> KeyProvider for CosmosClient<H> {
 			.map_err(|e| Error::from(format!("Could not parse account id {e}")))
 			.unwrap()
 	}
+
+	fn sign_payload(&self, payload: &[u8]) -> Result<PayloadSignature, anyhow::Error> {
+		let signature = self.keybase.signer.sign(payload)?;
+		Ok(PayloadSignature { public_key: self.keybase.public_key.clone(), signature })
+	}
 }
@@ -14,14 +14,18 @@
 
 use ibc::core::ics02_client::height::Height;
 
+pub mod authz;
 pub mod chain;
 pub mod client;
 pub mod encode;
+pub mod endpoints;
 pub mod error;
 pub mod events;
 pub mod key_provider;
 pub mod light_client;
+pub mod pagination;
 pub mod provider;
+pub mod subscription;
 #[cfg(any(test, feature = "testing"))]
 pub mod test_provider;
 pub mod tx;
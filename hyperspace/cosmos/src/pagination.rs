@@ -0,0 +1,92 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Paginated grpc queries. Nodes cap the number of items of a page whatever limit is requested, so
+//! the queries of lists that can grow large, such as the packet commitments of a channel, fetch
+//! all the pages. The pages are read at the same height, so that items moving between pages while
+//! they're fetched aren't missed.
+
+use crate::error::Error;
+use futures::{stream, Future, StreamExt, TryStreamExt};
+use ibc_proto::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
+
+/// Number of items requested per page.
+pub const PAGE_SIZE: u64 = 500;
+
+/// Number of pages fetched concurrently.
+pub const CONCURRENT_PAGES: usize = 8;
+
+/// Metadata key of the height the Cosmos SDK serves a grpc query at.
+pub const BLOCK_HEIGHT_HEADER: &str = "x-cosmos-block-height";
+
+/// Request of `message` served at `height`, or at the latest height if it's 0.
+pub fn request_at<T>(message: T, height: u64) -> tonic::Request<T> {
+	let mut request = tonic::Request::new(message);
+	if height != 0 {
+		request.metadata_mut().insert(BLOCK_HEIGHT_HEADER, height.into());
+	}
+	request
+}
+
+/// Fetches all the pages of a query, `fetch` returning the items of the requested page and the
+/// pagination of the response. The first page counts the items, so that the following ones are
+/// fetched concurrently by offset; nodes that don't count them are paginated by key instead.
+pub async fn query_all_pages<T, F, Fut>(fetch: F) -> Result<Vec<T>, Error>
+where
+	F: Fn(PageRequest) -> Fut,
+	Fut: Future<Output = Result<(Vec<T>, Option<PageResponse>), Error>>,
+{
+	let (mut items, page) =
+		fetch(PageRequest { limit: PAGE_SIZE, count_total: true, ..Default::default() }).await?;
+	let Some(page) = page else { return Ok(items) };
+	// the node may serve fewer items than requested per page
+	let page_size = items.len() as u64;
+	if page.total > page_size && page_size != 0 {
+		let offsets = (1..).map(|page| page * page_size).take_while(|offset| *offset < page.total);
+		let pages = stream::iter(offsets)
+			.map(|offset| fetch(PageRequest { offset, limit: page_size, ..Default::default() }))
+			.buffered(CONCURRENT_PAGES)
+			.try_collect::<Vec<_>>()
+			.await?;
+		items.extend(pages.into_iter().flat_map(|(page_items, _)| page_items));
+		return Ok(items)
+	}
+	let mut next_key = page.next_key;
+	while !next_key.is_empty() {
+		let (page_items, page) =
+			fetch(PageRequest { key: next_key, limit: PAGE_SIZE, ..Default::default() }).await?;
+		items.extend(page_items);
+		next_key = page.map(|page| page.next_key).unwrap_or_default();
+	}
+	Ok(items)
+}
+
+/// Queries `sequences` in chunks of [`PAGE_SIZE`], fetched concurrently, so that long lists of
+/// sequences don't exceed the size limit of the requests.
+pub async fn query_in_chunks<F, Fut>(sequences: Vec<u64>, fetch: F) -> Result<Vec<u64>, Error>
+where
+	F: Fn(Vec<u64>) -> Fut,
+	Fut: Future<Output = Result<Vec<u64>, Error>>,
+{
+	let chunks = sequences
+		.chunks(PAGE_SIZE as usize)
+		.map(|chunk| chunk.to_vec())
+		.collect::<Vec<_>>();
+	let results = stream::iter(chunks)
+		.map(fetch)
+		.buffered(CONCURRENT_PAGES)
+		.try_collect::<Vec<_>>()
+		.await?;
+	Ok(results.into_iter().flatten().collect())
+}
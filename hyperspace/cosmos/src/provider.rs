@@ -1,42 +1,37 @@
 use super::{
-	client::CosmosClient,
+	client::{CosmosClient, UPGRADE_QUERY_PATH},
 	events::{
 		event_is_type_channel, event_is_type_client, event_is_type_connection,
-		ibc_event_try_from_abci_event, IbcEventWithHeight,
+		ibc_event_try_from_abci_event, incentivized_packet_try_from_abci_event, IbcEventWithHeight,
 	},
+	pagination::{query_all_pages, query_in_chunks, request_at},
+	subscription::{self, SubscriptionItem},
 };
 use crate::error::Error;
-use futures::{
-	stream::{self, select_all},
-	Stream, StreamExt,
-};
+use futures::{stream, Stream, StreamExt};
 use ibc::{
-	applications::transfer::{Amount, BaseDenom, PrefixedCoin, PrefixedDenom, TracePath},
+	applications::transfer::PrefixedCoin,
 	core::{
-		ics02_client::{
-			client_state::ClientType, events as ClientEvents,
-			msgs::update_client::MsgUpdateAnyClient, trust_threshold::TrustThreshold,
-		},
+		ics02_client::{client_state::ClientType, events as ClientEvents},
 		ics04_channel::packet::Sequence,
-		ics23_commitment::{commitment::CommitmentPrefix, specs::ProofSpecs},
+		ics23_commitment::commitment::CommitmentPrefix,
 		ics24_host::{
 			identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
 			path::{
 				AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath,
-				CommitmentsPath, ConnectionsPath, Path, ReceiptsPath, SeqRecvsPath, SeqSendsPath,
+				ClientUpgradePath, CommitmentsPath, ConnectionsPath, Path, ReceiptsPath,
+				SeqRecvsPath, SeqSendsPath,
 			},
 		},
 	},
 	events::IbcEvent,
-	protobuf::Protobuf,
 	signer::Signer,
 	timestamp::Timestamp,
-	tx_msg::Msg,
 	Height,
 };
 use ibc_primitives::PacketInfo as IbcPacketInfo;
 use ibc_proto::{
-	cosmos::{bank::v1beta1::QueryBalanceRequest, base::query::v1beta1::PageRequest},
+	cosmos::base::query::v1beta1::PageRequest,
 	google::protobuf::Any,
 	ibc::core::{
 		channel::v1::{
@@ -55,18 +50,13 @@ use ibc_proto::{
 	},
 };
 use ibc_rpc::PacketInfo;
-use ics07_tendermint::{
-	client_message::ClientMessage, client_state::ClientState, consensus_state::ConsensusState,
-};
-use ics08_wasm::msg::MsgPushNewWasmCode;
-use pallet_ibc::light_clients::{
-	AnyClientMessage, AnyClientState, AnyConsensusState, HostFunctionsManager,
-};
+use ics07_tendermint::client_state::ClientState;
+use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState};
 use primitives::{
-	filter_events_by_ids, mock::LocalClientTypes, Chain, IbcProvider, KeyProvider, UpdateType,
+	fee::INCENTIVIZED_PACKET_EVENT, filter_events_by_ids, Chain, CommonClientState, IbcQuerier,
+	TxSubmitter, UpgradedClient,
 };
 use prost::Message;
-use rand::Rng;
 use std::{
 	collections::{hash_map::Entry, HashMap, HashSet},
 	pin::Pin,
@@ -79,9 +69,9 @@ use tendermint_rpc::{
 	endpoint::tx::Response,
 	event::{Event, EventData},
 	query::{EventType, Query},
-	Client, Error as RpcError, Order, SubscriptionClient,
+	Client, HttpClient, Order, WebSocketClient,
 };
-use tokio::{task::JoinSet, time::sleep};
+use tokio::time::sleep;
 
 // At least one *mandatory* update should happen during that period
 // TODO: make it configurable
@@ -98,196 +88,90 @@ pub struct TransactionId<Hash> {
 }
 
 #[async_trait::async_trait]
-impl<H> IbcProvider for CosmosClient<H>
+impl<H> IbcQuerier for CosmosClient<H>
 where
 	H: Clone + Send + Sync + 'static,
 {
-	type FinalityEvent = FinalityEvent;
-	type TransactionId = TransactionId<Hash>;
 	type AssetId = String;
 	type Error = Error;
 
-	async fn query_latest_ibc_events<C>(
-		&mut self,
-		finality_event: Self::FinalityEvent,
+	// TODO: Changed result: `Item =` from `IbcEvent` to `IbcEventWithHeight` to include the
+	// necessary height field, as `height` is removed from `Attribute` from ibc-rs v0.22.0
+	async fn query_ibc_events_in_range<C>(
+		&self,
 		counterparty: &C,
-	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+		from: u64,
+		to: u64,
+	) -> Result<Vec<IbcEvent>, anyhow::Error>
 	where
 		C: Chain,
 	{
-		let finality_event_height = match finality_event {
-			FinalityEvent::Tendermint { from: _, to } => to,
-		};
-		let client_id = self.client_id();
-		let latest_cp_height = counterparty.latest_height_and_timestamp().await?.0;
-		let latest_cp_client_state =
-			counterparty.query_client_state(latest_cp_height, client_id.clone()).await?;
-		let client_state_response = latest_cp_client_state
-			.client_state
-			.ok_or_else(|| Error::Custom("counterparty returned empty client state".to_string()))?;
-		let client_state =
-			ClientState::<HostFunctionsManager>::decode_vec(&client_state_response.value)
-				.map_err(|_| Error::Custom("failed to decode client state response".to_string()))?;
-		let latest_cp_client_height = client_state.latest_height().revision_height;
-		let latest_height = self.latest_height_and_timestamp().await?.0;
-		let latest_revision = latest_height.revision_number;
-
-		let from = TmHeight::try_from(latest_cp_client_height).unwrap();
-		let to = finality_event_height.min(
-			TmHeight::try_from(latest_cp_client_height + NUMBER_OF_BLOCKS_TO_PROCESS_PER_ITER)
-				.expect("should not overflow"),
-		);
-		log::info!(target: "hyperspace_cosmos", "Getting blocks {}..{}", from, to);
-
-		// query (exclusively) up to `to`, because the proof for the event at `to - 1` will be
-		// contained at `to` and will be fetched below by `msg_update_client_header`
-		let update_headers =
-			self.msg_update_client_header(from, to, client_state.latest_height).await?;
-		let mut block_events = Vec::new();
-		let mut join_set: JoinSet<Result<_, anyhow::Error>> = JoinSet::new();
-		let range = (from.value()..to.value()).collect::<Vec<_>>();
-		let to = self.rpc_call_delay().as_millis();
-		for heights in range.chunks(100) {
-			for height in heights.iter().copied() {
-				log::trace!(target: "hyperspace_cosmos", "Parsing events at height {:?}", height);
-				let client = self.clone();
-				let duration = Duration::from_millis(rand::thread_rng().gen_range(0..to) as u64);
-				let counterparty = counterparty.clone();
-				join_set.spawn(async move {
-					sleep(duration).await;
-					let xs = tokio::time::timeout(
-						Duration::from_secs(30),
-						client.parse_ibc_events_at(&counterparty, latest_revision, height),
-					)
-					.await??;
-					Ok((height, xs))
-				});
-			}
-			while let Some(res) = join_set.join_next().await {
-				let out = res??;
-				block_events.push(out);
-			}
-		}
-
-		if block_events.len() != update_headers.len() {
-			return Err(anyhow::anyhow!(
-				"block events and updates must match, got {} and {}",
-				block_events.len(),
-				update_headers.len()
-			))
-		}
-		block_events.sort_by_key(|(height, _)| *height);
-
-		let mut updates = Vec::new();
-		for (i, (events, (update_header, mut update_type))) in block_events
-			.into_iter()
-			.map(|(_, events)| events)
-			.zip(update_headers)
-			.enumerate()
-		{
-			if i == NUMBER_OF_BLOCKS_TO_PROCESS_PER_ITER as usize - 1 {
-				update_type = UpdateType::Mandatory;
-			}
-			let height = update_header.height();
-			let update_client_header = {
-				let msg = MsgUpdateAnyClient::<LocalClientTypes> {
-					client_id: client_id.clone(),
-					client_message: AnyClientMessage::Tendermint(ClientMessage::Header(
-						update_header,
-					)),
-					signer: counterparty.account_id(),
-				};
-				let value = msg.encode_vec().map_err(|e| {
-					Error::from(format!("Failed to encode MsgUpdateClient {msg:?}: {e:?}"))
-				})?;
-				Any { value, type_url: msg.type_url() }
-			};
-			updates.push((update_client_header, height, events, update_type));
+		let latest_revision = self.latest_height_and_timestamp().await?.0.revision_number;
+		let mut events = Vec::new();
+		for height in from..=to {
+			events.extend(self.parse_ibc_events_at(counterparty, latest_revision, height).await?);
 		}
-		Ok(updates)
+		Ok(events)
 	}
 
-	// TODO: Changed result: `Item =` from `IbcEvent` to `IbcEventWithHeight` to include the
-	// necessary height field, as `height` is removed from `Attribute` from ibc-rs v0.22.0
-	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
-		// Create websocket client. Like what `EventMonitor::subscribe()` does in `hermes`
-		let ws_client = self.rpc_ws_client();
-
+	async fn ibc_events(
+		&self,
+	) -> Pin<Box<dyn Stream<Item = Result<IbcEvent, Self::Error>> + Send + 'static>> {
 		let query_all = vec![
 			Query::from(EventType::NewBlock),
 			Query::eq("message.module", "ibc_client"),
 			Query::eq("message.module", "ibc_connection"),
 			Query::eq("message.module", "ibc_channel"),
+			// packets sent by CosmWasm contracts are part of `MsgExecuteContract` transactions
+			Query::eq("message.module", "wasm"),
 		];
-		let mut subscriptions = vec![];
-		for query in &query_all {
-			let subscription = ws_client
-				.subscribe(query.clone())
-				.await
-				.map_err(|e| Error::from(format!("Web Socket Client Error {e:?}")))
-				.unwrap();
-			subscriptions.push(subscription);
-		}
-		// Collect IBC events from each RPC event, Like what `stream_batches()` does in `hermes`
-		let all_subs: Box<dyn Stream<Item = Result<Event, RpcError>> + Send + Sync + Unpin> =
-			Box::new(select_all(subscriptions));
+		// Like what `EventMonitor::subscribe()` does in `hermes`, resubscribing on disconnections
+		let subscription = match subscription::subscribe(
+			self.endpoints.clone(),
+			query_all,
+			self.common_state.clone(),
+		)
+		.await
+		{
+			Ok(subscription) => subscription,
+			Err(e) =>
+				return stream::once(futures::future::ready(Err(Error::from(format!(
+					"Web Socket Client Error {e:?}"
+				)))))
+				.boxed(),
+		};
 		let chain_id = self.chain_id.clone();
-		let events = all_subs
-			.map(move |event| {
-				// Like what `get_all_events()` does in `hermes`
-				let mut events_with_height: Vec<IbcEventWithHeight> = vec![];
-				let Event { data, events: _, query } = event.unwrap();
-				match data {
-					EventData::NewBlock { block, .. }
-					if query == Query::from(EventType::NewBlock).to_string() =>
-						{
-							let height = Height::new(
-								ChainId::chain_version(chain_id.to_string().as_str()),
-								u64::from(block.as_ref().ok_or("tx.height").unwrap().header.height),
-							);
-							events_with_height.push(IbcEventWithHeight::new(
-								ClientEvents::NewBlock::new(height).into(),
+		let endpoints = self.endpoints.clone();
+		let common_state = self.common_state.clone();
+		subscription
+			.then(move |item| {
+				let chain_id = chain_id.clone();
+				let endpoints = endpoints.clone();
+				let common_state = common_state.clone();
+				async move {
+					match item {
+						Ok(SubscriptionItem::Event(event)) =>
+							subscription_ibc_events(event, &chain_id, &common_state),
+						Ok(SubscriptionItem::MissedBlock(height)) =>
+							missed_block_ibc_events(
+								&endpoints.active().rpc_client,
+								&chain_id,
 								height,
+								&common_state,
+							)
+							.await,
+						Err(e) => {
+							common_state.on_skipped_stream_item(&format!(
+								"Web Socket subscription error {e:?}"
 							));
+							vec![]
 						},
-					EventData::Tx { tx_result } => {
-						let height = Height::new(
-							ChainId::chain_version(chain_id.to_string().as_str()),
-							tx_result.height as u64,
-						);
-						for abci_event in &tx_result.result.events {
-							if let Ok(ibc_event) = ibc_event_try_from_abci_event(abci_event, height)
-							{
-								log::debug!(target: "hyperspace_cosmos", "Retrieved event: {}, query: {}, parsed: {:?}", abci_event.kind, query, ibc_event);
-								let is_client_event = query == Query::eq("message.module", "ibc_client").to_string() &&
-									event_is_type_client(&ibc_event);
-								let is_connection_event = (query ==
-									Query::eq("message.module", "ibc_connection").to_string() ||
-									query ==
-										Query::eq("message.module", "ibc_client").to_string()) &&
-									event_is_type_connection(&ibc_event);
-								let is_channel_event = query ==
-									Query::eq("message.module", "ibc_channel").to_string() &&
-									event_is_type_channel(&ibc_event);
-								if is_client_event || is_connection_event || is_channel_event {
-									events_with_height
-										.push(IbcEventWithHeight::new(ibc_event, height));
-								} else {
-									log::debug!(target: "hyperspace_cosmos", "the event is unknown");
-								}
-							} else {
-								log::debug!(target: "hyperspace_cosmos", "Event wasn't parsed {:?}", abci_event);
-							}
-						}
-					},
-					_ => {},
+					}
 				}
-				stream::iter(events_with_height)
 			})
-			.flatten()
-			.map(|e| e.event)
-			.boxed();
-		events
+			.flat_map(stream::iter)
+			.map(|e| Ok(e.event))
+			.boxed()
 	}
 
 	async fn query_client_consensus(
@@ -466,7 +350,7 @@ where
 		// Instead, we need to pull block height via `/abci_info` and then fetch block
 		// metadata at the given height via `/blockchain` endpoint.
 		let abci_info = self
-			.rpc_http_client
+			.rpc_http_client()
 			.abci_info()
 			.await
 			.map_err(|e| Error::RpcError(format!("{e:?}")))?;
@@ -476,7 +360,7 @@ where
 		// TODO: Replace this query with `/header`, once it's available.
 		//  https://github.com/informalsystems/tendermint-rs/pull/1101
 		let blocks = self
-			.rpc_http_client
+			.rpc_http_client()
 			.blockchain(abci_info.last_block_height, abci_info.last_block_height)
 			.await
 			.map_err(|e| {
@@ -499,37 +383,42 @@ where
 
 	async fn query_packet_commitments(
 		&self,
-		_at: Height,
+		at: Height,
 		channel_id: ChannelId,
 		port_id: PortId,
 	) -> Result<Vec<u64>, Self::Error> {
-		let mut grpc_client =
-			ibc_proto::ibc::core::channel::v1::query_client::QueryClient::connect(
-				self.grpc_url().to_string(),
-			)
-			.await
-			.map_err(|e| Error::from(e.to_string()))?;
-
-		let request = QueryPacketCommitmentsRequest {
-			port_id: port_id.to_string(),
-			channel_id: channel_id.to_string(),
-			pagination: Some(PageRequest { limit: u32::MAX as _, ..Default::default() }),
-		};
-		let request = tonic::Request::new(request);
-		let response = grpc_client
-			.packet_commitments(request)
-			.await
-			.map_err(|e| Error::from(e.to_string()))?
-			.into_inner();
+		let grpc_client = ibc_proto::ibc::core::channel::v1::query_client::QueryClient::connect(
+			self.grpc_url().to_string(),
+		)
+		.await
+		.map_err(|e| Error::from(e.to_string()))?;
+
+		let commitments = query_all_pages(|pagination| {
+			let mut grpc_client = grpc_client.clone();
+			let request = QueryPacketCommitmentsRequest {
+				port_id: port_id.to_string(),
+				channel_id: channel_id.to_string(),
+				pagination: Some(pagination),
+			};
+			let request = request_at(request, at.revision_height);
+			async move {
+				let response = grpc_client
+					.packet_commitments(request)
+					.await
+					.map_err(|e| Error::from(e.to_string()))?
+					.into_inner();
+				Ok((response.commitments, response.pagination))
+			}
+		})
+		.await?;
 
-		let commitment_sequences: Vec<u64> =
-			response.commitments.into_iter().map(|v| v.sequence).collect();
+		let commitment_sequences: Vec<u64> = commitments.into_iter().map(|v| v.sequence).collect();
 		Ok(commitment_sequences)
 	}
 
 	async fn query_packet_acknowledgements(
 		&self,
-		_at: Height,
+		at: Height,
 		channel_id: ChannelId,
 		port_id: PortId,
 	) -> Result<Vec<u64>, Self::Error> {
@@ -539,92 +428,102 @@ where
 			channel_id,
 			port_id
 		);
-		let mut grpc_client =
-			ibc_proto::ibc::core::channel::v1::query_client::QueryClient::connect(
-				self.grpc_url().to_string(),
-			)
-			.await
-			.map_err(|e| Error::from(e.to_string()))?;
-
-		let request = QueryPacketAcknowledgementsRequest {
-			port_id: port_id.to_string(),
-			channel_id: channel_id.to_string(),
-			packet_commitment_sequences: vec![],
-			pagination: Some(PageRequest { limit: u32::MAX as _, ..Default::default() }),
-		};
-		let request = tonic::Request::new(request);
-		let response = grpc_client
-			.packet_acknowledgements(request)
-			.await
-			.map_err(|e| Error::from(e.to_string()))?
-			.into_inner();
+		let grpc_client = ibc_proto::ibc::core::channel::v1::query_client::QueryClient::connect(
+			self.grpc_url().to_string(),
+		)
+		.await
+		.map_err(|e| Error::from(e.to_string()))?;
+
+		let acknowledgements = query_all_pages(|pagination| {
+			let mut grpc_client = grpc_client.clone();
+			let request = QueryPacketAcknowledgementsRequest {
+				port_id: port_id.to_string(),
+				channel_id: channel_id.to_string(),
+				packet_commitment_sequences: vec![],
+				pagination: Some(pagination),
+			};
+			let request = request_at(request, at.revision_height);
+			async move {
+				let response = grpc_client
+					.packet_acknowledgements(request)
+					.await
+					.map_err(|e| Error::from(e.to_string()))?
+					.into_inner();
+				Ok((response.acknowledgements, response.pagination))
+			}
+		})
+		.await?;
 
 		let commitment_sequences: Vec<u64> =
-			response.acknowledgements.into_iter().map(|v| v.sequence).collect();
+			acknowledgements.into_iter().map(|v| v.sequence).collect();
 
 		Ok(commitment_sequences)
 	}
 
 	async fn query_unreceived_packets(
 		&self,
-		_at: Height,
+		at: Height,
 		channel_id: ChannelId,
 		port_id: PortId,
 		seqs: Vec<u64>,
 	) -> Result<Vec<u64>, Self::Error> {
-		let mut grpc_client =
-			ibc_proto::ibc::core::channel::v1::query_client::QueryClient::connect(
-				self.grpc_url().to_string(),
-			)
-			.await
-			.map_err(|e| Error::from(e.to_string()))?;
-
-		let request = QueryUnreceivedPacketsRequest {
-			port_id: port_id.to_string(),
-			channel_id: channel_id.to_string(),
-			packet_commitment_sequences: seqs,
-		};
-		let request = tonic::Request::new(request);
-		let response = grpc_client
-			.unreceived_packets(request)
-			.await
-			.map_err(|e| Error::from(e.to_string()))?
-			.into_inner();
-
-		let commitment_sequences: Vec<u64> = response.sequences.into_iter().collect();
-
-		Ok(commitment_sequences)
+		let grpc_client = ibc_proto::ibc::core::channel::v1::query_client::QueryClient::connect(
+			self.grpc_url().to_string(),
+		)
+		.await
+		.map_err(|e| Error::from(e.to_string()))?;
+
+		query_in_chunks(seqs, |seqs| {
+			let mut grpc_client = grpc_client.clone();
+			let request = QueryUnreceivedPacketsRequest {
+				port_id: port_id.to_string(),
+				channel_id: channel_id.to_string(),
+				packet_commitment_sequences: seqs,
+			};
+			let request = request_at(request, at.revision_height);
+			async move {
+				let response = grpc_client
+					.unreceived_packets(request)
+					.await
+					.map_err(|e| Error::from(e.to_string()))?
+					.into_inner();
+				Ok(response.sequences)
+			}
+		})
+		.await
 	}
 
 	async fn query_unreceived_acknowledgements(
 		&self,
-		_at: Height,
+		at: Height,
 		channel_id: ChannelId,
 		port_id: PortId,
 		seqs: Vec<u64>,
 	) -> Result<Vec<u64>, Self::Error> {
-		let mut grpc_client =
-			ibc_proto::ibc::core::channel::v1::query_client::QueryClient::connect(
-				self.grpc_url().to_string(),
-			)
-			.await
-			.map_err(|e| Error::from(e.to_string()))?;
-
-		let request = QueryUnreceivedAcksRequest {
-			port_id: port_id.to_string(),
-			channel_id: channel_id.to_string(),
-			packet_ack_sequences: seqs,
-		};
-		let request = tonic::Request::new(request);
-		let response = grpc_client
-			.unreceived_acks(request)
-			.await
-			.map_err(|e| Error::from(e.to_string()))?
-			.into_inner();
-
-		let commitment_sequences: Vec<u64> = response.sequences.into_iter().collect();
-
-		Ok(commitment_sequences)
+		let grpc_client = ibc_proto::ibc::core::channel::v1::query_client::QueryClient::connect(
+			self.grpc_url().to_string(),
+		)
+		.await
+		.map_err(|e| Error::from(e.to_string()))?;
+
+		query_in_chunks(seqs, |seqs| {
+			let mut grpc_client = grpc_client.clone();
+			let request = QueryUnreceivedAcksRequest {
+				port_id: port_id.to_string(),
+				channel_id: channel_id.to_string(),
+				packet_ack_sequences: seqs,
+			};
+			let request = request_at(request, at.revision_height);
+			async move {
+				let response = grpc_client
+					.unreceived_acks(request)
+					.await
+					.map_err(|e| Error::from(e.to_string()))?
+					.into_inner();
+				Ok(response.sequences)
+			}
+		})
+		.await
 	}
 
 	fn channel_whitelist(&self) -> HashSet<(ChannelId, PortId)> {
@@ -682,7 +581,7 @@ where
 				.and_eq("send_packet.packet_sequence", seq.to_string());
 
 			let response = self
-				.rpc_http_client
+				.rpc_http_client()
 				.tx_search(
 					query_str,
 					true,
@@ -760,7 +659,7 @@ where
 					.and_eq("write_acknowledgement.packet_sequence", seq.to_string());
 
 			let response = self
-				.rpc_http_client
+				.rpc_http_client()
 				.tx_search(
 					query_str,
 					true,
@@ -837,7 +736,7 @@ where
 			.and_eq("create_client.consensus_height", client_height.to_string());
 		for query_str in [query_update, query_create] {
 			let response = self
-				.rpc_http_client
+				.rpc_http_client()
 				.tx_search(
 					query_str,
 					true,
@@ -883,40 +782,69 @@ where
 		unimplemented!()
 	}
 
+	async fn query_upgraded_client(
+		&self,
+		at: Height,
+	) -> Result<Option<UpgradedClient>, Self::Error> {
+		// the states are stored under the height the chain halts at for the upgrade, and proven
+		// against the app hash of the header at that height, committing to the previous block
+		let query_height = at
+			.decrement()
+			.map_err(|e| Error::from(format!("Invalid upgrade height: {e}")))?;
+		let client_key = ClientUpgradePath::UpgradedClientState(at.revision_height).to_string();
+		let (client_state, proof_client_state) = self
+			.query_store_path(UPGRADE_QUERY_PATH, client_key.into_bytes(), query_height, true)
+			.await?;
+		if client_state.value.is_empty() {
+			return Ok(None)
+		}
+		let consensus_key =
+			ClientUpgradePath::UpgradedClientConsensusState(at.revision_height).to_string();
+		let (consensus_state, proof_consensus_state) = self
+			.query_store_path(UPGRADE_QUERY_PATH, consensus_key.into_bytes(), query_height, true)
+			.await?;
+		if consensus_state.value.is_empty() {
+			return Ok(None)
+		}
+
+		let client_state = AnyClientState::try_from(Any::decode(&*client_state.value)?)
+			.map_err(|e| Error::from(format!("Invalid upgraded client state: {e:?}")))?;
+		let consensus_state = AnyConsensusState::try_from(Any::decode(&*consensus_state.value)?)
+			.map_err(|e| Error::from(format!("Invalid upgraded consensus state: {e:?}")))?;
+		Ok(Some(UpgradedClient {
+			client_state,
+			consensus_state,
+			proof_client_state,
+			proof_consensus_state,
+		}))
+	}
+
 	async fn query_ibc_balance(
 		&self,
 		asset_id: Self::AssetId,
 	) -> Result<Vec<PrefixedCoin>, Self::Error> {
-		let denom = &asset_id;
-		let mut grpc_client = ibc_proto::cosmos::bank::v1beta1::query_client::QueryClient::connect(
-			self.grpc_url().to_string(),
-		)
-		.await
-		.map_err(|e| Error::from(format!("{e:?}")))?;
-
-		let request = tonic::Request::new(QueryBalanceRequest {
-			address: self.keybase.clone().account,
-			denom: denom.to_string(),
-		});
-
-		let response = grpc_client
-			.balance(request)
-			.await
-			.map(|r| r.into_inner())
-			.map_err(|e| Error::from(format!("{e:?}")))?;
+		Ok(vec![self.query_balance_of(&self.keybase.account, &asset_id).await?])
+	}
 
-		// Querying for a balance might fail, i.e. if the account doesn't actually exist
-		let balance = response
-			.balance
-			.ok_or_else(|| Error::from(format!("No balance for denom {denom}")))?;
+	async fn query_native_balance(&self) -> Result<PrefixedCoin, Self::Error> {
+		self.query_ibc_balance(self.fee_denom.clone())
+			.await?
+			.pop()
+			.ok_or_else(|| Error::from(format!("No balance for denom {}", self.fee_denom)))
+	}
 
-		Ok(vec![PrefixedCoin {
-			denom: PrefixedDenom {
-				trace_path: TracePath::default(),
-				base_denom: BaseDenom::from_str(denom)?,
-			},
-			amount: Amount::from_str(balance.amount.as_str())?,
-		}])
+	async fn query_key_balances(&self) -> Result<Vec<(String, PrefixedCoin)>, Self::Error> {
+		// the fees of all the keys are paid by the granter, if any
+		if let Some(granter) = &self.fee_granter {
+			let balance = self.query_balance_of(granter, &self.fee_denom).await?;
+			return Ok(vec![(granter.clone(), balance); self.signing_keys.len()])
+		}
+		let mut balances = vec![];
+		for key in &self.signing_keys {
+			let balance = self.query_balance_of(&key.account, &self.fee_denom).await?;
+			balances.push((key.account.clone(), balance));
+		}
+		Ok(balances)
 	}
 
 	fn connection_prefix(&self) -> CommitmentPrefix {
@@ -968,9 +896,8 @@ where
 		let request = tonic::Request::new(QueryClientStatesRequest {
 			pagination: Some(PageRequest { limit: u32::MAX as _, ..Default::default() }),
 		});
-		let grpc_client = ibc_proto::ibc::core::client::v1::query_client::QueryClient::new(
-			self.grpc_client().clone(),
-		);
+		let grpc_client =
+			ibc_proto::ibc::core::client::v1::query_client::QueryClient::new(self.grpc_client());
 		let response = grpc_client
 			.clone()
 			.client_states(request)
@@ -1051,285 +978,66 @@ where
 		Ok(connections)
 	}
 
-	async fn is_update_required(
-		&self,
-		_latest_height: u64,
-		_latest_client_height_on_counterparty: u64,
-	) -> Result<bool, Self::Error> {
-		// we never need to use LightClientSync trait in this case, because
-		// all the events will be eventually submitted via `finality_notifications`
-		Ok(false)
+	fn name(&self) -> &str {
+		&self.name
 	}
 
-	async fn initialize_client_state(
-		&self,
-	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
-		let latest_height_timestamp = self.latest_height_and_timestamp().await?;
-		let client_state = ClientState::new(
-			self.chain_id.clone(),
-			TrustThreshold::default(),
-			Duration::from_secs(64000),
-			Duration::from_secs(1814400),
-			Duration::new(15, 0),
-			latest_height_timestamp.0,
-			ProofSpecs::default(),
-			vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
-		)
-		.map_err(|e| Error::from(format!("Invalid client state {e}")))?;
-		let light_block = self
-			.light_client
-			.verify(latest_height_timestamp.0, latest_height_timestamp.0, &client_state)
+	async fn query_best_and_finalized_heights(&self) -> Result<(u64, u64), Self::Error> {
+		// tendermint has instant finality
+		let abci_info = self
+			.rpc_http_client()
+			.abci_info()
 			.await
-			.map_err(|e| Error::from(format!("Invalid light block {e}")))?;
-		let consensus_state = ConsensusState::from(light_block.signed_header.header);
-		Ok((
-			AnyClientState::Tendermint(client_state),
-			AnyConsensusState::Tendermint(consensus_state),
-		))
+			.map_err(|e| Error::RpcError(format!("{e:?}")))?;
+		let height = u64::from(abci_info.last_block_height);
+		Ok((height, height))
 	}
 
-	async fn query_client_id_from_tx_hash(
-		&self,
-		tx_id: Self::TransactionId,
-	) -> Result<ClientId, Self::Error> {
-		const WAIT_BACKOFF: Duration = Duration::from_millis(300);
-		const TIME_OUT: Duration = Duration::from_millis(30000);
-		let start_time = std::time::Instant::now();
-
-		let response: Response = loop {
-			let response = self
-				.rpc_ws_client()
-				.tx_search(
-					Query::eq("tx.hash", tx_id.hash.to_string()),
-					false,
-					1,
-					1, // get only the first Tx matching the query
-					Order::Ascending,
-				)
-				.await
-				.map_err(|e| Error::from(format!("Failed to query tx hash: {e}")))?;
-			match response.txs.into_iter().next() {
-				None => {
-					let elapsed = start_time.elapsed();
-					if elapsed > TIME_OUT {
-						return Err(Error::from(format!(
-							"Timeout waiting for tx {:?} to be included in a block",
-							tx_id.hash
-						)))
-					} else {
-						std::thread::sleep(WAIT_BACKOFF);
-					}
-				},
-				Some(resp) => break resp,
+	async fn handle_error(&mut self, error: &anyhow::Error) -> Result<(), anyhow::Error> {
+		let err_str = if let Some(rpc_err) = error.downcast_ref::<Error>() {
+			match rpc_err {
+				Error::RpcError(s) => s.clone(),
+				_ => "".to_string(),
 			}
-		};
-
-		let height = Height::new(
-			ChainId::chain_version(self.chain_id.to_string().as_str()),
-			response.height.value(),
-		);
-		let deliver_tx_result = response.tx_result;
-		if deliver_tx_result.code.is_err() {
-			Err(Error::from(format!(
-				"Transaction failed with code {:?} and log {:?}",
-				deliver_tx_result.code, deliver_tx_result.log
-			)))
 		} else {
-			let result = deliver_tx_result
-				.events
-				.iter()
-				.flat_map(|e| ibc_event_try_from_abci_event(e, height).ok().into_iter())
-				.filter(|e| matches!(e, IbcEvent::CreateClient(_)))
-				.collect::<Vec<_>>();
-			if result.len() != 1 {
-				Err(Error::from(format!(
-					"Expected exactly one CreateClient event, found {}",
-					result.len()
-				)))
-			} else {
-				Ok(match result[0] {
-					IbcEvent::CreateClient(ref e) => e.client_id().clone(),
-					_ => unreachable!(),
-				})
-			}
+			error.to_string()
+		};
+		log::debug!(target: "hyperspace_cosmos", "Handling error: {err_str}");
+		if err_str.contains("dispatch task is gone") ||
+			err_str.contains("failed to send message to internal channel")
+		{
+			self.reconnect().await?;
+			self.common_state.rpc_call_delay *= 2;
 		}
-	}
 
-	async fn query_connection_id_from_tx_hash(
-		&self,
-		tx_id: Self::TransactionId,
-	) -> Result<ConnectionId, Self::Error> {
-		const WAIT_BACKOFF: Duration = Duration::from_millis(300);
-		const TIME_OUT: Duration = Duration::from_millis(30000);
-		let start_time = std::time::Instant::now();
-
-		let response: Response = loop {
-			let response = self
-				.rpc_ws_client()
-				.tx_search(
-					Query::eq("tx.hash", tx_id.hash.to_string()),
-					false,
-					1,
-					1, // get only the first Tx matching the query
-					Order::Ascending,
-				)
-				.await
-				.map_err(|e| Error::from(format!("Failed to query tx hash: {e}")))?;
-			match response.txs.into_iter().next() {
-				None => {
-					let elapsed = start_time.elapsed();
-					if elapsed > TIME_OUT {
-						return Err(Error::from(format!(
-							"Timeout waiting for tx {:?} to be included in a block",
-							tx_id.hash
-						)))
-					} else {
-						std::thread::sleep(WAIT_BACKOFF);
-					}
-				},
-				Some(resp) => break resp,
-			}
-		};
+		Ok(())
+	}
 
-		let height = Height::new(
-			ChainId::chain_version(self.chain_id.to_string().as_str()),
-			response.height.value(),
-		);
-		let deliver_tx_result = response.tx_result;
-		if deliver_tx_result.code.is_err() {
-			Err(Error::from(format!(
-				"Transaction failed with code {:?} and log {:?}",
-				deliver_tx_result.code, deliver_tx_result.log
-			)))
-		} else {
-			let result = deliver_tx_result
-				.events
-				.iter()
-				.flat_map(|e| ibc_event_try_from_abci_event(e, height).ok().into_iter())
-				.filter(|e| matches!(e, IbcEvent::OpenInitConnection(_)))
-				.collect::<Vec<_>>();
-			if result.len() != 1 {
-				Err(Error::from(format!(
-					"Expected exactly one CreateClient event, found {}",
-					result.len()
-				)))
-			} else {
-				Ok(match result[0] {
-					IbcEvent::OpenInitConnection(ref e) =>
-						e.connection_id().expect("Connection id wasn't found").clone(),
-					_ => unreachable!(),
-				})
-			}
-		}
+	fn rpc_call_delay(&self) -> Duration {
+		self.common_state.rpc_call_delay
 	}
 
-	async fn query_channel_id_from_tx_hash(
-		&self,
-		tx_id: Self::TransactionId,
-	) -> Result<(ChannelId, PortId), Self::Error> {
-		const WAIT_BACKOFF: Duration = Duration::from_millis(300);
-		const TIME_OUT: Duration = Duration::from_millis(30000);
-		let start_time = std::time::Instant::now();
+	fn set_rpc_call_delay(&mut self, delay: Duration) {
+		self.common_state.rpc_call_delay = delay;
+	}
 
-		let response: Response = loop {
-			let response = self
-				.rpc_ws_client()
-				.tx_search(
-					Query::eq("tx.hash", tx_id.hash.to_string()),
-					false,
-					1,
-					1, // get only the first Tx matching the query
-					Order::Ascending,
-				)
-				.await
-				.map_err(|e| Error::from(format!("Failed to query tx hash: {e}")))?;
-			match response.txs.into_iter().next() {
-				None => {
-					let elapsed = start_time.elapsed();
-					if elapsed > TIME_OUT {
-						return Err(Error::from(format!(
-							"Timeout waiting for tx {:?} to be included in a block",
-							tx_id.hash
-						)))
-					} else {
-						std::thread::sleep(WAIT_BACKOFF);
-					}
-				},
-				Some(resp) => break resp,
-			}
-		};
+	fn common_state(&self) -> &CommonClientState {
+		&self.common_state
+	}
 
-		let height = Height::new(
-			ChainId::chain_version(self.chain_id.to_string().as_str()),
-			response.height.value(),
-		);
-		let deliver_tx_result = response.tx_result;
-		if deliver_tx_result.code.is_err() {
-			Err(Error::from(format!(
-				"Transaction failed with code {:?} and log {:?}",
-				deliver_tx_result.code, deliver_tx_result.log
-			)))
-		} else {
-			let result = deliver_tx_result
-				.events
-				.iter()
-				.flat_map(|e| ibc_event_try_from_abci_event(e, height).ok().into_iter())
-				.filter(|e| matches!(e, IbcEvent::OpenInitChannel(_)))
-				.collect::<Vec<_>>();
-			if result.len() != 1 {
-				Err(Error::from(format!(
-					"Expected exactly one CreateClient event, found {}",
-					result.len()
-				)))
-			} else {
-				Ok(match result[0] {
-					IbcEvent::OpenInitChannel(ref e) =>
-						(*e.channel_id().expect("Channel id wasn't found"), e.port_id().clone()),
-					_ => unreachable!(),
-				})
-			}
-		}
+	fn common_state_mut(&mut self) -> &mut CommonClientState {
+		&mut self.common_state
 	}
 
-	async fn upload_wasm(&self, wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
-		let msg = MsgPushNewWasmCode { signer: self.account_id(), code: wasm };
-		let hash = self.submit(vec![msg.into()]).await?;
-		let resp = self.wait_for_tx_result(hash).await?;
-		let height = Height::new(
-			ChainId::chain_version(self.chain_id.to_string().as_str()),
-			resp.height.value(),
-		);
-		let deliver_tx_result = resp.tx_result;
-		let mut result = deliver_tx_result
-			.events
-			.iter()
-			.flat_map(|e| ibc_event_try_from_abci_event(e, height).ok().into_iter())
-			.filter(|e| matches!(e, IbcEvent::PushWasmCode(_)))
-			.collect::<Vec<_>>();
-		let code_id = if result.len() != 1 {
-			return Err(Error::from(format!(
-				"Expected exactly one PushWasmCode event, found {}",
-				result.len()
-			)))
-		} else {
-			match result.pop().unwrap() {
-				IbcEvent::PushWasmCode(ev) => ev.0,
-				_ => unreachable!(),
-			}
-		};
-		// let resp = MsgClient::connect(
-		// 	Endpoint::try_from(self.grpc_url().to_string())
-		// 		.map_err(|e| Error::from(format!("Failed to parse grpc url: {:?}", e)))?,
-		// )
-		// .await
-		// .map_err(|e| Error::from(format!("Failed to connect to grpc endpoint: {:?}", e)))?
-		// .push_new_wasm_code(msg)
-		// .await
-		// .map_err(|e| {
-		// 	Error::from(format!("Failed to upload wasm code to grpc endpoint: {:?}", e))
-		// })?;
-
-		Ok(code_id)
+	async fn reconnect(&mut self) -> anyhow::Result<()> {
+		// TODO: don't reconnect if the url is not presented
+		let (rpc_client, ws_driver) = WebSocketClient::new(self.websocket_url().clone())
+			.await
+			.map_err(|e| Error::RpcError(format!("{e:?}")))?;
+		self.join_handles.lock().await.push(tokio::spawn(ws_driver.run()));
+		self.rpc_ws_client = Some(rpc_client);
+		log::info!(target: "hyperspace_cosmos", "Reconnected to cosmos chain");
+		Ok(())
 	}
 }
 
@@ -1337,21 +1045,21 @@ impl<H> CosmosClient<H>
 where
 	H: 'static + Clone + Send + Sync,
 {
-	async fn parse_ibc_events_at<C: Chain>(
+	pub(crate) async fn parse_ibc_events_at<C: Chain>(
 		&self,
 		counterparty: &C,
 		latest_revision: u64,
 		height: u64,
-	) -> Result<Vec<IbcEvent>, <Self as IbcProvider>::Error> {
+	) -> Result<Vec<IbcEvent>, <Self as IbcQuerier>::Error> {
 		let mut ibc_events = Vec::new();
 
 		let block_results = self
-			.rpc_http_client
+			.rpc_http_client()
 			.block_results(TmHeight::try_from(height)?)
 			.await
 			.map_err(|e| {
-			Error::from(format!("Failed to query block result for height {height:?}: {e:?}"))
-		})?;
+				Error::from(format!("Failed to query block result for height {height:?}: {e:?}"))
+			})?;
 
 		let tx_events = block_results
 			.txs_results
@@ -1388,6 +1096,25 @@ where
 						log::debug!(target: "hyperspace_cosmos", "Filtered out event: {:?}", event.kind);
 					}
 				},
+				None if event.kind == INCENTIVIZED_PACKET_EVENT => {
+					match incentivized_packet_try_from_abci_event(&event) {
+						Ok((port_id, channel_id, sequence, fees))
+							if channel_and_port_ids.contains(&(channel_id, port_id.clone())) =>
+						{
+							log::debug!(target: "hyperspace_cosmos", "Packet {port_id}/{channel_id}/{sequence} is incentivized: {fees:?}");
+							self.common_state()
+								.incentivized_packets
+								.lock()
+								.unwrap()
+								.insert(port_id, channel_id, sequence, fees);
+						},
+						Ok(_) => {},
+						Err(e) => {
+							log::warn!(target: "hyperspace_cosmos", "Failed to parse incentivized packet event: {e}")
+						},
+					}
+					continue
+				},
 				None => {
 					let ignored_events = [
 						"commission",
@@ -1416,17 +1143,17 @@ where
 
 impl<H: Clone + Send + Sync + 'static> CosmosClient<H> {
 	#[allow(unused)]
-	async fn wait_for_tx_result(
+	pub(crate) async fn wait_for_tx_result(
 		&self,
-		tx_id: <Self as IbcProvider>::TransactionId,
-	) -> Result<Response, <Self as IbcProvider>::Error> {
+		tx_id: <Self as TxSubmitter>::TransactionId,
+	) -> Result<Response, <Self as IbcQuerier>::Error> {
 		const WAIT_BACKOFF: Duration = Duration::from_millis(300);
 		const TIME_OUT: Duration = Duration::from_millis(30000);
 		let start_time = std::time::Instant::now();
 
 		let response: Response = loop {
 			let response = self
-				.rpc_http_client
+				.rpc_http_client()
 				.tx_search(
 					Query::eq("tx.hash", tx_id.hash.to_string()),
 					false,
@@ -1472,3 +1199,103 @@ fn increment_proof_height(
 		..height
 	})
 }
+
+/// IBC events of an event of the [`CosmosClient::ibc_events`] subscriptions. Like what
+/// `get_all_events()` does in `hermes`.
+fn subscription_ibc_events(
+	event: Event,
+	chain_id: &ChainId,
+	common_state: &CommonClientState,
+) -> Vec<IbcEventWithHeight> {
+	let mut events_with_height: Vec<IbcEventWithHeight> = vec![];
+	let Event { data, events: _, query } = event;
+	match data {
+		EventData::NewBlock { block, .. }
+			if query == Query::from(EventType::NewBlock).to_string() =>
+		{
+			let Some(block) = block.as_ref() else {
+				common_state.on_skipped_stream_item("NewBlock event without a block");
+				return events_with_height;
+			};
+			let height = Height::new(
+				ChainId::chain_version(chain_id.to_string().as_str()),
+				u64::from(block.header.height),
+			);
+			events_with_height
+				.push(IbcEventWithHeight::new(ClientEvents::NewBlock::new(height).into(), height));
+		},
+		EventData::Tx { tx_result } => {
+			let height = Height::new(
+				ChainId::chain_version(chain_id.to_string().as_str()),
+				tx_result.height as u64,
+			);
+			for abci_event in &tx_result.result.events {
+				if let Ok(ibc_event) = ibc_event_try_from_abci_event(abci_event, height) {
+					log::debug!(target: "hyperspace_cosmos", "Retrieved event: {}, query: {}, parsed: {:?}", abci_event.kind, query, ibc_event);
+					let is_client_event = query ==
+						Query::eq("message.module", "ibc_client").to_string() &&
+						event_is_type_client(&ibc_event);
+					let is_connection_event = (query ==
+						Query::eq("message.module", "ibc_connection").to_string() ||
+						query == Query::eq("message.module", "ibc_client").to_string()) &&
+						event_is_type_connection(&ibc_event);
+					let is_channel_event = (query ==
+						Query::eq("message.module", "ibc_channel").to_string() ||
+						query == Query::eq("message.module", "wasm").to_string()) &&
+						event_is_type_channel(&ibc_event);
+					if is_client_event || is_connection_event || is_channel_event {
+						events_with_height.push(IbcEventWithHeight::new(ibc_event, height));
+					} else {
+						log::debug!(target: "hyperspace_cosmos", "the event is unknown");
+					}
+				} else {
+					log::debug!(target: "hyperspace_cosmos", "Event wasn't parsed {:?}", abci_event);
+				}
+			}
+		},
+		_ => {},
+	}
+	events_with_height
+}
+
+/// IBC events of the block at `height`, committed while the [`CosmosClient::ibc_events`]
+/// subscriptions were disconnected.
+async fn missed_block_ibc_events(
+	rpc_client: &HttpClient,
+	chain_id: &ChainId,
+	height: u64,
+	common_state: &CommonClientState,
+) -> Vec<IbcEventWithHeight> {
+	let ibc_height = Height::new(ChainId::chain_version(chain_id.to_string().as_str()), height);
+	let mut events_with_height =
+		vec![IbcEventWithHeight::new(ClientEvents::NewBlock::new(ibc_height).into(), ibc_height)];
+	let block_results = match TmHeight::try_from(height) {
+		Ok(tm_height) => rpc_client.block_results(tm_height).await.map_err(|e| format!("{e:?}")),
+		Err(e) => Err(format!("{e:?}")),
+	};
+	let block_results = match block_results {
+		Ok(block_results) => block_results,
+		Err(e) => {
+			common_state.on_skipped_stream_item(&format!(
+				"Failed to query block results of missed block {height}: {e}"
+			));
+			return events_with_height;
+		},
+	};
+	let tx_events = block_results
+		.txs_results
+		.unwrap_or_default()
+		.into_iter()
+		.flat_map(|tx| tx.events);
+	for abci_event in tx_events {
+		if let Ok(ibc_event) = ibc_event_try_from_abci_event(&abci_event, ibc_height) {
+			if event_is_type_client(&ibc_event) ||
+				event_is_type_connection(&ibc_event) ||
+				event_is_type_channel(&ibc_event)
+			{
+				events_with_height.push(IbcEventWithHeight::new(ibc_event, ibc_height));
+			}
+		}
+	}
+	events_with_height
+}
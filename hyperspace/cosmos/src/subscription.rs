@@ -0,0 +1,220 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tendermint websocket subscriptions that survive disconnections. The subscriptions are
+//! re-established with an exponential backoff whenever the websocket drops or goes silent, and the
+//! blocks committed while disconnected are yielded as [`SubscriptionItem::MissedBlock`], so that
+//! the consumers can replay them from the rpc.
+
+use crate::{
+	endpoints::{Endpoint, Endpoints},
+	error::Error,
+};
+use futures::{
+	stream::{select_all, BoxStream},
+	StreamExt,
+};
+use primitives::CommonClientState;
+use std::{collections::VecDeque, time::Duration};
+use tendermint_rpc::{
+	event::{Event, EventData},
+	query::Query,
+	Client, Error as RpcError, SubscriptionClient, WebSocketClient,
+};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+/// Delay before the first reconnection attempt, doubled after every failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Maximum delay between two reconnection attempts.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Time without any item after which the websocket is considered dead, as a connection that
+/// drops silently doesn't end the subscriptions.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Item of a [`subscribe`] stream.
+#[derive(Debug)]
+pub enum SubscriptionItem {
+	/// Event received from the websocket
+	Event(Event),
+	/// Height of a block committed while the subscriptions were disconnected
+	MissedBlock(u64),
+}
+
+struct State {
+	endpoints: Endpoints,
+	/// Endpoint the subscriptions are made on
+	endpoint: Option<Endpoint>,
+	queries: Vec<Query>,
+	common_state: CommonClientState,
+	client: Option<WebSocketClient>,
+	subscriptions: Option<BoxStream<'static, Result<Event, RpcError>>>,
+	driver: Option<JoinHandle<()>>,
+	/// Latest block height yielded, either from a `NewBlock` event or replayed
+	latest_height: Option<u64>,
+	pending: VecDeque<SubscriptionItem>,
+	backoff: Duration,
+}
+
+impl Drop for State {
+	fn drop(&mut self) {
+		if let Some(driver) = self.driver.take() {
+			driver.abort();
+		}
+	}
+}
+
+impl State {
+	async fn connect(&mut self) -> Result<(), Error> {
+		let endpoint = self.endpoints.active();
+		let result = self.subscribe(&endpoint).await;
+		if let Err(e) = &result {
+			self.endpoints.on_failure(endpoint.index, &format!("failed to subscribe {e}"));
+		}
+		self.endpoint = Some(endpoint);
+		result
+	}
+
+	async fn subscribe(&mut self, endpoint: &Endpoint) -> Result<(), Error> {
+		let websocket_url = endpoint.websocket_url.clone().ok_or_else(|| {
+			Error::Custom(format!("No websocket url for endpoint {}", endpoint.rpc_url))
+		})?;
+		let (client, driver) = WebSocketClient::new(websocket_url)
+			.await
+			.map_err(|e| Error::RpcError(format!("failed to connect to Websocket {e:?}")))?;
+		let driver = tokio::spawn(async move {
+			if let Err(e) = driver.run().await {
+				log::debug!(target: "hyperspace_cosmos", "Websocket driver stopped: {e:?}");
+			}
+		});
+		if let Some(previous) = self.driver.replace(driver) {
+			previous.abort();
+		}
+		let mut subscriptions = vec![];
+		for query in &self.queries {
+			subscriptions.push(
+				client.subscribe(query.clone()).await.map_err(|e| {
+					Error::RpcError(format!("failed to subscribe to {query} {e:?}"))
+				})?,
+			);
+		}
+		self.client = Some(client);
+		self.subscriptions = Some(select_all(subscriptions).boxed());
+
+		// the blocks committed while disconnected are replayed, subscribing before querying the
+		// latest height so that none is missed
+		if let Some(latest_height) = self.latest_height {
+			let height = endpoint
+				.rpc_client
+				.latest_block()
+				.await
+				.map_err(|e| Error::RpcError(format!("failed to query the latest block {e:?}")))?
+				.block
+				.header
+				.height
+				.value();
+			self.pending
+				.extend((latest_height + 1..=height).map(SubscriptionItem::MissedBlock));
+			self.latest_height = Some(height.max(latest_height));
+		}
+		Ok(())
+	}
+
+	fn disconnect(&mut self, reason: &str) {
+		self.common_state.on_disconnect(reason);
+		if let Some(endpoint) = self.endpoint.take() {
+			self.endpoints.on_failure(endpoint.index, reason);
+		}
+		self.client = None;
+		self.subscriptions = None;
+		if let Some(driver) = self.driver.take() {
+			driver.abort();
+		}
+	}
+
+	async fn next(&mut self) -> Result<SubscriptionItem, RpcError> {
+		loop {
+			if let Some(item) = self.pending.pop_front() {
+				return Ok(item)
+			}
+			let Some(subscriptions) = self.subscriptions.as_mut() else {
+				if let Err(e) = self.connect().await {
+					log::warn!(target: "hyperspace_cosmos", "Failed to resubscribe, retrying in {:?}: {e}", self.backoff);
+					self.subscriptions = None;
+					tokio::time::sleep(self.backoff).await;
+					self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+				} else {
+					self.backoff = INITIAL_BACKOFF;
+				}
+				continue
+			};
+			match tokio::time::timeout(IDLE_TIMEOUT, subscriptions.next()).await {
+				Ok(Some(Ok(event))) => {
+					if let EventData::NewBlock { block: Some(block), .. } = &event.data {
+						let height = block.header.height.value();
+						// already replayed after a reconnection
+						if self.latest_height.map_or(false, |latest| height <= latest) {
+							continue
+						}
+						self.latest_height = Some(height);
+					}
+					return Ok(SubscriptionItem::Event(event))
+				},
+				Ok(Some(Err(e))) => return Err(e),
+				Ok(None) => self.disconnect("websocket closed"),
+				Err(_) => self.disconnect(&format!("no event in {IDLE_TIMEOUT:?}")),
+			}
+		}
+	}
+}
+
+/// Subscribes to `queries` on the websocket of the active endpoint, resubscribing from a
+/// background task whenever the connection is lost, until the returned stream is dropped. The
+/// disconnections are recorded in `common_state`, and the heights of the blocks committed while
+/// disconnected are queried from the endpoint resubscribed to. Replaying the missed blocks relies
+/// on a `NewBlock` query being part of `queries`.
+pub async fn subscribe(
+	endpoints: Endpoints,
+	queries: Vec<Query>,
+	common_state: CommonClientState,
+) -> Result<UnboundedReceiverStream<Result<SubscriptionItem, RpcError>>, Error> {
+	let mut state = State {
+		endpoints,
+		endpoint: None,
+		queries,
+		common_state,
+		client: None,
+		subscriptions: None,
+		driver: None,
+		latest_height: None,
+		pending: VecDeque::new(),
+		backoff: INITIAL_BACKOFF,
+	};
+	// the first subscription is made eagerly so that a wrong url or query is reported
+	state.connect().await?;
+	let (sender, receiver) = mpsc::unbounded_channel();
+	tokio::spawn(async move {
+		loop {
+			tokio::select! {
+				item = state.next() => if sender.send(item).is_err() {
+					break
+				},
+				_ = sender.closed() => break,
+			}
+		}
+	});
+	Ok(UnboundedReceiverStream::new(receiver))
+}
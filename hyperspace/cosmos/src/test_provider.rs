@@ -1,4 +1,7 @@
-use super::client::CosmosClient;
+use super::{
+	client::CosmosClient,
+	subscription::{self, SubscriptionItem},
+};
 use crate::error::Error;
 use core::pin::Pin;
 use futures::{Stream, StreamExt};
@@ -11,7 +14,6 @@ use primitives::TestProvider;
 use tendermint_rpc::{
 	event::{Event, EventData},
 	query::{EventType, Query},
-	SubscriptionClient,
 };
 
 #[async_trait::async_trait]
@@ -36,23 +38,42 @@ where
 	}
 
 	/// Returns a stream that yields chain Block number
-	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>> {
-		let ws_client = self.rpc_ws_client();
-
-		let subscription = ws_client.subscribe(Query::from(EventType::NewBlock)).await.unwrap();
+	async fn subscribe_blocks(
+		&self,
+	) -> Pin<Box<dyn Stream<Item = Result<u64, Self::Error>> + Send + Sync>> {
+		let subscription = match subscription::subscribe(
+			self.endpoints.clone(),
+			vec![Query::from(EventType::NewBlock)],
+			self.common_state.clone(),
+		)
+		.await
+		{
+			Ok(subscription) => subscription,
+			Err(e) =>
+				return Box::pin(futures::stream::once(futures::future::ready(Err(Error::from(
+					format!("Web Socket Client Error {e:?}"),
+				))))),
+		};
 		log::info!(target: "hyperspace_cosmos", "🛰️ Subscribed to {} listening to finality notifications", self.name);
-		let stream = subscription.filter_map(|event| {
-			let event = event.unwrap();
-			let get_height = |event: &Event| {
-				let Event { data, events: _, query: _ } = &event;
-				let height = match &data {
-					EventData::NewBlock { block, .. } =>
-						block.as_ref().unwrap().header.height.value(),
-					_ => unreachable!(),
-				};
-				height
+		let common_state = self.common_state.clone();
+		let stream = subscription.filter_map(move |item| {
+			let height = match item {
+				Ok(SubscriptionItem::Event(Event {
+					data: EventData::NewBlock { block: Some(block), .. },
+					..
+				})) => Some(Ok(block.header.height.value())),
+				Ok(SubscriptionItem::Event(_)) => {
+					common_state.on_skipped_stream_item("NewBlock event without a block");
+					None
+				},
+				Ok(SubscriptionItem::MissedBlock(height)) => Some(Ok(height)),
+				Err(e) => {
+					common_state
+						.on_skipped_stream_item(&format!("Web Socket subscription error {e:?}"));
+					None
+				},
 			};
-			futures::future::ready(Some(get_height(&event)))
+			futures::future::ready(height)
 		});
 		Box::pin(stream)
 	}
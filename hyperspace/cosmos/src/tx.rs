@@ -1,7 +1,7 @@
 use super::{
 	encode::{
 		encode_auth_info, encode_key_bytes, encode_sign_doc, encode_signer_info, encode_tx,
-		encode_tx_body,
+		encode_tx_body, TxBodyOptions,
 	},
 	key_provider::KeyEntry,
 };
@@ -13,16 +13,32 @@ use ibc_proto::{
 	cosmos::{
 		auth::v1beta1::BaseAccount,
 		tx::v1beta1::{
-			service_client::ServiceClient, Fee, SimulateRequest, SimulateResponse, Tx, TxRaw,
+			service_client::ServiceClient, BroadcastMode, BroadcastTxRequest, Fee, SimulateRequest,
+			SimulateResponse, Tx, TxRaw,
 		},
 	},
 	google::protobuf::Any,
+	ibc::core::{
+		channel::v1::{
+			MsgAcknowledgement, MsgChannelCloseConfirm, MsgChannelCloseInit, MsgChannelOpenAck,
+			MsgChannelOpenConfirm, MsgChannelOpenInit, MsgChannelOpenTry, MsgRecvPacket,
+			MsgTimeout, MsgTimeoutOnClose,
+		},
+		client::v1::{MsgCreateClient, MsgSubmitMisbehaviour, MsgUpdateClient, MsgUpgradeClient},
+		connection::v1::{
+			MsgConnectionOpenAck, MsgConnectionOpenConfirm, MsgConnectionOpenInit,
+			MsgConnectionOpenTry,
+		},
+	},
 };
+use ibc_proto_registry::RawAny;
 use prost::Message;
+use std::str::FromStr;
 use tendermint::Hash;
 use tendermint_rpc::{
 	endpoint::tx::Response as TxResponse, query::Query, Client, Order, Url, WebSocketClient,
 };
+use tonic::transport::Channel;
 
 pub fn sign_tx(
 	key: KeyEntry,
@@ -30,15 +46,17 @@ pub fn sign_tx(
 	account_info: &BaseAccount,
 	messages: Vec<Any>,
 	fee: Fee,
+	options: TxBodyOptions,
 ) -> Result<(Tx, TxRaw, Vec<u8>), Error> {
 	let pk_bytes = encode_key_bytes(&key)?;
-	let signer_info = encode_signer_info(account_info.sequence, pk_bytes)?;
+	let signer_info =
+		encode_signer_info(account_info.sequence, &key.public_key_type_url, pk_bytes)?;
 
 	// Create and Encode AuthInfo
 	let (auth_info, auth_info_bytes) = encode_auth_info(signer_info, fee)?;
 
 	// Create and Encode TxBody
-	let (body, body_bytes) = encode_tx_body(messages)?;
+	let (body, body_bytes) = encode_tx_body(messages, options)?;
 
 	// Create and Encode TxRaw
 	let signature_bytes = encode_sign_doc(
@@ -57,6 +75,36 @@ pub fn sign_tx(
 	Ok((tx, tx_raw, tx_bytes))
 }
 
+/// Returns `message` with its signer set to `signer`, or `None` if it isn't an IBC core message.
+pub fn with_signer(message: &Any, signer: &str) -> Option<Any> {
+	let mut raw = RawAny::decode(message).ok()?;
+	match &mut raw {
+		RawAny::MsgCreateClient(MsgCreateClient { signer: msg_signer, .. }) |
+		RawAny::MsgUpdateClient(MsgUpdateClient { signer: msg_signer, .. }) |
+		RawAny::MsgUpgradeClient(MsgUpgradeClient { signer: msg_signer, .. }) |
+		RawAny::MsgSubmitMisbehaviour(MsgSubmitMisbehaviour { signer: msg_signer, .. }) |
+		RawAny::MsgConnectionOpenInit(MsgConnectionOpenInit { signer: msg_signer, .. }) |
+		RawAny::MsgConnectionOpenTry(MsgConnectionOpenTry { signer: msg_signer, .. }) |
+		RawAny::MsgConnectionOpenAck(MsgConnectionOpenAck { signer: msg_signer, .. }) |
+		RawAny::MsgConnectionOpenConfirm(MsgConnectionOpenConfirm {
+			signer: msg_signer, ..
+		}) |
+		RawAny::MsgChannelOpenInit(MsgChannelOpenInit { signer: msg_signer, .. }) |
+		RawAny::MsgChannelOpenTry(MsgChannelOpenTry { signer: msg_signer, .. }) |
+		RawAny::MsgChannelOpenAck(MsgChannelOpenAck { signer: msg_signer, .. }) |
+		RawAny::MsgChannelOpenConfirm(MsgChannelOpenConfirm { signer: msg_signer, .. }) |
+		RawAny::MsgChannelCloseInit(MsgChannelCloseInit { signer: msg_signer, .. }) |
+		RawAny::MsgChannelCloseConfirm(MsgChannelCloseConfirm { signer: msg_signer, .. }) |
+		RawAny::MsgRecvPacket(MsgRecvPacket { signer: msg_signer, .. }) |
+		RawAny::MsgAcknowledgement(MsgAcknowledgement { signer: msg_signer, .. }) |
+		RawAny::MsgTimeout(MsgTimeout { signer: msg_signer, .. }) |
+		RawAny::MsgTimeoutOnClose(MsgTimeoutOnClose { signer: msg_signer, .. }) =>
+			*msg_signer = signer.to_string(),
+		_ => return None,
+	}
+	Some(raw.encode())
+}
+
 pub async fn simulate_tx(
 	grpc_url: Url,
 	tx: Tx,
@@ -90,7 +138,30 @@ pub async fn broadcast_tx(rpc_client: &WebSocketClient, tx_bytes: Vec<u8>) -> Re
 	Ok(response.hash)
 }
 
-pub async fn confirm_tx(rpc_client: &WebSocketClient, tx_hash: Hash) -> Result<Hash, Error> {
+/// Broadcasts `tx_bytes` with the `BroadcastTx` method of the grpc tx service, in sync mode. Fails
+/// with [`Error::GrpcError`] if the request couldn't be served, e.g. because the service is rate
+/// limited or disabled on the node.
+pub async fn broadcast_tx_grpc(grpc_client: Channel, tx_bytes: Vec<u8>) -> Result<Hash, Error> {
+	let request = BroadcastTxRequest { tx_bytes, mode: BroadcastMode::Sync as i32 };
+	let response = ServiceClient::new(grpc_client)
+		.broadcast_tx(request)
+		.await
+		.map_err(|e| Error::GrpcError(format!("failed to broadcast transaction {e:?}")))?
+		.into_inner()
+		.tx_response
+		.ok_or_else(|| Error::GrpcError("empty broadcast response".to_string()))?;
+	if response.code != 0 {
+		return Err(Error::from(format!(
+			"transaction {} failed with code {}: {}",
+			response.txhash, response.code, response.raw_log
+		)))
+	}
+	Ok(Hash::from_str(&response.txhash)?)
+}
+
+/// Waits for the transaction `tx_hash` to be committed and returns its result, failing if its
+/// execution failed.
+pub async fn confirm_tx(rpc_client: &WebSocketClient, tx_hash: Hash) -> Result<TxResponse, Error> {
 	let start_time = tokio::time::Instant::now();
 	let timeout = Duration::from_millis(30000);
 	const WAIT_BACKOFF: Duration = Duration::from_millis(300);
@@ -126,7 +197,7 @@ pub async fn confirm_tx(rpc_client: &WebSocketClient, tx_hash: Hash) -> Result<H
 	if response_code.is_err() {
 		return Err(Error::from(format!("transaction {tx_hash} failed with code {response_code:?}")))
 	}
-	Ok(response.hash)
+	Ok(response)
 }
 
 pub fn encoded_tx_metrics(
@@ -134,8 +205,9 @@ pub fn encoded_tx_metrics(
 	chain_id: ChainId,
 	account_info: &BaseAccount,
 	fee: Fee,
+	options: TxBodyOptions,
 ) -> Result<(usize, usize), Error> {
-	let (_, tx_raw, _) = sign_tx(key, chain_id, account_info, vec![], fee)?;
+	let (_, tx_raw, _) = sign_tx(key, chain_id, account_info, vec![], fee, options)?;
 
 	let total_len = tx_raw.encoded_len();
 	let body_bytes_len = tx_raw.body_bytes.len();
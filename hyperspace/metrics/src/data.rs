@@ -13,7 +13,10 @@
 // limitations under the License.
 
 use super::*;
-use crate::register;
+use crate::{
+	latency::{PacketLatencyWindows, DEFAULT_LATENCY_WINDOW},
+	register,
+};
 use ibc::{core::ics24_host::identifier::ClientId, Height};
 use std::collections::HashMap;
 
@@ -118,10 +121,64 @@ pub struct Metrics {
 	pub number_of_undelivered_packets: Gauge<U64>,
 	/// Number of undelivered acknowledgements over time.
 	pub number_of_undelivered_acknowledgements: Gauge<U64>,
+	/// Number of stream items (blocks, events) skipped because they couldn't be fetched or
+	/// decoded.
+	pub number_of_skipped_stream_items: Gauge<U64>,
+	/// Number of times the event subscriptions were disconnected.
+	pub number_of_disconnects: Gauge<U64>,
+	/// Number of transactions of the relayer account waiting to be submitted to this chain or in
+	/// flight.
+	pub submission_queue_depth: Gauge<U64>,
 	/// Gas cost for every sent tx bundle.
 	pub gas_cost_for_sent_tx_bundle: Histogram,
 	/// Transaction length (in bytes) for every sent tx bundle.
 	pub transaction_length_for_sent_tx_bundle: Histogram,
+	/// Estimated fee of every sent tx bundle, per denomination.
+	pub fee_estimate_for_sent_tx_bundle: HistogramVec,
+	/// Gas spent by every successful submission.
+	pub gas_spent_per_submission: Histogram,
+	/// Total gas spent by successful submissions.
+	pub total_gas_spent: Counter<U64>,
+	/// Gas of every committed transaction as simulated before its submission, for chains that
+	/// simulate their transactions.
+	pub simulated_gas_per_transaction: Histogram,
+	/// Gas used by every committed transaction whose gas was simulated.
+	pub used_gas_per_transaction: Histogram,
+	/// Ratio of the gas used by every committed transaction to its simulated gas.
+	pub used_to_simulated_gas_ratio: Histogram,
+
+	/// Total number of packets successfully delivered to this chain, per source channel.
+	pub number_of_relayed_packets: CounterVec<U64>,
+	/// Total number of timeouts successfully delivered to this chain, per source channel.
+	pub number_of_relayed_timeouts: CounterVec<U64>,
+	/// Total number of packets, acknowledgements and timeouts whose submission to this chain
+	/// failed, per source channel.
+	pub number_of_failed_packets: CounterVec<U64>,
+	/// Time between a "send packet" event on this chain and the acknowledgement or timeout of the
+	/// packet, per source channel.
+	pub packet_latency: HistogramVec,
+	/// Percentiles of the end-to-end packet latency over a sliding window, per source channel.
+	pub packet_latency_window: PacketLatencyWindows,
+	/// Time between sending a transfer routed by the packet forward middleware and its
+	/// acknowledgement, which is only written once the last hop is acknowledged, per number of
+	/// hops.
+	pub forwarded_packet_latency: HistogramVec,
+	/// Balance of the relayer's wallet on this chain, per denomination, summed over its keys.
+	pub wallet_balance: GaugeVec<F64>,
+	/// Balance of each relayer key on this chain, per denomination.
+	pub key_balance: GaugeVec<F64>,
+	/// Fees of the transactions successfully submitted to this chain, per denomination, as
+	/// estimated before their submission.
+	pub projected_fees: CounterVec<F64>,
+	/// Fees spent by the relayer keys on this chain, per denomination, measured as the decreases
+	/// of their balances between two balance checks.
+	pub spent_fees: CounterVec<F64>,
+	/// Whether the last health check of this chain found nothing preventing it from being
+	/// relayed (1) or not (0).
+	pub chain_healthy: Gauge<U64>,
+	/// Seconds left before the client of this chain on the counterparty expires, as of the last
+	/// health check.
+	pub client_expires_in: Gauge<U64>,
 
 	/// Light client height.
 	pub light_client_height: HashMap<ClientId, LightClientMetrics>,
@@ -243,6 +300,36 @@ impl Metrics {
 				)?,
 				registry,
 			)?,
+			number_of_skipped_stream_items: register(
+				Gauge::with_opts(
+					Opts::new(
+						"hyperspace_number_of_skipped_stream_items".to_string(),
+						"Number of stream items skipped because they couldn't be fetched or decoded",
+					)
+					.const_label("name", prefix.to_string()),
+				)?,
+				registry,
+			)?,
+			number_of_disconnects: register(
+				Gauge::with_opts(
+					Opts::new(
+						"hyperspace_number_of_disconnects".to_string(),
+						"Number of times the event subscriptions were disconnected",
+					)
+					.const_label("name", prefix.to_string()),
+				)?,
+				registry,
+			)?,
+			submission_queue_depth: register(
+				Gauge::with_opts(
+					Opts::new(
+						"hyperspace_submission_queue_depth".to_string(),
+						"Number of transactions waiting to be submitted or in flight",
+					)
+					.const_label("name", prefix.to_string()),
+				)?,
+				registry,
+			)?,
 			gas_cost_for_sent_tx_bundle: register(
 				Histogram::with_opts(
 					HistogramOpts::new(
@@ -265,6 +352,194 @@ impl Metrics {
 				)?,
 				registry,
 			)?,
+			fee_estimate_for_sent_tx_bundle: register(
+				HistogramVec::new(
+					HistogramOpts::new(
+						"hyperspace_fee_estimate_for_sent_tx_bundle".to_string(),
+						"Estimated fee of every sent tx bundle",
+					)
+					.buckets(exponential_buckets(1.0, 10.0, 20)?)
+					.const_label("name", prefix.to_string()),
+					&["denom"],
+				)?,
+				registry,
+			)?,
+			gas_spent_per_submission: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"hyperspace_gas_spent_per_submission".to_string(),
+						"Gas spent by every successful submission",
+					)
+					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
+					.const_label("name", prefix.to_string()),
+				)?,
+				registry,
+			)?,
+			total_gas_spent: register(
+				Counter::with_opts(
+					Opts::new(
+						"hyperspace_total_gas_spent".to_string(),
+						"Total gas spent by successful submissions",
+					)
+					.const_label("name", prefix.to_string()),
+				)?,
+				registry,
+			)?,
+			simulated_gas_per_transaction: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"hyperspace_simulated_gas_per_transaction".to_string(),
+						"Simulated gas of every committed transaction",
+					)
+					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
+					.const_label("name", prefix.to_string()),
+				)?,
+				registry,
+			)?,
+			used_gas_per_transaction: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"hyperspace_used_gas_per_transaction".to_string(),
+						"Gas used by every committed transaction whose gas was simulated",
+					)
+					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0])
+					.const_label("name", prefix.to_string()),
+				)?,
+				registry,
+			)?,
+			used_to_simulated_gas_ratio: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"hyperspace_used_to_simulated_gas_ratio".to_string(),
+						"Ratio of the gas used by every committed transaction to its simulated gas",
+					)
+					.buckets(vec![0.5, 0.8, 0.9, 1.0, 1.1, 1.2, 1.5, 2.0])
+					.const_label("name", prefix.to_string()),
+				)?,
+				registry,
+			)?,
+			number_of_relayed_packets: register(
+				CounterVec::new(
+					Opts::new(
+						"hyperspace_number_of_relayed_packets".to_string(),
+						"Total number of successfully delivered packets",
+					)
+					.const_label("name", prefix.to_string()),
+					&["channel_id", "port_id"],
+				)?,
+				registry,
+			)?,
+			number_of_relayed_timeouts: register(
+				CounterVec::new(
+					Opts::new(
+						"hyperspace_number_of_relayed_timeouts".to_string(),
+						"Total number of successfully delivered timeouts",
+					)
+					.const_label("name", prefix.to_string()),
+					&["channel_id", "port_id"],
+				)?,
+				registry,
+			)?,
+			number_of_failed_packets: register(
+				CounterVec::new(
+					Opts::new(
+						"hyperspace_number_of_failed_packets".to_string(),
+						"Total number of packets, acknowledgements and timeouts that failed to be submitted",
+					)
+					.const_label("name", prefix.to_string()),
+					&["channel_id", "port_id"],
+				)?,
+				registry,
+			)?,
+			packet_latency: register(
+				HistogramVec::new(
+					HistogramOpts::new(
+						"hyperspace_packet_latency".to_string(),
+						"Time between sending a packet and its acknowledgement or timeout",
+					)
+					.buckets(exponential_buckets(1000.0, 2.0, 12)?)
+					.const_label("name", prefix.to_string()),
+					&["channel_id", "port_id"],
+				)?,
+				registry,
+			)?,
+			packet_latency_window: register(
+				PacketLatencyWindows::new(prefix, DEFAULT_LATENCY_WINDOW)?,
+				registry,
+			)?,
+			forwarded_packet_latency: register(
+				HistogramVec::new(
+					HistogramOpts::new(
+						"hyperspace_forwarded_packet_latency".to_string(),
+						"Time between sending a forwarded transfer and its acknowledgement",
+					)
+					.buckets(exponential_buckets(1000.0, 2.0, 14)?)
+					.const_label("name", prefix.to_string()),
+					&["hops"],
+				)?,
+				registry,
+			)?,
+			wallet_balance: register(
+				GaugeVec::new(
+					Opts::new(
+						"hyperspace_wallet_balance".to_string(),
+						"Balance of the relayer's wallet",
+					)
+					.const_label("name", prefix.to_string()),
+					&["denom"],
+				)?,
+				registry,
+			)?,
+			key_balance: register(
+				GaugeVec::new(
+					Opts::new("hyperspace_key_balance".to_string(), "Balance of a relayer key")
+						.const_label("name", prefix.to_string()),
+					&["address", "denom"],
+				)?,
+				registry,
+			)?,
+			projected_fees: register(
+				CounterVec::new(
+					Opts::new(
+						"hyperspace_projected_fees".to_string(),
+						"Estimated fees of the transactions submitted to the chain",
+					)
+					.const_label("name", prefix.to_string()),
+					&["denom"],
+				)?,
+				registry,
+			)?,
+			spent_fees: register(
+				CounterVec::new(
+					Opts::new(
+						"hyperspace_spent_fees".to_string(),
+						"Fees spent by the relayer keys, measured from their balances",
+					)
+					.const_label("name", prefix.to_string()),
+					&["denom"],
+				)?,
+				registry,
+			)?,
+			chain_healthy: register(
+				Gauge::with_opts(
+					Opts::new(
+						"hyperspace_chain_healthy".to_string(),
+						"Whether the last health check of the chain passed",
+					)
+					.const_label("name", prefix.to_string()),
+				)?,
+				registry,
+			)?,
+			client_expires_in: register(
+				Gauge::with_opts(
+					Opts::new(
+						"hyperspace_client_expires_in_seconds".to_string(),
+						"Seconds left before the client of the chain on the counterparty expires",
+					)
+					.const_label("name", prefix.to_string()),
+				)?,
+				registry,
+			)?,
 			light_client_height: HashMap::new(),
 			send_packet_event_time: register(
 				Histogram::with_opts(
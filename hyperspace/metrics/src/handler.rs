@@ -12,11 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::data::Metrics;
+use crate::{data::Metrics, latency::LatencyPercentiles};
 use ibc::{
 	core::{
 		ics04_channel::{
 			events::{TimeoutOnClosePacket, TimeoutPacket},
+			msgs::{
+				acknowledgement::MsgAcknowledgement, recv_packet::MsgRecvPacket,
+				timeout::MsgTimeout, timeout_on_close::MsgTimeoutOnClose,
+			},
 			packet::{Packet, Sequence},
 		},
 		ics24_host::identifier::{ChannelId, PortId},
@@ -24,13 +28,15 @@ use ibc::{
 	events::IbcEvent,
 };
 use ibc_proto::google::protobuf::Any;
+use ibc_proto_registry::type_url;
 use prometheus::{Histogram, Registry};
 use std::{
 	collections::HashMap,
 	ops::DerefMut,
 	sync::{Arc, Mutex},
-	time::Instant,
+	time::{Duration, Instant},
 };
+use tendermint_proto::Protobuf;
 
 #[derive(Eq, PartialEq, Hash)]
 pub struct PacketId {
@@ -51,6 +57,7 @@ impl From<Packet> for PacketId {
 
 pub type PacketMap = Arc<Mutex<HashMap<PacketId, Instant>>>;
 
+#[derive(Clone)]
 pub struct MetricsHandler {
 	registry: Registry,
 	metrics: Metrics,
@@ -59,6 +66,8 @@ pub struct MetricsHandler {
 	last_sent_acknowledgment_time: PacketMap,
 	last_sent_timeout_packet_time: PacketMap,
 	last_update_client_time: Arc<Mutex<Option<Instant>>>,
+	/// Send time and number of hops of the packets routed by the packet forward middleware.
+	forwarded_packets: Arc<Mutex<HashMap<PacketId, (Instant, usize)>>>,
 
 	counterparty_last_sent_packet_time: Option<PacketMap>,
 	counterparty_last_sent_acknowledgment_time: Option<PacketMap>,
@@ -74,6 +83,7 @@ impl MetricsHandler {
 			last_sent_acknowledgment_time: Arc::new(Mutex::new(HashMap::new())),
 			last_sent_timeout_packet_time: Arc::new(Mutex::new(HashMap::new())),
 			last_update_client_time: Arc::new(Mutex::new(None)),
+			forwarded_packets: Arc::new(Mutex::new(HashMap::new())),
 			counterparty_last_sent_packet_time: None,
 			counterparty_last_sent_acknowledgment_time: None,
 			counterparty_last_sent_timeout_packet_time: None,
@@ -136,6 +146,8 @@ impl MetricsHandler {
 						&self.counterparty_last_sent_acknowledgment_time,
 						&self.metrics.sent_acknowledgment_time,
 					);
+					self.observe_packet_latency(&packet.packet, true);
+					self.observe_forwarded_packet_latency(&packet.packet);
 				},
 				IbcEvent::TimeoutPacket(TimeoutPacket { packet, .. }) |
 				IbcEvent::TimeoutOnClosePacket(TimeoutOnClosePacket { packet, .. }) => {
@@ -145,6 +157,8 @@ impl MetricsHandler {
 						&self.counterparty_last_sent_timeout_packet_time,
 						&self.metrics.sent_timeout_packet_time,
 					);
+					self.observe_packet_latency(packet, false);
+					self.forwarded_packets.lock().unwrap().remove(&packet.clone().into());
 				},
 				IbcEvent::UpdateClient(update) => {
 					let mut guard = self.last_update_client_time.lock().unwrap();
@@ -169,7 +183,7 @@ impl MetricsHandler {
 	pub async fn handle_messages(&self, messages: &[Any]) {
 		for message in messages {
 			match message.type_url.as_str() {
-				"/ibc.core.channel.v1.MsgAcknowledgement" => {
+				type_url::MSG_ACKNOWLEDGEMENT => {
 					self.metrics.number_of_sent_acknowledgments.inc();
 					// The counters may be out of sync (e.g. when relayer was restarted), so we use
 					// saturating sub
@@ -181,7 +195,7 @@ impl MetricsHandler {
 						.number_of_undelivered_acknowledgements
 						.set(number_of_undelivered_acknowledgements);
 				},
-				"/ibc.core.channel.v1.MsgRecvPacket" => {
+				type_url::MSG_RECV_PACKET => {
 					self.metrics.number_of_undelivered_packets.set(
 						self.metrics.number_of_sent_packets.get().saturating_sub(
 							self.metrics.counterparty_number_of_received_packets().get(),
@@ -213,7 +227,7 @@ impl MetricsHandler {
 	pub async fn handle_timeouts(&self, timeouts: &[Any]) {
 		for message in timeouts {
 			match message.type_url.as_str() {
-				"/ibc.core.channel.v1.MsgTimeout" | "/ibc.core.channel.v1.MsgTimeoutOnClose" => {
+				type_url::MSG_TIMEOUT | type_url::MSG_TIMEOUT_ON_CLOSE => {
 					self.metrics.number_of_sent_timeout_packets.inc();
 				},
 				_ => (),
@@ -221,12 +235,156 @@ impl MetricsHandler {
 		}
 	}
 
+	pub fn update_skipped_stream_items(&self, count: u64) {
+		self.metrics.number_of_skipped_stream_items.set(count);
+	}
+
+	pub fn update_disconnects(&self, count: u64) {
+		self.metrics.number_of_disconnects.set(count);
+	}
+
+	pub fn update_submission_queue_depth(&self, depth: usize) {
+		self.metrics.submission_queue_depth.set(depth as u64);
+	}
+
 	pub async fn handle_transaction_costs(&self, batch_weight: u64, messages: &[Any]) {
 		let batch_size = messages.iter().map(|x| x.value.len()).sum::<usize>();
 		self.metrics.gas_cost_for_sent_tx_bundle.observe(batch_weight as f64);
 		self.metrics.transaction_length_for_sent_tx_bundle.observe(batch_size as f64);
 	}
 
+	/// Records the `fee`, in `denom`, estimated for a tx bundle before it's submitted.
+	pub fn handle_fee_estimate(&self, denom: &str, fee: f64) {
+		self.metrics
+			.fee_estimate_for_sent_tx_bundle
+			.with_label_values(&[denom])
+			.observe(fee);
+	}
+
+	/// Records the gas of a committed transaction, as `simulated` before it was submitted and as
+	/// `used` by its execution.
+	pub fn handle_gas_usage(&self, simulated: u64, used: u64) {
+		self.metrics.simulated_gas_per_transaction.observe(simulated as f64);
+		self.metrics.used_gas_per_transaction.observe(used as f64);
+		if simulated != 0 {
+			self.metrics.used_to_simulated_gas_ratio.observe(used as f64 / simulated as f64);
+		}
+	}
+
+	/// Records the outcome of submitting `messages`, which were estimated to cost `weight`.
+	pub fn handle_submission(&self, messages: &[Any], weight: u64, success: bool) {
+		if success {
+			self.metrics.gas_spent_per_submission.observe(weight as f64);
+			self.metrics.total_gas_spent.inc_by(weight);
+		}
+		for message in messages {
+			let Some(packet) = message_packet(message) else { continue };
+			let channel_id = packet.source_channel.to_string();
+			let labels = [channel_id.as_str(), packet.source_port.as_str()];
+			match (success, message.type_url.as_str()) {
+				(false, _) =>
+					self.metrics.number_of_failed_packets.with_label_values(&labels).inc(),
+				(true, type_url::MSG_RECV_PACKET) =>
+					self.metrics.number_of_relayed_packets.with_label_values(&labels).inc(),
+				(true, type_url::MSG_TIMEOUT) | (true, type_url::MSG_TIMEOUT_ON_CLOSE) =>
+					self.metrics.number_of_relayed_timeouts.with_label_values(&labels).inc(),
+				_ => (),
+			}
+		}
+	}
+
+	/// Records the balance of the relayer's wallet in `denom`.
+	pub fn update_wallet_balance(&self, denom: &str, amount: f64) {
+		self.metrics.wallet_balance.with_label_values(&[denom]).set(amount);
+	}
+
+	/// Records the balance of the relayer key with the given `address` in `denom`. A decrease
+	/// since the previous balance is counted as spent on fees.
+	pub fn update_key_balance(&self, address: &str, denom: &str, amount: f64) {
+		let balance = self.metrics.key_balance.with_label_values(&[address, denom]);
+		let spent = balance.get() - amount;
+		if spent > 0.0 {
+			self.metrics.spent_fees.with_label_values(&[denom]).inc_by(spent);
+		}
+		balance.set(amount);
+	}
+
+	/// Records the estimated `fee`, in `denom`, of a transaction successfully submitted to the
+	/// chain.
+	pub fn handle_projected_fee(&self, denom: &str, fee: f64) {
+		self.metrics.projected_fees.with_label_values(&[denom]).inc_by(fee);
+	}
+
+	/// Records the result of a health check of the chain.
+	pub fn update_health(&self, healthy: bool, client_expires_in: Option<u64>) {
+		self.metrics.chain_healthy.set(healthy as u64);
+		if let Some(client_expires_in) = client_expires_in {
+			self.metrics.client_expires_in.set(client_expires_in);
+		}
+	}
+
+	/// Sets the length of the sliding window the packet latency percentiles are computed over.
+	pub fn set_latency_window(&self, window: Duration) {
+		self.metrics.packet_latency_window.set_window(window);
+	}
+
+	/// Returns the packet latency percentiles of every channel over the current window.
+	pub fn packet_latency_percentiles(&self) -> Vec<LatencyPercentiles> {
+		self.metrics.packet_latency_window.percentiles()
+	}
+
+	/// Logs the packet latency percentiles of every channel over the current window.
+	pub fn log_packet_latency_percentiles(&self, chain: &str) {
+		for LatencyPercentiles { channel_id, port_id, count, p50, p95, p99 } in
+			self.packet_latency_percentiles()
+		{
+			log::info!(
+				target: "hyperspace",
+				"Packet latency on {chain} {port_id}/{channel_id} ({count} packets): p50 {p50}ms, p95 {p95}ms, p99 {p99}ms"
+			);
+		}
+	}
+
+	/// Observes the time since the "send packet" event of a packet that was acknowledged or timed
+	/// out. Only acknowledged packets count towards the latency percentiles.
+	fn observe_packet_latency(&self, packet: &Packet, acknowledged: bool) {
+		let sent_at = self.last_sent_packet_time.lock().unwrap().remove(&packet.clone().into());
+		if let Some(sent_at) = sent_at {
+			let latency = sent_at.elapsed().as_millis() as f64;
+			let channel_id = packet.source_channel.to_string();
+			self.metrics
+				.packet_latency
+				.with_label_values(&[channel_id.as_str(), packet.source_port.as_str()])
+				.observe(latency);
+			if acknowledged {
+				self.metrics.packet_latency_window.observe(
+					&channel_id,
+					packet.source_port.as_str(),
+					latency,
+				);
+			}
+		}
+	}
+
+	/// Tracks a packet sent from this chain that is forwarded over `hops` hops by the packet
+	/// forward middleware, until its acknowledgement.
+	pub fn track_forwarded_packet(&self, packet: &Packet, hops: usize) {
+		self.forwarded_packets
+			.lock()
+			.unwrap()
+			.insert(packet.clone().into(), (Instant::now(), hops));
+	}
+
+	fn observe_forwarded_packet_latency(&self, packet: &Packet) {
+		let forwarded = self.forwarded_packets.lock().unwrap().remove(&packet.clone().into());
+		if let Some((sent_at, hops)) = forwarded {
+			self.metrics
+				.forwarded_packet_latency
+				.with_label_values(&[hops.to_string().as_str()])
+				.observe(sent_at.elapsed().as_millis() as f64);
+		}
+	}
+
 	pub fn observe_last_packet_time(
 		&self,
 		packet: &Packet,
@@ -247,6 +405,33 @@ impl MetricsHandler {
 	}
 }
 
+/// Logs the packet latency percentiles of the given chains every `interval`.
+pub async fn report_packet_latency(chains: Vec<(String, MetricsHandler)>, interval: Duration) {
+	let mut interval = tokio::time::interval(interval);
+	// the first tick completes immediately, when there's nothing to report yet
+	interval.tick().await;
+	loop {
+		interval.tick().await;
+		for (name, metrics) in &chains {
+			metrics.log_packet_latency_percentiles(name);
+		}
+	}
+}
+
+/// Returns the packet relayed by `message`, if it's a packet message.
+fn message_packet(message: &Any) -> Option<Packet> {
+	let value = message.value.as_slice();
+	match message.type_url.as_str() {
+		type_url::MSG_RECV_PACKET => MsgRecvPacket::decode_vec(value).ok().map(|msg| msg.packet),
+		type_url::MSG_ACKNOWLEDGEMENT =>
+			MsgAcknowledgement::decode_vec(value).ok().map(|msg| msg.packet),
+		type_url::MSG_TIMEOUT => MsgTimeout::decode_vec(value).ok().map(|msg| msg.packet),
+		type_url::MSG_TIMEOUT_ON_CLOSE =>
+			MsgTimeoutOnClose::decode_vec(value).ok().map(|msg| msg.packet),
+		_ => None,
+	}
+}
+
 fn observe_delta_time(maybe_time: &mut Option<Instant>, time_metrics: &Histogram) {
 	let now = Instant::now();
 	if let Some(last_time) = maybe_time {
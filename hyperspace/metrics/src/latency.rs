@@ -0,0 +1,185 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end packet latency percentiles over a sliding window, used to track relaying SLOs.
+
+use prometheus::{
+	core::{Collector, Desc},
+	proto::{LabelPair, Metric, MetricFamily, MetricType, Quantile, Summary},
+	Error as PrometheusError,
+};
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+/// Default length of the sliding window the percentiles are computed over.
+pub const DEFAULT_LATENCY_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Maximum number of samples kept per channel, the oldest ones are dropped first.
+const MAX_SAMPLES_PER_CHANNEL: usize = 10_000;
+
+/// Exported quantiles.
+const QUANTILES: [f64; 3] = [0.5, 0.95, 0.99];
+
+/// Latency percentiles of a channel over the current window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyPercentiles {
+	pub channel_id: String,
+	pub port_id: String,
+	/// Number of packets acknowledged within the window.
+	pub count: usize,
+	/// Latencies in milliseconds.
+	pub p50: f64,
+	pub p95: f64,
+	pub p99: f64,
+}
+
+struct Windows {
+	window: Duration,
+	samples: HashMap<(String, String), VecDeque<(Instant, f64)>>,
+}
+
+impl Windows {
+	fn prune(&mut self, now: Instant) {
+		let window = self.window;
+		self.samples.retain(|_, samples| {
+			while samples.front().map_or(false, |(at, _)| now.duration_since(*at) > window) {
+				samples.pop_front();
+			}
+			!samples.is_empty()
+		});
+	}
+}
+
+/// Per-channel sliding windows of end-to-end packet latencies (from the "send packet" event to
+/// the observed acknowledgement), exported as a Prometheus summary with the 0.5, 0.95 and 0.99
+/// quantiles.
+#[derive(Clone)]
+pub struct PacketLatencyWindows {
+	desc: Desc,
+	name: String,
+	inner: Arc<Mutex<Windows>>,
+}
+
+impl PacketLatencyWindows {
+	pub fn new(prefix: &str, window: Duration) -> Result<Self, PrometheusError> {
+		let desc = Desc::new(
+			"hyperspace_packet_latency_window".to_string(),
+			"End-to-end packet latency in milliseconds over a sliding window".to_string(),
+			vec!["channel_id".to_string(), "port_id".to_string()],
+			HashMap::from([("name".to_string(), prefix.to_string())]),
+		)?;
+		Ok(Self {
+			desc,
+			name: prefix.to_string(),
+			inner: Arc::new(Mutex::new(Windows { window, samples: HashMap::new() })),
+		})
+	}
+
+	/// Changes the length of the sliding window.
+	pub fn set_window(&self, window: Duration) {
+		self.inner.lock().unwrap().window = window;
+	}
+
+	/// Records the latency, in milliseconds, of a packet sent over the given channel.
+	pub fn observe(&self, channel_id: &str, port_id: &str, latency_ms: f64) {
+		let mut inner = self.inner.lock().unwrap();
+		let samples =
+			inner.samples.entry((channel_id.to_string(), port_id.to_string())).or_default();
+		if samples.len() == MAX_SAMPLES_PER_CHANNEL {
+			samples.pop_front();
+		}
+		samples.push_back((Instant::now(), latency_ms));
+	}
+
+	/// Returns the latency percentiles of every channel that had a packet acknowledged within the
+	/// window.
+	pub fn percentiles(&self) -> Vec<LatencyPercentiles> {
+		self.summaries()
+			.into_iter()
+			.map(|((channel_id, port_id), count, _, quantiles)| LatencyPercentiles {
+				channel_id,
+				port_id,
+				count,
+				p50: quantiles[0],
+				p95: quantiles[1],
+				p99: quantiles[2],
+			})
+			.collect()
+	}
+
+	/// Returns the sample count, sum and [`QUANTILES`] of every channel.
+	fn summaries(&self) -> Vec<((String, String), usize, f64, [f64; 3])> {
+		let mut inner = self.inner.lock().unwrap();
+		inner.prune(Instant::now());
+		let mut summaries = inner
+			.samples
+			.iter()
+			.map(|(channel, samples)| {
+				let mut values = samples.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+				values.sort_by(|a, b| a.total_cmp(b));
+				let quantiles = QUANTILES.map(|q| quantile(&values, q));
+				(channel.clone(), values.len(), values.iter().sum(), quantiles)
+			})
+			.collect::<Vec<_>>();
+		summaries.sort_by(|a, b| a.0.cmp(&b.0));
+		summaries
+	}
+}
+
+/// Nearest-rank quantile of sorted, non-empty `values`.
+fn quantile(values: &[f64], q: f64) -> f64 {
+	let rank = (q * values.len() as f64).ceil() as usize;
+	values[rank.clamp(1, values.len()) - 1]
+}
+
+fn label(name: &str, value: &str) -> LabelPair {
+	let mut label = LabelPair::default();
+	label.set_name(name.to_string());
+	label.set_value(value.to_string());
+	label
+}
+
+impl Collector for PacketLatencyWindows {
+	fn desc(&self) -> Vec<&Desc> {
+		vec![&self.desc]
+	}
+
+	fn collect(&self) -> Vec<MetricFamily> {
+		let mut family = MetricFamily::default();
+		family.set_name(self.desc.fq_name.clone());
+		family.set_help(self.desc.help.clone());
+		family.set_field_type(MetricType::SUMMARY);
+		for ((channel_id, port_id), count, sum, quantiles) in self.summaries() {
+			let mut summary = Summary::default();
+			summary.set_sample_count(count as u64);
+			summary.set_sample_sum(sum);
+			for (q, value) in QUANTILES.iter().zip(quantiles) {
+				let mut quantile = Quantile::default();
+				quantile.set_quantile(*q);
+				quantile.set_value(value);
+				summary.mut_quantile().push(quantile);
+			}
+			let mut metric = Metric::default();
+			metric.mut_label().push(label("channel_id", &channel_id));
+			metric.mut_label().push(label("name", &self.name));
+			metric.mut_label().push(label("port_id", &port_id));
+			metric.set_summary(summary);
+			family.mut_metric().push(metric);
+		}
+		vec![family]
+	}
+}
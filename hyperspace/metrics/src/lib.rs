@@ -14,6 +14,7 @@
 
 pub mod data;
 pub mod handler;
+pub mod latency;
 
 use hyper::{
 	http::StatusCode,
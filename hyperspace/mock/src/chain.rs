@@ -0,0 +1,234 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, MockChain, MockFinalityEvent};
+use anyhow::anyhow;
+use futures::{channel::mpsc, Stream};
+use ibc::{
+	core::{
+		ics02_client::{
+			client_state::ClientState as _, events::UpdateClient, trust_threshold::TrustThreshold,
+		},
+		ics23_commitment::specs::ProofSpecs,
+		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+	},
+	events::IbcEvent,
+	signer::Signer,
+	Height,
+};
+use ibc_proto::google::protobuf::Any;
+use ibc_proto_registry::type_url::MSG_PUSH_NEW_WASM_CODE;
+use ics07_tendermint::client_state::ClientState;
+use pallet_ibc::light_clients::{
+	AnyClientMessage, AnyClientState, AnyConsensusState, HostFunctionsManager,
+};
+use primitives::{
+	Chain, Fee, FinalitySource, IbcQuerier, KeyProvider, LightClientSync, MisbehaviourHandler,
+	PayloadSignature, TxSubmitter, UpdateType,
+};
+use std::{pin::Pin, str::FromStr};
+
+#[async_trait::async_trait]
+impl LightClientSync for MockChain {
+	async fn is_synced<C: Chain>(&self, _counterparty: &C) -> Result<bool, anyhow::Error> {
+		// every block is relayed by `query_latest_ibc_events`
+		Ok(true)
+	}
+
+	async fn fetch_mandatory_updates<C: Chain>(
+		&self,
+		_counterparty: &C,
+	) -> Result<(Vec<Any>, Vec<IbcEvent>), anyhow::Error> {
+		Ok((vec![], vec![]))
+	}
+}
+
+#[async_trait::async_trait]
+impl MisbehaviourHandler for MockChain {
+	async fn detect_misbehaviour<C: Chain>(
+		&self,
+		_counterparty: &C,
+		_client_message: AnyClientMessage,
+	) -> Result<Option<Any>, anyhow::Error> {
+		Ok(None)
+	}
+}
+
+impl KeyProvider for MockChain {
+	fn account_id(&self) -> Signer {
+		Signer::from_str(&format!("{}-relayer", self.name)).expect("account id is not empty")
+	}
+
+	/// The "signature" is the payload itself, there are no keys on a mock chain.
+	fn sign_payload(&self, payload: &[u8]) -> Result<PayloadSignature, anyhow::Error> {
+		Ok(PayloadSignature {
+			public_key: self.account_id().to_string().into_bytes(),
+			signature: payload.to_vec(),
+		})
+	}
+}
+
+#[async_trait::async_trait]
+impl TxSubmitter for MockChain {
+	/// Index of the transaction on the chain.
+	type TransactionId = u64;
+
+	async fn query_client_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<ClientId, Self::Error> {
+		self.query(|ledger| {
+			ledger.transaction(tx_id)?.created_client.clone().ok_or_else(|| {
+				Error::Custom(format!("No client was created in transaction {tx_id}"))
+			})
+		})
+	}
+
+	async fn query_connection_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<ConnectionId, Self::Error> {
+		Err(Error::Custom(format!("No connection was created in transaction {tx_id}")))
+	}
+
+	async fn query_channel_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<(ChannelId, PortId), Self::Error> {
+		Err(Error::Custom(format!("No channel was created in transaction {tx_id}")))
+	}
+
+	async fn upload_wasm(&self, _wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
+		Err(Error::Unsupported(MSG_PUSH_NEW_WASM_CODE.to_string()))
+	}
+
+	fn block_max_weight(&self) -> u64 {
+		self.block_max_weight
+	}
+
+	async fn estimate_weight(&self, msg: Vec<Any>) -> Result<u64, Self::Error> {
+		Ok(self.weight_per_message * msg.len() as u64)
+	}
+
+	async fn estimate_fee(&self, msgs: &[Any]) -> Result<Fee, Self::Error> {
+		Ok(self.fee(msgs.len()))
+	}
+
+	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Self::Error> {
+		let fee = self.fee(messages.len());
+		let mut state = self.state.lock().unwrap();
+		state.faults.check_submission()?;
+		state.ledger.submit(messages, fee)
+	}
+}
+
+#[async_trait::async_trait]
+impl FinalitySource for MockChain {
+	type FinalityEvent = MockFinalityEvent;
+
+	async fn query_latest_ibc_events<T>(
+		&mut self,
+		finality_event: Self::FinalityEvent,
+		counterparty: &T,
+	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+	where
+		T: Chain,
+	{
+		let (counterparty_height, _) = counterparty
+			.latest_height_and_timestamp()
+			.await
+			.map_err(|e| anyhow!("Failed to query the height of {}: {e:?}", counterparty.name()))?;
+		let response = counterparty
+			.query_client_state(counterparty_height, self.client_id())
+			.await
+			.map_err(|e| anyhow!("Failed to query the client of {}: {e:?}", self.name))?;
+		let client_state = response
+			.client_state
+			.ok_or_else(|| anyhow!("Client of {} not found", self.name))
+			.and_then(|state| AnyClientState::try_from(state).map_err(|e| anyhow!("{e:?}")))?;
+		let from = client_state.latest_height().revision_height + 1;
+		let client_id = self.client_id();
+		let signer = counterparty.account_id();
+		Ok(self.query(|ledger| {
+			ledger.client_updates(&client_id, &signer, from, finality_event.height)
+		})?)
+	}
+
+	async fn is_update_required(
+		&self,
+		latest_height: u64,
+		latest_client_height_on_counterparty: u64,
+	) -> Result<bool, Self::Error> {
+		let lag = latest_height.saturating_sub(latest_client_height_on_counterparty);
+		Ok([self.client_update_period(), self.security_params().max_header_lag]
+			.into_iter()
+			.flatten()
+			.any(|period| lag >= period))
+	}
+
+	async fn initialize_client_state(
+		&self,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		let (latest_height, _) = self.latest_height_and_timestamp().await?;
+		self.initialize_client_state_at(latest_height.revision_height).await
+	}
+
+	async fn initialize_client_state_at(
+		&self,
+		height: u64,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		let header = self.query(|ledger| ledger.header(height))?;
+		let client_state = ClientState::<HostFunctionsManager>::new(
+			self.chain_id.clone(),
+			TrustThreshold::default(),
+			self.security_params().trusting_period(),
+			self.security_params().unbonding_period(),
+			self.security_params().max_clock_drift(),
+			header.height(),
+			ProofSpecs::default(),
+			vec![],
+		)
+		.map_err(|e| Error::from(format!("Invalid client state {e}")))?;
+		Ok((
+			AnyClientState::Tendermint(client_state),
+			AnyConsensusState::Tendermint(header.consensus_state()?),
+		))
+	}
+
+	async fn finality_notifications(
+		&self,
+	) -> Result<Pin<Box<dyn Stream<Item = Self::FinalityEvent> + Send + Sync>>, Self::Error> {
+		let mut state = self.state.lock().unwrap();
+		if state.faults.offline {
+			return Err(Error::Injected("chain is offline".to_string()))
+		}
+		let (sender, receiver) = mpsc::unbounded();
+		state.finality_subscribers.push(sender);
+		Ok(Box::pin(receiver))
+	}
+
+	async fn query_client_message(
+		&self,
+		update: UpdateClient,
+	) -> Result<AnyClientMessage, Self::Error> {
+		Err(Error::Custom(format!(
+			"The headers of a mock chain can't be queried, update of {}",
+			update.client_id()
+		)))
+	}
+
+	async fn get_proof_height(&self, block_height: Height) -> Height {
+		block_height
+	}
+}
@@ -0,0 +1,37 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Prefix of the messages of the injected failures.
+pub const INJECTED_FAILURE: &str = "Injected failure";
+
+/// Error definitions for the mock chain.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+	/// Custom error
+	#[error("{0}")]
+	Custom(String),
+	/// A failure injected by the test, see [`crate::MockChain::fail_next_queries`] and
+	/// [`crate::MockChain::fail_next_submissions`].
+	#[error("Injected failure: {0}")]
+	Injected(String),
+	/// The message isn't handled by the mock chain.
+	#[error("Unsupported message {0}")]
+	Unsupported(String),
+}
+
+impl From<String> for Error {
+	fn from(error: String) -> Self {
+		Self::Custom(error)
+	}
+}
@@ -0,0 +1,671 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The in-memory IBC state of a mock chain.
+//!
+//! The ledger only keeps its latest state: queries at past heights return it, and proofs are
+//! placeholder bytes. Blocks are only produced when the test asks for it, and the timestamp of a
+//! block only depends on its height, so that a test always observes the same chain. The events
+//! of the messages submitted and the packets sent by the test are included in the next block.
+
+use crate::error::Error;
+use ibc::{
+	core::{
+		ics02_client::{
+			client_state::ClientState as _,
+			events::{Attributes, CreateClient, UpdateClient},
+		},
+		ics03_connection::connection::ConnectionEnd,
+		ics04_channel::{
+			channel::ChannelEnd,
+			events::{
+				AcknowledgePacket, ReceivePacket, SendPacket, TimeoutOnClosePacket, TimeoutPacket,
+				WriteAcknowledgement,
+			},
+			packet::Packet,
+		},
+		ics23_commitment::commitment::CommitmentRoot,
+		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+	},
+	events::IbcEvent,
+	signer::Signer,
+	timestamp::Timestamp,
+	Height,
+};
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::core::{
+		channel::v1::Packet as RawPacket,
+		client::v1::{
+			MsgCreateClient as RawMsgCreateClient, MsgUpdateClient as RawMsgUpdateClient,
+		},
+	},
+};
+use ibc_proto_registry::RawAny;
+use ibc_rpc::PacketInfo;
+use ics07_tendermint::consensus_state::ConsensusState;
+use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState};
+use primitives::{Fee, UpdateType};
+use prost::Message;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	str::FromStr,
+	time::Duration,
+};
+
+/// Type URL of the [`MockHeader`]s carried by the client updates of a mock chain.
+pub const MOCK_HEADER_TYPE_URL: &str = "/hyperspace.mock.v1.Header";
+
+/// Acknowledgement written for every packet received by a mock chain.
+pub const MOCK_ACK: &[u8] = br#"{"result":"AQ=="}"#;
+
+/// Placeholder returned for every proof.
+pub const MOCK_PROOF: &[u8] = b"mock-proof";
+
+/// Header of a block of a mock chain, the client message of the updates of its client.
+#[derive(Clone, PartialEq, Message)]
+pub struct MockHeader {
+	#[prost(uint64, tag = "1")]
+	pub revision_number: u64,
+	#[prost(uint64, tag = "2")]
+	pub revision_height: u64,
+	/// Timestamp of the block, in nanoseconds.
+	#[prost(uint64, tag = "3")]
+	pub timestamp: u64,
+}
+
+impl MockHeader {
+	pub fn height(&self) -> Height {
+		Height::new(self.revision_number, self.revision_height)
+	}
+
+	pub fn to_any(&self) -> Any {
+		Any { type_url: MOCK_HEADER_TYPE_URL.to_string(), value: self.encode_to_vec() }
+	}
+
+	/// The consensus state of the block in a Tendermint client, whose root is derived from the
+	/// height of the block.
+	pub fn consensus_state(&self) -> Result<ConsensusState, Error> {
+		let timestamp = Timestamp::from_nanoseconds(self.timestamp)
+			.ok()
+			.and_then(|timestamp| timestamp.into_tm_time())
+			.ok_or_else(|| Error::Custom(format!("Invalid header timestamp {}", self.timestamp)))?;
+		let root = CommitmentRoot::from_bytes(&self.revision_height.to_be_bytes());
+		Ok(ConsensusState::new(root, timestamp, tendermint::Hash::None))
+	}
+}
+
+/// A block of a mock chain.
+#[derive(Debug, Clone, Default)]
+pub struct Block {
+	pub events: Vec<IbcEvent>,
+	/// Whether the update of the client of the chain to this block is mandatory.
+	pub mandatory: bool,
+}
+
+/// A consensus state of a client hosted by a mock chain.
+#[derive(Debug, Clone)]
+pub struct ConsensusEntry {
+	pub consensus_state: Any,
+	/// Height and timestamp of the block of the host chain the consensus state was stored in.
+	pub host_height: Height,
+	pub host_timestamp: Timestamp,
+}
+
+/// A client hosted by a mock chain.
+#[derive(Debug, Clone)]
+pub struct ClientEntry {
+	pub client_state: Any,
+	pub consensus_states: BTreeMap<Height, ConsensusEntry>,
+}
+
+/// A transaction submitted to a mock chain.
+#[derive(Debug, Clone)]
+pub struct MockTransaction {
+	pub id: u64,
+	/// Height of the block the transaction is included in.
+	pub height: u64,
+	pub messages: Vec<Any>,
+	pub fee: Fee,
+	/// The client created by the transaction, if any.
+	pub created_client: Option<ClientId>,
+}
+
+type ChannelKey = (PortId, ChannelId);
+
+type PacketKey = (PortId, ChannelId, u64);
+
+/// The IBC state of a mock chain.
+#[derive(Debug, Clone)]
+pub struct Ledger {
+	revision_number: u64,
+	/// Timestamp of the block at height `0`, in nanoseconds.
+	genesis_timestamp: u64,
+	block_time: Duration,
+	/// The blocks of the chain, the first one is at height `1`.
+	blocks: Vec<Block>,
+	/// Events included in the next block.
+	pending_events: Vec<IbcEvent>,
+	clients: BTreeMap<ClientId, ClientEntry>,
+	connections: BTreeMap<ConnectionId, ConnectionEnd>,
+	channels: BTreeMap<ChannelKey, ChannelEnd>,
+	next_sequence_send: BTreeMap<ChannelKey, u64>,
+	next_sequence_recv: BTreeMap<ChannelKey, u64>,
+	commitments: BTreeSet<PacketKey>,
+	/// Packets sent from this chain, with the height of the block they were sent in.
+	sent_packets: BTreeMap<PacketKey, (Packet, u64)>,
+	receipts: BTreeSet<PacketKey>,
+	acks: BTreeMap<PacketKey, Vec<u8>>,
+	/// Packets received by this chain, with the height of the block they were received in.
+	received_packets: BTreeMap<PacketKey, (Packet, u64)>,
+	/// Balance of the relayer account, in the token used to pay transaction fees.
+	balance: u128,
+	transactions: Vec<MockTransaction>,
+}
+
+impl Ledger {
+	/// Creates a chain whose genesis block, at height `1`, is already produced.
+	pub fn new(
+		revision_number: u64,
+		genesis_timestamp: u64,
+		block_time: Duration,
+		balance: u128,
+	) -> Self {
+		Self {
+			revision_number,
+			genesis_timestamp,
+			block_time,
+			blocks: vec![Block::default()],
+			pending_events: vec![],
+			clients: Default::default(),
+			connections: Default::default(),
+			channels: Default::default(),
+			next_sequence_send: Default::default(),
+			next_sequence_recv: Default::default(),
+			commitments: Default::default(),
+			sent_packets: Default::default(),
+			receipts: Default::default(),
+			acks: Default::default(),
+			received_packets: Default::default(),
+			balance,
+			transactions: vec![],
+		}
+	}
+
+	/// Height of the latest block.
+	pub fn height(&self) -> u64 {
+		self.blocks.len() as u64
+	}
+
+	pub fn latest_height(&self) -> Height {
+		Height::new(self.revision_number, self.height())
+	}
+
+	/// Timestamp of the block at `height`, which is `block_time` later than the previous one.
+	pub fn timestamp_at(&self, height: u64) -> Timestamp {
+		let elapsed = self.block_time.as_nanos() as u64 * height;
+		Timestamp::from_nanoseconds(self.genesis_timestamp + elapsed)
+			.expect("timestamp of a block is valid")
+	}
+
+	pub fn latest_timestamp(&self) -> Timestamp {
+		self.timestamp_at(self.height())
+	}
+
+	pub fn block(&self, height: u64) -> Option<&Block> {
+		height.checked_sub(1).and_then(|index| self.blocks.get(index as usize))
+	}
+
+	/// Header of the produced block at `height`.
+	pub fn header(&self, height: u64) -> Result<MockHeader, Error> {
+		if height == 0 || height > self.height() {
+			return Err(Error::Custom(format!("Block {height} wasn't produced yet")))
+		}
+		Ok(MockHeader {
+			revision_number: self.revision_number,
+			revision_height: height,
+			timestamp: self.timestamp_at(height).nanoseconds(),
+		})
+	}
+
+	/// Produces a new block with the pending events, and returns its height.
+	pub fn produce_block(&mut self, mandatory: bool) -> u64 {
+		let events = std::mem::take(&mut self.pending_events);
+		self.blocks.push(Block { events, mandatory });
+		self.height()
+	}
+
+	/// The events of the blocks `from..=to`.
+	pub fn events_in_range(&self, from: u64, to: u64) -> Vec<IbcEvent> {
+		(from.max(1)..=to.min(self.height()))
+			.filter_map(|height| self.block(height))
+			.flat_map(|block| block.events.iter().cloned())
+			.collect()
+	}
+
+	/// The updates of the client `client_id` of this chain on its counterparty to each block in
+	/// `from..=to`, with the events of the block.
+	pub fn client_updates(
+		&self,
+		client_id: &ClientId,
+		signer: &Signer,
+		from: u64,
+		to: u64,
+	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, Error> {
+		let mut updates = vec![];
+		for height in from.max(1)..=to.min(self.height()) {
+			let header = self.header(height)?;
+			let block = self.block(height).expect("block was produced");
+			let msg = RawMsgUpdateClient {
+				client_id: client_id.to_string(),
+				client_message: Some(header.to_any()),
+				signer: signer.to_string(),
+			};
+			let update_type =
+				if block.mandatory { UpdateType::Mandatory } else { UpdateType::Optional };
+			updates.push((
+				RawAny::MsgUpdateClient(msg).encode(),
+				header.height(),
+				block.events.clone(),
+				update_type,
+			));
+		}
+		Ok(updates)
+	}
+
+	pub fn client(&self, client_id: &ClientId) -> Result<&ClientEntry, Error> {
+		self.clients
+			.get(client_id)
+			.ok_or_else(|| Error::Custom(format!("Client {client_id} not found")))
+	}
+
+	pub fn clients(&self) -> Vec<ClientId> {
+		self.clients.keys().cloned().collect()
+	}
+
+	pub fn connection(&self, connection_id: &ConnectionId) -> Result<&ConnectionEnd, Error> {
+		self.connections
+			.get(connection_id)
+			.ok_or_else(|| Error::Custom(format!("Connection {connection_id} not found")))
+	}
+
+	pub fn connections(&self) -> impl Iterator<Item = (&ConnectionId, &ConnectionEnd)> {
+		self.connections.iter()
+	}
+
+	pub fn insert_connection(&mut self, connection_id: ConnectionId, connection: ConnectionEnd) {
+		self.connections.insert(connection_id, connection);
+	}
+
+	pub fn channel(&self, port_id: &PortId, channel_id: &ChannelId) -> Result<&ChannelEnd, Error> {
+		self.channels
+			.get(&(port_id.clone(), *channel_id))
+			.ok_or_else(|| Error::Custom(format!("Channel {port_id}/{channel_id} not found")))
+	}
+
+	pub fn channels(&self) -> impl Iterator<Item = (&PortId, &ChannelId, &ChannelEnd)> {
+		self.channels
+			.iter()
+			.map(|((port_id, channel_id), channel)| (port_id, channel_id, channel))
+	}
+
+	pub fn insert_channel(&mut self, port_id: PortId, channel_id: ChannelId, channel: ChannelEnd) {
+		self.channels.insert((port_id, channel_id), channel);
+	}
+
+	pub fn next_sequence_recv(&self, port_id: &PortId, channel_id: &ChannelId) -> u64 {
+		self.next_sequence_recv
+			.get(&(port_id.clone(), *channel_id))
+			.copied()
+			.unwrap_or(1)
+	}
+
+	pub fn has_commitment(&self, port_id: &PortId, channel_id: &ChannelId, seq: u64) -> bool {
+		self.commitments.contains(&(port_id.clone(), *channel_id, seq))
+	}
+
+	/// Commitment of a sent packet, derived from its sequence, empty if it isn't committed.
+	pub fn commitment(&self, port_id: &PortId, channel_id: &ChannelId, seq: u64) -> Vec<u8> {
+		if self.has_commitment(port_id, channel_id, seq) {
+			seq.to_be_bytes().to_vec()
+		} else {
+			vec![]
+		}
+	}
+
+	/// Sequences of the committed packets of a channel.
+	pub fn commitments(&self, port_id: &PortId, channel_id: &ChannelId) -> Vec<u64> {
+		self.commitments
+			.iter()
+			.filter(|(port, channel, _)| port == port_id && channel == channel_id)
+			.map(|(_, _, seq)| *seq)
+			.collect()
+	}
+
+	pub fn has_receipt(&self, port_id: &PortId, channel_id: &ChannelId, seq: u64) -> bool {
+		self.receipts.contains(&(port_id.clone(), *channel_id, seq))
+	}
+
+	/// Acknowledgement of a received packet, empty if it wasn't received.
+	pub fn ack(&self, port_id: &PortId, channel_id: &ChannelId, seq: u64) -> Vec<u8> {
+		self.acks.get(&(port_id.clone(), *channel_id, seq)).cloned().unwrap_or_default()
+	}
+
+	/// Sequences of the acknowledgements written on a channel.
+	pub fn acks(&self, port_id: &PortId, channel_id: &ChannelId) -> Vec<u64> {
+		self.acks
+			.keys()
+			.filter(|(port, channel, _)| port == port_id && channel == channel_id)
+			.map(|(_, _, seq)| *seq)
+			.collect()
+	}
+
+	/// The packets with the given sequences sent from a channel of this chain.
+	pub fn sent_packets(
+		&self,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seqs: &[u64],
+	) -> Vec<PacketInfo> {
+		seqs.iter()
+			.filter_map(|seq| self.sent_packets.get(&(port_id.clone(), *channel_id, *seq)))
+			.map(|(packet, height)| self.packet_info(packet, *height, None))
+			.collect()
+	}
+
+	/// The packets with the given sequences received on a channel of this chain, with their
+	/// acknowledgement.
+	pub fn received_packets(
+		&self,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seqs: &[u64],
+	) -> Vec<PacketInfo> {
+		seqs.iter()
+			.filter_map(|seq| {
+				let key = (port_id.clone(), *channel_id, *seq);
+				let (packet, height) = self.received_packets.get(&key)?;
+				Some(self.packet_info(packet, *height, self.acks.get(&key).cloned()))
+			})
+			.collect()
+	}
+
+	fn packet_info(&self, packet: &Packet, height: u64, ack: Option<Vec<u8>>) -> PacketInfo {
+		let channel_order = self
+			.channel(&packet.source_port, &packet.source_channel)
+			.or_else(|_| self.channel(&packet.destination_port, &packet.destination_channel))
+			.map(|channel| channel.ordering().to_string())
+			.unwrap_or_default();
+		PacketInfo {
+			height: Some(height),
+			sequence: packet.sequence.into(),
+			source_port: packet.source_port.to_string(),
+			source_channel: packet.source_channel.to_string(),
+			destination_port: packet.destination_port.to_string(),
+			destination_channel: packet.destination_channel.to_string(),
+			channel_order,
+			data: packet.data.clone(),
+			timeout_height: packet.timeout_height.into(),
+			timeout_timestamp: packet.timeout_timestamp.nanoseconds(),
+			ack,
+		}
+	}
+
+	pub fn balance(&self) -> u128 {
+		self.balance
+	}
+
+	pub fn transaction(&self, tx_id: u64) -> Result<&MockTransaction, Error> {
+		self.transactions
+			.get(tx_id as usize)
+			.ok_or_else(|| Error::Custom(format!("Transaction {tx_id} not found")))
+	}
+
+	pub fn transactions(&self) -> &[MockTransaction] {
+		&self.transactions
+	}
+
+	/// Height of the block the events emitted now are included in.
+	fn next_height(&self) -> Height {
+		Height::new(self.revision_number, self.height() + 1)
+	}
+
+	/// Sends a packet on an open channel, as an application of this chain would.
+	pub fn send_packet(
+		&mut self,
+		port_id: PortId,
+		channel_id: ChannelId,
+		data: Vec<u8>,
+		timeout_height: Height,
+		timeout_timestamp: Timestamp,
+	) -> Result<Packet, Error> {
+		let counterparty = self.channel(&port_id, &channel_id)?.counterparty().clone();
+		let destination_channel = *counterparty.channel_id().ok_or_else(|| {
+			Error::Custom(format!("Channel {port_id}/{channel_id} has no counterparty"))
+		})?;
+		let next_sequence =
+			self.next_sequence_send.entry((port_id.clone(), channel_id)).or_insert(1);
+		let sequence = *next_sequence;
+		*next_sequence += 1;
+
+		let packet = Packet {
+			sequence: sequence.into(),
+			source_port: port_id.clone(),
+			source_channel: channel_id,
+			destination_port: counterparty.port_id,
+			destination_channel,
+			data,
+			timeout_height,
+			timeout_timestamp,
+		};
+		let height = self.next_height();
+		let key = (port_id, channel_id, sequence);
+		self.commitments.insert(key.clone());
+		self.sent_packets.insert(key, (packet.clone(), height.revision_height));
+		self.pending_events
+			.push(IbcEvent::SendPacket(SendPacket { height, packet: packet.clone() }));
+		Ok(packet)
+	}
+
+	/// Applies the messages of a transaction, and charges `fee` for it. Nothing is applied if
+	/// any of the messages fails, like in a real transaction.
+	pub fn submit(&mut self, messages: Vec<Any>, fee: Fee) -> Result<u64, Error> {
+		let mut ledger = self.clone();
+		let mut created_client = None;
+		for message in &messages {
+			if let Some(client_id) = ledger.apply(message)? {
+				created_client = Some(client_id);
+			}
+		}
+		ledger.balance = ledger.balance.checked_sub(fee.amount).ok_or_else(|| {
+			Error::Custom(format!("Insufficient balance to pay a fee of {}", fee.amount))
+		})?;
+		let id = ledger.transactions.len() as u64;
+		let height = ledger.next_height().revision_height;
+		ledger
+			.transactions
+			.push(MockTransaction { id, height, messages, fee, created_client });
+		*self = ledger;
+		Ok(id)
+	}
+
+	/// Applies a message, and returns the client it created, if any.
+	fn apply(&mut self, message: &Any) -> Result<Option<ClientId>, Error> {
+		let raw =
+			RawAny::decode(message).map_err(|_| Error::Unsupported(message.type_url.clone()))?;
+		match raw {
+			RawAny::MsgCreateClient(msg) => return self.create_client(msg).map(Some),
+			RawAny::MsgUpdateClient(msg) => self.update_client(msg)?,
+			RawAny::MsgRecvPacket(msg) => self.recv_packet(msg.packet)?,
+			RawAny::MsgAcknowledgement(msg) => self.acknowledge_packet(msg.packet)?,
+			RawAny::MsgTimeout(msg) => self.timeout_packet(msg.packet, false)?,
+			RawAny::MsgTimeoutOnClose(msg) => self.timeout_packet(msg.packet, true)?,
+			_ => return Err(Error::Unsupported(message.type_url.clone())),
+		}
+		Ok(None)
+	}
+
+	fn create_client(&mut self, msg: RawMsgCreateClient) -> Result<ClientId, Error> {
+		let client_state = msg
+			.client_state
+			.ok_or_else(|| Error::Custom("Missing client state".to_string()))?;
+		let consensus_state = msg
+			.consensus_state
+			.ok_or_else(|| Error::Custom("Missing consensus state".to_string()))?;
+		let decoded = AnyClientState::try_from(client_state.clone())
+			.map_err(|e| Error::Custom(format!("Invalid client state: {e:?}")))?;
+		let client_type = decoded.client_type();
+		let client_id = ClientId::new(&client_type, self.clients.len() as u64)
+			.map_err(|e| Error::Custom(format!("Invalid client id: {e:?}")))?;
+		let consensus_height = decoded.latest_height();
+		let mut client = ClientEntry { client_state, consensus_states: BTreeMap::new() };
+		client
+			.consensus_states
+			.insert(consensus_height, self.consensus_entry(consensus_state));
+		self.clients.insert(client_id.clone(), client);
+		let height = self.next_height();
+		self.pending_events.push(IbcEvent::CreateClient(CreateClient(Attributes {
+			height,
+			client_id: client_id.clone(),
+			client_type,
+			consensus_height,
+		})));
+		Ok(client_id)
+	}
+
+	/// Updates a Tendermint client with a [`MockHeader`] of its mock chain. The header isn't
+	/// verified.
+	fn update_client(&mut self, msg: RawMsgUpdateClient) -> Result<(), Error> {
+		let client_id = ClientId::from_str(&msg.client_id)
+			.map_err(|e| Error::Custom(format!("Invalid client id: {e:?}")))?;
+		let message = msg
+			.client_message
+			.ok_or_else(|| Error::Custom("Missing client message".to_string()))?;
+		if message.type_url != MOCK_HEADER_TYPE_URL {
+			return Err(Error::Unsupported(message.type_url))
+		}
+		let header = MockHeader::decode(message.value.as_slice())
+			.map_err(|e| Error::Custom(format!("Invalid header: {e}")))?;
+		let consensus_state: Any = AnyConsensusState::Tendermint(header.consensus_state()?).into();
+		let consensus_entry = self.consensus_entry(consensus_state);
+
+		let client = self
+			.clients
+			.get_mut(&client_id)
+			.ok_or_else(|| Error::Custom(format!("Client {client_id} not found")))?;
+		let AnyClientState::Tendermint(mut client_state) =
+			AnyClientState::try_from(client.client_state.clone())
+				.map_err(|e| Error::Custom(format!("Invalid client state: {e:?}")))?
+		else {
+			return Err(Error::Custom(format!("Client {client_id} isn't a Tendermint client")))
+		};
+		let consensus_height = header.height();
+		let client_type = client_state.client_type();
+		if consensus_height > client_state.latest_height {
+			client_state.latest_height = consensus_height;
+			client.client_state = AnyClientState::Tendermint(client_state).into();
+		}
+		client.consensus_states.insert(consensus_height, consensus_entry);
+
+		let height = self.next_height();
+		self.pending_events.push(IbcEvent::UpdateClient(UpdateClient {
+			common: Attributes { height, client_id, client_type, consensus_height },
+			header: None,
+		}));
+		Ok(())
+	}
+
+	fn consensus_entry(&self, consensus_state: Any) -> ConsensusEntry {
+		let host_height = self.next_height();
+		ConsensusEntry {
+			consensus_state,
+			host_height,
+			host_timestamp: self.timestamp_at(host_height.revision_height),
+		}
+	}
+
+	/// Receives a packet and writes its acknowledgement. A packet can only be received once.
+	fn recv_packet(&mut self, packet: Option<RawPacket>) -> Result<(), Error> {
+		let packet = decode_packet(packet)?;
+		let sequence = u64::from(packet.sequence);
+		let (port_id, channel_id) = (&packet.destination_port, &packet.destination_channel);
+		self.channel(port_id, channel_id)?;
+		if self.has_receipt(port_id, channel_id, sequence) {
+			return Err(Error::Custom(format!(
+				"Packet {sequence} was already received on {port_id}/{channel_id}"
+			)))
+		}
+		if packet.timed_out(&self.latest_timestamp(), self.latest_height()) {
+			return Err(Error::Custom(format!(
+				"Packet {sequence} received on {port_id}/{channel_id} has timed out"
+			)))
+		}
+
+		let key = (port_id.clone(), *channel_id, sequence);
+		let height = self.next_height();
+		let next_sequence_recv =
+			self.next_sequence_recv.entry((port_id.clone(), *channel_id)).or_insert(1);
+		*next_sequence_recv = (*next_sequence_recv).max(sequence + 1);
+		self.receipts.insert(key.clone());
+		self.acks.insert(key.clone(), MOCK_ACK.to_vec());
+		self.received_packets.insert(key, (packet.clone(), height.revision_height));
+		self.pending_events
+			.push(IbcEvent::ReceivePacket(ReceivePacket { height, packet: packet.clone() }));
+		self.pending_events.push(IbcEvent::WriteAcknowledgement(WriteAcknowledgement {
+			height,
+			packet,
+			ack: MOCK_ACK.to_vec(),
+		}));
+		Ok(())
+	}
+
+	fn acknowledge_packet(&mut self, packet: Option<RawPacket>) -> Result<(), Error> {
+		let packet = self.delete_commitment(packet)?;
+		let height = self.next_height();
+		self.pending_events
+			.push(IbcEvent::AcknowledgePacket(AcknowledgePacket { height, packet }));
+		Ok(())
+	}
+
+	fn timeout_packet(&mut self, packet: Option<RawPacket>, on_close: bool) -> Result<(), Error> {
+		let packet = self.delete_commitment(packet)?;
+		let height = self.next_height();
+		let event = if on_close {
+			IbcEvent::TimeoutOnClosePacket(TimeoutOnClosePacket { height, packet })
+		} else {
+			IbcEvent::TimeoutPacket(TimeoutPacket { height, packet })
+		};
+		self.pending_events.push(event);
+		Ok(())
+	}
+
+	/// Deletes the commitment of an acknowledged or timed out packet sent from this chain.
+	fn delete_commitment(&mut self, packet: Option<RawPacket>) -> Result<Packet, Error> {
+		let packet = decode_packet(packet)?;
+		let sequence = u64::from(packet.sequence);
+		let key = (packet.source_port.clone(), packet.source_channel, sequence);
+		if !self.commitments.remove(&key) {
+			return Err(Error::Custom(format!(
+				"Packet {sequence} sent on {}/{} isn't committed",
+				packet.source_port, packet.source_channel
+			)))
+		}
+		Ok(packet)
+	}
+}
+
+fn decode_packet(packet: Option<RawPacket>) -> Result<Packet, Error> {
+	let packet = packet.ok_or_else(|| Error::Custom("Missing packet".to_string()))?;
+	Packet::try_from(packet).map_err(|e| Error::Custom(format!("Invalid packet: {e:?}")))
+}
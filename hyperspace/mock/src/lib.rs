@@ -0,0 +1,475 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A deterministic, in-memory chain implementing [`primitives::Chain`], to test the relay loop,
+//! the message queue and the packet relaying of hyperspace without running a parachain and a
+//! Cosmos node.
+//!
+//! A [`MockChain`] is a handle on a shared [`Ledger`]: the test keeps a clone to script the chain
+//! while the relayer drives another one. Blocks are only produced by [`MockChain::produce_block`],
+//! which notifies the finality subscribers, and the test sends packets with
+//! [`MockChain::send_packet`]. The client of a mock chain on its counterparty is a Tendermint
+//! client updated with [`MockHeader`]s, which are not verified, so two mock chains can relay to
+//! each other. Client creation, client updates, packets, acknowledgements and timeouts are
+//! supported, the connection and channel handshakes are set up by the test with
+//! [`MockChain::insert_connection`] and [`MockChain::insert_channel`], or all at once with
+//! [`open_transfer_channel`].
+//!
+//! Failures are injected with [`MockChain::fail_next_queries`],
+//! [`MockChain::fail_next_submissions`] and [`MockChain::set_offline`]. The relayer handles them
+//! as the errors of an overloaded node, doubling the delay between its rpc calls.
+
+use futures::channel::mpsc::UnboundedSender;
+use ibc::{
+	core::{
+		ics03_connection::{
+			connection::{self, ConnectionEnd},
+			version::Version as ConnectionVersion,
+		},
+		ics04_channel::{
+			channel::{self, ChannelEnd, Order},
+			packet::Packet,
+			Version,
+		},
+		ics23_commitment::commitment::CommitmentPrefix,
+		ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
+	},
+	events::IbcEvent,
+	timestamp::Timestamp,
+	Height,
+};
+use ibc_proto::ibc::core::client::v1::MsgCreateClient;
+use ibc_proto_registry::RawAny;
+use primitives::{
+	CommonClientConfig, CommonClientState, Fee, FinalitySource, IbcQuerier, KeyProvider,
+	TxSubmitter,
+};
+use std::{
+	collections::HashSet,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+pub mod chain;
+pub mod error;
+pub mod ledger;
+pub mod provider;
+
+pub use error::Error;
+pub use ledger::{Ledger, MockHeader, MockTransaction, MOCK_ACK, MOCK_HEADER_TYPE_URL};
+
+/// Notification of a new block of a mock chain, which is final as soon as it's produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockFinalityEvent {
+	pub height: u64,
+}
+
+/// Configuration of a [`MockChain`].
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+	/// Name of the chain, used in logs and as its chain id.
+	pub name: String,
+	/// Revision number of the heights of the chain.
+	pub revision_number: u64,
+	/// Time between two blocks.
+	pub block_time: Duration,
+	/// Timestamp of the block at height `0`, in nanoseconds.
+	pub genesis_timestamp: u64,
+	/// Token the transaction fees are paid in.
+	pub fee_denom: String,
+	/// Fee charged for each message of a transaction.
+	pub fee_per_message: u128,
+	/// Initial balance of the relayer account.
+	pub balance: u128,
+	/// Weight of each message of a transaction.
+	pub weight_per_message: u64,
+	/// Maximum weight of the transactions of a block.
+	pub block_max_weight: u64,
+	/// Common relayer parameters.
+	pub common: CommonClientConfig,
+}
+
+impl MockConfig {
+	pub fn new(name: &str) -> Self {
+		Self {
+			name: name.to_string(),
+			revision_number: 1,
+			block_time: Duration::from_secs(6),
+			genesis_timestamp: 1_700_000_000_000_000_000,
+			fee_denom: "stake".to_string(),
+			fee_per_message: 1_000,
+			balance: 1_000_000_000,
+			weight_per_message: 1_000,
+			block_max_weight: 100_000,
+			common: CommonClientConfig::default(),
+		}
+	}
+}
+
+/// Failures injected in a mock chain.
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+	/// Number of the next queries that fail.
+	pub failing_queries: u32,
+	/// Number of the next submissions that fail, without applying their messages.
+	pub failing_submissions: u32,
+	/// Whether the chain is unreachable: all the queries and submissions fail, and no finality
+	/// notification is delivered.
+	pub offline: bool,
+}
+
+impl Faults {
+	fn check_query(&mut self) -> Result<(), Error> {
+		if self.offline {
+			return Err(Error::Injected("chain is offline".to_string()))
+		}
+		if self.failing_queries > 0 {
+			self.failing_queries -= 1;
+			return Err(Error::Injected("query failed".to_string()))
+		}
+		Ok(())
+	}
+
+	fn check_submission(&mut self) -> Result<(), Error> {
+		if self.offline {
+			return Err(Error::Injected("chain is offline".to_string()))
+		}
+		if self.failing_submissions > 0 {
+			self.failing_submissions -= 1;
+			return Err(Error::Injected("submission failed".to_string()))
+		}
+		Ok(())
+	}
+}
+
+/// State shared by the clones of a [`MockChain`].
+pub(crate) struct MockState {
+	pub ledger: Ledger,
+	pub faults: Faults,
+	pub finality_subscribers: Vec<UnboundedSender<MockFinalityEvent>>,
+	pub event_subscribers: Vec<UnboundedSender<IbcEvent>>,
+}
+
+/// Implements the [`primitives::Chain`] traits on an in-memory [`Ledger`].
+#[derive(Clone)]
+pub struct MockChain {
+	pub name: String,
+	pub chain_id: ChainId,
+	pub(crate) block_time: Duration,
+	pub(crate) fee_denom: String,
+	pub(crate) fee_per_message: u128,
+	pub(crate) weight_per_message: u64,
+	pub(crate) block_max_weight: u64,
+	pub(crate) state: Arc<Mutex<MockState>>,
+	/// Light client id of this chain on the counterparty
+	pub client_id: Option<ClientId>,
+	/// Connection id on this chain
+	pub connection_id: Option<ConnectionId>,
+	/// Channels cleared for packet relay
+	pub channel_whitelist: HashSet<(ChannelId, PortId)>,
+	/// Common relayer data
+	pub common_state: CommonClientState,
+}
+
+impl MockChain {
+	pub fn new(config: MockConfig) -> Self {
+		let ledger = Ledger::new(
+			config.revision_number,
+			config.genesis_timestamp,
+			config.block_time,
+			config.balance,
+		);
+		let mut common_state = CommonClientState::default();
+		let default_max_packets_to_process = common_state.max_packets_to_process;
+		common_state.apply_config(&config.common, default_max_packets_to_process);
+		Self {
+			chain_id: ChainId::new(config.name.clone(), config.revision_number),
+			name: config.name,
+			block_time: config.block_time,
+			fee_denom: config.fee_denom,
+			fee_per_message: config.fee_per_message,
+			weight_per_message: config.weight_per_message,
+			block_max_weight: config.block_max_weight,
+			state: Arc::new(Mutex::new(MockState {
+				ledger,
+				faults: Faults::default(),
+				finality_subscribers: vec![],
+				event_subscribers: vec![],
+			})),
+			client_id: None,
+			connection_id: None,
+			channel_whitelist: HashSet::new(),
+			common_state,
+		}
+	}
+
+	/// Runs `f` on the ledger, unless a query failure was injected.
+	pub(crate) fn query<R>(&self, f: impl FnOnce(&Ledger) -> Result<R, Error>) -> Result<R, Error> {
+		let mut state = self.state.lock().unwrap();
+		state.faults.check_query()?;
+		f(&state.ledger)
+	}
+
+	/// Fee charged for a transaction of `messages` messages.
+	pub(crate) fn fee(&self, messages: usize) -> Fee {
+		Fee { amount: self.fee_per_message * messages as u128, denom: self.fee_denom.clone() }
+	}
+
+	/// Reads the ledger, regardless of the injected failures.
+	pub fn with_ledger<R>(&self, f: impl FnOnce(&Ledger) -> R) -> R {
+		f(&self.state.lock().unwrap().ledger)
+	}
+
+	/// Height of the latest block.
+	pub fn height(&self) -> Height {
+		self.with_ledger(|ledger| ledger.latest_height())
+	}
+
+	/// Transactions submitted to the chain, in order.
+	pub fn submitted(&self) -> Vec<MockTransaction> {
+		self.with_ledger(|ledger| ledger.transactions().to_vec())
+	}
+
+	/// Produces a block including the pending events, whose client update is optional, and
+	/// notifies the subscribers. Returns its height.
+	pub fn produce_block(&self) -> Height {
+		self.produce_block_with(false)
+	}
+
+	/// Same as [`Self::produce_block`], but the client update to the block is mandatory, as if it
+	/// changed the authority set.
+	pub fn produce_mandatory_block(&self) -> Height {
+		self.produce_block_with(true)
+	}
+
+	fn produce_block_with(&self, mandatory: bool) -> Height {
+		let mut state = self.state.lock().unwrap();
+		let height = state.ledger.produce_block(mandatory);
+		if !state.faults.offline {
+			let events = state.ledger.block(height).map(|block| block.events.clone());
+			let event = MockFinalityEvent { height };
+			state.finality_subscribers.retain(|sender| sender.unbounded_send(event).is_ok());
+			for event in events.unwrap_or_default() {
+				let subscribers = &mut state.event_subscribers;
+				subscribers.retain(|sender| sender.unbounded_send(event.clone()).is_ok());
+			}
+		}
+		state.ledger.latest_height()
+	}
+
+	/// Produces `count` blocks, see [`Self::produce_block`].
+	pub fn produce_blocks(&self, count: u64) -> Height {
+		(0..count).fold(self.height(), |_, _| self.produce_block())
+	}
+
+	pub fn insert_connection(&self, connection_id: ConnectionId, connection: ConnectionEnd) {
+		self.state.lock().unwrap().ledger.insert_connection(connection_id, connection)
+	}
+
+	pub fn insert_channel(&self, port_id: PortId, channel_id: ChannelId, channel: ChannelEnd) {
+		self.state.lock().unwrap().ledger.insert_channel(port_id, channel_id, channel)
+	}
+
+	/// Sends a packet on an open channel, as an application of the chain would. The packet is
+	/// committed, and its `SendPacket` event is included in the next block.
+	pub fn send_packet(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+		data: Vec<u8>,
+		timeout_height: Height,
+		timeout_timestamp: Timestamp,
+	) -> Result<Packet, Error> {
+		self.state.lock().unwrap().ledger.send_packet(
+			port_id,
+			channel_id,
+			data,
+			timeout_height,
+			timeout_timestamp,
+		)
+	}
+
+	/// Makes the next `count` queries fail.
+	pub fn fail_next_queries(&self, count: u32) {
+		self.state.lock().unwrap().faults.failing_queries = count;
+	}
+
+	/// Makes the next `count` submissions fail, without applying their messages.
+	pub fn fail_next_submissions(&self, count: u32) {
+		self.state.lock().unwrap().faults.failing_submissions = count;
+	}
+
+	/// Takes the chain offline, or brings it back. Going offline closes the finality streams, and
+	/// no new one can be opened until the chain is back online.
+	pub fn set_offline(&self, offline: bool) {
+		let mut state = self.state.lock().unwrap();
+		state.faults.offline = offline;
+		if offline {
+			state.finality_subscribers.clear();
+			state.event_subscribers.clear();
+		}
+	}
+}
+
+/// Creates the client of `chain` on `counterparty`.
+async fn create_client(chain: &mut MockChain, counterparty: &MockChain) -> Result<(), Error> {
+	let (client_state, consensus_state) = chain.initialize_client_state().await?;
+	let msg = MsgCreateClient {
+		client_state: Some(client_state.into()),
+		consensus_state: Some(consensus_state.into()),
+		signer: counterparty.account_id().to_string(),
+	};
+	let tx_id = counterparty.submit(vec![RawAny::MsgCreateClient(msg).encode()]).await?;
+	chain.set_client_id(counterparty.query_client_id_from_tx_hash(tx_id).await?);
+	Ok(())
+}
+
+/// Creates the clients of two mock chains on each other, and opens the channel `channel-0` of the
+/// transfer port between them, over the connection `connection-0` without delay. The channel is
+/// whitelisted on both chains, and a block including the `CreateClient` event is produced on each.
+pub async fn open_transfer_channel(
+	chain_a: &mut MockChain,
+	chain_b: &mut MockChain,
+	order: Order,
+) -> Result<(), Error> {
+	create_client(chain_a, chain_b).await?;
+	create_client(chain_b, chain_a).await?;
+
+	let port_id = PortId::transfer();
+	let channel_id = ChannelId::new(0);
+	let connection_id = ConnectionId::new(0);
+	let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).expect("prefix is not empty");
+	// the client of a chain is hosted by its counterparty
+	let (client_a, client_b) = (chain_a.client_id(), chain_b.client_id());
+	let chains =
+		[(&mut *chain_a, client_b.clone(), client_a.clone()), (&mut *chain_b, client_a, client_b)];
+	for (chain, client_id, counterparty_client_id) in chains {
+		let connection = ConnectionEnd::new(
+			connection::State::Open,
+			client_id,
+			connection::Counterparty::new(
+				counterparty_client_id,
+				Some(connection_id.clone()),
+				prefix.clone(),
+			),
+			vec![ConnectionVersion::default()],
+			Duration::ZERO,
+		);
+		let channel = ChannelEnd::new(
+			channel::State::Open,
+			order,
+			channel::Counterparty::new(port_id.clone(), Some(channel_id)),
+			vec![connection_id.clone()],
+			Version::new(port_id.to_string()),
+		);
+		chain.insert_connection(connection_id.clone(), connection);
+		chain.insert_channel(port_id.clone(), channel_id, channel);
+		chain.set_connection_id(connection_id.clone());
+		chain.add_channel_to_whitelist((channel_id, port_id.clone()));
+		chain.produce_block();
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+	use ibc_proto::{google::protobuf::Any, ibc::core::channel::v1::MsgRecvPacket};
+
+	/// Opens the channel `channel-0` on the transfer port between two mock chains.
+	async fn open_channel() -> (MockChain, MockChain) {
+		let mut chain_a = MockChain::new(MockConfig::new("chain-a"));
+		let mut chain_b = MockChain::new(MockConfig::new("chain-b"));
+		open_transfer_channel(&mut chain_a, &mut chain_b, Order::Unordered)
+			.await
+			.unwrap();
+		(chain_a, chain_b)
+	}
+
+	fn recv_packet(packet: &Packet, proof_height: Height, signer: String) -> Any {
+		let msg = MsgRecvPacket {
+			packet: Some(packet.clone().into()),
+			proof_commitment: ledger::MOCK_PROOF.to_vec(),
+			proof_height: Some(proof_height.into()),
+			signer,
+		};
+		RawAny::MsgRecvPacket(msg).encode()
+	}
+
+	#[tokio::test]
+	async fn blocks_are_deterministic() {
+		let chain = MockChain::new(MockConfig::new("chain-a"));
+		let height = chain.produce_blocks(3);
+		let (latest_height, timestamp) = chain.latest_height_and_timestamp().await.unwrap();
+		assert_eq!(height, Height::new(1, 4));
+		assert_eq!(latest_height, height);
+		assert_eq!(timestamp.nanoseconds(), 1_700_000_000_000_000_000 + 4 * 6_000_000_000);
+		assert_eq!(chain.query_timestamp_at(4).await.unwrap(), timestamp.nanoseconds());
+	}
+
+	#[tokio::test]
+	async fn relays_packet_once() {
+		let (mut chain_a, chain_b) = open_channel().await;
+		let packet = chain_a
+			.send_packet(
+				PortId::transfer(),
+				ChannelId::new(0),
+				b"data".to_vec(),
+				Height::new(1, 100),
+				Timestamp::none(),
+			)
+			.unwrap();
+		let height = chain_a.produce_block();
+
+		let event = MockFinalityEvent { height: height.revision_height };
+		let updates = chain_a.query_latest_ibc_events(event, &chain_b).await.unwrap();
+		let (update, update_height, events, _) = updates.last().unwrap().clone();
+		assert_eq!(update_height, height);
+		assert!(matches!(events.as_slice(), [IbcEvent::SendPacket(_)]));
+
+		let recv = recv_packet(&packet, height, chain_b.account_id().to_string());
+		chain_b.submit(vec![update, recv.clone()]).await.unwrap();
+		assert!(chain_b.with_ledger(|ledger| ledger.has_receipt(
+			&PortId::transfer(),
+			&ChannelId::new(0),
+			1
+		)));
+		// relaying the same packet twice is an error, like on a real chain
+		assert!(chain_b.submit(vec![recv]).await.is_err());
+		assert_eq!(chain_b.submitted().len(), 2);
+	}
+
+	#[tokio::test]
+	async fn injected_failures() {
+		let (mut chain_a, chain_b) = open_channel().await;
+		let balance = chain_b.with_ledger(|ledger| ledger.balance());
+		let event = MockFinalityEvent { height: chain_a.produce_block().revision_height };
+		let (update, ..) =
+			chain_a.query_latest_ibc_events(event, &chain_b).await.unwrap().pop().unwrap();
+		chain_b.fail_next_submissions(1);
+		assert!(matches!(chain_b.submit(vec![update.clone()]).await, Err(Error::Injected(_))));
+		assert_eq!(chain_b.with_ledger(|ledger| ledger.balance()), balance);
+		chain_b.submit(vec![update]).await.unwrap();
+		assert_eq!(chain_b.with_ledger(|ledger| ledger.balance()), balance - 1_000);
+
+		chain_a.fail_next_queries(1);
+		assert!(chain_a.latest_height_and_timestamp().await.is_err());
+		assert!(chain_a.latest_height_and_timestamp().await.is_ok());
+
+		chain_a.set_offline(true);
+		assert!(chain_a.finality_notifications().await.is_err());
+		chain_a.set_offline(false);
+		assert!(chain_a.finality_notifications().await.is_ok());
+	}
+}
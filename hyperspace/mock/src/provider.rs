@@ -0,0 +1,442 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	error::{Error, INJECTED_FAILURE},
+	ledger::MOCK_PROOF,
+	MockChain,
+};
+use futures::{channel::mpsc, Stream, StreamExt};
+use ibc::{
+	applications::transfer::{PrefixedCoin, PrefixedDenom},
+	core::{
+		ics02_client::client_state::ClientType,
+		ics03_connection::connection::IdentifiedConnectionEnd,
+		ics04_channel::channel::IdentifiedChannelEnd,
+		ics23_commitment::commitment::CommitmentPrefix,
+		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+	},
+	events::IbcEvent,
+	timestamp::Timestamp,
+	Height,
+};
+use ibc_proto::ibc::core::{
+	channel::v1::{
+		QueryChannelResponse, QueryChannelsResponse, QueryNextSequenceReceiveResponse,
+		QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
+		QueryPacketReceiptResponse,
+	},
+	client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
+	connection::v1::{IdentifiedConnection, QueryConnectionResponse},
+};
+use ibc_rpc::PacketInfo;
+use pallet_ibc::light_clients::AnyClientState;
+use primitives::{Chain, CommonClientState, IbcQuerier, KeyProvider, UpgradedClient};
+use std::{collections::HashSet, pin::Pin, str::FromStr, time::Duration};
+
+#[async_trait::async_trait]
+impl IbcQuerier for MockChain {
+	type AssetId = String;
+	type Error = Error;
+
+	async fn query_ibc_events_in_range<T>(
+		&self,
+		_counterparty: &T,
+		from: u64,
+		to: u64,
+	) -> Result<Vec<IbcEvent>, anyhow::Error>
+	where
+		T: Chain,
+	{
+		Ok(self.query(|ledger| Ok(ledger.events_in_range(from, to)))?)
+	}
+
+	async fn ibc_events(
+		&self,
+	) -> Pin<Box<dyn Stream<Item = Result<IbcEvent, Self::Error>> + Send + 'static>> {
+		let (sender, receiver) = mpsc::unbounded();
+		self.state.lock().unwrap().event_subscribers.push(sender);
+		Box::pin(receiver.map(Ok))
+	}
+
+	async fn query_client_consensus(
+		&self,
+		at: Height,
+		client_id: ClientId,
+		consensus_height: Height,
+	) -> Result<QueryConsensusStateResponse, Self::Error> {
+		let consensus_state = self.query(|ledger| {
+			let client = ledger.client(&client_id)?;
+			let entry = client.consensus_states.get(&consensus_height).ok_or_else(|| {
+				Error::Custom(format!(
+					"Consensus state of {client_id} at {consensus_height} not found"
+				))
+			})?;
+			Ok(entry.consensus_state.clone())
+		})?;
+		Ok(QueryConsensusStateResponse {
+			consensus_state: Some(consensus_state),
+			proof: MOCK_PROOF.to_vec(),
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn query_client_state(
+		&self,
+		at: Height,
+		client_id: ClientId,
+	) -> Result<QueryClientStateResponse, Self::Error> {
+		let client_state =
+			self.query(|ledger| Ok(ledger.client(&client_id)?.client_state.clone()))?;
+		Ok(QueryClientStateResponse {
+			client_state: Some(client_state),
+			proof: MOCK_PROOF.to_vec(),
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn query_connection_end(
+		&self,
+		at: Height,
+		connection_id: ConnectionId,
+	) -> Result<QueryConnectionResponse, Self::Error> {
+		let connection = self.query(|ledger| Ok(ledger.connection(&connection_id)?.clone()))?;
+		Ok(QueryConnectionResponse {
+			connection: Some(connection.into()),
+			proof: MOCK_PROOF.to_vec(),
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn query_channel_end(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryChannelResponse, Self::Error> {
+		let channel = self.query(|ledger| Ok(ledger.channel(&port_id, &channel_id)?.clone()))?;
+		Ok(QueryChannelResponse {
+			channel: Some(channel.into()),
+			proof: MOCK_PROOF.to_vec(),
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn query_proof(&self, _at: Height, _keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
+		self.query(|_| Ok(MOCK_PROOF.to_vec()))
+	}
+
+	async fn query_packet_commitment(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketCommitmentResponse, Self::Error> {
+		let commitment = self.query(|ledger| Ok(ledger.commitment(port_id, channel_id, seq)))?;
+		Ok(QueryPacketCommitmentResponse {
+			commitment,
+			proof: MOCK_PROOF.to_vec(),
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn query_packet_acknowledgement(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketAcknowledgementResponse, Self::Error> {
+		let acknowledgement = self.query(|ledger| Ok(ledger.ack(port_id, channel_id, seq)))?;
+		Ok(QueryPacketAcknowledgementResponse {
+			acknowledgement,
+			proof: MOCK_PROOF.to_vec(),
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn query_next_sequence_recv(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+	) -> Result<QueryNextSequenceReceiveResponse, Self::Error> {
+		let next_sequence_receive =
+			self.query(|ledger| Ok(ledger.next_sequence_recv(port_id, channel_id)))?;
+		Ok(QueryNextSequenceReceiveResponse {
+			next_sequence_receive,
+			proof: MOCK_PROOF.to_vec(),
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn query_packet_receipt(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketReceiptResponse, Self::Error> {
+		let received = self.query(|ledger| Ok(ledger.has_receipt(port_id, channel_id, seq)))?;
+		Ok(QueryPacketReceiptResponse {
+			received,
+			proof: MOCK_PROOF.to_vec(),
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn latest_height_and_timestamp(&self) -> Result<(Height, Timestamp), Self::Error> {
+		self.query(|ledger| Ok((ledger.latest_height(), ledger.latest_timestamp())))
+	}
+
+	async fn query_packet_commitments(
+		&self,
+		_at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.query(|ledger| Ok(ledger.commitments(&port_id, &channel_id)))
+	}
+
+	async fn query_packet_acknowledgements(
+		&self,
+		_at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.query(|ledger| Ok(ledger.acks(&port_id, &channel_id)))
+	}
+
+	async fn query_unreceived_packets(
+		&self,
+		_at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.query(|ledger| {
+			Ok(seqs
+				.into_iter()
+				.filter(|seq| !ledger.has_receipt(&port_id, &channel_id, *seq))
+				.collect())
+		})
+	}
+
+	async fn query_unreceived_acknowledgements(
+		&self,
+		_at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.query(|ledger| {
+			Ok(seqs
+				.into_iter()
+				.filter(|seq| ledger.has_commitment(&port_id, &channel_id, *seq))
+				.collect())
+		})
+	}
+
+	fn channel_whitelist(&self) -> HashSet<(ChannelId, PortId)> {
+		self.channel_whitelist.clone()
+	}
+
+	async fn query_connection_channels(
+		&self,
+		at: Height,
+		connection_id: &ConnectionId,
+	) -> Result<QueryChannelsResponse, Self::Error> {
+		let channels = self.query(|ledger| {
+			Ok(ledger
+				.channels()
+				.filter(|(_, _, channel)| channel.connection_hops().first() == Some(connection_id))
+				.map(|(port_id, channel_id, channel)| {
+					IdentifiedChannelEnd::new(port_id.clone(), *channel_id, channel.clone()).into()
+				})
+				.collect())
+		})?;
+		Ok(QueryChannelsResponse { channels, pagination: None, height: Some(at.into()) })
+	}
+
+	async fn query_send_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		self.query(|ledger| Ok(ledger.sent_packets(&port_id, &channel_id, &seqs)))
+	}
+
+	async fn query_received_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		self.query(|ledger| Ok(ledger.received_packets(&port_id, &channel_id, &seqs)))
+	}
+
+	fn expected_block_time(&self) -> Duration {
+		self.block_time
+	}
+
+	async fn query_client_update_time_and_height(
+		&self,
+		client_id: ClientId,
+		client_height: Height,
+	) -> Result<(Height, Timestamp), Self::Error> {
+		self.query(|ledger| {
+			let client = ledger.client(&client_id)?;
+			let entry = client.consensus_states.get(&client_height).ok_or_else(|| {
+				Error::Custom(format!(
+					"Consensus state of {client_id} at {client_height} not found"
+				))
+			})?;
+			Ok((entry.host_height, entry.host_timestamp))
+		})
+	}
+
+	async fn query_host_consensus_state_proof(
+		&self,
+		_client_state: &AnyClientState,
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		Ok(None)
+	}
+
+	async fn query_upgraded_client(
+		&self,
+		_at: Height,
+	) -> Result<Option<UpgradedClient>, Self::Error> {
+		Ok(None)
+	}
+
+	async fn query_ibc_balance(
+		&self,
+		asset_id: Self::AssetId,
+	) -> Result<Vec<PrefixedCoin>, Self::Error> {
+		if asset_id != self.fee_denom {
+			return Ok(vec![])
+		}
+		let amount = self.query(|ledger| Ok(ledger.balance()))?;
+		let denom = PrefixedDenom::from_str(&self.fee_denom)
+			.map_err(|e| Error::Custom(format!("Invalid denom {}: {e:?}", self.fee_denom)))?;
+		Ok(vec![PrefixedCoin { denom, amount: amount.into() }])
+	}
+
+	async fn query_native_balance(&self) -> Result<PrefixedCoin, Self::Error> {
+		self.query_ibc_balance(self.fee_denom.clone())
+			.await?
+			.pop()
+			.ok_or_else(|| Error::from(format!("No balance for denom {}", self.fee_denom)))
+	}
+
+	async fn query_key_balances(&self) -> Result<Vec<(String, PrefixedCoin)>, Self::Error> {
+		Ok(vec![(self.account_id().to_string(), self.query_native_balance().await?)])
+	}
+
+	fn connection_prefix(&self) -> CommitmentPrefix {
+		CommitmentPrefix::try_from(b"ibc".to_vec()).expect("prefix is not empty")
+	}
+
+	fn client_id(&self) -> ClientId {
+		self.client_id.clone().expect("Client id should be set")
+	}
+
+	fn set_client_id(&mut self, client_id: ClientId) {
+		self.client_id = Some(client_id);
+	}
+
+	fn connection_id(&self) -> Option<ConnectionId> {
+		self.connection_id.clone()
+	}
+
+	fn set_channel_whitelist(&mut self, channel_whitelist: HashSet<(ChannelId, PortId)>) {
+		self.channel_whitelist = channel_whitelist;
+	}
+
+	fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
+		self.channel_whitelist.insert(channel);
+	}
+
+	fn set_connection_id(&mut self, connection_id: ConnectionId) {
+		self.connection_id = Some(connection_id);
+	}
+
+	fn client_type(&self) -> ClientType {
+		"07-tendermint".to_string()
+	}
+
+	async fn query_timestamp_at(&self, block_number: u64) -> Result<u64, Self::Error> {
+		self.query(|ledger| Ok(ledger.timestamp_at(block_number).nanoseconds()))
+	}
+
+	async fn query_clients(&self) -> Result<Vec<ClientId>, Self::Error> {
+		self.query(|ledger| Ok(ledger.clients()))
+	}
+
+	async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
+		self.query(|ledger| {
+			Ok(ledger
+				.channels()
+				.map(|(port_id, channel_id, _)| (*channel_id, port_id.clone()))
+				.collect())
+		})
+	}
+
+	async fn query_connection_using_client(
+		&self,
+		_height: u32,
+		client_id: String,
+	) -> Result<Vec<IdentifiedConnection>, Self::Error> {
+		self.query(|ledger| {
+			Ok(ledger
+				.connections()
+				.filter(|(_, connection)| connection.client_id().as_str() == client_id)
+				.map(|(connection_id, connection)| {
+					IdentifiedConnectionEnd::new(connection_id.clone(), connection.clone()).into()
+				})
+				.collect())
+		})
+	}
+
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	async fn query_best_and_finalized_heights(&self) -> Result<(u64, u64), Self::Error> {
+		self.query(|ledger| Ok((ledger.height(), ledger.height())))
+	}
+
+	/// Injected failures are handled like the errors of an overloaded node: the delay between the
+	/// rpc calls is doubled, as the parachain client does on `MaxSlotsExceeded`.
+	async fn handle_error(&mut self, error: &anyhow::Error) -> Result<(), anyhow::Error> {
+		if format!("{error:#}").contains(INJECTED_FAILURE) {
+			self.common_state.rpc_call_delay *= 2;
+		}
+		Ok(())
+	}
+
+	fn common_state(&self) -> &CommonClientState {
+		&self.common_state
+	}
+
+	fn common_state_mut(&mut self) -> &mut CommonClientState {
+		&mut self.common_state
+	}
+
+	async fn reconnect(&mut self) -> anyhow::Result<()> {
+		Ok(self.query(|_| Ok(()))?)
+	}
+}
@@ -12,34 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::{error::Error, signer::ExtrinsicSigner, ParachainClient};
-use crate::{parachain::UncheckedExtrinsic, provider::TransactionId, FinalityProtocol};
+use super::{error::Error, ParachainClient};
+use crate::{
+	finality_protocol::FinalityEvent, parachain::UncheckedExtrinsic, provider::TransactionId,
+	FinalityProtocol,
+};
 use anyhow::anyhow;
 use codec::{Decode, Encode};
 use finality_grandpa::BlockNumberOps;
 use finality_grandpa_rpc::GrandpaApiClient;
-use futures::{Stream, StreamExt, TryFutureExt};
-use grandpa_light_client_primitives::{FinalityProof, ParachainHeaderProofs};
+use futures::{Stream, StreamExt};
+use grandpa_light_client_primitives::{
+	justification::{find_forced_change, find_scheduled_change},
+	FinalityProof, ParachainHeaderProofs,
+};
 use ibc::{
 	core::{
 		ics02_client::{
 			events::UpdateClient,
-			msgs::{update_client::MsgUpdateAnyClient, ClientMsg},
+			msgs::{
+				update_client::{MsgUpdateAnyClient, TYPE_URL as UPDATE_CLIENT_TYPE_URL},
+				ClientMsg,
+			},
 		},
+		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 		ics26_routing::msgs::Ics26Envelope,
 	},
 	events::IbcEvent,
 	tx_msg::Msg,
 	Height,
 };
-use ibc_proto::google::protobuf::Any;
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::core::{channel::v1::IdentifiedChannel, connection::v1::IdentifiedConnection},
+};
+use ibc_rpc::IbcApiClient;
 use ics10_grandpa::client_message::{ClientMessage, Misbehaviour, RelayChainHeader};
 use itertools::Itertools;
-use jsonrpsee_ws_client::WsClientBuilder;
-use light_client_common::config::{EventRecordT, RuntimeCall, RuntimeTransactions};
-use pallet_ibc::light_clients::AnyClientMessage;
+use light_client_common::config::{EventRecordT, RuntimeCall};
+use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState};
 use primitives::{
-	mock::LocalClientTypes, Chain, CommonClientState, IbcProvider, MisbehaviourHandler,
+	mock::LocalClientTypes, Chain, Fee, FinalitySource, IbcQuerier, KeyProvider,
+	MisbehaviourHandler, TxSubmitter, UpdateType,
 };
 use sc_consensus_beefy_rpc::BeefyApiClient;
 use sp_core::{twox_128, H256};
@@ -47,17 +61,15 @@ use sp_runtime::{
 	traits::{IdentifyAccount, One, Verify},
 	MultiSignature, MultiSigner,
 };
-use std::{collections::BTreeMap, fmt::Display, pin::Pin, sync::Arc, time::Duration};
+use std::{collections::BTreeMap, fmt::Display, pin::Pin, str::FromStr, time::Duration};
 use subxt::{
 	config::{
-		extrinsic_params::{BaseExtrinsicParamsBuilder, Era},
-		ExtrinsicParams, Header as HeaderT, Header,
+		extrinsic_params::BaseExtrinsicParamsBuilder, ExtrinsicParams, Header as HeaderT, Header,
 	},
 	events::Phase,
 };
 use tokio::time::sleep;
-use transaction_payment_rpc::TransactionPaymentApiClient;
-use transaction_payment_runtime_api::RuntimeDispatchInfo;
+use tokio_stream::wrappers::ReceiverStream;
 
 type GrandpaJustification = grandpa_light_client_primitives::justification::GrandpaJustification<
 	polkadot_core_primitives::Header,
@@ -70,32 +82,176 @@ type BeefyJustification =
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 struct JustificationNotification(sp_core::Bytes);
 
+/// Number of GRANDPA justifications out of which only the latest one is relayed, unless the
+/// earlier ones finalize an authority set change
+const GRANDPA_JUSTIFICATIONS_CHUNK_SIZE: usize = 3;
+
+/// Decodes the GRANDPA justifications of `notifs` and keeps the latest one, along with the ones
+/// finalizing a block that enacts an authority set change: those can't be skipped, since the
+/// justifications of the following blocks are signed by the new authority set.
+async fn debounce_justifications<T: subxt::Config>(
+	relay_client: &subxt::OnlineClient<T>,
+	notifs: Vec<Result<JustificationNotification, jsonrpsee::core::Error>>,
+) -> Vec<FinalityEvent>
+where
+	T::Hash: From<sp_core::H256>,
+{
+	let justifications = notifs
+		.into_iter()
+		.filter_map(|notif| {
+			let encoded_justification = match notif {
+				Ok(JustificationNotification(sp_core::Bytes(justification))) => justification,
+				Err(err) => {
+					log::error!("Failed to fetch Justification: {}", err);
+					return None
+				},
+			};
+			GrandpaJustification::decode(&mut &*encoded_justification)
+				.map_err(|err| log::error!("Grandpa Justification scale decode error: {}", err))
+				.ok()
+		})
+		.collect::<Vec<_>>();
+
+	let latest = justifications.len().saturating_sub(1);
+	let mut events = vec![];
+	for (i, justification) in justifications.into_iter().enumerate() {
+		let target_hash = justification.commit.target_hash;
+		let authority_set_changed = match relay_client.rpc().header(Some(target_hash.into())).await
+		{
+			Ok(Some(header)) => {
+				let header = RelayChainHeader::decode(&mut &*header.encode()).expect(
+					"Same header struct defined in different crates, decoding cannot panic",
+				);
+				find_scheduled_change(&header).is_some() || find_forced_change(&header).is_some()
+			},
+			Ok(None) => {
+				log::warn!(target: "hyperspace_parachain", "Relay chain header {target_hash:?} not found");
+				false
+			},
+			Err(err) => {
+				log::warn!(target: "hyperspace_parachain", "Failed to fetch relay chain header {target_hash:?}: {err}");
+				false
+			},
+		};
+		if authority_set_changed || i == latest {
+			events.push(FinalityEvent::Grandpa { justification, authority_set_changed });
+		} else {
+			log::debug!(target: "hyperspace_parachain", "Skipping justification for relay chain block {target_hash:?}");
+		}
+	}
+	events
+}
+
 #[async_trait::async_trait]
-impl<T: light_client_common::config::Config + Send + Sync + Clone + 'static> Chain
+impl<T: light_client_common::config::Config + Send + Sync + Clone + 'static> TxSubmitter
 	for ParachainClient<T>
 where
 	u32: From<<<T as subxt::Config>::Header as HeaderT>::Number>,
 	u32: From<<<T as subxt::Config>::Header as Header>::Number>,
+	Self: KeyProvider,
 	<<T as light_client_common::config::Config>::Signature as Verify>::Signer:
 		From<MultiSigner> + IdentifyAccount<AccountId = T::AccountId>,
 	MultiSigner: From<MultiSigner>,
 	<T as subxt::Config>::Address: From<<T as subxt::Config>::AccountId>,
 	<T as subxt::Config>::Signature: From<MultiSignature> + Send + Sync,
-	<<T as subxt::Config>::Header as Header>::Number:
-		BlockNumberOps + From<u32> + Display + Ord + sp_runtime::traits::Zero + One + Send + Sync,
+	<<T as subxt::Config>::Header as Header>::Number: BlockNumberOps
+		+ From<u32>
+		+ Display
+		+ Ord
+		+ sp_runtime::traits::Zero
+		+ One
+		+ Send
+		+ Sync
+		+ Clone,
 	<T as subxt::Config>::Header: Decode + Send + Sync + Clone,
 	T::Hash: From<sp_core::H256> + From<[u8; 32]>,
+	sp_core::H256: From<T::Hash>,
 	BTreeMap<sp_core::H256, ParachainHeaderProofs>:
 		From<BTreeMap<<T as subxt::Config>::Hash, ParachainHeaderProofs>>,
-	sp_core::H256: From<T::Hash>,
 	<T::ExtrinsicParams as ExtrinsicParams<T::Index, T::Hash>>::OtherParams:
 		From<BaseExtrinsicParamsBuilder<T, T::Tip>> + Send + Sync,
 	<T as subxt::Config>::AccountId: Send + Sync,
 	<T as subxt::Config>::Address: Send + Sync,
 	<T as light_client_common::config::Config>::AssetId: Clone,
 {
-	fn name(&self) -> &str {
-		&*self.name
+	type TransactionId = TransactionId<T::Hash>;
+
+	async fn query_client_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<ClientId, Self::Error> {
+		// Query newly created client Id
+		let TransactionId { ext_hash, block_hash } = tx_id;
+		let identified_client_state = IbcApiClient::<
+			u32,
+			H256,
+			<T as light_client_common::config::Config>::AssetId,
+		>::query_newly_created_client(
+			&*self.para_ws_client,
+			block_hash.into(),
+			ext_hash.into(),
+			self.pallet_prefix(),
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+
+		let client_id = ClientId::from_str(&identified_client_state.client_id)
+			.expect("Should have a valid client id");
+		Ok(client_id)
+	}
+
+	async fn query_connection_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<ConnectionId, Self::Error> {
+		// Query newly created connection Id
+		let TransactionId { ext_hash, block_hash } = tx_id;
+		let identified_connection: IdentifiedConnection = IbcApiClient::<
+			u32,
+			H256,
+			<T as light_client_common::config::Config>::AssetId,
+		>::query_newly_created_connection(
+			&*self.para_ws_client,
+			block_hash.into(),
+			ext_hash.into(),
+			self.pallet_prefix(),
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+
+		let connection_id = ConnectionId::from_str(&identified_connection.id)
+			.expect("Should have a valid connection id");
+		Ok(connection_id)
+	}
+
+	async fn query_channel_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<(ChannelId, PortId), Self::Error> {
+		// Query newly created channel Id
+		let TransactionId { ext_hash, block_hash } = tx_id;
+		let identified_channel: IdentifiedChannel = IbcApiClient::<
+			u32,
+			H256,
+			<T as light_client_common::config::Config>::AssetId,
+		>::query_newly_created_channel(
+			&*self.para_ws_client,
+			block_hash.into(),
+			ext_hash.into(),
+			self.pallet_prefix(),
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+
+		let channel_id = ChannelId::from_str(&identified_channel.channel_id)
+			.expect("Should have a valid channel id");
+		let port_id =
+			PortId::from_str(&identified_channel.port_id).expect("Should have a valid port id");
+		Ok((channel_id, port_id))
+	}
+
+	async fn upload_wasm(&self, _wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
+		Err(Error::Custom("Uploading WASM to parachain is not supported".to_string()))
 	}
 
 	fn block_max_weight(&self) -> u64 {
@@ -103,85 +259,207 @@ where
 	}
 
 	async fn estimate_weight(&self, messages: Vec<Any>) -> Result<u64, Self::Error> {
-		let extrinsic = {
-			// todo: put this in utils
-			let signer = ExtrinsicSigner::<T, Self>::new(
-				self.key_store.clone(),
-				self.key_type_id.clone(),
-				self.public_key.clone(),
-			);
-
-			let messages = messages
-				.into_iter()
-				.map(|msg| Any { type_url: msg.type_url.clone(), value: msg.value })
-				.collect::<Vec<_>>();
-
-			let tx_params = BaseExtrinsicParamsBuilder::new()
-				.tip(T::Tip::from(100_000u128))
-				.era(Era::Immortal, self.para_client.genesis_hash());
-			let call = T::Tx::ibc_deliver(messages);
-			self.para_client
-				.tx()
-				.create_signed(&call, &signer, tx_params.into())
-				.await?
-				.encoded()
-				.to_vec()
+		let estimate = self.estimate_extrinsic(messages).await?;
+		if self.check_extrinsic_limits(&estimate).is_err() {
+			// a batch exceeding the extrinsic limits is at least as heavy as a block, so that it's
+			// chunked, and as heavy as its share of the block length if it's too long
+			let length_weight = (estimate.length as u64).saturating_mul(self.block_max_weight()) /
+				(self.max_extrinsic_length as u64).max(1);
+			return Ok(estimate.weight.max(length_weight).max(self.block_max_weight()))
+		}
+		Ok(estimate.weight)
+	}
+
+	async fn estimate_fee(&self, messages: &[Any]) -> Result<Fee, Self::Error> {
+		let estimate = self.estimate_extrinsic(messages.to_vec()).await?;
+		let denom = self.query_native_token_symbol().await?;
+		Ok(Fee { amount: estimate.partial_fee, denom })
+	}
+
+	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Error> {
+		// only lone client updates are sent unsigned, the ones batched with other messages are
+		// delivered with them so that the batch has a single transaction id
+		if self.unsigned_client_updates &&
+			matches!(messages.as_slice(), [msg] if msg.type_url == UPDATE_CLIENT_TYPE_URL)
+		{
+			match self.submit_unsigned_client_update(messages[0].clone()).await {
+				Ok((ext_hash, block_hash)) => {
+					log::debug!(target: "hyperspace_parachain", "Submitted unsigned client update (hash: {:?}) to block {:?}", ext_hash, block_hash);
+					return Ok(TransactionId { ext_hash, block_hash })
+				},
+				Err(e) => {
+					log::debug!(target: "hyperspace_parachain", "Unsigned client update was rejected, submitting a signed one instead: {:?}", e);
+				},
+			}
+		}
+
+		let messages = messages
+			.into_iter()
+			.map(|msg| Any { type_url: msg.type_url.clone(), value: msg.value })
+			.collect::<Vec<_>>();
+		let messages_urls = messages.iter().map(|msg| msg.type_url.clone()).join(", ");
+		let messages_urls_c = messages_urls.clone();
+		log::debug!(target: "hyperspace_parachain", "Sending message: {messages_urls_c}");
+
+		let estimate = self.estimate_extrinsic(messages.clone()).await?;
+		log::debug!(target: "hyperspace_parachain", "Estimated extrinsic weight: {}, fee: {}, length: {}", estimate.weight, estimate.partial_fee, estimate.length);
+		self.check_extrinsic_limits(&estimate)?;
+
+		let call = crate::dynamic::ibc_deliver(&self.pallet_name, messages.clone());
+		let (ext_hash, block_hash) = self.submit_call(call).await?;
+
+		log::debug!(target: "hyperspace_parachain", "Submitted extrinsic (hash: {:?}) to block {:?}", ext_hash, block_hash);
+
+		Ok(TransactionId { ext_hash, block_hash })
+	}
+}
+
+#[async_trait::async_trait]
+impl<T: light_client_common::config::Config + Send + Sync + Clone + 'static> FinalitySource
+	for ParachainClient<T>
+where
+	u32: From<<<T as subxt::Config>::Header as HeaderT>::Number>,
+	u32: From<<<T as subxt::Config>::Header as Header>::Number>,
+	Self: KeyProvider,
+	<<T as light_client_common::config::Config>::Signature as Verify>::Signer:
+		From<MultiSigner> + IdentifyAccount<AccountId = T::AccountId>,
+	MultiSigner: From<MultiSigner>,
+	<T as subxt::Config>::Address: From<<T as subxt::Config>::AccountId>,
+	<T as subxt::Config>::Signature: From<MultiSignature> + Send + Sync,
+	<<T as subxt::Config>::Header as Header>::Number: BlockNumberOps
+		+ From<u32>
+		+ Display
+		+ Ord
+		+ sp_runtime::traits::Zero
+		+ One
+		+ Send
+		+ Sync
+		+ Clone,
+	<T as subxt::Config>::Header: Decode + Send + Sync + Clone,
+	T::Hash: From<sp_core::H256> + From<[u8; 32]>,
+	sp_core::H256: From<T::Hash>,
+	BTreeMap<sp_core::H256, ParachainHeaderProofs>:
+		From<BTreeMap<<T as subxt::Config>::Hash, ParachainHeaderProofs>>,
+	<T::ExtrinsicParams as ExtrinsicParams<T::Index, T::Hash>>::OtherParams:
+		From<BaseExtrinsicParamsBuilder<T, T::Tip>> + Send + Sync,
+	<T as subxt::Config>::AccountId: Send + Sync,
+	<T as subxt::Config>::Address: Send + Sync,
+	<T as light_client_common::config::Config>::AssetId: Clone,
+{
+	type FinalityEvent = FinalityEvent;
+
+	fn is_mandatory_finality_event(finality_event: &FinalityEvent) -> bool {
+		matches!(finality_event, FinalityEvent::Grandpa { authority_set_changed: true, .. })
+	}
+
+	async fn query_latest_ibc_events<C>(
+		&mut self,
+		finality_event: Self::FinalityEvent,
+		counterparty: &C,
+	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+	where
+		C: Chain,
+	{
+		self.finality_protocol
+			.clone()
+			.query_latest_ibc_events(self, finality_event, counterparty)
+			.await
+	}
+
+	async fn is_update_required(
+		&self,
+		latest_height: u64,
+		latest_client_height_on_counterparty: u64,
+	) -> Result<bool, Self::Error> {
+		let base = match self.client_update_period() {
+			Some(period) => period,
+			None => {
+				let prover = self.grandpa_prover();
+				let session_length = prover
+					.session_length()
+					.await
+					.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+				// We divide the session into some places and if the diff in block updates is
+				// greater than this update is required
+				if cfg!(test) {
+					(session_length / 2) as u64
+				} else {
+					(session_length / 12) as u64
+				}
+			},
 		};
-		let dispatch_info = TransactionPaymentApiClient::<
-			H256,
-			RuntimeDispatchInfo<u128, sp_weights::Weight>,
-		>::query_info(&*self.para_ws_client, extrinsic.into(), None)
-		.await
-		.map_err(|e| Error::from(format!("Rpc Error From Estimating weight {:?}", e)))?;
-		Ok(dispatch_info.weight.ref_time())
+		let diff = latest_height.saturating_sub(latest_client_height_on_counterparty);
+		let max_header_lag = self
+			.security_params()
+			.max_header_lag
+			.unwrap_or(primitives::security::DEFAULT_PARACHAIN_MAX_HEADER_LAG);
+		Ok(diff >= base.min(max_header_lag))
+	}
+
+	async fn initialize_client_state(
+		&self,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		match self.finality_protocol {
+			FinalityProtocol::Grandpa => {
+				let res = self.construct_grandpa_client_state().await?;
+				Ok(res)
+			},
+			FinalityProtocol::Beefy => {
+				let res = self.construct_beefy_client_state().await?;
+				Ok(res)
+			},
+		}
+	}
+
+	async fn initialize_client_state_at(
+		&self,
+		height: u64,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		let height = u32::try_from(height)
+			.map_err(|_| Error::Custom(format!("Invalid parachain height {height}")))?;
+		match self.finality_protocol {
+			FinalityProtocol::Grandpa => self.construct_grandpa_client_state_at(Some(height)).await,
+			FinalityProtocol::Beefy => Err(Error::Custom(
+				"Creating a BEEFY client at a past height is not supported".to_string(),
+			)),
+		}
 	}
 
 	async fn finality_notifications(
 		&self,
 	) -> Result<
-		Pin<Box<dyn Stream<Item = <Self as IbcProvider>::FinalityEvent> + Send + Sync>>,
+		Pin<Box<dyn Stream<Item = <Self as FinalitySource>::FinalityEvent> + Send + Sync>>,
 		Error,
 	> {
 		match self.finality_protocol {
 			FinalityProtocol::Grandpa => {
-				let subscription =
-					GrandpaApiClient::<JustificationNotification, sp_core::H256, u32>::subscribe_justifications(
-						&*self.relay_ws_client,
-					)
-						.await?
-						.chunks(3)
-						.map(|mut notifs| notifs.remove(notifs.len() - 1)); // skip every 3 finality notifications
-
-				let stream = subscription.filter_map(|justification_notif| {
-					let encoded_justification = match justification_notif {
-						Ok(JustificationNotification(sp_core::Bytes(justification))) =>
-							justification,
-						Err(err) => {
-							log::error!("Failed to fetch Justification: {}", err);
-							return futures::future::ready(None)
-						},
-					};
-
-					let justification =
-						match GrandpaJustification::decode(&mut &*encoded_justification) {
-							Ok(j) => j,
-							Err(err) => {
-								log::error!("Grandpa Justification scale decode error: {}", err);
-								return futures::future::ready(None)
-							},
-						};
-					futures::future::ready(Some(Self::FinalityEvent::Grandpa(justification)))
+				let mut subscription = GrandpaApiClient::<
+					JustificationNotification,
+					sp_core::H256,
+					u32,
+				>::subscribe_justifications(&*self.relay_ws_client)
+				.await?
+				.chunks(GRANDPA_JUSTIFICATIONS_CHUNK_SIZE);
+				let relay_client = self.relay_client.clone();
+				let (tx, rx) = tokio::sync::mpsc::channel(32);
+				tokio::spawn(async move {
+					while let Some(notifs) = subscription.next().await {
+						for event in debounce_justifications(&relay_client, notifs).await {
+							if tx.send(event).await.is_err() {
+								return
+							}
+						}
+					}
 				});
 
-				Ok(Box::pin(Box::new(stream)))
+				Ok(Box::pin(ReceiverStream::new(rx)))
 			},
 			FinalityProtocol::Beefy => {
 				let subscription =
 					BeefyApiClient::<JustificationNotification, sp_core::H256>::subscribe_justifications(
 						&*self.relay_ws_client,
 					)
-						.await
-						.expect("Failed to subscribe to beefy justifications");
+						.await?;
 
 				let stream = subscription.filter_map(|commitment_notification| {
 					let encoded_commitment = match commitment_notification {
@@ -208,23 +486,6 @@ where
 		}
 	}
 
-	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Error> {
-		let messages = messages
-			.into_iter()
-			.map(|msg| Any { type_url: msg.type_url.clone(), value: msg.value })
-			.collect::<Vec<_>>();
-		let messages_urls = messages.iter().map(|msg| msg.type_url.clone()).join(", ");
-		let messages_urls_c = messages_urls.clone();
-		log::debug!(target: "hyperspace_parachain", "Sending message: {messages_urls_c}");
-
-		let call = T::Tx::ibc_deliver(messages.clone());
-		let (ext_hash, block_hash) = self.submit_call(call).await?;
-
-		log::debug!(target: "hyperspace_parachain", "Submitted extrinsic (hash: {:?}) to block {:?}", ext_hash, block_hash);
-
-		Ok(TransactionId { ext_hash, block_hash })
-	}
-
 	async fn query_client_message(&self, update: UpdateClient) -> Result<AnyClientMessage, Error> {
 		let host_height = update.height();
 
@@ -322,62 +583,6 @@ where
 	async fn get_proof_height(&self, block_height: Height) -> Height {
 		block_height
 	}
-
-	async fn handle_error(&mut self, error: &anyhow::Error) -> Result<(), anyhow::Error> {
-		let err_str = if let Some(rpc_err) = error.downcast_ref::<Error>() {
-			match rpc_err {
-				Error::RpcError(s) => s.clone(),
-				_ => "".to_string(),
-			}
-		} else {
-			error.to_string()
-		};
-		log::debug!(target: "hyperspace", "Handling error: {err_str}");
-
-		if err_str.contains("MaxSlotsExceeded") {
-			self.common_state.rpc_call_delay = self.common_state.rpc_call_delay * 2;
-		} else if err_str.contains("RestartNeeded") || err_str.contains("restart required") {
-			self.reconnect().await?;
-			self.common_state.rpc_call_delay = self.common_state.rpc_call_delay * 2;
-		}
-
-		Ok(())
-	}
-
-	async fn reconnect(&mut self) -> anyhow::Result<()> {
-		let relay_ws_client = Arc::new(
-			WsClientBuilder::default()
-				.build(&self.relay_chain_rpc_url)
-				.await
-				.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
-		);
-		let para_ws_client = Arc::new(
-			WsClientBuilder::default()
-				.build(&self.parachain_rpc_url)
-				.await
-				.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
-		);
-
-		let para_client = subxt::OnlineClient::from_rpc_client(para_ws_client.clone()).await?;
-		let relay_client = subxt::OnlineClient::from_rpc_client(relay_ws_client.clone()).await?;
-
-		self.relay_ws_client = relay_ws_client;
-		self.para_ws_client = para_ws_client;
-		self.relay_client = relay_client;
-		self.para_client = para_client;
-
-		log::info!(target: "hyperspace", "Reconnected to relay chain and parachain");
-
-		Ok(())
-	}
-
-	fn common_state(&self) -> &CommonClientState {
-		&self.common_state
-	}
-
-	fn common_state_mut(&mut self) -> &mut CommonClientState {
-		&mut self.common_state
-	}
 }
 
 #[async_trait::async_trait]
@@ -402,11 +607,11 @@ where
 	<T as subxt::Config>::AccountId: Send + Sync,
 	<T as subxt::Config>::Address: Send + Sync,
 {
-	async fn check_for_misbehaviour<C: Chain>(
+	async fn detect_misbehaviour<C: Chain>(
 		&self,
 		counterparty: &C,
 		client_message: AnyClientMessage,
-	) -> Result<(), anyhow::Error> {
+	) -> Result<Option<Any>, anyhow::Error> {
 		let client_message = client_message.unpack_recursive_into();
 		match client_message {
 			AnyClientMessage::Grandpa(ClientMessage::Header(header)) => {
@@ -501,19 +706,18 @@ where
 						second_finality_proof: trusted_finality_proof,
 					});
 
-					counterparty
-						.submit(vec![MsgUpdateAnyClient::<LocalClientTypes>::new(
+					return Ok(Some(
+						MsgUpdateAnyClient::<LocalClientTypes>::new(
 							self.client_id(),
-							AnyClientMessage::Grandpa(misbehaviour.clone()),
+							AnyClientMessage::Grandpa(misbehaviour),
 							counterparty.account_id(),
 						)
-						.to_any()])
-						.map_err(|e| anyhow!("Failed to submit misbehaviour report: {:?}", e))
-						.await?;
+						.to_any(),
+					))
 				}
 			},
 			_ => {},
 		}
-		Ok(())
+		Ok(None)
 	}
 }
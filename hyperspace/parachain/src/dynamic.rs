@@ -0,0 +1,158 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage queries and extrinsics of the parachain built with subxt's dynamic API. They are encoded
+//! and decoded using the runtime metadata fetched when connecting to the parachain, rather than the
+//! statically generated bindings of one runtime, so that any runtime with pallet-ibc is supported.
+
+use crate::Error;
+use ibc_proto::google::protobuf::Any;
+use pallet_ibc::{MultiAddress, Timeout, TransferParams};
+use pallet_ibc_ping::SendPingParams;
+use subxt::{
+	dynamic::{DynamicAddress, Value},
+	metadata::Metadata,
+	tx::DynamicPayload,
+	OnlineClient,
+};
+
+/// Default name of the IBC pallet in the parachain runtime
+pub const IBC_PALLET: &str = "Ibc";
+
+/// Storage entries the parachain client reads, as `(pallet, entry)`
+const REQUIRED_STORAGE: &[(&str, &str)] = &[("Timestamp", "Now")];
+
+/// Calls of the IBC pallet the parachain client submits
+const REQUIRED_IBC_CALLS: &[&str] = &["deliver", "transfer"];
+
+/// Checks that the runtime described by `metadata` has the storage entries used by the parachain
+/// client, and that its IBC pallet named `ibc_pallet` has the calls it submits.
+pub fn check_metadata(metadata: &Metadata, ibc_pallet: &str) -> Result<(), Error> {
+	for (pallet_name, entry_name) in REQUIRED_STORAGE {
+		let pallet = metadata
+			.pallet_by_name(pallet_name)
+			.ok_or_else(|| Error::PalletNotFound(pallet_name.to_string()))?;
+		pallet
+			.storage()
+			.and_then(|storage| storage.entry_by_name(entry_name))
+			.ok_or_else(|| {
+				Error::Custom(format!("Storage entry '{pallet_name}.{entry_name}' not found"))
+			})?;
+	}
+	let pallet = metadata
+		.pallet_by_name(ibc_pallet)
+		.ok_or_else(|| Error::PalletNotFound(ibc_pallet.to_string()))?;
+	for call_name in REQUIRED_IBC_CALLS {
+		pallet.call_variant_by_name(call_name).ok_or(Error::CallNotFound(call_name))?;
+	}
+	Ok(())
+}
+
+/// Address of the timestamp of a block, in milliseconds.
+pub fn timestamp_now() -> DynamicAddress<Value> {
+	subxt::dynamic::storage("Timestamp", "Now", vec![])
+}
+
+/// Fetches the timestamp of the block `block_hash`, in milliseconds.
+pub async fn fetch_timestamp<T: subxt::Config>(
+	client: &OnlineClient<T>,
+	block_hash: T::Hash,
+) -> Result<Option<u64>, Error> {
+	client
+		.storage()
+		.at(block_hash)
+		.fetch(&timestamp_now())
+		.await?
+		.map(|timestamp| timestamp.as_type::<u64>())
+		.transpose()
+		.map_err(|e| Error::Custom(format!("Failed to decode timestamp: {e}")))
+}
+
+fn any_value(message: Any) -> Value {
+	Value::named_composite([
+		("type_url", Value::string(message.type_url)),
+		("value", Value::from_bytes(message.value)),
+	])
+}
+
+/// `deliver` extrinsic of `messages` to the IBC pallet named `ibc_pallet`.
+pub fn ibc_deliver(ibc_pallet: &str, messages: Vec<Any>) -> DynamicPayload {
+	subxt::dynamic::tx(
+		ibc_pallet,
+		"deliver",
+		vec![Value::unnamed_composite(messages.into_iter().map(any_value))],
+	)
+}
+
+/// `update_client_unsigned` extrinsic of a `MsgUpdateClient` to the IBC pallet named
+/// `ibc_pallet`.
+pub fn ibc_update_client_unsigned(ibc_pallet: &str, message: Any) -> DynamicPayload {
+	subxt::dynamic::tx(ibc_pallet, "update_client_unsigned", vec![any_value(message)])
+}
+
+fn timeout_value(timeout: Timeout) -> Value {
+	let option = |value: Option<u64>| match value {
+		Some(value) => Value::unnamed_variant("Some", [Value::u128(value as u128)]),
+		None => Value::unnamed_variant("None", []),
+	};
+	let (variant, timestamp, height) = match timeout {
+		Timeout::Offset { timestamp, height } => ("Offset", timestamp, height),
+		Timeout::Absolute { timestamp, height } => ("Absolute", timestamp, height),
+	};
+	Value::named_variant(variant, [("timestamp", option(timestamp)), ("height", option(height))])
+}
+
+/// `transfer` extrinsic to the IBC pallet named `ibc_pallet` of `amount` of `asset_id` with the
+/// optional ICS-20 `memo`. The account ids of the recipient can be of any length, e.g. 20 byte
+/// Ethereum addresses.
+pub fn ibc_transfer<AccountId: AsRef<[u8]>>(
+	ibc_pallet: &str,
+	params: TransferParams<AccountId>,
+	asset_id: u128,
+	amount: u128,
+	memo: Option<String>,
+) -> DynamicPayload {
+	let to = match params.to {
+		MultiAddress::Id(account) => Value::unnamed_variant("Id", [Value::from_bytes(account)]),
+		MultiAddress::Raw(bytes) => Value::unnamed_variant("Raw", [Value::from_bytes(bytes)]),
+	};
+	let params = Value::named_composite([
+		("to", to),
+		("source_channel", Value::u128(params.source_channel as u128)),
+		("timeout", timeout_value(params.timeout)),
+	]);
+	let memo = match memo {
+		Some(memo) => Value::unnamed_variant("Some", [Value::string(memo)]),
+		None => Value::unnamed_variant("None", []),
+	};
+	subxt::dynamic::tx(
+		ibc_pallet,
+		"transfer",
+		vec![params, Value::u128(asset_id), Value::u128(amount), memo],
+	)
+}
+
+/// `IbcPing::send_ping` extrinsic of `params`.
+pub fn ibc_ping_send_ping(params: SendPingParams) -> DynamicPayload {
+	subxt::dynamic::tx(
+		"IbcPing",
+		"send_ping",
+		vec![Value::named_composite([
+			("data", Value::from_bytes(params.data)),
+			("timeout_height_offset", Value::u128(params.timeout_height_offset as u128)),
+			("timeout_timestamp_offset", Value::u128(params.timeout_timestamp_offset as u128)),
+			("channel_id", Value::u128(params.channel_id as u128)),
+		])],
+	)
+}
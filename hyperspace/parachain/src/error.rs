@@ -30,7 +30,7 @@ pub enum Error {
 	Codec(#[from] codec::Error),
 	/// Update pallet name in call definition
 	#[error("Pallet '{0}' not found in metadata, update static definition of call")]
-	PalletNotFound(&'static str),
+	PalletNotFound(String),
 	/// Call not found, update function name in call definition
 	#[error("Call '{0}' not found in metadata, update static definition of call")]
 	CallNotFound(&'static str),
@@ -80,6 +80,9 @@ pub enum Error {
 	MetadataError(#[from] MetadataError),
 	#[error("Jsonrpsee error: {0}")]
 	JosnrpseeError(#[from] jsonrpsee::core::Error),
+	/// The extrinsic of a batch of messages exceeds the block limits
+	#[error("Extrinsic of weight {weight} and length {length} exceeds the limits of weight {max_weight} and length {max_length}")]
+	ExtrinsicTooLarge { weight: u64, max_weight: u64, length: usize, max_length: u32 },
 }
 
 impl From<String> for Error {
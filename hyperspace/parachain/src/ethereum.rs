@@ -0,0 +1,136 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ethereum-style accounts of EVM-flavoured parachains, such as Moonbeam: accounts are the 20 byte
+//! addresses of ECDSA keys and signatures are made over the keccak-256 hash of the payload. A
+//! [`light_client_common::config::Config`] of such a runtime uses [`EthereumSignature`] as its
+//! signature and [`AccountId20`] as its account id and address.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use serde::{Serialize, Serializer};
+use sp_core::{ecdsa, hashing::keccak_256};
+use sp_runtime::{
+	traits::{Convert, IdentifyAccount, Lazy, Verify},
+	MultiSignature, MultiSigner,
+};
+use std::fmt;
+
+/// A 20 byte account id, the Ethereum address of an ECDSA key.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Encode, Decode, MaxEncodedLen)]
+pub struct AccountId20(pub [u8; 20]);
+
+impl AccountId20 {
+	/// Returns the account of the ECDSA public key `public`: the last 20 bytes of the keccak-256
+	/// hash of the uncompressed key.
+	pub fn from_public(public: &ecdsa::Public) -> Self {
+		let address = beefy_mmr::BeefyEcdsaToEthereum::convert(
+			beefy_primitives::crypto::AuthorityId::from(public.clone()),
+		);
+		let mut account = [0u8; 20];
+		account.copy_from_slice(&address);
+		Self(account)
+	}
+}
+
+impl fmt::Display for AccountId20 {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "0x{}", hex::encode(self.0))
+	}
+}
+
+impl fmt::Debug for AccountId20 {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(self, f)
+	}
+}
+
+impl std::str::FromStr for AccountId20 {
+	type Err = hex::FromHexError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut account = [0u8; 20];
+		hex::decode_to_slice(s.trim_start_matches("0x"), &mut account)?;
+		Ok(Self(account))
+	}
+}
+
+impl AsRef<[u8]> for AccountId20 {
+	fn as_ref(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl Serialize for AccountId20 {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.to_string())
+	}
+}
+
+/// The signer of an [`EthereumSignature`], identified by its [`AccountId20`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct EthereumSigner(pub AccountId20);
+
+impl IdentifyAccount for EthereumSigner {
+	type AccountId = AccountId20;
+
+	fn into_account(self) -> AccountId20 {
+		self.0
+	}
+}
+
+impl From<ecdsa::Public> for EthereumSigner {
+	fn from(public: ecdsa::Public) -> Self {
+		Self(AccountId20::from_public(&public))
+	}
+}
+
+impl From<MultiSigner> for EthereumSigner {
+	fn from(signer: MultiSigner) -> Self {
+		match signer {
+			MultiSigner::Ecdsa(public) => public.into(),
+			_ => panic!("Ethereum accounts can only be derived from ECDSA keys"),
+		}
+	}
+}
+
+/// A recoverable ECDSA signature over the keccak-256 hash of the payload.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct EthereumSignature(pub ecdsa::Signature);
+
+impl Verify for EthereumSignature {
+	type Signer = EthereumSigner;
+
+	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &AccountId20) -> bool {
+		let message = keccak_256(msg.get());
+		match sp_io::crypto::secp256k1_ecdsa_recover(&self.0 .0, &message) {
+			Ok(public) => keccak_256(&public)[12..] == signer.0,
+			Err(_) => false,
+		}
+	}
+}
+
+impl From<ecdsa::Signature> for EthereumSignature {
+	fn from(signature: ecdsa::Signature) -> Self {
+		Self(signature)
+	}
+}
+
+impl From<MultiSignature> for EthereumSignature {
+	fn from(signature: MultiSignature) -> Self {
+		match signature {
+			MultiSignature::Ecdsa(signature) => signature.into(),
+			_ => panic!("Ethereum signatures can only be made with ECDSA keys"),
+		}
+	}
+}
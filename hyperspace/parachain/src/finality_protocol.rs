@@ -14,7 +14,7 @@
 
 //! Light client protocols for parachains.
 
-use crate::{error::Error, ParachainClient};
+use crate::{error::Error, utils::query_events_paginated, ParachainClient};
 use anyhow::anyhow;
 use beefy_light_client_primitives::{ClientState as BeefyPrimitivesClientState, NodesUtils};
 use codec::{Decode, Encode};
@@ -31,7 +31,7 @@ use ibc::{
 	Height,
 };
 use ibc_proto::google::protobuf::Any;
-use ibc_rpc::{BlockNumberOrHash, IbcApiClient};
+use ibc_rpc::BlockNumberOrHash;
 use ics10_grandpa::client_message::{ClientMessage, Header as GrandpaHeader};
 use ics11_beefy::client_message::{
 	BeefyHeader, ClientMessage as BeefyClientMessage, ParachainHeadersWithProof,
@@ -39,7 +39,7 @@ use ics11_beefy::client_message::{
 use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState};
 use primitives::{
 	filter_events_by_ids, mock::LocalClientTypes, query_maximum_height_for_timeout_proofs, Chain,
-	IbcProvider, KeyProvider, UpdateType,
+	FinalitySource, IbcQuerier, KeyProvider, UpdateType,
 };
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -50,7 +50,7 @@ use sp_runtime::{
 	MultiSignature, MultiSigner,
 };
 use std::{
-	collections::{BTreeMap, BTreeSet, HashMap},
+	collections::{BTreeMap, BTreeSet},
 	fmt::{Debug, Display},
 	time::Duration,
 };
@@ -73,11 +73,14 @@ pub enum FinalityProtocol {
 /// Finality event for parachains
 #[derive(Decode, Encode, Debug)]
 pub enum FinalityEvent {
-	Grandpa(
-		grandpa_light_client_primitives::justification::GrandpaJustification<
+	Grandpa {
+		justification: grandpa_light_client_primitives::justification::GrandpaJustification<
 			polkadot_core_primitives::Header,
 		>,
-	),
+		/// Whether the justified relay chain block enacts an authority set change, in which case
+		/// the event is relayed even if a later one is already pending
+		authority_set_changed: bool,
+	},
 	Beefy(beefy_primitives::SignedCommitment<u32, beefy_primitives::crypto::Signature>),
 }
 
@@ -261,12 +264,10 @@ where
 	};
 
 	// block_number => events
-	let events: HashMap<String, Vec<IbcEvent>> = IbcApiClient::<
-		u32,
-		H256,
-		<T as light_client_common::config::Config>::AssetId,
-	>::query_events(
-		&*source.para_ws_client, finalized_block_numbers
+	let events = query_events_paginated::<<T as light_client_common::config::Config>::AssetId>(
+		&source.para_ws_client,
+		finalized_block_numbers,
+		source.pallet_prefix(),
 	)
 	.await?;
 
@@ -456,7 +457,7 @@ where
 	<T as subxt::Config>::Address: Send + Sync,
 {
 	let latest_justification = match finality_event {
-		FinalityEvent::Grandpa(justification) => justification,
+		FinalityEvent::Grandpa { justification, .. } => justification,
 		_ => panic!("Expected grandpa finality event"),
 	};
 	let client_id = source.client_id();
@@ -576,12 +577,10 @@ where
 	};
 
 	// block_number => events
-	let events: HashMap<String, Vec<IbcEvent>> = IbcApiClient::<
-		u32,
-		H256,
-		<T as light_client_common::config::Config>::AssetId,
-	>::query_events(
-		&*source.para_ws_client, finalized_block_numbers
+	let events = query_events_paginated::<<T as light_client_common::config::Config>::AssetId>(
+		&source.para_ws_client,
+		finalized_block_numbers,
+		source.pallet_prefix(),
 	)
 	.await?;
 
@@ -13,19 +13,45 @@
 // limitations under the License.
 
 use super::ParachainClient;
-use primitives::KeyProvider;
-use sp_core::crypto::Ss58Codec;
-use sp_runtime::traits::IdentifyAccount;
+use anyhow::anyhow;
+use primitives::{signing::load_signing_keys, KeyProvider, PayloadSignature};
 use std::str::FromStr;
 
 impl<T: light_client_common::config::Config> KeyProvider for ParachainClient<T> {
 	fn account_id(&self) -> ibc::signer::Signer {
-		let hex_string = self
-			.public_key
-			.clone()
-			.into_account()
-			.to_ss58check_with_version(self.ss58_version);
+		let address = self.account_address(&self.public_key);
 
-		ibc::signer::Signer::from_str(&hex_string).expect("Account Id should be valid")
+		ibc::signer::Signer::from_str(&address).expect("Account Id should be valid")
+	}
+
+	fn sign_payload(&self, payload: &[u8]) -> Result<PayloadSignature, anyhow::Error> {
+		let signer = &self.signers[0];
+		let signature = signer.sign(payload)?;
+		Ok(PayloadSignature { public_key: signer.public_key().to_vec(), signature })
+	}
+
+	/// Blocks the calling thread while the keys are loaded, which must be the multi-threaded tokio
+	/// runtime. Raw keys of the config can't be reloaded.
+	fn reload_keys(&mut self) -> Result<Vec<String>, anyhow::Error> {
+		let signer = self
+			.signer
+			.as_ref()
+			.ok_or_else(|| anyhow!("The raw relayer keys of {} can't be reloaded", self.name))?;
+		let keys = tokio::task::block_in_place(|| {
+			tokio::runtime::Handle::current().block_on(load_signing_keys(
+				Some(signer),
+				"",
+				&[],
+				self.key_type.scheme(),
+			))
+		})?;
+		let (signing_keys, signers) = self.key_type.signers(keys)?;
+		self.public_key = signing_keys[0].clone();
+		self.common_state.keys = self.common_state.keys.with_key_count(signing_keys.len());
+		self.signing_keys = signing_keys;
+		self.signers = signers;
+		let accounts = self.signing_keys.iter().map(|key| self.account_address(key)).collect();
+		log::info!(target: "hyperspace_parachain", "Reloaded the relayer keys of {}", self.name);
+		Ok(accounts)
 	}
 }
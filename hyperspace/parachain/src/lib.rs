@@ -16,14 +16,17 @@
 
 use std::{
 	collections::{BTreeMap, HashSet},
-	path::PathBuf,
+	fmt::Debug,
+	future::Future,
 	str::FromStr,
 	sync::{Arc, Mutex},
 	time::Duration,
 };
 
 pub mod chain;
+pub mod dynamic;
 pub mod error;
+pub mod ethereum;
 pub mod key_provider;
 pub mod parachain;
 pub mod provider;
@@ -40,17 +43,24 @@ use frame_support::Serialize;
 use serde::Deserialize;
 
 use crate::{
-	finality_protocol::FinalityProtocol, signer::ExtrinsicSigner, utils::fetch_max_extrinsic_weight,
+	finality_protocol::FinalityProtocol,
+	signer::{ExtrinsicSigner, KeystoreSigner},
+	utils::{
+		connect_to_healthy_node, failover_order, fetch_max_extrinsic_length,
+		fetch_max_extrinsic_weight, is_pruned_state_error,
+	},
 };
 use beefy_light_client_primitives::{ClientState, MmrUpdateProof};
 use beefy_prover::Prover;
-use codec::Decode;
+use codec::{Decode, Encode};
 use grandpa_light_client_primitives::ParachainHeaderProofs;
 use grandpa_prover::GrandpaProver;
 use ibc::{
+	applications::transfer::{Amount, PrefixedCoin, PrefixedDenom},
 	core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 	timestamp::Timestamp,
 };
+use ibc_proto::google::protobuf::Any;
 use ics10_grandpa::{
 	client_state::ClientState as GrandpaClientState,
 	consensus_state::ConsensusState as GrandpaConsensusState,
@@ -60,12 +70,21 @@ use ics11_beefy::{
 	consensus_state::ConsensusState as BeefyConsensusState,
 };
 use jsonrpsee_ws_client::WsClientBuilder;
-use light_client_common::config::{AsInner, RuntimeStorage};
+use light_client_common::{
+	config::{AsInner, RuntimeStorage},
+	RelayChain,
+};
 use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState, HostFunctionsManager};
 use pallet_mmr_primitives::Proof;
-use primitives::{CommonClientState, KeyProvider};
+use primitives::{
+	signing::{self, check_signer_config, load_signing_keys, SignerConfig, SigningKey},
+	AdaptiveUpdateScheduler, CommonClientConfig, CommonClientState, KeyProvider, KeyRotation,
+	PendingMessages, RelayedSequences,
+};
 use sc_keystore::LocalKeystore;
-use sp_core::{ecdsa, ed25519, sr25519, Bytes, Pair, H256};
+use sp_core::{
+	blake2_128, crypto::Ss58Codec, ecdsa, ed25519, sr25519, twox_128, Bytes, Pair, H256,
+};
 use sp_keystore::KeystorePtr;
 use sp_runtime::{
 	traits::{IdentifyAccount, One, Verify},
@@ -73,10 +92,15 @@ use sp_runtime::{
 };
 use ss58_registry::Ss58AddressFormat;
 use subxt::{
-	config::{Header as HeaderT, Header},
+	config::{
+		extrinsic_params::{BaseExtrinsicParamsBuilder, Era},
+		ExtrinsicParams, Header as HeaderT, Header,
+	},
 	tx::TxPayload,
 };
 use tokio::sync::Mutex as AsyncMutex;
+use transaction_payment_rpc::TransactionPaymentApiClient;
+use transaction_payment_runtime_api::RuntimeDispatchInfo;
 
 /// Implements the [`crate::Chain`] trait for parachains.
 /// This is responsible for:
@@ -91,6 +115,14 @@ pub struct ParachainClient<T: light_client_common::config::Config> {
 	pub parachain_rpc_url: String,
 	/// rpc url for relay chain
 	pub relay_chain_rpc_url: String,
+	/// Parachain rpc urls failed over to when the active one is unavailable
+	pub fallback_parachain_rpc_urls: Vec<String>,
+	/// Relay chain rpc urls failed over to when the active one is unavailable
+	pub fallback_relay_chain_rpc_urls: Vec<String>,
+	/// rpc url of the parachain node the client is connected to
+	pub active_parachain_rpc_url: String,
+	/// rpc url of the relay chain node the client is connected to
+	pub active_relay_chain_rpc_url: String,
 	/// Relay chain rpc client
 	pub relay_client: subxt::OnlineClient<T>,
 	/// Parachain rpc client
@@ -99,6 +131,19 @@ pub struct ParachainClient<T: light_client_common::config::Config> {
 	pub relay_ws_client: Arc<jsonrpsee_ws_client::WsClient>,
 	/// Parachain ws client
 	pub para_ws_client: Arc<jsonrpsee_ws_client::WsClient>,
+	/// rpc url for the parachain archive node
+	pub archive_parachain_rpc_url: Option<String>,
+	/// Parachain archive node ws client, used for queries at pruned heights
+	pub archive_ws_client: Option<Arc<jsonrpsee_ws_client::WsClient>>,
+	/// Submit lone client updates as unsigned extrinsics when possible
+	pub unsigned_client_updates: bool,
+	/// Name of the IBC pallet in the parachain runtime, which extrinsics are submitted to
+	pub pallet_name: String,
+	/// Only stream the IBC events and blocks of finalized blocks
+	pub finalized_events: bool,
+	/// The chain is a standalone chain finalized by its own GRANDPA authorities, whose clients
+	/// stand in for the relay chain clients
+	pub standalone: bool,
 	/// Parachain Id
 	pub para_id: u32,
 	/// Light client id on counterparty chain
@@ -111,37 +156,135 @@ pub struct ParachainClient<T: light_client_common::config::Config> {
 	pub commitment_prefix: Vec<u8>,
 	/// Public key for relayer on chain
 	pub public_key: MultiSigner,
-	/// Reference to keystore
-	pub key_store: KeystorePtr,
-	/// Key type Id
-	pub key_type_id: KeyTypeId,
+	/// Public keys of all the relayer keys transactions are signed with, in the order of the
+	/// [`primitives::KeyRotation`]. The first one is `public_key`.
+	pub signing_keys: Vec<MultiSigner>,
+	/// Signers of the `signing_keys`
+	pub signers: Vec<Arc<dyn signing::Signer>>,
+	/// Backend the relayer keys were loaded from, if they weren't raw keys of the config
+	pub signer: Option<SignerConfig>,
 	/// used for encoding relayer address.
 	pub ss58_version: Ss58AddressFormat,
+	/// Signature scheme of the relayer keys
+	pub key_type: KeyType,
 	/// the maximum extrinsic weight allowed by this client
 	pub max_extrinsic_weight: u64,
+	/// the maximum length, in bytes, of a normal extrinsic
+	pub max_extrinsic_length: u32,
 	/// Finality protocol to use, eg Beefy, Grandpa
 	pub finality_protocol: FinalityProtocol,
+	/// Asset transaction fees are paid in, if it isn't the native token of the parachain
+	pub fee_asset: Option<AssetConfig>,
+	/// Assets whose balances are reported along with the queried one
+	pub assets: Vec<AssetConfig>,
 	/// Common relayer data
 	pub common_state: CommonClientState,
 }
 
-enum KeyType {
+/// Weight, fee and length of an extrinsic, estimated before it's submitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtrinsicEstimate {
+	/// Reference time weight of the extrinsic
+	pub weight: u64,
+	/// Fee of the extrinsic, excluding the tip
+	pub partial_fee: u128,
+	/// Length of the signed extrinsic, in bytes
+	pub length: usize,
+}
+
+/// Signature scheme of the relayer keys, which also determines the format of their accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
 	Sr25519,
 	Ed25519,
 	Ecdsa,
+	/// ECDSA keys of 20 byte Ethereum accounts, signing the keccak-256 hash of payloads. See
+	/// [`ethereum`].
+	Ethereum,
 }
 
 pub const DEFAULT_RPC_CALL_DELAY: Duration = Duration::from_millis(10);
 pub const WAIT_FOR_IN_BLOCK_TIMEOUT: Duration = Duration::from_secs(60 * 1);
+/// Number of packets relayed per batch when `max_packets_to_process` isn't configured
+pub const DEFAULT_MAX_PACKETS_TO_PROCESS: usize = 100;
 
 impl KeyType {
 	pub fn to_key_type_id(&self) -> KeyTypeId {
 		match self {
 			KeyType::Sr25519 => KeyTypeId(sr25519::CRYPTO_ID.0),
 			KeyType::Ed25519 => KeyTypeId(ed25519::CRYPTO_ID.0),
-			KeyType::Ecdsa => KeyTypeId(ecdsa::CRYPTO_ID.0),
+			KeyType::Ecdsa | KeyType::Ethereum => KeyTypeId(ecdsa::CRYPTO_ID.0),
+		}
+	}
+
+	/// Name of the signature scheme, as sent to remote signers.
+	fn scheme(&self) -> &'static str {
+		match self {
+			KeyType::Sr25519 => "sr25519",
+			KeyType::Ed25519 => "ed25519",
+			KeyType::Ecdsa => "ecdsa",
+			KeyType::Ethereum => "ethereum",
 		}
 	}
+
+	/// Returns the public key of the raw `private_key`.
+	fn public_key(&self, private_key: &str) -> Result<MultiSigner, Error> {
+		let public_key = match self {
+			KeyType::Sr25519 => sr25519::Pair::from_string_with_seed(private_key, None)
+				.map_err(|_| Error::Custom("invalid key".to_owned()))?
+				.0
+				.public()
+				.into(),
+			KeyType::Ed25519 => ed25519::Pair::from_string_with_seed(private_key, None)
+				.map_err(|_| Error::Custom("invalid key".to_owned()))?
+				.0
+				.public()
+				.into(),
+			KeyType::Ecdsa | KeyType::Ethereum =>
+				ecdsa::Pair::from_string_with_seed(private_key, None)
+					.map_err(|_| Error::Custom("invalid key".to_owned()))?
+					.0
+					.public()
+					.into(),
+		};
+		Ok(public_key)
+	}
+
+	/// Returns the public keys and signers of `keys`. Raw secrets are only held in memory.
+	fn signers(
+		&self,
+		keys: Vec<SigningKey>,
+	) -> Result<(Vec<MultiSigner>, Vec<Arc<dyn signing::Signer>>), Error> {
+		let key_store: KeystorePtr = Arc::new(LocalKeystore::in_memory());
+		let key_type_id = self.to_key_type_id();
+		let mut signing_keys = vec![];
+		let mut signers = vec![];
+		for key in keys {
+			let signer: Arc<dyn signing::Signer> = match key {
+				SigningKey::Secret(private_key) => {
+					let public_key = self.public_key(&private_key)?;
+					key_store.insert(key_type_id, &private_key, public_key.as_ref()).unwrap();
+					assert!(key_store.has_keys(&[(public_key.as_ref().to_vec(), key_type_id)]));
+					Arc::new(KeystoreSigner::new(key_store.clone(), *self, public_key))
+				},
+				SigningKey::Remote(signer) => Arc::new(signer),
+			};
+			signing_keys.push(self.decode_public_key(signer.public_key())?);
+			signers.push(signer);
+		}
+		Ok((signing_keys, signers))
+	}
+
+	/// Decodes a raw public key of this type.
+	fn decode_public_key(&self, public_key: &[u8]) -> Result<MultiSigner, Error> {
+		let public_key = match self {
+			KeyType::Sr25519 => sr25519::Public::try_from(public_key).map(MultiSigner::from),
+			KeyType::Ed25519 => ed25519::Public::try_from(public_key).map(MultiSigner::from),
+			KeyType::Ecdsa | KeyType::Ethereum =>
+				ecdsa::Public::try_from(public_key).map(MultiSigner::from),
+		};
+		public_key.map_err(|_| Error::Custom("invalid public key".to_owned()))
+	}
 }
 
 impl FromStr for KeyType {
@@ -152,8 +295,35 @@ impl FromStr for KeyType {
 			"sr25519" => Ok(KeyType::Sr25519),
 			"ed25519" => Ok(KeyType::Ed25519),
 			"ecdsa" => Ok(KeyType::Ecdsa),
-			_ => Err(Error::Custom("Invalid key type".to_string())),
+			"ethereum" => Ok(KeyType::Ethereum),
+			_ => Err(Error::Custom(format!(
+				"Invalid key type {s}, expected sr25519, ed25519, ecdsa or ethereum"
+			))),
+		}
+	}
+}
+
+/// An asset of the parachain the relayer holds.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AssetConfig {
+	/// Local asset id
+	pub asset_id: u128,
+	/// Denomination of the asset
+	pub denom: String,
+	/// Number of decimals of the asset, used to display its amounts
+	#[serde(default)]
+	pub decimals: u8,
+}
+
+impl AssetConfig {
+	/// Formats `amount`, in the smallest unit of the asset, as a decimal amount of its denom.
+	pub fn format_amount(&self, amount: u128) -> String {
+		if self.decimals == 0 {
+			return format!("{amount}{}", self.denom)
 		}
+		let unit = 10u128.saturating_pow(self.decimals as u32);
+		let width = self.decimals as usize;
+		format!("{}.{:0width$}{}", amount / unit, amount % unit, self.denom)
 	}
 }
 
@@ -166,27 +336,103 @@ pub struct ParachainClientConfig {
 	pub para_id: u32,
 	/// rpc url for parachain
 	pub parachain_rpc_url: String,
-	/// rpc url for relay chain
+	/// rpc url for relay chain, unused by standalone chains
+	#[serde(default)]
 	pub relay_chain_rpc_url: String,
+	/// Parachain rpc urls connected to, in order, when `parachain_rpc_url` is unreachable or its
+	/// node is syncing.
+	#[serde(default)]
+	pub fallback_parachain_rpc_urls: Vec<String>,
+	/// Relay chain rpc urls connected to, in order, when `relay_chain_rpc_url` is unreachable or
+	/// its node is syncing.
+	#[serde(default)]
+	pub fallback_relay_chain_rpc_urls: Vec<String>,
+	/// rpc url for a parachain archive node. Proofs at heights that were pruned on the
+	/// `parachain_rpc_url` node are queried from it.
+	#[serde(default)]
+	pub archive_parachain_rpc_url: Option<String>,
+	/// Submit lone client updates as unsigned `update_client_unsigned` extrinsics, which don't
+	/// pay fees. Requires unsigned client updates to be enabled on the parachain; updates that are
+	/// rejected (e.g. because of its rate limit) are sent with a signed `deliver` instead.
+	#[serde(default)]
+	pub unsigned_client_updates: bool,
+	/// Name of the `pallet_ibc` instance in the parachain runtime, e.g. `"Ibc"`. Extrinsics are
+	/// submitted to it, so it must be the instance whose `commitment_prefix` is configured.
+	#[serde(default = "default_pallet_name")]
+	pub pallet_name: String,
+	/// Stream the IBC events (and the blocks of the test provider) of finalized blocks only,
+	/// instead of every imported block. Events of imported blocks are seen earlier, but may come
+	/// from blocks that are reorged out before finality.
+	#[serde(default)]
+	pub finalized_events: bool,
+	/// The chain is a standalone chain finalized by its own GRANDPA authorities instead of a
+	/// parachain. Its own nodes are used in place of the relay chain ones, `para_id` is the
+	/// revision number of its heights and the finality protocol must be `Grandpa`.
+	#[serde(default)]
+	pub standalone: bool,
 	/// Light client id on counterparty chain
 	pub client_id: Option<ClientId>,
 	/// Connection Id
 	pub connection_id: Option<ConnectionId>,
 	/// Commitment prefix
 	pub commitment_prefix: Bytes,
-	/// Raw private key for signing transactions
+	/// Raw private key for signing transactions, unless `signer` is set
+	#[serde(default)]
 	pub private_key: String,
+	/// Raw private keys of further funded accounts, of the same `key_type`. Transactions are
+	/// signed with `private_key` and these keys in turn.
+	#[serde(default)]
+	pub additional_private_keys: Vec<String>,
+	/// Backend holding the relayer keys, instead of the raw `private_key` and
+	/// `additional_private_keys`
+	#[serde(default)]
+	pub signer: Option<SignerConfig>,
 	/// used for encoding relayer address.
 	pub ss58_version: u8,
 	/// Channels cleared for packet relay
 	pub channel_whitelist: Vec<(ChannelId, PortId)>,
 	/// Finality protocol
 	pub finality_protocol: FinalityProtocol,
+	/// Asset transaction fees are paid in, whose balance is reported for the relayer keys.
+	/// Defaults to the native token of the parachain, named after its `tokenSymbol` property.
+	#[serde(default)]
+	pub fee_asset: Option<AssetConfig>,
+	/// Assets whose balances are reported along with the queried one, such as the whitelisted
+	/// ICS-20 assets. Transfers of these denoms are sent with the configured asset ids.
+	#[serde(default)]
+	pub assets: Vec<AssetConfig>,
 	/// Digital signature scheme
 	pub key_type: String,
 	/// All the client states and headers will be wrapped in WASM ones using the WASM code ID.
 	#[serde(default)]
 	pub wasm_code_id: Option<String>,
+	/// Common client config
+	#[serde(flatten)]
+	pub common: CommonClientConfig,
+}
+
+fn default_pallet_name() -> String {
+	dynamic::IBC_PALLET.to_string()
+}
+
+impl ParachainClientConfig {
+	/// Checks the parameters, key type and relayer keys of the config without connecting to the
+	/// chain.
+	pub fn validate(&self) -> Result<(), Error> {
+		check_security_params(self)?;
+		if self.standalone && !matches!(self.finality_protocol, FinalityProtocol::Grandpa) {
+			return Err(Error::Custom(format!(
+				"{} is a standalone chain, which can only be finalized by Grandpa",
+				self.name
+			)))
+		}
+		if !self.standalone && self.relay_chain_rpc_url.is_empty() {
+			return Err(Error::Custom(format!("relay_chain_rpc_url of {} is not set", self.name)))
+		}
+		KeyType::from_str(&self.key_type)?;
+		check_signer_config(self.signer.as_ref(), &self.private_key, &self.additional_private_keys)
+			.map_err(|e| Error::Custom(format!("Invalid keys of {}: {e}", self.name)))
+	}
 }
 
 impl<T> ParachainClient<T>
@@ -195,57 +441,66 @@ where
 {
 	/// Initializes a [`ParachainClient`] given a [`ParachainConfig`]
 	pub async fn new(config: ParachainClientConfig) -> Result<Self, Error> {
-		let relay_ws_client = Arc::new(
-			WsClientBuilder::default()
-				.build(&config.relay_chain_rpc_url)
-				.await
-				.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
-		);
-		let para_ws_client = Arc::new(
-			WsClientBuilder::default()
-				.build(&config.parachain_rpc_url)
-				.await
-				.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
-		);
+		config.validate()?;
+		let (active_parachain_rpc_url, para_ws_client) = connect_to_healthy_node(&failover_order(
+			&config.parachain_rpc_url,
+			&config.parachain_rpc_url,
+			&config.fallback_parachain_rpc_urls,
+		))
+		.await?;
+		let (active_relay_chain_rpc_url, relay_ws_client) = if config.standalone {
+			(active_parachain_rpc_url.clone(), para_ws_client.clone())
+		} else {
+			connect_to_healthy_node(&failover_order(
+				&config.relay_chain_rpc_url,
+				&config.relay_chain_rpc_url,
+				&config.fallback_relay_chain_rpc_urls,
+			))
+			.await?
+		};
+
+		let archive_ws_client = match &config.archive_parachain_rpc_url {
+			Some(url) => Some(Arc::new(
+				WsClientBuilder::default()
+					.build(url)
+					.await
+					.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
+			)),
+			None => None,
+		};
 
 		let para_client = subxt::OnlineClient::from_rpc_client(para_ws_client.clone()).await?;
+		dynamic::check_metadata(&para_client.metadata(), &config.pallet_name)?;
 
-		let relay_client = subxt::OnlineClient::from_rpc_client(relay_ws_client.clone()).await?;
+		let relay_client = if config.standalone {
+			para_client.clone()
+		} else {
+			subxt::OnlineClient::from_rpc_client(relay_ws_client.clone()).await?
+		};
 
 		let max_extrinsic_weight = fetch_max_extrinsic_weight(&para_client).await?;
+		let max_extrinsic_length = fetch_max_extrinsic_length(&para_client).await?;
 
-		let temp_dir = PathBuf::from("/tmp/keystore");
-		let key_store: KeystorePtr = Arc::new(LocalKeystore::open(temp_dir, None).unwrap());
 		let key_type = KeyType::from_str(&config.key_type)?;
-		let key_type_id = key_type.to_key_type_id();
-
-		let public_key: MultiSigner = match key_type {
-			KeyType::Sr25519 => sr25519::Pair::from_string_with_seed(&config.private_key, None)
-				.map_err(|_| Error::Custom("invalid key".to_owned()))?
-				.0
-				.public()
-				.into(),
-			KeyType::Ed25519 => ed25519::Pair::from_string_with_seed(&config.private_key, None)
-				.map_err(|_| Error::Custom("invalid key".to_owned()))?
-				.0
-				.public()
-				.into(),
-			KeyType::Ecdsa => ecdsa::Pair::from_string_with_seed(&config.private_key, None)
-				.map_err(|_| Error::Custom("invalid key".to_owned()))?
-				.0
-				.public()
-				.into(),
-		};
-
-		key_store
-			.insert(key_type_id, &*config.private_key, public_key.as_ref())
-			.unwrap();
-
-		assert!(key_store.has_keys(&[(public_key.as_ref().to_vec(), key_type_id)]));
+		let keys = load_signing_keys(
+			config.signer.as_ref(),
+			&config.private_key,
+			&config.additional_private_keys,
+			key_type.scheme(),
+		)
+		.await
+		.map_err(|e| Error::Custom(format!("Failed to load the keys of {}: {e}", config.name)))?;
+		let (signing_keys, signers) = key_type.signers(keys)?;
+		let public_key = signing_keys[0].clone();
+		let keys = KeyRotation::new(signing_keys.len(), config.common.max_concurrent_submissions);
 		Ok(Self {
 			name: config.name,
 			parachain_rpc_url: config.parachain_rpc_url,
 			relay_chain_rpc_url: config.relay_chain_rpc_url,
+			fallback_parachain_rpc_urls: config.fallback_parachain_rpc_urls,
+			fallback_relay_chain_rpc_urls: config.fallback_relay_chain_rpc_urls,
+			active_parachain_rpc_url,
+			active_relay_chain_rpc_url,
 			para_client,
 			relay_client,
 			para_id: config.para_id,
@@ -253,24 +508,266 @@ where
 			commitment_prefix: config.commitment_prefix.0,
 			connection_id: Arc::new(Mutex::new(config.connection_id)),
 			public_key,
-			key_store,
-			key_type_id,
+			signing_keys,
+			signers,
+			signer: config.signer,
 			max_extrinsic_weight,
+			max_extrinsic_length,
 			para_ws_client,
 			relay_ws_client,
+			archive_parachain_rpc_url: config.archive_parachain_rpc_url,
+			archive_ws_client,
+			unsigned_client_updates: config.unsigned_client_updates,
+			pallet_name: config.pallet_name,
+			finalized_events: config.finalized_events,
+			standalone: config.standalone,
 			ss58_version: Ss58AddressFormat::from(config.ss58_version),
+			key_type,
 			channel_whitelist: Arc::new(Mutex::new(config.channel_whitelist.into_iter().collect())),
 			finality_protocol: config.finality_protocol,
+			fee_asset: config.fee_asset,
+			assets: config.assets,
 			common_state: CommonClientState {
-				skip_optional_client_updates: true,
+				skip_optional_client_updates: config.common.skip_optional_client_updates,
 				maybe_has_undelivered_packets: Arc::new(Mutex::new(Default::default())),
 				rpc_call_delay: DEFAULT_RPC_CALL_DELAY,
 				initial_rpc_call_delay: DEFAULT_RPC_CALL_DELAY,
 				misbehaviour_client_msg_queue: Arc::new(AsyncMutex::new(vec![])),
-				..Default::default()
+				max_packets_to_process: config
+					.common
+					.max_packets_to_process_or(DEFAULT_MAX_PACKETS_TO_PROCESS),
+				skip_tokens_list: config.common.skip_tokens_list.unwrap_or_default(),
+				client_update_period: config.common.client_update_period,
+				confirmation_depth: config.common.confirmation_depth,
+				confirmations: Default::default(),
+				optional_update_window: config
+					.common
+					.optional_update_window
+					.map(Duration::from_secs),
+				update_coalescer: Default::default(),
+				adaptive_updates: Arc::new(Mutex::new(
+					config.common.adaptive_client_updates.clone().map(AdaptiveUpdateScheduler::new),
+				)),
+				security: config.common.security.clone(),
+				counterparty_payee: config.common.counterparty_payee.clone(),
+				event_filter: config.common.event_filter.clone(),
+				incentivized_packets: Default::default(),
+				skipped_stream_items: Default::default(),
+				disconnects: Default::default(),
+				dry_run: false,
+				relay_status: Default::default(),
+				paused_channels: Default::default(),
+				force_client_update: Default::default(),
+				halted_channels: Default::default(),
+				min_balance: Arc::new(Mutex::new(config.common.min_balance)),
+				low_balance: Default::default(),
+				stall_reason: Default::default(),
+				relayed_sequences: Arc::new(Mutex::new(RelayedSequences::new(
+					config.common.relayed_sequences_retention,
+					config.common.relayed_sequences_path.clone(),
+				))),
+				pending_messages: Arc::new(Mutex::new(PendingMessages::new(
+					config.common.pending_messages_path.clone(),
+				))),
+				receipts: None,
+				ledger: None,
+				keys,
+				connection_delays: Default::default(),
+				pipeline_counters: Default::default(),
+				pending_timeouts: Default::default(),
+				gas_usage: Default::default(),
 			},
 		})
 	}
+
+	/// Runs `query` against the parachain node. If it fails because the state at the queried
+	/// height was pruned, the query is retried against the archive node, if one is configured.
+	pub async fn query_with_archive_fallback<R, E, F, Fut>(&self, query: F) -> Result<R, Error>
+	where
+		E: Debug,
+		F: Fn(Arc<jsonrpsee_ws_client::WsClient>) -> Fut,
+		Fut: Future<Output = Result<R, E>>,
+	{
+		let error = match query(self.para_ws_client.clone()).await {
+			Ok(res) => return Ok(res),
+			Err(e) => format!("{e:?}"),
+		};
+		match &self.archive_ws_client {
+			Some(archive_ws_client) if is_pruned_state_error(&error) => {
+				log::debug!(target: "hyperspace_parachain", "State is pruned on {}, querying the archive node: {error}", self.name);
+				query(archive_ws_client.clone())
+					.await
+					.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))
+			},
+			_ => Err(Error::from(format!("Rpc Error {error}"))),
+		}
+	}
+
+	/// Applies the parts of a reloaded [`ParachainClientConfig`] that can be changed without
+	/// reconnecting: channel whitelist, assets and common relayer parameters.
+	pub fn reload_config(&mut self, config: ParachainClientConfig) -> Result<(), Error> {
+		if config.para_id != self.para_id ||
+			config.parachain_rpc_url != self.parachain_rpc_url ||
+			config.relay_chain_rpc_url != self.relay_chain_rpc_url ||
+			config.fallback_parachain_rpc_urls != self.fallback_parachain_rpc_urls ||
+			config.fallback_relay_chain_rpc_urls != self.fallback_relay_chain_rpc_urls ||
+			config.archive_parachain_rpc_url != self.archive_parachain_rpc_url ||
+			config.pallet_name != self.pallet_name ||
+			config.standalone != self.standalone
+		{
+			return Err(Error::Custom(format!(
+				"Para id, rpc urls, pallet name and standalone mode of {} cannot be changed without \
+				 a restart",
+				self.name
+			)))
+		}
+		check_security_params(&config)?;
+		*self.channel_whitelist.lock().unwrap() = config.channel_whitelist.into_iter().collect();
+		self.unsigned_client_updates = config.unsigned_client_updates;
+		self.fee_asset = config.fee_asset;
+		self.assets = config.assets;
+		self.common_state.apply_config(&config.common, DEFAULT_MAX_PACKETS_TO_PROCESS);
+		Ok(())
+	}
+
+	/// Returns the pallet prefix of the `pallet_ibc` instance this client relays for, which is its
+	/// commitment prefix.
+	pub fn pallet_prefix(&self) -> Option<String> {
+		Some(String::from_utf8_lossy(&self.commitment_prefix).into_owned())
+	}
+
+	/// Returns the raw account id of `signer` on the parachain: the 20 byte Ethereum address of
+	/// ethereum keys, the 32 byte account id of other keys.
+	pub fn account_bytes(&self, signer: &MultiSigner) -> Vec<u8> {
+		match signer {
+			MultiSigner::Ecdsa(public) if self.key_type == KeyType::Ethereum =>
+				ethereum::AccountId20::from_public(public).0.to_vec(),
+			_ => <[u8; 32]>::from(signer.clone().into_account()).to_vec(),
+		}
+	}
+
+	/// Returns the address of `signer` on the parachain: the hex encoded Ethereum address of
+	/// ethereum keys, the SS58 address of other keys.
+	pub fn account_address(&self, signer: &MultiSigner) -> String {
+		match signer {
+			MultiSigner::Ecdsa(public) if self.key_type == KeyType::Ethereum =>
+				ethereum::AccountId20::from_public(public).to_string(),
+			_ => signer.clone().into_account().to_ss58check_with_version(self.ss58_version),
+		}
+	}
+
+	/// Decodes the raw account id of an `address` of the parachain, as returned by
+	/// [`Self::account_address`].
+	pub fn decode_account(&self, address: &str) -> Result<Vec<u8>, Error> {
+		if self.key_type == KeyType::Ethereum {
+			let account = ethereum::AccountId20::from_str(address)
+				.map_err(|e| Error::Custom(format!("Invalid ethereum address {address}: {e}")))?;
+			return Ok(account.0.to_vec())
+		}
+		sp_core::crypto::AccountId32::from_ss58check(address)
+			.map(|account| <[u8; 32]>::from(account).to_vec())
+			.map_err(|e| Error::Custom(format!("Invalid ss58 address {address}: {e:?}")))
+	}
+
+	/// Queries the free balance of the account of `signer` in the token used to pay transaction
+	/// fees, which is the native token of the parachain unless a fee asset is configured.
+	pub async fn query_native_balance_of(
+		&self,
+		signer: &MultiSigner,
+	) -> Result<PrefixedCoin, Error> {
+		if let Some(fee_asset) = &self.fee_asset {
+			return self.query_configured_asset_balance_of(signer, fee_asset).await
+		}
+		let account = self.account_bytes(signer);
+		let mut storage_key = twox_128(b"System").to_vec();
+		storage_key.extend(twox_128(b"Account"));
+		storage_key.extend(blake2_128(&account));
+		storage_key.extend(account);
+		let free = match self.para_client.rpc().storage(&storage_key, None).await? {
+			// `AccountInfo` starts with the nonce and the three reference counters, followed by
+			// the free balance
+			Some(data) => <(u32, u32, u32, u32, u128)>::decode(&mut &*data.0)
+				.map(|(.., free)| free)
+				.map_err(|e| Error::from(format!("Failed to decode account info: {e:?}")))?,
+			None => 0,
+		};
+
+		Ok(PrefixedCoin {
+			denom: PrefixedDenom::from_str(&self.query_native_token_symbol().await?)?,
+			amount: Amount::from_str(&free.to_string())?,
+		})
+	}
+
+	/// Queries the balance of the account of `signer` in `asset_id`, with the denom of the asset
+	/// known to the pallet.
+	pub async fn query_asset_balance_of(
+		&self,
+		signer: &MultiSigner,
+		asset_id: <T as light_client_common::config::Config>::AssetId,
+	) -> Result<PrefixedCoin, Error> {
+		let account = self.account_bytes(signer);
+		let coin: ibc_proto::cosmos::base::v1beta1::Coin = ibc_rpc::IbcApiClient::<
+			u32,
+			H256,
+			<T as light_client_common::config::Config>::AssetId,
+		>::query_balance_with_address(
+			&*self.para_ws_client,
+			format!("0x{}", hex::encode(account)),
+			asset_id,
+			self.pallet_prefix(),
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		Ok(PrefixedCoin {
+			denom: PrefixedDenom::from_str(&coin.denom)?,
+			amount: Amount::from_str(&coin.amount)?,
+		})
+	}
+
+	/// Queries the balance of the account of `signer` in the configured `asset`, with its
+	/// configured denom.
+	pub async fn query_configured_asset_balance_of(
+		&self,
+		signer: &MultiSigner,
+		asset: &AssetConfig,
+	) -> Result<PrefixedCoin, Error> {
+		let balance = self.query_asset_balance_of(signer, asset.asset_id.into()).await?;
+		Ok(PrefixedCoin { denom: PrefixedDenom::from_str(&asset.denom)?, ..balance })
+	}
+
+	/// Returns the configured fee asset or asset with the denom `denom`, if any.
+	pub fn configured_asset(&self, denom: &str) -> Option<&AssetConfig> {
+		// the denoms of the native assets may be quoted by the pallet
+		let denom = denom.trim_matches('"');
+		self.fee_asset.iter().chain(&self.assets).find(|asset| asset.denom == denom)
+	}
+
+	/// Queries the symbol of the native token of the parachain from its system properties,
+	/// defaulting to `UNIT`.
+	pub async fn query_native_token_symbol(&self) -> Result<String, Error> {
+		let properties = self.para_client.rpc().system_properties().await?;
+		let symbol = properties
+			.get("tokenSymbol")
+			.and_then(|symbol| symbol.as_str().or_else(|| symbol.as_array()?.first()?.as_str()))
+			.unwrap_or("UNIT");
+		Ok(symbol.to_string())
+	}
+}
+
+/// Checks that the common params of `config` are valid and supported by parachains.
+fn check_security_params(config: &ParachainClientConfig) -> Result<(), Error> {
+	config
+		.common
+		.validate()
+		.map_err(|e| Error::Custom(format!("Invalid params of {}: {e}", config.name)))?;
+	// GRANDPA and BEEFY finality proofs always target the latest finalized block
+	if config.common.security.finality_depth != 0 {
+		return Err(Error::Custom(format!(
+			"finality_depth is not supported by {}, which is finalized by {:?}",
+			config.name, config.finality_protocol
+		)))
+	}
+	Ok(())
 }
 
 impl<T: light_client_common::config::Config + Send + Sync> ParachainClient<T>
@@ -299,6 +796,7 @@ where
 			para_ws_client,
 			para_id: self.para_id,
 			rpc_call_delay: self.common_state.rpc_call_delay,
+			standalone: self.standalone,
 		}
 	}
 
@@ -405,8 +903,16 @@ where
 	/// and asserts that it was successfully dispatched on-chain.
 	///
 	/// We retry sending the transaction up to 5 times in the case where the transaction pool might
-	/// reject the transaction because of conflicting nonces.
+	/// reject the transaction because of conflicting nonces. The transaction is signed with the
+	/// next key of the chain's [`KeyRotation`], whose nonce is handed out by the key's
+	/// [`primitives::SubmissionCoordinator`], so concurrent submissions don't race on it.
 	pub async fn submit_call<C: TxPayload>(&self, call: C) -> Result<(T::Hash, T::Hash), Error> {
+		let key = self.common_state.keys.next_key();
+		let mut slot = self.common_state.keys.submissions(key).begin().await;
+		let signer = ExtrinsicSigner::<T, Self>::new(
+			self.signers[key].clone(),
+			self.signing_keys[key].clone(),
+		);
 		// Try extrinsic submission five times in case of failures
 		let mut count = 0;
 		let progress = loop {
@@ -416,17 +922,24 @@ where
 
 			let other_params = T::custom_extrinsic_params(&self.para_client).await?;
 
-			let res = {
-				let signer = ExtrinsicSigner::<T, Self>::new(
-					self.key_store.clone(),
-					self.key_type_id.clone(),
-					self.public_key.clone(),
-				);
-				self.para_client
+			let res = async {
+				let chain_nonce = self
+					.para_client
+					.rpc()
+					.system_account_next_index(subxt::tx::Signer::account_id(&signer))
+					.await?;
+				let nonce = slot.reserve_nonce(chain_nonce.into());
+				let nonce = serde_json::from_value(nonce.into())
+					.map_err(|e| Error::Custom(format!("Invalid nonce {nonce}: {e}")))?;
+				let progress = self
+					.para_client
 					.tx()
-					.sign_and_submit_then_watch(&call, &signer, other_params)
-					.await
-			};
+					.create_signed_with_nonce(&call, &signer, nonce, other_params)?
+					.submit_and_watch()
+					.await?;
+				Ok::<_, Error>(progress)
+			}
+			.await;
 			match res {
 				Ok(progress) => break progress,
 				Err(e) => {
@@ -444,9 +957,84 @@ where
 					Error::from(format!("[submit_call] Failed to wait for in block due to {:?}", e))
 				})??;
 		tx_in_block.wait_for_success().await?;
+		slot.confirm();
+		Ok((tx_in_block.extrinsic_hash(), tx_in_block.block_hash()))
+	}
+
+	/// Submits a `MsgUpdateClient` as an unsigned `update_client_unsigned` extrinsic and waits for
+	/// it to be successfully included in a block.
+	pub async fn submit_unsigned_client_update(
+		&self,
+		message: Any,
+	) -> Result<(T::Hash, T::Hash), Error> {
+		let call = crate::dynamic::ibc_update_client_unsigned(&self.pallet_name, message);
+		let progress = self.para_client.tx().create_unsigned(&call)?.submit_and_watch().await?;
+		let tx_in_block =
+			tokio::time::timeout(WAIT_FOR_IN_BLOCK_TIMEOUT, progress.wait_for_in_block())
+				.await
+				.map_err(|e| {
+					Error::from(format!(
+						"[submit_unsigned_client_update] Failed to wait for in block due to {:?}",
+						e
+					))
+				})??;
+		tx_in_block.wait_for_success().await?;
 		Ok((tx_in_block.extrinsic_hash(), tx_in_block.block_hash()))
 	}
 
+	/// Estimates the weight, the fee and the length of an `ibc_deliver` extrinsic of `messages`,
+	/// signed with the primary key, with the transaction payment runtime API.
+	pub async fn estimate_extrinsic(&self, messages: Vec<Any>) -> Result<ExtrinsicEstimate, Error>
+	where
+		<T::ExtrinsicParams as ExtrinsicParams<T::Index, T::Hash>>::OtherParams:
+			From<BaseExtrinsicParamsBuilder<T, T::Tip>>,
+	{
+		let extrinsic = {
+			// todo: put this in utils
+			let signer =
+				ExtrinsicSigner::<T, Self>::new(self.signers[0].clone(), self.public_key.clone());
+
+			let tx_params = BaseExtrinsicParamsBuilder::new()
+				.tip(T::Tip::from(100_000u128))
+				.era(Era::Immortal, self.para_client.genesis_hash());
+			let call = dynamic::ibc_deliver(&self.pallet_name, messages);
+			self.para_client
+				.tx()
+				.create_signed(&call, &signer, tx_params.into())
+				.await?
+				.encoded()
+				.to_vec()
+		};
+		let length = extrinsic.len();
+		let dispatch_info = TransactionPaymentApiClient::<
+			H256,
+			RuntimeDispatchInfo<u128, sp_weights::Weight>,
+		>::query_info(&*self.para_ws_client, extrinsic.into(), None)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error From Estimating weight {:?}", e)))?;
+		Ok(ExtrinsicEstimate {
+			weight: dispatch_info.weight.ref_time(),
+			partial_fee: dispatch_info.partial_fee,
+			length,
+		})
+	}
+
+	/// Checks that an extrinsic of the `estimate` fits within the limits of a block, so that
+	/// batches that are too large are rejected before they're submitted.
+	pub fn check_extrinsic_limits(&self, estimate: &ExtrinsicEstimate) -> Result<(), Error> {
+		if estimate.weight > self.max_extrinsic_weight ||
+			estimate.length > self.max_extrinsic_length as usize
+		{
+			return Err(Error::ExtrinsicTooLarge {
+				weight: estimate.weight,
+				max_weight: self.max_extrinsic_weight,
+				length: estimate.length,
+				max_length: self.max_extrinsic_length,
+			})
+		}
+		Ok(())
+	}
+
 	pub fn client_id(&self) -> ClientId {
 		self.client_id
 			.lock()
@@ -455,6 +1043,22 @@ where
 			.expect("Client Id should be defined")
 			.clone()
 	}
+
+	/// Returns the local asset id of the ibc denom `denom` (its full trace path), which is either
+	/// the one it was registered with or the one derived from its hash by
+	/// [`pallet_ibc::denom::derive_asset_id`].
+	pub async fn query_denom_asset_id(
+		&self,
+		denom: &str,
+	) -> Result<<T as light_client_common::config::Config>::AssetId, Error> {
+		ibc_rpc::IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_denom_asset_id(
+			&*self.para_ws_client,
+			denom.to_string(),
+			self.pallet_prefix(),
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))
+	}
 }
 
 impl<T: light_client_common::config::Config + Send + Sync> ParachainClient<T>
@@ -490,7 +1094,6 @@ where
 	{
 		use ibc::core::ics24_host::identifier::ChainId;
 		let api = self.relay_client.storage();
-		let para_client_api = self.para_client.storage();
 		let client_wrapper = Prover {
 			relay_client: self.relay_client.clone(),
 			para_client: self.para_client.clone(),
@@ -535,6 +1138,11 @@ where
 			};
 			// we can't use the genesis block to construct the initial state.
 			if block_number == 0 {
+				if para_height.is_some() {
+					Err(Error::Custom(
+						"Can't construct the client state at the genesis block".to_string(),
+					))?
+				}
 				continue
 			}
 			let subxt_block_number: subxt::rpc::types::BlockNumber = block_number.into();
@@ -542,10 +1150,7 @@ where
 				self.para_client.rpc().block_hash(Some(subxt_block_number)).await?.ok_or_else(
 					|| Error::Custom(format!("Couldn't find block hash for para block",)),
 				)?;
-			let timestamp_addr = T::Storage::timestamp_now();
-			let unix_timestamp_millis = para_client_api
-				.at(block_hash)
-				.fetch(&timestamp_addr)
+			let unix_timestamp_millis = dynamic::fetch_timestamp(&self.para_client, block_hash)
 				.await?
 				.expect("Timestamp should exist");
 			let timestamp_nanos = Duration::from_millis(unix_timestamp_millis).as_nanos() as u64;
@@ -565,6 +1170,25 @@ where
 	pub async fn construct_grandpa_client_state(
 		&self,
 	) -> Result<(AnyClientState, AnyConsensusState), Error>
+	where
+		Self: KeyProvider,
+		<<T as light_client_common::config::Config>::Signature as Verify>::Signer:
+			From<MultiSigner> + IdentifyAccount<AccountId = T::AccountId>,
+		MultiSigner: From<MultiSigner>,
+		<T as subxt::Config>::Address: From<<T as subxt::Config>::AccountId>,
+		u32: From<<<T as subxt::Config>::Header as Header>::Number>,
+		<T as subxt::Config>::Hash: From<H256>,
+		<T as subxt::Config>::Header: Decode,
+	{
+		self.construct_grandpa_client_state_at(None).await
+	}
+
+	/// Constructs the GRANDPA client and consensus states at the parachain block `para_height`,
+	/// or at the latest finalized parachain block if it's `None`.
+	pub async fn construct_grandpa_client_state_at(
+		&self,
+		para_height: Option<u32>,
+	) -> Result<(AnyClientState, AnyConsensusState), Error>
 	where
 		Self: KeyProvider,
 		<<T as light_client_common::config::Config>::Signature as Verify>::Signer:
@@ -584,40 +1208,46 @@ where
 			para_ws_client,
 			para_id: self.para_id,
 			rpc_call_delay: self.common_state.rpc_call_delay,
+			standalone: self.standalone,
 		};
-		let api = self.relay_client.storage();
-		let para_client_api = self.para_client.storage();
 		loop {
-			let light_client_state = prover
-				.initialize_client_state()
+			let light_client_state = match para_height {
+				Some(para_height) => {
+					let relay_hash = prover.query_relay_hash_for_para_height(para_height).await;
+					match relay_hash {
+						Ok(relay_hash) => prover.initialize_client_state_at(relay_hash).await,
+						Err(e) => Err(e),
+					}
+				},
+				None => prover.initialize_client_state().await,
+			}
+			.map_err(|e| Error::from(format!("Error constructing client state: {e}")))?;
+
+			// the parachain header included at the latest relay block, or the latest header
+			// itself for standalone chains
+			let para_head = prover
+				.query_latest_finalized_parachain_header(light_client_state.latest_relay_height)
 				.await
 				.map_err(|e| Error::from(format!("Error constructing client state: {e}")))?;
-
-			let heads_addr = T::Storage::paras_heads(self.para_id);
-			let head_data = <T::Storage as RuntimeStorage>::HeadData::from_inner(
-				api.at(light_client_state.latest_relay_hash.into())
-					.fetch(&heads_addr)
-					.await?
-					.ok_or_else(|| {
-						Error::Custom(format!(
-							"Couldn't find header for ParaId({}) at relay block {:?}",
-							self.para_id, light_client_state.latest_relay_hash
-						))
-					})?,
-			);
 			let decoded_para_head = sp_runtime::generic::Header::<
 				u32,
 				sp_runtime::traits::BlakeTwo256,
-			>::decode(&mut &*head_data.as_ref())?;
+			>::decode(&mut &*para_head.encode())?;
 			let block_number = decoded_para_head.number;
 			// we can't use the genesis block to construct the initial state.
 			if block_number == 0 {
+				if para_height.is_some() {
+					Err(Error::Custom(
+						"Can't construct the client state at the genesis block".to_string(),
+					))?
+				}
 				continue
 			}
 
 			let mut client_state = GrandpaClientState::<HostFunctionsManager>::default();
 
-			client_state.relay_chain = Default::default();
+			client_state.relay_chain =
+				if self.standalone { RelayChain::Standalone } else { Default::default() };
 			client_state.current_authorities = light_client_state.current_authorities;
 			client_state.current_set_id = light_client_state.current_set_id;
 			client_state.latest_relay_hash = light_client_state.latest_relay_hash.into();
@@ -625,6 +1255,10 @@ where
 			client_state.latest_para_height = block_number;
 			client_state.para_id = self.para_id;
 			client_state.latest_relay_height = light_client_state.latest_relay_height;
+			// standalone chains have no relay chain to take the unbonding period of the
+			// validators from
+			client_state.unbonding_period =
+				self.standalone.then(|| self.common_state.security.unbonding_period());
 
 			let subxt_block_number: subxt::rpc::types::BlockNumber = block_number.into();
 			let block_hash =
@@ -636,10 +1270,7 @@ where
 						))
 					},
 				)?;
-			let timestamp_addr = T::Storage::timestamp_now();
-			let unix_timestamp_millis = para_client_api
-				.at(block_hash)
-				.fetch(&timestamp_addr)
+			let unix_timestamp_millis = dynamic::fetch_timestamp(&self.para_client, block_hash)
 				.await?
 				.expect("Timestamp should exist");
 			let timestamp_nanos = Duration::from_millis(unix_timestamp_millis).as_nanos() as u64;
@@ -1,6 +1,6 @@
 use codec::{Decode, Encode};
 use std::{
-	collections::{BTreeMap, BTreeSet, HashMap},
+	collections::{BTreeMap, BTreeSet},
 	fmt::Display,
 };
 
@@ -24,7 +24,7 @@ use tendermint_proto::Protobuf;
 use ibc::{
 	core::ics24_host::identifier::ClientId, events::IbcEvent, signer::Signer, tx_msg::Msg, Height,
 };
-use ibc_rpc::{BlockNumberOrHash, IbcApiClient};
+use ibc_rpc::BlockNumberOrHash;
 use ics10_grandpa::client_message::{ClientMessage, Header as GrandpaHeader};
 use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState};
 
@@ -33,7 +33,7 @@ use primitives::{
 };
 
 use super::{error::Error, ParachainClient};
-use crate::finality_protocol::FinalityProtocol;
+use crate::{finality_protocol::FinalityProtocol, utils::query_events_paginated};
 
 const MAX_HEADERS_PER_ITERATION: usize = 100;
 
@@ -283,12 +283,10 @@ where
 		.collect::<Vec<_>>();
 
 	// block_number => events
-	let events: HashMap<String, Vec<IbcEvent>> = IbcApiClient::<
-		u32,
-		H256,
-		<T as light_client_common::config::Config>::AssetId,
-	>::query_events(
-		&*prover.para_ws_client, finalized_block_numbers
+	let events = query_events_paginated::<<T as light_client_common::config::Config>::AssetId>(
+		&prover.para_ws_client,
+		finalized_block_numbers,
+		prover.pallet_prefix(),
 	)
 	.await?;
 
@@ -13,14 +13,20 @@
 // limitations under the License.
 
 use super::{error::Error, ParachainClient};
-use crate::{finality_protocol::FinalityEvent, FinalityProtocol, GrandpaClientState};
+use crate::{
+	dynamic,
+	utils::{
+		connect_to_healthy_node, failover_order, is_method_not_found_error, query_events_pages,
+	},
+	FinalityProtocol, GrandpaClientState,
+};
 use beefy_prover::helpers::fetch_timestamp_extrinsic_with_proof;
 use codec::{Decode, Encode};
 use finality_grandpa::BlockNumberOps;
-use futures::Stream;
+use futures::{Stream, TryStreamExt};
 use grandpa_light_client_primitives::ParachainHeaderProofs;
 use ibc::{
-	applications::transfer::{Amount, PrefixedCoin, PrefixedDenom},
+	applications::transfer::PrefixedCoin,
 	core::{
 		ics02_client::client_state::{ClientState, ClientType},
 		ics23_commitment::commitment::CommitmentPrefix,
@@ -34,9 +40,9 @@ use ibc_proto::{
 	google::protobuf::Any,
 	ibc::core::{
 		channel::v1::{
-			IdentifiedChannel, QueryChannelResponse, QueryChannelsResponse,
-			QueryNextSequenceReceiveResponse, QueryPacketAcknowledgementResponse,
-			QueryPacketCommitmentResponse, QueryPacketReceiptResponse,
+			QueryChannelResponse, QueryChannelsResponse, QueryNextSequenceReceiveResponse,
+			QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
+			QueryPacketReceiptResponse,
 		},
 		client::v1::{
 			IdentifiedClientState, QueryClientStateResponse, QueryConsensusStateResponse,
@@ -44,14 +50,18 @@ use ibc_proto::{
 		connection::v1::{IdentifiedConnection, QueryConnectionResponse},
 	},
 };
-use ibc_rpc::{IbcApiClient, PacketInfo};
+use ibc_rpc::{BlockNumberOrHash, IbcApiClient, PacketInfo};
 use ics11_beefy::client_state::ClientState as BeefyClientState;
-use light_client_common::config::{AsInnerEvent, IbcEventsT, RuntimeStorage};
+use jsonrpsee_ws_client::WsClientBuilder;
+use light_client_common::config::{AsInnerEvent, IbcEventsT};
 use pallet_ibc::{
 	light_clients::{AnyClientState, AnyConsensusState, HostFunctionsManager},
 	HostConsensusProof,
 };
-use primitives::{apply_prefix, Chain, IbcProvider, KeyProvider, UpdateType};
+use primitives::{
+	apply_prefix, filter_events_by_ids, Chain, CommonClientState, IbcQuerier, KeyProvider,
+	UpgradedClient,
+};
 use sp_core::H256;
 use sp_runtime::{
 	traits::{IdentifyAccount, One, Verify},
@@ -62,11 +72,13 @@ use std::{
 	fmt::Display,
 	pin::Pin,
 	str::FromStr,
+	sync::Arc,
 	time::Duration,
 };
 use subxt::config::{
 	extrinsic_params::BaseExtrinsicParamsBuilder, ExtrinsicParams, Header as HeaderT, Header,
 };
+use tendermint_proto::Protobuf;
 use tokio_stream::wrappers::ReceiverStream;
 
 #[derive(Debug)]
@@ -76,7 +88,7 @@ pub struct TransactionId<Hash> {
 }
 
 #[async_trait::async_trait]
-impl<T: light_client_common::config::Config + Send + Sync + Clone> IbcProvider
+impl<T: light_client_common::config::Config + Send + Sync + Clone + 'static> IbcQuerier
 	for ParachainClient<T>
 where
 	u32: From<<<T as subxt::Config>::Header as HeaderT>::Number>,
@@ -107,74 +119,120 @@ where
 	<T as subxt::Config>::Address: Send + Sync,
 	<T as light_client_common::config::Config>::AssetId: Clone,
 {
-	type FinalityEvent = FinalityEvent;
-	type TransactionId = TransactionId<T::Hash>;
 	type Error = Error;
 	type AssetId = <T as light_client_common::config::Config>::AssetId;
 
-	async fn query_latest_ibc_events<C>(
-		&mut self,
-		finality_event: Self::FinalityEvent,
+	async fn query_ibc_events_in_range<C>(
+		&self,
 		counterparty: &C,
-	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+		from: u64,
+		to: u64,
+	) -> Result<Vec<IbcEvent>, anyhow::Error>
 	where
 		C: Chain,
 	{
-		self.finality_protocol
-			.clone()
-			.query_latest_ibc_events(self, finality_event, counterparty)
-			.await
+		let block_numbers =
+			(from..=to).map(|n| BlockNumberOrHash::Number(n as u32)).collect::<Vec<_>>();
+		let mut channel_and_port_ids = self.channel_whitelist();
+		channel_and_port_ids.extend(counterparty.channel_whitelist());
+		let connection_ids = [self.connection_id(), counterparty.connection_id()]
+			.into_iter()
+			.flatten()
+			.collect::<Vec<_>>();
+		let client_ids = [self.client_id(), counterparty.client_id()];
+
+		// the events are filtered page by page, so that only the relevant ones are kept
+		let mut pages = Box::pin(query_events_pages::<
+			<T as light_client_common::config::Config>::AssetId,
+		>(&self.para_ws_client, block_numbers, self.pallet_prefix()));
+		let mut events = vec![];
+		while let Some(page) = pages.try_next().await? {
+			events.extend(page.into_values().flatten().filter(|ev| {
+				filter_events_by_ids(ev, &client_ids, &connection_ids, &channel_and_port_ids)
+			}));
+		}
+		events.sort_by_key(|ev| ev.height());
+		Ok(events)
 	}
 
-	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
+	async fn ibc_events(
+		&self,
+	) -> Pin<Box<dyn Stream<Item = Result<IbcEvent, Self::Error>> + Send + 'static>> {
 		use futures::StreamExt;
 
 		let (tx, rx) = tokio::sync::mpsc::channel(32);
 		let event = self.para_client.events();
 		let para_client = self.para_client.clone();
+		let common_state = self.common_state.clone();
+		let finalized_events = self.finalized_events;
 		tokio::spawn(async move {
-			let stream = para_client
-				.blocks()
-				.subscribe_all()
-				.await
-				.expect("should susbcribe to blocks")
-				.filter_map(|block| async {
-					let block = block.ok()?;
-					let hash = block.hash();
-					let events = event.at(hash).await.ok()?;
-					let result = events
-						.find::<<T::Events as AsInnerEvent>::Inner>()
-						.filter_map(|ev| {
-							let ok_event = ev
-								.map_err(|e| {
-									log::error!(target: "hyperspace_parachain", "Error event at block {hash:?}: {:?}", e);
-								})
-								.ok()?;
-							let ev = <T::Events as AsInnerEvent>::from_inner(ok_event).events();
-							ev.into_iter()
-								.map(|ev| TryInto::<IbcEvent>::try_into(ev))
-								.collect::<Result<Vec<_>, _>>()
-								.ok()
-						})
-						.flatten()
-						.collect::<Vec<_>>();
-					Some(result)
-				});
+			let subscription = if finalized_events {
+				para_client.blocks().subscribe_finalized().await
+			} else {
+				para_client.blocks().subscribe_all().await
+			};
+			let subscription = match subscription {
+				Ok(subscription) => subscription,
+				Err(e) => {
+					let _ = tx
+						.send(Err(Error::from(format!("Failed to subscribe to blocks: {e:?}"))))
+						.await;
+					return
+				},
+			};
+			let stream = subscription.filter_map(|block| async {
+				let block = block
+					.map_err(|e| {
+						common_state
+							.on_skipped_stream_item(&format!("Failed to fetch block: {e:?}"))
+					})
+					.ok()?;
+				let hash = block.hash();
+				let events = event
+					.at(hash)
+					.await
+					.map_err(|e| {
+						common_state.on_skipped_stream_item(&format!(
+							"Failed to fetch events at block {hash:?}: {e:?}"
+						))
+					})
+					.ok()?;
+				let result = events
+					.find::<<T::Events as AsInnerEvent>::Inner>()
+					.filter_map(|ev| {
+						let ok_event = ev
+							.map_err(|e| {
+								common_state.on_skipped_stream_item(&format!(
+									"Error event at block {hash:?}: {e:?}"
+								))
+							})
+							.ok()?;
+						Some(<T::Events as AsInnerEvent>::from_inner(ok_event).events())
+					})
+					.flatten()
+					.filter_map(|ev| {
+						TryInto::<IbcEvent>::try_into(ev)
+							.map_err(|_| {
+								common_state.on_skipped_stream_item(&format!(
+									"Failed to convert event at block {hash:?}"
+								))
+							})
+							.ok()
+					})
+					.collect::<Vec<_>>();
+				Some(result)
+			});
 
 			let mut stream = Box::pin(stream);
 
 			while let Some(evs) = stream.next().await {
-				let mut should_exit = false;
 				for ev in evs {
-					if let Err(_) = tx.send(ev).await {
-						should_exit = true;
-						break
+					if tx.send(Ok(ev)).await.is_err() {
+						return
 					}
 				}
-				if should_exit {
-					break
-				}
 			}
+			let _ = tx.send(Err(Error::from("Block subscription closed".to_string()))).await;
 		});
 
 		Box::pin(ReceiverStream::new(rx))
@@ -193,6 +251,7 @@ where
 			consensus_height.revision_height,
 			consensus_height.revision_number,
 			false,
+			self.pallet_prefix(),
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -209,6 +268,7 @@ where
 				&*self.para_ws_client,
 				at.revision_height as u32,
 				client_id.to_string(),
+				self.pallet_prefix(),
 			)
 			.await
 			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -224,6 +284,7 @@ where
 			&*self.para_ws_client,
 			at.revision_height as u32,
 			connection_id.to_string(),
+			self.pallet_prefix(),
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -241,6 +302,7 @@ where
 			at.revision_height as u32,
 			channel_id.to_string(),
 			port_id.to_string(),
+			self.pallet_prefix(),
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -255,16 +317,87 @@ where
 		let prefixed_keys =
 			keys.into_iter().map(|path| apply_prefix(prefix.clone(), path)).collect();
 
-		let proof = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_proof(
-			&*self.para_ws_client,
-			at.revision_height as u32,
-			prefixed_keys,
-		)
-		.await
-		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		let proof = self
+			.query_with_archive_fallback(|client| {
+				let prefixed_keys = prefixed_keys.clone();
+				async move {
+					IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_proof(
+						&*client,
+						at.revision_height as u32,
+						prefixed_keys,
+						self.pallet_prefix(),
+					)
+					.await
+				}
+			})
+			.await?;
 		Ok(proof.proof)
 	}
 
+	/// Query the proofs of the given sets of keys at the given height in a single request.
+	///
+	/// Note: all the keys will be prefixed with the connection prefix.
+	async fn query_proofs(
+		&self,
+		at: Height,
+		keys: Vec<Vec<Vec<u8>>>,
+	) -> Result<Vec<Vec<u8>>, Self::Error> {
+		let prefix = self.connection_prefix().into_vec();
+		let prefixed_keys = keys
+			.into_iter()
+			.map(|keys| keys.into_iter().map(|path| apply_prefix(prefix.clone(), path)).collect())
+			.collect::<Vec<Vec<_>>>();
+
+		let proofs = self
+			.query_with_archive_fallback(|client| {
+				let prefixed_keys = prefixed_keys.clone();
+				async move {
+					IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_proofs(
+						&*client,
+						at.revision_height as u32,
+						prefixed_keys,
+						self.pallet_prefix(),
+					)
+					.await
+				}
+			})
+			.await?;
+		Ok(proofs.into_iter().map(|proof| proof.proof).collect())
+	}
+
+	/// Query a single proof of the given commitment paths at the given height.
+	///
+	/// Note: the paths are prefixed with the connection prefix by the runtime. Nodes that don't
+	/// expose the batch proof rpc yet return `None`, so that the paths are proven one by one.
+	async fn query_batch_proof(
+		&self,
+		at: Height,
+		paths: Vec<String>,
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		let result = self
+			.query_with_archive_fallback(|client| {
+				let paths = paths.clone();
+				async move {
+					IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_batch_proof(
+						&*client,
+						at.revision_height as u32,
+						paths,
+						self.pallet_prefix(),
+					)
+					.await
+				}
+			})
+			.await;
+		match result {
+			Ok(proof) => Ok(Some(proof.proof)),
+			Err(e) if is_method_not_found_error(&e.to_string()) => {
+				log::debug!(target: "hyperspace_parachain", "Batch proofs are not supported by {}: {e}", self.name);
+				Ok(None)
+			},
+			Err(e) => Err(e),
+		}
+	}
+
 	async fn query_packet_commitment(
 		&self,
 		at: Height,
@@ -272,16 +405,19 @@ where
 		channel_id: &ChannelId,
 		seq: u64,
 	) -> Result<QueryPacketCommitmentResponse, Self::Error> {
-		let res =
-			IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_packet_commitment(
-				&*self.para_ws_client,
-				at.revision_height as u32,
-				channel_id.to_string(),
-				port_id.to_string(),
-				seq,
-			)
-			.await
-			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		let res = self
+			.query_with_archive_fallback(|client| async move {
+				IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_packet_commitment(
+					&*client,
+					at.revision_height as u32,
+					channel_id.to_string(),
+					port_id.to_string(),
+					seq,
+					self.pallet_prefix(),
+				)
+				.await
+			})
+			.await?;
 		Ok(res)
 	}
 
@@ -292,15 +428,19 @@ where
 		channel_id: &ChannelId,
 		seq: u64,
 	) -> Result<QueryPacketAcknowledgementResponse, Self::Error> {
-		let res = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_packet_acknowledgement(
-			&*self.para_ws_client,
-			at.revision_height as u32,
-			channel_id.to_string(),
-			port_id.to_string(),
-			seq,
-		)
-		.await
-		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		let res = self
+			.query_with_archive_fallback(|client| async move {
+				IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_packet_acknowledgement(
+					&*client,
+					at.revision_height as u32,
+					channel_id.to_string(),
+					port_id.to_string(),
+					seq,
+					self.pallet_prefix(),
+				)
+				.await
+			})
+			.await?;
 		Ok(res)
 	}
 
@@ -310,14 +450,18 @@ where
 		port_id: &PortId,
 		channel_id: &ChannelId,
 	) -> Result<QueryNextSequenceReceiveResponse, Self::Error> {
-		let res = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_next_seq_recv(
-			&*self.para_ws_client,
-			at.revision_height as u32,
-			channel_id.to_string(),
-			port_id.to_string(),
-		)
-		.await
-		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		let res = self
+			.query_with_archive_fallback(|client| async move {
+				IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_next_seq_recv(
+					&*client,
+					at.revision_height as u32,
+					channel_id.to_string(),
+					port_id.to_string(),
+					self.pallet_prefix(),
+				)
+				.await
+			})
+			.await?;
 		Ok(res)
 	}
 
@@ -328,15 +472,19 @@ where
 		channel_id: &ChannelId,
 		seq: u64,
 	) -> Result<QueryPacketReceiptResponse, Self::Error> {
-		let res = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_packet_receipt(
-			&*self.para_ws_client,
-			at.revision_height as u32,
-			channel_id.to_string(),
-			port_id.to_string(),
-			seq,
-		)
-		.await
-		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		let res = self
+			.query_with_archive_fallback(|client| async move {
+				IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_packet_receipt(
+					&*client,
+					at.revision_height as u32,
+					channel_id.to_string(),
+					port_id.to_string(),
+					seq,
+					self.pallet_prefix(),
+				)
+				.await
+			})
+			.await?;
 		Ok(res)
 	}
 
@@ -355,12 +503,7 @@ where
 			self.para_client.rpc().block_hash(Some(subxt_block_number)).await?.ok_or_else(
 				|| Error::Custom("Latest block hash query returned None".to_string()),
 			)?;
-		let timestamp_addr = T::Storage::timestamp_now();
-		let unix_timestamp_millis = self
-			.para_client
-			.storage()
-			.at(block_hash)
-			.fetch(&timestamp_addr)
+		let unix_timestamp_millis = dynamic::fetch_timestamp(&self.para_client, block_hash)
 			.await?
 			.ok_or_else(|| Error::from("Timestamp should exist".to_string()))?;
 		let timestamp_nanos = Duration::from_millis(unix_timestamp_millis).as_nanos() as u64;
@@ -380,6 +523,7 @@ where
 				at.revision_height as u32,
 				channel_id.to_string(),
 				port_id.to_string(),
+				self.pallet_prefix(),
 			)
 			.await
 			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -397,6 +541,7 @@ where
 			at.revision_height as u32,
 			channel_id.to_string(),
 			port_id.to_string(),
+			self.pallet_prefix(),
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -421,6 +566,7 @@ where
 				channel_id.to_string(),
 				port_id.to_string(),
 				seqs,
+				self.pallet_prefix(),
 			)
 			.await
 			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -448,6 +594,7 @@ where
 			channel_id.to_string(),
 			port_id.to_string(),
 			seqs,
+			self.pallet_prefix(),
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -468,6 +615,7 @@ where
 				&*self.para_ws_client,
 				at.revision_height as u32,
 				connection_id.to_string(),
+				self.pallet_prefix(),
 			)
 			.await
 			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -486,6 +634,7 @@ where
 				channel_id.to_string(),
 				port_id.to_string(),
 				seqs,
+				self.pallet_prefix(),
 			)
 			.await
 			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -505,6 +654,7 @@ where
 				channel_id.to_string(),
 				port_id.to_string(),
 				seqs,
+				self.pallet_prefix(),
 			)
 			.await
 			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -532,6 +682,7 @@ where
 			client_id.to_string(),
 			client_height.revision_number,
 			client_height.revision_height,
+			self.pallet_prefix(),
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -575,29 +726,77 @@ where
 		Ok(Some(host_consensus_proof.encode()))
 	}
 
+	async fn query_upgraded_client(
+		&self,
+		at: Height,
+	) -> Result<Option<UpgradedClient>, Self::Error> {
+		// keys the states are stored at by the `upgrade_client` call of pallet-ibc
+		const CLIENT_STATE_UPGRADE_PATH: &[u8] = b"client-state-upgrade-path";
+		const CONSENSUS_STATE_UPGRADE_PATH: &[u8] = b"consensus-state-upgrade-path";
+
+		let block_hash = self
+			.para_client
+			.rpc()
+			.block_hash(Some(at.revision_height.into()))
+			.await?
+			.ok_or_else(|| Error::Custom(format!("Block hash not found for {at}")))?;
+		let rpc = self.para_client.rpc();
+		let client_state = rpc.storage(CLIENT_STATE_UPGRADE_PATH, Some(block_hash)).await?;
+		let consensus_state = rpc.storage(CONSENSUS_STATE_UPGRADE_PATH, Some(block_hash)).await?;
+		let (Some(client_state), Some(consensus_state)) = (client_state, consensus_state) else {
+			return Ok(None)
+		};
+		let client_state = AnyClientState::decode_vec(&client_state.0)
+			.map_err(|e| Error::Custom(format!("Invalid upgraded client state: {e}")))?;
+		let consensus_state = AnyConsensusState::decode_vec(&consensus_state.0)
+			.map_err(|e| Error::Custom(format!("Invalid upgraded consensus state: {e}")))?;
+
+		// the grandpa client checks both keys against the same state root
+		let keys = vec![CLIENT_STATE_UPGRADE_PATH, CONSENSUS_STATE_UPGRADE_PATH];
+		let proof = rpc
+			.read_proof(keys, Some(block_hash))
+			.await?
+			.proof
+			.into_iter()
+			.map(|bytes| bytes.0)
+			.collect::<Vec<_>>()
+			.encode();
+		Ok(Some(UpgradedClient {
+			client_state,
+			consensus_state,
+			proof_client_state: proof.clone(),
+			proof_consensus_state: proof,
+		}))
+	}
+
 	async fn query_ibc_balance(
 		&self,
 		asset_id: Self::AssetId,
 	) -> Result<Vec<PrefixedCoin>, Self::Error> {
-		let account = self.public_key.clone().into_account();
-		let account = subxt::utils::AccountId32::from(<[u8; 32]>::from(account));
-		let mut hex_string = hex::encode(account.0.to_vec());
-		hex_string.insert_str(0, "0x");
-		let coin: ibc_proto::cosmos::base::v1beta1::Coin = IbcApiClient::<
-			u32,
-			H256,
-			<T as light_client_common::config::Config>::AssetId,
-		>::query_balance_with_address(
-			&*self.para_ws_client,
-			hex_string,
-			asset_id,
-		)
-		.await
-		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
-		Ok(vec![PrefixedCoin {
-			denom: PrefixedDenom::from_str(&coin.denom)?,
-			amount: Amount::from_str(&coin.amount)?,
-		}])
+		let queried: u128 = asset_id.clone().into();
+		let mut balances = vec![];
+		for asset in self.fee_asset.iter().chain(&self.assets) {
+			if asset.asset_id != queried {
+				balances
+					.push(self.query_configured_asset_balance_of(&self.public_key, asset).await?);
+			}
+		}
+		// the balance of the queried asset comes last
+		balances.push(self.query_asset_balance_of(&self.public_key, asset_id).await?);
+		Ok(balances)
+	}
+
+	async fn query_native_balance(&self) -> Result<PrefixedCoin, Self::Error> {
+		self.query_native_balance_of(&self.public_key).await
+	}
+
+	async fn query_key_balances(&self) -> Result<Vec<(String, PrefixedCoin)>, Self::Error> {
+		let mut balances = vec![];
+		for signer in &self.signing_keys {
+			let address = self.account_address(signer);
+			balances.push((address, self.query_native_balance_of(signer).await?));
+		}
+		Ok(balances)
 	}
 
 	fn connection_prefix(&self) -> CommitmentPrefix {
@@ -629,12 +828,7 @@ where
 			self.para_client.rpc().block_hash(Some(subxt_block_number)).await?.ok_or_else(
 				|| Error::Custom("Block hash not found for block number".to_string()),
 			)?;
-		let timestamp_addr = T::Storage::timestamp_now();
-		let unix_timestamp_millis = self
-			.para_client
-			.storage()
-			.at(block_hash)
-			.fetch(&timestamp_addr)
+		let unix_timestamp_millis = dynamic::fetch_timestamp(&self.para_client, block_hash)
 			.await?
 			.expect("Timestamp should exist");
 		let timestamp_nanos = Duration::from_millis(unix_timestamp_millis).as_nanos() as u64;
@@ -647,7 +841,9 @@ where
 			u32,
 			H256,
 			<T as light_client_common::config::Config>::AssetId,
-		>::query_clients(&*self.para_ws_client)
+		>::query_clients(
+			&*self.para_ws_client, self.pallet_prefix()
+		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
 		response
@@ -662,6 +858,7 @@ where
 	async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
 		let response = IbcApiClient::<u32, H256, <T as light_client_common::config::Config>::AssetId>::query_channels(
 			&*self.para_ws_client,
+			self.pallet_prefix(),
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -688,6 +885,7 @@ where
 			&*self.para_ws_client,
 			height,
 			client_id,
+			self.pallet_prefix(),
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -695,123 +893,119 @@ where
 		Ok(response)
 	}
 
-	async fn is_update_required(
-		&self,
-		latest_height: u64,
-		latest_client_height_on_counterparty: u64,
-	) -> Result<bool, Self::Error> {
-		let prover = self.grandpa_prover();
-		let session_length = prover
-			.session_length()
-			.await
-			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
-		// We divide the session into some places and if the diff in block updates is greater than
-		// this update is required
-		let base =
-			if cfg!(test) { (session_length / 2) as u64 } else { (session_length / 12) as u64 };
-		let diff = latest_height.saturating_sub(latest_client_height_on_counterparty);
-		let pruning_len = 256;
-		Ok(diff >= base.min(pruning_len as u64))
+	/// Set the channel whitelist for the relayer task.
+	fn set_channel_whitelist(&mut self, channel_whitelist: HashSet<(ChannelId, PortId)>) {
+		*self.channel_whitelist.lock().unwrap() = channel_whitelist;
 	}
 
-	async fn initialize_client_state(
-		&self,
-	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
-		match self.finality_protocol {
-			FinalityProtocol::Grandpa => {
-				let res = self.construct_grandpa_client_state().await?;
-				Ok(res)
-			},
-			FinalityProtocol::Beefy => {
-				let res = self.construct_beefy_client_state().await?;
-				Ok(res)
-			},
-		}
+	fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
+		self.channel_whitelist.lock().unwrap().insert(channel);
 	}
 
-	async fn query_client_id_from_tx_hash(
-		&self,
-		tx_id: Self::TransactionId,
-	) -> Result<ClientId, Self::Error> {
-		// Query newly created client Id
-		let TransactionId { ext_hash, block_hash } = tx_id;
-		let identified_client_state = IbcApiClient::<
-			u32,
-			H256,
-			<T as light_client_common::config::Config>::AssetId,
-		>::query_newly_created_client(
-			&*self.para_ws_client, block_hash.into(), ext_hash.into()
-		)
-		.await
-		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
-
-		let client_id = ClientId::from_str(&identified_client_state.client_id)
-			.expect("Should have a valid client id");
-		Ok(client_id)
+	fn set_connection_id(&mut self, connection_id: ConnectionId) {
+		*self.connection_id.lock().unwrap() = Some(connection_id);
 	}
 
-	async fn query_connection_id_from_tx_hash(
-		&self,
-		tx_id: Self::TransactionId,
-	) -> Result<ConnectionId, Self::Error> {
-		// Query newly created connection Id
-		let TransactionId { ext_hash, block_hash } = tx_id;
-		let identified_connection: IdentifiedConnection = IbcApiClient::<
-			u32,
-			H256,
-			<T as light_client_common::config::Config>::AssetId,
-		>::query_newly_created_connection(
-			&*self.para_ws_client,
-			block_hash.into(),
-			ext_hash.into(),
-		)
-		.await
-		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+	fn name(&self) -> &str {
+		&*self.name
+	}
 
-		let connection_id = ConnectionId::from_str(&identified_connection.id)
-			.expect("Should have a valid connection id");
-		Ok(connection_id)
+	async fn query_best_and_finalized_heights(&self) -> Result<(u64, u64), Self::Error> {
+		let best_header = self
+			.para_client
+			.rpc()
+			.header(None)
+			.await?
+			.ok_or_else(|| Error::Custom("Best header query returned None".to_string()))?;
+		let finalized_hash = self.para_client.rpc().finalized_head().await?;
+		let finalized_header = self
+			.para_client
+			.rpc()
+			.header(Some(finalized_hash))
+			.await?
+			.ok_or_else(|| Error::Custom("Finalized header query returned None".to_string()))?;
+		Ok((u32::from(best_header.number()).into(), u32::from(finalized_header.number()).into()))
 	}
 
-	async fn query_channel_id_from_tx_hash(
-		&self,
-		tx_id: Self::TransactionId,
-	) -> Result<(ChannelId, PortId), Self::Error> {
-		// Query newly created channel Id
-		let TransactionId { ext_hash, block_hash } = tx_id;
-		let identified_channel: IdentifiedChannel = IbcApiClient::<
-			u32,
-			H256,
-			<T as light_client_common::config::Config>::AssetId,
-		>::query_newly_created_channel(
-			&*self.para_ws_client,
-			block_hash.into(),
-			ext_hash.into(),
-		)
-		.await
-		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+	async fn handle_error(&mut self, error: &anyhow::Error) -> Result<(), anyhow::Error> {
+		let err_str = if let Some(rpc_err) = error.downcast_ref::<Error>() {
+			match rpc_err {
+				Error::RpcError(s) => s.clone(),
+				_ => "".to_string(),
+			}
+		} else {
+			error.to_string()
+		};
+		log::debug!(target: "hyperspace", "Handling error: {err_str}");
 
-		let channel_id = ChannelId::from_str(&identified_channel.channel_id)
-			.expect("Should have a valid channel id");
-		let port_id =
-			PortId::from_str(&identified_channel.port_id).expect("Should have a valid port id");
-		Ok((channel_id, port_id))
-	}
+		if err_str.contains("MaxSlotsExceeded") {
+			self.common_state.rpc_call_delay = self.common_state.rpc_call_delay * 2;
+		} else if err_str.contains("RestartNeeded") || err_str.contains("restart required") {
+			self.reconnect().await?;
+			self.common_state.rpc_call_delay = self.common_state.rpc_call_delay * 2;
+		}
 
-	/// Set the channel whitelist for the relayer task.
-	fn set_channel_whitelist(&mut self, channel_whitelist: HashSet<(ChannelId, PortId)>) {
-		*self.channel_whitelist.lock().unwrap() = channel_whitelist;
+		Ok(())
 	}
 
-	fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
-		self.channel_whitelist.lock().unwrap().insert(channel);
+	async fn reconnect(&mut self) -> anyhow::Result<()> {
+		// the active endpoints are retried first, then the others in their configured order
+		let (parachain_rpc_url, para_ws_client) = connect_to_healthy_node(&failover_order(
+			&self.active_parachain_rpc_url,
+			&self.parachain_rpc_url,
+			&self.fallback_parachain_rpc_urls,
+		))
+		.await?;
+		let (relay_chain_rpc_url, relay_ws_client) = if self.standalone {
+			(parachain_rpc_url.clone(), para_ws_client.clone())
+		} else {
+			connect_to_healthy_node(&failover_order(
+				&self.active_relay_chain_rpc_url,
+				&self.relay_chain_rpc_url,
+				&self.fallback_relay_chain_rpc_urls,
+			))
+			.await?
+		};
+
+		if let Some(url) = &self.archive_parachain_rpc_url {
+			self.archive_ws_client = Some(Arc::new(
+				WsClientBuilder::default()
+					.build(url)
+					.await
+					.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
+			));
+		}
+
+		let para_client = subxt::OnlineClient::from_rpc_client(para_ws_client.clone()).await?;
+		let relay_client = if self.standalone {
+			para_client.clone()
+		} else {
+			subxt::OnlineClient::from_rpc_client(relay_ws_client.clone()).await?
+		};
+
+		self.relay_ws_client = relay_ws_client;
+		self.para_ws_client = para_ws_client;
+		self.relay_client = relay_client;
+		self.para_client = para_client;
+		if !self.standalone && relay_chain_rpc_url != self.active_relay_chain_rpc_url {
+			log::warn!(target: "hyperspace", "Failed over to relay chain rpc endpoint {relay_chain_rpc_url}");
+		}
+		if parachain_rpc_url != self.active_parachain_rpc_url {
+			log::warn!(target: "hyperspace", "Failed over to parachain rpc endpoint {parachain_rpc_url}");
+		}
+		self.active_relay_chain_rpc_url = relay_chain_rpc_url;
+		self.active_parachain_rpc_url = parachain_rpc_url;
+
+		log::info!(target: "hyperspace", "Reconnected to relay chain and parachain");
+
+		Ok(())
 	}
 
-	fn set_connection_id(&mut self, connection_id: ConnectionId) {
-		*self.connection_id.lock().unwrap() = Some(connection_id);
+	fn common_state(&self) -> &CommonClientState {
+		&self.common_state
 	}
 
-	async fn upload_wasm(&self, _wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
-		Err(Error::Custom("Uploading WASM to parachain is not supported".to_string()))
+	fn common_state_mut(&mut self) -> &mut CommonClientState {
+		&mut self.common_state
 	}
 }
@@ -12,22 +12,76 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use codec::Decode;
-use primitives::KeyProvider;
+use crate::KeyType;
+use anyhow::anyhow;
+use codec::{Decode, Encode};
+use primitives::{signing, KeyProvider};
+use sp_core::{ecdsa, hashing::keccak_256};
 use sp_keystore::{Keystore, KeystorePtr};
 use sp_runtime::{
 	traits::{IdentifyAccount, Verify},
 	KeyTypeId, MultiSignature, MultiSigner,
 };
+use std::sync::Arc;
 use subxt::tx::Signer;
 
+/// A [`signing::Signer`] of a key held in a local keystore.
+pub struct KeystoreSigner {
+	key_store: KeystorePtr,
+	key_type: KeyType,
+	key_type_id: KeyTypeId,
+	public_key: MultiSigner,
+}
+
+impl KeystoreSigner {
+	pub fn new(key_store: KeystorePtr, key_type: KeyType, public_key: MultiSigner) -> Self {
+		Self { key_store, key_type, key_type_id: key_type.to_key_type_id(), public_key }
+	}
+}
+
+impl signing::Signer for KeystoreSigner {
+	fn public_key(&self) -> &[u8] {
+		self.public_key.as_ref()
+	}
+
+	fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+		if self.key_type == KeyType::Ethereum {
+			// ethereum signatures are made over the keccak-256 hash of the payload
+			let public_key = ecdsa::Public::try_from(self.public_key.as_ref())
+				.map_err(|_| anyhow!("Ethereum relayer key is not an ECDSA key"))?;
+			return Keystore::ecdsa_sign_prehashed(
+				&*self.key_store,
+				self.key_type_id,
+				&public_key,
+				&keccak_256(payload),
+			)
+			.map_err(|e| anyhow!("Failed to sign payload: {e:?}"))?
+			.map(|signature| signature.encode())
+			.ok_or_else(|| anyhow!("Relayer key not found in the keystore"))
+		}
+		let crypto_type_id = match &self.public_key {
+			MultiSigner::Ed25519(_) => sp_core::ed25519::CRYPTO_ID,
+			MultiSigner::Sr25519(_) => sp_core::sr25519::CRYPTO_ID,
+			MultiSigner::Ecdsa(_) => sp_core::ecdsa::CRYPTO_ID,
+		};
+		Keystore::sign_with(
+			&*self.key_store,
+			self.key_type_id,
+			crypto_type_id,
+			self.public_key.as_ref(),
+			payload,
+		)
+		.map_err(|e| anyhow!("Failed to sign payload: {e:?}"))?
+		.ok_or_else(|| anyhow!("Relayer key not found in the keystore"))
+	}
+}
+
 /// A [`Signer`] implementation.
 #[derive(Clone)]
 pub struct ExtrinsicSigner<T: light_client_common::config::Config, Provider: KeyProvider> {
 	account_id: T::AccountId,
 	signer: MultiSigner,
-	key_store: KeystorePtr,
-	key_type_id: KeyTypeId,
+	key_signer: Arc<dyn signing::Signer>,
 	_phantom: std::marker::PhantomData<Provider>,
 }
 
@@ -42,8 +96,8 @@ where
 	<T as subxt::Config>::Address: Send + Sync,
 	<T as subxt::Config>::Signature: Send + Sync,
 {
-	/// Creates a new [`Signer`] from a key store reference and key type
-	pub fn new(key_store: KeystorePtr, key_type_id: KeyTypeId, public_key: MultiSigner) -> Self {
+	/// Creates a new [`Signer`] from the signer of the key and its public key
+	pub fn new(key_signer: Arc<dyn signing::Signer>, public_key: MultiSigner) -> Self {
 		let account_id =
 			<<T as light_client_common::config::Config>::Signature as Verify>::Signer::from(
 				public_key.clone(),
@@ -51,8 +105,7 @@ where
 			.into_account();
 		Self {
 			account_id,
-			key_store,
-			key_type_id,
+			key_signer,
 			signer: MultiSigner::from(public_key),
 			_phantom: Default::default(),
 		}
@@ -77,21 +130,7 @@ where
 	}
 
 	fn sign(&self, signer_payload: &[u8]) -> <T as subxt::Config>::Signature {
-		let (crypto_type_id, public_key) = match &self.signer {
-			MultiSigner::Ed25519(key) => (sp_core::ed25519::CRYPTO_ID, key.0.to_vec()),
-			MultiSigner::Sr25519(key) => (sp_core::sr25519::CRYPTO_ID, key.0.to_vec()),
-			MultiSigner::Ecdsa(key) => (sp_core::ecdsa::CRYPTO_ID, key.0.to_vec()),
-		};
-		let encoded_sig = Keystore::sign_with(
-			&*self.key_store,
-			self.key_type_id,
-			crypto_type_id,
-			&public_key,
-			signer_payload,
-		)
-		.ok()
-		.flatten()
-		.expect("Signing should not fail");
+		let encoded_sig = self.key_signer.sign(signer_payload).expect("Signing should not fail");
 		let signature: MultiSignature = match self.signer {
 			MultiSigner::Ed25519(_) => sp_core::ed25519::Signature::decode(&mut &encoded_sig[..])
 				.expect("Should decode same signature type as public key; qed")
@@ -28,10 +28,7 @@ use light_client_common::config::RuntimeTransactions;
 use pallet_ibc::{MultiAddress, Timeout, TransferParams};
 use pallet_ibc_ping::SendPingParams;
 use primitives::{KeyProvider, TestProvider};
-use sp_core::{
-	crypto::{AccountId32, Ss58Codec},
-	H256,
-};
+use sp_core::H256;
 use sp_runtime::{
 	traits::{IdentifyAccount, One, Verify},
 	MultiSignature, MultiSigner,
@@ -61,15 +58,13 @@ where
 	<<T as subxt::Config>::Header as Header>::Number: Ord + sp_runtime::traits::Zero,
 	<T as subxt::Config>::AccountId: Send + Sync,
 	<T as subxt::Config>::Address: Send + Sync,
-	<<T as light_client_common::config::Config>::Tx as RuntimeTransactions>::TransferParams:
-		From<TransferParams<AccountId32>>,
 {
 	pub fn set_client_id(&mut self, client_id: ClientId) {
 		*self.client_id.lock().unwrap() = Some(client_id)
 	}
 
 	pub async fn submit_create_client_msg(&self, msg: Any) -> Result<ClientId, Error> {
-		let call = T::Tx::ibc_deliver(vec![msg]);
+		let call = crate::dynamic::ibc_deliver(&self.pallet_name, vec![msg]);
 		let (ext_hash, block_hash) = self.submit_call(call).await?;
 
 		// Query newly created client Id
@@ -78,7 +73,10 @@ where
 			H256,
 			<T as light_client_common::config::Config>::AssetId,
 		>::query_newly_created_client(
-			&*self.para_ws_client, block_hash.into(), ext_hash.into()
+			&*self.para_ws_client,
+			block_hash.into(),
+			ext_hash.into(),
+			self.pallet_prefix(),
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -88,24 +86,24 @@ where
 		Ok(client_id)
 	}
 
-	pub async fn transfer_tokens(
+	/// Transfers `amount` of `asset_id` over ICS-20, with the optional `memo` of the packet, e.g.
+	/// the forwarding instructions of the packet forward middleware.
+	pub async fn transfer_tokens<AccountId: AsRef<[u8]>>(
 		&self,
-		params: TransferParams<AccountId32>,
+		params: TransferParams<AccountId>,
 		asset_id: u128,
 		amount: u128,
+		memo: Option<String>,
 	) -> Result<(), Error> {
 		// Submit extrinsic to parachain node
-		let call = T::Tx::ibc_transfer(params.into(), asset_id, amount, None);
+		let call = crate::dynamic::ibc_transfer(&self.pallet_name, params, asset_id, amount, memo);
 		self.submit_call(call).await?;
 		Ok(())
 	}
 
 	pub async fn submit_sudo_call(&self, call: T::ParaRuntimeCall) -> Result<(), Error> {
-		let signer = ExtrinsicSigner::<T, Self>::new(
-			self.key_store.clone(),
-			self.key_type_id.clone(),
-			self.public_key.clone(),
-		);
+		let signer =
+			ExtrinsicSigner::<T, Self>::new(self.signers[0].clone(), self.public_key.clone());
 
 		let ext = T::Tx::sudo_sudo(call);
 		// Submit extrinsic to parachain node
@@ -149,13 +147,10 @@ where
 	<T as subxt::Config>::AccountId: Send + Sync,
 	<T as subxt::Config>::Address: Send + Sync,
 	<T as light_client_common::config::Config>::AssetId: Clone,
-	<<T as light_client_common::config::Config>::Tx as RuntimeTransactions>::TransferParams:
-		From<TransferParams<AccountId32>>,
-	<<T as light_client_common::config::Config>::Tx as RuntimeTransactions>::SendPingParams:
-		From<SendPingParams>,
 {
 	async fn send_transfer(&self, transfer: MsgTransfer<PrefixedCoin>) -> Result<(), Self::Error> {
-		let account_id = AccountId32::from_ss58check(transfer.receiver.as_ref())
+		let account_id = self
+			.decode_account(transfer.receiver.as_ref())
 			.map(MultiAddress::Id)
 			.unwrap_or_else(|_| MultiAddress::Raw(transfer.receiver.to_string().into_bytes()));
 		let params = TransferParams {
@@ -167,14 +162,14 @@ where
 			},
 		};
 		let amount = str::parse::<u128>(&transfer.token.amount.to_string()).expect("Infallible!");
-		// TODO: get asset_id by denom
-		let string = transfer.token.denom.to_string();
-		let asset_id = if string == *r#""UNIT""# || string == "UNIT" { 1 } else { 2 };
-		log::info!(
-			"Sending transfer: {:?}, asset id: {asset_id}, amount: {amount}",
-			transfer.token.denom
-		);
-		self.transfer_tokens(params, asset_id, amount).await?;
+		let denom = transfer.token.denom.to_string();
+		let (asset_id, amount_str) = match self.configured_asset(&denom) {
+			Some(asset) => (asset.asset_id, asset.format_amount(amount)),
+			None => (self.query_denom_asset_id(&denom).await?.into(), format!("{amount}{denom}")),
+		};
+		let memo = Some(transfer.memo).filter(|memo| !memo.is_empty());
+		log::info!("Sending transfer of {amount_str}, asset id: {asset_id}, memo: {memo:?}");
+		self.transfer_tokens(params, asset_id, amount, memo).await?;
 
 		Ok(())
 	}
@@ -196,26 +191,41 @@ where
 			channel_id: channel_id.sequence(),
 		};
 
-		let call = T::Tx::ibc_ping_send_ping(params.into());
+		let call = crate::dynamic::ibc_ping_send_ping(params);
 
 		self.submit_call(call).await.map(|_| ())
 	}
 
-	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>> {
+	async fn subscribe_blocks(
+		&self,
+	) -> Pin<Box<dyn Stream<Item = Result<u64, Self::Error>> + Send + Sync>> {
 		let para_client = self.para_ws_client.clone();
-		let stream = para_client
-			.subscribe::<T::Header, _>(
-				"chain_subscribeNewHeads",
-				rpc_params![],
-				"chain_unsubscribeNewHeads",
-			)
+		let (subscribe, unsubscribe) = if self.finalized_events {
+			("chain_subscribeFinalizedHeads", "chain_unsubscribeFinalizedHeads")
+		} else {
+			("chain_subscribeNewHeads", "chain_unsubscribeNewHeads")
+		};
+		let subscription = match para_client
+			.subscribe::<T::Header, _>(subscribe, rpc_params![], unsubscribe)
 			.await
-			.unwrap()
-			.map(|header| {
-				let header = header.unwrap();
-				let block_number: u64 = (header.number()).into();
-				block_number
-			});
+		{
+			Ok(subscription) => subscription,
+			Err(e) =>
+				return Box::pin(futures::stream::once(futures::future::ready(Err(Error::from(
+					format!("Failed to subscribe to new heads: {e:?}"),
+				))))),
+		};
+		let common_state = self.common_state.clone();
+		let stream = subscription.filter_map(move |header| {
+			let block_number = header
+				.map(|header| u64::from(header.number()))
+				.map_err(|e| {
+					common_state.on_skipped_stream_item(&format!("Failed to decode header: {e:?}"))
+				})
+				.ok()
+				.map(Ok);
+			futures::future::ready(block_number)
+		});
 
 		Box::pin(Box::new(stream))
 	}
@@ -17,8 +17,18 @@ use beefy_light_client_primitives::{ClientState, MmrUpdateProof};
 use beefy_primitives::known_payloads::MMR_ROOT_ID;
 use codec::Decode;
 use frame_support::pallet_prelude::{DispatchClass, Weight};
-use frame_system::limits::BlockWeights;
+use frame_system::limits::{BlockLength, BlockWeights};
+use futures::{Stream, TryStreamExt};
+use ibc::events::IbcEvent;
+use ibc_rpc::{BlockNumberOrHash, IbcApiClient};
+use jsonrpsee::{core::client::ClientT, rpc_params};
+use jsonrpsee_ws_client::{WsClient, WsClientBuilder};
+use serde::{de::DeserializeOwned, Serialize};
 use sp_core::H256;
+use std::{collections::HashMap, sync::Arc};
+
+/// Number of blocks whose events are requested from the parachain node at once
+pub const EVENTS_QUERY_CHUNK_SIZE: usize = 100;
 
 pub fn get_updated_client_state(
 	mut client_state: ClientState,
@@ -59,3 +69,131 @@ pub async fn fetch_max_extrinsic_weight<T: light_client_common::config::Config>(
 		.unwrap_or(Weight::from_parts(u64::MAX, 0));
 	Ok(max_extrinsic_weight.ref_time())
 }
+
+/// Fetch the maximum allowed length, in bytes, of a normal extrinsic from a substrate node with the
+/// given client.
+pub async fn fetch_max_extrinsic_length<T: light_client_common::config::Config>(
+	client: &subxt::OnlineClient<T>,
+) -> Result<u32, Error> {
+	let metadata = client.rpc().metadata().await?;
+	let block_length = metadata
+		.pallet_by_name("System")
+		.expect("System pallet should exist")
+		.constant_by_name("BlockLength")
+		.expect("constant BlockLength should exist");
+	let length = BlockLength::decode(&mut &block_length.value()[..])?;
+	Ok(*length.max.get(DispatchClass::Normal))
+}
+
+/// Streams the ibc events deposited in the blocks `block_numbers`, keyed by block number or hash,
+/// one page of at most [`EVENTS_QUERY_CHUNK_SIZE`] blocks at a time, so that the events of the
+/// thousands of blocks finalized during a downtime are queried without timing out the node.
+pub fn query_events_pages<AssetId>(
+	client: &WsClient,
+	block_numbers: Vec<BlockNumberOrHash<H256>>,
+	pallet_prefix: Option<String>,
+) -> impl Stream<Item = Result<HashMap<String, Vec<IbcEvent>>, Error>> + Send + '_
+where
+	AssetId: codec::Codec + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+	futures::stream::try_unfold((block_numbers, 0), move |(block_numbers, start)| {
+		let pallet_prefix = pallet_prefix.clone();
+		async move {
+			if start >= block_numbers.len() {
+				return Ok(None)
+			}
+			let end = (start + EVENTS_QUERY_CHUNK_SIZE).min(block_numbers.len());
+			let page = IbcApiClient::<u32, H256, AssetId>::query_events_page(
+				client,
+				block_numbers[start..end].to_vec(),
+				0,
+				EVENTS_QUERY_CHUNK_SIZE as u32,
+				pallet_prefix,
+			)
+			.await?;
+			// the node may return fewer blocks than requested
+			let next = start + page.next_offset.map_or(end - start, |offset| offset as usize);
+			Ok(Some((page.events, (block_numbers, next))))
+		}
+	})
+}
+
+/// Queries the ibc events deposited in the blocks `block_numbers`, keyed by block number or hash,
+/// in pages, see [`query_events_pages`].
+pub async fn query_events_paginated<AssetId>(
+	client: &WsClient,
+	block_numbers: Vec<BlockNumberOrHash<H256>>,
+	pallet_prefix: Option<String>,
+) -> Result<HashMap<String, Vec<IbcEvent>>, Error>
+where
+	AssetId: codec::Codec + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+	query_events_pages::<AssetId>(client, block_numbers, pallet_prefix)
+		.try_fold(HashMap::new(), |mut events, page| async move {
+			events.extend(page);
+			Ok(events)
+		})
+		.await
+}
+
+/// Returns true if an RPC error was caused by querying state that the node has already pruned.
+pub fn is_pruned_state_error(error: &str) -> bool {
+	["State already discarded", "UnknownBlock", "Unknown block", "Header was not found"]
+		.iter()
+		.any(|pattern| error.contains(pattern))
+}
+
+/// Returns true if an RPC error was caused by calling a method the node doesn't expose, e.g. a
+/// method added to the ibc rpc after the node was released.
+pub fn is_method_not_found_error(error: &str) -> bool {
+	["MethodNotFound", "Method not found"]
+		.iter()
+		.any(|pattern| error.contains(pattern))
+}
+
+/// Returns the rpc urls of a node to connect to, in the order they should be tried: the `active`
+/// one first, then the `primary` one and its `fallbacks` in their configured order.
+pub fn failover_order<'a>(
+	active: &'a str,
+	primary: &'a str,
+	fallbacks: &'a [String],
+) -> Vec<&'a str> {
+	let mut urls = vec![active];
+	for url in std::iter::once(primary).chain(fallbacks.iter().map(String::as_str)) {
+		if !urls.contains(&url) {
+			urls.push(url);
+		}
+	}
+	urls
+}
+
+/// Connects to the first of `urls` whose node is reachable and not syncing, as reported by its
+/// `system_health` rpc.
+pub async fn connect_to_healthy_node(urls: &[&str]) -> Result<(String, Arc<WsClient>), Error> {
+	let mut errors = vec![];
+	for url in urls {
+		match connect_and_probe(url).await {
+			Ok(client) => return Ok((url.to_string(), client)),
+			Err(e) => {
+				log::warn!(target: "hyperspace_parachain", "Rpc endpoint {url} is unavailable: {e}");
+				errors.push(format!("{url}: {e}"));
+			},
+		}
+	}
+	Err(Error::Custom(format!("No healthy rpc endpoint: {}", errors.join(", "))))
+}
+
+async fn connect_and_probe(url: &str) -> Result<Arc<WsClient>, Error> {
+	let client = WsClientBuilder::default()
+		.build(url)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+	let health: serde_json::Value = client
+		.request("system_health", rpc_params![])
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+	if health["isSyncing"].as_bool().unwrap_or(false) {
+		return Err(Error::from("node is syncing".to_string()))
+	}
+	Ok(Arc::new(client))
+}
@@ -0,0 +1,136 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Adaptive scheduling of the optional client updates: the client of a chain on its counterparty
+//! is updated more often while packets are being sent from the chain, to lower their latency,
+//! and less often while it's idle, to save fees.
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Targets and bounds of the adaptive client update frequency of a path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveUpdateParams {
+	/// Number of blocks between two client updates while packets are sent at the target rate or
+	/// above.
+	pub min_update_period: u64,
+	/// Number of blocks between two client updates while no packets are sent.
+	pub max_update_period: u64,
+	/// Number of packets sent within `rate_window` blocks at which the client is updated every
+	/// `min_update_period` blocks. The period shrinks linearly as the rate approaches it.
+	pub target_packets: u64,
+	/// Number of most recent blocks the send-packet rate is measured over.
+	pub rate_window: u64,
+}
+
+impl Default for AdaptiveUpdateParams {
+	fn default() -> Self {
+		Self { min_update_period: 5, max_update_period: 600, target_packets: 10, rate_window: 100 }
+	}
+}
+
+impl AdaptiveUpdateParams {
+	/// Checks that the parameters are consistent with each other.
+	pub fn validate(&self) -> anyhow::Result<()> {
+		if self.min_update_period == 0 {
+			return Err(anyhow!("min_update_period must be greater than zero"))
+		}
+		if self.min_update_period > self.max_update_period {
+			return Err(anyhow!(
+				"min_update_period ({}) must not be greater than max_update_period ({})",
+				self.min_update_period,
+				self.max_update_period
+			))
+		}
+		if self.target_packets == 0 {
+			return Err(anyhow!("target_packets must be greater than zero"))
+		}
+		if self.rate_window == 0 {
+			return Err(anyhow!("rate_window must be greater than zero"))
+		}
+		Ok(())
+	}
+}
+
+/// Decides when the optional client updates of a chain are sent, based on the rate at which
+/// packets are sent from it.
+#[derive(Debug)]
+pub struct AdaptiveUpdateScheduler {
+	params: AdaptiveUpdateParams,
+	/// Recent blocks that sent packets, with the number of packets they sent, oldest first.
+	sent_packets: VecDeque<(u64, u64)>,
+	/// Height of the last client update that was sent.
+	last_update_height: Option<u64>,
+}
+
+impl AdaptiveUpdateScheduler {
+	pub fn new(params: AdaptiveUpdateParams) -> Self {
+		Self { params, sent_packets: Default::default(), last_update_height: None }
+	}
+
+	pub fn params(&self) -> &AdaptiveUpdateParams {
+		&self.params
+	}
+
+	/// Replaces the parameters, keeping the recorded packets and updates.
+	pub fn set_params(&mut self, params: AdaptiveUpdateParams) {
+		self.params = params;
+	}
+
+	/// Records that `count` packets were sent in the block at `height`. The first recorded block
+	/// starts the first update period.
+	pub fn record_send_packets(&mut self, height: u64, count: u64) {
+		self.last_update_height.get_or_insert(height);
+		if count != 0 {
+			self.sent_packets.push_back((height, count));
+		}
+		let window_start = height.saturating_sub(self.params.rate_window);
+		while matches!(self.sent_packets.front(), Some((sent_at, _)) if *sent_at <= window_start) {
+			self.sent_packets.pop_front();
+		}
+	}
+
+	/// Records that a client update to `height` was sent.
+	pub fn record_update(&mut self, height: u64) {
+		let last = self.last_update_height.get_or_insert(height);
+		*last = (*last).max(height);
+	}
+
+	/// Number of packets sent within the rate window ending at `height`.
+	pub fn packet_rate(&self, height: u64) -> u64 {
+		let window_start = height.saturating_sub(self.params.rate_window);
+		self.sent_packets
+			.iter()
+			.filter(|(sent_at, _)| *sent_at > window_start)
+			.map(|(_, count)| count)
+			.sum()
+	}
+
+	/// Number of blocks between two client updates at the packet rate measured at `height`.
+	pub fn update_period(&self, height: u64) -> u64 {
+		let AdaptiveUpdateParams { min_update_period, max_update_period, target_packets, .. } =
+			self.params;
+		let rate = self.packet_rate(height).min(target_packets);
+		let range = max_update_period.saturating_sub(min_update_period) as u128;
+		max_update_period - (range * rate as u128 / target_packets.max(1) as u128) as u64
+	}
+
+	/// Returns whether an optional client update to `height` should be sent.
+	pub fn is_update_due(&self, height: u64) -> bool {
+		self.last_update_height
+			.map_or(false, |last| height.saturating_sub(last) >= self.update_period(height))
+	}
+}
@@ -0,0 +1,67 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Coalescing of the optional client updates of a chain.
+//!
+//! Optional updates that don't prove any packet event are held instead of being submitted. Each
+//! one supersedes the held update, whose header is dropped, and the latest one is submitted once
+//! the first of them was held for the
+//! [`optional_update_window`](crate::CommonClientConfig::optional_update_window), or as soon as
+//! other messages are submitted to the counterparty.
+
+use ibc::Height;
+use ibc_proto::google::protobuf::Any;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct HeldUpdate {
+	msg: Any,
+	height: Height,
+	/// When the first update of the run of held updates was held.
+	since: Instant,
+}
+
+/// Optional client update of a chain held back from submission, if any.
+#[derive(Debug, Default)]
+pub struct UpdateCoalescer {
+	held: Option<HeldUpdate>,
+}
+
+impl UpdateCoalescer {
+	/// Holds the update `msg` at `height`, returning the height of the update it supersedes.
+	pub fn hold(&mut self, msg: Any, height: Height) -> Option<Height> {
+		let previous = self.held.take();
+		let since = previous.as_ref().map(|held| held.since).unwrap_or_else(Instant::now);
+		self.held = Some(HeldUpdate { msg, height, since });
+		previous.map(|held| held.height)
+	}
+
+	/// Takes the held update, if any.
+	pub fn take(&mut self) -> Option<(Any, Height)> {
+		self.held.take().map(|held| (held.msg, held.height))
+	}
+
+	/// Takes the held update if updates have been held for at least `window`.
+	pub fn take_expired(&mut self, window: Duration) -> Option<(Any, Height)> {
+		match &self.held {
+			Some(held) if held.since.elapsed() >= window => self.take(),
+			_ => None,
+		}
+	}
+
+	/// Height of the held update, if any.
+	pub fn held_height(&self) -> Option<Height> {
+		self.held.as_ref().map(|held| held.height)
+	}
+}
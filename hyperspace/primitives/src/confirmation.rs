@@ -0,0 +1,63 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Updates of a chain waiting for enough finalized blocks to be built on top of their block.
+//!
+//! Some RPC nodes briefly report finalized heads that don't end up canonical. With a
+//! [`CommonClientConfig::confirmation_depth`](crate::CommonClientConfig::confirmation_depth) of
+//! `N`, the client updates of a chain and the events they prove are buffered by height until `N`
+//! more blocks are finalized, and only then relayed.
+
+use crate::UpdateType;
+use ibc::{events::IbcEvent, Height};
+use ibc_proto::google::protobuf::Any;
+use std::collections::BTreeMap;
+
+/// A client update and the events it proves, as returned by
+/// [`FinalitySource::query_latest_ibc_events`](crate::FinalitySource::query_latest_ibc_events).
+pub type IbcUpdate = (Any, Height, Vec<IbcEvent>, UpdateType);
+
+/// Buffered updates of a chain, by height.
+#[derive(Debug, Default)]
+pub struct ConfirmationBuffer {
+	updates: BTreeMap<u64, IbcUpdate>,
+}
+
+impl ConfirmationBuffer {
+	/// Buffers `updates`. They replace the buffered updates at the same heights, which may have
+	/// been queried from a head that wasn't canonical.
+	pub fn push(&mut self, updates: impl IntoIterator<Item = IbcUpdate>) {
+		for update in updates {
+			self.updates.insert(update.1.revision_height, update);
+		}
+	}
+
+	/// Removes and returns the updates at or below `height`, by increasing height.
+	pub fn take_confirmed(&mut self, height: u64) -> Vec<IbcUpdate> {
+		let pending = match height.checked_add(1) {
+			Some(next) => self.updates.split_off(&next),
+			None => BTreeMap::new(),
+		};
+		std::mem::replace(&mut self.updates, pending).into_values().collect()
+	}
+
+	/// Number of buffered updates.
+	pub fn len(&self) -> usize {
+		self.updates.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.updates.is_empty()
+	}
+}
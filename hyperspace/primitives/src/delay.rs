@@ -0,0 +1,86 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracking of the connection delay periods.
+//!
+//! Messages proven at a height of the counterparty chain are only accepted on a connection with a
+//! delay period once both the time and block delays have elapsed since the client was updated to
+//! that height. The deadlines are remembered per connection and proof height, so that the messages
+//! waiting for them are withheld without querying the client update again on every block.
+
+use ibc::{core::ics24_host::identifier::ConnectionId, timestamp::Timestamp, Height};
+use std::{
+	collections::{BTreeMap, HashMap},
+	time::Duration,
+};
+
+/// Maximum number of proof heights whose deadline is remembered per connection.
+pub const MAX_TRACKED_PROOF_HEIGHTS: usize = 256;
+
+/// Earliest time and height of a chain at which the messages proven at some height can be
+/// submitted to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DelayDeadline {
+	pub earliest_time: Timestamp,
+	pub earliest_height: Height,
+}
+
+impl DelayDeadline {
+	/// Deadline of the messages proven at a consensus height that was installed in the client at
+	/// `update_time` and `update_height`.
+	pub fn new(
+		update_time: Timestamp,
+		update_height: Height,
+		delay_period_time: Duration,
+		delay_period_blocks: u64,
+	) -> Result<Self, anyhow::Error> {
+		let earliest_time =
+			(update_time + delay_period_time).map_err(|_| anyhow::anyhow!("Timestamp overflow"))?;
+		Ok(Self { earliest_time, earliest_height: update_height.add(delay_period_blocks) })
+	}
+
+	/// Whether both delays have elapsed at `time` and `height`.
+	pub fn has_elapsed(&self, time: Timestamp, height: Height) -> bool {
+		(time == self.earliest_time || time.after(&self.earliest_time)) &&
+			height >= self.earliest_height
+	}
+}
+
+/// Delay deadlines on the connections of a chain, keyed by the connection on this chain and the
+/// proof height of the counterparty chain.
+#[derive(Debug, Default)]
+pub struct ConnectionDelays {
+	deadlines: HashMap<ConnectionId, BTreeMap<Height, DelayDeadline>>,
+}
+
+impl ConnectionDelays {
+	pub fn get(&self, connection_id: &ConnectionId, proof_height: Height) -> Option<DelayDeadline> {
+		self.deadlines.get(connection_id)?.get(&proof_height).copied()
+	}
+
+	/// Remembers the deadline of the messages proven at `proof_height`. The lowest proof heights
+	/// of the connection are forgotten beyond [`MAX_TRACKED_PROOF_HEIGHTS`].
+	pub fn insert(
+		&mut self,
+		connection_id: ConnectionId,
+		proof_height: Height,
+		deadline: DelayDeadline,
+	) {
+		let deadlines = self.deadlines.entry(connection_id).or_default();
+		deadlines.insert(proof_height, deadline);
+		while deadlines.len() > MAX_TRACKED_PROOF_HEIGHTS {
+			deadlines.pop_first();
+		}
+	}
+}
@@ -0,0 +1,141 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ICS-29 relayer incentivization.
+//!
+//! Packets sent over channels with the fee middleware can be incentivized by escrowing fees that
+//! are paid to the relayers delivering them. The receive fee goes to the *counterparty payee*
+//! that the relayer submitting the `MsgRecvPacket` registered on the destination chain, the
+//! acknowledgement and timeout fees go to the relayer submitting the `MsgAcknowledgement` or
+//! `MsgTimeout` on the source chain. The middleware embeds the forward relayer address in the
+//! acknowledgement itself, so acknowledgements are relayed unchanged to collect the fees.
+//!
+//! The relayer registers its counterparty payee for the whitelisted channels on startup (see
+//! [`register_counterparty_payee`]), records the `incentivized_ibc_packet` events of each chain in
+//! its [`IncentivizedPackets`] and relays the incentivized packets before the other ones.
+
+use crate::Chain;
+use ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use ibc_proto::{google::protobuf::Any, ibc::applications::fee::v1::MsgRegisterCounterpartyPayee};
+use prost::Message;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Type of the event emitted when a fee is paid for a packet.
+pub const INCENTIVIZED_PACKET_EVENT: &str = "incentivized_ibc_packet";
+
+pub const REGISTER_COUNTERPARTY_PAYEE_TYPE_URL: &str =
+	ibc_proto_registry::type_url::MSG_REGISTER_COUNTERPARTY_PAYEE;
+
+/// Maximum number of incentivized packets remembered per chain. The packets with the lowest
+/// sequences are forgotten first.
+const MAX_INCENTIVIZED_PACKETS: usize = 10_000;
+
+/// Total fees escrowed for a packet, as reported by the chain (e.g. `"100stake,10uatom"`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PacketFees {
+	pub recv_fee: String,
+	pub ack_fee: String,
+	pub timeout_fee: String,
+}
+
+/// Packets sent from a chain that are incentivized, by their source port, channel and sequence.
+#[derive(Debug, Default)]
+pub struct IncentivizedPackets {
+	packets: BTreeMap<(PortId, ChannelId, u64), PacketFees>,
+}
+
+impl IncentivizedPackets {
+	/// Records the total fees of a packet, replacing the previously recorded ones.
+	pub fn insert(
+		&mut self,
+		port_id: PortId,
+		channel_id: ChannelId,
+		sequence: u64,
+		fees: PacketFees,
+	) {
+		self.packets.insert((port_id, channel_id, sequence), fees);
+		while self.packets.len() > MAX_INCENTIVIZED_PACKETS {
+			let Some(key) = self.packets.keys().next().cloned() else { break };
+			self.packets.remove(&key);
+		}
+	}
+
+	pub fn get(
+		&self,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: u64,
+	) -> Option<&PacketFees> {
+		self.packets.get(&(port_id.clone(), *channel_id, sequence))
+	}
+
+	pub fn is_incentivized(&self, port_id: &PortId, channel_id: &ChannelId, sequence: u64) -> bool {
+		self.get(port_id, channel_id, sequence).is_some()
+	}
+
+	/// Moves the incentivized sequences of the channel to the front of `sequences`, keeping the
+	/// order of the others.
+	pub fn prioritize(&self, port_id: &PortId, channel_id: &ChannelId, sequences: &mut [u64]) {
+		sequences.sort_by_key(|&sequence| !self.is_incentivized(port_id, channel_id, sequence));
+	}
+
+	pub fn len(&self) -> usize {
+		self.packets.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.packets.is_empty()
+	}
+}
+
+/// Registers the counterparty payee configured for `chain` on all of its whitelisted channels, so
+/// that the receive fees of the packets relayed to `chain` are paid on the counterparty. Does
+/// nothing if no payee is configured.
+pub async fn register_counterparty_payee(chain: &impl Chain) -> anyhow::Result<()> {
+	let Some(counterparty_payee) = chain.common_state().counterparty_payee.clone() else {
+		return Ok(())
+	};
+	let relayer = chain.account_id().to_string();
+	let msgs = chain
+		.channel_whitelist()
+		.into_iter()
+		.map(|(channel_id, port_id)| {
+			let msg = MsgRegisterCounterpartyPayee {
+				port_id: port_id.to_string(),
+				channel_id: channel_id.to_string(),
+				relayer: relayer.clone(),
+				counterparty_payee: counterparty_payee.clone(),
+			};
+			Any {
+				type_url: REGISTER_COUNTERPARTY_PAYEE_TYPE_URL.to_string(),
+				value: msg.encode_to_vec(),
+			}
+		})
+		.collect::<Vec<_>>();
+	if msgs.is_empty() {
+		return Ok(())
+	}
+	if chain.common_state().dry_run {
+		log::info!(target: "hyperspace", "Dry run: not registering {counterparty_payee} as the counterparty payee on {}", chain.name());
+		return Ok(())
+	}
+	chain.submit(msgs).await?;
+	log::info!(
+		target: "hyperspace",
+		"Registered {counterparty_payee} as the counterparty payee of {relayer} on {}",
+		chain.name()
+	);
+	Ok(())
+}
@@ -0,0 +1,161 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Filtering of the events relayed from a chain.
+//!
+//! The channel whitelist selects the channels whose packets are tracked, the event filter selects
+//! which of the events of the chain are turned into messages for its counterparty. An event is
+//! relayed if no `deny` rule matches it and, when there are `allow` rules, one of them does. The
+//! filter is evaluated on the events of the finality notifications and on the packets found by
+//! the periodic scan of the whitelisted channels, before any message is constructed, so that e.g.
+//! the transfer packets of a connection are relayed while its ping-pong packets are ignored.
+
+use ibc::{
+	core::ics24_host::identifier::{ChannelId, ClientId, PortId},
+	events::{IbcEvent, IbcEventType},
+};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A rule matching events by their type, port, channel and client. An empty list matches any
+/// value, a non-empty one only the events with one of its values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventRule {
+	/// Types of the events, e.g. `send_packet`, `write_acknowledgement` or `channel_open_init`.
+	#[serde(default)]
+	pub event_types: Vec<String>,
+	/// Ports of the events on this chain, i.e. the source port of sent packets and the
+	/// destination port of acknowledged ones.
+	#[serde(default)]
+	pub ports: Vec<PortId>,
+	/// Channels of the events on this chain.
+	#[serde(default)]
+	pub channels: Vec<ChannelId>,
+	/// Clients of the client and connection events. Channel and packet events don't match a rule
+	/// with clients.
+	#[serde(default)]
+	pub clients: Vec<ClientId>,
+}
+
+impl EventRule {
+	fn matches(&self, event: &EventAttributes) -> bool {
+		(self.event_types.is_empty() ||
+			self.event_types
+				.iter()
+				.any(|event_type| event_type == event.event_type.as_str())) &&
+			matches_any(&self.ports, event.port_id.as_ref()) &&
+			matches_any(&self.channels, event.channel_id.as_ref()) &&
+			matches_any(&self.clients, event.client_id.as_ref())
+	}
+}
+
+fn matches_any<T: PartialEq>(values: &[T], value: Option<&T>) -> bool {
+	values.is_empty() || value.map_or(false, |value| values.contains(value))
+}
+
+/// Allow and deny lists of the events relayed from a chain. Everything is relayed by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventFilter {
+	/// Rules of the relayed events. All events are relayed if empty.
+	#[serde(default)]
+	pub allow: Vec<EventRule>,
+	/// Rules of the events that are never relayed, even when they're allowed.
+	#[serde(default)]
+	pub deny: Vec<EventRule>,
+}
+
+impl EventFilter {
+	/// Checks that the event types of the rules exist.
+	pub fn validate(&self) -> anyhow::Result<()> {
+		for rule in self.allow.iter().chain(&self.deny) {
+			for event_type in &rule.event_types {
+				if IbcEventType::from_str(event_type).is_err() {
+					anyhow::bail!("Unknown event type `{event_type}` in the event filter")
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Whether messages are constructed from `event`.
+	pub fn allows(&self, event: &IbcEvent) -> bool {
+		self.allows_attributes(&EventAttributes::of(event))
+	}
+
+	/// Whether the packets of a channel found by the periodic scan are relayed, as if they were
+	/// reported by an event of `event_type`: `SendPacket` for the receipts and timeouts, `WriteAck`
+	/// for the acknowledgements.
+	pub fn allows_packets(
+		&self,
+		event_type: IbcEventType,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+	) -> bool {
+		self.allows_attributes(&EventAttributes {
+			event_type,
+			port_id: Some(port_id.clone()),
+			channel_id: Some(*channel_id),
+			client_id: None,
+		})
+	}
+
+	fn allows_attributes(&self, event: &EventAttributes) -> bool {
+		!self.deny.iter().any(|rule| rule.matches(event)) &&
+			(self.allow.is_empty() || self.allow.iter().any(|rule| rule.matches(event)))
+	}
+}
+
+/// The attributes of an event the rules match on.
+struct EventAttributes {
+	event_type: IbcEventType,
+	port_id: Option<PortId>,
+	channel_id: Option<ChannelId>,
+	client_id: Option<ClientId>,
+}
+
+impl EventAttributes {
+	fn of(event: &IbcEvent) -> Self {
+		let (port_id, channel_id) = match event {
+			IbcEvent::OpenInitChannel(ev) => (Some(ev.port_id()), ev.channel_id()),
+			IbcEvent::OpenTryChannel(ev) => (Some(ev.port_id()), ev.channel_id()),
+			IbcEvent::OpenAckChannel(ev) => (Some(ev.port_id()), ev.channel_id()),
+			IbcEvent::OpenConfirmChannel(ev) => (Some(ev.port_id()), ev.channel_id()),
+			IbcEvent::CloseInitChannel(ev) => (Some(ev.port_id()), Some(ev.channel_id())),
+			IbcEvent::CloseConfirmChannel(ev) => (Some(&ev.port_id), ev.channel_id()),
+			IbcEvent::ReceivePacket(_) | IbcEvent::WriteAcknowledgement(_) => event
+				.packet()
+				.map(|packet| (Some(&packet.destination_port), Some(&packet.destination_channel)))
+				.unwrap_or_default(),
+			_ => event
+				.packet()
+				.map(|packet| (Some(&packet.source_port), Some(&packet.source_channel)))
+				.unwrap_or_default(),
+		};
+		let client_id = match event {
+			IbcEvent::CreateClient(ev) => Some(ev.client_id()),
+			IbcEvent::UpdateClient(ev) => Some(ev.client_id()),
+			IbcEvent::UpgradeClient(ev) => Some(ev.client_id()),
+			IbcEvent::ClientMisbehaviour(ev) => Some(ev.client_id()),
+			_ => event.connection_attributes().map(|attributes| &attributes.client_id),
+		};
+		Self {
+			event_type: event.event_type(),
+			port_id: port_id.cloned(),
+			channel_id: channel_id.copied(),
+			client_id: client_id.cloned(),
+		}
+	}
+}
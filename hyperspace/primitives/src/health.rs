@@ -0,0 +1,249 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Health probes used to detect a stalled chain before submissions to it start failing, and
+//! health checks of everything a chain needs to be relayed.
+
+use crate::Chain;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use ibc::{
+	applications::transfer::Amount,
+	core::{
+		ics02_client::{client_consensus::ConsensusState, client_state::ClientState},
+		ics24_host::identifier::ClientId,
+	},
+};
+use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// State of a chain as observed by a health probe.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthProbe {
+	/// Seconds since the last finality event of the chain, if one was received.
+	pub finality_event_age: Option<u64>,
+	/// Height of the best block.
+	pub best_height: u64,
+	/// Height of the latest finalized block.
+	pub finalized_height: u64,
+	/// Time the RPC node took to answer the height queries, in milliseconds.
+	pub rpc_latency_ms: u64,
+}
+
+/// Limits above which a chain is considered stalled.
+#[derive(Debug, Clone, Copy)]
+pub struct HealthThresholds {
+	/// Maximum time since the last finality event.
+	pub max_finality_event_age: Duration,
+	/// Maximum number of blocks between the best and the finalized block.
+	pub max_finality_gap: u64,
+	/// Maximum time the RPC node may take to answer the height queries.
+	pub max_rpc_latency: Duration,
+}
+
+impl Default for HealthThresholds {
+	fn default() -> Self {
+		Self {
+			max_finality_event_age: Duration::from_secs(600),
+			max_finality_gap: 100,
+			max_rpc_latency: Duration::from_secs(10),
+		}
+	}
+}
+
+/// Why a chain is considered stalled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum StallReason {
+	/// The RPC node can't be queried.
+	Unreachable,
+	/// No finality event was received for too long.
+	FinalityStalled,
+	/// Finality lags too far behind the best block.
+	FinalityLagging,
+	/// The RPC node is too slow to answer.
+	SlowRpc,
+}
+
+impl std::fmt::Display for StallReason {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			StallReason::Unreachable => write!(f, "rpc node is unreachable"),
+			StallReason::FinalityStalled => write!(f, "finality stream is not progressing"),
+			StallReason::FinalityLagging => write!(f, "finality lags behind the best block"),
+			StallReason::SlowRpc => write!(f, "rpc node is too slow"),
+		}
+	}
+}
+
+impl HealthProbe {
+	/// Returns why the probed chain is stalled according to `thresholds`, if it is.
+	pub fn stall_reason(&self, thresholds: &HealthThresholds) -> Option<StallReason> {
+		if self
+			.finality_event_age
+			.map_or(false, |age| age > thresholds.max_finality_event_age.as_secs())
+		{
+			Some(StallReason::FinalityStalled)
+		} else if self.best_height.saturating_sub(self.finalized_height) >
+			thresholds.max_finality_gap
+		{
+			Some(StallReason::FinalityLagging)
+		} else if self.rpc_latency_ms > thresholds.max_rpc_latency.as_millis() as u64 {
+			Some(StallReason::SlowRpc)
+		} else {
+			None
+		}
+	}
+}
+
+/// Result of a [`ChainHealth::health_check`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HealthReport {
+	/// State of the chain, `None` if its RPC node couldn't be queried.
+	pub probe: Option<HealthProbe>,
+	/// Why the chain is stalled, if it is.
+	pub stall_reason: Option<StallReason>,
+	/// Addresses of the relayer keys whose balance is below the `min_balance` of the chain.
+	pub low_balance_keys: Vec<String>,
+	/// Whether the client of the chain on the counterparty is frozen.
+	pub client_frozen: bool,
+	/// Seconds left before the client of the chain on the counterparty expires, if it was checked.
+	pub client_expires_in: Option<u64>,
+}
+
+impl HealthReport {
+	/// Whether nothing prevents the chain from being relayed.
+	pub fn is_healthy(&self) -> bool {
+		self.problems().is_empty()
+	}
+
+	/// Describes what prevents the chain from being relayed.
+	pub fn problems(&self) -> Vec<String> {
+		let mut problems = vec![];
+		if let Some(reason) = self.stall_reason {
+			problems.push(reason.to_string());
+		}
+		if !self.low_balance_keys.is_empty() {
+			problems.push(format!(
+				"balance of {} is below the minimum",
+				self.low_balance_keys.join(", ")
+			));
+		}
+		if self.client_frozen {
+			problems.push("client on the counterparty is frozen".to_string());
+		} else if self.client_expires_in == Some(0) {
+			problems.push("client on the counterparty expired".to_string());
+		}
+		problems
+	}
+}
+
+/// Health probes of a [`Chain`], implemented for every chain on top of
+/// [`crate::IbcQuerier::query_best_and_finalized_heights`] and the relay status in its
+/// [`crate::CommonClientState`].
+#[async_trait]
+pub trait ChainHealth: Chain {
+	/// Probes the chain, measuring how long its RPC node takes to answer.
+	async fn probe_health(&self) -> Result<HealthProbe, Self::Error> {
+		let started = Instant::now();
+		let (best_height, finalized_height) = self.query_best_and_finalized_heights().await?;
+		let rpc_latency_ms = started.elapsed().as_millis() as u64;
+		let finality_event_age = self
+			.common_state()
+			.relay_status()
+			.last_finality_event
+			.map(|last| crate::unix_timestamp().saturating_sub(last));
+		Ok(HealthProbe { finality_event_age, best_height, finalized_height, rpc_latency_ms })
+	}
+
+	/// Probes the chain and returns why it's stalled according to `thresholds`, if it is.
+	async fn check_stalled(&self, thresholds: &HealthThresholds) -> Option<StallReason> {
+		match self.probe_health().await {
+			Ok(probe) => {
+				log::trace!(target: "hyperspace", "Health of {}: {:?}", self.name(), probe);
+				probe.stall_reason(thresholds)
+			},
+			Err(e) => {
+				log::warn!(target: "hyperspace", "Failed to probe the health of {}: {:?}", self.name(), e);
+				Some(StallReason::Unreachable)
+			},
+		}
+	}
+
+	/// Checks that the RPC node of the chain is reachable, that its finality stream progresses
+	/// according to `thresholds`, that the relayer keys are funded and that the client of the
+	/// chain on `counterparty` is neither frozen nor expired. Fails if the balances or the client
+	/// can't be queried.
+	async fn health_check<C: Chain>(
+		&self,
+		counterparty: &C,
+		thresholds: &HealthThresholds,
+	) -> anyhow::Result<HealthReport> {
+		let probe = match self.probe_health().await {
+			Ok(probe) => probe,
+			Err(e) => {
+				log::debug!(target: "hyperspace", "Failed to probe the health of {}: {:?}", self.name(), e);
+				let stall_reason = Some(StallReason::Unreachable);
+				return Ok(HealthReport { stall_reason, ..Default::default() })
+			},
+		};
+
+		let low_balance_keys = match self.common_state().min_balance() {
+			Some(min_balance) => self
+				.query_key_balances()
+				.await?
+				.into_iter()
+				.filter(|(_, balance)| balance.amount < Amount::from(min_balance))
+				.map(|(address, _)| address)
+				.collect(),
+			None => vec![],
+		};
+
+		let (client_state, elapsed) = query_client_age(counterparty, self.client_id()).await?;
+		let window = self.security_params().expiry_window(&client_state);
+		Ok(HealthReport {
+			stall_reason: probe.stall_reason(thresholds),
+			probe: Some(probe),
+			low_balance_keys,
+			client_frozen: client_state.frozen_height().is_some(),
+			client_expires_in: Some(window.saturating_sub(elapsed).as_secs()),
+		})
+	}
+}
+
+impl<T: Chain> ChainHealth for T {}
+
+/// Returns the state of the client `client_id` hosted on `host`, and the time elapsed on `host`
+/// since the client's latest consensus state.
+pub async fn query_client_age(
+	host: &impl Chain,
+	client_id: ClientId,
+) -> anyhow::Result<(AnyClientState, Duration)> {
+	let (height, timestamp) = host.latest_height_and_timestamp().await?;
+	let response = host.query_client_state(height, client_id.clone()).await?;
+	let client_state = response
+		.client_state
+		.ok_or_else(|| anyhow!("Client state {client_id} not found on {}", host.name()))
+		.and_then(|state| AnyClientState::try_from(state).map_err(|e| anyhow!("{e:?}")))?;
+
+	let response = host
+		.query_client_consensus(height, client_id.clone(), client_state.latest_height())
+		.await?;
+	let consensus_state = response
+		.consensus_state
+		.ok_or_else(|| anyhow!("Consensus state of {client_id} not found on {}", host.name()))
+		.and_then(|state| AnyConsensusState::try_from(state).map_err(|e| anyhow!("{e:?}")))?;
+	let elapsed = timestamp.duration_since(&consensus_state.timestamp()).unwrap_or_default();
+	Ok((client_state, elapsed))
+}
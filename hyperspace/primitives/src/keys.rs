@@ -0,0 +1,109 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rotation of the submissions to a chain between several relayer keys.
+//!
+//! The transactions of a single account are ordered by its nonce, which bounds the throughput of
+//! the relayer on busy paths. When several funded keys are configured for a chain, every
+//! submission is signed with the next key in turn, and each key has its own
+//! [`SubmissionCoordinator`], so that their nonces are tracked independently. Keys whose balance
+//! is below the chain's `min_balance` are left out of the rotation until they're refilled.
+
+use crate::nonce::{SubmissionCoordinator, DEFAULT_MAX_CONCURRENT_SUBMISSIONS};
+use std::sync::{
+	atomic::{AtomicBool, AtomicUsize, Ordering},
+	Arc,
+};
+
+#[derive(Debug)]
+struct KeyState {
+	submissions: SubmissionCoordinator,
+	/// Whether the balance of the key was below `min_balance` when last checked.
+	low_balance: AtomicBool,
+}
+
+/// Hands out the relayer keys of a chain in round-robin order. Keys are identified by their index
+/// in the chain's configuration, the primary key being `0`. Clones share the same state.
+#[derive(Debug, Clone)]
+pub struct KeyRotation {
+	keys: Arc<[KeyState]>,
+	next: Arc<AtomicUsize>,
+	max_concurrent: usize,
+}
+
+impl Default for KeyRotation {
+	fn default() -> Self {
+		Self::new(1, DEFAULT_MAX_CONCURRENT_SUBMISSIONS)
+	}
+}
+
+impl KeyRotation {
+	/// Creates a rotation between `keys` keys, each allowing up to `max_concurrent` transactions
+	/// in flight.
+	pub fn new(keys: usize, max_concurrent: usize) -> Self {
+		let keys = (0..keys.max(1))
+			.map(|_| KeyState {
+				submissions: SubmissionCoordinator::new(max_concurrent),
+				low_balance: Default::default(),
+			})
+			.collect();
+		Self { keys, next: Default::default(), max_concurrent }
+	}
+
+	/// Creates a rotation between `keys` keys with the same submission limits, for when the keys
+	/// of the chain are reloaded. The state of the current keys isn't carried over.
+	pub fn with_key_count(&self, keys: usize) -> Self {
+		Self::new(keys, self.max_concurrent)
+	}
+
+	/// Number of keys in the rotation.
+	pub fn key_count(&self) -> usize {
+		self.keys.len()
+	}
+
+	/// Returns the key the next transaction should be signed with. Keys with a low balance are
+	/// skipped, unless all of them have one.
+	pub fn next_key(&self) -> usize {
+		let len = self.keys.len();
+		let start = self.next.fetch_add(1, Ordering::Relaxed) % len;
+		(0..len)
+			.map(|offset| (start + offset) % len)
+			.find(|&key| !self.has_low_balance(key))
+			.unwrap_or(start)
+	}
+
+	/// Coordinator of the transactions signed with `key`.
+	pub fn submissions(&self, key: usize) -> &SubmissionCoordinator {
+		&self.keys[key].submissions
+	}
+
+	/// Records whether the balance of `key` is below `min_balance`. Returns the previous value.
+	pub fn set_low_balance(&self, key: usize, low: bool) -> bool {
+		self.keys[key].low_balance.swap(low, Ordering::SeqCst)
+	}
+
+	pub fn has_low_balance(&self, key: usize) -> bool {
+		self.keys[key].low_balance.load(Ordering::SeqCst)
+	}
+
+	/// Whether the balances of all the keys are below `min_balance`.
+	pub fn all_low_balance(&self) -> bool {
+		(0..self.keys.len()).all(|key| self.has_low_balance(key))
+	}
+
+	/// Number of submissions waiting for a slot or in flight, over all the keys.
+	pub fn queue_depth(&self) -> usize {
+		self.keys.iter().map(|key| key.submissions.queue_depth()).sum()
+	}
+}
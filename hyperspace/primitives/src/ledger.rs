@@ -0,0 +1,151 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ledger of the packets the relayer observed and delivered, kept as an audit trail of the
+//! bridged value.
+//!
+//! Every packet sent from a relayed chain, and every receipt, acknowledgement and timeout the
+//! relayer submitted, is appended to a [`PacketLedger`] file, one JSON object per line. The
+//! ledger is never rewritten: the entries added when reconciling it with the chains are marked
+//! as such.
+
+use crate::unix_timestamp;
+use anyhow::anyhow;
+use ibc::core::{
+	ics04_channel::packet::Packet,
+	ics24_host::identifier::{ChannelId, PortId},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+	io::{BufRead, BufReader, Write},
+	path::PathBuf,
+	sync::{Arc, Mutex},
+};
+
+/// Stage of the lifecycle of a packet recorded by a [`LedgerEntry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PacketStatus {
+	/// The packet was sent from `chain`.
+	Observed,
+	/// The packet was delivered to `chain` with a `MsgRecvPacket`.
+	Relayed,
+	/// The acknowledgement of the packet was delivered to `chain`, the packet's source.
+	Acknowledged,
+	/// The timeout of the packet was delivered to `chain`, the packet's source.
+	TimedOut,
+}
+
+impl PacketStatus {
+	/// Whether the packet was sent from the chain of an entry with this status, as opposed to
+	/// received on it.
+	pub fn is_on_source(&self) -> bool {
+		*self != PacketStatus::Relayed
+	}
+}
+
+/// A packet event recorded in the ledger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+	/// Chain the packet was observed on, or the message was submitted to.
+	pub chain: String,
+	pub status: PacketStatus,
+	pub source_port: PortId,
+	pub source_channel: ChannelId,
+	pub destination_port: PortId,
+	pub destination_channel: ChannelId,
+	pub sequence: u64,
+	/// Transaction of the submission that delivered the message, if it was relayed.
+	#[serde(default)]
+	pub tx_hash: Option<String>,
+	/// Unix timestamp of the entry, in seconds.
+	pub timestamp: u64,
+	/// Whether the entry was added by a reconciliation with the chains, rather than recorded by
+	/// the relayer.
+	#[serde(default)]
+	pub reconciled: bool,
+}
+
+impl LedgerEntry {
+	pub fn new(
+		chain: &str,
+		status: PacketStatus,
+		packet: &Packet,
+		tx_hash: Option<String>,
+	) -> Self {
+		Self {
+			chain: chain.to_string(),
+			status,
+			source_port: packet.source_port.clone(),
+			source_channel: packet.source_channel,
+			destination_port: packet.destination_port.clone(),
+			destination_channel: packet.destination_channel,
+			sequence: packet.sequence.into(),
+			tx_hash,
+			timestamp: unix_timestamp(),
+			reconciled: false,
+		}
+	}
+}
+
+/// Append-only file of ledger entries, one JSON object per line.
+#[derive(Debug, Clone)]
+pub struct PacketLedger {
+	path: PathBuf,
+	lock: Arc<Mutex<()>>,
+}
+
+impl PacketLedger {
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		Self { path: path.into(), lock: Default::default() }
+	}
+
+	pub fn append(&self, entries: &[LedgerEntry]) -> anyhow::Result<()> {
+		if entries.is_empty() {
+			return Ok(())
+		}
+		let _guard = self.lock.lock().unwrap();
+		let mut file =
+			std::fs::OpenOptions::new().create(true).append(true).open(&self.path).map_err(
+				|e| anyhow!("Failed to open packet ledger {}: {e}", self.path.display()),
+			)?;
+		let mut lines = vec![];
+		for entry in entries {
+			lines.extend(serde_json::to_vec(entry)?);
+			lines.push(b'\n');
+		}
+		file.write_all(&lines)?;
+		Ok(())
+	}
+
+	/// Returns all the entries of the ledger, oldest first.
+	pub fn entries(&self) -> anyhow::Result<Vec<LedgerEntry>> {
+		let _guard = self.lock.lock().unwrap();
+		if !self.path.exists() {
+			return Ok(vec![])
+		}
+		let file = std::fs::File::open(&self.path)?;
+		let mut entries = vec![];
+		for line in BufReader::new(file).lines() {
+			let line = line?;
+			if line.trim().is_empty() {
+				continue
+			}
+			let entry = serde_json::from_str::<LedgerEntry>(&line)
+				.map_err(|e| anyhow!("Invalid entry in {}: {e}", self.path.display()))?;
+			entries.push(entry);
+		}
+		Ok(entries)
+	}
+}
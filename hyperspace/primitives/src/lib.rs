@@ -27,17 +27,20 @@ use ibc_proto::{
 		connection::v1::QueryConnectionResponse,
 	},
 };
-use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::{
 	collections::{HashMap, HashSet},
 	fmt::Debug,
+	path::PathBuf,
 	pin::Pin,
 	str::FromStr,
-	sync::{Arc, Mutex},
-	time::Duration,
+	sync::{
+		atomic::{AtomicBool, AtomicU64, Ordering},
+		Arc, Mutex,
+	},
+	time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use tokio::{sync::Mutex as AsyncMutex, task::JoinSet, time::sleep};
+use tokio::sync::Mutex as AsyncMutex;
 
 use crate::error::Error;
 #[cfg(any(feature = "testing", test))]
@@ -52,7 +55,6 @@ use ibc::{
 		},
 		ics04_channel::{
 			channel::{ChannelEnd, Order},
-			context::calculate_block_delay,
 			packet::Packet,
 		},
 		ics23_commitment::commitment::CommitmentPrefix,
@@ -69,10 +71,49 @@ use ibc_proto::ibc::core::{
 use ibc_rpc::PacketInfo;
 use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState};
 
+pub mod adaptive;
+pub mod coalesce;
+pub mod confirmation;
+pub mod delay;
 pub mod error;
+pub mod fee;
+pub mod filter;
+pub mod health;
+pub mod keys;
+pub mod ledger;
 pub mod mock;
+pub mod nonce;
+pub mod pending;
+pub mod pfm;
+pub mod pipeline;
+pub mod receipt;
+pub mod relayed;
+pub mod security;
+pub mod signing;
+pub mod snapshot;
+pub mod timeouts;
+pub mod upgrade;
 pub mod utils;
 
+pub use adaptive::{AdaptiveUpdateParams, AdaptiveUpdateScheduler};
+pub use coalesce::UpdateCoalescer;
+pub use confirmation::ConfirmationBuffer;
+pub use delay::{ConnectionDelays, DelayDeadline};
+pub use fee::{IncentivizedPackets, PacketFees};
+pub use filter::{EventFilter, EventRule};
+pub use health::{ChainHealth, HealthProbe, HealthReport, HealthThresholds, StallReason};
+pub use keys::KeyRotation;
+pub use ledger::{LedgerEntry, PacketLedger, PacketStatus};
+pub use nonce::SubmissionCoordinator;
+pub use pending::PendingMessages;
+pub use pipeline::{ChannelCounters, PipelineCounters};
+pub use receipt::{PayloadSignature, ReceiptStore, RelayReceipt};
+pub use relayed::{PacketDirection, RelayedSequence, RelayedSequences};
+pub use security::{MisbehaviourPolicy, SecurityParams};
+pub use snapshot::{ChainSnapshot, RelayerSnapshot};
+pub use timeouts::{ChannelTimeouts, PendingTimeouts};
+pub use upgrade::UpgradedClient;
+
 pub enum UpdateMessage {
 	Single(Any),
 	Batch(Vec<Any>),
@@ -99,8 +140,12 @@ fn default_skip_optional_client_updates() -> bool {
 	true
 }
 
-fn max_packets_to_process() -> u32 {
-	50
+fn relayed_sequences_retention() -> u64 {
+	relayed::DEFAULT_RELAYED_SEQUENCES_RETENTION
+}
+
+fn max_concurrent_submissions() -> usize {
+	nonce::DEFAULT_MAX_CONCURRENT_SUBMISSIONS
 }
 
 // TODO: move other fields like `client_id`, `connection_id`, etc. here
@@ -110,8 +155,142 @@ pub struct CommonClientConfig {
 	/// Skip optional client updates
 	#[serde(default = "default_skip_optional_client_updates")]
 	pub skip_optional_client_updates: bool,
-	#[serde(default = "max_packets_to_process")]
-	pub max_packets_to_process: u32,
+	/// Maximum number of packets relayed per batch. Defaults to the
+	/// `DEFAULT_MAX_PACKETS_TO_PROCESS` of the chain's client: 100 on parachains and 50 on cosmos
+	/// chains.
+	#[serde(default)]
+	pub max_packets_to_process: Option<u32>,
+	/// Skip transfer packets with the following tokens base denoms
+	#[serde(default)]
+	pub skip_tokens_list: Option<Vec<String>>,
+	/// Minimum balance of each relayer key, in the smallest unit of the token used to pay
+	/// transaction fees. Keys below it are left out of the rotation, and submissions are halted
+	/// while all of them are.
+	#[serde(default)]
+	pub min_balance: Option<u64>,
+	/// Number of blocks the sequences relayed from this chain are remembered for, so that they
+	/// aren't relayed again by overlapping finality events. `0` disables the cache.
+	#[serde(default = "relayed_sequences_retention")]
+	pub relayed_sequences_retention: u64,
+	/// File the relayed sequences are persisted to, so that they're remembered across restarts.
+	#[serde(default)]
+	pub relayed_sequences_path: Option<PathBuf>,
+	/// File the message batches being submitted to this chain are journaled to, so that the ones
+	/// interrupted by a crash are re-validated and submitted again on restart.
+	#[serde(default)]
+	pub pending_messages_path: Option<PathBuf>,
+	/// Maximum number of transactions of each relayer key in flight on this chain. `1`
+	/// serializes the submissions of a key, higher values pipeline them with consecutive nonces.
+	#[serde(default = "max_concurrent_submissions")]
+	pub max_concurrent_submissions: usize,
+	/// Number of blocks after which the client of this chain on the counterparty is updated even
+	/// if no update is needed to relay packets. Lower values keep the client closer to the chain
+	/// at the cost of more transactions. Defaults to a chain-specific period.
+	#[serde(default)]
+	pub client_update_period: Option<u64>,
+	/// Number of blocks that must be finalized on top of the block of an event before it's
+	/// relayed, in case the RPC node reports finalized heads that don't end up canonical. `0`
+	/// relays the events as soon as their block is finalized.
+	#[serde(default)]
+	pub confirmation_depth: u64,
+	/// Window in seconds during which the optional client updates of this chain that don't prove
+	/// any packet event are held, and only the latest one is submitted. Held updates are submitted
+	/// right away along with any other message. Every optional update is submitted if unset.
+	#[serde(default)]
+	pub optional_update_window: Option<u64>,
+	/// Adapts the frequency of the optional client updates of this chain to the rate at which
+	/// packets are sent from it, within the given bounds. Disabled if unset.
+	#[serde(default)]
+	pub adaptive_client_updates: Option<AdaptiveUpdateParams>,
+	/// Trust assumptions of the client of this chain on the counterparty.
+	#[serde(default)]
+	pub security: SecurityParams,
+	/// Address on the counterparty that receives the ICS-29 receive fees of the packets relayed
+	/// to this chain. Registered for the whitelisted channels on startup if set.
+	#[serde(default)]
+	pub counterparty_payee: Option<String>,
+	/// Allow and deny lists of the events of this chain that are relayed.
+	#[serde(default)]
+	pub event_filter: EventFilter,
+}
+
+impl CommonClientConfig {
+	/// Returns the configured `max_packets_to_process`, or `default` if it isn't set.
+	pub fn max_packets_to_process_or(&self, default: usize) -> usize {
+		self.max_packets_to_process.map_or(default, |max| max as usize)
+	}
+
+	/// Checks that the security, adaptive client update, event filter and coalescing parameters
+	/// are valid.
+	pub fn validate(&self) -> anyhow::Result<()> {
+		self.security.validate()?;
+		if let Some(params) = &self.adaptive_client_updates {
+			params.validate()?;
+		}
+		self.event_filter.validate()?;
+		if self.optional_update_window == Some(0) {
+			return Err(anyhow::anyhow!("optional_update_window must not be zero"))
+		}
+		Ok(())
+	}
+}
+
+impl Default for CommonClientConfig {
+	fn default() -> Self {
+		Self {
+			skip_optional_client_updates: default_skip_optional_client_updates(),
+			max_packets_to_process: None,
+			skip_tokens_list: None,
+			min_balance: None,
+			relayed_sequences_retention: relayed_sequences_retention(),
+			relayed_sequences_path: None,
+			pending_messages_path: None,
+			max_concurrent_submissions: max_concurrent_submissions(),
+			client_update_period: None,
+			confirmation_depth: 0,
+			optional_update_window: None,
+			adaptive_client_updates: None,
+			security: SecurityParams::default(),
+			counterparty_payee: None,
+			event_filter: EventFilter::default(),
+		}
+	}
+}
+
+/// Relayer progress on a single chain, as reported by the health endpoints.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RelayStatus {
+	/// Unix timestamp (in seconds) of the last finality event received from the chain.
+	pub last_finality_event: Option<u64>,
+	/// Number of messages that are waiting to be submitted to the chain.
+	pub queue_depth: usize,
+	/// Unix timestamp (in seconds) of the last successful submission to the chain.
+	pub last_successful_submission: Option<u64>,
+}
+
+/// Why packets of a channel can't be relayed anymore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum HaltReason {
+	/// The channel is closed on either side. Only timeouts can still be delivered.
+	ChannelClosed,
+	/// A client verifying the channel's proofs is frozen.
+	ClientFrozen,
+	/// A client verifying the channel's proofs has expired.
+	ClientExpired,
+}
+
+impl std::fmt::Display for HaltReason {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			HaltReason::ChannelClosed => write!(f, "channel is closed"),
+			HaltReason::ClientFrozen => write!(f, "client is frozen"),
+			HaltReason::ClientExpired => write!(f, "client has expired"),
+		}
+	}
+}
+
+fn unix_timestamp() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
 }
 
 /// A common data that all clients should keep.
@@ -133,6 +312,70 @@ pub struct CommonClientState {
 	pub misbehaviour_client_msg_queue: Arc<AsyncMutex<Vec<AnyClientMessage>>>,
 	pub max_packets_to_process: usize,
 	pub skip_tokens_list: Vec<String>,
+	/// See [`CommonClientConfig::client_update_period`].
+	pub client_update_period: Option<u64>,
+	/// See [`CommonClientConfig::confirmation_depth`].
+	pub confirmation_depth: u64,
+	/// Updates of this chain waiting for `confirmation_depth` blocks to be finalized on top of
+	/// their block.
+	pub confirmations: Arc<Mutex<ConfirmationBuffer>>,
+	/// See [`CommonClientConfig::optional_update_window`].
+	pub optional_update_window: Option<Duration>,
+	/// Optional client update of this chain held during the `optional_update_window`.
+	pub update_coalescer: Arc<Mutex<UpdateCoalescer>>,
+	/// Scheduler of the optional client updates, if
+	/// [`CommonClientConfig::adaptive_client_updates`] is set.
+	pub adaptive_updates: Arc<Mutex<Option<AdaptiveUpdateScheduler>>>,
+	/// See [`CommonClientConfig::security`].
+	pub security: SecurityParams,
+	/// See [`CommonClientConfig::counterparty_payee`].
+	pub counterparty_payee: Option<String>,
+	/// See [`CommonClientConfig::event_filter`].
+	pub event_filter: EventFilter,
+	/// Incentivized packets sent from this chain.
+	pub incentivized_packets: Arc<Mutex<IncentivizedPackets>>,
+	/// Number of stream items (blocks, events) that were skipped because they could not be
+	/// fetched or decoded.
+	pub skipped_stream_items: Arc<AtomicU64>,
+	/// Number of times the event subscriptions of this chain were disconnected.
+	pub disconnects: Arc<AtomicU64>,
+	/// Whether the messages for this chain are logged with their estimated cost instead of being
+	/// submitted. Set by the `--dry-run` flag of the CLI.
+	pub dry_run: bool,
+	/// Relayer progress on this chain, shared with the health server.
+	pub relay_status: Arc<Mutex<RelayStatus>>,
+	/// Channels on this chain whose packets, acknowledgements and timeouts are not relayed.
+	pub paused_channels: Arc<Mutex<HashSet<(ChannelId, PortId)>>>,
+	/// Whether the next client update for this chain should be sent even if it's optional.
+	pub force_client_update: Arc<AtomicBool>,
+	/// Channels on this chain that can't be relayed anymore, as detected by the channel watcher.
+	pub halted_channels: Arc<Mutex<HashMap<(ChannelId, PortId), HaltReason>>>,
+	/// Minimum balance of a relayer key below which it's excluded from the rotation.
+	pub min_balance: Arc<Mutex<Option<u64>>>,
+	/// Whether the balances of all the relayer keys were below `min_balance` when last checked,
+	/// in which case submissions are halted.
+	pub low_balance: Arc<AtomicBool>,
+	/// Why the chain was considered stalled by the last health probe, if it was.
+	pub stall_reason: Arc<Mutex<Option<StallReason>>>,
+	/// Sequences recently relayed from this chain.
+	pub relayed_sequences: Arc<Mutex<RelayedSequences>>,
+	/// Message batches whose submission to this chain hasn't returned yet.
+	pub pending_messages: Arc<Mutex<PendingMessages>>,
+	/// Store of the receipts of the successful submissions to this chain, if they're issued.
+	pub receipts: Option<ReceiptStore>,
+	/// Ledger the packets sent from and delivered to this chain are recorded in, if any.
+	pub ledger: Option<PacketLedger>,
+	/// Relayer keys the transactions submitted to this chain are signed with, in rotation.
+	pub keys: KeyRotation,
+	/// Delay deadlines of the messages submitted to the connections of this chain.
+	pub connection_delays: Arc<Mutex<ConnectionDelays>>,
+	/// Packets seen and relayed per channel, compared over time by the watchdog.
+	pub pipeline_counters: Arc<Mutex<PipelineCounters>>,
+	/// Timeouts of the packets sent from this chain that are still committed on it.
+	pub pending_timeouts: Arc<Mutex<PendingTimeouts>>,
+	/// Gas usage of the transactions submitted to this chain that weren't reported yet, for chains
+	/// that simulate their transactions.
+	pub gas_usage: Arc<Mutex<Vec<GasUsage>>>,
 }
 
 impl Default for CommonClientState {
@@ -145,7 +388,39 @@ impl Default for CommonClientState {
 			initial_rpc_call_delay: rpc_call_delay,
 			misbehaviour_client_msg_queue: Arc::new(Default::default()),
 			max_packets_to_process: 100,
+			client_update_period: None,
+			confirmation_depth: 0,
+			confirmations: Default::default(),
+			optional_update_window: None,
+			update_coalescer: Default::default(),
+			adaptive_updates: Default::default(),
+			security: Default::default(),
+			counterparty_payee: None,
+			event_filter: Default::default(),
+			incentivized_packets: Default::default(),
 			skip_tokens_list: Default::default(),
+			skipped_stream_items: Default::default(),
+			disconnects: Default::default(),
+			dry_run: false,
+			relay_status: Default::default(),
+			paused_channels: Default::default(),
+			force_client_update: Default::default(),
+			halted_channels: Default::default(),
+			min_balance: Default::default(),
+			low_balance: Default::default(),
+			stall_reason: Default::default(),
+			relayed_sequences: Arc::new(Mutex::new(RelayedSequences::new(
+				relayed::DEFAULT_RELAYED_SEQUENCES_RETENTION,
+				None,
+			))),
+			pending_messages: Default::default(),
+			receipts: None,
+			ledger: None,
+			keys: Default::default(),
+			connection_delays: Default::default(),
+			pipeline_counters: Default::default(),
+			pending_timeouts: Default::default(),
+			gas_usage: Default::default(),
 		}
 	}
 }
@@ -174,9 +449,236 @@ impl CommonClientState {
 		self.rpc_call_delay
 	}
 
+	/// Records a stream item that was skipped instead of being yielded to the relayer.
+	pub fn on_skipped_stream_item(&self, reason: &str) {
+		log::warn!(target: "hyperspace", "Skipping stream item: {reason}");
+		self.skipped_stream_items.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn skipped_stream_items(&self) -> u64 {
+		self.skipped_stream_items.load(Ordering::Relaxed)
+	}
+
+	/// Records a disconnection of an event subscription.
+	pub fn on_disconnect(&self, reason: &str) {
+		log::warn!(target: "hyperspace", "Subscription disconnected: {reason}");
+		self.disconnects.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn disconnects(&self) -> u64 {
+		self.disconnects.load(Ordering::Relaxed)
+	}
+
+	/// Records the gas usage of a committed transaction, until it's reported by
+	/// [`Self::take_gas_usage`]. Only the latest [`MAX_UNREPORTED_GAS_USAGE`] ones are kept.
+	pub fn record_gas_usage(&self, usage: GasUsage) {
+		let mut gas_usage = self.gas_usage.lock().unwrap();
+		if gas_usage.len() >= MAX_UNREPORTED_GAS_USAGE {
+			gas_usage.remove(0);
+		}
+		gas_usage.push(usage);
+	}
+
+	/// Returns the gas usage recorded since the last call.
+	pub fn take_gas_usage(&self) -> Vec<GasUsage> {
+		std::mem::take(&mut *self.gas_usage.lock().unwrap())
+	}
+
+	/// Records that a finality event was received from the chain.
+	pub fn on_finality_event(&self) {
+		self.relay_status.lock().unwrap().last_finality_event = Some(unix_timestamp());
+	}
+
+	/// Records the number of messages that are waiting to be submitted to the chain.
+	pub fn set_queue_depth(&self, depth: usize) {
+		self.relay_status.lock().unwrap().queue_depth = depth;
+	}
+
+	/// Records a successful submission of `count` messages to the chain.
+	pub fn on_successful_submission(&self, count: usize) {
+		let mut status = self.relay_status.lock().unwrap();
+		status.queue_depth = status.queue_depth.saturating_sub(count);
+		status.last_successful_submission = Some(unix_timestamp());
+	}
+
+	pub fn relay_status(&self) -> RelayStatus {
+		self.relay_status.lock().unwrap().clone()
+	}
+
+	/// Stops relaying packets, acknowledgements and timeouts of the given channel. Returns `false`
+	/// if it was already paused.
+	pub fn pause_channel(&self, channel_id: ChannelId, port_id: PortId) -> bool {
+		self.paused_channels.lock().unwrap().insert((channel_id, port_id))
+	}
+
+	/// Resumes relaying of the given channel. Returns `false` if it wasn't paused.
+	pub fn resume_channel(&self, channel_id: &ChannelId, port_id: &PortId) -> bool {
+		self.paused_channels.lock().unwrap().remove(&(*channel_id, port_id.clone()))
+	}
+
+	pub fn is_channel_paused(&self, channel_id: &ChannelId, port_id: &PortId) -> bool {
+		self.paused_channels.lock().unwrap().contains(&(*channel_id, port_id.clone()))
+	}
+
+	pub fn paused_channels(&self) -> HashSet<(ChannelId, PortId)> {
+		self.paused_channels.lock().unwrap().clone()
+	}
+
+	/// Requests a client update to be sent on the next finality event, even if it's optional.
+	pub fn request_client_update(&self) {
+		self.force_client_update.store(true, Ordering::SeqCst);
+	}
+
+	/// Returns whether a client update was requested, and clears the request.
+	pub fn take_client_update_request(&self) -> bool {
+		self.force_client_update.swap(false, Ordering::SeqCst)
+	}
+
+	/// Records that `count` packets were sent in the block at `height`, if client updates are
+	/// scheduled adaptively.
+	pub fn record_send_packets(&self, height: u64, count: u64) {
+		if let Some(scheduler) = self.adaptive_updates.lock().unwrap().as_mut() {
+			scheduler.record_send_packets(height, count)
+		}
+	}
+
+	/// Records that a client update to `height` was sent, if client updates are scheduled
+	/// adaptively.
+	pub fn record_client_update(&self, height: u64) {
+		if let Some(scheduler) = self.adaptive_updates.lock().unwrap().as_mut() {
+			scheduler.record_update(height)
+		}
+	}
+
+	/// Returns whether the adaptive scheduler wants an optional client update to `height` to be
+	/// sent. Always `false` if client updates aren't scheduled adaptively.
+	pub fn is_adaptive_update_due(&self, height: u64) -> bool {
+		self.adaptive_updates
+			.lock()
+			.unwrap()
+			.as_ref()
+			.map_or(false, |scheduler| scheduler.is_update_due(height))
+	}
+
+	/// Current number of blocks between two optional client updates, if they're scheduled
+	/// adaptively.
+	pub fn adaptive_update_period(&self, height: u64) -> Option<u64> {
+		self.adaptive_updates
+			.lock()
+			.unwrap()
+			.as_ref()
+			.map(|scheduler| scheduler.update_period(height))
+	}
+
+	/// Marks the given channel as halted for `reason`. Returns the previous reason, if any.
+	pub fn halt_channel(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		reason: HaltReason,
+	) -> Option<HaltReason> {
+		self.halted_channels.lock().unwrap().insert((channel_id, port_id), reason)
+	}
+
+	/// Marks the given channel as relayable again. Returns the reason it was halted for, if any.
+	pub fn unhalt_channel(&self, channel_id: &ChannelId, port_id: &PortId) -> Option<HaltReason> {
+		self.halted_channels.lock().unwrap().remove(&(*channel_id, port_id.clone()))
+	}
+
+	pub fn channel_halt_reason(
+		&self,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+	) -> Option<HaltReason> {
+		self.halted_channels
+			.lock()
+			.unwrap()
+			.get(&(*channel_id, port_id.clone()))
+			.copied()
+	}
+
+	pub fn halted_channels(&self) -> HashMap<(ChannelId, PortId), HaltReason> {
+		self.halted_channels.lock().unwrap().clone()
+	}
+
+	/// Records whether the balances of all the relayer keys are below `min_balance`. Returns the
+	/// previous value.
+	pub fn set_low_balance(&self, low: bool) -> bool {
+		self.low_balance.swap(low, Ordering::SeqCst)
+	}
+
+	pub fn has_low_balance(&self) -> bool {
+		self.low_balance.load(Ordering::SeqCst)
+	}
+
+	/// Records why the chain is stalled, `None` if it's healthy. Returns the previous reason.
+	pub fn set_stall_reason(&self, reason: Option<StallReason>) -> Option<StallReason> {
+		std::mem::replace(&mut *self.stall_reason.lock().unwrap(), reason)
+	}
+
+	pub fn stall_reason(&self) -> Option<StallReason> {
+		*self.stall_reason.lock().unwrap()
+	}
+
+	/// Returns whether `sequence` was relayed recently.
+	pub fn is_relayed(&self, sequence: &RelayedSequence) -> bool {
+		self.relayed_sequences.lock().unwrap().contains(sequence)
+	}
+
+	/// Remembers `sequences` as relayed.
+	pub fn record_relayed(&self, sequences: impl IntoIterator<Item = RelayedSequence>) {
+		let sequences = sequences.into_iter().collect::<Vec<_>>();
+		let mut counters = self.pipeline_counters.lock().unwrap();
+		for sequence in sequences.iter().filter(|seq| seq.direction != PacketDirection::Ack) {
+			counters.submit(sequence.channel_id, sequence.port_id.clone(), 1);
+		}
+		self.relayed_sequences.lock().unwrap().insert(sequences)
+	}
+
+	/// Counts the packets sent in `events` for the watchdog, and updates the timeouts of the
+	/// committed packets.
+	pub fn observe_events(&self, events: &[IbcEvent]) {
+		self.pipeline_counters.lock().unwrap().observe(events);
+		self.pending_timeouts.lock().unwrap().observe(events)
+	}
+
+	/// Counts an error of the relay loop for the watchdog.
+	pub fn record_error(&self) {
+		self.pipeline_counters.lock().unwrap().error()
+	}
+
+	pub fn min_balance(&self) -> Option<u64> {
+		*self.min_balance.lock().unwrap()
+	}
+
 	pub fn set_rpc_call_delay(&mut self, delay: Duration) {
 		self.rpc_call_delay = delay;
 	}
+
+	/// Applies the runtime-adjustable parameters of a (re)loaded [`CommonClientConfig`], with the
+	/// client's default `max_packets_to_process`.
+	pub fn apply_config(
+		&mut self,
+		config: &CommonClientConfig,
+		default_max_packets_to_process: usize,
+	) {
+		self.skip_optional_client_updates = config.skip_optional_client_updates;
+		self.max_packets_to_process =
+			config.max_packets_to_process_or(default_max_packets_to_process);
+		self.skip_tokens_list = config.skip_tokens_list.clone().unwrap_or_default();
+		*self.min_balance.lock().unwrap() = config.min_balance;
+		self.client_update_period = config.client_update_period;
+		self.confirmation_depth = config.confirmation_depth;
+		self.optional_update_window = config.optional_update_window.map(Duration::from_secs);
+		let mut adaptive_updates = self.adaptive_updates.lock().unwrap();
+		match (adaptive_updates.as_mut(), &config.adaptive_client_updates) {
+			(Some(scheduler), Some(params)) => scheduler.set_params(params.clone()),
+			(_, params) => *adaptive_updates = params.clone().map(AdaptiveUpdateScheduler::new),
+		}
+		self.security = config.security.clone();
+		self.counterparty_payee = config.counterparty_payee.clone();
+		self.event_filter = config.event_filter.clone();
+	}
 }
 
 pub fn apply_prefix(mut commitment_prefix: Vec<u8>, path: impl Into<Vec<u8>>) -> Vec<u8> {
@@ -196,33 +698,35 @@ pub enum UndeliveredType {
 	Timeouts,
 }
 
-/// Provides an interface for accessing new events and Ibc data on the chain which must be
-/// relayed to the counterparty chain.
+/// Provides read-only access to the IBC state of a chain, its proofs and its relaying state. This
+/// is all a chain needs to implement to be monitored.
 #[async_trait::async_trait]
-pub trait IbcProvider {
-	/// Finality event type, passed on to [`Chain::query_latest_ibc_events`]
-	type FinalityEvent: Debug + Send + 'static;
-	/// A representation of the transaction id for the chain
-	type TransactionId: Debug;
+pub trait IbcQuerier {
 	/// Asset Id
 	type AssetId: Clone;
 
 	/// Error type, just needs to implement standard error trait.
 	type Error: std::error::Error + From<String> + Send + Sync + 'static;
 
-	/// Query the latest ibc events finalized by the recent finality event. Use the counterparty
-	/// [`Chain`] to query the on-chain [`ClientState`] so you can scan for new events in between
-	/// the client state and the new finality event.
-	async fn query_latest_ibc_events<T>(
-		&mut self,
-		finality_event: Self::FinalityEvent,
+	/// Query the ibc events emitted in the blocks `from..=to` that concern the clients,
+	/// connections or whitelisted channels of this chain and the counterparty [`Chain`]. The
+	/// height of each event is the height of the block it was emitted in.
+	async fn query_ibc_events_in_range<T>(
+		&self,
 		counterparty: &T,
-	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+		from: u64,
+		to: u64,
+	) -> Result<Vec<IbcEvent>, anyhow::Error>
 	where
 		T: Chain;
 
-	/// Return a stream that yields when new [`IbcEvents`] are parsed from a finality notification
-	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>>;
+	/// Return a stream that yields when new [`IbcEvents`] are parsed from a finality notification.
+	///
+	/// Malformed items are skipped (see [`CommonClientState::on_skipped_stream_item`]), an `Err`
+	/// is only yielded when the subscription itself failed and must be re-established.
+	async fn ibc_events(
+		&self,
+	) -> Pin<Box<dyn Stream<Item = Result<IbcEvent, Self::Error>> + Send + 'static>>;
 
 	/// Query client consensus state with proof
 	/// return the consensus height for the client along with the response
@@ -258,6 +762,30 @@ pub trait IbcProvider {
 	/// Query proof for provided key path
 	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error>;
 
+	/// Query a proof for each of the provided sets of key paths at the same height. Chains that
+	/// can generate several proofs in a single request should override this.
+	async fn query_proofs(
+		&self,
+		at: Height,
+		keys: Vec<Vec<Vec<u8>>>,
+	) -> Result<Vec<Vec<u8>>, Self::Error> {
+		let mut proofs = Vec::with_capacity(keys.len());
+		for keys in keys {
+			proofs.push(self.query_proof(at, keys).await?);
+		}
+		Ok(proofs)
+	}
+
+	/// Query a single proof of all the provided commitment paths at the same height, or `None` if
+	/// the chain can't prove several paths with one proof. Chains that can should override this.
+	async fn query_batch_proof(
+		&self,
+		_at: Height,
+		_paths: Vec<String>,
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		Ok(None)
+	}
+
 	/// Query packet commitment with proof
 	async fn query_packet_commitment(
 		&self,
@@ -385,12 +913,27 @@ pub trait IbcProvider {
 		client_state: &AnyClientState,
 	) -> Result<Option<Vec<u8>>, Self::Error>;
 
+	/// Return the client and consensus states this chain scheduled the upgrade of its light
+	/// clients on the counterparty to, with their proofs at the given height, if any.
+	async fn query_upgraded_client(
+		&self,
+		at: Height,
+	) -> Result<Option<UpgradedClient>, Self::Error>;
+
 	/// Should return the list of ibc denoms available to this account to spend.
 	async fn query_ibc_balance(
 		&self,
 		asset_id: Self::AssetId,
 	) -> Result<Vec<PrefixedCoin>, Self::Error>;
 
+	/// Query the balance of the relayer account in the token used to pay transaction fees
+	async fn query_native_balance(&self) -> Result<PrefixedCoin, Self::Error>;
+
+	/// Query the balances of all the relayer keys in the token used to pay transaction fees, with
+	/// their addresses, in the order of [`KeyRotation`]. When the fees of a key are paid by
+	/// another account, the balance and address of that account are returned.
+	async fn query_key_balances(&self) -> Result<Vec<(String, PrefixedCoin)>, Self::Error>;
+
 	/// Return the chain connection prefix
 	fn connection_prefix(&self) -> CommitmentPrefix;
 
@@ -431,18 +974,76 @@ pub trait IbcProvider {
 		client_id: String,
 	) -> Result<Vec<IdentifiedConnection>, Self::Error>;
 
-	/// Returns a boolean value that determines if the light client should receive a mandatory
-	/// update
-	async fn is_update_required(
-		&self,
-		latest_height: u64,
-		latest_client_height_on_counterparty: u64,
-	) -> Result<bool, Self::Error>;
+	/// Name of this chain, used in logs.
+	fn name(&self) -> &str;
 
-	/// This should return a subjectively chosen client and consensus state for this chain.
-	async fn initialize_client_state(
-		&self,
-	) -> Result<(AnyClientState, AnyConsensusState), Self::Error>;
+	/// Should return the heights of the best and of the latest finalized block. Chains with
+	/// instant finality return the same height twice.
+	async fn query_best_and_finalized_heights(&self) -> Result<(u64, u64), Self::Error>;
+
+	async fn handle_error(&mut self, error: &anyhow::Error) -> Result<(), anyhow::Error>;
+
+	fn common_state(&self) -> &CommonClientState;
+
+	fn common_state_mut(&mut self) -> &mut CommonClientState;
+
+	async fn on_undelivered_sequences(&self, has: bool, kind: UndeliveredType) {
+		self.common_state().on_undelivered_sequences(has, kind).await
+	}
+
+	fn has_undelivered_sequences(&self, kind: UndeliveredType) -> bool {
+		self.common_state().has_undelivered_sequences(kind)
+	}
+
+	fn rpc_call_delay(&self) -> Duration {
+		self.common_state().rpc_call_delay()
+	}
+
+	fn initial_rpc_call_delay(&self) -> Duration {
+		self.common_state().initial_rpc_call_delay
+	}
+
+	fn set_rpc_call_delay(&mut self, delay: Duration) {
+		self.common_state_mut().set_rpc_call_delay(delay)
+	}
+
+	/// Number of blocks after which the client of this chain on the counterparty is updated even
+	/// if the update is optional, if one was configured.
+	fn client_update_period(&self) -> Option<u64> {
+		self.common_state().client_update_period
+	}
+
+	/// Trust assumptions of the client of this chain on the counterparty.
+	fn security_params(&self) -> &SecurityParams {
+		&self.common_state().security
+	}
+
+	async fn reconnect(&mut self) -> anyhow::Result<()>;
+}
+
+/// Maximum number of [`GasUsage`] records kept until they're reported.
+pub const MAX_UNREPORTED_GAS_USAGE: usize = 1000;
+
+/// Gas of a committed transaction, as simulated before it was submitted and as used by its
+/// execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasUsage {
+	pub simulated: u64,
+	pub used: u64,
+}
+
+/// Fee paid for submitting a transaction, in the smallest unit of `denom`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Fee {
+	pub amount: u128,
+	pub denom: String,
+}
+
+/// Provides an interface for submitting messages to a chain.
+#[async_trait::async_trait]
+pub trait TxSubmitter: IbcQuerier {
+	/// A representation of the transaction id for the chain
+	type TransactionId: Debug;
 
 	/// Should find client id that was created in this transaction
 	async fn query_client_id_from_tx_hash(
@@ -463,6 +1064,81 @@ pub trait IbcProvider {
 	) -> Result<(ChannelId, PortId), Self::Error>;
 
 	async fn upload_wasm(&self, wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error>;
+
+	/// Should return a numerical value for the max weight of transactions allowed in a block.
+	fn block_max_weight(&self) -> u64;
+
+	/// Should return an estimate of the weight of a batch of messages.
+	async fn estimate_weight(&self, msg: Vec<Any>) -> Result<u64, Self::Error>;
+
+	/// Should return an estimate of the fee paid for submitting `msgs` in a single transaction
+	/// signed with the primary key, without submitting it.
+	async fn estimate_fee(&self, msgs: &[Any]) -> Result<Fee, Self::Error>;
+
+	/// This should be used to submit new messages [`Vec<Any>`] from a counterparty chain to this
+	/// chain.
+	/// Should return the transaction id
+	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Self::Error>;
+}
+
+/// Provides the finality notifications of a chain, the IBC events they finalize and the client
+/// messages and states built from its headers, which are relayed to the counterparty chain.
+#[async_trait::async_trait]
+pub trait FinalitySource: IbcQuerier {
+	/// Finality event type, passed on to [`FinalitySource::query_latest_ibc_events`]
+	type FinalityEvent: Debug + Send + 'static;
+
+	/// Returns true if `finality_event` must be processed even when a later one is already
+	/// pending, e.g. because it finalizes an authority set change. The relay loop otherwise skips
+	/// to the latest finality event when it falls behind.
+	fn is_mandatory_finality_event(_finality_event: &Self::FinalityEvent) -> bool {
+		false
+	}
+
+	/// Query the latest ibc events finalized by the recent finality event. Use the counterparty
+	/// [`Chain`] to query the on-chain [`ClientState`] so you can scan for new events in between
+	/// the client state and the new finality event.
+	async fn query_latest_ibc_events<T>(
+		&mut self,
+		finality_event: Self::FinalityEvent,
+		counterparty: &T,
+	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+	where
+		T: Chain;
+
+	/// Returns a boolean value that determines if the light client should receive a mandatory
+	/// update. Implementations should honour [`IbcQuerier::client_update_period`] and
+	/// [`SecurityParams::max_header_lag`] when they're set.
+	async fn is_update_required(
+		&self,
+		latest_height: u64,
+		latest_client_height_on_counterparty: u64,
+	) -> Result<bool, Self::Error>;
+
+	/// This should return a subjectively chosen client and consensus state for this chain.
+	async fn initialize_client_state(
+		&self,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error>;
+
+	/// Returns the client and consensus state for this chain at the past finalized `height`, so
+	/// that a client can be created at the same height as an existing client of the counterparty.
+	async fn initialize_client_state_at(
+		&self,
+		height: u64,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error>;
+
+	/// Return a stream that yields when new [`IbcEvents`] are ready to be queried.
+	async fn finality_notifications(
+		&self,
+	) -> Result<Pin<Box<dyn Stream<Item = Self::FinalityEvent> + Send + Sync>>, Self::Error>;
+
+	/// Returns an [`AnyClientMessage`] for an [`UpdateClient`] event
+	async fn query_client_message(
+		&self,
+		update: UpdateClient,
+	) -> Result<AnyClientMessage, Self::Error>;
+
+	async fn get_proof_height(&self, block_height: Height) -> Height;
 }
 
 /// Provides an interface that allows us run the hyperspace-testsuite
@@ -481,7 +1157,9 @@ pub trait TestProvider: Chain + Clone + 'static {
 	) -> Result<(), Self::Error>;
 
 	/// Returns a stream that yields chain Block number
-	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>>;
+	async fn subscribe_blocks(
+		&self,
+	) -> Pin<Box<dyn Stream<Item = Result<u64, Self::Error>> + Send + Sync>>;
 
 	/// Increases IBC counters by 1 to check that relayer uses proper values for source/sink chains.
 	async fn increase_counters(&mut self) -> Result<(), Self::Error>;
@@ -492,17 +1170,44 @@ pub trait KeyProvider {
 	/// Should return the relayer's account id on the host chain as a string in the expected format
 	/// Could be a hexadecimal, bech32 or ss58 string, any format the chain supports
 	fn account_id(&self) -> Signer;
+
+	/// Signs an arbitrary payload with the relayer's key on the host chain, e.g. a
+	/// [`RelayReceipt`].
+	fn sign_payload(&self, payload: &[u8]) -> Result<PayloadSignature, anyhow::Error>;
+
+	/// Reloads the relayer keys from the key backend of the chain config, e.g. after keys were
+	/// added to or removed from a keystore directory, and returns the accounts of the new keys, the
+	/// primary one first. Subsequent transactions are signed with the new keys.
+	fn reload_keys(&mut self) -> Result<Vec<String>, anyhow::Error> {
+		Err(anyhow::anyhow!("Reloading the relayer keys is not supported by this chain"))
+	}
 }
 
 /// Provides an interface for managing IBC misbehaviour.
 #[async_trait::async_trait]
 pub trait MisbehaviourHandler {
+	/// Check the client message, submitted to the counterparty's client of this chain, for
+	/// misbehaviour. Returns the message reporting the misbehaviour to the counterparty, if any.
+	async fn detect_misbehaviour<C: Chain>(
+		&self,
+		counterparty: &C,
+		client_message: AnyClientMessage,
+	) -> Result<Option<Any>, anyhow::Error>;
+
 	/// Check the client message for misbehaviour and submit it to the chain if any.
 	async fn check_for_misbehaviour<C: Chain>(
 		&self,
 		counterparty: &C,
 		client_message: AnyClientMessage,
-	) -> Result<(), anyhow::Error>;
+	) -> Result<(), anyhow::Error> {
+		if let Some(msg) = self.detect_misbehaviour(counterparty, client_message).await? {
+			counterparty
+				.submit(vec![msg])
+				.await
+				.map_err(|e| anyhow::anyhow!("Failed to submit misbehaviour report: {:?}", e))?;
+		}
+		Ok(())
+	}
 }
 
 /// Provides an interface for syncing light clients to the latest state
@@ -518,66 +1223,34 @@ pub trait LightClientSync {
 	) -> Result<(Vec<Any>, Vec<IbcEvent>), anyhow::Error>;
 }
 
-/// Provides an interface for the chain to the relayer core for submitting IbcEvents as well as
-/// finality notifications
-#[async_trait::async_trait]
+/// A chain the relayer can relay to and from. Implemented for every type implementing all of its
+/// supertraits.
 pub trait Chain:
-	IbcProvider + LightClientSync + MisbehaviourHandler + KeyProvider + Clone + Send + Sync + 'static
+	IbcQuerier
+	+ TxSubmitter
+	+ FinalitySource
+	+ LightClientSync
+	+ MisbehaviourHandler
+	+ KeyProvider
+	+ Clone
+	+ Send
+	+ Sync
+	+ 'static
 {
-	/// Name of this chain, used in logs.
-	fn name(&self) -> &str;
-
-	/// Should return a numerical value for the max weight of transactions allowed in a block.
-	fn block_max_weight(&self) -> u64;
-
-	/// Should return an estimate of the weight of a batch of messages.
-	async fn estimate_weight(&self, msg: Vec<Any>) -> Result<u64, Self::Error>;
-
-	/// Return a stream that yields when new [`IbcEvents`] are ready to be queried.
-	async fn finality_notifications(
-		&self,
-	) -> Result<Pin<Box<dyn Stream<Item = Self::FinalityEvent> + Send + Sync>>, Self::Error>;
-
-	/// This should be used to submit new messages [`Vec<Any>`] from a counterparty chain to this
-	/// chain.
-	/// Should return the transaction id
-	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Self::Error>;
-
-	/// Returns an [`AnyClientMessage`] for an [`UpdateClient`] event
-	async fn query_client_message(
-		&self,
-		update: UpdateClient,
-	) -> Result<AnyClientMessage, Self::Error>;
-
-	async fn get_proof_height(&self, block_height: Height) -> Height;
-
-	async fn handle_error(&mut self, error: &anyhow::Error) -> Result<(), anyhow::Error>;
-
-	fn common_state(&self) -> &CommonClientState;
-
-	fn common_state_mut(&mut self) -> &mut CommonClientState;
-
-	async fn on_undelivered_sequences(&self, has: bool, kind: UndeliveredType) {
-		self.common_state().on_undelivered_sequences(has, kind).await
-	}
-
-	fn has_undelivered_sequences(&self, kind: UndeliveredType) -> bool {
-		self.common_state().has_undelivered_sequences(kind)
-	}
-
-	fn rpc_call_delay(&self) -> Duration {
-		self.common_state().rpc_call_delay()
-	}
-
-	fn initial_rpc_call_delay(&self) -> Duration {
-		self.common_state().initial_rpc_call_delay
-	}
-
-	fn set_rpc_call_delay(&mut self, delay: Duration) {
-		self.common_state_mut().set_rpc_call_delay(delay)
-	}
+}
 
-	async fn reconnect(&mut self) -> anyhow::Result<()>;
+impl<T> Chain for T where
+	T: IbcQuerier
+		+ TxSubmitter
+		+ FinalitySource
+		+ LightClientSync
+		+ MisbehaviourHandler
+		+ KeyProvider
+		+ Clone
+		+ Send
+		+ Sync
+		+ 'static
+{
 }
 
 /// Returns undelivered packet sequences that have been sent out from
@@ -606,29 +1279,7 @@ pub async fn query_undelivered_sequences(
 		.into_iter()
 		.collect::<Vec<_>>();
 	log::trace!(target: "hyperspace", "Seqs: {:?}", seqs);
-	let counterparty_channel_id = channel_end
-		.counterparty()
-		.channel_id
-		.ok_or_else(|| Error::Custom("Expected counterparty channel id".to_string()))?;
-	let counterparty_port_id = channel_end.counterparty().port_id.clone();
-
-	let undelivered_sequences = if channel_end.ordering == Order::Unordered {
-		sink.query_unreceived_packets(
-			sink_height,
-			counterparty_channel_id,
-			counterparty_port_id.clone(),
-			seqs,
-		)
-		.await?
-	} else {
-		let next_seq_recv = sink
-			.query_next_sequence_recv(sink_height, &counterparty_port_id, &counterparty_channel_id)
-			.await?
-			.next_sequence_receive;
-		seqs.into_iter().filter(|seq| *seq > next_seq_recv).collect()
-	};
-
-	Ok(undelivered_sequences)
+	query_unreceived_sequences(sink, sink_height, &channel_end, seqs).await
 }
 
 /// Queries the `source` chain for packet acknowledgements that have not been seen by the `sink`
@@ -813,75 +1464,156 @@ pub async fn find_suitable_proof_height_for_client(
 	None
 }
 
+/// Returns the timeouts of the committed packets of a channel of `source` from its
+/// [`PendingTimeouts`]. The channel is scanned the first time, which fetches the packets of its
+/// latest `max_packets_to_process` commitments.
+async fn query_channel_timeouts(
+	source: &impl Chain,
+	source_height: Height,
+	channel_id: ChannelId,
+	port_id: PortId,
+) -> Result<ChannelTimeouts, anyhow::Error> {
+	let cached = source
+		.common_state()
+		.pending_timeouts
+		.lock()
+		.unwrap()
+		.channel(channel_id, &port_id);
+	if let Some(channel) = cached {
+		return Ok(channel)
+	}
+	let channel_response =
+		source.query_channel_end(source_height, channel_id, port_id.clone()).await?;
+	let channel_end = ChannelEnd::try_from(
+		channel_response
+			.channel
+			.ok_or_else(|| Error::Custom("ChannelEnd not could not be decoded".to_string()))?,
+	)
+	.map_err(|e| Error::Custom(e.to_string()))?;
+	let seqs = source
+		.query_packet_commitments(source_height, channel_id, port_id.clone())
+		.await?
+		.into_iter()
+		.rev()
+		.take(source.common_state().max_packets_to_process)
+		.collect();
+	let timeouts = source
+		.query_send_packets(channel_id, port_id.clone(), seqs)
+		.await?
+		.into_iter()
+		.map(|packet| {
+			(packet.sequence, (packet.timeout_height.revision_height, packet.timeout_timestamp))
+		})
+		// packets without a timeout never time out
+		.filter(|(_, timeout)| *timeout != (0, 0))
+		.collect();
+	let channel = ChannelTimeouts { channel_end, timeouts };
+	log::debug!(target: "hyperspace", "Scanned {} packets with a timeout on {port_id}/{channel_id} of {}", channel.timeouts.len(), source.name());
+	source.common_state().pending_timeouts.lock().unwrap().insert_channel(
+		channel_id,
+		port_id,
+		channel.clone(),
+	);
+	Ok(channel)
+}
+
+/// Returns the sequences among `seqs` of the packets sent on `channel_end` that weren't received
+/// by `sink`.
+async fn query_unreceived_sequences(
+	sink: &impl Chain,
+	sink_height: Height,
+	channel_end: &ChannelEnd,
+	seqs: Vec<u64>,
+) -> Result<Vec<u64>, anyhow::Error> {
+	let counterparty_channel_id = channel_end
+		.counterparty()
+		.channel_id
+		.ok_or_else(|| Error::Custom("Expected counterparty channel id".to_string()))?;
+	let counterparty_port_id = channel_end.counterparty().port_id.clone();
+	if channel_end.ordering == Order::Unordered {
+		Ok(sink
+			.query_unreceived_packets(
+				sink_height,
+				counterparty_channel_id,
+				counterparty_port_id,
+				seqs,
+			)
+			.await?)
+	} else {
+		let next_seq_recv = sink
+			.query_next_sequence_recv(sink_height, &counterparty_port_id, &counterparty_channel_id)
+			.await?
+			.next_sequence_receive;
+		Ok(seqs.into_iter().filter(|seq| *seq > next_seq_recv).collect())
+	}
+}
+
+/// Returns the first height of `sink` at which all of the undelivered packets sent from `source`
+/// have timed out, i.e. the height at which a client update allows proving all of their timeouts.
+///
+/// The timeouts of the packets come from the [`PendingTimeouts`] of `source`, which are kept up
+/// to date from its events after a scan of each channel on startup, so only their receipts are
+/// queried from `sink`. The height is then found with a binary search over the heights of `sink`
+/// that its client on `source` doesn't know yet, which only needs one timestamp query per step
+/// instead of several queries per packet.
 pub async fn query_maximum_height_for_timeout_proofs(
 	source: &impl Chain,
 	sink: &impl Chain,
 ) -> Option<u64> {
 	let (source_height, ..) = source.latest_height_and_timestamp().await.ok()?;
 	let (sink_height, ..) = sink.latest_height_and_timestamp().await.ok()?;
-	let mut join_set: JoinSet<Option<_>> = JoinSet::new();
-	for (channel, port_id) in source.channel_whitelist() {
-		let undelivered_sequences = query_undelivered_sequences(
-			source_height,
-			sink_height,
-			channel,
-			port_id.clone(),
-			source,
-			sink,
-		)
-		.await
-		.ok()?;
-		let undelivered_sequences = undelivered_sequences
-			.into_iter()
-			.rev()
-			.take(source.common_state().max_packets_to_process)
-			.collect();
-		let send_packets =
-			source.query_send_packets(channel, port_id, undelivered_sequences).await.ok()?;
-		for send_packet in send_packets {
-			let source = source.clone();
-			let sink = sink.clone();
-			let duration = Duration::from_millis(
-				rand::thread_rng().gen_range(1..source.rpc_call_delay().as_millis()) as u64,
-			);
-			join_set.spawn(async move {
-				sleep(duration).await;
-				let revision_height = send_packet.height.expect("expected height for packet");
-				let sink_client_state = source
-					.query_client_state(
-						Height::new(source_height.revision_number, revision_height),
-						sink.client_id(),
-					)
-					.await
-					.ok()?;
-				let sink_client_state =
-					AnyClientState::try_from(sink_client_state.client_state?).ok()?;
-				let height = sink_client_state.latest_height();
-				let timestamp_at_creation =
-					sink.query_timestamp_at(height.revision_height).await.ok()?;
-				let period = send_packet.timeout_timestamp.saturating_sub(timestamp_at_creation);
-				if period == 0 {
-					return Some(send_packet.timeout_height.revision_height)
-				}
-				let period = Duration::from_nanos(period);
-				let period =
-					calculate_block_delay(period, sink.expected_block_time()).saturating_add(1);
-				let approx_height = revision_height + period;
-				let timeout_height = if send_packet.timeout_height.revision_height < approx_height {
-					send_packet.timeout_height.revision_height
-				} else {
-					approx_height
-				};
-
-				Some(timeout_height)
-			});
+	let mut timeouts = vec![];
+	for (channel_id, port_id) in source.channel_whitelist() {
+		let channel =
+			query_channel_timeouts(source, source_height, channel_id, port_id).await.ok()?;
+		if channel.timeouts.is_empty() {
+			continue
 		}
+		let seqs = channel.timeouts.keys().copied().collect();
+		let undelivered_sequences =
+			query_unreceived_sequences(sink, sink_height, &channel.channel_end, seqs)
+				.await
+				.ok()?;
+		timeouts.extend(
+			undelivered_sequences
+				.into_iter()
+				.rev()
+				.take(source.common_state().max_packets_to_process)
+				.filter_map(|seq| channel.timeouts.get(&seq).copied()),
+		);
+	}
+	if timeouts.is_empty() {
+		return None
 	}
-	let mut min_timeout_height = None;
-	while let Some(timeout_height) = join_set.join_next().await {
-		min_timeout_height = min_timeout_height.max(timeout_height.ok()?)
+
+	// heights up to the latest one known by the client can't be used for new proofs
+	let client_state = source.query_client_state(source_height, sink.client_id()).await.ok()?;
+	let client_state = AnyClientState::try_from(client_state.client_state?).ok()?;
+	let mut start = client_state.latest_height().revision_height;
+	let mut end = sink_height.revision_height;
+	if start >= end {
+		return None
+	}
+
+	let all_timed_out = |height: u64, timestamp: u64| {
+		timeouts.iter().all(|(timeout_height, timeout_timestamp)| {
+			(*timeout_height != 0 && *timeout_height <= height) ||
+				(*timeout_timestamp != 0 && *timeout_timestamp <= timestamp)
+		})
+	};
+	if !all_timed_out(end, sink.query_timestamp_at(end).await.ok()?) {
+		return None
+	}
+	// invariant: not all packets have timed out at `start`, all of them have at `end`
+	while end - start > 1 {
+		let mid = start + (end - start) / 2;
+		if all_timed_out(mid, sink.query_timestamp_at(mid).await.ok()?) {
+			end = mid;
+		} else {
+			start = mid;
+		}
 	}
-	min_timeout_height
+	Some(end)
 }
 
 pub fn filter_events_by_ids(
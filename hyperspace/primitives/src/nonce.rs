@@ -0,0 +1,162 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Coordination of the transactions the relayer account submits to a chain.
+//!
+//! The relay loop and the background tasks (client expiry prevention, misbehaviour submission,
+//! ...) share the relayer account, so they can race on its nonce (the account sequence on Cosmos
+//! chains) when they submit at the same time. Every submission goes through the
+//! [`SubmissionCoordinator`] of the key it's signed with (see [`crate::keys`]), which bounds the
+//! number of transactions in flight (one by default, i.e. submissions are serialized) and hands out
+//! the nonces explicitly: the nonce reported by the chain is only authoritative while nothing is in
+//! flight, otherwise the transactions are pipelined with consecutive nonces.
+//!
+//! A failed transaction may leave a gap that stalls the transactions sent after it with higher
+//! nonces. The coordinator then forgets its local nonce, so that the next submission resyncs from
+//! the chain and fills the gap.
+
+use std::{
+	collections::BTreeSet,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Mutex,
+	},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Default maximum number of transactions in flight per chain.
+pub const DEFAULT_MAX_CONCURRENT_SUBMISSIONS: usize = 1;
+
+#[derive(Debug, Default)]
+struct NonceState {
+	/// Next nonce to hand out, if it's known locally.
+	next: Option<u64>,
+	/// Nonces of the transactions in flight.
+	in_flight: BTreeSet<u64>,
+}
+
+/// Serializes or pipelines the submissions of a chain with explicit nonce tracking. Clones share
+/// the same state.
+#[derive(Debug, Clone)]
+pub struct SubmissionCoordinator {
+	slots: Arc<Semaphore>,
+	nonces: Arc<Mutex<NonceState>>,
+	/// Number of submissions waiting for a slot or in flight.
+	queued: Arc<AtomicUsize>,
+}
+
+impl Default for SubmissionCoordinator {
+	fn default() -> Self {
+		Self::new(DEFAULT_MAX_CONCURRENT_SUBMISSIONS)
+	}
+}
+
+impl SubmissionCoordinator {
+	/// Creates a coordinator allowing up to `max_concurrent` transactions in flight.
+	pub fn new(max_concurrent: usize) -> Self {
+		Self {
+			slots: Arc::new(Semaphore::new(max_concurrent.max(1))),
+			nonces: Default::default(),
+			queued: Default::default(),
+		}
+	}
+
+	/// Waits until a transaction can be submitted. The slot is released when it's dropped.
+	pub async fn begin(&self) -> SubmissionSlot {
+		self.queued.fetch_add(1, Ordering::Relaxed);
+		let permit =
+			self.slots.clone().acquire_owned().await.expect("the semaphore is never closed");
+		SubmissionSlot { coordinator: self.clone(), nonce: None, _permit: permit }
+	}
+
+	/// Number of submissions waiting for a slot or in flight.
+	pub fn queue_depth(&self) -> usize {
+		self.queued.load(Ordering::Relaxed)
+	}
+
+	fn reserve(&self, chain_nonce: u64) -> u64 {
+		let mut state = self.nonces.lock().unwrap();
+		let nonce = match state.next {
+			Some(next) if !state.in_flight.is_empty() => next.max(chain_nonce),
+			Some(next) => {
+				if chain_nonce < next {
+					log::warn!(
+						target: "hyperspace",
+						"Nonce gap detected: nonces {chain_nonce}..{next} weren't included, resubmitting from {chain_nonce}"
+					);
+				}
+				chain_nonce
+			},
+			None => chain_nonce,
+		};
+		state.next = Some(nonce + 1);
+		state.in_flight.insert(nonce);
+		nonce
+	}
+
+	fn release(&self, nonce: u64, success: bool) {
+		let mut state = self.nonces.lock().unwrap();
+		state.in_flight.remove(&nonce);
+		if success {
+			return
+		}
+		if let Some(&later) = state.in_flight.iter().next_back().filter(|&&later| later > nonce) {
+			log::warn!(
+				target: "hyperspace",
+				"Transaction with nonce {nonce} failed while nonces up to {later} are in flight, resyncing the nonce"
+			);
+		}
+		// the nonce may or may not have been used, the chain knows
+		state.next = None;
+	}
+}
+
+/// Permission to submit a transaction, obtained with [`SubmissionCoordinator::begin`]. Dropping
+/// the slot without calling [`SubmissionSlot::confirm`] marks its transaction as failed.
+#[derive(Debug)]
+pub struct SubmissionSlot {
+	coordinator: SubmissionCoordinator,
+	nonce: Option<u64>,
+	_permit: OwnedSemaphorePermit,
+}
+
+impl SubmissionSlot {
+	/// Returns the nonce the transaction must be signed with, given the next nonce of the account
+	/// as reported by the chain. Calling it again, e.g. to retry the submission, releases the
+	/// previous nonce as failed.
+	pub fn reserve_nonce(&mut self, chain_nonce: u64) -> u64 {
+		if let Some(nonce) = self.nonce.take() {
+			self.coordinator.release(nonce, false);
+		}
+		let nonce = self.coordinator.reserve(chain_nonce);
+		self.nonce = Some(nonce);
+		nonce
+	}
+
+	/// Marks the transaction as included.
+	pub fn confirm(mut self) {
+		if let Some(nonce) = self.nonce.take() {
+			self.coordinator.release(nonce, true);
+		}
+	}
+}
+
+impl Drop for SubmissionSlot {
+	fn drop(&mut self) {
+		if let Some(nonce) = self.nonce.take() {
+			self.coordinator.release(nonce, false);
+		}
+		self.coordinator.queued.fetch_sub(1, Ordering::Relaxed);
+	}
+}
@@ -0,0 +1,151 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Journal of the message batches being submitted to a chain.
+//!
+//! A batch is written to the journal before it's submitted and removed once the submission
+//! returned, so the batches left in the journal on startup are the ones whose submission was
+//! interrupted by a crash. They may or may not have landed, so they're re-validated against the
+//! chain before being submitted again.
+
+use ibc_proto::google::protobuf::Any;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// A journaled message, as written to the journal file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingMessage {
+	pub type_url: String,
+	/// Hex encoded message.
+	pub value: String,
+}
+
+impl PendingMessage {
+	/// Decodes the message, `None` if its value isn't valid hex.
+	pub fn to_any(&self) -> Option<Any> {
+		Some(Any { type_url: self.type_url.clone(), value: hex::decode(&self.value).ok()? })
+	}
+}
+
+impl From<&Any> for PendingMessage {
+	fn from(msg: &Any) -> Self {
+		Self { type_url: msg.type_url.clone(), value: hex::encode(&msg.value) }
+	}
+}
+
+/// Message batches whose submission to a chain hasn't returned yet, optionally persisted to a
+/// JSON file so that they survive crashes.
+#[derive(Debug, Default)]
+pub struct PendingMessages {
+	next_id: u64,
+	batches: BTreeMap<u64, Vec<Any>>,
+	path: Option<PathBuf>,
+}
+
+impl PendingMessages {
+	/// Creates a journal, loading the batches left in `path` by a previous run if it's set.
+	pub fn new(path: Option<PathBuf>) -> Self {
+		let batches = path
+			.as_ref()
+			.filter(|path| path.exists())
+			.and_then(|path| {
+				let batches = std::fs::read(path).map_err(|e| e.to_string()).and_then(|bytes| {
+					serde_json::from_slice::<Vec<Vec<PendingMessage>>>(&bytes)
+						.map_err(|e| e.to_string())
+				});
+				match batches {
+					Ok(batches) => Some(batches),
+					Err(e) => {
+						log::warn!(target: "hyperspace", "Failed to load pending messages from {}: {e}", path.display());
+						None
+					},
+				}
+			})
+			.unwrap_or_default()
+			.into_iter()
+			.map(|batch| batch.iter().filter_map(PendingMessage::to_any).collect::<Vec<_>>())
+			.enumerate()
+			.map(|(id, batch)| (id as u64, batch))
+			.collect::<BTreeMap<_, _>>();
+		Self { next_id: batches.len() as u64, batches, path }
+	}
+
+	/// Journals a batch that is about to be submitted, returning its id.
+	pub fn insert(&mut self, batch: Vec<Any>) -> u64 {
+		let id = self.next_id;
+		self.next_id += 1;
+		if self.path.is_some() {
+			self.batches.insert(id, batch);
+			self.persist();
+		}
+		id
+	}
+
+	/// Removes a batch whose submission returned.
+	pub fn remove(&mut self, id: u64) {
+		if self.batches.remove(&id).is_some() {
+			self.persist();
+		}
+	}
+
+	/// Removes and returns all the journaled batches.
+	pub fn take(&mut self) -> Vec<Vec<Any>> {
+		let batches = std::mem::take(&mut self.batches).into_values().collect::<Vec<_>>();
+		if !batches.is_empty() {
+			self.persist();
+		}
+		batches
+	}
+
+	/// Returns the journaled batches, without removing them.
+	pub fn batches(&self) -> Vec<Vec<Any>> {
+		self.batches.values().cloned().collect()
+	}
+
+	/// Adds batches restored from a snapshot, so that they're resubmitted with the ones left by a
+	/// previous run. They're kept even if the journal isn't persisted.
+	pub fn restore(&mut self, batches: Vec<Vec<Any>>) {
+		for batch in batches {
+			self.batches.insert(self.next_id, batch);
+			self.next_id += 1;
+		}
+		self.persist();
+	}
+
+	pub fn len(&self) -> usize {
+		self.batches.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.batches.is_empty()
+	}
+
+	fn persist(&self) {
+		let Some(path) = &self.path else { return };
+		let batches = self
+			.batches
+			.values()
+			.map(|batch| batch.iter().map(PendingMessage::from).collect::<Vec<_>>())
+			.collect::<Vec<_>>();
+		// write to a temporary file first, so that a crash never leaves a truncated journal
+		let tmp = path.with_extension("tmp");
+		let result = serde_json::to_vec(&batches)
+			.map_err(|e| e.to_string())
+			.and_then(|bytes| std::fs::write(&tmp, bytes).map_err(|e| e.to_string()))
+			.and_then(|_| std::fs::rename(&tmp, path).map_err(|e| e.to_string()));
+		if let Err(e) = result {
+			log::warn!(target: "hyperspace", "Failed to save pending messages to {}: {e}", path.display());
+		}
+	}
+}
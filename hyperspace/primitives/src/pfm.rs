@@ -0,0 +1,114 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Memos of the packet forward middleware (PFM).
+//!
+//! A chain running the middleware forwards the tokens of an incoming ICS-20 transfer whose memo
+//! contains a `forward` object to the next chain, over `port`/`channel` to `receiver`, and only
+//! acknowledges the incoming packet once the forwarded one is acknowledged. The memo of the
+//! forwarded transfer is the `next` field of the object, so routes over several hops are built by
+//! nesting the objects:
+//!
+//! ```json
+//! {"forward": {"receiver": "...", "port": "transfer", "channel": "channel-1",
+//!   "next": {"forward": {"receiver": "...", "port": "transfer", "channel": "channel-7"}}}}
+//! ```
+
+use anyhow::anyhow;
+use ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A hop of a transfer forwarded by the packet forward middleware.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hop {
+	/// Port the tokens are forwarded over, on the chain forwarding them.
+	pub port: PortId,
+	/// Channel the tokens are forwarded over, on the chain forwarding them.
+	pub channel: ChannelId,
+	/// Receiver of the tokens on the next chain.
+	pub receiver: String,
+}
+
+impl FromStr for Hop {
+	type Err = anyhow::Error;
+
+	/// Parses a hop written as `{port}/{channel}/{receiver}`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.splitn(3, '/');
+		let (Some(port), Some(channel), Some(receiver)) =
+			(parts.next(), parts.next(), parts.next())
+		else {
+			return Err(anyhow!("Invalid hop {s}, expected {{port}}/{{channel}}/{{receiver}}"))
+		};
+		Ok(Self {
+			port: port.parse().map_err(|e| anyhow!("Invalid port of hop {s}: {e}"))?,
+			channel: channel.parse().map_err(|e| anyhow!("Invalid channel of hop {s}: {e}"))?,
+			receiver: receiver.to_string(),
+		})
+	}
+}
+
+/// The `forward` object of a memo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardMetadata {
+	pub receiver: String,
+	pub port: String,
+	pub channel: String,
+	/// Memo of the forwarded transfer.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub next: Option<Box<ForwardMemo>>,
+}
+
+impl ForwardMetadata {
+	/// Number of hops of the route, this one included.
+	pub fn hops(&self) -> usize {
+		1 + self.next.as_ref().map_or(0, |next| next.forward.hops())
+	}
+}
+
+/// A memo asking the receiving chain to forward the tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardMemo {
+	pub forward: ForwardMetadata,
+}
+
+impl ForwardMemo {
+	/// Builds the memo routing a transfer over `hops`, the first one being taken by the chain
+	/// receiving the transfer. Returns `None` if there are no hops.
+	pub fn new(hops: &[Hop]) -> Option<Self> {
+		hops.iter().rev().fold(None, |next, hop| {
+			Some(ForwardMemo {
+				forward: ForwardMetadata {
+					receiver: hop.receiver.clone(),
+					port: hop.port.to_string(),
+					channel: hop.channel.to_string(),
+					next: next.map(Box::new),
+				},
+			})
+		})
+	}
+
+	/// Parses the memo of a transfer, returning `None` if it doesn't ask for a forward.
+	pub fn parse(memo: &str) -> Option<Self> {
+		serde_json::from_str(memo).ok()
+	}
+}
+
+/// Returns the memo routing a transfer over `hops`, or an empty memo if there are none.
+pub fn forward_memo(hops: &[Hop]) -> String {
+	ForwardMemo::new(hops)
+		.map(|memo| serde_json::to_string(&memo).expect("memo is always serializable"))
+		.unwrap_or_default()
+}
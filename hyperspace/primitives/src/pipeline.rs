@@ -0,0 +1,79 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Counters of the relay pipeline of a chain, compared over time by the relayer's watchdog to
+//! detect packets that are seen but never relayed, without any error being reported.
+
+use ibc::{
+	core::ics24_host::identifier::{ChannelId, PortId},
+	events::IbcEvent,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Number of packets of a channel that were seen and relayed since the relayer started.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ChannelCounters {
+	/// `SendPacket` events seen on the chain.
+	pub observed: u64,
+	/// Packets whose `MsgRecvPacket`, `MsgTimeout` or `MsgTimeoutOnClose` was submitted.
+	pub submitted: u64,
+}
+
+/// Counters of the packets sent from the channels of a chain, and of the errors of its relay
+/// loop.
+#[derive(Debug, Default)]
+pub struct PipelineCounters {
+	channels: HashMap<(ChannelId, PortId), ChannelCounters>,
+	errors: u64,
+}
+
+impl PipelineCounters {
+	/// Counts the `SendPacket` events among `events`.
+	pub fn observe(&mut self, events: &[IbcEvent]) {
+		for event in events {
+			if let IbcEvent::SendPacket(send_packet) = event {
+				let packet = &send_packet.packet;
+				let key = (packet.source_channel, packet.source_port.clone());
+				self.channels.entry(key).or_default().observed += 1;
+			}
+		}
+	}
+
+	/// Counts `count` packets of the channel as submitted.
+	pub fn submit(&mut self, channel_id: ChannelId, port_id: PortId, count: u64) {
+		self.channels.entry((channel_id, port_id)).or_default().submitted += count;
+	}
+
+	/// Counts an error of the relay loop.
+	pub fn error(&mut self) {
+		self.errors += 1;
+	}
+
+	pub fn errors(&self) -> u64 {
+		self.errors
+	}
+
+	pub fn channel(&self, channel_id: ChannelId, port_id: PortId) -> ChannelCounters {
+		self.channels.get(&(channel_id, port_id)).copied().unwrap_or_default()
+	}
+
+	/// Counters of every channel, keyed by `port_id/channel_id`.
+	pub fn summary(&self) -> HashMap<String, ChannelCounters> {
+		self.channels
+			.iter()
+			.map(|((channel_id, port_id), counters)| (format!("{port_id}/{channel_id}"), *counters))
+			.collect()
+	}
+}
@@ -0,0 +1,143 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signed receipts of the submissions of the relayer, so that third parties (e.g. reimbursement
+//! or incentive programs) can verify the work it claims.
+//!
+//! A [`RelayReceipt`] is signed with the relayer's key on the chain the messages were submitted
+//! to, over the JSON encoding of the receipt with an empty `signature`. The receipts are appended
+//! to a [`ReceiptStore`], one JSON object per line.
+
+use crate::{KeyProvider, RelayedSequence};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::{
+	io::{BufRead, BufReader, Write},
+	path::PathBuf,
+	sync::{Arc, Mutex},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A payload signed with the relayer's key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadSignature {
+	/// Public key of the relayer, in the chain's encoding.
+	pub public_key: Vec<u8>,
+	pub signature: Vec<u8>,
+}
+
+/// Proof that the relayer submitted a batch of messages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayReceipt {
+	/// Chain the messages were submitted to.
+	pub chain: String,
+	/// Account of the relayer on `chain`.
+	pub relayer: String,
+	/// Packets, acknowledgements and timeouts delivered by the submission.
+	pub packets: Vec<RelayedSequence>,
+	/// Transaction (or extrinsic) of the submission.
+	pub tx_hash: String,
+	/// Estimated weight of the submission (gas on Cosmos chains).
+	pub weight: u64,
+	/// Unix timestamp of the submission, in seconds.
+	pub timestamp: u64,
+	/// Hex encoded public key of the relayer.
+	pub public_key: String,
+	/// Hex encoded signature of the receipt.
+	pub signature: String,
+}
+
+impl RelayReceipt {
+	/// Builds a receipt of a submission to `chain`, signed with its key.
+	pub fn new(
+		chain: &(impl KeyProvider + ?Sized),
+		chain_name: &str,
+		packets: Vec<RelayedSequence>,
+		tx_hash: String,
+		weight: u64,
+	) -> anyhow::Result<Self> {
+		let mut receipt = Self {
+			chain: chain_name.to_string(),
+			relayer: chain.account_id().to_string(),
+			packets,
+			tx_hash,
+			weight,
+			timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+			public_key: String::new(),
+			signature: String::new(),
+		};
+		let PayloadSignature { public_key, signature } =
+			chain.sign_payload(&receipt.signing_payload())?;
+		receipt.public_key = hex::encode(public_key);
+		receipt.signature = hex::encode(signature);
+		Ok(receipt)
+	}
+
+	/// Returns the bytes the signature is computed over: the JSON encoding of the receipt without
+	/// its signature.
+	pub fn signing_payload(&self) -> Vec<u8> {
+		let unsigned = Self { signature: String::new(), ..self.clone() };
+		serde_json::to_vec(&unsigned).expect("receipt is always serializable")
+	}
+}
+
+/// Append-only file of relay receipts, one JSON object per line.
+#[derive(Debug, Clone)]
+pub struct ReceiptStore {
+	path: PathBuf,
+	lock: Arc<Mutex<()>>,
+}
+
+impl ReceiptStore {
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		Self { path: path.into(), lock: Default::default() }
+	}
+
+	pub fn append(&self, receipt: &RelayReceipt) -> anyhow::Result<()> {
+		let _guard = self.lock.lock().unwrap();
+		let mut file =
+			std::fs::OpenOptions::new().create(true).append(true).open(&self.path).map_err(
+				|e| anyhow!("Failed to open receipt store {}: {e}", self.path.display()),
+			)?;
+		let mut line = serde_json::to_vec(receipt)?;
+		line.push(b'\n');
+		file.write_all(&line)?;
+		Ok(())
+	}
+
+	/// Returns up to `limit` receipts issued at or after `since`, oldest first.
+	pub fn receipts(&self, since: u64, limit: usize) -> anyhow::Result<Vec<RelayReceipt>> {
+		let _guard = self.lock.lock().unwrap();
+		if !self.path.exists() {
+			return Ok(vec![])
+		}
+		let file = std::fs::File::open(&self.path)?;
+		let mut receipts = vec![];
+		for line in BufReader::new(file).lines() {
+			let line = line?;
+			if line.trim().is_empty() {
+				continue
+			}
+			let receipt = serde_json::from_str::<RelayReceipt>(&line)
+				.map_err(|e| anyhow!("Invalid receipt in {}: {e}", self.path.display()))?;
+			if receipt.timestamp >= since {
+				receipts.push(receipt);
+				if receipts.len() == limit {
+					break
+				}
+			}
+		}
+		Ok(receipts)
+	}
+}
@@ -0,0 +1,160 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cache of the packet sequences that were relayed recently, so that overlapping finality events
+//! don't query proofs for, and resubmit, messages that were already delivered.
+
+use ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::PathBuf};
+
+/// Default number of blocks a relayed sequence is remembered for.
+pub const DEFAULT_RELAYED_SEQUENCES_RETENTION: u64 = 50;
+
+/// Message a packet was relayed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PacketDirection {
+	/// `MsgRecvPacket`
+	Recv,
+	/// `MsgAcknowledgement`
+	Ack,
+	/// `MsgTimeout` or `MsgTimeoutOnClose`
+	Timeout,
+}
+
+/// A relayed packet sequence. The channel is the channel end on the chain that owns the cache:
+/// the one packets are sent from for [`PacketDirection::Recv`] and [`PacketDirection::Timeout`],
+/// and the one packets are received on for [`PacketDirection::Ack`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RelayedSequence {
+	pub channel_id: ChannelId,
+	pub port_id: PortId,
+	pub sequence: u64,
+	pub direction: PacketDirection,
+}
+
+/// Sequences relayed within the last `retention` blocks of a chain, optionally persisted to a
+/// JSON file so that they survive restarts.
+#[derive(Debug, Default)]
+pub struct RelayedSequences {
+	retention: u64,
+	height: u64,
+	entries: HashMap<RelayedSequence, u64>,
+	path: Option<PathBuf>,
+}
+
+impl RelayedSequences {
+	/// Creates a cache remembering sequences for `retention` blocks, `0` disables it. If `path`
+	/// is set, the cache is loaded from and saved to it.
+	pub fn new(retention: u64, path: Option<PathBuf>) -> Self {
+		let entries = path
+			.as_ref()
+			.filter(|path| path.exists())
+			.and_then(|path| {
+				let entries = std::fs::read(path)
+					.map_err(|e| e.to_string())
+					.and_then(|bytes| {
+						serde_json::from_slice::<Vec<(RelayedSequence, u64)>>(&bytes)
+							.map_err(|e| e.to_string())
+					});
+				match entries {
+					Ok(entries) => Some(entries.into_iter().collect()),
+					Err(e) => {
+						log::warn!(target: "hyperspace", "Failed to load relayed sequences from {}: {e}", path.display());
+						None
+					},
+				}
+			})
+			.unwrap_or_default();
+		Self { retention, height: 0, entries, path }
+	}
+
+	/// Sets the current height of the chain, forgetting the sequences that were relayed more
+	/// than `retention` blocks ago.
+	pub fn advance(&mut self, height: u64) {
+		self.height = height;
+		let retention = self.retention;
+		let len = self.entries.len();
+		self.entries
+			.retain(|_, relayed_at| height.saturating_sub(*relayed_at) < retention);
+		if self.entries.len() != len {
+			self.persist();
+		}
+	}
+
+	/// Returns whether `sequence` was relayed within the last `retention` blocks.
+	pub fn contains(&self, sequence: &RelayedSequence) -> bool {
+		self.entries.contains_key(sequence)
+	}
+
+	/// Remembers `sequences` as relayed at the current height.
+	pub fn insert(&mut self, sequences: impl IntoIterator<Item = RelayedSequence>) {
+		if self.retention == 0 {
+			return
+		}
+		let height = self.height;
+		self.entries.extend(sequences.into_iter().map(|sequence| (sequence, height)));
+		self.persist();
+	}
+
+	/// Forgets the sequences relayed on the given channel end, so that they're queried again.
+	pub fn forget_channel(&mut self, channel_id: &ChannelId, port_id: &PortId) {
+		let len = self.entries.len();
+		self.entries
+			.retain(|relayed, _| relayed.channel_id != *channel_id || relayed.port_id != *port_id);
+		if self.entries.len() != len {
+			self.persist();
+		}
+	}
+
+	/// Height of the chain the cache was last advanced to, i.e. of the last finality event whose
+	/// packets were relayed.
+	pub fn height(&self) -> u64 {
+		self.height
+	}
+
+	/// Returns the cached sequences with the height they were relayed at.
+	pub fn entries(&self) -> Vec<(RelayedSequence, u64)> {
+		self.entries
+			.iter()
+			.map(|(sequence, height)| (sequence.clone(), *height))
+			.collect()
+	}
+
+	/// Replaces the cache with sequences restored from a snapshot, taken at `height`.
+	pub fn restore(&mut self, height: u64, entries: Vec<(RelayedSequence, u64)>) {
+		self.height = height;
+		self.entries = entries.into_iter().collect();
+		self.persist();
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	fn persist(&self) {
+		let Some(path) = &self.path else { return };
+		let entries = self.entries.iter().collect::<Vec<_>>();
+		let result = serde_json::to_vec(&entries)
+			.map_err(|e| e.to_string())
+			.and_then(|bytes| std::fs::write(path, bytes).map_err(|e| e.to_string()));
+		if let Err(e) = result {
+			log::warn!(target: "hyperspace", "Failed to save relayed sequences to {}: {e}", path.display());
+		}
+	}
+}
@@ -0,0 +1,135 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Trust assumptions of the light client of a chain, as created and kept up to date by the
+//! relayer.
+//!
+//! Which parameters are enforced depends on the client type:
+//! - `07-tendermint` clients store the trusting period, unbonding period and maximum clock drift in
+//!   their client state, so they're verified on-chain by the counterparty.
+//! - `10-grandpa` and `11-beefy` clients have no such fields. Their finality proofs always target
+//!   the latest finalized block, so they don't support a finality depth either.
+//! - the header lag, finality depth and misbehaviour policy are enforced by the relayer, which
+//!   sends a mandatory update whenever the client falls too far behind, holds back the blocks that
+//!   aren't buried deep enough and handles misbehaviour according to the policy.
+
+use anyhow::anyhow;
+use pallet_ibc::light_clients::AnyClientState;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Default trusting period of new `07-tendermint` clients, in seconds.
+pub const DEFAULT_TRUSTING_PERIOD: u64 = 64000;
+
+/// Default unbonding period of new `07-tendermint` clients, in seconds.
+pub const DEFAULT_UNBONDING_PERIOD: u64 = 1814400;
+
+/// Default maximum clock drift of new `07-tendermint` clients, in seconds.
+pub const DEFAULT_MAX_CLOCK_DRIFT: u64 = 15;
+
+/// Default maximum number of blocks a parachain client may lag behind the chain. The relay chain
+/// only keeps the state of this many blocks, past which the headers can't be proven anymore.
+pub const DEFAULT_PARACHAIN_MAX_HEADER_LAG: u64 = 256;
+
+/// What the relayer does when it detects a misbehaviour of a chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MisbehaviourPolicy {
+	/// Saves the evidence and submits it to the host of the client, freezing it.
+	#[default]
+	Submit,
+	/// Saves the evidence and raises an alert, leaving the submission to an operator.
+	Report,
+	/// Doesn't check the client updates for misbehaviour.
+	Ignore,
+}
+
+/// Trust assumptions of the light client of a chain on its counterparty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityParams {
+	/// Period during which a header can be trusted, in seconds.
+	pub trusting_period: u64,
+	/// Unbonding period of the chain's validators, in seconds. Must be longer than the trusting
+	/// period.
+	pub unbonding_period: u64,
+	/// Maximum drift between the clocks of the chain and its counterparty, in seconds.
+	pub max_clock_drift: u64,
+	/// Maximum number of blocks the client may lag behind the chain before it's updated, even if
+	/// no update is needed to relay packets. Defaults to a chain-specific value.
+	pub max_header_lag: Option<u64>,
+	/// Number of blocks a finalized block must be buried under before its events are relayed.
+	/// Only supported by chains finalized by Tendermint.
+	pub finality_depth: u64,
+	/// What to do when a misbehaviour of the chain is detected.
+	pub misbehaviour_policy: MisbehaviourPolicy,
+}
+
+impl Default for SecurityParams {
+	fn default() -> Self {
+		Self {
+			trusting_period: DEFAULT_TRUSTING_PERIOD,
+			unbonding_period: DEFAULT_UNBONDING_PERIOD,
+			max_clock_drift: DEFAULT_MAX_CLOCK_DRIFT,
+			max_header_lag: None,
+			finality_depth: 0,
+			misbehaviour_policy: MisbehaviourPolicy::default(),
+		}
+	}
+}
+
+impl SecurityParams {
+	pub fn trusting_period(&self) -> Duration {
+		Duration::from_secs(self.trusting_period)
+	}
+
+	pub fn unbonding_period(&self) -> Duration {
+		Duration::from_secs(self.unbonding_period)
+	}
+
+	pub fn max_clock_drift(&self) -> Duration {
+		Duration::from_secs(self.max_clock_drift)
+	}
+
+	/// Time after its latest consensus state past which `client_state` expires: the trusting
+	/// period of `07-tendermint` clients, which is part of their state, and the `trusting_period`
+	/// of these parameters for the other clients.
+	pub fn expiry_window(&self, client_state: &AnyClientState) -> Duration {
+		match client_state.unpack_recursive() {
+			AnyClientState::Tendermint(client_state) => client_state.trusting_period,
+			_ => self.trusting_period(),
+		}
+	}
+
+	/// Checks that the parameters are consistent with each other.
+	pub fn validate(&self) -> anyhow::Result<()> {
+		if self.trusting_period == 0 {
+			return Err(anyhow!("trusting_period must be greater than zero"))
+		}
+		if self.trusting_period >= self.unbonding_period {
+			return Err(anyhow!(
+				"trusting_period ({}s) must be shorter than unbonding_period ({}s)",
+				self.trusting_period,
+				self.unbonding_period
+			))
+		}
+		if self.max_clock_drift == 0 {
+			return Err(anyhow!("max_clock_drift must be greater than zero"))
+		}
+		if self.max_header_lag == Some(0) {
+			return Err(anyhow!("max_header_lag must be greater than zero"))
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,425 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Backends holding the relayer keys of a chain.
+//!
+//! By default the keys are read as raw secrets from the chain config (`private_key` or
+//! `mnemonic`). Setting `signer` in the chain config selects another backend:
+//! - `keystore`: the secrets are read from files encrypted with a passphrase, see [`EncryptedKey`],
+//! - `keystore_dir`: same as `keystore`, for all the files of a directory, so that keys can be
+//!   added or removed without editing the config and reloaded at runtime,
+//! - `remote`: the keys are held by a remote signing service, e.g. backed by an HSM, which signs
+//!   the payloads sent by the relayer over gRPC. The keys never enter the relayer process.
+
+use aes_gcm::{
+	aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+	Aes256Gcm, Nonce,
+};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::{
+	path::{Path, PathBuf},
+	time::Duration,
+};
+use tonic::{
+	client::Grpc,
+	codec::ProstCodec,
+	codegen::http::uri::PathAndQuery,
+	transport::{Channel, Endpoint},
+};
+
+/// Environment variable the passphrase of the encrypted keystore is read from by default.
+pub const DEFAULT_PASSPHRASE_ENV: &str = "HYPERSPACE_KEYSTORE_PASSPHRASE";
+
+/// Default timeout of the requests to a remote signer, in milliseconds.
+pub const DEFAULT_REMOTE_SIGNER_TIMEOUT_MS: u64 = 5_000;
+
+/// `log_n` parameter of the scrypt derivation of the key encrypting new keystore files.
+const DEFAULT_SCRYPT_LOG_N: u8 = 15;
+
+fn default_passphrase_env() -> String {
+	DEFAULT_PASSPHRASE_ENV.to_string()
+}
+
+fn default_remote_signer_timeout_ms() -> u64 {
+	DEFAULT_REMOTE_SIGNER_TIMEOUT_MS
+}
+
+/// Signs payloads with a relayer key.
+pub trait Signer: Send + Sync {
+	/// Public key, encoded as expected by the chain.
+	fn public_key(&self) -> &[u8];
+
+	/// Signs `payload` with the signature scheme of the key.
+	fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, anyhow::Error>;
+}
+
+/// Backend holding the relayer keys of a chain.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerConfig {
+	/// Secrets encrypted with a passphrase, one file per key, the first one being the primary key.
+	Keystore {
+		paths: Vec<PathBuf>,
+		/// Environment variable the passphrase is read from.
+		#[serde(default = "default_passphrase_env")]
+		passphrase_env: String,
+		/// See [`derive_secrets`].
+		#[serde(default)]
+		derivation_paths: Vec<String>,
+	},
+	/// Secrets encrypted with a passphrase, read from all the `.json` files of a directory in the
+	/// order of their names, the first one being the primary key.
+	KeystoreDir {
+		path: PathBuf,
+		/// Environment variable the passphrase is read from.
+		#[serde(default = "default_passphrase_env")]
+		passphrase_env: String,
+		/// See [`derive_secrets`].
+		#[serde(default)]
+		derivation_paths: Vec<String>,
+	},
+	/// Keys held by a remote signing service, the first one being the primary key.
+	Remote {
+		/// gRPC endpoint of the service.
+		url: String,
+		/// Identifiers of the keys in the service.
+		key_ids: Vec<String>,
+		#[serde(default = "default_remote_signer_timeout_ms")]
+		timeout_ms: u64,
+	},
+}
+
+/// A relayer key, as returned by [`load_signing_keys`].
+pub enum SigningKey {
+	/// Raw secret of the key, a private key or mnemonic depending on the chain.
+	Secret(String),
+	/// Key held by a remote signing service.
+	Remote(RemoteSigner),
+}
+
+/// Checks the relayer keys of a chain config without loading them: either the raw `primary` and
+/// `additional` secrets or a `signer` must be configured, and the files and passphrase of a
+/// keystore must exist.
+pub fn check_signer_config(
+	signer: Option<&SignerConfig>,
+	primary: &str,
+	additional: &[String],
+) -> Result<(), anyhow::Error> {
+	match signer {
+		None if primary.is_empty() => Err(anyhow!("No relayer key is configured")),
+		None => Ok(()),
+		Some(_) if !primary.is_empty() || !additional.is_empty() =>
+			Err(anyhow!("Raw relayer keys can't be configured along with a signer")),
+		Some(SignerConfig::Keystore { paths, passphrase_env, derivation_paths }) => {
+			if paths.is_empty() {
+				return Err(anyhow!("The keystore is empty"))
+			}
+			check_keystore_params(passphrase_env, derivation_paths)?;
+			match paths.iter().find(|path| !path.is_file()) {
+				Some(path) => Err(anyhow!("Keystore file {} not found", path.display())),
+				None => Ok(()),
+			}
+		},
+		Some(SignerConfig::KeystoreDir { path, passphrase_env, derivation_paths }) => {
+			if keystore_dir_files(path)?.is_empty() {
+				return Err(anyhow!("The keystore directory {} is empty", path.display()))
+			}
+			check_keystore_params(passphrase_env, derivation_paths)
+		},
+		Some(SignerConfig::Remote { key_ids, .. }) if key_ids.is_empty() =>
+			Err(anyhow!("No key of the remote signer is configured")),
+		Some(SignerConfig::Remote { .. }) => Ok(()),
+	}
+}
+
+fn check_keystore_params(
+	passphrase_env: &str,
+	derivation_paths: &[String],
+) -> Result<(), anyhow::Error> {
+	if std::env::var_os(passphrase_env).is_none() {
+		return Err(anyhow!("The keystore passphrase must be set in ${passphrase_env}"))
+	}
+	match derivation_paths.iter().find(|path| !path.starts_with('/')) {
+		Some(path) => Err(anyhow!("Invalid derivation path {path}, expected e.g. //relayer//0")),
+		None => Ok(()),
+	}
+}
+
+/// Returns the `.json` files of the keystore directory `path`, sorted by name.
+fn keystore_dir_files(path: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+	let entries = std::fs::read_dir(path)
+		.map_err(|e| anyhow!("Failed to read the keystore directory {}: {e}", path.display()))?;
+	let mut files = vec![];
+	for entry in entries {
+		let file = entry?.path();
+		if file.is_file() && file.extension().map_or(false, |extension| extension == "json") {
+			files.push(file);
+		}
+	}
+	files.sort();
+	Ok(files)
+}
+
+/// Derives the keys of `derivation_paths`, e.g. `//relayer//0`, from each of `secrets`, following
+/// the substrate secret URI syntax: hard junctions are prefixed with `//` and soft ones with `/`.
+/// Without derivation paths, the secrets are used as they are. Only supported by the schemes of
+/// substrate keys.
+pub fn derive_secrets(
+	secrets: Vec<String>,
+	derivation_paths: &[String],
+	scheme: &str,
+) -> Result<Vec<String>, anyhow::Error> {
+	if derivation_paths.is_empty() {
+		return Ok(secrets)
+	}
+	if !matches!(scheme, "sr25519" | "ed25519" | "ecdsa" | "ethereum") {
+		return Err(anyhow!("Derivation paths are not supported by {scheme} keys"))
+	}
+	Ok(secrets
+		.iter()
+		.flat_map(|secret| derivation_paths.iter().map(move |path| format!("{secret}{path}")))
+		.collect())
+}
+
+/// Decrypts the keystore files `paths` with the passphrase read from `passphrase_env`.
+fn read_keystore(
+	paths: &[PathBuf],
+	passphrase_env: &str,
+	derivation_paths: &[String],
+	scheme: &str,
+) -> Result<Vec<SigningKey>, anyhow::Error> {
+	let passphrase = std::env::var(passphrase_env)
+		.map_err(|_| anyhow!("The keystore passphrase must be set in ${passphrase_env}"))?;
+	let secrets = paths
+		.iter()
+		.map(|path| {
+			EncryptedKey::read(path)?
+				.decrypt(&passphrase)
+				.map_err(|e| anyhow!("Failed to decrypt {}: {e}", path.display()))
+		})
+		.collect::<Result<Vec<_>, anyhow::Error>>()?;
+	Ok(derive_secrets(secrets, derivation_paths, scheme)?
+		.into_iter()
+		.map(SigningKey::Secret)
+		.collect())
+}
+
+/// Loads the relayer keys of a chain, the primary key first. Without `signer`, these are the raw
+/// `primary` and `additional` secrets of the chain config, which must be left empty otherwise.
+/// `scheme` names the signature scheme of the keys, e.g. `sr25519` or `secp256k1`.
+pub async fn load_signing_keys(
+	signer: Option<&SignerConfig>,
+	primary: &str,
+	additional: &[String],
+	scheme: &str,
+) -> Result<Vec<SigningKey>, anyhow::Error> {
+	check_signer_config(signer, primary, additional)?;
+	let signer = match signer {
+		None => {
+			let secrets = std::iter::once(primary.to_string()).chain(additional.iter().cloned());
+			return Ok(secrets.map(SigningKey::Secret).collect())
+		},
+		Some(signer) => signer,
+	};
+	match signer {
+		SignerConfig::Keystore { paths, passphrase_env, derivation_paths } =>
+			read_keystore(paths, passphrase_env, derivation_paths, scheme),
+		SignerConfig::KeystoreDir { path, passphrase_env, derivation_paths } =>
+			read_keystore(&keystore_dir_files(path)?, passphrase_env, derivation_paths, scheme),
+		SignerConfig::Remote { url, key_ids, timeout_ms } => {
+			let channel = Endpoint::from_shared(url.clone())?
+				.timeout(Duration::from_millis(*timeout_ms))
+				.connect()
+				.await
+				.map_err(|e| anyhow!("Failed to connect to the remote signer at {url}: {e}"))?;
+			let mut keys = vec![];
+			for key_id in key_ids {
+				let signer = RemoteSigner::new(channel.clone(), key_id.clone(), scheme).await?;
+				keys.push(SigningKey::Remote(signer));
+			}
+			Ok(keys)
+		},
+	}
+}
+
+/// A secret encrypted with AES-256-GCM, under a key derived from a passphrase with scrypt. Stored
+/// as JSON, with the binary fields hex encoded.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EncryptedKey {
+	/// `log_n` parameter of scrypt, `r` and `p` being 8 and 1.
+	pub scrypt_log_n: u8,
+	pub salt: String,
+	pub nonce: String,
+	pub ciphertext: String,
+}
+
+impl EncryptedKey {
+	/// Encrypts `secret` with `passphrase`.
+	pub fn encrypt(secret: &str, passphrase: &str) -> Result<Self, anyhow::Error> {
+		let mut salt = [0u8; 32];
+		OsRng.fill_bytes(&mut salt);
+		let cipher = Self::cipher(passphrase, &salt, DEFAULT_SCRYPT_LOG_N)?;
+		let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+		let ciphertext = cipher
+			.encrypt(&nonce, secret.as_bytes())
+			.map_err(|_| anyhow!("Failed to encrypt the key"))?;
+		Ok(Self {
+			scrypt_log_n: DEFAULT_SCRYPT_LOG_N,
+			salt: hex::encode(salt),
+			nonce: hex::encode(nonce),
+			ciphertext: hex::encode(ciphertext),
+		})
+	}
+
+	/// Decrypts the secret with `passphrase`.
+	pub fn decrypt(&self, passphrase: &str) -> Result<String, anyhow::Error> {
+		let nonce = hex::decode(&self.nonce)?;
+		if nonce.len() != 12 {
+			return Err(anyhow!("Invalid nonce length {}", nonce.len()))
+		}
+		let cipher = Self::cipher(passphrase, &hex::decode(&self.salt)?, self.scrypt_log_n)?;
+		let secret = cipher
+			.decrypt(Nonce::from_slice(&nonce), hex::decode(&self.ciphertext)?.as_slice())
+			.map_err(|_| anyhow!("Wrong passphrase or corrupted key"))?;
+		Ok(String::from_utf8(secret)?)
+	}
+
+	pub fn read(path: &Path) -> Result<Self, anyhow::Error> {
+		let content = std::fs::read_to_string(path)
+			.map_err(|e| anyhow!("Failed to read {}: {e}", path.display()))?;
+		Ok(serde_json::from_str(&content)?)
+	}
+
+	pub fn write(&self, path: &Path) -> Result<(), anyhow::Error> {
+		std::fs::write(path, serde_json::to_string_pretty(self)?)
+			.map_err(|e| anyhow!("Failed to write {}: {e}", path.display()))
+	}
+
+	fn cipher(passphrase: &str, salt: &[u8], log_n: u8) -> Result<Aes256Gcm, anyhow::Error> {
+		let params = scrypt::Params::new(log_n, 8, 1, 32)
+			.map_err(|e| anyhow!("Invalid scrypt params: {e}"))?;
+		let mut key = [0u8; 32];
+		scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+			.map_err(|e| anyhow!("Failed to derive the key: {e}"))?;
+		Aes256Gcm::new_from_slice(&key).map_err(|_| anyhow!("Invalid key length"))
+	}
+}
+
+/// Messages of the `hyperspace.signer.v1.RemoteSigner` gRPC service.
+pub mod proto {
+	/// Requests the public key of `key_id`.
+	#[derive(Clone, PartialEq, prost::Message)]
+	pub struct PublicKeyRequest {
+		#[prost(string, tag = "1")]
+		pub key_id: String,
+		/// Expected signature scheme of the key, the service should fail if it doesn't match.
+		#[prost(string, tag = "2")]
+		pub scheme: String,
+	}
+
+	#[derive(Clone, PartialEq, prost::Message)]
+	pub struct PublicKeyResponse {
+		#[prost(bytes = "vec", tag = "1")]
+		pub public_key: Vec<u8>,
+	}
+
+	/// Requests the signature of `payload` with `key_id`.
+	#[derive(Clone, PartialEq, prost::Message)]
+	pub struct SignRequest {
+		#[prost(string, tag = "1")]
+		pub key_id: String,
+		#[prost(string, tag = "2")]
+		pub scheme: String,
+		#[prost(bytes = "vec", tag = "3")]
+		pub payload: Vec<u8>,
+	}
+
+	#[derive(Clone, PartialEq, prost::Message)]
+	pub struct SignResponse {
+		#[prost(bytes = "vec", tag = "1")]
+		pub signature: Vec<u8>,
+	}
+}
+
+const PUBLIC_KEY_PATH: &str = "/hyperspace.signer.v1.RemoteSigner/PublicKey";
+const SIGN_PATH: &str = "/hyperspace.signer.v1.RemoteSigner/Sign";
+
+/// A key held by a remote signing service.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+	client: Grpc<Channel>,
+	key_id: String,
+	scheme: String,
+	public_key: Vec<u8>,
+}
+
+impl RemoteSigner {
+	/// Fetches the public key of `key_id` from the service behind `channel`.
+	pub async fn new(
+		channel: Channel,
+		key_id: String,
+		scheme: &str,
+	) -> Result<Self, anyhow::Error> {
+		let request =
+			proto::PublicKeyRequest { key_id: key_id.clone(), scheme: scheme.to_string() };
+		let mut signer = Self {
+			client: Grpc::new(channel),
+			key_id,
+			scheme: scheme.to_string(),
+			public_key: vec![],
+		};
+		let response: proto::PublicKeyResponse = signer.call(PUBLIC_KEY_PATH, request).await?;
+		signer.public_key = response.public_key;
+		Ok(signer)
+	}
+
+	async fn call<Req, Res>(&self, path: &'static str, request: Req) -> Result<Res, anyhow::Error>
+	where
+		Req: prost::Message + 'static,
+		Res: prost::Message + Default + 'static,
+	{
+		let mut client = self.client.clone();
+		client.ready().await.map_err(|e| anyhow!("Remote signer is not ready: {e}"))?;
+		let response = client
+			.unary(
+				tonic::Request::new(request),
+				PathAndQuery::from_static(path),
+				ProstCodec::default(),
+			)
+			.await
+			.map_err(|e| anyhow!("Remote signer request for {} failed: {e}", self.key_id))?;
+		Ok(response.into_inner())
+	}
+}
+
+impl Signer for RemoteSigner {
+	fn public_key(&self) -> &[u8] {
+		&self.public_key
+	}
+
+	/// Blocks the calling thread until the service answers, since transactions are signed
+	/// synchronously. Must be called from the multi-threaded tokio runtime.
+	fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+		let request = proto::SignRequest {
+			key_id: self.key_id.clone(),
+			scheme: self.scheme.clone(),
+			payload: payload.to_vec(),
+		};
+		let response: proto::SignResponse = tokio::task::block_in_place(|| {
+			tokio::runtime::Handle::current().block_on(self.call(SIGN_PATH, request))
+		})?;
+		Ok(response.signature)
+	}
+}
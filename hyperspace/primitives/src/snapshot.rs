@@ -0,0 +1,122 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Snapshot of the in-memory relaying state of both chains of a relayer, dumped to a JSON file
+//! so that a stuck relayer can be inspected, and restored by another one to reproduce it.
+
+use crate::{pending::PendingMessage, IbcQuerier, RelayedSequence};
+use ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Relaying state of a single chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainSnapshot {
+	pub name: String,
+	/// Height of the last finality event of the chain whose packets were relayed.
+	pub last_processed_height: u64,
+	pub channel_whitelist: Vec<(ChannelId, PortId)>,
+	pub paused_channels: Vec<(ChannelId, PortId)>,
+	/// Sequences recently relayed from the chain, with the height they were relayed at.
+	pub relayed_sequences: Vec<(RelayedSequence, u64)>,
+	/// Message batches whose submission to the chain hasn't returned yet.
+	pub pending_messages: Vec<Vec<PendingMessage>>,
+}
+
+impl ChainSnapshot {
+	pub fn capture(chain: &impl IbcQuerier) -> Self {
+		let state = chain.common_state();
+		let relayed_sequences = state.relayed_sequences.lock().unwrap();
+		let sorted = |channels: Vec<(ChannelId, PortId)>| {
+			let mut channels = channels;
+			channels.sort();
+			channels
+		};
+		Self {
+			name: chain.name().to_string(),
+			last_processed_height: relayed_sequences.height(),
+			channel_whitelist: sorted(chain.channel_whitelist().into_iter().collect()),
+			paused_channels: sorted(state.paused_channels().into_iter().collect()),
+			relayed_sequences: relayed_sequences.entries(),
+			pending_messages: state
+				.pending_messages
+				.lock()
+				.unwrap()
+				.batches()
+				.iter()
+				.map(|batch| batch.iter().map(PendingMessage::from).collect())
+				.collect(),
+		}
+	}
+
+	/// Restores the snapshot into `chain`. The whitelisted channels of the snapshot are added to
+	/// the ones of the chain, the pending messages are resubmitted with the ones left by a previous
+	/// run.
+	pub fn restore(self, chain: &mut impl IbcQuerier) {
+		for channel in self.channel_whitelist {
+			chain.add_channel_to_whitelist(channel);
+		}
+		let state = chain.common_state();
+		for (channel_id, port_id) in self.paused_channels {
+			state.pause_channel(channel_id, port_id);
+		}
+		state
+			.relayed_sequences
+			.lock()
+			.unwrap()
+			.restore(self.last_processed_height, self.relayed_sequences);
+		let batches = self
+			.pending_messages
+			.iter()
+			.map(|batch| batch.iter().filter_map(PendingMessage::to_any).collect())
+			.collect();
+		state.pending_messages.lock().unwrap().restore(batches);
+	}
+}
+
+/// Relaying state of both chains of a relayer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RelayerSnapshot {
+	pub chains: Vec<ChainSnapshot>,
+}
+
+impl RelayerSnapshot {
+	pub fn capture(chain_a: &impl IbcQuerier, chain_b: &impl IbcQuerier) -> Self {
+		Self { chains: vec![ChainSnapshot::capture(chain_a), ChainSnapshot::capture(chain_b)] }
+	}
+
+	/// Restores the snapshot of the chain with the same name into `chain`. Returns the height of
+	/// the last finality event of the chain that was processed, if it was part of the snapshot and
+	/// any was processed.
+	pub fn restore(&self, chain: &mut impl IbcQuerier) -> Option<u64> {
+		let snapshot = self.chains.iter().find(|snapshot| snapshot.name == chain.name())?.clone();
+		let height = snapshot.last_processed_height;
+		log::info!(target: "hyperspace", "Restoring the state of {} at height {height}", chain.name());
+		snapshot.restore(chain);
+		Some(height).filter(|&height| height > 0)
+	}
+
+	pub fn read(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+		let path = path.as_ref();
+		let bytes = std::fs::read(path)
+			.map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+		Ok(serde_json::from_slice(&bytes)?)
+	}
+
+	pub fn write(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+		let path = path.as_ref();
+		std::fs::write(path, serde_json::to_vec_pretty(self)?)
+			.map_err(|e| anyhow::anyhow!("Failed to write {}: {e}", path.display()))
+	}
+}
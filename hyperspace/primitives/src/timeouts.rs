@@ -0,0 +1,101 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Timeouts of the packets sent from a chain that are still committed on it.
+//!
+//! [`query_maximum_height_for_timeout_proofs`](crate::query_maximum_height_for_timeout_proofs)
+//! needs the timeouts of the packets that may still time out on every finality event. Rather than
+//! querying the packet commitments and the packets of every whitelisted channel each time, a
+//! channel is scanned once, the first time its timeouts are needed, and its cache is then kept up
+//! to date from the events of the chain: `SendPacket` adds a packet, `AcknowledgePacket`,
+//! `TimeoutPacket` and `TimeoutOnClosePacket` remove it, since its commitment is deleted.
+
+use ibc::{
+	core::{
+		ics04_channel::channel::ChannelEnd,
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	events::IbcEvent,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Revision height (`0` if unset) and timestamp in nanoseconds (`0` if unset) at which a packet
+/// times out.
+pub type PacketTimeout = (u64, u64);
+
+/// The committed packets of a channel that have a timeout.
+#[derive(Debug, Clone)]
+pub struct ChannelTimeouts {
+	/// End of the channel on the chain, as of its scan, which names its counterparty.
+	pub channel_end: ChannelEnd,
+	/// Timeouts of the packets, by sequence.
+	pub timeouts: BTreeMap<u64, PacketTimeout>,
+}
+
+/// Timeouts of the committed packets of the channels of a chain that were scanned.
+#[derive(Debug, Default)]
+pub struct PendingTimeouts {
+	channels: HashMap<(ChannelId, PortId), ChannelTimeouts>,
+}
+
+impl PendingTimeouts {
+	/// Returns the timeouts of the channel, or `None` if it wasn't scanned yet.
+	pub fn channel(&self, channel_id: ChannelId, port_id: &PortId) -> Option<ChannelTimeouts> {
+		self.channels.get(&(channel_id, port_id.clone())).cloned()
+	}
+
+	/// Stores the result of the scan of a channel.
+	pub fn insert_channel(
+		&mut self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		channel: ChannelTimeouts,
+	) {
+		self.channels.insert((channel_id, port_id), channel);
+	}
+
+	/// Forgets the timeouts of a channel, which is scanned again the next time they're needed.
+	pub fn forget_channel(&mut self, channel_id: &ChannelId, port_id: &PortId) {
+		self.channels.remove(&(*channel_id, port_id.clone()));
+	}
+
+	/// Updates the scanned channels with the packets sent, acknowledged and timed out in
+	/// `events`. The events of the other channels are ignored, their scan will find the packets.
+	pub fn observe(&mut self, events: &[IbcEvent]) {
+		for event in events {
+			let Some(packet) = event.packet() else { continue };
+			let key = (packet.source_channel, packet.source_port.clone());
+			let Some(channel) = self.channels.get_mut(&key) else { continue };
+			let sequence = u64::from(packet.sequence);
+			match event {
+				IbcEvent::SendPacket(_) => {
+					let timeout = (
+						packet.timeout_height.revision_height,
+						packet.timeout_timestamp.nanoseconds(),
+					);
+					// packets without a timeout never time out
+					if timeout != (0, 0) {
+						channel.timeouts.insert(sequence, timeout);
+					}
+				},
+				IbcEvent::AcknowledgePacket(_) |
+				IbcEvent::TimeoutPacket(_) |
+				IbcEvent::TimeoutOnClosePacket(_) => {
+					channel.timeouts.remove(&sequence);
+				},
+				_ => (),
+			}
+		}
+	}
+}
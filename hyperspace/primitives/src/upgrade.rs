@@ -0,0 +1,97 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Upgrades of the light client of a chain on its counterparty.
+//!
+//! A chain that is upgraded in a way its counterparty clients can't follow (new chain id or
+//! revision, runtime upgrade changing its consensus) stores the client and consensus states the
+//! clients should be upgraded to. Once a client is updated to a height at which the states are
+//! stored, they can be proven against its latest consensus state and submitted to the
+//! counterparty with a `MsgUpgradeClient`.
+
+use crate::{mock::LocalClientTypes, Chain};
+use anyhow::anyhow;
+use ibc::{
+	core::ics02_client::{client_state::ClientState, msgs::upgrade_client::MsgUpgradeAnyClient},
+	protobuf::Protobuf,
+	tx_msg::Msg,
+};
+use ibc_proto::google::protobuf::Any;
+use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState};
+
+/// Client and consensus states a chain scheduled the upgrade of its counterparty clients to,
+/// with their proofs.
+#[derive(Debug, Clone)]
+pub struct UpgradedClient {
+	pub client_state: AnyClientState,
+	pub consensus_state: AnyConsensusState,
+	pub proof_client_state: Vec<u8>,
+	pub proof_consensus_state: Vec<u8>,
+}
+
+/// Returns the message upgrading the client of `source` on `sink`, if `source` stores an
+/// upgraded client that is more recent than the client.
+pub async fn query_client_upgrade(
+	source: &impl Chain,
+	sink: &impl Chain,
+) -> anyhow::Result<Option<Any>> {
+	let client_id = source.client_id();
+	let (latest_height, _) = sink.latest_height_and_timestamp().await?;
+	let response = sink.query_client_state(latest_height, client_id.clone()).await?;
+	let client_state = response
+		.client_state
+		.ok_or_else(|| anyhow!("Client state {client_id} not found on {}", sink.name()))
+		.and_then(|state| AnyClientState::try_from(state).map_err(|e| anyhow!("{e:?}")))?;
+	// frozen clients can only be recovered by governance
+	if client_state.frozen_height().is_some() {
+		return Ok(None)
+	}
+
+	let height = client_state.latest_height();
+	let Some(upgrade) = source.query_upgraded_client(height).await? else { return Ok(None) };
+	// the upgraded client is still stored once it was applied
+	if upgrade.client_state.latest_height() <= height {
+		return Ok(None)
+	}
+	log::info!(
+		target: "hyperspace",
+		"Client {client_id} of {} on {} can be upgraded from {height} to {}",
+		source.name(),
+		sink.name(),
+		upgrade.client_state.latest_height()
+	);
+
+	let msg = MsgUpgradeAnyClient::<LocalClientTypes> {
+		client_id,
+		client_state: upgrade.client_state,
+		consensus_state: upgrade.consensus_state,
+		proof_upgrade_client: upgrade.proof_client_state,
+		proof_upgrade_consensus_state: upgrade.proof_consensus_state,
+		signer: sink.account_id(),
+	};
+	Ok(Some(Any { type_url: msg.type_url(), value: msg.encode_vec()? }))
+}
+
+/// Upgrades the client of `source` on `sink` if `source` scheduled an upgrade of its clients.
+/// Returns whether an upgrade was submitted, which it isn't in dry runs.
+pub async fn upgrade_client(source: &impl Chain, sink: &impl Chain) -> anyhow::Result<bool> {
+	let Some(msg) = query_client_upgrade(source, sink).await? else { return Ok(false) };
+	if sink.common_state().dry_run {
+		log::info!(target: "hyperspace", "Dry run: not upgrading the client of {} on {}", source.name(), sink.name());
+		return Ok(false)
+	}
+	sink.submit(vec![msg]).await?;
+	log::info!(target: "hyperspace", "Upgraded the client of {} on {}", source.name(), sink.name());
+	Ok(true)
+}
@@ -17,6 +17,7 @@ use crate::TestProvider;
 use crate::{mock::LocalClientTypes, Chain};
 use futures::{future, StreamExt};
 use ibc::{
+	applications::transfer::PrefixedDenom,
 	core::{
 		ics02_client::msgs::create_client::MsgCreateAnyClient,
 		ics03_connection::{connection::Counterparty, msgs::conn_open_init::MsgConnectionOpenInit},
@@ -33,6 +34,7 @@ use ibc::{
 	tx_msg::Msg,
 };
 use ibc_proto::google::protobuf::Any;
+use ibc_rpc::ExpectedVoucherDenom;
 use std::{future::Future, time::Duration};
 
 pub async fn timeout_future<T: Future>(future: T, secs: u64, reason: String) -> T::Output {
@@ -67,34 +69,48 @@ pub async fn create_clients(
 	chain_a: &mut impl Chain,
 	chain_b: &mut impl Chain,
 ) -> Result<(ClientId, ClientId), anyhow::Error> {
-	let (client_state_a, cs_state_a) = chain_a.initialize_client_state().await?;
-	let (client_state_b, cs_state_b) = chain_b.initialize_client_state().await?;
-
-	let msg = MsgCreateAnyClient::<LocalClientTypes> {
-		client_state: client_state_b,
-		consensus_state: cs_state_b,
-		signer: chain_a.account_id(),
-	};
+	create_clients_at(chain_a, chain_b, None, None).await
+}
 
-	let msg = Any { type_url: msg.type_url(), value: msg.encode_vec()? };
+/// Creates the clients of both chains on each other. The client of a chain is initialized at the
+/// given past height of that chain, or at its latest finalized height if it's `None`.
+pub async fn create_clients_at(
+	chain_a: &mut impl Chain,
+	chain_b: &mut impl Chain,
+	height_a: Option<u64>,
+	height_b: Option<u64>,
+) -> Result<(ClientId, ClientId), anyhow::Error> {
+	let client_id_b_on_a = create_client_at(chain_a, chain_b, height_b).await?;
+	chain_b.set_client_id(client_id_b_on_a.clone());
+	let client_id_a_on_b = create_client_at(chain_b, chain_a, height_a).await?;
+	chain_a.set_client_id(client_id_a_on_b.clone());
+	Ok((client_id_a_on_b, client_id_b_on_a))
+}
 
-	let tx_id = chain_a.submit(vec![msg]).await?;
-	let client_id_b_on_a = chain_a.query_client_id_from_tx_hash(tx_id).await?;
-	chain_a.set_client_id(client_id_b_on_a.clone());
+/// Creates the client of `counterparty` on `host`, initialized at the given past height of
+/// `counterparty` or at its latest finalized height if it's `None`. The returned id is the
+/// [`IbcQuerier::client_id`](crate::IbcQuerier::client_id) of `counterparty`.
+pub async fn create_client_at(
+	host: &impl Chain,
+	counterparty: &impl Chain,
+	height: Option<u64>,
+) -> Result<ClientId, anyhow::Error> {
+	let (client_state, consensus_state) = match height {
+		Some(height) => counterparty.initialize_client_state_at(height).await?,
+		None => counterparty.initialize_client_state().await?,
+	};
 
 	let msg = MsgCreateAnyClient::<LocalClientTypes> {
-		client_state: client_state_a,
-		consensus_state: cs_state_a,
-		signer: chain_b.account_id(),
+		client_state,
+		consensus_state,
+		signer: host.account_id(),
 	};
 
 	let msg = Any { type_url: msg.type_url(), value: msg.encode_vec()? };
 
-	let tx_id = chain_b.submit(vec![msg]).await?;
-	let client_id_a_on_b = chain_b.query_client_id_from_tx_hash(tx_id).await?;
-	chain_a.set_client_id(client_id_b_on_a.clone());
-
-	Ok((client_id_a_on_b, client_id_b_on_a))
+	let tx_id = host.submit(vec![msg]).await?;
+	let client_id = host.query_client_id_from_tx_hash(tx_id).await?;
+	Ok(client_id)
 }
 
 /// Completes the connection handshake process
@@ -124,6 +140,7 @@ pub async fn create_connection(
 	let future = chain_b
 		.ibc_events()
 		.await
+		.filter_map(|ev| future::ready(ev.ok()))
 		.skip_while(|ev| future::ready(!matches!(ev, IbcEvent::OpenConfirmConnection(_))))
 		.take(1)
 		.collect::<Vec<_>>();
@@ -180,6 +197,7 @@ pub async fn create_channel(
 	let future = chain_b
 		.ibc_events()
 		.await
+		.filter_map(|ev| future::ready(ev.ok()))
 		.skip_while(|ev| future::ready(!matches!(ev, IbcEvent::OpenConfirmChannel(_))))
 		.take(1)
 		.collect::<Vec<_>>();
@@ -199,3 +217,34 @@ pub async fn create_channel(
 
 	Ok((channel_id_a, channel_id_b))
 }
+
+/// Computes the denomination that the counterparty of `chain` credits for `denom` when it's sent
+/// over the channel `port_id/channel_id`, so that it can be displayed before making the transfer.
+pub async fn query_expected_voucher_denom(
+	chain: &impl Chain,
+	denom: &PrefixedDenom,
+	channel_id: ChannelId,
+	port_id: PortId,
+) -> Result<ExpectedVoucherDenom, anyhow::Error> {
+	let (height, ..) = chain.latest_height_and_timestamp().await?;
+	let channel = chain
+		.query_channel_end(height, channel_id, port_id.clone())
+		.await?
+		.channel
+		.ok_or_else(|| {
+			anyhow::anyhow!("Channel {port_id}/{channel_id} not found on {}", chain.name())
+		})?;
+	let channel = ChannelEnd::try_from(channel)?;
+	let counterparty = channel.counterparty();
+	let counterparty_channel_id = *counterparty.channel_id().ok_or_else(|| {
+		anyhow::anyhow!("Channel {port_id}/{channel_id} has no counterparty channel id")
+	})?;
+
+	Ok(ExpectedVoucherDenom::new(
+		port_id,
+		channel_id,
+		counterparty.port_id().clone(),
+		counterparty_channel_id,
+		denom,
+	))
+}
@@ -0,0 +1,251 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Embeddable relayer: runs hyperspace inside another binary instead of through the CLI.
+//!
+//! ```ignore
+//! use hyperspace_sdk::{AnyConfig, Relayer, RelayerEvent, Side};
+//!
+//! let relayer = Relayer::from_configs(config_a, config_b).await?;
+//! let handle = relayer.start();
+//! let mut events = handle.subscribe();
+//! while let Ok(event) = events.recv().await {
+//! 	if let RelayerEvent::Ibc { chain, event } = event {
+//! 		println!("{chain}: {event:?}");
+//! 	}
+//! }
+//! handle.stop().await?;
+//! ```
+//!
+//! A running relayer can be controlled with the same [`RelayCommand`]s as the admin API, and
+//! arbitrary messages (e.g. `MsgTransfer`s) can be injected into either chain with
+//! [`RelayerHandle::submit`].
+
+use futures::StreamExt;
+use hyperspace_core::{plugin::ChainConfig, queue::flush_message_batch, relay_with_control, Mode};
+use ibc::events::IbcEvent;
+use ibc_proto::google::protobuf::Any;
+use metrics::handler::MetricsHandler;
+use primitives::Chain;
+use tokio::{
+	sync::{broadcast, mpsc::UnboundedSender},
+	task::JoinHandle,
+};
+
+pub use hyperspace_core::{
+	chain::{AnyChain, AnyConfig},
+	RelayCommand,
+};
+
+/// Number of events a subscriber can lag behind before missing some.
+const EVENTS_CAPACITY: usize = 1024;
+
+/// One of the two relayed chains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+	A,
+	B,
+}
+
+/// Events published by a running relayer.
+#[derive(Debug, Clone)]
+pub enum RelayerEvent {
+	/// An ibc event emitted by the chain named `chain`.
+	Ibc { chain: String, event: IbcEvent },
+	/// Messages were submitted to the chain named `chain` through [`RelayerHandle::submit`].
+	Submitted { chain: String, type_urls: Vec<String> },
+	/// The relay loop exited, with the error that stopped it if there was one.
+	Stopped { error: Option<String> },
+}
+
+/// A relayer between two chains that hasn't been started yet.
+pub struct Relayer<A: Chain, B: Chain> {
+	chain_a: A,
+	chain_b: B,
+	metrics_a: Option<MetricsHandler>,
+	metrics_b: Option<MetricsHandler>,
+	mode: Option<Mode>,
+}
+
+impl<A: Chain, B: Chain> Relayer<A, B> {
+	/// Creates a relayer between two already built chain clients.
+	pub fn new(chain_a: A, chain_b: B) -> Self {
+		Self { chain_a, chain_b, metrics_a: None, metrics_b: None, mode: None }
+	}
+
+	/// Records the metrics of both chains with the given handlers.
+	pub fn with_metrics(mut self, metrics_a: MetricsHandler, metrics_b: MetricsHandler) -> Self {
+		self.metrics_a = Some(metrics_a);
+		self.metrics_b = Some(metrics_b);
+		self
+	}
+
+	/// Sets the relay [`Mode`].
+	pub fn with_mode(mut self, mode: Mode) -> Self {
+		self.mode = Some(mode);
+		self
+	}
+
+	pub fn chain_a(&self) -> &A {
+		&self.chain_a
+	}
+
+	pub fn chain_b(&self) -> &B {
+		&self.chain_b
+	}
+
+	/// Spawns the relay loop and the event subscriptions on the current tokio runtime.
+	pub fn start(self) -> RelayerHandle<A, B> {
+		let Self { chain_a, chain_b, metrics_a, metrics_b, mode } = self;
+		let (events, _) = broadcast::channel(EVENTS_CAPACITY);
+		let (control, control_rx) = tokio::sync::mpsc::unbounded_channel();
+
+		let subscriptions = vec![
+			tokio::spawn(forward_ibc_events(chain_a.clone(), events.clone())),
+			tokio::spawn(forward_ibc_events(chain_b.clone(), events.clone())),
+		];
+		let relay = {
+			let (chain_a, chain_b, events) = (chain_a.clone(), chain_b.clone(), events.clone());
+			tokio::spawn(async move {
+				let result = relay_with_control(
+					chain_a,
+					chain_b,
+					metrics_a,
+					metrics_b,
+					mode,
+					Some(control_rx),
+				)
+				.await;
+				let error = result.as_ref().err().map(|e| format!("{e:?}"));
+				let _ = events.send(RelayerEvent::Stopped { error });
+				result
+			})
+		};
+
+		RelayerHandle { chain_a, chain_b, control, events, relay, subscriptions }
+	}
+}
+
+impl<C: Chain> Relayer<C, C> {
+	/// Builds both chain clients from their configs.
+	pub async fn from_configs<T: ChainConfig<Chain = C>>(
+		config_a: T,
+		config_b: T,
+	) -> anyhow::Result<Self> {
+		let chain_a = config_a.into_client().await?;
+		let chain_b = config_b.into_client().await?;
+		Ok(Self::new(chain_a, chain_b))
+	}
+}
+
+/// Handle of a running relayer. Dropping it doesn't stop the relayer, see
+/// [`RelayerHandle::stop`].
+pub struct RelayerHandle<A: Chain, B: Chain> {
+	chain_a: A,
+	chain_b: B,
+	control: UnboundedSender<RelayCommand<A, B>>,
+	events: broadcast::Sender<RelayerEvent>,
+	relay: JoinHandle<anyhow::Result<()>>,
+	subscriptions: Vec<JoinHandle<()>>,
+}
+
+impl<A: Chain, B: Chain> RelayerHandle<A, B> {
+	/// Returns a receiver of the events published from now on.
+	pub fn subscribe(&self) -> broadcast::Receiver<RelayerEvent> {
+		self.events.subscribe()
+	}
+
+	/// Clients of the relayed chains. They share their state with the relay loop.
+	pub fn chains(&self) -> (&A, &B) {
+		(&self.chain_a, &self.chain_b)
+	}
+
+	/// Queues `command` to be applied by the relay loop between two finality events.
+	pub fn send_command(&self, command: RelayCommand<A, B>) -> anyhow::Result<()> {
+		self.control
+			.send(command)
+			.map_err(|_| anyhow::anyhow!("Relay loop is not running"))
+	}
+
+	/// Submits `msgs` to the chain on `side`, e.g. to send packets from an application.
+	pub async fn submit(&self, side: Side, msgs: Vec<Any>) -> anyhow::Result<()> {
+		let type_urls = msgs.iter().map(|msg| msg.type_url.clone()).collect();
+		let chain = match side {
+			Side::A => {
+				flush_message_batch(msgs, None, &self.chain_a).await?;
+				self.chain_a.name()
+			},
+			Side::B => {
+				flush_message_batch(msgs, None, &self.chain_b).await?;
+				self.chain_b.name()
+			},
+		};
+		let _ = self
+			.events
+			.send(RelayerEvent::Submitted { chain: chain.to_string(), type_urls });
+		Ok(())
+	}
+
+	/// Returns whether the relay loop is still running.
+	pub fn is_running(&self) -> bool {
+		!self.relay.is_finished()
+	}
+
+	/// Stops the relayer, returning the error the relay loop exited with, if it had already
+	/// exited because of one.
+	pub async fn stop(self) -> anyhow::Result<()> {
+		for subscription in &self.subscriptions {
+			subscription.abort();
+		}
+		if self.relay.is_finished() {
+			return self.relay.await?
+		}
+		self.relay.abort();
+		let _ = self.events.send(RelayerEvent::Stopped { error: None });
+		Ok(())
+	}
+
+	/// Waits for the relay loop to exit.
+	pub async fn join(self) -> anyhow::Result<()> {
+		let result = self.relay.await?;
+		for subscription in &self.subscriptions {
+			subscription.abort();
+		}
+		result
+	}
+}
+
+/// Publishes the ibc events of `chain`, resubscribing when its event stream fails.
+async fn forward_ibc_events<C: Chain>(chain: C, events: broadcast::Sender<RelayerEvent>) {
+	loop {
+		let mut stream = chain.ibc_events().await;
+		while let Some(event) = stream.next().await {
+			match event {
+				Ok(event) => {
+					let _ =
+						events.send(RelayerEvent::Ibc { chain: chain.name().to_string(), event });
+				},
+				Err(e) => {
+					log::error!(
+						"Event stream failed for {}: {:?}. Resubscribing...",
+						chain.name(),
+						e
+					);
+					break
+				},
+			}
+		}
+		tokio::time::sleep(chain.expected_block_time()).await;
+	}
+}
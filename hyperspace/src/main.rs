@@ -13,34 +13,11 @@
 
 use anyhow::Result;
 use clap::Parser;
-use hyperspace_core::{
-	command::{Cli, Subcommand},
-	logging,
-};
+use hyperspace_core::{chain::AnyConfig, command::Cli};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-	logging::setup_logging();
 	let cli = Cli::parse();
 
-	match &cli.subcommand {
-		Subcommand::Relay(cmd) => cmd.run().await,
-		Subcommand::UploadWasm(cmd) => {
-			let new_config = cmd.run().await?;
-			cmd.save_config(&new_config).await
-		},
-		Subcommand::CreateClients(cmd) => {
-			let new_config = cmd.create_clients().await?;
-			cmd.save_config(&new_config).await
-		},
-		Subcommand::CreateConnection(cmd) => {
-			let new_config = cmd.create_connection().await?;
-			cmd.save_config(&new_config).await
-		},
-		Subcommand::CreateChannel(cmd) => {
-			let new_config = cmd.create_channel().await?;
-			cmd.save_config(&new_config).await
-		},
-		Subcommand::Fish(cmd) => cmd.fish().await,
-	}
+	cli.run::<AnyConfig>().await
 }
@@ -0,0 +1,783 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fault injection for the integration tests. A [`ChaosChain`] wraps a chain client and drops
+//! query responses, fails submissions, delays finality events and reports reorged heights, so that
+//! the retry and queue logic of the relayer can be exercised against real chains.
+
+use crate::send_transfer;
+use futures::{future, Stream, StreamExt};
+use hyperspace_primitives::{
+	utils::timeout_future, Chain, CommonClientState, Fee, FinalitySource, IbcQuerier, KeyProvider,
+	LightClientSync, MisbehaviourHandler, PayloadSignature, TestProvider, TxSubmitter, UpdateType,
+	UpgradedClient,
+};
+use ibc::{
+	applications::transfer::PrefixedCoin,
+	core::{
+		ics02_client::{client_state::ClientType, events::UpdateClient},
+		ics23_commitment::commitment::CommitmentPrefix,
+		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+	},
+	events::IbcEvent,
+	signer::Signer,
+	timestamp::Timestamp,
+	Height,
+};
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::core::{
+		channel::v1::{
+			QueryChannelResponse, QueryChannelsResponse, QueryNextSequenceReceiveResponse,
+			QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
+			QueryPacketReceiptResponse,
+		},
+		client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
+		connection::v1::{IdentifiedConnection, QueryConnectionResponse},
+	},
+};
+use ibc_proto_registry::{ProtoType, RawAny};
+use ibc_rpc::PacketInfo;
+use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState};
+use rand::Rng;
+use std::{
+	collections::{BTreeSet, HashMap, HashSet},
+	pin::Pin,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+/// The faults injected by a [`ChaosChain`]. Probabilities are between `0` and `1`, the default
+/// doesn't inject any fault.
+#[derive(Debug, Clone, Default)]
+pub struct ChaosConfig {
+	/// Probability that the response of a query is dropped, i.e. that the query fails.
+	pub dropped_responses: f64,
+	/// Probability that a submission fails before it reaches the chain.
+	pub failed_submissions: f64,
+	/// Finality events are delayed by a random duration up to this one.
+	pub max_finality_delay: Duration,
+	/// Probability that the latest height is reported up to `reorg_depth` blocks behind the
+	/// actual one.
+	pub reorgs: f64,
+	/// Maximum depth of a reorg.
+	pub reorg_depth: u64,
+}
+
+impl ChaosConfig {
+	/// Faults frequent enough to hit every code path of the relay loop within a few packets.
+	pub fn frequent() -> Self {
+		Self {
+			dropped_responses: 0.05,
+			failed_submissions: 0.2,
+			max_finality_delay: Duration::from_secs(12),
+			reorgs: 0.1,
+			reorg_depth: 3,
+		}
+	}
+}
+
+/// A packet message delivered to a chain, identified by the source end of the packet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Delivery {
+	/// `MsgRecvPacket`, `MsgAcknowledgement`, `MsgTimeout` or `MsgTimeoutOnClose`.
+	pub proto_type: ProtoType,
+	pub port_id: String,
+	pub channel_id: String,
+	pub sequence: u64,
+}
+
+impl Delivery {
+	fn from_message(message: &Any) -> Option<Self> {
+		let (proto_type, packet) = match RawAny::decode(message).ok()? {
+			RawAny::MsgRecvPacket(msg) => (ProtoType::MsgRecvPacket, msg.packet?),
+			RawAny::MsgAcknowledgement(msg) => (ProtoType::MsgAcknowledgement, msg.packet?),
+			RawAny::MsgTimeout(msg) => (ProtoType::MsgTimeout, msg.packet?),
+			RawAny::MsgTimeoutOnClose(msg) => (ProtoType::MsgTimeoutOnClose, msg.packet?),
+			_ => return None,
+		};
+		Some(Self {
+			proto_type,
+			port_id: packet.source_port,
+			channel_id: packet.source_channel,
+			sequence: packet.sequence,
+		})
+	}
+}
+
+/// A chain client injecting the faults of its [`ChaosConfig`]. The packet messages it submitted
+/// successfully are counted, see [`ChaosChain::deliveries`].
+#[derive(Clone)]
+pub struct ChaosChain<C> {
+	pub inner: C,
+	config: ChaosConfig,
+	deliveries: Arc<Mutex<HashMap<Delivery, usize>>>,
+}
+
+impl<C: Chain> ChaosChain<C> {
+	pub fn new(inner: C, config: ChaosConfig) -> Self {
+		Self { inner, config, deliveries: Default::default() }
+	}
+
+	/// Number of successful submissions of each packet message.
+	pub fn deliveries(&self) -> HashMap<Delivery, usize> {
+		self.deliveries.lock().unwrap().clone()
+	}
+
+	/// Number of successful submissions of a `proto_type` message for the packet `sequence` sent
+	/// over `channel_id`.
+	pub fn delivered(
+		&self,
+		proto_type: ProtoType,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: u64,
+	) -> usize {
+		let delivery = Delivery {
+			proto_type,
+			port_id: port_id.to_string(),
+			channel_id: channel_id.to_string(),
+			sequence,
+		};
+		self.deliveries.lock().unwrap().get(&delivery).copied().unwrap_or_default()
+	}
+
+	fn inject(&self, probability: f64, fault: &str) -> Result<(), C::Error> {
+		if probability > 0.0 && rand::thread_rng().gen_bool(probability.clamp(0.0, 1.0)) {
+			log::warn!(target: "hyperspace", "Injecting fault on {}: {fault}", self.inner.name());
+			return Err(C::Error::from(format!("Injected fault: {fault}")))
+		}
+		Ok(())
+	}
+
+	fn drop_response(&self, query: &str) -> Result<(), C::Error> {
+		self.inject(self.config.dropped_responses, &format!("dropped response of {query}"))
+	}
+
+	/// Returns the number of blocks the latest height is reorged by, if any.
+	fn reorg_depth(&self) -> u64 {
+		let mut rng = rand::thread_rng();
+		if self.config.reorg_depth == 0 || !rng.gen_bool(self.config.reorgs.clamp(0.0, 1.0)) {
+			return 0
+		}
+		rng.gen_range(1..=self.config.reorg_depth)
+	}
+}
+
+#[async_trait::async_trait]
+impl<C: Chain> IbcQuerier for ChaosChain<C> {
+	type AssetId = C::AssetId;
+	type Error = C::Error;
+
+	async fn query_ibc_events_in_range<T>(
+		&self,
+		counterparty: &T,
+		from: u64,
+		to: u64,
+	) -> Result<Vec<IbcEvent>, anyhow::Error>
+	where
+		T: Chain,
+	{
+		self.drop_response("query_ibc_events_in_range")?;
+		self.inner.query_ibc_events_in_range(counterparty, from, to).await
+	}
+
+	async fn ibc_events(
+		&self,
+	) -> Pin<Box<dyn Stream<Item = Result<IbcEvent, Self::Error>> + Send + 'static>> {
+		self.inner.ibc_events().await
+	}
+
+	async fn query_client_consensus(
+		&self,
+		at: Height,
+		client_id: ClientId,
+		consensus_height: Height,
+	) -> Result<QueryConsensusStateResponse, Self::Error> {
+		self.drop_response("query_client_consensus")?;
+		self.inner.query_client_consensus(at, client_id, consensus_height).await
+	}
+
+	async fn query_client_state(
+		&self,
+		at: Height,
+		client_id: ClientId,
+	) -> Result<QueryClientStateResponse, Self::Error> {
+		self.drop_response("query_client_state")?;
+		self.inner.query_client_state(at, client_id).await
+	}
+
+	async fn query_connection_end(
+		&self,
+		at: Height,
+		connection_id: ConnectionId,
+	) -> Result<QueryConnectionResponse, Self::Error> {
+		self.drop_response("query_connection_end")?;
+		self.inner.query_connection_end(at, connection_id).await
+	}
+
+	async fn query_channel_end(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryChannelResponse, Self::Error> {
+		self.drop_response("query_channel_end")?;
+		self.inner.query_channel_end(at, channel_id, port_id).await
+	}
+
+	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
+		self.drop_response("query_proof")?;
+		self.inner.query_proof(at, keys).await
+	}
+
+	async fn query_proofs(
+		&self,
+		at: Height,
+		keys: Vec<Vec<Vec<u8>>>,
+	) -> Result<Vec<Vec<u8>>, Self::Error> {
+		self.drop_response("query_proofs")?;
+		self.inner.query_proofs(at, keys).await
+	}
+
+	async fn query_batch_proof(
+		&self,
+		at: Height,
+		paths: Vec<String>,
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.drop_response("query_batch_proof")?;
+		self.inner.query_batch_proof(at, paths).await
+	}
+
+	async fn query_packet_commitment(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketCommitmentResponse, Self::Error> {
+		self.drop_response("query_packet_commitment")?;
+		self.inner.query_packet_commitment(at, port_id, channel_id, seq).await
+	}
+
+	async fn query_packet_acknowledgement(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketAcknowledgementResponse, Self::Error> {
+		self.drop_response("query_packet_acknowledgement")?;
+		self.inner.query_packet_acknowledgement(at, port_id, channel_id, seq).await
+	}
+
+	async fn query_next_sequence_recv(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+	) -> Result<QueryNextSequenceReceiveResponse, Self::Error> {
+		self.drop_response("query_next_sequence_recv")?;
+		self.inner.query_next_sequence_recv(at, port_id, channel_id).await
+	}
+
+	async fn query_packet_receipt(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketReceiptResponse, Self::Error> {
+		self.drop_response("query_packet_receipt")?;
+		self.inner.query_packet_receipt(at, port_id, channel_id, seq).await
+	}
+
+	async fn latest_height_and_timestamp(&self) -> Result<(Height, Timestamp), Self::Error> {
+		self.drop_response("latest_height_and_timestamp")?;
+		let (height, timestamp) = self.inner.latest_height_and_timestamp().await?;
+		let depth = self.reorg_depth();
+		if depth == 0 || depth >= height.revision_height {
+			return Ok((height, timestamp))
+		}
+		let height = Height::new(height.revision_number, height.revision_height - depth);
+		log::warn!(target: "hyperspace", "Injecting fault on {}: reorg to {height}", self.inner.name());
+		let timestamp = self.inner.query_timestamp_at(height.revision_height).await?;
+		let timestamp = Timestamp::from_nanoseconds(timestamp)
+			.map_err(|e| C::Error::from(format!("Invalid timestamp {timestamp}: {e}")))?;
+		Ok((height, timestamp))
+	}
+
+	async fn query_packet_commitments(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.drop_response("query_packet_commitments")?;
+		self.inner.query_packet_commitments(at, channel_id, port_id).await
+	}
+
+	async fn query_packet_acknowledgements(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.drop_response("query_packet_acknowledgements")?;
+		self.inner.query_packet_acknowledgements(at, channel_id, port_id).await
+	}
+
+	async fn query_unreceived_packets(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.drop_response("query_unreceived_packets")?;
+		self.inner.query_unreceived_packets(at, channel_id, port_id, seqs).await
+	}
+
+	async fn query_unreceived_acknowledgements(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<u64>, Self::Error> {
+		self.drop_response("query_unreceived_acknowledgements")?;
+		self.inner
+			.query_unreceived_acknowledgements(at, channel_id, port_id, seqs)
+			.await
+	}
+
+	fn channel_whitelist(&self) -> HashSet<(ChannelId, PortId)> {
+		self.inner.channel_whitelist()
+	}
+
+	async fn query_connection_channels(
+		&self,
+		at: Height,
+		connection_id: &ConnectionId,
+	) -> Result<QueryChannelsResponse, Self::Error> {
+		self.drop_response("query_connection_channels")?;
+		self.inner.query_connection_channels(at, connection_id).await
+	}
+
+	async fn query_send_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		self.drop_response("query_send_packets")?;
+		self.inner.query_send_packets(channel_id, port_id, seqs).await
+	}
+
+	async fn query_received_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		self.drop_response("query_received_packets")?;
+		self.inner.query_received_packets(channel_id, port_id, seqs).await
+	}
+
+	fn expected_block_time(&self) -> Duration {
+		self.inner.expected_block_time()
+	}
+
+	async fn query_client_update_time_and_height(
+		&self,
+		client_id: ClientId,
+		client_height: Height,
+	) -> Result<(Height, Timestamp), Self::Error> {
+		self.drop_response("query_client_update_time_and_height")?;
+		self.inner.query_client_update_time_and_height(client_id, client_height).await
+	}
+
+	async fn query_host_consensus_state_proof(
+		&self,
+		client_state: &AnyClientState,
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.drop_response("query_host_consensus_state_proof")?;
+		self.inner.query_host_consensus_state_proof(client_state).await
+	}
+
+	async fn query_upgraded_client(
+		&self,
+		at: Height,
+	) -> Result<Option<UpgradedClient>, Self::Error> {
+		self.drop_response("query_upgraded_client")?;
+		self.inner.query_upgraded_client(at).await
+	}
+
+	async fn query_ibc_balance(
+		&self,
+		asset_id: Self::AssetId,
+	) -> Result<Vec<PrefixedCoin>, Self::Error> {
+		self.drop_response("query_ibc_balance")?;
+		self.inner.query_ibc_balance(asset_id).await
+	}
+
+	async fn query_native_balance(&self) -> Result<PrefixedCoin, Self::Error> {
+		self.drop_response("query_native_balance")?;
+		self.inner.query_native_balance().await
+	}
+
+	async fn query_key_balances(&self) -> Result<Vec<(String, PrefixedCoin)>, Self::Error> {
+		self.drop_response("query_key_balances")?;
+		self.inner.query_key_balances().await
+	}
+
+	fn connection_prefix(&self) -> CommitmentPrefix {
+		self.inner.connection_prefix()
+	}
+
+	fn client_id(&self) -> ClientId {
+		self.inner.client_id()
+	}
+
+	fn set_client_id(&mut self, client_id: ClientId) {
+		self.inner.set_client_id(client_id)
+	}
+
+	fn connection_id(&self) -> Option<ConnectionId> {
+		self.inner.connection_id()
+	}
+
+	fn set_channel_whitelist(&mut self, channel_whitelist: HashSet<(ChannelId, PortId)>) {
+		self.inner.set_channel_whitelist(channel_whitelist)
+	}
+
+	fn add_channel_to_whitelist(&mut self, channel: (ChannelId, PortId)) {
+		self.inner.add_channel_to_whitelist(channel)
+	}
+
+	fn set_connection_id(&mut self, connection_id: ConnectionId) {
+		self.inner.set_connection_id(connection_id)
+	}
+
+	fn client_type(&self) -> ClientType {
+		self.inner.client_type()
+	}
+
+	async fn query_timestamp_at(&self, block_number: u64) -> Result<u64, Self::Error> {
+		self.drop_response("query_timestamp_at")?;
+		self.inner.query_timestamp_at(block_number).await
+	}
+
+	async fn query_clients(&self) -> Result<Vec<ClientId>, Self::Error> {
+		self.drop_response("query_clients")?;
+		self.inner.query_clients().await
+	}
+
+	async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
+		self.drop_response("query_channels")?;
+		self.inner.query_channels().await
+	}
+
+	async fn query_connection_using_client(
+		&self,
+		height: u32,
+		client_id: String,
+	) -> Result<Vec<IdentifiedConnection>, Self::Error> {
+		self.drop_response("query_connection_using_client")?;
+		self.inner.query_connection_using_client(height, client_id).await
+	}
+
+	fn name(&self) -> &str {
+		self.inner.name()
+	}
+
+	async fn query_best_and_finalized_heights(&self) -> Result<(u64, u64), Self::Error> {
+		self.drop_response("query_best_and_finalized_heights")?;
+		let (best, finalized) = self.inner.query_best_and_finalized_heights().await?;
+		let depth = self.reorg_depth();
+		Ok((best.saturating_sub(depth).max(1), finalized.saturating_sub(depth).max(1)))
+	}
+
+	async fn handle_error(&mut self, error: &anyhow::Error) -> Result<(), anyhow::Error> {
+		self.inner.handle_error(error).await
+	}
+
+	fn common_state(&self) -> &CommonClientState {
+		self.inner.common_state()
+	}
+
+	fn common_state_mut(&mut self) -> &mut CommonClientState {
+		self.inner.common_state_mut()
+	}
+
+	fn rpc_call_delay(&self) -> Duration {
+		self.inner.rpc_call_delay()
+	}
+
+	fn set_rpc_call_delay(&mut self, delay: Duration) {
+		self.inner.set_rpc_call_delay(delay)
+	}
+
+	async fn reconnect(&mut self) -> anyhow::Result<()> {
+		self.inner.reconnect().await
+	}
+}
+
+#[async_trait::async_trait]
+impl<C: Chain> TxSubmitter for ChaosChain<C> {
+	type TransactionId = C::TransactionId;
+
+	async fn query_client_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<ClientId, Self::Error> {
+		self.inner.query_client_id_from_tx_hash(tx_id).await
+	}
+
+	async fn query_connection_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<ConnectionId, Self::Error> {
+		self.inner.query_connection_id_from_tx_hash(tx_id).await
+	}
+
+	async fn query_channel_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<(ChannelId, PortId), Self::Error> {
+		self.inner.query_channel_id_from_tx_hash(tx_id).await
+	}
+
+	async fn upload_wasm(&self, wasm: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
+		self.inner.upload_wasm(wasm).await
+	}
+
+	fn block_max_weight(&self) -> u64 {
+		self.inner.block_max_weight()
+	}
+
+	async fn estimate_weight(&self, msg: Vec<Any>) -> Result<u64, Self::Error> {
+		self.drop_response("estimate_weight")?;
+		self.inner.estimate_weight(msg).await
+	}
+
+	async fn estimate_fee(&self, msgs: &[Any]) -> Result<Fee, Self::Error> {
+		self.drop_response("estimate_fee")?;
+		self.inner.estimate_fee(msgs).await
+	}
+
+	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Self::Error> {
+		self.inject(self.config.failed_submissions, "failed submission")?;
+		let tx_id = self.inner.submit(messages.clone()).await?;
+		let mut deliveries = self.deliveries.lock().unwrap();
+		for delivery in messages.iter().filter_map(Delivery::from_message) {
+			*deliveries.entry(delivery).or_default() += 1;
+		}
+		Ok(tx_id)
+	}
+}
+
+#[async_trait::async_trait]
+impl<C> FinalitySource for ChaosChain<C>
+where
+	C: Chain,
+	C::FinalityEvent: Sync,
+{
+	type FinalityEvent = C::FinalityEvent;
+
+	fn is_mandatory_finality_event(finality_event: &Self::FinalityEvent) -> bool {
+		C::is_mandatory_finality_event(finality_event)
+	}
+
+	async fn query_latest_ibc_events<T>(
+		&mut self,
+		finality_event: Self::FinalityEvent,
+		counterparty: &T,
+	) -> Result<Vec<(Any, Height, Vec<IbcEvent>, UpdateType)>, anyhow::Error>
+	where
+		T: Chain,
+	{
+		self.drop_response("query_latest_ibc_events")?;
+		self.inner.query_latest_ibc_events(finality_event, counterparty).await
+	}
+
+	async fn is_update_required(
+		&self,
+		latest_height: u64,
+		latest_client_height_on_counterparty: u64,
+	) -> Result<bool, Self::Error> {
+		self.inner
+			.is_update_required(latest_height, latest_client_height_on_counterparty)
+			.await
+	}
+
+	async fn initialize_client_state(
+		&self,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		self.inner.initialize_client_state().await
+	}
+
+	async fn initialize_client_state_at(
+		&self,
+		height: u64,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		self.inner.initialize_client_state_at(height).await
+	}
+
+	async fn finality_notifications(
+		&self,
+	) -> Result<Pin<Box<dyn Stream<Item = Self::FinalityEvent> + Send + Sync>>, Self::Error> {
+		let stream = self.inner.finality_notifications().await?;
+		let max_delay = self.config.max_finality_delay;
+		if max_delay.is_zero() {
+			return Ok(stream)
+		}
+		let name = self.inner.name().to_string();
+		Ok(Box::pin(stream.then(move |event| {
+			let delay = rand::thread_rng().gen_range(Duration::ZERO..=max_delay);
+			log::warn!(target: "hyperspace", "Injecting fault on {name}: finality event delayed by {delay:?}");
+			async move {
+				tokio::time::sleep(delay).await;
+				event
+			}
+		})))
+	}
+
+	async fn query_client_message(
+		&self,
+		update: UpdateClient,
+	) -> Result<AnyClientMessage, Self::Error> {
+		self.drop_response("query_client_message")?;
+		self.inner.query_client_message(update).await
+	}
+
+	async fn get_proof_height(&self, block_height: Height) -> Height {
+		self.inner.get_proof_height(block_height).await
+	}
+}
+
+#[async_trait::async_trait]
+impl<C: Chain> LightClientSync for ChaosChain<C> {
+	async fn is_synced<T: Chain>(&self, counterparty: &T) -> Result<bool, anyhow::Error> {
+		self.drop_response("is_synced")?;
+		self.inner.is_synced(counterparty).await
+	}
+
+	async fn fetch_mandatory_updates<T: Chain>(
+		&self,
+		counterparty: &T,
+	) -> Result<(Vec<Any>, Vec<IbcEvent>), anyhow::Error> {
+		self.drop_response("fetch_mandatory_updates")?;
+		self.inner.fetch_mandatory_updates(counterparty).await
+	}
+}
+
+#[async_trait::async_trait]
+impl<C: Chain> MisbehaviourHandler for ChaosChain<C> {
+	async fn detect_misbehaviour<T: Chain>(
+		&self,
+		counterparty: &T,
+		client_message: AnyClientMessage,
+	) -> Result<Option<Any>, anyhow::Error> {
+		self.inner.detect_misbehaviour(counterparty, client_message).await
+	}
+}
+
+impl<C: Chain> KeyProvider for ChaosChain<C> {
+	fn account_id(&self) -> Signer {
+		self.inner.account_id()
+	}
+
+	fn sign_payload(&self, payload: &[u8]) -> Result<PayloadSignature, anyhow::Error> {
+		self.inner.sign_payload(payload)
+	}
+
+	fn reload_keys(&mut self) -> Result<Vec<String>, anyhow::Error> {
+		self.inner.reload_keys()
+	}
+}
+
+/// Sends `packets` transfers from `chain_a` over `channel_a` while both chains are relayed with
+/// the faults of `config` injected, then asserts that every packet was received and acknowledged
+/// exactly once.
+pub async fn ibc_messaging_with_faults<A, B>(
+	chain_a: &mut A,
+	chain_b: &mut B,
+	asset_a: A::AssetId,
+	channel_a: ChannelId,
+	config: ChaosConfig,
+	packets: usize,
+) where
+	A: TestProvider,
+	A::FinalityEvent: Send + Sync,
+	A::Error: From<B::Error>,
+	B: TestProvider,
+	B::FinalityEvent: Send + Sync,
+	B::Error: From<A::Error>,
+{
+	let chaos_a = ChaosChain::new(chain_a.clone(), config.clone());
+	let chaos_b = ChaosChain::new(chain_b.clone(), config);
+	let (relayed_a, relayed_b) = (chaos_a.clone(), chaos_b.clone());
+	let handle = tokio::task::spawn(async move {
+		hyperspace_core::relay(relayed_a, relayed_b, None, None, None).await.unwrap()
+	});
+
+	let port_id = PortId::transfer();
+	let mut events = chain_a.ibc_events().await.filter_map(|ev| future::ready(ev.ok()));
+	for _ in 0..packets {
+		send_transfer(chain_a, chain_b, asset_a.clone(), channel_a, None).await;
+	}
+	let future = async {
+		let (mut sent, mut acknowledged) = (BTreeSet::new(), BTreeSet::new());
+		while let Some(event) = events.next().await {
+			match event {
+				IbcEvent::SendPacket(e) if e.packet.source_channel == channel_a => {
+					sent.insert(u64::from(e.packet.sequence));
+				},
+				IbcEvent::AcknowledgePacket(e) if e.packet.source_channel == channel_a => {
+					acknowledged.insert(u64::from(e.packet.sequence));
+				},
+				_ => continue,
+			}
+			if sent.len() == packets && sent.is_subset(&acknowledged) {
+				break
+			}
+		}
+		sent
+	};
+	let sent = timeout_future(
+		future,
+		packets as u64 * 10 * 60,
+		format!("Not all packets sent from {} were acknowledged", chain_a.name()),
+	)
+	.await;
+	handle.abort();
+
+	let (latest_height, _) = chain_a.latest_height_and_timestamp().await.unwrap();
+	let commitments = chain_a
+		.query_packet_commitments(latest_height, channel_a, port_id.clone())
+		.await
+		.unwrap();
+	let lost = sent.iter().filter(|seq| commitments.contains(*seq)).collect::<Vec<_>>();
+	assert!(lost.is_empty(), "Commitments of packets {lost:?} were not deleted");
+
+	for seq in sent {
+		let received = chaos_b.delivered(ProtoType::MsgRecvPacket, &port_id, &channel_a, seq);
+		assert_eq!(received, 1, "Packet {seq} was received {received} times");
+		let acknowledged =
+			chaos_a.delivered(ProtoType::MsgAcknowledgement, &port_id, &channel_a, seq);
+		assert_eq!(acknowledged, 1, "Packet {seq} was acknowledged {acknowledged} times");
+	}
+	log::info!(target: "hyperspace", "🚀🚀 {packets} packets relayed exactly once with faults injected");
+}
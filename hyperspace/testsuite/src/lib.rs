@@ -39,6 +39,7 @@ use std::{str::FromStr, time::Duration};
 use tendermint_proto::Protobuf;
 use tokio::task::JoinHandle;
 
+pub mod chaos;
 pub mod misbehaviour;
 pub mod ordered_channels;
 mod utils;
@@ -149,6 +150,27 @@ async fn send_transfer<A, B>(
 	channel_id: ChannelId,
 	timeout: Option<Timeout>,
 ) -> (u128, MsgTransfer<PrefixedCoin>)
+where
+	A: TestProvider,
+	A::FinalityEvent: Send + Sync,
+	A::Error: From<B::Error>,
+	B: TestProvider,
+	B::FinalityEvent: Send + Sync,
+	B::Error: From<A::Error>,
+{
+	send_transfer_with_memo(chain_a, chain_b, asset_a, channel_id, timeout, String::new()).await
+}
+
+/// Same as [`send_transfer`], with the ICS-20 `memo` of the packet, e.g. the forwarding
+/// instructions of the packet forward middleware built with [`primitives::pfm::forward_memo`].
+pub async fn send_transfer_with_memo<A, B>(
+	chain_a: &A,
+	chain_b: &B,
+	asset_a: A::AssetId,
+	channel_id: ChannelId,
+	timeout: Option<Timeout>,
+	memo: String,
+) -> (u128, MsgTransfer<PrefixedCoin>)
 where
 	A: TestProvider,
 	A::FinalityEvent: Send + Sync,
@@ -197,7 +219,7 @@ where
 		receiver: chain_b.account_id(),
 		timeout_height,
 		timeout_timestamp,
-		memo: "".to_string(),
+		memo,
 	};
 	chain_a.send_transfer(msg.clone()).await.expect("Failed to send transfer: ");
 	(amount, msg)
@@ -216,6 +238,7 @@ async fn assert_send_transfer<A>(
 	let future = chain
 		.ibc_events()
 		.await
+		.filter_map(|ev| future::ready(ev.ok()))
 		.skip_while(|ev| future::ready(!matches!(ev, IbcEvent::AcknowledgePacket(_))))
 		.take(1)
 		.collect::<Vec<_>>();
@@ -269,6 +292,7 @@ async fn send_packet_and_assert_height_timeout<A, B>(
 	let future = chain_b
 		.subscribe_blocks()
 		.await
+		.filter_map(|ev| future::ready(ev.ok()))
 		.skip_while(|block_number| {
 			future::ready(*block_number < msg.timeout_height.revision_height)
 		})
@@ -321,6 +345,7 @@ async fn send_packet_and_assert_timestamp_timeout<A, B>(
 	let future = chain_b
 		.subscribe_blocks()
 		.await
+		.filter_map(|ev| future::ready(ev.ok()))
 		.skip_while(|block_number| {
 			let block_number = *block_number;
 			let chain_clone = chain_b.clone();
@@ -402,6 +427,7 @@ async fn send_channel_close_init_and_assert_channel_close_confirm<A, B>(
 	let future = chain_b
 		.ibc_events()
 		.await
+		.filter_map(|ev| future::ready(ev.ok()))
 		.skip_while(|ev| future::ready(!matches!(ev, IbcEvent::CloseConfirmChannel(_))))
 		.take(1)
 		.collect::<Vec<_>>();
@@ -456,6 +482,7 @@ async fn send_packet_and_assert_timeout_on_channel_close<A, B>(
 	let future = chain_b
 		.subscribe_blocks()
 		.await
+		.filter_map(|ev| future::ready(ev.ok()))
 		.skip_while(|block_number| {
 			let block_number = *block_number;
 			let chain_clone = chain_b.clone();
@@ -40,8 +40,12 @@ where
 {
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
+	let store = hyperspace_core::misbehaviour::EvidenceStore::new(
+		std::env::temp_dir().join("hyperspace-misbehaviour-evidence"),
+	)
+	.unwrap();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::fish(client_a_clone, client_b_clone).await.unwrap()
+		hyperspace_core::fish(client_a_clone, client_b_clone, store).await.unwrap()
 	});
 	info!("Waiting for the next block...");
 
@@ -208,7 +212,7 @@ where
 		let mut events = client_a_clone.ibc_events().await;
 		while let Some(event) = events.next().await {
 			match event {
-				IbcEvent::ClientMisbehaviour { .. } => return,
+				Ok(IbcEvent::ClientMisbehaviour { .. }) => return,
 				_ => (),
 			}
 		}
@@ -155,6 +155,7 @@ async fn send_ordered_packet_and_assert_acknowledgement<A, B>(
 	let future = chain_b
 		.ibc_events()
 		.await
+		.filter_map(|ev| future::ready(ev.ok()))
 		.skip_while(|ev| future::ready(!matches!(ev, IbcEvent::AcknowledgePacket(_))))
 		.take(2)
 		.collect::<Vec<_>>();
@@ -196,6 +197,7 @@ async fn send_ordered_packet_and_assert_timeout<A, B>(
 	let future = chain_b
 		.subscribe_blocks()
 		.await
+		.filter_map(|ev| future::ready(ev.ok()))
 		.skip_while(|block_number| {
 			let block_number = *block_number;
 			let chain_clone = chain_b.clone();
@@ -26,6 +26,7 @@ where
 	let future = chain
 		.ibc_events()
 		.await
+		.filter_map(|ev| future::ready(ev.ok()))
 		.skip_while(|ev| {
 			future::ready(!matches!(
 				ev,
@@ -0,0 +1,192 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use core::time::Duration;
+use hyperspace_core::{
+	chain::{AnyAssetId, AnyChain, AnyConfig},
+	logging,
+};
+use hyperspace_cosmos::client::CosmosClientConfig;
+use hyperspace_primitives::{utils::create_clients, CommonClientConfig, IbcQuerier};
+use hyperspace_testsuite::{
+	ibc_messaging_packet_height_timeout_with_connection_delay,
+	ibc_messaging_packet_timestamp_timeout_with_connection_delay,
+	ibc_messaging_with_connection_delay, setup_connection_and_channel,
+};
+use ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use sp_core::hashing::sha2_256;
+
+#[derive(Debug, Clone)]
+pub struct Args {
+	pub chain_a: String,
+	pub chain_b: String,
+	pub cosmos_grpc_a: String,
+	pub cosmos_grpc_b: String,
+	pub cosmos_ws_a: String,
+	pub cosmos_ws_b: String,
+}
+
+impl Default for Args {
+	fn default() -> Self {
+		let cosmos_a = std::env::var("COSMOS_A_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+		let cosmos_b = std::env::var("COSMOS_B_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+
+		Args {
+			chain_a: format!("http://{cosmos_a}:26657"),
+			chain_b: format!("http://{cosmos_b}:36657"),
+			cosmos_grpc_a: format!("http://{cosmos_a}:9090"),
+			cosmos_grpc_b: format!("http://{cosmos_b}:19090"),
+			cosmos_ws_a: format!("ws://{cosmos_a}:26657/websocket"),
+			cosmos_ws_b: format!("ws://{cosmos_b}:36657/websocket"),
+		}
+	}
+}
+
+fn cosmos_config(
+	name: &str,
+	chain_id: &str,
+	rpc: &str,
+	grpc: &str,
+	ws: &str,
+) -> CosmosClientConfig {
+	CosmosClientConfig {
+		name: name.to_string(),
+		rpc_url: rpc.parse().unwrap(),
+		archive_rpc_url: None,
+		grpc_url: grpc.parse().unwrap(),
+		broadcast_transport: Default::default(),
+		query_transport: Default::default(),
+		websocket_url: ws.parse().unwrap(),
+		fallback_endpoints: vec![],
+		max_endpoint_latency: None,
+		chain_id: chain_id.to_string(),
+		client_id: None,
+		connection_id: None,
+		account_prefix: "cosmos".to_string(),
+		key_type: Default::default(),
+		public_key_type_url: None,
+		fee_denom: "stake".to_string(),
+		fee_amount: "4000".to_string(),
+		gas_limit: (i64::MAX - 1) as u64,
+		gas_adjustment: 1.1,
+		message_gas_floors: Default::default(),
+		fee_granter: None,
+		authz_granter: None,
+		verification_mode: Default::default(),
+		trust_threshold: None,
+		memo: "ibc".to_string(),
+		timeout_height_offset: None,
+		extension_options: vec![],
+		non_critical_extension_options: vec![],
+		store_prefix: "ibc".to_string(),
+		max_tx_size: 200000,
+		mnemonic:
+			"oxygen fall sure lava energy veteran enroll frown question detail include maximum"
+				.to_string(),
+		additional_mnemonics: vec![],
+		signer: None,
+		wasm_code_id: None,
+		channel_whitelist: vec![],
+		common: CommonClientConfig {
+			skip_optional_client_updates: true,
+			max_packets_to_process: Some(200),
+			..Default::default()
+		},
+	}
+}
+
+async fn setup_clients() -> (AnyChain, AnyChain) {
+	log::info!(target: "hyperspace", "=========================== Starting Test ===========================");
+	let args = Args::default();
+
+	let config_a =
+		cosmos_config("cosmos-a", "ibcgo-1", &args.chain_a, &args.cosmos_grpc_a, &args.cosmos_ws_a);
+	let config_b =
+		cosmos_config("cosmos-b", "ibcgo-2", &args.chain_b, &args.cosmos_grpc_b, &args.cosmos_ws_b);
+
+	let mut chain_a = AnyConfig::Cosmos(config_a).into_client().await.unwrap();
+	let mut chain_b = AnyConfig::Cosmos(config_b).into_client().await.unwrap();
+
+	let clients_on_a = chain_a.query_clients().await.unwrap();
+	let clients_on_b = chain_b.query_clients().await.unwrap();
+
+	if !clients_on_a.is_empty() && !clients_on_b.is_empty() {
+		chain_a.set_client_id(clients_on_b[0].clone());
+		chain_b.set_client_id(clients_on_a[0].clone());
+		return (chain_a, chain_b)
+	}
+
+	let (client_b, client_a) = create_clients(&mut chain_b, &mut chain_a).await.unwrap();
+	chain_a.set_client_id(client_a);
+	chain_b.set_client_id(client_b);
+	(chain_a, chain_b)
+}
+
+/// Denomination of the `stake` tokens of the counterparty received over `channel_id`.
+fn ibc_denom(channel_id: ChannelId) -> String {
+	let hash = sha2_256(format!("transfer/{channel_id}/stake").as_bytes());
+	format!("ibc/{}", hex::encode_upper(hash))
+}
+
+#[tokio::test]
+#[ignore]
+async fn cosmos_to_cosmos_ibc_messaging_full_integration_test() {
+	logging::setup_logging();
+
+	let (mut chain_a, mut chain_b) = setup_clients().await;
+	let (handle, channel_a, channel_b, connection_id_a, connection_id_b) =
+		setup_connection_and_channel(&mut chain_a, &mut chain_b, Duration::from_secs(60 * 2)).await;
+	handle.abort();
+
+	// Set connections and channel whitelist
+	chain_a.set_connection_id(connection_id_a);
+	chain_b.set_connection_id(connection_id_b);
+
+	chain_a.set_channel_whitelist(vec![(channel_a, PortId::transfer())].into_iter().collect());
+	chain_b.set_channel_whitelist(vec![(channel_b, PortId::transfer())].into_iter().collect());
+
+	let asset_id_a = AnyAssetId::Cosmos("stake".to_string());
+	let asset_id_b = AnyAssetId::Cosmos(ibc_denom(channel_b));
+
+	// Run tests sequentially
+
+	// no timeouts + connection delay
+	ibc_messaging_with_connection_delay(
+		&mut chain_a,
+		&mut chain_b,
+		asset_id_a.clone(),
+		asset_id_b.clone(),
+		channel_a,
+		channel_b,
+	)
+	.await;
+
+	// timeouts + connection delay
+	ibc_messaging_packet_height_timeout_with_connection_delay(
+		&mut chain_a,
+		&mut chain_b,
+		asset_id_a.clone(),
+		channel_a,
+		channel_b,
+	)
+	.await;
+	ibc_messaging_packet_timestamp_timeout_with_connection_delay(
+		&mut chain_a,
+		&mut chain_b,
+		asset_id_a.clone(),
+		channel_a,
+		channel_b,
+	)
+	.await;
+}
@@ -21,7 +21,7 @@ use hyperspace_core::{
 };
 use hyperspace_cosmos::client::{CosmosClient, CosmosClientConfig};
 use hyperspace_parachain::{finality_protocol::FinalityProtocol, ParachainClientConfig};
-use hyperspace_primitives::{utils::create_clients, CommonClientConfig, IbcProvider};
+use hyperspace_primitives::{utils::create_clients, CommonClientConfig, IbcQuerier, TxSubmitter};
 use hyperspace_testsuite::{
 	ibc_channel_close, ibc_messaging_packet_height_timeout_with_connection_delay,
 	ibc_messaging_packet_timeout_on_channel_close,
@@ -78,41 +78,84 @@ async fn setup_clients() -> (AnyChain, AnyChain) {
 		para_id: args.para_id,
 		parachain_rpc_url: args.chain_a,
 		relay_chain_rpc_url: args.relay_chain.clone(),
+		fallback_parachain_rpc_urls: vec![],
+		fallback_relay_chain_rpc_urls: vec![],
+		archive_parachain_rpc_url: None,
+		unsigned_client_updates: false,
+		pallet_name: "Ibc".to_string(),
+		finalized_events: false,
+		standalone: false,
 		client_id: None,
 		connection_id: None,
 		commitment_prefix: args.connection_prefix_a.as_bytes().to_vec().into(),
 		ss58_version: 42,
 		channel_whitelist: vec![],
 		finality_protocol: FinalityProtocol::Grandpa,
+		fee_asset: None,
+		assets: vec![],
 		private_key: "//Alice".to_string(),
+		additional_private_keys: vec![],
+		signer: None,
 		key_type: "sr25519".to_string(),
 		wasm_code_id: None,
+		common: Default::default(),
 	};
 
 	let mut config_b = CosmosClientConfig {
 		name: "cosmos".to_string(),
 		rpc_url: args.chain_b.clone().parse().unwrap(),
+		archive_rpc_url: None,
 		grpc_url: args.cosmos_grpc.clone().parse().unwrap(),
+		broadcast_transport: Default::default(),
+		query_transport: Default::default(),
 		websocket_url: args.cosmos_ws.clone().parse().unwrap(),
+		fallback_endpoints: vec![],
+		max_endpoint_latency: None,
 		chain_id: "ibcgo-1".to_string(),
 		client_id: None,
 		connection_id: None,
 		account_prefix: "cosmos".to_string(),
+		key_type: Default::default(),
+		public_key_type_url: None,
 		fee_denom: "stake".to_string(),
 		fee_amount: "4000".to_string(),
 		gas_limit: (i64::MAX - 1) as u64,
+		gas_adjustment: 1.1,
+		message_gas_floors: Default::default(),
+		fee_granter: None,
+		authz_granter: None,
+		verification_mode: Default::default(),
+		trust_threshold: None,
+		memo: "ibc".to_string(),
+		timeout_height_offset: None,
+		extension_options: vec![],
+		non_critical_extension_options: vec![],
 		store_prefix: args.connection_prefix_b,
 		max_tx_size: 200000,
 		mnemonic:
 			"oxygen fall sure lava energy veteran enroll frown question detail include maximum"
 				.to_string(),
+		additional_mnemonics: vec![],
+		signer: None,
 		wasm_code_id: None,
 		channel_whitelist: vec![],
 		common: CommonClientConfig {
 			skip_optional_client_updates: true,
-			max_packets_to_process: 200,
+			max_packets_to_process: Some(200),
+			skip_tokens_list: None,
+			min_balance: None,
+			relayed_sequences_retention: 50,
+			relayed_sequences_path: None,
+			pending_messages_path: None,
+			max_concurrent_submissions: 1,
+			client_update_period: None,
+			confirmation_depth: 0,
+			optional_update_window: None,
+			adaptive_client_updates: None,
+			security: Default::default(),
+			counterparty_payee: None,
+			event_filter: Default::default(),
 		},
-		skip_tokens_list: None,
 	};
 
 	let chain_b = CosmosClient::<DefaultConfig>::new(config_b.clone()).await.unwrap();
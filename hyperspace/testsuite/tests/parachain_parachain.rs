@@ -17,13 +17,15 @@ use hyperspace_core::{logging, substrate::DefaultConfig};
 use hyperspace_parachain::{
 	finality_protocol::FinalityProtocol, ParachainClient, ParachainClientConfig,
 };
-use hyperspace_primitives::{utils::create_clients, IbcProvider, TestProvider};
+use hyperspace_primitives::{utils::create_clients, IbcQuerier, TestProvider};
 use hyperspace_testsuite::{
+	chaos::{ibc_messaging_with_faults, ChaosConfig},
 	client_synchronization_test, ibc_channel_close,
 	ibc_messaging_packet_height_timeout_with_connection_delay,
 	ibc_messaging_packet_timeout_on_channel_close,
 	ibc_messaging_packet_timestamp_timeout_with_connection_delay,
-	ibc_messaging_with_connection_delay, misbehaviour::ibc_messaging_submit_misbehaviour,
+	ibc_messaging_with_connection_delay,
+	misbehaviour::ibc_messaging_submit_misbehaviour,
 };
 use std::time::Duration;
 
@@ -65,30 +67,54 @@ async fn setup_clients() -> (ParachainClient<DefaultConfig>, ParachainClient<Def
 		para_id: args.para_id_a,
 		parachain_rpc_url: args.chain_a,
 		relay_chain_rpc_url: args.relay_chain.clone(),
+		fallback_parachain_rpc_urls: vec![],
+		fallback_relay_chain_rpc_urls: vec![],
+		archive_parachain_rpc_url: None,
+		unsigned_client_updates: false,
+		pallet_name: "Ibc".to_string(),
+		finalized_events: false,
+		standalone: false,
 		client_id: None,
 		connection_id: None,
 		commitment_prefix: args.connection_prefix_b.as_bytes().to_vec().into(),
 		ss58_version: 42,
 		channel_whitelist: vec![],
 		finality_protocol: FinalityProtocol::Grandpa,
+		fee_asset: None,
+		assets: vec![],
 		private_key: "//Alice".to_string(),
+		additional_private_keys: vec![],
+		signer: None,
 		key_type: "sr25519".to_string(),
 		wasm_code_id: None,
+		common: Default::default(),
 	};
 	let config_b = ParachainClientConfig {
 		name: "9188".to_string(),
 		para_id: args.para_id_b,
 		parachain_rpc_url: args.chain_b,
 		relay_chain_rpc_url: args.relay_chain,
+		fallback_parachain_rpc_urls: vec![],
+		fallback_relay_chain_rpc_urls: vec![],
+		archive_parachain_rpc_url: None,
+		unsigned_client_updates: false,
+		pallet_name: "Ibc".to_string(),
+		finalized_events: false,
+		standalone: false,
 		client_id: None,
 		connection_id: None,
 		commitment_prefix: args.connection_prefix_b.as_bytes().to_vec().into(),
 		private_key: "//Alice".to_string(),
+		additional_private_keys: vec![],
+		signer: None,
 		ss58_version: 42,
 		channel_whitelist: vec![],
 		finality_protocol: FinalityProtocol::Grandpa,
+		fee_asset: None,
+		assets: vec![],
 		key_type: "sr25519".to_string(),
 		wasm_code_id: None,
+		common: Default::default(),
 	};
 
 	let mut chain_a = ParachainClient::<DefaultConfig>::new(config_a).await.unwrap();
@@ -186,6 +212,18 @@ async fn parachain_to_parachain_ibc_messaging_full_integration_test() {
 		res.unwrap();
 	}
 
+	// packets are relayed exactly once despite faults, with no other relayer running
+	ibc_messaging_with_faults(
+		&mut chain_a,
+		&mut chain_b,
+		asset_id,
+		channel_a,
+		ChaosConfig::frequent(),
+		5,
+	)
+	.await;
+	log::info!(target: "hyperspace", "🚀🚀 finished relaying with faults");
+
 	// channel closing semantics
 	let mut join_set = tokio::task::JoinSet::new();
 	let mut c1 = chain_a.clone();
@@ -20,6 +20,7 @@ use ibc_proto::{
 	ibc::applications::transfer::v1::DenomTrace as RawDenomTrace,
 };
 use serde::{Deserialize, Serialize};
+use subtle_encoding::hex;
 
 use super::error::Error;
 use crate::{
@@ -188,6 +189,17 @@ impl PrefixedDenom {
 	pub fn base_denom(&self) -> &BaseDenom {
 		&self.base_denom
 	}
+
+	/// Returns the `ibc/{hash}` form of this denomination used by chains that store hashed
+	/// denominations (e.g. cosmos-sdk chains), where `{hash}` is the upper case hex encoding of
+	/// `sha256` applied to the full denomination. Native denominations are returned unchanged.
+	pub fn hashed_denom(&self, sha256: impl FnOnce(Vec<u8>) -> Vec<u8>) -> String {
+		if self.trace_path.is_empty() {
+			return self.base_denom.to_string()
+		}
+		let hash = hex::encode_upper(sha256(self.to_string().into_bytes()));
+		format!("ibc/{}", String::from_utf8(hash).expect("hex encoded bytes are valid UTF8"))
+	}
 }
 
 /// Returns true if the denomination originally came from the sender chain and
@@ -239,6 +251,28 @@ pub fn is_receiver_chain_source(
 	denom.trace_path.starts_with(&prefix)
 }
 
+/// Returns the denomination that the receiving chain credits when `denom` is sent over the
+/// channel end `source_port/source_channel`, whose counterparty is `dest_port/dest_channel`.
+///
+/// Mirrors what the receiving chain does on `OnRecvPacket`: if it is the source of the token, the
+/// sender's prefix is removed (and the token may be native on the receiver again, i.e. have an
+/// empty trace path), otherwise the receiver's prefix is added.
+pub fn receiver_chain_denom(
+	source_port: PortId,
+	source_channel: ChannelId,
+	dest_port: PortId,
+	dest_channel: ChannelId,
+	denom: &PrefixedDenom,
+) -> PrefixedDenom {
+	let mut denom = denom.clone();
+	if is_receiver_chain_source(source_port.clone(), source_channel, &denom) {
+		denom.remove_trace_prefix(&TracePrefix::new(source_port, source_channel));
+	} else {
+		denom.add_trace_prefix(TracePrefix::new(dest_port, dest_channel));
+	}
+	denom
+}
+
 impl FromStr for PrefixedDenom {
 	type Err = Error;
 
@@ -493,6 +527,50 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_receiver_chain_denom() -> Result<(), Error> {
+		use sha2::Digest;
+		let sha256 = |data: Vec<u8>| sha2::Sha256::digest(data).to_vec();
+		let transfer = PortId::transfer();
+
+		// a native token of the sender gets prefixed with the receiver's channel end
+		let denom = receiver_chain_denom(
+			transfer.clone(),
+			ChannelId::new(1),
+			transfer.clone(),
+			ChannelId::new(0),
+			&PrefixedDenom::from_str("uatom")?,
+		);
+		assert_eq!(denom, PrefixedDenom::from_str("transfer/channel-0/uatom")?);
+		assert_eq!(
+			denom.hashed_denom(sha256),
+			"ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+		);
+
+		// a voucher sent back over the channel it came from is native on the receiver again
+		let denom = receiver_chain_denom(
+			transfer.clone(),
+			ChannelId::new(0),
+			transfer.clone(),
+			ChannelId::new(1),
+			&PrefixedDenom::from_str("transfer/channel-0/uatom")?,
+		);
+		assert!(denom.trace_path().is_empty());
+		assert_eq!(denom.hashed_denom(sha256), "uatom");
+
+		// a voucher sent over another channel gets prefixed again
+		let denom = receiver_chain_denom(
+			transfer.clone(),
+			ChannelId::new(2),
+			transfer,
+			ChannelId::new(5),
+			&PrefixedDenom::from_str("transfer/channel-0/uatom")?,
+		);
+		assert_eq!(denom, PrefixedDenom::from_str("transfer/channel-5/transfer/channel-0/uatom")?);
+
+		Ok(())
+	}
+
 	#[test]
 	fn packet_data_serde() {
 		let packet_data = PacketData {
@@ -34,7 +34,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub const TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransfer";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_TRANSFER;
 
 /// Message used to build an ICS20 token transfer packet.
 ///
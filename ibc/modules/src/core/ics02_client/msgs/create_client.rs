@@ -28,7 +28,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.client.v1.MsgCreateClient";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_CREATE_CLIENT;
 
 /// A type of message that triggers the creation of a new on-chain (IBC) client.
 #[derive(Clone, Debug, PartialEq, Eq)]
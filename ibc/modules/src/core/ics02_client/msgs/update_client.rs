@@ -31,7 +31,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.client.v1.MsgUpdateClient";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_UPDATE_CLIENT;
 
 /// A type of message that triggers the update of an on-chain (IBC) client with new headers.
 #[derive(Clone, Debug, PartialEq)] // TODO: Add Eq bound when possible
@@ -32,7 +32,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub(crate) const TYPE_URL: &str = "/ibc.core.client.v1.MsgUpgradeClient";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_UPGRADE_CLIENT;
 
 /// A type of message that triggers the upgrade of an on-chain (IBC) client.
 #[derive(Clone, Debug, PartialEq)]
@@ -35,7 +35,7 @@ use crate::{
 	Height,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.connection.v1.MsgConnectionOpenAck";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_CONNECTION_OPEN_ACK;
 
 /// Message definition `MsgConnectionOpenAck`  (i.e., `ConnOpenAck` datagram).
 #[derive(Clone, Debug, PartialEq, Eq)]
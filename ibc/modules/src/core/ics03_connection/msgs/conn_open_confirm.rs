@@ -25,7 +25,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.connection.v1.MsgConnectionOpenConfirm";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_CONNECTION_OPEN_CONFIRM;
 
 ///
 /// Message definition for `MsgConnectionOpenConfirm` (i.e., `ConnOpenConfirm` datagram).
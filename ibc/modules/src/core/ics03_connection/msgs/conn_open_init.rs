@@ -28,7 +28,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.connection.v1.MsgConnectionOpenInit";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_CONNECTION_OPEN_INIT;
 
 ///
 /// Message definition `MsgConnectionOpenInit`  (i.e., the `ConnOpenInit` datagram).
@@ -38,7 +38,7 @@ use crate::{
 	Height,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.connection.v1.MsgConnectionOpenTry";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_CONNECTION_OPEN_TRY;
 
 ///
 /// Message definition `MsgConnectionOpenTry`  (i.e., `ConnOpenTry` datagram).
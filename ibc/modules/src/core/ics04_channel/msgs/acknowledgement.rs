@@ -25,7 +25,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgAcknowledgement";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_ACKNOWLEDGEMENT;
 
 /// A generic Acknowledgement type that modules may interpret as they like.
 #[derive(Clone, Debug, PartialEq)]
@@ -28,7 +28,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelCloseConfirm";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_CHANNEL_CLOSE_CONFIRM;
 
 ///
 /// Message definition for the second step in the channel close handshake (the `ChanCloseConfirm`
@@ -27,7 +27,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelCloseInit";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_CHANNEL_CLOSE_INIT;
 
 ///
 /// Message definition for the first step in the channel close handshake (`ChanCloseInit` datagram).
@@ -26,7 +26,7 @@ use crate::{
 use ibc_proto::ibc::core::channel::v1::MsgChannelOpenAck as RawMsgChannelOpenAck;
 use tendermint_proto::Protobuf;
 
-pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelOpenAck";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_CHANNEL_OPEN_ACK;
 
 ///
 /// Message definition for the third step in the channel open handshake (`ChanOpenAck` datagram).
@@ -26,7 +26,7 @@ use crate::{
 use ibc_proto::ibc::core::channel::v1::MsgChannelOpenConfirm as RawMsgChannelOpenConfirm;
 use tendermint_proto::Protobuf;
 
-pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelOpenConfirm";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_CHANNEL_OPEN_CONFIRM;
 
 ///
 /// Message definition for the fourth step in the channel open handshake (`ChanOpenConfirm`
@@ -25,7 +25,7 @@ use crate::{
 use ibc_proto::ibc::core::channel::v1::MsgChannelOpenInit as RawMsgChannelOpenInit;
 use tendermint_proto::Protobuf;
 
-pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelOpenInit";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_CHANNEL_OPEN_INIT;
 
 ///
 /// Message definition for the first step in the channel open handshake (`ChanOpenInit` datagram).
@@ -26,7 +26,7 @@ use crate::{
 use ibc_proto::ibc::core::channel::v1::MsgChannelOpenTry as RawMsgChannelOpenTry;
 use tendermint_proto::Protobuf;
 
-pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelOpenTry";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_CHANNEL_OPEN_TRY;
 
 ///
 /// Message definition for the second step in the channel open handshake (`ChanOpenTry` datagram).
@@ -25,7 +25,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgRecvPacket";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_RECV_PACKET;
 
 ///
 /// Message definition for the "packet receiving" datagram.
@@ -28,7 +28,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgTimeout";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_TIMEOUT;
 
 ///
 /// Message definition for packet timeout domain type.
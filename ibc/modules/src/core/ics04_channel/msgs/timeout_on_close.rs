@@ -27,7 +27,7 @@ use crate::{
 	tx_msg::Msg,
 };
 
-pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgTimeoutOnClose";
+pub const TYPE_URL: &str = ibc_proto_registry::type_url::MSG_TIMEOUT_ON_CLOSE;
 
 ///
 /// Message definition for packet timeout domain type.
@@ -0,0 +1,335 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Registry of the protobuf `Any` type URLs of the IBC messages and light client types.
+//!
+//! Every type is registered once, in the `registry!` invocation below, which generates its
+//! constant in [`type_url`] and its [`ProtoType`] variant. Types whose raw definition lives in
+//! `ibc-proto` also get a [`RawAny`] variant, so that an `Any` can be decoded by its type URL.
+//! The raw GRANDPA and BEEFY types are generated by their light client crates, which decode them
+//! with [`decode`]: a mismatched type URL is then reported instead of the bytes being silently
+//! decoded as another type.
+//!
+//! Registering a type URL twice is a compile error, as it makes a pattern of
+//! [`ProtoType::from_type_url`] unreachable.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(unreachable_patterns)]
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use core::fmt;
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::{
+		applications::{fee, transfer},
+		core::{channel, client, connection},
+		lightclients::{tendermint, wasm},
+	},
+};
+use prost::Message;
+
+macro_rules! registry {
+	(
+		raw {
+			$($raw_name:ident, $raw_url_const:ident => $raw_url:literal: $raw_ty:ty,)*
+		}
+		external {
+			$($name:ident, $url_const:ident => $url:literal,)*
+		}
+	) => {
+		/// Type URLs of the registered types.
+		pub mod type_url {
+			$(pub const $raw_url_const: &str = $raw_url;)*
+			$(pub const $url_const: &str = $url;)*
+		}
+
+		/// A registered protobuf type.
+		#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+		pub enum ProtoType {
+			$($raw_name,)*
+			$($name,)*
+		}
+
+		impl ProtoType {
+			/// All the registered types.
+			pub const ALL: &'static [ProtoType] =
+				&[$(ProtoType::$raw_name,)* $(ProtoType::$name,)*];
+
+			pub fn type_url(&self) -> &'static str {
+				match self {
+					$(ProtoType::$raw_name => type_url::$raw_url_const,)*
+					$(ProtoType::$name => type_url::$url_const,)*
+				}
+			}
+
+			/// Returns the registered type with the given type URL.
+			pub fn from_type_url(url: &str) -> Option<Self> {
+				match url {
+					$(type_url::$raw_url_const => Some(ProtoType::$raw_name),)*
+					$(type_url::$url_const => Some(ProtoType::$name),)*
+					_ => None,
+				}
+			}
+
+			/// Whether the raw type is defined in `ibc-proto`, i.e. can be decoded into a
+			/// [`RawAny`].
+			pub fn is_raw(&self) -> bool {
+				match self {
+					$(ProtoType::$raw_name => true,)*
+					$(ProtoType::$name => false,)*
+				}
+			}
+		}
+
+		/// An `Any` decoded into the raw type registered for its type URL.
+		#[derive(Clone, Debug, PartialEq)]
+		pub enum RawAny {
+			$($raw_name($raw_ty),)*
+		}
+
+		impl RawAny {
+			/// Decodes `any` into the raw type registered for its type URL.
+			pub fn decode(any: &Any) -> Result<Self, Error> {
+				let proto_type = ProtoType::from_type_url(&any.type_url)
+					.ok_or_else(|| Error::UnknownTypeUrl(any.type_url.clone()))?;
+				match proto_type {
+					$(ProtoType::$raw_name => decode(proto_type, any).map(RawAny::$raw_name),)*
+					_ => Err(Error::External(proto_type)),
+				}
+			}
+
+			pub fn proto_type(&self) -> ProtoType {
+				match self {
+					$(RawAny::$raw_name(_) => ProtoType::$raw_name,)*
+				}
+			}
+
+			pub fn encode(&self) -> Any {
+				match self {
+					$(RawAny::$raw_name(message) => encode(ProtoType::$raw_name, message),)*
+				}
+			}
+		}
+
+		#[cfg(test)]
+		fn default_raw_messages() -> alloc::vec::Vec<RawAny> {
+			alloc::vec![$(RawAny::$raw_name(Default::default()),)*]
+		}
+	};
+}
+
+registry! {
+	raw {
+		MsgCreateClient, MSG_CREATE_CLIENT =>
+			"/ibc.core.client.v1.MsgCreateClient": client::v1::MsgCreateClient,
+		MsgUpdateClient, MSG_UPDATE_CLIENT =>
+			"/ibc.core.client.v1.MsgUpdateClient": client::v1::MsgUpdateClient,
+		MsgUpgradeClient, MSG_UPGRADE_CLIENT =>
+			"/ibc.core.client.v1.MsgUpgradeClient": client::v1::MsgUpgradeClient,
+		MsgSubmitMisbehaviour, MSG_SUBMIT_MISBEHAVIOUR =>
+			"/ibc.core.client.v1.MsgSubmitMisbehaviour": client::v1::MsgSubmitMisbehaviour,
+		MsgConnectionOpenInit, MSG_CONNECTION_OPEN_INIT =>
+			"/ibc.core.connection.v1.MsgConnectionOpenInit": connection::v1::MsgConnectionOpenInit,
+		MsgConnectionOpenTry, MSG_CONNECTION_OPEN_TRY =>
+			"/ibc.core.connection.v1.MsgConnectionOpenTry": connection::v1::MsgConnectionOpenTry,
+		MsgConnectionOpenAck, MSG_CONNECTION_OPEN_ACK =>
+			"/ibc.core.connection.v1.MsgConnectionOpenAck": connection::v1::MsgConnectionOpenAck,
+		MsgConnectionOpenConfirm, MSG_CONNECTION_OPEN_CONFIRM =>
+			"/ibc.core.connection.v1.MsgConnectionOpenConfirm":
+				connection::v1::MsgConnectionOpenConfirm,
+		MsgChannelOpenInit, MSG_CHANNEL_OPEN_INIT =>
+			"/ibc.core.channel.v1.MsgChannelOpenInit": channel::v1::MsgChannelOpenInit,
+		MsgChannelOpenTry, MSG_CHANNEL_OPEN_TRY =>
+			"/ibc.core.channel.v1.MsgChannelOpenTry": channel::v1::MsgChannelOpenTry,
+		MsgChannelOpenAck, MSG_CHANNEL_OPEN_ACK =>
+			"/ibc.core.channel.v1.MsgChannelOpenAck": channel::v1::MsgChannelOpenAck,
+		MsgChannelOpenConfirm, MSG_CHANNEL_OPEN_CONFIRM =>
+			"/ibc.core.channel.v1.MsgChannelOpenConfirm": channel::v1::MsgChannelOpenConfirm,
+		MsgChannelCloseInit, MSG_CHANNEL_CLOSE_INIT =>
+			"/ibc.core.channel.v1.MsgChannelCloseInit": channel::v1::MsgChannelCloseInit,
+		MsgChannelCloseConfirm, MSG_CHANNEL_CLOSE_CONFIRM =>
+			"/ibc.core.channel.v1.MsgChannelCloseConfirm": channel::v1::MsgChannelCloseConfirm,
+		MsgRecvPacket, MSG_RECV_PACKET =>
+			"/ibc.core.channel.v1.MsgRecvPacket": channel::v1::MsgRecvPacket,
+		MsgAcknowledgement, MSG_ACKNOWLEDGEMENT =>
+			"/ibc.core.channel.v1.MsgAcknowledgement": channel::v1::MsgAcknowledgement,
+		MsgTimeout, MSG_TIMEOUT =>
+			"/ibc.core.channel.v1.MsgTimeout": channel::v1::MsgTimeout,
+		MsgTimeoutOnClose, MSG_TIMEOUT_ON_CLOSE =>
+			"/ibc.core.channel.v1.MsgTimeoutOnClose": channel::v1::MsgTimeoutOnClose,
+		MsgTransfer, MSG_TRANSFER =>
+			"/ibc.applications.transfer.v1.MsgTransfer": transfer::v1::MsgTransfer,
+		MsgRegisterCounterpartyPayee, MSG_REGISTER_COUNTERPARTY_PAYEE =>
+			"/ibc.applications.fee.v1.MsgRegisterCounterpartyPayee":
+				fee::v1::MsgRegisterCounterpartyPayee,
+		TendermintClientState, TENDERMINT_CLIENT_STATE =>
+			"/ibc.lightclients.tendermint.v1.ClientState": tendermint::v1::ClientState,
+		TendermintConsensusState, TENDERMINT_CONSENSUS_STATE =>
+			"/ibc.lightclients.tendermint.v1.ConsensusState": tendermint::v1::ConsensusState,
+		TendermintHeader, TENDERMINT_HEADER =>
+			"/ibc.lightclients.tendermint.v1.Header": tendermint::v1::Header,
+		TendermintMisbehaviour, TENDERMINT_MISBEHAVIOUR =>
+			"/ibc.lightclients.tendermint.v1.Misbehaviour": tendermint::v1::Misbehaviour,
+		WasmClientState, WASM_CLIENT_STATE =>
+			"/ibc.lightclients.wasm.v1.ClientState": wasm::v1::ClientState,
+		WasmConsensusState, WASM_CONSENSUS_STATE =>
+			"/ibc.lightclients.wasm.v1.ConsensusState": wasm::v1::ConsensusState,
+		WasmHeader, WASM_HEADER =>
+			"/ibc.lightclients.wasm.v1.Header": wasm::v1::Header,
+		WasmMisbehaviour, WASM_MISBEHAVIOUR =>
+			"/ibc.lightclients.wasm.v1.Misbehaviour": wasm::v1::Misbehaviour,
+		MsgPushNewWasmCode, MSG_PUSH_NEW_WASM_CODE =>
+			"/ibc.lightclients.wasm.v1.MsgPushNewWasmCode": wasm::v1::MsgPushNewWasmCode,
+	}
+	external {
+		TendermintClientMessage, TENDERMINT_CLIENT_MESSAGE =>
+			"/ibc.lightclients.tendermint.v1.ClientMessage",
+		WasmClientMessage, WASM_CLIENT_MESSAGE => "/ibc.lightclients.wasm.v1.ClientMessage",
+		GrandpaClientState, GRANDPA_CLIENT_STATE => "/ibc.lightclients.grandpa.v1.ClientState",
+		GrandpaConsensusState, GRANDPA_CONSENSUS_STATE =>
+			"/ibc.lightclients.grandpa.v1.ConsensusState",
+		GrandpaClientMessage, GRANDPA_CLIENT_MESSAGE =>
+			"/ibc.lightclients.grandpa.v1.ClientMessage",
+		GrandpaHeader, GRANDPA_HEADER => "/ibc.lightclients.grandpa.v1.Header",
+		GrandpaMisbehaviour, GRANDPA_MISBEHAVIOUR => "/ibc.lightclients.grandpa.v1.Misbehaviour",
+		BeefyClientState, BEEFY_CLIENT_STATE => "/ibc.lightclients.beefy.v1.ClientState",
+		BeefyConsensusState, BEEFY_CONSENSUS_STATE => "/ibc.lightclients.beefy.v1.ConsensusState",
+		BeefyClientMessage, BEEFY_CLIENT_MESSAGE => "/ibc.lightclients.beefy.v1.ClientMessage",
+	}
+}
+
+/// Errors of the decoding of an `Any` through the registry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+	/// No type is registered with this type URL.
+	UnknownTypeUrl(String),
+	/// The `Any` doesn't have the type URL of the type it's decoded as.
+	TypeUrlMismatch { expected: ProtoType, found: String },
+	/// The raw type isn't defined in `ibc-proto` but in the crate of its light client.
+	External(ProtoType),
+	/// The value isn't a valid encoding of the type.
+	Decode { proto_type: ProtoType, error: prost::DecodeError },
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Error::UnknownTypeUrl(type_url) => write!(f, "unknown type url {type_url}"),
+			Error::TypeUrlMismatch { expected, found } =>
+				write!(f, "expected type url {}, found {found}", expected.type_url()),
+			Error::External(proto_type) => write!(
+				f,
+				"{} must be decoded by the crate of its light client",
+				proto_type.type_url()
+			),
+			Error::Decode { proto_type, error } =>
+				write!(f, "failed to decode {}: {error}", proto_type.type_url()),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Decodes `any` as the registered type `proto_type`, checking its type URL.
+pub fn decode<T: Message + Default>(proto_type: ProtoType, any: &Any) -> Result<T, Error> {
+	if any.type_url != proto_type.type_url() {
+		return Err(Error::TypeUrlMismatch { expected: proto_type, found: any.type_url.clone() })
+	}
+	T::decode(any.value.as_slice()).map_err(|error| Error::Decode { proto_type, error })
+}
+
+/// Encodes `message` as an `Any` of the registered type `proto_type`.
+pub fn encode<T: Message>(proto_type: ProtoType, message: &T) -> Any {
+	Any { type_url: proto_type.type_url().to_string(), value: message.encode_to_vec() }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use alloc::collections::BTreeSet;
+
+	#[test]
+	fn type_urls_round_trip() {
+		let mut type_urls = BTreeSet::new();
+		for proto_type in ProtoType::ALL {
+			let type_url = proto_type.type_url();
+			assert!(type_url.starts_with('/'), "{type_url} must start with '/'");
+			assert!(type_urls.insert(type_url), "{type_url} is registered twice");
+			assert_eq!(ProtoType::from_type_url(type_url), Some(*proto_type));
+		}
+		assert_eq!(ProtoType::from_type_url("/ibc.unknown.v1.Type"), None);
+	}
+
+	#[test]
+	fn raw_messages_round_trip() {
+		let messages = default_raw_messages();
+		let raw_types = ProtoType::ALL.iter().filter(|proto_type| proto_type.is_raw()).count();
+		assert_eq!(messages.len(), raw_types);
+		for message in messages {
+			let any = message.encode();
+			assert_eq!(any.type_url, message.proto_type().type_url());
+			assert_eq!(RawAny::decode(&any), Ok(message));
+		}
+	}
+
+	#[test]
+	fn populated_message_round_trips() {
+		let message = client::v1::MsgUpdateClient {
+			client_id: "10-grandpa-0".to_string(),
+			client_message: Some(Any {
+				type_url: type_url::GRANDPA_CLIENT_MESSAGE.to_string(),
+				value: alloc::vec![1, 2, 3],
+			}),
+			signer: "relayer".to_string(),
+		};
+		let any = encode(ProtoType::MsgUpdateClient, &message);
+		assert_eq!(any.type_url, type_url::MSG_UPDATE_CLIENT);
+		assert_eq!(RawAny::decode(&any), Ok(RawAny::MsgUpdateClient(message.clone())));
+		assert_eq!(decode(ProtoType::MsgUpdateClient, &any), Ok(message));
+	}
+
+	#[test]
+	fn mismatched_type_urls_are_rejected() {
+		let any = encode(ProtoType::MsgRecvPacket, &channel::v1::MsgRecvPacket::default());
+		assert_eq!(
+			decode::<channel::v1::MsgTimeout>(ProtoType::MsgTimeout, &any),
+			Err(Error::TypeUrlMismatch {
+				expected: ProtoType::MsgTimeout,
+				found: type_url::MSG_RECV_PACKET.to_string(),
+			})
+		);
+
+		let unknown = Any { type_url: "/ibc.unknown.v1.Type".to_string(), value: any.value };
+		assert_eq!(
+			RawAny::decode(&unknown),
+			Err(Error::UnknownTypeUrl("/ibc.unknown.v1.Type".to_string()))
+		);
+
+		let grandpa =
+			Any { type_url: type_url::GRANDPA_CLIENT_STATE.to_string(), value: alloc::vec![] };
+		assert_eq!(RawAny::decode(&grandpa), Err(Error::External(ProtoType::GrandpaClientState)));
+	}
+
+	#[test]
+	fn invalid_values_are_rejected() {
+		let any = Any { type_url: type_url::MSG_TIMEOUT.to_string(), value: alloc::vec![0xff] };
+		assert!(matches!(
+			RawAny::decode(&any),
+			Err(Error::Decode { proto_type: ProtoType::MsgTimeout, .. })
+		));
+	}
+}
@@ -292,7 +292,14 @@ pub trait IbcEventsT {
 #[async_trait]
 pub trait Config: subxt::Config + Sized {
 	/// Asset Id type used by the parachain runtime
-	type AssetId: codec::Codec + serde::Serialize + Send + Sync + 'static;
+	type AssetId: codec::Codec
+		+ serde::Serialize
+		+ serde::de::DeserializeOwned
+		+ From<u128>
+		+ Into<u128>
+		+ Send
+		+ Sync
+		+ 'static;
 	/// the signature type of the runtime
 	type Signature: sp_runtime::traits::Verify + From<<Self as subxt::Config>::Signature> + Decode;
 	/// Address type used by the runtime;
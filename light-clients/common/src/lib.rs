@@ -121,6 +121,9 @@ pub enum RelayChain {
 	Polkadot = 0,
 	Kusama = 1,
 	Rococo = 2,
+	/// Not a relay chain: the chain followed by the client is a standalone chain, whose own
+	/// GRANDPA finality is verified.
+	Standalone = 3,
 }
 
 impl Default for RelayChain {
@@ -148,6 +151,7 @@ impl RelayChain {
 			Self::Polkadot => "Polkadot",
 			Self::Kusama => "Kusama",
 			Self::Rococo => "Rococo",
+			Self::Standalone => "Standalone",
 		}
 	}
 
@@ -157,24 +161,32 @@ impl RelayChain {
 			0 => Ok(Self::Polkadot),
 			1 => Ok(Self::Kusama),
 			2 => Ok(Self::Rococo),
+			3 => Ok(Self::Standalone),
 			id => Err(anyhow!("Unknown relay chain {id}")),
 		}
 	}
 
-	pub fn unbonding_period(&self) -> Duration {
+	/// Unbonding period of the validators of the relay chain. Standalone chains have no relay
+	/// chain, so their unbonding period is configured by their clients instead.
+	pub fn unbonding_period(&self) -> Option<Duration> {
 		match self {
-			Self::Polkadot => Duration::from_secs(POLKADOT_UNBONDING_PERIOD * DAY),
-			Self::Kusama | Self::Rococo => Duration::from_secs(KUSAMA_UNBONDING_PERIOD * DAY),
+			Self::Polkadot => Some(Duration::from_secs(POLKADOT_UNBONDING_PERIOD * DAY)),
+			Self::Kusama | Self::Rococo => Some(Duration::from_secs(KUSAMA_UNBONDING_PERIOD * DAY)),
+			Self::Standalone => None,
 		}
 	}
 
-	pub fn trusting_period(&self) -> Duration {
-		let unbonding_period = self.unbonding_period();
-		// Trusting period is 1/3 of unbonding period
-		unbonding_period.checked_div(3).unwrap()
+	pub fn trusting_period(&self) -> Option<Duration> {
+		self.unbonding_period().map(trusting_period)
 	}
 }
 
+/// Trusting period of a chain whose validators unbond in `unbonding_period`.
+pub fn trusting_period(unbonding_period: Duration) -> Duration {
+	// Trusting period is 1/3 of unbonding period
+	unbonding_period.checked_div(3).unwrap()
+}
+
 impl FromStr for RelayChain {
 	type Err = anyhow::Error;
 
@@ -183,6 +195,7 @@ impl FromStr for RelayChain {
 			"polkadot" => Ok(Self::Polkadot),
 			"kusama" => Ok(Self::Kusama),
 			"rococo" => Ok(Self::Rococo),
+			"standalone" => Ok(Self::Standalone),
 			_ => Err(anyhow!("Unknown relay chain {s}")),
 		}
 	}
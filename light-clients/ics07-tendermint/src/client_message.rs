@@ -36,10 +36,11 @@ use serde::{Deserialize, Serialize};
 use tendermint::{block::signed_header::SignedHeader, validator::Set as ValidatorSet};
 use tendermint_proto::Protobuf;
 
-pub const TENDERMINT_HEADER_TYPE_URL: &str = "/ibc.lightclients.tendermint.v1.Header";
-pub const TENDERMINT_MISBEHAVIOUR_TYPE_URL: &str = "/ibc.lightclients.tendermint.v1.Misbehaviour";
+pub const TENDERMINT_HEADER_TYPE_URL: &str = ibc_proto_registry::type_url::TENDERMINT_HEADER;
+pub const TENDERMINT_MISBEHAVIOUR_TYPE_URL: &str =
+	ibc_proto_registry::type_url::TENDERMINT_MISBEHAVIOUR;
 pub const TENDERMINT_CLIENT_MESSAGE_TYPE_URL: &str =
-	"/ibc.lightclients.tendermint.v1.ClientMessage";
+	ibc_proto_registry::type_url::TENDERMINT_CLIENT_MESSAGE;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Misbehaviour {
@@ -95,16 +95,10 @@ where
 		let (inner_client_state, inner_consensus_update_result) = self.inner.update_state(
 			ctx,
 			client_id,
-			*client_state.inner,
+			*client_state.inner.clone(),
 			client_msg.into_inner(),
 		)?;
-		let client_state = ClientState {
-			data: client_state.data.clone(),
-			code_id: client_state.code_id.clone(),
-			inner: Box::new(inner_client_state),
-			latest_height: client_state.latest_height,
-			_phantom: PhantomData,
-		};
+		let client_state = client_state.with_inner(inner_client_state).map_err(Error::encode)?;
 		Ok((client_state, inner_consensus_update_result))
 	}
 
@@ -115,14 +109,8 @@ where
 	) -> Result<Self::ClientState, Error> {
 		let inner_client_state = self
 			.inner
-			.update_state_on_misbehaviour(*client_state.inner, client_msg.into_inner())?;
-		Ok(ClientState {
-			data: client_state.data.clone(),
-			code_id: client_state.code_id.clone(),
-			inner: Box::new(inner_client_state),
-			latest_height: client_state.latest_height,
-			_phantom: PhantomData,
-		})
+			.update_state_on_misbehaviour(*client_state.inner.clone(), client_msg.into_inner())?;
+		client_state.with_inner(inner_client_state).map_err(Error::encode)
 	}
 
 	fn check_for_misbehaviour<Ctx: ReaderContext>(
@@ -150,8 +138,8 @@ where
 		proof_upgrade_client: Vec<u8>,
 		proof_upgrade_consensus_state: Vec<u8>,
 	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Error> {
-		self.inner
-			.verify_upgrade_and_update_state(
+		let (inner_client_state, inner_consensus_update_result) =
+			self.inner.verify_upgrade_and_update_state(
 				ctx,
 				client_id,
 				&old_client_state.inner,
@@ -159,19 +147,10 @@ where
 				&upgrade_consensus_state.inner,
 				proof_upgrade_client,
 				proof_upgrade_consensus_state,
-			)
-			.map(|(client_state, result)| {
-				(
-					ClientState {
-						inner: Box::new(client_state),
-						data: old_client_state.data.clone(),
-						code_id: old_client_state.code_id.clone(),
-						latest_height: old_client_state.latest_height,
-						_phantom: Default::default(),
-					},
-					result,
-				)
-			})
+			)?;
+		let client_state =
+			old_client_state.with_inner(inner_client_state).map_err(Error::encode)?;
+		Ok((client_state, inner_consensus_update_result))
 	}
 
 	fn check_substitute_and_update_state<Ctx: ReaderContext>(
@@ -187,16 +166,11 @@ where
 				ctx,
 				subject_client_id,
 				substitute_client_id,
-				*old_client_state.inner,
+				*old_client_state.inner.clone(),
 				*substitute_client_state.inner,
 			)?;
-		let client_state = ClientState {
-			data: old_client_state.data.clone(),
-			code_id: old_client_state.code_id.clone(),
-			inner: Box::new(inner_client_state),
-			latest_height: old_client_state.latest_height,
-			_phantom: PhantomData,
-		};
+		let client_state =
+			old_client_state.with_inner(inner_client_state).map_err(Error::encode)?;
 		Ok((client_state, inner_consensus_update_result))
 	}
 
@@ -35,9 +35,9 @@ use ibc_proto::{
 };
 use prost::Message;
 
-pub const WASM_HEADER_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.Header";
-pub const WASM_MISBEHAVIOUR_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.Misbehaviour";
-pub const WASM_CLIENT_MESSAGE_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.ClientMessage";
+pub const WASM_HEADER_TYPE_URL: &str = ibc_proto_registry::type_url::WASM_HEADER;
+pub const WASM_MISBEHAVIOUR_TYPE_URL: &str = ibc_proto_registry::type_url::WASM_MISBEHAVIOUR;
+pub const WASM_CLIENT_MESSAGE_TYPE_URL: &str = ibc_proto_registry::type_url::WASM_CLIENT_MESSAGE;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ClientMessage<AnyClientMessage> {
@@ -44,7 +44,7 @@ use ibc_proto::{
 };
 use prost::Message;
 
-pub const WASM_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.ClientState";
+pub const WASM_CLIENT_STATE_TYPE_URL: &str = ibc_proto_registry::type_url::WASM_CLIENT_STATE;
 
 #[cfg_attr(feature = "cosmwasm", cw_serde)]
 #[cfg_attr(not(feature = "cosmwasm"), derive(Clone, Debug, PartialEq))]
@@ -143,6 +143,18 @@ where
 	AnyClient::ClientMessage: TryFrom<Any>,
 	<AnyClient::ClientMessage as TryFrom<Any>>::Error: Display,
 {
+	/// Wraps `inner`, the new state of the wrapped client, into the envelope of this client. The
+	/// `data` and `latest_height` of the envelope are refreshed from it.
+	pub fn with_inner(&self, inner: AnyClientState) -> Result<Self, tendermint_proto::Error> {
+		Ok(Self {
+			data: inner.encode_to_vec()?,
+			code_id: self.code_id.clone(),
+			latest_height: inner.latest_height(),
+			inner: Box::new(inner),
+			_phantom: PhantomData,
+		})
+	}
+
 	pub fn to_any(&self) -> Any {
 		Any {
 			type_url: WASM_CLIENT_STATE_TYPE_URL.to_string(),
@@ -40,7 +40,7 @@ use ibc_proto::{
 };
 use prost::Message;
 
-pub const WASM_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.ConsensusState";
+pub const WASM_CONSENSUS_STATE_TYPE_URL: &str = ibc_proto_registry::type_url::WASM_CONSENSUS_STATE;
 
 #[cfg_attr(feature = "cosmwasm", cw_serde)]
 #[cfg_attr(not(feature = "cosmwasm"), derive(Clone, Debug, PartialEq))]
@@ -276,7 +276,7 @@ fn process_message(
 			// Check that the substitute client state is valid:
 			// all fields should be the same as in the old state, except for the `relay_chain`,
 			// `para_id`, `latest_para_height`, `latest_relay_height`, `latest_relay_hash`,
-			// `frozen_height`, `current_authorities`, `current_set_id`
+			// `frozen_height`, `current_authorities`, `current_set_id`, `unbonding_period`
 			let ClientState {
 				relay_chain,
 				latest_relay_height,
@@ -286,6 +286,7 @@ fn process_message(
 				para_id,
 				current_set_id,
 				current_authorities,
+				unbonding_period,
 				_phantom,
 			} = substitute_client_state.clone();
 			old_client_state.relay_chain = relay_chain;
@@ -296,6 +297,7 @@ fn process_message(
 			old_client_state.frozen_height = frozen_height;
 			old_client_state.current_authorities = current_authorities.clone();
 			old_client_state.current_set_id = current_set_id;
+			old_client_state.unbonding_period = unbonding_period;
 
 			if old_client_state != substitute_client_state {
 				return Err(ContractError::Grandpa(
@@ -27,7 +27,7 @@ use grandpa_client_primitives::{
 	justification::{
 		find_forced_change, find_scheduled_change, AncestryChain, GrandpaJustification,
 	},
-	ParachainHeadersWithFinalityProof,
+	ParachainHeaderProofs, ParachainHeadersWithFinalityProof,
 };
 use ibc::{
 	core::{
@@ -61,8 +61,10 @@ use sp_runtime::traits::Header;
 use sp_trie::StorageProof;
 use tendermint_proto::Protobuf;
 
-const CLIENT_STATE_UPGRADE_PATH: &[u8] = b"client-state-upgrade-path";
-const CONSENSUS_STATE_UPGRADE_PATH: &[u8] = b"consensus-state-upgrade-path";
+/// Storage key of the upgraded client state in the state of the parachain
+pub const CLIENT_STATE_UPGRADE_PATH: &[u8] = b"client-state-upgrade-path";
+/// Storage key of the upgraded consensus state in the state of the parachain
+pub const CONSENSUS_STATE_UPGRADE_PATH: &[u8] = b"consensus-state-upgrade-path";
 
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
 pub struct GrandpaClient<T>(PhantomData<T>);
@@ -241,11 +243,8 @@ where
 				Error::Custom(format!("No relay chain header found for hash: {relay_hash:?}"))
 			})?;
 
-			let (height, consensus_state) = ConsensusState::from_header::<H>(
-				parachain_header_proof,
-				client_state.para_id,
-				header.state_root.clone(),
-			)?;
+			let (height, consensus_state) =
+				consensus_state_at::<H>(&client_state, header, parachain_header_proof)?;
 
 			// Skip duplicate consensus states
 			if ctx.consensus_state(&client_id, height).is_ok() {
@@ -343,11 +342,8 @@ where
 				return Ok(true)
 			}
 
-			let (height, consensus_state) = ConsensusState::from_header::<H>(
-				parachain_header_proof,
-				client_state.para_id,
-				header.state_root.clone(),
-			)?;
+			let (height, consensus_state) =
+				consensus_state_at::<H>(&client_state, header, parachain_header_proof)?;
 
 			match ctx.maybe_consensus_state(&client_id, height)? {
 				Some(cs) => {
@@ -424,7 +420,7 @@ where
 				StorageProof::new(nodes)
 			};
 
-			let encoded = Ctx::AnyConsensusState::wrap(upgrade_client_state)
+			let encoded = Ctx::AnyConsensusState::wrap(upgrade_consensus_state)
 				.expect("AnyConsensusState is type-checked; qed")
 				.encode_to_vec()
 				.map_err(Ics02Error::encode)?;
@@ -673,3 +669,28 @@ where
 		Ok(())
 	}
 }
+
+/// Consensus state of the chain tracked by `client_state` at the finalized relay chain `header`.
+/// Standalone chains are finalized by GRANDPA themselves, so their state root and height are the
+/// ones of `header`.
+fn consensus_state_at<H>(
+	client_state: &ClientState<H>,
+	header: &RelayChainHeader,
+	parachain_header_proof: ParachainHeaderProofs,
+) -> Result<(Height, ConsensusState), Error>
+where
+	H: grandpa_client_primitives::HostFunctions,
+{
+	if client_state.is_standalone() {
+		return ConsensusState::from_standalone_header(
+			header,
+			&parachain_header_proof.extrinsic,
+			client_state.para_id,
+		)
+	}
+	ConsensusState::from_header::<H>(
+		parachain_header_proof,
+		client_state.para_id,
+		header.state_root.clone(),
+	)
+}
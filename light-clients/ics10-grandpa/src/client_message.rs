@@ -30,9 +30,10 @@ use sp_runtime::traits::BlakeTwo256;
 use tendermint_proto::Protobuf;
 
 /// Protobuf type url for GRANDPA header
-pub const GRANDPA_CLIENT_MESSAGE_TYPE_URL: &str = "/ibc.lightclients.grandpa.v1.ClientMessage";
-pub const GRANDPA_HEADER_TYPE_URL: &str = "/ibc.lightclients.grandpa.v1.Header";
-pub const GRANDPA_MISBEHAVIOUR_TYPE_URL: &str = "/ibc.lightclients.grandpa.v1.Misbehaviour";
+pub const GRANDPA_CLIENT_MESSAGE_TYPE_URL: &str =
+	ibc_proto_registry::type_url::GRANDPA_CLIENT_MESSAGE;
+pub const GRANDPA_HEADER_TYPE_URL: &str = ibc_proto_registry::type_url::GRANDPA_HEADER;
+pub const GRANDPA_MISBEHAVIOUR_TYPE_URL: &str = ibc_proto_registry::type_url::GRANDPA_MISBEHAVIOUR;
 
 /// Relay chain substrate header type
 pub type RelayChainHeader = sp_runtime::generic::Header<u32, BlakeTwo256>;
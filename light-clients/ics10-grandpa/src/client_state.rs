@@ -41,7 +41,7 @@ use sp_core::{ed25519::Public, H256};
 use tendermint_proto::Protobuf;
 
 /// Protobuf type url for GRANDPA ClientState
-pub const GRANDPA_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.grandpa.v1.ClientState";
+pub const GRANDPA_CLIENT_STATE_TYPE_URL: &str = ibc_proto_registry::type_url::GRANDPA_CLIENT_STATE;
 
 #[derive(PartialEq, Clone, Debug, Default, Eq)]
 pub struct ClientState<H> {
@@ -61,6 +61,9 @@ pub struct ClientState<H> {
 	pub current_set_id: u64,
 	/// authorities for the current round
 	pub current_authorities: AuthorityList,
+	/// Unbonding period of the validators of a standalone chain, which has no relay chain to
+	/// take it from. Always `None` for parachains.
+	pub unbonding_period: Option<Duration>,
 	/// phantom type.
 	pub _phantom: PhantomData<H>,
 }
@@ -74,6 +77,7 @@ impl<H> From<ClientState<H>> for grandpa_client_primitives::ClientState {
 			latest_relay_height: client_state.latest_relay_height,
 			latest_para_height: client_state.latest_para_height,
 			para_id: client_state.para_id,
+			standalone: client_state.is_standalone(),
 		}
 	}
 }
@@ -111,6 +115,12 @@ impl<H: Clone> ClientState<H> {
 }
 
 impl<H> ClientState<H> {
+	/// Whether the client follows a standalone chain, whose own headers are finalized by the
+	/// GRANDPA justifications, instead of a parachain whose headers are proven in the relay chain.
+	pub fn is_standalone(&self) -> bool {
+		self.relay_chain == RelayChain::Standalone
+	}
+
 	pub fn latest_height(&self) -> Height {
 		Height::new(self.para_id.into(), self.latest_para_height.into())
 	}
@@ -140,10 +150,19 @@ impl<H> ClientState<H> {
 		self
 	}
 
+	/// Period during which the consensus states of the client are trusted: the one of the relay
+	/// chain for parachains, and the one derived from the unbonding period of standalone chains.
+	pub fn trusting_period(&self) -> Option<Duration> {
+		self.relay_chain
+			.trusting_period()
+			.or_else(|| self.unbonding_period.map(light_client_common::trusting_period))
+	}
+
 	/// Check if the state is expired when `elapsed` time has passed since the latest consensus
 	/// state timestamp
 	pub fn expired(&self, elapsed: Duration) -> bool {
-		elapsed > self.relay_chain.trusting_period()
+		// a client without a trusting period can't be trusted at all
+		self.trusting_period().map_or(true, |trusting_period| elapsed > trusting_period)
 	}
 
 	pub fn with_frozen_height(self, h: Height) -> Result<Self, Error> {
@@ -243,6 +262,14 @@ impl<H> TryFrom<RawClientState> for ClientState<H> {
 			.collect::<Result<_, Error>>()?;
 
 		let relay_chain = RelayChain::from_i32(raw.relay_chain)?;
+		let unbonding_period = raw.unbonding_period.map(Duration::from_secs);
+		if relay_chain == RelayChain::Standalone {
+			if unbonding_period.map_or(true, |unbonding_period| unbonding_period.is_zero()) {
+				Err(anyhow!("Missing unbonding period of standalone chain"))?
+			}
+		} else if unbonding_period.is_some() {
+			Err(anyhow!("Unbonding period of {relay_chain} parachains can't be configured"))?
+		}
 		if raw.latest_relay_hash.len() != 32 {
 			Err(anyhow!("Invalid ed25519 public key lenght: {}", raw.latest_relay_hash.len()))?
 		}
@@ -259,6 +286,7 @@ impl<H> TryFrom<RawClientState> for ClientState<H> {
 			current_authorities,
 			latest_relay_hash,
 			latest_relay_height: raw.latest_relay_height,
+			unbonding_period,
 			_phantom: Default::default(),
 		})
 	}
@@ -285,6 +313,9 @@ impl<H> From<ClientState<H>> for RawClientState {
 					weight,
 				})
 				.collect(),
+			unbonding_period: client_state
+				.unbonding_period
+				.map(|unbonding_period| unbonding_period.as_secs()),
 		}
 	}
 }
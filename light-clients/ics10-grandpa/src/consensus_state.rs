@@ -33,7 +33,8 @@ use sp_runtime::{generic, traits::BlakeTwo256, SaturatedConversion};
 use sp_trie::StorageProof;
 
 /// Protobuf type url for GRANDPA Consensus State
-pub const GRANDPA_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.lightclients.grandpa.v1.ConsensusState";
+pub const GRANDPA_CONSENSUS_STATE_TYPE_URL: &str =
+	ibc_proto_registry::type_url::GRANDPA_CONSENSUS_STATE;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ConsensusState {
@@ -76,18 +77,37 @@ impl ConsensusState {
 		let parachain_header =
 			generic::Header::<u32, BlakeTwo256>::decode(&mut &parachain_header_bytes[..])?;
 		let root = parachain_header.state_root.0.to_vec();
-
-		let timestamp = decode_timestamp_extrinsic(&parachain_header_proof.extrinsic)?;
-		let duration = core::time::Duration::from_millis(timestamp);
-		let timestamp = Timestamp::from_nanoseconds(duration.as_nanos().saturated_into::<u64>())?
-			.into_tm_time()
-			.ok_or_else(|| anyhow!("Error decoding Timestamp, timestamp cannot be zero"))?;
+		let timestamp = timestamp_from_extrinsic(&parachain_header_proof.extrinsic)?;
 
 		Ok((
 			Height::new(para_id as u64, parachain_header.number as u64),
 			Self { root: root.into(), timestamp },
 		))
 	}
+
+	/// Consensus state of a standalone chain at its finalized `header`, whose timestamp is set by
+	/// `extrinsic`. The heights of the chain are in the revision `revision_number`.
+	pub fn from_standalone_header(
+		header: &generic::Header<u32, BlakeTwo256>,
+		extrinsic: &Vec<u8>,
+		revision_number: u32,
+	) -> Result<(Height, Self), Error> {
+		let root = header.state_root.0.to_vec();
+		let timestamp = timestamp_from_extrinsic(extrinsic)?;
+		Ok((
+			Height::new(revision_number as u64, header.number as u64),
+			Self { root: root.into(), timestamp },
+		))
+	}
+}
+
+fn timestamp_from_extrinsic(extrinsic: &Vec<u8>) -> Result<Time, Error> {
+	let timestamp = decode_timestamp_extrinsic(extrinsic)?;
+	let duration = core::time::Duration::from_millis(timestamp);
+	let timestamp = Timestamp::from_nanoseconds(duration.as_nanos().saturated_into::<u64>())?
+		.into_tm_time()
+		.ok_or_else(|| anyhow!("Error decoding Timestamp, timestamp cannot be zero"))?;
+	Ok(timestamp)
 }
 
 impl ibc::core::ics02_client::client_consensus::ConsensusState for ConsensusState {
@@ -14,6 +14,7 @@
 // limitations under the License.
 
 use crate::{
+	client_def::GrandpaClient,
 	client_message::{ClientMessage, Header, RelayChainHeader},
 	client_state::ClientState,
 	consensus_state::ConsensusState,
@@ -22,11 +23,12 @@ use crate::{
 	},
 };
 use beefy_prover::helpers::{fetch_timestamp_extrinsic_with_proof, TimeStampExtWithProof};
-use codec::{Decode, Encode};
+use codec::{Compact, Decode, Encode};
+use finality_grandpa::{Precommit, SignedPrecommit};
 use finality_grandpa_rpc::GrandpaApiClient;
 use futures::stream::StreamExt;
 use grandpa_client_primitives::{
-	justification::GrandpaJustification, parachain_header_storage_key, FinalityProof,
+	justification::GrandpaJustification, parachain_header_storage_key, Commit, FinalityProof,
 	ParachainHeaderProofs, ParachainHeadersWithFinalityProof,
 };
 use grandpa_prover::{GrandpaProver, JustificationNotification};
@@ -34,6 +36,8 @@ use hyperspace_core::substrate::DefaultConfig as PolkadotConfig;
 use ibc::{
 	core::{
 		ics02_client::{
+			client_consensus::ConsensusState as _,
+			client_def::ClientDef,
 			client_state::ClientState as _,
 			context::{ClientKeeper, ClientReader},
 			handler::{dispatch, ClientResult::Update},
@@ -49,10 +53,13 @@ use ibc::{
 	test_utils::get_dummy_account_id,
 	Height,
 };
-use light_client_common::config::RuntimeStorage;
-use sp_core::{hexdisplay::AsBytesRef, H256};
-use std::time::Duration;
+use light_client_common::{config::RuntimeStorage, RelayChain};
+use sp_core::{ed25519, hexdisplay::AsBytesRef, Pair, H256};
+use sp_runtime::traits::Header as _;
+use sp_trie::{generate_trie_proof, LayoutV0, MemoryDB, TrieDBMutBuilder, TrieMut};
+use std::{collections::BTreeMap, time::Duration};
 use subxt::config::substrate::{BlakeTwo256, SubstrateHeader};
+use tendermint_proto::Protobuf;
 
 #[tokio::test]
 async fn test_continuous_update_of_grandpa_client() {
@@ -146,6 +153,7 @@ async fn test_continuous_update_of_grandpa_client() {
 			para_id: prover.para_id,
 			current_set_id: client_state.current_set_id,
 			current_authorities: client_state.current_authorities,
+			unbonding_period: None,
 			_phantom: Default::default(),
 		};
 		let subxt_block_number: subxt::rpc::types::BlockNumber = decoded_para_head.number.into();
@@ -306,3 +314,171 @@ async fn test_continuous_update_of_grandpa_client() {
 		}
 	}
 }
+
+const STANDALONE_TIMESTAMP_MILLIS: u64 = 1_650_894_363_000;
+
+fn standalone_authorities() -> Vec<ed25519::Pair> {
+	["//Alice", "//Bob", "//Charlie"]
+		.iter()
+		.map(|seed| ed25519::Pair::from_string(seed, None).unwrap())
+		.collect()
+}
+
+fn standalone_client_state() -> ClientState<HostFunctionsManager> {
+	ClientState {
+		relay_chain: RelayChain::Standalone,
+		latest_relay_height: 1,
+		latest_relay_hash: H256::zero(),
+		frozen_height: None,
+		latest_para_height: 1,
+		para_id: 1,
+		current_set_id: 1,
+		current_authorities: standalone_authorities()
+			.iter()
+			.map(|pair| (pair.public().into(), 1))
+			.collect(),
+		unbonding_period: Some(Duration::from_secs(21 * 24 * 60 * 60)),
+		_phantom: Default::default(),
+	}
+}
+
+/// Builds a client message finalizing the standalone header that follows the latest header of
+/// `client_state`, which includes the timestamp extrinsic.
+fn standalone_header(
+	client_state: &ClientState<HostFunctionsManager>,
+	authorities: &[ed25519::Pair],
+) -> (RelayChainHeader, Header) {
+	let mut db = MemoryDB::<sp_runtime::traits::BlakeTwo256>::default();
+	let extrinsic =
+		[vec![0, 0], (1u8, 0u8, Compact(STANDALONE_TIMESTAMP_MILLIS)).encode()].concat();
+	let key = Compact(0u64).encode();
+	let extrinsics_root = {
+		let mut root = Default::default();
+		let mut trie =
+			TrieDBMutBuilder::<LayoutV0<sp_runtime::traits::BlakeTwo256>>::new(&mut db, &mut root)
+				.build();
+		trie.insert(&key, &extrinsic).unwrap();
+		*trie.root()
+	};
+	let extrinsic_proof =
+		generate_trie_proof::<LayoutV0<sp_runtime::traits::BlakeTwo256>, _, _, _>(
+			&db,
+			extrinsics_root,
+			vec![&key],
+		)
+		.unwrap();
+
+	let header = RelayChainHeader {
+		parent_hash: client_state.latest_relay_hash,
+		number: client_state.latest_relay_height + 1,
+		state_root: H256::repeat_byte(1),
+		extrinsics_root,
+		digest: Default::default(),
+	};
+	let precommit = Precommit { target_hash: header.hash(), target_number: header.number };
+	let message = finality_grandpa::Message::Precommit(precommit.clone());
+	let payload = sp_consensus_grandpa::localized_payload(1, client_state.current_set_id, &message);
+	let precommits = authorities
+		.iter()
+		.map(|pair| SignedPrecommit {
+			precommit: precommit.clone(),
+			signature: pair.sign(&payload).into(),
+			id: pair.public().into(),
+		})
+		.collect();
+	let justification = GrandpaJustification::<RelayChainHeader> {
+		round: 1,
+		commit: Commit::<RelayChainHeader> {
+			target_hash: header.hash(),
+			target_number: header.number,
+			precommits,
+		},
+		votes_ancestries: vec![],
+	};
+
+	let proofs = ParachainHeaderProofs { state_proof: vec![], extrinsic, extrinsic_proof };
+	let client_message = Header {
+		finality_proof: FinalityProof {
+			block: header.hash(),
+			justification: justification.encode(),
+			unknown_headers: vec![header.clone()],
+		},
+		parachain_headers: BTreeMap::from([(header.hash(), proofs)]),
+		height: Height::new(client_state.para_id.into(), header.number.into()),
+	};
+	(header, client_message)
+}
+
+fn verify_standalone_header(header: Header) -> Result<(), ibc::core::ics02_client::error::Error> {
+	let ctx = MockContext::<MockClientTypes>::new(
+		ChainId::new("mockgaiaA".to_string(), 1),
+		MockHostType::Mock,
+		5,
+		Height::new(1, 11),
+	);
+	let client_id = ClientId::new(&ClientState::<HostFunctionsManager>::client_type(), 0).unwrap();
+	GrandpaClient::<HostFunctionsManager>::default().verify_client_message(
+		&ctx,
+		client_id,
+		standalone_client_state(),
+		ClientMessage::Header(header),
+	)
+}
+
+#[test]
+fn standalone_client_state_requires_an_unbonding_period() {
+	let client_state = standalone_client_state();
+	assert_eq!(client_state.trusting_period(), Some(Duration::from_secs(7 * 24 * 60 * 60)));
+	assert!(!client_state.expired(Duration::from_secs(24 * 60 * 60)));
+	let decoded =
+		ClientState::<HostFunctionsManager>::decode_vec(&client_state.encode_vec().unwrap());
+	assert_eq!(decoded.unwrap(), client_state);
+
+	let without_unbonding_period = ClientState { unbonding_period: None, ..client_state.clone() };
+	assert!(without_unbonding_period.expired(Duration::ZERO));
+	assert!(ClientState::<HostFunctionsManager>::decode_vec(
+		&without_unbonding_period.encode_vec().unwrap()
+	)
+	.is_err());
+
+	// parachains use the unbonding period of their relay chain
+	let parachain = ClientState { relay_chain: RelayChain::Polkadot, ..client_state };
+	assert!(
+		ClientState::<HostFunctionsManager>::decode_vec(&parachain.encode_vec().unwrap()).is_err()
+	);
+}
+
+#[test]
+fn standalone_headers_are_verified_by_the_client() {
+	let client_state = standalone_client_state();
+	let (finalized, header) = standalone_header(&client_state, &standalone_authorities());
+	let extrinsic = header.parachain_headers[&finalized.hash()].extrinsic.clone();
+
+	verify_standalone_header(header).unwrap();
+
+	let (height, consensus_state) =
+		ConsensusState::from_standalone_header(&finalized, &extrinsic, client_state.para_id)
+			.unwrap();
+	assert_eq!(height, Height::new(1, 2));
+	assert_eq!(consensus_state.root.as_bytes(), finalized.state_root.as_bytes());
+	assert_eq!(consensus_state.timestamp().nanoseconds(), STANDALONE_TIMESTAMP_MILLIS * 1_000_000);
+}
+
+#[test]
+fn standalone_headers_with_a_tampered_extrinsic_are_rejected() {
+	let client_state = standalone_client_state();
+	let (finalized, mut header) = standalone_header(&client_state, &standalone_authorities());
+	let proofs = header.parachain_headers.get_mut(&finalized.hash()).unwrap();
+	*proofs.extrinsic.last_mut().unwrap() ^= 1;
+
+	assert!(verify_standalone_header(header).is_err());
+}
+
+#[test]
+fn standalone_headers_with_a_tampered_header_are_rejected() {
+	let client_state = standalone_client_state();
+	let (_, mut header) = standalone_header(&client_state, &standalone_authorities());
+	header.finality_proof.unknown_headers[0].state_root = H256::repeat_byte(0xff);
+
+	assert!(verify_standalone_header(header).is_err());
+}
@@ -36,7 +36,7 @@ use ibc::{
 use light_client_common::RelayChain;
 
 /// Protobuf type url for Beefy ClientState
-pub const BEEFY_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.beefy.v1.ClientState";
+pub const BEEFY_CLIENT_STATE_TYPE_URL: &str = ibc_proto_registry::type_url::BEEFY_CLIENT_STATE;
 
 #[derive(PartialEq, Clone, Debug, Default, Eq)]
 pub struct ClientState<H> {
@@ -81,6 +81,9 @@ impl<H: Clone> ClientState<H> {
 					.to_string(),
 			))
 		}
+		if relay_chain == RelayChain::Standalone {
+			return Err(Error::Custom("BEEFY clients can't follow standalone chains".to_string()))
+		}
 		let chain_id = ChainId::new(relay_chain.to_string(), para_id.into());
 
 		Ok(Self {
@@ -217,7 +220,10 @@ impl<H> ClientState<H> {
 	/// Check if the state is expired when `elapsed` time has passed since the latest consensus
 	/// state timestamp
 	pub fn expired(&self, elapsed: Duration) -> bool {
-		elapsed > self.relay_chain.trusting_period()
+		// standalone chains aren't followed by BEEFY clients, which are rejected on decoding
+		self.relay_chain
+			.trusting_period()
+			.map_or(true, |trusting_period| elapsed > trusting_period)
 	}
 }
 
@@ -294,6 +300,9 @@ impl<H> TryFrom<RawClientState> for ClientState<H> {
 
 		let mmr_root_hash = H256::decode(&mut &*raw.mmr_root_hash)?;
 		let relay_chain = RelayChain::from_i32(raw.relay_chain)?;
+		if relay_chain == RelayChain::Standalone {
+			return Err(Error::Custom("BEEFY clients can't follow standalone chains".to_string()))
+		}
 		let chain_id = ChainId::new(relay_chain.to_string(), raw.para_id.into());
 
 		Ok(Self {
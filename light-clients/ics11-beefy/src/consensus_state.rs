@@ -27,7 +27,8 @@ use ibc::{core::ics23_commitment::commitment::CommitmentRoot, timestamp::Timesta
 use light_client_common::decode_timestamp_extrinsic;
 
 /// Protobuf type url for Beefy Consensus State
-pub const BEEFY_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.lightclients.beefy.v1.ConsensusState";
+pub const BEEFY_CONSENSUS_STATE_TYPE_URL: &str =
+	ibc_proto_registry::type_url::BEEFY_CONSENSUS_STATE;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct ConsensusState {
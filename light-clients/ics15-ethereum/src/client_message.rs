@@ -0,0 +1,32 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::types::LightClientUpdate;
+use alloc::vec::Vec;
+use codec::Encode;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientMessage {
+	/// Update of the client to a new finalized header.
+	Header(LightClientUpdate),
+}
+
+impl ibc::core::ics02_client::client_message::ClientMessage for ClientMessage {
+	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
+		match self {
+			ClientMessage::Header(update) => Ok(update.encode()),
+		}
+	}
+}
@@ -0,0 +1,210 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	client_def::EthereumClient,
+	error::Error,
+	types::{
+		compute_sync_committee_period_at_slot, BeaconBlockHeader, Fork, LightClientUpdate, Root,
+		SyncCommittee,
+	},
+	HostFunctions,
+};
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use codec::{Decode, Encode};
+use core::{marker::PhantomData, time::Duration};
+use ibc::{
+	core::{
+		ics02_client::client_state::{ClientState as _, ClientType},
+		ics24_host::identifier::ChainId,
+	},
+	Height,
+};
+
+/// State of the Ethereum light client: the latest finalized beacon block header it verified,
+/// and the sync committees that sign the next ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientState<H> {
+	pub chain_id: ChainId,
+	/// Root of the validators of the genesis state, part of the domain of the signatures.
+	pub genesis_validators_root: Root,
+	/// Unix timestamp of the genesis, in seconds.
+	pub genesis_time: u64,
+	/// Fork schedule of the chain, by increasing epoch.
+	pub fork_versions: Vec<Fork>,
+	/// Latest finalized header.
+	pub finalized_header: BeaconBlockHeader,
+	/// Sync committee of the period of `finalized_header`.
+	pub current_sync_committee: SyncCommittee,
+	/// Sync committee of the following period, once an update proved it.
+	pub next_sync_committee: Option<SyncCommittee>,
+	/// Address of the IBC contract on the execution layer.
+	pub ibc_contract: [u8; 20],
+	/// Storage slot of the mapping of the commitments in the IBC contract.
+	pub commitments_slot: u64,
+	/// Block height when the client was frozen due to a misbehaviour
+	pub frozen_height: Option<Height>,
+	pub _phantom: PhantomData<H>,
+}
+
+#[derive(Encode, Decode)]
+struct RawClientState {
+	chain_id: String,
+	genesis_validators_root: Root,
+	genesis_time: u64,
+	fork_versions: Vec<Fork>,
+	finalized_header: BeaconBlockHeader,
+	current_sync_committee: SyncCommittee,
+	next_sync_committee: Option<SyncCommittee>,
+	ibc_contract: [u8; 20],
+	commitments_slot: u64,
+	frozen_height: Option<(u64, u64)>,
+}
+
+impl<H> ClientState<H> {
+	pub fn client_type() -> ClientType {
+		"15-ethereum".to_string()
+	}
+
+	pub fn decode_vec(bytes: &[u8]) -> Result<Self, Error> {
+		let raw = RawClientState::decode(&mut &*bytes)?;
+		Ok(Self {
+			chain_id: raw.chain_id.into(),
+			genesis_validators_root: raw.genesis_validators_root,
+			genesis_time: raw.genesis_time,
+			fork_versions: raw.fork_versions,
+			finalized_header: raw.finalized_header,
+			current_sync_committee: raw.current_sync_committee,
+			next_sync_committee: raw.next_sync_committee,
+			ibc_contract: raw.ibc_contract,
+			commitments_slot: raw.commitments_slot,
+			frozen_height: raw.frozen_height.map(|(number, height)| Height::new(number, height)),
+			_phantom: PhantomData,
+		})
+	}
+}
+
+impl<H: HostFunctions> ClientState<H> {
+	/// Version of the fork the chain was at in `epoch`.
+	pub fn fork_version(&self, epoch: u64) -> Result<[u8; 4], Error> {
+		self.fork_versions
+			.iter()
+			.rev()
+			.find(|fork| fork.epoch <= epoch)
+			.map(|fork| fork.version)
+			.ok_or_else(|| Error::Custom(format!("No fork scheduled before epoch {epoch}")))
+	}
+
+	/// Moves the client to the finalized header of the verified `update`, following
+	/// `apply_light_client_update` of the specification: the sync committees are rotated when
+	/// the header is in the next period.
+	pub fn with_update(mut self, update: &LightClientUpdate) -> Self {
+		let store_period = compute_sync_committee_period_at_slot(self.finalized_header.slot);
+		let update_period = compute_sync_committee_period_at_slot(update.finalized_header.slot);
+		let next_sync_committee =
+			update.next_sync_committee.as_ref().map(|(committee, _)| committee.clone());
+		if self.next_sync_committee.is_none() {
+			self.next_sync_committee = next_sync_committee;
+		} else if update_period == store_period + 1 {
+			if let Some(committee) = self.next_sync_committee.take() {
+				self.current_sync_committee = committee;
+			}
+			self.next_sync_committee = next_sync_committee;
+		}
+		self.finalized_header = update.finalized_header.clone();
+		self
+	}
+
+	pub fn with_frozen_height(self, height: Height) -> Self {
+		Self { frozen_height: Some(height), ..self }
+	}
+
+	pub fn verify_height(&self, height: Height) -> Result<(), Error> {
+		let latest_height = self.latest_height();
+		if latest_height < height {
+			return Err(Error::Custom(format!(
+				"Insufficient height, known height: {latest_height}, given height: {height}"
+			)))
+		}
+
+		match self.frozen_height {
+			Some(frozen_height) if frozen_height <= height =>
+				Err(Error::Custom(format!("Client has been frozen at height {frozen_height}"))),
+			_ => Ok(()),
+		}
+	}
+}
+
+impl<H: HostFunctions> ibc::core::ics02_client::client_state::ClientState for ClientState<H> {
+	type UpgradeOptions = ();
+	type ClientDef = EthereumClient<H>;
+
+	fn chain_id(&self) -> ChainId {
+		self.chain_id.clone()
+	}
+
+	fn client_def(&self) -> Self::ClientDef {
+		EthereumClient::default()
+	}
+
+	fn client_type(&self) -> ClientType {
+		Self::client_type()
+	}
+
+	/// The heights of the client are the slots of the finalized headers.
+	fn latest_height(&self) -> Height {
+		Height::new(0, self.finalized_header.slot)
+	}
+
+	fn frozen_height(&self) -> Option<Height> {
+		self.frozen_height
+	}
+
+	fn upgrade(
+		self,
+		_upgrade_height: Height,
+		_upgrade_options: Self::UpgradeOptions,
+		_chain_id: ChainId,
+	) -> Self {
+		self
+	}
+
+	fn expired(&self, _elapsed: Duration) -> bool {
+		// the client can't verify updates once it missed a whole sync committee period
+		false
+	}
+
+	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
+		let raw = RawClientState {
+			chain_id: self.chain_id.to_string(),
+			genesis_validators_root: self.genesis_validators_root,
+			genesis_time: self.genesis_time,
+			fork_versions: self.fork_versions.clone(),
+			finalized_header: self.finalized_header.clone(),
+			current_sync_committee: self.current_sync_committee.clone(),
+			next_sync_committee: self.next_sync_committee.clone(),
+			ibc_contract: self.ibc_contract,
+			commitments_slot: self.commitments_slot,
+			frozen_height: self
+				.frozen_height
+				.map(|height| (height.revision_number, height.revision_height)),
+		};
+		Ok(raw.encode())
+	}
+}
@@ -0,0 +1,71 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	error::Error,
+	types::{LightClientUpdate, SECONDS_PER_SLOT},
+};
+use alloc::{string::ToString, vec::Vec};
+use codec::{Decode, Encode};
+use ibc::{
+	core::{ics02_client::client_consensus, ics23_commitment::commitment::CommitmentRoot},
+	timestamp::Timestamp,
+};
+
+/// Consensus state of a finalized block: the state root of its execution payload, which commits
+/// the storage of the IBC contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsensusState {
+	pub root: CommitmentRoot,
+	pub timestamp: Timestamp,
+}
+
+impl ConsensusState {
+	/// Consensus state of the finalized block of the verified `update`, of the chain whose
+	/// genesis was at `genesis_time`.
+	pub fn from_update(update: &LightClientUpdate, genesis_time: u64) -> Self {
+		let seconds = genesis_time + update.finalized_header.slot * SECONDS_PER_SLOT;
+		Self {
+			root: CommitmentRoot::from_bytes(&update.execution_state_root),
+			timestamp: Timestamp::from_nanoseconds(seconds * 1_000_000_000)
+				.unwrap_or_else(|_| Timestamp::none()),
+		}
+	}
+
+	pub fn decode_vec(bytes: &[u8]) -> Result<Self, Error> {
+		let (root, timestamp) = <(Vec<u8>, u64)>::decode(&mut &*bytes)?;
+		Ok(Self {
+			root: CommitmentRoot::from_bytes(&root),
+			timestamp: Timestamp::from_nanoseconds(timestamp)
+				.map_err(|e| Error::Custom(e.to_string()))?,
+		})
+	}
+}
+
+impl client_consensus::ConsensusState for ConsensusState {
+	type Error = Error;
+
+	fn root(&self) -> &CommitmentRoot {
+		&self.root
+	}
+
+	fn timestamp(&self) -> Timestamp {
+		self.timestamp
+	}
+
+	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
+		Ok((self.root.as_bytes(), self.timestamp.nanoseconds()).encode())
+	}
+}
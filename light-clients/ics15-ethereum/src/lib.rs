@@ -0,0 +1,68 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::all)]
+
+//! ICS-15 light client of Ethereum, following the finality of the beacon chain.
+//!
+//! The client tracks the finalized beacon block headers with the sync committee protocol of the
+//! Altair light client specification: an update is accepted when a supermajority of the current
+//! (or next) sync committee signed a header proving the new finalized header. The IBC state is
+//! proven against the state root of the execution payload of the finalized block, in which the
+//! IBC contract stores the commitments of its paths.
+//!
+//! The client isn't part of the `AnyClientState` of pallet-ibc yet, and hyperspace has no
+//! Ethereum chain backend: both need an implementation of the BLS12-381 host function, which
+//! none of the runtimes provides.
+
+extern crate alloc;
+
+use core::fmt::Debug;
+use types::{BlsPublicKey, BlsSignature};
+
+pub mod client_def;
+pub mod client_message;
+pub mod client_state;
+pub mod consensus_state;
+pub mod error;
+pub mod rlp;
+pub mod ssz;
+pub mod trie;
+pub mod types;
+pub mod verify;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Host functions that allow the light client perform cryptographic operations in native.
+pub trait HostFunctions: Clone + Send + Sync + Eq + Debug + Default {
+	/// The SHA-256 hash function, used for the SSZ merkleization of the beacon chain.
+	fn sha256(data: &[u8]) -> [u8; 32];
+
+	/// The Keccak-256 hash function, used by the tries of the execution layer.
+	fn keccak_256(data: &[u8]) -> [u8; 32];
+
+	/// Checks that `signature` is the BLS12-381 signature of `message` aggregated from all the
+	/// `public_keys`, the `FastAggregateVerify` of the consensus specification.
+	fn bls_fast_aggregate_verify(
+		public_keys: &[BlsPublicKey],
+		message: &[u8; 32],
+		signature: &BlsSignature,
+	) -> bool;
+}
@@ -0,0 +1,54 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	types::{BlsPublicKey, BlsSignature, Root},
+	HostFunctions,
+};
+use sha2::Digest;
+use std::{cell::RefCell, vec::Vec};
+
+/// Signature [`HostFunctionsManager`] rejects.
+pub const INVALID_SIGNATURE: BlsSignature = BlsSignature([0; 96]);
+
+thread_local! {
+	/// Number of signers and message of the signatures verified by the current test.
+	pub static VERIFIED_SIGNATURES: RefCell<Vec<(usize, Root)>> = RefCell::new(Vec::new());
+}
+
+#[derive(Clone, Default, PartialEq, Debug, Eq)]
+pub struct HostFunctionsManager;
+
+impl HostFunctions for HostFunctionsManager {
+	fn sha256(data: &[u8]) -> [u8; 32] {
+		sha2::Sha256::digest(data).into()
+	}
+
+	fn keccak_256(data: &[u8]) -> [u8; 32] {
+		sha3::Keccak256::digest(data).into()
+	}
+
+	/// The signatures aren't verified, only recorded for the tests to check what was signed:
+	/// every signature but [`INVALID_SIGNATURE`] is accepted.
+	fn bls_fast_aggregate_verify(
+		public_keys: &[BlsPublicKey],
+		message: &[u8; 32],
+		signature: &BlsSignature,
+	) -> bool {
+		VERIFIED_SIGNATURES
+			.with(|signatures| signatures.borrow_mut().push((public_keys.len(), *message)));
+		signature != &INVALID_SIGNATURE
+	}
+}
@@ -0,0 +1,89 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decoding of the RLP items of the execution layer tries.
+
+use crate::error::Error;
+use alloc::{format, vec::Vec};
+
+/// An RLP item at the start of an encoding.
+struct Item<'a> {
+	is_list: bool,
+	payload: &'a [u8],
+	/// Length of the whole encoding of the item.
+	len: usize,
+}
+
+fn read_length(data: &[u8], len_of_len: usize) -> Result<usize, Error> {
+	let bytes = data
+		.get(1..1 + len_of_len)
+		.ok_or_else(|| Error::Rlp(format!("truncated length of {len_of_len} bytes")))?;
+	if len_of_len > core::mem::size_of::<usize>() || bytes[0] == 0 {
+		return Err(Error::Rlp("non-canonical length".into()))
+	}
+	Ok(bytes.iter().fold(0, |len, byte| len << 8 | *byte as usize))
+}
+
+fn item(data: &[u8]) -> Result<Item, Error> {
+	let prefix = *data.first().ok_or_else(|| Error::Rlp("empty item".into()))?;
+	let (is_list, offset, payload_len) = match prefix {
+		0..=0x7f => return Ok(Item { is_list: false, payload: &data[..1], len: 1 }),
+		0x80..=0xb7 => (false, 1, (prefix - 0x80) as usize),
+		0xb8..=0xbf => {
+			let len_of_len = (prefix - 0xb7) as usize;
+			(false, 1 + len_of_len, read_length(data, len_of_len)?)
+		},
+		0xc0..=0xf7 => (true, 1, (prefix - 0xc0) as usize),
+		0xf8..=0xff => {
+			let len_of_len = (prefix - 0xf7) as usize;
+			(true, 1 + len_of_len, read_length(data, len_of_len)?)
+		},
+	};
+	let len = offset
+		.checked_add(payload_len)
+		.filter(|len| *len <= data.len())
+		.ok_or_else(|| Error::Rlp(format!("truncated item of {payload_len} bytes")))?;
+	Ok(Item { is_list, payload: &data[offset..len], len })
+}
+
+/// Whether `data` is the encoding of a list.
+pub fn is_list(data: &[u8]) -> bool {
+	data.first().map_or(false, |prefix| *prefix >= 0xc0)
+}
+
+/// Returns the payload of the byte string encoded in `data`.
+pub fn decode_bytes(data: &[u8]) -> Result<&[u8], Error> {
+	let item = item(data)?;
+	if item.is_list || item.len != data.len() {
+		return Err(Error::Rlp("expected a single byte string".into()))
+	}
+	Ok(item.payload)
+}
+
+/// Returns the encodings of the items of the list encoded in `data`.
+pub fn decode_list(data: &[u8]) -> Result<Vec<&[u8]>, Error> {
+	let list = item(data)?;
+	if !list.is_list || list.len != data.len() {
+		return Err(Error::Rlp("expected a single list".into()))
+	}
+	let mut items = Vec::new();
+	let mut rest = list.payload;
+	while !rest.is_empty() {
+		let len = item(rest)?.len;
+		items.push(&rest[..len]);
+		rest = &rest[len..];
+	}
+	Ok(items)
+}
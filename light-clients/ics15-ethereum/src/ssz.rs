@@ -0,0 +1,86 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SSZ merkleization of the beacon chain containers the light client verifies.
+
+use crate::{types::Root, HostFunctions};
+use alloc::vec::Vec;
+
+pub fn hash_pair<H: HostFunctions>(left: &Root, right: &Root) -> Root {
+	let mut data = [0; 64];
+	data[..32].copy_from_slice(left);
+	data[32..].copy_from_slice(right);
+	H::sha256(&data)
+}
+
+/// Root of the tree of `chunks`, padded with zero chunks up to a power of two.
+pub fn merkleize<H: HostFunctions>(chunks: &[Root]) -> Root {
+	let mut layer = chunks.to_vec();
+	layer.resize(chunks.len().next_power_of_two(), [0; 32]);
+	while layer.len() > 1 {
+		layer = layer
+			.chunks(2)
+			.map(|pair| hash_pair::<H>(&pair[0], &pair[1]))
+			.collect::<Vec<_>>();
+	}
+	layer.first().copied().unwrap_or_default()
+}
+
+pub fn uint64_root(value: u64) -> Root {
+	let mut root = [0; 32];
+	root[..8].copy_from_slice(&value.to_le_bytes());
+	root
+}
+
+/// Whether `branch` proves `leaf` at the generalized index `index` of the tree of `root`.
+pub fn is_valid_merkle_branch<H: HostFunctions>(
+	leaf: Root,
+	branch: &[Root],
+	index: u64,
+	root: &Root,
+) -> bool {
+	let depth = 63 - index.leading_zeros() as usize;
+	if branch.len() != depth {
+		return false
+	}
+	let value = branch.iter().enumerate().fold(leaf, |value, (i, node)| {
+		if index >> i & 1 == 1 {
+			hash_pair::<H>(node, &value)
+		} else {
+			hash_pair::<H>(&value, node)
+		}
+	});
+	&value == root
+}
+
+/// Domain of the signatures of `domain_type` under the fork `fork_version` of the chain of
+/// `genesis_validators_root`.
+pub fn compute_domain<H: HostFunctions>(
+	domain_type: [u8; 4],
+	fork_version: [u8; 4],
+	genesis_validators_root: &Root,
+) -> Root {
+	let mut version = [0; 32];
+	version[..4].copy_from_slice(&fork_version);
+	let fork_data_root = hash_pair::<H>(&version, genesis_validators_root);
+	let mut domain = [0; 32];
+	domain[..4].copy_from_slice(&domain_type);
+	domain[4..].copy_from_slice(&fork_data_root[..28]);
+	domain
+}
+
+pub fn compute_signing_root<H: HostFunctions>(object_root: &Root, domain: &Root) -> Root {
+	hash_pair::<H>(object_root, domain)
+}
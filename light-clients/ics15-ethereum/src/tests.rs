@@ -0,0 +1,335 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	client_state::ClientState,
+	consensus_state::ConsensusState,
+	mock::{HostFunctionsManager, INVALID_SIGNATURE, VERIFIED_SIGNATURES},
+	ssz, trie,
+	types::{
+		BeaconBlockHeader, BlsPublicKey, BlsSignature, Fork, LightClientUpdate, Root,
+		SyncAggregate, SyncCommittee, DOMAIN_SYNC_COMMITTEE,
+	},
+	verify::{verify_commitment, verify_storage, verify_update, StorageProof},
+	HostFunctions,
+};
+use codec::Encode;
+use core::marker::PhantomData;
+use ibc::core::ics02_client::client_consensus::ConsensusState as _;
+
+type H = HostFunctionsManager;
+
+fn root(hex: &str) -> Root {
+	hex::decode(hex).unwrap().try_into().unwrap()
+}
+
+fn nodes(hexes: &[&str]) -> Vec<Vec<u8>> {
+	hexes.iter().map(|node| hex::decode(node).unwrap()).collect()
+}
+
+#[test]
+fn empty_trie_root_is_the_hash_of_the_empty_string() {
+	assert_eq!(H::keccak_256(&[0x80]), trie::EMPTY_TRIE_ROOT);
+}
+
+/// The `puppy` trie of the `TrieTests/trietest.json` vectors of ethereum/tests, whose nodes are
+/// mostly inlined in their parents.
+#[test]
+fn trie_proofs_of_the_puppy_trie() {
+	let root = root("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84");
+	let verify = |key: &[u8], proof: &[&str]| trie::verify_proof::<H>(&root, key, &nodes(proof));
+	assert_eq!(verify(b"dog", PUPPY_PROOF_DOG).unwrap(), Some(b"puppy".to_vec()));
+	assert_eq!(verify(b"doge", PUPPY_PROOF_DOGE).unwrap(), Some(b"coin".to_vec()));
+	assert_eq!(verify(b"horse", PUPPY_PROOF_HORSE).unwrap(), Some(b"stallion".to_vec()));
+	assert_eq!(verify(b"dogs", PUPPY_PROOF_DOGS).unwrap(), None);
+	// the nodes must be the ones of the path of the key, from the root
+	assert!(verify(b"dog", PUPPY_PROOF_HORSE).is_err());
+	assert!(verify(b"dog", &PUPPY_PROOF_DOG[1..]).is_err());
+}
+
+/// Root of the genesis block of the mainnet beacon chain, whose body is empty.
+#[test]
+fn mainnet_genesis_block_root() {
+	let header = BeaconBlockHeader {
+		slot: 0,
+		proposer_index: 0,
+		parent_root: [0; 32],
+		state_root: root("7e76880eb67bbdc86250aa578958e9d0675e64e714337855204fb5abaaf82c2b"),
+		body_root: root("ccb62460692be0ec813b56be97f68a82cf57abc102e27bf49ebf4190ff22eedd"),
+	};
+	assert_eq!(
+		header.hash_tree_root::<H>(),
+		root("4d611d5b93fdab69013a7f0a2f961caca0c853f87cfe9595fe50038163079360")
+	);
+}
+
+/// Domain of the deposits, which is computed with the genesis fork version and an empty genesis
+/// validators root on every network.
+#[test]
+fn deposit_domain() {
+	let domain = ssz::compute_domain::<H>([3, 0, 0, 0], [0; 4], &[0; 32]);
+	assert_eq!(
+		hex::encode(domain),
+		"03000000f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a9"
+	);
+}
+
+fn sync_committee(fill: u8, aggregate: u8) -> SyncCommittee {
+	let public_keys = (0..512u16)
+		.map(|i| {
+			let mut key = [fill; 48];
+			key[..2].copy_from_slice(&i.to_be_bytes());
+			BlsPublicKey(key)
+		})
+		.collect();
+	SyncCommittee { public_keys, aggregate_public_key: BlsPublicKey([aggregate; 48]) }
+}
+
+/// Client of the mainnet beacon chain, finalized at the first slot of epoch 200000, after the
+/// Capella fork.
+fn client_state() -> ClientState<H> {
+	ClientState {
+		chain_id: "ethereum".to_string().into(),
+		genesis_validators_root: root(
+			"4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95",
+		),
+		genesis_time: 1_606_824_023,
+		fork_versions: vec![
+			Fork { epoch: 0, version: [0, 0, 0, 0] },
+			Fork { epoch: 74_240, version: [1, 0, 0, 0] },
+			Fork { epoch: 144_896, version: [2, 0, 0, 0] },
+			Fork { epoch: 194_048, version: [3, 0, 0, 0] },
+		],
+		finalized_header: BeaconBlockHeader {
+			slot: 6_400_000,
+			proposer_index: 1,
+			..Default::default()
+		},
+		current_sync_committee: sync_committee(0xa0, 0xaa),
+		next_sync_committee: None,
+		ibc_contract: hex::decode("5fbdb2315678afecb367f032d93f642f64180aa3")
+			.unwrap()
+			.try_into()
+			.unwrap(),
+		commitments_slot: 3,
+		frozen_height: None,
+		_phantom: PhantomData,
+	}
+}
+
+/// Update finalizing a header two epochs later, signed by all the sync committee but its last
+/// member. It proves the sync committee of the next period, and the execution state of
+/// [`STATE_ROOT`] in which the IBC contract commits [`COMMITMENT`].
+fn update() -> LightClientUpdate {
+	let mut sync_committee_bits = vec![0xff; 64];
+	sync_committee_bits[63] = 0x7f;
+	LightClientUpdate {
+		attested_header: BeaconBlockHeader {
+			slot: 6_400_128,
+			proposer_index: 3,
+			parent_root: H::sha256(b"attested-parent"),
+			state_root: root(ATTESTED_STATE_ROOT),
+			body_root: H::sha256(b"attested-body"),
+		},
+		next_sync_committee: Some((
+			sync_committee(0xb0, 0xbb),
+			NEXT_SYNC_COMMITTEE_BRANCH.iter().map(|node| root(node)).collect(),
+		)),
+		finalized_header: BeaconBlockHeader {
+			slot: 6_400_064,
+			proposer_index: 2,
+			parent_root: H::sha256(b"finalized-parent"),
+			state_root: H::sha256(b"finalized-state"),
+			body_root: root(BODY_ROOT),
+		},
+		finality_branch: FINALITY_BRANCH.iter().map(|node| root(node)).collect(),
+		execution_state_root: root(STATE_ROOT),
+		execution_branch: EXECUTION_BRANCH.iter().map(|node| root(node)).collect(),
+		sync_aggregate: SyncAggregate {
+			sync_committee_bits,
+			sync_committee_signature: BlsSignature([0x5a; 96]),
+		},
+		signature_slot: 6_400_129,
+	}
+}
+
+fn storage_proof(storage_proof: &[&str]) -> Vec<u8> {
+	StorageProof { account_proof: nodes(ACCOUNT_PROOF), storage_proof: nodes(storage_proof) }
+		.encode()
+}
+
+#[test]
+fn light_client_update_is_verified() {
+	let client_state = client_state();
+	let update = update();
+	VERIFIED_SIGNATURES.with(|signatures| signatures.borrow_mut().clear());
+	verify_update(&client_state, &update).unwrap();
+
+	// the attested header is signed in the sync committee domain of Capella
+	let domain = ssz::compute_domain::<H>(
+		DOMAIN_SYNC_COMMITTEE,
+		[3, 0, 0, 0],
+		&client_state.genesis_validators_root,
+	);
+	assert_eq!(
+		ssz::compute_signing_root::<H>(&update.attested_header.hash_tree_root::<H>(), &domain),
+		root(SIGNING_ROOT)
+	);
+	let signatures = VERIFIED_SIGNATURES.with(|signatures| signatures.take());
+	assert_eq!(signatures, [(511, root(SIGNING_ROOT))]);
+
+	let client_state = client_state.with_update(&update);
+	assert_eq!(client_state.finalized_header, update.finalized_header);
+	assert_eq!(client_state.current_sync_committee, sync_committee(0xa0, 0xaa));
+	assert_eq!(client_state.next_sync_committee, Some(sync_committee(0xb0, 0xbb)));
+	let consensus_state = ConsensusState::from_update(&update, client_state.genesis_time);
+	assert_eq!(consensus_state.root().as_bytes(), root(STATE_ROOT));
+	assert_eq!(
+		consensus_state.timestamp().nanoseconds(),
+		(1_606_824_023 + 6_400_064 * 12) * 1_000_000_000
+	);
+}
+
+#[test]
+fn invalid_light_client_updates_are_rejected() {
+	let client_state = client_state();
+
+	let mut update = update();
+	update.finality_branch[0][0] ^= 1;
+	assert!(verify_update(&client_state, &update).is_err());
+
+	let mut update = self::update();
+	update.execution_state_root[0] ^= 1;
+	assert!(verify_update(&client_state, &update).is_err());
+
+	let mut update = self::update();
+	update.next_sync_committee.as_mut().unwrap().0 = sync_committee(0xc0, 0xcc);
+	assert!(verify_update(&client_state, &update).is_err());
+
+	// less than two thirds of the sync committee signed
+	let mut update = self::update();
+	update.sync_aggregate.sync_committee_bits[..22].fill(0);
+	assert!(verify_update(&client_state, &update).is_err());
+
+	let mut update = self::update();
+	update.sync_aggregate.sync_committee_signature = INVALID_SIGNATURE;
+	assert!(verify_update(&client_state, &update).is_err());
+
+	// the update doesn't move the client forward
+	let client_state = client_state.with_update(&self::update());
+	assert!(verify_update(&client_state, &self::update()).is_err());
+}
+
+#[test]
+fn commitments_are_verified_against_the_execution_state() {
+	let client_state = client_state();
+	let state_root = root(STATE_ROOT);
+	let proof = storage_proof(STORAGE_PROOF);
+	verify_commitment(&client_state, &state_root, &proof, COMMITMENT_PATH, Some(COMMITMENT))
+		.unwrap();
+	assert!(verify_commitment(&client_state, &state_root, &proof, COMMITMENT_PATH, None).is_err());
+	assert!(verify_commitment(
+		&client_state,
+		&state_root,
+		&proof,
+		COMMITMENT_PATH,
+		Some(b"another-commitment")
+	)
+	.is_err());
+
+	// the storage proof of a path shows that nothing is committed under it
+	let absent_path = b"commitments/ports/transfer/channels/channel-0/sequences/9";
+	let proof = storage_proof(ABSENT_STORAGE_PROOF);
+	verify_commitment(&client_state, &state_root, &proof, absent_path, None).unwrap();
+	assert!(verify_commitment(&client_state, &state_root, &proof, absent_path, Some(COMMITMENT))
+		.is_err());
+
+	// the contract account is proven in the state trie
+	let mut contract = client_state.ibc_contract;
+	contract[0] ^= 1;
+	let proof = StorageProof { account_proof: nodes(ACCOUNT_PROOF), storage_proof: vec![] };
+	assert!(verify_storage::<H>(&state_root, &contract, &[0; 32], &proof).is_err());
+}
+
+const COMMITMENT_PATH: &[u8] = b"commitments/ports/transfer/channels/channel-0/sequences/1";
+const COMMITMENT: &[u8] = b"packet-commitment";
+
+// The execution state of the fixtures holds the IBC contract and 7 other accounts, the storage of
+// the contract 5 commitments and a value at slot 0. The other nodes of the beacon state and of
+// the block body are the SHA-256 hashes of `state-<index>` and `body-<index>`, by generalized
+// index.
+const ACCOUNT_PROOF: &[&str] = &[
+	"f8f1a04be1b3cbde5f3aaaa84b4318e0fa09e8c61ace2c4fd0c291008d2b84aed9f257808080a002969c00b240c260ea4a738f9429b9a858d01dd8a92b7ff46cdfa2ea2289e4e180a0e34262ca6dbfcb95b147ac5fc0dfd8937351a1e15e55243eded8ed2f8ddb0a318080a076197db4afcefc2a26d249c9267c115ceae26a07e75c4f683f613276fe061a15a0e783c22f2134c5b167f17bdaecba3959319bf4d426910cf0b33bc568cedc9206a0e5fa37095734aff0942e73320bc771120b245390e2e335a79f720297ca94927e8080a0343edca01477ae7f5713f50fcf81f907e83c7e501827f5151b541b86c22b8a728080",
+	"f869a034e659e60b21cc961f64ad47f20523c1d329d4bbda245ef3940a76dc89d0911bb846f8440180a0527ca69b326fbf5497b7cde6f6e6cbb5668b206fc66ae03717fc1f07f11a15e1a02a4effd2d8cc4c8cd7a90c9abccd704b7716098166a4ee2aff5bd4a4948d4aaf",
+];
+const STORAGE_PROOF: &[&str] = &[
+	"f8b180a097e2c41124c75103cf778fabfff1f5b69a381d4312ed91c9250cf1740b599f99a04fc5f13ab2f9ba0c2da88b0151ab0e7cf4d85d08cca45ccd923c6ab76323eb2880808080808080a04df6f017062278f2bed3646b59963b48954c66df2e3826502bd22631cfb42a6aa0313fbc3e823d638d3db9d2c84133ca0da325b5ab90f610019152867a378bee6da06002aba14d3d03ff606876b5ccabab8010f30f767849acb0d3d237a84949b14a80808080",
+	"f843a0309c9b6b5d20358fd9cf67dc2a68c0eee98f7a1b3c4e5a80d7a91cdcb1bfdeb8a1a02c2480b50911fba3f0efd45385ab09360e74978de645f781afb14e6bd2ddaf48",
+];
+const ABSENT_STORAGE_PROOF: &[&str] = &[
+	"f8b180a097e2c41124c75103cf778fabfff1f5b69a381d4312ed91c9250cf1740b599f99a04fc5f13ab2f9ba0c2da88b0151ab0e7cf4d85d08cca45ccd923c6ab76323eb2880808080808080a04df6f017062278f2bed3646b59963b48954c66df2e3826502bd22631cfb42a6aa0313fbc3e823d638d3db9d2c84133ca0da325b5ab90f610019152867a378bee6da06002aba14d3d03ff606876b5ccabab8010f30f767849acb0d3d237a84949b14a80808080",
+];
+const EXECUTION_BRANCH: &[&str] = &[
+	"58884646a293ea16648c9bb7111303d0dab4f2fb0e0fe8a5b594d1b29a39a607",
+	"0e20dd66f38ff3e053dc8f567cb8ae46793f6e7532b1263350270c2b72a6a4ef",
+	"c69891da1dd5ca8d9357a63af9c944c33c0aab8a8b5dc7141de5ed86c13e5c02",
+	"09b223eaffb0ef174c4fe426c98369cd4bfd38885e061d55a7a725819cce7d41",
+	"73bbb9d0f92804be98c21343c79271e5f7e80475e4af3106ab2b450729a9b4cb",
+	"241d927db7b73bc5cf1abcf25f6e8a61eddd5c7e61eb5c8583ce077aa19568f1",
+	"b3dadc90b95676f503734ef7a279e782a62eba10581a0b82921a7e59029b3dfd",
+	"d7505a6efea207ab24d5dbeb28de11ba1b8a5850999cf8f6a564a4fe047dc36f",
+];
+const FINALITY_BRANCH: &[&str] = &[
+	"adaa0ee1d47b3ac2b3d4ab7f6524f8db7e98e8468579a2cb61cff8d3c396c2df",
+	"c163a6e026bfcbb98a9a900a7fb0c29450ec36bea81370ed1b6fdce9a771a1c4",
+	"85e44b865715de23fc258fb2404d5dc1fa7438c854e849bc72abb68309dfe583",
+	"1c4eb7cd46bd153a1e1dac992db121b8480288f25be7e2e802747a006c120298",
+	"3abee33467402284180a367abe274bc9990323e9ce404f80f578a790db59a3eb",
+	"339306bbf09a799693ecb26fba3368313e68758ea6d7564c548104e4c11d026e",
+];
+const NEXT_SYNC_COMMITTEE_BRANCH: &[&str] = &[
+	"ce040900d46c2c9187c985ac7998b30cc8554809bd954006d03d41b23e55e93e",
+	"fa420f80d161fce3770becbd49669659f9069389e22425eada152bbfc20fc0db",
+	"1c4eb7cd46bd153a1e1dac992db121b8480288f25be7e2e802747a006c120298",
+	"3abee33467402284180a367abe274bc9990323e9ce404f80f578a790db59a3eb",
+	"339306bbf09a799693ecb26fba3368313e68758ea6d7564c548104e4c11d026e",
+];
+const STATE_ROOT: &str = "df67b1f523a50f79c4daf6d9ad5d5defc7248e91e0fd9c94919ca5609cf7c7fd";
+const BODY_ROOT: &str = "392fd5736cb38300400396e5c390111286d1be45b313981f70bd67646e39ddc9";
+const ATTESTED_STATE_ROOT: &str =
+	"705e8b066c0e42a6da7ba6cb9c9e7deb3ad51a9c0ab46de27dddf0b87c667b5e";
+const SIGNING_ROOT: &str = "8efda685fde31dcf9d74a8b8f12636593618a1b911edeb16a1e360067d68d24b";
+const PUPPY_PROOF_DOG: &[&str] = &[
+	"e216a0bd3ee507e6c67cfefca98f84be47c1bbc009315fabc4405db4ba32190374572a",
+	"f84080808080a094a9f95bd89698e4da1812e0518053813b4d5b87caaf6b3c6fa57e9e50c0ff68808080cf85206f727365887374616c6c696f6e8080808080808080",
+	"e482006fa0d43b87fdcd4217013ccc92d04662e12d36e4cc25dc690077cd821a1956fc3e36",
+	"f3808080808080de17dc808080808080c63584636f696e8080808080808080808570757070798080808080808080808476657262",
+];
+const PUPPY_PROOF_DOGE: &[&str] = &[
+	"e216a0bd3ee507e6c67cfefca98f84be47c1bbc009315fabc4405db4ba32190374572a",
+	"f84080808080a094a9f95bd89698e4da1812e0518053813b4d5b87caaf6b3c6fa57e9e50c0ff68808080cf85206f727365887374616c6c696f6e8080808080808080",
+	"e482006fa0d43b87fdcd4217013ccc92d04662e12d36e4cc25dc690077cd821a1956fc3e36",
+	"f3808080808080de17dc808080808080c63584636f696e8080808080808080808570757070798080808080808080808476657262",
+];
+const PUPPY_PROOF_HORSE: &[&str] = &[
+	"e216a0bd3ee507e6c67cfefca98f84be47c1bbc009315fabc4405db4ba32190374572a",
+	"f84080808080a094a9f95bd89698e4da1812e0518053813b4d5b87caaf6b3c6fa57e9e50c0ff68808080cf85206f727365887374616c6c696f6e8080808080808080",
+];
+const PUPPY_PROOF_DOGS: &[&str] = &[
+	"e216a0bd3ee507e6c67cfefca98f84be47c1bbc009315fabc4405db4ba32190374572a",
+	"f84080808080a094a9f95bd89698e4da1812e0518053813b4d5b87caaf6b3c6fa57e9e50c0ff68808080cf85206f727365887374616c6c696f6e8080808080808080",
+	"e482006fa0d43b87fdcd4217013ccc92d04662e12d36e4cc25dc690077cd821a1956fc3e36",
+	"f3808080808080de17dc808080808080c63584636f696e8080808080808080808570757070798080808080808080808476657262",
+];
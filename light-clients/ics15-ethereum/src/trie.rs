@@ -0,0 +1,120 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of the Merkle-Patricia proofs of the execution layer, as returned by
+//! `eth_getProof`.
+
+use crate::{error::Error, rlp, types::Root, HostFunctions};
+use alloc::{format, vec::Vec};
+
+/// Root of the empty trie, the hash of the RLP encoding of the empty string.
+pub const EMPTY_TRIE_ROOT: Root = [
+	0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+	0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+];
+
+/// Reference of a node to its child: nodes shorter than a hash are inlined in their parent.
+enum NodeRef<'a> {
+	Hash(Root),
+	Inline(&'a [u8]),
+}
+
+fn node_ref(item: &[u8]) -> Result<Option<NodeRef>, Error> {
+	if rlp::is_list(item) {
+		return Ok(Some(NodeRef::Inline(item)))
+	}
+	match rlp::decode_bytes(item)? {
+		[] => Ok(None),
+		hash => {
+			let hash = hash
+				.try_into()
+				.map_err(|_| Error::TrieProof(format!("invalid hash of {} bytes", hash.len())))?;
+			Ok(Some(NodeRef::Hash(hash)))
+		},
+	}
+}
+
+/// Decodes the hex-prefix encoded path of a leaf or extension node into its nibbles, and whether
+/// the node is a leaf.
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool), Error> {
+	let first = *encoded.first().ok_or_else(|| Error::TrieProof("empty node path".into()))?;
+	let is_leaf = first & 0x20 != 0;
+	let mut nibbles = Vec::with_capacity(encoded.len() * 2);
+	if first & 0x10 != 0 {
+		nibbles.push(first & 0x0f);
+	}
+	for byte in &encoded[1..] {
+		nibbles.extend([byte >> 4, byte & 0x0f]);
+	}
+	Ok((nibbles, is_leaf))
+}
+
+/// Verifies the `proof` of the value of `key` in the trie of `root`, the nodes on the path from
+/// the root to the key. Returns the value, or `None` if the proof shows that the trie doesn't
+/// contain the key.
+pub fn verify_proof<H: HostFunctions>(
+	root: &Root,
+	key: &[u8],
+	proof: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, Error> {
+	if root == &EMPTY_TRIE_ROOT {
+		return Ok(None)
+	}
+	let path = key.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect::<Vec<_>>();
+	let mut path = path.as_slice();
+	let mut proof = proof.iter();
+	let mut next = NodeRef::Hash(*root);
+	loop {
+		let node = match next {
+			NodeRef::Hash(hash) => {
+				let node = proof
+					.next()
+					.ok_or_else(|| Error::TrieProof("missing nodes in the proof".into()))?;
+				if H::keccak_256(node) != hash {
+					return Err(Error::TrieProof("node doesn't match its hash".into()))
+				}
+				node.as_slice()
+			},
+			NodeRef::Inline(node) => node,
+		};
+		let items = rlp::decode_list(node)?;
+		match items.len() {
+			17 => {
+				let Some((nibble, rest)) = path.split_first() else {
+					let value = rlp::decode_bytes(items[16])?;
+					return Ok((!value.is_empty()).then(|| value.to_vec()))
+				};
+				path = rest;
+				match node_ref(items[*nibble as usize])? {
+					Some(child) => next = child,
+					None => return Ok(None),
+				}
+			},
+			2 => {
+				let (node_path, is_leaf) = decode_path(rlp::decode_bytes(items[0])?)?;
+				if is_leaf {
+					let value = (path == node_path.as_slice())
+						.then(|| rlp::decode_bytes(items[1]).map(|value| value.to_vec()));
+					return value.transpose()
+				}
+				let Some(rest) = path.strip_prefix(node_path.as_slice()) else { return Ok(None) };
+				path = rest;
+				next = node_ref(items[1])?
+					.ok_or_else(|| Error::TrieProof("extension node without child".into()))?;
+			},
+			len => return Err(Error::TrieProof(format!("invalid node of {len} items"))),
+		}
+	}
+}
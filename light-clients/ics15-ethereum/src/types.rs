@@ -0,0 +1,155 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Beacon chain containers of the light client protocol, with the constants of the mainnet
+//! preset.
+
+use crate::{ssz, HostFunctions};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+
+/// SSZ hash tree root of a container.
+pub type Root = [u8; 32];
+
+pub const SLOTS_PER_EPOCH: u64 = 32;
+pub const SECONDS_PER_SLOT: u64 = 12;
+pub const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: u64 = 256;
+pub const SYNC_COMMITTEE_SIZE: usize = 512;
+pub const DOMAIN_SYNC_COMMITTEE: [u8; 4] = [7, 0, 0, 0];
+
+/// Generalized index of the finalized checkpoint root in the `BeaconState`.
+pub const FINALIZED_ROOT_INDEX: u64 = 105;
+/// Generalized index of the next sync committee in the `BeaconState`.
+pub const NEXT_SYNC_COMMITTEE_INDEX: u64 = 55;
+/// Generalized index of the `state_root` of the execution payload in the `BeaconBlockBody`.
+pub const EXECUTION_STATE_ROOT_INDEX: u64 = 402;
+
+pub fn compute_epoch_at_slot(slot: u64) -> u64 {
+	slot / SLOTS_PER_EPOCH
+}
+
+pub fn compute_sync_committee_period_at_slot(slot: u64) -> u64 {
+	compute_epoch_at_slot(slot) / EPOCHS_PER_SYNC_COMMITTEE_PERIOD
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct BlsPublicKey(pub [u8; 48]);
+
+impl BlsPublicKey {
+	pub fn hash_tree_root<H: HostFunctions>(&self) -> Root {
+		let mut chunks = [[0; 32]; 2];
+		chunks[0].copy_from_slice(&self.0[..32]);
+		chunks[1][..16].copy_from_slice(&self.0[32..]);
+		ssz::merkleize::<H>(&chunks)
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct BlsSignature(pub [u8; 96]);
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub struct BeaconBlockHeader {
+	pub slot: u64,
+	pub proposer_index: u64,
+	pub parent_root: Root,
+	pub state_root: Root,
+	pub body_root: Root,
+}
+
+impl BeaconBlockHeader {
+	pub fn hash_tree_root<H: HostFunctions>(&self) -> Root {
+		ssz::merkleize::<H>(&[
+			ssz::uint64_root(self.slot),
+			ssz::uint64_root(self.proposer_index),
+			self.parent_root,
+			self.state_root,
+			self.body_root,
+		])
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct SyncCommittee {
+	pub public_keys: Vec<BlsPublicKey>,
+	pub aggregate_public_key: BlsPublicKey,
+}
+
+impl SyncCommittee {
+	pub fn hash_tree_root<H: HostFunctions>(&self) -> Root {
+		let keys = self.public_keys.iter().map(|key| key.hash_tree_root::<H>()).collect::<Vec<_>>();
+		ssz::merkleize::<H>(&[
+			ssz::merkleize::<H>(&keys),
+			self.aggregate_public_key.hash_tree_root::<H>(),
+		])
+	}
+}
+
+/// Signature of a block header by the sync committee.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct SyncAggregate {
+	/// Bitvector of the members of the committee that signed, least significant bit first.
+	pub sync_committee_bits: Vec<u8>,
+	pub sync_committee_signature: BlsSignature,
+}
+
+impl SyncAggregate {
+	/// Public keys of the members of `committee` that signed.
+	pub fn participants(&self, committee: &SyncCommittee) -> Vec<BlsPublicKey> {
+		committee
+			.public_keys
+			.iter()
+			.enumerate()
+			.filter(|(i, _)| {
+				self.sync_committee_bits
+					.get(i / 8)
+					.map_or(false, |byte| byte >> (i % 8) & 1 == 1)
+			})
+			.map(|(_, key)| *key)
+			.collect()
+	}
+
+	/// Number of members of the committee that signed.
+	pub fn participation(&self) -> usize {
+		self.sync_committee_bits.iter().map(|byte| byte.count_ones() as usize).sum()
+	}
+}
+
+/// Version of the fork scheduled at `epoch`, which is part of the domain of the signatures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct Fork {
+	pub epoch: u64,
+	pub version: [u8; 4],
+}
+
+/// Update of the light client to a new finalized header, the `LightClientUpdate` of the
+/// specification extended with the proof of the execution state root of the finalized block.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct LightClientUpdate {
+	/// Header signed by the sync committee.
+	pub attested_header: BeaconBlockHeader,
+	/// Sync committee of the next period, with its branch in the state of `attested_header`.
+	pub next_sync_committee: Option<(SyncCommittee, Vec<Root>)>,
+	/// Header finalized in the state of `attested_header`.
+	pub finalized_header: BeaconBlockHeader,
+	/// Branch of `finalized_header` in the state of `attested_header`.
+	pub finality_branch: Vec<Root>,
+	/// State root of the execution payload of the block of `finalized_header`.
+	pub execution_state_root: Root,
+	/// Branch of `execution_state_root` in the body of `finalized_header`.
+	pub execution_branch: Vec<Root>,
+	pub sync_aggregate: SyncAggregate,
+	/// Slot of the block the sync aggregate is included in.
+	pub signature_slot: u64,
+}
@@ -0,0 +1,210 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of the light client updates, and of the IBC state against the execution state
+//! root of a finalized block.
+
+use crate::{
+	client_state::ClientState,
+	error::Error,
+	rlp, ssz, trie,
+	types::{
+		compute_epoch_at_slot, compute_sync_committee_period_at_slot, LightClientUpdate, Root,
+		DOMAIN_SYNC_COMMITTEE, EXECUTION_STATE_ROOT_INDEX, FINALIZED_ROOT_INDEX,
+		NEXT_SYNC_COMMITTEE_INDEX, SYNC_COMMITTEE_SIZE,
+	},
+	HostFunctions,
+};
+use alloc::{format, string::String, vec::Vec};
+use codec::{Decode, Encode};
+
+/// Verifies `update` against the sync committees known to `client_state`, following
+/// `validate_light_client_update` of the specification. Only updates signed by a supermajority
+/// of the sync committee are accepted, as no later update can replace a finalized header.
+pub fn verify_update<H: HostFunctions>(
+	client_state: &ClientState<H>,
+	update: &LightClientUpdate,
+) -> Result<(), Error> {
+	let participation = update.sync_aggregate.participation();
+	if participation * 3 < SYNC_COMMITTEE_SIZE * 2 {
+		return Err(Error::Custom(format!(
+			"Insufficient sync committee participation: {participation}/{SYNC_COMMITTEE_SIZE}"
+		)))
+	}
+
+	let finalized_slot = client_state.finalized_header.slot;
+	if !(update.signature_slot > update.attested_header.slot &&
+		update.attested_header.slot >= update.finalized_header.slot &&
+		update.finalized_header.slot > finalized_slot)
+	{
+		return Err(Error::Custom(format!(
+			"Invalid update slots: signature {}, attested {}, finalized {}, known {finalized_slot}",
+			update.signature_slot, update.attested_header.slot, update.finalized_header.slot
+		)))
+	}
+
+	let store_period = compute_sync_committee_period_at_slot(finalized_slot);
+	let signature_period = compute_sync_committee_period_at_slot(update.signature_slot);
+	let sync_committee = if signature_period == store_period {
+		&client_state.current_sync_committee
+	} else if signature_period == store_period + 1 {
+		client_state.next_sync_committee.as_ref().ok_or_else(|| {
+			Error::Custom(format!("Sync committee of period {signature_period} is unknown"))
+		})?
+	} else {
+		return Err(Error::Custom(format!(
+			"Update signed in period {signature_period}, the client is in period {store_period}"
+		)))
+	};
+
+	let finalized_period = compute_sync_committee_period_at_slot(update.finalized_header.slot);
+	if client_state.next_sync_committee.is_none() && finalized_period != store_period {
+		return Err(Error::Custom(format!(
+			"Update finalized in period {finalized_period} before the next sync committee is known"
+		)))
+	}
+
+	let finalized_root = update.finalized_header.hash_tree_root::<H>();
+	if !ssz::is_valid_merkle_branch::<H>(
+		finalized_root,
+		&update.finality_branch,
+		FINALIZED_ROOT_INDEX,
+		&update.attested_header.state_root,
+	) {
+		return Err(Error::Custom("Invalid finality branch".into()))
+	}
+
+	if let Some((next_sync_committee, branch)) = &update.next_sync_committee {
+		let attested_period = compute_sync_committee_period_at_slot(update.attested_header.slot);
+		let conflicts = client_state
+			.next_sync_committee
+			.as_ref()
+			.map_or(false, |known| known != next_sync_committee);
+		if attested_period == store_period && conflicts {
+			return Err(Error::Custom("Next sync committee conflicts with the known one".into()))
+		}
+		if !ssz::is_valid_merkle_branch::<H>(
+			next_sync_committee.hash_tree_root::<H>(),
+			branch,
+			NEXT_SYNC_COMMITTEE_INDEX,
+			&update.attested_header.state_root,
+		) {
+			return Err(Error::Custom("Invalid next sync committee branch".into()))
+		}
+	}
+
+	if !ssz::is_valid_merkle_branch::<H>(
+		update.execution_state_root,
+		&update.execution_branch,
+		EXECUTION_STATE_ROOT_INDEX,
+		&update.finalized_header.body_root,
+	) {
+		return Err(Error::Custom("Invalid execution state root branch".into()))
+	}
+
+	let fork_version_slot = update.signature_slot.max(1) - 1;
+	let fork_version = client_state.fork_version(compute_epoch_at_slot(fork_version_slot))?;
+	let domain = ssz::compute_domain::<H>(
+		DOMAIN_SYNC_COMMITTEE,
+		fork_version,
+		&client_state.genesis_validators_root,
+	);
+	let signing_root =
+		ssz::compute_signing_root::<H>(&update.attested_header.hash_tree_root::<H>(), &domain);
+	let participants = update.sync_aggregate.participants(sync_committee);
+	if !H::bls_fast_aggregate_verify(
+		&participants,
+		&signing_root,
+		&update.sync_aggregate.sync_committee_signature,
+	) {
+		return Err(Error::Custom("Invalid sync committee signature".into()))
+	}
+
+	Ok(())
+}
+
+/// Proof of a storage slot of a contract, the `accountProof` and `storageProof` returned by
+/// `eth_getProof`.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct StorageProof {
+	pub account_proof: Vec<Vec<u8>>,
+	pub storage_proof: Vec<Vec<u8>>,
+}
+
+/// Verifies `proof` of the value of the storage `slot` of `contract` in the execution state of
+/// `state_root`. Returns the value, or `None` if the slot is empty.
+pub fn verify_storage<H: HostFunctions>(
+	state_root: &Root,
+	contract: &[u8; 20],
+	slot: &Root,
+	proof: &StorageProof,
+) -> Result<Option<Vec<u8>>, Error> {
+	let account =
+		trie::verify_proof::<H>(state_root, &H::keccak_256(contract), &proof.account_proof)?
+			.ok_or_else(|| Error::TrieProof("IBC contract account doesn't exist".into()))?;
+	// accounts are encoded as `[nonce, balance, storage_root, code_hash]`
+	let fields = rlp::decode_list(&account)?;
+	let storage_root: Root = fields
+		.get(2)
+		.map(|field| rlp::decode_bytes(field))
+		.transpose()?
+		.and_then(|root| root.try_into().ok())
+		.ok_or_else(|| Error::Rlp("invalid account".into()))?;
+	trie::verify_proof::<H>(&storage_root, &H::keccak_256(slot), &proof.storage_proof)?
+		.map(|value| rlp::decode_bytes(&value).map(|value| value.to_vec()))
+		.transpose()
+}
+
+/// Storage slot of the commitment of `key` in the `mapping(bytes32 => bytes32)` of the IBC
+/// contract at slot `commitments_slot`. The contract commits the Keccak-256 hash of the values,
+/// under the Keccak-256 hash of their paths.
+pub fn commitment_slot<H: HostFunctions>(key: &[u8], commitments_slot: u64) -> Root {
+	let mut data = [0; 64];
+	data[..32].copy_from_slice(&H::keccak_256(key));
+	data[56..].copy_from_slice(&commitments_slot.to_be_bytes());
+	H::keccak_256(&data)
+}
+
+/// Verifies that the IBC contract of `client_state` commits `value` under `key`, or that it
+/// commits nothing under `key` if `value` is `None`.
+pub fn verify_commitment<H: HostFunctions>(
+	client_state: &ClientState<H>,
+	state_root: &[u8],
+	proof: &[u8],
+	key: &[u8],
+	value: Option<&[u8]>,
+) -> Result<(), Error> {
+	let state_root: Root = state_root
+		.try_into()
+		.map_err(|_| Error::Custom(format!("Invalid state root of {} bytes", state_root.len())))?;
+	let proof = StorageProof::decode(&mut &*proof)?;
+	let slot = commitment_slot::<H>(key, client_state.commitments_slot);
+	let stored = verify_storage::<H>(&state_root, &client_state.ibc_contract, &slot, &proof)?;
+	// storage values are encoded without their leading zeros
+	let stored = stored.map(|stored| {
+		let stored = &stored[stored.len().saturating_sub(32)..];
+		let mut word = [0; 32];
+		word[32 - stored.len()..].copy_from_slice(stored);
+		word
+	});
+	let expected = value.map(H::keccak_256);
+	if stored != expected {
+		return Err(Error::Custom(format!(
+			"Commitment of {} doesn't match the proven storage",
+			String::from_utf8_lossy(key)
+		)))
+	}
+	Ok(())
+}
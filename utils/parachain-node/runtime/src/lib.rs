@@ -23,7 +23,6 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 extern crate alloc;
 
 use alloc::string::String;
-use asset_registry::{AssetMetadata, DefaultAssetMetadata};
 use core::fmt::{Display, Formatter};
 
 mod weights;
@@ -37,8 +36,7 @@ use ibc::core::{
 	ics24_host::identifier::PortId,
 	ics26_routing::context::{Module, ModuleId},
 };
-use ibc_primitives::{runtime_interface::ss58_to_account_id_32, IbcAccount};
-use orml_traits::asset_registry::AssetProcessor;
+use ibc_primitives::{runtime_interface::ss58_to_account_id_32, CallbackWeight, IbcAccount};
 use pallet_ibc::{
 	ics20::SubstrateMultihopXcmHandlerNone, ics20_fee::NonFlatFeeConverter,
 	light_client_common::RelayChain, LightClientProtocol,
@@ -50,16 +48,14 @@ use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, Get, IdentifyAccount, Verify},
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, DispatchError, Either,
-	Either::*,
-	MultiSignature,
+	ApplyExtrinsicResult, Either, MultiSignature,
 };
 
 use frame_support::{
 	construct_runtime,
 	dispatch::DispatchClass,
 	parameter_types,
-	traits::{fungibles::metadata::Inspect as InspectMetadata, AsEnsureOriginWithArg, Everything},
+	traits::{AsEnsureOriginWithArg, Everything},
 	weights::{
 		constants::WEIGHT_REF_TIME_PER_SECOND, ConstantMultiplier, Weight, WeightToFeeCoefficient,
 		WeightToFeeCoefficients, WeightToFeePolynomial,
@@ -70,7 +66,6 @@ use frame_system::{
 	limits::{BlockLength, BlockWeights},
 	EnsureRoot, EnsureSigned,
 };
-use pallet_ibc::{DenomToAssetId, IbcAssetIds, IbcAssets, IbcDenoms};
 pub use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 pub use sp_runtime::{MultiAddress, Perbill, Permill};
 use sp_std::prelude::*;
@@ -583,120 +578,31 @@ impl ModuleRouter for Router {
 			_ => None,
 		}
 	}
-}
-
-pub struct IbcDenomToAssetIdConversion;
-
-// generate new asset id
-fn generate_asset_id() -> Result<AssetId, DispatchError> {
-	let (asset_id, ..) = <asset_registry::SequentialId<Runtime> as AssetProcessor<
-		AssetId,
-		DefaultAssetMetadata<Runtime>,
-	>>::pre_register(
-		None,
-		// Metadata is not useful to this call so we can use default values
-		AssetMetadata {
-			decimals: Default::default(),
-			name: Default::default(),
-			symbol: Default::default(),
-			existential_deposit: Default::default(),
-			location: None,
-			additional: (),
-		},
-	)
-	.map_err(|_| DispatchError::Other("Failed to generate asset id"))?;
-	let asset_id = if asset_id == 1 {
-		let (asset_id, ..) = <asset_registry::SequentialId<Runtime> as AssetProcessor<
-			AssetId,
-			DefaultAssetMetadata<Runtime>,
-		>>::pre_register(
-			None,
-			// Metadata is not useful to this call so we can use default values
-			AssetMetadata {
-				decimals: Default::default(),
-				name: Default::default(),
-				symbol: Default::default(),
-				existential_deposit: Default::default(),
-				location: None,
-				additional: (),
-			},
-		)
-		.map_err(|_| DispatchError::Other("Failed to generate asset id"))?;
-		asset_id
-	} else {
-		asset_id
-	};
-
-	Ok(asset_id)
-}
 
-impl DenomToAssetId<Runtime> for IbcDenomToAssetIdConversion {
-	type Error = DispatchError;
-
-	fn from_denom_to_asset_id(denom: &str) -> Result<AssetId, Self::Error> {
-		use frame_support::traits::fungibles::{metadata::Mutate, Create};
-
-		let denom_bytes = denom.as_bytes().to_vec();
-		if let Some(id) = IbcDenoms::<Runtime>::get(&denom_bytes) {
-			return Ok(id)
+	fn weight_handler(module_id: &ModuleId) -> Option<Box<dyn CallbackWeight>> {
+		match module_id.as_ref() {
+			pallet_ibc_ping::MODULE_ID =>
+				Some(Box::new(pallet_ibc_ping::WeightHandler::<Runtime>::default())),
+			_ => None,
 		}
-
-		let pallet_id: AccountId = PalletId(*b"pall-ibc").into_account_truncating();
-
-		let symbol = denom
-			.split('/')
-			.last()
-			.ok_or(DispatchError::Other("denom missing a name"))?
-			.as_bytes()
-			.to_vec();
-		let asset_id = generate_asset_id()?;
-
-		IbcDenoms::<Runtime>::insert(denom_bytes.clone(), asset_id);
-		IbcAssetIds::<Runtime>::insert(asset_id, denom_bytes.clone());
-
-		<pallet_assets::Pallet<Runtime> as Create<AccountId>>::create(
-			asset_id,
-			pallet_id.clone(),
-			true,
-			1,
-		)?;
-
-		<pallet_assets::Pallet<Runtime> as Mutate<AccountId>>::set(
-			asset_id,
-			&pallet_id,
-			denom_bytes,
-			symbol,
-			12,
-		)?;
-
-		Ok(asset_id)
-	}
-
-	fn from_asset_id_to_denom(id: AssetId) -> Option<String> {
-		let name = <pallet_assets::Pallet<Runtime> as InspectMetadata<AccountId>>::name(id);
-		String::from_utf8(name).ok()
 	}
+}
 
-	fn ibc_assets(start_key: Option<Either<AssetId, u32>>, limit: u64) -> IbcAssets<AssetId> {
-		let mut iterator = match start_key {
-			None => IbcAssetIds::<Runtime>::iter().skip(0),
-			Some(Left(asset_id)) => {
-				let raw_key = asset_id.encode();
-				IbcAssetIds::<Runtime>::iter_from(raw_key).skip(0)
-			},
-			Some(Right(offset)) => IbcAssetIds::<Runtime>::iter().skip(offset as usize),
-		};
-
-		let denoms = iterator.by_ref().take(limit as usize).map(|(_, denom)| denom).collect();
-		let maybe_currency_id = iterator.next().map(|(id, ..)| id);
-		IbcAssets {
-			denoms,
-			total_count: IbcAssetIds::<Runtime>::count() as u64,
-			next_id: maybe_currency_id,
-		}
-	}
+parameter_types! {
+	pub IbcPalletAccount: AccountId = PalletId(*b"pall-ibc").into_account_truncating();
 }
 
+/// The vouchers of the ibc denoms are created in the assets pallet, owned by the ibc pallet
+pub type IbcDenomToAssetIdConversion = pallet_ibc::asset_registry::IbcDenomRegistry<
+	Runtime,
+	pallet_ibc::asset_registry::FungiblesRegistry<
+		pallet_assets::Pallet<Runtime>,
+		AccountId,
+		IbcPalletAccount,
+		frame_support::traits::ConstU128<1>,
+	>,
+>;
+
 #[derive(
 	Debug, codec::Encode, Clone, codec::Decode, PartialEq, Eq, scale_info::TypeInfo, Default,
 )]
@@ -796,7 +702,7 @@ impl pallet_ibc::Config for Runtime {
 	type MinimumConnectionDelay = MinimumConnectionDelay;
 	type ParaId = parachain_info::Pallet<Runtime>;
 	type RelayChain = RelayChainId;
-	type WeightInfo = ();
+	type WeightInfo = weights::pallet_ibc::WeightInfo<Runtime>;
 	type AdminOrigin = EnsureRoot<AccountId>;
 	type FreezeOrigin = EnsureRoot<AccountId>;
 	type SpamProtectionDeposit = SpamProtectionDeposit;
@@ -874,6 +780,25 @@ mod benches {
 	);
 }
 
+/// Evaluates `$body` against the `pallet_ibc` instance whose `PalletPrefix` is `$prefix`, with
+/// `$ibc` bound to the instance's pallet and `$instance` to its instance type, and returns the
+/// default value when no instance uses that prefix. Add a branch here for every instance of
+/// `pallet_ibc` in the runtime.
+macro_rules! with_ibc_instance {
+	($prefix:expr, |$ibc:ident, $instance:ident| $body:expr) => {{
+		let prefix: Vec<u8> = $prefix;
+		if prefix == <Runtime as pallet_ibc::Config>::PalletPrefix::get() {
+			#[allow(dead_code)]
+			type $ibc = pallet_ibc::Pallet<Runtime>;
+			#[allow(dead_code)]
+			type $instance = ();
+			$body
+		} else {
+			Default::default()
+		}
+	}};
+}
+
 impl_runtime_apis! {
 	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
 		fn slot_duration() -> sp_consensus_aura::SlotDuration {
@@ -1009,126 +934,219 @@ impl_runtime_apis! {
 			Ibc::query_balance_with_address(addr, asset_id).ok()
 		}
 
-		fn query_send_packet_info(channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<ibc_primitives::PacketInfo>> {
-			Ibc::get_send_packet_info(channel_id, port_id, seqs).ok()
+		fn query_send_packet_info(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<ibc_primitives::PacketInfo>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::get_send_packet_info(channel_id, port_id, seqs).ok()
+			})
+		}
+
+		fn query_recv_packet_info(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<ibc_primitives::PacketInfo>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::get_recv_packet_info(channel_id, port_id, seqs).ok()
+			})
+		}
+
+		fn client_update_time_and_height(pallet_prefix: Vec<u8>, client_id: Vec<u8>, revision_number: u64, revision_height: u64) -> Option<(u64, u64)> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::client_update_time_and_height(client_id, revision_number, revision_height).ok()
+			})
+		}
+
+		fn client_state(pallet_prefix: Vec<u8>, client_id: Vec<u8>) -> Option<ibc_primitives::QueryClientStateResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::client(client_id).ok()
+			})
+		}
+
+		fn client_consensus_state(pallet_prefix: Vec<u8>, client_id: Vec<u8>, revision_number: u64, revision_height: u64, latest_cs: bool) -> Option<ibc_primitives::QueryConsensusStateResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::consensus_state(client_id, revision_number, revision_height, latest_cs).ok()
+			})
+		}
+
+		fn clients(pallet_prefix: Vec<u8>) -> Option<Vec<(Vec<u8>, Vec<u8>)>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Some(Ibc::clients())
+			})
 		}
 
-		fn query_recv_packet_info(channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<ibc_primitives::PacketInfo>> {
-			Ibc::get_recv_packet_info(channel_id, port_id, seqs).ok()
+		fn connection(pallet_prefix: Vec<u8>, connection_id: Vec<u8>) -> Option<ibc_primitives::QueryConnectionResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::connection(connection_id).ok()
+			})
 		}
 
-		fn client_update_time_and_height(client_id: Vec<u8>, revision_number: u64, revision_height: u64) -> Option<(u64, u64)>{
-			Ibc::client_update_time_and_height(client_id, revision_number, revision_height).ok()
+		fn connections(pallet_prefix: Vec<u8>) -> Option<ibc_primitives::QueryConnectionsResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::connections().ok()
+			})
 		}
 
-		fn client_state(client_id: Vec<u8>) -> Option<ibc_primitives::QueryClientStateResponse> {
-			Ibc::client(client_id).ok()
+		fn connection_using_client(pallet_prefix: Vec<u8>, client_id: Vec<u8>) -> Option<Vec<ibc_primitives::IdentifiedConnection>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::connection_using_client(client_id).ok()
+			})
 		}
 
-		fn client_consensus_state(client_id: Vec<u8>, revision_number: u64, revision_height: u64, latest_cs: bool) -> Option<ibc_primitives::QueryConsensusStateResponse> {
-			Ibc::consensus_state(client_id, revision_number, revision_height, latest_cs).ok()
+		fn connection_handshake(pallet_prefix: Vec<u8>, client_id: Vec<u8>, connection_id: Vec<u8>) -> Option<ibc_primitives::ConnectionHandshake> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::connection_handshake(client_id, connection_id).ok()
+			})
 		}
 
-		fn clients() -> Option<Vec<(Vec<u8>, Vec<u8>)>> {
-			Some(Ibc::clients())
+		fn commitment_trie_keys(pallet_prefix: Vec<u8>, paths: Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::commitment_trie_keys(paths).ok()
+			})
 		}
 
-		fn connection(connection_id: Vec<u8>) -> Option<ibc_primitives::QueryConnectionResponse>{
-			Ibc::connection(connection_id).ok()
+		fn channel(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryChannelResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::channel(channel_id, port_id).ok()
+			})
 		}
 
-		fn connections() -> Option<ibc_primitives::QueryConnectionsResponse> {
-			Ibc::connections().ok()
+		fn channel_client(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::IdentifiedClientState> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::channel_client(channel_id, port_id).ok()
+			})
 		}
 
-		fn connection_using_client(client_id: Vec<u8>) -> Option<Vec<ibc_primitives::IdentifiedConnection>>{
-			Ibc::connection_using_client(client_id).ok()
+		fn connection_channels(pallet_prefix: Vec<u8>, connection_id: Vec<u8>) -> Option<ibc_primitives::QueryChannelsResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::connection_channels(connection_id).ok()
+			})
 		}
 
-		fn connection_handshake(client_id: Vec<u8>, connection_id: Vec<u8>) -> Option<ibc_primitives::ConnectionHandshake> {
-			Ibc::connection_handshake(client_id, connection_id).ok()
+		fn channels(pallet_prefix: Vec<u8>) -> Option<ibc_primitives::QueryChannelsResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::channels().ok()
+			})
 		}
 
-		fn channel(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryChannelResponse> {
-			Ibc::channel(channel_id, port_id).ok()
+		fn packet_commitments(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryPacketCommitmentsResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::packet_commitments(channel_id, port_id).ok()
+			})
 		}
 
-		fn channel_client(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::IdentifiedClientState> {
-			Ibc::channel_client(channel_id, port_id).ok()
+		fn packet_acknowledgements(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryPacketAcknowledgementsResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::packet_acknowledgements(channel_id, port_id).ok()
+			})
 		}
 
-		fn connection_channels(connection_id: Vec<u8>) -> Option<ibc_primitives::QueryChannelsResponse> {
-			Ibc::connection_channels(connection_id).ok()
+		fn clients_page(pallet_prefix: Vec<u8>, page: ibc_primitives::PageRequest) -> Option<ibc_primitives::Page<ibc_primitives::IdentifiedClientState>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Some(Ibc::clients_page(page))
+			})
 		}
 
-		fn channels() -> Option<ibc_primitives::QueryChannelsResponse> {
-			Ibc::channels().ok()
+		fn connections_page(pallet_prefix: Vec<u8>, page: ibc_primitives::PageRequest) -> Option<ibc_primitives::Page<ibc_primitives::IdentifiedConnection>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Some(Ibc::connections_page(page))
+			})
 		}
 
-		fn packet_commitments(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryPacketCommitmentsResponse> {
-			Ibc::packet_commitments(channel_id, port_id).ok()
+		fn channels_page(pallet_prefix: Vec<u8>, page: ibc_primitives::PageRequest) -> Option<ibc_primitives::Page<ibc_primitives::IdentifiedChannel>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Some(Ibc::channels_page(page))
+			})
 		}
 
-		fn packet_acknowledgements(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryPacketAcknowledgementsResponse>{
-			Ibc::packet_acknowledgements(channel_id, port_id).ok()
+		fn packet_commitments_page(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, page: ibc_primitives::PageRequest) -> Option<ibc_primitives::Page<ibc_primitives::PacketState>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::packet_commitments_page(channel_id, port_id, page).ok()
+			})
 		}
 
-		fn unreceived_packets(channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<u64>> {
-			Ibc::unreceived_packets(channel_id, port_id, seqs).ok()
+		fn packet_acknowledgements_page(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, page: ibc_primitives::PageRequest) -> Option<ibc_primitives::Page<ibc_primitives::PacketState>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::packet_acknowledgements_page(channel_id, port_id, page).ok()
+			})
 		}
 
-		fn unreceived_acknowledgements(channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<u64>> {
-			Ibc::unreceived_acknowledgements(channel_id, port_id, seqs).ok()
+		fn unreceived_packets(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<u64>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::unreceived_packets(channel_id, port_id, seqs).ok()
+			})
 		}
 
-		fn next_seq_recv(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryNextSequenceReceiveResponse> {
-			Ibc::next_seq_recv(channel_id, port_id).ok()
+		fn unreceived_acknowledgements(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<u64>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::unreceived_acknowledgements(channel_id, port_id, seqs).ok()
+			})
 		}
 
-		fn packet_commitment(channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<ibc_primitives::QueryPacketCommitmentResponse> {
-			Ibc::packet_commitment(channel_id, port_id, seq).ok()
+		fn next_seq_recv(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryNextSequenceReceiveResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::next_seq_recv(channel_id, port_id).ok()
+			})
 		}
 
-		fn packet_acknowledgement(channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<ibc_primitives::QueryPacketAcknowledgementResponse> {
-			Ibc::packet_acknowledgement(channel_id, port_id, seq).ok()
+		fn packet_commitment(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<ibc_primitives::QueryPacketCommitmentResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::packet_commitment(channel_id, port_id, seq).ok()
+			})
 		}
 
-		fn packet_receipt(channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<ibc_primitives::QueryPacketReceiptResponse> {
-			Ibc::packet_receipt(channel_id, port_id, seq).ok()
+		fn packet_acknowledgement(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<ibc_primitives::QueryPacketAcknowledgementResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::packet_acknowledgement(channel_id, port_id, seq).ok()
+			})
 		}
 
-		fn denom_trace(asset_id: AssetId) -> Option<ibc_primitives::QueryDenomTraceResponse> {
-			Ibc::get_denom_trace(asset_id)
+		fn packet_receipt(pallet_prefix: Vec<u8>, channel_id: Vec<u8>, port_id: Vec<u8>, seq: u64) -> Option<ibc_primitives::QueryPacketReceiptResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::packet_receipt(channel_id, port_id, seq).ok()
+			})
 		}
 
-		fn denom_traces(key: Option<AssetId>, offset: Option<u32>, limit: u64, count_total: bool) -> ibc_primitives::QueryDenomTracesResponse {
-			let key = key.map(Either::Left).or_else(|| offset.map(Either::Right));
-			Ibc::get_denom_traces(key, limit, count_total)
+		fn denom_trace(pallet_prefix: Vec<u8>, asset_id: AssetId) -> Option<ibc_primitives::QueryDenomTraceResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::get_denom_trace(asset_id)
+			})
 		}
 
-		fn block_events(extrinsic_index: Option<u32>) -> Vec<Result<pallet_ibc::events::IbcEvent, pallet_ibc::errors::IbcError>> {
-			let mut raw_events = frame_system::Pallet::<Self>::read_events_no_consensus();
-			if let Some(idx) = extrinsic_index {
-				raw_events.find_map(|e| {
-					let frame_system::EventRecord{ event, phase, ..} = *e;
-					match (event, phase) {
-						(RuntimeEvent::Ibc(pallet_ibc::Event::Events{ events }), frame_system::Phase::ApplyExtrinsic(index)) if index == idx => Some(events),
-						_ => None
-					}
-				}).unwrap_or_default()
-			}
-			else {
-				raw_events.filter_map(|e| {
-					let frame_system::EventRecord{ event, ..} = *e;
+		fn denom_asset_id(pallet_prefix: Vec<u8>, denom: Vec<u8>) -> Option<AssetId> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::get_denom_asset_id(denom)
+			})
+		}
+
+		fn denom_trace_by_hash(pallet_prefix: Vec<u8>, hash: Vec<u8>) -> Option<ibc_primitives::QueryDenomTraceResponse> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				Ibc::get_denom_trace_by_hash(hash)
+			})
+		}
+
+		fn denom_traces(pallet_prefix: Vec<u8>, key: Option<AssetId>, offset: Option<u32>, limit: u64, count_total: bool) -> ibc_primitives::QueryDenomTracesResponse {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				let key = key.map(Either::Left).or_else(|| offset.map(Either::Right));
+				Ibc::get_denom_traces(key, limit, count_total)
+			})
+		}
 
-					match event {
-						RuntimeEvent::Ibc(pallet_ibc::Event::Events{ events }) => {
-								Some(events)
-							},
-						_ => None
-					}
-				}).flatten().collect()
-			}
+		fn block_events(pallet_prefix: Vec<u8>, extrinsic_index: Option<u32>) -> Vec<Result<pallet_ibc::events::IbcEvent, pallet_ibc::errors::IbcError>> {
+			with_ibc_instance!(pallet_prefix, |Ibc, Instance| {
+				let ibc_events = |event: RuntimeEvent| match event.try_into() {
+					Ok(pallet_ibc::Event::<Runtime, Instance>::Events { events }) => Some(events),
+					_ => None,
+				};
+				let mut raw_events = frame_system::Pallet::<Self>::read_events_no_consensus();
+				if let Some(idx) = extrinsic_index {
+					raw_events.find_map(|e| {
+						let frame_system::EventRecord{ event, phase, ..} = *e;
+						match phase {
+							frame_system::Phase::ApplyExtrinsic(index) if index == idx => ibc_events(event),
+							_ => None
+						}
+					}).unwrap_or_default()
+				}
+				else {
+					raw_events.filter_map(|e| ibc_events(e.event)).flatten().collect()
+				}
+			})
 		}
 	}
 
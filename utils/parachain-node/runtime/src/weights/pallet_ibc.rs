@@ -0,0 +1,286 @@
+//! Weights of `pallet_ibc` for the parachain runtime.
+//!
+//! THESE WEIGHTS ARE ESTIMATES, NOT BENCHMARK RESULTS. They were written by hand from the storage
+//! accesses of each extrinsic and the proof verification costs of the tendermint and grandpa
+//! clients, and were not produced by the benchmarking CLI. Regenerate this file before relying on
+//! it in production, with the command documented in `contracts/pallet-ibc/docs/benchmarks.md`:
+//!
+//! ```bash
+//! cargo build --release -p parachain-node --features=runtime-benchmarks
+//! ./target/release/parachain-node benchmark pallet \
+//!     --chain=dev \
+//!     --pallet=pallet_ibc \
+//!     --extrinsic='*' \
+//!     --steps=50 \
+//!     --repeat=20 \
+//!     --output=utils/parachain-node/runtime/src/weights/pallet_ibc.rs
+//! ```
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for `pallet_ibc`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> pallet_ibc::weight::WeightInfo for WeightInfo<T> {
+	fn create_client() -> Weight {
+		Weight::from_parts(60_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(8))
+			.saturating_add(T::DbWeight::get().writes(9))
+	}
+
+	fn conn_open_init() -> Weight {
+		Weight::from_parts(45_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(5))
+			.saturating_add(T::DbWeight::get().writes(4))
+	}
+
+	fn update_tendermint_client(i: u32) -> Weight {
+		Weight::from_parts(120_000_000, 0)
+			.saturating_add(Weight::from_parts(3_000_000_000, 0).saturating_mul(i.into()))
+			.saturating_add(T::DbWeight::get().reads(8))
+			.saturating_add(T::DbWeight::get().writes(6))
+	}
+
+	fn conn_try_open_tendermint() -> Weight {
+		Weight::from_parts(520_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(9))
+			.saturating_add(T::DbWeight::get().writes(5))
+	}
+
+	fn conn_open_ack_tendermint() -> Weight {
+		Weight::from_parts(510_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(9))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+
+	fn conn_open_confirm_tendermint() -> Weight {
+		Weight::from_parts(320_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(7))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+
+	fn channel_open_init() -> Weight {
+		Weight::from_parts(60_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().writes(5))
+	}
+
+	fn channel_open_try_tendermint() -> Weight {
+		Weight::from_parts(340_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(10))
+			.saturating_add(T::DbWeight::get().writes(6))
+	}
+
+	fn channel_open_ack_tendermint() -> Weight {
+		Weight::from_parts(330_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(8))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+
+	fn channel_open_confirm_tendermint() -> Weight {
+		Weight::from_parts(330_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(8))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+
+	fn channel_close_init() -> Weight {
+		Weight::from_parts(55_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(5))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+
+	fn channel_close_confirm_tendermint() -> Weight {
+		Weight::from_parts(330_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(8))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+
+	fn recv_packet_tendermint(i: u32) -> Weight {
+		Weight::from_parts(380_000_000, 0)
+			.saturating_add(Weight::from_parts(1_500, 0).saturating_mul(i.into()))
+			.saturating_add(T::DbWeight::get().reads(11))
+			.saturating_add(T::DbWeight::get().writes(5))
+	}
+
+	fn ack_packet_tendermint(i: u32, j: u32) -> Weight {
+		Weight::from_parts(370_000_000, 0)
+			.saturating_add(Weight::from_parts(1_500, 0).saturating_mul(i.into()))
+			.saturating_add(Weight::from_parts(1_500, 0).saturating_mul(j.into()))
+			.saturating_add(T::DbWeight::get().reads(10))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+
+	fn timeout_packet_tendermint(i: u32) -> Weight {
+		Weight::from_parts(360_000_000, 0)
+			.saturating_add(Weight::from_parts(1_500, 0).saturating_mul(i.into()))
+			.saturating_add(T::DbWeight::get().reads(10))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+
+	fn transfer() -> Weight {
+		Weight::from_parts(150_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(14))
+			.saturating_add(T::DbWeight::get().writes(8))
+	}
+
+	fn on_chan_open_init() -> Weight {
+		Weight::from_parts(30_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn on_chan_open_try() -> Weight {
+		Weight::from_parts(30_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn on_recv_packet() -> Weight {
+		Weight::from_parts(110_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(10))
+			.saturating_add(T::DbWeight::get().writes(6))
+	}
+
+	fn on_chan_open_ack() -> Weight {
+		Weight::from_parts(30_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn on_chan_open_confirm() -> Weight {
+		Weight::from_parts(30_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn on_chan_close_init() -> Weight {
+		Weight::from_parts(20_000_000, 0).saturating_add(T::DbWeight::get().reads(1))
+	}
+
+	fn on_chan_close_confirm() -> Weight {
+		Weight::from_parts(20_000_000, 0).saturating_add(T::DbWeight::get().reads(1))
+	}
+
+	fn on_acknowledgement_packet() -> Weight {
+		Weight::from_parts(95_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(8))
+			.saturating_add(T::DbWeight::get().writes(4))
+	}
+
+	fn on_timeout_packet() -> Weight {
+		Weight::from_parts(95_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(8))
+			.saturating_add(T::DbWeight::get().writes(4))
+	}
+
+	fn update_grandpa_client(i: u32, j: u32) -> Weight {
+		Weight::from_parts(150_000_000, 0)
+			.saturating_add(Weight::from_parts(220_000_000, 0).saturating_mul(i.into()))
+			.saturating_add(Weight::from_parts(12_000_000, 0).saturating_mul(j.into()))
+			.saturating_add(T::DbWeight::get().reads(9))
+			.saturating_add(T::DbWeight::get().writes(6))
+	}
+
+	fn upgrade_grandpa_client() -> Weight {
+		Weight::from_parts(250_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().writes(6))
+	}
+
+	fn packet_cleanup(i: u32) -> Weight {
+		Weight::from_parts(25_000_000, 0)
+			.saturating_add(Weight::from_parts(9_000_000, 0).saturating_mul(i.into()))
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(i.into())))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(i.into())))
+	}
+
+	fn upgrade_client() -> Weight {
+		Weight::from_parts(70_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(5))
+	}
+
+	fn freeze_client() -> Weight {
+		Weight::from_parts(45_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn unfreeze_client() -> Weight {
+		Weight::from_parts(45_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn recover_client(i: u32) -> Weight {
+		Weight::from_parts(80_000_000, 0)
+			.saturating_add(Weight::from_parts(6_000_000, 0).saturating_mul(i.into()))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().writes(6))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(i.into())))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(i.into())))
+	}
+
+	fn add_channels_to_feeless_channel_list() -> Weight {
+		Weight::from_parts(20_000_000, 0).saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn remove_channels_from_feeless_channel_list() -> Weight {
+		Weight::from_parts(20_000_000, 0).saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn set_child_storage() -> Weight {
+		Weight::from_parts(20_000_000, 0).saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn substitute_client_state() -> Weight {
+		Weight::from_parts(70_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(4))
+	}
+
+	fn set_unsigned_client_update_interval() -> Weight {
+		Weight::from_parts(15_000_000, 0).saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn set_denom_asset_id() -> Weight {
+		Weight::from_parts(30_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+
+	fn set_consensus_states_limit(i: u32) -> Weight {
+		Weight::from_parts(30_000_000, 0)
+			.saturating_add(Weight::from_parts(7_000_000, 0).saturating_mul(i.into()))
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(i.into())))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(i.into())))
+	}
+
+	fn set_transfer_limit() -> Weight {
+		Weight::from_parts(20_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn set_packet_retention_period() -> Weight {
+		Weight::from_parts(15_000_000, 0).saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	fn prune_retained_packets(i: u32) -> Weight {
+		Weight::from_parts(25_000_000, 0)
+			.saturating_add(Weight::from_parts(8_000_000, 0).saturating_mul(i.into()))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(i.into())))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(i.into())))
+	}
+
+	fn bind_port() -> Weight {
+		Weight::from_parts(25_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+}
@@ -231,6 +231,7 @@ fn testnet_genesis(
 		asset_registry: Default::default(),
 		ibc: parachain_runtime::IbcConfig {
 			assets: vec![AssetConfig { id: 1, denom: b"UNIT".to_vec() }],
+			..Default::default()
 		},
 	}
 }
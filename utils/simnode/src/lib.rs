@@ -141,9 +141,13 @@ mod tests {
 				para_id: 100,
 				current_set_id: 1,
 				current_authorities: Default::default(),
+				unbonding_period: None,
 				_phantom: Default::default(),
 			};
 
+			let trusting_period =
+				client_state.trusting_period().expect("relay chains have a trusting period");
+
 			let time = core::time::Duration::from_millis(1_000_000_000u64.saturating_mul(1000));
 			let consensus_state = ics10_grandpa::consensus_state::ConsensusState {
 				timestamp: Timestamp::from_nanoseconds(time.as_nanos().saturated_into::<u64>())
@@ -197,9 +201,8 @@ mod tests {
 					messages: vec![msg_create_client],
 				};
 			node.submit_extrinsic(call, sudo.clone()).await?;
-			let relaychain = light_client_common::RelayChain::default();
 			// Fast forward to a time beyond the trusting period of the client
-			let blocks_to_seal = (relaychain.trusting_period().as_secs() / 12).saturating_add(100);
+			let blocks_to_seal = (trusting_period.as_secs() / 12).saturating_add(100);
 			node.seal_blocks(blocks_to_seal as usize).await;
 			let call =
 				pallet_ibc::Call::<<ParachainRuntimeChainInfo as ChainInfo>::Runtime>::deliver {